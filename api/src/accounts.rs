@@ -5,12 +5,18 @@ use crate::{
     context::Context,
     failpoint::fail_point,
     metrics::metrics,
-    param::{AddressParam, LedgerVersionParam, MoveIdentifierParam, MoveStructTagParam},
+    openapi::{schema, Operation},
+    page::parse_limit,
+    param::{
+        AddressParam, LedgerVersionParam, MoveIdentifierParam, MoveStructTagParam, Param,
+        StructTagPrefixParam,
+    },
     version::Version,
 };
 
 use aptos_api_types::{
-    AccountData, Address, Error, LedgerInfo, MoveModuleBytecode, Response, TransactionId,
+    AccountData, Address, Error, LedgerInfo, MoveModuleBytecode, MoveResource, MoveStructTag,
+    Response, TransactionId,
 };
 use aptos_types::{
     account_config::AccountResource,
@@ -23,9 +29,79 @@ use move_core_types::{
     identifier::Identifier, language_storage::StructTag, move_resource::MoveStructType,
     value::MoveValue,
 };
-use std::convert::TryInto;
+use serde::Deserialize;
+use std::{convert::TryInto, num::NonZeroU16};
 use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
 
+const ACCOUNT_STATE_DESCRIPTION: &str = "\
+This API returns account {} for a specific ledger version (AKA transaction version).
+If not present, the latest version is used.
+
+The Aptos nodes prune account state history, via a configurable time window (link).
+
+If the requested data has been pruned, the server responds with a 404
+";
+
+const PREFIX_DESCRIPTION: &str = "\
+Only returns the resources whose struct tags match the prefix, in pages: the resources declared \
+at an address (`0x1::*`), in a module (`0x1::DiemAccount::*`), or all the instantiations of a \
+struct (`0x1::DiemAccount::Balance`).";
+const START_DESCRIPTION: &str = "\
+The struct tag of the last resource of the previous page, the page starts after it. \
+Requires a prefix.";
+const LIMIT_DESCRIPTION: &str =
+    "The max number of resources should be returned for the page. Default is 25. Requires a prefix.";
+
+pub fn operations() -> Vec<Operation> {
+    vec![
+        Operation::get("/accounts/{address}", "get_account")
+            .summary("Get account")
+            .tag("accounts")
+            .param("AccountAddress")
+            .response(
+                200,
+                "Returns the latest account core data resource.",
+                Some(schema::<AccountData>()),
+            )
+            .errors(&[400, 404, 500]),
+        Operation::get("/accounts/{address}/blob", "get_account_state_blob")
+            .summary("Get account state blob")
+            .tag("accounts")
+            .param("AccountAddress")
+            .response(
+                200,
+                "Returns the BCS bytes of the latest account state blob.",
+                Some(schema::<Vec<u8>>()),
+            )
+            .errors(&[400, 404, 500]),
+        Operation::get("/accounts/{address}/resources", "get_account_resources")
+            .summary("Get account resources")
+            .tag("accounts")
+            .param("AccountAddress")
+            .param("LedgerVersion")
+            .query_param("prefix", PREFIX_DESCRIPTION, schema::<String>())
+            .query_param("start", START_DESCRIPTION, schema::<MoveStructTag>())
+            .query_param("limit", LIMIT_DESCRIPTION, schema::<u16>())
+            .response(
+                200,
+                &ACCOUNT_STATE_DESCRIPTION.replace("{}", "resources"),
+                Some(schema::<Vec<MoveResource>>()),
+            )
+            .errors(&[400, 404, 500]),
+        Operation::get("/accounts/{address}/modules", "get_account_modules")
+            .summary("Get account modules")
+            .tag("accounts")
+            .param("AccountAddress")
+            .param("LedgerVersion")
+            .response(
+                200,
+                &ACCOUNT_STATE_DESCRIPTION.replace("{}", "modules"),
+                Some(schema::<Vec<MoveModuleBytecode>>()),
+            )
+            .errors(&[400, 404, 500]),
+    ]
+}
+
 // GET /accounts/<address>
 pub fn get_account(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam)
@@ -51,9 +127,7 @@ pub fn get_account_resources(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam / "resources")
         .and(warp::get())
         .and(context.filter())
-        .and(warp::query::<Version>())
-        .map(|address, ctx, version: Version| (version.version, address, ctx))
-        .untuple_one()
+        .and(warp::query::<ResourcesQuery>())
         .and_then(handle_get_account_resources)
         .with(metrics("get_account_resources"))
         .boxed()
@@ -89,12 +163,12 @@ async fn handle_get_account_state_blob(
 }
 
 async fn handle_get_account_resources(
-    ledger_version: Option<LedgerVersionParam>,
     address: AddressParam,
     context: Context,
+    query: ResourcesQuery,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_account_resources")?;
-    Ok(Account::new(ledger_version, address, context)?.resources()?)
+    Ok(Account::new(query.version.clone(), address, context)?.resources(query)?)
 }
 
 async fn handle_get_account_modules(
@@ -106,6 +180,16 @@ async fn handle_get_account_modules(
     Ok(Account::new(ledger_version, address, context)?.modules()?)
 }
 
+/// The query string of `GET /accounts/<address>/resources`, resources are only paged when they
+/// are filtered by a struct tag prefix.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct ResourcesQuery {
+    version: Option<LedgerVersionParam>,
+    prefix: Option<StructTagPrefixParam>,
+    start: Option<MoveStructTagParam>,
+    limit: Option<Param<NonZeroU16>>,
+}
+
 pub(crate) struct Account {
     ledger_version: u64,
     address: Address,
@@ -159,11 +243,40 @@ impl Account {
         Response::new(self.latest_ledger_info, &blob)
     }
 
-    pub fn resources(self) -> Result<impl Reply, Error> {
-        let resources = self
-            .context
-            .move_converter()
-            .try_into_resources(self.account_state()?.get_resources())?;
+    pub fn resources(self, query: ResourcesQuery) -> Result<impl Reply, Error> {
+        let converter = self.context.move_converter();
+        let resources = match query.prefix {
+            Some(prefix) => {
+                let prefix = prefix.parse("struct tag prefix")?;
+                let start: Option<StructTag> = match query.start {
+                    Some(start) => Some(start.parse("start struct tag")?.try_into()?),
+                    None => None,
+                };
+                let resources = self
+                    .context
+                    .get_account_resources_by_prefix(
+                        self.address.into(),
+                        &prefix,
+                        self.ledger_version,
+                        start.as_ref(),
+                        parse_limit(query.limit)?,
+                    )?
+                    .ok_or_else(|| self.account_not_found())?;
+                converter.try_into_resources(
+                    resources
+                        .iter()
+                        .map(|(struct_tag, value)| (struct_tag.clone(), value.as_slice())),
+                )?
+            }
+            None => {
+                if query.start.is_some() || query.limit.is_some() {
+                    return Err(Error::bad_request(
+                        "start and limit are only supported along with a prefix",
+                    ));
+                }
+                converter.try_into_resources(self.account_state()?.get_resources())?
+            }
+        };
         Response::new(self.latest_ledger_info, &resources)
     }
 
@@ -0,0 +1,251 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::Context,
+    failpoint::fail_point,
+    metrics::metrics,
+    openapi::{schema, Operation},
+    page::parse_limit,
+    param::{AddressParam, EventKeyParam, LedgerVersionParam, Param, TransactionIdParam},
+    version::Version,
+};
+
+use aptos_api_types::{
+    mime_types::JSON, Address, Error, EventKey, LedgerInfo, Response, TransactionId, U64,
+};
+use aptos_types::{
+    account_address::AccountAddress, account_state::AccountState,
+    account_state_blob::AccountStateBlob,
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeSet, HashMap},
+    convert::TryFrom,
+    num::NonZeroU16,
+};
+use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
+
+/// The max number of requests of a batch.
+const MAX_BATCH_SIZE: usize = 100;
+
+const BATCH_DESCRIPTION: &str = "\
+Executes a batch of reads at a single ledger version in one call, e.g. all the reads rendering a
+page. The reads are executed at the `version`, the latest ledger version by default.
+
+The results are in the order of the requests. A failed read doesn't fail the batch: its result
+holds the status code and the error the read fails with on its own endpoint. A batch has at most
+100 requests.
+";
+
+pub fn operations() -> Vec<Operation> {
+    vec![Operation::post("/batch", "batch")
+        .summary("Batch reads")
+        .description(BATCH_DESCRIPTION)
+        .param("LedgerVersion")
+        .request_body("Read requests", &[(JSON, schema::<Vec<BatchRequest>>())])
+        .response(
+            200,
+            "Returns the results of the requests, in order.",
+            Some(schema::<Vec<BatchResult>>()),
+        )
+        .errors(&[400, 404, 413, 415, 500])]
+}
+
+// POST /batch
+pub fn batch(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("batch")
+        .and(warp::post())
+        .and(warp::query::<Version>())
+        .and(warp::body::content_length_limit(
+            context.content_length_limit(),
+        ))
+        .and(warp::body::json::<Vec<BatchRequest>>())
+        .and(context.filter())
+        .and_then(handle_batch)
+        .with(metrics("batch"))
+        .boxed()
+}
+
+async fn handle_batch(
+    version: Version,
+    requests: Vec<BatchRequest>,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_batch")?;
+    Ok(Batch::new(version.version, context)?.execute(requests)?)
+}
+
+/// A read of a batch, with the parameters of the endpoint serving it on its own.
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BatchRequest {
+    /// `GET /accounts/{address}/resources`
+    AccountResources {
+        #[schemars(with = "Address")]
+        address: AddressParam,
+    },
+    /// `GET /transactions/{txn_hash_or_version}`, the pending transactions aren't looked up.
+    Transaction {
+        #[schemars(with = "String")]
+        txn_hash_or_version: TransactionIdParam,
+    },
+    /// `GET /events/{event_key}`
+    Events {
+        #[schemars(with = "EventKey")]
+        event_key: EventKeyParam,
+        #[schemars(with = "Option<U64>")]
+        start: Option<Param<u64>>,
+        #[schemars(with = "Option<String>")]
+        limit: Option<Param<NonZeroU16>>,
+    },
+}
+
+/// The status code and the body of the response a read of a batch gets on its own.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+struct BatchResult {
+    status: u16,
+    /// The body of the response, the `Error` of a failed read.
+    body: Value,
+}
+
+struct Batch {
+    ledger_version: u64,
+    latest_ledger_info: LedgerInfo,
+    context: Context,
+}
+
+impl Batch {
+    fn new(ledger_version: Option<LedgerVersionParam>, context: Context) -> Result<Self, Error> {
+        let latest_ledger_info = context.get_latest_ledger_info()?;
+        let ledger_version = ledger_version
+            .map(|v| v.parse("ledger version"))
+            .unwrap_or_else(|| Ok(latest_ledger_info.version()))?;
+        if ledger_version > latest_ledger_info.version() {
+            return Err(Error::not_found(
+                "ledger",
+                TransactionId::Version(ledger_version),
+                latest_ledger_info.version(),
+            ));
+        }
+
+        Ok(Self {
+            ledger_version,
+            latest_ledger_info,
+            context,
+        })
+    }
+
+    fn execute(self, requests: Vec<BatchRequest>) -> Result<impl Reply, Error> {
+        if requests.len() > MAX_BATCH_SIZE {
+            return Err(Error::invalid_request_body(format!(
+                "{} requests, exceed limit {}",
+                requests.len(),
+                MAX_BATCH_SIZE
+            )));
+        }
+        let account_state_blobs = self.read_account_state_blobs(&requests)?;
+        let results = requests
+            .into_iter()
+            .map(|request| match self.read(request, &account_state_blobs) {
+                Ok(body) => BatchResult { status: 200, body },
+                Err(err) => BatchResult {
+                    status: err.code,
+                    body: json!(err),
+                },
+            })
+            .collect::<Vec<_>>();
+        Response::new(self.latest_ledger_info, &results)
+    }
+
+    /// Reads the accounts of all the account resources requests at once, so the storage can
+    /// amortize the lookups. The requests with an invalid address fail on their own when read.
+    fn read_account_state_blobs(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<HashMap<AccountAddress, Option<AccountStateBlob>>, Error> {
+        let addresses = requests
+            .iter()
+            .filter_map(|request| match request {
+                BatchRequest::AccountResources { address } => {
+                    address.clone().parse("account address").ok()
+                }
+                _ => None,
+            })
+            .map(AccountAddress::from)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let blobs = self
+            .context
+            .get_account_state_blobs(&addresses, self.ledger_version)?;
+        Ok(addresses.into_iter().zip(blobs).collect())
+    }
+
+    fn read(
+        &self,
+        request: BatchRequest,
+        account_state_blobs: &HashMap<AccountAddress, Option<AccountStateBlob>>,
+    ) -> Result<Value, Error> {
+        let converter = self.context.move_converter();
+        let body = match request {
+            BatchRequest::AccountResources { address } => {
+                let address: Address = address.parse("account address")?;
+                let blob = account_state_blobs
+                    .get(&AccountAddress::from(address))
+                    .and_then(Option::as_ref)
+                    .ok_or_else(|| self.account_not_found(&address))?;
+                let account_state = AccountState::try_from(blob)?;
+                json!(converter.try_into_resources(account_state.get_resources())?)
+            }
+            BatchRequest::Transaction {
+                txn_hash_or_version,
+            } => {
+                let id = txn_hash_or_version.parse("transaction hash or version")?;
+                let txn = match id.clone() {
+                    TransactionId::Hash(hash) => self
+                        .context
+                        .get_transaction_by_hash(hash.into(), self.ledger_version)?,
+                    TransactionId::Version(version) if version <= self.ledger_version => Some(
+                        self.context
+                            .get_transaction_by_version(version, self.ledger_version)?,
+                    ),
+                    TransactionId::Version(_) => None,
+                }
+                .ok_or_else(|| {
+                    Error::not_found("transaction", id, self.latest_ledger_info.version())
+                })?;
+                let timestamp = self.context.get_block_timestamp(txn.version)?;
+                json!(converter.try_into_onchain_transaction(timestamp, txn)?)
+            }
+            BatchRequest::Events {
+                event_key,
+                start,
+                limit,
+            } => {
+                let events = self.context.get_events(
+                    &event_key.parse("event key")?.into(),
+                    start.map(|s| s.parse("start")).transpose()?.unwrap_or(0),
+                    parse_limit(limit)?,
+                    self.ledger_version,
+                )?;
+                json!(converter.try_into_events(&events)?)
+            }
+        };
+        Ok(body)
+    }
+
+    fn account_not_found(&self, address: &Address) -> Error {
+        Error::not_found(
+            "account",
+            format!(
+                "address({}) and ledger version({})",
+                address, self.ledger_version,
+            ),
+            self.latest_ledger_info.version(),
+        )
+    }
+}
@@ -0,0 +1,8 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Prints the OpenAPI specification served at `/spec.yaml`, without running a node.
+
+fn main() {
+    print!("{}", aptos_api::spec_yaml());
+}
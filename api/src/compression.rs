@@ -0,0 +1,104 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::convert::Infallible;
+use warp::{http::header, Filter, Rejection, Reply};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    Brotli,
+    Gzip,
+    Identity,
+}
+
+impl Encoding {
+    fn from_coding(coding: &str) -> Option<Self> {
+        match coding.to_ascii_lowercase().as_str() {
+            "br" => Some(Encoding::Brotli),
+            "gzip" | "x-gzip" => Some(Encoding::Gzip),
+            "identity" => Some(Encoding::Identity),
+            _ => None,
+        }
+    }
+}
+
+/// Picks the response encoding from an `Accept-Encoding` header: the supported coding with the
+/// highest quality value, preferring brotli over gzip on ties. Codings with `q=0` are refused.
+pub fn preferred_encoding(accept_encoding: Option<&str>) -> Encoding {
+    let mut preferred = Encoding::Identity;
+    let mut preferred_quality = 0.0;
+    for item in accept_encoding.unwrap_or_default().split(',') {
+        let mut parts = item.split(';').map(str::trim);
+        let coding = parts.next().unwrap_or_default();
+        let quality = parts
+            .find_map(|param| param.strip_prefix("q="))
+            .map_or(Some(1.0), |q| q.parse::<f32>().ok())
+            .unwrap_or(0.0);
+        let encoding = match Encoding::from_coding(coding) {
+            Some(encoding) if encoding != Encoding::Identity => encoding,
+            _ => continue,
+        };
+        if quality > preferred_quality
+            || (quality > 0.0 && quality == preferred_quality && encoding == Encoding::Brotli)
+        {
+            preferred = encoding;
+            preferred_quality = quality;
+        }
+    }
+    preferred
+}
+
+/// Compresses the replies of the given routes with the encoding negotiated from the request's
+/// `Accept-Encoding` header, replying uncompressed when the client accepts neither brotli nor
+/// gzip.
+pub fn compress<F>(
+    routes: F,
+) -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone + Send + Sync + 'static
+where
+    F: Filter<Error = Infallible> + Clone + Send + Sync + 'static,
+    F::Extract: Reply,
+{
+    negotiated(Encoding::Brotli)
+        .and(routes.clone())
+        .with(warp::compression::brotli())
+        .or(negotiated(Encoding::Gzip)
+            .and(routes.clone())
+            .with(warp::compression::gzip()))
+        .or(routes)
+}
+
+fn negotiated(encoding: Encoding) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>(header::ACCEPT_ENCODING.as_str())
+        .and_then(move |accept_encoding: Option<String>| async move {
+            if preferred_encoding(accept_encoding.as_deref()) == encoding {
+                Ok(())
+            } else {
+                Err(warp::reject())
+            }
+        })
+        .untuple_one()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferred_encoding() {
+        assert_eq!(preferred_encoding(None), Encoding::Identity);
+        assert_eq!(preferred_encoding(Some("")), Encoding::Identity);
+        assert_eq!(preferred_encoding(Some("deflate")), Encoding::Identity);
+        assert_eq!(preferred_encoding(Some("gzip")), Encoding::Gzip);
+        assert_eq!(
+            preferred_encoding(Some("gzip, deflate, br")),
+            Encoding::Brotli
+        );
+        assert_eq!(preferred_encoding(Some("br;q=0.5, gzip")), Encoding::Gzip);
+        assert_eq!(
+            preferred_encoding(Some("br;q=0, GZIP;q=0.1")),
+            Encoding::Gzip
+        );
+        assert_eq!(preferred_encoding(Some("gzip;q=0")), Encoding::Identity);
+        assert_eq!(preferred_encoding(Some("*, identity")), Encoding::Identity);
+    }
+}
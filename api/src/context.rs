@@ -1,11 +1,14 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::rate_limit::ApiRateLimiter;
 use aptos_api_types::{Error, LedgerInfo, MoveConverter, TransactionOnChainData};
 use aptos_config::config::{ApiConfig, RoleType};
 use aptos_crypto::HashValue;
 use aptos_mempool::{MempoolClientRequest, MempoolClientSender, SubmissionStatus};
+use aptos_state_view::StateView;
 use aptos_types::{
+    access_path::{AccessPath, StructTagPrefix},
     account_address::AccountAddress,
     account_state::AccountState,
     account_state_blob::AccountStateBlob,
@@ -13,14 +16,19 @@ use aptos_types::{
     contract_event::ContractEvent,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
-    transaction::{SignedTransaction, TransactionWithProof},
+    proof::AccumulatorConsistencyProof,
+    transaction::{SignedTransaction, Transaction, TransactionOutput, TransactionWithProof},
 };
+use aptos_vm::{AptosVM, VMExecutor};
+use move_core_types::language_storage::StructTag;
+use network::application::{storage::PeerMetadataStorage, types::ReachabilityStatus};
 use storage_interface::{MoveDbReader, Order};
 
 use anyhow::{ensure, format_err, Result};
 use futures::{channel::oneshot, SinkExt};
 use std::{
     borrow::Borrow,
+    collections::BTreeMap,
     convert::{Infallible, TryFrom},
     sync::Arc,
 };
@@ -32,8 +40,10 @@ pub struct Context {
     chain_id: ChainId,
     db: Arc<dyn MoveDbReader>,
     mp_sender: MempoolClientSender,
+    peer_metadata_storage: Arc<PeerMetadataStorage>,
     role: RoleType,
     api_config: ApiConfig,
+    rate_limiter: Arc<ApiRateLimiter>,
 }
 
 impl Context {
@@ -41,15 +51,19 @@ impl Context {
         chain_id: ChainId,
         db: Arc<dyn MoveDbReader>,
         mp_sender: MempoolClientSender,
+        peer_metadata_storage: Arc<PeerMetadataStorage>,
         role: RoleType,
         api_config: ApiConfig,
     ) -> Self {
+        let rate_limiter = Arc::new(ApiRateLimiter::new(api_config.rate_limit.as_ref()));
         Self {
             chain_id,
             db,
             mp_sender,
+            peer_metadata_storage,
             role,
             api_config,
+            rate_limiter,
         }
     }
 
@@ -61,10 +75,18 @@ impl Context {
         self.chain_id
     }
 
+    pub fn role(&self) -> RoleType {
+        self.role
+    }
+
     pub fn content_length_limit(&self) -> u64 {
         self.api_config.content_length_limit()
     }
 
+    pub fn rate_limiter(&self) -> Arc<ApiRateLimiter> {
+        self.rate_limiter.clone()
+    }
+
     pub fn filter(self) -> impl Filter<Extract = (Context,), Error = Infallible> + Clone {
         warp::any().map(move || self.clone())
     }
@@ -79,6 +101,57 @@ impl Context {
         callback.await?
     }
 
+    pub async fn submit_transaction_bundle(
+        &self,
+        txns: Vec<SignedTransaction>,
+    ) -> Result<SubmissionStatus> {
+        let (req_sender, callback) = oneshot::channel();
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::SubmitTransactionBundle(
+                txns, req_sender,
+            ))
+            .await?;
+
+        callback.await?
+    }
+
+    pub async fn get_mempool_size(&self) -> Result<usize> {
+        let (req_sender, callback) = oneshot::channel();
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetMempoolSize(req_sender))
+            .await?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
+    /// Returns the number of connected peers, keyed by network
+    pub fn get_connected_peer_counts(&self) -> BTreeMap<String, usize> {
+        self.peer_metadata_storage
+            .networks()
+            .map(|network_id| {
+                let connected = self
+                    .peer_metadata_storage
+                    .read_filtered(network_id, |(_, info)| info.is_connected())
+                    .len();
+                (network_id.to_string(), connected)
+            })
+            .collect()
+    }
+
+    pub fn get_reachability(&self) -> BTreeMap<String, ReachabilityStatus> {
+        self.peer_metadata_storage
+            .networks()
+            .map(|network_id| {
+                (
+                    network_id.to_string(),
+                    self.peer_metadata_storage.reachability(network_id),
+                )
+            })
+            .collect()
+    }
+
     pub fn get_latest_ledger_info(&self) -> Result<LedgerInfo, Error> {
         Ok(LedgerInfo::new(
             &self.chain_id(),
@@ -102,6 +175,21 @@ impl Context {
         })
     }
 
+    /// Gets the account state blobs of a batch of addresses at once, in the order of the
+    /// addresses, so the storage can amortize the lookups.
+    pub fn get_account_state_blobs(
+        &self,
+        accounts: &[AccountAddress],
+        version: u64,
+    ) -> Result<Vec<Option<AccountStateBlob>>> {
+        Ok(self
+            .db
+            .get_account_states_with_proof_by_version(accounts, version)?
+            .into_iter()
+            .map(|(account_state_blob, _)| account_state_blob)
+            .collect())
+    }
+
     pub fn get_account_state_blob(
         &self,
         account: AccountAddress,
@@ -113,6 +201,18 @@ impl Context {
         Ok(account_state_blob)
     }
 
+    pub fn get_account_resources_by_prefix(
+        &self,
+        account: AccountAddress,
+        prefix: &StructTagPrefix,
+        version: u64,
+        start: Option<&StructTag>,
+        limit: u16,
+    ) -> Result<Option<Vec<(StructTag, Vec<u8>)>>> {
+        self.db
+            .get_account_resources_by_prefix(account, prefix, version, start, limit as usize)
+    }
+
     pub fn get_block_timestamp(&self, version: u64) -> Result<u64> {
         self.db.get_block_timestamp(version)
     }
@@ -219,10 +319,32 @@ impl Context {
         )?)
     }
 
+    pub fn get_oldest_ledger_version(&self) -> Result<Option<u64>> {
+        self.db.get_first_txn_version()
+    }
+
+    pub fn get_genesis_transaction_hash(&self, ledger_version: u64) -> Result<HashValue> {
+        Ok(self
+            .db
+            .get_transaction_by_version(0, ledger_version, false)?
+            .proof
+            .transaction_info()
+            .transaction_hash())
+    }
+
     pub fn get_accumulator_root_hash(&self, version: u64) -> Result<HashValue> {
         self.db.get_accumulator_root_hash(version)
     }
 
+    pub fn get_accumulator_consistency_proof(
+        &self,
+        client_known_version: Option<u64>,
+        ledger_version: u64,
+    ) -> Result<AccumulatorConsistencyProof> {
+        self.db
+            .get_accumulator_consistency_proof(client_known_version, ledger_version)
+    }
+
     fn convert_into_transaction_on_chain_data(
         &self,
         txn: TransactionWithProof,
@@ -231,6 +353,27 @@ impl Context {
             .map(|h| (txn, h).into())
     }
 
+    /// Executes the transaction against the state at the given version, without committing its
+    /// output. Execution runs on the blocking thread pool so it doesn't stall the API's runtime.
+    pub async fn simulate_transaction(
+        &self,
+        txn: SignedTransaction,
+        version: u64,
+    ) -> Result<TransactionOutput> {
+        let state_view = DbStateView {
+            db: self.db.clone(),
+            version,
+        };
+        let mut outputs = tokio::task::spawn_blocking(move || {
+            AptosVM::execute_block(vec![Transaction::UserTransaction(txn)], &state_view)
+        })
+        .await?
+        .map_err(|status| format_err!("failed to execute transaction: {:?}", status))?;
+        outputs
+            .pop()
+            .ok_or_else(|| format_err!("no output for the simulated transaction"))
+    }
+
     pub fn get_events(
         &self,
         event_key: &EventKey,
@@ -241,14 +384,67 @@ impl Context {
         let events = self
             .db
             .get_events(event_key, start, Order::Ascending, limit as u64)?;
-        Ok(events
+        Ok(Self::events_at_ledger_version(events, ledger_version))
+    }
+
+    pub fn get_events_by_creation_number(
+        &self,
+        address: AccountAddress,
+        creation_number: u64,
+        start: u64,
+        limit: u16,
+        ledger_version: u64,
+    ) -> Result<Vec<ContractEvent>> {
+        let events = self.db.get_events_by_creation_number(
+            address,
+            creation_number,
+            start,
+            Order::Ascending,
+            limit as u64,
+        )?;
+        Ok(Self::events_at_ledger_version(events, ledger_version))
+    }
+
+    fn events_at_ledger_version(
+        events: Vec<(u64, ContractEvent)>,
+        ledger_version: u64,
+    ) -> Vec<ContractEvent> {
+        events
             .into_iter()
             .filter(|(version, _event)| version <= &ledger_version)
             .map(|(_, event)| event)
-            .collect::<Vec<_>>())
+            .collect::<Vec<_>>()
     }
 
     pub fn health_check_route(&self) -> BoxedFilter<(impl Reply,)> {
         super::health_check::health_check_route(self.db.clone())
     }
+
+    pub fn health_check_detail_route(&self) -> BoxedFilter<(impl Reply,)> {
+        super::health_check::health_check_detail_route(self.clone())
+    }
+}
+
+/// Read-only view of the state of the database at a version, on which transactions are simulated
+struct DbStateView {
+    db: Arc<dyn MoveDbReader>,
+    version: u64,
+}
+
+impl StateView for DbStateView {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        let (blob, _) = self
+            .db
+            .get_account_state_with_proof_by_version(access_path.address, self.version)?;
+        Ok(match blob {
+            Some(blob) => AccountState::try_from(&blob)?
+                .get(&access_path.path)
+                .cloned(),
+            None => None,
+        })
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
 }
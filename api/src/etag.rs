@@ -0,0 +1,136 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_api_types::{Error, X_APTOS_LEDGER_VERSION};
+use aptos_crypto::HashValue;
+use hyper::Body;
+use serde::Serialize;
+use std::convert::Infallible;
+use warp::{
+    http::{header, HeaderValue, Method, StatusCode},
+    path::FullPath,
+    Filter, Rejection, Reply,
+};
+
+/// The parts of a read request which, along with the response body, determine its ETag.
+#[derive(Clone, Debug, Serialize)]
+pub struct RequestKey {
+    method: String,
+    path: String,
+    query: String,
+    accept: Option<String>,
+    accept_encoding: Option<String>,
+    #[serde(skip)]
+    if_none_match: Option<String>,
+}
+
+impl RequestKey {
+    /// A strong ETag for the response to the request with the given body.
+    ///
+    /// The ETag is derived from the content rather than the ledger version the response was
+    /// served at, so it stays valid for as long as the requested data is not modified, e.g. the
+    /// resources of an account which no committed transaction has touched since.
+    pub fn etag(&self, body: &[u8]) -> String {
+        let bytes = bcs::to_bytes(&(self, body)).expect("RequestKey serializes.");
+        format!("\"{:x}\"", HashValue::sha3_256_of(&bytes))
+    }
+}
+
+/// Extracts the `RequestKey` of the request.
+pub fn request_key() -> impl Filter<Extract = (RequestKey,), Error = Infallible> + Clone {
+    warp::method()
+        .and(warp::path::full())
+        .and(warp::query::raw().or(warp::any().map(String::new)).unify())
+        .and(warp::header::optional::<String>(header::ACCEPT.as_str()))
+        .and(warp::header::optional::<String>(
+            header::ACCEPT_ENCODING.as_str(),
+        ))
+        .and(warp::header::optional::<String>(
+            header::IF_NONE_MATCH.as_str(),
+        ))
+        .map(
+            |method: Method,
+             path: FullPath,
+             query: String,
+             accept: Option<String>,
+             accept_encoding: Option<String>,
+             if_none_match: Option<String>| RequestKey {
+                method: method.to_string(),
+                path: path.as_str().to_owned(),
+                query,
+                accept,
+                accept_encoding,
+                if_none_match,
+            },
+        )
+}
+
+/// Tags the successful `GET` replies which carry a ledger version with an ETag, and replies
+/// `304 Not Modified` without a body when the request's `If-None-Match` header matches it.
+///
+/// The headers of a `304 Not Modified` reply are still those of the latest ledger version.
+pub async fn reply<R: Reply>(
+    key: RequestKey,
+    reply: R,
+) -> Result<warp::reply::Response, Rejection> {
+    let res = reply.into_response();
+    if key.method != Method::GET.as_str()
+        || res.status() != StatusCode::OK
+        || !res.headers().contains_key(X_APTOS_LEDGER_VERSION)
+    {
+        return Ok(res);
+    }
+    let (parts, body) = res.into_parts();
+    let body = hyper::body::to_bytes(body)
+        .await
+        .map_err(|err| Error::internal(err.into()))?;
+
+    let etag = key.etag(&body);
+    let not_modified = key
+        .if_none_match
+        .as_deref()
+        .map_or(false, |if_none_match| matches(if_none_match, &etag));
+
+    let mut res = warp::reply::Response::from_parts(parts, Body::from(body));
+    let headers = res.headers_mut();
+    headers.insert(
+        header::ETAG,
+        HeaderValue::from_str(&etag).expect("ETag is a valid header value."),
+    );
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    headers.insert(
+        header::VARY,
+        HeaderValue::from_static("Accept, Accept-Encoding"),
+    );
+    if not_modified {
+        headers.remove(header::CONTENT_LENGTH);
+        *res.status_mut() = StatusCode::NOT_MODIFIED;
+        *res.body_mut() = Body::empty();
+    }
+    Ok(res)
+}
+
+/// Whether an `If-None-Match` header matches the ETag, by the weak comparison RFC 7232 requires
+/// for it.
+fn matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_if_none_match() {
+        let etag = "\"abc\"";
+        assert!(matches("\"abc\"", etag));
+        assert!(matches("W/\"abc\"", etag));
+        assert!(matches("\"xyz\", \"abc\"", etag));
+        assert!(matches("*", etag));
+        assert!(!matches("\"xyz\"", etag));
+        assert!(!matches("abc", etag));
+    }
+}
@@ -6,16 +6,86 @@ use crate::{
     context::Context,
     failpoint::fail_point,
     metrics::metrics,
+    openapi::{schema, Operation},
     page::Page,
-    param::{AddressParam, EventKeyParam, MoveIdentifierParam, MoveStructTagParam},
+    param::{AddressParam, EventKeyParam, MoveIdentifierParam, MoveStructTagParam, Param},
 };
 
-use aptos_api_types::{Error, LedgerInfo, Response};
+use aptos_api_types::{Error, Event, LedgerInfo, MoveStructTag, Response, U64};
 
 use anyhow::Result;
-use aptos_types::event::EventKey;
+use aptos_types::{account_address::AccountAddress, event::EventKey};
 use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
 
+const START_DESCRIPTION: &str = "The start event sequence number of the page. Default is 0.";
+const LIMIT_DESCRIPTION: &str =
+    "The max number of events should be returned for the page. Default is 25.";
+
+pub fn operations() -> Vec<Operation> {
+    vec![
+        Operation::get("/events/{event_key}", "get_events_by_event_key")
+            .summary("Get events by event key")
+            .tag("events")
+            .path_param(
+                "event_key",
+                "Event key for an event stream.\n\
+                 It is BCS serialized bytes of `guid` field in the Move struct `EventHandle`.",
+                schema::<aptos_api_types::EventKey>(),
+            )
+            .query_param("start", START_DESCRIPTION, schema::<u64>())
+            .query_param("limit", LIMIT_DESCRIPTION, schema::<u16>())
+            .response(200, "Returns events", Some(schema::<Vec<Event>>()))
+            .errors(&[400, 404, 500]),
+        Operation::get(
+            "/accounts/{address}/events/{event_handle_struct}/{field_name}",
+            "get_events_by_event_handle",
+        )
+        .summary("Get events by event handle")
+        .description(
+            "This API extracts event key from the account resource identified\n\
+             by the `event_handle_struct` and `field_name`, then returns\n\
+             events identified by the event key.",
+        )
+        .tag("events")
+        .param("AccountAddress")
+        .path_param(
+            "event_handle_struct",
+            "The struct tag of the resource holding the `EventHandle`, e.g. `0x1::DiemAccount::DiemAccount`.",
+            schema::<MoveStructTag>(),
+        )
+        .path_param(
+            "field_name",
+            "The field name of the `EventHandle` in the struct.",
+            schema::<String>(),
+        )
+        .query_param("start", START_DESCRIPTION, schema::<u64>())
+        .query_param("limit", LIMIT_DESCRIPTION, schema::<u16>())
+        .response(200, "Returns events", Some(schema::<Vec<Event>>()))
+        .errors(&[400, 404, 500]),
+        Operation::get(
+            "/accounts/{address}/events/{creation_number}",
+            "get_events_by_creation_number",
+        )
+        .summary("Get events by creation number")
+        .description(
+            "The event stream is identified by the address of the account that created it\n\
+             and its creation number, the number of event streams the account created\n\
+             before it. It is the same event stream as the one of the event key made of them.",
+        )
+        .tag("events")
+        .param("AccountAddress")
+        .path_param(
+            "creation_number",
+            "Creation number of the event stream.",
+            schema::<U64>(),
+        )
+        .query_param("start", START_DESCRIPTION, schema::<u64>())
+        .query_param("limit", LIMIT_DESCRIPTION, schema::<u16>())
+        .response(200, "Returns events", Some(schema::<Vec<Event>>()))
+        .errors(&[400, 404, 500]),
+    ]
+}
+
 // GET /events/<event_key>
 pub fn get_events_by_event_key(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("events" / EventKeyParam)
@@ -38,13 +108,25 @@ pub fn get_events_by_event_handle(context: Context) -> BoxedFilter<(impl Reply,)
         .boxed()
 }
 
+// GET /accounts/<address>/events/<creation_number>
+pub fn get_events_by_creation_number(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("accounts" / AddressParam / "events" / Param<u64>)
+        .and(warp::get())
+        .and(warp::query::<Page>())
+        .and(context.filter())
+        .and_then(handle_get_events_by_creation_number)
+        .with(metrics("get_events_by_creation_number"))
+        .boxed()
+}
+
 async fn handle_get_events_by_event_key(
     event_key: EventKeyParam,
     page: Page,
     context: Context,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_events_by_event_key")?;
-    Ok(Events::new(event_key.parse("event key")?.into(), context)?.list(page)?)
+    let stream = EventStream::Key(event_key.parse("event key")?.into());
+    Ok(Events::new(stream, context)?.list(page)?)
 }
 
 async fn handle_get_events_by_event_handle(
@@ -57,32 +139,64 @@ async fn handle_get_events_by_event_handle(
     fail_point("endpoint_get_events_by_event_handle")?;
     let key =
         Account::new(None, address, context.clone())?.find_event_key(struct_tag, field_name)?;
-    Ok(Events::new(key, context)?.list(page)?)
+    Ok(Events::new(EventStream::Key(key), context)?.list(page)?)
+}
+
+async fn handle_get_events_by_creation_number(
+    address: AddressParam,
+    creation_number: Param<u64>,
+    page: Page,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_events_by_creation_number")?;
+    let stream = EventStream::CreationNumber(
+        address.parse("account address")?.into(),
+        creation_number.parse("creation number")?,
+    );
+    Ok(Events::new(stream, context)?.list(page)?)
+}
+
+/// The ways an event stream is identified.
+enum EventStream {
+    Key(EventKey),
+    /// The address of the account which created the stream and its creation number.
+    CreationNumber(AccountAddress, u64),
 }
 
 struct Events {
-    key: EventKey,
+    stream: EventStream,
     ledger_info: LedgerInfo,
     context: Context,
 }
 
 impl Events {
-    fn new(key: EventKey, context: Context) -> Result<Self, Error> {
+    fn new(stream: EventStream, context: Context) -> Result<Self, Error> {
         let ledger_info = context.get_latest_ledger_info()?;
         Ok(Self {
-            key,
+            stream,
             ledger_info,
             context,
         })
     }
 
     pub fn list(self, page: Page) -> Result<impl Reply, Error> {
-        let contract_events = self.context.get_events(
-            &self.key,
-            page.start(0, u64::MAX)?,
-            page.limit()?,
-            self.ledger_info.version(),
-        )?;
+        let start = page.start(0, u64::MAX)?;
+        let limit = page.limit()?;
+        let contract_events = match self.stream {
+            EventStream::Key(key) => {
+                self.context
+                    .get_events(&key, start, limit, self.ledger_info.version())?
+            }
+            EventStream::CreationNumber(address, creation_number) => {
+                self.context.get_events_by_creation_number(
+                    address,
+                    creation_number,
+                    start,
+                    limit,
+                    self.ledger_info.version(),
+                )?
+            }
+        };
 
         let converter = self.context.move_converter();
         let events = converter.try_into_events(&contract_events)?;
@@ -1,14 +1,25 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::{
+    context::Context,
+    openapi::{schema, Operation},
+};
 use anyhow::{ensure, Result};
+use aptos_api_types::{Error, Response, U64};
+use network::application::types::ReachabilityStatus;
+use schemars::JsonSchema;
+use serde::Serialize;
 use std::{
+    collections::BTreeMap,
     ops::Sub,
     sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use storage_interface::MoveDbReader;
-use warp::{filters::BoxedFilter, reject, Filter, Reply};
+use warp::{
+    filters::BoxedFilter, http::StatusCode, reject, reply::with_status, Filter, Rejection, Reply,
+};
 
 // HealthCheckParams is optional params for different layer's health check.
 // If no param is provided, server return 200 by default to indicate HTTP server is running health.
@@ -23,6 +34,60 @@ struct HealthCheckParams {
 struct HealthCheckError;
 impl reject::Reject for HealthCheckError {}
 
+/// Detailed health document served by `GET /-/healthy/detail`, intended for load balancers and
+/// operators that need more than a status code.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct HealthCheckDetail {
+    /// False when `duration_secs` is given and the latest ledger info is older than it.
+    pub healthy: bool,
+    pub ledger_version: U64,
+    pub ledger_timestamp: U64,
+    /// Seconds between now and the latest ledger timestamp, zero if the ledger is ahead.
+    pub ledger_lag_secs: U64,
+    pub mempool_size: U64,
+    /// Connected peer count keyed by network id.
+    pub connected_peers: BTreeMap<String, usize>,
+    /// Whether the node is reachable by peers, keyed by network id: `unknown`, `reachable` or
+    /// `unreachable`.
+    #[schemars(with = "BTreeMap<String, String>")]
+    pub reachability: BTreeMap<String, ReachabilityStatus>,
+}
+
+pub fn operations() -> Vec<Operation> {
+    vec![
+        Operation::get("/-/healthy", "health_check")
+            .summary("Health check")
+            .query_param(
+                "duration_secs",
+                "Returns 503 when the latest ledger info is older than the given seconds.",
+                schema::<u64>(),
+            )
+            .response(
+                200,
+                "Returns `aptos-node:ok` when the server is healthy.",
+                None,
+            )
+            .errors(&[503]),
+        Operation::get("/-/healthy/detail", "health_check_detail")
+            .summary("Detailed health check")
+            .query_param(
+                "duration_secs",
+                "Reports unhealthy when the latest ledger info is older than the given seconds.",
+                schema::<u64>(),
+            )
+            .response(
+                200,
+                "Returns the node health details.",
+                Some(schema::<HealthCheckDetail>()),
+            )
+            .response(
+                503,
+                "Returns the node health details of an unhealthy node.",
+                Some(schema::<HealthCheckDetail>()),
+            ),
+    ]
+}
+
 pub fn health_check_route(health_aptos_db: Arc<dyn MoveDbReader>) -> BoxedFilter<(impl Reply,)> {
     warp::path!("-" / "healthy")
         .and(warp::path::end())
@@ -33,6 +98,17 @@ pub fn health_check_route(health_aptos_db: Arc<dyn MoveDbReader>) -> BoxedFilter
         .boxed()
 }
 
+// GET /-/healthy/detail?duration_secs=N
+pub fn health_check_detail_route(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("-" / "healthy" / "detail")
+        .and(warp::get())
+        .and(warp::query::<HealthCheckParams>())
+        .and(context.filter())
+        .and(warp::any().map(SystemTime::now))
+        .and_then(health_check_detail)
+        .boxed()
+}
+
 async fn health_check(
     params: HealthCheckParams,
     db: Arc<dyn MoveDbReader>,
@@ -44,12 +120,55 @@ async fn health_check(
             .map_err(|_| reject::custom(HealthCheckError))?;
         let timestamp = ledger_info.ledger_info().timestamp_usecs();
 
-        check_latest_ledger_info_timestamp(duration, timestamp, now)
-            .map_err(|_| reject::custom(HealthCheckError))?;
+        check_latest_ledger_info_timestamp(duration, timestamp, now).map_err(|err| {
+            reject::custom(Error::from_anyhow_error(
+                StatusCode::SERVICE_UNAVAILABLE,
+                err,
+            ))
+        })?;
     }
     Ok(Box::new("aptos-node:ok"))
 }
 
+async fn health_check_detail(
+    params: HealthCheckParams,
+    context: Context,
+    now: SystemTime,
+) -> Result<impl Reply, Rejection> {
+    let ledger_info = context.get_latest_ledger_info()?;
+    let timestamp = ledger_info.timestamp();
+    let healthy = match params.duration_secs {
+        Some(duration) => check_latest_ledger_info_timestamp(duration, timestamp, now).is_ok(),
+        None => true,
+    };
+    let ledger_lag_secs = now
+        .duration_since(UNIX_EPOCH)
+        .map_err(|err| Error::internal(err.into()))?
+        .checked_sub(Duration::from_micros(timestamp))
+        .unwrap_or_default()
+        .as_secs();
+
+    let detail = HealthCheckDetail {
+        healthy,
+        ledger_version: ledger_info.ledger_version,
+        ledger_timestamp: ledger_info.ledger_timestamp,
+        ledger_lag_secs: ledger_lag_secs.into(),
+        mempool_size: context
+            .get_mempool_size()
+            .await
+            .map(|size| (size as u64).into())
+            .map_err(Error::internal)?,
+        connected_peers: context.get_connected_peer_counts(),
+        reachability: context.get_reachability(),
+    };
+    let status = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    Ok(with_status(Response::new(ledger_info, &detail)?, status))
+}
+
 pub fn check_latest_ledger_info_timestamp(
     duration_sec: u64,
     timestamp_usecs: u64,
@@ -59,6 +178,10 @@ pub fn check_latest_ledger_info_timestamp(
     let expectation = now
         .sub(Duration::from_secs(duration_sec))
         .duration_since(UNIX_EPOCH)?;
-    ensure!(timestamp >= expectation);
+    ensure!(
+        timestamp >= expectation,
+        "latest ledger info timestamp is older than {} seconds",
+        duration_sec
+    );
     Ok(())
 }
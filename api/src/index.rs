@@ -2,15 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    accounts,
+    accounts, batch, compression,
     context::Context,
-    events,
+    etag, events,
     failpoint::fail_point,
     log,
     metrics::{metrics, status_metrics},
-    transactions,
+    openapi::{self, schema, Operation},
+    proofs, transactions,
 };
-use aptos_api_types::{Error, Response};
+use aptos_api_types::{Error, NodeInfo, Response};
+use aptos_metrics::json_metrics::get_git_rev;
 
 use std::convert::Infallible;
 use warp::{
@@ -23,10 +25,10 @@ use warp::{
 };
 
 const OPEN_API_HTML: &str = include_str!("../doc/spec.html");
-const OPEN_API_SPEC: &str = include_str!("../doc/openapi.yaml");
 
 pub fn routes(context: Context) -> impl Filter<Extract = impl Reply, Error = Infallible> + Clone {
-    index(context.clone())
+    let api = index(context.clone())
+        .or(info(context.clone()))
         .or(openapi_spec())
         .or(accounts::get_account(context.clone()))
         .or(accounts::get_account_resources(context.clone()))
@@ -37,27 +39,81 @@ pub fn routes(context: Context) -> impl Filter<Extract = impl Reply, Error = Inf
         .or(transactions::get_account_transactions(context.clone()))
         .or(transactions::submit_bcs_transactions(context.clone()))
         .or(transactions::submit_json_transactions(context.clone()))
+        .or(transactions::simulate_bcs_transactions(context.clone()))
+        .or(transactions::submit_bcs_transaction_bundle(context.clone()))
         .or(transactions::create_signing_message(context.clone()))
         .or(events::get_events_by_event_key(context.clone()))
         .or(events::get_events_by_event_handle(context.clone()))
-        .or(context.health_check_route().with(metrics("health_check")))
+        .or(events::get_events_by_creation_number(context.clone()))
+        .or(proofs::get_accumulator_consistency_proof(context.clone()))
+        .or(batch::batch(context.clone()))
+        .or(context
+            .health_check_detail_route()
+            .with(metrics("health_check_detail")))
+        .or(context.health_check_route().with(metrics("health_check")));
+    let routes = context
+        .rate_limiter()
+        .filter()
+        .and(etag::request_key())
+        .and(api)
+        .and_then(etag::reply)
         .with(
             warp::cors()
                 .allow_any_origin()
                 .allow_methods(vec!["POST", "GET"])
-                .allow_headers(vec![header::CONTENT_TYPE]),
+                .allow_headers(vec![header::CONTENT_TYPE, header::IF_NONE_MATCH])
+                .expose_headers(vec![header::ETAG]),
         )
-        .recover(handle_rejection)
+        .recover(handle_rejection);
+    compression::compress(routes)
         .with(log::logger())
         .with(status_metrics())
 }
 
+pub fn operations() -> Vec<Operation> {
+    vec![
+        Operation::get("/", "get_ledger_info")
+            .summary("Ledger information")
+            .response(
+                200,
+                "Returns the latest ledger information.",
+                Some(schema::<LedgerInfo>()),
+            )
+            .errors(&[400, 500]),
+        Operation::get("/info", "get_node_info")
+            .summary("Node information")
+            .description(
+                "This API returns the chain id and genesis transaction hash of the network the\n\
+                 node belongs to, the role and build of the node, and the oldest ledger version\n\
+                 it retains; older transactions have been pruned.",
+            )
+            .response(
+                200,
+                "Returns the node information.",
+                Some(schema::<NodeInfo>()),
+            )
+            .errors(&[500]),
+        Operation::get("/spec.html", "get_spec_html")
+            .summary("API document")
+            .response(200, "Returns OpenAPI specification html document.", None)
+            .response(400, "Bad Request", None),
+        Operation::get("/spec.yaml", "get_spec_yaml")
+            .summary("OpenAPI specification")
+            .description("`/openapi.yaml` is kept as an alias of this endpoint.")
+            .response(200, "Returns OpenAPI specification YAML document.", None)
+            .response(400, "Bad Request", None),
+    ]
+}
+
+// GET /spec.yaml
 // GET /openapi.yaml
 // GET /spec.html
 pub fn openapi_spec() -> BoxedFilter<(impl Reply,)> {
-    let spec = warp::path!("openapi.yaml")
+    let spec = warp::path!("spec.yaml")
+        .or(warp::path!("openapi.yaml"))
+        .unify()
         .and(warp::get())
-        .map(|| OPEN_API_SPEC)
+        .map(|| openapi::SPEC_YAML.as_str())
         .with(metrics("openapi_yaml"))
         .boxed();
     let html = warp::path!("spec.html")
@@ -84,6 +140,42 @@ pub async fn handle_index(context: Context) -> Result<impl Reply, Rejection> {
     Ok(Response::new(info.clone(), &info)?)
 }
 
+// GET /info
+pub fn info(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("info")
+        .and(warp::get())
+        .and(context.filter())
+        .and_then(handle_info)
+        .with(metrics("get_node_info"))
+        .boxed()
+}
+
+pub async fn handle_info(context: Context) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_node_info")?;
+    Ok(node_info(context)?)
+}
+
+fn node_info(context: Context) -> Result<impl Reply, Error> {
+    let ledger_info = context.get_latest_ledger_info()?;
+    let oldest_ledger_version = context.get_oldest_ledger_version()?;
+    let genesis_hash = match oldest_ledger_version {
+        Some(0) => Some(
+            context
+                .get_genesis_transaction_hash(ledger_info.version())?
+                .into(),
+        ),
+        _ => None,
+    };
+    let node_info = NodeInfo {
+        chain_id: context.chain_id().id(),
+        genesis_hash,
+        node_role: context.role().to_string(),
+        oldest_ledger_version: oldest_ledger_version.map(Into::into),
+        build_version: get_git_rev(),
+    };
+    Response::new(ledger_info, &node_info)
+}
+
 async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
     let code;
     let body;
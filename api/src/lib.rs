@@ -2,14 +2,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod accounts;
+mod batch;
+mod compression;
 mod context;
+mod etag;
 mod events;
 mod health_check;
 mod index;
 pub(crate) mod log;
 mod metrics;
+mod openapi;
 mod page;
 pub(crate) mod param;
+mod proofs;
+pub mod rate_limit;
 pub mod runtime;
 mod transactions;
 pub(crate) mod version;
@@ -17,3 +23,8 @@ pub(crate) mod version;
 mod failpoint;
 #[cfg(any(test))]
 pub(crate) mod tests;
+
+/// The OpenAPI specification served at `/spec.yaml`.
+pub fn spec_yaml() -> &'static str {
+    openapi::SPEC_YAML.as_str()
+}
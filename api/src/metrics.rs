@@ -26,7 +26,7 @@ static RESPONSE_STATUS: Lazy<HistogramVec> = Lazy::new(|| {
 
 // Record metrics by method, operation_id and status.
 // The operation_id is the id for the request handler.
-// Should use same `operationId` defined in `openapi.yaml` whenever possible.
+// Should use same `operationId` defined in the route's `openapi::Operation` whenever possible.
 pub fn metrics(operation_id: &'static str) -> Log<impl Fn(Info) + Copy> {
     let func = move |info: Info| {
         HISTOGRAM
@@ -0,0 +1,255 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! OpenAPI specification generated from the route definitions.
+//!
+//! Every route module describes its endpoints with [`Operation`]s right next to the warp filters
+//! serving them; [`spec`] assembles those operations with the shared parameters and responses
+//! defined in `doc/components.yaml`. The schemas of the parameters, bodies and responses are
+//! derived from the types serialized by the endpoints (see [`schema`]), and collected in the
+//! `components/schemas` section of the specification.
+
+use crate::{accounts, batch, events, health_check, index, proofs, transactions};
+
+use aptos_api_types::{Address, Error, U64};
+use once_cell::sync::Lazy;
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::Schema,
+    visit::Visitor,
+    JsonSchema,
+};
+use serde_json::{json, Map, Value};
+
+const COMPONENTS: &str = include_str!("../doc/components.yaml");
+
+pub static SPEC: Lazy<Value> = Lazy::new(spec);
+pub static SPEC_YAML: Lazy<String> =
+    Lazy::new(|| serde_yaml::to_string(&*SPEC).expect("serialize OpenAPI spec to yaml failed"));
+
+/// Returns the schema of a parameter, request body or response, registering the schemas it
+/// refers to with the generator.
+pub type SchemaFn = fn(&mut SchemaGenerator) -> Value;
+
+/// The schema derived from a type, which refers to the schema of the type in
+/// `components/schemas` if it's not inlined.
+pub fn schema<T: JsonSchema>() -> SchemaFn {
+    |gen| {
+        let schema = gen.subschema_for::<T>();
+        to_openapi(gen, schema)
+    }
+}
+
+/// Applies the adjustments of the OpenAPI flavour of JSON Schema to the schema.
+fn to_openapi(gen: &mut SchemaGenerator, mut schema: Schema) -> Value {
+    for visitor in gen.visitors_mut() {
+        visitor.visit_schema(&mut schema);
+    }
+    serde_json::to_value(schema).expect("serialize schema failed")
+}
+
+#[derive(Clone, Debug)]
+pub struct Operation {
+    pub method: &'static str,
+    pub path: &'static str,
+    pub operation_id: &'static str,
+    summary: &'static str,
+    description: Option<&'static str>,
+    tag: &'static str,
+    parameters: Vec<(Value, Option<SchemaFn>)>,
+    request_body: Option<(String, Vec<(String, SchemaFn)>)>,
+    responses: Vec<(u16, Value, Option<SchemaFn>)>,
+}
+
+impl Operation {
+    pub fn get(path: &'static str, operation_id: &'static str) -> Self {
+        Self::new("get", path, operation_id)
+    }
+
+    pub fn post(path: &'static str, operation_id: &'static str) -> Self {
+        Self::new("post", path, operation_id)
+    }
+
+    fn new(method: &'static str, path: &'static str, operation_id: &'static str) -> Self {
+        Self {
+            method,
+            path,
+            operation_id,
+            summary: operation_id,
+            description: None,
+            tag: "general",
+            parameters: vec![],
+            request_body: None,
+            responses: vec![],
+        }
+    }
+
+    pub fn summary(mut self, summary: &'static str) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    pub fn description(mut self, description: &'static str) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    pub fn tag(mut self, tag: &'static str) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    /// Adds a parameter defined in `components/parameters`.
+    pub fn param(mut self, name: &str) -> Self {
+        self.parameters.push((
+            json!({ "$ref": format!("#/components/parameters/{}", name) }),
+            None,
+        ));
+        self
+    }
+
+    pub fn path_param(mut self, name: &str, description: &str, schema: SchemaFn) -> Self {
+        self.parameters.push((
+            json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "description": description,
+            }),
+            Some(schema),
+        ));
+        self
+    }
+
+    pub fn query_param(mut self, name: &str, description: &str, schema: SchemaFn) -> Self {
+        self.parameters.push((
+            json!({
+                "name": name,
+                "in": "query",
+                "required": false,
+                "description": description,
+            }),
+            Some(schema),
+        ));
+        self
+    }
+
+    /// Adds a required request body, `content` maps mime types to schemas.
+    pub fn request_body(mut self, description: &str, content: &[(&str, SchemaFn)]) -> Self {
+        let content = content
+            .iter()
+            .map(|(mime, schema)| (mime.to_string(), *schema))
+            .collect();
+        self.request_body = Some((description.to_owned(), content));
+        self
+    }
+
+    pub fn response(mut self, status: u16, description: &str, schema: Option<SchemaFn>) -> Self {
+        self.responses
+            .push((status, json!({ "description": description }), schema));
+        self
+    }
+
+    /// Adds error responses defined in `components/responses`.
+    pub fn errors(mut self, statuses: &[u16]) -> Self {
+        for status in statuses {
+            self.responses.push((
+                *status,
+                json!({ "$ref": format!("#/components/responses/{}", status) }),
+                None,
+            ));
+        }
+        self
+    }
+
+    fn to_value(&self, gen: &mut SchemaGenerator) -> Value {
+        let responses: Map<String, Value> = self
+            .responses
+            .iter()
+            .map(|(status, response, schema)| {
+                let mut response = response.clone();
+                if let Some(schema) = schema {
+                    response["content"] = json!({ "application/json": { "schema": schema(gen) } });
+                }
+                (status.to_string(), response)
+            })
+            .collect();
+        let mut op = json!({
+            "summary": self.summary,
+            "operationId": self.operation_id,
+            "tags": [self.tag],
+            "responses": responses,
+        });
+        if let Some(description) = self.description {
+            op["description"] = json!(description);
+        }
+        if !self.parameters.is_empty() {
+            let parameters: Vec<Value> = self
+                .parameters
+                .iter()
+                .map(|(param, schema)| {
+                    let mut param = param.clone();
+                    if let Some(schema) = schema {
+                        param["schema"] = schema(gen);
+                    }
+                    param
+                })
+                .collect();
+            op["parameters"] = json!(parameters);
+        }
+        if let Some((description, content)) = &self.request_body {
+            let content: Map<String, Value> = content
+                .iter()
+                .map(|(mime, schema)| (mime.clone(), json!({ "schema": schema(gen) })))
+                .collect();
+            op["requestBody"] = json!({
+                "description": description,
+                "required": true,
+                "content": content,
+            });
+        }
+        op
+    }
+}
+
+/// All operations served by the API, see `index::routes`.
+pub fn operations() -> Vec<Operation> {
+    let mut ret = index::operations();
+    ret.extend(health_check::operations());
+    ret.extend(accounts::operations());
+    ret.extend(transactions::operations());
+    ret.extend(events::operations());
+    ret.extend(proofs::operations());
+    ret.extend(batch::operations());
+    ret
+}
+
+pub fn spec() -> Value {
+    let mut doc: Value =
+        serde_yaml::from_str(COMPONENTS).expect("parse doc/components.yaml failed");
+    let mut gen = SchemaSettings::openapi3().into_generator();
+    let mut paths = Map::new();
+    for op in operations() {
+        let path = paths.entry(op.path.to_owned()).or_insert_with(|| json!({}));
+        assert!(
+            path.get(op.method).is_none(),
+            "duplicated operation: {} {}",
+            op.method,
+            op.path
+        );
+        path[op.method] = op.to_value(&mut gen);
+    }
+    doc["paths"] = Value::Object(paths);
+
+    // Referred to by the shared parameters and error responses
+    gen.subschema_for::<Address>();
+    gen.subschema_for::<U64>();
+    gen.subschema_for::<Error>();
+    let schemas: Map<String, Value> = gen
+        .take_definitions()
+        .into_iter()
+        .map(|(name, schema)| (name, to_openapi(&mut gen, schema)))
+        .collect();
+    doc["components"]["schemas"] = Value::Object(schemas);
+    doc
+}
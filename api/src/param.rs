@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_api_types::{Address, Error, EventKey, MoveStructTag, TransactionId};
+use aptos_types::access_path::StructTagPrefix;
 use move_core_types::identifier::Identifier;
 use percent_encoding::percent_decode_str;
 use serde::{Deserialize, Deserializer};
@@ -15,6 +16,7 @@ pub type LedgerVersionParam = Param<u64>;
 pub type EventKeyParam = Param<EventKey>;
 pub type MoveStructTagParam = Param<MoveStructTag>;
 pub type MoveIdentifierParam = Param<Identifier>;
+pub type StructTagPrefixParam = Param<StructTagPrefix>;
 
 /// `Param` is designed for parsing `warp` path parameter or query string
 /// into a type specified by the generic type parameter of `Param`.
@@ -0,0 +1,113 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::Context,
+    failpoint::fail_point,
+    metrics::metrics,
+    openapi::{schema, Operation},
+    param::LedgerVersionParam,
+};
+
+use aptos_api_types::{AccumulatorConsistencyProof, Error, Response, TransactionId, U64};
+
+use serde::Deserialize;
+use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
+
+const CLIENT_KNOWN_VERSION_DESCRIPTION: &str = "\
+The version of the accumulator the client already knows, e.g. a checkpoint it has pinned. \
+Default is pre-genesis, for which the proof is the whole accumulator at the ledger version.";
+const LEDGER_VERSION_DESCRIPTION: &str =
+    "The version the accumulator is extended to. Default is the latest ledger version.";
+
+pub fn operations() -> Vec<Operation> {
+    vec![Operation::get(
+        "/accumulator/consistency_proof",
+        "get_accumulator_consistency_proof",
+    )
+    .summary("Get transaction accumulator consistency proof")
+    .description(
+        "This API returns the subtrees appended to the transaction accumulator between two\n\
+         versions. Appending them to the frozen subtrees of the accumulator at\n\
+         `client_known_version` must give the accumulator root hash at `ledger_version`,\n\
+         which proves that the ledger only grew by appending transactions in between.",
+    )
+    .tag("proofs")
+    .query_param(
+        "client_known_version",
+        CLIENT_KNOWN_VERSION_DESCRIPTION,
+        schema::<U64>(),
+    )
+    .query_param(
+        "ledger_version",
+        LEDGER_VERSION_DESCRIPTION,
+        schema::<U64>(),
+    )
+    .response(
+        200,
+        "Returns the accumulator consistency proof",
+        Some(schema::<AccumulatorConsistencyProof>()),
+    )
+    .errors(&[400, 404, 500])]
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ConsistencyProofQuery {
+    client_known_version: Option<LedgerVersionParam>,
+    ledger_version: Option<LedgerVersionParam>,
+}
+
+// GET /accumulator/consistency_proof
+pub fn get_accumulator_consistency_proof(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("accumulator" / "consistency_proof")
+        .and(warp::get())
+        .and(warp::query::<ConsistencyProofQuery>())
+        .and(context.filter())
+        .and_then(handle_get_accumulator_consistency_proof)
+        .with(metrics("get_accumulator_consistency_proof"))
+        .boxed()
+}
+
+async fn handle_get_accumulator_consistency_proof(
+    query: ConsistencyProofQuery,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_accumulator_consistency_proof")?;
+    Ok(consistency_proof(query, context)?)
+}
+
+fn consistency_proof(query: ConsistencyProofQuery, context: Context) -> Result<impl Reply, Error> {
+    let latest_ledger_info = context.get_latest_ledger_info()?;
+    let ledger_version = query
+        .ledger_version
+        .map(|v| v.parse("ledger version"))
+        .unwrap_or_else(|| Ok(latest_ledger_info.version()))?;
+    if ledger_version > latest_ledger_info.version() {
+        return Err(Error::not_found(
+            "ledger",
+            TransactionId::Version(ledger_version),
+            latest_ledger_info.version(),
+        ));
+    }
+    let client_known_version = query
+        .client_known_version
+        .map(|v| v.parse("client known version"))
+        .transpose()?;
+    if let Some(client_known_version) = client_known_version {
+        if client_known_version > ledger_version {
+            return Err(Error::invalid_param(
+                "client known version",
+                format!(
+                    "{}, newer than ledger version {}",
+                    client_known_version, ledger_version
+                ),
+            ));
+        }
+    }
+
+    let proof = context.get_accumulator_consistency_proof(client_known_version, ledger_version)?;
+    Response::new(
+        latest_ledger_info,
+        &AccumulatorConsistencyProof::new(client_known_version, ledger_version, proof),
+    )
+}
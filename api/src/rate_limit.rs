@@ -0,0 +1,95 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_api_types::Error;
+use aptos_config::config::ApiRateLimitConfig;
+use aptos_infallible::RwLock;
+use aptos_rate_limiter::rate_limit::TokenBucketRateLimiter;
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+use warp::{http::StatusCode, Filter, Rejection};
+
+/// Limits the rate of requests of each client IP, with a limit which can be changed while the
+/// API is served.
+pub struct ApiRateLimiter {
+    limiter: RwLock<Option<Arc<TokenBucketRateLimiter<IpAddr>>>>,
+}
+
+impl ApiRateLimiter {
+    pub fn new(config: Option<&ApiRateLimitConfig>) -> Self {
+        Self {
+            limiter: RwLock::new(config.map(new_limiter)),
+        }
+    }
+
+    /// Replaces the limit, the clients start again with full buckets
+    pub fn update(&self, config: Option<&ApiRateLimitConfig>) {
+        *self.limiter.write() = config.map(new_limiter);
+    }
+
+    fn try_acquire(&self, ip: IpAddr) -> bool {
+        let limiter = self.limiter.read().clone();
+        match limiter {
+            Some(limiter) => limiter.bucket(ip).lock().acquire_all_tokens(1).is_ok(),
+            None => true,
+        }
+    }
+
+    /// Rejects the request with `429 Too Many Requests` when its client is over the limit
+    pub fn filter(self: Arc<Self>) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+        warp::addr::remote()
+            .and_then(move |addr: Option<SocketAddr>| {
+                let limiter = self.clone();
+                async move {
+                    match addr {
+                        Some(addr) if !limiter.try_acquire(addr.ip()) => {
+                            Err(warp::reject::custom(Error::new(
+                                StatusCode::TOO_MANY_REQUESTS,
+                                "Too many requests".to_owned(),
+                            )))
+                        }
+                        _ => Ok(()),
+                    }
+                }
+            })
+            .untuple_one()
+    }
+}
+
+fn new_limiter(config: &ApiRateLimitConfig) -> Arc<TokenBucketRateLimiter<IpAddr>> {
+    Arc::new(TokenBucketRateLimiter::new(
+        "api",
+        String::new(),
+        100,
+        config.burst_size.max(1),
+        config.requests_per_sec.max(1),
+        None,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_rate_limit() {
+        let ip: IpAddr = "127.0.0.1".parse().unwrap();
+        let limiter = ApiRateLimiter::new(None);
+        assert!((0..100).all(|_| limiter.try_acquire(ip)));
+
+        limiter.update(Some(&ApiRateLimitConfig {
+            requests_per_sec: 1,
+            burst_size: 2,
+        }));
+        assert!(limiter.try_acquire(ip));
+        assert!(limiter.try_acquire(ip));
+        assert!(!limiter.try_acquire(ip));
+        // Clients are limited independently
+        assert!(limiter.try_acquire("127.0.0.2".parse().unwrap()));
+
+        limiter.update(None);
+        assert!(limiter.try_acquire(ip));
+    }
+}
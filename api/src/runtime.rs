@@ -1,44 +1,56 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{context::Context, index};
+use crate::{context::Context, index, rate_limit::ApiRateLimiter};
 
 use aptos_config::config::{ApiConfig, JsonRpcConfig, NodeConfig};
 use aptos_mempool::MempoolClientSender;
+use aptos_metrics::runtime::instrumented_runtime;
 use aptos_types::chain_id::ChainId;
+use network::application::storage::PeerMetadataStorage;
 use storage_interface::MoveDbReader;
 use warp::{Filter, Reply};
 
 use std::{convert::Infallible, net::SocketAddr, sync::Arc};
-use tokio::runtime::{Builder, Runtime};
+use tokio::runtime::Runtime;
 
 /// Creates HTTP server (warp-based) serves for both REST and JSON-RPC API.
 /// When api and json-rpc are configured with same port, both API will be served for the port.
 /// When api and json-rpc are configured with different port, both API will be served for
 /// both ports.
-/// Returns corresponding Tokio runtime
+/// Returns corresponding Tokio runtime, and the rate limiter of the API for its limit to be
+/// changed while the API is served.
 pub fn bootstrap(
     config: &NodeConfig,
     chain_id: ChainId,
     db: Arc<dyn MoveDbReader>,
     mp_sender: MempoolClientSender,
-) -> anyhow::Result<Runtime> {
-    let runtime = Builder::new_multi_thread()
-        .thread_name("api")
-        .enable_all()
-        .build()
-        .expect("[api] failed to create runtime");
+    peer_metadata_storage: Arc<PeerMetadataStorage>,
+) -> anyhow::Result<(Runtime, Arc<ApiRateLimiter>)> {
+    let runtime = instrumented_runtime(
+        "api",
+        config.runtimes.api_worker_threads,
+        config.runtimes.stall_threshold(),
+    );
 
     let role = config.base.role;
     let api_config = config.api.clone();
     let api = WebServer::from(api_config.clone());
 
+    let context = Context::new(
+        chain_id,
+        db,
+        mp_sender,
+        peer_metadata_storage,
+        role,
+        api_config,
+    );
+    let rate_limiter = context.rate_limiter();
     runtime.spawn(async move {
-        let context = Context::new(chain_id, db, mp_sender, role, api_config);
         let routes = index::routes(context);
         api.serve(routes).await;
     });
-    Ok(runtime)
+    Ok((runtime, rate_limiter))
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -98,6 +110,7 @@ mod tests {
 
     use aptos_config::config::NodeConfig;
     use aptos_types::chain_id::ChainId;
+    use network::application::storage::PeerMetadataStorage;
 
     use crate::{
         runtime::bootstrap,
@@ -119,6 +132,7 @@ mod tests {
             ChainId::test(),
             context.db.clone(),
             context.mempool.ac_client.clone(),
+            PeerMetadataStorage::new(&[]),
         );
         assert!(ret.is_ok());
 
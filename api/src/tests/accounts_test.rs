@@ -3,7 +3,8 @@
 
 use crate::tests::{assert_json, find_value, new_test_context};
 use aptos_api_types::HexEncodedBytes;
-use serde_json::json;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde_json::{json, Value};
 
 #[tokio::test]
 async fn test_get_account_resources_returns_empty_array_for_account_has_no_resources() {
@@ -538,6 +539,94 @@ async fn test_get_account_resources_by_invalid_ledger_version() {
     );
 }
 
+#[tokio::test]
+async fn test_get_account_resources_by_prefix() {
+    let context = new_test_context();
+    let address = "0xdd";
+    let all_resources = context.get(&account_resources(address)).await;
+    let mut expected = all_resources
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|v| {
+            v["type"]
+                .as_str()
+                .unwrap()
+                .starts_with("0x1::DiemAccount::")
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    assert!(expected.len() > 1);
+
+    let resources = context
+        .get(&account_resources_with_prefix(
+            address,
+            "0x1::DiemAccount::*",
+        ))
+        .await;
+    let mut actual = resources.as_array().unwrap().clone();
+    let by_type = |v: &Value| v["type"].as_str().unwrap().to_owned();
+    expected.sort_by_key(by_type);
+    actual.sort_by_key(by_type);
+    assert_eq!(actual, expected);
+
+    // Pages of one resource, each starting after the last resource of the previous page
+    let mut paged = vec![];
+    let mut url = format!(
+        "{}&limit=1",
+        account_resources_with_prefix(address, "0x1::DiemAccount::*")
+    );
+    loop {
+        let page = context.get(&url).await;
+        let page = page.as_array().unwrap();
+        match page.first() {
+            Some(resource) => {
+                assert_eq!(page.len(), 1);
+                paged.push(resource.clone());
+                url = format!(
+                    "{}&limit=1&start={}",
+                    account_resources_with_prefix(address, "0x1::DiemAccount::*"),
+                    utf8_percent_encode(resource["type"].as_str().unwrap(), NON_ALPHANUMERIC)
+                );
+            }
+            None => break,
+        }
+    }
+    assert_eq!(paged, resources.as_array().unwrap().clone());
+}
+
+#[tokio::test]
+async fn test_get_account_resources_by_invalid_prefix() {
+    let context = new_test_context();
+    let resp = context
+        .expect_status_code(400)
+        .get(&account_resources_with_prefix("0xdd", "0x1::DiemAccount"))
+        .await;
+    assert_json(
+        resp,
+        json!({
+            "code": 400,
+            "message": "invalid parameter struct tag prefix: 0x1::DiemAccount"
+        }),
+    );
+}
+
+#[tokio::test]
+async fn test_get_account_resources_page_without_prefix() {
+    let context = new_test_context();
+    let resp = context
+        .expect_status_code(400)
+        .get(&format!("{}?limit=1", account_resources("0xdd")))
+        .await;
+    assert_json(
+        resp,
+        json!({
+            "code": 400,
+            "message": "start and limit are only supported along with a prefix"
+        }),
+    );
+}
+
 #[tokio::test]
 async fn test_get_account_modules_by_ledger_version() {
     let context = new_test_context();
@@ -602,6 +691,10 @@ fn account_resources_with_ledger_version(address: &str, ledger_version: i128) ->
     format!("{}?version={}", account_resources(address), ledger_version)
 }
 
+fn account_resources_with_prefix(address: &str, prefix: &str) -> String {
+    format!("{}?prefix={}", account_resources(address), prefix)
+}
+
 fn account_modules(address: &str) -> String {
     format!("/accounts/{}/modules", address)
 }
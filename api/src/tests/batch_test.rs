@@ -0,0 +1,117 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::{assert_json, new_test_context};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_batch() {
+    let context = new_test_context();
+
+    let resp = context
+        .post(
+            "/batch",
+            json!([
+                {"type": "account_resources", "address": "0xa550c18"},
+                {"type": "transaction", "txn_hash_or_version": "0"},
+                {
+                    "type": "events",
+                    "event_key": "0x00000000000000000000000000000000000000000a550c18",
+                    "limit": "1"
+                },
+            ]),
+        )
+        .await;
+
+    assert_json(
+        resp,
+        json!([
+            {
+                "status": 200,
+                "body": context.get("/accounts/0xa550c18/resources").await
+            },
+            {
+                "status": 200,
+                "body": context.get("/transactions/0").await
+            },
+            {
+                "status": 200,
+                "body": context
+                    .get("/events/0x00000000000000000000000000000000000000000a550c18?limit=1")
+                    .await
+            },
+        ]),
+    );
+}
+
+#[tokio::test]
+async fn test_batch_with_failed_request() {
+    let context = new_test_context();
+
+    let resp = context
+        .post(
+            "/batch",
+            json!([
+                {"type": "account_resources", "address": "0x0"},
+                {"type": "transaction", "txn_hash_or_version": "0"},
+            ]),
+        )
+        .await;
+
+    let info = context.get_latest_ledger_info();
+    assert_json(
+        resp[0].clone(),
+        json!({
+            "status": 404,
+            "body": {
+                "code": 404,
+                "message": format!(
+                    "account not found by address(0x0) and ledger version({})",
+                    info.version()
+                ),
+                "aptos_ledger_version": info.ledger_version,
+            }
+        }),
+    );
+    assert_eq!(resp[1]["status"], 200);
+}
+
+#[tokio::test]
+async fn test_batch_at_ledger_version() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    context.commit_block(&vec![txn]).await;
+
+    let resp = context
+        .post(
+            "/batch?version=0",
+            json!([
+                {"type": "transaction", "txn_hash_or_version": "0"},
+                {"type": "transaction", "txn_hash_or_version": "1"},
+            ]),
+        )
+        .await;
+
+    assert_eq!(resp[0]["status"], 200);
+    assert_eq!(resp[1]["status"], 404);
+}
+
+#[tokio::test]
+async fn test_batch_exceeds_max_size() {
+    let context = new_test_context();
+
+    let requests = vec![json!({"type": "transaction", "txn_hash_or_version": "0"}); 101];
+    let resp = context
+        .expect_status_code(400)
+        .post("/batch", json!(requests))
+        .await;
+
+    assert_json(
+        resp,
+        json!({
+            "code": 400,
+            "message": "invalid request body: 101 requests, exceed limit 100"
+        }),
+    );
+}
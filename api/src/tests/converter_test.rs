@@ -0,0 +1,133 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::new_test_context;
+
+use aptos_api_types::MoveType;
+use move_core_types::language_storage::TypeTag;
+use serde_json::{json, Value};
+
+use std::convert::TryInto;
+
+#[tokio::test]
+async fn test_encode_and_decode_nested_struct_values() {
+    let context = new_test_context();
+    let converter = context.context.move_converter();
+    let cases = vec![
+        ("0x1::Option::Option<u64>", json!(null)),
+        ("0x1::Option::Option<u64>", json!("7")),
+        ("0x1::Option::Option<u64>", json!({"vec": ["7"]})),
+        (
+            "0x1::Option::Option<0x1::FixedPoint32::FixedPoint32>",
+            json!({"value": "4294967296"}),
+        ),
+        ("vector<0x1::ASCII::String>", json!(["hello", "world"])),
+        (
+            "vector<0x1::Option::Option<0x1::ASCII::String>>",
+            json!([null, "hello"]),
+        ),
+        (
+            "0x1::Event::EventHandle<0x1::DiemAccount::SentPaymentEvent>",
+            json!({
+                "counter": "3",
+                "guid": {
+                    "len_bytes": 24,
+                    "guid": {
+                        "id": {
+                            "creation_num": "0",
+                            "addr": "0xa550c18",
+                        }
+                    }
+                }
+            }),
+        ),
+    ];
+    for (typ, input) in cases {
+        let move_type: MoveType = typ.parse().unwrap();
+        let type_tag: TypeTag = move_type.clone().try_into().unwrap();
+
+        let value = converter
+            .try_into_move_value(&move_type, input.clone())
+            .unwrap_or_else(|e| panic!("encode {} as {} failed: {}", input, typ, e));
+        let bytes = bcs::to_bytes(&value).unwrap();
+
+        let annotated = converter
+            .try_into_annotated_json(&type_tag, &bytes)
+            .unwrap();
+        let reencoded = converter
+            .try_into_move_value(&move_type, annotated.clone())
+            .unwrap_or_else(|e| panic!("encode {} as {} failed: {}", annotated, typ, e));
+        assert_eq!(bcs::to_bytes(&reencoded).unwrap(), bytes, "{}", typ);
+    }
+}
+
+#[tokio::test]
+async fn test_decode_struct_value_with_type_annotations() {
+    let context = new_test_context();
+    let converter = context.context.move_converter();
+    let move_type: MoveType = "0x1::Option::Option<0x1::FixedPoint32::FixedPoint32>"
+        .parse()
+        .unwrap();
+    let value = converter
+        .try_into_move_value(&move_type, json!({"value": "1"}))
+        .unwrap();
+
+    let annotated = converter
+        .try_into_annotated_json(
+            &move_type.try_into().unwrap(),
+            &bcs::to_bytes(&value).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(
+        annotated,
+        json!({
+            "type": "0x1::Option::Option<0x1::FixedPoint32::FixedPoint32>",
+            "data": {
+                "vec": [{
+                    "type": "0x1::FixedPoint32::FixedPoint32",
+                    "data": {"value": "1"},
+                }]
+            }
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_encode_invalid_struct_values() {
+    let context = new_test_context();
+    let converter = context.context.move_converter();
+    let cases: Vec<(&str, Value, &str)> = vec![
+        (
+            "0x1::FixedPoint32::FixedPoint32",
+            json!({}),
+            "missing field value of struct 0x1::FixedPoint32::FixedPoint32",
+        ),
+        (
+            "0x1::FixedPoint32::FixedPoint32",
+            json!({"value": "1", "scale": "2"}),
+            "unknown fields [\"scale\"] of struct 0x1::FixedPoint32::FixedPoint32",
+        ),
+        (
+            "0x1::FixedPoint32::FixedPoint32",
+            json!("1"),
+            "expected object<0x1::FixedPoint32::FixedPoint32>, but got: String(\"1\")",
+        ),
+        (
+            "0x1::ASCII::String",
+            json!("héllo"),
+            "expected ASCII string, but got: \"héllo\"",
+        ),
+        (
+            "0x1::FixedPoint32::Missing",
+            json!({}),
+            "could not find struct by 0x1::FixedPoint32::Missing",
+        ),
+    ];
+    for (typ, input, expected) in cases {
+        let move_type: MoveType = typ.parse().unwrap();
+        let err = converter
+            .try_into_move_value(&move_type, input)
+            .unwrap_err();
+        assert_eq!(err.to_string(), expected);
+    }
+}
@@ -214,3 +214,35 @@ async fn test_get_events_by_struct_type_has_generic_type_parameter() {
         }),
     );
 }
+
+#[tokio::test]
+async fn test_get_events_by_creation_number() {
+    let context = new_test_context();
+
+    let resp = context.get("/accounts/0xa550c18/events/0?start=1").await;
+
+    assert_json(
+        resp,
+        context
+            .get("/events/0x00000000000000000000000000000000000000000a550c18?start=1")
+            .await,
+    );
+}
+
+#[tokio::test]
+async fn test_get_events_by_invalid_creation_number() {
+    let context = new_test_context();
+
+    let resp = context
+        .expect_status_code(400)
+        .get("/accounts/0xa550c18/events/invalid")
+        .await;
+
+    assert_json(
+        resp,
+        json!({
+            "code": 400,
+            "message": "invalid parameter creation number: invalid"
+        }),
+    );
+}
@@ -20,6 +20,19 @@ async fn test_get_ledger_info() {
     assert_eq!(expected, resp);
 }
 
+#[tokio::test]
+async fn test_get_node_info() {
+    let context = new_test_context();
+    let genesis_hash = context.get_transactions(0, 1)[0].info.transaction_hash();
+    let resp = context.get("/info").await;
+
+    assert_eq!(resp["chain_id"], json!(4));
+    assert_eq!(resp["genesis_hash"], json!(genesis_hash.to_hex_literal()));
+    assert_eq!(resp["node_role"], json!("validator"));
+    assert_eq!(resp["oldest_ledger_version"], json!("0"));
+    assert!(resp["build_version"].is_string());
+}
+
 #[tokio::test]
 async fn test_returns_not_found_for_the_invalid_path() {
     let context = new_test_context();
@@ -52,10 +65,53 @@ async fn test_health_check() {
     assert_eq!(resp.status(), 200)
 }
 
+#[tokio::test]
+async fn test_health_check_with_stale_ledger() {
+    let context = new_test_context();
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/-/healthy?duration_secs=1"),
+        )
+        .await;
+    assert_eq!(resp.status(), 503)
+}
+
+#[tokio::test]
+async fn test_health_check_detail() {
+    let context = new_test_context();
+    let ledger_info = context.get_latest_ledger_info();
+    let resp = context.get("/-/healthy/detail").await;
+
+    assert_eq!(resp["healthy"], json!(true));
+    assert_eq!(
+        resp["ledger_version"],
+        json!(ledger_info.version().to_string())
+    );
+    assert_eq!(
+        resp["ledger_timestamp"],
+        json!(ledger_info.timestamp().to_string())
+    );
+    assert_eq!(resp["mempool_size"], json!("0"));
+    assert_eq!(resp["connected_peers"], json!({}));
+    assert_eq!(resp["reachability"], json!({}));
+}
+
+#[tokio::test]
+async fn test_health_check_detail_with_stale_ledger() {
+    let context = new_test_context();
+    let resp = context
+        .expect_status_code(503)
+        .get("/-/healthy/detail?duration_secs=1")
+        .await;
+    assert_eq!(resp["healthy"], json!(false));
+}
+
 #[tokio::test]
 async fn test_openapi_spec() {
     let context = new_test_context();
-    let paths = ["/openapi.yaml", "/spec.html"];
+    let paths = ["/spec.yaml", "/openapi.yaml", "/spec.html"];
     for path in paths {
         let req = warp::test::request().method("GET").path(path);
         let resp = context.reply(req).await;
@@ -135,3 +191,107 @@ async fn test_cors_on_non_200_responses() {
     let cors_header = resp.headers().get("access-control-allow-origin").unwrap();
     assert_eq!(cors_header, "*");
 }
+
+#[tokio::test]
+async fn test_etag_not_modified() {
+    let context = new_test_context();
+    let resp = context
+        .reply(warp::test::request().method("GET").path("/"))
+        .await;
+    assert_eq!(resp.status(), 200);
+    let etag = resp.headers()["etag"].clone();
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/")
+                .header("if-none-match", etag.clone()),
+        )
+        .await;
+    assert_eq!(resp.status(), 304);
+    assert_eq!(resp.headers()["etag"], etag);
+    assert!(resp.body().is_empty());
+
+    context.commit_mempool_txns(1).await;
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/")
+                .header("if-none-match", etag.clone()),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    assert_ne!(resp.headers()["etag"], etag);
+}
+
+#[tokio::test]
+async fn test_etag_unchanged_without_modification() {
+    let context = new_test_context();
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/accounts/0x1/modules"),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    let etag = resp.headers()["etag"].clone();
+    let ledger_version = resp.headers()["x-aptos-ledger-version"].clone();
+
+    context.commit_mempool_txns(1).await;
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/accounts/0x1/modules")
+                .header("if-none-match", etag.clone()),
+        )
+        .await;
+    assert_eq!(resp.status(), 304);
+    assert_eq!(resp.headers()["etag"], etag);
+    assert_ne!(resp.headers()["x-aptos-ledger-version"], ledger_version);
+}
+
+#[tokio::test]
+async fn test_etag_differs_by_request() {
+    let context = new_test_context();
+    let mut etags = vec![];
+    for path in ["/accounts/0x1/resources", "/accounts/0x1/modules", "/"] {
+        let resp = context
+            .reply(warp::test::request().method("GET").path(path))
+            .await;
+        assert_eq!(resp.status(), 200);
+        etags.push(resp.headers()["etag"].clone());
+    }
+    assert_ne!(etags[0], etags[1]);
+    assert_ne!(etags[1], etags[2]);
+}
+
+#[tokio::test]
+async fn test_compression() {
+    let context = new_test_context();
+    for (accept_encoding, content_encoding) in [
+        ("gzip", Some("gzip")),
+        ("gzip, br", Some("br")),
+        ("br;q=0, gzip", Some("gzip")),
+        ("deflate", None),
+    ] {
+        let resp = context
+            .reply(
+                warp::test::request()
+                    .method("GET")
+                    .path("/accounts/0x1/resources")
+                    .header("accept-encoding", accept_encoding),
+            )
+            .await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(
+            resp.headers()
+                .get("content-encoding")
+                .map(|v| v.to_str().unwrap()),
+            content_encoding
+        );
+    }
+}
@@ -2,9 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod accounts_test;
+mod batch_test;
+mod converter_test;
 mod events_test;
 mod index_test;
 mod invalid_post_request_test;
+mod openapi_test;
+mod proofs_test;
 mod string_resource_test;
 mod test_context;
 mod transactions_test;
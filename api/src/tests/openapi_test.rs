@@ -0,0 +1,289 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    openapi::{operations, SPEC},
+    tests::{new_test_context, pretty},
+};
+use aptos_api_types::{StateCheckpointTransaction, Transaction, TransactionInfo};
+use aptos_crypto::HashValue;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn test_spec_yaml_contains_all_operations() {
+    let context = new_test_context();
+    let resp = context
+        .reply(warp::test::request().method("GET").path("/spec.yaml"))
+        .await;
+    assert_eq!(resp.status(), 200);
+
+    let spec: Value = serde_yaml::from_slice(resp.body()).unwrap();
+    for op in operations() {
+        assert_eq!(
+            spec["paths"][op.path][op.method]["operationId"],
+            json!(op.operation_id)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_every_operation_is_routed() {
+    let context = new_test_context();
+    for op in operations() {
+        let path = op
+            .path
+            .replace("{address}", "0xa550c18")
+            .replace("{txn_hash_or_version}", "0")
+            .replace(
+                "{event_key}",
+                "0x00000000000000000000000000000000000000000a550c18",
+            )
+            .replace("{event_handle_struct}", "0x1::DiemAccount::DiemAccount")
+            .replace("{field_name}", "sent_events")
+            .replace("{creation_number}", "0");
+        let req = warp::test::request()
+            .method(&op.method.to_uppercase())
+            .path(&path)
+            .json(&json!({}));
+        let resp = context.reply(req).await;
+        let unrouted = resp.status() == 405
+            || (resp.status() == 404
+                && serde_json::from_slice::<Value>(resp.body()).ok()
+                    == Some(json!({"code": 404, "message": "Not Found"})));
+        assert!(!unrouted, "{} {} is not routed", op.method, path);
+    }
+}
+
+#[tokio::test]
+async fn test_responses_match_spec_schemas() {
+    let context = new_test_context();
+    let cases = [
+        ("/", "/"),
+        ("/info", "/info"),
+        ("/accounts/{address}", "/accounts/0xa550c18"),
+        ("/-/healthy/detail", "/-/healthy/detail"),
+        (
+            "/accumulator/consistency_proof",
+            "/accumulator/consistency_proof?client_known_version=0",
+        ),
+    ];
+    for (path, url) in cases {
+        let resp = context.get(url).await;
+        assert_matches_spec("get", path, 200, &resp);
+    }
+}
+
+#[tokio::test]
+async fn test_on_chain_data_matches_spec_schemas() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    let pending = context
+        .expect_status_code(202)
+        .post_bcs_txn("/transactions", bcs::to_bytes(&txn).unwrap())
+        .await;
+    assert_matches_spec("post", "/transactions", 202, &pending);
+    context.commit_block(&[txn]).await;
+
+    // The genesis, block metadata and user transactions, and the resources, modules and events
+    // they write, serialized by `aptos-api-types`
+    let tc_address = context.tc_account().address();
+    let cases = [
+        ("/transactions", "/transactions?start=0&limit=3".to_owned()),
+        (
+            "/transactions/{txn_hash_or_version}",
+            "/transactions/2".to_owned(),
+        ),
+        (
+            "/accounts/{address}/transactions",
+            format!("/accounts/{}/transactions", tc_address),
+        ),
+        (
+            "/accounts/{address}/resources",
+            format!("/accounts/{}/resources", account.address()),
+        ),
+        (
+            "/accounts/{address}/modules",
+            "/accounts/0x1/modules".to_owned(),
+        ),
+        (
+            "/accounts/{address}/events/{event_handle_struct}/{field_name}",
+            "/accounts/0xa550c18/events/0x1::DiemAccount::AccountOperationsCapability/creation_events"
+                .to_owned(),
+        ),
+    ];
+    for (path, url) in cases.iter() {
+        let resp = context.get(url).await;
+        assert_matches_spec("get", path, 200, &resp);
+    }
+}
+
+#[test]
+fn test_state_checkpoint_transaction_matches_spec_schema() {
+    // Not written by the test context's blocks, so it's serialized directly
+    let txn = Transaction::StateCheckpointTransaction(StateCheckpointTransaction {
+        info: TransactionInfo {
+            version: 1.into(),
+            hash: HashValue::zero().into(),
+            state_root_hash: HashValue::zero().into(),
+            event_root_hash: HashValue::zero().into(),
+            gas_used: 0.into(),
+            success: true,
+            vm_status: "Executed successfully".to_owned(),
+            accumulator_root_hash: HashValue::zero().into(),
+        },
+        timestamp: 1.into(),
+    });
+    let value = serde_json::to_value(&txn).unwrap();
+    validate(&json!({"$ref": "#/components/schemas/Transaction"}), &value).unwrap();
+}
+
+#[tokio::test]
+async fn test_error_response_matches_spec_schema() {
+    let context = new_test_context();
+    let resp = context
+        .expect_status_code(404)
+        .get("/accounts/0x0/resources")
+        .await;
+    validate(&json!({"$ref": "#/components/schemas/Error"}), &resp).unwrap();
+}
+
+#[test]
+fn test_spec_refs_resolve() {
+    let mut refs = vec![];
+    collect_refs(&SPEC, &mut refs);
+    assert!(!refs.is_empty());
+    for reference in refs {
+        assert!(
+            SPEC.pointer(reference.trim_start_matches('#')).is_some(),
+            "{} does not resolve",
+            reference
+        );
+    }
+}
+
+fn collect_refs<'a>(value: &'a Value, refs: &mut Vec<&'a str>) {
+    match value {
+        Value::Object(fields) => {
+            if let Some(reference) = fields.get("$ref").and_then(Value::as_str) {
+                refs.push(reference);
+            }
+            fields.values().for_each(|v| collect_refs(v, refs));
+        }
+        Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+        _ => (),
+    }
+}
+
+fn assert_matches_spec(method: &str, path: &str, status: u16, resp: &Value) {
+    let schema = &SPEC["paths"][path][method]["responses"][status.to_string()]["content"]
+        ["application/json"]["schema"];
+    assert!(
+        !schema.is_null(),
+        "{} {} has no {} schema",
+        method,
+        path,
+        status
+    );
+    if let Err(err) = validate(schema, resp) {
+        panic!(
+            "{} {} does not match spec: {}\n{}",
+            method.to_uppercase(),
+            path,
+            err,
+            pretty(resp)
+        );
+    }
+}
+
+fn resolve(schema: &Value) -> &Value {
+    match schema["$ref"].as_str() {
+        Some(reference) => {
+            let name = reference.trim_start_matches("#/components/schemas/");
+            resolve(&SPEC["components"]["schemas"][name])
+        }
+        None => schema,
+    }
+}
+
+/// Merges the parts of an `allOf` into one schema, as each part only declares some of the
+/// properties of an object, or a single part wraps a `$ref` with siblings, e.g. `nullable`.
+fn merge_all_of(schema: &Value) -> Value {
+    let mut merged = json!({});
+    for part in schema["allOf"].as_array().into_iter().flatten() {
+        let part = resolve(part);
+        let part = if part.get("allOf").is_some() {
+            merge_all_of(part)
+        } else {
+            part.clone()
+        };
+        for (key, value) in part.as_object().into_iter().flatten() {
+            match (&mut merged[key.as_str()], value) {
+                (Value::Array(required), Value::Array(more)) if key == "required" => {
+                    required.extend(more.iter().cloned())
+                }
+                (Value::Object(properties), Value::Object(more)) if key == "properties" => {
+                    properties.extend(more.clone())
+                }
+                (merged_value, value) => *merged_value = value.clone(),
+            }
+        }
+    }
+    merged
+}
+
+/// Validates the parts of the OpenAPI schema used by this API: `$ref`, `allOf`, `oneOf`, `type`,
+/// `nullable`, `enum`, `required`, `properties` and `items`. Objects must not have properties
+/// undeclared by the schema, unless it declares `additionalProperties` or no properties at all.
+fn validate(schema: &Value, value: &Value) -> Result<(), String> {
+    let schema = resolve(schema);
+    if value.is_null() && schema["nullable"] == json!(true) {
+        return Ok(());
+    }
+    if schema.get("allOf").is_some() {
+        return validate(&merge_all_of(schema), value);
+    }
+    if let Some(schemas) = schema["oneOf"].as_array() {
+        if schemas.iter().any(|s| validate(s, value).is_ok()) {
+            return Ok(());
+        }
+        return Err(format!("{} matches none of {}", value, schema["oneOf"]));
+    }
+    let matches_type = match schema["type"].as_str() {
+        Some("object") => value.is_object(),
+        Some("array") => value.is_array(),
+        Some("string") => value.is_string(),
+        Some("integer") => value.is_u64() || value.is_i64(),
+        Some("boolean") => value.is_boolean(),
+        _ => true,
+    };
+    if !matches_type {
+        return Err(format!("expected type {}, got {}", schema["type"], value));
+    }
+    if let Some(values) = schema["enum"].as_array() {
+        if !values.contains(value) {
+            return Err(format!("expected one of {}, got {}", schema["enum"], value));
+        }
+    }
+    if let Some(items) = value.as_array() {
+        return items.iter().try_for_each(|v| validate(&schema["items"], v));
+    }
+    if let Some(fields) = value.as_object() {
+        for required in schema["required"].as_array().into_iter().flatten() {
+            let required = required.as_str().unwrap();
+            if !fields.contains_key(required) {
+                return Err(format!("missing required field {}", required));
+            }
+        }
+        for (name, field) in fields {
+            match schema["properties"].get(name) {
+                Some(field_schema) => validate(field_schema, field)
+                    .map_err(|err| format!("field {}: {}", name, err))?,
+                None if schema.get("additionalProperties").is_some() => (),
+                None if schema.get("properties").is_none() => (),
+                None => return Err(format!("field {} is not declared", name)),
+            }
+        }
+    }
+    Ok(())
+}
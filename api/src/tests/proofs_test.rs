@@ -0,0 +1,104 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::{assert_json, new_test_context, TestContext};
+use aptos_api_types::AccumulatorConsistencyProof;
+use aptos_crypto::HashValue;
+use aptos_types::{
+    block_info::BlockInfo,
+    ledger_info::LedgerInfo,
+    proof::{AccumulatorConsistencyProof as ConsistencyProof, TransactionAccumulatorSummary},
+};
+use serde_json::json;
+
+async fn consistency_proof(context: &TestContext, query: &str) -> ConsistencyProof {
+    let resp = context
+        .get(&format!("/accumulator/consistency_proof?{}", query))
+        .await;
+    serde_json::from_value::<AccumulatorConsistencyProof>(resp)
+        .unwrap()
+        .into()
+}
+
+/// A ledger info committing to the accumulator root hash at the version, as pinned by a client
+fn ledger_info(context: &TestContext, version: u64) -> LedgerInfo {
+    let root_hash = context.get_transactions(version, 1)[0].accumulator_root_hash;
+    LedgerInfo::new(
+        BlockInfo::new(0, 0, HashValue::zero(), root_hash, version, 0, None),
+        HashValue::zero(),
+    )
+}
+
+#[tokio::test]
+async fn test_get_accumulator_consistency_proof() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    context.commit_block(&vec![txn]).await;
+    let latest_version = context.get_latest_ledger_info().version();
+    assert!(latest_version > 0);
+
+    let genesis_proof = consistency_proof(&context, "ledger_version=0").await;
+    let summary = TransactionAccumulatorSummary::try_from_genesis_proof(genesis_proof, 0).unwrap();
+    summary
+        .verify_consistency(&ledger_info(&context, 0))
+        .unwrap();
+
+    let proof = consistency_proof(&context, "client_known_version=0").await;
+    let latest_summary = summary
+        .try_extend_with_proof(&proof, &ledger_info(&context, latest_version))
+        .unwrap();
+    assert_eq!(latest_summary.version(), latest_version);
+
+    let proof = consistency_proof(
+        &context,
+        &format!("client_known_version=0&ledger_version={}", latest_version),
+    )
+    .await;
+    assert_eq!(
+        summary
+            .try_extend_with_proof(&proof, &ledger_info(&context, latest_version))
+            .unwrap(),
+        latest_summary
+    );
+
+    let empty_proof = consistency_proof(
+        &context,
+        &format!("client_known_version={}", latest_version),
+    )
+    .await;
+    assert!(empty_proof.is_empty());
+}
+
+#[tokio::test]
+async fn test_get_accumulator_consistency_proof_from_newer_version() {
+    let context = new_test_context();
+    let resp = context
+        .expect_status_code(400)
+        .get("/accumulator/consistency_proof?client_known_version=1&ledger_version=0")
+        .await;
+    assert_json(
+        resp,
+        json!({
+            "code": 400,
+            "message": "invalid parameter client known version: 1, newer than ledger version 0"
+        }),
+    );
+}
+
+#[tokio::test]
+async fn test_get_accumulator_consistency_proof_ledger_version_is_too_large() {
+    let context = new_test_context();
+    let resp = context
+        .expect_status_code(404)
+        .get("/accumulator/consistency_proof?ledger_version=1000000000000000000")
+        .await;
+    assert_json(
+        resp,
+        json!({
+            "code": 404,
+            "message": "ledger not found by version(1000000000000000000)",
+            "aptos_ledger_version": "0"
+        }),
+    );
+}
@@ -38,6 +38,7 @@ use executor::db_bootstrapper;
 use executor_types::BlockExecutorTrait;
 use hyper::Response;
 use mempool_notifications::MempoolNotificationSender;
+use network::application::storage::PeerMetadataStorage;
 use storage_interface::DbReaderWriter;
 
 use executor::block_executor::BlockExecutor;
@@ -73,6 +74,7 @@ pub fn new_test_context() -> TestContext {
             ChainId::test(),
             db.clone(),
             mempool.ac_client.clone(),
+            PeerMetadataStorage::new(&[]),
             RoleType::Validator,
             ApiConfig::default(),
         ),
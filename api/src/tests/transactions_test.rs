@@ -580,6 +580,31 @@ async fn test_post_bcs_format_transaction() {
         .await;
 }
 
+#[tokio::test]
+async fn test_post_committed_transaction() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    context.commit_block(&vec![txn.clone()]).await;
+    let version = context.get_latest_ledger_info().version();
+
+    let resp = context
+        .expect_status_code(400)
+        .post_bcs_txn("/transactions", bcs::to_bytes(&txn).unwrap())
+        .await;
+    assert_json(
+        resp,
+        json!({
+          "code": 400,
+          "message": format!(
+              "transaction {} is already committed at version {}",
+              txn.committed_hash().to_hex_literal(),
+              version,
+          ),
+        }),
+    );
+}
+
 #[tokio::test]
 async fn test_post_invalid_bcs_format_transaction() {
     let context = new_test_context();
@@ -615,6 +640,66 @@ async fn test_post_invalid_signature_transaction() {
     );
 }
 
+#[tokio::test]
+async fn test_simulate_transaction() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    let body = bcs::to_bytes(&txn).unwrap();
+    let ledger_version = context.get_latest_ledger_info().version();
+
+    let resp = context
+        .expect_status_code(200)
+        .post_bcs_txn("/transactions/simulate", body)
+        .await;
+    assert_eq!(resp["type"], "user_transaction");
+    assert_eq!(resp["version"], (ledger_version + 1).to_string());
+    assert_eq!(
+        resp["hash"],
+        Transaction::UserTransaction(txn).hash().to_hex_literal()
+    );
+    assert_eq!(resp["success"], true);
+    assert_ne!(resp["gas_used"], "0");
+
+    // The simulated transaction is not committed
+    assert_eq!(context.get_latest_ledger_info().version(), ledger_version);
+    context
+        .expect_status_code(404)
+        .get(&format!("/accounts/{}", account.address()))
+        .await;
+}
+
+#[tokio::test]
+async fn test_simulate_invalid_signature_transaction() {
+    let mut context = new_test_context();
+    let txn = context.create_invalid_signature_transaction();
+    let body = bcs::to_bytes(&txn).unwrap();
+    let resp = context
+        .expect_status_code(400)
+        .post_bcs_txn("/transactions/simulate", &body)
+        .await;
+    assert_json(
+        resp,
+        json!({
+          "code": 400,
+          "message": "invalid transaction: INVALID_SIGNATURE"
+        }),
+    );
+}
+
+#[tokio::test]
+async fn test_simulate_invalid_bcs_format_transaction() {
+    let context = new_test_context();
+    let resp = context
+        .expect_status_code(400)
+        .post_bcs_txn(
+            "/transactions/simulate",
+            bcs::to_bytes("invalid data").unwrap(),
+        )
+        .await;
+    assert_eq!(resp["code"], 400);
+}
+
 #[tokio::test]
 async fn test_post_transaction_rejected_by_mempool() {
     let mut context = new_test_context();
@@ -641,6 +726,61 @@ async fn test_post_transaction_rejected_by_mempool() {
     );
 }
 
+#[tokio::test]
+async fn test_post_transaction_bundle() {
+    let mut context = new_test_context();
+    let account1 = context.gen_account();
+    let account2 = context.gen_account();
+    let mut tc_account = context.tc_account();
+    let txns = vec![
+        context.create_parent_vasp_by_account(&mut tc_account, &account1),
+        context.create_parent_vasp_by_account(&mut tc_account, &account2),
+    ];
+
+    let resp = context
+        .expect_status_code(202)
+        .post_bcs_txn("/transactions/bundle", bcs::to_bytes(&txns).unwrap())
+        .await;
+    let hashes: Vec<_> = resp
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|txn| txn["hash"].clone())
+        .collect();
+    let expected_hashes: Vec<_> = txns
+        .into_iter()
+        .map(|txn| json!(Transaction::UserTransaction(txn).hash().to_hex_literal()))
+        .collect();
+    assert_eq!(hashes, expected_hashes);
+}
+
+#[tokio::test]
+async fn test_post_transaction_bundle_with_gap() {
+    let mut context = new_test_context();
+    let account1 = context.gen_account();
+    let account2 = context.gen_account();
+    let account3 = context.gen_account();
+    let mut tc_account = context.tc_account();
+    let txn1 = context.create_parent_vasp_by_account(&mut tc_account, &account1);
+    context.create_parent_vasp_by_account(&mut tc_account, &account2);
+    let txn3 = context.create_parent_vasp_by_account(&mut tc_account, &account3);
+
+    let resp = context
+        .expect_status_code(400)
+        .post_bcs_txn(
+            "/transactions/bundle",
+            bcs::to_bytes(&vec![txn1, txn3]).unwrap(),
+        )
+        .await;
+    assert_json(
+        resp,
+        json!({
+          "code": 400,
+          "message": "transaction bundle is rejected: InvalidBundle - bundle transactions must be of the same account, with consecutive sequence numbers"
+        }),
+    );
+}
+
 #[tokio::test]
 async fn test_multi_agent_signed_transaction() {
     let mut context = new_test_context();
@@ -1238,10 +1378,10 @@ async fn test_get_txn_execute_failed_by_invalid_write_set_payload() {
 }
 
 #[tokio::test]
-async fn test_get_txn_execute_failed_by_invalid_script_function_address() {
+async fn test_post_txn_rejected_by_invalid_script_function_address() {
     let context = new_test_context();
     let account = context.dd_account();
-    test_get_txn_execute_failed_by_invalid_script_function(
+    test_post_txn_rejected_by_invalid_script_function(
         context,
         account,
         "0x1222",
@@ -1254,16 +1394,16 @@ async fn test_get_txn_execute_failed_by_invalid_script_function_address() {
             bcs::to_bytes(&Vec::<u8>::new()).unwrap(),
             bcs::to_bytes(&Vec::<u8>::new()).unwrap(),
         ],
-        MISC_ERROR,
+        "LINKER_ERROR",
     )
     .await
 }
 
 #[tokio::test]
-async fn test_get_txn_execute_failed_by_invalid_script_function_module_name() {
+async fn test_post_txn_rejected_by_invalid_script_function_module_name() {
     let context = new_test_context();
     let account = context.dd_account();
-    test_get_txn_execute_failed_by_invalid_script_function(
+    test_post_txn_rejected_by_invalid_script_function(
         context,
         account,
         "0x1",
@@ -1276,16 +1416,16 @@ async fn test_get_txn_execute_failed_by_invalid_script_function_module_name() {
             bcs::to_bytes(&Vec::<u8>::new()).unwrap(),
             bcs::to_bytes(&Vec::<u8>::new()).unwrap(),
         ],
-        MISC_ERROR,
+        "LINKER_ERROR",
     )
     .await
 }
 
 #[tokio::test]
-async fn test_get_txn_execute_failed_by_invalid_script_function_name() {
+async fn test_post_txn_rejected_by_invalid_script_function_name() {
     let context = new_test_context();
     let account = context.dd_account();
-    test_get_txn_execute_failed_by_invalid_script_function(
+    test_post_txn_rejected_by_invalid_script_function(
         context,
         account,
         "0x1",
@@ -1298,16 +1438,16 @@ async fn test_get_txn_execute_failed_by_invalid_script_function_name() {
             bcs::to_bytes(&Vec::<u8>::new()).unwrap(),
             bcs::to_bytes(&Vec::<u8>::new()).unwrap(),
         ],
-        MISC_ERROR,
+        "FUNCTION_RESOLUTION_FAILURE",
     )
     .await
 }
 
 #[tokio::test]
-async fn test_get_txn_execute_failed_by_invalid_script_function_type_arguments() {
+async fn test_post_txn_rejected_by_invalid_script_function_type_arguments() {
     let context = new_test_context();
     let account = context.dd_account();
-    test_get_txn_execute_failed_by_invalid_script_function(
+    test_post_txn_rejected_by_invalid_script_function(
         context,
         account,
         "0x1",
@@ -1325,16 +1465,16 @@ async fn test_get_txn_execute_failed_by_invalid_script_function_type_arguments()
             bcs::to_bytes(&Vec::<u8>::new()).unwrap(),
             bcs::to_bytes(&Vec::<u8>::new()).unwrap(),
         ],
-        MISC_ERROR,
+        "FUNCTION_RESOLUTION_FAILURE",
     )
     .await
 }
 
 #[tokio::test]
-async fn test_get_txn_execute_failed_by_invalid_script_function_arguments() {
+async fn test_post_txn_rejected_by_invalid_script_function_arguments() {
     let context = new_test_context();
     let account = context.dd_account();
-    test_get_txn_execute_failed_by_invalid_script_function(
+    test_post_txn_rejected_by_invalid_script_function(
         context,
         account,
         "0x1",
@@ -1347,16 +1487,16 @@ async fn test_get_txn_execute_failed_by_invalid_script_function_arguments() {
             bcs::to_bytes(&Vec::<u8>::new()).unwrap(),
             bcs::to_bytes(&Vec::<u8>::new()).unwrap(),
         ],
-        MISC_ERROR,
+        "FAILED_TO_DESERIALIZE_ARGUMENT",
     )
     .await
 }
 
 #[tokio::test]
-async fn test_get_txn_execute_failed_by_missing_script_function_arguments() {
+async fn test_post_txn_rejected_by_missing_script_function_arguments() {
     let context = new_test_context();
     let account = context.dd_account();
-    test_get_txn_execute_failed_by_invalid_script_function(
+    test_post_txn_rejected_by_invalid_script_function(
         context,
         account,
         "0x1",
@@ -1367,7 +1507,7 @@ async fn test_get_txn_execute_failed_by_missing_script_function_arguments() {
             bcs::to_bytes(&AccountAddress::from_hex_literal("0xdd").unwrap()).unwrap(),
             // missing 3 arguments
         ],
-        MISC_ERROR,
+        "NUMBER_OF_ARGUMENTS_MISMATCH",
     )
     .await
 }
@@ -1473,7 +1613,62 @@ async fn test_get_txn_execute_failed_by_invalid_script_function(
     args: Vec<Vec<u8>>,
     vm_status: &str,
 ) {
-    let txn = account.sign_with_transaction_builder(context.transaction_factory().script_function(
+    let txn = sign_script_function_txn(
+        &context,
+        &mut account,
+        address,
+        module_id,
+        func,
+        ty_args,
+        args,
+    );
+    test_transaction_vm_status(context, txn, false, vm_status).await
+}
+
+// The arguments of script functions are validated along with the transaction, an invalid
+// script function is rejected when submitted.
+async fn test_post_txn_rejected_by_invalid_script_function(
+    context: TestContext,
+    mut account: LocalAccount,
+    address: &str,
+    module_id: &str,
+    func: &str,
+    ty_args: Vec<TypeTag>,
+    args: Vec<Vec<u8>>,
+    status_code: &str,
+) {
+    let txn = sign_script_function_txn(
+        &context,
+        &mut account,
+        address,
+        module_id,
+        func,
+        ty_args,
+        args,
+    );
+    let resp = context
+        .expect_status_code(400)
+        .post_bcs_txn("/transactions", bcs::to_bytes(&txn).unwrap())
+        .await;
+    assert_json(
+        resp,
+        json!({
+          "code": 400,
+          "message": format!("invalid transaction: {}", status_code)
+        }),
+    );
+}
+
+fn sign_script_function_txn(
+    context: &TestContext,
+    account: &mut LocalAccount,
+    address: &str,
+    module_id: &str,
+    func: &str,
+    ty_args: Vec<TypeTag>,
+    args: Vec<Vec<u8>>,
+) -> SignedTransaction {
+    account.sign_with_transaction_builder(context.transaction_factory().script_function(
         ScriptFunction::new(
             ModuleId::new(
                 AccountAddress::from_hex_literal(address).unwrap(),
@@ -1483,9 +1678,7 @@ async fn test_get_txn_execute_failed_by_invalid_script_function(
             ty_args,
             args,
         ),
-    ));
-
-    test_transaction_vm_status(context, txn, false, vm_status).await
+    ))
 }
 
 async fn test_transaction_vm_status(
@@ -5,26 +5,223 @@ use crate::{
     context::Context,
     failpoint::fail_point,
     metrics::metrics,
+    openapi::{schema, Operation},
     page::Page,
     param::{AddressParam, TransactionIdParam},
 };
 
 use aptos_api_types::{
-    mime_types::BCS_SIGNED_TRANSACTION, Error, LedgerInfo, Response, Transaction, TransactionData,
-    TransactionId, TransactionOnChainData, TransactionSigningMessage, UserTransactionRequest,
+    mime_types::{BCS_SIGNED_TRANSACTION, JSON},
+    Error, LedgerInfo, PendingTransaction, Response, Transaction, TransactionData, TransactionId,
+    TransactionOnChainData, TransactionSigningMessage, UserTransactionRequest,
 };
+use aptos_crypto::HashValue;
 use aptos_types::{
     mempool_status::MempoolStatusCode,
-    transaction::{RawTransaction, SignedTransaction},
+    transaction::{RawTransaction, SignedTransaction, TransactionInfo, TransactionStatus},
 };
 
 use anyhow::Result;
+use schemars::gen::SchemaGenerator;
+use serde_json::{json, Value};
 use warp::{
     filters::BoxedFilter,
     http::{header::CONTENT_TYPE, StatusCode},
     reply, Filter, Rejection, Reply,
 };
 
+const SUBMIT_TRANSACTION_DESCRIPTION: &str = "\
+**Submit transaction using JSON without additional tools**
+
+  * Send [POST /transactions/signing_message](#operation/create-signing-message) to create transaction signing message.
+  * Sign the transaction signing message and create transaction signature.
+  * Submit the user transaction request with the transaction siganture. The request header \"Content-Type\" must set to \"application/json\".
+
+**Submit transaction using signed transaction BCS bytes**
+
+  * Generate Aptos core types and transaction script functions for the client application langauge
+    by [Tranaction Builder](https://github.com/aptos-labs/aptos-core/tree/main/aptos-move/transaction-builder-generator)
+  * Create [RawTransaction](https://aptos-labs.github.io/aptos-core/aptos_types/transaction/struct.RawTransaction.html).
+  * Create transaction signing message: bytes(\"DIEM::RawTransaction\") + BCS bytes of the RawTransaction.
+    See [Crypto Spec](https://github.com/aptos-labs/aptos-core/blob/main/specifications/crypto/README.md) for more details.
+  * Sign the transaction signing message and create transaction signature.
+  * Create [SignedTransaction](https://aptos-labs.github.io/aptos-core/aptos_types/transaction/struct.SignedTransaction.html).
+  * Serialize [SignedTransaction](https://aptos-labs.github.io/aptos-core/aptos_types/transaction/struct.SignedTransaction.html)
+    into BCS bytes.
+  * Submit the [SignedTransaction](https://aptos-labs.github.io/aptos-core/aptos_types/transaction/struct.SignedTransaction.html)
+    BCS bytes (do not hex-encoded it). The request header \"Content-Type\" must set to \"application/x.diem.signed_transaction+bcs\".
+";
+
+const GET_TRANSACTION_DESCRIPTION: &str = "\
+There are two types of transaction identifiers:
+  1. Tranasction hash: included in any transaction JSON respond from server.
+  2. Transaction version: included in on-chain transaction JSON respond from server.
+
+When given transaction hash, server first looks up on-chain transaction by hash;
+if no on-chain transaction found, then look up transaction by hash in the mempool
+(pending) transactions.
+
+When given a transaction version, server looks up the transaction on-chain by version.
+
+To create a transaction hash:
+  1. Create hash message bytes: \"DIEM::Transaction\" bytes + BCS bytes of [Transaction](https://aptos-labs.github.io/aptos-core/aptos_types/transaction/enum.Transaction.html).
+  2. Apply hash algorithm `SHA3-256` to the hash message bytes.
+  3. Hex-encode the hash bytes with `0x` prefix.
+";
+
+const SIMULATE_TRANSACTION_DESCRIPTION: &str = "\
+Executes a signed transaction against the latest ledger state without submitting it,
+e.g. to estimate the gas it uses before submitting it.
+
+The request body is the BCS bytes of the signed transaction, as for
+[POST /transactions](#operation/submit_transaction). The success response is the transaction as
+it would be committed at the next version; its hashes other than the transaction hash are
+placeholders. A transaction which would be discarded is rejected with 400.
+";
+
+const SUBMIT_TRANSACTION_BUNDLE_DESCRIPTION: &str = "\
+Submits transactions of one account with consecutive sequence numbers, which are committed
+together, in order, or not at all.
+
+The request body is the BCS bytes of the vector of signed transactions (see
+[POST /transactions](#operation/submit_transaction) for the BCS bytes of a signed transaction).
+The whole bundle is rejected with 400 if any of its transactions is invalid.
+";
+
+const CREATE_SIGNING_MESSAGE_DESCRIPTION: &str = "\
+This API creates transaction signing message for client to create
+transaction signature.
+
+The success response contains hex-encoded signing message bytes.
+
+**To sign the message**
+
+  1. Client first needs to HEX decode the `message` into bytes.
+  2. Then sign the bytes to create signature.
+";
+
+pub fn operations() -> Vec<Operation> {
+    vec![
+        Operation::get("/transactions", "get_transactions")
+            .summary("Get transactions")
+            .tag("transactions")
+            .param("StartVersion")
+            .param("Limit")
+            .response(
+                200,
+                "Returns on-chain transactions, paginated.",
+                Some(schema::<Vec<Transaction>>()),
+            )
+            .errors(&[400, 404, 500]),
+        Operation::post("/transactions", "submit_transaction")
+            .summary("Submit transaction")
+            .description(SUBMIT_TRANSACTION_DESCRIPTION)
+            .tag("transactions")
+            .request_body(
+                "User transaction request with transaction sender's signature.",
+                &[
+                    (JSON, schema::<UserTransactionRequest>()),
+                    (BCS_SIGNED_TRANSACTION, bcs_signed_transaction),
+                ],
+            )
+            .response(
+                202,
+                "Transaction is accepted and submitted to mempool.",
+                Some(schema::<PendingTransaction>()),
+            )
+            .errors(&[400, 413, 415, 500]),
+        Operation::post("/transactions/bundle", "submit_transaction_bundle")
+            .summary("Submit transaction bundle")
+            .description(SUBMIT_TRANSACTION_BUNDLE_DESCRIPTION)
+            .tag("transactions")
+            .request_body(
+                "BCS bytes of the signed transactions.",
+                &[(BCS_SIGNED_TRANSACTION, bcs_signed_transactions)],
+            )
+            .response(
+                202,
+                "Transaction bundle is accepted and submitted to mempool.",
+                Some(schema::<Vec<PendingTransaction>>()),
+            )
+            .errors(&[400, 413, 500]),
+        Operation::get(
+            "/accounts/{address}/transactions",
+            "get_account_transactions",
+        )
+        .summary("Get account transactions")
+        .tag("transactions")
+        .param("AccountAddress")
+        .param("StartVersion")
+        .param("Limit")
+        .response(
+            200,
+            "Returns on-chain transactions, paginated.",
+            Some(schema::<Vec<Transaction>>()),
+        )
+        .errors(&[400, 500]),
+        Operation::get("/transactions/{txn_hash_or_version}", "get_transaction")
+            .summary("Get transaction")
+            .description(GET_TRANSACTION_DESCRIPTION)
+            .tag("transactions")
+            .path_param(
+                "txn_hash_or_version",
+                "* Transaction hash should be hex-encoded bytes string with `0x` prefix.\n\
+                 * Transaction version is an `uint64` number.",
+                schema::<String>(),
+            )
+            .response(
+                200,
+                "Returns a pending / on-chain transaction.",
+                Some(schema::<Transaction>()),
+            )
+            .errors(&[400, 404, 500]),
+        Operation::post("/transactions/simulate", "simulate_transaction")
+            .summary("Simulate transaction")
+            .description(SIMULATE_TRANSACTION_DESCRIPTION)
+            .tag("transactions")
+            .request_body(
+                "BCS bytes of the signed transaction.",
+                &[(BCS_SIGNED_TRANSACTION, bcs_signed_transaction)],
+            )
+            .response(
+                200,
+                "Returns the simulated on-chain transaction.",
+                Some(schema::<Transaction>()),
+            )
+            .errors(&[400, 413, 500]),
+        Operation::post("/transactions/signing_message", "create_signing_message")
+            .summary("Create transaction signing message")
+            .description(CREATE_SIGNING_MESSAGE_DESCRIPTION)
+            .tag("transactions")
+            .request_body(
+                "User transaction request",
+                &[(JSON, schema::<UserTransactionRequest>())],
+            )
+            .response(
+                200,
+                "Returns hex-encoded transaction signing message bytes.",
+                Some(schema::<TransactionSigningMessage>()),
+            )
+            .errors(&[400, 404, 413, 415, 500]),
+    ]
+}
+
+fn bcs_signed_transaction(_: &mut SchemaGenerator) -> Value {
+    json!({
+        "type": "string",
+        "format": "binary",
+        "description": "BCS bytes of the [SignedTransaction](https://aptos-labs.github.io/aptos-core/aptos_types/transaction/struct.SignedTransaction.html).",
+    })
+}
+
+fn bcs_signed_transactions(_: &mut SchemaGenerator) -> Value {
+    json!({
+        "type": "string",
+        "format": "binary",
+        "description": "BCS bytes of the vector of [SignedTransaction](https://aptos-labs.github.io/aptos-core/aptos_types/transaction/struct.SignedTransaction.html).",
+    })
+}
+
 // GET /transactions/{txn-hash / version}
 pub fn get_transaction(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("transactions" / TransactionIdParam)
@@ -94,6 +291,37 @@ pub fn submit_bcs_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
         .boxed()
 }
 
+// POST /transactions/simulate with BCS
+pub fn simulate_bcs_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
+    // Only BCS is accepted, so the content-type is not checked: a body which isn't a BCS signed
+    // txn is rejected as an invalid request body.
+    warp::path!("transactions" / "simulate")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(
+            context.content_length_limit(),
+        ))
+        .and(warp::body::bytes())
+        .and(context.filter())
+        .and_then(handle_simulate_bcs_transactions)
+        .with(metrics("simulate_bcs_transactions"))
+        .boxed()
+}
+
+// POST /transactions/bundle with BCS
+pub fn submit_bcs_transaction_bundle(context: Context) -> BoxedFilter<(impl Reply,)> {
+    // As for simulations, only BCS is accepted, so the content-type is not checked.
+    warp::path!("transactions" / "bundle")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(
+            context.content_length_limit(),
+        ))
+        .and(warp::body::bytes())
+        .and(context.filter())
+        .and_then(handle_submit_bcs_transaction_bundle)
+        .with(metrics("submit_bcs_transaction_bundle"))
+        .boxed()
+}
+
 // POST /transactions/signing_message
 pub fn create_signing_message(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("transactions" / "signing_message")
@@ -152,6 +380,26 @@ async fn handle_submit_bcs_transactions(
     Ok(Transactions::new(context)?.create(txn).await?)
 }
 
+async fn handle_simulate_bcs_transactions(
+    body: bytes::Bytes,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_simulate_bcs_transactions")?;
+    let txn = bcs::from_bytes(&body)
+        .map_err(|err| Error::invalid_request_body(format!("deserialize error: {}", err)))?;
+    Ok(Transactions::new(context)?.simulate(txn).await?)
+}
+
+async fn handle_submit_bcs_transaction_bundle(
+    body: bytes::Bytes,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_submit_bcs_transaction_bundle")?;
+    let txns = bcs::from_bytes(&body)
+        .map_err(|err| Error::invalid_request_body(format!("deserialize error: {}", err)))?;
+    Ok(Transactions::new(context)?.create_bundle(txns).await?)
+}
+
 async fn handle_create_signing_message(
     body: UserTransactionRequest,
     context: Context,
@@ -200,6 +448,21 @@ impl Transactions {
                 let resp = Response::new(self.ledger_info, &pending_txn)?;
                 Ok(reply::with_status(resp, StatusCode::ACCEPTED))
             }
+            MempoolStatusCode::AlreadyCommitted => {
+                let hash = txn.committed_hash();
+                let version = self
+                    .context
+                    .get_transaction_by_hash(hash, self.ledger_info.version())?
+                    .map(|t| t.version);
+                Err(Error::bad_request(match version {
+                    Some(version) => format!(
+                        "transaction {} is already committed at version {}",
+                        hash.to_hex_literal(),
+                        version
+                    ),
+                    None => format!("transaction {} is already committed", hash.to_hex_literal()),
+                }))
+            }
             MempoolStatusCode::VmError => Err(Error::bad_request(format!(
                 "invalid transaction: {}",
                 vm_status_opt
@@ -213,6 +476,75 @@ impl Transactions {
         }
     }
 
+    pub async fn create_bundle(self, txns: Vec<SignedTransaction>) -> Result<impl Reply, Error> {
+        let (mempool_status, vm_status_opt) =
+            self.context.submit_transaction_bundle(txns.clone()).await?;
+        match mempool_status.code {
+            MempoolStatusCode::Accepted => {
+                let converter = self.context.move_converter();
+                let pending_txns = txns
+                    .into_iter()
+                    .map(|txn| converter.try_into_pending_transaction(txn))
+                    .collect::<Result<Vec<_>>>()?;
+                let resp = Response::new(self.ledger_info, &pending_txns)?;
+                Ok(reply::with_status(resp, StatusCode::ACCEPTED))
+            }
+            MempoolStatusCode::VmError => Err(Error::bad_request(format!(
+                "invalid transaction: {}",
+                vm_status_opt
+                    .map(|s| format!("{:?}", s))
+                    .unwrap_or_else(|| "UNKNOWN".to_owned())
+            ))),
+            _ => Err(Error::bad_request(format!(
+                "transaction bundle is rejected: {}",
+                mempool_status,
+            ))),
+        }
+    }
+
+    pub async fn simulate(self, txn: SignedTransaction) -> Result<impl Reply, Error> {
+        let ledger_version = self.ledger_info.version();
+        let output = self
+            .context
+            .simulate_transaction(txn.clone(), ledger_version)
+            .await?;
+        let status = match output.status() {
+            TransactionStatus::Keep(status) => status.clone(),
+            TransactionStatus::Discard(status) => {
+                return Err(Error::bad_request(format!(
+                    "invalid transaction: {:?}",
+                    status
+                )))
+            }
+            TransactionStatus::Retry => {
+                return Err(Error::bad_request(
+                    "transaction has to be retried".to_owned(),
+                ))
+            }
+        };
+
+        let info = TransactionInfo::new(
+            txn.clone().committed_hash(),
+            HashValue::zero(),
+            HashValue::zero(),
+            output.gas_used(),
+            status,
+        );
+        let data = TransactionOnChainData {
+            version: ledger_version + 1,
+            transaction: aptos_types::transaction::Transaction::UserTransaction(txn),
+            info,
+            events: output.events().to_vec(),
+            accumulator_root_hash: HashValue::zero(),
+        };
+        let timestamp = self.context.get_block_timestamp(ledger_version)?;
+        let txn = self
+            .context
+            .move_converter()
+            .try_into_onchain_transaction(timestamp, data)?;
+        Response::new(self.ledger_info, &txn)
+    }
+
     pub fn list(self, page: Page) -> Result<impl Reply, Error> {
         let ledger_version = self.ledger_info.version();
         let limit = page.limit()?;
@@ -4,9 +4,11 @@
 use crate::{HexEncodedBytes, U64};
 
 use aptos_types::account_config::AccountResource;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// The core account resource, identifying the account and used to execute its transactions.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct AccountData {
     pub sequence_number: U64,
     pub authentication_key: HexEncodedBytes,
@@ -2,11 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_types::account_address::AccountAddress;
+use schemars::JsonSchema;
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use std::{fmt, str::FromStr};
 
-#[derive(Clone, Debug, PartialEq, Copy)]
-pub struct Address(AccountAddress);
+/// An account address, hex-encoded and prefixed with `0x`, with the leading zeros trimmed, e.g.
+/// `"0xdd"`.
+#[derive(Clone, Debug, PartialEq, Copy, JsonSchema)]
+pub struct Address(#[schemars(with = "String")] AccountAddress);
 
 impl Address {
     pub fn inner(&self) -> &AccountAddress {
@@ -37,6 +37,8 @@ pub trait Bytecode {
 
     fn find_script_function(&self, name: &IdentStr) -> Option<MoveFunction>;
 
+    fn find_struct(&self, name: &IdentStr) -> Option<MoveStruct>;
+
     fn new_move_struct_field(&self, def: &FieldDefinition) -> MoveStructField {
         MoveStructField {
             name: self.identifier_at(def.name).to_owned(),
@@ -179,6 +181,16 @@ impl Bytecode for CompiledModule {
             })
             .map(|def| self.new_move_function(def))
     }
+
+    fn find_struct(&self, name: &IdentStr) -> Option<MoveStruct> {
+        self.struct_defs
+            .iter()
+            .find(|def| {
+                let handle = ModuleAccess::struct_handle_at(self, def.struct_handle);
+                ModuleAccess::identifier_at(self, handle.name) == name
+            })
+            .map(|def| self.new_move_struct(def))
+    }
 }
 
 impl Bytecode for CompiledScript {
@@ -213,4 +225,8 @@ impl Bytecode for CompiledScript {
             None
         }
     }
+
+    fn find_struct(&self, _name: &IdentStr) -> Option<MoveStruct> {
+        None
+    }
 }
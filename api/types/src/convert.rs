@@ -3,9 +3,10 @@
 
 use crate::{
     Bytecode, DirectWriteSet, Event, HexEncodedBytes, MoveFunction, MoveModuleBytecode,
-    MoveResource, MoveScriptBytecode, MoveType, MoveValue, ScriptFunctionId, ScriptFunctionPayload,
-    ScriptPayload, ScriptWriteSet, Transaction, TransactionInfo, TransactionOnChainData,
-    TransactionPayload, UserTransactionRequest, WriteSet, WriteSetChange, WriteSetPayload,
+    MoveResource, MoveScriptBytecode, MoveStructTag, MoveType, MoveValue, ScriptFunctionId,
+    ScriptFunctionPayload, ScriptPayload, ScriptWriteSet, Transaction, TransactionInfo,
+    TransactionOnChainData, TransactionPayload, UserTransactionRequest, WriteSet, WriteSetChange,
+    WriteSetPayload,
 };
 use aptos_crypto::HashValue;
 use aptos_transaction_builder::error_explain;
@@ -20,14 +21,15 @@ use aptos_types::{
 use move_binary_format::file_format::FunctionHandleIndex;
 use move_core_types::{
     identifier::Identifier,
-    language_storage::{ModuleId, StructTag},
+    language_storage::{ModuleId, StructTag, TypeTag},
     resolver::MoveResolver,
+    value::MoveStruct,
 };
-use move_resource_viewer::MoveValueAnnotator;
+use move_resource_viewer::{AnnotatedMoveValue, MoveValueAnnotator};
 
 use crate::transaction::{ModuleBundlePayload, StateCheckpointTransaction};
-use anyhow::{ensure, format_err, Result};
-use serde_json::Value;
+use anyhow::{bail, ensure, format_err, Result};
+use serde_json::{json, Map, Value};
 use std::{
     convert::{TryFrom, TryInto},
     rc::Rc,
@@ -64,6 +66,13 @@ impl<'a, R: MoveResolver + ?Sized> MoveConverter<'a, R> {
         self.inner.move_struct_fields(typ, bytes)
     }
 
+    /// Decodes the BCS bytes of a Move value into JSON, annotating every struct with its fully
+    /// instantiated type: `{"type": "0x1::Option::Option<u64>", "data": {"vec": ["1"]}}`.
+    /// `try_into_move_value` accepts the output as input.
+    pub fn try_into_annotated_json(&self, typ: &TypeTag, bytes: &[u8]) -> Result<Value> {
+        annotated_json(self.inner.view_value(typ, bytes)?)
+    }
+
     pub fn try_into_pending_transaction(&self, txn: SignedTransaction) -> Result<Transaction> {
         let payload = self.try_into_transaction_payload(txn.payload().clone())?;
         Ok((txn, payload).into())
@@ -408,8 +417,8 @@ impl<'a, R: MoveResolver + ?Sized> MoveConverter<'a, R> {
             MoveType::U128 => serde_json::from_value::<crate::U128>(val)?.into(),
             MoveType::Address => serde_json::from_value::<crate::Address>(val)?.into(),
             MoveType::Vector { items } => self.try_into_move_value_vector(&*items, val)?,
+            MoveType::Struct(s) => self.try_into_move_struct_value(s, val)?,
             MoveType::Signer
-            | MoveType::Struct(_)
             | MoveType::GenericTypeParam { index: _ }
             | MoveType::Reference { mutable: _, to: _ } => {
                 return Err(format_err!(
@@ -444,6 +453,99 @@ impl<'a, R: MoveResolver + ?Sized> MoveConverter<'a, R> {
         }
     }
 
+    /// Encodes JSON into a Move struct value. Besides an object of field values, it accepts the
+    /// annotated form returned by `try_into_annotated_json`, a string for `0x1::ASCII::String`,
+    /// and `null` or the element value for `0x1::Option::Option`.
+    pub fn try_into_move_struct_value(
+        &self,
+        typ: &MoveStructTag,
+        val: Value,
+    ) -> Result<move_core_types::value::MoveValue> {
+        use move_core_types::value::MoveValue::{Struct, Vector};
+
+        let struct_tag = StructTag::try_from(typ.clone())?;
+        let val = match val {
+            Value::Object(mut obj)
+                if obj.len() == 2
+                    && obj.contains_key("data")
+                    && obj.get("type") == Some(&Value::String(typ.to_string())) =>
+            {
+                obj.remove("data").unwrap()
+            }
+            val => val,
+        };
+
+        if MoveValue::is_ascii_string(&struct_tag) {
+            let s = serde_json::from_value::<String>(val)?;
+            ensure!(s.is_ascii(), "expected ASCII string, but got: {:?}", s);
+            return Ok(Struct(MoveStruct::new(vec![HexEncodedBytes::from(
+                s.into_bytes(),
+            )
+            .into()])));
+        }
+        if MoveValue::is_option(&struct_tag) {
+            let elem = typ
+                .generic_type_params
+                .first()
+                .ok_or_else(|| format_err!("missing type argument for {}", typ))?;
+            match val {
+                Value::Null => return Ok(Struct(MoveStruct::new(vec![Vector(vec![])]))),
+                // The `{"vec": [...]}` form returned by the API is decoded as a plain struct below
+                Value::Object(ref obj) if obj.len() == 1 && obj.contains_key("vec") => (),
+                val => {
+                    let value = self.try_into_move_value(elem, val)?;
+                    return Ok(Struct(MoveStruct::new(vec![Vector(vec![value])])));
+                }
+            }
+        }
+
+        let code = self.inner.get_module(&struct_tag.module_id())? as Rc<dyn Bytecode>;
+        let def = code
+            .find_struct(typ.name.as_ident_str())
+            .ok_or_else(|| format_err!("could not find struct by {}", typ))?;
+        ensure!(!def.is_native, "unexpected native struct {}", typ);
+        ensure!(
+            def.generic_type_params.len() == typ.generic_type_params.len(),
+            "expect {} type arguments for struct {}, but got {}",
+            def.generic_type_params.len(),
+            typ,
+            typ.generic_type_params.len()
+        );
+        let mut fields = match val {
+            Value::Object(obj) => obj,
+            val => bail!(
+                "expected {}, but got: {:?}",
+                MoveType::Struct(typ.clone()).json_type_name(),
+                val
+            ),
+        };
+        let values = def
+            .fields
+            .iter()
+            .map(|field| {
+                let val = fields
+                    .remove(field.name.as_str())
+                    .ok_or_else(|| format_err!("missing field {} of struct {}", field.name, typ))?;
+                let field_type = field.typ.instantiate(&typ.generic_type_params)?;
+                self.try_into_move_value(&field_type, val).map_err(|e| {
+                    format_err!(
+                        "parse field {} of struct {} failed, caused by error: {}",
+                        field.name,
+                        typ,
+                        e
+                    )
+                })
+            })
+            .collect::<Result<_>>()?;
+        ensure!(
+            fields.is_empty(),
+            "unknown fields {:?} of struct {}",
+            fields.keys().collect::<Vec<_>>(),
+            typ
+        );
+        Ok(Struct(MoveStruct::new(values)))
+    }
+
     fn explain_vm_status(&self, status: &KeptVMStatus) -> String {
         match status {
             KeptVMStatus::MoveAbort(location, abort_code) => match &location {
@@ -498,3 +600,24 @@ impl<'a, R: MoveResolver + ?Sized> MoveConverter<'a, R> {
         Ok(format!("{}", id))
     }
 }
+
+fn annotated_json(val: AnnotatedMoveValue) -> Result<Value> {
+    Ok(match val {
+        AnnotatedMoveValue::Vector(_, vals) => Value::Array(
+            vals.into_iter()
+                .map(annotated_json)
+                .collect::<Result<_>>()?,
+        ),
+        AnnotatedMoveValue::Struct(s) if !MoveValue::is_ascii_string(&s.type_) => {
+            let mut data = Map::new();
+            for (id, val) in s.value {
+                data.insert(id.to_string(), annotated_json(val)?);
+            }
+            json!({
+                "type": MoveStructTag::from(s.type_).to_string(),
+                "data": data,
+            })
+        }
+        val => MoveValue::try_from(val)?.json()?,
+    })
+}
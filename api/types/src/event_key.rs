@@ -1,11 +1,15 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use schemars::JsonSchema;
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use std::{fmt, str::FromStr};
 
-#[derive(Clone, Debug, PartialEq, Copy)]
-pub struct EventKey(aptos_types::event::EventKey);
+/// The global index of an event stream: the hex-encoded BCS bytes of the `guid` of its
+/// `EventHandle`, i.e. its `u64` creation number followed by the account address, without
+/// trimming the leading zeros.
+#[derive(Clone, Debug, PartialEq, Copy, JsonSchema)]
+pub struct EventKey(#[schemars(with = "String")] aptos_types::event::EventKey);
 
 impl From<aptos_types::event::EventKey> for EventKey {
     fn from(val: aptos_types::event::EventKey) -> Self {
@@ -1,11 +1,13 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use schemars::JsonSchema;
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use std::{fmt, str::FromStr};
 
-#[derive(Clone, Debug, PartialEq, Copy)]
-pub struct HashValue(aptos_crypto::hash::HashValue);
+/// A 32 bytes hash, hex-encoded and prefixed with `0x`.
+#[derive(Clone, Debug, PartialEq, Copy, JsonSchema)]
+pub struct HashValue(#[schemars(with = "String")] aptos_crypto::hash::HashValue);
 
 impl From<aptos_crypto::hash::HashValue> for HashValue {
     fn from(val: aptos_crypto::hash::HashValue) -> Self {
@@ -5,13 +5,16 @@ use crate::U64;
 
 use aptos_types::{chain_id::ChainId, ledger_info::LedgerInfoWithSignatures};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, JsonSchema)]
 pub struct LedgerInfo {
     pub chain_id: u8,
+    /// The epoch of the latest ledger info.
     pub epoch: u64,
     pub ledger_version: U64,
+    /// In microseconds.
     pub ledger_timestamp: U64,
 }
 
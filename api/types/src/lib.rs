@@ -11,6 +11,8 @@ mod hash;
 mod ledger_info;
 pub mod mime_types;
 mod move_types;
+mod node_info;
+mod proof;
 mod response;
 mod transaction;
 
@@ -27,13 +29,15 @@ pub use move_types::{
     MoveScriptBytecode, MoveStructTag, MoveStructValue, MoveType, MoveValue, ScriptFunctionId,
     U128, U64,
 };
+pub use node_info::NodeInfo;
+pub use proof::AccumulatorConsistencyProof;
 pub use response::{
     Response, X_APTOS_CHAIN_ID, X_APTOS_EPOCH, X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
 };
 pub use transaction::{
     BlockMetadataTransaction, DirectWriteSet, Event, GenesisTransaction, PendingTransaction,
-    ScriptFunctionPayload, ScriptPayload, ScriptWriteSet, Transaction, TransactionData,
-    TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
+    ScriptFunctionPayload, ScriptPayload, ScriptWriteSet, StateCheckpointTransaction, Transaction,
+    TransactionData, TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
     TransactionSigningMessage, UserTransaction, UserTransactionRequest, WriteSet, WriteSetChange,
     WriteSetPayload,
 };
@@ -20,6 +20,11 @@ use move_core_types::{
 };
 use move_resource_viewer::{AnnotatedMoveStruct, AnnotatedMoveValue};
 
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Metadata, Schema, SchemaObject},
+    JsonSchema,
+};
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     collections::BTreeMap,
@@ -29,7 +34,8 @@ use std::{
     str::FromStr,
 };
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// A Move struct value belonging to an account.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct MoveResource {
     #[serde(rename = "type")]
     pub typ: MoveStructTag,
@@ -47,8 +53,9 @@ impl TryFrom<AnnotatedMoveStruct> for MoveResource {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Copy)]
-pub struct U64(pub u64);
+/// An unsigned 64 bits integer, serialized as a string, e.g. `"32425224034"`.
+#[derive(Clone, Debug, PartialEq, Copy, JsonSchema)]
+pub struct U64(#[schemars(with = "String")] pub u64);
 
 impl U64 {
     pub fn inner(&self) -> &u64 {
@@ -114,8 +121,9 @@ impl<'de> Deserialize<'de> for U64 {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Copy)]
-pub struct U128(u128);
+/// An unsigned 128 bits integer, serialized as a string.
+#[derive(Clone, Debug, PartialEq, Copy, JsonSchema)]
+pub struct U128(#[schemars(with = "String")] u128);
 
 impl U128 {
     pub fn inner(&self) -> &u128 {
@@ -159,8 +167,10 @@ impl<'de> Deserialize<'de> for U128 {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub struct HexEncodedBytes(Vec<u8>);
+/// Bytes, hex-encoded with two digits per byte and prefixed with `0x`. Unlike addresses, the
+/// leading zeros aren't trimmed.
+#[derive(Clone, Debug, PartialEq, JsonSchema)]
+pub struct HexEncodedBytes(#[schemars(with = "String")] Vec<u8>);
 
 impl HexEncodedBytes {
     pub fn json(&self) -> anyhow::Result<serde_json::Value> {
@@ -245,8 +255,12 @@ impl HexEncodedBytes {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-pub struct MoveStructValue(BTreeMap<Identifier, serde_json::Value>);
+/// A Move struct value, serialized as an object with the names and values of its fields as
+/// properties.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MoveStructValue(
+    #[schemars(with = "BTreeMap<String, MoveValue>")] BTreeMap<Identifier, serde_json::Value>,
+);
 
 impl TryFrom<AnnotatedMoveStruct> for MoveStructValue {
     type Error = anyhow::Error;
@@ -272,6 +286,29 @@ pub enum MoveValue {
     String(String),
 }
 
+impl JsonSchema for MoveValue {
+    fn schema_name() -> String {
+        "MoveValue".to_owned()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                description: Some(
+                    "A Move value: `bool` is serialized as a boolean, `u8` as an integer, \
+                     `u64` and `u128` as strings, `address` as an address, `vector<u8>` as \
+                     hex-encoded bytes, other vectors as arrays, `0x1::ASCII::String` as a \
+                     string and other structs as objects of their fields."
+                        .to_owned(),
+                ),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
 impl MoveValue {
     pub fn json(&self) -> anyhow::Result<serde_json::Value> {
         Ok(serde_json::to_value(self)?)
@@ -283,6 +320,12 @@ impl MoveValue {
             && st.module.to_string() == "ASCII"
     }
 
+    pub fn is_option(st: &StructTag) -> bool {
+        st.address == CORE_CODE_ADDRESS
+            && st.name.to_string() == "Option"
+            && st.module.to_string() == "Option"
+    }
+
     pub fn convert_ascii_string(v: AnnotatedMoveStruct) -> anyhow::Result<MoveValue> {
         if let Some((_, AnnotatedMoveValue::Bytes(bytes))) = v.value.into_iter().next() {
             Ok(MoveValue::String(String::from_utf8(bytes)?))
@@ -372,6 +415,22 @@ impl MoveStructTag {
     }
 }
 
+impl JsonSchema for MoveStructTag {
+    fn schema_name() -> String {
+        "MoveStructTag".to_owned()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            "A Move struct type: its module address, module name and name joined by `::`, \
+             followed by its generic type parameters joined by `, ` within `<>`. Should be \
+             percent-encoded when used in a URL path.",
+            "0x1::AptosAccount::Balance<0x1::XUS::XUS>",
+        )
+        .into()
+    }
+}
+
 impl FromStr for MoveStructTag {
     type Err = anyhow::Error;
 
@@ -468,12 +527,44 @@ impl MoveType {
                     format!("array<{}>", items.json_type_name())
                 }
             }
-            MoveType::Struct(_) | MoveType::GenericTypeParam { index: _ } => {
-                "string<move_struct_tag_id>".to_owned()
-            }
+            MoveType::Struct(s) => match StructTag::try_from(s.clone()) {
+                Ok(tag) if MoveValue::is_ascii_string(&tag) => "string".to_owned(),
+                Ok(tag) if MoveValue::is_option(&tag) && s.generic_type_params.len() == 1 => {
+                    format!("{} | null", s.generic_type_params[0].json_type_name())
+                }
+                _ => format!("object<{}>", s),
+            },
+            MoveType::GenericTypeParam { index: _ } => "string<move_struct_tag_id>".to_owned(),
             MoveType::Reference { mutable: _, to } => to.json_type_name(),
         }
     }
+
+    /// Replaces generic type params with the given type arguments, e.g. `vector<T0>` with
+    /// `[u64]` becomes `vector<u64>`.
+    pub fn instantiate(&self, type_args: &[MoveType]) -> anyhow::Result<MoveType> {
+        Ok(match self {
+            MoveType::GenericTypeParam { index } => type_args
+                .get(*index as usize)
+                .cloned()
+                .ok_or_else(|| format_err!("missing type argument for {}", self))?,
+            MoveType::Vector { items } => MoveType::Vector {
+                items: Box::new(items.instantiate(type_args)?),
+            },
+            MoveType::Struct(s) => MoveType::Struct(MoveStructTag {
+                generic_type_params: s
+                    .generic_type_params
+                    .iter()
+                    .map(|t| t.instantiate(type_args))
+                    .collect::<anyhow::Result<_>>()?,
+                ..s.clone()
+            }),
+            MoveType::Reference { mutable, to } => MoveType::Reference {
+                mutable: *mutable,
+                to: Box::new(to.instantiate(type_args)?),
+            },
+            _ => self.clone(),
+        })
+    }
 }
 
 impl fmt::Display for MoveType {
@@ -499,6 +590,22 @@ impl fmt::Display for MoveType {
     }
 }
 
+impl JsonSchema for MoveType {
+    fn schema_name() -> String {
+        "MoveType".to_owned()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            "A Move type: `bool`, `u8`, `u64`, `u128`, `address`, `signer`, `vector<{type}>`, a \
+             struct type, a reference `&{type}` or `&mut {type}`, or a generic type parameter \
+             `T{index}` of the enclosing struct or function.",
+            "vector<0x1::AptosAccount::Balance<0x1::XUS::XUS>>",
+        )
+        .into()
+    }
+}
+
 // Implementation is imperfect, only parses type tags,
 // can't parse generic type params and references.
 impl FromStr for MoveType {
@@ -580,9 +687,11 @@ impl TryFrom<MoveType> for TypeTag {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// The binary interface of a Move module.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct MoveModule {
     pub address: Address,
+    #[schemars(with = "String")]
     pub name: Identifier,
     pub friends: Vec<MoveModuleId>,
     pub exposed_functions: Vec<MoveFunction>,
@@ -665,6 +774,20 @@ fn invalid_move_module_id(s: &str) -> anyhow::Error {
     format_err!("invalid Move module id: {}", s)
 }
 
+impl JsonSchema for MoveModuleId {
+    fn schema_name() -> String {
+        "MoveModuleId".to_owned()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            "A Move module: its address and case sensitive name joined by `::`.",
+            "0x1::Aptos",
+        )
+        .into()
+    }
+}
+
 impl Serialize for MoveModuleId {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         self.to_string().serialize(serializer)
@@ -681,8 +804,9 @@ impl<'de> Deserialize<'de> for MoveModuleId {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct MoveStruct {
+    #[schemars(with = "String")]
     pub name: Identifier,
     pub is_native: bool,
     pub abilities: Vec<MoveAbility>,
@@ -731,6 +855,26 @@ impl FromStr for MoveAbility {
     }
 }
 
+impl JsonSchema for MoveAbility {
+    fn schema_name() -> String {
+        "MoveAbility".to_owned()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        let mut schema = string_schema(
+            "An ability, controlling which actions are permissible for the values of a type.",
+            "key",
+        );
+        schema.enum_values = Some(
+            ["copy", "drop", "store", "key"]
+                .iter()
+                .map(|ability| serde_json::json!(ability))
+                .collect(),
+        );
+        schema.into()
+    }
+}
+
 impl Serialize for MoveAbility {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         self.to_string().serialize(serializer)
@@ -747,7 +891,7 @@ impl<'de> Deserialize<'de> for MoveAbility {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct MoveStructGenericTypeParam {
     pub constraints: Vec<MoveAbility>,
     pub is_phantom: bool,
@@ -766,15 +910,17 @@ impl From<&StructTypeParameter> for MoveStructGenericTypeParam {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct MoveStructField {
+    #[schemars(with = "String")]
     pub name: Identifier,
     #[serde(rename = "type")]
     pub typ: MoveType,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct MoveFunction {
+    #[schemars(with = "String")]
     pub name: Identifier,
     pub visibility: MoveFunctionVisibility,
     pub generic_type_params: Vec<MoveFunctionGenericTypeParam>,
@@ -804,7 +950,7 @@ impl From<&CompiledScript> for MoveFunction {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum MoveFunctionVisibility {
     Private,
@@ -835,7 +981,7 @@ impl From<MoveFunctionVisibility> for Visibility {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct MoveFunctionGenericTypeParam {
     pub constraints: Vec<MoveAbility>,
 }
@@ -848,7 +994,7 @@ impl From<&AbilitySet> for MoveFunctionGenericTypeParam {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct MoveModuleBytecode {
     pub bytecode: HexEncodedBytes,
     // We don't need deserialize MoveModule as it should be serialized
@@ -884,7 +1030,7 @@ impl From<Module> for MoveModuleBytecode {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct MoveScriptBytecode {
     pub bytecode: HexEncodedBytes,
     // We don't need deserialize MoveModule as it should be serialized
@@ -939,6 +1085,20 @@ fn invalid_script_function_id(s: &str) -> anyhow::Error {
     format_err!("invalid script function id {:?}", s)
 }
 
+impl JsonSchema for ScriptFunctionId {
+    fn schema_name() -> String {
+        "ScriptFunctionId".to_owned()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            "A script function: its module and case sensitive name joined by `::`.",
+            "0x1::PaymentScripts::peer_to_peer_with_metadata",
+        )
+        .into()
+    }
+}
+
 impl Serialize for ScriptFunctionId {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         self.to_string().serialize(serializer)
@@ -961,6 +1121,19 @@ impl fmt::Display for ScriptFunctionId {
     }
 }
 
+/// The schema of the types serialized as strings with their `Display` implementation.
+pub(crate) fn string_schema(description: &str, example: &str) -> SchemaObject {
+    SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        metadata: Some(Box::new(Metadata {
+            description: Some(description.to_owned()),
+            examples: vec![serde_json::json!(example)],
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -0,0 +1,22 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{HashValue, U64};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The network a node belongs to and the part of the ledger history it still has. Clients
+/// compare the chain id and genesis hash with those of the network they intend to use.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, JsonSchema)]
+pub struct NodeInfo {
+    pub chain_id: u8,
+    /// `None` if the genesis transaction has been pruned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub genesis_hash: Option<HashValue>,
+    pub node_role: String,
+    /// `None` if the ledger is empty.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oldest_ledger_version: Option<U64>,
+    pub build_version: String,
+}
@@ -0,0 +1,39 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{HashValue, U64};
+
+use aptos_types::proof::AccumulatorConsistencyProof as ConsistencyProof;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The subtrees of the transaction accumulator appended between `client_known_version`
+/// (pre-genesis if `None`) and `ledger_version`, see [`ConsistencyProof`].
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, JsonSchema)]
+pub struct AccumulatorConsistencyProof {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_known_version: Option<U64>,
+    pub ledger_version: U64,
+    pub subtrees: Vec<HashValue>,
+}
+
+impl AccumulatorConsistencyProof {
+    pub fn new(
+        client_known_version: Option<u64>,
+        ledger_version: u64,
+        proof: ConsistencyProof,
+    ) -> Self {
+        Self {
+            client_known_version: client_known_version.map(U64::from),
+            ledger_version: ledger_version.into(),
+            subtrees: proof.into_subtrees().into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<AccumulatorConsistencyProof> for ConsistencyProof {
+    fn from(proof: AccumulatorConsistencyProof) -> Self {
+        ConsistencyProof::new(proof.subtrees.into_iter().map(Into::into).collect())
+    }
+}
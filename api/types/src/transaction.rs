@@ -10,6 +10,7 @@ use anyhow::bail;
 use aptos_crypto::{
     ed25519::{self, Ed25519PublicKey},
     multi_ed25519::{self, MultiEd25519PublicKey},
+    secp256k1_ecdsa::{self, Secp256k1EcdsaPublicKey},
     validatable::Validatable,
 };
 use aptos_types::{
@@ -22,6 +23,7 @@ use aptos_types::{
     },
 };
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::{
     boxed::Box,
@@ -97,7 +99,9 @@ impl
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// A transaction, tagged by its `type`. Only the transactions submitted and not committed yet
+/// are pending.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Transaction {
     PendingTransaction(PendingTransaction),
@@ -234,26 +238,29 @@ impl From<(&SignedTransaction, TransactionPayload)> for UserTransactionRequest {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct TransactionInfo {
     pub version: U64,
     pub hash: HashValue,
     pub state_root_hash: HashValue,
     pub event_root_hash: HashValue,
     pub gas_used: U64,
+    /// Whether the transaction was executed successfully, see `vm_status` for the reason of a
+    /// failure.
     pub success: bool,
+    /// Human readable result of the execution of the transaction by the VM.
     pub vm_status: String,
     pub accumulator_root_hash: HashValue,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct PendingTransaction {
     pub hash: HashValue,
     #[serde(flatten)]
     pub request: UserTransactionRequest,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct UserTransaction {
     #[serde(flatten)]
     pub info: TransactionInfo,
@@ -263,14 +270,14 @@ pub struct UserTransaction {
     pub timestamp: U64,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct StateCheckpointTransaction {
     #[serde(flatten)]
     pub info: TransactionInfo,
     pub timestamp: U64,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct UserTransactionRequest {
     pub sender: Address,
     pub sequence_number: U64,
@@ -283,7 +290,7 @@ pub struct UserTransactionRequest {
     pub signature: Option<TransactionSignature>,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct GenesisTransaction {
     #[serde(flatten)]
     pub info: TransactionInfo,
@@ -291,7 +298,7 @@ pub struct GenesisTransaction {
     pub events: Vec<Event>,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct BlockMetadataTransaction {
     #[serde(flatten)]
     pub info: TransactionInfo,
@@ -302,12 +309,15 @@ pub struct BlockMetadataTransaction {
     pub timestamp: U64,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// An event, identified by its `key` and `sequence_number`. The `data` is decoded with its
+/// `type`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Event {
     pub key: EventKey,
     pub sequence_number: U64,
     #[serde(rename = "type")]
     pub typ: MoveType,
+    #[schemars(with = "MoveValue")]
     pub data: serde_json::Value,
 }
 
@@ -324,13 +334,13 @@ impl From<(&ContractEvent, serde_json::Value)> for Event {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum GenesisPayload {
     WriteSetPayload(WriteSetPayload),
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TransactionPayload {
     ScriptFunctionPayload(ScriptFunctionPayload),
@@ -339,22 +349,24 @@ pub enum TransactionPayload {
     WriteSetPayload(WriteSetPayload),
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ModuleBundlePayload {
     pub modules: Vec<MoveModuleBytecode>,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ScriptFunctionPayload {
     pub function: ScriptFunctionId,
     pub type_arguments: Vec<MoveType>,
+    #[schemars(with = "Vec<MoveValue>")]
     pub arguments: Vec<serde_json::Value>,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ScriptPayload {
     pub code: MoveScriptBytecode,
     pub type_arguments: Vec<MoveType>,
+    #[schemars(with = "Vec<MoveValue>")]
     pub arguments: Vec<serde_json::Value>,
 }
 
@@ -374,31 +386,31 @@ impl TryFrom<Script> for ScriptPayload {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct WriteSetPayload {
     pub write_set: WriteSet,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum WriteSet {
     ScriptWriteSet(ScriptWriteSet),
     DirectWriteSet(DirectWriteSet),
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ScriptWriteSet {
     pub execute_as: Address,
     pub script: ScriptPayload,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct DirectWriteSet {
     pub changes: Vec<WriteSetChange>,
     pub events: Vec<Event>,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum WriteSetChange {
     DeleteModule {
@@ -419,12 +431,13 @@ pub enum WriteSetChange {
     },
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TransactionSignature {
     Ed25519Signature(Ed25519Signature),
     MultiEd25519Signature(MultiEd25519Signature),
     MultiAgentSignature(MultiAgentSignature),
+    Secp256k1EcdsaSignature(Secp256k1EcdsaSignature),
 }
 
 impl TryFrom<TransactionSignature> for TransactionAuthenticator {
@@ -434,11 +447,12 @@ impl TryFrom<TransactionSignature> for TransactionAuthenticator {
             TransactionSignature::Ed25519Signature(sig) => sig.try_into()?,
             TransactionSignature::MultiEd25519Signature(sig) => sig.try_into()?,
             TransactionSignature::MultiAgentSignature(sig) => sig.try_into()?,
+            TransactionSignature::Secp256k1EcdsaSignature(sig) => sig.try_into()?,
         })
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Ed25519Signature {
     public_key: HexEncodedBytes,
     signature: HexEncodedBytes,
@@ -474,7 +488,7 @@ impl TryFrom<Ed25519Signature> for AccountAuthenticator {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct MultiEd25519Signature {
     public_keys: Vec<HexEncodedBytes>,
     signatures: Vec<HexEncodedBytes>,
@@ -542,11 +556,48 @@ impl TryFrom<MultiEd25519Signature> for AccountAuthenticator {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Secp256k1EcdsaSignature {
+    public_key: HexEncodedBytes,
+    signature: HexEncodedBytes,
+}
+
+impl TryFrom<Secp256k1EcdsaSignature> for TransactionAuthenticator {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Secp256k1EcdsaSignature) -> Result<Self, Self::Error> {
+        let Secp256k1EcdsaSignature {
+            public_key,
+            signature,
+        } = value;
+        Ok(TransactionAuthenticator::secp256k1_ecdsa(
+            public_key.inner().try_into()?,
+            signature.inner().try_into()?,
+        ))
+    }
+}
+
+impl TryFrom<Secp256k1EcdsaSignature> for AccountAuthenticator {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Secp256k1EcdsaSignature) -> Result<Self, Self::Error> {
+        let Secp256k1EcdsaSignature {
+            public_key,
+            signature,
+        } = value;
+        Ok(AccountAuthenticator::secp256k1_ecdsa(
+            public_key.inner().try_into()?,
+            signature.inner().try_into()?,
+        ))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum AccountSignature {
     Ed25519Signature(Ed25519Signature),
     MultiEd25519Signature(MultiEd25519Signature),
+    Secp256k1EcdsaSignature(Secp256k1EcdsaSignature),
 }
 
 impl TryFrom<AccountSignature> for AccountAuthenticator {
@@ -556,11 +607,12 @@ impl TryFrom<AccountSignature> for AccountAuthenticator {
         Ok(match sig {
             AccountSignature::Ed25519Signature(s) => s.try_into()?,
             AccountSignature::MultiEd25519Signature(s) => s.try_into()?,
+            AccountSignature::Secp256k1EcdsaSignature(s) => s.try_into()?,
         })
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct MultiAgentSignature {
     sender: AccountSignature,
     secondary_signer_addresses: Vec<Address>,
@@ -608,6 +660,25 @@ impl From<(&Ed25519PublicKey, &ed25519::Ed25519Signature)> for Ed25519Signature
     }
 }
 
+impl
+    From<(
+        &Secp256k1EcdsaPublicKey,
+        &secp256k1_ecdsa::Secp256k1EcdsaSignature,
+    )> for Secp256k1EcdsaSignature
+{
+    fn from(
+        (pk, sig): (
+            &Secp256k1EcdsaPublicKey,
+            &secp256k1_ecdsa::Secp256k1EcdsaSignature,
+        ),
+    ) -> Self {
+        Self {
+            public_key: pk.to_bytes().to_vec().into(),
+            signature: sig.to_bytes().to_vec().into(),
+        }
+    }
+}
+
 impl
     From<(
         &MultiEd25519PublicKey,
@@ -649,6 +720,10 @@ impl From<&AccountAuthenticator> for AccountSignature {
                 public_key,
                 signature,
             } => Self::MultiEd25519Signature((public_key, signature).into()),
+            Secp256k1Ecdsa {
+                public_key,
+                signature,
+            } => Self::Secp256k1EcdsaSignature((public_key, signature).into()),
         }
     }
 }
@@ -694,6 +769,10 @@ impl From<TransactionAuthenticator> for TransactionSignature {
             } => Self::MultiAgentSignature(
                 (sender, secondary_signer_addresses, secondary_signers).into(),
             ),
+            Secp256k1Ecdsa {
+                public_key,
+                signature,
+            } => Self::Secp256k1EcdsaSignature((public_key, signature).into()),
         }
     }
 }
@@ -728,7 +807,7 @@ impl fmt::Display for TransactionId {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct TransactionSigningMessage {
     pub message: HexEncodedBytes,
 }
@@ -25,6 +25,7 @@ impl DBDebuggerInterface {
             NO_OP_STORAGE_PRUNER_CONFIG,
             RocksdbConfig::default(),
             true, /* account_count_migration, ignored anyway */
+            true, /* state_value_index, ignored anyway */
         )?)))
     }
 }
@@ -1,7 +1,10 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{counters::*, data_cache::StateViewCache};
+use crate::{
+    counters::*, data_cache::StateViewCache,
+    transaction_arg_validation::validate_script_function_args,
+};
 use anyhow::Result;
 use aptos_state_view::StateView;
 use aptos_types::{
@@ -74,6 +77,7 @@ pub trait VMAdapter {
 /// 1. Check the signature(s) included in the signed transaction
 /// 2. Check that the transaction is allowed in the context provided by the `adapter`
 /// 3. Run the prologue to perform additional on-chain checks
+/// 4. Check the arguments of a script function against its parameters
 /// The returned `VMValidatorResult` will have status `None` and if all checks succeeded
 /// and `Some(DiscardedVMStatus)` otherwise.
 pub fn validate_signed_transaction<A: VMAdapter>(
@@ -100,7 +104,14 @@ pub fn validate_signed_transaction<A: VMAdapter>(
     };
 
     let validation_result =
-        validate_signature_checked_transaction(adapter, &mut session, &txn, true, &log_context);
+        validate_signature_checked_transaction(adapter, &mut session, &txn, true, &log_context)
+            .and_then(|()| match txn.payload() {
+                // Only checked at submission, as executing blocks must not depend on it
+                TransactionPayload::ScriptFunction(script_fn) => {
+                    validate_script_function_args(&remote_cache, script_fn)
+                }
+                _ => Ok(()),
+            });
 
     let (status, gas_price) = match (status, validation_result) {
         (Some(_), _) => (status, 0),
@@ -8,11 +8,12 @@ use crate::{
         validate_signed_transaction, PreprocessedTransaction, VMAdapter,
     },
     aptos_vm_impl::{
-        charge_global_write_gas_usage, convert_changeset_and_events, get_currency_info,
-        get_gas_currency_code, get_transaction_output, AptosVMImpl, AptosVMInternals,
+        charge_global_write_gas_usage, charge_storage_fee, convert_changeset_and_events,
+        get_currency_info, get_gas_currency_code, get_transaction_output, squash_write_sets,
+        AptosVMImpl, AptosVMInternals,
     },
     counters::*,
-    data_cache::{RemoteStorage, StateViewCache},
+    data_cache::{RemoteStorage, StateViewCache, WriteSetResolver},
     errors::expect_only_successful_execution,
     logging::AdapterLogSchema,
     script_to_script_function,
@@ -27,8 +28,8 @@ use aptos_types::{
     account_config,
     block_metadata::BlockMetadata,
     on_chain_config::{
-        OnChainConfig, ParallelExecutionConfig, VMConfig, VMPublishingOption, Version,
-        DIEM_VERSION_2, DIEM_VERSION_3,
+        FeatureFlag, OnChainConfig, ParallelExecutionConfig, StorageGasSchedule, VMConfig,
+        VMPublishingOption, Version, DIEM_VERSION_2, DIEM_VERSION_3,
     },
     transaction::{
         ChangeSet, ModuleBundle, SignatureCheckedTransaction, SignedTransaction, Transaction,
@@ -52,6 +53,7 @@ use move_core_types::{
 use move_vm_runtime::session::Session;
 use move_vm_types::gas_schedule::GasStatus;
 use std::{
+    cmp::min,
     collections::HashSet,
     convert::{AsMut, AsRef},
 };
@@ -162,16 +164,30 @@ impl AptosVM {
 
     fn success_transaction_cleanup<S: MoveResolver>(
         &self,
+        storage: &S,
         mut session: Session<S>,
         gas_status: &mut GasStatus,
         txn_data: &TransactionMetadata,
         account_currency_symbol: &IdentStr,
         log_context: &AdapterLogSchema,
     ) -> Result<(VMStatus, TransactionOutput), VMStatus> {
+        if let Some(storage_gas_schedule) = self.0.storage_gas_schedule() {
+            return self.success_transaction_cleanup_with_storage_fee(
+                storage,
+                session,
+                storage_gas_schedule,
+                gas_status,
+                txn_data,
+                account_currency_symbol,
+                log_context,
+            );
+        }
+
         gas_status.set_metering(false);
         self.0.run_success_epilogue(
             &mut session,
             gas_status,
+            0, /* storage_refund */
             txn_data,
             account_currency_symbol,
             log_context,
@@ -189,8 +205,60 @@ impl AptosVM {
         ))
     }
 
+    /// Charges the storage fee of the writes of the transaction, then runs the epilogue in a new
+    /// session on top of them, so that the sender pays for the fee.
+    fn success_transaction_cleanup_with_storage_fee<S: MoveResolver>(
+        &self,
+        storage: &S,
+        session: Session<S>,
+        storage_gas_schedule: &StorageGasSchedule,
+        gas_status: &mut GasStatus,
+        txn_data: &TransactionMetadata,
+        account_currency_symbol: &IdentStr,
+        log_context: &AdapterLogSchema,
+    ) -> Result<(VMStatus, TransactionOutput), VMStatus> {
+        let (changeset, events) = session.finish().map_err(|e| e.into_vm_status())?;
+        let (write_set, mut events) = convert_changeset_and_events(changeset, events)?;
+        let storage_refund =
+            charge_storage_fee(gas_status, storage_gas_schedule, &write_set, storage)?;
+
+        gas_status.set_metering(false);
+        let resolver = WriteSetResolver::new(storage, &write_set);
+        let mut session = self.0.new_session(&resolver);
+        self.0.run_success_epilogue(
+            &mut session,
+            gas_status,
+            storage_refund,
+            txn_data,
+            account_currency_symbol,
+            log_context,
+        )?;
+        let (epilogue_changeset, epilogue_events) =
+            session.finish().map_err(|e| e.into_vm_status())?;
+        let (epilogue_write_set, epilogue_events) =
+            convert_changeset_and_events(epilogue_changeset, epilogue_events)?;
+        events.extend(epilogue_events);
+
+        // The refund is capped by the gas used, like in the epilogue.
+        let gas_used = txn_data
+            .max_gas_amount()
+            .sub(gas_status.remaining_gas())
+            .get();
+        let storage_refund = min(storage_refund, gas_used);
+        Ok((
+            VMStatus::Executed,
+            TransactionOutput::new(
+                squash_write_sets(write_set, epilogue_write_set)?,
+                events,
+                gas_used - storage_refund,
+                TransactionStatus::Keep(KeptVMStatus::Executed),
+            ),
+        ))
+    }
+
     fn execute_script_or_script_function<S: MoveResolver>(
         &self,
+        storage: &S,
         mut session: Session<S>,
         gas_status: &mut GasStatus,
         txn_data: &TransactionMetadata,
@@ -267,6 +335,7 @@ impl AptosVM {
             charge_global_write_gas_usage(gas_status, &session, &txn_data.sender())?;
 
             self.success_transaction_cleanup(
+                storage,
                 session,
                 gas_status,
                 txn_data,
@@ -278,6 +347,7 @@ impl AptosVM {
 
     fn execute_modules<S: MoveResolver>(
         &self,
+        storage: &S,
         mut session: Session<S>,
         gas_status: &mut GasStatus,
         txn_data: &TransactionMetadata,
@@ -302,13 +372,24 @@ impl AptosVM {
             .charge_intrinsic_gas(txn_data.transaction_size())
             .map_err(|e| e.into_vm_status())?;
 
+        // The metadata sections were checked by the prologue and are not published
+        let codes = if self.0.is_feature_enabled(FeatureFlag::ModuleMetadata) {
+            modules
+                .iter()
+                .map(|module| module.split_metadata().map(|(code, _)| code.to_vec()))
+                .collect::<Result<Vec<_>>>()
+                .map_err(|_| VMStatus::Error(StatusCode::CODE_DESERIALIZATION_ERROR))?
+        } else {
+            modules.clone().into_inner()
+        };
         session
-            .publish_module_bundle(modules.clone().into_inner(), module_address, gas_status)
+            .publish_module_bundle(codes, module_address, gas_status)
             .map_err(|e| e.into_vm_status())?;
 
         charge_global_write_gas_usage(gas_status, &session, &txn_data.sender())?;
 
         self.success_transaction_cleanup(
+            storage,
             session,
             gas_status,
             txn_data,
@@ -365,6 +446,7 @@ impl AptosVM {
             payload @ TransactionPayload::Script(_)
             | payload @ TransactionPayload::ScriptFunction(_) => self
                 .execute_script_or_script_function(
+                    storage,
                     session,
                     &mut gas_status,
                     &txn_data,
@@ -373,6 +455,7 @@ impl AptosVM {
                     log_context,
                 ),
             TransactionPayload::ModuleBundle(m) => self.execute_modules(
+                storage,
                 session,
                 &mut gas_status,
                 &txn_data,
@@ -757,9 +840,9 @@ impl VMValidator for AptosVM {
     /// 2. The script to be executed is under given specific configuration.
     /// 3. Invokes `Account.prologue`, which checks properties such as the transaction has the
     /// right sequence number and the sender has enough balance to pay for the gas.
-    /// TBD:
-    /// 1. Transaction arguments matches the main function's type signature.
-    ///    We don't check this item for now and would execute the check at execution time.
+    /// 4. The arguments of a script function match the parameters of the function, see
+    ///    `transaction_arg_validation`. The arguments of scripts are only checked at execution
+    ///    time.
     fn validate_transaction(
         &self,
         transaction: SignedTransaction,
@@ -837,8 +920,9 @@ impl VMAdapter for AptosVM {
                 self.0
                     .run_script_prologue(session, &txn_data, &currency_code, log_context)
             }
-            TransactionPayload::ModuleBundle(_module) => {
+            TransactionPayload::ModuleBundle(modules) => {
                 self.0.check_gas(&txn_data, log_context)?;
+                self.0.check_module_metadata(modules, log_context)?;
                 self.0
                     .run_module_prologue(session, &txn_data, &currency_code, log_context)
             }
@@ -14,14 +14,16 @@ use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
 use aptos_state_view::StateView;
 use aptos_types::{
+    access_path::Path,
     account_config,
     account_config::{ChainSpecificAccountInfo, CurrencyInfoResource},
     contract_event::ContractEvent,
     event::EventKey,
     on_chain_config::{
-        ConfigStorage, OnChainConfig, VMConfig, VMPublishingOption, Version, DIEM_VERSION_3,
+        ConfigStorage, FeatureFlag, Features, OnChainConfig, StorageGasSchedule, VMConfig,
+        VMPublishingOption, Version, DIEM_VERSION_3,
     },
-    transaction::{SignedTransaction, TransactionOutput, TransactionStatus},
+    transaction::{ModuleBundle, SignedTransaction, TransactionOutput, TransactionStatus},
     vm_status::{KeptVMStatus, StatusCode, VMStatus},
     write_set::{WriteOp, WriteSet, WriteSetMut},
 };
@@ -48,6 +50,8 @@ pub struct AptosVMImpl {
     on_chain_config: Option<VMConfig>,
     version: Option<Version>,
     publishing_option: Option<VMPublishingOption>,
+    storage_gas_schedule: Option<StorageGasSchedule>,
+    features: Option<Features>,
     chain_account_info: Option<ChainSpecificAccountInfo>,
 }
 
@@ -61,6 +65,8 @@ impl AptosVMImpl {
             on_chain_config: None,
             version: None,
             publishing_option: None,
+            storage_gas_schedule: None,
+            features: None,
             chain_account_info: None,
         };
         vm.load_configs_impl(&RemoteStorage::new(state));
@@ -80,6 +86,8 @@ impl AptosVMImpl {
             on_chain_config: Some(on_chain_config),
             version: Some(version),
             publishing_option: Some(publishing_option),
+            storage_gas_schedule: None,
+            features: None,
             chain_account_info: None,
         }
     }
@@ -113,6 +121,8 @@ impl AptosVMImpl {
         self.on_chain_config = VMConfig::fetch_config(data_cache);
         self.version = Version::fetch_config(data_cache);
         self.publishing_option = VMPublishingOption::fetch_config(data_cache);
+        self.storage_gas_schedule = StorageGasSchedule::fetch_config(data_cache);
+        self.features = Features::fetch_config(data_cache);
     }
 
     // TODO: Move this to an on-chain config once those are a part of the core framework
@@ -150,6 +160,59 @@ impl AptosVMImpl {
         })
     }
 
+    /// The storage fees, only charged if they are configured on chain.
+    pub(crate) fn storage_gas_schedule(&self) -> Option<&StorageGasSchedule> {
+        self.storage_gas_schedule.as_ref()
+    }
+
+    /// Whether the feature is enabled on chain, gating behaviors introduced after genesis. No
+    /// feature is enabled if the config isn't published.
+    pub fn is_feature_enabled(&self, feature: impl Into<u64>) -> bool {
+        self.features
+            .as_ref()
+            .map_or(false, |features| features.is_enabled(feature))
+    }
+
+    /// Checks the metadata sections of the modules to publish, rejecting the bundle if a module
+    /// requires a protocol flag, or a runtime feature which isn't enabled on chain. Metadata
+    /// sections are only recognized once `FeatureFlag::ModuleMetadata` is enabled.
+    pub(crate) fn check_module_metadata(
+        &self,
+        modules: &ModuleBundle,
+        log_context: &AdapterLogSchema,
+    ) -> Result<(), VMStatus> {
+        if !self.is_feature_enabled(FeatureFlag::ModuleMetadata) {
+            return Ok(());
+        }
+        for module in modules.iter() {
+            let (_, metadata) = module.split_metadata().map_err(|err| {
+                warn!(*log_context, "[VM] Malformed module metadata: {}", err);
+                VMStatus::Error(StatusCode::CODE_DESERIALIZATION_ERROR)
+            })?;
+            let required_features = metadata
+                .map(|metadata| metadata.required_features)
+                .unwrap_or_default();
+            for feature in required_features {
+                if !Features::is_runtime_feature(feature) {
+                    warn!(
+                        *log_context,
+                        "[VM] Module requires protocol flag {}, which is not a runtime feature",
+                        feature
+                    );
+                    return Err(VMStatus::Error(StatusCode::CODE_DESERIALIZATION_ERROR));
+                }
+                if !self.is_feature_enabled(feature) {
+                    warn!(
+                        *log_context,
+                        "[VM] Module requires feature {} which is not enabled", feature
+                    );
+                    return Err(VMStatus::Error(StatusCode::FEATURE_UNDER_GATING));
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn check_gas(
         &self,
         txn_data: &TransactionMetadata,
@@ -351,11 +414,13 @@ impl AptosVMImpl {
     }
 
     /// Run the epilogue of a transaction by calling into `EPILOGUE_NAME` function stored
-    /// in the `ACCOUNT_MODULE` on chain.
+    /// in the `ACCOUNT_MODULE` on chain. The `storage_refund` is given back to the sender,
+    /// up to the gas used.
     pub(crate) fn run_success_epilogue<S: MoveResolver>(
         &self,
         session: &mut Session<S>,
         gas_status: &mut GasStatus,
+        storage_refund: u64,
         txn_data: &TransactionMetadata,
         account_currency_symbol: &IdentStr,
         log_context: &AdapterLogSchema,
@@ -371,7 +436,11 @@ impl AptosVMImpl {
         let txn_sequence_number = txn_data.sequence_number();
         let txn_gas_price = txn_data.gas_unit_price().get();
         let txn_max_gas_units = txn_data.max_gas_amount().get();
-        let gas_remaining = gas_status.remaining_gas().get();
+        let gas_remaining = gas_status
+            .remaining_gas()
+            .get()
+            .saturating_add(storage_refund)
+            .min(txn_max_gas_units);
         session
             .execute_function(
                 &chain_specific_info.module_id(),
@@ -623,6 +692,70 @@ pub(crate) fn charge_global_write_gas_usage<R: MoveResolver>(
         .map_err(|p_err| p_err.finish(Location::Undefined).into_vm_status())
 }
 
+/// Charges the storage fee of the writes of a transaction, made on top of `storage`: every slot
+/// created and every byte written are charged, the slots deleted are refunded. Returns the
+/// refund, in gas units.
+pub(crate) fn charge_storage_fee<R: MoveResolver>(
+    gas_status: &mut GasStatus,
+    storage_gas_schedule: &StorageGasSchedule,
+    write_set: &WriteSet,
+    storage: &R,
+) -> Result<u64, VMStatus> {
+    let mut charge: u64 = 0;
+    let mut refund: u64 = 0;
+    for (access_path, write_op) in write_set {
+        let exists = match access_path.get_path() {
+            Path::Code(module_id) => storage.get_module(&module_id),
+            Path::Resource(struct_tag) => storage.get_resource(&access_path.address, &struct_tag),
+        }
+        .map_err(|_| VMStatus::Error(StatusCode::STORAGE_ERROR))?
+        .is_some();
+        match write_op {
+            WriteOp::Value(blob) => {
+                if !exists {
+                    charge = charge.saturating_add(storage_gas_schedule.per_slot_create);
+                }
+                charge = charge.saturating_add(
+                    storage_gas_schedule
+                        .per_byte_write
+                        .saturating_mul(blob.len() as u64),
+                );
+            }
+            WriteOp::Deletion => {
+                if exists {
+                    refund = refund.saturating_add(storage_gas_schedule.per_slot_refund);
+                }
+            }
+        }
+    }
+    gas_status
+        .deduct_gas(InternalGasUnits::new(charge))
+        .map_err(|p_err| p_err.finish(Location::Undefined).into_vm_status())?;
+    Ok(gas_status
+        .cost_table()
+        .gas_constants
+        .to_external_units(InternalGasUnits::new(refund))
+        .get())
+}
+
+/// Merges the writes of two consecutive sessions of a transaction, the writes of the later one
+/// overriding the ones of the earlier one to the same access paths.
+pub(crate) fn squash_write_sets(
+    write_set: WriteSet,
+    later: WriteSet,
+) -> Result<WriteSet, VMStatus> {
+    let mut ops = write_set.into_iter().collect::<Vec<_>>();
+    for (access_path, write_op) in later {
+        match ops.iter_mut().find(|(ap, _)| *ap == access_path) {
+            Some((_, op)) => *op = write_op,
+            None => ops.push((access_path, write_op)),
+        }
+    }
+    WriteSetMut::new(ops)
+        .freeze()
+        .map_err(|_| VMStatus::Error(StatusCode::DATA_FORMAT_ERROR))
+}
+
 pub(crate) fn get_transaction_output<A: AccessPathCache, S: MoveResolver>(
     ap_cache: &mut A,
     session: Session<S>,
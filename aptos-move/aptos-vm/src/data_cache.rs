@@ -18,7 +18,7 @@ use move_binary_format::errors::*;
 use move_core_types::{
     account_address::AccountAddress,
     language_storage::{ModuleId, StructTag},
-    resolver::{ModuleResolver, ResourceResolver},
+    resolver::{ModuleResolver, MoveResolver, ResourceResolver},
 };
 use std::collections::btree_map::BTreeMap;
 
@@ -176,3 +176,53 @@ impl<'a, S: StateView> ConfigStorage for RemoteStorage<'a, S> {
         self.get(&access_path).ok()?
     }
 }
+
+/// A view of the storage with the writes of a session applied on top, for the sessions which
+/// continue the execution of a transaction after the previous one has been finished, e.g. to
+/// charge the storage fee of the transaction before running its epilogue.
+pub(crate) struct WriteSetResolver<'a, S> {
+    base: &'a S,
+    writes: BTreeMap<&'a AccessPath, &'a WriteOp>,
+}
+
+impl<'a, S: MoveResolver> WriteSetResolver<'a, S> {
+    pub fn new(base: &'a S, write_set: &'a WriteSet) -> Self {
+        Self {
+            base,
+            writes: write_set.iter().map(|(ap, op)| (ap, op)).collect(),
+        }
+    }
+
+    fn get_written(&self, access_path: &AccessPath) -> Option<Option<Vec<u8>>> {
+        self.writes.get(access_path).map(|write_op| match write_op {
+            WriteOp::Value(blob) => Some(blob.clone()),
+            WriteOp::Deletion => None,
+        })
+    }
+}
+
+impl<'a, S: MoveResolver> ModuleResolver for WriteSetResolver<'a, S> {
+    type Error = S::Err;
+
+    fn get_module(&self, module_id: &ModuleId) -> Result<Option<Vec<u8>>, Self::Error> {
+        match self.get_written(&AccessPath::from(module_id)) {
+            Some(blob) => Ok(blob),
+            None => self.base.get_module(module_id),
+        }
+    }
+}
+
+impl<'a, S: MoveResolver> ResourceResolver for WriteSetResolver<'a, S> {
+    type Error = S::Err;
+
+    fn get_resource(
+        &self,
+        address: &AccountAddress,
+        struct_tag: &StructTag,
+    ) -> Result<Option<Vec<u8>>, Self::Error> {
+        match self.get_written(&create_access_path(*address, struct_tag.clone())) {
+            Some(blob) => Ok(blob),
+            None => self.base.get_resource(address, struct_tag),
+        }
+    }
+}
@@ -120,6 +120,7 @@ pub mod parallel_executor;
 pub mod read_write_set_analysis;
 pub mod script_to_script_function;
 pub mod system_module_names;
+mod transaction_arg_validation;
 pub mod transaction_metadata;
 
 #[cfg(test)]
@@ -65,6 +65,31 @@ impl ParallelAptosVM {
     pub fn execute_block<S: StateView>(
         transactions: Vec<Transaction>,
         state_view: &S,
+    ) -> Result<(Vec<TransactionOutput>, Option<Error<VMStatus>>), VMStatus> {
+        Self::execute_block_with_executor(
+            transactions,
+            state_view,
+            ParallelTransactionExecutor::new(),
+        )
+    }
+
+    /// Executes the block with `concurrency_level` worker tasks rather than one per CPU.
+    pub fn execute_block_with_concurrency_level<S: StateView>(
+        transactions: Vec<Transaction>,
+        state_view: &S,
+        concurrency_level: usize,
+    ) -> Result<(Vec<TransactionOutput>, Option<Error<VMStatus>>), VMStatus> {
+        Self::execute_block_with_executor(
+            transactions,
+            state_view,
+            ParallelTransactionExecutor::with_concurrency_level(concurrency_level),
+        )
+    }
+
+    fn execute_block_with_executor<'a, S: StateView>(
+        transactions: Vec<Transaction>,
+        state_view: &'a S,
+        executor: ParallelTransactionExecutor<PreprocessedTransaction, DiemVMWrapper<'a, S>>,
     ) -> Result<(Vec<TransactionOutput>, Option<Error<VMStatus>>), VMStatus> {
         // Verify the signatures of all the transactions in parallel.
         // This is time consuming so don't wait and do the checking
@@ -74,9 +99,7 @@ impl ParallelAptosVM {
             .map(|txn| preprocess_transaction::<AptosVM>(txn.clone()))
             .collect();
 
-        match ParallelTransactionExecutor::<PreprocessedTransaction, DiemVMWrapper<S>>::new()
-            .execute_transactions_parallel(state_view, signature_verified_block)
-        {
+        match executor.execute_transactions_parallel(state_view, signature_verified_block) {
             Ok(results) => Ok((
                 results
                     .into_iter()
@@ -0,0 +1,208 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Validation of the arguments of script functions against the parameters of the functions,
+//! done with the other checks of the transactions, before the functions are executed. A
+//! transaction whose arguments don't match is discarded, instead of aborting in the middle of
+//! its execution.
+//!
+//! The arguments are the BCS serialized values of the parameters following the signers:
+//! * primitive types and addresses;
+//! * vectors of arguments, nested at most `MAX_VECTOR_DEPTH` times;
+//! * the structs of `ALLOWED_STRUCTS`, built from the BCS of their fields and validated like
+//!   their constructors would, e.g. `0x1::ASCII::String`.
+//!
+//! The parameters of generic types are left to the VM.
+
+use aptos_types::{
+    account_address::AccountAddress, account_config::CORE_CODE_ADDRESS, transaction::ScriptFunction,
+};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{SignatureToken, Visibility},
+    CompiledModule,
+};
+use move_core_types::{
+    resolver::{ModuleResolver, MoveResolver},
+    vm_status::{StatusCode, VMStatus},
+};
+
+/// The max number of vectors nested in the type of an argument.
+pub(crate) const MAX_VECTOR_DEPTH: usize = 2;
+
+type StructValidator = fn(&mut &[u8]) -> Result<(), StatusCode>;
+
+/// The structs the arguments may be, by address, module and name, with the validation of their
+/// value.
+const ALLOWED_STRUCTS: &[(AccountAddress, &str, &str, StructValidator)] =
+    &[(CORE_CODE_ADDRESS, "ASCII", "String", validate_ascii_string)];
+
+pub(crate) fn validate_script_function_args<S: MoveResolver>(
+    storage: &S,
+    script_fn: &ScriptFunction,
+) -> Result<(), VMStatus> {
+    let module_bytes = storage
+        .get_module(script_fn.module())
+        .map_err(|_| VMStatus::Error(StatusCode::STORAGE_ERROR))?
+        .ok_or(VMStatus::Error(StatusCode::LINKER_ERROR))?;
+    let module = CompiledModule::deserialize(&module_bytes)
+        .map_err(|_| VMStatus::Error(StatusCode::CODE_DESERIALIZATION_ERROR))?;
+    let handle = module
+        .function_defs()
+        .iter()
+        .filter(|def| matches!(def.visibility, Visibility::Script))
+        .map(|def| module.function_handle_at(def.function))
+        .find(|handle| module.identifier_at(handle.name) == script_fn.function())
+        .ok_or(VMStatus::Error(StatusCode::FUNCTION_RESOLUTION_FAILURE))?;
+
+    // The signers are passed by the VM, not as arguments.
+    let params = module
+        .signature_at(handle.parameters)
+        .0
+        .iter()
+        .skip_while(|token| is_signer(token))
+        .collect::<Vec<_>>();
+    if params.len() != script_fn.args().len() {
+        return Err(VMStatus::Error(StatusCode::NUMBER_OF_ARGUMENTS_MISMATCH));
+    }
+    params
+        .into_iter()
+        .zip(script_fn.args())
+        .try_for_each(|(token, arg)| validate_arg(&module, token, arg))
+        .map_err(VMStatus::Error)
+}
+
+fn is_signer(token: &SignatureToken) -> bool {
+    match token {
+        SignatureToken::Signer => true,
+        SignatureToken::Reference(token) => token.as_ref() == &SignatureToken::Signer,
+        _ => false,
+    }
+}
+
+fn is_generic(token: &SignatureToken) -> bool {
+    match token {
+        SignatureToken::TypeParameter(_) => true,
+        SignatureToken::Vector(token) => is_generic(token),
+        _ => false,
+    }
+}
+
+/// Validates the type of the parameter, then the argument against it.
+pub(crate) fn validate_arg(
+    module: &CompiledModule,
+    token: &SignatureToken,
+    mut arg: &[u8],
+) -> Result<(), StatusCode> {
+    if is_generic(token) {
+        return Ok(());
+    }
+    check_param_type(module, token, 0)?;
+    validate_value(module, token, &mut arg)?;
+    if !arg.is_empty() {
+        return Err(StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT);
+    }
+    Ok(())
+}
+
+fn check_param_type(
+    module: &CompiledModule,
+    token: &SignatureToken,
+    vector_depth: usize,
+) -> Result<(), StatusCode> {
+    match token {
+        SignatureToken::Bool
+        | SignatureToken::U8
+        | SignatureToken::U64
+        | SignatureToken::U128
+        | SignatureToken::Address => Ok(()),
+        SignatureToken::Vector(token) if vector_depth < MAX_VECTOR_DEPTH => {
+            check_param_type(module, token, vector_depth + 1)
+        }
+        SignatureToken::Struct(_) if allowed_struct(module, token).is_some() => Ok(()),
+        _ => Err(StatusCode::INVALID_MAIN_FUNCTION_SIGNATURE),
+    }
+}
+
+/// The validator of the struct if the token is one of the `ALLOWED_STRUCTS`.
+fn allowed_struct(module: &CompiledModule, token: &SignatureToken) -> Option<StructValidator> {
+    let idx = match token {
+        SignatureToken::Struct(idx) => *idx,
+        _ => return None,
+    };
+    let struct_handle = module.struct_handle_at(idx);
+    let module_handle = module.module_handle_at(struct_handle.module);
+    let address = module.address_identifier_at(module_handle.address);
+    let module_name = module.identifier_at(module_handle.name).as_str();
+    let name = module.identifier_at(struct_handle.name).as_str();
+    ALLOWED_STRUCTS
+        .iter()
+        .find(|(a, m, n, _)| a == address && *m == module_name && *n == name)
+        .map(|(_, _, _, validator)| *validator)
+}
+
+/// Consumes the BCS of a value of the type from the front of `bytes`.
+fn validate_value(
+    module: &CompiledModule,
+    token: &SignatureToken,
+    bytes: &mut &[u8],
+) -> Result<(), StatusCode> {
+    match token {
+        SignatureToken::Bool => match take(bytes, 1)? {
+            [0] | [1] => Ok(()),
+            _ => Err(StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT),
+        },
+        SignatureToken::U8 => take(bytes, 1).map(|_| ()),
+        SignatureToken::U64 => take(bytes, 8).map(|_| ()),
+        SignatureToken::U128 => take(bytes, 16).map(|_| ()),
+        SignatureToken::Address => take(bytes, AccountAddress::LENGTH).map(|_| ()),
+        SignatureToken::Vector(token) => {
+            let len = take_uleb128_len(bytes)?;
+            if token.as_ref() == &SignatureToken::U8 {
+                return take(bytes, len).map(|_| ());
+            }
+            (0..len).try_for_each(|_| validate_value(module, token, bytes))
+        }
+        SignatureToken::Struct(_) => match allowed_struct(module, token) {
+            Some(validator) => validator(bytes),
+            None => Err(StatusCode::INVALID_MAIN_FUNCTION_SIGNATURE),
+        },
+        _ => Err(StatusCode::INVALID_MAIN_FUNCTION_SIGNATURE),
+    }
+}
+
+/// `0x1::ASCII::String`, a vector of ASCII characters.
+fn validate_ascii_string(bytes: &mut &[u8]) -> Result<(), StatusCode> {
+    let len = take_uleb128_len(bytes)?;
+    if take(bytes, len)?.iter().all(u8::is_ascii) {
+        Ok(())
+    } else {
+        Err(StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT)
+    }
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], StatusCode> {
+    if bytes.len() < len {
+        return Err(StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT);
+    }
+    let (taken, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(taken)
+}
+
+/// The length of a BCS sequence, a ULEB128 encoded u32.
+fn take_uleb128_len(bytes: &mut &[u8]) -> Result<usize, StatusCode> {
+    let mut len = 0u64;
+    for shift in (0..32).step_by(7) {
+        let byte = take(bytes, 1)?[0];
+        len |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            // BCS requires the shortest encoding.
+            if (shift > 0 && byte == 0) || len > u64::from(u32::MAX) {
+                return Err(StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT);
+            }
+            return Ok(len as usize);
+        }
+    }
+    Err(StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT)
+}
@@ -2,3 +2,4 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod script_to_script_function_tests;
+mod transaction_arg_validation_tests;
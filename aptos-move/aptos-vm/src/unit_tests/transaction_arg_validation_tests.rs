@@ -0,0 +1,147 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::transaction_arg_validation::validate_arg;
+use aptos_types::{account_address::AccountAddress, vm_status::StatusCode};
+use diem_framework_releases::current_modules;
+use move_binary_format::{access::ModuleAccess, file_format::SignatureToken, CompiledModule};
+
+fn ascii_module() -> &'static CompiledModule {
+    current_modules()
+        .iter()
+        .find(|module| module.self_id().name().as_str() == "ASCII")
+        .unwrap()
+}
+
+fn ascii_string_token(module: &CompiledModule) -> SignatureToken {
+    module
+        .signatures()
+        .iter()
+        .flat_map(|signature| signature.0.iter())
+        .find(|token| match token {
+            SignatureToken::Struct(idx) => {
+                module
+                    .identifier_at(module.struct_handle_at(*idx).name)
+                    .as_str()
+                    == "String"
+            }
+            _ => false,
+        })
+        .unwrap()
+        .clone()
+}
+
+fn vector(token: SignatureToken) -> SignatureToken {
+    SignatureToken::Vector(Box::new(token))
+}
+
+#[test]
+fn test_validate_primitive_args() {
+    let module = ascii_module();
+    let validate = |token, arg: Vec<u8>| validate_arg(module, &token, &arg);
+
+    assert_eq!(validate(SignatureToken::Bool, vec![1]), Ok(()));
+    assert_eq!(
+        validate(SignatureToken::Bool, vec![2]),
+        Err(StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT)
+    );
+    assert_eq!(
+        validate(SignatureToken::U64, bcs::to_bytes(&7u64).unwrap()),
+        Ok(())
+    );
+    assert_eq!(
+        validate(SignatureToken::U64, bcs::to_bytes(&7u32).unwrap()),
+        Err(StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT)
+    );
+    assert_eq!(
+        validate(SignatureToken::U8, vec![1, 2]),
+        Err(StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT)
+    );
+    assert_eq!(
+        validate(
+            SignatureToken::Address,
+            bcs::to_bytes(&AccountAddress::random()).unwrap()
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        validate(SignatureToken::Signer, vec![]),
+        Err(StatusCode::INVALID_MAIN_FUNCTION_SIGNATURE)
+    );
+}
+
+#[test]
+fn test_validate_vector_args() {
+    let module = ascii_module();
+    let validate = |token, arg: Vec<u8>| validate_arg(module, &token, &arg);
+
+    let bytes = vec![vec![1u8, 2], vec![], vec![3]];
+    assert_eq!(
+        validate(
+            vector(vector(SignatureToken::U8)),
+            bcs::to_bytes(&bytes).unwrap()
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        validate(vector(SignatureToken::U128), bcs::to_bytes(&bytes).unwrap()),
+        Err(StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT)
+    );
+    // The length doesn't match the content.
+    assert_eq!(
+        validate(vector(SignatureToken::U8), vec![3, 1, 2]),
+        Err(StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT)
+    );
+    // The length isn't in its shortest encoding.
+    assert_eq!(
+        validate(vector(SignatureToken::U8), vec![0x81, 0]),
+        Err(StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT)
+    );
+    assert_eq!(
+        validate(
+            vector(vector(vector(SignatureToken::U8))),
+            bcs::to_bytes(&vec![bytes]).unwrap()
+        ),
+        Err(StatusCode::INVALID_MAIN_FUNCTION_SIGNATURE)
+    );
+    // The generic parameters are left to the VM.
+    assert_eq!(
+        validate(vector(SignatureToken::TypeParameter(0)), vec![]),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_validate_struct_args() {
+    let module = ascii_module();
+    let string = ascii_string_token(module);
+
+    assert_eq!(
+        validate_arg(module, &string, &bcs::to_bytes("hello").unwrap()),
+        Ok(())
+    );
+    assert_eq!(
+        validate_arg(
+            module,
+            &vector(string.clone()),
+            &bcs::to_bytes(&vec!["hello", "world"]).unwrap()
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        validate_arg(module, &string, &bcs::to_bytes("héllo").unwrap()),
+        Err(StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT)
+    );
+
+    // Other structs aren't allowed.
+    let char = module
+        .signatures()
+        .iter()
+        .flat_map(|signature| signature.0.iter())
+        .find(|token| matches!(token, SignatureToken::Struct(_)) && **token != string)
+        .unwrap();
+    assert_eq!(
+        validate_arg(module, char, &[b'a']),
+        Err(StatusCode::INVALID_MAIN_FUNCTION_SIGNATURE)
+    );
+}
@@ -2,10 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_types::{
+    access_path::AccessPath,
     account_config::{self},
-    on_chain_config::VMPublishingOption,
-    transaction::TransactionStatus,
+    on_chain_config::{
+        access_path_for_config, FeatureFlag, Features, OnChainConfig, VMPublishingOption,
+        FIRST_RUNTIME_FEATURE,
+    },
+    transaction::{Module, ModuleMetadata, TransactionStatus},
     vm_status::{KeptVMStatus, StatusCode},
+    write_set::{WriteOp, WriteSetMut},
 };
 use language_e2e_tests::{
     account::Account, assert_prologue_parity, compile::compile_module, current_function_name,
@@ -450,3 +455,95 @@ pub fn test_publishing_allow_modules() {
         &TransactionStatus::Keep(KeptVMStatus::Executed)
     );
 }
+
+#[test]
+pub fn test_publishing_modules_requiring_features() {
+    let mut executor = FakeExecutor::from_genesis_with_options(VMPublishingOption::open());
+
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    executor.add_account_data(&sender);
+    let set_features = |executor: &mut FakeExecutor, features: &[u64]| {
+        executor.apply_write_set(
+            &WriteSetMut::new(vec![(
+                access_path_for_config(Features::CONFIG_ID),
+                WriteOp::Value(bcs::to_bytes(&Features::new(features)).unwrap()),
+            )])
+            .freeze()
+            .unwrap(),
+        )
+    };
+    let module_metadata: u64 = FeatureFlag::ModuleMetadata.into();
+    set_features(&mut executor, &[module_metadata]);
+
+    let program = format!(
+        "
+        module 0x{}.M {{
+        }}
+        ",
+        sender.address(),
+    );
+    let (compiled_module, module) = compile_module(&program);
+    let code = module.into_inner();
+
+    // A module whose metadata section doesn't deserialize
+    let mut malformed = code.clone();
+    malformed.extend_from_slice(b"MDAT");
+    let txn = sender
+        .account()
+        .transaction()
+        .module(Module::new(malformed))
+        .sequence_number(10)
+        .sign();
+    assert_prologue_parity!(
+        executor.verify_transaction(txn.clone()).status(),
+        executor.execute_transaction(txn).status(),
+        StatusCode::CODE_DESERIALIZATION_ERROR
+    );
+
+    // A module requiring a protocol flag, even an enabled one
+    let metadata = ModuleMetadata {
+        compiler_version: 1,
+        required_features: vec![module_metadata],
+    };
+    let txn = sender
+        .account()
+        .transaction()
+        .module(Module::new_with_metadata(code.clone(), &metadata))
+        .sequence_number(10)
+        .sign();
+    assert_prologue_parity!(
+        executor.verify_transaction(txn.clone()).status(),
+        executor.execute_transaction(txn).status(),
+        StatusCode::CODE_DESERIALIZATION_ERROR
+    );
+
+    let runtime_feature = FIRST_RUNTIME_FEATURE + 3;
+    let metadata = ModuleMetadata {
+        compiler_version: 1,
+        required_features: vec![runtime_feature],
+    };
+    let txn = sender
+        .account()
+        .transaction()
+        .module(Module::new_with_metadata(code.clone(), &metadata))
+        .sequence_number(10)
+        .sign();
+    assert_prologue_parity!(
+        executor.verify_transaction(txn.clone()).status(),
+        executor.execute_transaction(txn.clone()).status(),
+        StatusCode::FEATURE_UNDER_GATING
+    );
+
+    set_features(&mut executor, &[module_metadata, runtime_feature]);
+    assert_eq!(executor.verify_transaction(txn.clone()).status(), None);
+    assert_eq!(
+        executor.execute_and_apply(txn).status(),
+        &TransactionStatus::Keep(KeptVMStatus::Executed)
+    );
+
+    // The metadata section isn't published with the module
+    assert_eq!(
+        executor.read_from_access_path(&AccessPath::code_access_path(compiled_module.self_id())),
+        Some(code)
+    );
+}
@@ -4,6 +4,7 @@
 use aptos_crypto::{
     ed25519::Ed25519PrivateKey,
     multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+    secp256k1_ecdsa::Secp256k1EcdsaPrivateKey,
     PrivateKey, SigningKey, Uniform,
 };
 use aptos_keygen::KeyGen;
@@ -17,6 +18,7 @@ use language_e2e_tests::{
     test_with_different_versions,
     versioning::CURRENT_RELEASE_VERSIONS,
 };
+use std::convert::TryFrom;
 
 #[test]
 fn rotate_ed25519_key() {
@@ -135,6 +137,73 @@ fn rotate_ed25519_multisig_key() {
     }
 }
 
+#[test]
+fn rotate_secp256k1_ecdsa_key() {
+    test_with_different_versions! {CURRENT_RELEASE_VERSIONS, |test_env| {
+        let mut executor = test_env.executor;
+
+        let mut seq_number = 10;
+        // create and publish sender
+        let sender = executor.create_raw_account_data(1_000_000, seq_number);
+        executor.add_account_data(&sender);
+
+        let privkey = Secp256k1EcdsaPrivateKey::generate_for_testing();
+        let pubkey = privkey.public_key();
+        let new_auth_key = AuthenticationKey::secp256k1_ecdsa(&pubkey);
+
+        // (1) rotate key to secp256k1
+        let output = &executor.execute_transaction(rotate_key_txn(
+            sender.account(),
+            new_auth_key.to_vec(),
+            seq_number,
+        ));
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Keep(KeptVMStatus::Executed),
+        );
+        executor.apply_write_set(output.write_set());
+        seq_number += 1;
+
+        // (2) send a tx signed by the secp256k1 key
+        let txn = raw_rotate_key_txn(sender.account(), new_auth_key.to_vec(), seq_number);
+        let signed_txn = txn
+            .sign_secp256k1_ecdsa(&privkey, pubkey.clone())
+            .unwrap()
+            .into_inner();
+        let output = &executor.execute_transaction(signed_txn);
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Keep(KeptVMStatus::Executed),
+        );
+        executor.apply_write_set(output.write_set());
+        seq_number += 1;
+
+        // (3) a tx signed by another secp256k1 key doesn't match the authentication key
+        let other_privkey = Secp256k1EcdsaPrivateKey::try_from(&[7u8; 32][..]).unwrap();
+        let txn = raw_rotate_key_txn(sender.account(), new_auth_key.to_vec(), seq_number);
+        let signed_txn = txn
+            .sign_secp256k1_ecdsa(&other_privkey, other_privkey.public_key())
+            .unwrap()
+            .into_inner();
+        let output = &executor.execute_transaction(signed_txn);
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Discard(StatusCode::INVALID_AUTH_KEY),
+        );
+
+        // (4) a tx with a signature from the wrong key is rejected
+        let txn = raw_rotate_key_txn(sender.account(), new_auth_key.to_vec(), seq_number);
+        let signature = other_privkey.sign(&txn);
+        let signed_txn = SignedTransaction::new_secp256k1_ecdsa(txn, pubkey, signature);
+        let output = &executor.execute_transaction(signed_txn);
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Discard(StatusCode::INVALID_SIGNATURE),
+        );
+    }
+    }
+}
+
 #[test]
 
 fn rotate_shared_ed25519_public_key() {}
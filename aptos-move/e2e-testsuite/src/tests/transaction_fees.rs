@@ -5,16 +5,24 @@ use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
 use aptos_transaction_builder::stdlib::*;
 use aptos_types::{
     account_config::{self, BurnEvent, XUS_NAME},
-    transaction::{authenticator::AuthenticationKey, Script, TransactionArgument},
+    on_chain_config::{access_path_for_config, OnChainConfig, StorageGasSchedule},
+    transaction::{
+        authenticator::AuthenticationKey, Script, TransactionArgument, TransactionOutput,
+        TransactionStatus,
+    },
     vm_status::KeptVMStatus,
+    write_set::{WriteOp, WriteSetMut},
 };
 use diem_framework_releases::legacy::transaction_scripts::LegacyStdlibScript;
-use language_e2e_tests::{test_with_different_versions, versioning::CURRENT_RELEASE_VERSIONS};
+use language_e2e_tests::{
+    common_transactions::peer_to_peer_txn, executor::FakeExecutor, test_with_different_versions,
+    versioning::CURRENT_RELEASE_VERSIONS,
+};
 use move_core_types::{
     identifier::Identifier,
     language_storage::{StructTag, TypeTag},
 };
-use std::convert::TryFrom;
+use std::{collections::BTreeSet, convert::TryFrom};
 
 #[test]
 fn burn_txn_fees() {
@@ -108,3 +116,49 @@ fn burn_txn_fees() {
     }
     }
 }
+
+#[test]
+fn charge_storage_fee() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = executor.create_raw_account_data(1_000_000, 10);
+    let receiver = executor.create_raw_account_data(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+
+    let output = executor.execute_transaction(txn.clone());
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(KeptVMStatus::Executed)
+    );
+
+    let storage_gas_schedule = StorageGasSchedule {
+        per_slot_create: 0,
+        per_byte_write: 1_000,
+        per_slot_refund: 0,
+    };
+    executor.apply_write_set(
+        &WriteSetMut::new(vec![(
+            access_path_for_config(StorageGasSchedule::CONFIG_ID),
+            WriteOp::Value(bcs::to_bytes(&storage_gas_schedule).unwrap()),
+        )])
+        .freeze()
+        .unwrap(),
+    );
+    let output_with_fee = executor.execute_transaction(txn);
+    assert_eq!(
+        output_with_fee.status(),
+        &TransactionStatus::Keep(KeptVMStatus::Executed)
+    );
+    assert!(output_with_fee.gas_used() > output.gas_used());
+
+    // The epilogue runs in a session of its own, writing to the same slots.
+    let access_paths = |output: &TransactionOutput| {
+        output
+            .write_set()
+            .iter()
+            .map(|(access_path, _)| access_path.clone())
+            .collect::<BTreeSet<_>>()
+    };
+    assert_eq!(access_paths(&output), access_paths(&output_with_fee));
+}
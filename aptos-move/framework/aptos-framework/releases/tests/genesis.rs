@@ -0,0 +1,74 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Builds a genesis from the current release, as nodes do, to catch a release which wasn't
+//! regenerated after changing the framework: the tests compiling the framework from its sources
+//! wouldn't notice.
+
+use aptos_framework_releases::current_module_blobs;
+use aptos_types::{
+    access_path::AccessPath,
+    account_config::CORE_CODE_ADDRESS,
+    on_chain_config::{BlockGasLimit, ConfigStorage, Features, OnChainConfig, VMPublishingOption},
+    write_set::WriteOp,
+};
+use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use std::collections::HashMap;
+
+/// The values written by the genesis.
+struct GenesisWrites(HashMap<AccessPath, Vec<u8>>);
+
+impl GenesisWrites {
+    fn new() -> Self {
+        let (change_set, _) = vm_genesis::generate_test_genesis(
+            current_module_blobs(),
+            VMPublishingOption::open(),
+            Some(1),
+            false,
+        );
+        Self(
+            change_set
+                .write_set()
+                .iter()
+                .filter_map(|(access_path, op)| match op {
+                    WriteOp::Value(bytes) => Some((access_path.clone(), bytes.clone())),
+                    WriteOp::Deletion => None,
+                })
+                .collect(),
+        )
+    }
+}
+
+impl GenesisWrites {
+    fn has_module(&self, name: &str) -> bool {
+        let id = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new(name).unwrap());
+        self.0.contains_key(&AccessPath::code_access_path(id))
+    }
+}
+
+impl ConfigStorage for GenesisWrites {
+    fn fetch_config(&self, access_path: AccessPath) -> Option<Vec<u8>> {
+        self.0.get(&access_path).cloned()
+    }
+}
+
+#[test]
+fn test_genesis_publishes_configs() {
+    let writes = GenesisWrites::new();
+    assert_eq!(Features::fetch_config(&writes), Some(Features::default()));
+    // The gas of the blocks isn't limited until the limit is published
+    assert_eq!(BlockGasLimit::fetch_config(&writes), None);
+}
+
+#[test]
+fn test_genesis_publishes_modules() {
+    let writes = GenesisWrites::new();
+    // Recording the metadata of the packages published by the CLI
+    assert!(writes.has_module("PackageRegistry"));
+    // Setting the storage fees, which aren't charged until the schedule is published
+    assert!(writes.has_module("StorageGasSchedule"));
+    assert!(writes.has_module("AptosStorageGasSchedule"));
+    // Limiting the gas of the blocks, which isn't limited until the limit is published
+    assert!(writes.has_module("BlockGasLimit"));
+    assert!(writes.has_module("AptosBlockGasLimit"));
+}
@@ -8,7 +8,10 @@ module AptosFramework::Genesis {
     // Config imports
     use CoreFramework::ValidatorConfig;
     use CoreFramework::ValidatorOperatorConfig;
+    use AptosFramework::AptosBlockGasLimit;
     use AptosFramework::AptosConsensusConfig;
+    use AptosFramework::AptosFeatures;
+    use AptosFramework::AptosStorageGasSchedule;
     use AptosFramework::AptosTransactionPublishingOption;
     use AptosFramework::AptosValidatorConfig;
     use AptosFramework::AptosValidatorOperatorConfig;
@@ -83,6 +86,9 @@ module AptosFramework::Genesis {
         AptosValidatorConfig::initialize(core_resource_account);
         AptosValidatorOperatorConfig::initialize(core_resource_account);
         AptosTransactionPublishingOption::initialize(core_resource_account, initial_script_allow_list, is_open_module);
+        AptosFeatures::initialize(core_resource_account);
+        AptosStorageGasSchedule::initialize(core_resource_account);
+        AptosBlockGasLimit::initialize(core_resource_account);
 
         TestCoin::initialize(core_resource_account, 1000000);
         TestCoin::mint_internal(core_resource_account, Signer::address_of(core_resource_account), 18446744073709551615);
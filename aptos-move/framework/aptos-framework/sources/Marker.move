@@ -4,7 +4,10 @@ module AptosFramework::Marker {
     use CoreFramework::SystemAddresses;
 
     friend AptosFramework::AptosAccount;
+    friend AptosFramework::AptosBlockGasLimit;
     friend AptosFramework::AptosConsensusConfig;
+    friend AptosFramework::AptosFeatures;
+    friend AptosFramework::AptosStorageGasSchedule;
     friend AptosFramework::AptosTransactionPublishingOption;
     friend AptosFramework::AptosValidatorConfig;
     friend AptosFramework::AptosValidatorOperatorConfig;
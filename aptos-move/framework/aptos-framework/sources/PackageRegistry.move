@@ -0,0 +1,56 @@
+/// The `PackageRegistry` module keeps the metadata of the Move packages published by an account,
+/// so the package a module comes from, and the sources it was built from, can be found on-chain.
+module AptosFramework::PackageRegistry {
+    use Std::Errors;
+    use Std::Signer;
+    use Std::Vector;
+
+    /// The module names and the bytecode hashes of a package don't match
+    const EMODULES_MISMATCH: u64 = 0;
+
+    /// The metadata of a package published by an account
+    struct PackageMetadata has store, drop {
+        name: vector<u8>,
+        /// The digest of the sources the package was built from, empty if unknown
+        source_digest: vector<u8>,
+        modules: vector<vector<u8>>,
+        /// SHA3-256 of the bytecode of each module, in the order of `modules`
+        bytecode_hashes: vector<vector<u8>>,
+    }
+
+    /// The packages published by an account, with at most one entry per package name
+    struct PackageRegistry has key {
+        packages: vector<PackageMetadata>,
+    }
+
+    /// Records the metadata of a package published by `account`, replacing the metadata of the
+    /// previous publication of a package of the same name.
+    public(script) fun publish_package_metadata(
+        account: signer,
+        name: vector<u8>,
+        source_digest: vector<u8>,
+        modules: vector<vector<u8>>,
+        bytecode_hashes: vector<vector<u8>>,
+    ) acquires PackageRegistry {
+        assert!(
+            Vector::length(&modules) == Vector::length(&bytecode_hashes),
+            Errors::invalid_argument(EMODULES_MISMATCH)
+        );
+        let addr = Signer::address_of(&account);
+        if (!exists<PackageRegistry>(addr)) {
+            move_to(&account, PackageRegistry { packages: Vector::empty() });
+        };
+        let packages = &mut borrow_global_mut<PackageRegistry>(addr).packages;
+        let metadata = PackageMetadata { name, source_digest, modules, bytecode_hashes };
+        let i = 0;
+        let len = Vector::length(packages);
+        while (i < len) {
+            if (&Vector::borrow(packages, i).name == &metadata.name) {
+                *Vector::borrow_mut(packages, i) = metadata;
+                return
+            };
+            i = i + 1;
+        };
+        Vector::push_back(packages, metadata);
+    }
+}
@@ -0,0 +1,24 @@
+module AptosFramework::AptosBlockGasLimit {
+    use Std::Capability;
+    use CoreFramework::BlockGasLimit;
+    use AptosFramework::Marker::{Self, ChainMarker};
+
+    /// Publishes the chain marker of the BlockGasLimit config, without limiting the gas of blocks.
+    public fun initialize(core_resource_account: &signer) {
+        BlockGasLimit::initialize<ChainMarker>(core_resource_account);
+    }
+
+    /// Starts limiting the gas of the blocks, or updates the limit, from the next block.
+    public fun set(account: &signer, max_gas_per_block: u64) {
+        BlockGasLimit::set(
+            account,
+            max_gas_per_block,
+            &Capability::acquire(account, &Marker::get()),
+        );
+    }
+
+    /// Stops limiting the gas of the blocks from the next block.
+    public fun remove(account: &signer) {
+        BlockGasLimit::remove(&Capability::acquire(account, &Marker::get()));
+    }
+}
@@ -0,0 +1,20 @@
+module AptosFramework::AptosFeatures {
+    use Std::Capability;
+    use CoreFramework::Features;
+    use AptosFramework::Marker::{Self, ChainMarker};
+
+    /// Publishes the Features config, with no feature enabled.
+    public fun initialize(core_resource_account: &signer) {
+        Features::initialize<ChainMarker>(core_resource_account);
+    }
+
+    /// Enables `feature`, allowing modules requiring it to be published.
+    public fun enable(account: &signer, feature: u64) {
+        Features::enable(feature, &Capability::acquire(account, &Marker::get()));
+    }
+
+    /// Disables `feature`. Modules already published keep using it.
+    public fun disable(account: &signer, feature: u64) {
+        Features::disable(feature, &Capability::acquire(account, &Marker::get()));
+    }
+}
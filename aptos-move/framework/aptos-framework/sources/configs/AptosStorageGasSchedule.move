@@ -0,0 +1,31 @@
+module AptosFramework::AptosStorageGasSchedule {
+    use Std::Capability;
+    use CoreFramework::StorageGasSchedule;
+    use AptosFramework::Marker::{Self, ChainMarker};
+
+    /// Publishes the chain marker of the StorageGasSchedule config, without charging storage fees.
+    public fun initialize(core_resource_account: &signer) {
+        StorageGasSchedule::initialize<ChainMarker>(core_resource_account);
+    }
+
+    /// Starts charging storage fees, or updates them, from the next epoch.
+    public fun set(
+        account: &signer,
+        per_slot_create: u64,
+        per_byte_write: u64,
+        per_slot_refund: u64,
+    ) {
+        StorageGasSchedule::set(
+            account,
+            per_slot_create,
+            per_byte_write,
+            per_slot_refund,
+            &Capability::acquire(account, &Marker::get()),
+        );
+    }
+
+    /// Stops charging storage fees from the next epoch.
+    public fun remove(account: &signer) {
+        StorageGasSchedule::remove(&Capability::acquire(account, &Marker::get()));
+    }
+}
@@ -0,0 +1,79 @@
+/// Maintains the max gas the transactions of a block may use, matching `BlockGasLimit` in Rust.
+/// The block executor doesn't limit the gas of the blocks until the limit is published, which
+/// doesn't happen at genesis.
+module CoreFramework::BlockGasLimit {
+    use Std::Capability::Cap;
+    use Std::Errors;
+    use CoreFramework::Reconfiguration;
+    use CoreFramework::Timestamp;
+    use CoreFramework::SystemAddresses;
+
+    /// Marker to be stored under 0x1 during genesis
+    struct BlockGasLimitChainMarker<phantom T> has key {}
+
+    /// The limit, in the units of the gas used by the transactions. The block ends with the
+    /// transaction reaching it, the transactions after it are retried in the next blocks.
+    struct BlockGasLimit has key {
+        max_gas_per_block: u64,
+    }
+
+    /// Error with chain marker
+    const ECHAIN_MARKER: u64 = 0;
+    /// Error with config
+    const ECONFIG: u64 = 1;
+    /// The limit is 0
+    const EINVALID_LIMIT: u64 = 2;
+
+    /// Publishes the chain marker, without any limit: the gas of the blocks isn't limited.
+    public fun initialize<T>(account: &signer) {
+        Timestamp::assert_genesis();
+
+        SystemAddresses::assert_core_resource(account);
+
+        assert!(
+            !exists<BlockGasLimitChainMarker<T>>(@CoreResources),
+            Errors::already_published(ECHAIN_MARKER)
+        );
+
+        move_to(
+            account,
+            BlockGasLimitChainMarker<T> {},
+        );
+    }
+
+    /// Publishes or updates the limit, reconfiguring so that it applies from the next block.
+    public fun set<T>(
+        account: &signer,
+        max_gas_per_block: u64,
+        _cap: &Cap<T>
+    ) acquires BlockGasLimit {
+        SystemAddresses::assert_core_resource(account);
+        assert!(
+            exists<BlockGasLimitChainMarker<T>>(@CoreResources),
+            Errors::not_published(ECHAIN_MARKER)
+        );
+        assert!(max_gas_per_block > 0, Errors::invalid_argument(EINVALID_LIMIT));
+
+        if (exists<BlockGasLimit>(@CoreResources)) {
+            let limit = borrow_global_mut<BlockGasLimit>(@CoreResources);
+            limit.max_gas_per_block = max_gas_per_block;
+        } else {
+            move_to(account, BlockGasLimit { max_gas_per_block });
+        };
+
+        Reconfiguration::reconfigure();
+    }
+
+    /// Unpublishes the limit, no longer limiting the gas of the blocks from the next block.
+    public fun remove<T>(_cap: &Cap<T>) acquires BlockGasLimit {
+        assert!(
+            exists<BlockGasLimitChainMarker<T>>(@CoreResources),
+            Errors::not_published(ECHAIN_MARKER)
+        );
+        assert!(exists<BlockGasLimit>(@CoreResources), Errors::not_published(ECONFIG));
+
+        let BlockGasLimit { max_gas_per_block: _ } = move_from<BlockGasLimit>(@CoreResources);
+
+        Reconfiguration::reconfigure();
+    }
+}
@@ -0,0 +1,129 @@
+/// Maintains the features enabled on chain: the protocol feature flags gating new behaviors of
+/// the VM, and the runtime features modules can require in their metadata. The protocol flags
+/// are below `FIRST_RUNTIME_FEATURE`, which modules cannot require. A module requiring a runtime
+/// feature can only be published once the feature is enabled.
+module CoreFramework::Features {
+    use Std::Capability::Cap;
+    use Std::Errors;
+    use Std::Vector;
+    use CoreFramework::Reconfiguration;
+    use CoreFramework::Timestamp;
+    use CoreFramework::SystemAddresses;
+
+    /// Marker to be stored under 0x1 during genesis
+    struct FeaturesChainMarker<phantom T> has key {}
+
+    /// The enabled features, as a bitset where feature `n` is bit `n % 8` of byte `n / 8`.
+    struct Features has key {
+        features: vector<u8>,
+    }
+
+    /// Error with chain marker
+    const ECHAIN_MARKER: u64 = 0;
+    /// Error with config
+    const ECONFIG: u64 = 1;
+
+    // Protocol feature flags, matching `FeatureFlag` in Rust. New behaviors are gated on a flag
+    // so that validators switch over together, at the reconfiguration enabling it.
+
+    /// Modules may carry a metadata section, which is checked and stripped when publishing them.
+    const MODULE_METADATA: u64 = 0;
+
+    public fun module_metadata(): u64 { MODULE_METADATA }
+
+    public fun module_metadata_enabled(): bool acquires Features {
+        is_enabled(MODULE_METADATA)
+    }
+
+    /// The first runtime feature, matching `FIRST_RUNTIME_FEATURE` in Rust. The features below it
+    /// are reserved for protocol flags.
+    const FIRST_RUNTIME_FEATURE: u64 = 64;
+
+    public fun first_runtime_feature(): u64 { FIRST_RUNTIME_FEATURE }
+
+    /// Publishes the Features config, with no feature enabled.
+    public fun initialize<T>(account: &signer) {
+        Timestamp::assert_genesis();
+
+        SystemAddresses::assert_core_resource(account);
+
+        assert!(
+            !exists<FeaturesChainMarker<T>>(@CoreResources),
+            Errors::already_published(ECHAIN_MARKER)
+        );
+
+        assert!(
+            !exists<Features>(@CoreResources),
+            Errors::already_published(ECONFIG)
+        );
+
+        move_to(
+            account,
+            FeaturesChainMarker<T> {},
+        );
+        move_to(
+            account,
+            Features { features: Vector::empty() },
+        );
+    }
+
+    /// Whether `feature` is enabled.
+    public fun is_enabled(feature: u64): bool acquires Features {
+        exists<Features>(@CoreResources) &&
+            contains(&borrow_global<Features>(@CoreResources).features, feature)
+    }
+
+    /// Enables `feature`, allowing modules requiring it to be published.
+    public fun enable<T>(feature: u64, _cap: &Cap<T>) acquires Features {
+        assert!(exists<FeaturesChainMarker<T>>(@CoreResources), Errors::not_published(ECHAIN_MARKER));
+        assert!(exists<Features>(@CoreResources), Errors::not_published(ECONFIG));
+        set(&mut borrow_global_mut<Features>(@CoreResources).features, feature, true);
+
+        Reconfiguration::reconfigure();
+    }
+
+    /// Disables `feature`. Modules already published keep using it.
+    public fun disable<T>(feature: u64, _cap: &Cap<T>) acquires Features {
+        assert!(exists<FeaturesChainMarker<T>>(@CoreResources), Errors::not_published(ECHAIN_MARKER));
+        assert!(exists<Features>(@CoreResources), Errors::not_published(ECONFIG));
+        set(&mut borrow_global_mut<Features>(@CoreResources).features, feature, false);
+
+        Reconfiguration::reconfigure();
+    }
+
+    fun contains(features: &vector<u8>, feature: u64): bool {
+        let byte_index = feature / 8;
+        let bit_mask: u8 = 1 << ((feature % 8) as u8);
+        byte_index < Vector::length(features) && (*Vector::borrow(features, byte_index) & bit_mask) != 0
+    }
+
+    fun set(features: &mut vector<u8>, feature: u64, enabled: bool) {
+        let byte_index = feature / 8;
+        let bit_mask: u8 = 1 << ((feature % 8) as u8);
+        while (Vector::length(features) <= byte_index) {
+            Vector::push_back(features, 0)
+        };
+        let byte = Vector::borrow_mut(features, byte_index);
+        if (enabled) {
+            *byte = *byte | bit_mask;
+        } else {
+            *byte = *byte & (0xff ^ bit_mask);
+        }
+    }
+
+    #[test]
+    fun set_and_contains() {
+        let features = Vector::empty();
+        set(&mut features, 1, true);
+        set(&mut features, 17, true);
+        assert!(Vector::length(&features) == 3, 0);
+        assert!(contains(&features, 1), 1);
+        assert!(contains(&features, 17), 2);
+        assert!(!contains(&features, 2), 3);
+        assert!(!contains(&features, 100), 4);
+
+        set(&mut features, 1, false);
+        assert!(!contains(&features, 1), 5);
+        assert!(contains(&features, 17), 6);
+    }
+}
@@ -0,0 +1,91 @@
+/// Maintains the fees charged for the state used by transactions, matching `StorageGasSchedule`
+/// in Rust. The VM only charges storage fees once the schedule is published, which doesn't
+/// happen at genesis.
+module CoreFramework::StorageGasSchedule {
+    use Std::Capability::Cap;
+    use Std::Errors;
+    use CoreFramework::Reconfiguration;
+    use CoreFramework::Timestamp;
+    use CoreFramework::SystemAddresses;
+
+    /// Marker to be stored under 0x1 during genesis
+    struct StorageGasScheduleChainMarker<phantom T> has key {}
+
+    /// The fees, in internal gas units. A slot is a resource or a module in the state.
+    struct StorageGasSchedule has key {
+        /// Charged for every slot created.
+        per_slot_create: u64,
+        /// Charged for every byte written, to a new or an existing slot.
+        per_byte_write: u64,
+        /// Refunded for every slot deleted.
+        per_slot_refund: u64,
+    }
+
+    /// Error with chain marker
+    const ECHAIN_MARKER: u64 = 0;
+    /// Error with config
+    const ECONFIG: u64 = 1;
+
+    /// Publishes the chain marker, without any schedule: no storage fee is charged.
+    public fun initialize<T>(account: &signer) {
+        Timestamp::assert_genesis();
+
+        SystemAddresses::assert_core_resource(account);
+
+        assert!(
+            !exists<StorageGasScheduleChainMarker<T>>(@CoreResources),
+            Errors::already_published(ECHAIN_MARKER)
+        );
+
+        move_to(
+            account,
+            StorageGasScheduleChainMarker<T> {},
+        );
+    }
+
+    /// Publishes or updates the schedule, taking effect at the next epoch.
+    public fun set<T>(
+        account: &signer,
+        per_slot_create: u64,
+        per_byte_write: u64,
+        per_slot_refund: u64,
+        _cap: &Cap<T>
+    ) acquires StorageGasSchedule {
+        SystemAddresses::assert_core_resource(account);
+        assert!(
+            exists<StorageGasScheduleChainMarker<T>>(@CoreResources),
+            Errors::not_published(ECHAIN_MARKER)
+        );
+
+        if (exists<StorageGasSchedule>(@CoreResources)) {
+            let schedule = borrow_global_mut<StorageGasSchedule>(@CoreResources);
+            schedule.per_slot_create = per_slot_create;
+            schedule.per_byte_write = per_byte_write;
+            schedule.per_slot_refund = per_slot_refund;
+        } else {
+            move_to(
+                account,
+                StorageGasSchedule { per_slot_create, per_byte_write, per_slot_refund },
+            );
+        };
+
+        Reconfiguration::reconfigure();
+    }
+
+    /// Unpublishes the schedule, no longer charging storage fees from the next epoch.
+    public fun remove<T>(_cap: &Cap<T>) acquires StorageGasSchedule {
+        assert!(
+            exists<StorageGasScheduleChainMarker<T>>(@CoreResources),
+            Errors::not_published(ECHAIN_MARKER)
+        );
+        assert!(exists<StorageGasSchedule>(@CoreResources), Errors::not_published(ECONFIG));
+
+        let StorageGasSchedule {
+            per_slot_create: _,
+            per_byte_write: _,
+            per_slot_refund: _,
+        } = move_from<StorageGasSchedule>(@CoreResources);
+
+        Reconfiguration::reconfigure();
+    }
+}
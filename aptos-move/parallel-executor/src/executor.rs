@@ -102,8 +102,14 @@ where
     E: ExecutorTask<T = T>,
 {
     pub fn new() -> Self {
+        Self::with_concurrency_level(num_cpus::get())
+    }
+
+    /// Creates an executor running `concurrency_level` worker tasks rather than one per CPU.
+    pub fn with_concurrency_level(concurrency_level: usize) -> Self {
+        assert!(concurrency_level > 0, "Concurrency level must be positive");
         Self {
-            num_cpus: num_cpus::get(),
+            num_cpus: concurrency_level,
             phantom: PhantomData,
         }
     }
@@ -7,11 +7,14 @@
 //! cargo run -p transaction-builder-generator -- --help
 //! '''
 
+use aptos_rest_client::Client;
+use aptos_types::account_address::AccountAddress;
 use serde_generate as serdegen;
 use serde_reflection::Registry;
 use std::path::PathBuf;
 use structopt::{clap::arg_enum, StructOpt};
 use transaction_builder_generator as buildgen;
+use url::Url;
 
 arg_enum! {
 #[derive(Debug, StructOpt)]
@@ -36,6 +39,15 @@ struct Options {
     /// Path to the directory containing ABI files in BCS encoding.
     abi_directories: Vec<PathBuf>,
 
+    /// REST endpoint of a node to fetch the modules published under the `--account`s from, e.g.
+    /// third-party modules. The ABIs of their script functions are derived from the bytecode.
+    #[structopt(long, requires = "accounts")]
+    rest_url: Option<Url>,
+
+    /// Account whose published modules get transaction builders, along with `--rest-url`.
+    #[structopt(long = "account", requires = "rest-url", number_of_values = 1)]
+    accounts: Vec<AccountAddress>,
+
     /// Language for code generation.
     #[structopt(long, possible_values = &Language::variants(), case_insensitive = true, default_value = "Python3")]
     language: Language,
@@ -77,8 +89,20 @@ struct Options {
 
 fn main() {
     let options = Options::from_args();
-    let abis =
+    let mut abis =
         buildgen::read_abis(&options.abi_directories).expect("Failed to read ABI in directory");
+    if let Some(rest_url) = options.rest_url.clone() {
+        let runtime = tokio::runtime::Runtime::new().expect("Failed to create runtime");
+        let onchain_abis = runtime
+            .block_on(buildgen::onchain::fetch_script_function_abis(
+                &Client::new(rest_url),
+                &options.accounts,
+            ))
+            .expect("Failed to fetch ABI from node");
+        abis.extend(onchain_abis);
+        #[allow(clippy::unnecessary_sort_by)]
+        abis.sort_by(|a, b| a.name().cmp(b.name()));
+    }
 
     let install_dir = match options.target_source_dir {
         None => {
@@ -0,0 +1,104 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! ABIs of the script functions of modules published on chain, e.g. by third parties. The ABI
+//! files are only produced along with the compilation of the modules, so the ABIs are derived
+//! from the bytecode of the modules instead. The bytecode doesn't keep parameter names: type
+//! arguments are named `t0`, `t1`, ... and arguments `arg0`, `arg1`, ...
+
+use anyhow::Result;
+use aptos_rest_client::Client;
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{ArgumentABI, ScriptABI, ScriptFunctionABI, TypeArgumentABI},
+};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{FunctionDefinition, SignatureToken, Visibility},
+    CompiledModule,
+};
+use move_core_types::language_storage::TypeTag;
+
+/// Returns the ABIs of the script functions of the module. Functions taking arguments the
+/// transaction builders don't support, like structs, are skipped.
+pub fn script_function_abis(module: &CompiledModule) -> Vec<ScriptABI> {
+    module
+        .function_defs()
+        .iter()
+        .filter(|def| matches!(def.visibility, Visibility::Script))
+        .filter_map(|def| script_function_abi(module, def))
+        .map(ScriptABI::ScriptFunction)
+        .collect()
+}
+
+fn script_function_abi(
+    module: &CompiledModule,
+    def: &FunctionDefinition,
+) -> Option<ScriptFunctionABI> {
+    let handle = module.function_handle_at(def.function);
+    let ty_args = (0..handle.type_parameters.len())
+        .map(|i| TypeArgumentABI::new(format!("t{}", i)))
+        .collect();
+    // The signers of the transaction are passed by the VM, not as arguments.
+    let args = module
+        .signature_at(handle.parameters)
+        .0
+        .iter()
+        .skip_while(|token| is_signer(token))
+        .enumerate()
+        .map(|(i, token)| Some(ArgumentABI::new(format!("arg{}", i), argument_type(token)?)))
+        .collect::<Option<_>>()?;
+
+    Some(ScriptFunctionABI::new(
+        module.identifier_at(handle.name).to_string(),
+        module.self_id(),
+        String::new(),
+        ty_args,
+        args,
+    ))
+}
+
+fn is_signer(token: &SignatureToken) -> bool {
+    match token {
+        SignatureToken::Signer => true,
+        SignatureToken::Reference(token) => token.as_ref() == &SignatureToken::Signer,
+        _ => false,
+    }
+}
+
+/// The argument types supported by the transaction builders of all languages.
+fn argument_type(token: &SignatureToken) -> Option<TypeTag> {
+    Some(match token {
+        SignatureToken::Bool => TypeTag::Bool,
+        SignatureToken::U8 => TypeTag::U8,
+        SignatureToken::U64 => TypeTag::U64,
+        SignatureToken::U128 => TypeTag::U128,
+        SignatureToken::Address => TypeTag::Address,
+        SignatureToken::Vector(token) => match token.as_ref() {
+            SignatureToken::U8 => TypeTag::Vector(Box::new(TypeTag::U8)),
+            SignatureToken::Vector(token) if token.as_ref() == &SignatureToken::U8 => {
+                TypeTag::Vector(Box::new(TypeTag::Vector(Box::new(TypeTag::U8))))
+            }
+            _ => return None,
+        },
+        _ => return None,
+    })
+}
+
+/// Fetches the modules published under the accounts from a node and returns the ABIs of their
+/// script functions, sorted by name like [`read_abis`](crate::read_abis).
+pub async fn fetch_script_function_abis(
+    client: &Client,
+    addresses: &[AccountAddress],
+) -> Result<Vec<ScriptABI>> {
+    let mut abis = Vec::new();
+    for address in addresses {
+        for module in client.get_account_modules(*address).await?.into_inner() {
+            let module = CompiledModule::deserialize(module.bytecode.inner())?;
+            abis.extend(script_function_abis(&module));
+        }
+    }
+    #[allow(clippy::unnecessary_sort_by)]
+    abis.sort_by(|a, b| a.name().cmp(b.name()));
+    Ok(abis)
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_types::transaction::ScriptABI;
+use move_binary_format::CompiledModule;
 use serde_generate as serdegen;
 use serde_generate::SourceInstaller as _;
 use serde_reflection::Registry;
@@ -39,6 +40,33 @@ const EXPECTED_TX_SCRIPT_OUTPUT: &str = "224 1 161 28 235 11 1 0 0 0 7 1 0 2 2 2
 
 const EXPECTED_SCRIPT_FUN_OUTPUT: &str = "3 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 1 14 80 97 121 109 101 110 116 83 99 114 105 112 116 115 26 112 101 101 114 95 116 111 95 112 101 101 114 95 119 105 116 104 95 109 101 116 97 100 97 116 97 1 7 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 1 3 88 68 88 3 88 68 88 0 4 16 34 34 34 34 34 34 34 34 34 34 34 34 34 34 34 34 8 135 214 18 0 0 0 0 0 1 0 1 0 \n";
 
+#[test]
+fn test_script_function_abis_from_bytecode() {
+    let dir = Path::new("../framework/DPN/releases/artifacts/current/build/DPNFramework");
+    let bytecode = std::fs::read(dir.join("bytecode_modules/PaymentScripts.mv")).unwrap();
+    let module = CompiledModule::deserialize(&bytecode).unwrap();
+    let abis = buildgen::onchain::script_function_abis(&module);
+    let expected_abis = buildgen::read_abis(&[dir.join("abis/PaymentScripts")]).unwrap();
+
+    // Names of the parameters aren't in the bytecode
+    let signature = |abi: &ScriptABI| {
+        (
+            abi.name().to_string(),
+            abi.ty_args().len(),
+            abi.args()
+                .iter()
+                .map(|arg| arg.type_tag().clone())
+                .collect::<Vec<_>>(),
+        )
+    };
+    let mut signatures = abis.iter().map(signature).collect::<Vec<_>>();
+    signatures.sort();
+    assert_eq!(
+        signatures,
+        expected_abis.iter().map(signature).collect::<Vec<_>>()
+    );
+}
+
 #[test]
 fn test_typescript_replace_keywords() {
     let yamlpath = "./tests/keyworded_registry.yaml";
@@ -0,0 +1,266 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    create_and_initialize_main_accounts, create_and_initialize_owners_operators,
+    create_and_initialize_testnet_minting, exec_function, exec_script_function,
+    genesis_context::GenesisStateView, publish_stdlib, reconfigure, verify_genesis_write_set,
+    Validator,
+};
+use anyhow::{ensure, format_err, Result};
+use aptos_crypto::ed25519::Ed25519PublicKey;
+use aptos_transaction_builder::stdlib as transaction_builder;
+use aptos_types::{
+    account_config::{self, DESIGNATED_DEALER_MODULE},
+    chain_id::{ChainId, NamedChain},
+    on_chain_config::{OnChainConsensusConfig, ReadWriteSetAnalysis, VMPublishingOption},
+    transaction::{authenticator::AuthenticationKey, ChangeSet, Transaction, WriteSetPayload},
+};
+use aptos_vm::{convert_changeset_and_events, data_cache::StateViewCache};
+use diem_framework_releases::legacy::transaction_scripts::LegacyStdlibScript;
+use move_binary_format::CompiledModule;
+use move_bytecode_utils::Modules;
+use move_core_types::{
+    gas_schedule::CostTable,
+    value::{serialize_values, MoveValue},
+};
+use move_vm_runtime::{move_vm::MoveVM, session::Session};
+use move_vm_types::gas_schedule::INITIAL_COST_SCHEDULE;
+
+/// An account created at genesis, funded with `balance` XUS.
+#[derive(Clone, Debug)]
+pub struct GenesisAccount {
+    /// Authentication key of the account, from which its address is derived
+    pub auth_key: AuthenticationKey,
+    /// Initial XUS balance of the account
+    pub balance: u64,
+}
+
+/// Builds the genesis transaction of a network out of an arbitrary bundle of framework modules.
+/// The bundle must contain the `Genesis` module the builder initializes the chain with, and the
+/// `DesignatedDealer` module when initial accounts are funded.
+#[derive(Clone)]
+pub struct GenesisBuilder {
+    modules: Vec<Vec<u8>>,
+    chain_id: ChainId,
+    aptos_root_key: Ed25519PublicKey,
+    treasury_compliance_key: Ed25519PublicKey,
+    validators: Vec<Validator>,
+    accounts: Vec<GenesisAccount>,
+    vm_publishing_option: VMPublishingOption,
+    gas_schedule: CostTable,
+    consensus_config: OnChainConsensusConfig,
+    enable_parallel_execution: bool,
+}
+
+impl GenesisBuilder {
+    pub fn new(
+        modules: Vec<Vec<u8>>,
+        chain_id: ChainId,
+        aptos_root_key: Ed25519PublicKey,
+        treasury_compliance_key: Ed25519PublicKey,
+    ) -> Self {
+        Self {
+            modules,
+            chain_id,
+            aptos_root_key,
+            treasury_compliance_key,
+            validators: vec![],
+            accounts: vec![],
+            vm_publishing_option: VMPublishingOption::locked(LegacyStdlibScript::allowlist()),
+            gas_schedule: INITIAL_COST_SCHEDULE.clone(),
+            consensus_config: OnChainConsensusConfig::default(),
+            enable_parallel_execution: false,
+        }
+    }
+
+    pub fn validators(mut self, validators: Vec<Validator>) -> Self {
+        self.validators = validators;
+        self
+    }
+
+    pub fn accounts(mut self, accounts: Vec<GenesisAccount>) -> Self {
+        self.accounts = accounts;
+        self
+    }
+
+    pub fn vm_publishing_option(mut self, vm_publishing_option: VMPublishingOption) -> Self {
+        self.vm_publishing_option = vm_publishing_option;
+        self
+    }
+
+    pub fn gas_schedule(mut self, gas_schedule: CostTable) -> Self {
+        self.gas_schedule = gas_schedule;
+        self
+    }
+
+    pub fn consensus_config(mut self, consensus_config: OnChainConsensusConfig) -> Self {
+        self.consensus_config = consensus_config;
+        self
+    }
+
+    pub fn enable_parallel_execution(mut self, enable_parallel_execution: bool) -> Self {
+        self.enable_parallel_execution = enable_parallel_execution;
+        self
+    }
+
+    /// Builds the genesis transaction. Its waypoint is computed by executing it on an empty DB,
+    /// see `executor::db_bootstrapper::generate_waypoint`.
+    pub fn build(&self) -> Result<Transaction> {
+        Ok(Transaction::GenesisTransaction(WriteSetPayload::Direct(
+            self.build_change_set()?,
+        )))
+    }
+
+    pub fn build_change_set(&self) -> Result<ChangeSet> {
+        let mut stdlib_modules = Vec::new();
+        // create a data view for move_vm
+        let mut state_view = GenesisStateView::new();
+        let mut has_dd_module = false;
+        for module_bytes in &self.modules {
+            let module = CompiledModule::deserialize(module_bytes)
+                .map_err(|e| format_err!("Invalid genesis module: {:?}", e))?;
+            if module.self_id() == *DESIGNATED_DEALER_MODULE {
+                has_dd_module = true;
+            }
+            state_view.add_module(&module.self_id(), module_bytes);
+            stdlib_modules.push(module)
+        }
+        ensure!(
+            has_dd_module || self.accounts.is_empty(),
+            "Funding genesis accounts requires the DesignatedDealer module"
+        );
+        let data_cache = StateViewCache::new(&state_view);
+
+        let move_vm = MoveVM::new(aptos_vm::natives::aptos_natives())
+            .map_err(|e| format_err!("Failed to create the Move VM: {:?}", e))?;
+        let mut session = move_vm.new_session(&data_cache);
+
+        create_and_initialize_main_accounts(
+            &mut session,
+            &self.aptos_root_key,
+            &self.treasury_compliance_key,
+            self.vm_publishing_option.clone(),
+            &self.gas_schedule,
+            self.consensus_config.clone(),
+            self.chain_id,
+        )?;
+        // generate the genesis WriteSet
+        create_and_initialize_owners_operators(&mut session, &self.validators)?;
+        reconfigure(&mut session)?;
+
+        let is_test_chain = [NamedChain::TESTNET, NamedChain::DEVNET, NamedChain::TESTING]
+            .iter()
+            .any(|test_chain_id| test_chain_id.id() == self.chain_id.id());
+        if has_dd_module && is_test_chain {
+            create_and_initialize_testnet_minting(
+                &mut session,
+                &self.treasury_compliance_key,
+                std::u64::MAX / 2,
+            )?;
+        } else if !self.accounts.is_empty() {
+            let total_balance = self
+                .accounts
+                .iter()
+                .try_fold(0u64, |total, account| total.checked_add(account.balance))
+                .ok_or_else(|| format_err!("Total balance of genesis accounts overflows"))?;
+            create_and_initialize_testnet_minting(
+                &mut session,
+                &self.treasury_compliance_key,
+                total_balance,
+            )?;
+        }
+        create_and_fund_accounts(&mut session, &self.accounts)?;
+
+        if self.enable_parallel_execution {
+            let payload = bcs::to_bytes(&ReadWriteSetAnalysis::V1(
+                read_write_set::analyze(&stdlib_modules)
+                    .map_err(|e| format_err!("Failed to get the ReadWriteSet: {}", e))?
+                    .normalize_all_scripts(
+                        aptos_vm::read_write_set_analysis::add_on_functions_list(),
+                    )
+                    .trim()
+                    .into_inner(),
+            ))?;
+
+            exec_function(
+                &mut session,
+                "ParallelExecutionConfig",
+                "enable_parallel_execution_with_config",
+                vec![],
+                serialize_values(&vec![
+                    MoveValue::Signer(account_config::aptos_root_address()),
+                    MoveValue::vector_u8(payload),
+                ]),
+            )?;
+        }
+
+        let (mut changeset1, mut events1) = session
+            .finish()
+            .map_err(|e| format_err!("Failed to finish the genesis session: {:?}", e))?;
+
+        let state_view = GenesisStateView::new();
+        let data_cache = StateViewCache::new(&state_view);
+        let mut session = move_vm.new_session(&data_cache);
+        publish_stdlib(&mut session, Modules::new(stdlib_modules.iter()))?;
+        let (changeset2, events2) = session
+            .finish()
+            .map_err(|e| format_err!("Failed to finish publishing the framework: {:?}", e))?;
+
+        changeset1
+            .squash(changeset2)
+            .map_err(|e| format_err!("Failed to merge the genesis change sets: {:?}", e))?;
+        events1.extend(events2);
+
+        let (write_set, events) = convert_changeset_and_events(changeset1, events1)
+            .map_err(|e| format_err!("Failed to convert the genesis change set: {:?}", e))?;
+
+        ensure!(
+            !write_set.iter().any(|(_, op)| op.is_deletion()),
+            "The genesis write set can't delete anything"
+        );
+        // Perform DPN genesis verification
+        if has_dd_module {
+            verify_genesis_write_set(&events)?;
+        }
+        Ok(ChangeSet::new(write_set, events))
+    }
+}
+
+/// Creates each account as a parent VASP, paying its balance from the testnet designated dealer.
+fn create_and_fund_accounts(
+    session: &mut Session<StateViewCache<GenesisStateView>>,
+    accounts: &[GenesisAccount],
+) -> Result<()> {
+    for account in accounts {
+        let address = account.auth_key.derived_address();
+        exec_script_function(
+            session,
+            account_config::treasury_compliance_account_address(),
+            &transaction_builder::encode_create_parent_vasp_account_script_function(
+                account_config::xus_tag(),
+                0,
+                address,
+                account.auth_key.prefix().to_vec(),
+                address.short_str_lossless().into_bytes(),
+                false, /* add_all_currencies */
+            )
+            .into_script_function(),
+        )?;
+        if account.balance > 0 {
+            exec_script_function(
+                session,
+                account_config::testnet_dd_account_address(),
+                &transaction_builder::encode_peer_to_peer_with_metadata_script_function(
+                    account_config::xus_tag(),
+                    address,
+                    account.balance,
+                    vec![],
+                    vec![],
+                )
+                .into_script_function(),
+            )?;
+        }
+    }
+    Ok(())
+}
@@ -3,9 +3,13 @@
 
 #![forbid(unsafe_code)]
 
+mod builder;
 mod genesis_context;
 
+pub use builder::{GenesisAccount, GenesisBuilder};
+
 use crate::genesis_context::GenesisStateView;
+use anyhow::{ensure, format_err, Result};
 use aptos_crypto::{
     ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
     PrivateKey, Uniform,
@@ -15,32 +19,30 @@ use aptos_types::{
     account_config::{
         self,
         events::{CreateAccountEvent, NewEpochEvent},
-        DESIGNATED_DEALER_MODULE,
     },
-    chain_id::{ChainId, NamedChain},
+    chain_id::ChainId,
     contract_event::ContractEvent,
     on_chain_config::{
-        ConsensusConfigV1, OnChainConsensusConfig, ReadWriteSetAnalysis, VMPublishingOption,
-        DIEM_MAX_KNOWN_VERSION,
+        ConsensusConfigV1, OnChainConsensusConfig, VMPublishingOption, DIEM_MAX_KNOWN_VERSION,
     },
     transaction::{
         authenticator::AuthenticationKey, ChangeSet, ScriptFunction, Transaction, WriteSetPayload,
     },
 };
-use aptos_vm::{convert_changeset_and_events, data_cache::StateViewCache};
+use aptos_vm::data_cache::StateViewCache;
 use diem_framework_releases::{
     current_module_blobs, legacy::transaction_scripts::LegacyStdlibScript,
 };
-use move_binary_format::CompiledModule;
 use move_bytecode_utils::Modules;
 use move_core_types::{
     account_address::AccountAddress,
+    gas_schedule::CostTable,
     identifier::Identifier,
     language_storage::{ModuleId, TypeTag},
     value::{serialize_values, MoveValue},
 };
-use move_vm_runtime::{move_vm::MoveVM, session::Session};
-use move_vm_types::gas_schedule::{GasStatus, INITIAL_COST_SCHEDULE};
+use move_vm_runtime::session::Session;
+use move_vm_types::gas_schedule::GasStatus;
 use once_cell::sync::Lazy;
 use rand::prelude::*;
 use transaction_builder::encode_create_designated_dealer_script_function;
@@ -90,84 +92,18 @@ pub fn encode_genesis_change_set(
     chain_id: ChainId,
     enable_parallel_execution: bool,
 ) -> ChangeSet {
-    let mut stdlib_modules = Vec::new();
-    // create a data view for move_vm
-    let mut state_view = GenesisStateView::new();
-    let mut has_dd_module = false;
-    for module_bytes in stdlib_module_bytes {
-        let module = CompiledModule::deserialize(module_bytes).unwrap();
-        if module.self_id() == *DESIGNATED_DEALER_MODULE {
-            has_dd_module = true;
-        }
-        state_view.add_module(&module.self_id(), module_bytes);
-        stdlib_modules.push(module)
-    }
-    let data_cache = StateViewCache::new(&state_view);
-
-    let move_vm = MoveVM::new(aptos_vm::natives::aptos_natives()).unwrap();
-    let mut session = move_vm.new_session(&data_cache);
-
-    create_and_initialize_main_accounts(
-        &mut session,
-        aptos_root_key,
-        treasury_compliance_key,
-        vm_publishing_option,
-        consensus_config,
+    GenesisBuilder::new(
+        stdlib_module_bytes.to_vec(),
         chain_id,
-    );
-    // generate the genesis WriteSet
-    create_and_initialize_owners_operators(&mut session, validators);
-    reconfigure(&mut session);
-
-    if has_dd_module
-        && [NamedChain::TESTNET, NamedChain::DEVNET, NamedChain::TESTING]
-            .iter()
-            .any(|test_chain_id| test_chain_id.id() == chain_id.id())
-    {
-        create_and_initialize_testnet_minting(&mut session, treasury_compliance_key);
-    }
-
-    if enable_parallel_execution {
-        let payload = bcs::to_bytes(&ReadWriteSetAnalysis::V1(
-            read_write_set::analyze(&stdlib_modules)
-                .expect("Failed to get ReadWriteSet for current Diem Framework")
-                .normalize_all_scripts(aptos_vm::read_write_set_analysis::add_on_functions_list())
-                .trim()
-                .into_inner(),
-        ))
-        .expect("Failed to serialize analyze result");
-
-        exec_function(
-            &mut session,
-            "ParallelExecutionConfig",
-            "enable_parallel_execution_with_config",
-            vec![],
-            serialize_values(&vec![
-                MoveValue::Signer(account_config::aptos_root_address()),
-                MoveValue::vector_u8(payload),
-            ]),
-        )
-    }
-
-    let (mut changeset1, mut events1) = session.finish().unwrap();
-
-    let state_view = GenesisStateView::new();
-    let data_cache = StateViewCache::new(&state_view);
-    let mut session = move_vm.new_session(&data_cache);
-    publish_stdlib(&mut session, Modules::new(stdlib_modules.iter()));
-    let (changeset2, events2) = session.finish().unwrap();
-
-    changeset1.squash(changeset2).unwrap();
-    events1.extend(events2);
-
-    let (write_set, events) = convert_changeset_and_events(changeset1, events1).unwrap();
-
-    assert!(!write_set.iter().any(|(_, op)| op.is_deletion()));
-    // Perform DPN genesis verification
-    if has_dd_module {
-        verify_genesis_write_set(&events);
-    }
-    ChangeSet::new(write_set, events)
+        aptos_root_key.clone(),
+        treasury_compliance_key.clone(),
+    )
+    .validators(validators.to_vec())
+    .vm_publishing_option(vm_publishing_option)
+    .consensus_config(consensus_config)
+    .enable_parallel_execution(enable_parallel_execution)
+    .build_change_set()
+    .expect("Failed to build the genesis change set")
 }
 
 fn exec_function(
@@ -176,26 +112,26 @@ fn exec_function(
     function_name: &str,
     ty_args: Vec<TypeTag>,
     args: Vec<Vec<u8>>,
-) {
+) -> Result<()> {
     session
         .execute_function(
             &ModuleId::new(
                 account_config::CORE_CODE_ADDRESS,
-                Identifier::new(module_name).unwrap(),
+                Identifier::new(module_name)?,
             ),
-            &Identifier::new(function_name).unwrap(),
+            &Identifier::new(function_name)?,
             ty_args,
             args,
             &mut GasStatus::new_unmetered(),
         )
-        .unwrap_or_else(|e| {
-            panic!(
+        .map_err(|e| {
+            format_err!(
                 "Error calling {}.{}: {}",
                 module_name,
                 function_name,
                 e.into_vm_status()
             )
-        });
+        })
 }
 
 fn exec_script_function(
@@ -203,7 +139,7 @@ fn exec_script_function(
 
     sender: AccountAddress,
     script_function: &ScriptFunction,
-) {
+) -> Result<()> {
     session
         .execute_script_function(
             script_function.module(),
@@ -213,7 +149,14 @@ fn exec_script_function(
             vec![sender],
             &mut GasStatus::new_unmetered(),
         )
-        .unwrap()
+        .map_err(|e| {
+            format_err!(
+                "Error calling {}::{}: {}",
+                script_function.module(),
+                script_function.function(),
+                e.into_vm_status()
+            )
+        })
 }
 
 /// Create and initialize Association and Core Code accounts.
@@ -222,9 +165,10 @@ fn create_and_initialize_main_accounts(
     aptos_root_key: &Ed25519PublicKey,
     treasury_compliance_key: &Ed25519PublicKey,
     publishing_option: VMPublishingOption,
+    genesis_gas_schedule: &CostTable,
     consensus_config: OnChainConsensusConfig,
     chain_id: ChainId,
-) {
+) -> Result<()> {
     let aptos_root_auth_key = AuthenticationKey::ed25519(aptos_root_key);
     let treasury_compliance_auth_key = AuthenticationKey::ed25519(treasury_compliance_key);
 
@@ -239,14 +183,9 @@ fn create_and_initialize_main_accounts(
             .collect(),
     );
 
-    let genesis_gas_schedule = &INITIAL_COST_SCHEDULE;
-    let instr_gas_costs = bcs::to_bytes(&genesis_gas_schedule.instruction_table)
-        .expect("Failure serializing genesis instr gas costs");
-    let native_gas_costs = bcs::to_bytes(&genesis_gas_schedule.native_table)
-        .expect("Failure serializing genesis native gas costs");
-
-    let consensus_config_bytes =
-        bcs::to_bytes(&consensus_config).expect("Failure serializing genesis consensus config");
+    let instr_gas_costs = bcs::to_bytes(&genesis_gas_schedule.instruction_table)?;
+    let native_gas_costs = bcs::to_bytes(&genesis_gas_schedule.native_table)?;
+    let consensus_config_bytes = bcs::to_bytes(&consensus_config)?;
 
     exec_function(
         session,
@@ -266,14 +205,15 @@ fn create_and_initialize_main_accounts(
             MoveValue::U64(DIEM_MAX_KNOWN_VERSION.major),
             MoveValue::vector_u8(consensus_config_bytes),
         ]),
-    );
+    )
 }
 
+/// Creates the testnet designated dealer, with `amount` XUS minted to it.
 fn create_and_initialize_testnet_minting(
     session: &mut Session<StateViewCache<GenesisStateView>>,
-
     public_key: &Ed25519PublicKey,
-) {
+    amount: u64,
+) -> Result<()> {
     let genesis_auth_key = AuthenticationKey::ed25519(public_key);
     let create_dd_script = encode_create_designated_dealer_script_function(
         account_config::xus_tag(),
@@ -289,7 +229,7 @@ fn create_and_initialize_testnet_minting(
         account_config::xus_tag(),
         0,
         account_config::testnet_dd_account_address(),
-        std::u64::MAX / 2,
+        amount,
         3,
     )
     .into_script_function();
@@ -299,11 +239,11 @@ fn create_and_initialize_testnet_minting(
         session,
         account_config::treasury_compliance_account_address(),
         &create_dd_script,
-    );
+    )?;
 
     // mint XUS.
     let treasury_compliance_account_address = account_config::treasury_compliance_account_address();
-    exec_script_function(session, treasury_compliance_account_address, &mint_max_xus);
+    exec_script_function(session, treasury_compliance_account_address, &mint_max_xus)?;
 
     let testnet_dd_account_address = account_config::testnet_dd_account_address();
     exec_script_function(
@@ -313,7 +253,7 @@ fn create_and_initialize_testnet_minting(
             genesis_auth_key.to_vec(),
         )
         .into_script_function(),
-    );
+    )
 }
 
 /// Creates and initializes each validator owner and validator operator. This method creates all
@@ -322,7 +262,7 @@ fn create_and_initialize_testnet_minting(
 fn create_and_initialize_owners_operators(
     session: &mut Session<StateViewCache<GenesisStateView>>,
     validators: &[Validator],
-) {
+) -> Result<()> {
     let aptos_root_address = account_config::aptos_root_address();
     let mut owners = vec![];
     let mut owner_names = vec![];
@@ -362,65 +302,65 @@ fn create_and_initialize_owners_operators(
             MoveValue::Vector(validator_network_addresses),
             MoveValue::Vector(full_node_network_addresses),
         ]),
-    );
+    )
 }
 
 /// Publish the standard library.
-fn publish_stdlib(session: &mut Session<StateViewCache<GenesisStateView>>, stdlib: Modules) {
+fn publish_stdlib(
+    session: &mut Session<StateViewCache<GenesisStateView>>,
+    stdlib: Modules,
+) -> Result<()> {
     let dep_graph = stdlib.compute_dependency_graph();
     let mut addr_opt: Option<AccountAddress> = None;
-    let modules = dep_graph
-        .compute_topological_order()
-        .unwrap()
-        .map(|m| {
-            let addr = *m.self_id().address();
-            if let Some(a) = addr_opt {
-              assert!(
-                  a == addr,
-                  "All genesis modules must be published under the same address, but found modules under both {} and {}",
-                  a.short_str_lossless(),
-                  addr.short_str_lossless()
-              );
-            } else {
-                addr_opt = Some(addr)
-            }
-            let mut bytes = vec![];
-            m.serialize(&mut bytes).unwrap();
-            bytes
-        })
-        .collect::<Vec<Vec<u8>>>();
+    let mut modules = vec![];
+    for m in dep_graph.compute_topological_order()? {
+        let addr = *m.self_id().address();
+        if let Some(a) = addr_opt {
+            ensure!(
+                a == addr,
+                "All genesis modules must be published under the same address, but found modules \
+                 under both {} and {}",
+                a.short_str_lossless(),
+                addr.short_str_lossless()
+            );
+        } else {
+            addr_opt = Some(addr)
+        }
+        let mut bytes = vec![];
+        m.serialize(&mut bytes)?;
+        modules.push(bytes);
+    }
     // TODO: allow genesis modules published under different addresses. supporting this while
     // maintaining the topological order is challenging.
+    let addr = addr_opt.ok_or_else(|| format_err!("No genesis modules to publish"))?;
     session
-        .publish_module_bundle(modules, addr_opt.unwrap(), &mut GasStatus::new_unmetered())
-        .unwrap_or_else(|e| panic!("Failure publishing modules {:?}", e));
+        .publish_module_bundle(modules, addr, &mut GasStatus::new_unmetered())
+        .map_err(|e| format_err!("Failure publishing modules {:?}", e))
 }
 
 /// Trigger a reconfiguration. This emits an event that will be passed along to the storage layer.
-fn reconfigure(session: &mut Session<StateViewCache<GenesisStateView>>) {
+fn reconfigure(session: &mut Session<StateViewCache<GenesisStateView>>) -> Result<()> {
     exec_function(
         session,
         "Reconfiguration",
         "emit_genesis_reconfiguration_event",
         vec![],
         vec![],
-    );
+    )
 }
 
 /// Verify the consistency of the genesis `WriteSet`
-fn verify_genesis_write_set(events: &[ContractEvent]) {
+fn verify_genesis_write_set(events: &[ContractEvent]) -> Result<()> {
     // (1) first event is account creation event for DiemRoot
-    let create_diem_root_event = &events[0];
-    assert_eq!(
-        *create_diem_root_event.key(),
-        CreateAccountEvent::event_key(),
+    ensure!(
+        events.get(0).map(|event| *event.key()) == Some(CreateAccountEvent::event_key()),
+        "The first genesis event should create the DiemRoot account"
     );
 
     // (2) second event is account creation event for TreasuryCompliance
-    let create_treasury_compliance_event = &events[1];
-    assert_eq!(
-        *create_treasury_compliance_event.key(),
-        CreateAccountEvent::event_key(),
+    ensure!(
+        events.get(1).map(|event| *event.key()) == Some(CreateAccountEvent::event_key()),
+        "The second genesis event should create the TreasuryCompliance account"
     );
 
     // (3) The first non-account creation event should be the new epoch event
@@ -428,12 +368,16 @@ fn verify_genesis_write_set(events: &[ContractEvent]) {
         .iter()
         .filter(|e| e.key() == &NewEpochEvent::event_key())
         .collect();
-    assert!(
+    ensure!(
         new_epoch_events.len() == 1,
         "There should only be one NewEpochEvent"
     );
     // (4) This should be the first new_epoch_event
-    assert_eq!(new_epoch_events[0].sequence_number(), 0,);
+    ensure!(
+        new_epoch_events[0].sequence_number() == 0,
+        "The NewEpochEvent should be the first of its stream"
+    );
+    Ok(())
 }
 
 /// An enum specifying whether the compiled stdlib/scripts should be used or freshly built versions
@@ -0,0 +1,123 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_api::rate_limit::ApiRateLimiter;
+use aptos_config::config::{Error, NodeConfig};
+use aptos_logger::{prelude::*, Filter, Logger};
+use aptos_mempool::MempoolConfigUpdater;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::{
+    runtime::{Builder, Runtime},
+    signal::unix::{signal, SignalKind},
+};
+
+const CONFIG_FILE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Applies the changes of the node config file to the running node, when the node receives a
+/// SIGHUP or once the file is modified. Only the fields in `RELOADABLE_FIELDS` can be changed,
+/// a file changing any other field is rejected as a whole and the running config is kept.
+pub struct ConfigReloader {
+    path: PathBuf,
+    config: NodeConfig,
+    logger: Option<Arc<Logger>>,
+    mempool: MempoolConfigUpdater,
+    api_rate_limiter: Arc<ApiRateLimiter>,
+}
+
+impl ConfigReloader {
+    pub fn new(
+        path: PathBuf,
+        config: NodeConfig,
+        logger: Option<Arc<Logger>>,
+        mempool: MempoolConfigUpdater,
+        api_rate_limiter: Arc<ApiRateLimiter>,
+    ) -> Self {
+        Self {
+            path,
+            config,
+            logger,
+            mempool,
+            api_rate_limiter,
+        }
+    }
+
+    /// Re-reads the config file and applies its changes, returning the paths of the changed
+    /// fields.
+    pub fn reload(&mut self) -> Result<Vec<String>, Error> {
+        let new_config = NodeConfig::load(&self.path)?;
+        let changes = self.config.reloadable_changes(&new_config)?;
+        if changes.is_empty() {
+            return Ok(changes);
+        }
+
+        if let Some(logger) = &self.logger {
+            // As when the logger is built, RUST_LOG takes precedence over the config
+            if env::var("RUST_LOG").is_ok() {
+                warn!("RUST_LOG is set, the log levels of the config are ignored");
+            } else {
+                let mut filter = Filter::builder();
+                filter.filter_level(new_config.logger.level.into());
+                for (module, level) in &new_config.logger.module_levels {
+                    filter.filter_module(module, (*level).into());
+                }
+                logger.set_filter(filter.build());
+            }
+        }
+        self.mempool.update(&new_config.mempool);
+        self.api_rate_limiter
+            .update(new_config.api.rate_limit.as_ref());
+
+        self.config = new_config;
+        Ok(changes)
+    }
+
+    /// Reloads the config on SIGHUP, and when the modification time of the file changes
+    pub fn start(mut self) -> Runtime {
+        let runtime = Builder::new_multi_thread()
+            .thread_name("config-reloader")
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .expect("[config reloader] failed to create runtime");
+
+        runtime.spawn(async move {
+            let mut hangups =
+                signal(SignalKind::hangup()).expect("[config reloader] failed to handle SIGHUP");
+            let mut interval = tokio::time::interval(CONFIG_FILE_POLL_INTERVAL);
+            let mut modified = modified_time(&self.path);
+            loop {
+                tokio::select! {
+                    _ = hangups.recv() => info!("Received SIGHUP, reloading the config"),
+                    _ = interval.tick() => {
+                        let last_modified = modified_time(&self.path);
+                        if last_modified == modified {
+                            continue;
+                        }
+                        modified = last_modified;
+                        info!("The config file was modified, reloading the config");
+                    }
+                }
+                match self.reload() {
+                    Ok(changes) if changes.is_empty() => info!("The config is unchanged"),
+                    Ok(changes) => info!("Applied the config changes: {}", changes.join(", ")),
+                    Err(e) => error!(
+                        "Failed to reload the config {:?}, keeping the running one: {}",
+                        self.path, e
+                    ),
+                }
+            }
+        });
+        runtime
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
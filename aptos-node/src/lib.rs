@@ -1,11 +1,11 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_api::runtime::bootstrap as bootstrap_api;
+use aptos_api::{rate_limit::ApiRateLimiter, runtime::bootstrap as bootstrap_api};
 use aptos_config::{
     config::{
         AptosDataClientConfig, DataStreamingServiceConfig, NetworkConfig, NodeConfig,
-        PersistableConfig, StorageServiceConfig,
+        PersistableConfig, RuntimeConfig, StorageServiceConfig,
     },
     network_id::NetworkId,
     utils::get_genesis_txn,
@@ -13,7 +13,9 @@ use aptos_config::{
 use aptos_data_client::aptosnet::AptosNetDataClient;
 use aptos_infallible::RwLock;
 use aptos_logger::{prelude::*, Logger};
-use aptos_metrics::metric_server;
+use aptos_mempool::{MempoolConfigUpdater, TransactionFilter, TransactionTracer};
+use aptos_metrics::{json_metrics::get_git_rev, metric_server, runtime::instrumented_runtime};
+use aptos_telemetry::TelemetryService;
 use aptos_time_service::TimeService;
 use aptos_types::{
     account_config::aptos_root_address,
@@ -26,36 +28,35 @@ use aptos_types::{
 use aptos_vm::AptosVM;
 use aptosdb::AptosDB;
 use backup_service::start_backup_service;
+use config_reloader::ConfigReloader;
 use consensus::consensus_provider::start_consensus;
 use consensus_notifications::ConsensusNotificationListener;
 use data_streaming_service::{
     streaming_client::{new_streaming_service_client_listener_pair, StreamingServiceClient},
     streaming_service::DataStreamingService,
 };
-use debug_interface::node_debug_service::NodeDebugService;
+use debug_interface::{admin_service::AdminService, node_debug_service::NodeDebugService};
 use event_notifications::EventSubscriptionService;
 use executor::{chunk_executor::ChunkExecutor, db_bootstrapper::maybe_bootstrap};
 use futures::channel::mpsc::channel;
 use mempool_notifications::MempoolNotificationSender;
 use network::application::storage::PeerMetadataStorage;
 use network_builder::builder::NetworkBuilder;
+use shutdown::ShutdownCoordinator;
 use state_sync_multiplexer::{
     state_sync_v1_network_config, StateSyncMultiplexer, StateSyncRuntimes,
 };
 use state_sync_v1::network::{StateSyncEvents, StateSyncSender};
 use std::{
     boxed::Box,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     convert::TryFrom,
     io::Write,
     net::ToSocketAddrs,
-    path::PathBuf,
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
+    path::{Path, PathBuf},
+    sync::Arc,
     thread,
-    time::Instant,
+    time::{Duration, Instant},
 };
 use storage_interface::DbReaderWriter;
 use storage_service::start_storage_service_with_db;
@@ -63,25 +64,44 @@ use storage_service_client::{StorageServiceClient, StorageServiceMultiSender};
 use storage_service_server::{
     network::StorageServiceNetworkEvents, StorageReader, StorageServiceServer,
 };
-use tokio::runtime::{Builder, Runtime};
+use tokio::runtime::Runtime;
 use tokio_stream::wrappers::IntervalStream;
 
+mod config_reloader;
+mod shutdown;
+
 const AC_SMP_CHANNEL_BUFFER_SIZE: usize = 1_024;
 const INTRA_NODE_CHANNEL_BUFFER_SIZE: usize = 1;
 const MEMPOOL_NETWORK_CHANNEL_BUFFER_SIZE: usize = 1_024;
 
 pub struct AptosHandle {
-    _api: Runtime,
-    _backup: Runtime,
-    _consensus_runtime: Option<Runtime>,
-    _debug: NodeDebugService,
-    _mempool: Runtime,
-    _network_runtimes: Vec<Runtime>,
-    _state_sync_runtimes: StateSyncRuntimes,
+    admin_service: Option<AdminService>,
+    api: Runtime,
+    backup: Runtime,
+    consensus_runtime: Option<Runtime>,
+    debug: NodeDebugService,
+    mempool: Runtime,
+    network_runtimes: Vec<Runtime>,
+    state_sync_runtimes: StateSyncRuntimes,
+    telemetry: Option<TelemetryService>,
+    aptos_db: Arc<AptosDB>,
+    api_rate_limiter: Arc<ApiRateLimiter>,
+    mempool_config_updater: MempoolConfigUpdater,
 }
 
-pub fn start(config: &NodeConfig, log_file: Option<PathBuf>) {
-    crash_handler::setup_panic_handler();
+/// Starts the node, reloading the config from `config_path` on SIGHUP or file change if given.
+pub fn start(config: &NodeConfig, config_path: Option<&Path>, log_file: Option<PathBuf>) {
+    if config.crash_dumps.enabled {
+        let mut metadata = BTreeMap::new();
+        metadata.insert("build_version".to_string(), get_git_rev());
+        metadata.insert("role".to_string(), config.base.role.to_string());
+        crash_handler::setup_panic_handler_with_crash_dumps(
+            config.crash_dumps.dir(config.data_dir()),
+            metadata,
+        );
+    } else {
+        crash_handler::setup_panic_handler();
+    }
 
     let mut logger = aptos_logger::Logger::new();
     logger
@@ -89,8 +109,30 @@ pub fn start(config: &NodeConfig, log_file: Option<PathBuf>) {
         .is_async(config.logger.is_async)
         .level(config.logger.level)
         .read_env();
+    if config.crash_dumps.enabled {
+        logger.recent_logs(config.crash_dumps.recent_logs);
+    }
+    for (module, level) in &config.logger.module_levels {
+        logger.module_level(module, *level);
+    }
+    if let Some(sampling) = &config.logger.identical_sampling {
+        logger.identical_sampling(
+            sampling.max_per_window,
+            Duration::from_millis(sampling.window_ms),
+        );
+    }
     if let Some(log_file) = log_file {
         logger.printer(Box::new(FileWriter::new(log_file)));
+    } else if let Some(file) = &config.logger.file {
+        logger.printer(Box::new(RotatingFileWriter::new(
+            file.path.clone(),
+            file.max_size_bytes,
+            file.max_age_secs.map(Duration::from_secs),
+            file.max_rotated_files,
+        )));
+        if file.json {
+            logger.custom_format(aptos_logger::json_format);
+        }
     }
     let logger = Some(logger.build());
 
@@ -108,12 +150,22 @@ pub fn start(config: &NodeConfig, log_file: Option<PathBuf>) {
         warn!("failpoints is set in config, but the binary doesn't compile with this feature");
     }
 
-    let _node_handle = setup_environment(config, logger);
-    let term = Arc::new(AtomicBool::new(false));
+    let node_handle = setup_environment(config, logger.clone());
+    let _config_reloader = config_path.map(|path| {
+        ConfigReloader::new(
+            path.to_path_buf(),
+            config.clone(),
+            logger,
+            node_handle.mempool_config_updater.clone(),
+            node_handle.api_rate_limiter.clone(),
+        )
+        .start()
+    });
 
-    while !term.load(Ordering::Acquire) {
-        std::thread::park();
-    }
+    let shutdown_coordinator =
+        ShutdownCoordinator::new(Duration::from_millis(config.base.shutdown_deadline_ms));
+    shutdown_coordinator.wait_for_signal();
+    shutdown_coordinator.shutdown(node_handle);
 }
 
 pub fn load_test_environment<R>(
@@ -188,7 +240,7 @@ pub fn load_test_environment<R>(
     println!("Aptos is running, press ctrl-c to exit");
     println!();
 
-    start(&validators[0].config, Some(log_file))
+    start(&validators[0].config, None, Some(log_file))
 }
 
 pub fn print_api_config(config: &NodeConfig, lazy: bool) {
@@ -227,7 +279,7 @@ fn fetch_chain_id(db: &DbReaderWriter) -> ChainId {
         .chain_id()
 }
 
-fn setup_debug_interface(config: &NodeConfig, logger: Option<Arc<Logger>>) -> NodeDebugService {
+fn setup_debug_interface(config: &NodeConfig) -> NodeDebugService {
     let addr = format!(
         "{}:{}",
         config.debug_interface.address, config.debug_interface.admission_control_node_debug_port,
@@ -237,7 +289,7 @@ fn setup_debug_interface(config: &NodeConfig, logger: Option<Arc<Logger>>) -> No
     .next()
     .unwrap();
 
-    NodeDebugService::new(addr, logger, config)
+    NodeDebugService::new(addr)
 }
 
 fn create_state_sync_runtimes<M: MempoolNotificationSender + 'static>(
@@ -257,6 +309,7 @@ fn create_state_sync_runtimes<M: MempoolNotificationSender + 'static>(
 ) -> StateSyncRuntimes {
     // Start the state sync storage service
     let storage_service_runtime = setup_state_sync_storage_service(
+        &node_config.runtimes,
         node_config.state_sync.storage_service,
         storage_service_server_network_handles,
         &db_rw,
@@ -264,6 +317,7 @@ fn create_state_sync_runtimes<M: MempoolNotificationSender + 'static>(
 
     // Start the data client
     let (aptos_data_client, aptos_data_client_runtime) = setup_aptos_data_client(
+        &node_config.runtimes,
         node_config.state_sync.storage_service,
         node_config.state_sync.aptos_data_client,
         storage_service_client_network_handles,
@@ -272,13 +326,16 @@ fn create_state_sync_runtimes<M: MempoolNotificationSender + 'static>(
 
     // Start the data streaming service
     let (streaming_service_client, streaming_service_runtime) = setup_data_streaming_service(
+        &node_config.runtimes,
         node_config.state_sync.data_streaming_service,
         aptos_data_client.clone(),
     );
 
     // Create the chunk executor
     let chunk_executor = Arc::new(
-        ChunkExecutor::<AptosVM>::new(db_rw.clone()).expect("Unable to create the chunk executor!"),
+        ChunkExecutor::<AptosVM>::new(db_rw.clone())
+            .expect("Unable to create the chunk executor!")
+            .with_chunk_audit(node_config.execution.audit_chunks),
     );
 
     // Create the state sync multiplexer
@@ -286,7 +343,7 @@ fn create_state_sync_runtimes<M: MempoolNotificationSender + 'static>(
         state_sync_network_handles,
         mempool_notifier,
         consensus_listener,
-        db_rw.reader,
+        db_rw,
         chunk_executor,
         node_config,
         waypoint,
@@ -305,6 +362,7 @@ fn create_state_sync_runtimes<M: MempoolNotificationSender + 'static>(
 }
 
 fn setup_data_streaming_service(
+    runtime_config: &RuntimeConfig,
     config: DataStreamingServiceConfig,
     aptos_data_client: AptosNetDataClient,
 ) -> (StreamingServiceClient, Runtime) {
@@ -315,17 +373,18 @@ fn setup_data_streaming_service(
         DataStreamingService::new(config, aptos_data_client, streaming_service_listener);
 
     // Start the data streaming service
-    let streaming_service_runtime = Builder::new_multi_thread()
-        .thread_name("data-streaming-service")
-        .enable_all()
-        .build()
-        .expect("Failed to create data streaming service!");
+    let streaming_service_runtime = instrumented_runtime(
+        "data-streaming-service",
+        runtime_config.state_sync_worker_threads,
+        runtime_config.stall_threshold(),
+    );
     streaming_service_runtime.spawn(data_streaming_service.start_service());
 
     (streaming_service_client, streaming_service_runtime)
 }
 
 fn setup_aptos_data_client(
+    runtime_config: &RuntimeConfig,
     storage_service_config: StorageServiceConfig,
     aptos_data_client_config: AptosDataClientConfig,
     network_handles: HashMap<NetworkId, storage_service_client::StorageServiceNetworkSender>,
@@ -346,27 +405,28 @@ fn setup_aptos_data_client(
     );
 
     // Create a new runtime for the data client and spawn the data poller
-    let aptos_data_client_runtime = Builder::new_multi_thread()
-        .thread_name("aptos-data-client")
-        .enable_all()
-        .build()
-        .expect("Failed to create aptos data client!");
+    let aptos_data_client_runtime = instrumented_runtime(
+        "aptos-data-client",
+        runtime_config.state_sync_worker_threads,
+        runtime_config.stall_threshold(),
+    );
     aptos_data_client_runtime.spawn(data_summary_poller.start());
 
     (aptos_data_client, aptos_data_client_runtime)
 }
 
 fn setup_state_sync_storage_service(
+    runtime_config: &RuntimeConfig,
     config: StorageServiceConfig,
     network_handles: Vec<StorageServiceNetworkEvents>,
     db_rw: &DbReaderWriter,
 ) -> Runtime {
     // Create a new state sync storage service runtime
-    let storage_service_runtime = Builder::new_multi_thread()
-        .thread_name("storage-service-server")
-        .enable_all()
-        .build()
-        .expect("Failed to start the AptosNet storage-service runtime.");
+    let storage_service_runtime = instrumented_runtime(
+        "storage-service-server",
+        runtime_config.state_sync_worker_threads,
+        runtime_config.stall_threshold(),
+    );
 
     // Spawn all state sync storage service servers on the same runtime
     let storage_reader = StorageReader::new(Arc::clone(&db_rw.reader));
@@ -375,6 +435,7 @@ fn setup_state_sync_storage_service(
             config,
             storage_service_runtime.handle().clone(),
             storage_reader.clone(),
+            TimeService::real(),
             events,
         );
         storage_service_runtime.spawn(service.start());
@@ -428,7 +489,24 @@ async fn periodic_state_dump(node_config: NodeConfig, db: DbReaderWriter) {
 }
 
 pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>) -> AptosHandle {
-    let debug_if = setup_debug_interface(node_config, logger);
+    let transaction_filter = Arc::new(
+        TransactionFilter::new(&node_config.mempool.transaction_filter)
+            .expect("Invalid mempool transaction filter"),
+    );
+    let transaction_tracer = Arc::new(TransactionTracer::new(
+        node_config.mempool.max_traced_transactions,
+    ));
+    let admin_service = if node_config.admin_service.enabled {
+        Some(AdminService::new(
+            node_config,
+            logger,
+            transaction_filter.clone(),
+            transaction_tracer.clone(),
+        ))
+    } else {
+        None
+    };
+    let debug_if = setup_debug_interface(node_config);
 
     let metrics_port = node_config.debug_interface.metrics_server_port;
     let metric_host = node_config.debug_interface.address.clone();
@@ -444,9 +522,10 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
         AptosDB::open(
             &node_config.storage.dir(),
             false, /* readonly */
-            node_config.storage.storage_pruner_config,
+            node_config.storage.storage_pruner_config.clone(),
             node_config.storage.rocksdb_config,
             node_config.storage.account_count_migration,
+            node_config.storage.state_value_index,
         )
         .expect("DB should open."),
     );
@@ -522,11 +601,11 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
     let peer_metadata_storage = PeerMetadataStorage::new(&network_ids);
     for network_config in network_configs.into_iter() {
         debug!("Creating runtime for {}", network_config.network_id);
-        let runtime = Builder::new_multi_thread()
-            .thread_name(format!("network-{}", network_config.network_id))
-            .enable_all()
-            .build()
-            .expect("Failed to start runtime. Won't be able to start networking.");
+        let runtime = instrumented_runtime(
+            &format!("network-{}", network_config.network_id),
+            node_config.runtimes.network_worker_threads,
+            node_config.runtimes.stall_threshold(),
+        );
 
         // Entering here gives us a runtime to instantiate all the pieces of the builder
         let _enter = runtime.enter();
@@ -615,13 +694,27 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
 
     let (mp_client_sender, mp_client_events) = channel(AC_SMP_CHANNEL_BUFFER_SIZE);
 
-    let api_runtime = bootstrap_api(node_config, chain_id, aptos_db, mp_client_sender).unwrap();
+    let (api_runtime, api_rate_limiter) = bootstrap_api(
+        node_config,
+        chain_id,
+        aptos_db.clone(),
+        mp_client_sender,
+        peer_metadata_storage.clone(),
+    )
+    .unwrap();
+
+    let telemetry = TelemetryService::start(
+        node_config,
+        chain_id,
+        Arc::clone(&db_rw.reader),
+        peer_metadata_storage.clone(),
+    );
 
     let mut consensus_runtime = None;
     let (consensus_to_mempool_sender, consensus_requests) = channel(INTRA_NODE_CHANNEL_BUFFER_SIZE);
 
     instant = Instant::now();
-    let mempool = aptos_mempool::bootstrap(
+    let (mempool, mempool_config_updater) = aptos_mempool::bootstrap(
         node_config,
         Arc::clone(&db_rw.reader),
         mempool_network_handles,
@@ -630,6 +723,8 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
         mempool_listener,
         mempool_reconfig_subscription,
         peer_metadata_storage.clone(),
+        transaction_filter,
+        transaction_tracer.clone(),
     );
     debug!("Mempool started in {} ms", instant.elapsed().as_millis());
 
@@ -657,6 +752,7 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
             consensus_reconfig_subscription
                 .expect("Consensus requires a reconfiguration subscription!"),
             peer_metadata_storage,
+            transaction_tracer,
         ));
         debug!("Consensus started in {} ms", instant.elapsed().as_millis());
     }
@@ -668,12 +764,17 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
         .spawn(periodic_state_dump(node_config.to_owned(), db_rw));
 
     AptosHandle {
-        _api: api_runtime,
-        _backup: backup_service,
-        _consensus_runtime: consensus_runtime,
-        _debug: debug_if,
-        _mempool: mempool,
-        _network_runtimes: network_runtimes,
-        _state_sync_runtimes: state_sync_runtimes,
+        admin_service,
+        api: api_runtime,
+        backup: backup_service,
+        consensus_runtime,
+        debug: debug_if,
+        mempool,
+        network_runtimes,
+        state_sync_runtimes,
+        telemetry,
+        aptos_db,
+        api_rate_limiter,
+        mempool_config_updater,
     }
 }
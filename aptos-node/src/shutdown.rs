@@ -0,0 +1,103 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::AptosHandle;
+use aptos_logger::prelude::*;
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::{
+    runtime::{Builder, Runtime},
+    signal::unix::{signal, SignalKind},
+};
+
+/// Shuts the node down on SIGTERM or SIGINT, draining its components in dependency order: the
+/// API and mempool stop taking traffic, consensus finishes persisting the block it's committing,
+/// and the DB is flushed. If draining outlasts the deadline, the process exits regardless.
+pub struct ShutdownCoordinator {
+    deadline: Duration,
+}
+
+impl ShutdownCoordinator {
+    pub fn new(deadline: Duration) -> Self {
+        Self { deadline }
+    }
+
+    /// Blocks until the node receives SIGTERM or SIGINT.
+    pub fn wait_for_signal(&self) {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("[shutdown] failed to create runtime");
+        runtime.block_on(async {
+            let mut terminate =
+                signal(SignalKind::terminate()).expect("[shutdown] failed to handle SIGTERM");
+            let mut interrupt =
+                signal(SignalKind::interrupt()).expect("[shutdown] failed to handle SIGINT");
+            tokio::select! {
+                _ = terminate.recv() => info!("Received SIGTERM, shutting down"),
+                _ = interrupt.recv() => info!("Received SIGINT, shutting down"),
+            }
+        });
+    }
+
+    pub fn shutdown(&self, handle: AptosHandle) {
+        let deadline = Instant::now() + self.deadline;
+        let timeout = self.deadline;
+        thread::spawn(move || {
+            thread::sleep(timeout);
+            error!(
+                "Failed to shut down within {} ms, exiting",
+                timeout.as_millis()
+            );
+            aptos_logger::flush();
+            std::process::exit(1);
+        });
+
+        let AptosHandle {
+            admin_service,
+            api,
+            backup,
+            consensus_runtime,
+            debug,
+            mempool,
+            network_runtimes,
+            state_sync_runtimes,
+            telemetry,
+            aptos_db,
+            ..
+        } = handle;
+
+        info!("Stopping the API");
+        stop(api, deadline);
+        info!("Stopping mempool");
+        stop(mempool, deadline);
+        if let Some(consensus_runtime) = consensus_runtime {
+            info!("Stopping consensus once the current commit is persisted");
+            stop(consensus_runtime, deadline);
+        }
+        info!("Stopping state sync and the networks");
+        drop(state_sync_runtimes);
+        for runtime in network_runtimes {
+            stop(runtime, deadline);
+        }
+
+        info!("Flushing storage");
+        stop(backup, deadline);
+        if let Err(e) = aptos_db.flush() {
+            error!(error = ?e, "Failed to flush storage");
+        }
+
+        drop(telemetry);
+        drop(admin_service);
+        drop(debug);
+        info!("Shutdown complete");
+        aptos_logger::flush();
+    }
+}
+
+/// Shuts down the runtime, waiting for the tasks being polled until the deadline at most.
+fn stop(runtime: Runtime, deadline: Instant) {
+    runtime.shutdown_timeout(deadline.saturating_duration_since(Instant::now()));
+}
@@ -222,18 +222,18 @@ impl<S: KVStorage> GenesisBuilder<S> {
         let validators = self.validators()?;
         let move_modules = self.move_modules()?;
 
-        let genesis = vm_genesis::encode_genesis_transaction(
+        let mut builder = vm_genesis::GenesisBuilder::new(
+            move_modules,
+            chain_id,
             aptos_root_key,
             treasury_compliance_key,
-            &validators,
-            &move_modules,
-            publishing_option,
-            consensus_config,
-            chain_id,
-            // TODO: Make this flag configurable via cli command.
-            false,
-        );
-
-        Ok(genesis)
+        )
+        .validators(validators)
+        .consensus_config(consensus_config);
+        if let Some(publishing_option) = publishing_option {
+            builder = builder.vm_publishing_option(publishing_option);
+        }
+        // TODO: Make parallel execution configurable via cli command.
+        builder.build()
     }
 }
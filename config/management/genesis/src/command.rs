@@ -32,6 +32,8 @@ pub enum Command {
     ValidatorConfig(crate::validator_config::ValidatorConfig),
     #[structopt(about = "Verifies and prints the current configuration state")]
     Verify(crate::verify::Verify),
+    #[structopt(about = "Verifies that a genesis blob was built from the shared storage")]
+    VerifyGenesis(crate::genesis::VerifyGenesis),
 }
 
 #[derive(Debug, PartialEq)]
@@ -48,6 +50,7 @@ pub enum CommandName {
     TreasuryComplianceKey,
     ValidatorConfig,
     Verify,
+    VerifyGenesis,
 }
 
 impl From<&Command> for CommandName {
@@ -65,6 +68,7 @@ impl From<&Command> for CommandName {
             Command::TreasuryComplianceKey(_) => CommandName::TreasuryComplianceKey,
             Command::ValidatorConfig(_) => CommandName::ValidatorConfig,
             Command::Verify(_) => CommandName::Verify,
+            Command::VerifyGenesis(_) => CommandName::VerifyGenesis,
         }
     }
 }
@@ -84,6 +88,7 @@ impl std::fmt::Display for CommandName {
             CommandName::TreasuryComplianceKey => "treasury-compliance-key",
             CommandName::ValidatorConfig => "validator-config",
             CommandName::Verify => "verify",
+            CommandName::VerifyGenesis => "verify-genesis",
         };
         write!(f, "{}", name)
     }
@@ -108,6 +113,7 @@ impl Command {
                 .map(|_| "Success!".to_string()),
             Command::ValidatorConfig(_) => self.validator_config().map(|_| "Success!".to_string()),
             Command::Verify(_) => self.verify(),
+            Command::VerifyGenesis(_) => self.verify_genesis(),
         }
     }
 
@@ -162,6 +168,10 @@ impl Command {
     pub fn verify(self) -> Result<String, Error> {
         execute_command!(self, Command::Verify, CommandName::Verify)
     }
+
+    pub fn verify_genesis(self) -> Result<String, Error> {
+        execute_command!(self, Command::VerifyGenesis, CommandName::VerifyGenesis)
+    }
 }
 
 /// These tests depends on running Vault, which can be done by using the provided docker run script
@@ -310,11 +320,34 @@ pub mod tests {
         assert!(!contents.is_empty());
 
         // Step 9) Verify
+        let waypoint = helper.create_waypoint(ChainId::test()).unwrap();
         for ns in [operator_alice_ns, operator_bob_ns, operator_carol_ns].iter() {
-            let waypoint = helper.create_waypoint(ChainId::test()).unwrap();
             helper.insert_waypoint(ns, waypoint).unwrap();
             helper.verify_genesis(ns, genesis_path.path()).unwrap();
         }
+
+        // Step 10) Anyone can rebuild genesis from the shared storage and compare it
+        helper
+            .verify_genesis_from_shared_storage(
+                ChainId::test(),
+                genesis_path.path(),
+                Some(waypoint),
+            )
+            .unwrap();
+        helper
+            .verify_genesis_from_shared_storage(
+                ChainId::test(),
+                genesis_path.path(),
+                Some(Waypoint::default()),
+            )
+            .unwrap_err();
+        helper
+            .verify_genesis_from_shared_storage(
+                ChainId::new(ChainId::test().id() + 1),
+                genesis_path.path(),
+                None,
+            )
+            .unwrap_err();
     }
 
     #[test]
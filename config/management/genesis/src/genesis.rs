@@ -8,6 +8,7 @@ use aptos_types::{
     chain_id::ChainId,
     on_chain_config::{ConsensusConfigV2, OnChainConsensusConfig, VMPublishingOption},
     transaction::Transaction,
+    waypoint::Waypoint,
 };
 use std::{fs::File, io::Write, path::PathBuf};
 use structopt::StructOpt;
@@ -66,3 +67,62 @@ impl Genesis {
         Ok(genesis)
     }
 }
+
+/// Rebuilds genesis from the shared storage and checks that it is identical to a genesis blob, so
+/// that anyone with read access to the shared storage can audit the genesis distributed to the
+/// validators. Optionally checks the waypoint of the genesis as well.
+#[derive(Debug, StructOpt)]
+pub struct VerifyGenesis {
+    #[structopt(flatten)]
+    pub config: ConfigPath,
+    #[structopt(long, required_unless("config"))]
+    pub chain_id: Option<ChainId>,
+    #[structopt(flatten)]
+    pub backend: SharedBackend,
+    /// Path to the genesis blob to verify
+    #[structopt(long)]
+    pub genesis_path: PathBuf,
+    /// The waypoint expected for the genesis
+    #[structopt(long)]
+    pub waypoint: Option<Waypoint>,
+}
+
+impl VerifyGenesis {
+    pub fn execute(self) -> Result<String, Error> {
+        let genesis = Genesis {
+            config: self.config,
+            chain_id: self.chain_id,
+            backend: self.backend,
+            path: None,
+        }
+        .execute()?;
+        let expected_bytes = bcs::to_bytes(&genesis).map_err(|e| {
+            Error::UnexpectedError(format!("Unable to serialize genesis: {}", e.to_string()))
+        })?;
+
+        let bytes = std::fs::read(&self.genesis_path).map_err(|e| {
+            Error::UnableToReadFile(format!("{:?}", self.genesis_path), e.to_string())
+        })?;
+        if bytes != expected_bytes {
+            return Err(Error::UnexpectedError(format!(
+                "Genesis at {:?} does not match the genesis built from the shared storage",
+                self.genesis_path
+            )));
+        }
+
+        let waypoint = crate::waypoint::create_genesis_waypoint(&genesis)?;
+        if let Some(expected_waypoint) = self.waypoint {
+            if waypoint != expected_waypoint {
+                return Err(Error::UnexpectedError(format!(
+                    "Waypoint mismatch, expected: {}, found: {}",
+                    expected_waypoint, waypoint
+                )));
+            }
+        }
+
+        Ok(format!(
+            "Genesis matches the shared storage, waypoint: {}",
+            waypoint
+        ))
+    }
+}
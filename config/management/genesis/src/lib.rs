@@ -23,3 +23,4 @@ mod storage_helper;
 
 #[cfg(any(test, feature = "testing"))]
 pub use crate::config_builder::test_config;
+pub use crate::waypoint::{build_genesis_with_waypoint, create_genesis_waypoint};
@@ -388,4 +388,32 @@ impl StorageHelper {
         let command = Command::from_iter(args.split_whitespace());
         command.verify()
     }
+
+    pub fn verify_genesis_from_shared_storage(
+        &self,
+        chain_id: ChainId,
+        genesis_path: &Path,
+        waypoint: Option<Waypoint>,
+    ) -> Result<String, Error> {
+        let mut args = format!(
+            "
+                aptos-genesis-tool
+                verify-genesis
+                --chain-id {chain_id}
+                --shared-backend backend={backend};\
+                    path={path}
+                --genesis-path {genesis_path}
+            ",
+            chain_id = chain_id,
+            backend = DISK,
+            path = self.path_string(),
+            genesis_path = genesis_path.to_str().expect("Unable to parse genesis_path"),
+        );
+        if let Some(waypoint) = waypoint {
+            args = format!("{} --waypoint {}", args, waypoint);
+        }
+
+        let command = Command::from_iter(args.split_whitespace());
+        command.verify_genesis()
+    }
 }
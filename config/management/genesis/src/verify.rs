@@ -215,6 +215,7 @@ fn compute_genesis(
         NO_OP_STORAGE_PRUNER_CONFIG,
         RocksdbConfig::default(),
         true, /* account_count_migration */
+        true, /* state_value_index */
     )
     .map_err(|e| Error::UnexpectedError(e.to_string()))?;
     let db_rw = DbReaderWriter::new(aptosdb);
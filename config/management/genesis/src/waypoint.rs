@@ -10,6 +10,7 @@ use aptosdb::AptosDB;
 use executor::db_bootstrapper;
 use storage_interface::DbReaderWriter;
 use structopt::StructOpt;
+use vm_genesis::GenesisBuilder;
 
 /// Produces a waypoint from Genesis from the shared storage. It then computes the Waypoint and
 /// optionally inserts it into another storage, typically the validator storage.
@@ -46,6 +47,7 @@ pub fn create_genesis_waypoint(genesis: &Transaction) -> Result<Waypoint, Error>
         NO_OP_STORAGE_PRUNER_CONFIG,
         RocksdbConfig::default(),
         true, /* account_count_migration */
+        true, /* state_value_index */
     )
     .map_err(|e| Error::UnexpectedError(e.to_string()))?;
     let db_rw = DbReaderWriter::new(aptosdb);
@@ -53,3 +55,14 @@ pub fn create_genesis_waypoint(genesis: &Transaction) -> Result<Waypoint, Error>
     db_bootstrapper::generate_waypoint::<AptosVM>(&db_rw, genesis)
         .map_err(|e| Error::UnexpectedError(e.to_string()))
 }
+
+/// Builds the genesis transaction of a network, along with the waypoint its nodes bootstrap from.
+pub fn build_genesis_with_waypoint(
+    builder: &GenesisBuilder,
+) -> Result<(Transaction, Waypoint), Error> {
+    let genesis = builder
+        .build()
+        .map_err(|e| Error::UnexpectedError(e.to_string()))?;
+    let waypoint = create_genesis_waypoint(&genesis)?;
+    Ok((genesis, waypoint))
+}
@@ -2,8 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    account_resource::SimplifiedAccountResource, validator_config::DecodedValidatorConfig,
-    validator_set::DecryptedValidatorInfo, validator_state::VerifyValidatorStateResult,
+    account_resource::SimplifiedAccountResource,
+    storage_checker::StorageHealth,
+    validator_config::{ConsensusKeyRotationPlan, DecodedValidatorConfig},
+    validator_set::DecryptedValidatorInfo,
+    validator_state::VerifyValidatorStateResult,
     TransactionContext,
 };
 use aptos_config::config::Peer;
@@ -23,12 +26,16 @@ pub enum Command {
     AddValidator(crate::governance::AddValidator),
     #[structopt(about = "Check an endpoint for a listening socket")]
     CheckEndpoint(crate::network_checker::CheckEndpoint),
+    #[structopt(about = "Check the health and signing latency of the validator storage")]
+    CheckStorage(crate::storage_checker::CheckStorage),
     #[structopt(about = "Check all on-chain endpoints for a listening socket")]
     CheckValidatorSetEndpoints(crate::network_checker::CheckValidatorSetEndpoints),
     #[structopt(about = "Create a new validator account")]
     CreateValidator(crate::governance::CreateValidator),
     #[structopt(about = "Create a new validator operator account")]
     CreateValidatorOperator(crate::governance::CreateValidatorOperator),
+    #[structopt(about = "Encrypts a plaintext on disk storage into an encrypted one")]
+    EncryptStorage(crate::encrypted_storage::EncryptStorage),
     #[structopt(about = "Extract a trusted peer identity from an x25519 PrivateKey file")]
     ExtractPeerFromFile(crate::keys::ExtractPeerFromFile),
     #[structopt(about = "Extract a trusted peer identity from storage")]
@@ -61,6 +68,8 @@ pub enum Command {
     RotateFullNodeNetworkKey(crate::validator_config::RotateFullNodeNetworkKey),
     #[structopt(about = "Rotates the operator key for the operator")]
     RotateOperatorKey(crate::account_resource::RotateOperatorKey),
+    #[structopt(about = "Rotates the passphrase of an encrypted on disk storage")]
+    RotateStoragePassphrase(crate::encrypted_storage::RotateStoragePassphrase),
     #[structopt(about = "Rotates a validator network key")]
     RotateValidatorNetworkKey(crate::validator_config::RotateValidatorNetworkKey),
     #[structopt(about = "Sets the validator config")]
@@ -82,9 +91,11 @@ pub enum CommandName {
     AccountResource,
     AddValidator,
     CheckEndpoint,
+    CheckStorage,
     CheckValidatorSetEndpoints,
     CreateValidator,
     CreateValidatorOperator,
+    EncryptStorage,
     ExtractPeerFromFile,
     ExtractPeerFromStorage,
     ExtractPeersFromKeys,
@@ -100,6 +111,7 @@ pub enum CommandName {
     RotateConsensusKey,
     RotateOperatorKey,
     RotateFullNodeNetworkKey,
+    RotateStoragePassphrase,
     RotateValidatorNetworkKey,
     SetValidatorConfig,
     SetValidatorOperator,
@@ -115,9 +127,11 @@ impl From<&Command> for CommandName {
             Command::AccountResource(_) => CommandName::AccountResource,
             Command::AddValidator(_) => CommandName::AddValidator,
             Command::CheckEndpoint(_) => CommandName::CheckEndpoint,
+            Command::CheckStorage(_) => CommandName::CheckStorage,
             Command::CheckValidatorSetEndpoints(_) => CommandName::CheckValidatorSetEndpoints,
             Command::CreateValidator(_) => CommandName::CreateValidator,
             Command::CreateValidatorOperator(_) => CommandName::CreateValidatorOperator,
+            Command::EncryptStorage(_) => CommandName::EncryptStorage,
             Command::ExtractPrivateKey(_) => CommandName::ExtractPrivateKey,
             Command::ExtractPublicKey(_) => CommandName::ExtractPublicKey,
             Command::ExtractPeerFromFile(_) => CommandName::ExtractPeerFromFile,
@@ -133,6 +147,7 @@ impl From<&Command> for CommandName {
             Command::RotateConsensusKey(_) => CommandName::RotateConsensusKey,
             Command::RotateOperatorKey(_) => CommandName::RotateOperatorKey,
             Command::RotateFullNodeNetworkKey(_) => CommandName::RotateFullNodeNetworkKey,
+            Command::RotateStoragePassphrase(_) => CommandName::RotateStoragePassphrase,
             Command::RotateValidatorNetworkKey(_) => CommandName::RotateValidatorNetworkKey,
             Command::SetValidatorConfig(_) => CommandName::SetValidatorConfig,
             Command::SetValidatorOperator(_) => CommandName::SetValidatorOperator,
@@ -150,9 +165,11 @@ impl std::fmt::Display for CommandName {
             CommandName::AccountResource => "account-resource",
             CommandName::AddValidator => "add-validator",
             CommandName::CheckEndpoint => "check-endpoint",
+            CommandName::CheckStorage => "check-storage",
             CommandName::CheckValidatorSetEndpoints => "check-validator-set-endpoints",
             CommandName::CreateValidator => "create-validator",
             CommandName::CreateValidatorOperator => "create-validator-operator",
+            CommandName::EncryptStorage => "encrypt-storage",
             CommandName::ExtractPrivateKey => "extract-private-key",
             CommandName::ExtractPublicKey => "extract-public-key",
             CommandName::ExtractPeerFromFile => "extract-peer-from-file",
@@ -168,6 +185,7 @@ impl std::fmt::Display for CommandName {
             CommandName::RotateConsensusKey => "rotate-consensus-key",
             CommandName::RotateOperatorKey => "rotate-operator-key",
             CommandName::RotateFullNodeNetworkKey => "rotate-full-node-network-key",
+            CommandName::RotateStoragePassphrase => "rotate-storage-passphrase",
             CommandName::RotateValidatorNetworkKey => "rotate-validator-network-key",
             CommandName::SetValidatorConfig => "set-validator-config",
             CommandName::SetValidatorOperator => "set-validator-operator",
@@ -186,6 +204,7 @@ impl Command {
             Command::AccountResource(cmd) => Self::pretty_print(cmd.execute().await),
             Command::AddValidator(cmd) => Self::print_transaction_context(cmd.execute().await),
             Command::CheckEndpoint(cmd) => Self::pretty_print(cmd.execute().await),
+            Command::CheckStorage(cmd) => Self::pretty_print(cmd.execute()),
             Command::CheckValidatorSetEndpoints(cmd) => Self::pretty_print(cmd.execute().await),
             Command::CreateValidator(cmd) => {
                 Self::print_transaction_context(cmd.execute().await.map(|(txn_ctx, _)| txn_ctx))
@@ -193,6 +212,7 @@ impl Command {
             Command::CreateValidatorOperator(cmd) => {
                 Self::print_transaction_context(cmd.execute().await.map(|(txn_ctx, _)| txn_ctx))
             }
+            Command::EncryptStorage(cmd) => Self::print_success(cmd.execute()),
             Command::InsertWaypoint(cmd) => Self::print_success(cmd.execute()),
             Command::ExtractPeerFromFile(cmd) => Self::pretty_print(cmd.execute()),
             Command::ExtractPeerFromStorage(cmd) => Self::pretty_print(cmd.execute()),
@@ -205,6 +225,9 @@ impl Command {
             Command::PrintXKey(cmd) => Self::pretty_print(cmd.execute()),
             Command::PrintWaypoint(cmd) => Self::pretty_print(cmd.execute()),
             Command::RemoveValidator(cmd) => Self::print_transaction_context(cmd.execute().await),
+            Command::RotateConsensusKey(cmd) if cmd.is_dry_run() => {
+                Self::pretty_print(cmd.plan().await)
+            }
             Command::RotateConsensusKey(cmd) => {
                 Self::print_transaction_context(cmd.execute().await.map(|(txn_ctx, _)| txn_ctx))
             }
@@ -214,6 +237,7 @@ impl Command {
             Command::RotateFullNodeNetworkKey(cmd) => {
                 Self::print_transaction_context(cmd.execute().await.map(|(txn_ctx, _)| txn_ctx))
             }
+            Command::RotateStoragePassphrase(cmd) => Self::print_success(cmd.execute()),
             Command::RotateValidatorNetworkKey(cmd) => {
                 Self::print_transaction_context(cmd.execute().await.map(|(txn_ctx, _)| txn_ctx))
             }
@@ -290,6 +314,10 @@ impl Command {
         execute_command_await!(self, Command::CheckEndpoint, CommandName::CheckEndpoint)
     }
 
+    pub async fn check_storage(self) -> Result<StorageHealth, Error> {
+        execute_command!(self, Command::CheckStorage, CommandName::CheckStorage)
+    }
+
     pub async fn check_validator_set_endpoints(self) -> Result<String, Error> {
         execute_command_await!(
             self,
@@ -312,6 +340,10 @@ impl Command {
         )
     }
 
+    pub async fn encrypt_storage(self) -> Result<(), Error> {
+        execute_command!(self, Command::EncryptStorage, CommandName::EncryptStorage)
+    }
+
     pub async fn extract_private_key(self) -> Result<(), Error> {
         execute_command!(
             self,
@@ -390,6 +422,17 @@ impl Command {
         )
     }
 
+    pub async fn rotate_consensus_key_plan(self) -> Result<ConsensusKeyRotationPlan, Error> {
+        if let Command::RotateConsensusKey(cmd) = self {
+            cmd.plan().await
+        } else {
+            Err(Error::UnexpectedCommand(
+                CommandName::RotateConsensusKey.to_string(),
+                CommandName::from(&self).to_string(),
+            ))
+        }
+    }
+
     pub async fn rotate_operator_key(
         self,
     ) -> Result<(TransactionContext, Ed25519PublicKey), Error> {
@@ -410,6 +453,14 @@ impl Command {
         )
     }
 
+    pub async fn rotate_storage_passphrase(self) -> Result<(), Error> {
+        execute_command!(
+            self,
+            Command::RotateStoragePassphrase,
+            CommandName::RotateStoragePassphrase
+        )
+    }
+
     pub async fn rotate_validator_network_key(
         self,
     ) -> Result<(TransactionContext, x25519::PublicKey), Error> {
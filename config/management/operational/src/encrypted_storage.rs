@@ -0,0 +1,67 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_config::config::Token;
+use aptos_management::error::Error;
+use aptos_secure_storage::EncryptedOnDiskStorage;
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+
+const ENCRYPTED_STORAGE: &str = "encrypted";
+
+#[derive(Debug, StructOpt)]
+pub struct EncryptStorage {
+    /// Path of the plaintext on disk storage to encrypt, which is left as is
+    #[structopt(long)]
+    plaintext_path: PathBuf,
+    /// Path of the encrypted on disk storage to create
+    #[structopt(long)]
+    path: PathBuf,
+    /// Path of the file holding the passphrase of the encrypted storage
+    #[structopt(long)]
+    passphrase_file: PathBuf,
+}
+
+impl EncryptStorage {
+    pub fn execute(self) -> Result<(), Error> {
+        let passphrase = read_passphrase(&self.passphrase_file)?;
+        EncryptedOnDiskStorage::migrate_from_plaintext(
+            &self.plaintext_path,
+            self.path,
+            &passphrase,
+        )
+        .map_err(|e| Error::StorageWriteError(ENCRYPTED_STORAGE, "storage", e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct RotateStoragePassphrase {
+    /// Path of the encrypted on disk storage
+    #[structopt(long)]
+    path: PathBuf,
+    /// Path of the file holding the current passphrase of the storage
+    #[structopt(long)]
+    passphrase_file: PathBuf,
+    /// Path of the file holding the new passphrase of the storage
+    #[structopt(long)]
+    new_passphrase_file: PathBuf,
+}
+
+impl RotateStoragePassphrase {
+    pub fn execute(self) -> Result<(), Error> {
+        let passphrase = read_passphrase(&self.passphrase_file)?;
+        let new_passphrase = read_passphrase(&self.new_passphrase_file)?;
+        let mut storage = EncryptedOnDiskStorage::new(self.path, &passphrase)
+            .map_err(|e| Error::StorageUnavailable(ENCRYPTED_STORAGE, e.to_string()))?;
+        storage
+            .rotate_master_key(&new_passphrase)
+            .map_err(|e| Error::StorageWriteError(ENCRYPTED_STORAGE, "master key", e.to_string()))
+    }
+}
+
+fn read_passphrase(path: &Path) -> Result<String, Error> {
+    Token::FromDisk(path.to_path_buf())
+        .read_token()
+        .map_err(|e| Error::UnableToReadFile(path.display().to_string(), e.to_string()))
+}
@@ -6,11 +6,13 @@
 mod account_resource;
 mod auto_validate;
 pub mod command;
+mod encrypted_storage;
 mod governance;
 pub mod keys;
 mod owner;
 mod print;
 pub mod rest_client;
+mod storage_checker;
 mod validate_transaction;
 mod validator_config;
 mod validator_set;
@@ -253,6 +253,7 @@ async fn check_endpoint_inner(
         address.clone(),
         remote_peer_id,
         remote_pubkey,
+        address.find_handshake_version().unwrap(),
     )
     .await
     {
@@ -0,0 +1,101 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_crypto::{ed25519::Ed25519PublicKey, Signature};
+use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
+use aptos_global_constants::CONSENSUS_KEY;
+use aptos_management::{config::ConfigPath, error::Error, secure_backend::ValidatorBackend};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use structopt::StructOpt;
+
+const HEALTH_CHECK_MESSAGE: &str = "storage health check";
+
+/// The message signed to check that a key can be used, which can't be mistaken for anything the
+/// validator signs.
+#[derive(BCSCryptoHash, CryptoHasher, Deserialize, Serialize)]
+struct StorageHealthCheck {
+    nonce: u64,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct CheckStorage {
+    #[structopt(flatten)]
+    config: ConfigPath,
+    /// The key name in storage used to sign the health check messages, defaults to the
+    /// consensus key
+    #[structopt(long)]
+    key_name: Option<String>,
+    /// The number of health check messages to sign
+    #[structopt(long, default_value = "10")]
+    num_signatures: u64,
+    #[structopt(flatten)]
+    validator_backend: ValidatorBackend,
+}
+
+/// The outcome of a storage health check. Latencies are in microseconds.
+#[derive(Debug, Serialize)]
+pub struct StorageHealth {
+    pub available_latency_us: u64,
+    pub public_key: Ed25519PublicKey,
+    pub get_public_key_latency_us: u64,
+    pub mean_sign_latency_us: u64,
+    pub max_sign_latency_us: u64,
+}
+
+impl CheckStorage {
+    pub fn execute(self) -> Result<StorageHealth, Error> {
+        if self.num_signatures == 0 {
+            return Err(Error::CommandArgumentError(
+                "At least one message must be signed".to_string(),
+            ));
+        }
+
+        let config = self
+            .config
+            .load()?
+            .override_validator_backend(&self.validator_backend.validator_backend)?;
+        let storage = config.validator_backend();
+        let key_name: &'static str = match self.key_name {
+            Some(key_name) => Box::leak(key_name.into_boxed_str()),
+            None => CONSENSUS_KEY,
+        };
+
+        let (available_latency, result) = timed(|| storage.available());
+        result?;
+        let (get_public_key_latency, public_key) =
+            timed(|| storage.ed25519_public_from_private(key_name));
+        let public_key = public_key?;
+
+        let mut total_sign_latency = Duration::default();
+        let mut max_sign_latency = Duration::default();
+        for nonce in 0..self.num_signatures {
+            let message = StorageHealthCheck { nonce };
+            let (sign_latency, signature) =
+                timed(|| storage.sign_message(key_name, HEALTH_CHECK_MESSAGE, &message));
+            signature?.verify(&message, &public_key).map_err(|e| {
+                Error::UnexpectedError(format!(
+                    "Signature by '{}' doesn't match its public key: {}",
+                    key_name, e
+                ))
+            })?;
+            total_sign_latency += sign_latency;
+            max_sign_latency = max_sign_latency.max(sign_latency);
+        }
+
+        Ok(StorageHealth {
+            available_latency_us: available_latency.as_micros() as u64,
+            public_key,
+            get_public_key_latency_us: get_public_key_latency.as_micros() as u64,
+            mean_sign_latency_us: (total_sign_latency.as_micros() / self.num_signatures as u128)
+                as u64,
+            max_sign_latency_us: max_sign_latency.as_micros() as u64,
+        })
+    }
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (Duration, T) {
+    let start = Instant::now();
+    let result = f();
+    (start.elapsed(), result)
+}
@@ -5,7 +5,8 @@ use crate::{
     account_resource::SimplifiedAccountResource,
     command::{Command, CommandName},
     keys::{load_key, EncodingType, KeyType},
-    validator_config::DecodedValidatorConfig,
+    storage_checker::StorageHealth,
+    validator_config::{ConsensusKeyRotationPlan, DecodedValidatorConfig},
     validator_set::DecryptedValidatorInfo,
     validator_state::VerifyValidatorStateResult,
     TransactionContext,
@@ -109,6 +110,28 @@ impl OperationalTool {
             .await
     }
 
+    pub async fn check_storage(
+        &self,
+        key_name: &str,
+        num_signatures: u64,
+        backend: &config::SecureBackend,
+    ) -> Result<StorageHealth, Error> {
+        let args = format!(
+            "
+                {command}
+                --key-name {key_name}
+                --num-signatures {num_signatures}
+                --validator-backend {backend_args}
+            ",
+            command = command(TOOL_NAME, CommandName::CheckStorage),
+            key_name = key_name,
+            num_signatures = num_signatures,
+            backend_args = backend_args(backend)?,
+        );
+        let command = Command::from_iter(args.split_whitespace());
+        command.check_storage().await
+    }
+
     pub fn create_account(
         &self,
         name: &str,
@@ -473,6 +496,28 @@ impl OperationalTool {
             .await
     }
 
+    /// Plans the rotation of the consensus key, without modifying storage or the blockchain
+    pub async fn rotate_consensus_key_dry_run(
+        &self,
+        backend: &config::SecureBackend,
+    ) -> Result<ConsensusKeyRotationPlan, Error> {
+        let args = format!(
+            "
+                {command}
+                --chain-id {chain_id}
+                --json-server {host}
+                --validator-backend {backend_args}
+                --dry-run
+            ",
+            command = command(TOOL_NAME, CommandName::RotateConsensusKey),
+            host = self.host,
+            chain_id = self.chain_id.id(),
+            backend_args = backend_args(backend)?,
+        );
+        let command = Command::from_iter(args.split_whitespace());
+        command.rotate_consensus_key_plan().await
+    }
+
     pub async fn rotate_operator_key(
         &self,
         backend: &config::SecureBackend,
@@ -1,7 +1,9 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{auto_validate::AutoValidate, rest_client::RestClient, TransactionContext};
+use crate::{
+    auto_validate::AutoValidate, rest_client::RestClient, TransactionContext, TransactionStatus,
+};
 use aptos_crypto::{ed25519::Ed25519PublicKey, x25519};
 use aptos_global_constants::{
     CONSENSUS_KEY, FULLNODE_NETWORK_KEY, OPERATOR_ACCOUNT, OWNER_ACCOUNT, VALIDATOR_NETWORK_KEY,
@@ -12,7 +14,7 @@ use aptos_types::{
     network_address::{NetworkAddress, Protocol},
 };
 use serde::Serialize;
-use std::{convert::TryFrom, str::FromStr};
+use std::{convert::TryFrom, str::FromStr, time::Duration};
 use structopt::StructOpt;
 
 // TODO: Load all chain IDs from the host
@@ -198,15 +200,189 @@ impl RotateKey {
     }
 }
 
+/// Rotates the consensus key in storage and on-chain, then waits for the transaction to be
+/// executed and for the reconfiguration that puts the new key in the validator set.
 #[derive(Debug, StructOpt)]
 pub struct RotateConsensusKey {
     #[structopt(flatten)]
     rotate_key: RotateKey,
+    #[structopt(
+        long,
+        help = "Prints the rotation that would be performed, without modifying storage or the blockchain"
+    )]
+    dry_run: bool,
+    #[structopt(
+        long,
+        help = "The timeout in seconds for the transaction to be executed, and then for the validator set to reflect the new key",
+        default_value = "30"
+    )]
+    reconfiguration_timeout: u64,
 }
 
 impl RotateConsensusKey {
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
     pub async fn execute(self) -> Result<(TransactionContext, Ed25519PublicKey), Error> {
-        self.rotate_key.execute(CONSENSUS_KEY).await
+        if self.dry_run {
+            return Err(Error::CommandArgumentError(
+                "A dry run only plans the rotation of the key".into(),
+            ));
+        }
+
+        let config = self
+            .rotate_key
+            .validator_config
+            .config()?
+            .override_json_server(&self.rotate_key.json_server);
+        let owner_account = config.validator_backend().account_address(OWNER_ACCOUNT)?;
+        let (mut transaction_context, consensus_key) =
+            self.rotate_key.execute(CONSENSUS_KEY).await?;
+        let client = RestClient::new(config.json_server);
+
+        // The key is already rotated in storage, so the rotation is only complete once the
+        // transaction is executed (whether or not auto validation is enabled) and succeeded.
+        if transaction_context.execution_result.is_none() {
+            let status =
+                wait_for_transaction(&client, &transaction_context, self.reconfiguration_timeout)
+                    .await?;
+            transaction_context.execution_result = Some(status);
+        }
+        if let Some(status) = transaction_context
+            .execution_result
+            .as_ref()
+            .filter(|status| !status.success)
+        {
+            return Err(Error::UnexpectedError(format!(
+                "The transaction setting the new consensus key failed: {}",
+                status.message
+            )));
+        }
+
+        wait_for_validator_set(
+            &client,
+            owner_account,
+            &consensus_key,
+            self.reconfiguration_timeout,
+        )
+        .await?;
+        Ok((transaction_context, consensus_key))
+    }
+
+    /// Describes the rotation that would be performed, without modifying storage or the
+    /// blockchain.
+    pub async fn plan(self) -> Result<ConsensusKeyRotationPlan, Error> {
+        let config = self
+            .rotate_key
+            .validator_config
+            .config()?
+            .override_json_server(&self.rotate_key.json_server);
+        let storage = config.validator_backend();
+        let client = RestClient::new(config.json_server.clone());
+
+        let owner_account = storage.account_address(OWNER_ACCOUNT)?;
+        let storage_key = storage.ed25519_public_from_private(CONSENSUS_KEY)?;
+        let on_chain_key = client
+            .validator_config(owner_account)
+            .await
+            .and_then(|vc| DecodedValidatorConfig::from_validator_config_resource(&vc))?
+            .consensus_public_key;
+        let validator_set_key = validator_set_consensus_key(&client, owner_account).await?;
+
+        Ok(ConsensusKeyRotationPlan {
+            owner_account,
+            generates_new_key: storage_key == on_chain_key,
+            storage_key,
+            on_chain_key,
+            validator_set_key,
+        })
+    }
+}
+
+/// The rotation of the consensus key that `rotate-consensus-key` would perform
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ConsensusKeyRotationPlan {
+    pub owner_account: AccountAddress,
+    /// The key in storage, which is submitted on-chain if it isn't already
+    pub storage_key: Ed25519PublicKey,
+    /// The key in the validator config on-chain
+    pub on_chain_key: Ed25519PublicKey,
+    /// The key in the validator set, if the validator is in it
+    pub validator_set_key: Option<Ed25519PublicKey>,
+    /// Whether a new key is generated in storage, which only happens if the storage key is
+    /// already on-chain. Otherwise the storage key is submitted to resynchronize the config.
+    pub generates_new_key: bool,
+}
+
+/// Returns the consensus key of the validator in the validator set, or None if the validator
+/// isn't in the set.
+async fn validator_set_consensus_key(
+    client: &RestClient,
+    owner_account: AccountAddress,
+) -> Result<Option<Ed25519PublicKey>, Error> {
+    Ok(client
+        .validator_set(None)
+        .await?
+        .iter()
+        .find(|info| info.account_address() == &owner_account)
+        .map(|info| info.consensus_public_key().clone()))
+}
+
+/// Waits until the transaction is executed and returns its execution result
+async fn wait_for_transaction(
+    client: &RestClient,
+    transaction_context: &TransactionContext,
+    timeout_secs: u64,
+) -> Result<TransactionStatus, Error> {
+    let mut time_slept = 0;
+    loop {
+        if let Some(status) = client
+            .transaction_status(
+                transaction_context.address,
+                transaction_context.sequence_number,
+            )
+            .await?
+        {
+            return Ok(status);
+        }
+        if time_slept >= timeout_secs {
+            return Err(Error::Timeout(
+                "Waiting for the transaction",
+                format!(
+                    "The transaction {}:{} isn't executed after {} seconds",
+                    transaction_context.address, transaction_context.sequence_number, timeout_secs
+                ),
+            ));
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        time_slept += 1;
+    }
+}
+
+/// Waits until the validator set holds the consensus key of the validator, which happens upon the
+/// reconfiguration following the update of its config. A validator outside of the set has nothing
+/// to wait for, it joins with the key in its config.
+async fn wait_for_validator_set(
+    client: &RestClient,
+    owner_account: AccountAddress,
+    consensus_key: &Ed25519PublicKey,
+    timeout_secs: u64,
+) -> Result<(), Error> {
+    let mut time_slept = 0;
+    loop {
+        match validator_set_consensus_key(client, owner_account).await? {
+            Some(key) if &key != consensus_key => (),
+            _ => return Ok(()),
+        }
+        if time_slept >= timeout_secs {
+            return Err(Error::UnexpectedError(format!(
+                "The validator set doesn't hold the new consensus key after {} seconds",
+                timeout_secs
+            )));
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        time_slept += 1;
     }
 }
 
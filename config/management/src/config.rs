@@ -123,10 +123,17 @@ impl Config {
     pub fn shared_backend_with_namespace(&self, namespace: String) -> StorageWrapper {
         let mut shared_backend = self.shared_backend.clone();
         match &mut shared_backend {
+            config::SecureBackend::EncryptedOnDiskStorage(config) => {
+                config.namespace = Some(namespace)
+            }
             config::SecureBackend::GitHub(config) => config.namespace = Some(namespace),
             config::SecureBackend::InMemoryStorage => panic!("Unsupported namespace for InMemory"),
             config::SecureBackend::Vault(config) => config.namespace = Some(namespace),
             config::SecureBackend::OnDiskStorage(config) => config.namespace = Some(namespace),
+            config::SecureBackend::Pkcs11(config) => config.namespace = Some(namespace),
+            config::SecureBackend::S3(config) => config.namespace = Some(namespace),
+            config::SecureBackend::AwsKms(config) => config.namespace = Some(namespace),
+            config::SecureBackend::GcpKms(config) => config.namespace = Some(namespace),
         };
         StorageWrapper {
             storage_name: "shared",
@@ -174,7 +181,10 @@ mod tests {
                 namespace: None,
                 server: "127.0.0.1:8200".to_string(),
                 ca_certificate: None,
-                token: Token::FromConfig("test".to_string()),
+                token: Some(Token::FromConfig("test".to_string())),
+                kubernetes_auth: None,
+                vault_namespace: None,
+                cache_ttl_ms: None,
                 renew_ttl_secs: None,
                 disable_cas: None,
                 connection_timeout_ms: None,
@@ -184,7 +194,10 @@ mod tests {
                 namespace: None,
                 server: "127.0.0.1:8200".to_string(),
                 ca_certificate: None,
-                token: Token::FromConfig("test".to_string()),
+                token: Some(Token::FromConfig("test".to_string())),
+                kubernetes_auth: None,
+                vault_namespace: None,
+                cache_ttl_ms: None,
                 renew_ttl_secs: None,
                 disable_cas: None,
                 connection_timeout_ms: None,
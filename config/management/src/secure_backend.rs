@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::error::Error;
-use aptos_config::config::{self, GitHubConfig, OnDiskStorageConfig, Token, VaultConfig};
+use aptos_config::config::{
+    self, AwsKmsConfig, EncryptedOnDiskStorageConfig, GcpKmsConfig, GitHubConfig,
+    OnDiskStorageConfig, Pkcs11Config, S3Config, Token, VaultConfig,
+};
 use std::{
     collections::HashMap,
     convert::{TryFrom, TryInto},
@@ -11,10 +14,15 @@ use std::{
 };
 use structopt::StructOpt;
 
+pub const AWS_KMS: &str = "aws_kms";
 pub const BACKEND: &str = "backend";
 pub const DISK: &str = "disk";
+pub const ENCRYPTED_DISK: &str = "encrypted_disk";
+pub const GCP_KMS: &str = "gcp_kms";
 pub const GITHUB: &str = "github";
 pub const MEMORY: &str = "memory";
+pub const PKCS11: &str = "pkcs11";
+pub const S3: &str = "s3";
 pub const VAULT: &str = "vault";
 
 // Custom timeouts for vault backend operations when using the management tooling.
@@ -80,6 +88,23 @@ impl TryInto<config::SecureBackend> for SecureBackend {
                 config.namespace = self.parameters.remove("namespace");
                 config::SecureBackend::OnDiskStorage(config)
             }
+            ENCRYPTED_DISK => {
+                let path = self
+                    .parameters
+                    .remove("path")
+                    .ok_or_else(|| Error::BackendParsingError("missing path".into()))?;
+                let passphrase = self
+                    .parameters
+                    .remove("passphrase")
+                    .ok_or_else(|| Error::BackendParsingError("missing passphrase".into()))?;
+                let mut config = EncryptedOnDiskStorageConfig::new(
+                    PathBuf::from(path),
+                    Token::FromDisk(PathBuf::from(passphrase)),
+                );
+                config.set_data_dir(PathBuf::from(""));
+                config.namespace = self.parameters.remove("namespace");
+                config::SecureBackend::EncryptedOnDiskStorage(config)
+            }
             GITHUB => {
                 let repository_owner = self
                     .parameters
@@ -103,6 +128,102 @@ impl TryInto<config::SecureBackend> for SecureBackend {
                 })
             }
             MEMORY => config::SecureBackend::InMemoryStorage,
+            PKCS11 => {
+                let library = self
+                    .parameters
+                    .remove("library")
+                    .ok_or_else(|| Error::BackendParsingError("missing library".into()))?;
+                let token_label = self
+                    .parameters
+                    .remove("token_label")
+                    .ok_or_else(|| Error::BackendParsingError("missing token label".into()))?;
+                let pin = self
+                    .parameters
+                    .remove("pin")
+                    .ok_or_else(|| Error::BackendParsingError("missing pin".into()))?;
+                config::SecureBackend::Pkcs11(Pkcs11Config {
+                    namespace: self.parameters.remove("namespace"),
+                    library: PathBuf::from(library),
+                    token_label,
+                    pin: Token::FromDisk(PathBuf::from(pin)),
+                })
+            }
+            S3 => {
+                let bucket = self
+                    .parameters
+                    .remove("bucket")
+                    .ok_or_else(|| Error::BackendParsingError("missing bucket".into()))?;
+                let region = self
+                    .parameters
+                    .remove("region")
+                    .ok_or_else(|| Error::BackendParsingError("missing region".into()))?;
+                let access_key_id = self
+                    .parameters
+                    .remove("access_key_id")
+                    .ok_or_else(|| Error::BackendParsingError("missing access key id".into()))?;
+                let secret_access_key =
+                    self.parameters.remove("secret_access_key").ok_or_else(|| {
+                        Error::BackendParsingError("missing secret access key".into())
+                    })?;
+                config::SecureBackend::S3(S3Config {
+                    namespace: self.parameters.remove("namespace"),
+                    bucket,
+                    region,
+                    endpoint: self.parameters.remove("endpoint"),
+                    access_key_id,
+                    secret_access_key: Token::FromDisk(PathBuf::from(secret_access_key)),
+                })
+            }
+            AWS_KMS => {
+                let region = self
+                    .parameters
+                    .remove("region")
+                    .ok_or_else(|| Error::BackendParsingError("missing region".into()))?;
+                let access_key_id = self
+                    .parameters
+                    .remove("access_key_id")
+                    .ok_or_else(|| Error::BackendParsingError("missing access key id".into()))?;
+                let secret_access_key =
+                    self.parameters.remove("secret_access_key").ok_or_else(|| {
+                        Error::BackendParsingError("missing secret access key".into())
+                    })?;
+                config::SecureBackend::AwsKms(AwsKmsConfig {
+                    namespace: self.parameters.remove("namespace"),
+                    region,
+                    endpoint: self.parameters.remove("endpoint"),
+                    access_key_id,
+                    secret_access_key: Token::FromDisk(PathBuf::from(secret_access_key)),
+                    session_token: self
+                        .parameters
+                        .remove("session_token")
+                        .map(|token| Token::FromDisk(PathBuf::from(token))),
+                })
+            }
+            GCP_KMS => {
+                let project = self
+                    .parameters
+                    .remove("project")
+                    .ok_or_else(|| Error::BackendParsingError("missing project".into()))?;
+                let location = self
+                    .parameters
+                    .remove("location")
+                    .ok_or_else(|| Error::BackendParsingError("missing location".into()))?;
+                let key_ring = self
+                    .parameters
+                    .remove("key_ring")
+                    .ok_or_else(|| Error::BackendParsingError("missing key ring".into()))?;
+                config::SecureBackend::GcpKms(GcpKmsConfig {
+                    namespace: self.parameters.remove("namespace"),
+                    project,
+                    location,
+                    key_ring,
+                    protection_level: self.parameters.remove("protection_level"),
+                    token: self
+                        .parameters
+                        .remove("token")
+                        .map(|token| Token::FromDisk(PathBuf::from(token))),
+                })
+            }
             VAULT => {
                 let certificate = self.parameters.remove("ca_certificate").map(PathBuf::from);
                 let server = self
@@ -117,7 +238,10 @@ impl TryInto<config::SecureBackend> for SecureBackend {
                     namespace: self.parameters.remove("namespace"),
                     server,
                     ca_certificate: certificate,
-                    token: Token::FromDisk(PathBuf::from(token)),
+                    token: Some(Token::FromDisk(PathBuf::from(token))),
+                    kubernetes_auth: None,
+                    vault_namespace: self.parameters.remove("vault_namespace"),
+                    cache_ttl_ms: None,
                     renew_ttl_secs: None,
                     disable_cas: Some(true),
                     connection_timeout_ms: Some(CONNECTION_TIMEOUT_MS),
@@ -154,11 +278,27 @@ pair: "k0=v0;k1=v1;...".  The current supported formats are:
     Vault: "backend=vault;server=URL;token=PATH_TO_TOKEN"
         an optional namespace: "namespace=NAMESPACE"
         an optional server certificate: "ca_certificate=PATH_TO_CERT"
+        an optional Vault Enterprise namespace: "vault_namespace=VAULT_NAMESPACE"
     GitHub: "backend=github;repository_owner=REPOSITORY_OWNER;repository=REPOSITORY;token=PATH_TO_TOKEN"
         an optional branch: "branch=BRANCH", defaults to master
         an optional namespace: "namespace=NAMESPACE"
     InMemory: "backend=memory"
     OnDisk: "backend=disk;path=LOCAL_PATH"
+    EncryptedOnDisk: "backend=encrypted_disk;path=LOCAL_PATH;passphrase=PATH_TO_PASSPHRASE"
+        an optional namespace: "namespace=NAMESPACE"
+    PKCS#11: "backend=pkcs11;library=PATH_TO_LIBRARY;token_label=LABEL;pin=PATH_TO_PIN"
+        an optional namespace: "namespace=NAMESPACE"
+    S3: "backend=s3;bucket=BUCKET;region=REGION;access_key_id=ACCESS_KEY_ID;secret_access_key=PATH_TO_SECRET_ACCESS_KEY"
+        an optional endpoint: "endpoint=URL", defaults to AWS in the region
+        an optional namespace: "namespace=NAMESPACE"
+    AWS KMS: "backend=aws_kms;region=REGION;access_key_id=ACCESS_KEY_ID;secret_access_key=PATH_TO_SECRET_ACCESS_KEY"
+        an optional session token: "session_token=PATH_TO_SESSION_TOKEN"
+        an optional endpoint: "endpoint=URL", defaults to AWS in the region
+        an optional namespace: "namespace=NAMESPACE"
+    Google Cloud KMS: "backend=gcp_kms;project=PROJECT;location=LOCATION;key_ring=KEY_RING"
+        an optional access token: "token=PATH_TO_TOKEN", defaults to the metadata server
+        an optional protection level: "protection_level=SOFTWARE", defaults to HSM
+        an optional namespace: "namespace=NAMESPACE"
                 "#)
             )]
             pub $field_name: Option<SecureBackend>,
@@ -212,6 +352,20 @@ mod tests {
         assert!(storage(disk).is_err());
     }
 
+    #[test]
+    fn test_encrypted_disk() {
+        let encrypted_disk = "backend=encrypted_disk;path=storage.enc;passphrase=/passphrase";
+        let backend = storage(encrypted_disk).unwrap();
+        assert_eq!(backend.namespace(), None);
+
+        let encrypted_disk = format!("{};namespace=test", encrypted_disk);
+        let backend = storage(&encrypted_disk).unwrap();
+        assert_eq!(backend.namespace(), Some("test"));
+
+        let encrypted_disk = "backend=encrypted_disk;path=storage.enc";
+        storage(encrypted_disk).unwrap_err();
+    }
+
     #[test]
     fn test_github() {
         let path = aptos_temppath::TempPath::new();
@@ -263,10 +417,85 @@ mod tests {
         );
         storage(&vault).unwrap();
 
+        let vault = format!(
+            "backend=vault;server=http://127.0.0.1:8080;token={};vault_namespace=test",
+            path_str
+        );
+        storage(&vault).unwrap();
+
         let vault = "backend=vault";
         storage(vault).unwrap_err();
     }
 
+    #[test]
+    fn test_pkcs11() {
+        let pkcs11 =
+            "backend=pkcs11;library=/usr/lib/yubihsm_pkcs11.so;token_label=validator;pin=/pin";
+        let backend = storage(pkcs11).unwrap();
+        assert_eq!(backend.namespace(), None);
+
+        let pkcs11 = format!("{};namespace=test", pkcs11);
+        let backend = storage(&pkcs11).unwrap();
+        assert_eq!(backend.namespace(), Some("test"));
+
+        let pkcs11 = "backend=pkcs11;library=/usr/lib/yubihsm_pkcs11.so";
+        storage(pkcs11).unwrap_err();
+    }
+
+    #[test]
+    fn test_s3() {
+        let path = aptos_temppath::TempPath::new();
+        path.create_as_file().unwrap();
+        let mut file = File::create(path.path()).unwrap();
+        file.write_all(b"secret_access_key").unwrap();
+        let path_str = path.path().to_str().unwrap();
+
+        let s3 = format!(
+            "backend=s3;bucket=genesis;region=us-west-2;access_key_id=ID;secret_access_key={}",
+            path_str
+        );
+        let backend = storage(&s3).unwrap();
+        assert_eq!(backend.namespace(), None);
+
+        let s3 = format!("{};endpoint=http://127.0.0.1:9000;namespace=test", s3);
+        let backend = storage(&s3).unwrap();
+        assert_eq!(backend.namespace(), Some("test"));
+
+        let s3 = "backend=s3;bucket=genesis;region=us-west-2";
+        storage(s3).unwrap_err();
+    }
+
+    #[test]
+    fn test_aws_kms() {
+        let aws_kms = "backend=aws_kms;region=us-west-2;access_key_id=ID;secret_access_key=/secret";
+        let backend = storage(aws_kms).unwrap();
+        assert_eq!(backend.namespace(), None);
+
+        let aws_kms = format!("{};session_token=/session_token;namespace=test", aws_kms);
+        let backend = storage(&aws_kms).unwrap();
+        assert_eq!(backend.namespace(), Some("test"));
+
+        let aws_kms = "backend=aws_kms;region=us-west-2";
+        storage(aws_kms).unwrap_err();
+    }
+
+    #[test]
+    fn test_gcp_kms() {
+        let gcp_kms = "backend=gcp_kms;project=validator;location=us-west1;key_ring=keys";
+        let backend = storage(gcp_kms).unwrap();
+        assert_eq!(backend.namespace(), None);
+
+        let gcp_kms = format!(
+            "{};token=/token;protection_level=SOFTWARE;namespace=test",
+            gcp_kms
+        );
+        let backend = storage(&gcp_kms).unwrap();
+        assert_eq!(backend.namespace(), Some("test"));
+
+        let gcp_kms = "backend=gcp_kms;project=validator";
+        storage(gcp_kms).unwrap_err();
+    }
+
     fn storage(s: &str) -> Result<config::SecureBackend, Error> {
         let management_backend: SecureBackend = s.try_into()?;
         management_backend.try_into()
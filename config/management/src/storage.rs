@@ -3,7 +3,8 @@
 
 use crate::error::Error;
 use aptos_crypto::{
-    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    hash::CryptoHash,
     x25519,
 };
 use aptos_secure_storage::{CryptoStorage, KVStorage, Storage};
@@ -28,6 +29,13 @@ impl StorageWrapper {
         }
     }
 
+    /// Checks that the storage is online and available
+    pub fn available(&self) -> Result<(), Error> {
+        self.storage
+            .available()
+            .map_err(|e| Error::StorageUnavailable(self.storage_name, e.to_string()))
+    }
+
     pub fn value<T: DeserializeOwned>(&self, name: &'static str) -> Result<T, Error> {
         self.storage
             .get(name)
@@ -143,6 +151,18 @@ impl StorageWrapper {
         ))
     }
 
+    /// Sign an arbitrary message
+    pub fn sign_message<T: CryptoHash + Serialize>(
+        &self,
+        key_name: &'static str,
+        message_name: &'static str,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        self.storage.sign(key_name, message).map_err(|e| {
+            Error::StorageSigningError(self.storage_name, message_name, key_name, e.to_string())
+        })
+    }
+
     /// Sign a transaction with the given version
     pub fn sign_using_version(
         &mut self,
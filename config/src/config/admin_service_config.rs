@@ -0,0 +1,63 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::utils;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The name under which calls authenticated by `authentication_token` are logged
+pub const DEFAULT_ADMIN_CALLER: &str = "default";
+
+/// The admin service inspects and reconfigures a running node. It only listens on localhost, and
+/// every request must be authenticated, by a bearer token or by a TLS client certificate.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct AdminServiceConfig {
+    pub enabled: bool,
+    pub port: u16,
+    // A bearer token, whose calls are logged under the "default" caller
+    pub authentication_token: Option<String>,
+    // Bearer tokens by the name of their caller, which is logged with each of their calls
+    pub authentication_tokens: BTreeMap<String, String>,
+    // The service is served over TLS when set
+    pub tls_cert_path: Option<String>,
+    pub tls_key_path: Option<String>,
+    // Clients must present a certificate signed by this CA when set. Without any bearer token,
+    // the certificate alone authenticates them.
+    pub tls_client_ca_path: Option<String>,
+}
+
+impl Default for AdminServiceConfig {
+    fn default() -> AdminServiceConfig {
+        AdminServiceConfig {
+            enabled: false,
+            port: 9103,
+            authentication_token: None,
+            authentication_tokens: BTreeMap::new(),
+            tls_cert_path: None,
+            tls_key_path: None,
+            tls_client_ca_path: None,
+        }
+    }
+}
+
+impl AdminServiceConfig {
+    pub fn randomize_ports(&mut self) {
+        self.port = utils::get_available_port();
+    }
+
+    /// All the bearer tokens accepted by the service, by the name of their caller
+    pub fn tokens(&self) -> BTreeMap<String, String> {
+        let mut tokens = self.authentication_tokens.clone();
+        if let Some(token) = &self.authentication_token {
+            tokens.insert(DEFAULT_ADMIN_CALLER.to_string(), token.clone());
+        }
+        tokens
+    }
+
+    /// Removes the bearer tokens, before the config is exposed
+    pub fn redact_tokens(&mut self) {
+        self.authentication_token = None;
+        self.authentication_tokens.clear();
+    }
+}
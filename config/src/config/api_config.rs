@@ -18,6 +18,9 @@ pub struct ApiConfig {
     // optional for compatible with old configuration
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub content_length_limit: Option<u64>,
+    // Requests are rate limited per client IP when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<ApiRateLimitConfig>,
 }
 
 pub const DEFAULT_ADDRESS: &str = "127.0.0.1";
@@ -38,6 +41,7 @@ impl Default for ApiConfig {
             tls_cert_path: None,
             tls_key_path: None,
             content_length_limit: None,
+            rate_limit: None,
         }
     }
 }
@@ -54,3 +58,21 @@ impl ApiConfig {
         }
     }
 }
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct ApiRateLimitConfig {
+    // Requests allowed per second for each client IP
+    pub requests_per_sec: usize,
+    // Requests a client can make at once above the rate
+    pub burst_size: usize,
+}
+
+impl Default for ApiRateLimitConfig {
+    fn default() -> ApiRateLimitConfig {
+        ApiRateLimitConfig {
+            requests_per_sec: 100,
+            burst_size: 200,
+        }
+    }
+}
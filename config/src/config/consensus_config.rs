@@ -26,6 +26,20 @@ pub struct ConsensusConfig {
     // the period = (poll_count - 1) * 30ms
     pub mempool_poll_count: u64,
     pub channel_size: usize,
+    // If set, proposals are disseminated over broadcast trees with this fanout: the proposer
+    // only sends its proposal to `fanout` validators, which relay it further. All validators
+    // must run a version that relays proposals, with the same fanout, before this is enabled.
+    pub proposal_broadcast_fanout: Option<u64>,
+    // How long a validator waits for a child in the broadcast tree to acknowledge a relayed
+    // proposal before sending the proposal to the child's subtree directly (in milliseconds)
+    pub proposal_relay_timeout_ms: u64,
+    // Proposals with a timestamp further ahead of the local clock than this are rejected (in
+    // milliseconds)
+    pub max_proposal_timestamp_drift_ms: u64,
+    // The order in which the messages broadcast to all validators (e.g. proposals and timeout
+    // votes) are sent. Sending to the closest validators first can lower the time to a quorum in
+    // globally distributed validator sets. Round trip times are measured by the peer monitor.
+    pub broadcast_order: BroadcastOrder,
 }
 
 impl Default for ConsensusConfig {
@@ -45,6 +59,10 @@ impl Default for ConsensusConfig {
             sync_only: false,
             mempool_poll_count: 20,
             channel_size: 30, // hard-coded
+            proposal_broadcast_fanout: None,
+            proposal_relay_timeout_ms: 500,
+            max_proposal_timestamp_drift_ms: 5000,
+            broadcast_order: BroadcastOrder::ValidatorSet,
         }
     }
 }
@@ -64,14 +82,37 @@ pub enum ConsensusProposerType {
     RotatingProposer,
     // Committed history based proposer election
     LeaderReputation(LeaderReputationConfig),
+    // Committed history based proposer election, with round robin rounds guaranteeing every
+    // proposer at least one round per fairness window
+    FairLeaderReputation(FairLeaderReputationConfig),
     // Pre-specified proposers for each round,
     // or default proposer if round proposer not
     // specified
     RoundProposer(HashMap<Round, AccountAddress>),
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BroadcastOrder {
+    // The order of the validator set
+    ValidatorSet,
+    // The validators with the lowest measured round trip time first, the ones without a
+    // measurement last
+    ClosestFirst,
+    // The proposer of the next round first, then the other validators closest first
+    LeaderFirst,
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct LeaderReputationConfig {
     pub active_weights: u64,
     pub inactive_weights: u64,
 }
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FairLeaderReputationConfig {
+    pub leader_reputation: LeaderReputationConfig,
+    // Number of rounds in which every proposer is elected at least once, regardless of its
+    // reputation. Should be at least the number of validators.
+    pub fairness_window: u64,
+}
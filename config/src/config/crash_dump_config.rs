@@ -0,0 +1,35 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// When the node panics, a crash dump with the backtrace, the recent logs and the metadata of the
+/// node is written locally, for bug reports.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct CrashDumpConfig {
+    pub enabled: bool,
+    // Defaults to the crash_dumps directory in the data directory
+    pub dir: Option<PathBuf>,
+    // Number of the most recent logs included in the dumps
+    pub recent_logs: usize,
+}
+
+impl Default for CrashDumpConfig {
+    fn default() -> CrashDumpConfig {
+        CrashDumpConfig {
+            enabled: true,
+            dir: None,
+            recent_logs: 1_000,
+        }
+    }
+}
+
+impl CrashDumpConfig {
+    pub fn dir(&self, data_dir: &Path) -> PathBuf {
+        self.dir
+            .clone()
+            .unwrap_or_else(|| data_dir.join("crash_dumps"))
+    }
+}
@@ -15,6 +15,8 @@ pub enum Error {
     Yaml(String, #[source] serde_yaml::Error),
     #[error("Config is missing expected value: {0}")]
     Missing(&'static str),
+    #[error("Config changes can't be applied while the node runs: {0}")]
+    UnsafeReload(String),
 }
 
 pub fn invariant(cond: bool, msg: String) -> Result<(), Error> {
@@ -23,6 +23,12 @@ pub struct ExecutionConfig {
     pub service: ExecutionCorrectnessService,
     pub backend: SecureBackend,
     pub network_timeout_ms: u64,
+    /// Re-execute the transactions of the chunks synced as transaction outputs, and report the
+    /// ones whose results differ from the proven transaction infos. Meant for audit fullnodes.
+    pub audit_chunks: bool,
+    /// The number of committed blocks which can be queued for persistence on a dedicated thread
+    /// while the following blocks execute. When 0, blocks are persisted before commits return.
+    pub max_pending_commits: usize,
 }
 
 impl std::fmt::Debug for ExecutionConfig {
@@ -40,8 +46,13 @@ impl std::fmt::Debug for ExecutionConfig {
         )?;
         write!(
             f,
-            ", sign_vote_proposal: {:?}, service: {:?}, backend: {:?} }}",
-            self.sign_vote_proposal, self.service, self.backend
+            ", sign_vote_proposal: {:?}, service: {:?}, backend: {:?}, audit_chunks: {:?}, \
+            max_pending_commits: {:?} }}",
+            self.sign_vote_proposal,
+            self.service,
+            self.backend,
+            self.audit_chunks,
+            self.max_pending_commits
         )?;
         self.service.fmt(f)
     }
@@ -57,6 +68,8 @@ impl Default for ExecutionConfig {
             sign_vote_proposal: true,
             // Default value of 30 seconds for the network timeout.
             network_timeout_ms: 30_000,
+            audit_chunks: false,
+            max_pending_commits: 0,
         }
     }
 }
@@ -91,9 +104,12 @@ impl ExecutionConfig {
     }
 
     pub fn set_data_dir(&mut self, data_dir: PathBuf) {
-        if let SecureBackend::OnDiskStorage(backend) = &mut self.backend {
-            backend.set_data_dir(data_dir);
-        }
+        self.backend.set_data_dir(data_dir);
+    }
+
+    /// Removes the tokens of the storage backend, before the config is exposed
+    pub fn redact_secrets(&mut self) {
+        self.backend.redact_secrets();
     }
 }
 
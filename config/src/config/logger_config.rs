@@ -3,6 +3,7 @@
 
 use aptos_logger::{Level, CHANNEL_SIZE};
 use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default)]
@@ -13,6 +14,12 @@ pub struct LoggerConfig {
     pub is_async: bool,
     // The default logging level for slog.
     pub level: Level,
+    // Levels overriding the default one for modules and their submodules, by module path
+    pub module_levels: BTreeMap<String, Level>,
+    // Write logs to a rotated file instead of stderr
+    pub file: Option<LogFileConfig>,
+    // Rate limit identical logs, from the same place with the same content
+    pub identical_sampling: Option<IdenticalLogSamplingConfig>,
 }
 
 impl Default for LoggerConfig {
@@ -21,6 +28,52 @@ impl Default for LoggerConfig {
             chan_size: CHANNEL_SIZE,
             is_async: true,
             level: Level::Info,
+            module_levels: BTreeMap::new(),
+            file: None,
+            identical_sampling: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct LogFileConfig {
+    pub path: PathBuf,
+    // Write logs as JSON lines, like the ones sent to remote loggers
+    pub json: bool,
+    // The file is rotated before growing larger
+    pub max_size_bytes: u64,
+    // The file is rotated once older, if set
+    pub max_age_secs: Option<u64>,
+    // Number of rotated files kept, the oldest ones are deleted
+    pub max_rotated_files: usize,
+}
+
+impl Default for LogFileConfig {
+    fn default() -> LogFileConfig {
+        LogFileConfig {
+            path: PathBuf::from("/opt/aptos/logs/aptos-node.log"),
+            json: false,
+            max_size_bytes: 100 * 1024 * 1024,
+            max_age_secs: None,
+            max_rotated_files: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct IdenticalLogSamplingConfig {
+    // Identical logs let through per window, the next ones are dropped until the window ends
+    pub max_per_window: u64,
+    pub window_ms: u64,
+}
+
+impl Default for IdenticalLogSamplingConfig {
+    fn default() -> IdenticalLogSamplingConfig {
+        IdenticalLogSamplingConfig {
+            max_per_window: 10,
+            window_ms: 1_000,
         }
     }
 }
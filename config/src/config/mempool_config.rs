@@ -19,6 +19,14 @@ pub struct MempoolConfig {
     pub shared_mempool_tick_interval_ms: u64,
     pub system_transaction_timeout_secs: u64,
     pub system_transaction_gc_interval_ms: u64,
+    // The script functions the node accepts, it can be changed with the admin service
+    pub transaction_filter: TransactionFilterConfig,
+    // The number of recent transactions whose lifecycle is traced for latency debugging, the
+    // traces are served by the admin service. Tracing is disabled when 0.
+    pub max_traced_transactions: usize,
+    // When transactions are committed, evict the parked transactions of their senders which can't
+    // become valid anymore, instead of waiting for them to become ready and fail validation
+    pub eager_parking_lot_eviction: bool,
 }
 
 impl Default for MempoolConfig {
@@ -36,6 +44,22 @@ impl Default for MempoolConfig {
             default_failovers: 3,
             system_transaction_timeout_secs: 600,
             system_transaction_gc_interval_ms: 60_000,
+            transaction_filter: TransactionFilterConfig::default(),
+            max_traced_transactions: 0,
+            eager_parking_lot_eviction: true,
         }
     }
 }
+
+/// The script functions of the transactions accepted by mempool, named
+/// `<address>::<module>::<function>`, or `<address>::<module>` for all the functions of a module.
+/// Scripts and module bundles are rejected when either list is set, their calls can't be checked.
+/// It is local to the node: the transactions of blocks are executed whatever the filter.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct TransactionFilterConfig {
+    // When not empty, only the transactions calling one of these functions are accepted
+    pub allow: Vec<String>,
+    // The transactions calling one of these functions are rejected
+    pub deny: Vec<String>,
+}
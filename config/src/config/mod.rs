@@ -16,8 +16,12 @@ use std::{
 };
 use thiserror::Error;
 
+mod admin_service_config;
+pub use admin_service_config::*;
 mod consensus_config;
 pub use consensus_config::*;
+mod crash_dump_config;
+pub use crash_dump_config::*;
 mod debug_interface_config;
 pub use debug_interface_config::*;
 mod error;
@@ -32,6 +36,10 @@ mod mempool_config;
 pub use mempool_config::*;
 mod network_config;
 pub use network_config::*;
+mod reload;
+pub use reload::*;
+mod runtime_config;
+pub use runtime_config::*;
 mod json_rpc_config;
 pub use json_rpc_config::*;
 mod secure_backend_config;
@@ -42,6 +50,8 @@ mod storage_config;
 pub use storage_config::*;
 mod safety_rules_config;
 pub use safety_rules_config::*;
+mod telemetry_config;
+pub use telemetry_config::*;
 mod test_config;
 pub use test_config::*;
 mod api_config;
@@ -57,11 +67,15 @@ pub struct DeprecatedConfig {}
 /// so that only that module can be passed around
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct NodeConfig {
+    #[serde(default)]
+    pub admin_service: AdminServiceConfig,
     #[serde(default)]
     pub base: BaseConfig,
     #[serde(default)]
     pub consensus: ConsensusConfig,
     #[serde(default)]
+    pub crash_dumps: CrashDumpConfig,
+    #[serde(default)]
     pub debug_interface: DebugInterfaceConfig,
     #[serde(default)]
     pub execution: ExecutionConfig,
@@ -78,10 +92,14 @@ pub struct NodeConfig {
     #[serde(default)]
     pub api: ApiConfig,
     #[serde(default)]
+    pub runtimes: RuntimeConfig,
+    #[serde(default)]
     pub state_sync: StateSyncConfig,
     #[serde(default)]
     pub storage: StorageConfig,
     #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    #[serde(default)]
     pub test: Option<TestConfig>,
     #[serde(default)]
     pub validator_network: Option<NetworkConfig>,
@@ -95,6 +113,8 @@ pub struct BaseConfig {
     data_dir: PathBuf,
     pub role: RoleType,
     pub waypoint: WaypointConfig,
+    // Time allowed for the node to drain its components on SIGTERM before exiting regardless
+    pub shutdown_deadline_ms: u64,
 }
 
 impl Default for BaseConfig {
@@ -103,6 +123,7 @@ impl Default for BaseConfig {
             data_dir: PathBuf::from("/opt/aptos/data"),
             role: RoleType::Validator,
             waypoint: WaypointConfig::None,
+            shutdown_deadline_ms: 30_000,
         }
     }
 }
@@ -290,7 +311,29 @@ impl NodeConfig {
         Ok(())
     }
 
+    /// Removes the keys and the tokens of the config, before it is exposed, e.g. by the admin
+    /// service. The keys of identities within the config can't be replaced, so those identities
+    /// are removed.
+    pub fn redact_secrets(&mut self) {
+        self.admin_service.redact_tokens();
+        self.consensus.safety_rules.redact_secrets();
+        self.execution.redact_secrets();
+        if let WaypointConfig::FromStorage(backend) = &mut self.base.waypoint {
+            backend.redact_secrets();
+        }
+        if let Some(test) = &mut self.test {
+            test.redact_secrets();
+        }
+        if let Some(network) = &mut self.validator_network {
+            network.redact_secrets();
+        }
+        for network in &mut self.full_node_networks {
+            network.redact_secrets();
+        }
+    }
+
     pub fn randomize_ports(&mut self) {
+        self.admin_service.randomize_ports();
         self.debug_interface.randomize_ports();
         self.json_rpc.randomize_ports();
         self.api.randomize_ports();
@@ -460,6 +503,47 @@ mod test {
         }
     }
 
+    #[test]
+    fn verify_redact_secrets() {
+        let mut config = NodeConfig::random();
+        config.admin_service.authentication_token = Some("admin".into());
+        config.consensus.safety_rules.backend = SecureBackend::Pkcs11(Pkcs11Config {
+            library: PathBuf::from("pkcs11.so"),
+            token_label: "aptos".into(),
+            pin: Token::FromConfig("1234".into()),
+            namespace: None,
+        });
+        config.execution.backend = SecureBackend::Pkcs11(Pkcs11Config {
+            library: PathBuf::from("pkcs11.so"),
+            token_label: "aptos".into(),
+            pin: Token::FromDisk(PathBuf::from("/pin")),
+            namespace: None,
+        });
+
+        config.redact_secrets();
+        assert!(config.admin_service.tokens().is_empty());
+        match &config.consensus.safety_rules.backend {
+            SecureBackend::Pkcs11(backend) => {
+                assert_eq!(backend.pin, Token::FromConfig(REDACTED.into()))
+            }
+            backend => panic!("Unexpected backend {:?}", backend),
+        }
+        // Tokens read from disk only reveal their path
+        match &config.execution.backend {
+            SecureBackend::Pkcs11(backend) => {
+                assert_eq!(backend.pin, Token::FromDisk(PathBuf::from("/pin")))
+            }
+            backend => panic!("Unexpected backend {:?}", backend),
+        }
+        let safety_rules_test = config.consensus.safety_rules.test.as_ref().unwrap();
+        assert!(safety_rules_test.consensus_key.is_none());
+        assert!(safety_rules_test.execution_key.is_none());
+        let test = config.test.as_ref().unwrap();
+        assert!(test.operator_key.is_none() && test.owner_key.is_none());
+        assert!(test.execution_key.is_none());
+        assert_eq!(config.validator_network.unwrap().identity, Identity::None);
+    }
+
     #[test]
     fn verify_configs() {
         NodeConfig::default_for_public_full_node();
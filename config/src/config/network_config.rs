@@ -31,10 +31,14 @@ use std::{
 /// Current supported protocol negotiation handshake version. See
 /// [`network::protocols::wire::v1`](../../network/protocols/wire/handshake/v1/index.html).
 pub const HANDSHAKE_VERSION: u8 = 0;
+/// Handshake version advertised by listeners with a `HandshakeRateLimitConfig`, whose Noise
+/// handshake carries a cookie. Dialers support both versions.
+pub const COOKIE_HANDSHAKE_VERSION: u8 = 1;
 pub const NETWORK_CHANNEL_SIZE: usize = 1024;
 pub const PING_INTERVAL_MS: u64 = 1000;
 pub const PING_TIMEOUT_MS: u64 = 10_000;
 pub const PING_FAILURES_TOLERATED: u64 = 5;
+pub const PEER_MONITORING_INTERVAL_MS: u64 = 15_000;
 pub const CONNECTIVITY_CHECK_INTERVAL_MS: u64 = 5000;
 pub const MAX_CONCURRENT_NETWORK_REQS: usize = 100;
 pub const MAX_CONNECTION_DELAY_MS: u64 = 60_000; /* 1 minute */
@@ -44,6 +48,15 @@ pub const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024; /* 16 MiB */
 pub const CONNECTION_BACKOFF_BASE: u64 = 2;
 pub const IP_BYTE_BUCKET_RATE: usize = 102400 /* 100 KiB */;
 pub const IP_BYTE_BUCKET_SIZE: usize = IP_BYTE_BUCKET_RATE;
+pub const PEER_BYTE_BUCKET_RATE: usize = 1048576 /* 1 MiB */;
+pub const PEER_BYTE_BUCKET_SIZE: usize = PEER_BYTE_BUCKET_RATE;
+pub const IP_PREFIX_HANDSHAKE_RATE: usize = 10;
+pub const IP_PREFIX_HANDSHAKE_BURST: usize = 50;
+pub const HANDSHAKE_COOKIE_THRESHOLD: usize = 100;
+pub const REGISTRATION_INTERVAL_MS: u64 = 300_000; /* 5 minutes */
+pub const REGISTRATION_TTL_MS: u64 = 900_000; /* 15 minutes */
+pub const MAX_REGISTERED_PEERS: usize = 1000;
+pub const MAX_REGISTERED_PEERS_PER_SUBNET: usize = 8;
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default)]
@@ -65,7 +78,9 @@ pub struct NetworkConfig {
     pub discovery_methods: Vec<DiscoveryMethod>,
     pub identity: Identity,
     // TODO: Add support for multiple listen/advertised addresses in config.
-    // The address that this node is listening on for new connections.
+    // The address that this node is listening on for new connections. TCP
+    // addresses followed by `/tls` (e.g., `/ip4/0.0.0.0/tcp/6180/tls`) wrap
+    // connections in TLS, for networks where middleboxes block Noise.
     pub listen_address: NetworkAddress,
     // Select this to enforce that both peers should authenticate each other, otherwise
     // authentication only occurs for outgoing connections.
@@ -83,6 +98,9 @@ pub struct NetworkConfig {
     pub max_frame_size: usize,
     // Enables proxy protocol on incoming connections to get original source addresses
     pub enable_proxy_protocol: bool,
+    // Enables 0-RTT session resumption when dialing previously seen peers over
+    // QUIC. QUIC is used instead of TCP when the listen address is a `/udp/` address.
+    pub enable_quic_0rtt: bool,
     // Interval to send healthcheck pings to peers
     pub ping_interval_ms: u64,
     // Timeout until a healthcheck ping is rejected
@@ -91,12 +109,35 @@ pub struct NetworkConfig {
     pub ping_failures_tolerated: u64,
     // Maximum number of outbound connections, limited by ConnectivityManager
     pub max_outbound_connections: usize,
+    // Interval at which the least healthy outbound connection is replaced by a
+    // connection to another eligible peer, once the outbound connection limit
+    // is reached. If not specified, outbound connections are never rotated.
+    pub outbound_rotation_interval_ms: Option<u64>,
     // Maximum number of outbound connections, limited by PeerManager
     pub max_inbound_connections: usize,
     // Inbound rate limiting configuration, if not specified, no rate limiting
     pub inbound_rate_limit_config: Option<RateLimitConfig>,
     // Outbound rate limiting configuration, if not specified, no rate limiting
     pub outbound_rate_limit_config: Option<RateLimitConfig>,
+    // Per peer rate limiting configuration, by the role of the remote peer. Peers
+    // with a role that isn't specified are not rate limited on a per peer basis.
+    pub peer_rate_limit_configs: HashMap<PeerRole, PeerRateLimitConfig>,
+    // Inbound Noise handshake limits, if not specified, handshakes are neither
+    // rate limited nor required to carry cookies. When specified, the listen
+    // address is advertised with the `COOKIE_HANDSHAKE_VERSION`, which the
+    // addresses shared with peers (e.g., on chain) must use as well.
+    pub handshake_rate_limit_config: Option<HandshakeRateLimitConfig>,
+    // Interval at which connected peers are asked to dial back the listen address,
+    // to determine if this node is publicly reachable. If not specified, the node
+    // doesn't check its own reachability (but still serves dial backs for peers).
+    pub reachability_check_interval_ms: Option<u64>,
+    // Interval at which connected peers are pinged by the peer monitoring service,
+    // to measure their latency and exchange node information
+    pub peer_monitoring_interval_ms: u64,
+    // Self-registration of public fullnodes with registrar peers, which serve the
+    // registered peers to other nodes. If not specified, the node neither registers
+    // nor serves registrations.
+    pub registrar_config: Option<RegistrarConfig>,
 }
 
 impl Default for NetworkConfig {
@@ -119,6 +160,7 @@ impl NetworkConfig {
             seeds: PeerSet::default(),
             max_frame_size: MAX_FRAME_SIZE,
             enable_proxy_protocol: false,
+            enable_quic_0rtt: true,
             max_connection_delay_ms: MAX_CONNECTION_DELAY_MS,
             connectivity_check_interval_ms: CONNECTIVITY_CHECK_INTERVAL_MS,
             network_channel_size: NETWORK_CHANNEL_SIZE,
@@ -128,9 +170,15 @@ impl NetworkConfig {
             ping_timeout_ms: PING_TIMEOUT_MS,
             ping_failures_tolerated: PING_FAILURES_TOLERATED,
             max_outbound_connections: MAX_FULLNODE_OUTBOUND_CONNECTIONS,
+            outbound_rotation_interval_ms: None,
             max_inbound_connections: MAX_INBOUND_CONNECTIONS,
             inbound_rate_limit_config: None,
             outbound_rate_limit_config: None,
+            peer_rate_limit_configs: HashMap::new(),
+            handshake_rate_limit_config: None,
+            reachability_check_interval_ms: None,
+            peer_monitoring_interval_ms: PEER_MONITORING_INTERVAL_MS,
+            registrar_config: None,
         };
         config.prepare_identity();
         config
@@ -239,6 +287,19 @@ impl NetworkConfig {
         };
     }
 
+    /// Removes the identity key and the tokens of the storage backends, before the config is
+    /// exposed. A key within the config can't be replaced, so that identity is removed.
+    pub fn redact_secrets(&mut self) {
+        match &mut self.identity {
+            Identity::FromConfig(_) => self.identity = Identity::None,
+            Identity::FromStorage(config) => config.backend.redact_secrets(),
+            Identity::None => {}
+        }
+        if let Some(backend) = &mut self.network_address_key_backend {
+            backend.redact_secrets();
+        }
+    }
+
     pub fn random(&mut self, rng: &mut StdRng) {
         self.random_with_peer_id(rng, None);
     }
@@ -294,6 +355,9 @@ impl NetworkConfig {
 pub enum DiscoveryMethod {
     Onchain,
     File(PathBuf, Duration),
+    // DNS names whose TXT records hold seed addresses, and the interval at
+    // which to re-resolve them.
+    Dns(Vec<String>, Duration),
     None,
 }
 
@@ -359,6 +423,98 @@ impl Default for RateLimitConfig {
     }
 }
 
+/// Rate limits applied to every connection with a peer of a given `PeerRole`,
+/// on top of the per IP rate limits.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct PeerRateLimitConfig {
+    /// Maximum number of bytes/s received from a peer
+    pub ingress_byte_bucket_rate: usize,
+    /// Maximum burst of bytes received from a peer
+    pub ingress_byte_bucket_size: usize,
+    /// Maximum number of bytes/s sent to a peer
+    pub egress_byte_bucket_rate: usize,
+    /// Maximum burst of bytes sent to a peer
+    pub egress_byte_bucket_size: usize,
+    /// Initial amount of tokens initially in the bucket
+    pub initial_bucket_fill_percentage: u8,
+    /// Allow for disabling the throttles
+    pub enabled: bool,
+}
+
+impl Default for PeerRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            ingress_byte_bucket_rate: PEER_BYTE_BUCKET_RATE,
+            ingress_byte_bucket_size: PEER_BYTE_BUCKET_SIZE,
+            egress_byte_bucket_rate: PEER_BYTE_BUCKET_RATE,
+            egress_byte_bucket_size: PEER_BYTE_BUCKET_SIZE,
+            initial_bucket_fill_percentage: 25,
+            enabled: true,
+        }
+    }
+}
+
+/// Limits on inbound Noise handshakes, so that a flood of connections can't
+/// exhaust the node with Diffie-Hellman computations.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct HandshakeRateLimitConfig {
+    /// Maximum number of handshakes/s from an IP prefix (/24 for IPv4, /48 for IPv6)
+    pub ip_prefix_handshake_rate: usize,
+    /// Maximum burst of handshakes from an IP prefix
+    pub ip_prefix_handshake_burst: usize,
+    /// Number of concurrent inbound handshakes above which clients have to dial
+    /// again with a stateless cookie, proving they own their address
+    pub cookie_threshold: usize,
+    /// Allow for disabling the throttles
+    pub enabled: bool,
+}
+
+impl Default for HandshakeRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            ip_prefix_handshake_rate: IP_PREFIX_HANDSHAKE_RATE,
+            ip_prefix_handshake_burst: IP_PREFIX_HANDSHAKE_BURST,
+            cookie_threshold: HANDSHAKE_COOKIE_THRESHOLD,
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct RegistrarConfig {
+    /// Accept registrations from peers, and serve the registered peers to other nodes
+    pub serve_registrations: bool,
+    /// The registrars the node registers its listen address with, and discovers the
+    /// registered peers from, once connected to them (e.g., as seeds)
+    pub registrars: HashSet<PeerId>,
+    /// Interval at which the node registers and fetches the registered peers
+    pub registration_interval_ms: u64,
+    /// Time after which a registration that isn't renewed expires
+    pub registration_ttl_ms: u64,
+    /// Maximum number of registrations served, further registrations evict the least
+    /// recently renewed ones
+    pub max_registered_peers: usize,
+    /// Maximum number of registrations served per subnet (/24 for IPv4, /48 for IPv6),
+    /// further registrations from the subnet evict its least recently renewed ones
+    pub max_registered_peers_per_subnet: usize,
+}
+
+impl Default for RegistrarConfig {
+    fn default() -> Self {
+        Self {
+            serve_registrations: false,
+            registrars: HashSet::new(),
+            registration_interval_ms: REGISTRATION_INTERVAL_MS,
+            registration_ttl_ms: REGISTRATION_TTL_MS,
+            max_registered_peers: MAX_REGISTERED_PEERS,
+            max_registered_peers_per_subnet: MAX_REGISTERED_PEERS_PER_SUBNET,
+        }
+    }
+}
+
 pub type PeerSet = HashMap<PeerId, Peer>;
 
 // TODO: Combine with RoleType?
@@ -385,6 +541,20 @@ pub enum PeerRole {
     Unknown,
 }
 
+impl PeerRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PeerRole::Validator => "validator",
+            PeerRole::PreferredUpstream => "preferred_upstream_peer",
+            PeerRole::Upstream => "upstream_peer",
+            PeerRole::ValidatorFullNode => "validator_fullnode",
+            PeerRole::Downstream => "downstream_peer",
+            PeerRole::Known => "known_peer",
+            PeerRole::Unknown => "unknown_peer",
+        }
+    }
+}
+
 impl Default for PeerRole {
     /// Default to least trusted
     fn default() -> Self {
@@ -0,0 +1,139 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::{Error, NodeConfig};
+use serde_yaml::Value;
+
+/// The fields of the config, by path, which can be changed while the node runs. A change of any
+/// other field requires a restart.
+pub const RELOADABLE_FIELDS: &[&str] = &[
+    "logger.level",
+    "logger.module_levels",
+    "mempool.capacity",
+    "mempool.capacity_per_user",
+    "api.rate_limit",
+];
+
+impl NodeConfig {
+    /// Returns the paths of the fields changed from this config to the new one, or an error
+    /// listing the changed fields which can't be changed while the node runs.
+    pub fn reloadable_changes(&self, new_config: &NodeConfig) -> Result<Vec<String>, Error> {
+        let mut changes = vec![];
+        diff("", &to_value(self)?, &to_value(new_config)?, &mut changes);
+
+        let unsafe_changes = changes
+            .iter()
+            .filter(|path| !is_reloadable(path))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !unsafe_changes.is_empty() {
+            return Err(Error::UnsafeReload(unsafe_changes.join(", ")));
+        }
+        Ok(changes)
+    }
+}
+
+fn to_value(config: &NodeConfig) -> Result<Value, Error> {
+    serde_yaml::to_value(config).map_err(|e| Error::Yaml("NodeConfig".to_string(), e))
+}
+
+fn is_reloadable(path: &str) -> bool {
+    RELOADABLE_FIELDS.iter().any(|field| {
+        path == *field || (path.starts_with(field) && path[field.len()..].starts_with('.'))
+    })
+}
+
+/// Collects the paths of the differences between the values, descending into the mappings and
+/// the sequences of the same length.
+fn diff(path: &str, old: &Value, new: &Value, changes: &mut Vec<String>) {
+    let join = |key: String| {
+        if path.is_empty() {
+            key
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    match (old, new) {
+        (Value::Mapping(old), Value::Mapping(new)) => {
+            let null = Value::Null;
+            for key in old
+                .iter()
+                .map(|(k, _)| k)
+                .chain(new.iter().map(|(k, _)| k).filter(|k| !old.contains_key(k)))
+            {
+                diff(
+                    &join(key_name(key)),
+                    old.get(key).unwrap_or(&null),
+                    new.get(key).unwrap_or(&null),
+                    changes,
+                );
+            }
+        }
+        (Value::Sequence(old), Value::Sequence(new)) if old.len() == new.len() => {
+            for (index, (old, new)) in old.iter().zip(new).enumerate() {
+                diff(&join(index.to_string()), old, new, changes);
+            }
+        }
+        (old, new) => {
+            if old != new {
+                changes.push(path.to_string());
+            }
+        }
+    }
+}
+
+fn key_name(key: &Value) -> String {
+    match key {
+        Value::String(key) => key.clone(),
+        key => serde_yaml::to_string(key)
+            .map(|key| key.trim_start_matches("---").trim().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aptos_logger::Level;
+
+    #[test]
+    fn test_reloadable_changes() {
+        let config = NodeConfig::default();
+        assert!(config.reloadable_changes(&config).unwrap().is_empty());
+
+        let mut new_config = config.clone();
+        new_config.logger.level = Level::Debug;
+        new_config
+            .logger
+            .module_levels
+            .insert("consensus".to_string(), Level::Trace);
+        new_config.mempool.capacity += 1;
+        new_config.api.rate_limit = Some(Default::default());
+        assert_eq!(
+            config.reloadable_changes(&new_config).unwrap(),
+            vec![
+                "logger.level",
+                "logger.module_levels.consensus",
+                "mempool.capacity",
+                "api.rate_limit",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsafe_changes() {
+        let config = NodeConfig::default();
+        let mut new_config = config.clone();
+        new_config.logger.level = Level::Debug;
+        new_config.logger.is_async = !config.logger.is_async;
+        new_config.mempool.capacity_per_user += 1;
+        new_config.mempool.max_broadcasts_per_peer += 1;
+
+        match config.reloadable_changes(&new_config) {
+            Err(Error::UnsafeReload(fields)) => {
+                assert_eq!(fields, "logger.is_async, mempool.max_broadcasts_per_peer")
+            }
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+}
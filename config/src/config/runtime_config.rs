@@ -0,0 +1,43 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The tokio runtimes of the major components of the node, each of which is instrumented.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct RuntimeConfig {
+    // Worker threads of the runtime of each component, the number of CPUs when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_worker_threads: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consensus_worker_threads: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mempool_worker_threads: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_worker_threads: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_sync_worker_threads: Option<usize>,
+    // A runtime is reported stalled when a task ready to run waits longer than this for a worker
+    pub stall_threshold_ms: u64,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> RuntimeConfig {
+        RuntimeConfig {
+            api_worker_threads: None,
+            consensus_worker_threads: None,
+            mempool_worker_threads: None,
+            network_worker_threads: None,
+            state_sync_worker_threads: None,
+            stall_threshold_ms: 100,
+        }
+    }
+}
+
+impl RuntimeConfig {
+    pub fn stall_threshold(&self) -> Duration {
+        Duration::from_millis(self.stall_threshold_ms)
+    }
+}
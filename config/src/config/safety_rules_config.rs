@@ -46,8 +46,21 @@ impl Default for SafetyRulesConfig {
 
 impl SafetyRulesConfig {
     pub fn set_data_dir(&mut self, data_dir: PathBuf) {
-        if let SecureBackend::OnDiskStorage(backend) = &mut self.backend {
-            backend.set_data_dir(data_dir);
+        self.backend.set_data_dir(data_dir);
+    }
+
+    /// Whether safety rules export the consensus key from the storage to sign with it, instead of
+    /// having the storage sign. Keys can't be exported from some backends, e.g., HSMs.
+    pub fn exports_consensus_key(&self) -> bool {
+        self.export_consensus_key && self.backend.can_export_keys()
+    }
+
+    /// Removes the keys and the tokens of the storage backend, before the config is exposed
+    pub fn redact_secrets(&mut self) {
+        self.backend.redact_secrets();
+        if let Some(test) = &mut self.test {
+            test.consensus_key = None;
+            test.execution_key = None;
         }
     }
 }
@@ -1,6 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::config::RoleType;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -33,6 +34,10 @@ pub struct StateSyncConfig {
     pub aptos_data_client: AptosDataClientConfig,
     pub state_sync_driver: StateSyncDriverConfig,
     pub storage_service: StorageServiceConfig,
+    // If set, the node syncs to exactly this version and then stops applying
+    // new data (while continuing to serve reads). This is useful for
+    // reproducing historical states, e.g., for incident forensics.
+    pub target_version: Option<u64>,
 }
 
 impl Default for StateSyncConfig {
@@ -51,6 +56,7 @@ impl Default for StateSyncConfig {
             aptos_data_client: AptosDataClientConfig::default(),
             state_sync_driver: StateSyncDriverConfig::default(),
             storage_service: StorageServiceConfig::default(),
+            target_version: None,
         }
     }
 }
@@ -73,13 +79,25 @@ pub enum ContinuousSyncingMode {
     ApplyTransactionOutputs, // Applies transaction outputs to stay up-to-date
 }
 
+/// The syncing modes to use for nodes of a specific role. This allows nodes
+/// that trust execution (e.g., fullnodes) to apply transaction outputs, while
+/// other nodes (e.g., validators) continue to execute all transactions.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SyncingModes {
+    pub bootstrapping_mode: BootstrappingMode, // The mode by which to bootstrap
+    pub continuous_syncing_mode: ContinuousSyncingMode, // The mode by which to sync after bootstrapping
+}
+
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct StateSyncDriverConfig {
     pub bootstrapping_mode: BootstrappingMode, // The mode by which to bootstrap
     pub enable_state_sync_v2: bool,            // If the node should sync with state sync v2
     pub continuous_syncing_mode: ContinuousSyncingMode, // The mode by which to sync after bootstrapping
+    pub fullnode_syncing_modes: Option<SyncingModes>,   // Overrides the syncing modes for fullnodes
     pub progress_check_interval_ms: u64, // The interval (ms) at which to check state sync progress
+    pub validator_syncing_modes: Option<SyncingModes>, // Overrides the syncing modes for validators
 }
 
 /// The default state sync driver config will be the one that gets (and keeps)
@@ -90,29 +108,53 @@ impl Default for StateSyncDriverConfig {
             bootstrapping_mode: BootstrappingMode::DownloadLatestAccountStates,
             enable_state_sync_v2: false,
             continuous_syncing_mode: ContinuousSyncingMode::ApplyTransactionOutputs,
+            fullnode_syncing_modes: None,
             progress_check_interval_ms: 100,
+            validator_syncing_modes: None,
         }
     }
 }
 
+impl StateSyncDriverConfig {
+    /// Returns the config with any syncing mode overrides for the given role
+    /// applied to `bootstrapping_mode` and `continuous_syncing_mode`.
+    pub fn for_role(mut self, role: RoleType) -> Self {
+        let syncing_modes = match role {
+            RoleType::Validator => self.validator_syncing_modes,
+            RoleType::FullNode => self.fullnode_syncing_modes,
+        };
+        if let Some(syncing_modes) = syncing_modes {
+            self.bootstrapping_mode = syncing_modes.bootstrapping_mode;
+            self.continuous_syncing_mode = syncing_modes.continuous_syncing_mode;
+        }
+        self
+    }
+}
+
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default)]
 pub struct StorageServiceConfig {
     pub max_account_states_chunk_sizes: u64, // Max num of accounts per chunk
+    pub max_bytes_per_peer_per_second: u64,  // Max num of response bytes sent to a peer per second
     pub max_concurrent_requests: u64,        // Max num of concurrent storage server tasks
     pub max_epoch_chunk_size: u64,           // Max num of epoch ending ledger infos per chunk
-    pub max_transaction_chunk_size: u64,     // Max num of transactions per chunk
+    pub max_subscription_period_ms: u64, // Max period (ms) a data subscription is held before expiring (>= the client timeout)
+    pub max_transaction_chunk_size: u64, // Max num of transactions per chunk
     pub max_transaction_output_chunk_size: u64, // Max num of transaction outputs per chunk
+    pub subscription_refresh_interval_ms: u64, // The interval (ms) at which to refresh data subscriptions
 }
 
 impl Default for StorageServiceConfig {
     fn default() -> Self {
         Self {
             max_account_states_chunk_sizes: 3000,
+            max_bytes_per_peer_per_second: 50 * 1024 * 1024, // 50 MiB
             max_concurrent_requests: 50,
             max_epoch_chunk_size: 100,
+            max_subscription_period_ms: 10_000,
             max_transaction_chunk_size: 3000,
             max_transaction_output_chunk_size: 3000,
+            subscription_refresh_interval_ms: 100,
         }
     }
 }
@@ -120,10 +162,17 @@ impl Default for StorageServiceConfig {
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default)]
 pub struct DataStreamingServiceConfig {
+    // Whether continuous streams (without a target) should subscribe to new
+    // data once they have caught up to the highest advertised version, instead
+    // of waiting for the next data summary poll to discover new data.
+    pub enable_subscription_streaming: bool,
+
     // The interval (milliseconds) at which to refresh the global data summary.
     pub global_summary_refresh_interval_ms: u64,
 
-    // Maximum number of concurrent data client requests (per stream).
+    // Maximum number of concurrent data client requests (per stream). Streams
+    // halve their number of concurrent requests on every failed request and
+    // grow it back (up to this maximum) as data notifications are sent.
     pub max_concurrent_requests: u64,
 
     // Maximum channel sizes for each data stream listener. If messages are not
@@ -146,6 +195,7 @@ pub struct DataStreamingServiceConfig {
 impl Default for DataStreamingServiceConfig {
     fn default() -> Self {
         Self {
+            enable_subscription_streaming: false,
             global_summary_refresh_interval_ms: 300,
             max_concurrent_requests: 5,
             max_data_stream_channel_sizes: 1000,
@@ -159,15 +209,76 @@ impl Default for DataStreamingServiceConfig {
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default)]
 pub struct AptosDataClientConfig {
+    pub max_peer_offences: u64, // Number of (undecayed) malicious responses before a peer is blocklisted
+    pub peer_blocklist_duration_ms: u64, // Duration (in milliseconds) of a peer's first blocklisting
+    pub peer_offence_decay_interval_ms: u64, // Interval (in milliseconds) after which a single offence is forgiven
     pub response_timeout_ms: u64, // Timeout (in milliseconds) when waiting for a response
+    pub subscription_response_timeout_ms: u64, // Timeout (in milliseconds) when waiting for a data subscription response
     pub summary_poll_interval_ms: u64, // Interval (in milliseconds) between data summary polls
 }
 
 impl Default for AptosDataClientConfig {
     fn default() -> Self {
         Self {
+            max_peer_offences: 5,
+            peer_blocklist_duration_ms: 60_000,
+            peer_offence_decay_interval_ms: 30_000,
             response_timeout_ms: 3_000,
+            subscription_response_timeout_ms: 10_000,
             summary_poll_interval_ms: 300,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syncing_modes_for_role() {
+        // Create a driver config with a validator override
+        let driver_config = StateSyncDriverConfig {
+            bootstrapping_mode: BootstrappingMode::ApplyTransactionOutputsFromGenesis,
+            continuous_syncing_mode: ContinuousSyncingMode::ApplyTransactionOutputs,
+            validator_syncing_modes: Some(SyncingModes {
+                bootstrapping_mode: BootstrappingMode::ExecuteTransactionsFromGenesis,
+                continuous_syncing_mode: ContinuousSyncingMode::ExecuteTransactions,
+            }),
+            ..StateSyncDriverConfig::default()
+        };
+
+        // Verify validators use the override
+        let validator_config = driver_config.for_role(RoleType::Validator);
+        assert_eq!(
+            validator_config.bootstrapping_mode,
+            BootstrappingMode::ExecuteTransactionsFromGenesis
+        );
+        assert_eq!(
+            validator_config.continuous_syncing_mode,
+            ContinuousSyncingMode::ExecuteTransactions
+        );
+
+        // Verify fullnodes use the default modes
+        assert_eq!(driver_config.for_role(RoleType::FullNode), driver_config);
+    }
+
+    #[test]
+    fn test_parse_syncing_modes() {
+        let driver_config: StateSyncDriverConfig = serde_yaml::from_str(
+            r#"
+            fullnode_syncing_modes:
+                bootstrapping_mode: ApplyTransactionOutputsFromGenesis
+                continuous_syncing_mode: ApplyTransactionOutputs
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            driver_config.fullnode_syncing_modes,
+            Some(SyncingModes {
+                bootstrapping_mode: BootstrappingMode::ApplyTransactionOutputsFromGenesis,
+                continuous_syncing_mode: ContinuousSyncingMode::ApplyTransactionOutputs,
+            })
+        );
+        assert_eq!(driver_config.validator_syncing_modes, None);
+    }
+}
@@ -53,14 +53,26 @@ pub struct StorageConfig {
     /// wiped and re-synced.
     #[serde(default)]
     pub account_count_migration: bool,
+    /// If enabled, the state values are also indexed by key and version, so that the reads which
+    /// don't need a proof (e.g. the ones of the API and the VM) take a single seek instead of a
+    /// walk down the state Merkle tree. This writes every updated value twice, as the leaves of
+    /// the tree hold the values too: the extra bytes written are reported by the
+    /// `aptos_storage_state_value_index_bytes` metric, and the size on disk by the
+    /// `aptos_rocksdb_properties` metric of the `state_value` column family. The index is deleted
+    /// when the node starts with it disabled, and rebuilt from the values written after it's
+    /// enabled again.
+    #[serde(default)]
+    pub state_value_index: bool,
 }
 
 pub const NO_OP_STORAGE_PRUNER_CONFIG: StoragePrunerConfig = StoragePrunerConfig {
     state_store_prune_window: None,
     default_prune_window: None,
+    keep_epoch_ending_versions: false,
+    pinned_versions: Vec::new(),
 };
 
-#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct StoragePrunerConfig {
     /// None disables pruning. The size of the window should be calculated based on disk space
     /// availability and system TPS.
@@ -69,6 +81,14 @@ pub struct StoragePrunerConfig {
     /// being big in size, we might want to configure a smaller window for state store vs other
     /// store.
     pub default_prune_window: Option<u64>,
+    /// If enabled, the state at the epoch ending versions is exempted from pruning, so that the
+    /// epoch snapshots remain servable to the peers fast syncing to them.
+    #[serde(default)]
+    pub keep_epoch_ending_versions: bool,
+    /// Versions whose state is exempted from pruning, e.g. snapshots pinned by the operator.
+    /// Unpinning a version which the prune window has passed doesn't get its state pruned.
+    #[serde(default)]
+    pub pinned_versions: Vec<u64>,
 }
 
 impl StoragePrunerConfig {
@@ -79,6 +99,8 @@ impl StoragePrunerConfig {
         StoragePrunerConfig {
             state_store_prune_window,
             default_prune_window: default_store_prune_window,
+            keep_epoch_ending_versions: false,
+            pinned_versions: vec![],
         }
     }
 }
@@ -99,12 +121,15 @@ impl Default for StorageConfig {
             storage_pruner_config: StoragePrunerConfig {
                 state_store_prune_window: Some(1_000_000),
                 default_prune_window: Some(10_000_000),
+                keep_epoch_ending_versions: false,
+                pinned_versions: vec![],
             },
             data_dir: PathBuf::from("/opt/aptos/data"),
             // Default read/write/connection timeout, in milliseconds
             timeout_ms: 30_000,
             rocksdb_config: RocksdbConfig::default(),
             account_count_migration: false,
+            state_value_index: false,
         }
     }
 }
@@ -0,0 +1,35 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+
+/// Telemetry periodically pushes anonymized metrics of the node to an endpoint. It is on unless
+/// the node opts out, either by disabling it here or by setting `APTOS_DISABLE_TELEMETRY`, in which
+/// case nothing is collected at all.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    pub enabled: bool,
+    // Metrics are only pushed when an endpoint is configured
+    pub endpoint: Option<String>,
+    pub push_interval_ms: u64,
+    pub push_timeout_ms: u64,
+    // Samples kept while the endpoint can't be reached, the oldest ones are dropped first
+    pub max_buffered_samples: usize,
+    // Crash dumps, which include recent logs scrubbed of peer addresses and ids, are only
+    // uploaded when an endpoint is configured
+    pub crash_dump_endpoint: Option<String>,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> TelemetryConfig {
+        TelemetryConfig {
+            enabled: true,
+            endpoint: None,
+            push_interval_ms: 60_000,
+            push_timeout_ms: 10_000,
+            max_buffered_samples: 1_440,
+            crash_dump_endpoint: None,
+        }
+    }
+}
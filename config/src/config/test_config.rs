@@ -101,6 +101,13 @@ impl TestConfig {
         self.execution_key = Some(ConfigKey::new(privkey));
     }
 
+    /// Removes the keys, before the config is exposed
+    pub fn redact_secrets(&mut self) {
+        self.operator_key = None;
+        self.owner_key = None;
+        self.execution_key = None;
+    }
+
     pub fn temp_dir(&self) -> Option<&Path> {
         self.temp_dir.as_ref().map(|temp_dir| temp_dir.path())
     }
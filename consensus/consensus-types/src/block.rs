@@ -224,7 +224,8 @@ impl Block {
         match self.block_data.block_type() {
             BlockType::Genesis => bail!("We should not accept genesis from others"),
             BlockType::NilBlock => self.quorum_cert().verify(validator),
-            BlockType::Proposal { author, .. } => {
+            BlockType::Proposal { author, .. }
+            | BlockType::ReconfigurationSuffix { author, .. } => {
                 let signature = self
                     .signature
                     .as_ref()
@@ -257,6 +258,25 @@ impl Block {
                 "Reconfiguration suffix should not carry payload"
             );
         }
+        if let Some(reconfig_block_id) = self.block_data.reconfiguration_suffix_of() {
+            ensure!(
+                parent.has_reconfiguration(),
+                "Reconfiguration suffix must extend a block with reconfiguration"
+            );
+            // the parent is either the reconfiguration block, or another suffix of it, which
+            // carries over the state of its own parent
+            let grandparent = self.quorum_cert().parent_block();
+            ensure!(
+                parent.id() == reconfig_block_id
+                    || (grandparent.has_reconfiguration()
+                        && grandparent.round() < parent.round()
+                        && grandparent.executed_state_id() == parent.executed_state_id()
+                        && grandparent.version() == parent.version()
+                        && grandparent.timestamp_usecs() == parent.timestamp_usecs()),
+                "Reconfiguration suffix of {} doesn't extend it",
+                reconfig_block_id
+            );
+        }
         if self.is_nil_block() || parent.has_reconfiguration() {
             ensure!(
                 self.timestamp_usecs() == parent.timestamp_usecs(),
@@ -289,6 +309,49 @@ impl Block {
         Ok(())
     }
 
+    /// Makes sure that a reconfiguration suffix extends the reconfiguration block it's marked
+    /// with, given its parent: the parent is either the reconfiguration block, or a suffix marked
+    /// with the same block. In the latter case the grandparent is the reconfiguration block, or
+    /// a suffix of it itself, which was checked along the parent.
+    pub fn verify_reconfiguration_suffix(&self, parent: &Block) -> anyhow::Result<()> {
+        ensure!(
+            parent.id() == self.parent_id(),
+            "Block {} isn't the parent of {}",
+            parent.id(),
+            self.id()
+        );
+        let reconfig_block_id = match self.block_data.reconfiguration_suffix_of() {
+            Some(reconfig_block_id) => reconfig_block_id,
+            None => return Ok(()),
+        };
+        if parent.id() == reconfig_block_id {
+            return Ok(());
+        }
+        ensure!(
+            parent.block_data.reconfiguration_suffix_of() == Some(reconfig_block_id),
+            "Parent {} of reconfiguration suffix {} isn't a suffix of {}",
+            parent.id(),
+            self.id(),
+            reconfig_block_id
+        );
+        let grandparent = self.quorum_cert().parent_block();
+        ensure!(
+            grandparent.id() == parent.parent_id(),
+            "Grandparent {} of {} isn't the parent of {}",
+            grandparent.id(),
+            self.id(),
+            parent.id()
+        );
+        ensure!(
+            grandparent.id() == reconfig_block_id || grandparent.has_reconfiguration(),
+            "Grandparent {} of reconfiguration suffix {} isn't {} or a suffix of it",
+            grandparent.id(),
+            self.id(),
+            reconfig_block_id
+        );
+        Ok(())
+    }
+
     pub fn transactions_to_execute(&self) -> Vec<Transaction> {
         std::iter::once(Transaction::BlockMetadata(self.into()))
             .chain(
@@ -32,6 +32,25 @@ pub enum BlockType {
     /// from the previous epoch.  The genesis block is used as the the first root block of the
     /// BlockTree for all epochs.
     Genesis,
+    /// A reconfiguration suffix is proposed on top of a block with a reconfiguration (or on top of
+    /// its suffix) until the reconfiguration is committed. It doesn't carry any payload and the
+    /// state after the reconfiguration block is carried over.
+    ReconfigurationSuffix {
+        /// Author of the block that can be validated by the author's public key and the signature
+        author: Author,
+        /// Id of the block with the reconfiguration
+        reconfig_block_id: HashValue,
+    },
+}
+
+impl BlockType {
+    /// The marker of a block proposed by the author after the reconfiguration block.
+    pub fn suffix_of(reconfig_block_id: HashValue, author: Author) -> Self {
+        BlockType::ReconfigurationSuffix {
+            author,
+            reconfig_block_id,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, CryptoHasher, BCSCryptoHash)]
@@ -69,10 +88,10 @@ pub struct BlockData {
 
 impl BlockData {
     pub fn author(&self) -> Option<Author> {
-        if let BlockType::Proposal { author, .. } = self.block_type {
-            Some(author)
-        } else {
-            None
+        match self.block_type {
+            BlockType::Proposal { author, .. }
+            | BlockType::ReconfigurationSuffix { author, .. } => Some(author),
+            BlockType::NilBlock | BlockType::Genesis => None,
         }
     }
 
@@ -185,6 +204,23 @@ impl BlockData {
         }
     }
 
+    /// A reconfiguration suffix has the timestamp of its parent: no transaction is executed after
+    /// the reconfiguration.
+    pub fn new_reconfiguration_suffix(
+        reconfig_block_id: HashValue,
+        author: Author,
+        round: Round,
+        quorum_cert: QuorumCert,
+    ) -> Self {
+        Self {
+            epoch: quorum_cert.certified_block().epoch(),
+            round,
+            timestamp_usecs: quorum_cert.certified_block().timestamp_usecs(),
+            quorum_cert,
+            block_type: BlockType::suffix_of(reconfig_block_id, author),
+        }
+    }
+
     pub fn new_proposal(
         payload: Payload,
         author: Author,
@@ -201,9 +237,23 @@ impl BlockData {
         }
     }
 
-    /// It's a reconfiguration suffix block if the parent block's executed state indicates next epoch.
+    /// The id of the reconfiguration block if the block is marked as a suffix of it.
+    pub fn reconfiguration_suffix_of(&self) -> Option<HashValue> {
+        if let BlockType::ReconfigurationSuffix {
+            reconfig_block_id, ..
+        } = self.block_type
+        {
+            Some(reconfig_block_id)
+        } else {
+            None
+        }
+    }
+
+    /// It's a reconfiguration suffix block if it's marked as such, or if the parent block's
+    /// executed state indicates next epoch.
     pub fn is_reconfiguration_suffix(&self) -> bool {
-        self.quorum_cert.certified_block().has_reconfiguration()
+        self.reconfiguration_suffix_of().is_some()
+            || self.quorum_cert.certified_block().has_reconfiguration()
     }
 }
 
@@ -233,6 +283,23 @@ fn test_reconfiguration_suffix() {
         ),
     );
     let reconfig_suffix_block =
-        BlockData::new_proposal(vec![], AccountAddress::random(), 2, 2, quorum_cert);
+        BlockData::new_proposal(vec![], AccountAddress::random(), 2, 2, quorum_cert.clone());
     assert!(reconfig_suffix_block.is_reconfiguration_suffix());
+    assert_eq!(reconfig_suffix_block.reconfiguration_suffix_of(), None);
+
+    let author = AccountAddress::random();
+    let marked_suffix_block = BlockData::new_reconfiguration_suffix(
+        quorum_cert.certified_block().id(),
+        author,
+        2,
+        quorum_cert.clone(),
+    );
+    assert!(marked_suffix_block.is_reconfiguration_suffix());
+    assert_eq!(
+        marked_suffix_block.reconfiguration_suffix_of(),
+        Some(quorum_cert.certified_block().id())
+    );
+    assert_eq!(marked_suffix_block.author(), Some(author));
+    assert_eq!(marked_suffix_block.payload(), None);
+    assert_eq!(marked_suffix_block.timestamp_usecs(), 1);
 }
@@ -120,17 +120,23 @@ impl BlockRetrievalResponse {
         );
         self.blocks
             .iter()
-            .try_fold(retrieval_request.block_id(), |expected_id, block| {
-                block.validate_signature(sig_verifier)?;
-                block.verify_well_formed()?;
-                ensure!(
-                    block.id() == expected_id,
-                    "blocks doesn't form a chain: expect {}, get {}",
-                    expected_id,
-                    block.id()
-                );
-                Ok(block.parent_id())
-            })
+            .try_fold(
+                (retrieval_request.block_id(), None),
+                |(expected_id, child): (_, Option<&Block>), block| {
+                    block.validate_signature(sig_verifier)?;
+                    block.verify_well_formed()?;
+                    ensure!(
+                        block.id() == expected_id,
+                        "blocks doesn't form a chain: expect {}, get {}",
+                        expected_id,
+                        block.id()
+                    );
+                    if let Some(child) = child {
+                        child.verify_reconfiguration_suffix(block)?;
+                    }
+                    Ok((block.parent_id(), Some(block)))
+                },
+            )
             .map(|_| ())
     }
 }
@@ -6,10 +6,14 @@ use crate::{
         block_test_utils::{certificate_for_genesis, *},
         Block,
     },
+    block_data::BlockData,
     quorum_cert::QuorumCert,
 };
 use aptos_crypto::hash::HashValue;
-use aptos_types::{validator_signer::ValidatorSigner, validator_verifier::ValidatorVerifier};
+use aptos_types::{
+    block_info::BlockInfo, epoch_state::EpochState, validator_signer::ValidatorSigner,
+    validator_verifier::ValidatorVerifier,
+};
 use std::{collections::BTreeMap, sync::Arc};
 
 #[test]
@@ -134,3 +138,157 @@ fn test_same_qc_different_authors() {
     assert!(block_round_1.id() != block_round_1_altered.id());
     assert_eq!(block_round_1.id(), block_round_1_same.id());
 }
+
+#[test]
+fn test_reconfiguration_suffix_well_formed() {
+    let signer = ValidatorSigner::random(None);
+    let genesis_qc = certificate_for_genesis();
+    let reconfig_block = Block::new_proposal(
+        vec![],
+        1,
+        genesis_qc.certified_block().timestamp_usecs() + 1,
+        genesis_qc.clone(),
+        &signer,
+    );
+    let reconfig_block_info =
+        reconfig_block.gen_block_info(HashValue::random(), 1, Some(EpochState::empty()));
+    let reconfig_qc = gen_test_certificate(
+        vec![&signer],
+        reconfig_block_info.clone(),
+        genesis_qc.certified_block().clone(),
+        None,
+    );
+    let suffix = |reconfig_block_id, round, quorum_cert| {
+        Block::new_proposal_from_block_data(
+            BlockData::new_reconfiguration_suffix(
+                reconfig_block_id,
+                signer.author(),
+                round,
+                quorum_cert,
+            ),
+            &signer,
+        )
+    };
+
+    // A direct suffix of the reconfiguration block
+    let first_suffix = suffix(reconfig_block.id(), 2, reconfig_qc.clone());
+    assert!(first_suffix.verify_well_formed().is_ok());
+    assert!(suffix(HashValue::random(), 2, reconfig_qc)
+        .verify_well_formed()
+        .is_err());
+
+    // A suffix of a suffix, which carries over the state of the reconfiguration block
+    let first_suffix_info = first_suffix.gen_block_info(
+        reconfig_block_info.executed_state_id(),
+        reconfig_block_info.version(),
+        Some(EpochState::empty()),
+    );
+    let first_suffix_qc = gen_test_certificate(
+        vec![&signer],
+        first_suffix_info,
+        reconfig_block_info.clone(),
+        None,
+    );
+    assert!(suffix(reconfig_block.id(), 3, first_suffix_qc)
+        .verify_well_formed()
+        .is_ok());
+
+    // A parent with another state isn't a suffix of the reconfiguration block
+    let other_state_info =
+        first_suffix.gen_block_info(HashValue::random(), 2, Some(EpochState::empty()));
+    let other_state_qc =
+        gen_test_certificate(vec![&signer], other_state_info, reconfig_block_info, None);
+    assert!(suffix(reconfig_block.id(), 3, other_state_qc)
+        .verify_well_formed()
+        .is_err());
+}
+
+#[test]
+fn test_verify_reconfiguration_suffix() {
+    let signer = ValidatorSigner::random(None);
+    let genesis_qc = certificate_for_genesis();
+    let reconfig_block = Block::new_proposal(
+        vec![],
+        1,
+        genesis_qc.certified_block().timestamp_usecs() + 1,
+        genesis_qc.clone(),
+        &signer,
+    );
+    let reconfig_block_info =
+        reconfig_block.gen_block_info(HashValue::random(), 1, Some(EpochState::empty()));
+    let reconfig_qc = gen_test_certificate(
+        vec![&signer],
+        reconfig_block_info.clone(),
+        genesis_qc.certified_block().clone(),
+        None,
+    );
+    let suffix = |reconfig_block_id, round, quorum_cert| {
+        Block::new_proposal_from_block_data(
+            BlockData::new_reconfiguration_suffix(
+                reconfig_block_id,
+                signer.author(),
+                round,
+                quorum_cert,
+            ),
+            &signer,
+        )
+    };
+    // A certificate of a block carrying over the state of the reconfiguration block
+    let carried_over_qc = |block: &Block, parent_block_info: BlockInfo| {
+        let block_info = block.gen_block_info(
+            reconfig_block_info.executed_state_id(),
+            reconfig_block_info.version(),
+            Some(EpochState::empty()),
+        );
+        gen_test_certificate(vec![&signer], block_info, parent_block_info, None)
+    };
+
+    // A chain of suffixes
+    let first_suffix = suffix(reconfig_block.id(), 2, reconfig_qc.clone());
+    assert!(first_suffix
+        .verify_reconfiguration_suffix(&reconfig_block)
+        .is_ok());
+    let first_suffix_qc = carried_over_qc(&first_suffix, reconfig_block_info.clone());
+    let second_suffix = suffix(reconfig_block.id(), 3, first_suffix_qc.clone());
+    assert!(second_suffix
+        .verify_reconfiguration_suffix(&first_suffix)
+        .is_ok());
+    let second_suffix_qc =
+        carried_over_qc(&second_suffix, first_suffix_qc.certified_block().clone());
+    assert!(suffix(reconfig_block.id(), 4, second_suffix_qc)
+        .verify_reconfiguration_suffix(&second_suffix)
+        .is_ok());
+
+    // Not the parent
+    assert!(second_suffix
+        .verify_reconfiguration_suffix(&reconfig_block)
+        .is_err());
+
+    // A parent extending the reconfiguration block without being marked as its suffix
+    let unmarked = Block::new_proposal(
+        vec![],
+        2,
+        reconfig_block.timestamp_usecs(),
+        reconfig_qc.clone(),
+        &signer,
+    );
+    let unmarked_qc = carried_over_qc(&unmarked, reconfig_block_info.clone());
+    assert!(suffix(reconfig_block.id(), 3, unmarked_qc)
+        .verify_reconfiguration_suffix(&unmarked)
+        .is_err());
+
+    // A parent marked as the suffix of another block
+    let other_suffix = suffix(HashValue::random(), 2, reconfig_qc);
+    let other_suffix_qc = carried_over_qc(&other_suffix, reconfig_block_info.clone());
+    assert!(suffix(reconfig_block.id(), 3, other_suffix_qc)
+        .verify_reconfiguration_suffix(&other_suffix)
+        .is_err());
+
+    // A parent marked as the suffix of the reconfiguration block, but extending another block
+    let misplaced_suffix = suffix(reconfig_block.id(), 2, genesis_qc.clone());
+    let misplaced_suffix_qc =
+        carried_over_qc(&misplaced_suffix, genesis_qc.certified_block().clone());
+    assert!(suffix(reconfig_block.id(), 3, misplaced_suffix_qc)
+        .verify_reconfiguration_suffix(&misplaced_suffix)
+        .is_err());
+}
@@ -11,15 +11,22 @@ use aptos_crypto::hash::HashValue;
 use aptos_types::{
     block_info::BlockInfo,
     contract_event::ContractEvent,
-    transaction::{Transaction, TransactionStatus},
+    transaction::{SignedTransaction, Transaction, TransactionStatus},
 };
 use executor_types::StateComputeResult;
+use serde::{Deserialize, Serialize};
 use std::fmt::{Debug, Display, Formatter};
 
+#[cfg(test)]
+#[path = "executed_block_test.rs"]
+pub mod executed_block_test;
+
 /// ExecutedBlocks are managed in a speculative tree, the committed blocks form a chain. Besides
 /// block data, each executed block also has other derived meta data which could be regenerated from
 /// blocks.
-#[derive(Clone, Eq, PartialEq)]
+/// They're serialized to ship the results of execution workers running out of the consensus
+/// process back to consensus.
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
 pub struct ExecutedBlock {
     /// Block data that cannot be regenerated.
     block: Block,
@@ -65,6 +72,17 @@ impl ExecutedBlock {
         self.block().payload()
     }
 
+    /// The transactions of the payload executed as part of the block: when the block reached the
+    /// block gas limit, the ones after the cut are left out, to be proposed again.
+    pub fn executed_payload(&self) -> Option<&[SignedTransaction]> {
+        self.payload()
+            .map(|txns| match self.compute_result().block_gas_limit_cut() {
+                // the cut counts the block metadata transaction executed first
+                Some(cut) => &txns[..cut.saturating_sub(1).min(txns.len())],
+                None => &txns[..],
+            })
+    }
+
     pub fn parent_id(&self) -> HashValue {
         self.quorum_cert().certified_block().id()
     }
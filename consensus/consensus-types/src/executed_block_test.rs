@@ -0,0 +1,140 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    block::{
+        block_test_utils::{certificate_for_genesis, random_payload},
+        Block,
+    },
+    executed_block::ExecutedBlock,
+};
+use aptos_crypto::{hash::TransactionAccumulatorHasher, HashValue};
+use aptos_types::{
+    proof::accumulator::InMemoryAccumulator, transaction::TransactionStatus,
+    validator_signer::ValidatorSigner, vm_status::KeptVMStatus,
+};
+use executor_types::StateComputeResult;
+use std::sync::Arc;
+
+fn roundtrip(executed_block: &ExecutedBlock) -> ExecutedBlock {
+    bcs::from_bytes(&bcs::to_bytes(executed_block).unwrap()).unwrap()
+}
+
+#[test]
+fn test_executed_block_serialization() {
+    let parent_accumulator = InMemoryAccumulator::<TransactionAccumulatorHasher>::from_leaves(&[
+        HashValue::random(),
+        HashValue::random(),
+        HashValue::random(),
+    ]);
+    let transaction_info_hashes = vec![HashValue::random(), HashValue::random()];
+    let accumulator = parent_accumulator.append(&transaction_info_hashes);
+    let compute_result = StateComputeResult::new(
+        accumulator.root_hash(),
+        accumulator.frozen_subtree_roots().clone(),
+        accumulator.num_leaves(),
+        parent_accumulator.frozen_subtree_roots().clone(),
+        parent_accumulator.num_leaves(),
+        None,
+        vec![TransactionStatus::Keep(KeptVMStatus::Executed); 2],
+        transaction_info_hashes,
+        vec![],
+    )
+    .unwrap();
+    let executed_block = ExecutedBlock::new(Block::make_genesis_block(), compute_result);
+    assert_eq!(roundtrip(&executed_block), executed_block);
+
+    // Results which aren't an extension of their parent, like the root of the block tree or the
+    // dummy ones, are serialized as is
+    let root_result = StateComputeResult::new(
+        accumulator.root_hash(),
+        accumulator.frozen_subtree_roots().clone(),
+        accumulator.num_leaves(),
+        vec![],
+        0,
+        None,
+        vec![],
+        vec![],
+        vec![],
+    )
+    .unwrap();
+    for compute_result in vec![root_result, StateComputeResult::new_dummy()] {
+        let executed_block = ExecutedBlock::new(Block::make_genesis_block(), compute_result);
+        assert_eq!(roundtrip(&executed_block), executed_block);
+    }
+}
+
+#[test]
+fn test_compute_result_derived_from_parent_accumulator() {
+    let parent_accumulator = Arc::new(
+        InMemoryAccumulator::<TransactionAccumulatorHasher>::from_leaves(&[HashValue::random()]),
+    );
+    let transaction_info_hashes = vec![HashValue::random(); 3];
+    let accumulator = parent_accumulator.append(&transaction_info_hashes);
+    let materialized = StateComputeResult::new(
+        accumulator.root_hash(),
+        accumulator.frozen_subtree_roots().clone(),
+        accumulator.num_leaves(),
+        parent_accumulator.frozen_subtree_roots().clone(),
+        parent_accumulator.num_leaves(),
+        None,
+        vec![],
+        transaction_info_hashes.clone(),
+        vec![],
+    )
+    .unwrap();
+    let derived = StateComputeResult::new_with_parent_accumulator(
+        Arc::clone(&parent_accumulator),
+        None, /* accumulator */
+        None,
+        vec![],
+        transaction_info_hashes,
+        vec![],
+    );
+    assert!(Arc::ptr_eq(
+        derived.parent_accumulator(),
+        &parent_accumulator
+    ));
+    assert_eq!(derived, materialized);
+    assert_eq!(derived.root_hash(), accumulator.root_hash());
+    assert_eq!(derived.version(), 3);
+
+    let executed_block = ExecutedBlock::new(Block::make_genesis_block(), derived);
+    assert_eq!(roundtrip(&executed_block), executed_block);
+}
+
+#[test]
+fn test_executed_payload() {
+    let signer = ValidatorSigner::random(None);
+    let payload = random_payload(3);
+    let block = Block::new_proposal(payload.clone(), 1, 1, certificate_for_genesis(), &signer);
+    let compute_result = |block_gas_limit_cut| {
+        StateComputeResult::new_dummy().with_block_gas_limit_cut(block_gas_limit_cut)
+    };
+
+    let executed_block = ExecutedBlock::new(block.clone(), compute_result(None));
+    assert_eq!(executed_block.executed_payload(), Some(&payload[..]));
+
+    // The cut counts the block metadata transaction
+    let executed_block = ExecutedBlock::new(block.clone(), compute_result(Some(2)));
+    assert_eq!(executed_block.executed_payload(), Some(&payload[..1]));
+    let executed_block = ExecutedBlock::new(block, compute_result(Some(1)));
+    assert_eq!(executed_block.executed_payload(), Some(&payload[..0]));
+}
+
+#[test]
+fn test_compute_result_with_invalid_accumulator() {
+    // One leaf but no frozen subtree root
+    let compute_result = StateComputeResult::new(
+        HashValue::random(),
+        vec![],
+        1,
+        vec![],
+        0,
+        None,
+        vec![],
+        vec![],
+        vec![],
+    );
+    assert!(compute_result.is_err());
+}
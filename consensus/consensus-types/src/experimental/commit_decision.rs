@@ -1,11 +1,23 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::common::Round;
-use anyhow::Context;
-use aptos_types::{ledger_info::LedgerInfoWithSignatures, validator_verifier::ValidatorVerifier};
+use crate::common::{Author, Round};
+use anyhow::{ensure, Context};
+use aptos_crypto::ed25519::Ed25519Signature;
+use aptos_types::{
+    block_info::BlockInfo,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_verifier::ValidatorVerifier,
+};
 use serde::{Deserialize, Serialize};
-use std::fmt::{Debug, Display, Formatter};
+use std::{
+    collections::BTreeMap,
+    fmt::{Debug, Display, Formatter},
+};
+
+#[cfg(test)]
+#[path = "commit_decision_test.rs"]
+pub mod commit_decision_test;
 
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct CommitDecision {
@@ -44,6 +56,36 @@ impl CommitDecision {
         &self.ledger_info
     }
 
+    /// Generates the LedgerInfo the validators vote on to commit ordered blocks once they're
+    /// executed: the block info after the execution with the consensus data of the ordered proof.
+    pub fn commit_ledger_info(
+        commit_info: BlockInfo,
+        ordered_proof: &LedgerInfoWithSignatures,
+    ) -> LedgerInfo {
+        LedgerInfo::new(
+            commit_info,
+            ordered_proof.ledger_info().consensus_data_hash(),
+        )
+    }
+
+    /// Aggregates the signatures of the commit votes on the LedgerInfo, dropping the invalid ones.
+    /// The result is a commit decision once the signatures reach the quorum voting power.
+    pub fn aggregate_signatures(
+        commit_ledger_info: &LedgerInfo,
+        signatures: BTreeMap<Author, Ed25519Signature>,
+        validator: &ValidatorVerifier,
+    ) -> LedgerInfoWithSignatures {
+        let valid_signatures = signatures
+            .into_iter()
+            .filter(|(author, signature)| {
+                validator
+                    .verify(*author, commit_ledger_info, signature)
+                    .is_ok()
+            })
+            .collect();
+        LedgerInfoWithSignatures::new(commit_ledger_info.clone(), valid_signatures)
+    }
+
     /// Verifies that the signatures carried in the message forms a valid quorum,
     /// and then verifies the signature.
     pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
@@ -53,4 +95,26 @@ impl CommitDecision {
             .verify_signatures(validator)
             .context("Failed to verify Commit Decision")
     }
+
+    /// Verifies that the commit decision commits the blocks of the ordered proof, so that nodes
+    /// not executing the blocks, e.g. fullnodes, can commit them with the verified decision.
+    pub fn verify_ordered_proof(
+        &self,
+        ordered_proof: &LedgerInfoWithSignatures,
+    ) -> anyhow::Result<()> {
+        ensure!(
+            ordered_proof
+                .commit_info()
+                .match_ordered_only(self.ledger_info.commit_info()),
+            "Commit Decision {} doesn't match the ordered block {}",
+            self.ledger_info.commit_info(),
+            ordered_proof.commit_info(),
+        );
+        ensure!(
+            self.ledger_info.ledger_info().consensus_data_hash()
+                == ordered_proof.ledger_info().consensus_data_hash(),
+            "Commit Decision doesn't match the consensus data of the ordered proof",
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,61 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::experimental::{commit_decision::CommitDecision, commit_vote::CommitVote};
+use aptos_crypto::HashValue;
+use aptos_types::{
+    block_info::BlockInfo,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_verifier::random_validator_verifier,
+};
+use std::collections::BTreeMap;
+
+#[test]
+fn test_aggregate_commit_votes() {
+    let (signers, validator) = random_validator_verifier(4, None, false);
+    let block_id = HashValue::random();
+    let ordered_info = BlockInfo::new(1, 10, block_id, HashValue::zero(), 0, 100, None);
+    let ordered_li = LedgerInfo::new(ordered_info, HashValue::random());
+    let ordered_proof = LedgerInfoWithSignatures::new(ordered_li, BTreeMap::new());
+    let commit_info = BlockInfo::new(1, 10, block_id, HashValue::random(), 42, 100, None);
+    let commit_ledger_info = CommitDecision::commit_ledger_info(commit_info, &ordered_proof);
+
+    // a vote on another ledger info is dropped
+    let mut signatures = BTreeMap::new();
+    let vote = CommitVote::new(
+        signers[0].author(),
+        ordered_proof.ledger_info().clone(),
+        &signers[0],
+    );
+    signatures.insert(vote.author(), vote.signature().clone());
+    for signer in &signers[1..3] {
+        let vote = CommitVote::new(signer.author(), commit_ledger_info.clone(), signer);
+        vote.verify(&validator).unwrap();
+        signatures.insert(vote.author(), vote.signature().clone());
+    }
+    let commit_proof =
+        CommitDecision::aggregate_signatures(&commit_ledger_info, signatures.clone(), &validator);
+    assert_eq!(commit_proof.signatures().len(), 2);
+    assert!(commit_proof.check_voting_power(&validator).is_err());
+
+    let vote = CommitVote::new(signers[3].author(), commit_ledger_info.clone(), &signers[3]);
+    signatures.insert(vote.author(), vote.signature().clone());
+    let commit_proof =
+        CommitDecision::aggregate_signatures(&commit_ledger_info, signatures, &validator);
+    let commit_decision = CommitDecision::new(commit_proof);
+    commit_decision.verify(&validator).unwrap();
+    commit_decision
+        .verify_ordered_proof(&ordered_proof)
+        .unwrap();
+
+    let other_ordered_proof = LedgerInfoWithSignatures::new(
+        LedgerInfo::new(
+            BlockInfo::new(1, 11, HashValue::random(), HashValue::zero(), 0, 100, None),
+            ordered_proof.ledger_info().consensus_data_hash(),
+        ),
+        BTreeMap::new(),
+    );
+    assert!(commit_decision
+        .verify_ordered_proof(&other_ordered_proof)
+        .is_err());
+}
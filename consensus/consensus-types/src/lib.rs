@@ -11,8 +11,12 @@ pub mod epoch_retrieval;
 pub mod executed_block;
 pub mod experimental;
 pub mod proposal_msg;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod proptest_types;
 pub mod quorum_cert;
 pub mod safety_data;
+#[cfg(test)]
+mod serialization_test;
 pub mod sync_info;
 pub mod timeout;
 pub mod timeout_2chain;
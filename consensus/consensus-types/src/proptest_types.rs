@@ -0,0 +1,237 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    block::{block_test_utils::placeholder_ledger_info, Block},
+    common::Round,
+    quorum_cert::QuorumCert,
+    sync_info::SyncInfo,
+    timeout::Timeout,
+    timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
+    timeout_certificate::TimeoutCertificate,
+    vote::Vote,
+    vote_data::VoteData,
+};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_types::{
+    block_info::BlockInfo,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    transaction::SignedTransaction,
+    validator_signer::{proptests, ValidatorSigner},
+};
+use proptest::{collection::vec, option, prelude::*};
+
+/// The maximum number of validators signing a generated certificate.
+const MAX_SIGNERS: usize = 4;
+
+/// The maximum round of a generated block, leaving room for higher timeout rounds.
+const MAX_ROUND: Round = 1_000_000;
+
+/// The kind of timeout carried by a generated vote or sync info.
+#[derive(Clone, Copy, Debug)]
+pub enum TimeoutKind {
+    None,
+    Timeout,
+    TwoChainTimeout,
+}
+
+pub fn arb_timeout_kind() -> impl Strategy<Value = TimeoutKind> {
+    prop_oneof![
+        Just(TimeoutKind::None),
+        Just(TimeoutKind::Timeout),
+        Just(TimeoutKind::TwoChainTimeout),
+    ]
+}
+
+pub fn arb_signers() -> impl Strategy<Value = Vec<ValidatorSigner>> {
+    vec(proptests::arb_signer(), 1..=MAX_SIGNERS)
+}
+
+pub fn arb_block_info() -> impl Strategy<Value = BlockInfo> {
+    (
+        any::<u64>(),
+        0..MAX_ROUND,
+        any::<HashValue>(),
+        any::<HashValue>(),
+        any::<u64>(),
+        any::<u64>(),
+        option::of(any::<EpochState>()),
+    )
+        .prop_map(
+            |(epoch, round, id, executed_state_id, version, timestamp_usecs, next_epoch_state)| {
+                BlockInfo::new(
+                    epoch,
+                    round,
+                    id,
+                    executed_state_id,
+                    version,
+                    timestamp_usecs,
+                    next_epoch_state,
+                )
+            },
+        )
+}
+
+prop_compose! {
+    /// This produces a quorum certificate signed by a few validators, which may also certify
+    /// a commit.
+    pub fn arb_quorum_cert()(
+        signers in arb_signers(),
+        proposed in arb_block_info(),
+        parent in arb_block_info(),
+        committed in option::of(arb_block_info()),
+    ) -> QuorumCert {
+        let vote_data = VoteData::new(proposed, parent);
+        let ledger_info = match committed {
+            Some(committed) => LedgerInfo::new(committed, vote_data.hash()),
+            None => {
+                let mut placeholder = placeholder_ledger_info();
+                placeholder.set_consensus_data_hash(vote_data.hash());
+                placeholder
+            }
+        };
+        let signatures = signers
+            .iter()
+            .map(|signer| (signer.author(), signer.sign(&ledger_info)))
+            .collect();
+        QuorumCert::new(
+            vote_data,
+            LedgerInfoWithSignatures::new(ledger_info, signatures),
+        )
+    }
+}
+
+prop_compose! {
+    /// This produces a proposal carrying a few transactions, signed by its author.
+    pub fn arb_proposal()(
+        payload in vec(any::<SignedTransaction>(), 0..3),
+        round in 0..MAX_ROUND,
+        timestamp_usecs in any::<u64>(),
+        quorum_cert in arb_quorum_cert(),
+        signer in proptests::arb_signer(),
+    ) -> Block {
+        Block::new_proposal(payload, round, timestamp_usecs, quorum_cert, &signer)
+    }
+}
+
+/// This produces a genesis, nil or proposal block.
+pub fn arb_block() -> impl Strategy<Value = Block> {
+    prop_oneof![
+        1 => Just(Block::make_genesis_block()),
+        2 => (0..MAX_ROUND, arb_quorum_cert())
+            .prop_map(|(round, quorum_cert)| Block::new_nil(round, quorum_cert)),
+        7 => arb_proposal(),
+    ]
+}
+
+prop_compose! {
+    /// This produces a timeout certificate for a round above `min_round`.
+    pub fn arb_timeout_certificate(min_round: Round)(
+        epoch in any::<u64>(),
+        round in min_round + 1..min_round + MAX_ROUND,
+        signers in arb_signers(),
+    ) -> TimeoutCertificate {
+        let timeout = Timeout::new(epoch, round);
+        let mut timeout_certificate = TimeoutCertificate::new(timeout.clone());
+        for signer in signers.iter() {
+            timeout_certificate.add_signature(signer.author(), timeout.sign(signer));
+        }
+        timeout_certificate
+    }
+}
+
+prop_compose! {
+    /// This produces a 2-chain timeout certificate for a round above `min_round`.
+    pub fn arb_two_chain_timeout_certificate(min_round: Round)(
+        epoch in any::<u64>(),
+        round in min_round + 1..min_round + MAX_ROUND,
+        quorum_cert in arb_quorum_cert(),
+        signers in arb_signers(),
+    ) -> TwoChainTimeoutCertificate {
+        let timeout = TwoChainTimeout::new(epoch, round, quorum_cert);
+        let mut timeout_certificate = TwoChainTimeoutCertificate::new(timeout.clone());
+        for signer in signers.iter() {
+            timeout_certificate.add(signer.author(), timeout.clone(), timeout.sign(signer));
+        }
+        timeout_certificate
+    }
+}
+
+prop_compose! {
+    /// This produces a vote for a proposal, carrying a timeout of the given kind.
+    pub fn arb_vote(timeout_kind: impl Strategy<Value = TimeoutKind>)(
+        signer in proptests::arb_signer(),
+        proposed in arb_block_info(),
+        parent in arb_block_info(),
+        quorum_cert in arb_quorum_cert(),
+        timeout_kind in timeout_kind,
+    ) -> Vote {
+        let mut vote = Vote::new(
+            VoteData::new(proposed, parent),
+            signer.author(),
+            placeholder_ledger_info(),
+            &signer,
+        );
+        match timeout_kind {
+            TimeoutKind::None => (),
+            TimeoutKind::Timeout => {
+                let signature = vote.generate_timeout().sign(&signer);
+                vote.add_timeout_signature(signature);
+            }
+            TimeoutKind::TwoChainTimeout => {
+                let timeout = vote.generate_2chain_timeout(quorum_cert);
+                let signature = timeout.sign(&signer);
+                vote.add_2chain_timeout(timeout, signature);
+            }
+        }
+        vote
+    }
+}
+
+/// This produces the sync info of a peer, carrying a timeout certificate of the given kind.
+pub fn arb_sync_info(
+    timeout_kind: impl Strategy<Value = TimeoutKind>,
+) -> impl Strategy<Value = SyncInfo> {
+    (arb_quorum_cert(), arb_quorum_cert(), timeout_kind).prop_flat_map(
+        |(highest_quorum_cert, highest_ordered_cert, timeout_kind)| {
+            let certified_round = highest_quorum_cert.certified_block().round();
+            let (timeout_cert, two_chain_timeout_cert) = match timeout_kind {
+                TimeoutKind::None => (Just(None).boxed(), Just(None).boxed()),
+                TimeoutKind::Timeout => (
+                    arb_timeout_certificate(certified_round)
+                        .prop_map(Some)
+                        .boxed(),
+                    Just(None).boxed(),
+                ),
+                TimeoutKind::TwoChainTimeout => (
+                    Just(None).boxed(),
+                    arb_two_chain_timeout_certificate(certified_round)
+                        .prop_map(Some)
+                        .boxed(),
+                ),
+            };
+            (
+                Just(highest_quorum_cert),
+                Just(highest_ordered_cert),
+                timeout_cert,
+                two_chain_timeout_cert,
+            )
+                .prop_map(
+                    |(
+                        highest_quorum_cert,
+                        highest_ordered_cert,
+                        highest_timeout_cert,
+                        highest_2chain_timeout_cert,
+                    )| {
+                        SyncInfo::new(
+                            highest_quorum_cert,
+                            highest_ordered_cert,
+                            highest_timeout_cert,
+                            highest_2chain_timeout_cert,
+                        )
+                    },
+                )
+        },
+    )
+}
@@ -0,0 +1,177 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    block::Block,
+    proptest_types::{
+        arb_block, arb_quorum_cert, arb_sync_info, arb_timeout_certificate, arb_vote, TimeoutKind,
+    },
+    quorum_cert::QuorumCert,
+    sync_info::SyncInfo,
+    timeout_certificate::TimeoutCertificate,
+    vote::Vote,
+};
+use proptest::{collection::vec, prelude::*};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{collections::BTreeMap, fmt::Debug};
+
+// BCS is not self-describing, so an optional field that is skipped when empty can't be told
+// apart from the bytes following it: only votes and sync infos carrying a 2-chain timeout,
+// their last field, round-trip through BCS.
+fn bcs_timeout_kind() -> impl Strategy<Value = TimeoutKind> {
+    Just(TimeoutKind::TwoChainTimeout)
+}
+
+fn assert_round_trip<T>(value: &T)
+where
+    T: Debug + DeserializeOwned + PartialEq + Serialize,
+{
+    let bytes = bcs::to_bytes(value).unwrap();
+    let decoded: T = bcs::from_bytes(&bytes).unwrap();
+    assert_eq!(&decoded, value);
+    assert_eq!(bcs::to_bytes(&decoded).unwrap(), bytes);
+}
+
+fn assert_rejects_malformed<T>(value: &T, trailing_bytes: &[u8], truncate_at: prop::sample::Index)
+where
+    T: Debug + DeserializeOwned + Serialize,
+{
+    let bytes = bcs::to_bytes(value).unwrap();
+
+    let mut with_trailing_bytes = bytes.clone();
+    with_trailing_bytes.extend_from_slice(trailing_bytes);
+    assert_eq!(
+        bcs::from_bytes::<T>(&with_trailing_bytes).unwrap_err(),
+        bcs::Error::RemainingInput
+    );
+
+    let truncated = &bytes[..truncate_at.index(bytes.len())];
+    assert!(bcs::from_bytes::<T>(truncated).is_err());
+}
+
+/// Re-encodes `value`, whose last field is the `signatures` map, with the signatures in the
+/// order of `entries`.
+fn with_signature_entries<T, K, V>(
+    value: &T,
+    signatures: &BTreeMap<K, V>,
+    entries: Vec<(&K, &V)>,
+) -> Vec<u8>
+where
+    T: Serialize,
+    K: Serialize,
+    V: Serialize,
+{
+    let bytes = bcs::to_bytes(value).unwrap();
+    let map_len = bcs::to_bytes(signatures).unwrap().len();
+    let mut reencoded = bytes[..bytes.len() - map_len].to_vec();
+    reencoded.extend(bcs::to_bytes(&entries).unwrap());
+    reencoded
+}
+
+/// Verifies that `value` only deserializes when its signatures are sorted and unique.
+fn assert_rejects_non_canonical_signatures<T, K, V>(value: &T, signatures: &BTreeMap<K, V>)
+where
+    T: Debug + DeserializeOwned + PartialEq + Serialize,
+    K: Serialize,
+    V: Serialize,
+{
+    let sorted: Vec<_> = signatures.iter().collect();
+    let canonical = with_signature_entries(value, signatures, sorted.clone());
+    assert_eq!(&bcs::from_bytes::<T>(&canonical).unwrap(), value);
+
+    let mut reversed = sorted.clone();
+    reversed.reverse();
+    let unsorted = with_signature_entries(value, signatures, reversed);
+    assert_eq!(
+        bcs::from_bytes::<T>(&unsorted).unwrap_err(),
+        bcs::Error::NonCanonicalMap
+    );
+
+    let mut duplicated = sorted.clone();
+    duplicated.insert(0, sorted[0]);
+    let duplicate = with_signature_entries(value, signatures, duplicated);
+    assert_eq!(
+        bcs::from_bytes::<T>(&duplicate).unwrap_err(),
+        bcs::Error::NonCanonicalMap
+    );
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(20))]
+
+    #[test]
+    fn test_block_round_trip(block in arb_block()) {
+        assert_round_trip(&block);
+        let decoded: Block = bcs::from_bytes(&bcs::to_bytes(&block).unwrap()).unwrap();
+        assert_eq!(decoded.id(), block.id());
+    }
+
+    #[test]
+    fn test_quorum_cert_round_trip(quorum_cert in arb_quorum_cert()) {
+        assert_round_trip(&quorum_cert);
+    }
+
+    #[test]
+    fn test_vote_round_trip(vote in arb_vote(bcs_timeout_kind())) {
+        assert_round_trip(&vote);
+    }
+
+    #[test]
+    fn test_timeout_certificate_round_trip(timeout_cert in arb_timeout_certificate(0)) {
+        assert_round_trip(&timeout_cert);
+    }
+
+    #[test]
+    fn test_sync_info_round_trip(sync_info in arb_sync_info(bcs_timeout_kind())) {
+        assert_round_trip(&sync_info);
+    }
+
+    #[test]
+    fn test_malformed_inputs_rejected(
+        block in arb_block(),
+        quorum_cert in arb_quorum_cert(),
+        vote in arb_vote(bcs_timeout_kind()),
+        timeout_cert in arb_timeout_certificate(0),
+        sync_info in arb_sync_info(bcs_timeout_kind()),
+        trailing_bytes in vec(any::<u8>(), 1..8),
+        truncate_at in any::<prop::sample::Index>(),
+    ) {
+        assert_rejects_malformed::<Block>(&block, &trailing_bytes, truncate_at);
+        assert_rejects_malformed::<QuorumCert>(&quorum_cert, &trailing_bytes, truncate_at);
+        assert_rejects_malformed::<Vote>(&vote, &trailing_bytes, truncate_at);
+        assert_rejects_malformed::<TimeoutCertificate>(&timeout_cert, &trailing_bytes, truncate_at);
+        assert_rejects_malformed::<SyncInfo>(&sync_info, &trailing_bytes, truncate_at);
+    }
+
+    #[test]
+    fn test_block_invalid_tags_rejected(block in arb_block(), tag in 4u8..0x80) {
+        let bytes = bcs::to_bytes(&block).unwrap();
+
+        // The block type follows the epoch, round, timestamp and quorum cert of the block data
+        let block_type_offset = 24 + bcs::to_bytes(block.quorum_cert()).unwrap().len();
+        let mut invalid_block_type = bytes.clone();
+        invalid_block_type[block_type_offset] = tag;
+        assert!(bcs::from_bytes::<Block>(&invalid_block_type).is_err());
+
+        // The optional signature is the last field of the block
+        let signature_offset = bcs::to_bytes(block.block_data()).unwrap().len();
+        let mut invalid_signature = bytes;
+        invalid_signature[signature_offset] = tag;
+        assert!(bcs::from_bytes::<Block>(&invalid_signature).is_err());
+    }
+
+    #[test]
+    fn test_quorum_cert_non_canonical_signatures_rejected(quorum_cert in arb_quorum_cert()) {
+        let signatures = quorum_cert.ledger_info().signatures();
+        prop_assume!(signatures.len() > 1);
+        assert_rejects_non_canonical_signatures(&quorum_cert, signatures);
+    }
+
+    #[test]
+    fn test_timeout_certificate_non_canonical_signatures_rejected(
+        timeout_cert in arb_timeout_certificate(0),
+    ) {
+        prop_assume!(timeout_cert.signatures().len() > 1);
+        assert_rejects_non_canonical_signatures(&timeout_cert, timeout_cert.signatures());
+    }
+}
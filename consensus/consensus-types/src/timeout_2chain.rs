@@ -133,19 +133,40 @@ impl TwoChainTimeoutCertificate {
         // Verify the highest timeout validity.
         self.timeout.verify(validators)?;
         let hqc_round = self.timeout.hqc_round();
-        let mut signed_round = 0;
         validators.check_voting_power(self.signatures.keys())?;
-        for (author, (qc_round, signature)) in &self.signatures {
-            let t = TimeoutSigningRepr {
-                epoch: self.timeout.epoch(),
-                round: self.timeout.round(),
-                hqc_round: *qc_round,
-            };
-            validators
-                .verify(*author, &t, signature)
-                .with_context(|| format!("Failed to verify {}'s TimeoutSigningRepr", *author))?;
-            signed_round = std::cmp::max(signed_round, *qc_round);
+        let timeouts: Vec<_> = self
+            .signatures
+            .iter()
+            .map(|(author, (qc_round, signature))| {
+                let t = TimeoutSigningRepr {
+                    epoch: self.timeout.epoch(),
+                    round: self.timeout.round(),
+                    hqc_round: *qc_round,
+                };
+                (*author, t, signature)
+            })
+            .collect();
+        let signed_messages: Vec<_> = timeouts
+            .iter()
+            .map(|(author, t, signature)| (*author, t, *signature))
+            .collect();
+        // Fallback is required to identify the source of the problem if batching fails.
+        if validators
+            .batch_verify_signatures(&signed_messages)
+            .is_err()
+        {
+            for (author, t, signature) in signed_messages {
+                validators
+                    .verify(author, t, signature)
+                    .with_context(|| format!("Failed to verify {}'s TimeoutSigningRepr", author))?;
+            }
         }
+        let signed_round = self
+            .signatures
+            .values()
+            .map(|(qc_round, _)| *qc_round)
+            .max()
+            .unwrap_or(0);
         ensure!(
             hqc_round == signed_round,
             "Inconsistent hqc round, qc has round {}, highest signed round {}",
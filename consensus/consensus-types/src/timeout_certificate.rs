@@ -42,7 +42,7 @@ impl TimeoutCertificate {
     /// Verifies the signatures for the round
     pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
         validator
-            .verify_aggregated_struct_signature(&self.timeout, &self.signatures)
+            .batch_verify_aggregated_signatures(&self.timeout, &self.signatures)
             .context("Failed to verify TimeoutCertificate")?;
         Ok(())
     }
@@ -29,6 +29,8 @@ pub enum Error {
     InvalidQuorumCertificate(String),
     #[error("{0} is not set, SafetyRules is not initialized")]
     NotInitialized(String),
+    #[error("Safety data import rejected: {0}")]
+    SafetyDataImportRejected(String),
     #[error("Data not found in secure storage: {0}")]
     SecureStorageMissingDataError(String),
     #[error("Unexpected error returned by secure storage: {0}")]
@@ -55,6 +57,8 @@ pub enum Error {
     WaypointOutOfDate(u64, u64, u64, u64),
     #[error("Invalid Timeout: {0}")]
     InvalidTimeout(String),
+    #[error("The safety data was exported from this storage, which can't sign anymore")]
+    SafetyDataExported,
 }
 
 impl From<serde_json::Error> for Error {
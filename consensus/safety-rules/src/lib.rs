@@ -9,6 +9,7 @@ mod counters;
 mod error;
 mod local_client;
 mod logging;
+pub mod migration;
 mod persistent_safety_storage;
 mod process;
 mod remote_service;
@@ -44,6 +44,8 @@ pub enum LogEntry {
     ConstructAndSignVote,
     ConstructAndSignVoteTwoChain,
     Epoch,
+    ExportSafetyData,
+    ImportSafetyData,
     Initialize,
     KeyReconciliation,
     LastVotedRound,
@@ -64,6 +66,8 @@ impl LogEntry {
             LogEntry::ConstructAndSignVote => "construct_and_sign_vote",
             LogEntry::ConstructAndSignVoteTwoChain => "construct_and_sign_vote_2chain",
             LogEntry::Epoch => "epoch",
+            LogEntry::ExportSafetyData => "export_safety_data",
+            LogEntry::ImportSafetyData => "import_safety_data",
             LogEntry::Initialize => "initialize",
             LogEntry::LastVotedRound => "last_voted_round",
             LogEntry::KeyReconciliation => "key_reconciliation",
@@ -2,26 +2,40 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Usage: ./safety-rules node.config
+//!
+//! To migrate a validator to another host, with the validator stopped on the old host:
+//!   ./safety-rules export-safety-data node.config safety_data.json --confirm-equivocation-risk
+//! and then, on the new host:
+//!   ./safety-rules import-safety-data node.config safety_data.json --confirm-equivocation-risk
 
 #![forbid(unsafe_code)]
 
 use aptos_config::config::{PersistableConfig, SafetyRulesConfig};
 use aptos_secure_push_metrics::MetricsPusher;
-use safety_rules::Process;
-use std::{env, process};
+use safety_rules::{
+    migration::{self, ExportedSafetyData},
+    Process,
+};
+use std::{env, fs, process};
+
+const CONFIRM_EQUIVOCATION_RISK: &str = "--confirm-equivocation-risk";
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    if args.len() != 2 {
-        eprintln!("Incorrect number of parameters, expected a path to a config file");
-        process::exit(1);
+    match args.get(1).map(String::as_str) {
+        Some("export-safety-data") => export_safety_data(&args[2..]),
+        Some("import-safety-data") => import_safety_data(&args[2..]),
+        _ if args.len() == 2 => run(&args[1]),
+        _ => {
+            eprintln!("Incorrect number of parameters, expected a path to a config file");
+            process::exit(1);
+        }
     }
+}
 
-    let config = SafetyRulesConfig::load_config(&args[1]).unwrap_or_else(|e| {
-        eprintln!("Unable to read provided config: {}", e);
-        process::exit(1);
-    });
+fn run(config_path: &str) {
+    let config = load_config(config_path);
 
     aptos_logger::Logger::new()
         .channel_size(config.logger.chan_size)
@@ -38,3 +52,89 @@ fn main() {
     let mut service = Process::new(config);
     service.start();
 }
+
+fn export_safety_data(args: &[String]) {
+    let (config_path, output_path) = migration_args("export-safety-data", args);
+    confirm_equivocation_risk(
+        args,
+        "Exporting safety data while the validator can still vote on this host risks \
+         double-signing once it is imported on another host. Stop the validator here, and \
+         keep it stopped, before exporting.",
+    );
+
+    let config = load_config(config_path);
+    let exported = migration::export_safety_data(&config).unwrap_or_else(|e| {
+        eprintln!("Unable to export safety data: {}", e);
+        process::exit(1);
+    });
+    let json = serde_json::to_string_pretty(&exported).expect("Unable to serialize safety data");
+    fs::write(output_path, &json).unwrap_or_else(|e| {
+        // The storage is fenced by the export, so the safety data must not be lost
+        eprintln!(
+            "Unable to write {}: {}. The safety data exported is:\n{}",
+            output_path, e, json
+        );
+        process::exit(1);
+    });
+    println!(
+        "Exported {} and waypoint {} to {}",
+        exported.safety_data, exported.waypoint, output_path
+    );
+}
+
+fn import_safety_data(args: &[String]) {
+    let (config_path, input_path) = migration_args("import-safety-data", args);
+    confirm_equivocation_risk(
+        args,
+        "Importing safety data while the validator can still vote on the host it was exported \
+         from risks double-signing. Make sure the validator is stopped there, and will not be \
+         restarted, before importing.",
+    );
+
+    let config = load_config(config_path);
+    let exported: ExportedSafetyData = fs::read_to_string(input_path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+        .unwrap_or_else(|e| {
+            eprintln!("Unable to read safety data from {}: {}", input_path, e);
+            process::exit(1);
+        });
+    migration::import_safety_data(&config, &exported).unwrap_or_else(|e| {
+        eprintln!("Unable to import safety data: {}", e);
+        process::exit(1);
+    });
+    println!(
+        "Imported {} and waypoint {} from {}",
+        exported.safety_data, exported.waypoint, input_path
+    );
+}
+
+fn migration_args<'a>(command: &str, args: &'a [String]) -> (&'a str, &'a str) {
+    let paths: Vec<_> = args
+        .iter()
+        .filter(|arg| *arg != CONFIRM_EQUIVOCATION_RISK)
+        .collect();
+    if paths.len() != 2 {
+        eprintln!(
+            "Usage: safety-rules {} <config file> <safety data file> {}",
+            command, CONFIRM_EQUIVOCATION_RISK
+        );
+        process::exit(1);
+    }
+    (paths[0].as_str(), paths[1].as_str())
+}
+
+fn confirm_equivocation_risk(args: &[String], warning: &str) {
+    if !args.iter().any(|arg| arg == CONFIRM_EQUIVOCATION_RISK) {
+        eprintln!("{}", warning);
+        eprintln!("Pass {} to proceed.", CONFIRM_EQUIVOCATION_RISK);
+        process::exit(1);
+    }
+}
+
+fn load_config(config_path: &str) -> SafetyRulesConfig {
+    SafetyRulesConfig::load_config(config_path).unwrap_or_else(|e| {
+        eprintln!("Unable to read provided config: {}", e);
+        process::exit(1);
+    })
+}
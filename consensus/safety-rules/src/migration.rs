@@ -0,0 +1,175 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Migration of a validator's persistent safety data from one host to another.
+//!
+//! The safety data holds the highest rounds the validator voted in, which keep it from signing
+//! two conflicting votes. It must therefore only be exported once the validator has stopped on
+//! the old host. The storage of the old host is fenced by the export, so that it can't vote again
+//! once the safety data has been imported on the new one.
+
+use crate::{safety_rules_manager, Error};
+use aptos_config::config::SafetyRulesConfig;
+use aptos_types::waypoint::Waypoint;
+use consensus_types::{common::Author, safety_data::SafetyData};
+use serde::{Deserialize, Serialize};
+
+/// The persistent safety data of a validator, as exported from its safety rules storage.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ExportedSafetyData {
+    pub author: Author,
+    pub safety_data: SafetyData,
+    pub waypoint: Waypoint,
+}
+
+impl ExportedSafetyData {
+    /// Whether importing this data over `current` would let the validator vote again in a round
+    /// it already voted in.
+    pub fn is_behind(&self, current: &SafetyData) -> bool {
+        let exported = &self.safety_data;
+        exported.epoch < current.epoch
+            || (exported.epoch == current.epoch
+                && (exported.last_voted_round < current.last_voted_round
+                    || exported.preferred_round < current.preferred_round
+                    || exported.one_chain_round < current.one_chain_round))
+    }
+}
+
+/// Exports the safety data of the validator from the storage configured in `config`.
+pub fn export_safety_data(config: &SafetyRulesConfig) -> Result<ExportedSafetyData, Error> {
+    safety_rules_manager::storage(config).export_safety_data()
+}
+
+/// Imports safety data exported from another host into the storage configured in `config`.
+pub fn import_safety_data(
+    config: &SafetyRulesConfig,
+    exported: &ExportedSafetyData,
+) -> Result<(), Error> {
+    safety_rules_manager::storage(config).import_safety_data(exported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils, PersistentSafetyStorage, SafetyRules, TSafetyRules};
+    use aptos_crypto::{ed25519::Ed25519PrivateKey, Uniform};
+    use aptos_secure_storage::{InMemoryStorage, OnDiskStorage, Storage};
+    use aptos_temppath::TempPath;
+    use aptos_types::validator_signer::ValidatorSigner;
+    use consensus_types::timeout::Timeout;
+
+    fn safety_storage(author: Author) -> PersistentSafetyStorage {
+        PersistentSafetyStorage::initialize(
+            Storage::from(InMemoryStorage::new()),
+            author,
+            ValidatorSigner::from_int(0).private_key().clone(),
+            Ed25519PrivateKey::generate_for_testing(),
+            Waypoint::default(),
+            true,
+        )
+    }
+
+    #[test]
+    fn test_export_import_safety_data() {
+        let author = Author::random();
+        let mut old_host = safety_storage(author);
+        let mut new_host = safety_storage(author);
+
+        let safety_data = SafetyData::new(9, 8, 7, 6, None);
+        old_host.set_safety_data(safety_data.clone()).unwrap();
+        let exported = old_host.export_safety_data().unwrap();
+        assert_eq!(exported.author, author);
+        assert_eq!(exported.safety_data, safety_data);
+
+        new_host.import_safety_data(&exported).unwrap();
+        assert_eq!(new_host.safety_data().unwrap(), safety_data);
+        assert_eq!(new_host.waypoint().unwrap(), exported.waypoint);
+
+        // Importing the same data again is a no-op
+        new_host.import_safety_data(&exported).unwrap();
+        assert_eq!(new_host.safety_data().unwrap(), safety_data);
+    }
+
+    #[test]
+    fn test_import_rejects_other_validator() {
+        let mut old_host = safety_storage(Author::random());
+        let mut new_host = safety_storage(Author::random());
+
+        let exported = old_host.export_safety_data().unwrap();
+        assert!(matches!(
+            new_host.import_safety_data(&exported),
+            Err(Error::SafetyDataImportRejected(_))
+        ));
+    }
+
+    #[test]
+    fn test_import_rejects_rollback() {
+        let author = Author::random();
+        let mut old_host = safety_storage(author);
+        let mut new_host = safety_storage(author);
+
+        old_host
+            .set_safety_data(SafetyData::new(9, 8, 7, 6, None))
+            .unwrap();
+        let exported = old_host.export_safety_data().unwrap();
+
+        // The new host already voted in a later round of the same epoch
+        for current in [
+            SafetyData::new(9, 9, 7, 6, None),
+            SafetyData::new(9, 8, 8, 6, None),
+            SafetyData::new(9, 8, 7, 7, None),
+            SafetyData::new(10, 0, 0, 0, None),
+        ] {
+            new_host.set_safety_data(current.clone()).unwrap();
+            assert!(matches!(
+                new_host.import_safety_data(&exported),
+                Err(Error::SafetyDataImportRejected(_))
+            ));
+            assert_eq!(new_host.safety_data().unwrap(), current);
+        }
+    }
+
+    #[test]
+    fn test_export_fences_storage() {
+        let signer = ValidatorSigner::from_int(0);
+        let path = TempPath::new();
+        path.create_as_file().unwrap();
+        // Both storages are backed by the same file, and don't cache the safety data
+        let on_disk_storage = || Storage::from(OnDiskStorage::new(path.path().to_path_buf()));
+        let storage = PersistentSafetyStorage::initialize(
+            on_disk_storage(),
+            signer.author(),
+            signer.private_key().clone(),
+            Ed25519PrivateKey::generate_for_testing(),
+            test_utils::validator_signers_to_waypoint(&[&signer]),
+            false,
+        );
+
+        let (proof, genesis_qc) = test_utils::make_genesis(&signer);
+        let round = genesis_qc.certified_block().round();
+        let a1 = test_utils::make_proposal_with_qc(round + 1, genesis_qc, &signer, None);
+        let a2 = test_utils::make_proposal_with_parent(vec![], round + 2, &a1, None, &signer, None);
+        let mut safety_rules = SafetyRules::new(storage, false, false);
+        safety_rules.initialize(&proof).unwrap();
+        safety_rules.construct_and_sign_vote(&a1).unwrap();
+
+        let mut old_host = PersistentSafetyStorage::new(on_disk_storage(), false);
+        let exported = old_host.export_safety_data().unwrap();
+        assert_eq!(exported.safety_data.last_voted_round, round + 1);
+        // The safety data can only be exported once
+        assert_eq!(
+            old_host.export_safety_data().unwrap_err(),
+            Error::SafetyDataExported
+        );
+
+        // The old host can't vote, propose or time out anymore, even in a new epoch
+        assert!(safety_rules.construct_and_sign_vote(&a2).is_err());
+        assert!(safety_rules
+            .sign_proposal(a2.vote_proposal.block().block_data())
+            .is_err());
+        assert!(safety_rules
+            .sign_timeout(&Timeout::new(1, round + 2))
+            .is_err());
+        assert!(safety_rules.initialize(&proof).is_err());
+    }
+}
@@ -4,6 +4,7 @@
 use crate::{
     counters,
     logging::{self, LogEntry, LogEvent},
+    migration::ExportedSafetyData,
     Error,
 };
 use aptos_crypto::{
@@ -185,6 +186,85 @@ impl PersistentSafetyStorage {
         Ok(())
     }
 
+    /// Exports the safety data and waypoint of this validator, so that they can be imported into
+    /// the storage of the host the validator is migrated to. The safety data of this storage is
+    /// then fenced, with the highest epoch and rounds, so that this host can't sign anything
+    /// anymore, even after an epoch change. Only a new storage can vote for the validator here
+    /// again.
+    pub fn export_safety_data(&mut self) -> Result<ExportedSafetyData, Error> {
+        let safety_data = self.safety_data()?;
+        if safety_data == Self::fenced_safety_data() {
+            return Err(Error::SafetyDataExported);
+        }
+        let exported = ExportedSafetyData {
+            author: self.author()?,
+            safety_data,
+            waypoint: self.waypoint()?,
+        };
+        self.set_safety_data(Self::fenced_safety_data())?;
+        info!(
+            logging::SafetyLogSchema::new(LogEntry::ExportSafetyData, LogEvent::Success)
+                .author(exported.author)
+                .epoch(exported.safety_data.epoch)
+                .last_voted_round(exported.safety_data.last_voted_round)
+                .preferred_round(exported.safety_data.preferred_round)
+                .waypoint(exported.waypoint)
+        );
+        Ok(exported)
+    }
+
+    /// The safety data of a storage whose safety data was exported. No epoch can be initialized
+    /// and no round can be voted in, proposed or timed out from it.
+    fn fenced_safety_data() -> SafetyData {
+        SafetyData::new(u64::MAX, u64::MAX, u64::MAX, u64::MAX, None)
+    }
+
+    /// Imports the safety data and waypoint exported from another host of this validator. The
+    /// import is rejected if it belongs to another validator, or if it would roll back the safety
+    /// data or waypoint already in storage, as voting from the older state could equivocate.
+    pub fn import_safety_data(&mut self, exported: &ExportedSafetyData) -> Result<(), Error> {
+        let author = self.author()?;
+        if exported.author != author {
+            return Err(Error::SafetyDataImportRejected(format!(
+                "exported by validator {}, but this storage belongs to {}",
+                exported.author, author
+            )));
+        }
+
+        match self.safety_data() {
+            Ok(current) if exported.is_behind(&current) => {
+                return Err(Error::SafetyDataImportRejected(format!(
+                    "exported {} is behind the current {}",
+                    exported.safety_data, current
+                )));
+            }
+            Ok(_) | Err(Error::SecureStorageMissingDataError(_)) => (),
+            Err(error) => return Err(error),
+        }
+        match self.waypoint() {
+            Ok(current) if exported.waypoint.version() < current.version() => {
+                return Err(Error::SafetyDataImportRejected(format!(
+                    "exported waypoint {} is behind the current waypoint {}",
+                    exported.waypoint, current
+                )));
+            }
+            Ok(_) | Err(Error::SecureStorageMissingDataError(_)) => (),
+            Err(error) => return Err(error),
+        }
+
+        self.set_safety_data(exported.safety_data.clone())?;
+        self.set_waypoint(&exported.waypoint)?;
+        info!(
+            logging::SafetyLogSchema::new(LogEntry::ImportSafetyData, LogEvent::Success)
+                .author(author)
+                .epoch(exported.safety_data.epoch)
+                .last_voted_round(exported.safety_data.last_voted_round)
+                .preferred_round(exported.safety_data.preferred_round)
+                .waypoint(exported.waypoint)
+        );
+        Ok(())
+    }
+
     #[cfg(any(test, feature = "testing"))]
     pub fn internal_store(&mut self) -> &mut Storage {
         &mut self.internal_store
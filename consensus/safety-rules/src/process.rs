@@ -19,7 +19,7 @@ impl Process {
         let storage = safety_rules_manager::storage(&config);
 
         let verify_vote_proposal_signature = config.verify_vote_proposal_signature;
-        let export_consensus_key = config.export_consensus_key;
+        let export_consensus_key = config.exports_consensus_key();
         let service = match &config.service {
             SafetyRulesService::Process(service) => service,
             _ => panic!("Unexpected SafetyRules service: {:?}", config.service),
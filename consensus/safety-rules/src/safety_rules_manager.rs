@@ -68,7 +68,7 @@ impl SafetyRulesManager {
 
         let storage = storage(config);
         let verify_vote_proposal_signature = config.verify_vote_proposal_signature;
-        let export_consensus_key = config.export_consensus_key;
+        let export_consensus_key = config.exports_consensus_key();
         match config.service {
             SafetyRulesService::Local => Self::new_local(
                 storage,
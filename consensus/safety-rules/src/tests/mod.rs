@@ -3,6 +3,7 @@
 
 mod local;
 mod networking;
+mod pkcs11;
 mod safety_rules;
 mod serializer;
 mod suite;
@@ -0,0 +1,76 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{test_utils, tests::suite, SafetyRulesManager};
+use aptos_config::config::{
+    Pkcs11Config, SafetyRulesConfig, SafetyRulesService, SafetyRulesTestConfig, SecureBackend,
+    Token,
+};
+use aptos_crypto::{ed25519::Ed25519PrivateKey, Uniform};
+use aptos_secure_storage::{KVStorage, Storage};
+use aptos_types::validator_signer::ValidatorSigner;
+
+// These tests must be run against a dedicated token (e.g., initialized with SoftHSM) via:
+// `cargo xtest -- --ignored --test-threads=1`
+// Also the constants below must be defined with proper values -- never commit these values to
+// the repository.
+const LIBRARY: &str = "LIBRARY";
+const TOKEN_LABEL: &str = "TOKEN_LABEL";
+const PIN: &str = "PIN";
+
+/// Safety rules sign with the keys of the token, which can't be exported from it, even when
+/// configured to export the consensus key.
+#[ignore]
+#[test]
+fn test() {
+    let boolean_values = [false, true];
+    for verify_vote_proposal_signature in &boolean_values {
+        for export_consensus_key in &boolean_values {
+            suite::run_test_suite(&safety_rules(
+                *verify_vote_proposal_signature,
+                *export_consensus_key,
+            ));
+        }
+    }
+}
+
+fn safety_rules(
+    verify_vote_proposal_signature: bool,
+    export_consensus_key: bool,
+) -> suite::Callback {
+    Box::new(move || {
+        let signer = ValidatorSigner::from_int(0);
+        let backend = SecureBackend::Pkcs11(Pkcs11Config {
+            library: LIBRARY.into(),
+            token_label: TOKEN_LABEL.into(),
+            pin: Token::FromConfig(PIN.into()),
+            namespace: None,
+        });
+        let mut storage = Storage::from(&backend);
+        storage.reset_and_clear().unwrap();
+
+        let mut test_config = SafetyRulesTestConfig::new(signer.author());
+        test_config.consensus_key(signer.private_key().clone());
+        test_config.execution_key(Ed25519PrivateKey::generate_for_testing());
+        test_config.waypoint = Some(test_utils::validator_signers_to_waypoint(&[&signer]));
+        let config = SafetyRulesConfig {
+            backend,
+            service: SafetyRulesService::Local,
+            test: Some(test_config),
+            verify_vote_proposal_signature,
+            export_consensus_key,
+            ..Default::default()
+        };
+        let safety_rules_manager = SafetyRulesManager::new(&config);
+        let safety_rules = safety_rules_manager.client();
+        (
+            safety_rules,
+            signer,
+            if verify_vote_proposal_signature {
+                Some(Ed25519PrivateKey::generate_for_testing())
+            } else {
+                None
+            },
+        )
+    })
+}
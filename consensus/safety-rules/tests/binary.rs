@@ -6,7 +6,7 @@ use aptos_config::{
     utils,
 };
 use aptos_types::validator_signer::ValidatorSigner;
-use safety_rules::{test_utils, SafetyRulesManager};
+use safety_rules::{migration::ExportedSafetyData, test_utils, SafetyRulesManager};
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
 const BINARY: &str = env!("CARGO_BIN_EXE_safety-rules");
@@ -48,3 +48,43 @@ fn test_consensus_state() {
         .expect("could not wait on safety-rules process");
     consensus_state.unwrap();
 }
+
+#[test]
+fn test_export_import_safety_data() {
+    let mut config = NodeConfig::random().consensus.safety_rules;
+    let test_config = config.test.as_mut().unwrap();
+    let private_key = test_config.consensus_key.as_ref().unwrap().private_key();
+    let signer = ValidatorSigner::new(test_config.author, private_key);
+    let waypoint = test_utils::validator_signers_to_waypoint(&[&signer]);
+    test_config.waypoint = Some(waypoint);
+
+    let config_path = aptos_temppath::TempPath::new();
+    config_path.create_as_file().unwrap();
+    config.save_config(config_path.path()).unwrap();
+    let safety_data_path = aptos_temppath::TempPath::new();
+
+    let run = |operation: &str, confirm: bool| {
+        let mut command = std::process::Command::new(BINARY);
+        command
+            .arg(operation)
+            .arg(config_path.path())
+            .arg(safety_data_path.path());
+        if confirm {
+            command.arg("--confirm-equivocation-risk");
+        }
+        command.status().unwrap()
+    };
+
+    // Both operations require confirming the equivocation risk
+    assert!(!run("export-safety-data", false).success());
+    assert!(!safety_data_path.path().exists());
+    assert!(run("export-safety-data", true).success());
+
+    let exported: ExportedSafetyData =
+        serde_json::from_slice(&std::fs::read(safety_data_path.path()).unwrap()).unwrap();
+    assert_eq!(exported.author, signer.author());
+    assert_eq!(exported.waypoint, waypoint);
+
+    assert!(!run("import-safety-data", false).success());
+    assert!(run("import-safety-data", true).success());
+}
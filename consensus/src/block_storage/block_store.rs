@@ -139,18 +139,44 @@ impl BlockStore {
     async fn try_commit(&self) {
         // reproduce the same batches (important for the commit phase)
 
-        let mut certs = self.inner.read().get_all_quorum_certs_with_commit_info();
-        certs.sort_unstable_by_key(|qc| qc.commit_info().round());
+        let mut ledger_infos: Vec<_> = self
+            .inner
+            .read()
+            .get_all_quorum_certs_with_commit_info()
+            .into_iter()
+            .map(|qc| qc.ledger_info().clone())
+            .collect();
+        // the ordered proofs queued in the buffer manager before the restart (decoupled execution)
+        let (ordered_proofs, stale_proofs): (Vec<_>, Vec<_>) = self
+            .storage
+            .retrieve_ordered_proofs()
+            .unwrap_or_else(|e| {
+                error!("Failed to retrieve ordered proofs. {}", e.to_string());
+                vec![]
+            })
+            .into_iter()
+            .partition(|li| li.commit_info().round() > self.commit_root().round());
+        if let Err(e) = self.storage.delete_ordered_proofs(
+            stale_proofs
+                .iter()
+                .map(|li| li.commit_info().id())
+                .collect(),
+        ) {
+            error!("Failed to delete stale ordered proofs. {}", e.to_string());
+        }
+        ledger_infos.extend(ordered_proofs);
+        ledger_infos.sort_unstable_by_key(|li| li.commit_info().round());
+        ledger_infos.dedup_by_key(|li| li.commit_info().round());
 
-        for qc in certs {
-            if qc.commit_info().round() > self.commit_root().round() {
+        for ledger_info in ledger_infos {
+            if ledger_info.commit_info().round() > self.commit_root().round() {
                 info!(
                     "trying to commit to round {} with ledger info {}",
-                    qc.commit_info().round(),
-                    qc.ledger_info()
+                    ledger_info.commit_info().round(),
+                    ledger_info
                 );
 
-                if let Err(e) = self.commit(qc.ledger_info().clone()).await {
+                if let Err(e) = self.commit(ledger_info).await {
                     error!("Error in try-committing blocks. {}", e.to_string());
                 }
             }
@@ -200,7 +226,8 @@ impl BlockStore {
             vec![],                   /* compute_status */
             vec![],                   /* txn_infos */
             vec![],                   /* reconfig_events */
-        );
+        )
+        .expect("Invalid accumulator of the committed trees");
 
         let executed_root_block = ExecutedBlock::new(
             root_block,
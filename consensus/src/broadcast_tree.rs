@@ -0,0 +1,266 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Broadcast trees used to disseminate proposals.
+//!
+//! Instead of sending its proposal to every validator, the proposer only sends it to its
+//! children in a tree rooted at itself, and every validator relays the proposal to its own
+//! children once the proposal is verified. The proposer therefore uploads `fanout` copies
+//! of the proposal instead of `n - 1`, and the proposal reaches every validator after
+//! O(log n) hops.
+//!
+//! The trees are deterministic: they only depend on the validator set of the epoch, the
+//! root and the locally configured fanout, so every validator with the same configuration
+//! computes the same tree without coordination. Proposals are relayed as RPCs, which the
+//! children acknowledge on receipt. If a child doesn't acknowledge the proposal in time
+//! (e.g., because it's not connected), the proposal is sent directly to the child's subtree
+//! instead.
+
+use crate::{
+    counters,
+    network_interface::{ConsensusMsg, ConsensusNetworkSender, RelayedProposalMsg},
+};
+use aptos_logger::prelude::*;
+use aptos_types::validator_verifier::ValidatorVerifier;
+use consensus_types::{
+    common::{Author, Round},
+    proposal_msg::ProposalMsg,
+};
+use network::protocols::network::ApplicationNetworkSender;
+use std::{
+    collections::{BTreeMap, HashSet},
+    time::Duration,
+};
+
+/// Proposals more than this many rounds behind the highest relayed proposal aren't relayed.
+const MAX_RELAYED_ROUND_LAG: Round = 10;
+
+/// The broadcast trees over the validators of an epoch
+#[derive(Clone, Debug)]
+pub struct BroadcastTree {
+    /// The validators, in the same order on every node
+    validators: Vec<Author>,
+    fanout: usize,
+    /// How long to wait for a child to acknowledge a relayed proposal
+    relay_timeout: Duration,
+}
+
+impl BroadcastTree {
+    /// Returns `None` if the fanout is 0, as no tree can be built.
+    pub fn new(verifier: &ValidatorVerifier, fanout: u64, relay_timeout: Duration) -> Option<Self> {
+        if fanout == 0 {
+            return None;
+        }
+        Some(Self {
+            validators: verifier.get_ordered_account_addresses_iter().collect(),
+            fanout: fanout as usize,
+            relay_timeout,
+        })
+    }
+
+    /// The children of `node` in the tree rooted at `root`. The tree is laid out as a
+    /// complete `fanout`-ary tree over the validators, rotated so that `root` is first.
+    pub fn children(&self, root: Author, node: Author) -> Vec<Author> {
+        let num_validators = self.validators.len();
+        let (root_index, node_index) = match (self.index_of(root), self.index_of(node)) {
+            (Some(root_index), Some(node_index)) => (root_index, node_index),
+            _ => return vec![],
+        };
+
+        let position = (node_index + num_validators - root_index) % num_validators;
+        let first_child = position.saturating_mul(self.fanout).saturating_add(1);
+        (first_child..first_child.saturating_add(self.fanout).min(num_validators))
+            .map(|child_position| self.validators[(child_position + root_index) % num_validators])
+            .collect()
+    }
+
+    /// The subtree of `node` in the tree rooted at `root`, including `node`.
+    pub fn subtree(&self, root: Author, node: Author) -> Vec<Author> {
+        let mut subtree = vec![];
+        let mut pending = vec![node];
+        while let Some(node) = pending.pop() {
+            pending.extend(self.children(root, node));
+            subtree.push(node);
+        }
+        subtree
+    }
+
+    /// Relays the proposal to the children of `node` in the tree rooted at the proposer.
+    /// The subtrees of the children which don't acknowledge the proposal within the relay
+    /// timeout are sent the proposal directly. Must be called within a tokio runtime.
+    pub fn relay(
+        &self,
+        network_sender: &ConsensusNetworkSender,
+        node: Author,
+        proposal_msg: &ProposalMsg,
+    ) {
+        let root = proposal_msg.proposer();
+        let relayed_msg = ConsensusMsg::RelayedProposalMsg(Box::new(RelayedProposalMsg::new(
+            proposal_msg.clone(),
+        )));
+        let direct_msg = ConsensusMsg::ProposalMsg(Box::new(proposal_msg.clone()));
+
+        for child in self.children(root, node) {
+            let network_sender = network_sender.clone();
+            let relayed_msg = relayed_msg.clone();
+            let direct_msg = direct_msg.clone();
+            let subtree = self.subtree(root, child);
+            let relay_timeout = self.relay_timeout;
+            tokio::spawn(async move {
+                match network_sender
+                    .send_rpc(child, relayed_msg, relay_timeout)
+                    .await
+                {
+                    Ok(ConsensusMsg::RelayedProposalAck) => return,
+                    Ok(response) => warn!(
+                        remote_peer = child,
+                        "Unexpected response to a relayed proposal: {:?}", response,
+                    ),
+                    Err(error) => warn!(
+                        remote_peer = child,
+                        error = ?error,
+                        "Failed to relay the proposal, sending it to the subtree directly",
+                    ),
+                }
+                counters::PROPOSAL_RELAY_FALLBACKS.inc();
+                if let Err(error) = network_sender.send_to_many(subtree.into_iter(), direct_msg) {
+                    warn!(
+                        remote_peer = child,
+                        error = ?error,
+                        "Failed to send the proposal to the subtree",
+                    );
+                }
+            });
+        }
+    }
+
+    fn index_of(&self, author: Author) -> Option<usize> {
+        self.validators.binary_search(&author).ok()
+    }
+}
+
+/// The proposals relayed in an epoch, by round and proposer. Only the recent rounds are kept.
+#[derive(Debug, Default)]
+pub struct RelayedProposals {
+    proposers_by_round: BTreeMap<Round, HashSet<Author>>,
+}
+
+impl RelayedProposals {
+    /// Records that the proposal of `proposer` for `round` is relayed. Returns false if it
+    /// shouldn't be relayed: either it was relayed already, or its round is more than
+    /// `MAX_RELAYED_ROUND_LAG` rounds behind the highest relayed round.
+    pub fn insert(&mut self, round: Round, proposer: Author) -> bool {
+        let highest_round = self
+            .proposers_by_round
+            .keys()
+            .next_back()
+            .map_or(round, |highest_round| round.max(*highest_round));
+        let min_round = highest_round.saturating_sub(MAX_RELAYED_ROUND_LAG);
+        if round < min_round
+            || !self
+                .proposers_by_round
+                .entry(round)
+                .or_default()
+                .insert(proposer)
+        {
+            return false;
+        }
+        self.proposers_by_round = self.proposers_by_round.split_off(&min_round);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BroadcastTree, RelayedProposals, MAX_RELAYED_ROUND_LAG};
+    use aptos_types::validator_verifier::random_validator_verifier;
+    use consensus_types::common::Author;
+    use std::{collections::HashSet, time::Duration};
+
+    /// Returns all the nodes reached from the root, checking each is reached exactly once
+    fn reached(tree: &BroadcastTree, root: Author) -> HashSet<Author> {
+        let mut reached = HashSet::new();
+        let mut pending = vec![root];
+        while let Some(node) = pending.pop() {
+            assert!(reached.insert(node));
+            pending.extend(tree.children(root, node));
+        }
+        reached
+    }
+
+    #[test]
+    fn test_tree_covers_validators() {
+        let (_, verifier) = random_validator_verifier(20, None, false);
+        let validators: HashSet<_> = verifier.get_ordered_account_addresses_iter().collect();
+        for fanout in 1..=20 {
+            let tree = BroadcastTree::new(&verifier, fanout, Duration::from_secs(1)).unwrap();
+            for root in &validators {
+                assert_eq!(reached(&tree, *root), validators);
+                assert_eq!(
+                    tree.subtree(*root, *root)
+                        .into_iter()
+                        .collect::<HashSet<_>>(),
+                    validators
+                );
+                assert!(tree.children(*root, *root).len() <= fanout as usize);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tree_shape() {
+        let (_, verifier) = random_validator_verifier(7, None, false);
+        let validators: Vec<_> = verifier.get_ordered_account_addresses_iter().collect();
+        let tree = BroadcastTree::new(&verifier, 2, Duration::from_secs(1)).unwrap();
+
+        // The tree is rotated to start at the root
+        let root = validators[3];
+        assert_eq!(
+            tree.children(root, root),
+            vec![validators[4], validators[5]]
+        );
+        assert_eq!(
+            tree.children(root, validators[4]),
+            vec![validators[6], validators[0]]
+        );
+        assert_eq!(
+            tree.children(root, validators[5]),
+            vec![validators[1], validators[2]]
+        );
+        assert!(tree.children(root, validators[6]).is_empty());
+        assert_eq!(
+            tree.subtree(root, validators[4]),
+            vec![validators[4], validators[0], validators[6]]
+        );
+
+        // Unknown nodes have no children
+        assert!(tree.children(root, Author::random()).is_empty());
+        assert!(BroadcastTree::new(&verifier, 0, Duration::from_secs(1)).is_none());
+    }
+
+    #[test]
+    fn test_relayed_proposals() {
+        let mut relayed_proposals = RelayedProposals::default();
+        let (proposer, other_proposer) = (Author::random(), Author::random());
+
+        // Proposals are relayed once per round and proposer
+        assert!(relayed_proposals.insert(5, proposer));
+        assert!(!relayed_proposals.insert(5, proposer));
+        assert!(relayed_proposals.insert(5, other_proposer));
+        assert!(relayed_proposals.insert(4, proposer));
+
+        // Proposals far behind the highest relayed round aren't relayed, nor kept
+        let highest_round = 5 + MAX_RELAYED_ROUND_LAG;
+        assert!(relayed_proposals.insert(highest_round, proposer));
+        assert!(!relayed_proposals.insert(4, other_proposer));
+        assert!(!relayed_proposals.insert(5, proposer));
+        assert!(relayed_proposals.insert(6, proposer));
+        assert_eq!(
+            relayed_proposals
+                .proposers_by_round
+                .keys()
+                .collect::<Vec<_>>(),
+            vec![&5, &6, &highest_round]
+        );
+    }
+}
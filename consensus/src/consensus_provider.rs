@@ -13,7 +13,8 @@ use crate::{
 };
 use aptos_config::config::NodeConfig;
 use aptos_logger::prelude::*;
-use aptos_mempool::ConsensusRequest;
+use aptos_mempool::{ConsensusRequest, TransactionTracer};
+use aptos_metrics::runtime::instrumented_runtime;
 use consensus_notifications::ConsensusNotificationSender;
 use event_notifications::ReconfigNotificationListener;
 use execution_correctness::ExecutionCorrectnessManager;
@@ -21,7 +22,7 @@ use futures::channel::mpsc;
 use network::application::storage::PeerMetadataStorage;
 use std::sync::Arc;
 use storage_interface::DbReaderWriter;
-use tokio::runtime::{self, Runtime};
+use tokio::runtime::Runtime;
 
 /// Helper function to start consensus based on configuration and return the runtime
 pub fn start_consensus(
@@ -33,19 +34,23 @@ pub fn start_consensus(
     aptos_db: DbReaderWriter,
     reconfig_events: ReconfigNotificationListener,
     peer_metadata_storage: Arc<PeerMetadataStorage>,
+    transaction_tracer: Arc<TransactionTracer>,
 ) -> Runtime {
-    let runtime = runtime::Builder::new_multi_thread()
-        .thread_name("consensus")
-        .enable_all()
-        .build()
-        .expect("Failed to create Tokio runtime!");
+    let runtime = instrumented_runtime(
+        "consensus",
+        node_config.runtimes.consensus_worker_threads,
+        node_config.runtimes.stall_threshold(),
+    );
     let storage = Arc::new(StorageWriteProxy::new(node_config, aptos_db.reader.clone()));
-    let txn_manager = Arc::new(MempoolProxy::new(
-        consensus_to_mempool_sender,
-        node_config.consensus.mempool_poll_count,
-        node_config.consensus.mempool_txn_pull_timeout_ms,
-        node_config.consensus.mempool_executed_txn_timeout_ms,
-    ));
+    let txn_manager = Arc::new(
+        MempoolProxy::new(
+            consensus_to_mempool_sender,
+            node_config.consensus.mempool_poll_count,
+            node_config.consensus.mempool_txn_pull_timeout_ms,
+            node_config.consensus.mempool_executed_txn_timeout_ms,
+        )
+        .with_transaction_tracer(transaction_tracer),
+    );
     let execution_correctness_manager = ExecutionCorrectnessManager::new(node_config, aptos_db);
 
     let state_computer = Arc::new(ExecutionProxy::new(
@@ -73,3 +73,22 @@ fn test_delete_block_and_qc() {
     assert_eq!(db.get_blocks().unwrap().len(), 0);
     assert_eq!(db.get_quorum_certificates().unwrap().len(), 0);
 }
+
+#[test]
+fn test_save_and_delete_ordered_proofs() {
+    let tmp_dir = TempPath::new();
+    let db = ConsensusDB::new(&tmp_dir);
+
+    assert_eq!(db.get_ordered_proofs().unwrap().len(), 0);
+
+    let ordered_proof = certificate_for_genesis().ledger_info().clone();
+    db.save_ordered_proof(&ordered_proof).unwrap();
+    assert_eq!(
+        db.get_ordered_proofs().unwrap(),
+        vec![ordered_proof.clone()]
+    );
+
+    db.delete_ordered_proofs(vec![ordered_proof.commit_info().id()])
+        .unwrap();
+    assert_eq!(db.get_ordered_proofs().unwrap().len(), 0);
+}
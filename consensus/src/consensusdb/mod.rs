@@ -8,6 +8,7 @@ mod schema;
 use crate::{
     consensusdb::schema::{
         block::BlockSchema,
+        ordered_proof::OrderedProofSchema,
         quorum_certificate::QCSchema,
         single_entry::{SingleEntryKey, SingleEntrySchema},
     },
@@ -16,8 +17,9 @@ use crate::{
 use anyhow::Result;
 use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
+use aptos_types::ledger_info::LedgerInfoWithSignatures;
 use consensus_types::{block::Block, quorum_cert::QuorumCert};
-use schema::{BLOCK_CF_NAME, QC_CF_NAME, SINGLE_ENTRY_CF_NAME};
+use schema::{BLOCK_CF_NAME, ORDERED_PROOF_CF_NAME, QC_CF_NAME, SINGLE_ENTRY_CF_NAME};
 use schemadb::{Options, ReadOptions, SchemaBatch, DB, DEFAULT_CF_NAME};
 use std::{collections::HashMap, iter::Iterator, path::Path, time::Instant};
 
@@ -32,6 +34,7 @@ impl ConsensusDB {
             BLOCK_CF_NAME,
             QC_CF_NAME,
             SINGLE_ENTRY_CF_NAME,
+            ORDERED_PROOF_CF_NAME,
         ];
 
         let path = db_root_path.as_ref().join("consensusdb");
@@ -141,6 +144,32 @@ impl ConsensusDB {
         self.commit(batch)
     }
 
+    pub fn save_ordered_proof(
+        &self,
+        ordered_proof: &LedgerInfoWithSignatures,
+    ) -> Result<(), DbError> {
+        let mut batch = SchemaBatch::new();
+        batch.put::<OrderedProofSchema>(&ordered_proof.commit_info().id(), ordered_proof)?;
+        self.commit(batch)
+    }
+
+    pub fn delete_ordered_proofs(&self, block_ids: Vec<HashValue>) -> Result<(), DbError> {
+        let mut batch = SchemaBatch::new();
+        block_ids
+            .iter()
+            .try_for_each(|hash| batch.delete::<OrderedProofSchema>(hash))?;
+        self.commit(batch)
+    }
+
+    /// Get all the ordered proofs of the blocks not committed by the buffer manager yet.
+    pub fn get_ordered_proofs(&self) -> Result<Vec<LedgerInfoWithSignatures>, DbError> {
+        let mut iter = self.db.iter::<OrderedProofSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        Ok(iter
+            .map(|res| res.map(|(_block_hash, ordered_proof)| ordered_proof))
+            .collect::<Result<Vec<_>>>()?)
+    }
+
     /// Write the whole schema batch including all data necessary to mutate the ledger
     /// state of some transaction by leveraging rocksdb atomicity support.
     fn commit(&self, batch: SchemaBatch) -> Result<(), DbError> {
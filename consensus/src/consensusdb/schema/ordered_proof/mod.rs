@@ -0,0 +1,50 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the ordered proofs of the blocks waiting in
+//! the buffer manager to be executed and committed.
+//!
+//! Serialized ordered proof bytes identified by the hash of the last ordered block.
+//! ```text
+//! |<---key---->|<-----------value---------->|
+//! | block_hash |  LedgerInfoWithSignatures  |
+//! ```
+
+use super::ORDERED_PROOF_CF_NAME;
+use anyhow::Result;
+use aptos_crypto::HashValue;
+use aptos_types::ledger_info::LedgerInfoWithSignatures;
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+
+define_schema!(
+    OrderedProofSchema,
+    HashValue,
+    LedgerInfoWithSignatures,
+    ORDERED_PROOF_CF_NAME
+);
+
+impl KeyCodec<OrderedProofSchema> for HashValue {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_vec())
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(HashValue::from_slice(data)?)
+    }
+}
+
+impl ValueCodec<OrderedProofSchema> for LedgerInfoWithSignatures {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+#[cfg(test)]
+mod test;
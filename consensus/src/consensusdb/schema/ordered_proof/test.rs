@@ -0,0 +1,14 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use consensus_types::block::block_test_utils::certificate_for_genesis;
+use schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+#[test]
+fn test_encode_decode() {
+    let ordered_proof = certificate_for_genesis().ledger_info().clone();
+    assert_encode_decode::<OrderedProofSchema>(&ordered_proof.commit_info().id(), &ordered_proof);
+}
+
+test_no_panic_decoding!(OrderedProofSchema);
@@ -3,8 +3,8 @@
 
 use aptos_metrics::{
     register_histogram, register_histogram_vec, register_int_counter, register_int_counter_vec,
-    register_int_gauge, DurationHistogram, Histogram, HistogramVec, IntCounter, IntCounterVec,
-    IntGauge,
+    register_int_gauge, register_int_gauge_vec, DurationHistogram, Histogram, HistogramVec,
+    IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -71,6 +71,16 @@ pub static PROPOSALS_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!("aptos_consensus_proposals_count", "Count of the block proposals sent by this validator since last restart (both primary and secondary)").unwrap()
 });
 
+/// Count of the proposals sent directly to the subtree of a broadcast tree node, because
+/// they couldn't be relayed to the node
+pub static PROPOSAL_RELAY_FALLBACKS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_consensus_proposal_relay_fallbacks",
+        "Count of the proposals sent directly to the subtree of a broadcast tree node"
+    )
+    .unwrap()
+});
+
 /// Count the number of times a validator voted for a nil block since last restart.
 pub static VOTE_NIL_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
@@ -145,6 +155,27 @@ pub static ROUND_TIMEOUT_MS: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+/// The clock skew of the proposers, as the difference between the timestamp of their latest
+/// proposal and the local time when it was received.
+pub static PROPOSER_CLOCK_SKEW_MS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_consensus_proposer_clock_skew_ms",
+        "The clock skew of the proposers, observed on the timestamp of their latest proposal.",
+        &["proposer"]
+    )
+    .unwrap()
+});
+
+/// Count the number of proposals rejected because of their timestamp, by reason.
+pub static INVALID_PROPOSAL_TIMESTAMP_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_invalid_proposal_timestamp_count",
+        "Count the number of proposals rejected because of their timestamp.",
+        &["reason"]
+    )
+    .unwrap()
+});
+
 ////////////////////////
 // SYNC MANAGER COUNTERS
 ////////////////////////
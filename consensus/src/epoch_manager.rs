@@ -3,6 +3,7 @@
 
 use crate::{
     block_storage::BlockStore,
+    broadcast_tree::{BroadcastTree, RelayedProposals},
     counters,
     error::{error_kind, DbError},
     experimental::{
@@ -13,6 +14,7 @@ use crate::{
     liveness::{
         leader_reputation::{ActiveInactiveHeuristic, AptosDBBackend, LeaderReputation},
         proposal_generator::ProposalGenerator,
+        proposal_timestamp_checker::ProposalTimestampChecker,
         proposer_election::ProposerElection,
         rotating_proposer_election::{choose_leader, RotatingProposer},
         round_proposer_election::RoundProposer,
@@ -43,6 +45,7 @@ use channel::{aptos_channel, message_queues::QueueStyle};
 use consensus_types::{
     common::{Author, Round},
     epoch_retrieval::EpochRetrievalRequest,
+    proposal_msg::ProposalMsg,
 };
 use event_notifications::ReconfigNotificationListener;
 use futures::{
@@ -98,6 +101,10 @@ pub struct EpochManager {
         aptos_channel::Sender<(Author, Discriminant<VerifiedEvent>), (Author, VerifiedEvent)>,
     >,
     epoch_state: Option<EpochState>,
+    // the broadcast tree of the current epoch, if proposals are relayed
+    broadcast_tree: Option<BroadcastTree>,
+    // the proposals relayed in the current epoch
+    relayed_proposals: RelayedProposals,
 }
 
 impl EpochManager {
@@ -132,6 +139,8 @@ impl EpochManager {
             buffer_manager_reset_tx: None,
             round_manager_tx: None,
             epoch_state: None,
+            broadcast_tree: None,
+            relayed_proposals: RelayedProposals::default(),
         }
     }
 
@@ -198,6 +207,25 @@ impl EpochManager {
                     backend,
                     heuristic,
                     onchain_config.leader_reputation_exclude_round(),
+                    None,
+                ))
+            }
+            ConsensusProposerType::FairLeaderReputation(config) => {
+                let backend = Box::new(AptosDBBackend::new(
+                    proposers.len(),
+                    self.storage.aptos_db(),
+                ));
+                let heuristic = Box::new(ActiveInactiveHeuristic::new(
+                    self.author,
+                    config.leader_reputation.active_weights,
+                    config.leader_reputation.inactive_weights,
+                ));
+                Box::new(LeaderReputation::new(
+                    proposers,
+                    backend,
+                    heuristic,
+                    onchain_config.leader_reputation_exclude_round(),
+                    Some(config.fairness_window),
                 ))
             }
             ConsensusProposerType::RoundProposer(round_proposers) => {
@@ -335,6 +363,7 @@ impl EpochManager {
                 block_rx,
                 reset_rx,
                 verifier,
+                self.storage.clone(),
             );
 
         tokio::spawn(execution_phase.start());
@@ -408,12 +437,23 @@ impl EpochManager {
 
         info!(epoch = epoch, "Create ProposerElection");
         let proposer_election = self.create_proposer_election(&epoch_state, &onchain_config);
+        let broadcast_tree = self.config.proposal_broadcast_fanout.and_then(|fanout| {
+            BroadcastTree::new(
+                &epoch_state.verifier,
+                fanout,
+                Duration::from_millis(self.config.proposal_relay_timeout_ms),
+            )
+        });
+        self.broadcast_tree = broadcast_tree.clone();
+        self.relayed_proposals = RelayedProposals::default();
         let network_sender = NetworkSender::new(
             self.author,
             self.network_sender.clone(),
             self.self_sender.clone(),
             epoch_state.verifier.clone(),
-        );
+        )
+        .with_broadcast_tree(broadcast_tree)
+        .with_broadcast_order(self.config.broadcast_order);
 
         let safety_rules_container = Arc::new(Mutex::new(safety_rules));
 
@@ -445,6 +485,7 @@ impl EpochManager {
             self.txn_manager.clone(),
             self.time_service.clone(),
             self.config.max_block_size,
+            onchain_config.mark_reconfiguration_suffix(),
         );
 
         let mut round_manager = RoundManager::new(
@@ -458,6 +499,10 @@ impl EpochManager {
             self.storage.clone(),
             self.config.sync_only,
             onchain_config,
+            ProposalTimestampChecker::new(
+                self.time_service.clone(),
+                Duration::from_millis(self.config.max_proposal_timestamp_drift_ms),
+            ),
         );
 
         round_manager.init(last_vote).await;
@@ -496,6 +541,9 @@ impl EpochManager {
         peer_id: AccountAddress,
         consensus_msg: ConsensusMsg,
     ) -> anyhow::Result<()> {
+        // relayed proposals are processed like any other proposal once relayed further
+        let relayed = matches!(consensus_msg, ConsensusMsg::RelayedProposalMsg(_));
+
         // we can't verify signatures from a different epoch
         let maybe_unverified_event = self.check_epoch(peer_id, consensus_msg).await?;
 
@@ -515,6 +563,12 @@ impl EpochManager {
                     err
                 })?;
 
+            if let VerifiedEvent::ProposalMsg(proposal_msg) = &verified_event {
+                if relayed {
+                    self.relay_proposal(proposal_msg);
+                }
+            }
+
             // process the verified event
             self.process_event(peer_id, verified_event)?;
         }
@@ -528,6 +582,7 @@ impl EpochManager {
     ) -> anyhow::Result<Option<UnverifiedEvent>> {
         match msg {
             ConsensusMsg::ProposalMsg(_)
+            | ConsensusMsg::RelayedProposalMsg(_)
             | ConsensusMsg::SyncInfo(_)
             | ConsensusMsg::VoteMsg(_)
             | ConsensusMsg::CommitVoteMsg(_)
@@ -602,6 +657,19 @@ impl EpochManager {
         Ok(())
     }
 
+    /// Relays the verified proposal to our children in the broadcast tree of the epoch, unless
+    /// it was already relayed or is too old (see `RelayedProposals`).
+    fn relay_proposal(&mut self, proposal_msg: &ProposalMsg) {
+        if let Some(broadcast_tree) = &self.broadcast_tree {
+            if self
+                .relayed_proposals
+                .insert(proposal_msg.proposal().round(), proposal_msg.proposer())
+            {
+                broadcast_tree.relay(&self.network_sender, self.author, proposal_msg);
+            }
+        }
+    }
+
     fn forward_to_round_manager(&mut self, peer_id: Author, event: VerifiedEvent) {
         let sender = self
             .round_manager_tx
@@ -9,41 +9,18 @@ use itertools::zip_eq;
 use aptos_crypto::ed25519::Ed25519Signature;
 use aptos_logger::prelude::*;
 use aptos_types::{
-    account_address::AccountAddress,
-    block_info::BlockInfo,
-    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    account_address::AccountAddress, block_info::BlockInfo, ledger_info::LedgerInfoWithSignatures,
     validator_verifier::ValidatorVerifier,
 };
 use consensus_types::{
-    common::Author, executed_block::ExecutedBlock, experimental::commit_vote::CommitVote,
+    common::Author,
+    executed_block::ExecutedBlock,
+    experimental::{commit_decision::CommitDecision, commit_vote::CommitVote},
 };
 
 use crate::{experimental::hashable::Hashable, state_replication::StateComputerCommitCallBackType};
 use aptos_crypto::HashValue;
 
-fn generate_commit_proof(
-    commit_info: &BlockInfo,
-    ordered_proof: &LedgerInfoWithSignatures,
-) -> LedgerInfo {
-    LedgerInfo::new(
-        commit_info.clone(),
-        ordered_proof.ledger_info().consensus_data_hash(),
-    )
-}
-
-fn aggregate_ledger_info(
-    commit_ledger_info: &LedgerInfo,
-    unverified_signatures: BTreeMap<AccountAddress, Ed25519Signature>,
-    validator: &ValidatorVerifier,
-) -> LedgerInfoWithSignatures {
-    let valid_sigs = unverified_signatures
-        .into_iter()
-        .filter(|(author, sig)| validator.verify(*author, commit_ledger_info, sig).is_ok())
-        .collect();
-
-    LedgerInfoWithSignatures::new(commit_ledger_info.clone(), valid_sigs)
-}
-
 // we differentiate buffer items at different stages
 // for better code readability
 pub struct OrderedItem {
@@ -139,8 +116,8 @@ impl BufferItem {
                     );
                     commit_info.change_timestamp(ts);
                 }
-                let commit_proof = aggregate_ledger_info(
-                    &generate_commit_proof(&commit_info, &ordered_proof),
+                let commit_proof = CommitDecision::aggregate_signatures(
+                    &CommitDecision::commit_ledger_info(commit_info.clone(), &ordered_proof),
                     unverified_signatures,
                     validator,
                 );
@@ -207,10 +184,11 @@ impl BufferItem {
 
     /// this function assumes block id matches and the validity of ledger_info and that it has the voting power
     /// it returns an updated item
-    pub fn try_advance_to_aggregated_with_ledger_info(
+    pub fn try_advance_to_aggregated_with_commit_decision(
         self,
-        commit_proof: LedgerInfoWithSignatures,
+        commit_decision: CommitDecision,
     ) -> Self {
+        let commit_proof = commit_decision.ledger_info().clone();
         match self {
             Self::Signed(signed_item) => {
                 let SignedItem {
@@ -250,10 +228,12 @@ impl BufferItem {
             }
             Self::Ordered(ordered_item) => {
                 let ordered = *ordered_item;
-                assert!(ordered
-                    .ordered_proof
-                    .commit_info()
-                    .match_ordered_only(commit_proof.commit_info()));
+                // the blocks aren't executed yet, the decision can only be checked against the
+                // ordered proof
+                if let Err(e) = commit_decision.verify_ordered_proof(&ordered.ordered_proof) {
+                    warn!("Ignore commit decision: {}", e);
+                    return Self::Ordered(Box::new(ordered));
+                }
                 // can't aggregate it without execution, only store the signatures
                 debug!(
                     "{} received commit decision in ordered stage",
@@ -29,6 +29,7 @@ use crate::{
     },
     network::NetworkSender,
     network_interface::ConsensusMsg,
+    persistent_liveness_storage::PersistentLivenessStorage,
     round_manager::VerifiedEvent,
     state_replication::StateComputerCommitCallBackType,
 };
@@ -64,6 +65,8 @@ pub fn create_channel<T>() -> (Sender<T>, Receiver<T>) {
 /// BufferManager handles the states of ordered blocks and
 /// interacts with the execution phase, the signing phase, and
 /// the persisting phase.
+/// The ordered proofs of the queued blocks are persisted in ConsensusDB until the blocks are
+/// committed or dropped by a reset, so that they can be ordered again after a restart.
 pub struct BufferManager {
     author: Author,
 
@@ -90,6 +93,8 @@ pub struct BufferManager {
     stop: bool,
 
     verifier: ValidatorVerifier,
+
+    storage: Arc<dyn PersistentLivenessStorage>,
 }
 
 impl BufferManager {
@@ -105,6 +110,7 @@ impl BufferManager {
         block_rx: UnboundedReceiver<OrderedBlocks>,
         reset_rx: UnboundedReceiver<ResetRequest>,
         verifier: ValidatorVerifier,
+        storage: Arc<dyn PersistentLivenessStorage>,
     ) -> Self {
         let buffer = Buffer::<BufferItem>::new();
 
@@ -131,6 +137,8 @@ impl BufferManager {
             stop: false,
 
             verifier,
+
+            storage,
         }
     }
 
@@ -144,6 +152,14 @@ impl BufferManager {
         } = ordered_blocks;
         debug!("Receive ordered block {}", ordered_proof.commit_info());
 
+        // the write is synced, keep it off the main loop
+        let storage = self.storage.clone();
+        let proof = ordered_proof.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = storage.save_ordered_proof(&proof) {
+                error!("Failed to persist ordered proof {:?}", e);
+            }
+        });
         let item = BufferItem::new_ordered(ordered_blocks, ordered_proof, callback);
         self.buffer.push_back(item);
     }
@@ -194,8 +210,10 @@ impl BufferManager {
     /// Send persist request.
     async fn advance_head(&mut self, target_block_id: HashValue) {
         let mut blocks_to_persist: Vec<Arc<ExecutedBlock>> = vec![];
+        let mut ordered_block_ids = vec![];
 
         while let Some(item) = self.buffer.pop_front() {
+            ordered_block_ids.push(item.block_id());
             blocks_to_persist.extend(
                 item.get_blocks()
                     .iter()
@@ -210,6 +228,8 @@ impl BufferManager {
             }
             if item.block_id() == target_block_id {
                 let aggregated_item = item.unwrap_aggregated();
+                let callback = aggregated_item.callback;
+                let storage = self.storage.clone();
                 if aggregated_item.commit_proof.ledger_info().ends_epoch() {
                     self.commit_msg_tx
                         .notify_epoch_change(EpochChangeProof::new(
@@ -228,7 +248,11 @@ impl BufferManager {
                         // the encoded values are references to the block_tree, storage, and a commit root
                         // the block_tree and storage are the same for all the callbacks in the current epoch
                         // the commit root is used in logging only.
-                        callback: aggregated_item.callback,
+                        // the ordered proofs are deleted in one batch once the blocks are committed
+                        callback: Box::new(move |blocks, ledger_info| {
+                            callback(blocks, ledger_info);
+                            delete_ordered_proofs(storage.as_ref(), ordered_block_ids);
+                        }),
                     })
                     .await
                     .expect("Failed to send persist request");
@@ -245,6 +269,13 @@ impl BufferManager {
         debug!("Receive reset");
 
         self.stop = stop;
+        let mut ordered_block_ids = vec![];
+        let mut cursor = *self.buffer.head_cursor();
+        while cursor.is_some() {
+            ordered_block_ids.push(self.buffer.get(&cursor).block_id());
+            cursor = self.buffer.get_next(&cursor);
+        }
+        delete_ordered_proofs(self.storage.as_ref(), ordered_block_ids);
         self.buffer = Buffer::new();
         self.execution_root = None;
         self.signing_root = None;
@@ -366,9 +397,8 @@ impl BufferManager {
                     .find_elem_by_key(*self.buffer.head_cursor(), target_block_id);
                 if cursor.is_some() {
                     let item = self.buffer.take(&cursor);
-                    let new_item = item.try_advance_to_aggregated_with_ledger_info(
-                        commit_proof.ledger_info().clone(),
-                    );
+                    let new_item =
+                        item.try_advance_to_aggregated_with_commit_decision(*commit_proof);
                     let aggregated = new_item.is_aggregated();
                     self.buffer.set(&cursor, new_item);
                     if aggregated {
@@ -451,3 +481,9 @@ impl BufferManager {
         info!("Buffer manager stops.");
     }
 }
+
+fn delete_ordered_proofs(storage: &dyn PersistentLivenessStorage, block_ids: Vec<HashValue>) {
+    if let Err(e) = storage.delete_ordered_proofs(block_ids) {
+        error!("Failed to delete ordered proofs {:?}", e);
+    }
+}
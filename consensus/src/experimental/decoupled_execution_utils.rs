@@ -11,6 +11,7 @@ use crate::{
     },
     metrics_safety_rules::MetricsSafetyRules,
     network::NetworkSender,
+    persistent_liveness_storage::PersistentLivenessStorage,
     round_manager::VerifiedEvent,
     state_replication::StateComputer,
 };
@@ -32,6 +33,7 @@ pub fn prepare_phases_and_buffer_manager(
     block_rx: UnboundedReceiver<OrderedBlocks>,
     sync_rx: UnboundedReceiver<ResetRequest>,
     verifier: ValidatorVerifier,
+    storage: Arc<dyn PersistentLivenessStorage>,
 ) -> (
     PipelinePhase<ExecutionPhase>,
     PipelinePhase<SigningPhase>,
@@ -90,6 +92,7 @@ pub fn prepare_phases_and_buffer_manager(
             block_rx,
             sync_rx,
             verifier,
+            storage,
         ),
     )
 }
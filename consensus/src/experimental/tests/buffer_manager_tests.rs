@@ -17,6 +17,7 @@ use crate::{
     metrics_safety_rules::MetricsSafetyRules,
     network::NetworkSender,
     network_interface::{ConsensusMsg, ConsensusNetworkSender},
+    persistent_liveness_storage::PersistentLivenessStorage,
     round_manager::{UnverifiedEvent, VerifiedEvent},
     test_utils::{
         consensus_runtime, timed_block_on, EmptyStateComputer, MockStorage,
@@ -63,6 +64,7 @@ pub fn prepare_buffer_manager() -> (
     Vec<ValidatorSigner>,
     Receiver<OrderedBlocks>,
     ValidatorVerifier,
+    Arc<MockStorage>,
 ) {
     let num_nodes = 1;
     let channel_size = 30;
@@ -87,7 +89,7 @@ pub fn prepare_buffer_manager() -> (
 
     let safety_rules_manager = SafetyRulesManager::new_local(safety_storage, false, false);
 
-    let mut safety_rules = MetricsSafetyRules::new(safety_rules_manager.client(), storage);
+    let mut safety_rules = MetricsSafetyRules::new(safety_rules_manager.client(), storage.clone());
     safety_rules.perform_initialize().unwrap();
 
     let (network_reqs_tx, _network_reqs_rx) = aptos_channel::new(QueueStyle::FIFO, 8, None);
@@ -134,6 +136,7 @@ pub fn prepare_buffer_manager() -> (
         block_rx,
         buffer_reset_rx,
         validators.clone(),
+        storage.clone(),
     );
 
     (
@@ -149,6 +152,7 @@ pub fn prepare_buffer_manager() -> (
         signers,
         result_rx,
         validators,
+        storage,
     )
 }
 
@@ -162,6 +166,7 @@ pub fn launch_buffer_manager() -> (
     Vec<ValidatorSigner>,
     Receiver<OrderedBlocks>,
     ValidatorVerifier,
+    Arc<MockStorage>,
 ) {
     let runtime = consensus_runtime();
 
@@ -178,6 +183,7 @@ pub fn launch_buffer_manager() -> (
         signers,
         result_rx,
         validators,
+        storage,
     ) = prepare_buffer_manager();
 
     runtime.spawn(execution_phase_pipeline.start());
@@ -195,6 +201,7 @@ pub fn launch_buffer_manager() -> (
         signers,
         result_rx,
         validators,
+        storage,
     )
 }
 
@@ -217,7 +224,11 @@ async fn loopback_commit_vote(
 
 async fn assert_results(batches: Vec<Vec<ExecutedBlock>>, result_rx: &mut Receiver<OrderedBlocks>) {
     for (i, batch) in enumerate(batches) {
-        let OrderedBlocks { ordered_blocks, .. } = result_rx.next().await.unwrap();
+        let OrderedBlocks {
+            ordered_blocks,
+            ordered_proof,
+            callback,
+        } = result_rx.next().await.unwrap();
         assert_eq!(
             ordered_blocks.last().unwrap().id(),
             batch.last().unwrap().id(),
@@ -226,6 +237,9 @@ async fn assert_results(batches: Vec<Vec<ExecutedBlock>>, result_rx: &mut Receiv
             ordered_blocks.last().unwrap().id(),
             i,
         );
+        // the blocks are committed
+        let blocks: Vec<_> = ordered_blocks.into_iter().map(Arc::new).collect();
+        callback(&blocks, ordered_proof);
     }
 }
 
@@ -242,6 +256,7 @@ fn buffer_manager_happy_path_test() {
         signers,
         mut result_rx,
         verifier,
+        _storage,
     ) = launch_buffer_manager();
 
     let genesis_qc = certificate_for_genesis();
@@ -303,6 +318,7 @@ fn buffer_manager_sync_test() {
         signers,
         mut result_rx,
         verifier,
+        storage,
     ) = launch_buffer_manager();
 
     let genesis_qc = certificate_for_genesis();
@@ -352,12 +368,17 @@ fn buffer_manager_sync_test() {
         // make sure the messages are processed
         sleep(Duration::from_millis(100));
 
+        assert_results(batches[..2].to_vec(), &mut result_rx).await;
+
         // reset
         let (tx, rx) = oneshot::channel::<ResetAck>();
 
         reset_tx.send(ResetRequest { tx, stop: false }).await.ok();
         rx.await.ok();
 
+        // the ordered proofs of the committed or dropped batches are deleted
+        assert!(storage.retrieve_ordered_proofs().unwrap().is_empty());
+
         for i in 2..num_batches {
             block_tx
                 .send(OrderedBlocks {
@@ -369,9 +390,7 @@ fn buffer_manager_sync_test() {
                 .ok();
         }
 
-        // we should only see batches[0..num_batches]
-        assert_results(batches.drain(..2).collect(), &mut result_rx).await;
-
+        // we should only see batches[0..2]
         assert!(matches!(result_rx.next().now_or_never(), None));
     });
 }
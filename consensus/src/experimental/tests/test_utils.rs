@@ -97,7 +97,8 @@ pub fn prepare_executed_blocks_with_ledger_info(
         vec![],
         vec![],
         vec![],
-    );
+    )
+    .unwrap();
 
     let li = LedgerInfo::new(
         proposals.last().unwrap().block().gen_block_info(
@@ -13,6 +13,7 @@
 #![recursion_limit = "512"]
 
 mod block_storage;
+mod broadcast_tree;
 mod consensusdb;
 mod counters;
 mod epoch_manager;
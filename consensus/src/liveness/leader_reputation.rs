@@ -14,7 +14,7 @@ use consensus_types::{
     common::{Author, Round},
 };
 use std::{
-    cmp::Ordering,
+    cmp::{max, Ordering},
     collections::{HashMap, HashSet},
     sync::Arc,
 };
@@ -155,12 +155,16 @@ impl ReputationHeuristic for ActiveInactiveHeuristic {
 
 /// Committed history based proposer election implementation that could help bias towards
 /// successful leaders to help improve performance.
+///
+/// With a fairness window, some rounds are elected round robin instead, so that every proposer
+/// proposes at least once per window and the proposers with a low weight aren't starved.
 pub struct LeaderReputation {
     proposers: Vec<Author>,
     backend: Box<dyn MetadataBackend>,
     heuristic: Box<dyn ReputationHeuristic>,
     already_proposed: Mutex<(Round, HashMap<Author, HashValue>)>,
     exclude_round: u64,
+    fairness_window: Option<u64>,
 }
 
 impl LeaderReputation {
@@ -169,6 +173,7 @@ impl LeaderReputation {
         backend: Box<dyn MetadataBackend>,
         heuristic: Box<dyn ReputationHeuristic>,
         exclude_round: u64,
+        fairness_window: Option<u64>,
     ) -> Self {
         Self {
             proposers,
@@ -176,12 +181,29 @@ impl LeaderReputation {
             heuristic,
             already_proposed: Mutex::new((0, HashMap::new())),
             exclude_round,
+            fairness_window,
         }
     }
+
+    /// Return the proposer of the round if it's elected round robin. One round out of every
+    /// `fairness_window / proposers` is, so that the rotation over all the proposers fits in
+    /// the window. A window smaller than the number of proposers degrades to a plain rotation.
+    fn round_robin_proposer(&self, round: Round) -> Option<Author> {
+        let fairness_window = self.fairness_window?;
+        let num_proposers = self.proposers.len() as u64;
+        let interval = max(fairness_window / num_proposers, 1);
+        if round % interval != 0 {
+            return None;
+        }
+        Some(self.proposers[((round / interval) % num_proposers) as usize])
+    }
 }
 
 impl ProposerElection for LeaderReputation {
     fn get_valid_proposer(&self, round: Round) -> Author {
+        if let Some(proposer) = self.round_robin_proposer(round) {
+            return proposer;
+        }
         let target_round = round.saturating_sub(self.exclude_round);
         let sliding_window = self.backend.get_block_metadata(target_round);
         let mut weights = self.heuristic.get_weights(&self.proposers, &sliding_window);
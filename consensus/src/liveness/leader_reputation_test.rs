@@ -100,6 +100,7 @@ fn test_api() {
             inactive_weight,
         )),
         4,
+        None,
     );
     let round = 42u64;
     // first metadata is ignored because of window size 1
@@ -159,3 +160,75 @@ fn test_api() {
     // good proposal still passes
     assert!(proposer_election.is_valid_proposal(&good_proposal));
 }
+
+/// Weights the candidates by their stake, regardless of the history.
+struct StakeHeuristic {
+    stakes: Vec<u64>,
+}
+
+impl ReputationHeuristic for StakeHeuristic {
+    fn get_weights(&self, _candidates: &[Author], _history: &[NewBlockEvent]) -> Vec<u64> {
+        self.stakes.clone()
+    }
+}
+
+fn stake_weighted_election(
+    proposers: &[Author],
+    stakes: Vec<u64>,
+    fairness_window: Option<u64>,
+) -> LeaderReputation {
+    LeaderReputation::new(
+        proposers.to_vec(),
+        Box::new(MockHistory::new(0, vec![])),
+        Box::new(StakeHeuristic { stakes }),
+        4,
+        fairness_window,
+    )
+}
+
+#[test]
+fn test_fairness_window_on_skewed_stakes() {
+    let proposers = (0..5)
+        .map(|i| ValidatorSigner::random([i; 32]).author())
+        .collect::<Vec<_>>();
+    let stakes = vec![1_000_000, 1_000_000, 1_000_000, 1, 1];
+    let fairness_window = 20;
+    let fair_election = stake_weighted_election(&proposers, stakes.clone(), Some(fairness_window));
+    let reputation_election = stake_weighted_election(&proposers, stakes, None);
+
+    let elected = (1..=500)
+        .map(|round| fair_election.get_valid_proposer(round))
+        .collect::<Vec<_>>();
+    // every proposer, including the ones with a negligible stake, is elected in any window
+    for window in elected.windows(fairness_window as usize) {
+        for proposer in &proposers {
+            assert!(window.contains(proposer));
+        }
+    }
+    // the other rounds are still elected by reputation, 1 out of every 4 rounds is round robin
+    for (round, proposer) in (1..=500).zip(&elected) {
+        if *proposer != reputation_election.get_valid_proposer(round) {
+            assert_eq!(round % 4, 0);
+        }
+    }
+    // the small proposers only propose in their round robin rounds
+    for proposer in &proposers[3..] {
+        let count = elected.iter().filter(|p| *p == proposer).count();
+        assert_eq!(count, 500 / 4 / proposers.len());
+    }
+}
+
+#[test]
+fn test_fairness_window_smaller_than_proposers() {
+    let proposers = (0..5)
+        .map(|i| ValidatorSigner::random([i; 32]).author())
+        .collect::<Vec<_>>();
+    let election = stake_weighted_election(&proposers, vec![100, 1, 1, 1, 1], Some(3));
+    // degrades to a plain rotation over the proposers
+    for round in 0..20u64 {
+        assert_eq!(
+            election.get_valid_proposer(round),
+            proposers[(round % 5) as usize]
+        );
+    }
+}
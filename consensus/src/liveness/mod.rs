@@ -3,6 +3,7 @@
 
 pub(crate) mod leader_reputation;
 pub(crate) mod proposal_generator;
+pub(crate) mod proposal_timestamp_checker;
 pub(crate) mod proposer_election;
 pub(crate) mod rotating_proposer_election;
 pub(crate) mod round_proposer_election;
@@ -11,6 +12,8 @@ pub(crate) mod round_state;
 #[cfg(test)]
 mod leader_reputation_test;
 #[cfg(test)]
+mod proposal_timestamp_checker_test;
+#[cfg(test)]
 mod rotating_proposer_test;
 #[cfg(test)]
 mod round_proposer_test;
@@ -41,6 +41,9 @@ pub struct ProposalGenerator {
     time_service: Arc<dyn TimeService>,
     // Max number of transactions to be added to a proposed block.
     max_block_size: u64,
+    // Whether the blocks proposed after a reconfiguration are marked as its suffix, as enabled by
+    // the on-chain consensus config
+    mark_reconfiguration_suffix: bool,
     // Last round that a proposal was generated
     last_round_generated: Mutex<Round>,
 }
@@ -52,6 +55,7 @@ impl ProposalGenerator {
         txn_manager: Arc<dyn TxnManager>,
         time_service: Arc<dyn TimeService>,
         max_block_size: u64,
+        mark_reconfiguration_suffix: bool,
     ) -> Self {
         Self {
             author,
@@ -59,6 +63,7 @@ impl ProposalGenerator {
             txn_manager,
             time_service,
             max_block_size,
+            mark_reconfiguration_suffix,
             last_round_generated: Mutex::new(0),
         }
     }
@@ -99,60 +104,79 @@ impl ProposalGenerator {
 
         let hqc = self.ensure_highest_quorum_cert(round)?;
 
-        let (payload, timestamp) = if hqc.certified_block().has_reconfiguration() {
+        if hqc.certified_block().has_reconfiguration() {
             // Reconfiguration rule - we propose empty blocks with parents' timestamp
             // after reconfiguration until it's committed
-            (vec![], hqc.certified_block().timestamp_usecs())
-        } else {
-            // One needs to hold the blocks with the references to the payloads while get_block is
-            // being executed: pending blocks vector keeps all the pending ancestors of the extended branch.
-            let mut pending_blocks = self
-                .block_store
-                .path_from_commit_root(hqc.certified_block().id())
-                .ok_or_else(|| format_err!("HQC {} already pruned", hqc.certified_block().id()))?;
-            // Avoid txn manager long poll if the root block has txns, so that the leader can
-            // deliver the commit proof to others without delay.
-            pending_blocks.push(self.block_store.commit_root());
-
-            // Exclude all the pending transactions: these are all the ancestors of
-            // parent (including) up to the root (including).
-            let exclude_payload: Vec<&Vec<_>> = pending_blocks
-                .iter()
-                .flat_map(|block| block.payload())
-                .collect();
-
-            let pending_ordering = self
+            if !self.mark_reconfiguration_suffix {
+                return Ok(BlockData::new_proposal(
+                    vec![],
+                    self.author,
+                    round,
+                    hqc.certified_block().timestamp_usecs(),
+                    hqc.as_ref().clone(),
+                ));
+            }
+            // marked as suffix of the reconfiguration block once the on-chain config enables it
+            let reconfig_block_id = self
                 .block_store
-                .path_from_ordered_root(hqc.certified_block().id())
-                .ok_or_else(|| format_err!("HQC {} already pruned", hqc.certified_block().id()))?
-                .iter()
-                .any(|block| !block.payload().map_or(true, |txns| txns.is_empty()));
-
-            // All proposed blocks in a branch are guaranteed to have increasing timestamps
-            // since their predecessor block will not be added to the BlockStore until
-            // the local time exceeds it.
-            let timestamp = self.time_service.get_current_timestamp();
-
-            let payload = self
-                .txn_manager
-                .pull_txns(
-                    self.max_block_size,
-                    exclude_payload,
-                    wait_callback,
-                    pending_ordering,
-                )
-                .await
-                .context("Fail to retrieve txn")?;
-
-            (payload, timestamp.as_micros() as u64)
-        };
+                .get_block(hqc.certified_block().id())
+                .and_then(|parent| parent.block().block_data().reconfiguration_suffix_of())
+                .unwrap_or_else(|| hqc.certified_block().id());
+            return Ok(BlockData::new_reconfiguration_suffix(
+                reconfig_block_id,
+                self.author,
+                round,
+                hqc.as_ref().clone(),
+            ));
+        }
+
+        // One needs to hold the blocks with the references to the payloads while get_block is
+        // being executed: pending blocks vector keeps all the pending ancestors of the extended branch.
+        let mut pending_blocks = self
+            .block_store
+            .path_from_commit_root(hqc.certified_block().id())
+            .ok_or_else(|| format_err!("HQC {} already pruned", hqc.certified_block().id()))?;
+        // Avoid txn manager long poll if the root block has txns, so that the leader can
+        // deliver the commit proof to others without delay.
+        pending_blocks.push(self.block_store.commit_root());
+
+        // Exclude all the pending transactions: these are all the ancestors of
+        // parent (including) up to the root (including). The transactions left out of their
+        // blocks by the block gas limit are proposed again.
+        let exclude_payload: Vec<&[_]> = pending_blocks
+            .iter()
+            .flat_map(|block| block.executed_payload())
+            .collect();
+
+        let pending_ordering = self
+            .block_store
+            .path_from_ordered_root(hqc.certified_block().id())
+            .ok_or_else(|| format_err!("HQC {} already pruned", hqc.certified_block().id()))?
+            .iter()
+            .any(|block| !block.payload().map_or(true, |txns| txns.is_empty()));
+
+        // All proposed blocks in a branch are guaranteed to have increasing timestamps
+        // since their predecessor block will not be added to the BlockStore until
+        // the local time exceeds it.
+        let timestamp = self.time_service.get_current_timestamp();
+
+        let payload = self
+            .txn_manager
+            .pull_txns(
+                self.max_block_size,
+                exclude_payload,
+                wait_callback,
+                pending_ordering,
+            )
+            .await
+            .context("Fail to retrieve txn")?;
 
         // create block proposal
         Ok(BlockData::new_proposal(
             payload,
             self.author,
             round,
-            timestamp,
+            timestamp.as_micros() as u64,
             hqc.as_ref().clone(),
         ))
     }
@@ -0,0 +1,80 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{counters, util::time_service::TimeService};
+use anyhow::{bail, ensure};
+use consensus_types::block::Block;
+use std::{sync::Arc, time::Duration};
+
+const NOT_INCREASING_LABEL: &str = "not_increasing";
+const TOO_FAR_IN_FUTURE_LABEL: &str = "too_far_in_future";
+
+/// Checks the timestamps of proposals against the timestamps of their parents and the local
+/// clock, so that validators don't vote for blocks that move time backwards or too far ahead.
+/// The skew between the clock of the proposer and the local clock is reported for every proposal.
+pub struct ProposalTimestampChecker {
+    time_service: Arc<dyn TimeService>,
+    /// How far ahead of the local clock the timestamp of a proposal may be
+    max_drift: Duration,
+}
+
+impl ProposalTimestampChecker {
+    pub fn new(time_service: Arc<dyn TimeService>, max_drift: Duration) -> Self {
+        Self {
+            time_service,
+            max_drift,
+        }
+    }
+
+    /// Ensures the timestamp of the proposal is strictly greater than the one of its parent, and
+    /// not further ahead of the local clock than the max drift. The suffix of a reconfiguration
+    /// must carry the timestamp of its parent instead.
+    pub fn check(&self, proposal: &Block) -> anyhow::Result<()> {
+        let timestamp_usecs = proposal.timestamp_usecs();
+        let parent = proposal.quorum_cert().certified_block();
+        if parent.has_reconfiguration() {
+            ensure!(
+                timestamp_usecs == parent.timestamp_usecs(),
+                "[ProposalTimestampChecker] Reconfiguration suffix {} must have the same \
+                timestamp as its parent {}",
+                proposal,
+                parent.timestamp_usecs()
+            );
+            return Ok(());
+        }
+
+        let now_usecs = self.time_service.get_current_timestamp().as_micros() as u64;
+        if let Some(author) = proposal.author() {
+            let skew_ms = (i128::from(timestamp_usecs) - i128::from(now_usecs)) / 1000;
+            counters::PROPOSER_CLOCK_SKEW_MS
+                .with_label_values(&[&author.to_string()])
+                .set(skew_ms as i64);
+        }
+
+        if timestamp_usecs <= parent.timestamp_usecs() {
+            counters::INVALID_PROPOSAL_TIMESTAMP_COUNT
+                .with_label_values(&[NOT_INCREASING_LABEL])
+                .inc();
+            bail!(
+                "[ProposalTimestampChecker] Proposal {} timestamp must be greater than its \
+                parent timestamp {}",
+                proposal,
+                parent.timestamp_usecs()
+            );
+        }
+        let max_timestamp_usecs = now_usecs.saturating_add(self.max_drift.as_micros() as u64);
+        if timestamp_usecs > max_timestamp_usecs {
+            counters::INVALID_PROPOSAL_TIMESTAMP_COUNT
+                .with_label_values(&[TOO_FAR_IN_FUTURE_LABEL])
+                .inc();
+            bail!(
+                "[ProposalTimestampChecker] Proposal {} timestamp is more than {:?} ahead of \
+                the local time {}",
+                proposal,
+                self.max_drift,
+                now_usecs
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    liveness::proposal_timestamp_checker::ProposalTimestampChecker,
+    util::{mock_time_service::SimulatedTimeService, time_service::TimeService},
+};
+use aptos_types::validator_signer::ValidatorSigner;
+use consensus_types::block::{block_test_utils::certificate_for_genesis, Block};
+use std::{sync::Arc, time::Duration};
+
+#[test]
+fn test_proposal_timestamp_checker() {
+    let signer = ValidatorSigner::random(None);
+    let time_service = Arc::new(SimulatedTimeService::new());
+    time_service.sleep(Duration::from_secs(100));
+    let checker = ProposalTimestampChecker::new(time_service.clone(), Duration::from_secs(5));
+    let genesis_qc = certificate_for_genesis();
+    let genesis_timestamp_usecs = genesis_qc.certified_block().timestamp_usecs();
+    let proposal = |timestamp_usecs| {
+        Block::new_proposal(vec![], 1, timestamp_usecs, genesis_qc.clone(), &signer)
+    };
+
+    // Timestamps must be strictly increasing
+    assert!(checker.check(&proposal(genesis_timestamp_usecs)).is_err());
+    assert!(checker
+        .check(&proposal(genesis_timestamp_usecs + 1))
+        .is_ok());
+
+    // Timestamps may be ahead of the local clock, up to the max drift
+    let now_usecs = time_service.get_current_timestamp().as_micros() as u64;
+    assert!(checker.check(&proposal(now_usecs + 5_000_000)).is_ok());
+    assert!(checker.check(&proposal(now_usecs + 5_000_001)).is_err());
+
+    // Once the local clock catches up, the same proposal is valid
+    time_service.sleep(Duration::from_secs(1));
+    assert!(checker.check(&proposal(now_usecs + 5_000_001)).is_ok());
+}
@@ -2,11 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    broadcast_tree::BroadcastTree,
     counters,
     logging::LogEvent,
     network_interface::{ConsensusMsg, ConsensusNetworkEvents, ConsensusNetworkSender},
 };
 use anyhow::{anyhow, ensure};
+use aptos_config::config::BroadcastOrder;
 use aptos_logger::prelude::*;
 use aptos_metrics::monitor;
 use aptos_types::{
@@ -19,6 +21,7 @@ use consensus_types::{
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse, MAX_BLOCKS_PER_REQUEST},
     common::Author,
     experimental::commit_decision::CommitDecision,
+    proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
     vote_msg::VoteMsg,
 };
@@ -35,6 +38,33 @@ use std::{
     time::Duration,
 };
 
+/// Orders the recipients of a broadcast: with `ClosestFirst`, by increasing round trip latency,
+/// the recipients without a known latency last. `LeaderFirst` moves the next leader to the front
+/// of the closest first order. Ties keep the order of the validator set.
+pub fn order_recipients(
+    mut recipients: Vec<Author>,
+    broadcast_order: BroadcastOrder,
+    next_leader: Option<Author>,
+    latency: impl Fn(Author) -> Option<Duration>,
+) -> Vec<Author> {
+    if broadcast_order == BroadcastOrder::ValidatorSet {
+        return recipients;
+    }
+    recipients.sort_by_cached_key(|author| match latency(*author) {
+        Some(latency) => (false, latency),
+        None => (true, Duration::ZERO),
+    });
+    if broadcast_order == BroadcastOrder::LeaderFirst {
+        if let Some(index) =
+            next_leader.and_then(|leader| recipients.iter().position(|a| *a == leader))
+        {
+            let leader = recipients.remove(index);
+            recipients.insert(0, leader);
+        }
+    }
+    recipients
+}
+
 /// The block retrieval request is used internally for implementing RPC: the callback is executed
 /// for carrying the response
 #[derive(Debug)]
@@ -65,6 +95,13 @@ pub struct NetworkSender {
     // Note that we do not support self rpc requests as it might cause infinite recursive calls.
     self_sender: channel::Sender<Event<ConsensusMsg>>,
     validators: ValidatorVerifier,
+    // If set, proposals are disseminated over the broadcast tree instead of being sent to
+    // every validator directly.
+    broadcast_tree: Option<BroadcastTree>,
+    // The order in which broadcast messages are sent to the validators.
+    broadcast_order: BroadcastOrder,
+    // The proposer of the next round, sent broadcast messages first with `LeaderFirst`.
+    next_leader: Option<Author>,
 }
 
 impl NetworkSender {
@@ -79,9 +116,29 @@ impl NetworkSender {
             network_sender,
             self_sender,
             validators,
+            broadcast_tree: None,
+            broadcast_order: BroadcastOrder::ValidatorSet,
+            next_leader: None,
         }
     }
 
+    /// Disseminates the proposals over the given broadcast tree, if any.
+    pub fn with_broadcast_tree(mut self, broadcast_tree: Option<BroadcastTree>) -> Self {
+        self.broadcast_tree = broadcast_tree;
+        self
+    }
+
+    /// Sends broadcast messages to the validators in the given order.
+    pub fn with_broadcast_order(mut self, broadcast_order: BroadcastOrder) -> Self {
+        self.broadcast_order = broadcast_order;
+        self
+    }
+
+    /// Records the proposer of the next round, for the `LeaderFirst` broadcast order.
+    pub fn set_next_leader(&mut self, next_leader: Option<Author>) {
+        self.next_leader = next_leader;
+    }
+
     /// Tries to retrieve num of blocks backwards starting from id from the given peer: the function
     /// returns a future that is fulfilled with BlockRetrievalResponse.
     pub async fn request_block(
@@ -127,20 +184,54 @@ impl NetworkSender {
             error!("Error broadcasting to self: {:?}", err);
         }
 
-        // Get the list of validators excluding our own account address. Note the
-        // ordering is not important in this case.
+        // Get the list of validators excluding our own account address, in the order the
+        // messages are sent.
         let self_author = self.author;
         let other_validators = self
             .validators
             .get_ordered_account_addresses_iter()
-            .filter(|author| author != &self_author);
+            .filter(|author| author != &self_author)
+            .collect();
+        let network_sender = &self.network_sender;
+        let other_validators = order_recipients(
+            other_validators,
+            self.broadcast_order,
+            self.next_leader,
+            |author| network_sender.ping_latency(author),
+        );
 
         // Broadcast message over direct-send to all other validators.
-        if let Err(err) = self.network_sender.send_to_many(other_validators, msg) {
+        if let Err(err) = self
+            .network_sender
+            .send_to_many(other_validators.into_iter(), msg)
+        {
             error!(error = ?err, "Error broadcasting message");
         }
     }
 
+    /// Tries to send the proposal to all the participants, over the broadcast tree if there's
+    /// one. Like `broadcast`, this doesn't indicate whether the proposal is delivered.
+    pub async fn broadcast_proposal(&mut self, proposal_msg: ProposalMsg) {
+        let broadcast_tree = match &self.broadcast_tree {
+            Some(broadcast_tree) => broadcast_tree,
+            None => {
+                return self
+                    .broadcast(ConsensusMsg::ProposalMsg(Box::new(proposal_msg)))
+                    .await
+            }
+        };
+
+        broadcast_tree.relay(&self.network_sender, self.author, &proposal_msg);
+
+        let self_msg = Event::Message(
+            self.author,
+            ConsensusMsg::ProposalMsg(Box::new(proposal_msg)),
+        );
+        if let Err(err) = self.self_sender.send(self_msg).await {
+            error!("Error broadcasting to self: {:?}", err);
+        }
+    }
+
     /// Tries to send msg to given recipients.
     pub async fn send(&self, msg: ConsensusMsg, recipients: Vec<Author>) {
         let network_sender = self.network_sender.clone();
@@ -271,6 +362,24 @@ impl NetworkTask {
                             warn!(error = ?e, "aptos channel closed");
                         }
                     }
+                    relayed_proposal @ ConsensusMsg::RelayedProposalMsg(_) => {
+                        // acknowledge the receipt right away, so that the relayer doesn't
+                        // send the proposal to our subtree as well
+                        let ack = protocol
+                            .to_bytes(&ConsensusMsg::RelayedProposalAck)
+                            .map(Bytes::from)
+                            .map_err(RpcError::from);
+                        let _ = callback.send(ack);
+                        if let Err(e) = self.consensus_messages_tx.push(
+                            (peer_id, discriminant(&relayed_proposal)),
+                            (peer_id, relayed_proposal),
+                        ) {
+                            warn!(
+                                remote_peer = peer_id,
+                                error = ?e, "Error pushing consensus msg",
+                            );
+                        }
+                    }
                     _ => {
                         warn!(remote_peer = peer_id, "Unexpected msg: {:?}", msg);
                         continue;
@@ -62,6 +62,35 @@ pub enum ConsensusMsg {
     /// than 2f + 1 signatures on the commit proposal. This part is not on the critical path, but
     /// it can save slow machines to quickly confirm the execution result.
     CommitDecisionMsg(Box<CommitDecision>),
+    /// A proposal disseminated over a broadcast tree, which the recipient relays to its own
+    /// children in the tree once the proposal is verified. Sent as an RPC request.
+    RelayedProposalMsg(Box<RelayedProposalMsg>),
+    /// The response to a RelayedProposalMsg: the recipient takes over relaying the proposal
+    /// to its subtree.
+    RelayedProposalAck,
+}
+
+/// A proposal relayed over the broadcast tree rooted at its proposer
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RelayedProposalMsg {
+    proposal_msg: ProposalMsg,
+}
+
+impl RelayedProposalMsg {
+    /// Wraps the proposal to be relayed
+    pub fn new(proposal_msg: ProposalMsg) -> Self {
+        Self { proposal_msg }
+    }
+
+    /// The relayed proposal
+    pub fn proposal_msg(&self) -> &ProposalMsg {
+        &self.proposal_msg
+    }
+
+    /// Returns the relayed proposal
+    pub fn into_proposal_msg(self) -> ProposalMsg {
+        self.proposal_msg
+    }
 }
 
 /// The interface from Network to Consensus layer.
@@ -139,6 +168,16 @@ impl ConsensusNetworkSender {
         }
     }
 
+    /// The moving average of the round trip latency to the peer, as measured by the peer
+    /// monitor, if known.
+    pub fn ping_latency(&self, peer: PeerId) -> Option<Duration> {
+        self.peer_metadata_storage
+            .as_ref()?
+            .read(PeerNetworkId::new(NetworkId::Validator, peer))?
+            .monitoring_metadata
+            .average_ping_latency
+    }
+
     /// Choose the overlapping protocol for peer. The local protocols are sorted from most to least preferred.
     fn preferred_protocol_for_peer(
         &self,
@@ -478,8 +478,11 @@ impl DropConfigRound {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::network::NetworkTask;
-    use aptos_config::network_id::NetworkId;
+    use crate::{
+        broadcast_tree::BroadcastTree,
+        network::{order_recipients, NetworkTask},
+    };
+    use aptos_config::{config::BroadcastOrder, network_id::NetworkId};
     use aptos_crypto::HashValue;
     use aptos_types::validator_verifier::random_validator_verifier;
     use bytes::Bytes;
@@ -646,6 +649,125 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_broadcast_proposal_over_tree() {
+        let mut runtime = consensus_runtime();
+        let num_nodes = 7;
+        let mut receivers: Vec<NetworkReceivers> = Vec::new();
+        let mut playground = NetworkPlayground::new(runtime.handle().clone());
+        let mut nodes = Vec::new();
+        let (signers, validator_verifier) = random_validator_verifier(num_nodes, None, false);
+        let peers: Vec<_> = signers.iter().map(|signer| signer.author()).collect();
+        let peer_metadata_storage = PeerMetadataStorage::new(&[NetworkId::Validator]);
+        let broadcast_tree =
+            BroadcastTree::new(&validator_verifier, 3, Duration::from_millis(200)).unwrap();
+
+        // The first child of the proposer isn't connected and the second one never
+        // acknowledges the relayed proposal, so their subtrees are sent the proposal directly.
+        // The third child acknowledges the relayed proposal and relays it to its subtree.
+        let root = peers[0];
+        let children = broadcast_tree.children(root, root);
+        let (unreachable, unresponsive, relayer) = (children[0], children[1], children[2]);
+        let mut expected_recipients = broadcast_tree.subtree(root, unreachable);
+        expected_recipients.retain(|peer| *peer != unreachable);
+        assert_eq!(
+            broadcast_tree.subtree(root, unresponsive),
+            vec![unresponsive]
+        );
+
+        for (peer_id, peer) in peers.iter().enumerate() {
+            let (network_reqs_tx, network_reqs_rx) = aptos_channel::new(QueueStyle::FIFO, 8, None);
+            let (connection_reqs_tx, _) = aptos_channel::new(QueueStyle::FIFO, 8, None);
+            let (consensus_tx, consensus_rx) = aptos_channel::new(QueueStyle::FIFO, 8, None);
+            let (_conn_mgr_reqs_tx, conn_mgr_reqs_rx) = channel::new_test(8);
+            let (_, conn_status_rx) = conn_notifs_channel::new();
+            if *peer != unreachable {
+                add_peer_to_storage(
+                    &peer_metadata_storage,
+                    peer,
+                    &[
+                        ProtocolId::ConsensusDirectSendBcs,
+                        ProtocolId::ConsensusRpcBcs,
+                    ],
+                );
+            }
+            let mut network_sender = ConsensusNetworkSender::new(
+                PeerManagerRequestSender::new(network_reqs_tx),
+                ConnectionRequestSender::new(connection_reqs_tx),
+            );
+            network_sender.initialize(peer_metadata_storage.clone());
+            let network_events = ConsensusNetworkEvents::new(consensus_rx, conn_status_rx);
+
+            let twin_id = TwinId {
+                id: peer_id,
+                author: *peer,
+            };
+
+            playground.add_node(twin_id, consensus_tx, network_reqs_rx, conn_mgr_reqs_rx);
+
+            let (self_sender, self_receiver) = channel::new_test(8);
+            let node = NetworkSender::new(
+                *peer,
+                network_sender,
+                self_sender,
+                validator_verifier.clone(),
+            )
+            .with_broadcast_tree(Some(broadcast_tree.clone()));
+            let (task, receiver) = NetworkTask::new(network_events, self_receiver);
+            receivers.push(receiver);
+            // The unresponsive child never handles the relayed proposal
+            if *peer != unresponsive {
+                runtime.handle().spawn(task.start());
+            }
+            nodes.push(node);
+        }
+        let previous_qc = certificate_for_genesis();
+        let proposal = ProposalMsg::new(
+            Block::new_proposal(vec![], 1, 1, previous_qc.clone(), &signers[0]),
+            SyncInfo::new(previous_qc.clone(), previous_qc, None, None),
+        );
+        timed_block_on(&mut runtime, async {
+            nodes[0].broadcast_proposal(proposal.clone()).await;
+
+            // The direct sends to the subtree of the unreachable child, and to the
+            // unresponsive child once its relay times out
+            let direct_msgs = playground
+                .wait_for_messages(expected_recipients.len() + 1, NetworkPlayground::take_all)
+                .await;
+            for (_, msg) in direct_msgs {
+                match msg {
+                    ConsensusMsg::ProposalMsg(p) => assert_eq!(*p, proposal),
+                    _ => panic!("unexpected messages"),
+                }
+            }
+
+            // The proposer delivers the proposal to itself directly
+            let (_, msg) = receivers[0].consensus_messages.next().await.unwrap();
+            match msg {
+                ConsensusMsg::ProposalMsg(p) => assert_eq!(*p, proposal),
+                _ => panic!("unexpected messages"),
+            }
+            for recipient in expected_recipients {
+                let index = peers.iter().position(|peer| *peer == recipient).unwrap();
+                let (_, msg) = receivers[index].consensus_messages.next().await.unwrap();
+                match msg {
+                    ConsensusMsg::ProposalMsg(p) => assert_eq!(*p, proposal),
+                    _ => panic!("unexpected messages"),
+                }
+            }
+
+            // The relayer receives the proposal over RPC, to relay it further
+            let index = peers.iter().position(|peer| *peer == relayer).unwrap();
+            let (_, msg) = receivers[index].consensus_messages.next().await.unwrap();
+            match msg {
+                ConsensusMsg::RelayedProposalMsg(relayed) => {
+                    assert_eq!(*relayed.proposal_msg(), proposal)
+                }
+                _ => panic!("unexpected messages"),
+            }
+        });
+    }
+
     #[test]
     fn test_rpc() {
         let mut runtime = consensus_runtime();
@@ -809,4 +931,56 @@ mod tests {
         let mut runtime = consensus_runtime();
         timed_block_on(&mut runtime, future::join(f_network_task, f_check));
     }
+
+    #[test]
+    fn test_order_recipients() {
+        let (a, b, c, d) = (
+            PeerId::random(),
+            PeerId::random(),
+            PeerId::random(),
+            PeerId::random(),
+        );
+        let latencies: HashMap<_, _> = vec![
+            (a, Duration::from_millis(150)),
+            (c, Duration::from_millis(20)),
+            (d, Duration::from_millis(150)),
+        ]
+        .into_iter()
+        .collect();
+        let latency = |peer| latencies.get(&peer).copied();
+        let recipients = vec![a, b, c, d];
+
+        assert_eq!(
+            order_recipients(
+                recipients.clone(),
+                BroadcastOrder::ValidatorSet,
+                Some(d),
+                latency
+            ),
+            vec![a, b, c, d]
+        );
+        // Ties keep the validator set order, peers without a latency come last
+        assert_eq!(
+            order_recipients(
+                recipients.clone(),
+                BroadcastOrder::ClosestFirst,
+                Some(d),
+                latency
+            ),
+            vec![c, a, d, b]
+        );
+        assert_eq!(
+            order_recipients(
+                recipients.clone(),
+                BroadcastOrder::LeaderFirst,
+                Some(b),
+                latency
+            ),
+            vec![b, c, a, d]
+        );
+        assert_eq!(
+            order_recipients(recipients, BroadcastOrder::LeaderFirst, None, latency),
+            vec![c, a, d, b]
+        );
+    }
 }
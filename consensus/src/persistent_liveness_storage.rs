@@ -57,6 +57,16 @@ pub trait PersistentLivenessStorage: Send + Sync {
     /// ValidatorVerifier.
     fn retrieve_epoch_change_proof(&self, version: u64) -> Result<EpochChangeProof>;
 
+    /// Persist the ordered proof of blocks queued in the buffer manager, so that they can be
+    /// ordered again after a restart (decoupled execution).
+    fn save_ordered_proof(&self, ordered_proof: &LedgerInfoWithSignatures) -> Result<()>;
+
+    /// Delete the ordered proofs of the blocks committed or dropped by the buffer manager.
+    fn delete_ordered_proofs(&self, block_ids: Vec<HashValue>) -> Result<()>;
+
+    /// Retrieve the ordered proofs of the blocks queued in the buffer manager before a restart.
+    fn retrieve_ordered_proofs(&self) -> Result<Vec<LedgerInfoWithSignatures>>;
+
     /// Returns a handle of the aptosdb.
     fn aptos_db(&self) -> Arc<dyn DbReader>;
 }
@@ -491,6 +501,21 @@ impl PersistentLivenessStorage for StorageWriteProxy {
         Ok(proofs)
     }
 
+    fn save_ordered_proof(&self, ordered_proof: &LedgerInfoWithSignatures) -> Result<()> {
+        Ok(self.db.save_ordered_proof(ordered_proof)?)
+    }
+
+    fn delete_ordered_proofs(&self, block_ids: Vec<HashValue>) -> Result<()> {
+        if !block_ids.is_empty() {
+            self.db.delete_ordered_proofs(block_ids)?;
+        }
+        Ok(())
+    }
+
+    fn retrieve_ordered_proofs(&self) -> Result<Vec<LedgerInfoWithSignatures>> {
+        Ok(self.db.get_ordered_proofs()?)
+    }
+
     fn aptos_db(&self) -> Arc<dyn DbReader> {
         self.aptos_db.clone()
     }
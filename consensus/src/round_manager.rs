@@ -10,6 +10,7 @@ use crate::{
     error::{error_kind, VerifyError},
     liveness::{
         proposal_generator::ProposalGenerator,
+        proposal_timestamp_checker::ProposalTimestampChecker,
         proposer_election::ProposerElection,
         round_state::{NewRoundEvent, NewRoundReason, RoundState, RoundStateLogSchema},
     },
@@ -103,6 +104,9 @@ impl From<ConsensusMsg> for UnverifiedEvent {
             ConsensusMsg::SyncInfo(m) => UnverifiedEvent::SyncInfo(m),
             ConsensusMsg::CommitVoteMsg(m) => UnverifiedEvent::CommitVote(m),
             ConsensusMsg::CommitDecisionMsg(m) => UnverifiedEvent::CommitDecision(m),
+            ConsensusMsg::RelayedProposalMsg(m) => {
+                UnverifiedEvent::ProposalMsg(Box::new(m.into_proposal_msg()))
+            }
             _ => unreachable!("Unexpected conversion"),
         }
     }
@@ -146,6 +150,7 @@ pub struct RoundManager {
     storage: Arc<dyn PersistentLivenessStorage>,
     sync_only: bool,
     onchain_config: OnChainConsensusConfig,
+    timestamp_checker: ProposalTimestampChecker,
 }
 
 impl RoundManager {
@@ -160,6 +165,7 @@ impl RoundManager {
         storage: Arc<dyn PersistentLivenessStorage>,
         sync_only: bool,
         onchain_config: OnChainConsensusConfig,
+        timestamp_checker: ProposalTimestampChecker,
     ) -> Self {
         // when decoupled execution is false,
         // the counter is still static.
@@ -183,6 +189,7 @@ impl RoundManager {
             storage,
             sync_only,
             onchain_config,
+            timestamp_checker,
         }
     }
 
@@ -232,6 +239,10 @@ impl RoundManager {
             self.new_log(LogEvent::NewRound),
             reason = new_round_event.reason
         );
+        self.network.set_next_leader(Some(
+            self.proposer_election
+                .get_valid_proposer(new_round_event.round + 1),
+        ));
         if self
             .proposer_election
             .is_valid_proposer(self.proposal_generator.author(), new_round_event.round)
@@ -243,9 +254,10 @@ impl RoundManager {
                 self.attempt_to_inject_reconfiguration_error(&proposal_msg)
                     .await?;
             }
-            network
-                .broadcast(ConsensusMsg::ProposalMsg(proposal_msg))
-                .await;
+            fail_point!("consensus::broadcast_proposal", |_| {
+                Err(anyhow::anyhow!("Injected error in broadcast_proposal"))
+            });
+            network.broadcast_proposal(*proposal_msg).await;
             counters::PROPOSALS_COUNT.inc();
         }
         Ok(())
@@ -320,15 +332,8 @@ impl RoundManager {
         help_remote: bool,
     ) -> anyhow::Result<()> {
         let local_sync_info = self.block_store.sync_info();
-        if help_remote && local_sync_info.has_newer_certificates(sync_info) {
-            counters::SYNC_INFO_MSGS_SENT_COUNT.inc();
-            debug!(
-                self.new_log(LogEvent::HelpPeerSync).remote_peer(author),
-                "Remote peer has stale state {}, send it back {}", sync_info, local_sync_info,
-            );
-            self.network
-                .send_sync_info(local_sync_info.clone(), author)
-                .await;
+        if help_remote {
+            self.help_remote_sync(sync_info, author).await;
         }
         if sync_info.has_newer_certificates(&local_sync_info) {
             debug!(
@@ -359,6 +364,20 @@ impl RoundManager {
         }
     }
 
+    /// Sends the local sync info back to the peer if it has newer certificates than the sync info
+    /// sent by the peer.
+    async fn help_remote_sync(&self, sync_info: &SyncInfo, author: Author) {
+        let local_sync_info = self.block_store.sync_info();
+        if local_sync_info.has_newer_certificates(sync_info) {
+            counters::SYNC_INFO_MSGS_SENT_COUNT.inc();
+            debug!(
+                self.new_log(LogEvent::HelpPeerSync).remote_peer(author),
+                "Remote peer has stale state {}, send it back {}", sync_info, local_sync_info,
+            );
+            self.network.send_sync_info(local_sync_info, author).await;
+        }
+    }
+
     /// The function makes sure that it ensures the message_round equal to what we have locally,
     /// brings the missing dependencies from the QC and LedgerInfo of the given sync info and
     /// update the round_state with the certificates if succeed.
@@ -542,6 +561,12 @@ impl RoundManager {
             proposal,
         );
 
+        self.check_reconfiguration_suffix(&proposal)?;
+
+        self.timestamp_checker
+            .check(&proposal)
+            .context("[RoundManager] Invalid proposal timestamp")?;
+
         let block_time_since_epoch = Duration::from_micros(proposal.timestamp_usecs());
 
         ensure!(
@@ -567,10 +592,50 @@ impl RoundManager {
 
         self.round_state.record_vote(vote.clone());
         let vote_msg = VoteMsg::new(vote, self.block_store.sync_info());
+        fail_point!("consensus::send_vote", |_| {
+            Err(anyhow::anyhow!("Injected error in send_vote"))
+        });
         self.network.send_vote(vote_msg, vec![recipients]).await;
         Ok(())
     }
 
+    /// Once enabled by the on-chain config, the proposals extending a reconfiguration are marked
+    /// as its suffix, and the mark must name the reconfiguration block that the parent is, or
+    /// is a suffix of. Marked proposals are rejected otherwise.
+    fn check_reconfiguration_suffix(&self, proposal: &Block) -> anyhow::Result<()> {
+        let suffix_of = proposal.block_data().reconfiguration_suffix_of();
+        if !self.onchain_config.mark_reconfiguration_suffix() {
+            ensure!(
+                suffix_of.is_none(),
+                "[RoundManager] Reconfiguration suffix {} is not enabled",
+                proposal
+            );
+            return Ok(());
+        }
+        match suffix_of {
+            None => ensure!(
+                !proposal
+                    .quorum_cert()
+                    .certified_block()
+                    .has_reconfiguration(),
+                "[RoundManager] Proposal {} extending a reconfiguration isn't marked as its suffix",
+                proposal
+            ),
+            Some(_) => {
+                let parent = self
+                    .block_store
+                    .get_block(proposal.parent_id())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("[RoundManager] Parent of {} not found", proposal)
+                    })?;
+                proposal
+                    .verify_reconfiguration_suffix(parent.block())
+                    .context("[RoundManager] Invalid reconfiguration suffix")?;
+            }
+        }
+        Ok(())
+    }
+
     /// The function generates a VoteMsg for a given proposed_block:
     /// * first execute the block and add it to the block store
     /// * then verify the voting rules
@@ -615,6 +680,9 @@ impl RoundManager {
         ))?;
         observe_block(executed_block.block().timestamp_usecs(), BlockStage::VOTED);
 
+        fail_point!("consensus::save_vote", |_| {
+            Err(anyhow::anyhow!("Injected error in save_vote"))
+        });
         self.storage
             .save_vote(&vote)
             .context("[RoundManager] Fail to persist last vote")?;
@@ -623,7 +691,8 @@ impl RoundManager {
     }
 
     /// Upon new vote:
-    /// 1. Ensures we're processing the vote from the same round as local round
+    /// 1. Ensures we're processing the vote from the same round as local round, votes from past
+    /// rounds only get the local sync info sent back if the voter lags more than a round behind
     /// 2. Filter out votes for rounds that should not be processed by this validator (to avoid
     /// potential attacks).
     /// 2. Add the vote to the pending votes and check whether it finishes a QC.
@@ -632,6 +701,18 @@ impl RoundManager {
         fail_point!("consensus::process_vote_msg", |_| {
             Err(anyhow::anyhow!("Injected error in process_vote_msg"))
         });
+        // A vote from a past round means the voter lags behind. Votes of the previous round
+        // keep arriving after its QC is formed and the voter gets it with the next proposal, but
+        // a voter missing more certificates would only catch up after timing out: send it back
+        // the certificates it's missing right away.
+        if vote_msg.vote().vote_data().proposed().round() < self.round_state.current_round() {
+            let local_certified_round = self.block_store.sync_info().highest_certified_round();
+            if vote_msg.sync_info().highest_certified_round() + 1 < local_certified_round {
+                self.help_remote_sync(vote_msg.sync_info(), vote_msg.vote().author())
+                    .await;
+            }
+            return Ok(());
+        }
         // Check whether this validator is a valid recipient of the vote.
         if self
             .ensure_round_and_sync_up(
@@ -5,6 +5,7 @@ use crate::{
     block_storage::BlockStore,
     liveness::{
         proposal_generator::ProposalGenerator,
+        proposal_timestamp_checker::ProposalTimestampChecker,
         rotating_proposer_election::RotatingProposer,
         round_state::{ExponentialTimeInterval, NewRoundEvent, NewRoundReason, RoundState},
     },
@@ -143,8 +144,9 @@ fn create_node_for_fuzzing() -> RoundManager {
         signer.author(),
         block_store.clone(),
         Arc::new(MockTransactionManager::new(None)),
-        time_service,
+        time_service.clone(),
         1,
+        false,
     );
 
     //
@@ -168,6 +170,7 @@ fn create_node_for_fuzzing() -> RoundManager {
         storage,
         false,
         OnChainConsensusConfig::default(),
+        ProposalTimestampChecker::new(time_service, Duration::from_secs(5)),
     )
 }
 
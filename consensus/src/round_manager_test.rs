@@ -5,6 +5,7 @@ use crate::{
     block_storage::{BlockReader, BlockStore},
     liveness::{
         proposal_generator::ProposalGenerator,
+        proposal_timestamp_checker::ProposalTimestampChecker,
         proposer_election::ProposerElection,
         rotating_proposer_election::RotatingProposer,
         round_state::{ExponentialTimeInterval, RoundState},
@@ -204,9 +205,10 @@ impl NodeSetup {
             Arc::new(MockTransactionManager::new(None)),
             time_service.clone(),
             1,
+            false,
         );
 
-        let round_state = Self::create_round_state(time_service);
+        let round_state = Self::create_round_state(time_service.clone());
         let proposer_election = Self::create_proposer_election(proposer_author);
         let mut safety_rules =
             MetricsSafetyRules::new(safety_rules_manager.client(), storage.clone());
@@ -223,6 +225,7 @@ impl NodeSetup {
             storage.clone(),
             false,
             OnChainConsensusConfig::default(),
+            ProposalTimestampChecker::new(time_service, Duration::from_secs(5)),
         );
         block_on(round_manager.init(last_vote_sent));
         Self {
@@ -821,6 +824,88 @@ fn sync_info_sent_on_stale_sync_info() {
     });
 }
 
+#[test]
+fn sync_info_sent_on_stale_vote() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 2);
+    runtime.spawn(playground.start());
+    let genesis_qc = certificate_for_genesis();
+    let block_0 = Block::new_proposal(vec![], 1, 1, genesis_qc, &nodes[0].signer);
+    let parent_block_info = block_0.quorum_cert().certified_block();
+    let block_0_quorum_cert = gen_test_certificate(
+        vec![&nodes[0].signer, &nodes[1].signer],
+        // Follow MockStateComputer implementation
+        block_0.gen_block_info(
+            parent_block_info.executed_state_id(),
+            parent_block_info.version(),
+            parent_block_info.next_epoch_state().cloned(),
+        ),
+        parent_block_info.clone(),
+        None,
+    );
+    let block_1 = Block::new_proposal(vec![], 2, 2, block_0_quorum_cert.clone(), &nodes[0].signer);
+    let parent_block_info = block_1.quorum_cert().certified_block();
+    let block_1_quorum_cert = gen_test_certificate(
+        vec![&nodes[0].signer, &nodes[1].signer],
+        block_1.gen_block_info(
+            parent_block_info.executed_state_id(),
+            parent_block_info.version(),
+            parent_block_info.next_epoch_state().cloned(),
+        ),
+        parent_block_info.clone(),
+        None,
+    );
+    let mut behind_node = nodes.pop().unwrap();
+    let mut ahead_node = nodes.pop().unwrap();
+    runtime
+        .block_on(ahead_node.block_store.execute_and_insert_block(block_0))
+        .unwrap();
+    runtime
+        .block_on(ahead_node.block_store.execute_and_insert_block(block_1))
+        .unwrap();
+
+    timed_block_on(&mut runtime, async {
+        ahead_node.next_proposal().await;
+        behind_node.next_proposal().await;
+        // ahead node moves on to round 3, two certificates ahead of the behind node
+        let sync_info = SyncInfo::new(
+            block_1_quorum_cert.clone(),
+            certificate_for_genesis(),
+            None,
+            None,
+        );
+        ahead_node
+            .round_manager
+            .process_sync_info_msg(sync_info, behind_node.signer.author())
+            .await
+            .unwrap();
+        // broadcast timeout
+        behind_node
+            .round_manager
+            .process_local_timeout(1)
+            .await
+            .unwrap_err();
+        let timeout_vote_msg = behind_node.next_vote().await;
+        assert!(timeout_vote_msg.vote().is_timeout());
+
+        // the vote is stale, but the sync info it carries is answered
+        ahead_node
+            .round_manager
+            .process_vote_msg(timeout_vote_msg)
+            .await
+            .unwrap();
+        // The ahead node may have sent its round 3 proposal first
+        let sync_info = loop {
+            if let ConsensusMsg::SyncInfo(sync_info) = behind_node.next_message().await {
+                break *sync_info;
+            }
+        };
+
+        assert_eq!(*sync_info.highest_quorum_cert(), block_1_quorum_cert);
+    });
+}
+
 #[test]
 fn sync_on_partial_newer_sync_info() {
     let mut runtime = consensus_runtime();
@@ -11,7 +11,9 @@ use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
 use aptos_metrics::monitor;
 use aptos_types::{
-    contract_event::ContractEvent, ledger_info::LedgerInfoWithSignatures, transaction::Transaction,
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{Transaction, Version},
 };
 use consensus_notifications::ConsensusNotificationSender;
 use consensus_types::{block::Block, executed_block::ExecutedBlock};
@@ -23,6 +25,7 @@ use std::{boxed::Box, sync::Arc};
 
 type NotificationType = (
     Box<dyn FnOnce() + Send + Sync>,
+    Version,
     Vec<Transaction>,
     Vec<ContractEvent>,
 );
@@ -30,7 +33,7 @@ type NotificationType = (
 /// Basic communication with the Execution module;
 /// implements StateComputer traits.
 pub struct ExecutionProxy {
-    execution_correctness_client: Box<dyn ExecutionCorrectness + Send + Sync>,
+    execution_correctness_client: Arc<dyn ExecutionCorrectness + Send + Sync>,
     mempool_notifier: Arc<dyn TxnManager>,
     state_sync_notifier: Arc<dyn ConsensusNotificationSender>,
     async_state_sync_notifier: channel::Sender<NotificationType>,
@@ -45,9 +48,34 @@ impl ExecutionProxy {
     ) -> Self {
         let (tx, mut rx) =
             channel::new::<NotificationType>(10, &counters::PENDING_STATE_SYNC_NOTIFICATION);
+        let execution_correctness_client: Arc<dyn ExecutionCorrectness + Send + Sync> =
+            Arc::from(execution_correctness_client);
+        let client = execution_correctness_client.clone();
         let notifier = state_sync_notifier.clone();
         handle.spawn(async move {
-            while let Some((callback, txns, reconfig_events)) = rx.next().await {
+            while let Some((callback, version, txns, reconfig_events)) = rx.next().await {
+                // Commits may return before being persisted, and nothing can be exposed until it
+                // is.
+                let persisted_client = client.clone();
+                let persisted = monitor!(
+                    "wait_for_persisted",
+                    tokio::task::spawn_blocking(move || {
+                        persisted_client.wait_for_persisted(version)
+                    })
+                    .await
+                );
+                match persisted {
+                    Ok(Ok(())) => (),
+                    Ok(Err(e)) => {
+                        error!(error = ?e, version = version, "Failed to persist the commit");
+                        continue;
+                    }
+                    Err(e) => {
+                        error!(error = ?e, version = version, "Failed to wait for the commit");
+                        continue;
+                    }
+                }
+
                 if let Err(e) = monitor!(
                     "notify_state_sync",
                     notifier.notify_new_commit(txns, reconfig_events).await
@@ -107,7 +135,8 @@ impl StateComputer for ExecutionProxy {
         Ok(compute_result)
     }
 
-    /// Send a successful commit. A future is fulfilled when the state is finalized.
+    /// Send a successful commit. A future is fulfilled when the state is finalized. State sync is
+    /// notified, and the callback called, once the committed blocks are persisted.
     async fn commit(
         &self,
         blocks: &[Arc<ExecutedBlock>],
@@ -124,19 +153,25 @@ impl StateComputer for ExecutionProxy {
             reconfig_events.extend(block.reconfig_event());
         }
 
+        fail_point!("consensus::commit", |_| {
+            Err(ExecutionError::InternalError {
+                error: "Injected error in commit".into(),
+            })
+        });
         monitor!(
             "commit_block",
             self.execution_correctness_client
                 .commit_blocks(block_ids, finality_proof.clone())?
         );
 
+        let version = finality_proof.ledger_info().version();
         let blocks = blocks.to_vec();
         let wrapped_callback = move || {
             callback(&blocks, finality_proof);
         };
         self.async_state_sync_notifier
             .clone()
-            .send((Box::new(wrapped_callback), txns, reconfig_events))
+            .send((Box::new(wrapped_callback), version, txns, reconfig_events))
             .await
             .expect("Failed to send async state sync notification");
 
@@ -148,6 +183,9 @@ impl StateComputer for ExecutionProxy {
         fail_point!("consensus::sync_to", |_| {
             Err(anyhow::anyhow!("Injected error in sync_to").into())
         });
+        // State sync writes to the DB, so the blocks whose commit returned must be persisted
+        // first, rather than concurrently.
+        self.execution_correctness_client.flush_commits()?;
         // Here to start to do state synchronization where ChunkExecutor inside will
         // process chunks and commit to Storage. However, after block execution and
         // commitments, the the sync state of ChunkExecutor may be not up to date so
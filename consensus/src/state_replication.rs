@@ -4,7 +4,7 @@
 use crate::error::{MempoolError, StateSyncError};
 use anyhow::Result;
 use aptos_crypto::HashValue;
-use aptos_types::ledger_info::LedgerInfoWithSignatures;
+use aptos_types::{ledger_info::LedgerInfoWithSignatures, transaction::SignedTransaction};
 use consensus_types::{block::Block, common::Payload, executed_block::ExecutedBlock};
 use executor_types::{Error as ExecutionError, StateComputeResult};
 use futures::future::BoxFuture;
@@ -18,14 +18,15 @@ pub type StateComputerCommitCallBackType =
 pub trait TxnManager: Send + Sync {
     /// Brings new transactions to be applied.
     /// The `exclude_txns` list includes the transactions that are already pending in the
-    /// branch of blocks consensus is trying to extend.
+    /// branch of blocks consensus is trying to extend, without the ones left out of their blocks
+    /// by the block gas limit.
     ///
     /// wait_callback is executed when there's no transactions available and it decides to wait.
     /// pending_ordering indicates if we should long poll mempool or propose empty blocks to help commit pending txns
     async fn pull_txns(
         &self,
         max_size: u64,
-        exclude: Vec<&Payload>,
+        exclude: Vec<&[SignedTransaction]>,
         wait_callback: BoxFuture<'static, ()>,
         pending_ordering: bool,
     ) -> Result<Payload, MempoolError>;
@@ -30,6 +30,7 @@ pub struct MockSharedStorage {
     pub block: Mutex<HashMap<HashValue, Block>>,
     pub qc: Mutex<HashMap<HashValue, QuorumCert>>,
     pub lis: Mutex<HashMap<u64, LedgerInfoWithSignatures>>,
+    pub ordered_proofs: Mutex<HashMap<HashValue, LedgerInfoWithSignatures>>,
     pub last_vote: Mutex<Option<Vote>>,
 
     // Liveness state
@@ -44,6 +45,7 @@ impl MockSharedStorage {
             block: Mutex::new(HashMap::new()),
             qc: Mutex::new(HashMap::new()),
             lis: Mutex::new(HashMap::new()),
+            ordered_proofs: Mutex::new(HashMap::new()),
             last_vote: Mutex::new(None),
             highest_timeout_certificate: Mutex::new(None),
             highest_2chain_timeout_certificate: Mutex::new(None),
@@ -244,6 +246,31 @@ impl PersistentLivenessStorage for MockStorage {
         Ok(EpochChangeProof::new(vec![lis], false))
     }
 
+    fn save_ordered_proof(&self, ordered_proof: &LedgerInfoWithSignatures) -> Result<()> {
+        self.shared_storage
+            .ordered_proofs
+            .lock()
+            .insert(ordered_proof.commit_info().id(), ordered_proof.clone());
+        Ok(())
+    }
+
+    fn delete_ordered_proofs(&self, block_ids: Vec<HashValue>) -> Result<()> {
+        for id in block_ids {
+            self.shared_storage.ordered_proofs.lock().remove(&id);
+        }
+        Ok(())
+    }
+
+    fn retrieve_ordered_proofs(&self) -> Result<Vec<LedgerInfoWithSignatures>> {
+        Ok(self
+            .shared_storage
+            .ordered_proofs
+            .lock()
+            .values()
+            .cloned()
+            .collect())
+    }
+
     fn aptos_db(&self) -> Arc<dyn DbReader> {
         unimplemented!()
     }
@@ -316,6 +343,18 @@ impl PersistentLivenessStorage for EmptyStorage {
         Ok(EpochChangeProof::new(vec![], false))
     }
 
+    fn save_ordered_proof(&self, _: &LedgerInfoWithSignatures) -> Result<()> {
+        Ok(())
+    }
+
+    fn delete_ordered_proofs(&self, _: Vec<HashValue>) -> Result<()> {
+        Ok(())
+    }
+
+    fn retrieve_ordered_proofs(&self) -> Result<Vec<LedgerInfoWithSignatures>> {
+        Ok(vec![])
+    }
+
     fn aptos_db(&self) -> Arc<dyn DbReader> {
         unimplemented!()
     }
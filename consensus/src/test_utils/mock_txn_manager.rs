@@ -5,7 +5,7 @@ use crate::{error::MempoolError, state_replication::TxnManager, txn_manager::Mem
 use anyhow::Result;
 use aptos_mempool::ConsensusRequest;
 use aptos_types::{
-    transaction::TransactionStatus,
+    transaction::{SignedTransaction, TransactionStatus},
     vm_status::{KeptVMStatus, StatusCode},
 };
 use consensus_types::{
@@ -53,7 +53,7 @@ impl TxnManager for MockTransactionManager {
     async fn pull_txns(
         &self,
         _max_size: u64,
-        _exclude_txns: Vec<&Payload>,
+        _exclude_txns: Vec<&[SignedTransaction]>,
         _callback: BoxFuture<'static, ()>,
         _pending_ordering: bool,
     ) -> Result<Payload, MempoolError> {
@@ -77,7 +77,8 @@ impl TxnManager for MockTransactionManager {
                 mock_transaction_status(block.payload().map_or(0, |txns| txns.len())),
                 compute_results.transaction_info_hashes().clone(),
                 compute_results.reconfig_events().to_vec(),
-            );
+            )
+            .unwrap();
             assert!(self
                 .mempool_proxy
                 .as_ref()
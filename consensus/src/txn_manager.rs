@@ -4,9 +4,11 @@
 use crate::{error::MempoolError, state_replication::TxnManager};
 use anyhow::{format_err, Result};
 use aptos_logger::prelude::*;
-use aptos_mempool::{ConsensusRequest, ConsensusResponse, TransactionSummary};
+use aptos_mempool::{
+    ConsensusRequest, ConsensusResponse, TraceStage, TransactionSummary, TransactionTracer,
+};
 use aptos_metrics::monitor;
-use aptos_types::transaction::TransactionStatus;
+use aptos_types::transaction::{SignedTransaction, TransactionStatus};
 use consensus_types::{block::Block, common::Payload};
 use executor_types::StateComputeResult;
 use fail::fail_point;
@@ -15,7 +17,7 @@ use futures::{
     future::BoxFuture,
 };
 use itertools::Itertools;
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 use tokio::time::{sleep, timeout};
 
 const NO_TXN_DELAY: u64 = 30;
@@ -29,6 +31,8 @@ pub struct MempoolProxy {
     mempool_executed_txn_timeout_ms: u64,
     /// Timeout for consensus to pull transactions from mempool and get a response (in milliseconds)
     mempool_txn_pull_timeout_ms: u64,
+    /// Records the execution of the transactions traced by mempool
+    transaction_tracer: Arc<TransactionTracer>,
 }
 
 impl MempoolProxy {
@@ -47,9 +51,16 @@ impl MempoolProxy {
             poll_count,
             mempool_executed_txn_timeout_ms,
             mempool_txn_pull_timeout_ms,
+            transaction_tracer: Arc::new(TransactionTracer::default()),
         }
     }
 
+    /// Records the execution of transactions in the traces of the tracer of mempool.
+    pub fn with_transaction_tracer(mut self, transaction_tracer: Arc<TransactionTracer>) -> Self {
+        self.transaction_tracer = transaction_tracer;
+        self
+    }
+
     async fn pull_internal(
         &self,
         max_size: u64,
@@ -89,7 +100,7 @@ impl TxnManager for MempoolProxy {
     async fn pull_txns(
         &self,
         max_size: u64,
-        exclude_payloads: Vec<&Payload>,
+        exclude_payloads: Vec<&[SignedTransaction]>,
         wait_callback: BoxFuture<'static, ()>,
         pending_ordering: bool,
     ) -> Result<Payload, MempoolError> {
@@ -142,6 +153,11 @@ impl TxnManager for MempoolProxy {
             .iter()
             .zip_eq(compute_results.compute_status().iter().skip(1))
         {
+            self.transaction_tracer.record(
+                txn.sender(),
+                txn.sequence_number(),
+                TraceStage::Executed,
+            );
             if let TransactionStatus::Discard(_) = status {
                 rejected_txns.push(TransactionSummary {
                     sender: txn.sender(),
@@ -0,0 +1,571 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides an API for BLS signatures over the BLS12-381 curve, following the
+//! proof-of-possession ciphersuite of the
+//! [IRTF draft](https://datatracker.ietf.org/doc/html/draft-irtf-cfrg-bls-signature-04), with
+//! public keys in G1 and signatures in G2.
+//!
+//! Signatures on the same message can be aggregated into a multi-signature verified against all
+//! the signers' public keys, and signatures on distinct messages can be aggregated into an
+//! aggregate signature. Multi-signatures are only secure against rogue-key attacks when every
+//! public key comes with a verified [`BLS12381ProofOfPossession`].
+//!
+//! Public keys are validated (i.e., checked to be in the prime-order subgroup) when deserialized,
+//! and signatures are checked to be in the prime-order subgroup when verified.
+//!
+//! # Examples
+//!
+//! ```
+//! use aptos_crypto_derive::{CryptoHasher, BCSCryptoHash};
+//! use aptos_crypto::{
+//!     bls12381::*,
+//!     traits::{Signature, SigningKey, Uniform},
+//! };
+//! use rand::{rngs::StdRng, SeedableRng};
+//! use serde::{Serialize, Deserialize};
+//!
+//! #[derive(Serialize, Deserialize, CryptoHasher, BCSCryptoHash)]
+//! pub struct TestCryptoDocTest(String);
+//! let message = TestCryptoDocTest("Test message".to_string());
+//!
+//! let mut rng: StdRng = SeedableRng::from_seed([0; 32]);
+//! let private_key = BLS12381PrivateKey::generate(&mut rng);
+//! let public_key: BLS12381PublicKey = (&private_key).into();
+//! let pop = BLS12381ProofOfPossession::create(&private_key);
+//! assert!(pop.verify(&public_key).is_ok());
+//!
+//! let signature = private_key.sign(&message);
+//! assert!(signature.verify(&message, &public_key).is_ok());
+//! ```
+//! **Note**: The above example generates a private key using a private function intended only for
+//! testing purposes. Production code should find an alternate means for secure key generation.
+
+use crate::{hash::CryptoHash, traits::*};
+use anyhow::{anyhow, ensure, Result};
+use aptos_crypto_derive::{DeserializeKey, SerializeKey, SilentDebug, SilentDisplay};
+use blst::{min_pk as blst_core, BLST_ERROR};
+use core::convert::TryFrom;
+use serde::Serialize;
+use std::fmt;
+
+/// The length of the BLS12381PrivateKey
+pub const BLS12381_PRIVATE_KEY_LENGTH: usize = 32;
+/// The length of the BLS12381PublicKey, a compressed G1 point
+pub const BLS12381_PUBLIC_KEY_LENGTH: usize = 48;
+/// The length of the BLS12381Signature, a compressed G2 point
+pub const BLS12381_SIGNATURE_LENGTH: usize = 96;
+/// The length of the BLS12381ProofOfPossession, a compressed G2 point
+pub const BLS12381_POP_LENGTH: usize = BLS12381_SIGNATURE_LENGTH;
+
+/// The domain separation tag used when hashing messages to G2 for signing
+const DST_BLS_SIG_IN_G2_WITH_POP: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+/// The domain separation tag used when hashing public keys to G2 for proofs of possession
+const DST_BLS_POP_IN_G2: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// A BLS12-381 private key
+#[derive(DeserializeKey, SerializeKey, SilentDebug, SilentDisplay)]
+pub struct BLS12381PrivateKey(blst_core::SecretKey);
+
+#[cfg(feature = "assert-private-keys-not-cloneable")]
+static_assertions::assert_not_impl_any!(BLS12381PrivateKey: Clone);
+
+#[cfg(any(test, feature = "cloneable-private-keys"))]
+impl Clone for BLS12381PrivateKey {
+    fn clone(&self) -> Self {
+        let serialized: &[u8] = &(self.to_bytes());
+        BLS12381PrivateKey::try_from(serialized).unwrap()
+    }
+}
+
+/// A BLS12-381 public key
+#[derive(DeserializeKey, Clone, SerializeKey)]
+pub struct BLS12381PublicKey(blst_core::PublicKey);
+
+/// A BLS12-381 signature, either produced by a single signer or aggregated
+#[derive(DeserializeKey, Clone, SerializeKey)]
+pub struct BLS12381Signature(blst_core::Signature);
+
+/// A proof of possession of the private key of a BLS12-381 public key, i.e., a signature on the
+/// public key under a dedicated domain separation tag.
+#[derive(DeserializeKey, Clone, SerializeKey)]
+pub struct BLS12381ProofOfPossession(blst_core::Signature);
+
+/// Maps a blst status to a `Result`
+fn check_blst_result(result: BLST_ERROR) -> Result<()> {
+    match result {
+        BLST_ERROR::BLST_SUCCESS => Ok(()),
+        error => Err(anyhow!("BLS12-381 verification failed: {:?}", error)),
+    }
+}
+
+impl BLS12381PrivateKey {
+    /// The length of the BLS12381PrivateKey
+    pub const LENGTH: usize = BLS12381_PRIVATE_KEY_LENGTH;
+
+    /// Serialize a BLS12381PrivateKey.
+    pub fn to_bytes(&self) -> [u8; BLS12381_PRIVATE_KEY_LENGTH] {
+        self.0.to_bytes()
+    }
+
+    /// Private function aimed at minimizing code duplication between sign
+    /// methods of the SigningKey implementation. This should remain private.
+    fn sign_arbitrary_message(&self, message: &[u8]) -> BLS12381Signature {
+        BLS12381Signature(self.0.sign(message, DST_BLS_SIG_IN_G2_WITH_POP, &[]))
+    }
+}
+
+impl BLS12381PublicKey {
+    /// Serialize a BLS12381PublicKey.
+    pub fn to_bytes(&self) -> [u8; BLS12381_PUBLIC_KEY_LENGTH] {
+        self.0.to_bytes()
+    }
+
+    /// Aggregates the public keys into the public key verifying multi-signatures from all of
+    /// them. The public keys must have verified proofs of possession.
+    pub fn aggregate(public_keys: Vec<&BLS12381PublicKey>) -> Result<BLS12381PublicKey> {
+        ensure!(!public_keys.is_empty(), "No public keys to aggregate");
+        let blst_public_keys: Vec<_> = public_keys.iter().map(|pk| &pk.0).collect();
+        // The public keys were validated when deserialized
+        let aggregate = blst_core::AggregatePublicKey::aggregate(&blst_public_keys, false)
+            .map_err(|error| anyhow!("Failed to aggregate public keys: {:?}", error))?;
+        Ok(BLS12381PublicKey(aggregate.to_public_key()))
+    }
+}
+
+impl BLS12381Signature {
+    /// The length of the BLS12381Signature
+    pub const LENGTH: usize = BLS12381_SIGNATURE_LENGTH;
+
+    /// Serialize a BLS12381Signature.
+    pub fn to_bytes(&self) -> [u8; BLS12381_SIGNATURE_LENGTH] {
+        self.0.to_bytes()
+    }
+
+    /// Aggregates the signatures, either into a multi-signature if they're on the same message
+    /// or into an aggregate signature otherwise.
+    pub fn aggregate(signatures: Vec<&BLS12381Signature>) -> Result<BLS12381Signature> {
+        ensure!(!signatures.is_empty(), "No signatures to aggregate");
+        let blst_signatures: Vec<_> = signatures.iter().map(|sig| &sig.0).collect();
+        let aggregate = blst_core::AggregateSignature::aggregate(&blst_signatures, true)
+            .map_err(|error| anyhow!("Failed to aggregate signatures: {:?}", error))?;
+        Ok(BLS12381Signature(aggregate.to_signature()))
+    }
+
+    /// Verifies a multi-signature, i.e., an aggregation of signatures on the same message, against
+    /// the public keys of all the signers. The public keys must have verified proofs of
+    /// possession, as multi-signatures are otherwise subject to rogue-key attacks.
+    pub fn verify_multisig<T: CryptoHash + Serialize>(
+        &self,
+        message: &T,
+        public_keys: &[&BLS12381PublicKey],
+    ) -> Result<()> {
+        ensure!(!public_keys.is_empty(), "No public keys to verify against");
+        let blst_public_keys: Vec<_> = public_keys.iter().map(|pk| &pk.0).collect();
+        check_blst_result(self.0.fast_aggregate_verify(
+            true,
+            &signing_message(message),
+            DST_BLS_SIG_IN_G2_WITH_POP,
+            &blst_public_keys,
+        ))
+    }
+
+    /// Verifies an aggregate signature, i.e., an aggregation of signatures on distinct messages,
+    /// where the i-th message is signed by the owner of the i-th public key.
+    pub fn verify_aggregate<T: CryptoHash + Serialize>(
+        &self,
+        messages: &[&T],
+        public_keys: &[&BLS12381PublicKey],
+    ) -> Result<()> {
+        ensure!(!public_keys.is_empty(), "No public keys to verify against");
+        ensure!(
+            messages.len() == public_keys.len(),
+            "Got {} messages for {} public keys",
+            messages.len(),
+            public_keys.len()
+        );
+        let signing_messages: Vec<_> = messages
+            .iter()
+            .map(|message| signing_message(*message))
+            .collect();
+        let signing_messages: Vec<&[u8]> = signing_messages.iter().map(Vec::as_slice).collect();
+        let blst_public_keys: Vec<_> = public_keys.iter().map(|pk| &pk.0).collect();
+        check_blst_result(self.0.aggregate_verify(
+            true,
+            &signing_messages,
+            DST_BLS_SIG_IN_G2_WITH_POP,
+            &blst_public_keys,
+            false,
+        ))
+    }
+
+    /// return an arbitrary valid point in G2 (for test only)
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn dummy_signature() -> Self {
+        BLS12381PrivateKey::genesis().sign_arbitrary_message(b"dummy")
+    }
+}
+
+impl BLS12381ProofOfPossession {
+    /// Creates a proof of possession of the private key, for its public key.
+    pub fn create(private_key: &BLS12381PrivateKey) -> BLS12381ProofOfPossession {
+        let public_key = BLS12381PublicKey::from(private_key);
+        BLS12381ProofOfPossession(private_key.0.sign(
+            &public_key.to_bytes(),
+            DST_BLS_POP_IN_G2,
+            &[],
+        ))
+    }
+
+    /// Verifies the proof of possession for the public key.
+    pub fn verify(&self, public_key: &BLS12381PublicKey) -> Result<()> {
+        check_blst_result(self.0.verify(
+            true,
+            &public_key.to_bytes(),
+            DST_BLS_POP_IN_G2,
+            &[],
+            &public_key.0,
+            false,
+        ))
+    }
+
+    /// Serialize a BLS12381ProofOfPossession.
+    pub fn to_bytes(&self) -> [u8; BLS12381_POP_LENGTH] {
+        self.0.to_bytes()
+    }
+}
+
+///////////////////////
+// PrivateKey Traits //
+///////////////////////
+
+impl PrivateKey for BLS12381PrivateKey {
+    type PublicKeyMaterial = BLS12381PublicKey;
+}
+
+impl SigningKey for BLS12381PrivateKey {
+    type VerifyingKeyMaterial = BLS12381PublicKey;
+    type SignatureMaterial = BLS12381Signature;
+
+    fn sign<T: CryptoHash + Serialize>(&self, message: &T) -> BLS12381Signature {
+        BLS12381PrivateKey::sign_arbitrary_message(self, signing_message(message).as_ref())
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    fn sign_arbitrary_message(&self, message: &[u8]) -> BLS12381Signature {
+        BLS12381PrivateKey::sign_arbitrary_message(self, message)
+    }
+}
+
+impl Uniform for BLS12381PrivateKey {
+    fn generate<R>(rng: &mut R) -> Self
+    where
+        R: ::rand::RngCore + ::rand::CryptoRng,
+    {
+        // Key generation requires at least 32 bytes of input key material
+        let mut ikm = [0u8; 32];
+        rng.fill_bytes(&mut ikm);
+        BLS12381PrivateKey(
+            blst_core::SecretKey::key_gen(&ikm, &[])
+                .expect("Key generation with 32 bytes of key material should not fail."),
+        )
+    }
+}
+
+impl PartialEq<Self> for BLS12381PrivateKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for BLS12381PrivateKey {}
+
+impl TryFrom<&[u8]> for BLS12381PrivateKey {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a BLS12381PrivateKey. This method will also check that the key is a non-zero
+    /// scalar smaller than the order of the groups.
+    fn try_from(bytes: &[u8]) -> std::result::Result<BLS12381PrivateKey, CryptoMaterialError> {
+        if bytes.len() != BLS12381_PRIVATE_KEY_LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        blst_core::SecretKey::from_bytes(bytes)
+            .map(BLS12381PrivateKey)
+            .map_err(|_| CryptoMaterialError::DeserializationError)
+    }
+}
+
+impl Length for BLS12381PrivateKey {
+    fn length(&self) -> usize {
+        Self::LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for BLS12381PrivateKey {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl Genesis for BLS12381PrivateKey {
+    fn genesis() -> Self {
+        let mut buf = [0u8; BLS12381_PRIVATE_KEY_LENGTH];
+        buf[BLS12381_PRIVATE_KEY_LENGTH - 1] = 1;
+        Self::try_from(buf.as_ref()).unwrap()
+    }
+}
+
+//////////////////////
+// PublicKey Traits //
+//////////////////////
+
+impl From<&BLS12381PrivateKey> for BLS12381PublicKey {
+    fn from(private_key: &BLS12381PrivateKey) -> Self {
+        BLS12381PublicKey(private_key.0.sk_to_pk())
+    }
+}
+
+impl PublicKey for BLS12381PublicKey {
+    type PrivateKeyMaterial = BLS12381PrivateKey;
+}
+
+impl std::hash::Hash for BLS12381PublicKey {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let encoded_pubkey = self.to_bytes();
+        state.write(&encoded_pubkey);
+    }
+}
+
+impl PartialEq for BLS12381PublicKey {
+    fn eq(&self, other: &BLS12381PublicKey) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for BLS12381PublicKey {}
+
+impl VerifyingKey for BLS12381PublicKey {
+    type SigningKeyMaterial = BLS12381PrivateKey;
+    type SignatureMaterial = BLS12381Signature;
+}
+
+impl fmt::Display for BLS12381PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(&self.to_bytes()))
+    }
+}
+
+impl fmt::Debug for BLS12381PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BLS12381PublicKey({})", self)
+    }
+}
+
+impl TryFrom<&[u8]> for BLS12381PublicKey {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a BLS12381PublicKey. This method will also check for key validity, i.e., it
+    /// will only deserialize keys that aren't the identity and lie in the prime-order subgroup.
+    fn try_from(bytes: &[u8]) -> std::result::Result<BLS12381PublicKey, CryptoMaterialError> {
+        // blst also accepts uncompressed points, which we don't
+        if bytes.len() != BLS12381_PUBLIC_KEY_LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        match blst_core::PublicKey::key_validate(bytes) {
+            Ok(public_key) => Ok(BLS12381PublicKey(public_key)),
+            Err(BLST_ERROR::BLST_POINT_NOT_IN_GROUP) => {
+                Err(CryptoMaterialError::SmallSubgroupError)
+            }
+            Err(BLST_ERROR::BLST_POINT_NOT_ON_CURVE) => {
+                Err(CryptoMaterialError::PointNotOnCurveError)
+            }
+            Err(BLST_ERROR::BLST_PK_IS_INFINITY) => Err(CryptoMaterialError::ValidationError),
+            Err(_) => Err(CryptoMaterialError::DeserializationError),
+        }
+    }
+}
+
+impl Length for BLS12381PublicKey {
+    fn length(&self) -> usize {
+        BLS12381_PUBLIC_KEY_LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for BLS12381PublicKey {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+//////////////////////
+// Signature Traits //
+//////////////////////
+
+impl Signature for BLS12381Signature {
+    type VerifyingKeyMaterial = BLS12381PublicKey;
+    type SigningKeyMaterial = BLS12381PrivateKey;
+
+    /// Verifies that the provided signature is valid for the provided message. The signature is
+    /// checked to lie in the prime-order subgroup.
+    fn verify<T: CryptoHash + Serialize>(
+        &self,
+        message: &T,
+        public_key: &BLS12381PublicKey,
+    ) -> Result<()> {
+        Self::verify_arbitrary_msg(self, &signing_message(message), public_key)
+    }
+
+    /// Checks that `self` is valid for an arbitrary &[u8] `message` using `public_key`.
+    fn verify_arbitrary_msg(&self, message: &[u8], public_key: &BLS12381PublicKey) -> Result<()> {
+        check_blst_result(self.0.verify(
+            true,
+            message,
+            DST_BLS_SIG_IN_G2_WITH_POP,
+            &[],
+            &public_key.0,
+            false,
+        ))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl Length for BLS12381Signature {
+    fn length(&self) -> usize {
+        BLS12381_SIGNATURE_LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for BLS12381Signature {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl std::hash::Hash for BLS12381Signature {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let encoded_signature = self.to_bytes();
+        state.write(&encoded_signature);
+    }
+}
+
+impl TryFrom<&[u8]> for BLS12381Signature {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a BLS12381Signature. Subgroup membership is only checked during verification.
+    fn try_from(bytes: &[u8]) -> std::result::Result<BLS12381Signature, CryptoMaterialError> {
+        // blst also accepts uncompressed points, which we don't
+        if bytes.len() != BLS12381_SIGNATURE_LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        blst_core::Signature::from_bytes(bytes)
+            .map(BLS12381Signature)
+            .map_err(|_| CryptoMaterialError::DeserializationError)
+    }
+}
+
+impl PartialEq for BLS12381Signature {
+    fn eq(&self, other: &BLS12381Signature) -> bool {
+        self.to_bytes()[..] == other.to_bytes()[..]
+    }
+}
+
+impl Eq for BLS12381Signature {}
+
+impl fmt::Display for BLS12381Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(&self.to_bytes()[..]))
+    }
+}
+
+impl fmt::Debug for BLS12381Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BLS12381Signature({})", self)
+    }
+}
+
+//////////////////////////////
+// ProofOfPossession Traits //
+//////////////////////////////
+
+impl Length for BLS12381ProofOfPossession {
+    fn length(&self) -> usize {
+        BLS12381_POP_LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for BLS12381ProofOfPossession {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl std::hash::Hash for BLS12381ProofOfPossession {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let encoded_pop = self.to_bytes();
+        state.write(&encoded_pop);
+    }
+}
+
+impl TryFrom<&[u8]> for BLS12381ProofOfPossession {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a BLS12381ProofOfPossession. Subgroup membership is only checked during
+    /// verification.
+    fn try_from(
+        bytes: &[u8],
+    ) -> std::result::Result<BLS12381ProofOfPossession, CryptoMaterialError> {
+        if bytes.len() != BLS12381_POP_LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        blst_core::Signature::from_bytes(bytes)
+            .map(BLS12381ProofOfPossession)
+            .map_err(|_| CryptoMaterialError::DeserializationError)
+    }
+}
+
+impl PartialEq for BLS12381ProofOfPossession {
+    fn eq(&self, other: &BLS12381ProofOfPossession) -> bool {
+        self.to_bytes()[..] == other.to_bytes()[..]
+    }
+}
+
+impl Eq for BLS12381ProofOfPossession {}
+
+impl fmt::Display for BLS12381ProofOfPossession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(&self.to_bytes()[..]))
+    }
+}
+
+impl fmt::Debug for BLS12381ProofOfPossession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BLS12381ProofOfPossession({})", self)
+    }
+}
+
+#[cfg(any(test, feature = "fuzzing"))]
+use crate::test_utils::{self, KeyPair};
+
+/// Produces a uniformly random BLS12-381 keypair from a seed
+#[cfg(any(test, feature = "fuzzing"))]
+pub fn keypair_strategy() -> impl Strategy<Value = KeyPair<BLS12381PrivateKey, BLS12381PublicKey>> {
+    test_utils::uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>()
+}
+
+#[cfg(any(test, feature = "fuzzing"))]
+use proptest::prelude::*;
+
+#[cfg(any(test, feature = "fuzzing"))]
+impl proptest::arbitrary::Arbitrary for BLS12381PublicKey {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        crate::test_utils::uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>()
+            .prop_map(|v| v.public_key)
+            .boxed()
+    }
+}
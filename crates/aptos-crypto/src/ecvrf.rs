@@ -0,0 +1,501 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides an API for the verifiable random function of
+//! [RFC 9381](https://www.rfc-editor.org/rfc/rfc9381.html), with the
+//! ECVRF-EDWARDS25519-SHA512-TAI ciphersuite.
+//!
+//! A VRF is the public key version of a keyed hash: only the holder of the private key can
+//! compute the output for an input, but anyone with the public key can verify, given a proof,
+//! that the output is the right one. Outputs are unpredictable without the private key, and
+//! unique, as there is only one valid output per public key and input. This makes them suitable
+//! for electing proposers and producing randomness that no one can bias.
+//!
+//! Keys are the same as Ed25519 keys, but must not be used for both signing and proving.
+//! Public keys of small order are rejected, as are non-canonical encodings of points and scalars,
+//! so that outputs stay unique even for adversarially chosen public keys.
+//!
+//! # Examples
+//!
+//! ```
+//! use aptos_crypto::{ecvrf::*, traits::Uniform};
+//! use rand::{rngs::StdRng, SeedableRng};
+//!
+//! let mut rng: StdRng = SeedableRng::from_seed([0; 32]);
+//! let private_key = VRFPrivateKey::generate(&mut rng);
+//! let public_key: VRFPublicKey = (&private_key).into();
+//!
+//! let alpha = b"round 42";
+//! let proof = private_key.prove(alpha);
+//! let output = public_key.verify(&proof, alpha).unwrap();
+//! assert_eq!(output, VRFOutput::from(&proof));
+//! ```
+//! **Note**: The above example generates a private key using a private function intended only for
+//! testing purposes. Production code should find an alternate means for secure key generation.
+
+use crate::traits::*;
+use anyhow::{anyhow, Result};
+use aptos_crypto_derive::{DeserializeKey, SerializeKey, SilentDebug, SilentDisplay};
+use core::convert::TryFrom;
+use curve25519_dalek::{
+    constants::ED25519_BASEPOINT_TABLE,
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::Scalar,
+};
+use sha2::{Digest, Sha512};
+use std::fmt;
+
+/// The length of the VRFPrivateKey
+pub const VRF_PRIVATE_KEY_LENGTH: usize = 32;
+/// The length of the VRFPublicKey
+pub const VRF_PUBLIC_KEY_LENGTH: usize = 32;
+/// The length of the VRFProof
+pub const VRF_PROOF_LENGTH: usize = 80;
+/// The length of the VRFOutput
+pub const VRF_OUTPUT_LENGTH: usize = 64;
+
+/// The identifier of the ECVRF-EDWARDS25519-SHA512-TAI ciphersuite
+const SUITE_STRING: u8 = 0x03;
+/// The length of the challenge of a proof
+const CHALLENGE_LENGTH: usize = 16;
+
+/// A VRF private key
+#[derive(DeserializeKey, SerializeKey, SilentDebug, SilentDisplay)]
+pub struct VRFPrivateKey([u8; VRF_PRIVATE_KEY_LENGTH]);
+
+#[cfg(feature = "assert-private-keys-not-cloneable")]
+static_assertions::assert_not_impl_any!(VRFPrivateKey: Clone);
+
+#[cfg(any(test, feature = "cloneable-private-keys"))]
+impl Clone for VRFPrivateKey {
+    fn clone(&self) -> Self {
+        VRFPrivateKey(self.0)
+    }
+}
+
+/// A VRF public key
+#[derive(DeserializeKey, Clone, SerializeKey)]
+pub struct VRFPublicKey {
+    bytes: [u8; VRF_PUBLIC_KEY_LENGTH],
+    point: EdwardsPoint,
+}
+
+/// A proof that an output was computed with the private key of a public key, which also
+/// determines the output
+#[derive(DeserializeKey, Clone, SerializeKey)]
+pub struct VRFProof {
+    gamma: EdwardsPoint,
+    challenge: [u8; CHALLENGE_LENGTH],
+    response: Scalar,
+}
+
+/// The pseudorandom output of the VRF for an input
+#[derive(DeserializeKey, Clone, Copy, SerializeKey)]
+pub struct VRFOutput([u8; VRF_OUTPUT_LENGTH]);
+
+impl VRFPrivateKey {
+    /// The length of the VRFPrivateKey
+    pub const LENGTH: usize = VRF_PRIVATE_KEY_LENGTH;
+
+    /// Serialize a VRFPrivateKey.
+    pub fn to_bytes(&self) -> [u8; VRF_PRIVATE_KEY_LENGTH] {
+        self.0
+    }
+
+    /// Expands the private key into the secret scalar and the nonce seed, as for Ed25519.
+    fn expand(&self) -> (Scalar, [u8; 32]) {
+        let hash = Sha512::digest(&self.0);
+        let mut scalar_bytes = [0u8; 32];
+        scalar_bytes.copy_from_slice(&hash[..32]);
+        scalar_bytes[0] &= 248;
+        scalar_bytes[31] &= 127;
+        scalar_bytes[31] |= 64;
+        let mut nonce_seed = [0u8; 32];
+        nonce_seed.copy_from_slice(&hash[32..]);
+        (Scalar::from_bytes_mod_order(scalar_bytes), nonce_seed)
+    }
+
+    /// Computes the proof of the VRF output for the input `alpha`. Proving is deterministic.
+    pub fn prove(&self, alpha: &[u8]) -> VRFProof {
+        let public_key = VRFPublicKey::from(self);
+        let (secret, nonce_seed) = self.expand();
+        // Hashing to the curve only fails with a probability of about 2^-256
+        let h = hash_to_curve(&public_key, alpha).expect("Failed to hash the input to the curve");
+        let h_bytes = h.compress().to_bytes();
+        let gamma = h * secret;
+
+        let nonce = Scalar::from_hash(Sha512::new().chain(&nonce_seed).chain(&h_bytes));
+        let challenge = challenge(
+            &public_key,
+            &h,
+            &gamma,
+            &(&ED25519_BASEPOINT_TABLE * &nonce),
+            &(h * nonce),
+        );
+        VRFProof {
+            gamma,
+            challenge,
+            response: nonce + challenge_scalar(&challenge) * secret,
+        }
+    }
+}
+
+impl VRFPublicKey {
+    /// The length of the VRFPublicKey
+    pub const LENGTH: usize = VRF_PUBLIC_KEY_LENGTH;
+
+    /// Serialize a VRFPublicKey.
+    pub fn to_bytes(&self) -> [u8; VRF_PUBLIC_KEY_LENGTH] {
+        self.bytes
+    }
+
+    /// Verifies that the proof is valid for the input `alpha`, and returns the VRF output it
+    /// determines.
+    pub fn verify(&self, proof: &VRFProof, alpha: &[u8]) -> Result<VRFOutput> {
+        let h = hash_to_curve(self, alpha).ok_or_else(|| anyhow!("Failed to hash to the curve"))?;
+        let c = challenge_scalar(&proof.challenge);
+        // U = s * B - c * Y and V = s * H - c * Gamma
+        let u =
+            EdwardsPoint::vartime_double_scalar_mul_basepoint(&-c, &self.point, &proof.response);
+        let v = h * proof.response - proof.gamma * c;
+        if challenge(self, &h, &proof.gamma, &u, &v) != proof.challenge {
+            return Err(anyhow!("Invalid VRF proof"));
+        }
+        Ok(VRFOutput::from(proof))
+    }
+}
+
+impl VRFProof {
+    /// The length of the VRFProof
+    pub const LENGTH: usize = VRF_PROOF_LENGTH;
+
+    /// Serialize a VRFProof as `Gamma || c || s`.
+    pub fn to_bytes(&self) -> [u8; VRF_PROOF_LENGTH] {
+        let mut bytes = [0u8; VRF_PROOF_LENGTH];
+        bytes[..32].copy_from_slice(self.gamma.compress().as_bytes());
+        bytes[32..32 + CHALLENGE_LENGTH].copy_from_slice(&self.challenge);
+        bytes[32 + CHALLENGE_LENGTH..].copy_from_slice(self.response.as_bytes());
+        bytes
+    }
+}
+
+impl VRFOutput {
+    /// The length of the VRFOutput
+    pub const LENGTH: usize = VRF_OUTPUT_LENGTH;
+
+    /// Serialize a VRFOutput.
+    pub fn to_bytes(&self) -> [u8; VRF_OUTPUT_LENGTH] {
+        self.0
+    }
+}
+
+/// Decodes a point, rejecting non-canonical encodings so that every point has a single encoding
+fn decode_point(bytes: &[u8]) -> Option<EdwardsPoint> {
+    let point = CompressedEdwardsY::from_slice(bytes).decompress()?;
+    if point.compress().as_bytes() != bytes {
+        return None;
+    }
+    Some(point)
+}
+
+/// ECVRF_encode_to_curve with the try-and-increment method, which returns a point of the prime
+/// order subgroup.
+fn hash_to_curve(public_key: &VRFPublicKey, alpha: &[u8]) -> Option<EdwardsPoint> {
+    (0..=u8::MAX).find_map(|counter| {
+        let hash = Sha512::new()
+            .chain(&[SUITE_STRING, 0x01])
+            .chain(&public_key.bytes)
+            .chain(alpha)
+            .chain(&[counter, 0x00])
+            .finalize();
+        decode_point(&hash[..32]).map(|point| point.mul_by_cofactor())
+    })
+}
+
+/// ECVRF_challenge_generation, truncated to its first `CHALLENGE_LENGTH` bytes
+fn challenge(
+    public_key: &VRFPublicKey,
+    h: &EdwardsPoint,
+    gamma: &EdwardsPoint,
+    u: &EdwardsPoint,
+    v: &EdwardsPoint,
+) -> [u8; CHALLENGE_LENGTH] {
+    let mut hasher = Sha512::new()
+        .chain(&[SUITE_STRING, 0x02])
+        .chain(&public_key.bytes);
+    for point in &[h, gamma, u, v] {
+        hasher.update(point.compress().as_bytes());
+    }
+    let hash = hasher.chain(&[0x00]).finalize();
+    let mut challenge = [0u8; CHALLENGE_LENGTH];
+    challenge.copy_from_slice(&hash[..CHALLENGE_LENGTH]);
+    challenge
+}
+
+fn challenge_scalar(challenge: &[u8; CHALLENGE_LENGTH]) -> Scalar {
+    let mut bytes = [0u8; 32];
+    bytes[..CHALLENGE_LENGTH].copy_from_slice(challenge);
+    Scalar::from_bytes_mod_order(bytes)
+}
+
+///////////////////////
+// PrivateKey Traits //
+///////////////////////
+
+impl PrivateKey for VRFPrivateKey {
+    type PublicKeyMaterial = VRFPublicKey;
+}
+
+impl Uniform for VRFPrivateKey {
+    fn generate<R>(rng: &mut R) -> Self
+    where
+        R: ::rand::RngCore + ::rand::CryptoRng,
+    {
+        let mut bytes = [0u8; VRF_PRIVATE_KEY_LENGTH];
+        rng.fill_bytes(&mut bytes);
+        VRFPrivateKey(bytes)
+    }
+}
+
+impl PartialEq<Self> for VRFPrivateKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for VRFPrivateKey {}
+
+impl TryFrom<&[u8]> for VRFPrivateKey {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a VRFPrivateKey. Any 32 bytes are a valid private key.
+    fn try_from(bytes: &[u8]) -> std::result::Result<VRFPrivateKey, CryptoMaterialError> {
+        let bytes = <[u8; VRF_PRIVATE_KEY_LENGTH]>::try_from(bytes)
+            .map_err(|_| CryptoMaterialError::WrongLengthError)?;
+        Ok(VRFPrivateKey(bytes))
+    }
+}
+
+impl Length for VRFPrivateKey {
+    fn length(&self) -> usize {
+        Self::LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for VRFPrivateKey {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+//////////////////////
+// PublicKey Traits //
+//////////////////////
+
+impl From<&VRFPrivateKey> for VRFPublicKey {
+    fn from(private_key: &VRFPrivateKey) -> Self {
+        let (secret, _) = private_key.expand();
+        let point = &ED25519_BASEPOINT_TABLE * &secret;
+        VRFPublicKey {
+            bytes: point.compress().to_bytes(),
+            point,
+        }
+    }
+}
+
+impl PublicKey for VRFPublicKey {
+    type PrivateKeyMaterial = VRFPrivateKey;
+}
+
+impl std::hash::Hash for VRFPublicKey {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write(&self.bytes);
+    }
+}
+
+impl PartialEq for VRFPublicKey {
+    fn eq(&self, other: &VRFPublicKey) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl Eq for VRFPublicKey {}
+
+impl fmt::Display for VRFPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(&self.bytes))
+    }
+}
+
+impl fmt::Debug for VRFPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VRFPublicKey({})", self)
+    }
+}
+
+impl TryFrom<&[u8]> for VRFPublicKey {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a VRFPublicKey. This method will also check that the point is canonically
+    /// encoded and is not of small order.
+    fn try_from(bytes: &[u8]) -> std::result::Result<VRFPublicKey, CryptoMaterialError> {
+        let bytes = <[u8; VRF_PUBLIC_KEY_LENGTH]>::try_from(bytes)
+            .map_err(|_| CryptoMaterialError::WrongLengthError)?;
+        let point = decode_point(&bytes).ok_or(CryptoMaterialError::PointNotOnCurveError)?;
+        if point.is_small_order() {
+            return Err(CryptoMaterialError::SmallSubgroupError);
+        }
+        Ok(VRFPublicKey { bytes, point })
+    }
+}
+
+impl Length for VRFPublicKey {
+    fn length(&self) -> usize {
+        Self::LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for VRFPublicKey {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+//////////////////
+// Proof Traits //
+//////////////////
+
+impl PartialEq for VRFProof {
+    fn eq(&self, other: &VRFProof) -> bool {
+        self.to_bytes()[..] == other.to_bytes()[..]
+    }
+}
+
+impl Eq for VRFProof {}
+
+impl fmt::Display for VRFProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(&self.to_bytes()[..]))
+    }
+}
+
+impl fmt::Debug for VRFProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VRFProof({})", self)
+    }
+}
+
+impl TryFrom<&[u8]> for VRFProof {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a VRFProof. This method will also check that Gamma is canonically encoded and
+    /// that s is a canonical scalar.
+    fn try_from(bytes: &[u8]) -> std::result::Result<VRFProof, CryptoMaterialError> {
+        if bytes.len() != VRF_PROOF_LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        let gamma = decode_point(&bytes[..32]).ok_or(CryptoMaterialError::PointNotOnCurveError)?;
+        let mut challenge = [0u8; CHALLENGE_LENGTH];
+        challenge.copy_from_slice(&bytes[32..32 + CHALLENGE_LENGTH]);
+        let mut response = [0u8; 32];
+        response.copy_from_slice(&bytes[32 + CHALLENGE_LENGTH..]);
+        let response = Scalar::from_canonical_bytes(response)
+            .ok_or(CryptoMaterialError::CanonicalRepresentationError)?;
+        Ok(VRFProof {
+            gamma,
+            challenge,
+            response,
+        })
+    }
+}
+
+impl Length for VRFProof {
+    fn length(&self) -> usize {
+        Self::LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for VRFProof {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+///////////////////
+// Output Traits //
+///////////////////
+
+impl From<&VRFProof> for VRFOutput {
+    /// ECVRF_proof_to_hash. The output of an unverified proof must not be trusted.
+    fn from(proof: &VRFProof) -> Self {
+        let hash = Sha512::new()
+            .chain(&[SUITE_STRING, 0x03])
+            .chain(proof.gamma.mul_by_cofactor().compress().as_bytes())
+            .chain(&[0x00])
+            .finalize();
+        let mut output = [0u8; VRF_OUTPUT_LENGTH];
+        output.copy_from_slice(&hash);
+        VRFOutput(output)
+    }
+}
+
+impl PartialEq for VRFOutput {
+    fn eq(&self, other: &VRFOutput) -> bool {
+        self.0[..] == other.0[..]
+    }
+}
+
+impl Eq for VRFOutput {}
+
+impl std::hash::Hash for VRFOutput {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write(&self.0);
+    }
+}
+
+impl fmt::Display for VRFOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(&self.0[..]))
+    }
+}
+
+impl fmt::Debug for VRFOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VRFOutput({})", self)
+    }
+}
+
+impl TryFrom<&[u8]> for VRFOutput {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a VRFOutput.
+    fn try_from(bytes: &[u8]) -> std::result::Result<VRFOutput, CryptoMaterialError> {
+        let bytes = <[u8; VRF_OUTPUT_LENGTH]>::try_from(bytes)
+            .map_err(|_| CryptoMaterialError::WrongLengthError)?;
+        Ok(VRFOutput(bytes))
+    }
+}
+
+impl Length for VRFOutput {
+    fn length(&self) -> usize {
+        Self::LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for VRFOutput {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+#[cfg(any(test, feature = "fuzzing"))]
+use crate::test_utils::{self, KeyPair};
+
+/// Produces a uniformly random VRF keypair from a seed
+#[cfg(any(test, feature = "fuzzing"))]
+pub fn keypair_strategy() -> impl Strategy<Value = KeyPair<VRFPrivateKey, VRFPublicKey>> {
+    test_utils::uniform_keypair_strategy::<VRFPrivateKey, VRFPublicKey>()
+}
+
+#[cfg(any(test, feature = "fuzzing"))]
+use proptest::prelude::*;
@@ -7,14 +7,18 @@
 #![cfg_attr(mirai, allow(incomplete_features), feature(const_generics))]
 
 //! A library supplying various cryptographic primitives
+pub mod bls12381;
 pub mod compat;
+pub mod ecvrf;
 pub mod ed25519;
 pub mod error;
 pub mod hash;
 pub mod hkdf;
 pub mod multi_ed25519;
 pub mod noise;
+pub mod secp256k1_ecdsa;
 pub mod test_utils;
+pub mod threshold_bls12381;
 pub mod traits;
 pub mod validatable;
 pub mod x25519;
@@ -0,0 +1,401 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides an API for ECDSA signatures over the secp256k1 curve, the signature
+//! scheme of Bitcoin and Ethereum wallets.
+//!
+//! Messages are signed the way Ethereum wallets sign them (`personal_sign`): the signed digest is
+//! the Keccak-256 hash of the message prefixed as per EIP-191, i.e.,
+//! `keccak256("\x19Ethereum Signed Message:\n" || len(message) || message)`, with the length of
+//! the message in decimal. Signatures are serialized as `r || s` and verification rejects
+//! signatures whose `s` is in the upper half of the curve order, so that signatures aren't
+//! malleable. Public keys are serialized in the uncompressed 65 bytes format.
+//!
+//! # Examples
+//!
+//! ```
+//! use aptos_crypto_derive::{CryptoHasher, BCSCryptoHash};
+//! use aptos_crypto::{
+//!     secp256k1_ecdsa::*,
+//!     traits::{Signature, SigningKey, Uniform},
+//! };
+//! use rand::{rngs::StdRng, SeedableRng};
+//! use serde::{Serialize, Deserialize};
+//!
+//! #[derive(Serialize, Deserialize, CryptoHasher, BCSCryptoHash)]
+//! pub struct TestCryptoDocTest(String);
+//! let message = TestCryptoDocTest("Test message".to_string());
+//!
+//! let mut rng: StdRng = SeedableRng::from_seed([0; 32]);
+//! let private_key = Secp256k1EcdsaPrivateKey::generate(&mut rng);
+//! let public_key: Secp256k1EcdsaPublicKey = (&private_key).into();
+//! let signature = private_key.sign(&message);
+//! assert!(signature.verify(&message, &public_key).is_ok());
+//! ```
+//! **Note**: The above example generates a private key using a private function intended only for
+//! testing purposes. Production code should find an alternate means for secure key generation.
+
+use crate::{hash::CryptoHash, traits::*};
+use anyhow::{anyhow, Result};
+use aptos_crypto_derive::{DeserializeKey, SerializeKey, SilentDebug, SilentDisplay};
+use core::convert::TryFrom;
+use serde::Serialize;
+use std::fmt;
+use tiny_keccak::{Hasher, Keccak};
+
+/// The length of the Secp256k1EcdsaPrivateKey
+pub const SECP256K1_ECDSA_PRIVATE_KEY_LENGTH: usize = libsecp256k1::util::SECRET_KEY_SIZE;
+/// The length of the Secp256k1EcdsaPublicKey, in the uncompressed format
+pub const SECP256K1_ECDSA_PUBLIC_KEY_LENGTH: usize = libsecp256k1::util::FULL_PUBLIC_KEY_SIZE;
+/// The length of the Secp256k1EcdsaSignature
+pub const SECP256K1_ECDSA_SIGNATURE_LENGTH: usize = libsecp256k1::util::SIGNATURE_SIZE;
+
+/// A secp256k1 ECDSA private key
+#[derive(DeserializeKey, SerializeKey, SilentDebug, SilentDisplay)]
+pub struct Secp256k1EcdsaPrivateKey(libsecp256k1::SecretKey);
+
+#[cfg(feature = "assert-private-keys-not-cloneable")]
+static_assertions::assert_not_impl_any!(Secp256k1EcdsaPrivateKey: Clone);
+
+#[cfg(any(test, feature = "cloneable-private-keys"))]
+impl Clone for Secp256k1EcdsaPrivateKey {
+    fn clone(&self) -> Self {
+        let serialized: &[u8] = &(self.to_bytes());
+        Secp256k1EcdsaPrivateKey::try_from(serialized).unwrap()
+    }
+}
+
+/// A secp256k1 ECDSA public key
+#[derive(DeserializeKey, Clone, SerializeKey)]
+pub struct Secp256k1EcdsaPublicKey(libsecp256k1::PublicKey);
+
+/// A secp256k1 ECDSA signature
+#[derive(DeserializeKey, Clone, SerializeKey)]
+pub struct Secp256k1EcdsaSignature(libsecp256k1::Signature);
+
+/// The prefix of the messages signed by Ethereum wallets (EIP-191 version `0x45`)
+const ETHEREUM_SIGNED_MESSAGE_PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n";
+
+/// The digest of a message, which is what's actually signed
+fn message_digest(message: &[u8]) -> libsecp256k1::Message {
+    let mut keccak = Keccak::v256();
+    keccak.update(ETHEREUM_SIGNED_MESSAGE_PREFIX);
+    keccak.update(message.len().to_string().as_bytes());
+    keccak.update(message);
+    let mut digest = [0u8; 32];
+    keccak.finalize(&mut digest);
+    libsecp256k1::Message::parse(&digest)
+}
+
+impl Secp256k1EcdsaPrivateKey {
+    /// The length of the Secp256k1EcdsaPrivateKey
+    pub const LENGTH: usize = SECP256K1_ECDSA_PRIVATE_KEY_LENGTH;
+
+    /// Serialize a Secp256k1EcdsaPrivateKey.
+    pub fn to_bytes(&self) -> [u8; SECP256K1_ECDSA_PRIVATE_KEY_LENGTH] {
+        self.0.serialize()
+    }
+
+    /// Private function aimed at minimizing code duplication between sign
+    /// methods of the SigningKey implementation. This should remain private.
+    fn sign_arbitrary_message(&self, message: &[u8]) -> Secp256k1EcdsaSignature {
+        // The signature is normalized to a low s
+        let (signature, _recovery_id) = libsecp256k1::sign(&message_digest(message), &self.0);
+        Secp256k1EcdsaSignature(signature)
+    }
+}
+
+impl Secp256k1EcdsaPublicKey {
+    /// Serialize a Secp256k1EcdsaPublicKey.
+    pub fn to_bytes(&self) -> [u8; SECP256K1_ECDSA_PUBLIC_KEY_LENGTH] {
+        self.0.serialize()
+    }
+}
+
+impl Secp256k1EcdsaSignature {
+    /// The length of the Secp256k1EcdsaSignature
+    pub const LENGTH: usize = SECP256K1_ECDSA_SIGNATURE_LENGTH;
+
+    /// Serialize a Secp256k1EcdsaSignature.
+    pub fn to_bytes(&self) -> [u8; SECP256K1_ECDSA_SIGNATURE_LENGTH] {
+        self.0.serialize()
+    }
+
+    /// Check for malleability issues. Given a valid signature `(r, s)`, the signature `(r, -s)` is
+    /// also valid, so only signatures with an `s` in the lower half of the curve order are
+    /// accepted.
+    pub fn check_malleability(&self) -> std::result::Result<(), CryptoMaterialError> {
+        if self.0.s.is_high() {
+            return Err(CryptoMaterialError::CanonicalRepresentationError);
+        }
+        Ok(())
+    }
+}
+
+///////////////////////
+// PrivateKey Traits //
+///////////////////////
+
+impl PrivateKey for Secp256k1EcdsaPrivateKey {
+    type PublicKeyMaterial = Secp256k1EcdsaPublicKey;
+}
+
+impl SigningKey for Secp256k1EcdsaPrivateKey {
+    type VerifyingKeyMaterial = Secp256k1EcdsaPublicKey;
+    type SignatureMaterial = Secp256k1EcdsaSignature;
+
+    fn sign<T: CryptoHash + Serialize>(&self, message: &T) -> Secp256k1EcdsaSignature {
+        Secp256k1EcdsaPrivateKey::sign_arbitrary_message(self, signing_message(message).as_ref())
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    fn sign_arbitrary_message(&self, message: &[u8]) -> Secp256k1EcdsaSignature {
+        Secp256k1EcdsaPrivateKey::sign_arbitrary_message(self, message)
+    }
+}
+
+impl Uniform for Secp256k1EcdsaPrivateKey {
+    fn generate<R>(rng: &mut R) -> Self
+    where
+        R: ::rand::RngCore + ::rand::CryptoRng,
+    {
+        // Rejection sampling, as not every 32 bytes are a valid scalar
+        let mut bytes = [0u8; SECP256K1_ECDSA_PRIVATE_KEY_LENGTH];
+        loop {
+            rng.fill_bytes(&mut bytes);
+            if let Ok(secret_key) = libsecp256k1::SecretKey::parse(&bytes) {
+                return Secp256k1EcdsaPrivateKey(secret_key);
+            }
+        }
+    }
+}
+
+impl PartialEq<Self> for Secp256k1EcdsaPrivateKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for Secp256k1EcdsaPrivateKey {}
+
+impl TryFrom<&[u8]> for Secp256k1EcdsaPrivateKey {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a Secp256k1EcdsaPrivateKey. This method will also check that the key is a
+    /// non-zero scalar smaller than the order of the curve.
+    fn try_from(
+        bytes: &[u8],
+    ) -> std::result::Result<Secp256k1EcdsaPrivateKey, CryptoMaterialError> {
+        if bytes.len() != SECP256K1_ECDSA_PRIVATE_KEY_LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        libsecp256k1::SecretKey::parse_slice(bytes)
+            .map(Secp256k1EcdsaPrivateKey)
+            .map_err(|_| CryptoMaterialError::DeserializationError)
+    }
+}
+
+impl Length for Secp256k1EcdsaPrivateKey {
+    fn length(&self) -> usize {
+        Self::LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for Secp256k1EcdsaPrivateKey {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+//////////////////////
+// PublicKey Traits //
+//////////////////////
+
+impl From<&Secp256k1EcdsaPrivateKey> for Secp256k1EcdsaPublicKey {
+    fn from(private_key: &Secp256k1EcdsaPrivateKey) -> Self {
+        Secp256k1EcdsaPublicKey(libsecp256k1::PublicKey::from_secret_key(&private_key.0))
+    }
+}
+
+impl PublicKey for Secp256k1EcdsaPublicKey {
+    type PrivateKeyMaterial = Secp256k1EcdsaPrivateKey;
+}
+
+impl std::hash::Hash for Secp256k1EcdsaPublicKey {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let encoded_pubkey = self.to_bytes();
+        state.write(&encoded_pubkey);
+    }
+}
+
+impl PartialEq for Secp256k1EcdsaPublicKey {
+    fn eq(&self, other: &Secp256k1EcdsaPublicKey) -> bool {
+        self.to_bytes()[..] == other.to_bytes()[..]
+    }
+}
+
+impl Eq for Secp256k1EcdsaPublicKey {}
+
+impl VerifyingKey for Secp256k1EcdsaPublicKey {
+    type SigningKeyMaterial = Secp256k1EcdsaPrivateKey;
+    type SignatureMaterial = Secp256k1EcdsaSignature;
+}
+
+impl fmt::Display for Secp256k1EcdsaPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(&self.to_bytes()[..]))
+    }
+}
+
+impl fmt::Debug for Secp256k1EcdsaPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secp256k1EcdsaPublicKey({})", self)
+    }
+}
+
+impl TryFrom<&[u8]> for Secp256k1EcdsaPublicKey {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a Secp256k1EcdsaPublicKey from its uncompressed format. This method will also
+    /// check that the point is on the curve. As secp256k1 has a cofactor of 1, there are no small
+    /// subgroups to check for.
+    fn try_from(bytes: &[u8]) -> std::result::Result<Secp256k1EcdsaPublicKey, CryptoMaterialError> {
+        if bytes.len() != SECP256K1_ECDSA_PUBLIC_KEY_LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        libsecp256k1::PublicKey::parse_slice(bytes, Some(libsecp256k1::PublicKeyFormat::Full))
+            .map(Secp256k1EcdsaPublicKey)
+            .map_err(|_| CryptoMaterialError::PointNotOnCurveError)
+    }
+}
+
+impl Length for Secp256k1EcdsaPublicKey {
+    fn length(&self) -> usize {
+        SECP256K1_ECDSA_PUBLIC_KEY_LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for Secp256k1EcdsaPublicKey {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+//////////////////////
+// Signature Traits //
+//////////////////////
+
+impl Signature for Secp256k1EcdsaSignature {
+    type VerifyingKeyMaterial = Secp256k1EcdsaPublicKey;
+    type SigningKeyMaterial = Secp256k1EcdsaPrivateKey;
+
+    /// Verifies that the provided signature is valid for the provided message. Signatures with a
+    /// high s are rejected.
+    fn verify<T: CryptoHash + Serialize>(
+        &self,
+        message: &T,
+        public_key: &Secp256k1EcdsaPublicKey,
+    ) -> Result<()> {
+        Self::verify_arbitrary_msg(self, &signing_message(message), public_key)
+    }
+
+    /// Checks that `self` is valid for an arbitrary &[u8] `message` using `public_key`.
+    fn verify_arbitrary_msg(
+        &self,
+        message: &[u8],
+        public_key: &Secp256k1EcdsaPublicKey,
+    ) -> Result<()> {
+        self.check_malleability()?;
+        if libsecp256k1::verify(&message_digest(message), &self.0, &public_key.0) {
+            Ok(())
+        } else {
+            Err(anyhow!("Secp256k1 ECDSA signature verification failed"))
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl Length for Secp256k1EcdsaSignature {
+    fn length(&self) -> usize {
+        SECP256K1_ECDSA_SIGNATURE_LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for Secp256k1EcdsaSignature {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl std::hash::Hash for Secp256k1EcdsaSignature {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let encoded_signature = self.to_bytes();
+        state.write(&encoded_signature);
+    }
+}
+
+impl TryFrom<&[u8]> for Secp256k1EcdsaSignature {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a Secp256k1EcdsaSignature, rejecting `r` or `s` values that aren't smaller than
+    /// the order of the curve.
+    fn try_from(bytes: &[u8]) -> std::result::Result<Secp256k1EcdsaSignature, CryptoMaterialError> {
+        if bytes.len() != SECP256K1_ECDSA_SIGNATURE_LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        libsecp256k1::Signature::parse_standard_slice(bytes)
+            .map(Secp256k1EcdsaSignature)
+            .map_err(|_| CryptoMaterialError::DeserializationError)
+    }
+}
+
+impl PartialEq for Secp256k1EcdsaSignature {
+    fn eq(&self, other: &Secp256k1EcdsaSignature) -> bool {
+        self.to_bytes()[..] == other.to_bytes()[..]
+    }
+}
+
+impl Eq for Secp256k1EcdsaSignature {}
+
+impl fmt::Display for Secp256k1EcdsaSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(&self.to_bytes()[..]))
+    }
+}
+
+impl fmt::Debug for Secp256k1EcdsaSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secp256k1EcdsaSignature({})", self)
+    }
+}
+
+#[cfg(any(test, feature = "fuzzing"))]
+use crate::test_utils::{self, KeyPair};
+
+/// Produces a uniformly random secp256k1 ECDSA keypair from a seed
+#[cfg(any(test, feature = "fuzzing"))]
+pub fn keypair_strategy(
+) -> impl Strategy<Value = KeyPair<Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey>> {
+    test_utils::uniform_keypair_strategy::<Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey>()
+}
+
+#[cfg(any(test, feature = "fuzzing"))]
+use proptest::prelude::*;
+
+#[cfg(any(test, feature = "fuzzing"))]
+impl proptest::arbitrary::Arbitrary for Secp256k1EcdsaPublicKey {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        crate::test_utils::uniform_keypair_strategy::<
+            Secp256k1EcdsaPrivateKey,
+            Secp256k1EcdsaPublicKey,
+        >()
+        .prop_map(|v| v.public_key)
+        .boxed()
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides an API for threshold BLS signatures over the BLS12-381 curve, built on
+//! the signatures of the [`bls12381`](crate::bls12381) module.
+//!
+//! A private key is split into `n` shares with Shamir's secret sharing, so that any `t` of them
+//! can jointly sign, while fewer than `t` learn nothing about the private key. Every share is a
+//! regular [`BLS12381PrivateKey`] producing a partial signature, and any `t` valid partial
+//! signatures on the same message are combined, by Lagrange interpolation in the exponent, into
+//! the signature of the original private key. Combined signatures are thus indistinguishable
+//! from signatures by the original key, and verify against its public key.
+//!
+//! # Examples
+//!
+//! ```
+//! use aptos_crypto_derive::{CryptoHasher, BCSCryptoHash};
+//! use aptos_crypto::{
+//!     bls12381::*,
+//!     threshold_bls12381::*,
+//!     traits::{Signature, Uniform},
+//! };
+//! use rand::{rngs::StdRng, SeedableRng};
+//! use serde::{Serialize, Deserialize};
+//!
+//! #[derive(Serialize, Deserialize, CryptoHasher, BCSCryptoHash)]
+//! pub struct TestCryptoDocTest(String);
+//! let message = TestCryptoDocTest("Test message".to_string());
+//!
+//! let mut rng: StdRng = SeedableRng::from_seed([0; 32]);
+//! let private_key = BLS12381PrivateKey::generate(&mut rng);
+//! let public_key: BLS12381PublicKey = (&private_key).into();
+//!
+//! let shares = BLS12381PrivateKeyShare::split(&private_key, 2, 3, &mut rng).unwrap();
+//! let partial_signatures: Vec<_> = shares[1..].iter().map(|share| share.sign(&message)).collect();
+//! let signature = BLS12381PartialSignature::combine(&partial_signatures).unwrap();
+//! assert!(signature.verify(&message, &public_key).is_ok());
+//! ```
+
+use crate::{
+    bls12381::{BLS12381PrivateKey, BLS12381PublicKey, BLS12381Signature},
+    hash::CryptoHash,
+    traits::*,
+};
+use anyhow::{anyhow, ensure, Result};
+use bls12_381::{G2Affine, G2Projective, Scalar};
+use core::convert::TryFrom;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A share of a BLS12-381 private key, i.e., the evaluation at `index` of the polynomial whose
+/// constant term is the private key.
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(any(test, feature = "cloneable-private-keys"), derive(Clone))]
+pub struct BLS12381PrivateKeyShare {
+    index: u8,
+    private_key: BLS12381PrivateKey,
+}
+
+/// A signature by a share of a BLS12-381 private key
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BLS12381PartialSignature {
+    index: u8,
+    signature: BLS12381Signature,
+}
+
+impl BLS12381PrivateKeyShare {
+    /// Splits the private key into `num_shares` shares, indexed from 1, any `threshold` of which
+    /// can sign on behalf of the private key.
+    pub fn split<R>(
+        private_key: &BLS12381PrivateKey,
+        threshold: u8,
+        num_shares: u8,
+        rng: &mut R,
+    ) -> Result<Vec<BLS12381PrivateKeyShare>>
+    where
+        R: ::rand::RngCore + ::rand::CryptoRng,
+    {
+        ensure!(
+            threshold > 0 && threshold <= num_shares,
+            "The threshold must be between 1 and the number of shares"
+        );
+
+        let mut coefficients = vec![private_key_to_scalar(private_key)?];
+        for _ in 1..threshold {
+            let mut bytes = [0u8; 64];
+            rng.fill_bytes(&mut bytes);
+            coefficients.push(Scalar::from_bytes_wide(&bytes));
+        }
+
+        (1..=num_shares)
+            .map(|index| {
+                // Evaluates the polynomial at index with Horner's method
+                let x = Scalar::from(index as u64);
+                let y = coefficients
+                    .iter()
+                    .rev()
+                    .fold(Scalar::zero(), |y, coefficient| y * x + coefficient);
+                Ok(BLS12381PrivateKeyShare {
+                    index,
+                    private_key: scalar_to_private_key(&y)?,
+                })
+            })
+            .collect()
+    }
+
+    /// The index of the share
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// The public key verifying the partial signatures of this share
+    pub fn public_key(&self) -> BLS12381PublicKey {
+        (&self.private_key).into()
+    }
+
+    /// Signs the message with the share
+    pub fn sign<T: CryptoHash + Serialize>(&self, message: &T) -> BLS12381PartialSignature {
+        BLS12381PartialSignature {
+            index: self.index,
+            signature: self.private_key.sign(message),
+        }
+    }
+}
+
+impl BLS12381PartialSignature {
+    /// The index of the share that produced the partial signature
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Verifies the partial signature against the public key of the share that produced it
+    pub fn verify<T: CryptoHash + Serialize>(
+        &self,
+        message: &T,
+        public_key_share: &BLS12381PublicKey,
+    ) -> Result<()> {
+        self.signature.verify(message, public_key_share)
+    }
+
+    /// Combines partial signatures on the same message by distinct shares into the signature of
+    /// the private key. At least as many partial signatures as the threshold are required, and
+    /// they must all be valid, as the combined signature is otherwise invalid.
+    pub fn combine(partial_signatures: &[BLS12381PartialSignature]) -> Result<BLS12381Signature> {
+        ensure!(!partial_signatures.is_empty(), "No signatures to combine");
+        let indices: Vec<_> = partial_signatures.iter().map(|sig| sig.index).collect();
+        ensure!(
+            indices.iter().collect::<HashSet<_>>().len() == indices.len(),
+            "Partial signatures must be by distinct shares"
+        );
+        ensure!(!indices.contains(&0), "Shares are indexed from 1");
+
+        let mut combined = G2Projective::identity();
+        for (i, partial_signature) in partial_signatures.iter().enumerate() {
+            let point: Option<G2Affine> =
+                G2Affine::from_compressed(&partial_signature.signature.to_bytes()).into();
+            let point = point.ok_or_else(|| anyhow!("Invalid partial signature"))?;
+            combined += G2Projective::from(point) * lagrange_coefficient_at_zero(&indices, i)?;
+        }
+
+        BLS12381Signature::try_from(&G2Affine::from(combined).to_compressed()[..])
+            .map_err(|error| anyhow!("Failed to combine partial signatures: {}", error))
+    }
+}
+
+/// The Lagrange coefficient of the `i`-th index, for interpolating at 0 the polynomial going
+/// through the points at `indices`.
+fn lagrange_coefficient_at_zero(indices: &[u8], i: usize) -> Result<Scalar> {
+    let x_i = Scalar::from(indices[i] as u64);
+    let mut numerator = Scalar::one();
+    let mut denominator = Scalar::one();
+    for (j, index) in indices.iter().enumerate() {
+        if i != j {
+            let x_j = Scalar::from(*index as u64);
+            numerator *= x_j;
+            denominator *= x_j - x_i;
+        }
+    }
+    let inverse: Option<Scalar> = denominator.invert().into();
+    inverse
+        .map(|inverse| numerator * inverse)
+        .ok_or_else(|| anyhow!("Partial signatures must be by distinct shares"))
+}
+
+/// Private keys are serialized as big-endian scalars, while scalars are little-endian.
+fn private_key_to_scalar(private_key: &BLS12381PrivateKey) -> Result<Scalar> {
+    let mut bytes = private_key.to_bytes();
+    bytes.reverse();
+    let scalar: Option<Scalar> = Scalar::from_bytes(&bytes).into();
+    scalar.ok_or_else(|| anyhow!("Invalid private key"))
+}
+
+fn scalar_to_private_key(scalar: &Scalar) -> Result<BLS12381PrivateKey> {
+    let mut bytes = scalar.to_bytes();
+    bytes.reverse();
+    // A share is only zero with negligible probability
+    BLS12381PrivateKey::try_from(&bytes[..])
+        .map_err(|error| anyhow!("Failed to derive a private key share: {}", error))
+}
@@ -0,0 +1,184 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    bls12381::{
+        BLS12381PrivateKey, BLS12381ProofOfPossession, BLS12381PublicKey, BLS12381Signature,
+        BLS12381_POP_LENGTH, BLS12381_PRIVATE_KEY_LENGTH, BLS12381_PUBLIC_KEY_LENGTH,
+        BLS12381_SIGNATURE_LENGTH,
+    },
+    test_utils::{random_serializable_struct, uniform_keypair_strategy, KeyPair},
+    traits::*,
+};
+
+use core::convert::TryFrom;
+use proptest::{collection::vec, prelude::*};
+
+type BLS12381KeyPair = KeyPair<BLS12381PrivateKey, BLS12381PublicKey>;
+
+proptest! {
+    #[test]
+    fn test_keys_encode(keypair in uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>()) {
+        {
+            let encoded = keypair.private_key.to_encoded_string().unwrap();
+            prop_assert_eq!(2 * BLS12381_PRIVATE_KEY_LENGTH, encoded.len());
+            let decoded = BLS12381PrivateKey::from_encoded_string(&encoded);
+            prop_assert_eq!(Some(keypair.private_key), decoded.ok());
+        }
+        {
+            let encoded = keypair.public_key.to_encoded_string().unwrap();
+            prop_assert_eq!(2 * BLS12381_PUBLIC_KEY_LENGTH, encoded.len());
+            let decoded = BLS12381PublicKey::from_encoded_string(&encoded);
+            prop_assert_eq!(Some(keypair.public_key), decoded.ok());
+        }
+    }
+
+    #[test]
+    fn test_keys_custom_serialisation(
+        keypair in uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>()
+    ) {
+        {
+            let serialized: &[u8] = &(keypair.private_key.to_bytes());
+            prop_assert_eq!(BLS12381_PRIVATE_KEY_LENGTH, serialized.len());
+            let deserialized = BLS12381PrivateKey::try_from(serialized);
+            prop_assert_eq!(Some(keypair.private_key), deserialized.ok());
+        }
+        {
+            let serialized: &[u8] = &(keypair.public_key.to_bytes());
+            prop_assert_eq!(BLS12381_PUBLIC_KEY_LENGTH, serialized.len());
+            let deserialized = BLS12381PublicKey::try_from(serialized);
+            prop_assert_eq!(Some(keypair.public_key), deserialized.ok());
+        }
+    }
+
+    #[test]
+    fn test_keys_bcs_serialisation(
+        keypair in uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>()
+    ) {
+        let serialized = bcs::to_bytes(&keypair.public_key).unwrap();
+        // The key is serialized as bytes, prefixed with their length
+        prop_assert_eq!(1 + BLS12381_PUBLIC_KEY_LENGTH, serialized.len());
+        let deserialized: BLS12381PublicKey = bcs::from_bytes(&serialized).unwrap();
+        prop_assert_eq!(keypair.public_key, deserialized);
+    }
+
+    #[test]
+    fn test_signature_verification_custom_serialisation(
+        message in random_serializable_struct(),
+        keypair in uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>()
+    ) {
+        let signature = keypair.private_key.sign(&message);
+        let serialized: &[u8] = &(signature.to_bytes());
+        prop_assert_eq!(BLS12381_SIGNATURE_LENGTH, serialized.len());
+        let deserialized = BLS12381Signature::try_from(serialized).unwrap();
+        prop_assert!(deserialized.verify(&message, &keypair.public_key).is_ok());
+    }
+
+    #[test]
+    fn test_signature_verification_from_arbitrary(
+        msg in vec(proptest::num::u8::ANY, 1..128),
+        keypair in uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>(),
+        other_keypair in uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>()
+    ) {
+        let signature = keypair.private_key.sign_arbitrary_message(&msg);
+        prop_assert!(signature.verify_arbitrary_msg(&msg, &keypair.public_key).is_ok());
+        // The signature doesn't verify under another key
+        prop_assume!(keypair.public_key != other_keypair.public_key);
+        prop_assert!(signature.verify_arbitrary_msg(&msg, &other_keypair.public_key).is_err());
+    }
+
+    #[test]
+    fn test_proof_of_possession(
+        keypair in uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>(),
+        other_keypair in uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>()
+    ) {
+        let pop = BLS12381ProofOfPossession::create(&keypair.private_key);
+        prop_assert!(pop.verify(&keypair.public_key).is_ok());
+
+        let serialized: &[u8] = &(pop.to_bytes());
+        prop_assert_eq!(BLS12381_POP_LENGTH, serialized.len());
+        let deserialized = BLS12381ProofOfPossession::try_from(serialized).unwrap();
+        prop_assert!(deserialized.verify(&keypair.public_key).is_ok());
+
+        prop_assume!(keypair.public_key != other_keypair.public_key);
+        prop_assert!(pop.verify(&other_keypair.public_key).is_err());
+    }
+
+    #[test]
+    fn test_multisig(
+        message in random_serializable_struct(),
+        other_message in random_serializable_struct(),
+        keypairs in proptest::array::uniform5(uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>())
+    ) {
+        prop_assume!(message.0 != other_message.0);
+        let public_keys: Vec<_> = keypairs.iter().map(|keypair| &keypair.public_key).collect();
+        let signatures: Vec<_> = keypairs.iter().map(|keypair| keypair.private_key.sign(&message)).collect();
+        let multisig = BLS12381Signature::aggregate(signatures.iter().collect()).unwrap();
+
+        prop_assert!(multisig.verify_multisig(&message, &public_keys).is_ok());
+        prop_assert!(multisig.verify_multisig(&other_message, &public_keys).is_err());
+        prop_assert!(multisig.verify_multisig(&message, &public_keys[1..]).is_err());
+
+        // The multi-signature verifies under the aggregated public key
+        let aggregate_public_key = BLS12381PublicKey::aggregate(public_keys).unwrap();
+        prop_assert!(multisig.verify(&message, &aggregate_public_key).is_ok());
+    }
+
+    #[test]
+    fn test_aggregate_signature(
+        messages in vec(random_serializable_struct(), 5),
+        keypairs in proptest::array::uniform5(uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>())
+    ) {
+        let public_keys: Vec<_> = keypairs.iter().map(|keypair| &keypair.public_key).collect();
+        let signatures: Vec<_> = keypairs
+            .iter()
+            .zip(messages.iter())
+            .map(|(keypair, message)| keypair.private_key.sign(message))
+            .collect();
+        let aggregate = BLS12381Signature::aggregate(signatures.iter().collect()).unwrap();
+        let message_refs: Vec<_> = messages.iter().collect();
+
+        prop_assert!(aggregate.verify_aggregate(&message_refs, &public_keys).is_ok());
+        prop_assert!(aggregate.verify_aggregate(&message_refs[1..], &public_keys).is_err());
+
+        // Messages are bound to the key that signed them
+        let mut swapped_keys = public_keys.clone();
+        swapped_keys.swap(0, 1);
+        prop_assume!(messages[0].0 != messages[1].0);
+        prop_assert!(aggregate.verify_aggregate(&message_refs, &swapped_keys).is_err());
+    }
+}
+
+#[test]
+fn test_deserialization_rejects_invalid_material() {
+    // The identity is not a valid public key
+    let mut identity = [0u8; BLS12381_PUBLIC_KEY_LENGTH];
+    identity[0] = 0xc0;
+    assert_eq!(
+        BLS12381PublicKey::try_from(&identity[..]),
+        Err(CryptoMaterialError::ValidationError)
+    );
+
+    // Wrong lengths are rejected
+    let keypair: BLS12381KeyPair = BLS12381PrivateKey::generate_for_testing().into();
+    let public_key = keypair.public_key.to_bytes();
+    assert_eq!(
+        BLS12381PublicKey::try_from(&public_key[1..]),
+        Err(CryptoMaterialError::WrongLengthError)
+    );
+    let signature = keypair
+        .private_key
+        .sign_arbitrary_message(b"message")
+        .to_bytes();
+    assert_eq!(
+        BLS12381Signature::try_from(&signature[1..]),
+        Err(CryptoMaterialError::WrongLengthError)
+    );
+
+    // The zero scalar is not a valid private key
+    assert!(BLS12381PrivateKey::try_from(&[0u8; BLS12381_PRIVATE_KEY_LENGTH][..]).is_err());
+
+    // Nothing to aggregate
+    assert!(BLS12381Signature::aggregate(vec![]).is_err());
+    assert!(BLS12381PublicKey::aggregate(vec![]).is_err());
+}
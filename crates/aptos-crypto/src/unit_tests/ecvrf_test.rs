@@ -0,0 +1,147 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    ecvrf::{
+        keypair_strategy, VRFOutput, VRFPrivateKey, VRFProof, VRFPublicKey, VRF_PRIVATE_KEY_LENGTH,
+        VRF_PROOF_LENGTH, VRF_PUBLIC_KEY_LENGTH,
+    },
+    traits::*,
+};
+
+use core::convert::TryFrom;
+use proptest::{collection::vec, prelude::*};
+
+/// The ECVRF-EDWARDS25519-SHA512-TAI test vectors of RFC 9381, section B.3: the private key, the
+/// public key, the input, the proof and the output.
+const TEST_VECTORS: &[(&str, &str, &str, &str, &str)] = &[
+    (
+        "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        "",
+        "8657106690b5526245a92b003bb079ccd1a92130477671f6fc01ad16f26f723f26f8a57ccaed74ee1b190bed1f479d9727d2d0f9b005a6e456a35d4fb0daab1268a1b0db10836d9826a528ca76567805",
+        "90cf1df3b703cce59e2a35b925d411164068269d7b2d29f3301c03dd757876ff66b71dda49d2de59d03450451af026798e8f81cd2e333de5cdf4f3e140fdd8ae",
+    ),
+    (
+        "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+        "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        "72",
+        "f3141cd382dc42909d19ec5110469e4feae18300e94f304590abdced48aed5933bf0864a62558b3ed7f2fea45c92a465301b3bbf5e3e54ddf2d935be3b67926da3ef39226bbc355bdc9850112c8f4b02",
+        "eb4440665d3891d668e7e0fcaf587f1b4bd7fbfe99d0eb2211ccec90496310eb5e33821bc613efb94db5e5b54c70a848a0bef4553a41befc57663b56373a5031",
+    ),
+    (
+        "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+        "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+        "af82",
+        "9bc0f79119cc5604bf02d23b4caede71393cedfbb191434dd016d30177ccbf8096bb474e53895c362d8628ee9f9ea3c0e52c7a5c691b6c18c9979866568add7a2d41b00b05081ed0f58ee5e31b3a970e",
+        "645427e5d00c62a23fb703732fa5d892940935942101e456ecca7bb217c61c452118fec1219202a0edcf038bb6373241578be7217ba85a2687f7a0310b2df19f",
+    ),
+];
+
+#[test]
+fn test_vectors() {
+    for (private_key, public_key, alpha, proof, output) in TEST_VECTORS {
+        let private_key = VRFPrivateKey::from_encoded_string(private_key).unwrap();
+        let public_key = VRFPublicKey::from_encoded_string(public_key).unwrap();
+        let alpha = hex::decode(alpha).unwrap();
+        let proof = VRFProof::from_encoded_string(proof).unwrap();
+        let output = VRFOutput::from_encoded_string(output).unwrap();
+
+        assert_eq!(VRFPublicKey::from(&private_key), public_key);
+        assert_eq!(private_key.prove(&alpha), proof);
+        assert_eq!(public_key.verify(&proof, &alpha).unwrap(), output);
+    }
+}
+
+#[test]
+fn test_deserialization_rejects_invalid_material() {
+    // The identity is of small order
+    let mut identity = [0u8; VRF_PUBLIC_KEY_LENGTH];
+    identity[0] = 1;
+    assert_eq!(
+        VRFPublicKey::try_from(&identity[..]),
+        Err(CryptoMaterialError::SmallSubgroupError)
+    );
+
+    // 2^255 - 18 is a non-canonical encoding of y = 1, the identity
+    let mut non_canonical = [0xffu8; VRF_PUBLIC_KEY_LENGTH];
+    non_canonical[0] = 0xee;
+    non_canonical[31] = 0x7f;
+    assert_eq!(
+        VRFPublicKey::try_from(&non_canonical[..]),
+        Err(CryptoMaterialError::PointNotOnCurveError)
+    );
+
+    assert_eq!(
+        VRFPrivateKey::try_from(&[0u8; VRF_PRIVATE_KEY_LENGTH - 1][..]),
+        Err(CryptoMaterialError::WrongLengthError)
+    );
+
+    // A proof whose s is not reduced modulo the group order
+    let (_, _, _, proof, _) = TEST_VECTORS[0];
+    let mut proof = hex::decode(proof).unwrap();
+    proof[VRF_PROOF_LENGTH - 1] |= 0xf0;
+    assert_eq!(
+        VRFProof::try_from(&proof[..]),
+        Err(CryptoMaterialError::CanonicalRepresentationError)
+    );
+}
+
+proptest! {
+    #[test]
+    fn test_keys_encode(keypair in keypair_strategy()) {
+        {
+            let encoded = keypair.private_key.to_encoded_string().unwrap();
+            prop_assert_eq!(2 * VRF_PRIVATE_KEY_LENGTH, encoded.len());
+            let decoded = VRFPrivateKey::from_encoded_string(&encoded);
+            prop_assert_eq!(Some(keypair.private_key), decoded.ok());
+        }
+        {
+            let encoded = keypair.public_key.to_encoded_string().unwrap();
+            prop_assert_eq!(2 * VRF_PUBLIC_KEY_LENGTH, encoded.len());
+            let decoded = VRFPublicKey::from_encoded_string(&encoded);
+            prop_assert_eq!(Some(keypair.public_key), decoded.ok());
+        }
+    }
+
+    #[test]
+    fn test_prove_and_verify(
+        keypair in keypair_strategy(),
+        alpha in vec(proptest::num::u8::ANY, 0..128),
+    ) {
+        let proof = keypair.private_key.prove(&alpha);
+        let output = keypair.public_key.verify(&proof, &alpha).unwrap();
+        prop_assert_eq!(output, VRFOutput::from(&proof));
+
+        // Proofs survive serialization
+        let serialized = bcs::to_bytes(&proof).unwrap();
+        let deserialized: VRFProof = bcs::from_bytes(&serialized).unwrap();
+        prop_assert_eq!(&deserialized, &proof);
+        let serialized = serde_json::to_string(&proof).unwrap();
+        let deserialized: VRFProof = serde_json::from_str(&serialized).unwrap();
+        prop_assert_eq!(&deserialized, &proof);
+    }
+
+    #[test]
+    fn test_verify_rejects_invalid_proofs(
+        keypair in keypair_strategy(),
+        other_keypair in keypair_strategy(),
+        alpha in vec(proptest::num::u8::ANY, 1..128),
+        index in 0..VRF_PROOF_LENGTH,
+    ) {
+        prop_assume!(keypair.public_key != other_keypair.public_key);
+        let proof = keypair.private_key.prove(&alpha);
+
+        let mut other_alpha = alpha.clone();
+        other_alpha[0] ^= 1;
+        prop_assert!(keypair.public_key.verify(&proof, &other_alpha).is_err());
+        prop_assert!(other_keypair.public_key.verify(&proof, &alpha).is_err());
+
+        // Flipping any bit either makes the proof invalid or fails its deserialization
+        let mut tampered = proof.to_bytes();
+        tampered[index] ^= 1;
+        if let Ok(tampered) = VRFProof::try_from(&tampered[..]) {
+            prop_assert!(keypair.public_key.verify(&tampered, &alpha).is_err());
+        }
+    }
+}
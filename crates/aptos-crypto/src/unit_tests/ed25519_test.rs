@@ -318,6 +318,35 @@ proptest! {
         prop_assert!(Ed25519Signature::batch_verify(&message, signatures).is_err());
     }
 
+    #[test]
+    fn test_batch_verify_messages(
+        messages in proptest::array::uniform10(random_serializable_struct()),
+        keypairs in proptest::array::uniform10(uniform_keypair_strategy::<Ed25519PrivateKey, Ed25519PublicKey>())
+    ) {
+        let messages: Vec<_> = messages.iter().collect();
+        let public_keys: Vec<_> = keypairs.iter().map(|keypair| &keypair.public_key).collect();
+        let mut signatures: Vec<_> = keypairs
+            .iter()
+            .zip(&messages)
+            .map(|(keypair, message)| keypair.private_key.sign(*message))
+            .collect();
+        let signature_refs: Vec<_> = signatures.iter().collect();
+        prop_assert!(
+            Ed25519Signature::batch_verify_messages(&messages, &public_keys, &signature_refs).is_ok()
+        );
+        // Mismatched lengths are rejected
+        prop_assert!(
+            Ed25519Signature::batch_verify_messages(&messages[1..], &public_keys, &signature_refs).is_err()
+        );
+        // The signature of the first message doesn't verify the last one
+        let last = signatures.len() - 1;
+        signatures[last] = signatures[0].clone();
+        let signature_refs: Vec<_> = signatures.iter().collect();
+        prop_assert!(
+            Ed25519Signature::batch_verify_messages(&messages, &public_keys, &signature_refs).is_err()
+        );
+    }
+
     #[test]
     fn test_keys_custom_serialisation(
         keypair in uniform_keypair_strategy::<Ed25519PrivateKey, Ed25519PublicKey>()
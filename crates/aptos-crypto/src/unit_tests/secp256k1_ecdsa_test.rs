@@ -0,0 +1,153 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    secp256k1_ecdsa::{
+        Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey, Secp256k1EcdsaSignature,
+        SECP256K1_ECDSA_PRIVATE_KEY_LENGTH, SECP256K1_ECDSA_PUBLIC_KEY_LENGTH,
+        SECP256K1_ECDSA_SIGNATURE_LENGTH,
+    },
+    test_utils::{random_serializable_struct, uniform_keypair_strategy},
+    traits::*,
+};
+
+use core::convert::TryFrom;
+use proptest::{collection::vec, prelude::*};
+
+proptest! {
+    #[test]
+    fn test_keys_encode(keypair in uniform_keypair_strategy::<Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey>()) {
+        {
+            let encoded = keypair.private_key.to_encoded_string().unwrap();
+            prop_assert_eq!(2 * SECP256K1_ECDSA_PRIVATE_KEY_LENGTH, encoded.len());
+            let decoded = Secp256k1EcdsaPrivateKey::from_encoded_string(&encoded);
+            prop_assert_eq!(Some(keypair.private_key), decoded.ok());
+        }
+        {
+            let encoded = keypair.public_key.to_encoded_string().unwrap();
+            prop_assert_eq!(2 * SECP256K1_ECDSA_PUBLIC_KEY_LENGTH, encoded.len());
+            let decoded = Secp256k1EcdsaPublicKey::from_encoded_string(&encoded);
+            prop_assert_eq!(Some(keypair.public_key), decoded.ok());
+        }
+    }
+
+    #[test]
+    fn test_keys_custom_serialisation(
+        keypair in uniform_keypair_strategy::<Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey>()
+    ) {
+        {
+            let serialized: &[u8] = &(keypair.private_key.to_bytes());
+            prop_assert_eq!(SECP256K1_ECDSA_PRIVATE_KEY_LENGTH, serialized.len());
+            let deserialized = Secp256k1EcdsaPrivateKey::try_from(serialized);
+            prop_assert_eq!(Some(keypair.private_key), deserialized.ok());
+        }
+        {
+            let serialized: &[u8] = &(keypair.public_key.to_bytes());
+            prop_assert_eq!(SECP256K1_ECDSA_PUBLIC_KEY_LENGTH, serialized.len());
+            let deserialized = Secp256k1EcdsaPublicKey::try_from(serialized);
+            prop_assert_eq!(Some(keypair.public_key), deserialized.ok());
+        }
+    }
+
+    #[test]
+    fn test_signature_verification_custom_serialisation(
+        message in random_serializable_struct(),
+        keypair in uniform_keypair_strategy::<Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey>()
+    ) {
+        let signature = keypair.private_key.sign(&message);
+        let serialized: &[u8] = &(signature.to_bytes());
+        prop_assert_eq!(SECP256K1_ECDSA_SIGNATURE_LENGTH, serialized.len());
+        let deserialized = Secp256k1EcdsaSignature::try_from(serialized).unwrap();
+        prop_assert!(deserialized.verify(&message, &keypair.public_key).is_ok());
+    }
+
+    #[test]
+    fn test_signature_verification_from_arbitrary(
+        msg in vec(proptest::num::u8::ANY, 1..128),
+        keypair in uniform_keypair_strategy::<Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey>(),
+        other_keypair in uniform_keypair_strategy::<Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey>()
+    ) {
+        let signature = keypair.private_key.sign_arbitrary_message(&msg);
+        prop_assert!(signature.verify_arbitrary_msg(&msg, &keypair.public_key).is_ok());
+        prop_assume!(keypair.public_key != other_keypair.public_key);
+        prop_assert!(signature.verify_arbitrary_msg(&msg, &other_keypair.public_key).is_err());
+    }
+
+    // Check that signatures with a high s are rejected
+    #[test]
+    fn test_signature_malleability(
+        message in random_serializable_struct(),
+        keypair in uniform_keypair_strategy::<Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey>()
+    ) {
+        let signature = keypair.private_key.sign(&message);
+        prop_assert!(signature.check_malleability().is_ok());
+
+        let mut malleable = libsecp256k1::Signature::parse_standard_slice(&signature.to_bytes()).unwrap();
+        malleable.s = -malleable.s;
+        let malleable = Secp256k1EcdsaSignature::try_from(&malleable.serialize()[..]).unwrap();
+        prop_assert_eq!(
+            malleable.check_malleability(),
+            Err(CryptoMaterialError::CanonicalRepresentationError)
+        );
+        prop_assert!(malleable.verify(&message, &keypair.public_key).is_err());
+    }
+}
+
+// Check that the signed digest is the one signed by Ethereum wallets
+#[test]
+fn test_ethereum_signed_message_digest() {
+    let private_key = Secp256k1EcdsaPrivateKey::generate_for_testing();
+    let public_key = Secp256k1EcdsaPublicKey::from(&private_key);
+
+    // keccak256("\x19Ethereum Signed Message:\n11Hello World"), as computed by `hashMessage` in
+    // ethers.js
+    let digest =
+        hex::decode("a1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2").unwrap();
+    let secret_key = libsecp256k1::SecretKey::parse_slice(&private_key.to_bytes()).unwrap();
+    let (signature, _recovery_id) = libsecp256k1::sign(
+        &libsecp256k1::Message::parse_slice(&digest).unwrap(),
+        &secret_key,
+    );
+    let signature = Secp256k1EcdsaSignature::try_from(&signature.serialize()[..]).unwrap();
+
+    assert!(signature
+        .verify_arbitrary_msg(b"Hello World", &public_key)
+        .is_ok());
+    assert_eq!(
+        private_key.sign_arbitrary_message(b"Hello World"),
+        signature
+    );
+}
+
+#[test]
+fn test_deserialization_rejects_invalid_material() {
+    // The zero scalar is not a valid private key
+    assert!(
+        Secp256k1EcdsaPrivateKey::try_from(&[0u8; SECP256K1_ECDSA_PRIVATE_KEY_LENGTH][..]).is_err()
+    );
+
+    // Points that aren't on the curve are rejected
+    let mut not_on_curve = [0u8; SECP256K1_ECDSA_PUBLIC_KEY_LENGTH];
+    not_on_curve[0] = 0x04;
+    assert_eq!(
+        Secp256k1EcdsaPublicKey::try_from(&not_on_curve[..]),
+        Err(CryptoMaterialError::PointNotOnCurveError)
+    );
+
+    // Compressed public keys are rejected
+    let public_key =
+        Secp256k1EcdsaPublicKey::from(&Secp256k1EcdsaPrivateKey::generate_for_testing());
+    let compressed = libsecp256k1::PublicKey::parse_slice(&public_key.to_bytes(), None)
+        .unwrap()
+        .serialize_compressed();
+    assert_eq!(
+        Secp256k1EcdsaPublicKey::try_from(&compressed[..]),
+        Err(CryptoMaterialError::WrongLengthError)
+    );
+
+    // Signatures with r or s overflowing the curve order are rejected
+    assert_eq!(
+        Secp256k1EcdsaSignature::try_from(&[0xff; SECP256K1_ECDSA_SIGNATURE_LENGTH][..]),
+        Err(CryptoMaterialError::DeserializationError)
+    );
+}
@@ -0,0 +1,94 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    bls12381::{BLS12381PrivateKey, BLS12381PublicKey},
+    test_utils::{random_serializable_struct, uniform_keypair_strategy, TestAptosCrypto},
+    threshold_bls12381::{BLS12381PartialSignature, BLS12381PrivateKeyShare},
+    traits::*,
+};
+
+use proptest::{prelude::*, sample::subsequence};
+use rand::{rngs::StdRng, SeedableRng};
+
+proptest! {
+    #[test]
+    fn test_threshold_signature(
+        message in random_serializable_struct(),
+        keypair in uniform_keypair_strategy::<BLS12381PrivateKey, BLS12381PublicKey>(),
+        signers in subsequence((0..5).collect::<Vec<usize>>(), 3..=5),
+        seed in any::<[u8; 32]>(),
+    ) {
+        let shares =
+            BLS12381PrivateKeyShare::split(&keypair.private_key, 3, 5, &mut StdRng::from_seed(seed))
+                .unwrap();
+        let partial_signatures: Vec<_> = signers
+            .iter()
+            .map(|signer| shares[*signer].sign(&message))
+            .collect();
+        for (signer, partial_signature) in signers.iter().zip(partial_signatures.iter()) {
+            prop_assert!(partial_signature.verify(&message, &shares[*signer].public_key()).is_ok());
+        }
+
+        // Any threshold of partial signatures combine into the signature of the private key
+        let signature = BLS12381PartialSignature::combine(&partial_signatures).unwrap();
+        prop_assert_eq!(&signature, &keypair.private_key.sign(&message));
+        prop_assert!(signature.verify(&message, &keypair.public_key).is_ok());
+
+        // Fewer partial signatures than the threshold don't
+        let signature = BLS12381PartialSignature::combine(&partial_signatures[..2]).unwrap();
+        prop_assert!(signature.verify(&message, &keypair.public_key).is_err());
+    }
+}
+
+#[test]
+fn test_invalid_partial_signatures() {
+    let mut rng = StdRng::from_seed([0u8; 32]);
+    let private_key = BLS12381PrivateKey::generate(&mut rng);
+    let public_key: BLS12381PublicKey = (&private_key).into();
+    let shares = BLS12381PrivateKeyShare::split(&private_key, 2, 3, &mut rng).unwrap();
+    assert_eq!(
+        shares.iter().map(|share| share.index()).collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+
+    let message = TestAptosCrypto("Hello, World".to_string());
+    let other_message = TestAptosCrypto("Goodbye, World".to_string());
+
+    // Partial signatures on distinct messages don't combine into a valid signature
+    let partial_signatures = vec![shares[0].sign(&message), shares[1].sign(&other_message)];
+    let signature = BLS12381PartialSignature::combine(&partial_signatures).unwrap();
+    assert!(signature.verify(&message, &public_key).is_err());
+
+    // Partial signatures must be by distinct shares
+    let partial_signatures = vec![shares[0].sign(&message), shares[0].sign(&message)];
+    assert!(BLS12381PartialSignature::combine(&partial_signatures).is_err());
+    assert!(BLS12381PartialSignature::combine(&[]).is_err());
+
+    // Partial signatures only verify against the public key of their share
+    let partial_signature = shares[0].sign(&message);
+    assert!(partial_signature
+        .verify(&message, &shares[1].public_key())
+        .is_err());
+
+    // The threshold must be reachable
+    assert!(BLS12381PrivateKeyShare::split(&private_key, 0, 3, &mut rng).is_err());
+    assert!(BLS12381PrivateKeyShare::split(&private_key, 4, 3, &mut rng).is_err());
+}
+
+#[test]
+fn test_key_shares_serialization() {
+    let mut rng = StdRng::from_seed([0u8; 32]);
+    let private_key = BLS12381PrivateKey::generate(&mut rng);
+    let share = BLS12381PrivateKeyShare::split(&private_key, 1, 1, &mut rng)
+        .unwrap()
+        .remove(0);
+
+    // With a threshold of 1, the share is the private key itself
+    assert_eq!(share.public_key(), BLS12381PublicKey::from(&private_key));
+
+    let serialized = bcs::to_bytes(&share).unwrap();
+    let deserialized: BLS12381PrivateKeyShare = bcs::from_bytes(&serialized).unwrap();
+    assert_eq!(deserialized.index(), share.index());
+    assert_eq!(deserialized.public_key(), share.public_key());
+}
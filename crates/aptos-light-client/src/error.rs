@@ -0,0 +1,73 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use alloc::string::{String, ToString};
+use aptos_types::transaction::Version;
+use core::fmt;
+
+/// The reasons a proof is rejected. On error, the trusted state of the light client is left
+/// unchanged.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The light client only trusts its waypoint, an epoch change proof from the waypoint is
+    /// needed before verifying anything else.
+    EpochChangeRequired,
+    /// The proof is behind the trusted version of the light client.
+    StaleProof {
+        trusted_version: Version,
+        proof_version: Version,
+    },
+    InvalidEpochChangeProof(String),
+    InvalidLedgerInfo(String),
+    InvalidTransactionProof(String),
+    InvalidAccountStateProof(String),
+}
+
+impl Error {
+    pub(crate) fn invalid_epoch_change_proof(error: impl fmt::Display) -> Self {
+        Error::InvalidEpochChangeProof(error.to_string())
+    }
+
+    pub(crate) fn invalid_ledger_info(error: impl fmt::Display) -> Self {
+        Error::InvalidLedgerInfo(error.to_string())
+    }
+
+    pub(crate) fn invalid_transaction_proof(error: impl fmt::Display) -> Self {
+        Error::InvalidTransactionProof(error.to_string())
+    }
+
+    pub(crate) fn invalid_account_state_proof(error: impl fmt::Display) -> Self {
+        Error::InvalidAccountStateProof(error.to_string())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::EpochChangeRequired => {
+                write!(f, "An epoch change proof from the waypoint is required")
+            }
+            Error::StaleProof {
+                trusted_version,
+                proof_version,
+            } => write!(
+                f,
+                "The proof version ({}) is behind the trusted version ({})",
+                proof_version, trusted_version
+            ),
+            Error::InvalidEpochChangeProof(error) => {
+                write!(f, "Invalid epoch change proof: {}", error)
+            }
+            Error::InvalidLedgerInfo(error) => write!(f, "Invalid ledger info: {}", error),
+            Error::InvalidTransactionProof(error) => {
+                write!(f, "Invalid transaction proof: {}", error)
+            }
+            Error::InvalidAccountStateProof(error) => {
+                write!(f, "Invalid account state proof: {}", error)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
@@ -0,0 +1,216 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![forbid(unsafe_code)]
+
+//! Verification of the proofs served by the nodes, for light clients which don't replay the
+//! ledger such as bridges and mobile wallets.
+//!
+//! A [`LightClient`] starts from a trusted [`Waypoint`], usually the genesis waypoint, and
+//! ratchets its trusted view of the ledger forward with [`EpochChangeProof`]s and
+//! [`LedgerInfoWithSignatures`] signed by a quorum of the validators of their epoch. Transactions
+//! and account states are then verified against the latest trusted ledger info.
+//!
+//! The crate itself only depends on `core` and `alloc`: without its default `std` feature, which
+//! only adds the `std::error::Error` impl, it's `no_std`. Its `aptos-types` and `aptos-crypto`
+//! dependencies still need `std` though, so a `no_std` target, e.g. a bridge contract, can't build
+//! it until they can be built without `std` too. Until then, only targets with `std` are
+//! supported, e.g. mobile wallets.
+
+extern crate alloc;
+
+mod error;
+#[cfg(test)]
+mod tests;
+
+pub use error::Error;
+
+use alloc::format;
+use aptos_crypto::hash::CryptoHash;
+use aptos_types::{
+    account_address::{AccountAddress, HashAccountAddress},
+    account_state_blob::AccountStateBlob,
+    epoch_change::{EpochChangeProof, Verifier},
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    proof::{AccountStateProof, TransactionInfoWithProof},
+    state_proof::StateProof,
+    transaction::{Transaction, Version},
+    waypoint::Waypoint,
+};
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+/// The trusted view of the ledger of a light client. The view only moves forward: proofs behind
+/// it are rejected and a rejected proof leaves it unchanged.
+#[derive(Clone, Debug)]
+pub struct LightClient {
+    /// The latest trusted epoch change
+    waypoint: Waypoint,
+    /// The validators of the trusted epoch, unknown until the first epoch change proof from the
+    /// initial waypoint
+    epoch_state: Option<EpochState>,
+    latest_ledger_info: Option<LedgerInfoWithSignatures>,
+}
+
+impl LightClient {
+    /// Starts trusting the ledger from an epoch change waypoint
+    pub fn new(waypoint: Waypoint) -> Self {
+        Self {
+            waypoint,
+            epoch_state: None,
+            latest_ledger_info: None,
+        }
+    }
+
+    pub fn waypoint(&self) -> Waypoint {
+        self.waypoint
+    }
+
+    pub fn epoch_state(&self) -> Option<&EpochState> {
+        self.epoch_state.as_ref()
+    }
+
+    pub fn latest_ledger_info(&self) -> Option<&LedgerInfo> {
+        self.latest_ledger_info
+            .as_ref()
+            .map(LedgerInfoWithSignatures::ledger_info)
+    }
+
+    /// The latest trusted version
+    pub fn version(&self) -> Version {
+        self.latest_ledger_info()
+            .map_or_else(|| self.waypoint.version(), LedgerInfo::version)
+    }
+
+    /// Verifies the epoch changes from the trusted epoch, or from the waypoint, and moves into the
+    /// last epoch of the proof.
+    pub fn verify_epoch_change_proof(&mut self, proof: &EpochChangeProof) -> Result<()> {
+        self.ratchet_epoch(proof).map(|_| ())
+    }
+
+    /// Verifies a ledger info of the trusted epoch and moves to its version.
+    pub fn verify_ledger_info(&mut self, ledger_info: &LedgerInfoWithSignatures) -> Result<()> {
+        let epoch_state = self
+            .epoch_state
+            .as_ref()
+            .ok_or(Error::EpochChangeRequired)?;
+        let proof_version = ledger_info.ledger_info().version();
+        if proof_version < self.version() {
+            return Err(Error::StaleProof {
+                trusted_version: self.version(),
+                proof_version,
+            });
+        }
+        Verifier::verify(epoch_state, ledger_info).map_err(Error::invalid_ledger_info)?;
+
+        self.latest_ledger_info = Some(ledger_info.clone());
+        Ok(())
+    }
+
+    /// Verifies the response of a node to a state proof request: the epoch changes needed to
+    /// reach the epoch of its latest ledger info, then the latest ledger info. When the proof
+    /// only covers part of the epoch changes, the light client moves to the last epoch change
+    /// of the proof.
+    ///
+    /// The accumulator consistency proof isn't verified: transactions are verified with their
+    /// accumulator proof to the root hash of the latest ledger info.
+    pub fn verify_state_proof(&mut self, state_proof: &StateProof) -> Result<()> {
+        let latest_li = state_proof.latest_ledger_info_w_sigs();
+        let epoch_changes = state_proof.epoch_changes();
+        let mut next = self.clone();
+
+        if next.epoch_change_required(latest_li.ledger_info().next_block_epoch()) {
+            let epoch_change_li = next.ratchet_epoch(epoch_changes)?;
+            let new_epoch = epoch_change_li.ledger_info().next_block_epoch();
+            let latest_epoch = latest_li.ledger_info().epoch();
+            if epoch_change_li == latest_li || (latest_epoch > new_epoch && epoch_changes.more) {
+                // Already verified, or unreachable until the next epoch changes are verified
+            } else if latest_epoch == new_epoch {
+                next.verify_ledger_info(latest_li)?;
+            } else {
+                return Err(Error::InvalidEpochChangeProof(
+                    "Inconsistent epoch change proof and latest ledger info".into(),
+                ));
+            }
+        } else {
+            next.verify_ledger_info(latest_li)?;
+        }
+
+        *self = next;
+        Ok(())
+    }
+
+    /// Verifies a transaction is committed at the version, with a proof to the latest trusted
+    /// ledger info.
+    pub fn verify_transaction(
+        &self,
+        version: Version,
+        transaction: &Transaction,
+        proof: &TransactionInfoWithProof,
+    ) -> Result<()> {
+        let ledger_info = self.trusted_ledger_info()?;
+        let transaction_hash = CryptoHash::hash(transaction);
+        if transaction_hash != proof.transaction_info().transaction_hash() {
+            return Err(Error::InvalidTransactionProof(format!(
+                "The transaction hash ({:x}) doesn't match the transaction info ({:x})",
+                transaction_hash,
+                proof.transaction_info().transaction_hash(),
+            )));
+        }
+        proof
+            .verify(ledger_info, version)
+            .map_err(Error::invalid_transaction_proof)
+    }
+
+    /// Verifies the state of an account at the version, with a proof to the latest trusted
+    /// ledger info. Without blob, the proof must show the account doesn't exist.
+    pub fn verify_account_state(
+        &self,
+        address: AccountAddress,
+        version: Version,
+        account_state_blob: Option<&AccountStateBlob>,
+        proof: &AccountStateProof,
+    ) -> Result<()> {
+        let ledger_info = self.trusted_ledger_info()?;
+        proof
+            .verify(ledger_info, version, address.hash(), account_state_blob)
+            .map_err(Error::invalid_account_state_proof)
+    }
+
+    fn trusted_ledger_info(&self) -> Result<&LedgerInfo> {
+        self.latest_ledger_info().ok_or(Error::EpochChangeRequired)
+    }
+
+    fn epoch_change_required(&self, epoch: u64) -> bool {
+        match &self.epoch_state {
+            Some(epoch_state) => epoch_state.epoch_change_verification_required(epoch),
+            None => true,
+        }
+    }
+
+    fn ratchet_epoch<'a>(
+        &mut self,
+        proof: &'a EpochChangeProof,
+    ) -> Result<&'a LedgerInfoWithSignatures> {
+        let verifier: &dyn Verifier = match &self.epoch_state {
+            Some(epoch_state) => epoch_state,
+            None => &self.waypoint,
+        };
+        let epoch_change_li = proof
+            .verify(verifier)
+            .map_err(Error::invalid_epoch_change_proof)?;
+        let ledger_info = epoch_change_li.ledger_info();
+        let epoch_state = ledger_info.next_epoch_state().cloned().ok_or_else(|| {
+            Error::InvalidEpochChangeProof("The last ledger info isn't an epoch change".into())
+        })?;
+        let waypoint =
+            Waypoint::new_epoch_boundary(ledger_info).map_err(Error::invalid_epoch_change_proof)?;
+
+        self.waypoint = waypoint;
+        self.epoch_state = Some(epoch_state);
+        self.latest_ledger_info = Some(epoch_change_li.clone());
+        Ok(epoch_change_li)
+    }
+}
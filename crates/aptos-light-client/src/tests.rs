@@ -0,0 +1,243 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Error, LightClient};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_types::{
+    account_address::{AccountAddress, HashAccountAddress},
+    account_state_blob::AccountStateBlob,
+    block_info::BlockInfo,
+    block_metadata::BlockMetadata,
+    epoch_change::EpochChangeProof,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    proof::{
+        AccountStateProof, AccumulatorConsistencyProof, SparseMerkleLeafNode, SparseMerkleProof,
+        TransactionAccumulatorProof, TransactionInfoWithProof,
+    },
+    state_proof::StateProof,
+    transaction::{Transaction, TransactionInfo, Version},
+    validator_signer::ValidatorSigner,
+    validator_verifier::{ValidatorConsensusInfo, ValidatorVerifier},
+    vm_status::KeptVMStatus,
+    waypoint::Waypoint,
+};
+
+fn validators(seed: u8) -> Vec<ValidatorSigner> {
+    (0..4)
+        .map(|i| ValidatorSigner::random([seed * 4 + i; 32]))
+        .collect()
+}
+
+fn epoch_state(epoch: u64, signers: &[ValidatorSigner]) -> EpochState {
+    EpochState {
+        epoch,
+        verifier: ValidatorVerifier::new(
+            signers
+                .iter()
+                .map(|signer| {
+                    (
+                        signer.author(),
+                        ValidatorConsensusInfo::new(signer.public_key(), 1 /* voting power */),
+                    )
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn ledger_info(
+    signers: &[ValidatorSigner],
+    epoch: u64,
+    version: Version,
+    root_hash: HashValue,
+    next_epoch_state: Option<EpochState>,
+) -> LedgerInfoWithSignatures {
+    let ledger_info = LedgerInfo::new(
+        BlockInfo::new(
+            epoch,
+            0,                 /* round */
+            HashValue::zero(), /* id */
+            root_hash,         /* executed_state_id */
+            version,
+            0, /* timestamp_usecs */
+            next_epoch_state,
+        ),
+        HashValue::zero(),
+    );
+    let signatures = signers
+        .iter()
+        .map(|signer| (signer.author(), signer.sign(&ledger_info)))
+        .collect();
+    LedgerInfoWithSignatures::new(ledger_info, signatures)
+}
+
+/// The genesis ledger info, a single transaction ledger whose root hash is the hash of the
+/// transaction info, and its waypoint
+fn genesis(
+    transaction_info: &TransactionInfo,
+    validators: &[ValidatorSigner],
+) -> (LedgerInfoWithSignatures, Waypoint) {
+    let genesis_li = ledger_info(
+        &[],
+        0,
+        0,
+        transaction_info.hash(),
+        Some(epoch_state(1, validators)),
+    );
+    let waypoint = Waypoint::new_epoch_boundary(genesis_li.ledger_info()).unwrap();
+    (genesis_li, waypoint)
+}
+
+fn transaction_info(transaction: &Transaction, state_change_hash: HashValue) -> TransactionInfo {
+    TransactionInfo::new(
+        transaction.hash(),
+        state_change_hash,
+        HashValue::zero(),
+        0,
+        KeptVMStatus::Executed,
+    )
+}
+
+#[test]
+fn test_ratchet_epoch_and_version() {
+    let validators = validators(0);
+    let (genesis_li, waypoint) = genesis(
+        &transaction_info(&Transaction::StateCheckpoint, HashValue::zero()),
+        &validators,
+    );
+    let mut client = LightClient::new(waypoint);
+    let li = ledger_info(&validators, 1, 10, HashValue::random(), None);
+    assert_eq!(
+        client.verify_ledger_info(&li).unwrap_err(),
+        Error::EpochChangeRequired
+    );
+
+    client
+        .verify_epoch_change_proof(&EpochChangeProof::new(vec![genesis_li], false))
+        .unwrap();
+    assert_eq!(client.epoch_state().unwrap().epoch, 1);
+    client.verify_ledger_info(&li).unwrap();
+    assert_eq!(client.version(), 10);
+
+    let stale_li = ledger_info(&validators, 1, 5, HashValue::random(), None);
+    assert!(matches!(
+        client.verify_ledger_info(&stale_li).unwrap_err(),
+        Error::StaleProof {
+            trusted_version: 10,
+            proof_version: 5,
+        }
+    ));
+    let forged_li = ledger_info(&self::validators(1), 1, 20, HashValue::random(), None);
+    assert!(matches!(
+        client.verify_ledger_info(&forged_li).unwrap_err(),
+        Error::InvalidLedgerInfo(_)
+    ));
+    assert_eq!(client.version(), 10);
+}
+
+#[test]
+fn test_verify_state_proof() {
+    let (validators_1, validators_2) = (validators(0), validators(1));
+    let (genesis_li, waypoint) = genesis(
+        &transaction_info(&Transaction::StateCheckpoint, HashValue::zero()),
+        &validators_1,
+    );
+    let epoch_1_li = ledger_info(
+        &validators_1,
+        1,
+        20,
+        HashValue::random(),
+        Some(epoch_state(2, &validators_2)),
+    );
+    let latest_li = ledger_info(&validators_2, 2, 30, HashValue::random(), None);
+
+    // A latest ledger info signed by the wrong validators is rejected as a whole
+    let mut client = LightClient::new(waypoint);
+    let forged_proof = StateProof::new(
+        ledger_info(&validators_1, 2, 30, HashValue::random(), None),
+        EpochChangeProof::new(vec![genesis_li.clone(), epoch_1_li.clone()], false),
+        AccumulatorConsistencyProof::new(vec![]),
+    );
+    assert!(matches!(
+        client.verify_state_proof(&forged_proof).unwrap_err(),
+        Error::InvalidLedgerInfo(_)
+    ));
+    assert!(client.epoch_state().is_none());
+    assert_eq!(client.waypoint(), waypoint);
+
+    let state_proof = StateProof::new(
+        latest_li.clone(),
+        EpochChangeProof::new(vec![genesis_li, epoch_1_li], false),
+        AccumulatorConsistencyProof::new(vec![]),
+    );
+    client.verify_state_proof(&state_proof).unwrap();
+    assert_eq!(client.epoch_state().unwrap().epoch, 2);
+    assert_eq!(client.version(), 30);
+    assert_eq!(client.waypoint().version(), 20);
+    assert_eq!(client.latest_ledger_info(), Some(latest_li.ledger_info()));
+}
+
+#[test]
+fn test_verify_transaction_and_account_state() {
+    let validators = validators(0);
+    let address = AccountAddress::random();
+    let blob = AccountStateBlob::from(vec![1, 2, 3]);
+    let leaf = SparseMerkleLeafNode::new(address.hash(), blob.hash());
+    let transaction = Transaction::StateCheckpoint;
+    let transaction_info = transaction_info(&transaction, leaf.hash());
+    let (genesis_li, waypoint) = genesis(&transaction_info, &validators);
+
+    let transaction_proof =
+        TransactionInfoWithProof::new(TransactionAccumulatorProof::new(vec![]), transaction_info);
+    let account_proof = AccountStateProof::new(
+        transaction_proof.clone(),
+        SparseMerkleProof::new(Some(leaf), vec![]),
+    );
+    let mut client = LightClient::new(waypoint);
+    assert_eq!(
+        client
+            .verify_transaction(0, &transaction, &transaction_proof)
+            .unwrap_err(),
+        Error::EpochChangeRequired
+    );
+    client
+        .verify_epoch_change_proof(&EpochChangeProof::new(vec![genesis_li], false))
+        .unwrap();
+
+    client
+        .verify_transaction(0, &transaction, &transaction_proof)
+        .unwrap();
+    assert!(matches!(
+        client
+            .verify_transaction(
+                0,
+                &Transaction::BlockMetadata(BlockMetadata::new(
+                    HashValue::zero(),
+                    1,
+                    0,
+                    vec![],
+                    AccountAddress::random()
+                )),
+                &transaction_proof
+            )
+            .unwrap_err(),
+        Error::InvalidTransactionProof(_)
+    ));
+    assert!(matches!(
+        client
+            .verify_transaction(1, &transaction, &transaction_proof)
+            .unwrap_err(),
+        Error::InvalidTransactionProof(_)
+    ));
+
+    client
+        .verify_account_state(address, 0, Some(&blob), &account_proof)
+        .unwrap();
+    assert!(matches!(
+        client
+            .verify_account_state(address, 0, None, &account_proof)
+            .unwrap_err(),
+        Error::InvalidAccountStateProof(_)
+    ));
+}
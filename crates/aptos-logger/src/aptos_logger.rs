@@ -6,27 +6,34 @@
 
 use crate::{
     counters::{
-        PROCESSED_STRUCT_LOG_COUNT, SENT_STRUCT_LOG_BYTES, SENT_STRUCT_LOG_COUNT,
-        STRUCT_LOG_PARSE_ERROR_COUNT, STRUCT_LOG_QUEUE_ERROR_COUNT, STRUCT_LOG_SEND_ERROR_COUNT,
+        DROPPED_IDENTICAL_STRUCT_LOG_COUNT, PROCESSED_STRUCT_LOG_COUNT, SENT_STRUCT_LOG_BYTES,
+        SENT_STRUCT_LOG_COUNT, STRUCT_LOG_PARSE_ERROR_COUNT, STRUCT_LOG_QUEUE_ERROR_COUNT,
+        STRUCT_LOG_SEND_ERROR_COUNT,
     },
     logger::Logger,
+    sample::IdenticalSampling,
     struct_log::TcpWriter,
     Event, Filter, Key, Level, LevelFilter, Metadata,
 };
-use aptos_infallible::RwLock;
+use aptos_infallible::{Mutex, RwLock};
 use backtrace::Backtrace;
 use chrono::{SecondsFormat, Utc};
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use serde::Serialize;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, VecDeque},
     env, fmt,
-    io::Write,
+    fs::{self, File, OpenOptions},
+    hash::{Hash, Hasher},
+    io::{self, Write},
+    mem,
+    path::{Path, PathBuf},
     sync::{
         mpsc::{self, Receiver, SyncSender},
         Arc,
     },
     thread,
+    time::{Duration, Instant},
 };
 
 const RUST_LOG: &str = "RUST_LOG";
@@ -35,6 +42,33 @@ const RUST_LOG_REMOTE: &str = "RUST_LOG_REMOTE";
 pub const CHANNEL_SIZE: usize = 10000;
 const NUM_SEND_RETRIES: u8 = 1;
 
+/// The most recent logs, as formatted by the logger, kept in memory to be dumped when the process
+/// crashes
+static RECENT_LOGS: OnceCell<RecentLogs> = OnceCell::new();
+
+struct RecentLogs {
+    capacity: usize,
+    logs: Mutex<VecDeque<String>>,
+}
+
+impl RecentLogs {
+    fn push(&self, log: String) {
+        let mut logs = self.logs.lock();
+        if logs.len() == self.capacity {
+            logs.pop_front();
+        }
+        logs.push_back(log);
+    }
+}
+
+/// Returns the most recent logs, oldest first, if the logger was built to keep them
+pub fn recent_logs() -> Vec<String> {
+    RECENT_LOGS
+        .get()
+        .map(|recent_logs| recent_logs.logs.lock().iter().cloned().collect())
+        .unwrap_or_default()
+}
+
 /// A single log entry emitted by a logging macro with associated metadata
 #[derive(Debug, Serialize)]
 pub struct LogEntry {
@@ -158,6 +192,9 @@ pub struct AptosDataBuilder {
     printer: Option<Box<dyn Writer>>,
     is_async: bool,
     custom_format: Option<fn(&LogEntry) -> Result<String, fmt::Error>>,
+    module_levels: Vec<(String, Level)>,
+    identical_sampling: Option<(u64, Duration)>,
+    recent_logs: usize,
 }
 
 impl AptosDataBuilder {
@@ -171,6 +208,9 @@ impl AptosDataBuilder {
             printer: Some(Box::new(StderrWriter)),
             is_async: false,
             custom_format: None,
+            module_levels: Vec::new(),
+            identical_sampling: None,
+            recent_logs: 0,
         }
     }
 
@@ -191,6 +231,26 @@ impl AptosDataBuilder {
         self
     }
 
+    /// Overrides the level of the module, and of its submodules, in the local filter. Ignored when
+    /// the filter is set by `RUST_LOG`.
+    pub fn module_level(&mut self, module: &str, level: Level) -> &mut Self {
+        self.module_levels.push((module.to_string(), level));
+        self
+    }
+
+    /// Only logs `max_per_window` identical logs, from the same place with the same content, per
+    /// window of time. The next log let through carries the number of identical logs dropped.
+    pub fn identical_sampling(&mut self, max_per_window: u64, window: Duration) -> &mut Self {
+        self.identical_sampling = Some((max_per_window, window));
+        self
+    }
+
+    /// Keeps the given number of the most recent logs in memory, readable with `recent_logs`.
+    pub fn recent_logs(&mut self, capacity: usize) -> &mut Self {
+        self.recent_logs = capacity;
+        self
+    }
+
     pub fn remote_level(&mut self, level: Level) -> &mut Self {
         self.remote_level = level;
         self
@@ -232,6 +292,9 @@ impl AptosDataBuilder {
                     filter_builder.with_env(RUST_LOG);
                 } else {
                     filter_builder.filter_level(self.level.into());
+                    for (module, level) in &self.module_levels {
+                        filter_builder.filter_module(module, (*level).into());
+                    }
                 }
 
                 filter_builder.build()
@@ -260,6 +323,18 @@ impl AptosDataBuilder {
             }
         };
 
+        if self.recent_logs > 0 {
+            // Only the first logger built keeps the recent logs
+            let _ = RECENT_LOGS.set(RecentLogs {
+                capacity: self.recent_logs,
+                logs: Mutex::new(VecDeque::with_capacity(self.recent_logs)),
+            });
+        }
+
+        let identical_sampling = self
+            .identical_sampling
+            .map(|(max_per_window, window)| IdenticalSampling::new(max_per_window, window));
+
         let logger = if self.is_async {
             let (sender, receiver) = mpsc::sync_channel(self.channel_size);
             let logger = Arc::new(AptosData {
@@ -267,6 +342,7 @@ impl AptosDataBuilder {
                 printer: None,
                 filter: RwLock::new(filter),
                 formatter: self.custom_format.take().unwrap_or(default_format),
+                identical_sampling,
             });
             let service = LoggerService {
                 receiver,
@@ -283,6 +359,7 @@ impl AptosDataBuilder {
                 printer: self.printer.take(),
                 filter: RwLock::new(filter),
                 formatter: self.custom_format.take().unwrap_or(default_format),
+                identical_sampling,
             })
         };
 
@@ -310,6 +387,7 @@ pub struct AptosData {
     printer: Option<Box<dyn Writer>>,
     filter: RwLock<FilterPair>,
     pub(crate) formatter: fn(&LogEntry) -> Result<String, fmt::Error>,
+    identical_sampling: Option<IdenticalSampling>,
 }
 
 impl AptosData {
@@ -341,10 +419,29 @@ impl AptosData {
         self.filter.write().remote_filter = filter;
     }
 
+    /// Overrides the level of the module in the local filter, or removes its override if the
+    /// level is None.
+    pub fn set_module_level(&self, module: &str, level: Option<LevelFilter>) {
+        self.filter
+            .write()
+            .local_filter
+            .set_module_level(module, level);
+    }
+
     fn send_entry(&self, entry: LogEntry) {
-        if let Some(printer) = &self.printer {
+        let recent_logs = RECENT_LOGS.get();
+        if self.printer.is_some() || recent_logs.is_some() {
+            // Format once and share the result between the printer and the recent logs
             let s = (self.formatter)(&entry).expect("Unable to format");
-            printer.write(s);
+            match (&self.printer, recent_logs) {
+                (Some(printer), Some(recent_logs)) => {
+                    recent_logs.push(s.clone());
+                    printer.write(s);
+                }
+                (Some(printer), None) => printer.write(s),
+                (None, Some(recent_logs)) => recent_logs.push(s),
+                (None, None) => {}
+            }
         }
 
         if let Some(sender) = &self.sender {
@@ -356,13 +453,68 @@ impl AptosData {
     }
 }
 
+/// Identifies identical logs: the same place with the same content. Hashes the data in place,
+/// rather than serializing it.
+struct IdenticalLogKey<'a>(&'a LogEntry);
+
+impl Hash for IdenticalLogKey<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.metadata.location().hash(state);
+        self.0.message.hash(state);
+        for (key, value) in &self.0.data {
+            key.hash(state);
+            hash_json_value(value, state);
+        }
+    }
+}
+
+fn hash_json_value<H: Hasher>(value: &serde_json::Value, state: &mut H) {
+    use serde_json::Value;
+
+    mem::discriminant(value).hash(state);
+    match value {
+        Value::Null => {}
+        Value::Bool(b) => b.hash(state),
+        Value::Number(n) => n.to_string().hash(state),
+        Value::String(s) => s.hash(state),
+        Value::Array(values) => {
+            values.len().hash(state);
+            for value in values {
+                hash_json_value(value, state);
+            }
+        }
+        Value::Object(map) => {
+            map.len().hash(state);
+            for (key, value) in map {
+                key.hash(state);
+                hash_json_value(value, state);
+            }
+        }
+    }
+}
+
 impl Logger for AptosData {
     fn enabled(&self, metadata: &Metadata) -> bool {
         self.filter.read().enabled(metadata)
     }
 
     fn record(&self, event: &Event) {
-        let entry = LogEntry::new(event, ::std::thread::current().name());
+        let mut entry = LogEntry::new(event, ::std::thread::current().name());
+
+        if let Some(sampling) = &self.identical_sampling {
+            match sampling.sample(IdenticalLogKey(&entry)) {
+                None => {
+                    DROPPED_IDENTICAL_STRUCT_LOG_COUNT.inc();
+                    return;
+                }
+                Some(0) => {}
+                Some(dropped) => {
+                    entry
+                        .data
+                        .insert(Key::new("dropped_identical_logs"), dropped.into());
+                }
+            }
+        }
 
         self.send_entry(entry)
     }
@@ -522,6 +674,101 @@ impl Writer for FileWriter {
     }
 }
 
+/// A struct for writing logs to a file, which is rotated when it grows too large or too old. The
+/// rotated files are named after the file, suffixed by `.1` for the most recent one, `.2`, etc.
+pub struct RotatingFileWriter {
+    path: PathBuf,
+    max_size_bytes: u64,
+    max_age: Option<Duration>,
+    max_rotated_files: usize,
+    state: Mutex<RotatingFileState>,
+}
+
+struct RotatingFileState {
+    file: File,
+    size: u64,
+    /// When the file was opened, the age of a file written by a previous process starts then.
+    opened_at: Instant,
+}
+
+impl RotatingFileWriter {
+    pub fn new(
+        path: PathBuf,
+        max_size_bytes: u64,
+        max_age: Option<Duration>,
+        max_rotated_files: usize,
+    ) -> Self {
+        let state = Self::open(&path).expect("Unable to open log file");
+        Self {
+            path,
+            max_size_bytes,
+            max_age,
+            max_rotated_files,
+            state: Mutex::new(state),
+        }
+    }
+
+    fn open(path: &Path) -> io::Result<RotatingFileState> {
+        let file = OpenOptions::new().append(true).create(true).open(path)?;
+        Ok(RotatingFileState {
+            size: file.metadata()?.len(),
+            file,
+            opened_at: Instant::now(),
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+
+    fn rotate(&self, state: &mut RotatingFileState) -> io::Result<()> {
+        // Shift the rotated files, the oldest one being overwritten
+        for index in (1..self.max_rotated_files).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(from, self.rotated_path(index + 1))?;
+            }
+        }
+        if self.max_rotated_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+        *state = Self::open(&self.path)?;
+        Ok(())
+    }
+}
+
+impl Writer for RotatingFileWriter {
+    /// Write to file, after rotating it if the log would make it too large or it is too old
+    fn write(&self, log: String) {
+        let mut state = self.state.lock();
+        let len = log.len() as u64 + 1;
+        let too_large = state.size > 0 && state.size + len > self.max_size_bytes;
+        let too_old = self
+            .max_age
+            .map_or(false, |max_age| state.opened_at.elapsed() >= max_age);
+        if too_large || too_old {
+            if let Err(err) = self.rotate(&mut state) {
+                eprintln!("Unable to rotate log file: {}", err);
+            }
+        }
+
+        if let Err(err) = writeln!(state.file, "{}", log) {
+            eprintln!("Unable to write to log file: {}", err);
+        } else {
+            state.size += len;
+        }
+    }
+}
+
+/// Converts a record into a JSON line, as sent to remote loggers
+pub fn json_format(entry: &LogEntry) -> Result<String, fmt::Error> {
+    serde_json::to_string(entry).map_err(|_| fmt::Error)
+}
+
 /// Converts a record into a string representation:
 /// UNIX_TIMESTAMP LOG_LEVEL [thread_name] FILE:LINE MESSAGE JSON_DATA
 /// Example:
@@ -556,7 +803,7 @@ fn default_format(entry: &LogEntry) -> Result<String, fmt::Error> {
 
 #[cfg(test)]
 mod tests {
-    use super::LogEntry;
+    use super::{hash_json_value, LogEntry, RotatingFileWriter, Writer};
     use crate::{
         debug, error, info, logger::Logger, trace, warn, Event, Key, KeyValue, Level, Metadata,
         Schema, Value, Visitor,
@@ -569,6 +816,7 @@ mod tests {
             Arc,
         },
         thread,
+        time::Duration,
     };
 
     #[derive(serde::Serialize)]
@@ -732,6 +980,59 @@ mod tests {
         error!("Literal" = %display_struct, other = "value", identifier = ?debug_struct, "Mixed test");
     }
 
+    #[test]
+    fn rotating_file_writer() {
+        let dir = std::env::temp_dir().join(format!("aptos-logger-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("node.log");
+        let writer = RotatingFileWriter::new(path.clone(), 22, None, 2);
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).ok();
+
+        // Each line is 11 bytes with its newline, so a file holds 2 of them
+        for i in 0..7 {
+            writer.write(format!("log line {}", i));
+        }
+        assert_eq!(read("node.log").as_deref(), Some("log line 6\n"));
+        assert_eq!(
+            read("node.log.1").as_deref(),
+            Some("log line 4\nlog line 5\n")
+        );
+        assert_eq!(
+            read("node.log.2").as_deref(),
+            Some("log line 2\nlog line 3\n")
+        );
+        assert_eq!(read("node.log.3"), None);
+
+        // Files are also rotated once too old
+        let writer = RotatingFileWriter::new(path, 1000, Some(Duration::from_millis(0)), 2);
+        writer.write("too old".into());
+        assert_eq!(read("node.log").as_deref(), Some("too old\n"));
+        assert_eq!(read("node.log.1").as_deref(), Some("log line 6\n"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn json_value_hash() {
+        use std::{collections::hash_map::DefaultHasher, hash::Hasher};
+
+        let hash = |value: JsonValue| {
+            let mut hasher = DefaultHasher::new();
+            hash_json_value(&value, &mut hasher);
+            hasher.finish()
+        };
+        let value = serde_json::json!({"a": [1, "x", null], "b": {"c": true}});
+        assert_eq!(hash(value.clone()), hash(value));
+        assert_ne!(
+            hash(serde_json::json!({"a": [1, "x"]})),
+            hash(serde_json::json!({"a": [1, "y"]}))
+        );
+        assert_ne!(
+            hash(serde_json::json!({"a": "1"})),
+            hash(serde_json::json!({"a": 1}))
+        );
+    }
+
     struct DebugStruct {}
 
     impl std::fmt::Debug for DebugStruct {
@@ -37,6 +37,15 @@ pub static SENT_STRUCT_LOG_BYTES: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count of struct logs dropped because too many identical ones were logged
+pub static DROPPED_IDENTICAL_STRUCT_LOG_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_struct_log_dropped_identical_count",
+        "Count of the struct logs dropped as identical to too many recent ones."
+    )
+    .unwrap()
+});
+
 /// Metric for when we connect the outbound TCP
 pub static STRUCT_LOG_TCP_CONNECT_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
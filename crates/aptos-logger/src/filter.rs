@@ -107,13 +107,7 @@ impl Builder {
             // Add the default filter if none exist
             self.filter_level(LevelFilter::Error);
         } else {
-            // Sort the directives by length of their name, this allows a
-            // little more efficient lookup at runtime.
-            self.directives.sort_by(|a, b| {
-                let alen = a.name.as_ref().map(|a| a.len()).unwrap_or(0);
-                let blen = b.name.as_ref().map(|b| b.len()).unwrap_or(0);
-                alen.cmp(&blen)
-            });
+            sort_directives(&mut self.directives);
         }
 
         Filter {
@@ -122,6 +116,16 @@ impl Builder {
     }
 }
 
+/// Sort the directives by length of their name, this allows a little more efficient lookup at
+/// runtime.
+fn sort_directives(directives: &mut Vec<Directive>) {
+    directives.sort_by(|a, b| {
+        let alen = a.name.as_ref().map(|a| a.len()).unwrap_or(0);
+        let blen = b.name.as_ref().map(|b| b.len()).unwrap_or(0);
+        alen.cmp(&blen)
+    });
+}
+
 /// A logging filter to determine which logs to keep or remove based on `Directive`s
 #[derive(Debug)]
 pub struct Filter {
@@ -143,6 +147,17 @@ impl Filter {
         }
         false
     }
+
+    /// Sets the level of a module, replacing any directive for the exact same module, or removes
+    /// the directive of the module if the level is None.
+    pub fn set_module_level(&mut self, module: &str, level: Option<LevelFilter>) {
+        self.directives
+            .retain(|directive| directive.name.as_deref() != Some(module));
+        if let Some(level) = level {
+            self.directives.push(Directive::new(Some(module), level));
+            sort_directives(&mut self.directives);
+        }
+    }
 }
 
 /// A `Filter` directive for which logs to keep based on a module `name` based filter
@@ -277,6 +292,27 @@ mod tests {
         assert!(logger.enabled(&make_metadata(Level::Info, "crate2::mod2")));
     }
 
+    #[test]
+    fn set_module_level() {
+        let mut logger = Builder::new()
+            .filter(None, LevelFilter::Info)
+            .filter(Some("crate1"), LevelFilter::Warn)
+            .build();
+
+        // Overrides the directive of the module, and adds the ones of new modules
+        logger.set_module_level("crate1", Some(LevelFilter::Debug));
+        logger.set_module_level("crate1::mod1", Some(LevelFilter::Error));
+        assert!(logger.enabled(&make_metadata(Level::Debug, "crate1::mod2")));
+        assert!(!logger.enabled(&make_metadata(Level::Warn, "crate1::mod1")));
+
+        // Removing the directive falls back to the one of the parent module
+        logger.set_module_level("crate1::mod1", None);
+        assert!(logger.enabled(&make_metadata(Level::Debug, "crate1::mod1")));
+        logger.set_module_level("crate1", None);
+        assert!(!logger.enabled(&make_metadata(Level::Debug, "crate1::mod1")));
+        assert!(logger.enabled(&make_metadata(Level::Info, "crate1::mod1")));
+    }
+
     #[test]
     fn parse_valid() {
         let mut builder = Builder::new();
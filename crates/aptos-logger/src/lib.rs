@@ -135,7 +135,7 @@
 
 pub mod prelude {
     pub use crate::{
-        aptos_logger::FileWriter,
+        aptos_logger::{FileWriter, RotatingFileWriter},
         debug, error, info, sample,
         sample::{SampleRate, Sampling},
         security::SecurityEvent,
@@ -157,7 +157,8 @@ mod security;
 mod struct_log;
 
 pub use crate::aptos_logger::{
-    AptosData, AptosData as Logger, AptosDataBuilder, Writer, CHANNEL_SIZE,
+    json_format, recent_logs, AptosData, AptosData as Logger, AptosDataBuilder, RotatingFileWriter,
+    Writer, CHANNEL_SIZE,
 };
 pub use event::Event;
 pub use filter::{Filter, LevelFilter};
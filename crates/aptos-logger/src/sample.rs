@@ -3,9 +3,13 @@
 
 //! Periodic sampling for logs, metrics, and other use cases through a simple macro
 
+use aptos_infallible::Mutex;
 use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    mem,
     sync::atomic::{AtomicU64, Ordering},
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 
 /// The rate at which a `sample!` macro will run it's given function
@@ -79,6 +83,71 @@ impl Sampling {
     }
 }
 
+/// Rate limits identical events, e.g. logs emitted from the same place with the same content, by
+/// only letting through a number of them per window of time. The events are tracked in shards,
+/// each with its own lock, so that distinct events rarely contend.
+pub struct IdenticalSampling {
+    max_per_window: u64,
+    window: Duration,
+    shards: Vec<Mutex<HashMap<u64, WindowState>>>,
+}
+
+struct WindowState {
+    start: Instant,
+    sampled: u64,
+    dropped: u64,
+}
+
+impl IdenticalSampling {
+    /// Number of distinct events tracked per shard, beyond which the ones whose window ended are
+    /// forgotten.
+    const MAX_TRACKED_EVENTS_PER_SHARD: usize = 1_000;
+    const NUM_SHARDS: usize = 16;
+
+    pub fn new(max_per_window: u64, window: Duration) -> Self {
+        Self {
+            max_per_window,
+            window,
+            shards: (0..Self::NUM_SHARDS)
+                .map(|_| Mutex::new(HashMap::new()))
+                .collect(),
+        }
+    }
+
+    /// Returns None if the event identified by `key` should be dropped, otherwise the number of
+    /// identical events dropped since the last one which was sampled.
+    pub fn sample<K: Hash>(&self, key: K) -> Option<u64> {
+        let key = {
+            let mut hasher = DefaultHasher::new();
+            key.hash(&mut hasher);
+            hasher.finish()
+        };
+        let now = Instant::now();
+        let mut events = self.shards[key as usize % Self::NUM_SHARDS].lock();
+        if events.len() >= Self::MAX_TRACKED_EVENTS_PER_SHARD && !events.contains_key(&key) {
+            let window = self.window;
+            events.retain(|_, state| now.duration_since(state.start) < window);
+        }
+
+        let state = events.entry(key).or_insert(WindowState {
+            start: now,
+            sampled: 0,
+            dropped: 0,
+        });
+        if now.duration_since(state.start) >= self.window {
+            state.start = now;
+            state.sampled = 0;
+        }
+        if state.sampled < self.max_per_window {
+            state.sampled += 1;
+            Some(mem::take(&mut state.dropped))
+        } else {
+            state.dropped += 1;
+            None
+        }
+    }
+}
+
 /// Samples a given function at a `SampleRate`, useful for periodically emitting logs or metrics on
 /// high throughput pieces of code.
 #[macro_export]
@@ -144,6 +213,40 @@ mod tests {
         assert_eq!(v.len(), 2);
     }
 
+    #[test]
+    fn identical() {
+        let sampling = IdenticalSampling::new(2, Duration::from_secs(3600));
+        let mut v = Vec::new();
+        for i in 0..5 {
+            v.push((sampling.sample("same"), sampling.sample(i)));
+        }
+
+        // Every distinct event is sampled, but only 2 of the identical ones
+        assert_eq!(
+            v,
+            vec![
+                (Some(0), Some(0)),
+                (Some(0), Some(0)),
+                (None, Some(0)),
+                (None, Some(0)),
+                (None, Some(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn identical_window() {
+        let sampling = IdenticalSampling::new(1, Duration::from_millis(100));
+        assert_eq!(sampling.sample("same"), Some(0));
+        assert_eq!(sampling.sample("same"), None);
+        assert_eq!(sampling.sample("same"), None);
+
+        // The next window reports the events dropped during the previous one
+        std::thread::sleep(Duration::from_millis(150));
+        assert_eq!(sampling.sample("same"), Some(2));
+        assert_eq!(sampling.sample("same"), None);
+    }
+
     #[test]
     fn macro_expansion() {
         for i in 0..10 {
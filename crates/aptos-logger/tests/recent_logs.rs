@@ -0,0 +1,30 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_logger::{info, json_format, recent_logs, warn, AptosData};
+use serde_json::Value;
+
+#[test]
+fn verify_recent_logs() {
+    assert!(recent_logs().is_empty());
+    AptosData::builder()
+        .is_async(false)
+        .custom_format(json_format)
+        .recent_logs(2)
+        .build();
+
+    info!("first");
+    info!(number = 2, "second");
+    warn!("third");
+
+    // Only the most recent logs are kept, in the logger's format
+    let logs: Vec<Value> = recent_logs()
+        .iter()
+        .map(|log| serde_json::from_str(log).unwrap())
+        .collect();
+    assert_eq!(logs.len(), 2);
+    assert_eq!(logs[0]["message"], "second");
+    assert_eq!(logs[0]["data"]["number"], 2);
+    assert_eq!(logs[1]["message"], "third");
+    assert_eq!(logs[1]["level"], "WARN");
+}
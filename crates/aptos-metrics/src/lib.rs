@@ -51,6 +51,7 @@ mod json_encoder;
 pub mod json_metrics;
 pub mod metric_server;
 mod public_metrics;
+pub mod runtime;
 
 mod op_counters;
 pub use op_counters::{DurationHistogram, OpMetrics};
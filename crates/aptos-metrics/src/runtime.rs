@@ -0,0 +1,141 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Instrumented tokio runtimes. The worker threads of each runtime report how long they poll
+//! tasks between parks, and a heartbeat task measures how late a ready task gets polled, which
+//! grows with the depth of the run queues and flags the runtime as stalled past a threshold.
+
+use crate::{
+    register_histogram_vec, register_int_counter_vec, register_int_gauge_vec, HistogramVec,
+    IntCounterVec, IntGaugeVec,
+};
+use aptos_logger::prelude::*;
+use once_cell::sync::Lazy;
+use std::{
+    cell::Cell,
+    time::{Duration, Instant},
+};
+use tokio::runtime::{Builder, Runtime};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(100);
+
+static RUNTIME_THREADS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_runtime_threads",
+        "Number of live threads of each tokio runtime, including its blocking threads",
+        &["runtime"]
+    )
+    .unwrap()
+});
+
+static RUNTIME_BUSY_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_runtime_busy_seconds",
+        "Time the threads of each tokio runtime spend polling tasks between two parks",
+        &["runtime"]
+    )
+    .unwrap()
+});
+
+static RUNTIME_SCHEDULING_DELAY_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_runtime_scheduling_delay_seconds",
+        "Time a ready task of each tokio runtime waits before it is polled",
+        &["runtime"]
+    )
+    .unwrap()
+});
+
+static RUNTIME_STALLS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_runtime_stalls",
+        "Number of times a ready task of each tokio runtime waited longer than the stall threshold",
+        &["runtime"]
+    )
+    .unwrap()
+});
+
+thread_local! {
+    static UNPARKED_AT: Cell<Option<Instant>> = Cell::new(None);
+}
+
+/// Builds a multi-threaded runtime named `name`, with `worker_threads` workers or one per CPU,
+/// and instruments it.
+pub fn instrumented_runtime(
+    name: &str,
+    worker_threads: Option<usize>,
+    stall_threshold: Duration,
+) -> Runtime {
+    let threads = RUNTIME_THREADS.with_label_values(&[name]);
+    let stopped_threads = threads.clone();
+    let busy_seconds = RUNTIME_BUSY_SECONDS.with_label_values(&[name]);
+
+    let mut builder = Builder::new_multi_thread();
+    if let Some(worker_threads) = worker_threads {
+        builder.worker_threads(worker_threads);
+    }
+    let runtime = builder
+        .thread_name(name)
+        .on_thread_start(move || threads.inc())
+        .on_thread_stop(move || stopped_threads.dec())
+        .on_thread_unpark(|| UNPARKED_AT.with(|unparked_at| unparked_at.set(Some(Instant::now()))))
+        .on_thread_park(move || {
+            if let Some(unparked_at) = UNPARKED_AT.with(Cell::take) {
+                busy_seconds.observe(unparked_at.elapsed().as_secs_f64());
+            }
+        })
+        .enable_all()
+        .build()
+        .unwrap_or_else(|e| panic!("Failed to create {} runtime: {}", name, e));
+    runtime.spawn(heartbeat(name.to_string(), stall_threshold));
+    runtime
+}
+
+/// Sleeps repeatedly, measuring how late it is woken up.
+async fn heartbeat(name: String, stall_threshold: Duration) {
+    let scheduling_delay = RUNTIME_SCHEDULING_DELAY_SECONDS.with_label_values(&[&name]);
+    let stalls = RUNTIME_STALLS.with_label_values(&[&name]);
+    loop {
+        let start = Instant::now();
+        tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+        let delay = start.elapsed().saturating_sub(HEARTBEAT_INTERVAL);
+        scheduling_delay.observe(delay.as_secs_f64());
+        if delay > stall_threshold {
+            stalls.inc();
+            sample!(
+                SampleRate::Duration(Duration::from_secs(10)),
+                warn!(
+                    runtime = name,
+                    delay_ms = delay.as_millis() as u64,
+                    "Runtime stalled: a ready task waited too long to be polled"
+                )
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instrumented_runtime() {
+        let runtime = instrumented_runtime("test-runtime", Some(1), Duration::from_millis(50));
+        runtime.block_on(async {
+            // Block the only worker for longer than the stall threshold
+            tokio::spawn(async { std::thread::sleep(Duration::from_millis(300)) })
+                .await
+                .unwrap();
+            tokio::time::sleep(2 * HEARTBEAT_INTERVAL).await;
+        });
+
+        assert!(RUNTIME_THREADS.with_label_values(&["test-runtime"]).get() >= 1);
+        assert!(
+            RUNTIME_BUSY_SECONDS
+                .with_label_values(&["test-runtime"])
+                .get_sample_count()
+                > 0
+        );
+        assert!(RUNTIME_STALLS.with_label_values(&["test-runtime"]).get() > 0);
+    }
+}
@@ -0,0 +1,395 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Polling of the events of an event stream, e.g. the deposits into an account, resuming where it
+//! stopped after a restart. An [`EventStream`] pages through the events from the REST API, and
+//! saves the sequence number of the next event to deliver in a [`CursorStore`] only once the
+//! events before it were handled. Events are thus delivered at least once: the events delivered
+//! since the cursor was last saved are delivered again after a restart.
+
+use crate::{Client, Event, Response};
+use anyhow::{bail, Result};
+use aptos_types::{account_address::AccountAddress, event::EventKey};
+use move_core_types::{identifier::Identifier, language_storage::StructTag};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs, io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 100;
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The event streams which can be polled.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EventSource {
+    /// The stream with the given key.
+    Key(EventKey),
+    /// The stream of the event handle `field_name` of the resource `struct_tag` of an account.
+    Handle {
+        address: AccountAddress,
+        struct_tag: StructTag,
+        field_name: Identifier,
+    },
+}
+
+impl EventSource {
+    /// Identifies the stream in a cursor store.
+    pub fn id(&self) -> String {
+        match self {
+            Self::Key(key) => format!("events/{}", key),
+            Self::Handle {
+                address,
+                struct_tag,
+                field_name,
+            } => format!("accounts/{}/events/{}/{}", address, struct_tag, field_name),
+        }
+    }
+}
+
+/// Persists the sequence number of the next event to deliver of event streams, by their id.
+pub trait CursorStore: Send + Sync {
+    fn load(&self, stream_id: &str) -> Result<Option<u64>>;
+
+    fn save(&self, stream_id: &str, next_sequence_number: u64) -> Result<()>;
+}
+
+/// Keeps the cursors in memory, for the lifetime of the process only.
+#[derive(Debug, Default)]
+pub struct InMemoryCursorStore {
+    cursors: Mutex<BTreeMap<String, u64>>,
+}
+
+impl CursorStore for InMemoryCursorStore {
+    fn load(&self, stream_id: &str) -> Result<Option<u64>> {
+        Ok(self
+            .cursors
+            .lock()
+            .expect("Lock poisoned.")
+            .get(stream_id)
+            .copied())
+    }
+
+    fn save(&self, stream_id: &str, next_sequence_number: u64) -> Result<()> {
+        self.cursors
+            .lock()
+            .expect("Lock poisoned.")
+            .insert(stream_id.to_string(), next_sequence_number);
+        Ok(())
+    }
+}
+
+/// Keeps the cursors in a JSON file mapping the id of each stream to its cursor. The file is
+/// replaced by renaming a new one over it, so that it's never left partially written.
+#[derive(Debug)]
+pub struct FileCursorStore {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileCursorStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn read(&self) -> Result<BTreeMap<String, u64>> {
+        match fs::read(&self.path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl CursorStore for FileCursorStore {
+    fn load(&self, stream_id: &str) -> Result<Option<u64>> {
+        let _guard = self.lock.lock().expect("Lock poisoned.");
+        Ok(self.read()?.get(stream_id).copied())
+    }
+
+    fn save(&self, stream_id: &str, next_sequence_number: u64) -> Result<()> {
+        let _guard = self.lock.lock().expect("Lock poisoned.");
+        let mut cursors = self.read()?;
+        cursors.insert(stream_id.to_string(), next_sequence_number);
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(&cursors)?)?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Delivers the events of a stream in order, waiting for new ones once all were delivered.
+/// Calling `next` again means the event it returned last was handled, so its cursor is saved
+/// past that event, before fetching the next page of events, or on `commit`.
+pub struct EventStream {
+    client: Client,
+    source: EventSource,
+    store: Arc<dyn CursorStore>,
+    stream_id: String,
+    page_size: u64,
+    poll_interval: Duration,
+    /// The sequence number of the next event to deliver.
+    cursor: u64,
+    /// The cursor last saved in the store.
+    saved_cursor: u64,
+    /// The events fetched but not delivered yet, from `cursor` on.
+    buffer: VecDeque<Event>,
+}
+
+impl EventStream {
+    /// Creates the stream of the events of `source`, resuming from the cursor saved in `store`,
+    /// or starting from the event with sequence number `start` if there's none.
+    pub fn new(
+        client: Client,
+        source: EventSource,
+        store: Arc<dyn CursorStore>,
+        start: u64,
+    ) -> Result<Self> {
+        let stream_id = source.id();
+        let cursor = store.load(&stream_id)?.unwrap_or(start);
+        Ok(Self {
+            client,
+            source,
+            store,
+            stream_id,
+            page_size: DEFAULT_PAGE_SIZE,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            cursor,
+            saved_cursor: cursor,
+            buffer: VecDeque::new(),
+        })
+    }
+
+    /// The maximum number of events fetched at once, at most 1000 as limited by the API.
+    pub fn with_page_size(mut self, page_size: u64) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// The interval between two polls once all the events were delivered.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// The sequence number of the next event to deliver.
+    pub fn cursor(&self) -> u64 {
+        self.cursor
+    }
+
+    /// Waits for the next event of the stream. Errors, e.g. while fetching events, leave the
+    /// stream as it was, and `next` can be called again.
+    pub async fn next(&mut self) -> Result<Event> {
+        loop {
+            if let Some(event) = self.buffer.pop_front() {
+                self.cursor = event.sequence_number.0 + 1;
+                return Ok(event);
+            }
+
+            self.commit()?;
+            let events = self.fetch().await?.into_inner();
+            if events.is_empty() {
+                tokio::time::sleep(self.poll_interval).await;
+                continue;
+            }
+            for event in events {
+                let sequence_number = event.sequence_number.0;
+                let expected = self.cursor + self.buffer.len() as u64;
+                if sequence_number < expected {
+                    continue;
+                }
+                if sequence_number > expected {
+                    bail!(
+                        "Event stream {} skipped from {} to {}, the events may have been pruned",
+                        self.stream_id,
+                        expected,
+                        sequence_number
+                    );
+                }
+                self.buffer.push_back(event);
+            }
+        }
+    }
+
+    /// Saves the cursor past all the events delivered, e.g. once the last one was handled before
+    /// shutting down.
+    pub fn commit(&mut self) -> Result<()> {
+        if self.cursor != self.saved_cursor {
+            self.store.save(&self.stream_id, self.cursor)?;
+            self.saved_cursor = self.cursor;
+        }
+        Ok(())
+    }
+
+    async fn fetch(&self) -> Result<Response<Vec<Event>>> {
+        let start = Some(self.cursor);
+        let limit = Some(self.page_size);
+        match &self.source {
+            EventSource::Key(key) => self.client.get_events_by_key(*key, start, limit).await,
+            EventSource::Handle {
+                address,
+                struct_tag,
+                field_name,
+            } => {
+                self.client
+                    .get_events_by_event_handle(*address, struct_tag, field_name, start, limit)
+                    .await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_api_types::{
+        MoveType, X_APTOS_CHAIN_ID, X_APTOS_EPOCH, X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
+    };
+    use aptos_temppath::TempPath;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use url::Url;
+    use warp::Filter;
+
+    /// A node serving the given pages of events, one per request whatever its start, and then
+    /// no events. The starts of the requests are recorded.
+    struct FakeNode {
+        pages: Arc<Mutex<VecDeque<Vec<u64>>>>,
+        starts: Arc<Mutex<Vec<u64>>>,
+        client: Client,
+    }
+
+    impl FakeNode {
+        fn start(key: EventKey, pages: Vec<Vec<u64>>) -> Self {
+            let pages = Arc::new(Mutex::new(VecDeque::from(pages)));
+            let starts = Arc::new(Mutex::new(vec![]));
+            let (pages_clone, starts_clone) = (pages.clone(), starts.clone());
+            let route = warp::path!("events" / String)
+                .and(warp::query::<HashMap<String, u64>>())
+                .map(move |_key: String, query: HashMap<String, u64>| {
+                    starts_clone.lock().unwrap().push(query["start"]);
+                    let sequence_numbers = pages_clone.lock().unwrap().pop_front();
+                    let events: Vec<Event> = sequence_numbers
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|sequence_number| event(key, sequence_number))
+                        .collect();
+                    let reply = warp::reply::json(&events);
+                    let reply = warp::reply::with_header(reply, X_APTOS_CHAIN_ID, "4");
+                    let reply = warp::reply::with_header(reply, X_APTOS_EPOCH, "1");
+                    let reply = warp::reply::with_header(reply, X_APTOS_LEDGER_VERSION, "10");
+                    warp::reply::with_header(reply, X_APTOS_LEDGER_TIMESTAMP, "100")
+                });
+            let (address, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+            tokio::spawn(server);
+            let url = Url::parse(&format!("http://{}", address)).unwrap();
+            Self {
+                pages,
+                starts,
+                client: Client::new(url),
+            }
+        }
+
+        fn starts(&self) -> Vec<u64> {
+            self.starts.lock().unwrap().clone()
+        }
+    }
+
+    fn event(key: EventKey, sequence_number: u64) -> Event {
+        Event {
+            key: key.into(),
+            sequence_number: sequence_number.into(),
+            typ: MoveType::U64,
+            data: json!(sequence_number.to_string()),
+        }
+    }
+
+    fn random_key() -> EventKey {
+        EventKey::new_from_address(&AccountAddress::random(), 0)
+    }
+
+    async fn next_sequence_number(stream: &mut EventStream) -> u64 {
+        stream.next().await.unwrap().sequence_number.0
+    }
+
+    #[test]
+    fn test_file_cursor_store_round_trip() {
+        let path = TempPath::new();
+        let store = FileCursorStore::new(path.path());
+        assert_eq!(store.load("a").unwrap(), None);
+        store.save("a", 3).unwrap();
+        store.save("b", 5).unwrap();
+        store.save("a", 4).unwrap();
+
+        // The cursors are read back from the file, which is left complete
+        let store = FileCursorStore::new(path.path());
+        assert_eq!(store.load("a").unwrap(), Some(4));
+        assert_eq!(store.load("b").unwrap(), Some(5));
+        assert_eq!(store.load("c").unwrap(), None);
+        assert!(!path.path().with_extension("tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn test_resume_from_saved_cursor() {
+        let key = random_key();
+        let node = FakeNode::start(key, vec![vec![5, 6]]);
+        let source = EventSource::Key(key);
+        let store = Arc::new(InMemoryCursorStore::default());
+        store.save(&source.id(), 5).unwrap();
+
+        // The start is ignored as a cursor was saved
+        let mut stream = EventStream::new(node.client.clone(), source.clone(), store.clone(), 0)
+            .unwrap()
+            .with_page_size(2);
+        assert_eq!(stream.cursor(), 5);
+        assert_eq!(next_sequence_number(&mut stream).await, 5);
+        assert_eq!(next_sequence_number(&mut stream).await, 6);
+        assert_eq!(node.starts(), vec![5]);
+
+        // The cursor is only saved past the events handled
+        assert_eq!(store.load(&source.id()).unwrap(), Some(5));
+        stream.commit().unwrap();
+        assert_eq!(store.load(&source.id()).unwrap(), Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_skip_duplicate_events() {
+        let key = random_key();
+        let node = FakeNode::start(key, vec![vec![0, 1], vec![1, 2]]);
+        let store = Arc::new(InMemoryCursorStore::default());
+        let mut stream = EventStream::new(node.client.clone(), EventSource::Key(key), store, 0)
+            .unwrap()
+            .with_page_size(2);
+
+        assert_eq!(next_sequence_number(&mut stream).await, 0);
+        assert_eq!(next_sequence_number(&mut stream).await, 1);
+        // The event 1 served again is skipped
+        assert_eq!(next_sequence_number(&mut stream).await, 2);
+        assert_eq!(node.starts(), vec![0, 2]);
+        assert!(node.pages.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pruned_events() {
+        let key = random_key();
+        let node = FakeNode::start(key, vec![vec![8, 9], vec![5, 6]]);
+        let store = Arc::new(InMemoryCursorStore::default());
+        let mut stream = EventStream::new(node.client.clone(), EventSource::Key(key), store, 5)
+            .unwrap()
+            .with_page_size(2);
+
+        // The events from 5 to 7 are missing
+        let error = stream.next().await.unwrap_err();
+        assert!(error.to_string().contains("pruned"), "{}", error);
+        assert_eq!(stream.cursor(), 5);
+
+        // The stream is left as it was, so the next call fetches from the cursor again
+        assert_eq!(next_sequence_number(&mut stream).await, 5);
+        assert_eq!(node.starts(), vec![5, 5]);
+    }
+}
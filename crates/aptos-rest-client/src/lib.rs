@@ -2,9 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{anyhow, Result};
-pub use aptos_api_types::{MoveModuleBytecode, PendingTransaction, Transaction};
+pub use aptos_api_types::{Event, MoveModuleBytecode, PendingTransaction, Transaction};
 use aptos_crypto::HashValue;
-use aptos_types::{account_address::AccountAddress, transaction::SignedTransaction};
+use aptos_types::{
+    account_address::AccountAddress, event::EventKey, transaction::SignedTransaction,
+};
 use move_core_types::{
     identifier::Identifier,
     language_storage::{StructTag, TypeTag},
@@ -17,6 +19,8 @@ use url::Url;
 
 pub use aptos_api_types;
 pub mod error;
+pub mod event_stream;
+pub use event_stream::EventStream;
 pub mod faucet;
 pub use faucet::FaucetClient;
 pub mod response;
@@ -144,6 +148,42 @@ impl Client {
         self.json(response).await
     }
 
+    /// Submits transactions of one account with consecutive sequence numbers, which are committed
+    /// together, in order, or not at all
+    pub async fn submit_bundle(
+        &self,
+        txns: &[SignedTransaction],
+    ) -> Result<Response<Vec<PendingTransaction>>> {
+        let txns_payload = bcs::to_bytes(txns)?;
+        let url = self.base_url.join("transactions/bundle")?;
+
+        let response = self
+            .inner
+            .post(url)
+            .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
+            .body(txns_payload)
+            .send()
+            .await?;
+
+        self.json(response).await
+    }
+
+    /// Executes the transaction against the latest state of the ledger without submitting it
+    pub async fn simulate(&self, txn: &SignedTransaction) -> Result<Response<Transaction>> {
+        let txn_payload = bcs::to_bytes(txn)?;
+        let url = self.base_url.join("transactions/simulate")?;
+
+        let response = self
+            .inner
+            .post(url)
+            .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
+            .body(txn_payload)
+            .send()
+            .await?;
+
+        self.json(response).await
+    }
+
     pub async fn submit_and_wait(&self, txn: &SignedTransaction) -> Result<Response<Transaction>> {
         self.submit(txn).await?;
         self.wait_for_signed_transaction(txn).await
@@ -283,6 +323,51 @@ impl Client {
         self.json(response).await
     }
 
+    pub async fn get_events_by_key(
+        &self,
+        key: EventKey,
+        start: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Response<Vec<Event>>> {
+        let url = self.base_url.join(&format!("events/{}", key))?;
+        self.get_events(url, start, limit).await
+    }
+
+    pub async fn get_events_by_event_handle(
+        &self,
+        address: AccountAddress,
+        struct_tag: &StructTag,
+        field_name: &Identifier,
+        start: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Response<Vec<Event>>> {
+        let url = self.base_url.join(&format!(
+            "accounts/{}/events/{}/{}",
+            address, struct_tag, field_name
+        ))?;
+        self.get_events(url, start, limit).await
+    }
+
+    async fn get_events(
+        &self,
+        url: Url,
+        start: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Response<Vec<Event>>> {
+        let mut request = self.inner.get(url);
+        if let Some(start) = start {
+            request = request.query(&[("start", start)])
+        }
+
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit)])
+        }
+
+        let response = request.send().await?;
+
+        self.json(response).await
+    }
+
     pub async fn get_account_state_blob(
         &self,
         address: AccountAddress,
@@ -308,6 +393,21 @@ impl Client {
         self.json(response).await
     }
 
+    pub async fn get_account_resources_at_version(
+        &self,
+        address: AccountAddress,
+        version: u64,
+    ) -> Result<Response<Vec<Resource>>> {
+        let url = self.base_url.join(&format!(
+            "accounts/{}/resources?version={}",
+            address, version
+        ))?;
+
+        let response = self.inner.get(url).send().await?;
+
+        self.json(response).await
+    }
+
     pub async fn get_account_resources_by_type(
         &self,
         address: AccountAddress,
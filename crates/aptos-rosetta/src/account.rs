@@ -0,0 +1,80 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    block::{block_identifier_at, block_version},
+    endpoint,
+    error::ApiError,
+    operations::{native_coin, parse_address},
+    types::{AccountBalanceRequest, AccountBalanceResponse, Amount},
+    RosettaContext,
+};
+use aptos_sdk::move_types::{
+    identifier::Identifier,
+    language_storage::{StructTag, CORE_CODE_ADDRESS},
+};
+use std::sync::Arc;
+use warp::{Filter, Rejection, Reply};
+
+pub fn routes(
+    context: Arc<RosettaContext>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    endpoint(warp::path!("account" / "balance"), context, account_balance)
+}
+
+/// The balance at the block of the request, the latest block by default
+async fn account_balance(
+    request: AccountBalanceRequest,
+    context: Arc<RosettaContext>,
+) -> Result<AccountBalanceResponse, ApiError> {
+    context.check_network(&request.network_identifier)?;
+    let client = context.rest_client()?;
+    let address = parse_address(&request.account_identifier.address)?;
+    if let Some(currencies) = &request.currencies {
+        if currencies.iter().any(|currency| currency != &native_coin()) {
+            return Err(ApiError::InvalidRequest(format!(
+                "Only {} is supported",
+                native_coin().symbol
+            )));
+        }
+    }
+
+    let partial_block_identifier = request.block_identifier.unwrap_or_default();
+    let version = block_version(client, &partial_block_identifier).await?;
+    let (block_identifier, _) = block_identifier_at(client, version).await?;
+    if partial_block_identifier
+        .hash
+        .as_ref()
+        .map_or(false, |hash| hash != &block_identifier.hash)
+    {
+        return Err(ApiError::InvalidRequest(
+            "The block index and hash don't match".to_string(),
+        ));
+    }
+
+    let resources = client
+        .get_account_resources_at_version(address, version)
+        .await
+        .map_err(|_| ApiError::AccountNotFound)?
+        .into_inner();
+    let balance_type = StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("TestCoin").unwrap(),
+        name: Identifier::new("Balance").unwrap(),
+        type_params: vec![],
+    };
+    let value = resources
+        .iter()
+        .find(|resource| resource.resource_type == balance_type)
+        .and_then(|resource| resource.data.get("coin")?.get("value")?.as_str())
+        .ok_or(ApiError::AccountNotFound)?
+        .to_string();
+
+    Ok(AccountBalanceResponse {
+        block_identifier,
+        balances: vec![Amount {
+            value,
+            currency: native_coin(),
+        }],
+    })
+}
@@ -0,0 +1,180 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    endpoint,
+    error::ApiError,
+    operations::transaction_operations,
+    types::{
+        Block, BlockIdentifier, BlockRequest, BlockResponse, BlockTransactionRequest,
+        BlockTransactionResponse, MempoolResponse, MempoolTransactionRequest, NetworkRequest,
+        PartialBlockIdentifier, Transaction, TransactionIdentifier,
+    },
+    RosettaContext,
+};
+use aptos_crypto::HashValue;
+use aptos_rest_client::{aptos_api_types, Client};
+use std::sync::Arc;
+use warp::{Filter, Rejection, Reply};
+
+pub fn routes(
+    context: Arc<RosettaContext>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    endpoint(warp::path!("block"), context.clone(), block)
+        .or(endpoint(
+            warp::path!("block" / "transaction"),
+            context.clone(),
+            block_transaction,
+        ))
+        .or(endpoint(warp::path!("mempool"), context.clone(), mempool))
+        .or(endpoint(
+            warp::path!("mempool" / "transaction"),
+            context,
+            mempool_transaction,
+        ))
+}
+
+async fn block(
+    request: BlockRequest,
+    context: Arc<RosettaContext>,
+) -> Result<BlockResponse, ApiError> {
+    context.check_network(&request.network_identifier)?;
+    let client = context.rest_client()?;
+    let version = block_version(client, &request.block_identifier).await?;
+
+    // The parent block is the previous version, the genesis block is its own parent
+    let start = version.saturating_sub(1);
+    let txns = client
+        .get_transactions(Some(start), Some(version - start + 1))
+        .await?
+        .into_inner();
+    let (parent, txn) = match txns.as_slice() {
+        [txn] if version == 0 => (txn, txn),
+        [parent, txn] => (parent, txn),
+        _ => return Err(ApiError::BlockNotFound),
+    };
+
+    let block_identifier = block_identifier(txn)?;
+    if request
+        .block_identifier
+        .hash
+        .as_ref()
+        .map_or(false, |hash| hash != &block_identifier.hash)
+    {
+        return Err(ApiError::InvalidRequest(
+            "The block index and hash don't match".to_string(),
+        ));
+    }
+    Ok(BlockResponse {
+        block: Block {
+            block_identifier,
+            parent_block_identifier: self::block_identifier(parent)?,
+            timestamp: txn.timestamp() / 1000,
+            transactions: vec![transaction(txn)?],
+        },
+    })
+}
+
+async fn block_transaction(
+    request: BlockTransactionRequest,
+    context: Arc<RosettaContext>,
+) -> Result<BlockTransactionResponse, ApiError> {
+    context.check_network(&request.network_identifier)?;
+    let client = context.rest_client()?;
+    let txn = client
+        .get_transaction_by_version(request.block_identifier.index)
+        .await?
+        .into_inner();
+
+    let transaction = transaction(&txn)?;
+    if transaction.transaction_identifier != request.transaction_identifier {
+        return Err(ApiError::TransactionNotFound);
+    }
+    Ok(BlockTransactionResponse { transaction })
+}
+
+/// Transactions are only known once committed
+async fn mempool(
+    request: NetworkRequest,
+    context: Arc<RosettaContext>,
+) -> Result<MempoolResponse, ApiError> {
+    context.check_network(&request.network_identifier)?;
+    Ok(MempoolResponse {
+        transaction_identifiers: vec![],
+    })
+}
+
+async fn mempool_transaction(
+    request: MempoolTransactionRequest,
+    context: Arc<RosettaContext>,
+) -> Result<Transaction, ApiError> {
+    context.check_network(&request.network_identifier)?;
+    Err(ApiError::TransactionNotFound)
+}
+
+/// The version of the block, the latest version if the block isn't identified
+pub(crate) async fn block_version(
+    client: &Client,
+    block_identifier: &PartialBlockIdentifier,
+) -> Result<u64, ApiError> {
+    let ledger_version = client.get_ledger_information().await?.inner().version;
+    let version = match (block_identifier.index, &block_identifier.hash) {
+        (Some(index), _) => index,
+        (None, Some(hash)) => {
+            let hash = parse_hash(hash)?;
+            let txn = client
+                .get_transaction(hash)
+                .await
+                .map_err(|_| ApiError::BlockNotFound)?
+                .into_inner();
+            txn.transaction_info()
+                .map_err(|_| ApiError::BlockNotFound)?
+                .version
+                .0
+        }
+        (None, None) => ledger_version,
+    };
+    if version > ledger_version {
+        return Err(ApiError::BlockNotFound);
+    }
+    Ok(version)
+}
+
+/// The identifier of the block of a committed transaction
+pub(crate) fn block_identifier(
+    txn: &aptos_api_types::Transaction,
+) -> Result<BlockIdentifier, ApiError> {
+    let info = txn
+        .transaction_info()
+        .map_err(|e| ApiError::NodeError(e.to_string()))?;
+    Ok(BlockIdentifier {
+        index: info.version.0,
+        hash: info.hash.to_string(),
+    })
+}
+
+pub(crate) async fn block_identifier_at(
+    client: &Client,
+    version: u64,
+) -> Result<(BlockIdentifier, u64), ApiError> {
+    let txn = client
+        .get_transaction_by_version(version)
+        .await?
+        .into_inner();
+    Ok((block_identifier(&txn)?, txn.timestamp() / 1000))
+}
+
+fn transaction(txn: &aptos_api_types::Transaction) -> Result<Transaction, ApiError> {
+    Ok(Transaction {
+        transaction_identifier: TransactionIdentifier {
+            hash: block_identifier(txn)?.hash,
+        },
+        operations: transaction_operations(txn),
+        metadata: None,
+    })
+}
+
+fn parse_hash(hash: &str) -> Result<HashValue, ApiError> {
+    HashValue::from_hex(hash.trim_start_matches("0x"))
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid hash {}: {}", hash, e)))
+}
@@ -0,0 +1,475 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The Construction API builds, signs and submits transactions. Every endpoint but
+//! `/construction/metadata` and `/construction/submit` works offline.
+
+use crate::{
+    endpoint,
+    error::ApiError,
+    operations::{call_operations, native_coin, operations_call, parse_address},
+    types::{
+        AccountIdentifier, Amount, ConstructionCombineRequest, ConstructionCombineResponse,
+        ConstructionDeriveRequest, ConstructionDeriveResponse, ConstructionHashRequest,
+        ConstructionMetadata, ConstructionMetadataRequest, ConstructionMetadataResponse,
+        ConstructionParseRequest, ConstructionParseResponse, ConstructionPayloadsRequest,
+        ConstructionPayloadsResponse, ConstructionPreprocessRequest,
+        ConstructionPreprocessResponse, ConstructionSubmitRequest, CurveType, MetadataOptions,
+        PreprocessMetadata, PublicKey, SignatureType, SigningPayload, TransactionIdentifier,
+        TransactionIdentifierResponse,
+    },
+    RosettaContext,
+};
+use aptos_crypto::{
+    ed25519::{Ed25519PublicKey, Ed25519Signature},
+    Signature,
+};
+use aptos_sdk::{
+    transaction_builder::{aptos_stdlib::ScriptFunctionCall, TransactionFactory},
+    types::{
+        account_address::AccountAddress,
+        chain_id::ChainId,
+        transaction::{authenticator::AuthenticationKey, RawTransaction, SignedTransaction},
+    },
+};
+use serde::de::DeserializeOwned;
+use std::{
+    convert::TryFrom,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use warp::{Filter, Rejection, Reply};
+
+pub const DEFAULT_MAX_GAS_AMOUNT: u64 = 1_000_000;
+pub const DEFAULT_GAS_UNIT_PRICE: u64 = 1;
+/// How long after their construction transactions expire by default
+pub const DEFAULT_EXPIRATION_SECS: u64 = 30;
+
+pub fn routes(
+    context: Arc<RosettaContext>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    endpoint(
+        warp::path!("construction" / "derive"),
+        context.clone(),
+        construction_derive,
+    )
+    .or(endpoint(
+        warp::path!("construction" / "preprocess"),
+        context.clone(),
+        construction_preprocess,
+    ))
+    .or(endpoint(
+        warp::path!("construction" / "metadata"),
+        context.clone(),
+        construction_metadata,
+    ))
+    .or(endpoint(
+        warp::path!("construction" / "payloads"),
+        context.clone(),
+        construction_payloads,
+    ))
+    .or(endpoint(
+        warp::path!("construction" / "combine"),
+        context.clone(),
+        construction_combine,
+    ))
+    .or(endpoint(
+        warp::path!("construction" / "parse"),
+        context.clone(),
+        construction_parse,
+    ))
+    .or(endpoint(
+        warp::path!("construction" / "hash"),
+        context.clone(),
+        construction_hash,
+    ))
+    .or(endpoint(
+        warp::path!("construction" / "submit"),
+        context,
+        construction_submit,
+    ))
+}
+
+async fn construction_derive(
+    request: ConstructionDeriveRequest,
+    context: Arc<RosettaContext>,
+) -> Result<ConstructionDeriveResponse, ApiError> {
+    context.check_network(&request.network_identifier)?;
+    let public_key = public_key(&request.public_key)?;
+    Ok(ConstructionDeriveResponse {
+        account_identifier: account_identifier(
+            AuthenticationKey::ed25519(&public_key).derived_address(),
+        ),
+    })
+}
+
+async fn construction_preprocess(
+    request: ConstructionPreprocessRequest,
+    context: Arc<RosettaContext>,
+) -> Result<ConstructionPreprocessResponse, ApiError> {
+    context.check_network(&request.network_identifier)?;
+    let (sender, _) = operations_call(&request.operations)?;
+    Ok(ConstructionPreprocessResponse {
+        options: MetadataOptions {
+            sender: account_identifier(sender),
+            transaction: request.metadata.unwrap_or_default(),
+        },
+        required_public_keys: vec![account_identifier(sender)],
+    })
+}
+
+async fn construction_metadata(
+    request: ConstructionMetadataRequest,
+    context: Arc<RosettaContext>,
+) -> Result<ConstructionMetadataResponse, ApiError> {
+    context.check_network(&request.network_identifier)?;
+    let client = context.rest_client()?;
+    let sender = parse_address(&request.options.sender.address)?;
+    let sequence_number = client
+        .get_account(sender)
+        .await
+        .map_err(|_| ApiError::AccountNotFound)?
+        .inner()
+        .sequence_number;
+
+    let PreprocessMetadata {
+        max_gas_amount,
+        gas_unit_price,
+        expiration_timestamp_secs,
+    } = request.options.transaction;
+    let max_gas_amount = max_gas_amount.unwrap_or(DEFAULT_MAX_GAS_AMOUNT);
+    let gas_unit_price = gas_unit_price.unwrap_or(DEFAULT_GAS_UNIT_PRICE);
+    let expiration_timestamp_secs = expiration_timestamp_secs.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + DEFAULT_EXPIRATION_SECS
+    });
+    let suggested_fee = max_gas_amount
+        .checked_mul(gas_unit_price)
+        .ok_or_else(|| ApiError::InvalidRequest("The maximum fee overflows".to_string()))?;
+
+    Ok(ConstructionMetadataResponse {
+        metadata: ConstructionMetadata {
+            sender: request.options.sender,
+            sequence_number,
+            max_gas_amount,
+            gas_unit_price,
+            expiration_timestamp_secs,
+            chain_id: context.chain_id().id(),
+        },
+        suggested_fee: vec![Amount {
+            value: suggested_fee.to_string(),
+            currency: native_coin(),
+        }],
+    })
+}
+
+async fn construction_payloads(
+    request: ConstructionPayloadsRequest,
+    context: Arc<RosettaContext>,
+) -> Result<ConstructionPayloadsResponse, ApiError> {
+    context.check_network(&request.network_identifier)?;
+    let (sender, call) = operations_call(&request.operations)?;
+    let metadata = request.metadata;
+    if parse_address(&metadata.sender.address)? != sender {
+        return Err(ApiError::InvalidOperations(
+            "The sender of the operations isn't the sender of the metadata".to_string(),
+        ));
+    }
+    if metadata.chain_id != context.chain_id().id() {
+        return Err(ApiError::InvalidRequest(format!(
+            "The metadata is for chain {}",
+            metadata.chain_id
+        )));
+    }
+
+    let raw_txn = TransactionFactory::new(ChainId::new(metadata.chain_id))
+        .payload(call.encode())
+        .sender(sender)
+        .sequence_number(metadata.sequence_number)
+        .max_gas_amount(metadata.max_gas_amount)
+        .gas_unit_price(metadata.gas_unit_price)
+        .expiration_timestamp_secs(metadata.expiration_timestamp_secs)
+        .build();
+    Ok(ConstructionPayloadsResponse {
+        unsigned_transaction: encode_bcs(&raw_txn)?,
+        payloads: vec![SigningPayload {
+            account_identifier: account_identifier(sender),
+            hex_bytes: hex::encode(raw_txn.signing_message()),
+            signature_type: SignatureType::Ed25519,
+        }],
+    })
+}
+
+async fn construction_combine(
+    request: ConstructionCombineRequest,
+    context: Arc<RosettaContext>,
+) -> Result<ConstructionCombineResponse, ApiError> {
+    context.check_network(&request.network_identifier)?;
+    let raw_txn: RawTransaction = decode_bcs(&request.unsigned_transaction)?;
+    let signature = match request.signatures.as_slice() {
+        [signature] => signature,
+        _ => {
+            return Err(ApiError::InvalidSignature(
+                "Transactions are signed by exactly one signature".to_string(),
+            ))
+        }
+    };
+    if signature.signature_type != SignatureType::Ed25519 {
+        return Err(ApiError::InvalidSignature(format!(
+            "Unsupported signature type {:?}",
+            signature.signature_type
+        )));
+    }
+
+    let public_key = public_key(&signature.public_key)?;
+    if AuthenticationKey::ed25519(&public_key).derived_address() != raw_txn.sender() {
+        return Err(ApiError::InvalidSignature(
+            "The public key isn't the key of the sender".to_string(),
+        ));
+    }
+    let signature = Ed25519Signature::try_from(decode_hex(&signature.hex_bytes)?.as_slice())
+        .map_err(|e| ApiError::InvalidSignature(e.to_string()))?;
+    signature
+        .verify_arbitrary_msg(&raw_txn.signing_message(), &public_key)
+        .map_err(|e| ApiError::InvalidSignature(e.to_string()))?;
+
+    Ok(ConstructionCombineResponse {
+        signed_transaction: encode_bcs(&SignedTransaction::new(raw_txn, public_key, signature))?,
+    })
+}
+
+async fn construction_parse(
+    request: ConstructionParseRequest,
+    context: Arc<RosettaContext>,
+) -> Result<ConstructionParseResponse, ApiError> {
+    context.check_network(&request.network_identifier)?;
+    let (sender, payload, account_identifier_signers) = if request.signed {
+        let txn: SignedTransaction = decode_bcs(&request.transaction)?;
+        let sender = txn.sender();
+        let payload = txn.into_raw_transaction().into_payload();
+        (sender, payload, vec![account_identifier(sender)])
+    } else {
+        let txn: RawTransaction = decode_bcs(&request.transaction)?;
+        (txn.sender(), txn.into_payload(), vec![])
+    };
+
+    // Only the script functions which map to operations can be constructed
+    let operations = ScriptFunctionCall::decode(&payload)
+        .map(|call| call_operations(sender, &call, None))
+        .filter(|operations| !operations.is_empty())
+        .ok_or_else(|| ApiError::InvalidTransaction("Unsupported payload".to_string()))?;
+    Ok(ConstructionParseResponse {
+        operations,
+        account_identifier_signers,
+    })
+}
+
+async fn construction_hash(
+    request: ConstructionHashRequest,
+    context: Arc<RosettaContext>,
+) -> Result<TransactionIdentifierResponse, ApiError> {
+    context.check_network(&request.network_identifier)?;
+    let txn: SignedTransaction = decode_bcs(&request.signed_transaction)?;
+    Ok(transaction_identifier(txn))
+}
+
+async fn construction_submit(
+    request: ConstructionSubmitRequest,
+    context: Arc<RosettaContext>,
+) -> Result<TransactionIdentifierResponse, ApiError> {
+    context.check_network(&request.network_identifier)?;
+    let client = context.rest_client()?;
+    let txn: SignedTransaction = decode_bcs(&request.signed_transaction)?;
+    client
+        .submit(&txn)
+        .await
+        .map_err(|e| ApiError::InvalidTransaction(e.to_string()))?;
+    Ok(transaction_identifier(txn))
+}
+
+fn transaction_identifier(txn: SignedTransaction) -> TransactionIdentifierResponse {
+    TransactionIdentifierResponse {
+        transaction_identifier: TransactionIdentifier {
+            hash: txn.committed_hash().to_hex_literal(),
+        },
+    }
+}
+
+fn account_identifier(address: AccountAddress) -> AccountIdentifier {
+    AccountIdentifier {
+        address: address.to_hex_literal(),
+    }
+}
+
+fn public_key(public_key: &PublicKey) -> Result<Ed25519PublicKey, ApiError> {
+    if public_key.curve_type != CurveType::Edwards25519 {
+        return Err(ApiError::InvalidRequest(format!(
+            "Unsupported curve {:?}",
+            public_key.curve_type
+        )));
+    }
+    let bytes = decode_hex(&public_key.hex_bytes)?;
+    Ed25519PublicKey::try_from(bytes.as_slice())
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid public key: {}", e)))
+}
+
+fn decode_hex(hex_bytes: &str) -> Result<Vec<u8>, ApiError> {
+    hex::decode(hex_bytes.trim_start_matches("0x"))
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid hex {}: {}", hex_bytes, e)))
+}
+
+fn encode_bcs<T: serde::Serialize>(value: &T) -> Result<String, ApiError> {
+    bcs::to_bytes(value)
+        .map(hex::encode)
+        .map_err(|e| ApiError::InvalidTransaction(e.to_string()))
+}
+
+fn decode_bcs<T: DeserializeOwned>(hex_bytes: &str) -> Result<T, ApiError> {
+    bcs::from_bytes(&decode_hex(hex_bytes)?)
+        .map_err(|e| ApiError::InvalidTransaction(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{NetworkIdentifier, Signature as RosettaSignature};
+    use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, SigningKey, Uniform};
+
+    fn context() -> Arc<RosettaContext> {
+        Arc::new(RosettaContext::new(None, ChainId::test()))
+    }
+
+    fn network_identifier() -> NetworkIdentifier {
+        context().network_identifier()
+    }
+
+    #[tokio::test]
+    async fn test_construction_offline_roundtrip() {
+        let private_key = Ed25519PrivateKey::generate_for_testing();
+        let public_key = PublicKey {
+            hex_bytes: hex::encode(private_key.public_key().to_bytes()),
+            curve_type: CurveType::Edwards25519,
+        };
+        let sender = construction_derive(
+            ConstructionDeriveRequest {
+                network_identifier: network_identifier(),
+                public_key: public_key.clone(),
+            },
+            context(),
+        )
+        .await
+        .unwrap()
+        .account_identifier;
+        let operations = call_operations(
+            parse_address(&sender.address).unwrap(),
+            &ScriptFunctionCall::Transfer {
+                to: AccountAddress::random(),
+                amount: 100,
+            },
+            None,
+        );
+
+        let preprocess = construction_preprocess(
+            ConstructionPreprocessRequest {
+                network_identifier: network_identifier(),
+                operations: operations.clone(),
+                metadata: None,
+            },
+            context(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(preprocess.required_public_keys, vec![sender.clone()]);
+
+        let payloads = construction_payloads(
+            ConstructionPayloadsRequest {
+                network_identifier: network_identifier(),
+                operations: operations.clone(),
+                metadata: ConstructionMetadata {
+                    sender: sender.clone(),
+                    sequence_number: 0,
+                    max_gas_amount: DEFAULT_MAX_GAS_AMOUNT,
+                    gas_unit_price: DEFAULT_GAS_UNIT_PRICE,
+                    expiration_timestamp_secs: 100,
+                    chain_id: ChainId::test().id(),
+                },
+            },
+            context(),
+        )
+        .await
+        .unwrap();
+        let unsigned = construction_parse(
+            ConstructionParseRequest {
+                network_identifier: network_identifier(),
+                signed: false,
+                transaction: payloads.unsigned_transaction.clone(),
+            },
+            context(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(unsigned.operations, operations);
+        assert!(unsigned.account_identifier_signers.is_empty());
+
+        let raw_txn: RawTransaction = decode_bcs(&payloads.unsigned_transaction).unwrap();
+        let signing_payload = payloads.payloads[0].clone();
+        assert_eq!(
+            signing_payload.hex_bytes,
+            hex::encode(raw_txn.signing_message())
+        );
+        let signature = private_key.sign(&raw_txn);
+        let signed = construction_combine(
+            ConstructionCombineRequest {
+                network_identifier: network_identifier(),
+                unsigned_transaction: payloads.unsigned_transaction,
+                signatures: vec![RosettaSignature {
+                    signing_payload,
+                    public_key,
+                    signature_type: SignatureType::Ed25519,
+                    hex_bytes: hex::encode(signature.to_bytes()),
+                }],
+            },
+            context(),
+        )
+        .await
+        .unwrap()
+        .signed_transaction;
+        let parsed = construction_parse(
+            ConstructionParseRequest {
+                network_identifier: network_identifier(),
+                signed: true,
+                transaction: signed.clone(),
+            },
+            context(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(parsed.operations, operations);
+        assert_eq!(parsed.account_identifier_signers, vec![sender]);
+
+        construction_hash(
+            ConstructionHashRequest {
+                network_identifier: network_identifier(),
+                signed_transaction: signed.clone(),
+            },
+            context(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            construction_submit(
+                ConstructionSubmitRequest {
+                    network_identifier: network_identifier(),
+                    signed_transaction: signed,
+                },
+                context(),
+            )
+            .await
+            .unwrap_err(),
+            ApiError::OfflineMode
+        );
+    }
+}
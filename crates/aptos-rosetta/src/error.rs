@@ -0,0 +1,148 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::types;
+use std::fmt;
+use warp::{http::StatusCode, reject::Reject, Rejection, Reply};
+
+/// The errors returned by the API. Rosetta clients tell them apart by code, so the codes of the
+/// existing errors must not change, nor be reused. Code 10 was the error of historical balance
+/// lookups, which are now supported.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ApiError {
+    UnsupportedNetwork(String),
+    OfflineMode,
+    InvalidRequest(String),
+    InvalidOperations(String),
+    InvalidTransaction(String),
+    InvalidSignature(String),
+    BlockNotFound,
+    TransactionNotFound,
+    AccountNotFound,
+    NodeError(String),
+    StakingUnsupported,
+}
+
+impl ApiError {
+    /// One error of each kind, listed by `/network/options`
+    pub fn all() -> Vec<ApiError> {
+        vec![
+            ApiError::UnsupportedNetwork(String::new()),
+            ApiError::OfflineMode,
+            ApiError::InvalidRequest(String::new()),
+            ApiError::InvalidOperations(String::new()),
+            ApiError::InvalidTransaction(String::new()),
+            ApiError::InvalidSignature(String::new()),
+            ApiError::BlockNotFound,
+            ApiError::TransactionNotFound,
+            ApiError::AccountNotFound,
+            ApiError::NodeError(String::new()),
+            ApiError::StakingUnsupported,
+        ]
+    }
+
+    pub fn code(&self) -> u32 {
+        match self {
+            ApiError::UnsupportedNetwork(_) => 1,
+            ApiError::OfflineMode => 2,
+            ApiError::InvalidRequest(_) => 3,
+            ApiError::InvalidOperations(_) => 4,
+            ApiError::InvalidTransaction(_) => 5,
+            ApiError::InvalidSignature(_) => 6,
+            ApiError::BlockNotFound => 7,
+            ApiError::TransactionNotFound => 8,
+            ApiError::AccountNotFound => 9,
+            ApiError::NodeError(_) => 11,
+            ApiError::StakingUnsupported => 12,
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            ApiError::UnsupportedNetwork(_) => "Network is not supported",
+            ApiError::OfflineMode => "Endpoint is unavailable in offline mode",
+            ApiError::InvalidRequest(_) => "Request is invalid",
+            ApiError::InvalidOperations(_) => "Operations are invalid",
+            ApiError::InvalidTransaction(_) => "Transaction is invalid",
+            ApiError::InvalidSignature(_) => "Signature is invalid",
+            ApiError::BlockNotFound => "Block not found",
+            ApiError::TransactionNotFound => "Transaction not found",
+            ApiError::AccountNotFound => "Account not found",
+            ApiError::NodeError(_) => "Request to the node failed",
+            ApiError::StakingUnsupported => "Staking is not supported by the Aptos framework",
+        }
+    }
+
+    pub fn retriable(&self) -> bool {
+        matches!(
+            self,
+            ApiError::BlockNotFound | ApiError::AccountNotFound | ApiError::NodeError(_)
+        )
+    }
+
+    fn details(&self) -> Option<&str> {
+        match self {
+            ApiError::UnsupportedNetwork(details)
+            | ApiError::InvalidRequest(details)
+            | ApiError::InvalidOperations(details)
+            | ApiError::InvalidTransaction(details)
+            | ApiError::InvalidSignature(details)
+            | ApiError::NodeError(details) => Some(details),
+            _ => None,
+        }
+    }
+
+    pub fn into_error(self) -> types::Error {
+        types::Error {
+            code: self.code(),
+            message: self.message().to_string(),
+            retriable: self.retriable(),
+            details: self
+                .details()
+                .filter(|details| !details.is_empty())
+                .map(|details| serde_json::json!({ "error": details })),
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.details() {
+            Some(details) if !details.is_empty() => write!(f, "{}: {}", self.message(), details),
+            _ => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl Reject for ApiError {}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        ApiError::NodeError(error.to_string())
+    }
+}
+
+impl From<ApiError> for Rejection {
+    fn from(error: ApiError) -> Self {
+        warp::reject::custom(error)
+    }
+}
+
+/// Rosetta expects all errors to be returned with a 500 and an Error object, including those of
+/// requests warp couldn't route or deserialize.
+pub async fn handle_rejection(rejection: Rejection) -> Result<impl Reply, Rejection> {
+    let error = if let Some(error) = rejection.find::<ApiError>() {
+        error.clone()
+    } else if let Some(error) = rejection.find::<warp::filters::body::BodyDeserializeError>() {
+        ApiError::InvalidRequest(error.to_string())
+    } else if rejection.is_not_found() {
+        ApiError::InvalidRequest("Endpoint not found".to_string())
+    } else {
+        ApiError::InvalidRequest(format!("{:?}", rejection))
+    };
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&error.into_error()),
+        StatusCode::INTERNAL_SERVER_ERROR,
+    ))
+}
@@ -0,0 +1,136 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This crate provides a server implementing the [Rosetta](https://www.rosetta-api.org/) Data and
+//! Construction APIs for the Aptos Network, so that exchanges can integrate with it using the
+//! standard Rosetta tooling. It reads the chain through the REST API of a fullnode.
+//!
+//! Each ledger version is a Rosetta block holding the transaction of the version, the index of
+//! the block being the version and its hash the hash of the transaction. See `operations` for
+//! how transactions map to operations.
+//!
+//! ## Launch service
+//!
+//! Online, serving both APIs with the REST API of a local fullnode:
+//!
+//! ```bash
+//! cargo run -p aptos-rosetta -- -c TESTNET -s http://localhost:8080 -p 8082
+//! ```
+//!
+//! Offline, serving only the Construction API endpoints which don't need the chain, to build
+//! and sign transactions on an air-gapped machine:
+//!
+//! ```bash
+//! cargo run -p aptos-rosetta -- -c TESTNET -p 8082
+//! ```
+
+use crate::{error::ApiError, types::NetworkIdentifier};
+use aptos_logger::info;
+use aptos_rest_client::Client;
+use aptos_sdk::types::chain_id::ChainId;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{future::Future, sync::Arc};
+use warp::{Filter, Rejection, Reply};
+
+pub mod account;
+pub mod block;
+pub mod construction;
+pub mod error;
+pub mod network;
+pub mod operations;
+pub mod types;
+
+/// The blockchain of the network identifiers
+pub const BLOCKCHAIN: &str = "aptos";
+
+pub struct RosettaContext {
+    rest_client: Option<Client>,
+    chain_id: ChainId,
+}
+
+impl RosettaContext {
+    /// Without REST client, the server is offline and only serves the endpoints which don't
+    /// need the chain.
+    pub fn new(rest_client: Option<Client>, chain_id: ChainId) -> Self {
+        Self {
+            rest_client,
+            chain_id,
+        }
+    }
+
+    pub fn rest_client(&self) -> Result<&Client, ApiError> {
+        self.rest_client.as_ref().ok_or(ApiError::OfflineMode)
+    }
+
+    pub fn chain_id(&self) -> ChainId {
+        self.chain_id
+    }
+
+    pub fn network_identifier(&self) -> NetworkIdentifier {
+        NetworkIdentifier {
+            blockchain: BLOCKCHAIN.to_string(),
+            network: self.chain_id.to_string(),
+        }
+    }
+
+    /// Checks the request is for the network of the server. Networks are named after their
+    /// chain, by name or by id.
+    pub fn check_network(&self, network: &NetworkIdentifier) -> Result<(), ApiError> {
+        let chain_id = network.network.parse::<ChainId>().ok();
+        if network.blockchain == BLOCKCHAIN && chain_id == Some(self.chain_id) {
+            Ok(())
+        } else {
+            Err(ApiError::UnsupportedNetwork(format!(
+                "{}:{}",
+                network.blockchain, network.network
+            )))
+        }
+    }
+}
+
+pub fn routes(
+    context: Arc<RosettaContext>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    network::routes(context.clone())
+        .or(account::routes(context.clone()))
+        .or(block::routes(context.clone()))
+        .or(construction::routes(context))
+        .recover(error::handle_rejection)
+        .with(warp::log::custom(|info| {
+            info!(
+                "\"{} {}\" {} {:?}",
+                info.method(),
+                info.path(),
+                info.status().as_u16(),
+                info.elapsed(),
+            )
+        }))
+        .with(warp::cors().allow_any_origin().allow_methods(vec!["POST"]))
+}
+
+/// All the Rosetta endpoints are POST endpoints taking a JSON request and returning a JSON
+/// response.
+fn endpoint<Request, Response, Handler, Fut>(
+    path: impl Filter<Extract = (), Error = Rejection> + Clone,
+    context: Arc<RosettaContext>,
+    handler: Handler,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone
+where
+    Request: DeserializeOwned + Send + 'static,
+    Response: Serialize,
+    Handler: Fn(Request, Arc<RosettaContext>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Result<Response, ApiError>> + Send,
+{
+    path.and(warp::post())
+        .and(warp::body::json())
+        .and(warp::any().map(move || context.clone()))
+        .and_then(move |request: Request, context: Arc<RosettaContext>| {
+            let handler = handler.clone();
+            async move {
+                handler(request, context)
+                    .await
+                    .map(|response| warp::reply::json(&response))
+                    .map_err(warp::reject::custom)
+            }
+        })
+}
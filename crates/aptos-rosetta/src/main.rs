@@ -0,0 +1,61 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_logger::info;
+use aptos_rest_client::Client;
+use aptos_rosetta::RosettaContext;
+use aptos_sdk::types::chain_id::ChainId;
+use std::sync::Arc;
+use structopt::StructOpt;
+use url::Url;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "Aptos Rosetta",
+    author = "The Diem Core Contributors",
+    about = "Rosetta API server for exchange integrations with the Aptos Network"
+)]
+struct Args {
+    /// Rosetta service listen address
+    #[structopt(short = "a", long, default_value = "127.0.0.1")]
+    pub address: String,
+    /// Rosetta service listen port
+    #[structopt(short = "p", long, default_value = "8082")]
+    pub port: u16,
+    /// Aptos fullnode REST API URL.
+    /// If not present, the server runs offline and only serves the Construction API endpoints
+    /// which don't need the chain
+    #[structopt(short = "s", long)]
+    pub server_url: Option<Url>,
+    /// Chain ID of the network to serve.
+    /// For mainnet: "MAINNET" or 1, testnet: "TESTNET" or 2, devnet: "DEVNET" or 3,
+    /// local swarm: "TESTING" or 4
+    #[structopt(short = "c", long, default_value = "2")]
+    pub chain_id: ChainId,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::from_args();
+    aptos_logger::Logger::new().init();
+
+    let address: std::net::SocketAddr = format!("{}:{}", args.address, args.port)
+        .parse()
+        .expect("invalid address or port number");
+
+    info!(
+        "[rosetta]: chain id: {}, server url: {:?}",
+        args.chain_id,
+        args.server_url.as_ref().map(Url::as_str),
+    );
+
+    let context = Arc::new(RosettaContext::new(
+        args.server_url.map(Client::new),
+        args.chain_id,
+    ));
+
+    info!("[rosetta]: running on: {}", address);
+    warp::serve(aptos_rosetta::routes(context))
+        .run(address)
+        .await;
+}
@@ -0,0 +1,103 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    block::block_identifier_at,
+    endpoint,
+    error::ApiError,
+    operations::{OperationType, STATUS_FAILURE, STATUS_SUCCESS},
+    types::{
+        Allow, MetadataRequest, NetworkListResponse, NetworkOptionsResponse, NetworkRequest,
+        NetworkStatusResponse, OperationStatus, Version, ROSETTA_VERSION,
+    },
+    RosettaContext,
+};
+use std::sync::Arc;
+use warp::{Filter, Rejection, Reply};
+
+pub fn routes(
+    context: Arc<RosettaContext>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    endpoint(
+        warp::path!("network" / "list"),
+        context.clone(),
+        network_list,
+    )
+    .or(endpoint(
+        warp::path!("network" / "options"),
+        context.clone(),
+        network_options,
+    ))
+    .or(endpoint(
+        warp::path!("network" / "status"),
+        context,
+        network_status,
+    ))
+}
+
+async fn network_list(
+    _request: MetadataRequest,
+    context: Arc<RosettaContext>,
+) -> Result<NetworkListResponse, ApiError> {
+    Ok(NetworkListResponse {
+        network_identifiers: vec![context.network_identifier()],
+    })
+}
+
+async fn network_options(
+    request: NetworkRequest,
+    context: Arc<RosettaContext>,
+) -> Result<NetworkOptionsResponse, ApiError> {
+    context.check_network(&request.network_identifier)?;
+    let version = env!("CARGO_PKG_VERSION").to_string();
+    Ok(NetworkOptionsResponse {
+        version: Version {
+            rosetta_version: ROSETTA_VERSION.to_string(),
+            node_version: version.clone(),
+            middleware_version: version,
+        },
+        allow: Allow {
+            operation_statuses: vec![
+                OperationStatus {
+                    status: STATUS_SUCCESS.to_string(),
+                    successful: true,
+                },
+                OperationStatus {
+                    status: STATUS_FAILURE.to_string(),
+                    successful: false,
+                },
+            ],
+            operation_types: OperationType::all()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            errors: ApiError::all()
+                .into_iter()
+                .map(ApiError::into_error)
+                .collect(),
+            historical_balance_lookup: true,
+            call_methods: vec![],
+            balance_exemptions: vec![],
+            mempool_coins: false,
+        },
+    })
+}
+
+async fn network_status(
+    request: NetworkRequest,
+    context: Arc<RosettaContext>,
+) -> Result<NetworkStatusResponse, ApiError> {
+    context.check_network(&request.network_identifier)?;
+    let client = context.rest_client()?;
+    let ledger_version = client.get_ledger_information().await?.inner().version;
+    let (current_block_identifier, current_block_timestamp) =
+        block_identifier_at(client, ledger_version).await?;
+    let (genesis_block_identifier, _) = block_identifier_at(client, 0).await?;
+
+    Ok(NetworkStatusResponse {
+        current_block_identifier,
+        current_block_timestamp,
+        genesis_block_identifier,
+        peers: vec![],
+    })
+}
@@ -0,0 +1,435 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Conversions between transactions and Rosetta operations.
+//!
+//! The coin movements of a transaction are found from its script function call, only the calls
+//! of the Aptos framework moving coins are understood:
+//! * `TestCoin::transfer` is a `transfer` operation debiting the sender and a related `transfer`
+//!   operation crediting the receiver.
+//! * `TestCoin::mint` is a `mint` operation crediting the receiver.
+//! * `AptosAccount::create_account` is a `create_account` operation of the new account.
+//!
+//! The gas paid by the sender of a committed transaction is an additional `fee` operation, which
+//! succeeds even when the transaction fails. The framework doesn't support staking, so there are
+//! no staking operations, and constructing one fails with an explicit unsupported error.
+
+use crate::{
+    error::ApiError,
+    types::{AccountIdentifier, Amount, Currency, Operation, OperationIdentifier},
+};
+use aptos_rest_client::aptos_api_types::{
+    self, Address, HexEncodedBytes, TransactionPayload as ApiTransactionPayload, U64,
+};
+use aptos_sdk::{
+    transaction_builder::aptos_stdlib::ScriptFunctionCall, types::account_address::AccountAddress,
+};
+use serde_json::{json, Value};
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+/// The symbol of the native coin
+pub const NATIVE_COIN: &str = "TC";
+/// The number of decimals of the native coin, i.e. the log10 of its scaling factor
+pub const NATIVE_COIN_DECIMALS: u32 = 6;
+
+pub const STATUS_SUCCESS: &str = "success";
+pub const STATUS_FAILURE: &str = "failure";
+
+pub fn native_coin() -> Currency {
+    Currency {
+        symbol: NATIVE_COIN.to_string(),
+        decimals: NATIVE_COIN_DECIMALS,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperationType {
+    Transfer,
+    Mint,
+    CreateAccount,
+    Fee,
+}
+
+impl OperationType {
+    pub fn all() -> Vec<OperationType> {
+        vec![
+            OperationType::Transfer,
+            OperationType::Mint,
+            OperationType::CreateAccount,
+            OperationType::Fee,
+        ]
+    }
+}
+
+impl FromStr for OperationType {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, ApiError> {
+        match s {
+            "transfer" => Ok(OperationType::Transfer),
+            "mint" => Ok(OperationType::Mint),
+            "create_account" => Ok(OperationType::CreateAccount),
+            "fee" => Ok(OperationType::Fee),
+            "stake" | "unstake" | "withdraw_stake" => Err(ApiError::StakingUnsupported),
+            _ => Err(ApiError::InvalidOperations(format!(
+                "Unknown operation type: {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for OperationType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OperationType::Transfer => "transfer",
+            OperationType::Mint => "mint",
+            OperationType::CreateAccount => "create_account",
+            OperationType::Fee => "fee",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The operations of a committed transaction
+pub fn transaction_operations(txn: &aptos_api_types::Transaction) -> Vec<Operation> {
+    let txn = match txn {
+        aptos_api_types::Transaction::UserTransaction(txn) => txn,
+        // The coins moved by genesis and the other system transactions aren't tracked
+        _ => return vec![],
+    };
+
+    let sender = *txn.request.sender.inner();
+    let status = if txn.info.success {
+        STATUS_SUCCESS
+    } else {
+        STATUS_FAILURE
+    };
+    let mut operations = decode_api_payload(&txn.request.payload)
+        .map(|call| call_operations(sender, &call, Some(status)))
+        .unwrap_or_default();
+
+    let fee = txn.info.gas_used.0 as u128 * txn.request.gas_unit_price.0 as u128;
+    if fee > 0 {
+        operations.push(operation(
+            operations.len() as u64,
+            OperationType::Fee,
+            sender,
+            Some(-(fee as i128)),
+            Some(STATUS_SUCCESS),
+        ));
+    }
+    operations
+}
+
+/// The operations of the script function call sent by the sender, without status for the ones
+/// of transactions being constructed. Returns no operation for calls which don't move coins.
+pub fn call_operations(
+    sender: AccountAddress,
+    call: &ScriptFunctionCall,
+    status: Option<&str>,
+) -> Vec<Operation> {
+    match call {
+        ScriptFunctionCall::Transfer { to, amount } => {
+            let mut credit = operation(
+                1,
+                OperationType::Transfer,
+                *to,
+                Some(*amount as i128),
+                status,
+            );
+            credit.related_operations = vec![OperationIdentifier { index: 0 }];
+            vec![
+                operation(
+                    0,
+                    OperationType::Transfer,
+                    sender,
+                    Some(-(*amount as i128)),
+                    status,
+                ),
+                credit,
+            ]
+        }
+        ScriptFunctionCall::Mint { mint_addr, amount } => {
+            let mut mint = operation(
+                0,
+                OperationType::Mint,
+                *mint_addr,
+                Some(*amount as i128),
+                status,
+            );
+            mint.metadata = Some(json!({ "sender": sender.to_hex_literal() }));
+            vec![mint]
+        }
+        ScriptFunctionCall::CreateAccount {
+            new_account_address,
+            auth_key_preimage,
+        } => {
+            let mut create_account = operation(
+                0,
+                OperationType::CreateAccount,
+                *new_account_address,
+                None,
+                status,
+            );
+            create_account.metadata = Some(json!({
+                "sender": sender.to_hex_literal(),
+                "auth_key_preimage": hex::encode(auth_key_preimage),
+            }));
+            vec![create_account]
+        }
+        _ => vec![],
+    }
+}
+
+/// The sender and the script function call of the operations of a transaction to construct
+pub fn operations_call(
+    operations: &[Operation],
+) -> Result<(AccountAddress, ScriptFunctionCall), ApiError> {
+    let types = operations
+        .iter()
+        .map(|operation| operation.operation_type.parse())
+        .collect::<Result<Vec<OperationType>, _>>()?;
+
+    match types.as_slice() {
+        [OperationType::Transfer, OperationType::Transfer] => {
+            let first = (account(&operations[0])?, amount(&operations[0])?);
+            let second = (account(&operations[1])?, amount(&operations[1])?);
+            let ((sender, debit), (to, credit)) = if first.1 < 0 {
+                (first, second)
+            } else {
+                (second, first)
+            };
+            if debit >= 0 || debit + credit != 0 {
+                return Err(ApiError::InvalidOperations(
+                    "A transfer must debit the sender of the amount credited to the receiver"
+                        .to_string(),
+                ));
+            }
+            let amount = u64::try_from(credit).map_err(|_| {
+                ApiError::InvalidOperations(format!("Invalid transfer amount: {}", credit))
+            })?;
+            Ok((sender, ScriptFunctionCall::Transfer { to, amount }))
+        }
+        [OperationType::Mint] => {
+            let operation = &operations[0];
+            let credit = amount(operation)?;
+            let amount = u64::try_from(credit).map_err(|_| {
+                ApiError::InvalidOperations(format!("Invalid mint amount: {}", credit))
+            })?;
+            Ok((
+                metadata_sender(operation)?,
+                ScriptFunctionCall::Mint {
+                    mint_addr: account(operation)?,
+                    amount,
+                },
+            ))
+        }
+        [OperationType::CreateAccount] => {
+            let operation = &operations[0];
+            let auth_key_preimage = operation
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.get("auth_key_preimage"))
+                .and_then(Value::as_str)
+                .ok_or_else(|| {
+                    ApiError::InvalidOperations(
+                        "create_account requires the auth_key_preimage metadata".to_string(),
+                    )
+                })?;
+            let auth_key_preimage = hex::decode(auth_key_preimage).map_err(|e| {
+                ApiError::InvalidOperations(format!("Invalid auth_key_preimage: {}", e))
+            })?;
+            Ok((
+                metadata_sender(operation)?,
+                ScriptFunctionCall::CreateAccount {
+                    new_account_address: account(operation)?,
+                    auth_key_preimage,
+                },
+            ))
+        }
+        _ => Err(ApiError::InvalidOperations(format!(
+            "Unsupported operations: {:?}",
+            types
+        ))),
+    }
+}
+
+/// Decodes the script function calls of the API representation of a payload, with JSON arguments
+fn decode_api_payload(payload: &ApiTransactionPayload) -> Option<ScriptFunctionCall> {
+    let payload = match payload {
+        ApiTransactionPayload::ScriptFunctionPayload(payload) => payload,
+        _ => return None,
+    };
+    let address = |index: usize| -> Option<AccountAddress> {
+        let address: Address =
+            serde_json::from_value(payload.arguments.get(index)?.clone()).ok()?;
+        Some(address.into())
+    };
+    let u64_arg = |index: usize| -> Option<u64> {
+        let value: U64 = serde_json::from_value(payload.arguments.get(index)?.clone()).ok()?;
+        Some(value.0)
+    };
+    let bytes = |index: usize| -> Option<Vec<u8>> {
+        let bytes: HexEncodedBytes = payload.arguments.get(index)?.as_str()?.parse().ok()?;
+        Some(bytes.into())
+    };
+
+    match payload.function.to_string().as_str() {
+        "0x1::TestCoin::transfer" => Some(ScriptFunctionCall::Transfer {
+            to: address(0)?,
+            amount: u64_arg(1)?,
+        }),
+        "0x1::TestCoin::mint" => Some(ScriptFunctionCall::Mint {
+            mint_addr: address(0)?,
+            amount: u64_arg(1)?,
+        }),
+        "0x1::AptosAccount::create_account" => Some(ScriptFunctionCall::CreateAccount {
+            new_account_address: address(0)?,
+            auth_key_preimage: bytes(1)?,
+        }),
+        _ => None,
+    }
+}
+
+fn operation(
+    index: u64,
+    operation_type: OperationType,
+    address: AccountAddress,
+    amount: Option<i128>,
+    status: Option<&str>,
+) -> Operation {
+    Operation {
+        operation_identifier: OperationIdentifier { index },
+        related_operations: vec![],
+        operation_type: operation_type.to_string(),
+        status: status.map(str::to_string),
+        account: Some(AccountIdentifier {
+            address: address.to_hex_literal(),
+        }),
+        amount: amount.map(|value| Amount {
+            value: value.to_string(),
+            currency: native_coin(),
+        }),
+        metadata: None,
+    }
+}
+
+pub fn parse_address(address: &str) -> Result<AccountAddress, ApiError> {
+    AccountAddress::from_hex_literal(address)
+        .or_else(|_| AccountAddress::from_hex(address))
+        .map_err(|e| ApiError::InvalidRequest(format!("Invalid address {}: {}", address, e)))
+}
+
+fn account(operation: &Operation) -> Result<AccountAddress, ApiError> {
+    let account = operation.account.as_ref().ok_or_else(|| {
+        ApiError::InvalidOperations(format!(
+            "Operation {} has no account",
+            operation.operation_identifier.index
+        ))
+    })?;
+    parse_address(&account.address)
+}
+
+fn amount(operation: &Operation) -> Result<i128, ApiError> {
+    let amount = operation.amount.as_ref().ok_or_else(|| {
+        ApiError::InvalidOperations(format!(
+            "Operation {} has no amount",
+            operation.operation_identifier.index
+        ))
+    })?;
+    if amount.currency != native_coin() {
+        return Err(ApiError::InvalidOperations(format!(
+            "Unsupported currency: {}",
+            amount.currency.symbol
+        )));
+    }
+    amount
+        .value
+        .parse()
+        .map_err(|e| ApiError::InvalidOperations(format!("Invalid amount {}: {}", amount.value, e)))
+}
+
+fn metadata_sender(operation: &Operation) -> Result<AccountAddress, ApiError> {
+    let sender = operation
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.get("sender"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| {
+            ApiError::InvalidOperations(format!(
+                "{} requires the sender metadata",
+                operation.operation_type
+            ))
+        })?;
+    parse_address(sender)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_operations() {
+        let sender = AccountAddress::random();
+        let to = AccountAddress::random();
+        let call = ScriptFunctionCall::Transfer { to, amount: 100 };
+
+        let operations = call_operations(sender, &call, None);
+        assert_eq!(operations.len(), 2);
+        assert_eq!(operations[0].amount.as_ref().unwrap().value, "-100");
+        assert_eq!(operations[1].amount.as_ref().unwrap().value, "100");
+        assert_eq!(
+            operations_call(&operations).unwrap(),
+            (sender, call.clone())
+        );
+
+        // The order of the operations doesn't matter
+        let reversed = operations.iter().rev().cloned().collect::<Vec<_>>();
+        assert_eq!(operations_call(&reversed).unwrap(), (sender, call));
+
+        // Unbalanced transfers are rejected
+        let mut unbalanced = operations;
+        unbalanced[1].amount.as_mut().unwrap().value = "99".to_string();
+        assert!(operations_call(&unbalanced).is_err());
+    }
+
+    #[test]
+    fn test_single_operations() {
+        let sender = AccountAddress::random();
+        for call in vec![
+            ScriptFunctionCall::Mint {
+                mint_addr: AccountAddress::random(),
+                amount: 10,
+            },
+            ScriptFunctionCall::CreateAccount {
+                new_account_address: AccountAddress::random(),
+                auth_key_preimage: vec![1, 2, 3],
+            },
+        ] {
+            let operations = call_operations(sender, &call, None);
+            assert_eq!(operations_call(&operations).unwrap(), (sender, call));
+        }
+
+        let call = ScriptFunctionCall::ClaimMintCapability {};
+        assert!(call_operations(sender, &call, None).is_empty());
+        assert!(operations_call(&[]).is_err());
+    }
+
+    #[test]
+    fn test_staking_operations_unsupported() {
+        let mut operations = call_operations(
+            AccountAddress::random(),
+            &ScriptFunctionCall::Mint {
+                mint_addr: AccountAddress::random(),
+                amount: 10,
+            },
+            None,
+        );
+        operations[0].operation_type = "stake".to_string();
+        assert_eq!(
+            operations_call(&operations).unwrap_err(),
+            ApiError::StakingUnsupported
+        );
+    }
+}
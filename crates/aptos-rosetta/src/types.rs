@@ -0,0 +1,390 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The models of the Rosetta API, see https://www.rosetta-api.org/docs/api_objects.html.
+//! Optional fields are left out of the JSON when they aren't set.
+
+use serde::{Deserialize, Serialize};
+
+/// The version of the Rosetta specification implemented
+pub const ROSETTA_VERSION: &str = "1.4.12";
+
+//
+// Identifiers
+//
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct NetworkIdentifier {
+    pub blockchain: String,
+    pub network: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BlockIdentifier {
+    pub index: u64,
+    pub hash: String,
+}
+
+/// Identifies a block by index or by hash, or the latest block when neither is set
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PartialBlockIdentifier {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TransactionIdentifier {
+    pub hash: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AccountIdentifier {
+    pub address: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OperationIdentifier {
+    pub index: u64,
+}
+
+//
+// Objects
+//
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Currency {
+    pub symbol: String,
+    pub decimals: u32,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Amount {
+    /// The value in the smallest unit of the currency, negative for debits
+    pub value: String,
+    pub currency: Currency,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Operation {
+    pub operation_identifier: OperationIdentifier,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related_operations: Vec<OperationIdentifier>,
+    #[serde(rename = "type")]
+    pub operation_type: String,
+    /// Only set for the operations of committed transactions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<AccountIdentifier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Amount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Transaction {
+    pub transaction_identifier: TransactionIdentifier,
+    pub operations: Vec<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Block {
+    pub block_identifier: BlockIdentifier,
+    pub parent_block_identifier: BlockIdentifier,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub transactions: Vec<Transaction>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Version {
+    pub rosetta_version: String,
+    pub node_version: String,
+    pub middleware_version: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OperationStatus {
+    pub status: String,
+    pub successful: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Allow {
+    pub operation_statuses: Vec<OperationStatus>,
+    pub operation_types: Vec<String>,
+    pub errors: Vec<Error>,
+    pub historical_balance_lookup: bool,
+    pub call_methods: Vec<String>,
+    pub balance_exemptions: Vec<serde_json::Value>,
+    pub mempool_coins: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Error {
+    pub code: u32,
+    pub message: String,
+    pub retriable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Peer {
+    pub peer_id: String,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CurveType {
+    Edwards25519,
+    Secp256k1,
+    Secp256r1,
+    Tweedle,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureType {
+    Ed25519,
+    Ecdsa,
+    EcdsaRecovery,
+    Schnorr1,
+    SchnorrPoseidon,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PublicKey {
+    pub hex_bytes: String,
+    pub curve_type: CurveType,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SigningPayload {
+    pub account_identifier: AccountIdentifier,
+    pub hex_bytes: String,
+    pub signature_type: SignatureType,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Signature {
+    pub signing_payload: SigningPayload,
+    pub public_key: PublicKey,
+    pub signature_type: SignatureType,
+    pub hex_bytes: String,
+}
+
+//
+// Data API requests and responses
+//
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct MetadataRequest {}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NetworkRequest {
+    pub network_identifier: NetworkIdentifier,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NetworkListResponse {
+    pub network_identifiers: Vec<NetworkIdentifier>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NetworkOptionsResponse {
+    pub version: Version,
+    pub allow: Allow,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NetworkStatusResponse {
+    pub current_block_identifier: BlockIdentifier,
+    pub current_block_timestamp: u64,
+    pub genesis_block_identifier: BlockIdentifier,
+    pub peers: Vec<Peer>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AccountBalanceRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub account_identifier: AccountIdentifier,
+    #[serde(default)]
+    pub block_identifier: Option<PartialBlockIdentifier>,
+    #[serde(default)]
+    pub currencies: Option<Vec<Currency>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AccountBalanceResponse {
+    pub block_identifier: BlockIdentifier,
+    pub balances: Vec<Amount>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BlockRequest {
+    pub network_identifier: NetworkIdentifier,
+    #[serde(default)]
+    pub block_identifier: PartialBlockIdentifier,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BlockResponse {
+    pub block: Block,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BlockTransactionRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub block_identifier: BlockIdentifier,
+    pub transaction_identifier: TransactionIdentifier,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BlockTransactionResponse {
+    pub transaction: Transaction,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MempoolResponse {
+    pub transaction_identifiers: Vec<TransactionIdentifier>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MempoolTransactionRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub transaction_identifier: TransactionIdentifier,
+}
+
+//
+// Construction API requests and responses
+//
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConstructionDeriveRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub public_key: PublicKey,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConstructionDeriveResponse {
+    pub account_identifier: AccountIdentifier,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConstructionPreprocessRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub operations: Vec<Operation>,
+    #[serde(default)]
+    pub metadata: Option<PreprocessMetadata>,
+}
+
+/// The transaction options which can be given to `/construction/preprocess`
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PreprocessMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_gas_amount: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_unit_price: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration_timestamp_secs: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConstructionPreprocessResponse {
+    pub options: MetadataOptions,
+    pub required_public_keys: Vec<AccountIdentifier>,
+}
+
+/// The options `/construction/metadata` fetches the metadata with
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MetadataOptions {
+    pub sender: AccountIdentifier,
+    #[serde(flatten)]
+    pub transaction: PreprocessMetadata,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConstructionMetadataRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub options: MetadataOptions,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConstructionMetadataResponse {
+    pub metadata: ConstructionMetadata,
+    pub suggested_fee: Vec<Amount>,
+}
+
+/// Everything besides the operations needed to build the transaction
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ConstructionMetadata {
+    pub sender: AccountIdentifier,
+    pub sequence_number: u64,
+    pub max_gas_amount: u64,
+    pub gas_unit_price: u64,
+    pub expiration_timestamp_secs: u64,
+    pub chain_id: u8,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConstructionPayloadsRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub operations: Vec<Operation>,
+    pub metadata: ConstructionMetadata,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConstructionPayloadsResponse {
+    /// The hex encoded BCS of the RawTransaction
+    pub unsigned_transaction: String,
+    pub payloads: Vec<SigningPayload>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConstructionCombineRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub unsigned_transaction: String,
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConstructionCombineResponse {
+    /// The hex encoded BCS of the SignedTransaction
+    pub signed_transaction: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConstructionParseRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub signed: bool,
+    pub transaction: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConstructionParseResponse {
+    pub operations: Vec<Operation>,
+    pub account_identifier_signers: Vec<AccountIdentifier>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConstructionHashRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub signed_transaction: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConstructionSubmitRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub signed_transaction: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TransactionIdentifierResponse {
+    pub transaction_identifier: TransactionIdentifier,
+}
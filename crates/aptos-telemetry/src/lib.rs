@@ -0,0 +1,257 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Telemetry periodically samples a few anonymized metrics of the node and pushes them to the
+//! configured endpoint. Samples which can't be pushed are buffered locally, up to a limit, and
+//! pushed with the next batch.
+
+pub mod payload;
+#[cfg(test)]
+mod tests;
+
+use crate::payload::{
+    load_or_create_node_id, CrashReport, MetricBatch, MetricSample, NODE_ID_FILE,
+};
+use anyhow::{bail, Result};
+use aptos_config::config::{NodeConfig, TelemetryConfig};
+use aptos_crypto::HashValue;
+use aptos_logger::prelude::*;
+use aptos_metrics::json_metrics::get_git_rev;
+use aptos_types::chain_id::ChainId;
+use network::application::storage::PeerMetadataStorage;
+use std::{
+    collections::VecDeque,
+    env,
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use storage_interface::DbReader;
+use tokio::runtime::{Builder, Runtime};
+
+/// Setting this environment variable, to any value, opts the node out of telemetry regardless of
+/// its config.
+pub const DISABLE_TELEMETRY_ENV: &str = "APTOS_DISABLE_TELEMETRY";
+
+/// Whether the node opted out of telemetry, in which case nothing is collected nor pushed.
+pub fn is_opted_out(config: &TelemetryConfig) -> bool {
+    !config.enabled || env::var_os(DISABLE_TELEMETRY_ENV).is_some()
+}
+
+pub struct TelemetryService {
+    _runtime: Runtime,
+}
+
+impl TelemetryService {
+    /// Starts pushing metrics, and uploading the crash dumps of previous runs, unless the node
+    /// opted out of telemetry or no endpoint is configured.
+    pub fn start(
+        node_config: &NodeConfig,
+        chain_id: ChainId,
+        db: Arc<dyn DbReader>,
+        peer_metadata_storage: Arc<PeerMetadataStorage>,
+    ) -> Option<Self> {
+        let config = &node_config.telemetry;
+        if is_opted_out(config) {
+            info!("Telemetry is disabled.");
+            return None;
+        }
+        if config.endpoint.is_none() && config.crash_dump_endpoint.is_none() {
+            info!("Telemetry is enabled, but no endpoint is configured.");
+            return None;
+        }
+
+        let runtime = Builder::new_multi_thread()
+            .thread_name("telemetry")
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .expect("[telemetry] failed to create runtime");
+        let node_id_path = node_config.data_dir().join(NODE_ID_FILE);
+        let node_id = load_or_create_node_id(&node_id_path).unwrap_or_else(|e| {
+            warn!(error = %e, "Failed to persist the node id, using a random one until restart.");
+            HashValue::random().to_hex()
+        });
+        let header = MetricBatch::new(node_id, node_config, chain_id, get_git_rev());
+
+        if let Some(endpoint) = config.crash_dump_endpoint.clone() {
+            let client = reqwest::Client::new();
+            let crash_dump_dir = node_config.crash_dumps.dir(node_config.data_dir());
+            let header = header.clone();
+            let timeout = Duration::from_millis(config.push_timeout_ms);
+            runtime.spawn(async move {
+                match upload_crash_dumps(&client, &endpoint, timeout, &header, &crash_dump_dir)
+                    .await
+                {
+                    Ok(uploaded) => info!(uploaded = uploaded, "Uploaded crash dumps."),
+                    Err(e) => warn!(error = %e, "Failed to upload crash dumps."),
+                }
+            });
+        }
+
+        if let Some(endpoint) = config.endpoint.clone() {
+            let mut pusher = MetricPusher::new(endpoint, header, config);
+            let push_interval = Duration::from_millis(config.push_interval_ms);
+            runtime.spawn(async move {
+                let mut interval = tokio::time::interval(push_interval);
+                loop {
+                    interval.tick().await;
+                    // Reading the DB blocks, so it's kept off the runtime's worker thread
+                    let (db, peer_metadata_storage) = (db.clone(), peer_metadata_storage.clone());
+                    match tokio::task::spawn_blocking(move || {
+                        collect_sample(&*db, &peer_metadata_storage)
+                    })
+                    .await
+                    {
+                        Ok(sample) => pusher.buffer(sample),
+                        Err(e) => warn!(error = %e, "Failed to collect a telemetry sample."),
+                    }
+                    if let Err(e) = pusher.push().await {
+                        warn!(
+                            error = %e,
+                            buffered_samples = pusher.buffered_samples(),
+                            "Failed to push telemetry, will retry with the next batch."
+                        );
+                    }
+                }
+            });
+        }
+        info!(
+            endpoint = config.endpoint,
+            crash_dump_endpoint = config.crash_dump_endpoint,
+            "Telemetry started."
+        );
+
+        Some(Self { _runtime: runtime })
+    }
+}
+
+fn collect_sample(db: &dyn DbReader, peer_metadata_storage: &PeerMetadataStorage) -> MetricSample {
+    let timestamp_usecs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_micros() as u64;
+    let synced_version = db
+        .get_latest_transaction_info_option()
+        .ok()
+        .flatten()
+        .map(|(version, _)| version);
+    let peer_count = peer_metadata_storage
+        .networks()
+        .map(|network_id| {
+            peer_metadata_storage
+                .read_filtered(network_id, |(_, peer_info)| peer_info.is_connected())
+                .len()
+        })
+        .sum();
+    let reachability = peer_metadata_storage
+        .networks()
+        .map(|network_id| {
+            (
+                network_id.to_string(),
+                peer_metadata_storage.reachability(network_id),
+            )
+        })
+        .collect();
+
+    MetricSample {
+        timestamp_usecs,
+        synced_version,
+        peer_count,
+        reachability,
+    }
+}
+
+/// Uploads the crash dumps in the directory which weren't uploaded yet, marking them as uploaded.
+/// Returns the number of dumps uploaded.
+pub async fn upload_crash_dumps(
+    client: &reqwest::Client,
+    endpoint: &str,
+    timeout: Duration,
+    header: &MetricBatch,
+    crash_dump_dir: &Path,
+) -> Result<usize> {
+    let mut uploaded = 0;
+    for (path, dump) in crash_handler::read_crash_dumps(crash_dump_dir)? {
+        if crash_handler::is_uploaded(&path) {
+            continue;
+        }
+        let report = CrashReport::new(header, dump);
+        let response = client
+            .post(endpoint)
+            .timeout(timeout)
+            .json(&report)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!("Crash dump endpoint responded {}", response.status());
+        }
+        crash_handler::mark_uploaded(&path)?;
+        uploaded += 1;
+    }
+    Ok(uploaded)
+}
+
+/// Pushes the buffered samples in batches, keeping them until a push succeeds.
+pub struct MetricPusher {
+    client: reqwest::Client,
+    endpoint: String,
+    /// Describes the node in every batch, without samples.
+    header: MetricBatch,
+    samples: VecDeque<MetricSample>,
+    max_buffered_samples: usize,
+    push_timeout: Duration,
+}
+
+impl MetricPusher {
+    pub fn new(endpoint: String, header: MetricBatch, config: &TelemetryConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoint,
+            header,
+            samples: VecDeque::new(),
+            max_buffered_samples: config.max_buffered_samples,
+            push_timeout: Duration::from_millis(config.push_timeout_ms),
+        }
+    }
+
+    /// Buffers a sample for the next push, dropping the oldest one when the buffer is full.
+    pub fn buffer(&mut self, sample: MetricSample) {
+        if self.max_buffered_samples == 0 {
+            return;
+        }
+        if self.samples.len() == self.max_buffered_samples {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn buffered_samples(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Pushes all the buffered samples, which are only dropped if the push succeeds.
+    pub async fn push(&mut self) -> Result<()> {
+        if self.samples.is_empty() {
+            return Ok(());
+        }
+        let batch = MetricBatch {
+            samples: self.samples.iter().cloned().collect(),
+            ..self.header.clone()
+        };
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .timeout(self.push_timeout)
+            .json(&batch)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!("Telemetry endpoint responded {}", response.status());
+        }
+        self.samples.clear();
+        Ok(())
+    }
+}
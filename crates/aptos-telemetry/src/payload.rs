@@ -0,0 +1,123 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The payload pushed to the telemetry endpoint. Any change to it must bump `SCHEMA_VERSION` and
+//! be reflected in the schema documented in the README of the crate.
+
+use anyhow::Result;
+use aptos_config::config::NodeConfig;
+use aptos_crypto::HashValue;
+use aptos_types::{chain_id::ChainId, transaction::Version};
+use crash_handler::CrashDump;
+use network::application::types::ReachabilityStatus;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// The file, in the data directory of the node, persisting its node id.
+pub const NODE_ID_FILE: &str = "telemetry_node_id";
+
+/// Samples of the metrics of a node, pushed together as a JSON body.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MetricBatch {
+    pub schema_version: u32,
+    /// Identifies the node across pushes and restarts: a random id persisted by the node, which
+    /// can't be linked to its peer id.
+    pub node_id: String,
+    pub chain_id: u8,
+    pub role: String,
+    /// Git revision the node was built from.
+    pub build_version: String,
+    pub samples: Vec<MetricSample>,
+}
+
+impl MetricBatch {
+    /// A batch without samples, describing the node.
+    pub fn new(
+        node_id: String,
+        node_config: &NodeConfig,
+        chain_id: ChainId,
+        build_version: String,
+    ) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            node_id,
+            chain_id: chain_id.id(),
+            role: node_config.base.role.to_string(),
+            build_version,
+            samples: Vec::new(),
+        }
+    }
+}
+
+/// Reads the node id persisted in the file, persisting a new random one if there's no valid id yet.
+pub fn load_or_create_node_id(path: &Path) -> Result<String> {
+    if let Ok(node_id) = fs::read_to_string(path) {
+        if let Ok(node_id) = HashValue::from_hex(node_id.trim()) {
+            return Ok(node_id.to_hex());
+        }
+    }
+    let node_id = HashValue::random().to_hex();
+    fs::write(path, &node_id)?;
+    Ok(node_id)
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MetricSample {
+    /// When the sample was collected, in microseconds since the Unix epoch.
+    pub timestamp_usecs: u64,
+    /// Version of the latest transaction synced by the node, if it could be read.
+    pub synced_version: Option<Version>,
+    /// Peers connected to the node, on all its networks.
+    pub peer_count: usize,
+    /// Whether the node is reachable by other peers, by network.
+    pub reachability: BTreeMap<String, ReachabilityStatus>,
+}
+
+/// What identifies the node or its peers on the network in the logs: network addresses, socket
+/// addresses, and 32 bytes hex values such as peer ids and keys (which also covers hashes).
+static PEER_IDENTIFIERS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r#"(/(ip4|ip6|dns|dns4|dns6|tcp|memory|noise-ik|handshake)/[^/\s"\\,]+)+"#,
+        r"|\[[0-9a-fA-F:.]*:[0-9a-fA-F:.]*\](:\d+)?",
+        r"|\b\d{1,3}(\.\d{1,3}){3}(:\d+)?\b",
+        r"|\b(0x)?[0-9a-fA-F]{64}\b",
+    ))
+    .expect("invalid peer identifiers regex")
+});
+
+pub const REDACTED: &str = "<redacted>";
+
+/// Redacts the peer identifiers in the text, see `PEER_IDENTIFIERS`.
+pub fn scrub(text: &str) -> String {
+    PEER_IDENTIFIERS.replace_all(text, REDACTED).into_owned()
+}
+
+/// A crash dump of a node, pushed as a JSON body to the crash dump endpoint.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CrashReport {
+    pub schema_version: u32,
+    pub node_id: String,
+    pub chain_id: u8,
+    pub dump: CrashDump,
+}
+
+impl CrashReport {
+    /// A report of the dump, whose panic message and recent logs are scrubbed of the addresses
+    /// and ids of the node and its peers.
+    pub fn new(header: &MetricBatch, mut dump: CrashDump) -> Self {
+        dump.details = scrub(&dump.details);
+        for log in dump.recent_logs.iter_mut() {
+            *log = scrub(log);
+        }
+        Self {
+            schema_version: header.schema_version,
+            node_id: header.node_id.clone(),
+            chain_id: header.chain_id,
+            dump,
+        }
+    }
+}
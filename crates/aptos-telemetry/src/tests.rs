@@ -0,0 +1,237 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    is_opted_out,
+    payload::{
+        load_or_create_node_id, scrub, CrashReport, MetricBatch, MetricSample, NODE_ID_FILE,
+        REDACTED, SCHEMA_VERSION,
+    },
+    upload_crash_dumps, MetricPusher,
+};
+use aptos_config::config::{NodeConfig, TelemetryConfig};
+use aptos_infallible::Mutex;
+use aptos_temppath::TempPath;
+use aptos_types::chain_id::ChainId;
+use crash_handler::CrashDump;
+use network::application::types::ReachabilityStatus;
+use serde_json::json;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use warp::{http::StatusCode, Filter};
+
+fn sample(timestamp_usecs: u64) -> MetricSample {
+    MetricSample {
+        timestamp_usecs,
+        synced_version: Some(timestamp_usecs * 10),
+        peer_count: 3,
+        reachability: vec![("Public".to_string(), ReachabilityStatus::Reachable)]
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// Serves an endpoint recording the bodies pushed to it, or failing while `failing` is set.
+fn start_endpoint<T: serde::de::DeserializeOwned + Send + 'static>(
+    received: Arc<Mutex<Vec<T>>>,
+    failing: Arc<AtomicBool>,
+) -> String {
+    let route = warp::post().and(warp::body::json()).map(move |batch: T| {
+        if failing.load(Ordering::SeqCst) {
+            return StatusCode::INTERNAL_SERVER_ERROR;
+        }
+        received.lock().push(batch);
+        StatusCode::OK
+    });
+    let (address, future) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(future);
+    format!("http://{}/", address)
+}
+
+#[test]
+fn test_opt_out() {
+    let mut config = TelemetryConfig::default();
+    assert!(!is_opted_out(&config));
+    config.enabled = false;
+    assert!(is_opted_out(&config));
+}
+
+#[test]
+fn test_payload_schema() {
+    let mut batch = MetricBatch::new(
+        "id".into(),
+        &NodeConfig::default(),
+        ChainId::test(),
+        "rev".into(),
+    );
+    batch.samples.push(sample(1));
+    let value = serde_json::to_value(&batch).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "schema_version": SCHEMA_VERSION,
+            "node_id": "id",
+            "chain_id": 4,
+            "role": "validator",
+            "build_version": "rev",
+            "samples": [{
+                "timestamp_usecs": 1,
+                "synced_version": 10,
+                "peer_count": 3,
+                "reachability": {"Public": "reachable"},
+            }],
+        })
+    );
+}
+
+#[test]
+fn test_node_id_persisted() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let path = dir.path().join(NODE_ID_FILE);
+
+    // The id is random, and kept across restarts
+    let node_id = load_or_create_node_id(&path).unwrap();
+    assert_eq!(load_or_create_node_id(&path).unwrap(), node_id);
+    let other_dir = TempPath::new();
+    other_dir.create_as_dir().unwrap();
+    assert_ne!(
+        load_or_create_node_id(&other_dir.path().join(NODE_ID_FILE)).unwrap(),
+        node_id
+    );
+
+    // An invalid id is replaced
+    std::fs::write(&path, "invalid").unwrap();
+    let new_node_id = load_or_create_node_id(&path).unwrap();
+    assert_ne!(new_node_id, node_id);
+    assert_eq!(load_or_create_node_id(&path).unwrap(), new_node_id);
+}
+
+#[tokio::test]
+async fn test_buffer_until_pushed() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let failing = Arc::new(AtomicBool::new(true));
+    let endpoint = start_endpoint(received.clone(), failing.clone());
+    let config = TelemetryConfig {
+        max_buffered_samples: 3,
+        ..TelemetryConfig::default()
+    };
+    let header = MetricBatch::new(
+        "id".into(),
+        &NodeConfig::default(),
+        ChainId::test(),
+        "rev".into(),
+    );
+    let mut pusher = MetricPusher::new(endpoint, header.clone(), &config);
+
+    // Samples are kept while the endpoint fails, dropping the oldest ones
+    for timestamp_usecs in 0..5 {
+        pusher.buffer(sample(timestamp_usecs));
+        assert!(pusher.push().await.is_err());
+    }
+    assert_eq!(pusher.buffered_samples(), 3);
+    assert!(received.lock().is_empty());
+
+    failing.store(false, Ordering::SeqCst);
+    pusher.push().await.unwrap();
+    assert_eq!(pusher.buffered_samples(), 0);
+    assert_eq!(
+        *received.lock(),
+        vec![MetricBatch {
+            samples: vec![sample(2), sample(3), sample(4)],
+            ..header
+        }]
+    );
+
+    // Nothing is pushed without samples
+    pusher.push().await.unwrap();
+    assert_eq!(received.lock().len(), 1);
+}
+
+#[tokio::test]
+async fn test_upload_crash_dumps() {
+    let received = Arc::new(Mutex::new(Vec::<CrashReport>::new()));
+    let failing = Arc::new(AtomicBool::new(true));
+    let endpoint = start_endpoint(received.clone(), failing.clone());
+    let header = MetricBatch::new(
+        "id".into(),
+        &NodeConfig::default(),
+        ChainId::test(),
+        "rev".into(),
+    );
+    let client = reqwest::Client::new();
+    let timeout = Duration::from_secs(10);
+
+    let dir = TempPath::new();
+    let dump = CrashDump {
+        timestamp_secs: 1,
+        thread_name: None,
+        details: "panicked".into(),
+        backtrace: "backtrace".into(),
+        metadata: BTreeMap::new(),
+        recent_logs: vec!["dialing 127.0.0.1:6180".into()],
+    };
+    crash_handler::write_crash_dump(dir.path(), &dump).unwrap();
+
+    // Dumps are kept until uploaded
+    assert!(
+        upload_crash_dumps(&client, &endpoint, timeout, &header, dir.path())
+            .await
+            .is_err()
+    );
+    failing.store(false, Ordering::SeqCst);
+    assert_eq!(
+        upload_crash_dumps(&client, &endpoint, timeout, &header, dir.path())
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        upload_crash_dumps(&client, &endpoint, timeout, &header, dir.path())
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        *received.lock(),
+        vec![CrashReport {
+            schema_version: SCHEMA_VERSION,
+            node_id: header.node_id,
+            chain_id: header.chain_id,
+            dump: CrashDump {
+                recent_logs: vec![format!("dialing {}", REDACTED)],
+                ..dump
+            },
+        }]
+    );
+}
+
+#[test]
+fn test_scrub() {
+    let key = "ab".repeat(32);
+    let log = json!({
+        "message": format!("Dialing /ip4/10.0.0.1/tcp/6180/noise-ik/0x{}/handshake/0", key),
+        "remote_peer": key,
+        "addr": "192.168.1.1:6180",
+        "local_addr": "[::1]:6180",
+        "timestamp": "2022-01-01T12:34:56.789Z",
+    })
+    .to_string();
+    assert_eq!(
+        scrub(&log),
+        json!({
+            "message": format!("Dialing {}", REDACTED),
+            "remote_peer": REDACTED,
+            "addr": REDACTED,
+            "local_addr": REDACTED,
+            "timestamp": "2022-01-01T12:34:56.789Z",
+        })
+        .to_string()
+    );
+}
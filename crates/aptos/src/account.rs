@@ -0,0 +1,243 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands managing the accounts of profiles.
+//!
+//! `aptos account create` generates the key of an account in a profile, and creates the account
+//! on-chain through the faucet of the network when there is one. The other commands then operate
+//! on the account of a profile through the REST API of its network.
+
+use crate::config::{CliConfig, ProfileConfig, ProfileOptions};
+use anyhow::{anyhow, bail, Result};
+use aptos_rest_client::{Client, FaucetClient};
+use aptos_sdk::{
+    crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+        PrivateKey, Uniform, ValidCryptoMaterialStringExt,
+    },
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{
+        account_address::AccountAddress, chain_id::ChainId,
+        transaction::authenticator::AuthenticationKey, AccountKey, LocalAccount,
+    },
+};
+use rand::rngs::OsRng;
+use std::future::Future;
+use structopt::StructOpt;
+use url::Url;
+
+#[derive(Debug, StructOpt)]
+pub enum AccountTool {
+    #[structopt(about = "Creates an account and the profile holding its key")]
+    Create(CreateAccount),
+    #[structopt(about = "Funds the account of a profile with coins from the faucet")]
+    FundWithFaucet(FundWithFaucet),
+    #[structopt(about = "Transfers coins from the account of a profile to another account")]
+    Transfer(Transfer),
+    #[structopt(about = "Lists the resources of an account")]
+    List(ListResources),
+}
+
+impl AccountTool {
+    pub fn execute(self) -> Result<()> {
+        match self {
+            AccountTool::Create(tool) => tool.execute(),
+            AccountTool::FundWithFaucet(tool) => tool.execute(),
+            AccountTool::Transfer(tool) => tool.execute(),
+            AccountTool::List(tool) => tool.execute(),
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct CreateAccount {
+    #[structopt(flatten)]
+    profile_options: ProfileOptions,
+    /// REST endpoint of the network of the account
+    #[structopt(long, default_value = "http://localhost:8080")]
+    rest_url: Url,
+    /// Faucet endpoint of the network, creating the account on-chain. Without a faucet, the
+    /// account has to be created by the owner of an existing account.
+    #[structopt(long)]
+    faucet_url: Option<Url>,
+    /// Private key of the account as a hex string, a new key is generated otherwise
+    #[structopt(long, parse(try_from_str = Ed25519PrivateKey::from_encoded_string))]
+    private_key: Option<Ed25519PrivateKey>,
+    /// Replaces the profile if it already exists
+    #[structopt(long)]
+    force: bool,
+}
+
+impl CreateAccount {
+    pub fn execute(self) -> Result<()> {
+        let config_path = self.profile_options.config_path()?;
+        let mut config = CliConfig::load(&config_path)?;
+        let name = self.profile_options.profile.clone();
+        if config.profiles.contains_key(&name) && !self.force {
+            bail!(
+                "Profile '{}' already exists, pass --force to replace it",
+                name
+            );
+        }
+
+        let private_key = self
+            .private_key
+            .unwrap_or_else(|| Ed25519PrivateKey::generate(&mut OsRng));
+        let public_key = private_key.public_key();
+        let account = AuthenticationKey::ed25519(&public_key).derived_address();
+
+        // The profile is saved first, so that the key isn't lost if the creation fails
+        config.profiles.insert(
+            name.clone(),
+            ProfileConfig {
+                private_key,
+                public_key: public_key.clone(),
+                account,
+                rest_url: self.rest_url.to_string(),
+                faucet_url: self.faucet_url.as_ref().map(Url::to_string),
+            },
+        );
+        config.save(&config_path)?;
+        println!("Saved account {} in profile '{}'", account, name);
+
+        if let Some(faucet_url) = self.faucet_url {
+            faucet_client(faucet_url.as_str(), self.rest_url.as_str())
+                .create_account(public_key)?;
+            println!("Created account {} on-chain", account);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct FundWithFaucet {
+    #[structopt(flatten)]
+    profile_options: ProfileOptions,
+    /// Number of coins to mint
+    #[structopt(long)]
+    amount: u64,
+    /// Faucet endpoint, overriding the one of the profile
+    #[structopt(long)]
+    faucet_url: Option<Url>,
+    /// Public key of the account to fund, instead of the account of the profile
+    #[structopt(long, parse(try_from_str = Ed25519PublicKey::from_encoded_string))]
+    public_key: Option<Ed25519PublicKey>,
+}
+
+impl FundWithFaucet {
+    pub fn execute(self) -> Result<()> {
+        let profile = self.profile_options.load_profile()?;
+        let faucet_url = match (self.faucet_url, profile.faucet_url) {
+            (Some(faucet_url), _) => faucet_url.to_string(),
+            (None, Some(faucet_url)) => faucet_url,
+            (None, None) => bail!(
+                "Profile '{}' has no faucet, pass --faucet-url",
+                self.profile_options.profile
+            ),
+        };
+        let public_key = self.public_key.unwrap_or(profile.public_key);
+        let account = AuthenticationKey::ed25519(&public_key).derived_address();
+
+        // The faucet creates the account if it doesn't exist yet
+        faucet_client(&faucet_url, &profile.rest_url).fund(public_key, self.amount)?;
+        println!("Funded account {} with {} coins", account, self.amount);
+        Ok(())
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct Transfer {
+    #[structopt(flatten)]
+    profile_options: ProfileOptions,
+    /// Account receiving the coins
+    #[structopt(long)]
+    to: AccountAddress,
+    /// Number of coins to transfer
+    #[structopt(long)]
+    amount: u64,
+    /// Maximum amount of gas units the transaction may use
+    #[structopt(long, default_value = "1000000")]
+    max_gas_amount: u64,
+    /// Price of a gas unit
+    #[structopt(long, default_value = "1")]
+    gas_unit_price: u64,
+}
+
+impl Transfer {
+    pub fn execute(self) -> Result<()> {
+        let ProfileConfig {
+            private_key,
+            account,
+            rest_url,
+            ..
+        } = self.profile_options.load_profile()?;
+        let client = rest_client(&rest_url)?;
+        let payload = aptos_stdlib::encode_transfer_script_function(self.to, self.amount);
+        let (max_gas_amount, gas_unit_price) = (self.max_gas_amount, self.gas_unit_price);
+
+        let transaction = block_on(async move {
+            // The chain id and the sequence number of the sender are read from the network
+            let sender = client.get_account(account).await?;
+            let chain_id = ChainId::new(sender.state().chain_id);
+            let sender = LocalAccount::new(
+                account,
+                AccountKey::from_private_key(private_key),
+                sender.inner().sequence_number,
+            );
+
+            let factory = TransactionFactory::new(chain_id)
+                .with_max_gas_amount(max_gas_amount)
+                .with_gas_unit_price(gas_unit_price);
+            let txn = sender.sign_with_transaction_builder(factory.payload(payload));
+            client.submit_and_wait(&txn).await
+        })??
+        .into_inner();
+
+        let info = transaction.transaction_info()?;
+        println!(
+            "Transferred {} coins from {} to {} in transaction {} at version {}",
+            self.amount, account, self.to, info.hash, info.version
+        );
+        Ok(())
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct ListResources {
+    #[structopt(flatten)]
+    profile_options: ProfileOptions,
+    /// Account whose resources are listed, instead of the account of the profile
+    #[structopt(long)]
+    account: Option<AccountAddress>,
+}
+
+impl ListResources {
+    pub fn execute(self) -> Result<()> {
+        let profile = self.profile_options.load_profile()?;
+        let client = rest_client(&profile.rest_url)?;
+        let account = self.account.unwrap_or(profile.account);
+
+        let resources = block_on(client.get_account_resources(account))??.into_inner();
+        let resources: serde_json::Map<_, _> = resources
+            .into_iter()
+            .map(|resource| (resource.resource_type.to_string(), resource.data))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&resources)?);
+        Ok(())
+    }
+}
+
+pub(crate) fn rest_client(rest_url: &str) -> Result<Client> {
+    let url = Url::parse(rest_url).map_err(|e| anyhow!("Invalid REST url {}: {}", rest_url, e))?;
+    Ok(Client::new(url))
+}
+
+fn faucet_client(faucet_url: &str, rest_url: &str) -> FaucetClient {
+    FaucetClient::new(faucet_url.to_string(), rest_url.to_string())
+}
+
+/// Runs a future of the REST client. The faucet client blocks on its own runtime, so it must not
+/// be called from here.
+pub(crate) fn block_on<F: Future>(future: F) -> Result<F::Output> {
+    Ok(tokio::runtime::Runtime::new()?.block_on(future))
+}
@@ -0,0 +1,229 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Profiles of the `aptos` tool, stored in `~/.aptos/config.yaml`.
+//!
+//! A profile holds the key of an account together with the endpoints of the network it lives on,
+//! so that commands only need the name of the profile to operate on the account.
+
+use anyhow::{anyhow, bail, Context, Result};
+use aptos_sdk::{
+    crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    types::account_address::AccountAddress,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+
+/// The directory of the config, in the home directory of the user
+const CONFIG_DIR: &str = ".aptos";
+const CONFIG_FILE: &str = "config.yaml";
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CliConfig {
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProfileConfig {
+    pub private_key: Ed25519PrivateKey,
+    pub public_key: Ed25519PublicKey,
+    pub account: AccountAddress,
+    pub rest_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub faucet_url: Option<String>,
+}
+
+impl CliConfig {
+    /// Loads the config, which is empty if the file doesn't exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config {:?}", path))?;
+        serde_yaml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config {:?}", path))
+    }
+
+    /// Saves the config, which is only readable by the user as it holds private keys
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = serde_yaml::to_string(self)?;
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options
+            .open(path)
+            .with_context(|| format!("Failed to write config {:?}", path))?;
+        // The mode only applies to new files, an existing config keeps its permissions otherwise
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))
+                .with_context(|| format!("Failed to set the permissions of config {:?}", path))?;
+        }
+        file.write_all(contents.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn profile(&self, name: &str) -> Result<&ProfileConfig> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| anyhow!("Profile '{}' doesn't exist", name))
+    }
+
+    pub fn take_profile(mut self, name: &str) -> Result<ProfileConfig> {
+        self.profiles
+            .remove(name)
+            .ok_or_else(|| anyhow!("Profile '{}' doesn't exist", name))
+    }
+}
+
+/// Selects the profile a command operates on
+#[derive(Debug, StructOpt)]
+pub struct ProfileOptions {
+    /// Name of the profile
+    #[structopt(long, default_value = DEFAULT_PROFILE)]
+    pub profile: String,
+    /// Path of the config holding the profiles, `~/.aptos/config.yaml` by default
+    #[structopt(long, parse(from_os_str))]
+    pub config: Option<PathBuf>,
+}
+
+impl ProfileOptions {
+    pub fn config_path(&self) -> Result<PathBuf> {
+        if let Some(path) = &self.config {
+            return Ok(path.clone());
+        }
+        match std::env::var_os("HOME") {
+            Some(home) => Ok(PathBuf::from(home).join(CONFIG_DIR).join(CONFIG_FILE)),
+            None => bail!("Unable to find the home directory, pass --config instead"),
+        }
+    }
+
+    pub fn load_config(&self) -> Result<CliConfig> {
+        CliConfig::load(&self.config_path()?)
+    }
+
+    pub fn load_profile(&self) -> Result<ProfileConfig> {
+        self.load_config()?.take_profile(&self.profile)
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub enum ConfigTool {
+    #[structopt(about = "Shows the profiles, without their private keys")]
+    ShowProfiles(ShowProfiles),
+}
+
+impl ConfigTool {
+    pub fn execute(self) -> Result<()> {
+        match self {
+            ConfigTool::ShowProfiles(tool) => tool.execute(),
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct ShowProfiles {
+    #[structopt(flatten)]
+    profile_options: ProfileOptions,
+}
+
+/// A profile as shown to the user
+#[derive(Debug, Serialize)]
+struct ProfileSummary<'a> {
+    public_key: &'a Ed25519PublicKey,
+    account: &'a AccountAddress,
+    rest_url: &'a str,
+    faucet_url: Option<&'a str>,
+}
+
+impl ShowProfiles {
+    pub fn execute(self) -> Result<()> {
+        let config = self.profile_options.load_config()?;
+        let profiles: BTreeMap<_, _> = config
+            .profiles
+            .iter()
+            .map(|(name, profile)| {
+                let summary = ProfileSummary {
+                    public_key: &profile.public_key,
+                    account: &profile.account,
+                    rest_url: &profile.rest_url,
+                    faucet_url: profile.faucet_url.as_deref(),
+                };
+                (name, summary)
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&profiles)?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::{crypto::Uniform, types::transaction::authenticator::AuthenticationKey};
+    use aptos_temppath::TempPath;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_save_and_load() {
+        let path = TempPath::new();
+        let path = path.path().join(CONFIG_DIR).join(CONFIG_FILE);
+        assert!(CliConfig::load(&path).unwrap().profiles.is_empty());
+
+        let private_key = Ed25519PrivateKey::generate(&mut StdRng::from_seed([0; 32]));
+        let public_key = Ed25519PublicKey::from(&private_key);
+        let account = AuthenticationKey::ed25519(&public_key).derived_address();
+        let mut config = CliConfig::default();
+        config.profiles.insert(
+            DEFAULT_PROFILE.into(),
+            ProfileConfig {
+                private_key,
+                public_key: public_key.clone(),
+                account,
+                rest_url: "http://localhost:8080".into(),
+                faucet_url: None,
+            },
+        );
+        config.save(&path).unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+
+            // The permissions of an existing config are restricted as well
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+            config.save(&path).unwrap();
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let loaded = CliConfig::load(&path).unwrap();
+        let profile = loaded.profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(profile.public_key, public_key);
+        assert_eq!(
+            Ed25519PublicKey::from(&profile.private_key),
+            profile.public_key
+        );
+        assert_eq!(profile.account, account);
+        assert!(loaded.profile("other").is_err());
+    }
+}
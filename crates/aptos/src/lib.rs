@@ -0,0 +1,36 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+pub mod account;
+pub mod config;
+pub mod move_tool;
+pub mod node;
+
+use structopt::StructOpt;
+
+/// The commands of the `aptos` tool, grouped by what they operate on
+#[derive(Debug, StructOpt)]
+#[structopt(name = "aptos", about = "Command line tool for interacting with Aptos")]
+pub enum Tool {
+    #[structopt(about = "Creates and operates on accounts")]
+    Account(account::AccountTool),
+    #[structopt(about = "Manages the profiles of the tool")]
+    Config(config::ConfigTool),
+    #[structopt(about = "Publishes Move packages")]
+    Move(move_tool::MoveTool),
+    #[structopt(about = "Runs and operates Aptos nodes")]
+    Node(node::NodeTool),
+}
+
+impl Tool {
+    pub fn execute(self) -> anyhow::Result<()> {
+        match self {
+            Tool::Account(tool) => tool.execute(),
+            Tool::Config(tool) => tool.execute(),
+            Tool::Move(tool) => tool.execute(),
+            Tool::Node(tool) => tool.execute(),
+        }
+    }
+}
@@ -0,0 +1,14 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use aptos::Tool;
+use structopt::StructOpt;
+
+fn main() {
+    if let Err(err) = Tool::from_args().execute() {
+        println!("Operation unsuccessful: {}", err);
+        std::process::exit(1);
+    }
+}
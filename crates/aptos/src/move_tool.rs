@@ -0,0 +1,452 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands publishing Move packages.
+//!
+//! `aptos move publish` compiles a package and publishes its modules under the account of a
+//! profile, and `aptos move upgrade` replaces modules the account already published. The gas of
+//! transactions is estimated by simulating them first. Once the modules are published, the metadata
+//! of the package is recorded on-chain in the `PackageRegistry` of the account, and in the build
+//! directory of the package.
+
+use crate::{
+    account::{block_on, rest_client},
+    config::{ProfileConfig, ProfileOptions},
+};
+use anyhow::{anyhow, bail, Context, Result};
+use aptos_rest_client::{Client, Transaction};
+use aptos_sdk::{
+    crypto::HashValue,
+    move_types::{
+        ident_str,
+        language_storage::{ModuleId, CORE_CODE_ADDRESS},
+    },
+    transaction_builder::TransactionFactory,
+    types::{
+        account_address::AccountAddress,
+        chain_id::ChainId,
+        transaction::{ModuleBundle, ScriptFunction, TransactionPayload},
+        AccountKey, LocalAccount,
+    },
+};
+use move_binary_format::{access::ModuleAccess, CompiledModule};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    str::FromStr,
+};
+use structopt::StructOpt;
+
+/// The metadata of the last publication of a package, in its build directory
+const METADATA_FILE: &str = "package-metadata.json";
+/// Margin applied on the gas used by the simulation, as the state may change before the
+/// transaction is executed
+const GAS_ESTIMATE_MULTIPLIER: f64 = 1.5;
+
+#[derive(Debug, StructOpt)]
+pub enum MoveTool {
+    #[structopt(about = "Publishes the modules of a package under the account of a profile")]
+    Publish(PublishPackage),
+    #[structopt(about = "Upgrades the modules of a package published by the account of a profile")]
+    Upgrade(UpgradePackage),
+}
+
+impl MoveTool {
+    pub fn execute(self) -> Result<()> {
+        match self {
+            MoveTool::Publish(tool) => tool.options.execute(PublishMode::Publish),
+            MoveTool::Upgrade(tool) => tool.options.execute(PublishMode::Upgrade),
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct PublishPackage {
+    #[structopt(flatten)]
+    options: PackageOptions,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct UpgradePackage {
+    #[structopt(flatten)]
+    options: PackageOptions,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct PackageOptions {
+    #[structopt(flatten)]
+    profile_options: ProfileOptions,
+    /// Directory of the package, holding its `Move.toml`
+    #[structopt(long, parse(from_os_str), default_value = ".")]
+    package_dir: PathBuf,
+    /// Addresses of named addresses left unassigned by the package, e.g. `Sender=0x1234`
+    #[structopt(long, use_delimiter = true, parse(try_from_str = parse_named_address))]
+    named_addresses: Vec<(String, AccountAddress)>,
+    /// Maximum amount of gas units the transaction may use, including the estimation margin
+    #[structopt(long, default_value = "1000000")]
+    max_gas: u64,
+    /// Price of a gas unit
+    #[structopt(long, default_value = "1")]
+    gas_unit_price: u64,
+    /// Checks that the bytecode on-chain is the bytecode of the package once it's published
+    #[structopt(long)]
+    verify: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum PublishMode {
+    /// None of the modules may exist on-chain
+    Publish,
+    /// Some of the modules have to exist on-chain, and are replaced
+    Upgrade,
+}
+
+/// The metadata of a package published on-chain
+#[derive(Debug, Serialize)]
+struct PackageMetadata {
+    name: String,
+    source_digest: Option<String>,
+    account: AccountAddress,
+    modules: Vec<String>,
+    /// SHA3-256 of the bytecode of each module
+    bytecode_hashes: BTreeMap<String, HashValue>,
+    transaction_hash: String,
+    version: u64,
+    gas_used: u64,
+    /// The transaction recording the metadata in the `PackageRegistry` of the account
+    metadata_transaction_hash: String,
+}
+
+impl PackageOptions {
+    fn execute(self, mode: PublishMode) -> Result<()> {
+        let ProfileConfig {
+            private_key,
+            account,
+            rest_url,
+            ..
+        } = self.profile_options.load_profile()?;
+        let client = rest_client(&rest_url)?;
+
+        let build_config = move_package::BuildConfig {
+            additional_named_addresses: self.named_addresses.iter().cloned().collect(),
+            ..Default::default()
+        };
+        let package = build_config
+            .compile_package(&self.package_dir, &mut std::io::stderr())
+            .with_context(|| format!("Failed to compile package {:?}", self.package_dir))?;
+        let package_name = package.compiled_package_info.package_name.to_string();
+
+        // The modules of a bundle are published in order, so dependencies come first
+        let modules: Vec<CompiledModule> = package
+            .compiled_modules()
+            .compute_dependency_order()?
+            .into_iter()
+            .cloned()
+            .collect();
+        if modules.is_empty() {
+            bail!("Package '{}' has no modules", package_name);
+        }
+        let mut names = vec![];
+        let mut blobs = vec![];
+        for module in &modules {
+            let id = module.self_id();
+            if *id.address() != account {
+                bail!(
+                    "Module {} is not at the address of the profile {}, pass --named-addresses \
+                     to set the address of the package",
+                    id,
+                    account
+                );
+            }
+            let mut blob = vec![];
+            module.serialize(&mut blob)?;
+            names.push(id.name().to_string());
+            blobs.push(blob);
+        }
+
+        let source_digest = package
+            .compiled_package_info
+            .source_digest
+            .map(|digest| digest.to_string());
+        let bytecode_hashes: BTreeMap<_, _> = names
+            .iter()
+            .cloned()
+            .zip(blobs.iter().map(|blob| HashValue::sha3_256_of(blob)))
+            .collect();
+
+        let (max_gas, gas_unit_price, verify) = (self.max_gas, self.gas_unit_price, self.verify);
+        let payload = TransactionPayload::ModuleBundle(ModuleBundle::new(blobs.clone()));
+        let metadata_payload = package_metadata_payload(
+            &package_name,
+            source_digest.as_deref(),
+            &names,
+            &bytecode_hashes,
+        )?;
+        let (transaction, metadata_transaction) = block_on(async {
+            // Nothing is published if the metadata can't be recorded afterwards
+            if !published_modules(&client, CORE_CODE_ADDRESS)
+                .await?
+                .contains_key("PackageRegistry")
+            {
+                bail!("The framework of the node has no PackageRegistry to record the package in");
+            }
+            let existing = published_modules(&client, account).await?;
+            check_existing_modules(mode, &names, &existing)?;
+
+            let sender = client.get_account(account).await?;
+            let chain_id = ChainId::new(sender.state().chain_id);
+            let mut sender = LocalAccount::new(
+                account,
+                AccountKey::from_private_key(private_key),
+                sender.inner().sequence_number,
+            );
+            let factory = TransactionFactory::new(chain_id).with_gas_unit_price(gas_unit_price);
+
+            let transaction =
+                simulate_and_submit(&client, &mut sender, &factory, payload, max_gas).await?;
+            if verify {
+                verify_published_modules(&client, account, &blobs).await?;
+            }
+            // The metadata can only be recorded once the modules it describes are published
+            let metadata_transaction =
+                simulate_and_submit(&client, &mut sender, &factory, metadata_payload, max_gas)
+                    .await?;
+            Ok::<_, anyhow::Error>((transaction, metadata_transaction))
+        })??;
+
+        let info = transaction.transaction_info()?;
+        let metadata = PackageMetadata {
+            name: package_name,
+            source_digest,
+            account,
+            bytecode_hashes,
+            modules: names,
+            transaction_hash: info.hash.to_string(),
+            version: info.version.into(),
+            gas_used: info.gas_used.into(),
+            metadata_transaction_hash: metadata_transaction.transaction_info()?.hash.to_string(),
+        };
+        let metadata_path = self
+            .package_dir
+            .join("build")
+            .join(&metadata.name)
+            .join(METADATA_FILE);
+        let metadata = serde_json::to_string_pretty(&metadata)?;
+        std::fs::write(&metadata_path, &metadata)
+            .with_context(|| format!("Failed to write package metadata {:?}", metadata_path))?;
+        println!("{}", metadata);
+        if verify {
+            println!("Verified the bytecode of the modules on-chain");
+        }
+        Ok(())
+    }
+}
+
+fn parse_named_address(s: &str) -> Result<(String, AccountAddress)> {
+    let (name, address) = s
+        .split_once('=')
+        .ok_or_else(|| anyhow!("Invalid named address '{}', expected name=address", s))?;
+    let address = AccountAddress::from_hex_literal(address)
+        .or_else(|_| AccountAddress::from_str(address))
+        .map_err(|e| anyhow!("Invalid address of '{}': {}", name, e))?;
+    Ok((name.to_string(), address))
+}
+
+/// The transaction recording the metadata of a package in the `PackageRegistry` of its account
+fn package_metadata_payload(
+    name: &str,
+    source_digest: Option<&str>,
+    modules: &[String],
+    bytecode_hashes: &BTreeMap<String, HashValue>,
+) -> Result<TransactionPayload> {
+    // The hashes are in the order of the modules, as the registry expects
+    let hashes: Vec<Vec<u8>> = modules
+        .iter()
+        .map(|name| bytecode_hashes[name].to_vec())
+        .collect();
+    let modules: Vec<&[u8]> = modules.iter().map(|name| name.as_bytes()).collect();
+    Ok(TransactionPayload::ScriptFunction(ScriptFunction::new(
+        ModuleId::new(CORE_CODE_ADDRESS, ident_str!("PackageRegistry").to_owned()),
+        ident_str!("publish_package_metadata").to_owned(),
+        vec![],
+        vec![
+            bcs::to_bytes(name.as_bytes())?,
+            bcs::to_bytes(source_digest.unwrap_or_default().as_bytes())?,
+            bcs::to_bytes(&modules)?,
+            bcs::to_bytes(&hashes)?,
+        ],
+    )))
+}
+
+/// Simulates the transaction to estimate its gas, then submits it with a margin over the gas used
+/// and waits for it to be committed
+async fn simulate_and_submit(
+    client: &Client,
+    sender: &mut LocalAccount,
+    factory: &TransactionFactory,
+    payload: TransactionPayload,
+    max_gas: u64,
+) -> Result<Transaction> {
+    let sign = |sender: &LocalAccount, max_gas_amount| {
+        sender.sign_transaction(
+            factory
+                .payload(payload.clone())
+                .sender(sender.address())
+                .sequence_number(sender.sequence_number())
+                .max_gas_amount(max_gas_amount)
+                .build(),
+        )
+    };
+
+    let simulated = client.simulate(&sign(sender, max_gas)).await?.into_inner();
+    if !simulated.success() {
+        bail!("Simulation failed: {}", simulated.vm_status());
+    }
+    let gas_used = simulated.transaction_info()?.gas_used.into();
+    let max_gas_amount = estimate_max_gas(gas_used, max_gas);
+    println!(
+        "Simulation used {} gas units, submitting with a maximum of {}",
+        gas_used, max_gas_amount
+    );
+
+    let transaction = client
+        .submit_and_wait(&sign(sender, max_gas_amount))
+        .await?
+        .into_inner();
+    *sender.sequence_number_mut() += 1;
+    Ok(transaction)
+}
+
+/// Returns the maximum gas of the transaction, with a margin over the gas used by the simulation
+/// bounded by the given maximum. The simulation ran with that maximum, so it used less.
+fn estimate_max_gas(gas_used: u64, max_gas: u64) -> u64 {
+    let estimate = (gas_used as f64 * GAS_ESTIMATE_MULTIPLIER).ceil() as u64;
+    estimate.min(max_gas)
+}
+
+/// The modules published on-chain by the account, keyed by name
+async fn published_modules(
+    client: &Client,
+    account: AccountAddress,
+) -> Result<BTreeMap<String, Vec<u8>>> {
+    client
+        .get_account_modules(account)
+        .await?
+        .into_inner()
+        .into_iter()
+        .map(|module| {
+            let bytecode: Vec<u8> = module.bytecode.into();
+            let name = CompiledModule::deserialize(&bytecode)
+                .map_err(|e| anyhow!("Invalid module on-chain: {:?}", e))?
+                .self_id()
+                .name()
+                .to_string();
+            Ok((name, bytecode))
+        })
+        .collect()
+}
+
+fn check_existing_modules(
+    mode: PublishMode,
+    names: &[String],
+    existing: &BTreeMap<String, Vec<u8>>,
+) -> Result<()> {
+    let existing: BTreeSet<_> = names
+        .iter()
+        .filter(|name| existing.contains_key(*name))
+        .collect();
+    match mode {
+        PublishMode::Publish if !existing.is_empty() => bail!(
+            "Modules {:?} are already published, use `aptos move upgrade` to replace them",
+            existing
+        ),
+        PublishMode::Upgrade if existing.is_empty() => {
+            bail!("None of the modules are published yet, use `aptos move publish` to publish them")
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn verify_published_modules(
+    client: &Client,
+    account: AccountAddress,
+    blobs: &[Vec<u8>],
+) -> Result<()> {
+    let on_chain: BTreeSet<_> = published_modules(client, account)
+        .await?
+        .into_iter()
+        .map(|(_, bytecode)| bytecode)
+        .collect();
+    for blob in blobs {
+        if !on_chain.contains(blob) {
+            let name = CompiledModule::deserialize(blob)
+                .map(|module| module.self_id().name().to_string())
+                .unwrap_or_default();
+            bail!(
+                "The bytecode of module {} on-chain differs from the package",
+                name
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_named_address() {
+        let (name, address) = parse_named_address("Sender=0x1234").unwrap();
+        assert_eq!(name, "Sender");
+        assert_eq!(address, AccountAddress::from_hex_literal("0x1234").unwrap());
+        assert!(parse_named_address("Sender").is_err());
+        assert!(parse_named_address("Sender=not_an_address").is_err());
+    }
+
+    #[test]
+    fn test_estimate_max_gas() {
+        assert_eq!(estimate_max_gas(100, 1000), 150);
+        assert_eq!(estimate_max_gas(101, 1000), 152);
+        // The margin is bounded by the maximum
+        assert_eq!(estimate_max_gas(100, 120), 120);
+    }
+
+    #[test]
+    fn test_check_existing_modules() {
+        let names = vec!["A".to_string(), "B".to_string()];
+        let mut existing = BTreeMap::new();
+        check_existing_modules(PublishMode::Publish, &names, &existing).unwrap();
+        check_existing_modules(PublishMode::Upgrade, &names, &existing).unwrap_err();
+
+        existing.insert("A".to_string(), vec![]);
+        check_existing_modules(PublishMode::Publish, &names, &existing).unwrap_err();
+        check_existing_modules(PublishMode::Upgrade, &names, &existing).unwrap();
+    }
+
+    #[test]
+    fn test_package_metadata_payload() {
+        let names = vec!["B".to_string(), "A".to_string()];
+        let hashes: BTreeMap<_, _> = names
+            .iter()
+            .map(|name| (name.clone(), HashValue::sha3_256_of(name.as_bytes())))
+            .collect();
+        let function = match package_metadata_payload("Pkg", None, &names, &hashes).unwrap() {
+            TransactionPayload::ScriptFunction(function) => function,
+            payload => panic!("Unexpected payload {:?}", payload),
+        };
+        assert_eq!(function.module().name().as_str(), "PackageRegistry");
+        assert_eq!(function.function().as_str(), "publish_package_metadata");
+
+        let args = function.args();
+        assert_eq!(bcs::from_bytes::<Vec<u8>>(&args[0]).unwrap(), b"Pkg");
+        assert!(bcs::from_bytes::<Vec<u8>>(&args[1]).unwrap().is_empty());
+        let modules: Vec<Vec<u8>> = bcs::from_bytes(&args[2]).unwrap();
+        assert_eq!(modules, vec![b"B".to_vec(), b"A".to_vec()]);
+        // The hashes are in the order of the modules, not sorted by name
+        let module_hashes: Vec<Vec<u8>> = bcs::from_bytes(&args[3]).unwrap();
+        assert_eq!(module_hashes[0], hashes["B"].to_vec());
+        assert_eq!(module_hashes[1], hashes["A"].to_vec());
+    }
+}
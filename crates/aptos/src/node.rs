@@ -0,0 +1,197 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands running Aptos nodes.
+//!
+//! `aptos node run-local-testnet` runs a single validator network for development, together with
+//! a faucet minting coins from the aptos root account. The network is generated in the test
+//! directory on the first run and reused by later runs, so that accounts and transactions survive
+//! restarts.
+
+use anyhow::{anyhow, Context, Result};
+use aptos_config::config::NodeConfig;
+use aptos_faucet::Service;
+use aptos_genesis_tool::validator_builder::ValidatorBuilder;
+use aptos_sdk::types::{account_config::aptos_root_address, chain_id::ChainId, LocalAccount};
+use hex::FromHex;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+};
+use structopt::StructOpt;
+
+/// The key of the aptos root account, from which the faucet mints
+const MINT_KEY_FILE: &str = "mint.key";
+/// The config of the validator, written in the directory named after its index
+const NODE_CONFIG_FILE: &str = "0/node.yaml";
+const WAYPOINT_FILE: &str = "waypoint.txt";
+const LOG_FILE: &str = "validator.log";
+
+#[derive(Debug, StructOpt)]
+pub enum NodeTool {
+    #[structopt(about = "Runs a single validator testnet with a faucet")]
+    RunLocalTestnet(RunLocalTestnet),
+}
+
+impl NodeTool {
+    pub fn execute(self) -> Result<()> {
+        match self {
+            NodeTool::RunLocalTestnet(tool) => tool.execute(),
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct RunLocalTestnet {
+    /// Directory holding the configuration and the state of the testnet, which are reused when
+    /// the testnet is restarted
+    #[structopt(long, parse(from_os_str))]
+    test_dir: PathBuf,
+    /// RNG seed generating the keys of the testnet, when it is created
+    #[structopt(long, parse(try_from_str = FromHex::from_hex))]
+    seed: Option<[u8; 32]>,
+    /// Deletes the state in the test directory and creates a new testnet
+    #[structopt(long)]
+    force_restart: bool,
+    /// Address the faucet listens on. Anyone reaching the faucet can mint coins, so it's only
+    /// reachable from the host by default
+    #[structopt(long, default_value = "127.0.0.1")]
+    faucet_address: IpAddr,
+    /// Port of the faucet
+    #[structopt(long, default_value = "8081")]
+    faucet_port: u16,
+    /// Runs the testnet without a faucet
+    #[structopt(long)]
+    no_faucet: bool,
+}
+
+impl RunLocalTestnet {
+    pub fn execute(self) -> Result<()> {
+        let rng = self
+            .seed
+            .map(StdRng::from_seed)
+            .unwrap_or_else(StdRng::from_entropy);
+        let config = load_or_create_test_environment(&self.test_dir, self.force_restart, rng)?;
+        let test_dir = self.test_dir.canonicalize()?;
+
+        println!("Entering test mode, this should never be used in production!");
+        println!("\tTest directory: {:?}", test_dir);
+        println!("\tLog file: {:?}", test_dir.join(LOG_FILE));
+        println!("\tAptos root key path: {:?}", test_dir.join(MINT_KEY_FILE));
+        println!("\tChainId: {}", ChainId::test());
+        aptos_node::print_api_config(&config, false);
+
+        let faucet_address = SocketAddr::new(self.faucet_address, self.faucet_port);
+        if !self.no_faucet {
+            println!("\tFaucet endpoint: {}", faucet_address);
+            println!();
+        }
+        println!("Aptos is running, press ctrl-c to exit");
+        println!();
+
+        // The node blocks the thread it runs on
+        let node_config = config.clone();
+        let log_file = test_dir.join(LOG_FILE);
+        let node = thread::spawn(move || aptos_node::start(&node_config, None, Some(log_file)));
+
+        if self.no_faucet {
+            node.join().map_err(|_| anyhow!("The node panicked"))?;
+        } else {
+            let service = faucet_service(&test_dir, &config);
+            tokio::runtime::Runtime::new()?
+                .block_on(warp::serve(aptos_faucet::routes(service)).run(faucet_address));
+        }
+        Ok(())
+    }
+}
+
+/// Loads the config of the validator from the test directory, generating the testnet and its
+/// genesis first if the directory doesn't hold one or if it has to be restarted.
+fn load_or_create_test_environment<R>(
+    test_dir: &Path,
+    force_restart: bool,
+    rng: R,
+) -> Result<NodeConfig>
+where
+    R: ::rand::RngCore + ::rand::CryptoRng,
+{
+    let config_path = test_dir.join(NODE_CONFIG_FILE);
+    if force_restart && test_dir.exists() {
+        std::fs::remove_dir_all(test_dir)
+            .with_context(|| format!("Failed to delete test directory {:?}", test_dir))?;
+    }
+    if config_path.exists() {
+        return NodeConfig::load(&config_path)
+            .with_context(|| format!("Failed to load node config {:?}", config_path));
+    }
+
+    std::fs::create_dir_all(test_dir)?;
+    let mut template = NodeConfig::default_for_validator();
+    // Expose the REST API outside of the host, e.g. from containers
+    template.api.address.set_ip([0, 0, 0, 0].into());
+    let (root_keys, _genesis, genesis_waypoint, mut validators) = ValidatorBuilder::new(
+        test_dir,
+        aptos_framework_releases::current_module_blobs().to_vec(),
+    )
+    .template(template)
+    .build(rng)?;
+
+    generate_key::save_key(root_keys.root_key, test_dir.join(MINT_KEY_FILE));
+    std::fs::write(test_dir.join(WAYPOINT_FILE), genesis_waypoint.to_string())?;
+    Ok(validators.remove(0).config)
+}
+
+/// The faucet of the testnet, minting from the aptos root account through the REST API of the
+/// validator.
+fn faucet_service(test_dir: &Path, config: &NodeConfig) -> Arc<Service> {
+    let key = generate_key::load_key(test_dir.join(MINT_KEY_FILE));
+    // The faucet refreshes the sequence number of the account before minting
+    let faucet_account = LocalAccount::new(aptos_root_address(), key, 0);
+    let endpoint = format!("http://127.0.0.1:{}", config.api.address.port());
+    Arc::new(Service::new(
+        endpoint,
+        ChainId::test(),
+        faucet_account,
+        None,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+
+    fn validator_peer_id(config: &NodeConfig) -> String {
+        config
+            .validator_network
+            .as_ref()
+            .unwrap()
+            .peer_id()
+            .to_string()
+    }
+
+    #[test]
+    fn test_test_environment_persists_across_restarts() {
+        let test_dir = TempPath::new();
+        let test_dir = test_dir.path();
+
+        let config =
+            load_or_create_test_environment(test_dir, false, StdRng::from_seed([0; 32])).unwrap();
+        assert!(test_dir.join(MINT_KEY_FILE).exists());
+        assert!(test_dir.join(WAYPOINT_FILE).exists());
+
+        // The testnet is reused, even with another seed
+        let reloaded =
+            load_or_create_test_environment(test_dir, false, StdRng::from_seed([1; 32])).unwrap();
+        assert_eq!(validator_peer_id(&reloaded), validator_peer_id(&config));
+        assert_eq!(reloaded.storage.dir(), config.storage.dir());
+
+        // Restarting creates a new testnet
+        let restarted =
+            load_or_create_test_environment(test_dir, true, StdRng::from_seed([1; 32])).unwrap();
+        assert_ne!(validator_peer_id(&restarted), validator_peer_id(&config));
+    }
+}
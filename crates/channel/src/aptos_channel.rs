@@ -7,7 +7,7 @@
 //! over how the internal queueing in the channel happens and how we schedule messages
 //! to be sent out from this channel.
 //! Internally, it uses the `PerKeyQueue` to store messages
-use crate::message_queues::{PerKeyQueue, QueueStyle};
+use crate::message_queues::{KeyQueueConfig, PerKeyQueue, QueueStyle};
 use anyhow::{ensure, Result};
 use aptos_infallible::{Mutex, NonZeroUsize};
 use aptos_metrics::IntCounterVec;
@@ -16,6 +16,7 @@ use futures::{
     stream::{FusedStream, Stream},
 };
 use std::{
+    collections::HashMap,
     fmt::{Debug, Formatter},
     hash::Hash,
     pin::Pin,
@@ -235,11 +236,30 @@ pub fn new<K: Eq + Hash + Clone, M>(
     queue_style: QueueStyle,
     max_queue_size_per_key: usize,
     counters: Option<&'static IntCounterVec>,
+) -> (Sender<K, M>, Receiver<K, M>) {
+    new_with_key_queue_configs(
+        queue_style,
+        max_queue_size_per_key,
+        counters,
+        HashMap::new(),
+    )
+}
+
+/// Create a new Channel where the messages of the given keys are queued according
+/// to their own `KeyQueueConfig`, instead of the channel's default queue style and
+/// max queue size. Returns the two ends of the channel.
+pub fn new_with_key_queue_configs<K: Eq + Hash + Clone, M>(
+    queue_style: QueueStyle,
+    max_queue_size_per_key: usize,
+    counters: Option<&'static IntCounterVec>,
+    key_queue_configs: HashMap<K, KeyQueueConfig>,
 ) -> (Sender<K, M>, Receiver<K, M>) {
     let max_queue_size_per_key =
         NonZeroUsize!(max_queue_size_per_key, "aptos_channel cannot be of size 0");
+    let internal_queue = PerKeyQueue::new(queue_style, max_queue_size_per_key, counters)
+        .with_key_queue_configs(key_queue_configs);
     let shared_state = Arc::new(Mutex::new(SharedState {
-        internal_queue: PerKeyQueue::new(queue_style, max_queue_size_per_key, counters),
+        internal_queue,
         waker: None,
         num_senders: 1,
         receiver_dropped: false,
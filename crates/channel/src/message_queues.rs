@@ -32,6 +32,41 @@ impl Default for QueueStyle {
     }
 }
 
+/// KeyQueueConfig overrides the QueueStyle and the maximum queue size of a
+/// PerKeyQueue for the messages of a single key. This allows different keys
+/// (e.g., different protocols) to have their own capacity and dropping policy.
+#[derive(Clone, Copy)]
+pub struct KeyQueueConfig {
+    /// QueueStyle for the messages of this key
+    pub queue_style: QueueStyle,
+    /// Maximum number of messages to store for this key
+    pub max_queue_size: NonZeroUsize,
+    /// Optional counters for recording # enqueued, # dequeued, and # dropped
+    /// messages of this key. The counters are labeled by `[label, state]`.
+    pub labeled_counters: Option<(&'static IntCounterVec, &'static str)>,
+}
+
+impl KeyQueueConfig {
+    pub fn new(queue_style: QueueStyle, max_queue_size: NonZeroUsize) -> Self {
+        Self {
+            queue_style,
+            max_queue_size,
+            labeled_counters: None,
+        }
+    }
+
+    /// Records the messages of this key in the given counters, using `label`
+    /// as the first label value.
+    pub fn labeled_counters(
+        mut self,
+        counters: &'static IntCounterVec,
+        label: &'static str,
+    ) -> Self {
+        self.labeled_counters = Some((counters, label));
+        self
+    }
+}
+
 /// PerKeyQueue maintains a queue of messages per key. It
 /// is a bounded queue of messages per Key and the style (FIFO, LIFO) is
 /// configurable. When a new message is added using `push`, it is added to
@@ -60,6 +95,8 @@ pub(crate) struct PerKeyQueue<K: Eq + Hash + Clone, T> {
     /// Optional counters for recording # enqueued, # dequeued, and # dropped
     /// messages
     counters: Option<&'static IntCounterVec>,
+    /// Per-key overrides of the queue style, max queue size and counters
+    key_queue_configs: HashMap<K, KeyQueueConfig>,
 }
 
 impl<K: Eq + Hash + Clone, T> Debug for PerKeyQueue<K, T> {
@@ -68,6 +105,7 @@ impl<K: Eq + Hash + Clone, T> Debug for PerKeyQueue<K, T> {
             .field("queue_style", &self.queue_style)
             .field("max_queue_size", &self.max_queue_size)
             .field("num_popped_since_gc", &self.num_popped_since_gc)
+            .field("num_key_queue_configs", &self.key_queue_configs.len())
             .finish()
     }
 }
@@ -87,6 +125,39 @@ impl<K: Eq + Hash + Clone, T> PerKeyQueue<K, T> {
             round_robin_queue: VecDeque::new(),
             num_popped_since_gc: 0,
             counters,
+            key_queue_configs: HashMap::new(),
+        }
+    }
+
+    /// Overrides the queue style, max queue size and counters for the given keys
+    pub(crate) fn with_key_queue_configs(
+        mut self,
+        key_queue_configs: HashMap<K, KeyQueueConfig>,
+    ) -> Self {
+        self.key_queue_configs = key_queue_configs;
+        self
+    }
+
+    /// Returns the queue style and max queue size of the given key
+    fn queue_config(&self, key: &K) -> (QueueStyle, NonZeroUsize) {
+        match self.key_queue_configs.get(key) {
+            Some(config) => (config.queue_style, config.max_queue_size),
+            None => (self.queue_style, self.max_queue_size),
+        }
+    }
+
+    /// Increments the channel counters and the key's labeled counters (if any)
+    /// for the given state
+    fn inc_counters(&self, key: &K, state: &str) {
+        if let Some(c) = self.counters.as_ref() {
+            c.with_label_values(&[state]).inc();
+        }
+        if let Some((c, label)) = self
+            .key_queue_configs
+            .get(key)
+            .and_then(|config| config.labeled_counters)
+        {
+            c.with_label_values(&[label, state]).inc();
         }
     }
 
@@ -94,9 +165,10 @@ impl<K: Eq + Hash + Clone, T> PerKeyQueue<K, T> {
     /// It also returns a boolean indicating whether the keys queue is empty
     /// after popping the message
     fn pop_from_key_queue(&mut self, key: &K) -> (Option<T>, bool) {
+        let (queue_style, _) = self.queue_config(key);
         if let Some(q) = self.per_key_queue.get_mut(key) {
             // Extract message from the key's queue
-            let retval = match self.queue_style {
+            let retval = match queue_style {
                 QueueStyle::FIFO | QueueStyle::KLAST => q.pop_front(),
                 QueueStyle::LIFO => q.pop_back(),
             };
@@ -110,8 +182,14 @@ impl<K: Eq + Hash + Clone, T> PerKeyQueue<K, T> {
     /// add the key to round_robin_queue if it didnt already exist.
     /// Returns Some(T) if the new or an existing element was dropped. Returns None otherwise.
     pub(crate) fn push(&mut self, key: K, message: T) -> Option<T> {
-        if let Some(c) = self.counters.as_ref() {
-            c.with_label_values(&["enqueued"]).inc();
+        self.inc_counters(&key, "enqueued");
+        let (queue_style, max_queue_size) = self.queue_config(&key);
+        let is_full = self
+            .per_key_queue
+            .get(&key)
+            .map_or(false, |queue| queue.len() >= max_queue_size.get());
+        if is_full {
+            self.inc_counters(&key, "dropped");
         }
 
         let key_message_queue = self
@@ -131,11 +209,8 @@ impl<K: Eq + Hash + Clone, T> PerKeyQueue<K, T> {
         }
 
         // Push the message to the actual key message queue
-        if key_message_queue.len() >= self.max_queue_size.get() {
-            if let Some(c) = self.counters.as_ref() {
-                c.with_label_values(&["dropped"]).inc();
-            }
-            match self.queue_style {
+        if is_full {
+            match queue_style {
                 // Drop the newest message for FIFO
                 QueueStyle::FIFO => Some(message),
                 // Drop the oldest message for LIFO
@@ -167,9 +242,7 @@ impl<K: Eq + Hash + Clone, T> PerKeyQueue<K, T> {
         }
 
         if message.is_some() {
-            if let Some(c) = self.counters.as_ref() {
-                c.with_label_values(&["dequeued"]).inc();
-            }
+            self.inc_counters(&key, "dequeued");
 
             // Remove empty per-key-queues every `POPS_PER_GC` successful dequeue
             // operations.
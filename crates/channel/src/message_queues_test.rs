@@ -1,9 +1,10 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::message_queues::{PerKeyQueue, QueueStyle};
+use crate::message_queues::{KeyQueueConfig, PerKeyQueue, QueueStyle};
 use aptos_infallible::NonZeroUsize;
 use aptos_types::account_address::AccountAddress;
+use std::collections::HashMap;
 
 /// This represents a proposal message from a validator
 #[derive(Debug, PartialEq)]
@@ -437,3 +438,53 @@ fn test_message_queue_clear() {
     );
     assert_eq!(q.pop().unwrap().msg, "msg3".to_string());
 }
+
+#[test]
+fn test_key_queue_configs() {
+    let validator1 = AccountAddress::new([0u8; AccountAddress::LENGTH]);
+    let validator2 = AccountAddress::new([1u8; AccountAddress::LENGTH]);
+
+    // validator1 uses the default FIFO queue of size 2, while validator2
+    // overrides it with a LIFO queue of size 1.
+    let mut key_queue_configs = HashMap::new();
+    key_queue_configs.insert(
+        validator2,
+        KeyQueueConfig::new(QueueStyle::LIFO, NonZeroUsize!(1)),
+    );
+    let mut q = PerKeyQueue::new(QueueStyle::FIFO, NonZeroUsize!(2), None)
+        .with_key_queue_configs(key_queue_configs);
+
+    for i in 1..=3 {
+        let dropped = q.push(
+            validator1,
+            ProposalMsg {
+                msg: format!("validator1_msg{}", i),
+            },
+        );
+        // The newest message is dropped once the queue is full
+        if i == 3 {
+            assert_eq!(dropped.unwrap().msg, "validator1_msg3".to_string());
+        } else {
+            assert!(dropped.is_none());
+        }
+    }
+    for i in 1..=3 {
+        let dropped = q.push(
+            validator2,
+            ProposalMsg {
+                msg: format!("validator2_msg{}", i),
+            },
+        );
+        // The oldest message is dropped once the queue is full
+        if i > 1 {
+            assert_eq!(dropped.unwrap().msg, format!("validator2_msg{}", i - 1));
+        } else {
+            assert!(dropped.is_none());
+        }
+    }
+
+    assert_eq!(q.pop().unwrap().msg, "validator1_msg1".to_string());
+    assert_eq!(q.pop().unwrap().msg, "validator2_msg3".to_string());
+    assert_eq!(q.pop().unwrap().msg, "validator1_msg2".to_string());
+    assert_eq!(q.pop(), None);
+}
@@ -5,35 +5,96 @@
 
 use aptos_logger::prelude::*;
 use backtrace::Backtrace;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
+    fs, io,
     panic::{self, PanicInfo},
-    process,
+    path::{Path, PathBuf},
+    process, thread,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
+/// Prefix of the names of the crash dump files
+const CRASH_DUMP_PREFIX: &str = "crash-";
+/// Suffix of the names of the crash dump files which were uploaded
+pub const UPLOADED_SUFFIX: &str = ".uploaded";
+
 #[derive(Debug, Serialize)]
 pub struct CrashInfo {
     details: String,
     backtrace: String,
 }
 
+/// Everything known about a crash, written to a local file for bug reports.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CrashDump {
+    pub timestamp_secs: u64,
+    pub thread_name: Option<String>,
+    pub details: String,
+    pub backtrace: String,
+    /// Describes the node, e.g. its role and build version.
+    pub metadata: BTreeMap<String, String>,
+    /// The most recent logs before the crash, as formatted by the logger.
+    pub recent_logs: Vec<String>,
+}
+
+/// Where and what to dump when the process crashes
+struct CrashDumps {
+    dir: PathBuf,
+    metadata: BTreeMap<String, String>,
+}
+
 /// Invoke to ensure process exits on a thread panic.
 ///
 /// Tokio's default behavior is to catch panics and ignore them.  Invoking this function will
 /// ensure that all subsequent thread panics (even Tokio threads) will report the
 /// details/backtrace and then exit.
 pub fn setup_panic_handler() {
+    set_panic_hook(None);
+}
+
+/// Like `setup_panic_handler`, but also writes a `CrashDump` in the given directory when the
+/// process panics. The dump includes the recent logs if the logger was built to keep them.
+pub fn setup_panic_handler_with_crash_dumps(
+    crash_dump_dir: PathBuf,
+    metadata: BTreeMap<String, String>,
+) {
+    set_panic_hook(Some(CrashDumps {
+        dir: crash_dump_dir,
+        metadata,
+    }));
+}
+
+fn set_panic_hook(crash_dumps: Option<CrashDumps>) {
     panic::set_hook(Box::new(move |pi: &PanicInfo<'_>| {
-        handle_panic(pi);
+        handle_panic(pi, crash_dumps.as_ref());
     }));
 }
 
 // Formats and logs panic information
-fn handle_panic(panic_info: &PanicInfo<'_>) {
+fn handle_panic(panic_info: &PanicInfo<'_>, crash_dumps: Option<&CrashDumps>) {
     // The Display formatter for a PanicInfo contains the message, payload and location.
     let details = format!("{}", panic_info);
     let backtrace = format!("{:#?}", Backtrace::new());
 
+    if let Some(crash_dumps) = crash_dumps {
+        let dump = CrashDump {
+            timestamp_secs: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs()),
+            thread_name: thread::current().name().map(ToOwned::to_owned),
+            details: details.clone(),
+            backtrace: backtrace.clone(),
+            metadata: crash_dumps.metadata.clone(),
+            recent_logs: aptos_logger::recent_logs(),
+        };
+        match write_crash_dump(&crash_dumps.dir, &dump) {
+            Ok(path) => eprintln!("Crash dump written to {}", path.display()),
+            Err(e) => eprintln!("Unable to write crash dump: {}", e),
+        }
+    }
+
     let info = CrashInfo { details, backtrace };
     error!("{}", crash_info = toml::to_string_pretty(&info).unwrap());
 
@@ -43,3 +104,88 @@ fn handle_panic(panic_info: &PanicInfo<'_>) {
     // Kill the process
     process::exit(12);
 }
+
+/// Writes the dump as JSON in the directory, returning the path of the file.
+pub fn write_crash_dump(dir: &Path, dump: &CrashDump) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "{}{}-{}.json",
+        CRASH_DUMP_PREFIX,
+        dump.timestamp_secs,
+        process::id()
+    ));
+    fs::write(&path, serde_json::to_vec_pretty(dump)?)?;
+    Ok(path)
+}
+
+/// Reads the crash dumps in the directory, including the uploaded ones, ordered by path. A missing
+/// directory has no dumps.
+pub fn read_crash_dumps(dir: &Path) -> io::Result<Vec<(PathBuf, CrashDump)>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e),
+    };
+
+    let mut dumps = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        let is_dump = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| name.starts_with(CRASH_DUMP_PREFIX));
+        if is_dump {
+            let dump = serde_json::from_slice(&fs::read(&path)?)?;
+            dumps.push((path, dump));
+        }
+    }
+    dumps.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(dumps)
+}
+
+/// Whether the dump at the path was uploaded.
+pub fn is_uploaded(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(UPLOADED_SUFFIX)
+}
+
+/// Marks the dump at the path as uploaded, by renaming it.
+pub fn mark_uploaded(path: &Path) -> io::Result<()> {
+    let mut uploaded = path.as_os_str().to_owned();
+    uploaded.push(UPLOADED_SUFFIX);
+    fs::rename(path, uploaded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+
+    #[test]
+    fn test_crash_dumps() {
+        let dir = TempPath::new();
+        assert!(read_crash_dumps(dir.path()).unwrap().is_empty());
+
+        let mut dump = CrashDump {
+            timestamp_secs: 1,
+            thread_name: Some("main".into()),
+            details: "panicked at 'oops'".into(),
+            backtrace: "backtrace".into(),
+            metadata: vec![("role".to_string(), "validator".to_string())]
+                .into_iter()
+                .collect(),
+            recent_logs: vec!["{}".into()],
+        };
+        let first = write_crash_dump(dir.path(), &dump).unwrap();
+        dump.timestamp_secs = 2;
+        write_crash_dump(dir.path(), &dump).unwrap();
+        fs::write(dir.path().join("other.json"), "not a dump").unwrap();
+
+        mark_uploaded(&first).unwrap();
+        let dumps = read_crash_dumps(dir.path()).unwrap();
+        assert_eq!(dumps.len(), 2);
+        assert!(is_uploaded(&dumps[0].0));
+        assert_eq!(dumps[0].1.timestamp_secs, 1);
+        assert!(!is_uploaded(&dumps[1].0));
+        assert_eq!(dumps[1].1, dump);
+    }
+}
@@ -0,0 +1,984 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Admin service to inspect and reconfigure a running node. It only listens on localhost, and
+//! every request must carry one of the bearer tokens of the config. The service may also be
+//! served over TLS, requiring clients to present a certificate signed by the configured CA, which
+//! authenticates them on its own when no token is configured. Every call is logged with its
+//! caller: the name of its token in the config, or otherwise the subject of its certificate.
+//!
+//! * `GET /config`: the effective config of the node, without its keys and tokens.
+//! * `GET /node-info`: the effective config of the node, as for `/config`, and the git revision
+//!   the node was built at.
+//! * `GET /log/levels`, `POST /log/levels`: the log levels of the node. The body of a POST maps
+//!   modules to their new level, or to null to remove their override.
+//! * `POST /log/remote-filter`: replaces the filter of the logs sent remotely. The body holds its
+//!   directives, e.g. `info,consensus=debug`.
+//! * `GET /threads`: the OS threads of the node, with their state and kernel stack when readable.
+//!   These aren't async tasks: Tokio doesn't list the tasks of a runtime, but the worker threads
+//!   of a runtime are named after it.
+//! * `GET /failpoints`, `POST /failpoints`: the failpoints of builds with the `failpoints`
+//!   feature. The body of a POST maps failpoints to their new actions, or to null to remove them.
+//!   Failpoints are named after their component, e.g. `consensus::save_vote` before a vote is
+//!   persisted or `aptosdb::save_transactions` before a batch is committed to the DB.
+//! * `GET /mempool/transaction_filter`, `POST /mempool/transaction_filter`: the script functions
+//!   whose transactions are accepted by mempool, e.g. to reject the calls of a function during an
+//!   incident. The body of a POST replaces the filter, with the format of the mempool config.
+//! * `GET /mempool/traces`, `GET /mempool/traces/<sender>/<sequence number>`: the lifecycle
+//!   traces of the latest transactions, or of a given transaction, when mempool tracing is
+//!   enabled. The sender is a hex literal, e.g. `0x1`.
+
+use aptos_config::config::{AdminServiceConfig, LoggerConfig, NodeConfig, TransactionFilterConfig};
+use aptos_infallible::Mutex;
+use aptos_logger::{info, warn, Filter, LevelFilter, Logger};
+use aptos_mempool::{TransactionFilter, TransactionTracer};
+use aptos_metrics::json_metrics::get_git_rev;
+use aptos_types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, convert::Infallible, env, fs, io, net::SocketAddr, sync::Arc};
+use tokio::{
+    net::TcpListener,
+    runtime::{Builder, Runtime},
+};
+use tokio_rustls::{
+    rustls::{
+        internal::pemfile, AllowAnyAuthenticatedClient, NoClientAuth, RootCertStore, ServerConfig,
+    },
+    TlsAcceptor,
+};
+use warp::{
+    http::{Method, StatusCode},
+    hyper::{self, service::Service as _},
+    path::FullPath,
+    reply::Response,
+    Filter as _, Rejection, Reply,
+};
+
+const RUST_LOG: &str = "RUST_LOG";
+// 16kb should be long enough for a request
+const MAX_BODY_LENGTH: u64 = 1024 * 16;
+
+#[derive(Debug)]
+pub struct AdminService {
+    runtime: Runtime,
+}
+
+impl AdminService {
+    /// Starts the service, which requires an authentication token or a client CA in the config.
+    pub fn new(
+        node_config: &NodeConfig,
+        logger: Option<Arc<Logger>>,
+        transaction_filter: Arc<TransactionFilter>,
+        transaction_tracer: Arc<TransactionTracer>,
+    ) -> Self {
+        let config = node_config.admin_service.clone();
+        let authenticator = Authenticator::new(&config);
+        assert!(
+            !authenticator.tokens.is_empty() || config.tls_client_ca_path.is_some(),
+            "The admin service requires an authentication token or TLS client authentication"
+        );
+        assert!(
+            config.tls_cert_path.is_some() == config.tls_key_path.is_some(),
+            "The admin service requires both a TLS certificate and key to serve over TLS"
+        );
+        assert!(
+            config.tls_client_ca_path.is_none() || config.tls_cert_path.is_some(),
+            "TLS client authentication requires the admin service to serve over TLS"
+        );
+        let address = SocketAddr::from(([127, 0, 0, 1], config.port));
+        let tls_config = match (&config.tls_cert_path, &config.tls_key_path) {
+            (Some(cert_path), Some(key_path)) => Some(
+                tls_config(cert_path, key_path, config.tls_client_ca_path.as_deref())
+                    .unwrap_or_else(|e| panic!("[admin] invalid TLS config: {}", e)),
+            ),
+            _ => None,
+        };
+
+        let runtime = Builder::new_multi_thread()
+            .thread_name("admin")
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .expect("[admin] failed to create runtime");
+
+        let routes = routes(
+            authenticator,
+            node_config,
+            logger,
+            transaction_filter,
+            transaction_tracer,
+        );
+        runtime.handle().spawn(async move {
+            let tls_config = match tls_config {
+                Some(tls_config) => tls_config,
+                None => return warp::serve(routes).bind(address).await,
+            };
+
+            // Unlike warp, the connections are accepted here so that the subject of the client
+            // certificate of each connection is passed along its requests, to name their caller
+            let acceptor = TlsAcceptor::from(Arc::new(tls_config));
+            let listener = TcpListener::bind(address)
+                .await
+                .expect("[admin] failed to bind");
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        warn!(error = %e, "Failed to accept an admin connection");
+                        continue;
+                    }
+                };
+                let acceptor = acceptor.clone();
+                let routes = routes.clone();
+                tokio::spawn(async move {
+                    let stream = match acceptor.accept(stream).await {
+                        Ok(stream) => stream,
+                        Err(e) => {
+                            warn!(error = %e, "Failed the TLS handshake of an admin connection");
+                            return;
+                        }
+                    };
+                    let client_subject = stream
+                        .get_ref()
+                        .1
+                        .get_peer_certificates()
+                        .and_then(|certificates| {
+                            certificates
+                                .first()
+                                .and_then(|certificate| certificate_subject(&certificate.0))
+                        })
+                        .map(ClientSubject);
+                    let mut service = warp::service(routes);
+                    let service = hyper::service::service_fn(move |mut request| {
+                        if let Some(client_subject) = &client_subject {
+                            request.extensions_mut().insert(client_subject.clone());
+                        }
+                        service.call(request)
+                    });
+                    if let Err(e) = hyper::server::conn::Http::new()
+                        .serve_connection(stream, service)
+                        .await
+                    {
+                        warn!(error = %e, "Failed to serve an admin connection");
+                    }
+                });
+            }
+        });
+
+        Self { runtime }
+    }
+
+    pub fn runtime(&self) -> &Runtime {
+        &self.runtime
+    }
+}
+
+/// The log levels of the node: the directives the logger started with, overridden per module.
+/// Only the local filter of the logger is changed, logs sent remotely are filtered as before.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LogLevels {
+    pub base: String,
+    pub modules: BTreeMap<String, String>,
+}
+
+impl LogLevels {
+    /// The logger starts with the directives of `RUST_LOG`, or otherwise with the configured levels
+    fn new(config: &LoggerConfig) -> Self {
+        let base = env::var(RUST_LOG).unwrap_or_else(|_| {
+            let mut directives = vec![config.level.to_string()];
+            for (module, level) in &config.module_levels {
+                directives.push(format!("{}={}", module, level));
+            }
+            directives.join(",")
+        });
+        Self {
+            base,
+            modules: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the level of the modules, or removes their override if their level is None. Nothing
+    /// changes if any of the updates is invalid.
+    fn update(&mut self, updates: BTreeMap<String, Option<String>>) -> Result<(), String> {
+        for (module, level) in &updates {
+            if module.is_empty() || module.contains(|c| c == ',' || c == '=') {
+                return Err(format!("Invalid module '{}'", module));
+            }
+            if let Some(level) = level {
+                if level.parse::<LevelFilter>().is_err() {
+                    return Err(format!("Invalid level '{}' for module '{}'", level, module));
+                }
+            }
+        }
+
+        for (module, level) in updates {
+            match level {
+                Some(level) => self.modules.insert(module, level),
+                None => self.modules.remove(&module),
+            };
+        }
+        Ok(())
+    }
+
+    fn filter(&self) -> Filter {
+        let mut builder = Filter::builder();
+        builder.parse(&self.base);
+        for (module, level) in &self.modules {
+            if let Ok(level) = level.parse() {
+                builder.filter_module(module, level);
+            }
+        }
+        builder.build()
+    }
+}
+
+/// An OS thread of the node
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ThreadInfo {
+    pub id: u32,
+    pub name: String,
+    pub state: String,
+    /// Only readable with enough privileges
+    pub kernel_stack: Option<Vec<String>>,
+}
+
+/// Lists the OS threads of the process from procfs, where they're called tasks
+fn threads() -> io::Result<Vec<ThreadInfo>> {
+    let mut threads = vec![];
+    for entry in fs::read_dir("/proc/self/task")? {
+        let path = entry?.path();
+        let id = match path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.parse().ok())
+        {
+            Some(id) => id,
+            None => continue,
+        };
+        // The thread may have exited since the directory was read
+        let name = match fs::read_to_string(path.join("comm")) {
+            Ok(name) => name.trim().to_string(),
+            Err(_) => continue,
+        };
+        let state = fs::read_to_string(path.join("stat"))
+            .ok()
+            .and_then(|stat| thread_state(&stat))
+            .unwrap_or_default();
+        let kernel_stack = fs::read_to_string(path.join("stack"))
+            .ok()
+            .map(|stack| stack.lines().map(str::to_string).collect());
+
+        threads.push(ThreadInfo {
+            id,
+            name,
+            state,
+            kernel_stack,
+        });
+    }
+    threads.sort_by_key(|thread| thread.id);
+    Ok(threads)
+}
+
+/// The state follows the name of the thread in its stat, which is in parentheses and may itself
+/// contain spaces and parentheses.
+fn thread_state(stat: &str) -> Option<String> {
+    stat.rsplit_once(')')?
+        .1
+        .split_whitespace()
+        .next()
+        .map(str::to_string)
+}
+
+/// Sets the actions of the failpoints, or removes them if their actions are None
+fn configure_failpoints(updates: BTreeMap<String, Option<String>>) -> Result<(), String> {
+    if !fail::has_failpoints() {
+        return Err("Failpoints aren't enabled in this build".into());
+    }
+
+    for (name, actions) in updates {
+        match actions {
+            Some(actions) => fail::cfg(name.clone(), &actions)
+                .map_err(|e| format!("Invalid actions for failpoint '{}': {}", name, e))?,
+            None => fail::remove(name),
+        }
+    }
+    Ok(())
+}
+
+/// The git revision the node was built at and the config it runs with
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct NodeInfo {
+    #[serde(default)]
+    pub node_config: NodeConfig,
+    #[serde(default)]
+    pub git_revision: String,
+}
+
+/// The TLS config of the service, which requires clients to present a certificate signed by the
+/// CA when set
+fn tls_config(
+    cert_path: &str,
+    key_path: &str,
+    client_ca_path: Option<&str>,
+) -> Result<ServerConfig, String> {
+    let read = |path: &str| fs::read(path).map_err(|e| format!("Unable to read {}: {}", path, e));
+
+    let certificates = pemfile::certs(&mut read(cert_path)?.as_slice())
+        .map_err(|()| format!("Invalid certificates in {}", cert_path))?;
+    let key = read(key_path)?;
+    let mut keys = pemfile::pkcs8_private_keys(&mut key.as_slice()).unwrap_or_default();
+    if keys.is_empty() {
+        keys = pemfile::rsa_private_keys(&mut key.as_slice()).unwrap_or_default();
+    }
+    let key = keys
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No private key in {}", key_path))?;
+
+    let client_auth = match client_ca_path {
+        Some(client_ca_path) => {
+            let mut roots = RootCertStore::empty();
+            match roots.add_pem_file(&mut read(client_ca_path)?.as_slice()) {
+                Ok((valid, _)) if valid > 0 => AllowAnyAuthenticatedClient::new(roots),
+                _ => return Err(format!("No valid CA certificate in {}", client_ca_path)),
+            }
+        }
+        None => NoClientAuth::new(),
+    };
+    let mut config = ServerConfig::new(client_auth);
+    config
+        .set_single_cert(certificates, key)
+        .map_err(|e| format!("Invalid certificate or key: {}", e))?;
+    Ok(config)
+}
+
+/// The subject of the verified TLS client certificate of a connection
+#[derive(Clone, Debug, PartialEq)]
+struct ClientSubject(String);
+
+/// The subject of a DER encoded X.509 certificate, e.g. `O=Aptos,CN=operator`, with its attributes
+/// in the order of the certificate. Attributes whose type has no short name are named by their
+/// OID.
+fn certificate_subject(certificate: &[u8]) -> Option<String> {
+    let attributes = yasna::parse_der(certificate, |reader| {
+        reader.read_sequence(|reader| {
+            let attributes = reader.next().read_sequence(|reader| {
+                // The version is explicitly tagged, and omitted by v1 certificates
+                reader.read_optional(|reader| {
+                    reader.read_tagged(yasna::Tag::context(0), |reader| reader.read_i64())
+                })?;
+                // The serial number, signature algorithm, issuer and validity precede the subject
+                for _ in 0..4 {
+                    reader.next().read_der()?;
+                }
+                let attributes = reader.next().collect_sequence_of(|reader| {
+                    reader.collect_set_of(|reader| {
+                        reader.read_sequence(|reader| {
+                            let attribute_type = reader.next().read_oid()?;
+                            let value = reader.next().read_tagged_der()?;
+                            Ok((attribute_type, value))
+                        })
+                    })
+                })?;
+                while reader.read_optional(|reader| reader.read_der())?.is_some() {}
+                Ok(attributes)
+            })?;
+            // The signature algorithm and signature follow the signed part
+            while reader.read_optional(|reader| reader.read_der())?.is_some() {}
+            Ok(attributes)
+        })
+    })
+    .ok()?;
+
+    let attributes: Vec<_> = attributes
+        .into_iter()
+        .flatten()
+        .map(|(attribute_type, value)| {
+            let name = match attribute_type.components().as_slice() {
+                [2, 5, 4, 3] => "CN".to_string(),
+                [2, 5, 4, 6] => "C".to_string(),
+                [2, 5, 4, 7] => "L".to_string(),
+                [2, 5, 4, 8] => "ST".to_string(),
+                [2, 5, 4, 10] => "O".to_string(),
+                [2, 5, 4, 11] => "OU".to_string(),
+                _ => attribute_type.to_string(),
+            };
+            format!("{}={}", name, value.as_str().unwrap_or_default())
+        })
+        .collect();
+    (!attributes.is_empty()).then(|| attributes.join(","))
+}
+
+#[derive(Debug)]
+struct Unauthorized;
+
+impl warp::reject::Reject for Unauthorized {}
+
+/// Authenticates the callers of the service by their bearer token, or otherwise by their TLS
+/// client certificate
+#[derive(Clone)]
+struct Authenticator {
+    /// The expected authorization headers, by the name of their caller
+    tokens: Vec<(String, String)>,
+    /// Whether the callers are authenticated by their TLS client certificate, when there's no
+    /// token to check
+    client_authenticated: bool,
+}
+
+impl Authenticator {
+    fn new(config: &AdminServiceConfig) -> Self {
+        let tokens = config
+            .tokens()
+            .into_iter()
+            .map(|(caller, token)| (caller, format!("Bearer {}", token)))
+            .collect();
+        Self {
+            tokens,
+            client_authenticated: config.tls_client_ca_path.is_some(),
+        }
+    }
+
+    /// The caller of a request with the given authorization header, if authenticated. All the
+    /// tokens are checked, so that the time taken doesn't reveal which one nearly matched.
+    /// Without tokens, the caller is named by the subject of its certificate, which was verified
+    /// during the TLS handshake.
+    fn caller(
+        &self,
+        authorization: Option<&str>,
+        client_subject: Option<&ClientSubject>,
+    ) -> Option<String> {
+        if self.tokens.is_empty() {
+            if !self.client_authenticated {
+                return None;
+            }
+            return client_subject.map(|client_subject| client_subject.0.clone());
+        }
+        let authorization = authorization?;
+        let mut caller = None;
+        for (name, expected) in &self.tokens {
+            if tokens_match(authorization, expected) {
+                caller = Some(name.clone());
+            }
+        }
+        caller
+    }
+}
+
+/// Compares the tokens in constant time, so that the time taken doesn't reveal the expected token
+fn tokens_match(token: &str, expected: &str) -> bool {
+    token.len() == expected.len()
+        && token
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// An authenticated call, logged once answered
+struct AdminCall {
+    caller: String,
+    method: Method,
+    path: String,
+}
+
+fn bad_request(error: String) -> Response {
+    warp::reply::with_status(error, StatusCode::BAD_REQUEST).into_response()
+}
+
+async fn handle_rejection(rejection: Rejection) -> Result<impl Reply, Infallible> {
+    let status = if rejection.find::<Unauthorized>().is_some() {
+        StatusCode::UNAUTHORIZED
+    } else if rejection.is_not_found() {
+        StatusCode::NOT_FOUND
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+    Ok(warp::reply::with_status(warp::reply(), status))
+}
+
+fn routes(
+    authenticator: Authenticator,
+    node_config: &NodeConfig,
+    logger: Option<Arc<Logger>>,
+    transaction_filter: Arc<TransactionFilter>,
+    transaction_tracer: Arc<TransactionTracer>,
+) -> impl warp::Filter<Extract = impl Reply, Error = Infallible> + Clone {
+    let authenticated = warp::method()
+        .and(warp::path::full())
+        .and(warp::header::optional::<String>("authorization"))
+        .and(warp::ext::optional::<ClientSubject>())
+        .and_then(
+            move |method: Method,
+                  path: FullPath,
+                  authorization: Option<String>,
+                  client_subject: Option<ClientSubject>| {
+                let caller =
+                    authenticator.caller(authorization.as_deref(), client_subject.as_ref());
+                async move {
+                    let path = path.as_str().to_string();
+                    match caller {
+                        Some(caller) => Ok(AdminCall {
+                            caller,
+                            method,
+                            path,
+                        }),
+                        None => {
+                            warn!(method = %method, path = path, "Unauthorized admin call");
+                            Err(warp::reject::custom(Unauthorized))
+                        }
+                    }
+                }
+            },
+        );
+
+    // GET /config
+    let mut config = node_config.clone();
+    config.redact_secrets();
+    let node_info = NodeInfo {
+        node_config: config.clone(),
+        git_revision: get_git_rev(),
+    };
+    let config_route = warp::path!("config")
+        .and(warp::get())
+        .map(move || warp::reply::json(&config));
+
+    // GET /node-info
+    let node_info_route = warp::path!("node-info")
+        .and(warp::get())
+        .map(move || warp::reply::json(&node_info));
+
+    // GET /log/levels
+    let log_levels = Arc::new(Mutex::new(LogLevels::new(&node_config.logger)));
+    let get_log_levels = {
+        let log_levels = log_levels.clone();
+        warp::path!("log" / "levels")
+            .and(warp::get())
+            .map(move || warp::reply::json(&*log_levels.lock()))
+    };
+
+    // POST /log/remote-filter
+    let set_remote_filter = {
+        let logger = logger.clone();
+        warp::path!("log" / "remote-filter")
+            .and(warp::post())
+            .and(warp::body::content_length_limit(MAX_BODY_LENGTH))
+            .and(warp::body::bytes())
+            .map(move |body: bytes::Bytes| {
+                let filter = match std::str::from_utf8(&body) {
+                    Ok(filter) => filter,
+                    Err(_) => return bad_request("The filter isn't valid UTF-8".into()),
+                };
+                if let Some(logger) = &logger {
+                    info!(filter = filter, "Updating remote logging filter");
+                    logger.set_remote_filter(Filter::builder().parse(filter).build());
+                }
+                warp::reply().into_response()
+            })
+    };
+
+    // POST /log/levels
+    let set_log_levels = warp::path!("log" / "levels")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_BODY_LENGTH))
+        .and(warp::body::json())
+        .map(move |updates: BTreeMap<String, Option<String>>| {
+            let mut log_levels = log_levels.lock();
+            if let Err(error) = log_levels.update(updates) {
+                return bad_request(error);
+            }
+            if let Some(logger) = &logger {
+                info!(log_levels = ?log_levels.modules, "Updating local log levels");
+                logger.set_filter(log_levels.filter());
+            }
+            warp::reply::json(&*log_levels).into_response()
+        });
+
+    // GET /threads
+    let threads_route = warp::path!("threads")
+        .and(warp::get())
+        .map(|| match threads() {
+            Ok(threads) => warp::reply::json(&threads).into_response(),
+            Err(e) => warp::reply::with_status(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+                .into_response(),
+        });
+
+    // GET /failpoints
+    let get_failpoints = warp::path!("failpoints").and(warp::get()).map(|| {
+        let failpoints: BTreeMap<_, _> = fail::list().into_iter().collect();
+        warp::reply::json(&failpoints)
+    });
+
+    // POST /failpoints
+    let set_failpoints = warp::path!("failpoints")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_BODY_LENGTH))
+        .and(warp::body::json())
+        .map(
+            |updates: BTreeMap<String, Option<String>>| match configure_failpoints(updates) {
+                Ok(()) => warp::reply().into_response(),
+                Err(error) => bad_request(error),
+            },
+        );
+
+    // GET /mempool/transaction_filter
+    let get_transaction_filter = {
+        let transaction_filter = transaction_filter.clone();
+        warp::path!("mempool" / "transaction_filter")
+            .and(warp::get())
+            .map(move || warp::reply::json(&transaction_filter.config()))
+    };
+
+    // POST /mempool/transaction_filter
+    let set_transaction_filter = warp::path!("mempool" / "transaction_filter")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_BODY_LENGTH))
+        .and(warp::body::json())
+        .map(move |config: TransactionFilterConfig| {
+            if let Err(error) = transaction_filter.update(config.clone()) {
+                return bad_request(error.to_string());
+            }
+            info!(transaction_filter = ?config, "Updating the mempool transaction filter");
+            warp::reply::json(&config).into_response()
+        });
+
+    // GET /mempool/traces
+    let get_traces = {
+        let transaction_tracer = transaction_tracer.clone();
+        warp::path!("mempool" / "traces")
+            .and(warp::get())
+            .map(move || warp::reply::json(&transaction_tracer.traces()))
+    };
+
+    // GET /mempool/traces/<sender>/<sequence number>
+    let get_trace = warp::path!("mempool" / "traces" / String / u64)
+        .and(warp::get())
+        .map(move |sender: String, sequence_number: u64| {
+            let sender = match AccountAddress::from_hex_literal(&sender) {
+                Ok(sender) => sender,
+                Err(_) => return bad_request(format!("Invalid sender '{}'", sender)),
+            };
+            match transaction_tracer.get(sender, sequence_number) {
+                Some(trace) => warp::reply::json(&trace).into_response(),
+                None => StatusCode::NOT_FOUND.into_response(),
+            }
+        });
+
+    authenticated
+        .and(
+            config_route
+                .or(node_info_route)
+                .or(get_log_levels)
+                .or(set_log_levels)
+                .or(set_remote_filter)
+                .or(threads_route)
+                .or(get_failpoints)
+                .or(set_failpoints)
+                .or(get_transaction_filter)
+                .or(set_transaction_filter)
+                .or(get_traces)
+                .or(get_trace)
+                .recover(handle_rejection),
+        )
+        .map(|call: AdminCall, reply| {
+            let response = Reply::into_response(reply);
+            info!(
+                caller = call.caller,
+                method = %call.method,
+                path = call.path,
+                status = response.status().as_u16(),
+                "Admin call"
+            );
+            response
+        })
+        .recover(handle_rejection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_logger::{Level, Metadata};
+    use aptos_mempool::{TraceStage, TransactionTrace};
+    use serde_json::json;
+
+    const TOKEN: &str = "token";
+
+    fn test_routes() -> impl warp::Filter<Extract = impl Reply, Error = Infallible> + Clone {
+        let mut node_config = NodeConfig::default();
+        node_config.admin_service.authentication_token = Some(TOKEN.into());
+        routes(
+            Authenticator::new(&node_config.admin_service),
+            &node_config,
+            None,
+            Arc::new(TransactionFilter::default()),
+            Arc::new(TransactionTracer::default()),
+        )
+    }
+
+    fn request(method: &str, path: &str) -> warp::test::RequestBuilder {
+        warp::test::request()
+            .method(method)
+            .path(path)
+            .header("authorization", format!("Bearer {}", TOKEN))
+    }
+
+    #[tokio::test]
+    async fn test_authentication() {
+        let routes = test_routes();
+        for authorization in &[None, Some("Bearer other"), Some("token")] {
+            let mut request = warp::test::request().path("/config");
+            if let Some(authorization) = authorization {
+                request = request.header("authorization", *authorization);
+            }
+            let response = request.reply(&routes).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        let response = request("GET", "/config").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let config: NodeConfig = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(config.admin_service.authentication_token, None);
+
+        let response = request("GET", "/unknown").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_authenticator() {
+        let mut config = AdminServiceConfig {
+            authentication_token: Some(TOKEN.into()),
+            ..AdminServiceConfig::default()
+        };
+        config
+            .authentication_tokens
+            .insert("operator".into(), "operator_token".into());
+        let authenticator = Authenticator::new(&config);
+        assert_eq!(
+            authenticator.caller(Some("Bearer token"), None),
+            Some("default".to_string())
+        );
+        assert_eq!(
+            authenticator.caller(Some("Bearer operator_token"), None),
+            Some("operator".to_string())
+        );
+        assert_eq!(authenticator.caller(Some("Bearer other"), None), None);
+        assert_eq!(authenticator.caller(None, None), None);
+
+        // With a client CA, tokens are still required when configured
+        let client_subject = ClientSubject("CN=operator".into());
+        config.tls_client_ca_path = Some("ca.pem".into());
+        assert_eq!(
+            Authenticator::new(&config).caller(None, Some(&client_subject)),
+            None
+        );
+
+        // and otherwise the client certificate is enough, naming the caller
+        config.redact_tokens();
+        assert_eq!(
+            Authenticator::new(&config).caller(None, Some(&client_subject)),
+            Some("CN=operator".to_string())
+        );
+        assert_eq!(Authenticator::new(&config).caller(None, None), None);
+
+        config.tls_client_ca_path = None;
+        assert_eq!(
+            Authenticator::new(&config).caller(None, Some(&client_subject)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_certificate_subject() {
+        let mut params = rcgen::CertificateParams::new(vec!["localhost".into()]);
+        params.distinguished_name = rcgen::DistinguishedName::new();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::OrganizationName, "Aptos");
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "operator");
+        let certificate = rcgen::Certificate::from_params(params).unwrap();
+        assert_eq!(
+            certificate_subject(&certificate.serialize_der().unwrap()),
+            Some("O=Aptos,CN=operator".to_string())
+        );
+
+        assert_eq!(certificate_subject(b"not a certificate"), None);
+    }
+
+    #[tokio::test]
+    async fn test_client_certificate() {
+        let mut node_config = NodeConfig::default();
+        node_config.admin_service.tls_client_ca_path = Some("ca.pem".into());
+        let routes = routes(
+            Authenticator::new(&node_config.admin_service),
+            &node_config,
+            None,
+            Arc::new(TransactionFilter::default()),
+            Arc::new(TransactionTracer::default()),
+        );
+
+        let response = warp::test::request()
+            .path("/node-info")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = warp::test::request()
+            .path("/node-info")
+            .extension(ClientSubject("CN=operator".into()))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let node_info: NodeInfo = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(node_info.git_revision, get_git_rev());
+    }
+
+    #[tokio::test]
+    async fn test_log_levels() {
+        let routes = test_routes();
+        let update = |body| request("POST", "/log/levels").json(&body);
+
+        let response = update(json!({"consensus": "debug", "mempool": "off"}))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let log_levels: LogLevels = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(log_levels.modules.len(), 2);
+
+        // Invalid updates are rejected as a whole
+        let response = update(json!({"consensus": "info", "network": "verbose"}))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = update(json!({"consensus=info": "info"}))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = update(json!({ "mempool": null })).reply(&routes).await;
+        let log_levels: LogLevels = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(
+            log_levels.modules,
+            vec![("consensus".to_string(), "debug".to_string())]
+                .into_iter()
+                .collect()
+        );
+
+        let response = request("GET", "/log/levels").reply(&routes).await;
+        let current: LogLevels = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(current, log_levels);
+    }
+
+    #[test]
+    fn test_log_levels_filter() {
+        let mut log_levels = LogLevels {
+            base: "info".into(),
+            modules: BTreeMap::new(),
+        };
+        let mut updates = BTreeMap::new();
+        updates.insert("consensus".to_string(), Some("debug".to_string()));
+        updates.insert("mempool".to_string(), Some("error".to_string()));
+        log_levels.update(updates).unwrap();
+
+        let filter = log_levels.filter();
+        let enabled = |level: Level, module: &'static str| {
+            filter.enabled(&Metadata::new(level, module, module, "", 0, ""))
+        };
+        assert!(enabled(Level::Debug, "consensus::round_manager"));
+        assert!(!enabled(Level::Debug, "network"));
+        assert!(enabled(Level::Info, "network"));
+        assert!(!enabled(Level::Warn, "mempool"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_threads() {
+        let response = request("GET", "/threads").reply(&test_routes()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let threads: Vec<ThreadInfo> = serde_json::from_slice(response.body()).unwrap();
+        assert!(!threads.is_empty());
+        assert!(threads.iter().all(|thread| !thread.state.is_empty()));
+
+        assert_eq!(
+            thread_state("42 (tokio (worker)) S 1 2"),
+            Some("S".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transaction_filter() {
+        let routes = test_routes();
+        let update = |body| request("POST", "/mempool/transaction_filter").json(&body);
+
+        let response = update(json!({"deny": ["0x1::PaymentScripts"]}))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Invalid updates are rejected as a whole
+        let response = update(json!({"allow": ["0x1::PaymentScripts::p2p"], "deny": ["0x1"]}))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = request("GET", "/mempool/transaction_filter")
+            .reply(&routes)
+            .await;
+        let config: TransactionFilterConfig = serde_json::from_slice(response.body()).unwrap();
+        assert!(config.allow.is_empty());
+        assert_eq!(config.deny, vec!["0x1::PaymentScripts".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_traces() {
+        let mut node_config = NodeConfig::default();
+        node_config.admin_service.authentication_token = Some(TOKEN.into());
+        let transaction_tracer = Arc::new(TransactionTracer::new(10));
+        let routes = routes(
+            Authenticator::new(&node_config.admin_service),
+            &node_config,
+            None,
+            Arc::new(TransactionFilter::default()),
+            transaction_tracer.clone(),
+        );
+        let sender = AccountAddress::from_hex_literal("0x1").unwrap();
+        transaction_tracer.start(sender, 3, TraceStage::Submitted);
+        transaction_tracer.record(sender, 3, TraceStage::Validated);
+
+        let response = request("GET", "/mempool/traces").reply(&routes).await;
+        let traces: Vec<TransactionTrace> = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(traces, transaction_tracer.traces());
+        assert_eq!(traces.len(), 1);
+
+        let response = request("GET", "/mempool/traces/0x1/3").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let trace: TransactionTrace = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(trace.events.len(), 2);
+
+        let response = request("GET", "/mempool/traces/0x1/4").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = request("GET", "/mempool/traces/nothex/3")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_failpoints() {
+        let routes = test_routes();
+        let response = request("POST", "/failpoints")
+            .json(&json!({"admin_service::test": "return"}))
+            .reply(&routes)
+            .await;
+        if fail::has_failpoints() {
+            assert_eq!(response.status(), StatusCode::OK);
+            let response = request("GET", "/failpoints").reply(&routes).await;
+            let failpoints: BTreeMap<String, String> =
+                serde_json::from_slice(response.body()).unwrap();
+            assert_eq!(failpoints["admin_service::test"], "return");
+            fail::remove("admin_service::test");
+        } else {
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, Result};
+use aptos_config::config::NodeConfig;
+use aptos_metrics::json_metrics::get_git_rev;
+use crash_handler::CrashDump;
+use serde::Serialize;
+use std::{
+    env::consts::{ARCH, OS},
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(about = "Tools to debug Aptos nodes.")]
+enum Command {
+    #[structopt(about = "Bundle the crash dumps of a node into a single file for bug reports.")]
+    Collect(CollectOpt),
+}
+
+#[derive(StructOpt)]
+struct CollectOpt {
+    #[structopt(
+        long,
+        parse(from_os_str),
+        help = "Config of the node, to find its crash dumps."
+    )]
+    config: Option<PathBuf>,
+    #[structopt(
+        long,
+        parse(from_os_str),
+        conflicts_with = "config",
+        help = "Directory of the crash dumps of the node."
+    )]
+    crash_dump_dir: Option<PathBuf>,
+    #[structopt(
+        long,
+        help = "Also bundle the crash dumps which were already uploaded."
+    )]
+    include_uploaded: bool,
+    #[structopt(long, parse(from_os_str), help = "File the bundle is written to.")]
+    output: PathBuf,
+}
+
+#[derive(Serialize)]
+struct Bundle {
+    created_at_secs: u64,
+    tool_build_version: String,
+    os: &'static str,
+    arch: &'static str,
+    crash_dumps: Vec<BundledCrashDump>,
+}
+
+#[derive(Serialize)]
+struct BundledCrashDump {
+    file: PathBuf,
+    dump: CrashDump,
+}
+
+fn main() -> Result<()> {
+    match Command::from_args() {
+        Command::Collect(opt) => collect(opt),
+    }
+}
+
+fn collect(opt: CollectOpt) -> Result<()> {
+    let crash_dump_dir = match (opt.config, opt.crash_dump_dir) {
+        (Some(config), None) => {
+            let config = NodeConfig::load(&config)?;
+            config.crash_dumps.dir(config.data_dir())
+        }
+        (None, Some(crash_dump_dir)) => crash_dump_dir,
+        _ => bail!("Either --config or --crash-dump-dir must be provided"),
+    };
+
+    let crash_dumps: Vec<_> = crash_handler::read_crash_dumps(&crash_dump_dir)?
+        .into_iter()
+        .filter(|(path, _)| opt.include_uploaded || !crash_handler::is_uploaded(path))
+        .map(|(file, dump)| BundledCrashDump { file, dump })
+        .collect();
+    let bundle = Bundle {
+        created_at_secs: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        tool_build_version: get_git_rev(),
+        os: OS,
+        arch: ARCH,
+        crash_dumps,
+    };
+    fs::write(&opt.output, serde_json::to_vec_pretty(&bundle)?)?;
+
+    println!(
+        "Bundled {} crash dumps from {} into {}",
+        bundle.crash_dumps.len(),
+        crash_dump_dir.display(),
+        opt.output.display()
+    );
+    Ok(())
+}
@@ -5,6 +5,7 @@ use anyhow::Result;
 use reqwest::{blocking, Url};
 use std::collections::HashMap;
 
+pub mod admin_service;
 pub mod node_debug_service;
 
 /// Implement default utility client for NodeDebugInterface
@@ -1,7 +1,8 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{format_err, Context, Result};
+use anyhow::{bail, format_err, Context, Result};
+use aptos_infallible::Mutex;
 use aptos_logger::*;
 use aptos_rest_client::{Client as RestClient, PendingTransaction, Response};
 use aptos_sdk::{
@@ -15,7 +16,6 @@ use aptos_sdk::{
     },
 };
 use futures::future::{try_join_all, FutureExt};
-use itertools::zip;
 use rand::{
     distributions::{Distribution, Standard},
     seq::{IteratorRandom, SliceRandom},
@@ -38,11 +38,13 @@ use tokio::{runtime::Handle, task::JoinHandle, time};
 pub mod atomic_histogram;
 pub mod cluster;
 pub mod instance;
+pub mod workload;
 
 use aptos_crypto::ed25519::Ed25519PrivateKey;
 use aptos_sdk::types::AccountKey;
 use atomic_histogram::*;
 use rand::rngs::StdRng;
+use workload::*;
 
 /// Max transactions per account in mempool
 const MAX_TXN_BATCH_SIZE: usize = 100;
@@ -52,6 +54,7 @@ const TXN_EXPIRATION_SECONDS: u64 = 180;
 const TXN_MAX_WAIT: Duration = Duration::from_secs(TXN_EXPIRATION_SECONDS as u64 + 30);
 const MAX_CHILD_VASP_NUM: usize = 65536;
 const MAX_VASP_ACCOUNT_NUM: usize = 16;
+const TPS_CONTROL_INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(Clone)]
 pub struct EmitThreadParams {
@@ -77,6 +80,8 @@ pub struct EmitJobRequest {
     gas_price: u64,
     invalid_transaction_ratio: usize,
     vasp: bool,
+    transaction_mix: TransactionMix,
+    nft_minter: Option<Arc<Mutex<LocalAccount>>>,
 }
 
 impl Default for EmitJobRequest {
@@ -89,6 +94,8 @@ impl Default for EmitJobRequest {
             gas_price: 0,
             invalid_transaction_ratio: 0,
             vasp: false,
+            transaction_mix: TransactionMix::default(),
+            nft_minter: None,
         }
     }
 }
@@ -145,6 +152,17 @@ impl EmitJobRequest {
         self.vasp = true;
         self
     }
+
+    pub fn transaction_mix(mut self, transaction_mix: TransactionMix) -> Self {
+        self.transaction_mix = transaction_mix;
+        self
+    }
+
+    /// The BARS account, which signs the NFT mint transactions of all the workers
+    pub fn nft_minter(mut self, nft_minter: LocalAccount) -> Self {
+        self.nft_minter = Some(Arc::new(Mutex::new(nft_minter)));
+        self
+    }
 }
 
 #[derive(Debug, Default)]
@@ -162,6 +180,8 @@ pub struct TxnStatsRate {
     pub committed: u64,
     pub expired: u64,
     pub latency: u64,
+    pub p50_latency: u64,
+    pub p90_latency: u64,
     pub p99_latency: u64,
 }
 
@@ -182,6 +202,18 @@ pub struct EmitJob {
     workers: Vec<Worker>,
     stop: Arc<AtomicBool>,
     stats: Arc<StatsAccumulator>,
+    /// The wait of the workers between the starts of their batches, which paces the emission
+    wait_millis: Arc<AtomicU64>,
+    /// The number of transactions sent by all the workers in one batch each
+    txns_per_round: u64,
+}
+
+/// The transactions of a batch sent by an account, which are committed once the sequence number
+/// of the account reaches `sequence_number`
+struct PendingTxns {
+    address: AccountAddress,
+    sequence_number: u64,
+    count: u64,
 }
 
 struct SubmissionWorker {
@@ -190,18 +222,22 @@ struct SubmissionWorker {
     all_addresses: Arc<Vec<AccountAddress>>,
     stop: Arc<AtomicBool>,
     params: EmitThreadParams,
+    wait_millis: Arc<AtomicU64>,
     stats: Arc<StatsAccumulator>,
     txn_factory: TransactionFactory,
     invalid_transaction_ratio: usize,
+    transaction_mix: TransactionMix,
+    /// A parent VASP account creating the accounts of the account creation transactions
+    creator: Option<LocalAccount>,
+    nft_minter: Option<Arc<Mutex<LocalAccount>>>,
     rng: ::rand::rngs::StdRng,
 }
 
 impl SubmissionWorker {
-    #[allow(clippy::collapsible_if)]
     async fn run(mut self, gas_price: u64) -> Vec<LocalAccount> {
-        let wait_duration = Duration::from_millis(self.params.wait_millis);
         while !self.stop.load(Ordering::Relaxed) {
-            let requests = self.gen_requests(gas_price);
+            let wait_duration = Duration::from_millis(self.wait_millis.load(Ordering::Relaxed));
+            let (requests, pending) = self.gen_requests(gas_price);
             let num_requests = requests.len();
             let start_time = Instant::now();
             let wait_until = start_time + wait_duration;
@@ -215,47 +251,33 @@ impl SubmissionWorker {
                     warn!("[{:?}] Failed to submit request: {:?}", self.client, e);
                 }
             }
-            if self.params.wait_committed {
-                if let Err(uncommitted) =
-                    wait_for_accounts_sequence(&self.client, &mut self.accounts).await
-                {
-                    let num_committed = (num_requests - uncommitted.len()) as u64;
-                    // To avoid negative result caused by uncommitted tx occur
-                    // Simplified from:
-                    // end_time * num_committed - (txn_offset_time/num_requests) * num_committed
-                    // to
-                    // (end_time - txn_offset_time / num_requests) * num_committed
-                    let latency = (Instant::now() - start_time).as_millis() as u64
-                        - txn_offset_time / num_requests as u64;
-                    let committed_latency = latency * num_committed as u64;
-                    self.stats
-                        .committed
-                        .fetch_add(num_committed, Ordering::Relaxed);
-                    self.stats
-                        .expired
-                        .fetch_add(uncommitted.len() as u64, Ordering::Relaxed);
-                    self.stats
-                        .latency
-                        .fetch_add(committed_latency, Ordering::Relaxed);
-                    self.stats
-                        .latencies
-                        .record_data_point(latency, num_committed);
+            if self.params.wait_committed && num_requests > 0 {
+                let num_uncommitted = wait_for_pending_txns(&self.client, &pending).await;
+                // To avoid negative result caused by uncommitted tx occur
+                // Simplified from:
+                // end_time * num_committed - (txn_offset_time/num_requests) * num_committed
+                // to
+                // (end_time - txn_offset_time / num_requests) * num_committed
+                let num_committed = (num_requests as u64).saturating_sub(num_uncommitted);
+                let latency = (Instant::now() - start_time).as_millis() as u64
+                    - txn_offset_time / num_requests as u64;
+                self.stats
+                    .committed
+                    .fetch_add(num_committed, Ordering::Relaxed);
+                self.stats
+                    .expired
+                    .fetch_add(num_uncommitted, Ordering::Relaxed);
+                self.stats
+                    .latency
+                    .fetch_add(latency * num_committed, Ordering::Relaxed);
+                self.stats
+                    .latencies
+                    .record_data_point(latency, num_committed);
+                if num_uncommitted > 0 {
                     info!(
-                        "[{:?}] Transactions were not committed before expiration: {:?}",
-                        self.client, uncommitted
+                        "[{:?}] {} transactions were not committed before expiration",
+                        self.client, num_uncommitted
                     );
-                } else {
-                    let latency = (Instant::now() - start_time).as_millis() as u64
-                        - txn_offset_time / num_requests as u64;
-                    self.stats
-                        .committed
-                        .fetch_add(num_requests as u64, Ordering::Relaxed);
-                    self.stats
-                        .latency
-                        .fetch_add(latency * num_requests as u64, Ordering::Relaxed);
-                    self.stats
-                        .latencies
-                        .record_data_point(latency, num_requests as u64);
                 }
             }
             let now = Instant::now();
@@ -266,34 +288,69 @@ impl SubmissionWorker {
         self.accounts
     }
 
-    fn gen_requests(&mut self, gas_price: u64) -> Vec<SignedTransaction> {
+    /// Generates a batch of transactions, returning them with the accounts which sent them
+    fn gen_requests(&mut self, gas_price: u64) -> (Vec<SignedTransaction>, Vec<PendingTxns>) {
         let batch_size = max(MAX_TXN_BATCH_SIZE, self.accounts.len());
         let accounts = self
             .accounts
             .iter_mut()
             .choose_multiple(&mut self.rng, batch_size);
         let mut requests = Vec::with_capacity(accounts.len());
+        let mut pending = Vec::with_capacity(accounts.len());
         let invalid_size = if self.invalid_transaction_ratio != 0 {
             // if enable mix invalid tx, at least 1 invalid tx per batch
             max(1, accounts.len() * self.invalid_transaction_ratio / 100)
         } else {
             0
         };
+        let creator_sequence_number = self.creator.as_ref().map(|c| c.sequence_number());
+        let mut num_nft_mints = 0;
+        let mut nft_minter_sequence_number = 0;
         let mut num_valid_tx = accounts.len() - invalid_size;
         for sender in accounts {
+            let sender_sequence_number = sender.sequence_number();
             let receiver = self
                 .all_addresses
                 .choose(&mut self.rng)
                 .expect("all_addresses can't be empty");
             let request = if num_valid_tx > 0 {
                 num_valid_tx -= 1;
-                gen_transfer_txn_request(
-                    sender,
-                    receiver,
-                    SEND_AMOUNT,
-                    &self.txn_factory,
-                    gas_price,
-                )
+                match self.transaction_mix.sample(&mut self.rng) {
+                    TransactionType::P2P => gen_transfer_txn_request(
+                        sender,
+                        receiver,
+                        SEND_AMOUNT,
+                        &self.txn_factory,
+                        gas_price,
+                    ),
+                    TransactionType::AccountCreation => gen_account_creation_txn_request(
+                        self.creator
+                            .as_mut()
+                            .expect("account creation requires a creator"),
+                        &LocalAccount::generate(&mut self.rng),
+                        &self.txn_factory,
+                        gas_price,
+                    ),
+                    TransactionType::PublishModule => {
+                        gen_publish_module_txn_request(sender, &self.txn_factory, gas_price)
+                    }
+                    TransactionType::NftMint => {
+                        let mut minter = self
+                            .nft_minter
+                            .as_ref()
+                            .expect("NFT mint requires a minter")
+                            .lock();
+                        num_nft_mints += 1;
+                        let request = gen_nft_mint_txn_request(
+                            &mut minter,
+                            &sender.address(),
+                            &self.txn_factory,
+                            gas_price,
+                        );
+                        nft_minter_sequence_number = minter.sequence_number();
+                        request
+                    }
+                }
             } else {
                 generate_invalid_transaction(
                     sender,
@@ -306,8 +363,34 @@ impl SubmissionWorker {
                 )
             };
             requests.push(request);
+            if sender.sequence_number() > sender_sequence_number {
+                pending.push(PendingTxns {
+                    address: sender.address(),
+                    sequence_number: sender.sequence_number(),
+                    count: sender.sequence_number() - sender_sequence_number,
+                });
+            }
+        }
+        if let (Some(creator), Some(sequence_number)) = (&self.creator, creator_sequence_number) {
+            if creator.sequence_number() > sequence_number {
+                pending.push(PendingTxns {
+                    address: creator.address(),
+                    sequence_number: creator.sequence_number(),
+                    count: creator.sequence_number() - sequence_number,
+                });
+            }
         }
-        requests
+        if num_nft_mints > 0 {
+            // The minter is shared with the other workers, so its sequence number reaching the
+            // one of the last mint of this batch means the mints of the batch are committed
+            let minter = self.nft_minter.as_ref().unwrap().lock();
+            pending.push(PendingTxns {
+                address: minter.address(),
+                sequence_number: nft_minter_sequence_number,
+                count: num_nft_mints,
+            });
+        }
+        (requests, pending)
     }
 }
 
@@ -380,6 +463,14 @@ impl<'t, 'd> TxnEmitter<'t, 'd> {
         index: usize,
     ) -> Result<LocalAccount> {
         let file = "vasp".to_owned() + index.to_string().as_str() + ".key";
+        self.load_account_from_key_file(client, &file).await
+    }
+
+    pub async fn load_account_from_key_file(
+        &self,
+        client: &RestClient,
+        file: &str,
+    ) -> Result<LocalAccount> {
         let mint_key: Ed25519PrivateKey = generate_key::load_key(file);
         let account_key = AccountKey::from_private_key(mint_key);
         let address = account_key.authentication_key().derived_address();
@@ -387,8 +478,9 @@ impl<'t, 'd> TxnEmitter<'t, 'd> {
             .await
             .map_err(|e| {
                 format_err!(
-                    "query_sequence_numbers on {:?} for dd account failed: {}",
+                    "query_sequence_numbers on {:?} for account {} failed: {}",
                     client,
+                    address,
                     e
                 )
             })?[0];
@@ -541,6 +633,16 @@ impl<'t, 'd> TxnEmitter<'t, 'd> {
     }
 
     pub async fn start_job(&mut self, req: EmitJobRequest) -> Result<EmitJob> {
+        if req.transaction_mix.contains(TransactionType::NftMint) && req.nft_minter.is_none() {
+            bail!("NFT mint transactions require the BARS account as NFT minter");
+        }
+        if req
+            .transaction_mix
+            .contains(TransactionType::AccountCreation)
+            && req.vasp
+        {
+            bail!("Account creation transactions aren't supported with VASP accounts");
+        }
         let workers_per_endpoint = match req.workers_per_endpoint {
             Some(x) => x,
             None => {
@@ -564,12 +666,22 @@ impl<'t, 'd> TxnEmitter<'t, 'd> {
         );
         self.mint_accounts(&req, num_accounts).await?;
         let all_accounts = self.accounts.split_off(self.accounts.len() - num_accounts);
+        let mut creators = if req
+            .transaction_mix
+            .contains(TransactionType::AccountCreation)
+        {
+            self.create_account_creators(&req, num_clients).await?
+        } else {
+            vec![]
+        }
+        .into_iter();
         let mut workers = vec![];
         let all_addresses: Vec<_> = all_accounts.iter().map(|d| d.address()).collect();
         let all_addresses = Arc::new(all_addresses);
         let mut all_accounts = all_accounts.into_iter();
         let stop = Arc::new(AtomicBool::new(false));
         let stats = Arc::new(StatsAccumulator::default());
+        let wait_millis = Arc::new(AtomicU64::new(req.thread_params.wait_millis));
         let tokio_handle = Handle::current();
         for client in req.rest_clients {
             for _ in 0..workers_per_endpoint {
@@ -584,9 +696,13 @@ impl<'t, 'd> TxnEmitter<'t, 'd> {
                     all_addresses,
                     stop,
                     params,
+                    wait_millis: wait_millis.clone(),
                     stats,
                     txn_factory: self.txn_factory.clone(),
                     invalid_transaction_ratio: req.invalid_transaction_ratio,
+                    transaction_mix: req.transaction_mix.clone(),
+                    creator: creators.next(),
+                    nft_minter: req.nft_minter.clone(),
                     rng: self.from_rng(),
                 };
                 let join_handle = tokio_handle.spawn(worker.run(req.gas_price).boxed());
@@ -598,9 +714,42 @@ impl<'t, 'd> TxnEmitter<'t, 'd> {
             workers,
             stop,
             stats,
+            wait_millis,
+            txns_per_round: num_accounts as u64,
         })
     }
 
+    /// Creates and funds a parent VASP account for each worker, to create the accounts of its
+    /// account creation transactions
+    async fn create_account_creators(
+        &mut self,
+        req: &EmitJobRequest,
+        num_creators: usize,
+    ) -> Result<Vec<LocalAccount>> {
+        let creators = self
+            .get_seed_accounts(&req.rest_clients, num_creators, false)
+            .await?;
+        let coins_per_creator = SEND_AMOUNT * MAX_TXNS;
+        let txn_factory = self.txn_factory.clone();
+        let client = self.pick_mint_client(&req.rest_clients).clone();
+        let rng = self.from_rng();
+        let faucet_account = self
+            .get_money_source(coins_per_creator * num_creators as u64)
+            .await?;
+        mint_to_new_accounts(
+            faucet_account,
+            &creators,
+            coins_per_creator,
+            100,
+            client,
+            &txn_factory,
+            rng,
+        )
+        .await
+        .map_err(|e| format_err!("Failed to mint account creators: {}", e))?;
+        Ok(creators)
+    }
+
     pub async fn stop_job(&mut self, job: EmitJob) -> TxnStats {
         job.stop.store(true, Ordering::Relaxed);
         for worker in job.workers {
@@ -642,6 +791,65 @@ impl<'t, 'd> TxnEmitter<'t, 'd> {
         Ok(stats)
     }
 
+    /// Emits transactions through the phases, returning the stats of each phase. During a phase
+    /// with a target rate, the wait of the workers is adjusted periodically for the rate at which
+    /// they submit transactions to converge to the target.
+    pub async fn emit_txn_for_phases(
+        &mut self,
+        phases: &[EmitPhase],
+        emit_job_request: EmitJobRequest,
+    ) -> Result<Vec<TxnStats>> {
+        let job = self.start_job(emit_job_request).await?;
+        let mut phase_stats = Vec::with_capacity(phases.len());
+        let mut prev_stats = self.peek_job_stats(&job);
+        for phase in phases {
+            println!(
+                "starting phase of {} secs, target TPS: {:?}",
+                phase.duration.as_secs(),
+                phase.target_tps
+            );
+            self.run_phase(&job, phase).await;
+            let stats = self.peek_job_stats(&job);
+            let delta = &stats - &prev_stats;
+            println!("phase stats: {}", delta.rate(phase.duration));
+            phase_stats.push(delta);
+            prev_stats = stats;
+        }
+        self.stop_job(job).await;
+        Ok(phase_stats)
+    }
+
+    async fn run_phase(&self, job: &EmitJob, phase: &EmitPhase) {
+        let deadline = Instant::now() + phase.duration;
+        let target_tps = match phase.target_tps {
+            Some(target_tps) => target_tps.get(),
+            None => {
+                job.wait_millis.store(0, Ordering::Relaxed);
+                time::sleep(phase.duration).await;
+                return;
+            }
+        };
+        // Start from the wait which would reach the target if transactions committed instantly
+        let mut wait_millis = job.txns_per_round * 1000 / target_tps;
+        job.wait_millis.store(wait_millis, Ordering::Relaxed);
+        let mut prev_stats = self.peek_job_stats(job);
+        let mut prev_instant = Instant::now();
+        while Instant::now() < deadline {
+            time::sleep(min(
+                TPS_CONTROL_INTERVAL,
+                deadline.saturating_duration_since(Instant::now()),
+            ))
+            .await;
+            let stats = self.peek_job_stats(job);
+            let elapsed_millis = max(1, prev_instant.elapsed().as_millis() as u64);
+            let actual_tps = (stats.submitted - prev_stats.submitted) * 1000 / elapsed_millis;
+            wait_millis = next_wait_millis(wait_millis, actual_tps, target_tps);
+            job.wait_millis.store(wait_millis, Ordering::Relaxed);
+            prev_stats = stats;
+            prev_instant = Instant::now();
+        }
+    }
+
     pub async fn emit_txn_for_with_stats(
         &mut self,
         duration: Duration,
@@ -715,25 +923,23 @@ pub async fn execute_and_wait_transactions(
     Ok(())
 }
 
-async fn wait_for_accounts_sequence(
-    client: &RestClient,
-    accounts: &mut [LocalAccount],
-) -> Result<(), Vec<AccountAddress>> {
+/// Waits for the pending transactions to be committed, returning how many weren't before expiration
+async fn wait_for_pending_txns(client: &RestClient, pending: &[PendingTxns]) -> u64 {
     let deadline = Instant::now() + Duration::from_secs(TXN_EXPIRATION_SECONDS); //TXN_MAX_WAIT;
-    let addresses: Vec<_> = accounts.iter().map(|d| d.address()).collect();
-    let mut uncommitted = addresses.clone().into_iter().collect::<HashSet<_>>();
+    let addresses: Vec<_> = pending.iter().map(|p| p.address).collect();
+    let mut uncommitted = (0..pending.len()).collect::<HashSet<_>>();
 
     while Instant::now() < deadline {
         match query_sequence_numbers(client, &addresses).await {
             Ok(sequence_numbers) => {
-                for (account, sequence_number) in zip(accounts.iter(), &sequence_numbers) {
-                    if account.sequence_number() == *sequence_number {
-                        uncommitted.remove(&account.address());
+                for (i, sequence_number) in sequence_numbers.iter().enumerate() {
+                    if *sequence_number >= pending[i].sequence_number {
+                        uncommitted.remove(&i);
                     }
                 }
 
                 if uncommitted.is_empty() {
-                    return Ok(());
+                    return 0;
                 }
             }
             Err(e) => {
@@ -747,7 +953,7 @@ async fn wait_for_accounts_sequence(
         time::sleep(Duration::from_millis(500)).await;
     }
 
-    Err(uncommitted.into_iter().collect())
+    uncommitted.into_iter().map(|i| pending[i].count).sum()
 }
 
 pub async fn query_sequence_numbers(
@@ -964,6 +1170,8 @@ impl TxnStats {
             } else {
                 self.latency / self.committed
             },
+            p50_latency: self.latency_buckets.percentile(50, 100),
+            p90_latency: self.latency_buckets.percentile(90, 100),
             p99_latency: self.latency_buckets.percentile(99, 100),
         }
     }
@@ -997,8 +1205,8 @@ impl fmt::Display for TxnStatsRate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "submitted: {} txn/s, committed: {} txn/s, expired: {} txn/s, latency: {} ms, p99 latency: {} ms",
-            self.submitted, self.committed, self.expired, self.latency, self.p99_latency,
+            "submitted: {} txn/s, committed: {} txn/s, expired: {} txn/s, latency: {} ms, p50 latency: {} ms, p90 latency: {} ms, p99 latency: {} ms",
+            self.submitted, self.committed, self.expired, self.latency, self.p50_latency, self.p90_latency, self.p99_latency,
         )
     }
 }
@@ -19,8 +19,11 @@ use std::{
 use structopt::StructOpt;
 use termion::color;
 use transaction_emitter::{
-    cluster::Cluster, instance::Instance, query_sequence_numbers, EmitJobRequest, EmitThreadParams,
-    TxnEmitter,
+    cluster::Cluster,
+    instance::Instance,
+    query_sequence_numbers,
+    workload::{EmitPhase, TransactionMix},
+    EmitJobRequest, EmitThreadParams, TxnEmitter,
 };
 
 #[derive(StructOpt, Debug)]
@@ -59,6 +62,23 @@ struct Args {
     duration: u64,
     #[structopt(long, help = "Percentage of invalid txs", default_value = "0")]
     invalid_tx: usize,
+    #[structopt(
+        long,
+        help = "Weighted transaction types to emit, e.g. p2p:70,account_creation:20,publish_module:10",
+        default_value = "p2p"
+    )]
+    transaction_mix: TransactionMix,
+    #[structopt(
+        long,
+        use_delimiter = true,
+        help = "Phases to emit through instead of --duration, as duration_secs[:target_tps], e.g. 60:100,60:500,60:1000"
+    )]
+    phases: Vec<EmitPhase>,
+    #[structopt(
+        long,
+        help = "Key file of the BARS account, required to emit nft_mint transactions"
+    )]
+    nft_minter_key: Option<String>,
 }
 
 #[tokio::main]
@@ -121,6 +141,27 @@ async fn emit_tx(cluster: &Cluster, args: &Args) -> Result<()> {
     if args.vasp {
         emit_job_request = emit_job_request.vasp();
     }
+    emit_job_request = emit_job_request.transaction_mix(args.transaction_mix.clone());
+    if let Some(nft_minter_key) = &args.nft_minter_key {
+        let nft_minter = emitter
+            .load_account_from_key_file(&cluster.random_instance().rest_client(), nft_minter_key)
+            .await?;
+        emit_job_request = emit_job_request.nft_minter(nft_minter);
+    }
+    if !args.phases.is_empty() {
+        let phase_stats = emitter
+            .emit_txn_for_phases(&args.phases, emit_job_request)
+            .await?;
+        for (phase, stats) in zip(&args.phases, &phase_stats) {
+            println!(
+                "Phase of {} secs, target TPS {:?}: {}",
+                phase.duration.as_secs(),
+                phase.target_tps,
+                stats.rate(phase.duration)
+            );
+        }
+        return Ok(());
+    }
     let stats = emitter
         .emit_txn_for_with_stats(duration, emit_job_request, 10)
         .await?;
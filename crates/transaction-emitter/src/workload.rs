@@ -0,0 +1,289 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, format_err, Result};
+use aptos_sdk::{
+    move_types::{account_address::AccountAddress, identifier::Identifier},
+    transaction_builder::{Currency, TransactionFactory},
+    types::{transaction::SignedTransaction, LocalAccount},
+};
+use aptos_transaction_builder::experimental_stdlib;
+use move_binary_format::file_format::empty_module;
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+use std::{fmt, num::NonZeroU64, str::FromStr, time::Duration};
+
+/// The kinds of transactions the emitter generates
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransactionType {
+    /// A transfer of coins to another emitter account
+    P2P,
+    /// The creation of a new child VASP account, by a parent VASP account of the worker
+    AccountCreation,
+    /// The publishing of a new module under the sender's account
+    PublishModule,
+    /// The minting of a BARS NFT to the sender, by the BARS account. Requires a network with the
+    /// experimental framework, and the BARS account to be given to the emitter
+    NftMint,
+}
+
+impl FromStr for TransactionType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "p2p" => Ok(TransactionType::P2P),
+            "account_creation" => Ok(TransactionType::AccountCreation),
+            "publish_module" => Ok(TransactionType::PublishModule),
+            "nft_mint" => Ok(TransactionType::NftMint),
+            _ => bail!("Unknown transaction type: {}", s),
+        }
+    }
+}
+
+impl fmt::Display for TransactionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TransactionType::P2P => "p2p",
+            TransactionType::AccountCreation => "account_creation",
+            TransactionType::PublishModule => "publish_module",
+            TransactionType::NftMint => "nft_mint",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Weighted transaction types, the type of each transaction being picked with a probability
+/// proportional to its weight.
+#[derive(Clone, Debug)]
+pub struct TransactionMix {
+    types: Vec<(TransactionType, usize)>,
+    index: WeightedIndex<usize>,
+}
+
+impl TransactionMix {
+    pub fn new(types: Vec<(TransactionType, usize)>) -> Result<Self> {
+        let index = WeightedIndex::new(types.iter().map(|(_, weight)| *weight))
+            .map_err(|e| format_err!("Invalid transaction mix weights: {}", e))?;
+        Ok(Self { types, index })
+    }
+
+    /// Whether transactions of the type can be picked
+    pub fn contains(&self, transaction_type: TransactionType) -> bool {
+        self.types
+            .iter()
+            .any(|(t, weight)| *t == transaction_type && *weight > 0)
+    }
+
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> TransactionType {
+        self.types[self.index.sample(rng)].0
+    }
+}
+
+impl Default for TransactionMix {
+    fn default() -> Self {
+        Self::new(vec![(TransactionType::P2P, 1)]).unwrap()
+    }
+}
+
+/// Parses comma separated `type[:weight]`, e.g. `p2p:70,account_creation:20,publish_module:10`.
+/// The weight defaults to 1.
+impl FromStr for TransactionMix {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let types = s
+            .split(',')
+            .map(|entry| {
+                let mut parts = entry.trim().splitn(2, ':');
+                let transaction_type = parts.next().unwrap_or_default().parse()?;
+                let weight = match parts.next() {
+                    Some(weight) => weight.parse()?,
+                    None => 1,
+                };
+                Ok((transaction_type, weight))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::new(types)
+    }
+}
+
+impl fmt::Display for TransactionMix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries = self
+            .types
+            .iter()
+            .map(|(t, weight)| format!("{}:{}", t, weight))
+            .collect::<Vec<_>>();
+        write!(f, "{}", entries.join(","))
+    }
+}
+
+/// A phase of an emission, during which transactions are emitted at the target rate, or as fast
+/// as the workers can if there is none.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmitPhase {
+    pub duration: Duration,
+    pub target_tps: Option<NonZeroU64>,
+}
+
+impl EmitPhase {
+    /// Splits the duration into `steps` phases, with target rates increasing linearly from
+    /// `from_tps` to `to_tps`.
+    pub fn ramp(from_tps: u64, to_tps: u64, steps: u64, duration: Duration) -> Vec<EmitPhase> {
+        let steps = steps.max(1);
+        (0..steps)
+            .map(|step| {
+                let tps = if steps == 1 {
+                    to_tps
+                } else if to_tps >= from_tps {
+                    from_tps + (to_tps - from_tps) * step / (steps - 1)
+                } else {
+                    from_tps - (from_tps - to_tps) * step / (steps - 1)
+                };
+                EmitPhase {
+                    duration: duration / steps as u32,
+                    target_tps: NonZeroU64::new(tps),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Parses `duration_secs[:target_tps]`, e.g. `60:1000`.
+impl FromStr for EmitPhase {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.trim().splitn(2, ':');
+        let duration = Duration::from_secs(parts.next().unwrap_or_default().parse()?);
+        let target_tps = match parts.next() {
+            Some(tps) => Some(
+                tps.parse::<NonZeroU64>()
+                    .map_err(|e| format_err!("Invalid target TPS {}: {}", tps, e))?,
+            ),
+            None => None,
+        };
+        Ok(Self {
+            duration,
+            target_tps,
+        })
+    }
+}
+
+/// Scales the wait of the workers by the ratio of the actual rate to the target rate, changing it
+/// by at most a factor of 2 at once to dampen oscillations.
+pub fn next_wait_millis(wait_millis: u64, actual_tps: u64, target_tps: u64) -> u64 {
+    let wait_millis = wait_millis.max(1);
+    let next = (wait_millis as u128 * actual_tps as u128 / target_tps.max(1) as u128) as u64;
+    next.max(wait_millis / 2).min(wait_millis * 2)
+}
+
+pub fn gen_account_creation_txn_request(
+    creator: &mut LocalAccount,
+    new_account: &LocalAccount,
+    txn_factory: &TransactionFactory,
+    gas_price: u64,
+) -> SignedTransaction {
+    creator.sign_with_transaction_builder(
+        txn_factory
+            .create_child_vasp_account(Currency::XUS, new_account.authentication_key(), false, 0)
+            .gas_unit_price(gas_price),
+    )
+}
+
+/// Publishes an empty module under the sender's account, named after the sequence number of the
+/// transaction for every module to be new.
+pub fn gen_publish_module_txn_request(
+    sender: &mut LocalAccount,
+    txn_factory: &TransactionFactory,
+    gas_price: u64,
+) -> SignedTransaction {
+    let mut module = empty_module();
+    module.address_identifiers[0] = sender.address();
+    module.identifiers[0] =
+        Identifier::new(format!("Emitted{}", sender.sequence_number())).unwrap();
+    let mut code = vec![];
+    module
+        .serialize(&mut code)
+        .expect("Failed to serialize module");
+    sender.sign_with_transaction_builder(txn_factory.module(code).gas_unit_price(gas_price))
+}
+
+pub fn gen_nft_mint_txn_request(
+    minter: &mut LocalAccount,
+    receiver: &AccountAddress,
+    txn_factory: &TransactionFactory,
+    gas_price: u64,
+) -> SignedTransaction {
+    minter.sign_with_transaction_builder(
+        txn_factory
+            .payload(experimental_stdlib::encode_mint_bars_script_function(
+                *receiver,
+                b"transaction_emitter".to_vec(),
+                b"transaction_emitter".to_vec(),
+                1,
+            ))
+            .gas_unit_price(gas_price),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_transaction_mix() {
+        let mix: TransactionMix = "p2p:70, account_creation:30,publish_module"
+            .parse()
+            .unwrap();
+        assert!(mix.contains(TransactionType::P2P));
+        assert!(mix.contains(TransactionType::PublishModule));
+        assert!(!mix.contains(TransactionType::NftMint));
+        assert_eq!(
+            mix.to_string(),
+            "p2p:70,account_creation:30,publish_module:1"
+        );
+
+        let mix: TransactionMix = "nft_mint:0,p2p:1".parse().unwrap();
+        assert!(!mix.contains(TransactionType::NftMint));
+        let mut rng = rand::thread_rng();
+        assert!((0..100).all(|_| mix.sample(&mut rng) == TransactionType::P2P));
+
+        assert!("p2p:0".parse::<TransactionMix>().is_err());
+        assert!("transfer:1".parse::<TransactionMix>().is_err());
+    }
+
+    #[test]
+    fn test_next_wait_millis() {
+        // Too fast, wait longer
+        assert_eq!(next_wait_millis(100, 150, 100), 150);
+        // Too slow, wait less
+        assert_eq!(next_wait_millis(100, 80, 100), 80);
+        // Changes are bounded
+        assert_eq!(next_wait_millis(100, 1000, 100), 200);
+        assert_eq!(next_wait_millis(100, 0, 100), 50);
+        // A wait of 0 can still be increased
+        assert_eq!(next_wait_millis(0, 200, 100), 2);
+    }
+
+    #[test]
+    fn test_phases() {
+        let phase: EmitPhase = "60:1000".parse().unwrap();
+        assert_eq!(phase.duration, Duration::from_secs(60));
+        assert_eq!(phase.target_tps, NonZeroU64::new(1000));
+        assert_eq!("30".parse::<EmitPhase>().unwrap().target_tps, None);
+        assert!("30:0".parse::<EmitPhase>().is_err());
+
+        let tps = EmitPhase::ramp(100, 400, 4, Duration::from_secs(120))
+            .into_iter()
+            .map(|phase| {
+                assert_eq!(phase.duration, Duration::from_secs(30));
+                phase.target_tps.unwrap().get()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(tps, vec![100, 200, 300, 400]);
+    }
+}
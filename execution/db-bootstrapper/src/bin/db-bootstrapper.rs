@@ -57,6 +57,7 @@ fn main() -> Result<()> {
             NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
             RocksdbConfig::default(),
             opt.account_count_migration,
+            true, /* state_value_index */
         )
     } else {
         // When not committing, we open the DB as secondary so the tool is usable along side a
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_crypto::HashValue;
-use aptos_types::ledger_info::LedgerInfoWithSignatures;
+use aptos_types::{ledger_info::LedgerInfoWithSignatures, transaction::Version};
 use consensus_types::block::Block;
 use executor_types::{Error, StateComputeResult};
 
@@ -13,6 +13,12 @@ pub trait ExecutionCorrectness: Send {
 
     fn reset(&self) -> Result<(), Error>;
 
+    /// Waits for the committed blocks to be persisted.
+    fn flush_commits(&self) -> Result<(), Error>;
+
+    /// Waits for the blocks committed up to `version` to be persisted.
+    fn wait_for_persisted(&self, version: Version) -> Result<(), Error>;
+
     /// Executes a block.
     fn execute_block(
         &self,
@@ -69,7 +69,11 @@ impl ExecutionCorrectnessManager {
         let storage_address = config.storage.address;
         let timeout_ms = config.storage.timeout_ms;
         match &config.execution.service {
-            ExecutionCorrectnessService::Local => Self::new_local(local_db, execution_prikey),
+            ExecutionCorrectnessService::Local => Self::new_local(
+                local_db,
+                execution_prikey,
+                config.execution.max_pending_commits,
+            ),
             ExecutionCorrectnessService::Serializer => {
                 Self::new_serializer(storage_address, execution_prikey, timeout_ms)
             }
@@ -83,8 +87,13 @@ impl ExecutionCorrectnessManager {
         }
     }
 
-    pub fn new_local(db: DbReaderWriter, execution_prikey: Option<Ed25519PrivateKey>) -> Self {
-        let block_executor = Box::new(BlockExecutor::<AptosVM>::new(db));
+    pub fn new_local(
+        db: DbReaderWriter,
+        execution_prikey: Option<Ed25519PrivateKey>,
+        max_pending_commits: usize,
+    ) -> Self {
+        let block_executor =
+            Box::new(BlockExecutor::<AptosVM>::new(db).with_pipelined_commit(max_pending_commits));
         Self {
             internal_execution_correctness: ExecutionCorrectnessWrapper::Local(Arc::new(
                 LocalService::new(block_executor, execution_prikey),
@@ -3,7 +3,7 @@
 
 use crate::execution_correctness::ExecutionCorrectness;
 use aptos_crypto::{ed25519::Ed25519PrivateKey, traits::SigningKey, HashValue};
-use aptos_types::ledger_info::LedgerInfoWithSignatures;
+use aptos_types::{ledger_info::LedgerInfoWithSignatures, transaction::Version};
 use consensus_types::{block::Block, vote_proposal::VoteProposal};
 use executor_types::{BlockExecutorTrait, Error, StateComputeResult};
 use std::{boxed::Box, sync::Arc};
@@ -47,6 +47,14 @@ impl ExecutionCorrectness for LocalClient {
         self.internal.block_executor.reset()
     }
 
+    fn flush_commits(&self) -> Result<(), Error> {
+        self.internal.block_executor.flush_commits()
+    }
+
+    fn wait_for_persisted(&self, version: Version) -> Result<(), Error> {
+        self.internal.block_executor.wait_for_persisted(version)
+    }
+
     fn execute_block(
         &self,
         block: Block,
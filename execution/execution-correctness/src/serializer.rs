@@ -3,7 +3,7 @@
 
 use crate::execution_correctness::ExecutionCorrectness;
 use aptos_crypto::{ed25519::Ed25519PrivateKey, traits::SigningKey, HashValue};
-use aptos_types::ledger_info::LedgerInfoWithSignatures;
+use aptos_types::{ledger_info::LedgerInfoWithSignatures, transaction::Version};
 use consensus_types::{block::Block, vote_proposal::VoteProposal};
 use executor_types::{BlockExecutorTrait, Error, StateComputeResult};
 use serde::{Deserialize, Serialize};
@@ -15,6 +15,8 @@ pub enum ExecutionCorrectnessInput {
     Reset,
     ExecuteBlock(Box<(Block, HashValue)>),
     CommitBlocks(Box<(Vec<HashValue>, LedgerInfoWithSignatures)>),
+    FlushCommits,
+    WaitForPersisted(Version),
 }
 
 pub struct SerializerService {
@@ -64,6 +66,12 @@ impl SerializerService {
                     .internal
                     .commit_blocks(blocks_with_li.0, blocks_with_li.1),
             ),
+            ExecutionCorrectnessInput::FlushCommits => {
+                bcs::to_bytes(&self.internal.flush_commits())
+            }
+            ExecutionCorrectnessInput::WaitForPersisted(version) => {
+                bcs::to_bytes(&self.internal.wait_for_persisted(version))
+            }
         };
         Ok(output?)
     }
@@ -99,6 +107,16 @@ impl ExecutionCorrectness for SerializerClient {
         bcs::from_bytes(&response)?
     }
 
+    fn flush_commits(&self) -> Result<(), Error> {
+        let response = self.request(ExecutionCorrectnessInput::FlushCommits)?;
+        bcs::from_bytes(&response)?
+    }
+
+    fn wait_for_persisted(&self, version: Version) -> Result<(), Error> {
+        let response = self.request(ExecutionCorrectnessInput::WaitForPersisted(version))?;
+        bcs::from_bytes(&response)?
+    }
+
     fn execute_block(
         &self,
         block: Block,
@@ -27,6 +27,6 @@ fn execution_correctness(
     } else {
         (None, None)
     };
-    let execution_correctness_manager = ExecutionCorrectnessManager::new_local(db_rw, prikey);
+    let execution_correctness_manager = ExecutionCorrectnessManager::new_local(db_rw, prikey, 0);
     (execution_correctness_manager.client(), pubkey)
 }
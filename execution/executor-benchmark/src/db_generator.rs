@@ -53,6 +53,7 @@ pub fn run(
             storage_pruner_config, /* pruner */
             RocksdbConfig::default(),
             true, /* account_count_migration */
+            true, /* state_value_index */
         )
         .expect("DB should open."),
     );
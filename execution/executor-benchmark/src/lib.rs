@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod db_generator;
+pub mod replay;
 pub mod transaction_committer;
 pub mod transaction_executor;
 pub mod transaction_generator;
@@ -32,6 +33,7 @@ pub fn init_db_and_executor(config: &NodeConfig) -> (Arc<dyn DbReader>, BlockExe
             NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
             RocksdbConfig::default(),
             true, /* account_count_migration */
+            true, /* state_value_index */
         )
         .expect("DB should open."),
     );
@@ -61,6 +63,7 @@ pub fn run_benchmark(
         NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
         RocksdbConfig::default(),
         true, /* account_count_migration */
+        true, /* state_value_index */
     )
     .expect("db open failure.")
     .create_checkpoint(checkpoint_dir.as_ref().join("aptosdb"))
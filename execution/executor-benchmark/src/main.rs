@@ -3,7 +3,10 @@
 
 use aptos_config::config::StoragePrunerConfig;
 use aptos_secure_push_metrics::MetricsPusher;
-use std::path::PathBuf;
+use aptos_types::transaction::Version;
+use aptos_validator_interface::DBDebuggerInterface;
+use executor_benchmark::replay::{self, ReplayReport};
+use std::{fs, path::PathBuf, process};
 use structopt::StructOpt;
 
 #[global_allocator]
@@ -56,6 +59,50 @@ enum Command {
         )]
         verify: bool,
     },
+    /// Replays blocks recorded in a DB under sequential and parallel execution, reporting the
+    /// throughput of each
+    ReplayBlocks {
+        #[structopt(long, parse(from_os_str))]
+        db_dir: PathBuf,
+
+        #[structopt(long, about = "version to look for the first block from")]
+        start_version: Version,
+
+        #[structopt(long, default_value = "100")]
+        num_blocks: usize,
+
+        #[structopt(
+            long,
+            default_value = "1,2,4,8,16",
+            use_delimiter = true,
+            about = "thread counts to run the parallel executor with"
+        )]
+        concurrency_levels: Vec<usize>,
+
+        #[structopt(
+            long,
+            default_value = "3",
+            about = "number of times to execute the blocks"
+        )]
+        repeats: usize,
+
+        #[structopt(long, parse(from_os_str), about = "file to write the JSON report to")]
+        report: Option<PathBuf>,
+
+        #[structopt(
+            long,
+            parse(from_os_str),
+            about = "JSON report of a previous run to check for regressions against"
+        )]
+        baseline: Option<PathBuf>,
+
+        #[structopt(
+            long,
+            default_value = "0.1",
+            about = "largest tolerated drop in throughput from the baseline, as a fraction"
+        )]
+        max_regression: f64,
+    },
 }
 
 fn main() {
@@ -98,5 +145,43 @@ fn main() {
                 verify,
             );
         }
+        Command::ReplayBlocks {
+            db_dir,
+            start_version,
+            num_blocks,
+            concurrency_levels,
+            repeats,
+            report,
+            baseline,
+            max_regression,
+        } => {
+            let db = DBDebuggerInterface::open(&db_dir).expect("Failed to open DB");
+            let blocks =
+                replay::load_blocks(&db, start_version, num_blocks).expect("Failed to load blocks");
+            let replay_report = replay::run(&db, &blocks, &concurrency_levels, repeats)
+                .expect("Failed to replay blocks");
+            println!("{}", replay_report);
+
+            if let Some(report) = report {
+                fs::write(
+                    report,
+                    serde_json::to_string_pretty(&replay_report).unwrap(),
+                )
+                .expect("Failed to write report");
+            }
+            if let Some(baseline) = baseline {
+                let baseline: ReplayReport =
+                    serde_json::from_slice(&fs::read(baseline).expect("Failed to read baseline"))
+                        .expect("Failed to parse baseline");
+                let regressions =
+                    replay::find_regressions(&replay_report, &baseline, max_regression);
+                for regression in &regressions {
+                    eprintln!("Regression: {}", regression);
+                }
+                if !regressions.is_empty() {
+                    process::exit(1);
+                }
+            }
+        }
     }
 }
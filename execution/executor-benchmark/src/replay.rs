@@ -0,0 +1,411 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Replays blocks recorded in a DB under sequential and parallel execution, measuring the
+//! throughput of each executor configuration. Every block is executed on top of the state it was
+//! originally executed on, so the DB must hold the state of the replayed versions (i.e., it must
+//! not have pruned them). Blocks in a backup can be replayed once restored with `db-restore`.
+
+use anyhow::{format_err, Result};
+use aptos_infallible::RwLock;
+use aptos_state_view::StateView;
+use aptos_types::{
+    access_path::AccessPath,
+    transaction::{Transaction, TransactionOutput, Version},
+};
+use aptos_validator_interface::{AptosValidatorInterface, DebuggerStateView};
+use aptos_vm::{parallel_executor::ParallelAptosVM, AptosVM};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, time::Instant};
+
+/// Number of transactions fetched from the DB at a time while loading blocks.
+const FETCH_CHUNK_SIZE: u64 = 1000;
+
+/// A block of transactions as committed on-chain, starting with its block metadata transaction.
+pub struct RecordedBlock {
+    pub first_version: Version,
+    pub transactions: Vec<Transaction>,
+}
+
+/// Loads the first `num_blocks` blocks committed at or after `start_version`.
+pub fn load_blocks(
+    db: &dyn AptosValidatorInterface,
+    start_version: Version,
+    num_blocks: usize,
+) -> Result<Vec<RecordedBlock>> {
+    let latest_version = db.get_latest_version()?;
+    let mut transactions = vec![];
+    let mut num_block_starts = 0;
+    let mut version = start_version;
+    // One more block start than requested marks the end of the last block
+    while num_block_starts <= num_blocks && version <= latest_version {
+        let limit = std::cmp::min(FETCH_CHUNK_SIZE, latest_version - version + 1);
+        let chunk = db.get_committed_transactions(version, limit)?;
+        num_block_starts += chunk.iter().filter(|txn| is_block_start(txn)).count();
+        transactions.extend(chunk);
+        version += limit;
+    }
+    Ok(split_into_blocks(start_version, transactions, num_blocks))
+}
+
+/// Splits the transactions committed from `first_version` on into at most `num_blocks` blocks,
+/// skipping the transactions preceding the first block start.
+pub fn split_into_blocks(
+    first_version: Version,
+    transactions: Vec<Transaction>,
+    num_blocks: usize,
+) -> Vec<RecordedBlock> {
+    let mut blocks: Vec<RecordedBlock> = vec![];
+    for (version, txn) in (first_version..).zip(transactions) {
+        if is_block_start(&txn) {
+            if blocks.len() == num_blocks {
+                break;
+            }
+            blocks.push(RecordedBlock {
+                first_version: version,
+                transactions: vec![txn],
+            });
+        } else if let Some(block) = blocks.last_mut() {
+            block.transactions.push(txn);
+        }
+    }
+    blocks
+}
+
+fn is_block_start(txn: &Transaction) -> bool {
+    matches!(txn, Transaction::BlockMetadata(_))
+}
+
+/// A configuration of the executor to measure.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExecutorConfig {
+    Sequential,
+    Parallel { concurrency_level: usize },
+}
+
+impl fmt::Display for ExecutorConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExecutorConfig::Sequential => write!(f, "sequential"),
+            ExecutorConfig::Parallel { concurrency_level } => {
+                write!(f, "parallel-{}", concurrency_level)
+            }
+        }
+    }
+}
+
+/// The throughput of the executor configurations measured on the same blocks.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReplayReport {
+    pub first_version: Version,
+    pub num_blocks: usize,
+    pub num_transactions: usize,
+    pub results: Vec<ExecutionResult>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ExecutionResult {
+    pub config: String,
+    pub seconds: f64,
+    pub tps: f64,
+    /// Throughput relative to sequential execution
+    pub speedup: f64,
+    /// Number of blocks the parallel executor handed over to sequential execution
+    pub sequential_fallbacks: usize,
+    /// Number of transactions whose output differs from sequential execution
+    pub mismatched_outputs: usize,
+}
+
+impl fmt::Display for ReplayReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Replayed {} blocks ({} transactions) from version {}",
+            self.num_blocks, self.num_transactions, self.first_version
+        )?;
+        writeln!(
+            f,
+            "{:<16}{:>12}{:>10}{:>12}{:>12}",
+            "config", "tps", "speedup", "fallbacks", "mismatches"
+        )?;
+        for result in &self.results {
+            writeln!(
+                f,
+                "{:<16}{:>12.0}{:>9.2}x{:>12}{:>12}",
+                result.config,
+                result.tps,
+                result.speedup,
+                result.sequential_fallbacks,
+                result.mismatched_outputs
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A view of the state a recorded block was executed on, caching what is read from the DB so
+/// that the measurements aren't dominated by DB reads.
+struct CachedStateView<'a> {
+    state_view: DebuggerStateView<'a>,
+    cache: RwLock<HashMap<AccessPath, Option<Vec<u8>>>>,
+}
+
+impl<'a> StateView for CachedStateView<'a> {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        if let Some(value) = self.cache.read().get(access_path) {
+            return Ok(value.clone());
+        }
+        let value = self.state_view.get(access_path)?;
+        self.cache
+            .write()
+            .insert(access_path.clone(), value.clone());
+        Ok(value)
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+}
+
+/// Executes `blocks` sequentially and then in parallel with each of `concurrency_levels`,
+/// `repeats` times each, and reports the throughput of every configuration.
+pub fn run(
+    db: &dyn AptosValidatorInterface,
+    blocks: &[RecordedBlock],
+    concurrency_levels: &[usize],
+    repeats: usize,
+) -> Result<ReplayReport> {
+    let first_version = blocks
+        .first()
+        .ok_or_else(|| format_err!("No blocks to replay"))?
+        .first_version;
+    let num_transactions = blocks.iter().map(|block| block.transactions.len()).sum();
+    let state_views: Vec<_> = blocks
+        .iter()
+        .map(|block| CachedStateView {
+            state_view: DebuggerStateView::new(db, block.first_version.checked_sub(1)),
+            cache: RwLock::new(HashMap::new()),
+        })
+        .collect();
+
+    // Warm the caches up, recording the outputs every configuration must reproduce
+    let mut expected_outputs = vec![];
+    for (block, state_view) in blocks.iter().zip(&state_views) {
+        expected_outputs.push(execute(ExecutorConfig::Sequential, block, state_view)?.0);
+    }
+
+    let configs = std::iter::once(ExecutorConfig::Sequential).chain(
+        concurrency_levels
+            .iter()
+            .map(|&concurrency_level| ExecutorConfig::Parallel { concurrency_level }),
+    );
+    let mut results: Vec<ExecutionResult> = vec![];
+    for config in configs {
+        let num_threads = match config {
+            ExecutorConfig::Sequential => 1,
+            ExecutorConfig::Parallel { concurrency_level } => concurrency_level,
+        };
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .thread_name(move |index| format!("replay-{}-{}", num_threads, index))
+            .build()?;
+
+        let mut sequential_fallbacks = 0;
+        let mut mismatched_outputs = 0;
+        let start = Instant::now();
+        for repeat in 0..repeats {
+            for ((block, state_view), expected) in
+                blocks.iter().zip(&state_views).zip(&expected_outputs)
+            {
+                let (outputs, fell_back) =
+                    thread_pool.install(|| execute(config, block, state_view))?;
+                if repeat == 0 {
+                    sequential_fallbacks += fell_back as usize;
+                    mismatched_outputs += outputs
+                        .iter()
+                        .zip(expected)
+                        .filter(|(output, expected)| output != expected)
+                        .count();
+                }
+            }
+        }
+        let seconds = start.elapsed().as_secs_f64();
+
+        let tps = (num_transactions * repeats) as f64 / seconds;
+        let sequential_tps = results.first().map_or(tps, |sequential| sequential.tps);
+        results.push(ExecutionResult {
+            config: config.to_string(),
+            seconds,
+            tps,
+            speedup: tps / sequential_tps,
+            sequential_fallbacks,
+            mismatched_outputs,
+        });
+    }
+
+    Ok(ReplayReport {
+        first_version,
+        num_blocks: blocks.len(),
+        num_transactions,
+        results,
+    })
+}
+
+/// Executes the block with the given executor configuration, returning its outputs and whether
+/// the parallel executor fell back to sequential execution.
+fn execute(
+    config: ExecutorConfig,
+    block: &RecordedBlock,
+    state_view: &CachedStateView,
+) -> Result<(Vec<TransactionOutput>, bool)> {
+    let transactions = block.transactions.clone();
+    match config {
+        ExecutorConfig::Sequential => {
+            let outputs = AptosVM::execute_block_and_keep_vm_status(transactions, state_view)
+                .map_err(|e| {
+                    format_err!("Failed to execute block {}: {:?}", block.first_version, e)
+                })?;
+            Ok((
+                outputs.into_iter().map(|(_, output)| output).collect(),
+                false,
+            ))
+        }
+        ExecutorConfig::Parallel { concurrency_level } => {
+            let (outputs, fallback_error) = ParallelAptosVM::execute_block_with_concurrency_level(
+                transactions,
+                state_view,
+                concurrency_level,
+            )
+            .map_err(|e| format_err!("Failed to execute block {}: {:?}", block.first_version, e))?;
+            Ok((outputs, fallback_error.is_some()))
+        }
+    }
+}
+
+/// Compares `report` with `baseline`, returning a description of each configuration whose
+/// throughput dropped by more than `max_regression` (a fraction of the baseline throughput) or
+/// whose outputs differ from sequential execution.
+pub fn find_regressions(
+    report: &ReplayReport,
+    baseline: &ReplayReport,
+    max_regression: f64,
+) -> Vec<String> {
+    let mut regressions = vec![];
+    for result in &report.results {
+        if result.mismatched_outputs > 0 {
+            regressions.push(format!(
+                "{}: {} outputs differ from sequential execution",
+                result.config, result.mismatched_outputs
+            ));
+        }
+        if let Some(baseline_result) = baseline
+            .results
+            .iter()
+            .find(|baseline_result| baseline_result.config == result.config)
+        {
+            if result.tps < baseline_result.tps * (1.0 - max_regression) {
+                regressions.push(format!(
+                    "{}: {:.0} TPS is {:.1}% below the baseline's {:.0} TPS",
+                    result.config,
+                    result.tps,
+                    100.0 * (1.0 - result.tps / baseline_result.tps),
+                    baseline_result.tps
+                ));
+            }
+        }
+    }
+    regressions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::HashValue;
+    use aptos_types::{
+        account_address::AccountAddress,
+        block_metadata::BlockMetadata,
+        transaction::{ChangeSet, WriteSetPayload},
+        write_set::WriteSet,
+    };
+
+    fn block_metadata(round: u64) -> Transaction {
+        Transaction::BlockMetadata(BlockMetadata::new(
+            HashValue::random(),
+            round,
+            0,
+            vec![],
+            AccountAddress::random(),
+        ))
+    }
+
+    fn write_set_txn() -> Transaction {
+        Transaction::GenesisTransaction(WriteSetPayload::Direct(ChangeSet::new(
+            WriteSet::default(),
+            vec![],
+        )))
+    }
+
+    fn report(results: &[(&str, f64, usize)]) -> ReplayReport {
+        ReplayReport {
+            first_version: 0,
+            num_blocks: 1,
+            num_transactions: 1,
+            results: results
+                .iter()
+                .map(|(config, tps, mismatched_outputs)| ExecutionResult {
+                    config: config.to_string(),
+                    seconds: 1.0,
+                    tps: *tps,
+                    speedup: 1.0,
+                    sequential_fallbacks: 0,
+                    mismatched_outputs: *mismatched_outputs,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_split_into_blocks() {
+        let transactions = vec![
+            write_set_txn(),
+            block_metadata(1),
+            write_set_txn(),
+            write_set_txn(),
+            block_metadata(2),
+            block_metadata(3),
+            write_set_txn(),
+        ];
+
+        let blocks = split_into_blocks(10, transactions.clone(), 5);
+        assert_eq!(
+            blocks
+                .iter()
+                .map(|block| (block.first_version, block.transactions.len()))
+                .collect::<Vec<_>>(),
+            vec![(11, 3), (14, 1), (15, 2)]
+        );
+
+        let blocks = split_into_blocks(10, transactions, 2);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].first_version, 14);
+    }
+
+    #[test]
+    fn test_find_regressions() {
+        let baseline = report(&[("sequential", 1000.0, 0), ("parallel-4", 3000.0, 0)]);
+
+        let report_within_threshold =
+            report(&[("sequential", 950.0, 0), ("parallel-4", 2800.0, 0)]);
+        assert!(find_regressions(&report_within_threshold, &baseline, 0.1).is_empty());
+
+        let slower = report(&[("sequential", 1000.0, 0), ("parallel-4", 2000.0, 0)]);
+        let regressions = find_regressions(&slower, &baseline, 0.1);
+        assert_eq!(regressions.len(), 1);
+        assert!(regressions[0].starts_with("parallel-4"));
+
+        // Mismatched outputs are regressions regardless of throughput, and configurations
+        // missing from the baseline are only checked for mismatches
+        let mismatched = report(&[("sequential", 1000.0, 0), ("parallel-8", 5000.0, 2)]);
+        assert_eq!(find_regressions(&mismatched, &baseline, 0.1).len(), 1);
+    }
+}
@@ -3,17 +3,18 @@
 
 #![forbid(unsafe_code)]
 
-use crate::{ExecutedTrees, StateComputeResult, TransactionData};
+use crate::{ExecutedTrees, StateComputeResult, StateDelta, TransactionData};
 use anyhow::{ensure, Result};
 use aptos_crypto::hash::{CryptoHash, TransactionAccumulatorHasher};
 use aptos_types::{
+    account_address::HashAccountAddress,
     contract_event::ContractEvent,
     epoch_state::EpochState,
     ledger_info::LedgerInfoWithSignatures,
     proof::accumulator::InMemoryAccumulator,
     transaction::{Transaction, TransactionInfo, TransactionStatus, TransactionToCommit},
 };
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 #[derive(Default)]
 pub struct ExecutedChunk {
@@ -23,6 +24,9 @@ pub struct ExecutedChunk {
     /// If set, this is the new epoch info that should be changed to if this is committed.
     pub next_epoch_state: Option<EpochState>,
     pub ledger_info: Option<LedgerInfoWithSignatures>,
+    /// If set, the index of the first transaction left out because the block reached the block
+    /// gas limit.
+    pub block_gas_limit_cut: Option<usize>,
 }
 
 impl ExecutedChunk {
@@ -152,6 +156,9 @@ impl ExecutedChunk {
     pub fn combine(self, rhs: Self) -> Result<Self> {
         let mut to_commit = self.to_commit;
         to_commit.extend(rhs.to_commit.into_iter());
+        let block_gas_limit_cut = self
+            .block_gas_limit_cut
+            .or_else(|| rhs.block_gas_limit_cut.map(|cut| self.status.len() + cut));
         let mut status = self.status;
         status.extend(rhs.status.into_iter());
 
@@ -161,6 +168,7 @@ impl ExecutedChunk {
             result_view: rhs.result_view,
             next_epoch_state: rhs.next_epoch_state,
             ledger_info: rhs.ledger_info,
+            block_gas_limit_cut,
         })
     }
 
@@ -168,26 +176,33 @@ impl ExecutedChunk {
         &self,
         parent_accumulator: &Arc<InMemoryAccumulator<TransactionAccumulatorHasher>>,
     ) -> StateComputeResult {
-        let txn_accu = self.result_view.txn_accumulator();
-
         let mut transaction_info_hashes = Vec::new();
         let mut reconfig_events = Vec::new();
+        let mut updated_keys = HashSet::new();
 
         for (_, txn_data) in &self.to_commit {
             transaction_info_hashes.push(txn_data.txn_info_hash());
-            reconfig_events.extend(txn_data.reconfig_events.iter().cloned())
+            reconfig_events.extend(txn_data.reconfig_events.iter().cloned());
+            updated_keys.extend(
+                txn_data
+                    .account_blobs()
+                    .keys()
+                    .map(|address| address.hash()),
+            );
         }
 
-        StateComputeResult::new(
-            txn_accu.root_hash(),
-            txn_accu.frozen_subtree_roots().clone(),
-            txn_accu.num_leaves(),
-            parent_accumulator.frozen_subtree_roots().clone(),
-            parent_accumulator.num_leaves(),
+        StateComputeResult::new_with_parent_accumulator(
+            Arc::clone(parent_accumulator),
+            Some(Arc::clone(self.result_view.txn_accumulator())),
             self.next_epoch_state.clone(),
             self.status.clone(),
             transaction_info_hashes,
             reconfig_events,
         )
+        .with_block_gas_limit_cut(self.block_gas_limit_cut)
+        .with_state_delta(StateDelta::new(
+            self.result_view.state_tree().clone(),
+            updated_keys.into_iter().collect(),
+        ))
     }
 }
@@ -8,7 +8,7 @@ mod executed_chunk;
 
 pub use error::Error;
 
-use anyhow::Result;
+use anyhow::{format_err, Result};
 use aptos_crypto::{
     ed25519::Ed25519Signature,
     hash::{
@@ -32,9 +32,10 @@ use aptos_types::{
     },
     write_set::WriteSet,
 };
-use scratchpad::ProofRead;
+use once_cell::sync::OnceCell;
+use scratchpad::{AccountStatus, ProofRead};
 use serde::{Deserialize, Serialize};
-use std::{cmp::max, collections::HashMap, sync::Arc};
+use std::{cmp::max, collections::HashMap, convert::TryFrom, sync::Arc};
 use storage_interface::DbReader;
 
 pub use executed_chunk::ExecutedChunk;
@@ -81,6 +82,11 @@ pub trait ChunkExecutorTrait: Send + Sync {
         verified_target_li: &LedgerInfoWithSignatures,
         epoch_change_li: Option<&LedgerInfoWithSignatures>,
     ) -> Result<(Vec<ContractEvent>, Vec<Transaction>)>;
+
+    /// Resets the executor state to the latest state in storage, discarding any
+    /// chunks that have been executed but not committed. This is required after
+    /// storage has been modified externally (e.g., a state snapshot was restored).
+    fn reset(&self) -> Result<()>;
 }
 
 pub trait BlockExecutorTrait: Send + Sync {
@@ -90,6 +96,18 @@ pub trait BlockExecutorTrait: Send + Sync {
     /// Reset the internal state including cache with newly fetched latest committed block from storage.
     fn reset(&self) -> Result<(), Error>;
 
+    /// Waits for the committed blocks to be persisted, when commits return before. Returns the
+    /// error of the first commit which failed to be persisted.
+    fn flush_commits(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Waits for the blocks committed up to `version` to be persisted, when commits return
+    /// before. Fails if one of their commits failed to be persisted.
+    fn wait_for_persisted(&self, _version: Version) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Executes a block.
     fn execute_block(
         &self,
@@ -123,6 +141,8 @@ pub trait TransactionReplayer: Send {
     fn commit(&self) -> Result<Arc<ExecutedChunk>>;
 }
 
+type TransactionAccumulator = InMemoryAccumulator<TransactionAccumulatorHasher>;
+
 /// A structure that summarizes the result of the execution needed for consensus to agree on.
 /// The execution is responsible for generating the ID of the new state, which is returned in the
 /// result.
@@ -131,23 +151,33 @@ pub trait TransactionReplayer: Send {
 /// of success / failure of the transactions.
 /// Note that the specific details of compute_status are opaque to StateMachineReplication,
 /// which is going to simply pass the results between StateComputer and TxnManager.
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+///
+/// The result is a delta on the transaction accumulator of the parent block: the accumulator
+/// after the block, whose frozen subtree roots make the frontier voted on, is computed on first
+/// access unless the executor hands it over. Accumulators are shared with the executor and
+/// between the results of a chain of blocks instead of being copied into every result. The
+/// updates of the state by the block are kept as a [`StateDelta`] for the same reason.
+///
+/// It's serialized in a compact form, see `CompactStateComputeResult`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(
+    into = "CompactStateComputeResult",
+    try_from = "CompactStateComputeResult"
+)]
 pub struct StateComputeResult {
-    /// transaction accumulator root hash is identified as `state_id` in Consensus.
-    root_hash: HashValue,
-    /// Represents the roots of all the full subtrees from left to right in this accumulator
-    /// after the execution. For details, please see [`InMemoryAccumulator`](accumulator::InMemoryAccumulator).
-    frozen_subtree_roots: Vec<HashValue>,
+    /// The transaction accumulator after executing the parent block.
+    parent_accumulator: Arc<TransactionAccumulator>,
 
-    /// The frozen subtrees roots of the parent block,
-    parent_frozen_subtree_roots: Vec<HashValue>,
+    /// The transaction info hashes of all success txns, appended to the parent accumulator.
+    transaction_info_hashes: Vec<HashValue>,
 
-    /// The number of leaves of the transaction accumulator after executing a proposed block.
-    /// This state must be persisted to ensure that on restart that the version is calculated correctly.
-    num_leaves: u64,
+    /// The transaction accumulator after executing the block, derived from the parent
+    /// accumulator and the transaction info hashes unless given.
+    accumulator: OnceCell<Arc<TransactionAccumulator>>,
 
-    /// The number of leaves after executing the parent block,
-    parent_num_leaves: u64,
+    /// transaction accumulator root hash is identified as `state_id` in Consensus. It's the root
+    /// hash of `accumulator` unless given, e.g. for the dummy results.
+    root_hash: OnceCell<HashValue>,
 
     /// If set, this is the new epoch info that should be changed to if this block is committed.
     epoch_state: Option<EpochState>,
@@ -156,16 +186,24 @@ pub struct StateComputeResult {
     /// TxnManager.
     compute_status: Vec<TransactionStatus>,
 
-    /// The transaction info hashes of all success txns.
-    transaction_info_hashes: Vec<HashValue>,
-
     /// The signature of the VoteProposal corresponding to this block.
     signature: Option<Ed25519Signature>,
 
     reconfig_events: Vec<ContractEvent>,
+
+    /// If the block reached the block gas limit, the index in `compute_status` of the first
+    /// transaction left out of the block, with the `Retry` status as all the ones after it.
+    block_gas_limit_cut: Option<usize>,
+
+    /// The updates of the state by the block, for the results of the local executor only: it's
+    /// determined by the rest of the result, and left out of the serialized form.
+    state_delta: Option<StateDelta>,
 }
 
 impl StateComputeResult {
+    /// Creates a result out of the materialized accumulators before and after the block, failing
+    /// if they aren't valid accumulators. The root hash may differ from the one of the
+    /// accumulator for the results not derived from an execution.
     pub fn new(
         root_hash: HashValue,
         frozen_subtree_roots: Vec<HashValue>,
@@ -176,18 +214,48 @@ impl StateComputeResult {
         compute_status: Vec<TransactionStatus>,
         transaction_info_hashes: Vec<HashValue>,
         reconfig_events: Vec<ContractEvent>,
+    ) -> Result<Self> {
+        let accumulator = TransactionAccumulator::new(frozen_subtree_roots, num_leaves)
+            .map_err(|err| format_err!("Invalid transaction accumulator: {}", err))?;
+        let parent_accumulator =
+            TransactionAccumulator::new(parent_frozen_subtree_roots, parent_num_leaves)
+                .map_err(|err| format_err!("Invalid parent transaction accumulator: {}", err))?;
+        Ok(Self {
+            parent_accumulator: Arc::new(parent_accumulator),
+            transaction_info_hashes,
+            accumulator: OnceCell::with_value(Arc::new(accumulator)),
+            root_hash: OnceCell::with_value(root_hash),
+            epoch_state,
+            compute_status,
+            signature: None,
+            reconfig_events,
+            block_gas_limit_cut: None,
+            state_delta: None,
+        })
+    }
+
+    /// Creates the result of executing a block on top of the parent accumulator, sharing the
+    /// accumulators with the caller. The accumulator after the block is computed on first access
+    /// if not given.
+    pub fn new_with_parent_accumulator(
+        parent_accumulator: Arc<TransactionAccumulator>,
+        accumulator: Option<Arc<TransactionAccumulator>>,
+        epoch_state: Option<EpochState>,
+        compute_status: Vec<TransactionStatus>,
+        transaction_info_hashes: Vec<HashValue>,
+        reconfig_events: Vec<ContractEvent>,
     ) -> Self {
         Self {
-            root_hash,
-            frozen_subtree_roots,
-            num_leaves,
-            parent_frozen_subtree_roots,
-            parent_num_leaves,
+            parent_accumulator,
+            transaction_info_hashes,
+            accumulator: accumulator.map_or_else(OnceCell::new, OnceCell::with_value),
+            root_hash: OnceCell::new(),
             epoch_state,
             compute_status,
-            transaction_info_hashes,
-            reconfig_events,
             signature: None,
+            reconfig_events,
+            block_gas_limit_cut: None,
+            state_delta: None,
         }
     }
 
@@ -195,17 +263,18 @@ impl StateComputeResult {
     /// this function is used in RandomComputeResultStateComputer to assert that the compute
     /// function is really called.
     pub fn new_dummy_with_root_hash(root_hash: HashValue) -> Self {
+        let empty_accumulator = Arc::new(TransactionAccumulator::new_empty());
         Self {
-            root_hash,
-            frozen_subtree_roots: vec![],
-            num_leaves: 0,
-            parent_frozen_subtree_roots: vec![],
-            parent_num_leaves: 0,
+            parent_accumulator: Arc::clone(&empty_accumulator),
+            transaction_info_hashes: vec![],
+            accumulator: OnceCell::with_value(empty_accumulator),
+            root_hash: OnceCell::with_value(root_hash),
             epoch_state: None,
             compute_status: vec![],
-            transaction_info_hashes: vec![],
-            reconfig_events: vec![],
             signature: None,
+            reconfig_events: vec![],
+            block_gas_limit_cut: None,
+            state_delta: None,
         }
     }
 
@@ -216,17 +285,44 @@ impl StateComputeResult {
     pub fn new_dummy() -> Self {
         StateComputeResult::new_dummy_with_root_hash(*ACCUMULATOR_PLACEHOLDER_HASH)
     }
+
+    pub fn with_block_gas_limit_cut(mut self, block_gas_limit_cut: Option<usize>) -> Self {
+        self.block_gas_limit_cut = block_gas_limit_cut;
+        self
+    }
+
+    pub fn with_state_delta(mut self, state_delta: StateDelta) -> Self {
+        self.state_delta = Some(state_delta);
+        self
+    }
 }
 
 impl StateComputeResult {
     pub fn version(&self) -> Version {
-        max(self.num_leaves, 1)
+        max(self.num_leaves(), 1)
             .checked_sub(1)
             .expect("Integer overflow occurred")
     }
 
     pub fn root_hash(&self) -> HashValue {
-        self.root_hash
+        *self
+            .root_hash
+            .get_or_init(|| self.accumulator().root_hash())
+    }
+
+    /// The transaction accumulator after the block, computed from the parent accumulator on
+    /// first access if not given.
+    pub fn accumulator(&self) -> &Arc<TransactionAccumulator> {
+        self.accumulator.get_or_init(|| {
+            Arc::new(
+                self.parent_accumulator
+                    .append(&self.transaction_info_hashes),
+            )
+        })
+    }
+
+    pub fn parent_accumulator(&self) -> &Arc<TransactionAccumulator> {
+        &self.parent_accumulator
     }
 
     pub fn compute_status(&self) -> &Vec<TransactionStatus> {
@@ -237,9 +333,20 @@ impl StateComputeResult {
         &self.epoch_state
     }
 
+    /// The transactions of the payload from this index on weren't executed as part of the block
+    /// because it reached the block gas limit, and are to be proposed again.
+    pub fn block_gas_limit_cut(&self) -> Option<usize> {
+        self.block_gas_limit_cut
+    }
+
+    /// The updates of the state by the block, if executed by the local executor.
+    pub fn state_delta(&self) -> Option<&StateDelta> {
+        self.state_delta.as_ref()
+    }
+
     pub fn extension_proof(&self) -> AccumulatorExtensionProof<TransactionAccumulatorHasher> {
         AccumulatorExtensionProof::<TransactionAccumulatorHasher>::new(
-            self.parent_frozen_subtree_roots.clone(),
+            self.parent_frozen_subtree_roots().clone(),
             self.parent_num_leaves(),
             self.transaction_info_hashes().clone(),
         )
@@ -250,19 +357,19 @@ impl StateComputeResult {
     }
 
     pub fn num_leaves(&self) -> u64 {
-        self.num_leaves
+        self.accumulator().num_leaves()
     }
 
     pub fn frozen_subtree_roots(&self) -> &Vec<HashValue> {
-        &self.frozen_subtree_roots
+        self.accumulator().frozen_subtree_roots()
     }
 
     pub fn parent_num_leaves(&self) -> u64 {
-        self.parent_num_leaves
+        self.parent_accumulator.num_leaves()
     }
 
     pub fn parent_frozen_subtree_roots(&self) -> &Vec<HashValue> {
-        &self.parent_frozen_subtree_roots
+        self.parent_accumulator.frozen_subtree_roots()
     }
 
     pub fn has_reconfiguration(&self) -> bool {
@@ -280,6 +387,165 @@ impl StateComputeResult {
     pub fn set_signature(&mut self, sig: Ed25519Signature) {
         self.signature = Some(sig);
     }
+
+    /// Whether the accumulator after the block is the parent accumulator extended with the
+    /// transaction infos of the block, which is the case for every executed block but the dummy
+    /// ones and the root of the block tree.
+    fn extends_parent_accumulator(&self) -> bool {
+        // The root hash is only given along with the accumulator.
+        if self.accumulator.get().is_none() {
+            return true;
+        }
+        let extended = self
+            .parent_accumulator
+            .append(&self.transaction_info_hashes);
+        extended.num_leaves() == self.num_leaves()
+            && extended.frozen_subtree_roots() == self.frozen_subtree_roots()
+            && extended.root_hash() == self.root_hash()
+    }
+}
+
+/// Results are compared by value, whether their accumulators are computed yet or not.
+impl PartialEq for StateComputeResult {
+    fn eq(&self, other: &Self) -> bool {
+        self.root_hash() == other.root_hash()
+            && self.num_leaves() == other.num_leaves()
+            && self.frozen_subtree_roots() == other.frozen_subtree_roots()
+            && self.parent_num_leaves() == other.parent_num_leaves()
+            && self.parent_frozen_subtree_roots() == other.parent_frozen_subtree_roots()
+            && self.transaction_info_hashes == other.transaction_info_hashes
+            && self.epoch_state == other.epoch_state
+            && self.compute_status == other.compute_status
+            && self.signature == other.signature
+            && self.reconfig_events == other.reconfig_events
+            && self.block_gas_limit_cut == other.block_gas_limit_cut
+    }
+}
+
+impl Eq for StateComputeResult {}
+
+/// The serialized form of a `StateComputeResult`, shipped by the execution workers running out
+/// of the consensus process. The transaction accumulator after the block is left out when it's
+/// the parent accumulator extended with the transaction infos of the block, which is the case
+/// for every executed block but the dummy ones and the root of the block tree.
+#[derive(Deserialize, Serialize)]
+struct CompactStateComputeResult {
+    parent_frozen_subtree_roots: Vec<HashValue>,
+    parent_num_leaves: u64,
+    transaction_info_hashes: Vec<HashValue>,
+    /// The root hash, frozen subtree roots and number of leaves of the accumulator after the
+    /// block, if not derived from the parent accumulator.
+    accumulator: Option<(HashValue, Vec<HashValue>, u64)>,
+    epoch_state: Option<EpochState>,
+    compute_status: Vec<TransactionStatus>,
+    signature: Option<Ed25519Signature>,
+    reconfig_events: Vec<ContractEvent>,
+    block_gas_limit_cut: Option<usize>,
+}
+
+impl From<StateComputeResult> for CompactStateComputeResult {
+    fn from(result: StateComputeResult) -> Self {
+        let accumulator = if result.extends_parent_accumulator() {
+            None
+        } else {
+            Some((
+                result.root_hash(),
+                result.frozen_subtree_roots().clone(),
+                result.num_leaves(),
+            ))
+        };
+        Self {
+            parent_frozen_subtree_roots: result.parent_frozen_subtree_roots().clone(),
+            parent_num_leaves: result.parent_num_leaves(),
+            transaction_info_hashes: result.transaction_info_hashes,
+            accumulator,
+            epoch_state: result.epoch_state,
+            compute_status: result.compute_status,
+            signature: result.signature,
+            reconfig_events: result.reconfig_events,
+            block_gas_limit_cut: result.block_gas_limit_cut,
+        }
+    }
+}
+
+/// The accumulator after the block is left to be computed on first access when it's derived
+/// from the parent accumulator.
+impl TryFrom<CompactStateComputeResult> for StateComputeResult {
+    type Error = anyhow::Error;
+
+    fn try_from(compact: CompactStateComputeResult) -> Result<Self> {
+        let parent_accumulator = Arc::new(TransactionAccumulator::new(
+            compact.parent_frozen_subtree_roots,
+            compact.parent_num_leaves,
+        )?);
+        let (accumulator, root_hash) = match compact.accumulator {
+            Some((root_hash, frozen_subtree_roots, num_leaves)) => (
+                OnceCell::with_value(Arc::new(TransactionAccumulator::new(
+                    frozen_subtree_roots,
+                    num_leaves,
+                )?)),
+                OnceCell::with_value(root_hash),
+            ),
+            None => (OnceCell::new(), OnceCell::new()),
+        };
+        Ok(Self {
+            parent_accumulator,
+            transaction_info_hashes: compact.transaction_info_hashes,
+            accumulator,
+            root_hash,
+            epoch_state: compact.epoch_state,
+            compute_status: compact.compute_status,
+            signature: compact.signature,
+            reconfig_events: compact.reconfig_events,
+            block_gas_limit_cut: compact.block_gas_limit_cut,
+            state_delta: None,
+        })
+    }
+}
+
+/// The updates of the state sparse Merkle tree by a block. Rather than copies of the new account
+/// states, it holds the tree after the block, which shares its nodes with the trees of the parent
+/// blocks and the executor's, and the keys the block updated: the update batch is only
+/// materialized on demand.
+#[derive(Clone, Debug)]
+pub struct StateDelta {
+    state_tree: SparseMerkleTree,
+    updated_keys: Arc<Vec<HashValue>>,
+}
+
+impl StateDelta {
+    pub fn new(state_tree: SparseMerkleTree, updated_keys: Vec<HashValue>) -> Self {
+        Self {
+            state_tree,
+            updated_keys: Arc::new(updated_keys),
+        }
+    }
+
+    /// The root hash of the state tree after the block.
+    pub fn root_hash(&self) -> HashValue {
+        self.state_tree.root_hash()
+    }
+
+    /// The hashes of the addresses of the accounts updated by the block.
+    pub fn updated_keys(&self) -> &[HashValue] {
+        &self.updated_keys
+    }
+
+    /// The update batch of the block, which turns the state tree of the parent block into the one
+    /// after the block: the updated keys with the new account states, read from the tree.
+    pub fn update_batch(&self) -> Result<Vec<(HashValue, AccountStateBlob)>> {
+        let state_tree = self.state_tree.clone().freeze();
+        self.updated_keys
+            .iter()
+            .map(|key| match state_tree.get(*key) {
+                AccountStatus::ExistsInScratchPad(blob) => Ok((*key, blob)),
+                _ => Err(format_err!(
+                    "Account state {} updated by the block isn't in the state tree",
+                    key
+                )),
+            })
+            .collect()
+    }
 }
 
 /// A wrapper of the in-memory state sparse merkle tree and the transaction accumulator that
@@ -6,19 +6,31 @@
 use crate::logging::{LogEntry, LogSchema};
 use anyhow::Result;
 use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
 use aptos_state_view::StateViewId;
-use aptos_types::{ledger_info::LedgerInfoWithSignatures, transaction::Transaction};
-use aptos_vm::VMExecutor;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    on_chain_config::{BlockGasLimit, OnChainConfig},
+    transaction::{Transaction, Version},
+};
+use aptos_vm::{data_cache::RemoteStorage, VMExecutor};
 use executor_types::{BlockExecutorTrait, Error, StateComputeResult};
 use fail::fail_point;
-use std::marker::PhantomData;
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    sync::{mpsc::Receiver, Arc},
+};
 
 use crate::{
-    components::{block_tree::BlockTree, chunk_output::ChunkOutput},
+    components::{
+        block_tree::BlockTree,
+        chunk_output::ChunkOutput,
+        commit_pipeline::{self, CommitPipeline},
+    },
     metrics::{
         DIEM_EXECUTOR_COMMIT_BLOCKS_SECONDS, DIEM_EXECUTOR_EXECUTE_BLOCK_SECONDS,
-        DIEM_EXECUTOR_SAVE_TRANSACTIONS_SECONDS, DIEM_EXECUTOR_TRANSACTIONS_SAVED,
         DIEM_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS,
     },
 };
@@ -26,7 +38,15 @@ use storage_interface::DbReaderWriter;
 
 pub struct BlockExecutor<V> {
     pub db: DbReaderWriter,
-    block_tree: BlockTree,
+    block_tree: Arc<BlockTree>,
+    /// Persists the committed blocks on a dedicated thread, when commits are pipelined
+    commit_pipeline: Option<CommitPipeline>,
+    /// The version following the latest commit queued in the commit pipeline, until the pipeline
+    /// is flushed. The root of the block tree only moves once the commits are persisted.
+    next_version_to_commit: Mutex<Option<Version>>,
+    /// The completions of the commits queued in the commit pipeline, by the version of their
+    /// ledger info, until they're waited for.
+    pending_persists: Mutex<VecDeque<(Version, Receiver<()>)>>,
     phantom: PhantomData<V>,
 }
 
@@ -38,10 +58,33 @@ where
         let block_tree = BlockTree::new(&db.reader).expect("Block tree failed to init.");
         Self {
             db,
-            block_tree,
+            block_tree: Arc::new(block_tree),
+            commit_pipeline: None,
+            next_version_to_commit: Mutex::new(None),
+            pending_persists: Mutex::new(VecDeque::new()),
             phantom: PhantomData,
         }
     }
+
+    /// Pipelines the commits: the committed blocks are persisted on a dedicated thread, in order,
+    /// so that the following blocks execute while they're being written. Commits return once
+    /// queued, and block while `max_pending_commits` commits are already queued, so callers have
+    /// to wait for their persistence (see `BlockExecutorTrait::wait_for_persisted`) before
+    /// exposing them. The commits of blocks ending epochs, and resets, wait for the queued
+    /// commits to be persisted. When `max_pending_commits` is 0, the blocks are persisted before
+    /// commits return.
+    pub fn with_pipelined_commit(mut self, max_pending_commits: usize) -> Self {
+        self.commit_pipeline = if max_pending_commits > 0 {
+            Some(CommitPipeline::new(
+                self.db.clone(),
+                self.block_tree.clone(),
+                max_pending_commits,
+            ))
+        } else {
+            None
+        };
+        self
+    }
 }
 
 impl<V> BlockExecutorTrait for BlockExecutor<V>
@@ -49,13 +92,62 @@ where
     V: VMExecutor,
 {
     fn committed_block_id(&self) -> HashValue {
+        if let Err(e) = self.flush_commits() {
+            error!(
+                LogSchema::new(LogEntry::BlockExecutor),
+                error = ?e,
+                "Failed to persist committed blocks"
+            );
+        }
         self.block_tree.root_block().id
     }
 
     fn reset(&self) -> Result<(), Error> {
+        if let Err(e) = self.flush_commits() {
+            warn!(
+                LogSchema::new(LogEntry::BlockExecutor),
+                error = ?e,
+                "Failed to persist committed blocks, resetting to the persisted ones"
+            );
+        }
         Ok(self.block_tree.reset(&self.db.reader)?)
     }
 
+    fn flush_commits(&self) -> Result<(), Error> {
+        let mut next_version_to_commit = self.next_version_to_commit.lock();
+        *next_version_to_commit = None;
+        match &self.commit_pipeline {
+            Some(commit_pipeline) => Ok(commit_pipeline.flush()?),
+            None => Ok(()),
+        }
+    }
+
+    fn wait_for_persisted(&self, version: Version) -> Result<(), Error> {
+        loop {
+            // the lock isn't held while waiting, so that commits can still be queued
+            let persisted = {
+                let mut pending_persists = self.pending_persists.lock();
+                match pending_persists.front() {
+                    Some((commit_version, _)) if *commit_version <= version => {
+                        pending_persists.pop_front()
+                    }
+                    _ => None,
+                }
+            };
+            match persisted {
+                Some((commit_version, persisted)) => {
+                    persisted.recv().map_err(|_| Error::InternalError {
+                        error: format!(
+                            "The commit of version {} failed to be persisted",
+                            commit_version
+                        ),
+                    })?
+                }
+                None => return Ok(()),
+            }
+        }
+    }
+
     fn execute_block(
         &self,
         block: (HashValue, Vec<Transaction>),
@@ -105,7 +197,10 @@ where
                         "Injected error in vm_execute_block"
                     )))
                 });
+                let block_gas_limit = BlockGasLimit::fetch_config(&RemoteStorage::new(&state_view))
+                    .map(|config| config.max_gas_per_block);
                 ChunkOutput::by_transaction_execution::<V>(transactions, state_view)?
+                    .with_block_gas_limit(block_gas_limit)
             };
             chunk_output.trace_log_transaction_status();
 
@@ -125,10 +220,10 @@ where
         ledger_info_with_sigs: LedgerInfoWithSignatures,
     ) -> Result<(), Error> {
         let _timer = DIEM_EXECUTOR_COMMIT_BLOCKS_SECONDS.start_timer();
-        let committed_block = self.block_tree.root_block();
-        if committed_block.num_persisted_transactions()
-            == ledger_info_with_sigs.ledger_info().version() + 1
-        {
+        let mut next_version_to_commit = self.next_version_to_commit.lock();
+        let first_version = next_version_to_commit
+            .unwrap_or_else(|| self.block_tree.root_block().num_persisted_transactions());
+        if first_version == ledger_info_with_sigs.ledger_info().version() + 1 {
             // a retry
             return Ok(());
         }
@@ -148,11 +243,6 @@ where
             .flatten()
             .collect();
 
-        let first_version = committed_block
-            .output
-            .result_view
-            .txn_accumulator()
-            .num_leaves();
         let to_commit = txns_to_commit.len();
         let target_version = ledger_info_with_sigs.ledger_info().version();
         if first_version + txns_to_commit.len() as u64 != target_version + 1 {
@@ -163,22 +253,31 @@ where
             });
         }
 
-        {
-            let _timer = DIEM_EXECUTOR_SAVE_TRANSACTIONS_SECONDS.start_timer();
-            DIEM_EXECUTOR_TRANSACTIONS_SAVED.observe(to_commit as f64);
-
-            fail_point!("executor::commit_blocks", |_| {
-                Err(anyhow::anyhow!("Injected error in commit_blocks.").into())
-            });
-
-            self.db.writer.save_transactions(
+        match &self.commit_pipeline {
+            Some(commit_pipeline) => {
+                let ends_epoch = ledger_info_with_sigs.ledger_info().ends_epoch();
+                let persisted = commit_pipeline.enqueue(
+                    txns_to_commit,
+                    first_version,
+                    ledger_info_with_sigs,
+                )?;
+                self.pending_persists
+                    .lock()
+                    .push_back((target_version, persisted));
+                *next_version_to_commit = Some(target_version + 1);
+                if ends_epoch {
+                    // the next epoch starts from the persisted state
+                    *next_version_to_commit = None;
+                    commit_pipeline.flush()?;
+                }
+            }
+            None => commit_pipeline::persist(
+                &self.db,
+                &self.block_tree,
                 &txns_to_commit,
                 first_version,
-                Some(&ledger_info_with_sigs),
-            )?;
-            self.block_tree
-                .prune(ledger_info_with_sigs.ledger_info())
-                .expect("Failure pruning block tree.");
+                &ledger_info_with_sigs,
+            )?,
         }
         Ok(())
     }
@@ -11,11 +11,13 @@ use crate::{
     },
     logging::{LogEntry, LogSchema},
     metrics::{
-        DIEM_EXECUTOR_APPLY_CHUNK_SECONDS, DIEM_EXECUTOR_COMMIT_CHUNK_SECONDS,
+        DIEM_EXECUTOR_APPLY_CHUNK_SECONDS, DIEM_EXECUTOR_AUDIT_CHUNK_SECONDS,
+        DIEM_EXECUTOR_CHUNK_AUDITS, DIEM_EXECUTOR_COMMIT_CHUNK_SECONDS,
         DIEM_EXECUTOR_EXECUTE_CHUNK_SECONDS, DIEM_EXECUTOR_VM_EXECUTE_CHUNK_SECONDS,
     },
 };
 use anyhow::Result;
+use aptos_crypto::hash::CryptoHash;
 use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
 use aptos_state_view::StateViewId;
@@ -35,6 +37,8 @@ use storage_interface::{state_view::VerifiedStateView, DbReaderWriter};
 pub struct ChunkExecutor<V> {
     db: DbReaderWriter,
     commit_queue: Mutex<ChunkCommitQueue>,
+    /// Whether the transactions of applied chunks are re-executed to audit their outputs
+    audit_chunks: bool,
     _phantom: PhantomData<V>,
 }
 
@@ -44,6 +48,7 @@ impl<V> ChunkExecutor<V> {
         Ok(Self {
             db,
             commit_queue,
+            audit_chunks: false,
             _phantom: PhantomData,
         })
     }
@@ -53,13 +58,16 @@ impl<V> ChunkExecutor<V> {
         Self {
             db,
             commit_queue,
+            audit_chunks: false,
             _phantom: PhantomData,
         }
     }
 
-    pub fn reset(&self) -> Result<()> {
-        *self.commit_queue.lock() = ChunkCommitQueue::new_from_db(&self.db.reader)?;
-        Ok(())
+    /// Re-executes the transactions of the chunks applied from transaction outputs, and
+    /// reports the ones whose results don't match the proven transaction infos.
+    pub fn with_chunk_audit(mut self, audit_chunks: bool) -> Self {
+        self.audit_chunks = audit_chunks;
+        self
     }
 
     fn state_view(
@@ -197,6 +205,17 @@ impl<V: VMExecutor> ChunkExecutorTrait for ChunkExecutor<V> {
         )?;
         let mut txns_and_outputs = txn_output_list_with_proof.transactions_and_outputs;
         txns_and_outputs.drain(..txns_to_skip as usize);
+        let transaction_infos = &txn_output_list_with_proof.proof.transaction_infos[txns_to_skip..];
+        let txns_to_audit = if self.audit_chunks {
+            Some(
+                txns_and_outputs
+                    .iter()
+                    .map(|(txn, _)| txn.clone())
+                    .collect::<Vec<_>>(),
+            )
+        } else {
+            None
+        };
 
         // Apply transaction outputs.
         let state_view = self.state_view(&latest_view, &persisted_view);
@@ -206,9 +225,19 @@ impl<V: VMExecutor> ChunkExecutorTrait for ChunkExecutor<V> {
             epoch_change_li,
             &latest_view,
             chunk_output,
-            &txn_output_list_with_proof.proof.transaction_infos[txns_to_skip..],
+            transaction_infos,
         )?;
 
+        // Audit the outputs, mismatches are reported but don't stop the node from syncing.
+        if let Some(transactions) = txns_to_audit {
+            self.audit_chunk(
+                transactions,
+                transaction_infos,
+                &latest_view,
+                &persisted_view,
+            );
+        }
+
         // Add result to commit queue.
         self.commit_queue.lock().enqueue(executed_chunk);
 
@@ -261,9 +290,81 @@ impl<V: VMExecutor> ChunkExecutorTrait for ChunkExecutor<V> {
         )?;
         self.commit_chunk()
     }
+
+    fn reset(&self) -> Result<()> {
+        *self.commit_queue.lock() = ChunkCommitQueue::new_from_db(&self.db.reader)?;
+        Ok(())
+    }
 }
 
-impl<V: VMExecutor> ChunkExecutor<V> {}
+impl<V: VMExecutor> ChunkExecutor<V> {
+    /// Re-executes the transactions of an applied chunk and compares the results against the
+    /// proven transaction infos. Discrepancies are only logged and counted: the applied outputs
+    /// are proven by the ledger info, so a mismatch points at the executing validators (or at the
+    /// local VM) rather than at the chunk.
+    fn audit_chunk(
+        &self,
+        transactions: Vec<Transaction>,
+        transaction_infos: &[TransactionInfo],
+        latest_view: &ExecutedTrees,
+        persisted_view: &ExecutedTrees,
+    ) {
+        let _timer = DIEM_EXECUTOR_AUDIT_CHUNK_SECONDS.start_timer();
+        let first_version = latest_view.txn_accumulator().num_leaves();
+
+        let result = match self.reexecute_chunk(transactions, latest_view, persisted_view) {
+            Ok(executed_chunk) => {
+                let mismatch = executed_chunk
+                    .to_commit
+                    .iter()
+                    .map(|(_, txn_data)| txn_data.txn_info_hash())
+                    .zip(transaction_infos.iter().map(CryptoHash::hash))
+                    .position(|(txn_info_hash, expected)| txn_info_hash != expected);
+                if let Some(index) = mismatch {
+                    error!(
+                        LogSchema::new(LogEntry::ChunkExecutor)
+                            .local_synced_version(first_version)
+                            .num_txns_in_request(transaction_infos.len()),
+                        "Chunk audit mismatch! Re-executed transaction info of version {} \
+                        differs from the proven one: {:?}",
+                        first_version + index as u64,
+                        transaction_infos[index],
+                    );
+                    "mismatch"
+                } else {
+                    "match"
+                }
+            }
+            Err(err) => {
+                error!(
+                    LogSchema::new(LogEntry::ChunkExecutor)
+                        .local_synced_version(first_version)
+                        .num_txns_in_request(transaction_infos.len()),
+                    "Chunk audit failed to re-execute the transactions: {:?}", err,
+                );
+                "error"
+            }
+        };
+        DIEM_EXECUTOR_CHUNK_AUDITS
+            .with_label_values(&[result])
+            .inc();
+    }
+
+    fn reexecute_chunk(
+        &self,
+        transactions: Vec<Transaction>,
+        latest_view: &ExecutedTrees,
+        persisted_view: &ExecutedTrees,
+    ) -> Result<ExecutedChunk> {
+        let state_view = self.state_view(latest_view, persisted_view);
+        let (executed_chunk, to_discard, to_retry) =
+            ChunkOutput::by_transaction_execution::<V>(transactions, state_view)?
+                .apply_to_ledger(latest_view.txn_accumulator())?;
+        ensure_no_discard(to_discard)?;
+        ensure_no_retry(to_retry)?;
+        Ok(executed_chunk)
+    }
+}
 
 impl<V: VMExecutor> TransactionReplayer for ChunkExecutor<V> {
     fn replay(
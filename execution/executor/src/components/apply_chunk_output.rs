@@ -49,11 +49,12 @@ impl ApplyChunkOutput {
             state_cache,
             transactions,
             transaction_outputs,
+            block_gas_limit,
         } = chunk_output;
 
         // Separate transactions with different VM statuses.
-        let (new_epoch, status, to_keep, to_discard, to_retry) =
-            Self::sort_transactions(transactions, transaction_outputs)?;
+        let (new_epoch, block_gas_limit_cut, status, to_keep, to_discard, to_retry) =
+            Self::sort_transactions(transactions, transaction_outputs, block_gas_limit)?;
 
         // Apply the write set, get the latest state.
         let (account_blobs, roots_with_node_hashes, result_state, next_epoch_state) =
@@ -73,6 +74,7 @@ impl ApplyChunkOutput {
                 ),
                 next_epoch_state,
                 ledger_info: None,
+                block_gas_limit_cut,
             },
             to_discard,
             to_retry,
@@ -82,8 +84,10 @@ impl ApplyChunkOutput {
     fn sort_transactions(
         mut transactions: Vec<Transaction>,
         transaction_outputs: Vec<TransactionOutput>,
+        block_gas_limit: Option<u64>,
     ) -> Result<(
         bool,
+        Option<usize>,
         Vec<TransactionStatus>,
         Vec<(Transaction, ParsedTransactionOutput)>,
         Vec<Transaction>,
@@ -98,15 +102,34 @@ impl ApplyChunkOutput {
             .position(|o| o.is_reconfig())
             .map(|idx| idx + 1);
 
-        // Transactions after the epoch ending are all to be retried.
-        let to_retry = if let Some(pos) = new_epoch_marker {
+        // N.B. off-by-1 intentionally as well, the transaction reaching the limit is included.
+        let block_gas_limit_marker = block_gas_limit.and_then(|limit| {
+            let mut gas_used = 0u64;
+            transaction_outputs
+                .iter()
+                .position(|o| {
+                    if let TransactionStatus::Keep(_) = o.status() {
+                        gas_used = gas_used.saturating_add(o.gas_used());
+                    }
+                    gas_used >= limit
+                })
+                .map(|idx| idx + 1)
+        });
+        // The block is only cut by the gas limit if transactions are left out.
+        let block_gas_limit_cut = block_gas_limit_marker
+            .filter(|pos| *pos < num_txns && new_epoch_marker.map_or(true, |e| *pos < e));
+        let new_epoch_marker = new_epoch_marker.filter(|_| block_gas_limit_cut.is_none());
+
+        // Transactions after the epoch ending or the block gas limit are all to be retried.
+        let to_retry = if let Some(pos) = block_gas_limit_cut.or(new_epoch_marker) {
             transaction_outputs.drain(pos..);
             transactions.drain(pos..).collect()
         } else {
             vec![]
         };
 
-        // N.B. Transaction status after the epoch marker are ignored and set to Retry forcibly.
+        // N.B. Transaction status after the epoch marker or the block gas limit are ignored and
+        // set to Retry forcibly.
         let status = transaction_outputs
             .iter()
             .map(|t| t.status())
@@ -146,6 +169,7 @@ impl ApplyChunkOutput {
 
         Ok((
             new_epoch_marker.is_some(),
+            block_gas_limit_cut,
             status,
             to_keep,
             to_discard,
@@ -7,16 +7,22 @@ use crate::components::apply_chunk_output::ApplyChunkOutput;
 use anyhow::Result;
 use aptos_crypto::hash::TransactionAccumulatorHasher;
 use aptos_logger::trace;
-use aptos_state_view::StateView;
 use aptos_types::{
+    access_path::AccessPath,
+    account_config::AccountResource,
     proof::accumulator::InMemoryAccumulator,
     transaction::{Transaction, TransactionOutput},
 };
 use aptos_vm::VMExecutor;
 use executor_types::ExecutedChunk;
 use fail::fail_point;
+use futures::executor::block_on;
+use move_core_types::move_resource::MoveResource;
 use std::{collections::HashSet, sync::Arc};
-use storage_interface::state_view::{StateCache, VerifiedStateView};
+use storage_interface::{
+    async_state_view::AsyncStateView,
+    state_view::{StateCache, VerifiedStateView},
+};
 
 pub struct ChunkOutput {
     /// Input transactions.
@@ -27,6 +33,10 @@ pub struct ChunkOutput {
     /// execution result is processed; as well as al the accounts touched during execution, together
     /// with their proofs.
     pub state_cache: StateCache,
+    /// If set, the transactions after the one the gas used by the chunk reaches the limit at are
+    /// left out, to be retried. Only the blocks are limited, not the chunks of committed
+    /// transactions.
+    pub block_gas_limit: Option<u64>,
 }
 
 impl ChunkOutput {
@@ -34,12 +44,26 @@ impl ChunkOutput {
         transactions: Vec<Transaction>,
         state_view: VerifiedStateView,
     ) -> Result<Self> {
+        // Every user transaction reads its sender's account, read them all in a batch rather than
+        // one by one from the execution threads.
+        let sender_access_paths = transactions
+            .iter()
+            .filter_map(|txn| match txn {
+                Transaction::UserTransaction(txn) => Some(AccessPath::new(
+                    txn.sender(),
+                    AccountResource::resource_path(),
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        block_on(state_view.multi_get_state_values(&sender_access_paths))?;
         let transaction_outputs = V::execute_block(transactions.clone(), &state_view)?;
 
         Ok(Self {
             transactions,
             transaction_outputs,
             state_cache: state_view.into_state_cache(),
+            block_gas_limit: None,
         })
     }
 
@@ -55,22 +79,27 @@ impl ChunkOutput {
             .iter()
             .map(|o| o.write_set())
             .flatten()
-            .collect::<HashSet<_>>();
+            .map(|(p, _)| p.clone())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
 
-        // prime the state cache by fetching all touched accounts
-        // TODO: add concurrency
-        access_paths
-            .iter()
-            .map(|(p, _)| state_view.get(p))
-            .collect::<Result<Vec<_>>>()?;
+        // prime the state cache by fetching all touched accounts in a batch
+        block_on(state_view.multi_get_state_values(&access_paths))?;
 
         Ok(Self {
             transactions,
             transaction_outputs,
             state_cache: state_view.into_state_cache(),
+            block_gas_limit: None,
         })
     }
 
+    pub fn with_block_gas_limit(mut self, block_gas_limit: Option<u64>) -> Self {
+        self.block_gas_limit = block_gas_limit;
+        self
+    }
+
     pub fn apply_to_ledger(
         self,
         base_accumulator: &Arc<InMemoryAccumulator<TransactionAccumulatorHasher>>,
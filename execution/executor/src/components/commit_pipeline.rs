@@ -0,0 +1,204 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! This module provides `CommitPipeline`, which persists the committed blocks on a dedicated
+//! thread, so that the following blocks can be executed while the ledger is being written.
+
+use crate::{
+    components::block_tree::BlockTree,
+    logging::{LogEntry, LogSchema},
+    metrics::{
+        DIEM_EXECUTOR_PENDING_COMMITS, DIEM_EXECUTOR_SAVE_TRANSACTIONS_SECONDS,
+        DIEM_EXECUTOR_TRANSACTIONS_SAVED,
+    },
+};
+use anyhow::{format_err, Result};
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{TransactionToCommit, Version},
+};
+use fail::fail_point;
+use std::{
+    sync::{
+        mpsc::{sync_channel, Receiver, SyncSender},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+use storage_interface::DbReaderWriter;
+
+/// Saves the transactions of the committed blocks to the DB, then makes the last committed block
+/// the root of the block tree.
+pub fn persist(
+    db: &DbReaderWriter,
+    block_tree: &BlockTree,
+    txns_to_commit: &[TransactionToCommit],
+    first_version: Version,
+    ledger_info_with_sigs: &LedgerInfoWithSignatures,
+) -> Result<()> {
+    let _timer = DIEM_EXECUTOR_SAVE_TRANSACTIONS_SECONDS.start_timer();
+    DIEM_EXECUTOR_TRANSACTIONS_SAVED.observe(txns_to_commit.len() as f64);
+
+    fail_point!("executor::commit_blocks", |_| {
+        Err(anyhow::anyhow!("Injected error in commit_blocks."))
+    });
+
+    db.writer
+        .save_transactions(txns_to_commit, first_version, Some(ledger_info_with_sigs))?;
+    block_tree
+        .prune(ledger_info_with_sigs.ledger_info())
+        .expect("Failure pruning block tree.");
+    Ok(())
+}
+
+enum Command {
+    Commit {
+        txns_to_commit: Vec<TransactionToCommit>,
+        first_version: Version,
+        ledger_info_with_sigs: LedgerInfoWithSignatures,
+        /// Replied to once the commit is persisted, dropped if it failed to be.
+        persisted_sender: SyncSender<()>,
+    },
+    /// Replied to once the commits queued before are persisted.
+    Flush(SyncSender<()>),
+    Quit,
+}
+
+/// It creates a commit thread on construction, which persists the queued commits in order, and
+/// joins it on destruction, once the queued commits are persisted.
+pub struct CommitPipeline {
+    /// The commit thread handle. It only becomes `None` after joined in `drop()`.
+    commit_thread: Option<JoinHandle<()>>,
+    /// The sender side of the bounded channel to the commit thread. Sending blocks while the
+    /// channel is full, which back-pressures the commits.
+    command_sender: Mutex<SyncSender<Command>>,
+    /// The error of the first commit which failed to be persisted. The following commits build on
+    /// it, so they're dropped until the error is taken by a flush.
+    error: Arc<Mutex<Option<anyhow::Error>>>,
+}
+
+impl CommitPipeline {
+    pub fn new(db: DbReaderWriter, block_tree: Arc<BlockTree>, max_pending_commits: usize) -> Self {
+        let (command_sender, command_receiver) = sync_channel(max_pending_commits);
+        let error = Arc::new(Mutex::new(None));
+        let commit_error = error.clone();
+        let commit_thread = std::thread::Builder::new()
+            .name("block_commit".into())
+            .spawn(move || Self::work(db, block_tree, command_receiver, commit_error))
+            .expect("Creating commit thread should succeed.");
+        Self {
+            commit_thread: Some(commit_thread),
+            command_sender: Mutex::new(command_sender),
+            error,
+        }
+    }
+
+    /// Queues a commit, waiting while the queue is full. Fails if a previous commit failed.
+    /// Returns the completion of the commit: the receiver gets a message once the commit is
+    /// persisted, or disconnects if it failed to be.
+    pub fn enqueue(
+        &self,
+        txns_to_commit: Vec<TransactionToCommit>,
+        first_version: Version,
+        ledger_info_with_sigs: LedgerInfoWithSignatures,
+    ) -> Result<Receiver<()>> {
+        if let Some(error) = self.error.lock().as_ref() {
+            return Err(format_err!("A previous commit failed: {}", error));
+        }
+        let (persisted_sender, persisted_receiver) = sync_channel(1);
+        DIEM_EXECUTOR_PENDING_COMMITS.inc();
+        self.command_sender
+            .lock()
+            .send(Command::Commit {
+                txns_to_commit,
+                first_version,
+                ledger_info_with_sigs,
+                persisted_sender,
+            })
+            .expect("Commit thread should not exit prematurely.");
+        Ok(persisted_receiver)
+    }
+
+    /// Waits for the queued commits to be persisted. Returns the error of the first one which
+    /// failed, after which commits can be queued again.
+    pub fn flush(&self) -> Result<()> {
+        let (reply_sender, reply_receiver) = sync_channel(1);
+        self.command_sender
+            .lock()
+            .send(Command::Flush(reply_sender))
+            .expect("Commit thread should not exit prematurely.");
+        reply_receiver
+            .recv()
+            .expect("Commit thread should reply to flushes.");
+        match self.error.lock().take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn work(
+        db: DbReaderWriter,
+        block_tree: Arc<BlockTree>,
+        command_receiver: Receiver<Command>,
+        error: Arc<Mutex<Option<anyhow::Error>>>,
+    ) {
+        while let Ok(command) = command_receiver.recv() {
+            match command {
+                Command::Commit {
+                    txns_to_commit,
+                    first_version,
+                    ledger_info_with_sigs,
+                    persisted_sender,
+                } => {
+                    DIEM_EXECUTOR_PENDING_COMMITS.dec();
+                    if error.lock().is_some() {
+                        continue;
+                    }
+                    match persist(
+                        &db,
+                        &block_tree,
+                        &txns_to_commit,
+                        first_version,
+                        &ledger_info_with_sigs,
+                    ) {
+                        Ok(()) => {
+                            let _ = persisted_sender.send(());
+                        }
+                        Err(e) => {
+                            error!(
+                                LogSchema::new(LogEntry::BlockExecutor).block_id(
+                                    ledger_info_with_sigs.ledger_info().consensus_block_id()
+                                ),
+                                error = ?e,
+                                "Failed to persist committed blocks"
+                            );
+                            *error.lock() = Some(e);
+                        }
+                    }
+                }
+                Command::Flush(reply_sender) => {
+                    let _ = reply_sender.send(());
+                }
+                Command::Quit => break,
+            }
+        }
+    }
+}
+
+impl Drop for CommitPipeline {
+    fn drop(&mut self) {
+        self.command_sender
+            .lock()
+            .send(Command::Quit)
+            .expect("Commit thread should not exit prematurely.");
+        self.commit_thread
+            .take()
+            .expect("Commit thread must exist.")
+            .join()
+            .expect("Commit thread should join peacefully.");
+    }
+}
@@ -7,3 +7,4 @@ pub mod apply_chunk_output;
 pub mod block_tree;
 pub mod chunk_commit_queue;
 pub mod chunk_output;
+pub mod commit_pipeline;
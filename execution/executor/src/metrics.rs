@@ -1,7 +1,10 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_metrics::{register_histogram, register_int_counter, Histogram, IntCounter};
+use aptos_metrics::{
+    register_histogram, register_int_counter, register_int_counter_vec, register_int_gauge,
+    Histogram, IntCounter, IntCounterVec, IntGauge,
+};
 use once_cell::sync::Lazy;
 
 pub static DIEM_EXECUTOR_EXECUTE_CHUNK_SECONDS: Lazy<Histogram> = Lazy::new(|| {
@@ -24,6 +27,27 @@ pub static DIEM_EXECUTOR_APPLY_CHUNK_SECONDS: Lazy<Histogram> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static DIEM_EXECUTOR_AUDIT_CHUNK_SECONDS: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        // metric name
+        "aptos_executor_audit_chunk_seconds",
+        // metric description
+        "The time spent in seconds of re-executing applied chunks to audit them in Diem executor"
+    )
+    .unwrap()
+});
+
+pub static DIEM_EXECUTOR_CHUNK_AUDITS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "aptos_executor_chunk_audits",
+        // metric description
+        "Number of applied chunks audited by re-execution, by result (match, mismatch or error)",
+        &["result"]
+    )
+    .unwrap()
+});
+
 pub static DIEM_EXECUTOR_COMMIT_CHUNK_SECONDS: Lazy<Histogram> = Lazy::new(|| {
     register_histogram!(
         // metric name
@@ -97,3 +121,13 @@ pub static DIEM_EXECUTOR_TRANSACTIONS_SAVED: Lazy<Histogram> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static DIEM_EXECUTOR_PENDING_COMMITS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        // metric name
+        "aptos_executor_pending_commits",
+        // metric description
+        "The number of block commits queued for persistence in Diem executor"
+    )
+    .unwrap()
+});
@@ -7,6 +7,7 @@ use crate::{
     block_executor::BlockExecutor,
     chunk_executor::ChunkExecutor,
     db_bootstrapper::{generate_waypoint, maybe_bootstrap},
+    metrics::DIEM_EXECUTOR_CHUNK_AUDITS,
     mock_vm::{encode_mint_transaction, MockVM},
     tests,
 };
@@ -268,3 +269,43 @@ fn test_executor_execute_and_commit_chunk_local_result_mismatch() {
         .execute_and_commit_chunk(chunks[0].clone(), &ledger_info, None)
         .is_err());
 }
+
+#[test]
+fn test_executor_apply_and_commit_chunk_with_audit() {
+    let (chunks, ledger_info) = tests::create_transaction_chunks(vec![1..31, 31..51]);
+    let chunks = {
+        let TestExecutor {
+            _path,
+            db,
+            executor,
+        } = TestExecutor::new();
+        execute_and_commit_chunk(chunks, ledger_info.clone(), &db, &executor);
+
+        let ledger_version = db.reader.get_latest_version().unwrap();
+        vec![
+            db.reader
+                .get_transaction_outputs(1, 30, ledger_version)
+                .unwrap(),
+            db.reader
+                .get_transaction_outputs(31, 20, ledger_version)
+                .unwrap(),
+        ]
+    };
+
+    // The re-executed transactions match the proven transaction infos.
+    let TestExecutor {
+        _path,
+        db,
+        executor,
+    } = TestExecutor::new();
+    let executor = executor.with_chunk_audit(true);
+    let audits = DIEM_EXECUTOR_CHUNK_AUDITS.with_label_values(&["match"]);
+    let matches = audits.get();
+    for chunk in chunks {
+        executor
+            .apply_and_commit_chunk(chunk, &ledger_info, None)
+            .unwrap();
+    }
+    assert_eq!(audits.get(), matches + 2);
+    assert_eq!(db.reader.get_latest_ledger_info().unwrap(), ledger_info);
+}
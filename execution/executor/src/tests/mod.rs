@@ -7,24 +7,27 @@ use crate::{
     components::{apply_chunk_output::IntoLedgerView, chunk_output::ChunkOutput},
     db_bootstrapper::{generate_waypoint, maybe_bootstrap},
     mock_vm::{
-        encode_mint_transaction, encode_reconfiguration_transaction, encode_transfer_transaction,
-        MockVM, DISCARD_STATUS, KEEP_STATUS,
+        balance_ap, encode_mint_transaction, encode_reconfiguration_transaction,
+        encode_transfer_transaction, MockVM, DISCARD_STATUS, KEEP_STATUS,
     },
 };
 use aptos_crypto::HashValue;
-use aptos_state_view::StateViewId;
+use aptos_state_view::{StateView, StateViewId};
 use aptos_types::{
-    account_address::AccountAddress,
+    account_address::{AccountAddress, HashAccountAddress},
     block_info::BlockInfo,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     proof::definition::LeafCount,
     transaction::{Transaction, TransactionListWithProof, TransactionStatus, Version},
 };
 use aptosdb::AptosDB;
-use executor_types::{BlockExecutorTrait, ChunkExecutorTrait, ExecutedTrees, TransactionReplayer};
+use executor_types::{
+    BlockExecutorTrait, ChunkExecutorTrait, ExecutedTrees, StateComputeResult, TransactionReplayer,
+};
+use futures::executor::block_on;
 use proptest::prelude::*;
-use std::collections::BTreeMap;
-use storage_interface::DbReaderWriter;
+use std::collections::{BTreeMap, HashMap};
+use storage_interface::{async_state_view::AsyncStateView, DbReaderWriter};
 
 mod chunk_executor_tests;
 
@@ -227,6 +230,145 @@ fn test_executor_commit_twice() {
         .unwrap();
 }
 
+#[test]
+fn test_executor_pipelined_commit() {
+    let mut executor = TestExecutor::new();
+    executor.executor = BlockExecutor::new(executor.db.clone()).with_pipelined_commit(2);
+    let mut parent_block_id = executor.committed_block_id();
+
+    // Blocks execute on top of the ones queued for persistence
+    for i in 0..10 {
+        parent_block_id = execute_and_commit_block(&executor, parent_block_id, i);
+    }
+    let block_id = gen_block_id(11);
+    let output = executor
+        .execute_block(
+            (
+                block_id,
+                vec![encode_mint_transaction(gen_address(10), 100)],
+            ),
+            parent_block_id,
+        )
+        .unwrap();
+    assert_eq!(output.version(), 11);
+
+    // Queued commits can be retried
+    let ledger_info = gen_ledger_info(11, output.root_hash(), block_id, 11);
+    executor
+        .commit_blocks(vec![block_id], ledger_info.clone())
+        .unwrap();
+    executor.commit_blocks(vec![block_id], ledger_info).unwrap();
+
+    // The commits are persisted in order
+    assert_eq!(executor.committed_block_id(), block_id);
+    assert_eq!(executor.db.reader.get_latest_version().unwrap(), 11);
+}
+
+#[test]
+fn test_executor_flush_pipelined_commits() {
+    let mut executor = TestExecutor::new();
+    executor.executor = BlockExecutor::new(executor.db.clone()).with_pipelined_commit(10);
+    let mut parent_block_id = executor.committed_block_id();
+    for i in 0..5 {
+        parent_block_id = execute_and_commit_block(&executor, parent_block_id, i);
+    }
+
+    // Once flushed, the DB can be written to by others, e.g., state sync
+    executor.flush_commits().unwrap();
+    assert_eq!(executor.db.reader.get_latest_version().unwrap(), 5);
+    assert_eq!(executor.committed_block_id(), parent_block_id);
+}
+
+#[test]
+fn test_executor_wait_for_persisted() {
+    let mut executor = TestExecutor::new();
+    executor.executor = BlockExecutor::new(executor.db.clone()).with_pipelined_commit(10);
+    let mut parent_block_id = executor.committed_block_id();
+    for i in 0..5 {
+        parent_block_id = execute_and_commit_block(&executor, parent_block_id, i);
+    }
+
+    // Committed blocks can only be exposed once persisted
+    executor.wait_for_persisted(3).unwrap();
+    assert!(executor.db.reader.get_latest_version().unwrap() >= 3);
+    executor.wait_for_persisted(5).unwrap();
+    assert_eq!(executor.db.reader.get_latest_version().unwrap(), 5);
+}
+
+#[test]
+fn test_executor_state_delta() {
+    let executor = TestExecutor::new();
+    let parent_block_id = executor.committed_block_id();
+    let block_id = gen_block_id(1);
+    let txns = (0..3)
+        .map(|i| encode_mint_transaction(gen_address(i), 100))
+        .collect();
+    let output = executor
+        .execute_block((block_id, txns), parent_block_id)
+        .unwrap();
+
+    // The accounts of the mints, with the states committed along the block
+    let update_batch: HashMap<_, _> = output
+        .state_delta()
+        .unwrap()
+        .update_batch()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(update_batch.len(), 3);
+    let ledger_info = gen_ledger_info(3, output.root_hash(), block_id, 1);
+    executor.commit_blocks(vec![block_id], ledger_info).unwrap();
+    for i in 0..3 {
+        let address = gen_address(i);
+        let (committed_blob, _) = executor
+            .db
+            .reader
+            .get_account_state_with_proof_by_version(address, 3)
+            .unwrap();
+        assert_eq!(committed_blob.as_ref(), update_batch.get(&address.hash()));
+    }
+
+    // Results which didn't come from the local executor have no delta
+    let result: StateComputeResult = bcs::from_bytes(&bcs::to_bytes(&output).unwrap()).unwrap();
+    assert!(result.state_delta().is_none());
+}
+
+#[test]
+fn test_verified_state_view_multi_get() {
+    let executor = TestExecutor::new();
+    let mut parent_block_id = executor.committed_block_id();
+    for i in 0..3 {
+        parent_block_id = execute_and_commit_block(&executor, parent_block_id, i);
+    }
+
+    let db = &executor.db;
+    let ledger_view: ExecutedTrees = db
+        .reader
+        .get_latest_tree_state()
+        .unwrap()
+        .into_ledger_view(&db.reader)
+        .unwrap();
+    // The accounts of the mints, twice, and one which doesn't exist
+    let access_paths: Vec<_> = (0..3)
+        .chain(0..3)
+        .chain(10..11)
+        .map(|i| balance_ap(gen_address(i)))
+        .collect();
+
+    let state_view =
+        ledger_view.state_view(&ledger_view, StateViewId::Miscellaneous, db.reader.clone());
+    let values = block_on(state_view.multi_get_state_values(&access_paths)).unwrap();
+    let state_view =
+        ledger_view.state_view(&ledger_view, StateViewId::Miscellaneous, db.reader.clone());
+    let expected_values: Vec<_> = access_paths
+        .iter()
+        .map(|access_path| state_view.get(access_path).unwrap())
+        .collect();
+    assert_eq!(values, expected_values);
+    assert!(values[..6].iter().all(Option::is_some));
+    assert_eq!(values[6], None);
+}
+
 #[test]
 fn test_executor_execute_same_block_multiple_times() {
     let executor = TestExecutor::new();
@@ -323,6 +465,58 @@ fn test_noop_block_after_reconfiguration() {
     assert_eq!(output1.root_hash(), output2.root_hash());
 }
 
+#[test]
+fn test_block_gas_limit() {
+    let executor = TestExecutor::new();
+    let db = &executor.db;
+    let ledger_view: ExecutedTrees = db
+        .reader
+        .get_latest_tree_state()
+        .unwrap()
+        .into_ledger_view(&db.reader)
+        .unwrap();
+    let txns = vec![
+        encode_mint_transaction(gen_address(0), 100),
+        encode_reconfiguration_transaction(gen_address(1)),
+        encode_mint_transaction(gen_address(2), 100),
+    ];
+    let execute = |block_gas_limit| {
+        ChunkOutput::by_transaction_execution::<MockVM>(
+            txns.clone(),
+            ledger_view.state_view(&ledger_view, StateViewId::Miscellaneous, db.reader.clone()),
+        )
+        .unwrap()
+        .with_block_gas_limit(block_gas_limit)
+        .apply_to_ledger(ledger_view.txn_accumulator())
+        .unwrap()
+    };
+
+    // The mock VM uses no gas: the first transaction reaches the limit of 0, the block is cut
+    // before the reconfiguration.
+    let (output, _, to_retry) = execute(Some(0));
+    assert_eq!(output.block_gas_limit_cut, Some(1));
+    assert_eq!(output.transactions(), txns[..1].to_vec());
+    assert_eq!(to_retry, txns[1..].to_vec());
+    assert_eq!(
+        output.status[1..],
+        [TransactionStatus::Retry, TransactionStatus::Retry]
+    );
+    assert!(output.next_epoch_state.is_none());
+    assert_eq!(
+        output
+            .as_state_compute_result(ledger_view.txn_accumulator())
+            .block_gas_limit_cut(),
+        Some(1)
+    );
+
+    // Without a limit, the block ends with the reconfiguration.
+    let (output, _, to_retry) = execute(None);
+    assert_eq!(output.block_gas_limit_cut, None);
+    assert_eq!(output.transactions(), txns[..2].to_vec());
+    assert_eq!(to_retry, txns[2..].to_vec());
+    assert!(output.next_epoch_state.is_some());
+}
+
 struct TestBlock {
     txns: Vec<Transaction>,
     id: HashValue,
@@ -0,0 +1,113 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, Uniform};
+use aptos_temppath::TempPath;
+use aptos_transaction_builder::stdlib::encode_create_parent_vasp_account_script;
+use aptos_types::{
+    account_config::{treasury_compliance_account_address, xus_tag},
+    on_chain_config::{access_path_for_config, BlockGasLimit, OnChainConfig},
+    transaction::{
+        authenticator::AuthenticationKey, ChangeSet, Transaction, TransactionPayload,
+        TransactionStatus, WriteSetPayload,
+    },
+    validator_signer::ValidatorSigner,
+    write_set::WriteOp,
+};
+use aptos_vm::AptosVM;
+use executor::block_executor::BlockExecutor;
+use executor_test_helpers::{
+    bootstrap_genesis, gen_ledger_info_with_sigs, get_test_signed_transaction,
+};
+use executor_types::BlockExecutorTrait;
+use rand::SeedableRng;
+use storage_interface::{DbReader, DbReaderWriter};
+
+fn get_account_transaction(tc_seq_num: u64, account_key: &Ed25519PrivateKey) -> Transaction {
+    let genesis_key = &vm_genesis::GENESIS_KEYPAIR.0;
+    let account_auth_key = AuthenticationKey::ed25519(&account_key.public_key());
+    get_test_signed_transaction(
+        treasury_compliance_account_address(),
+        tc_seq_num,
+        genesis_key.clone(),
+        genesis_key.public_key(),
+        Some(TransactionPayload::Script(
+            encode_create_parent_vasp_account_script(
+                xus_tag(),
+                0,
+                account_auth_key.derived_address(),
+                account_auth_key.prefix().to_vec(),
+                vec![],
+                false,
+            ),
+        )),
+    )
+}
+
+#[test]
+fn test_block_gas_limit() {
+    let (genesis, validators) = vm_genesis::test_genesis_change_set_and_validators(Some(1));
+    let signer = ValidatorSigner::new(validators[0].data.address, validators[0].key.clone());
+
+    // Publish the limit where the BlockGasLimit module does: every transaction reaches it.
+    let (write_set, events) = genesis.into_inner();
+    let mut write_set = write_set.into_mut();
+    write_set.push((
+        access_path_for_config(BlockGasLimit::CONFIG_ID),
+        WriteOp::Value(
+            bcs::to_bytes(&BlockGasLimit {
+                max_gas_per_block: 1,
+            })
+            .unwrap(),
+        ),
+    ));
+    let genesis_txn = Transaction::GenesisTransaction(WriteSetPayload::Direct(ChangeSet::new(
+        write_set.freeze().unwrap(),
+        events,
+    )));
+
+    let tmp_dir = TempPath::new();
+    let db = DbReaderWriter::new(aptosdb::AptosDB::new_for_test(&tmp_dir));
+    bootstrap_genesis::<AptosVM>(&db, &genesis_txn).unwrap();
+    let executor = BlockExecutor::<AptosVM>::new(db.clone());
+
+    let mut rng = ::rand::rngs::StdRng::from_seed([3u8; 32]);
+    let txns: Vec<_> = (0..3)
+        .map(|i| get_account_transaction(i, &Ed25519PrivateKey::generate(&mut rng)))
+        .collect();
+
+    // Each block ends with the transaction reaching the limit, the ones after it are retried.
+    let mut parent_block_id = executor.committed_block_id();
+    for i in 0..txns.len() {
+        let li = db.reader.get_latest_ledger_info().unwrap();
+        let version = li.ledger_info().version();
+        let block_id = HashValue::random();
+        let output = executor
+            .execute_block((block_id, txns[i..].to_vec()), parent_block_id)
+            .unwrap();
+        assert_eq!(output.version(), version + 1);
+        assert!(matches!(
+            output.compute_status()[0],
+            TransactionStatus::Keep(_)
+        ));
+        assert!(output.compute_status()[1..]
+            .iter()
+            .all(|status| *status == TransactionStatus::Retry));
+        let block_gas_limit_cut = if i + 1 < txns.len() { Some(1) } else { None };
+        assert_eq!(output.block_gas_limit_cut(), block_gas_limit_cut);
+
+        let ledger_info_with_sigs = gen_ledger_info_with_sigs(
+            li.ledger_info().next_block_epoch(),
+            &output,
+            block_id,
+            vec![&signer],
+        );
+        executor
+            .commit_blocks(vec![block_id], ledger_info_with_sigs)
+            .unwrap();
+        parent_block_id = block_id;
+    }
+    assert_eq!(db.reader.get_latest_version().unwrap(), txns.len() as u64);
+}
@@ -18,6 +18,7 @@ use aptos_types::{
     },
     account_state::AccountState,
     account_state_blob::AccountStateBlob,
+    chain_id::ChainId,
     contract_event::ContractEvent,
     on_chain_config,
     on_chain_config::{
@@ -91,6 +92,41 @@ fn test_empty_db() {
     assert!(!maybe_bootstrap::<AptosVM>(&db_rw, &genesis_txn, waypoint).unwrap());
 }
 
+#[test]
+fn test_genesis_builder() {
+    let validators = vm_genesis::TestValidator::new_test_set(Some(1));
+    let (account1, account1_key, account2, account2_key) = get_demo_accounts();
+    let genesis_txn = vm_genesis::GenesisBuilder::new(
+        diem_framework_releases::current_module_blobs().to_vec(),
+        ChainId::new(42),
+        vm_genesis::GENESIS_KEYPAIR.1.clone(),
+        vm_genesis::GENESIS_KEYPAIR.1.clone(),
+    )
+    .validators(validators.iter().map(|v| v.data.clone()).collect())
+    .accounts(vec![
+        vm_genesis::GenesisAccount {
+            auth_key: AuthenticationKey::ed25519(&account1_key.public_key()),
+            balance: 1_000_000,
+        },
+        vm_genesis::GenesisAccount {
+            auth_key: AuthenticationKey::ed25519(&account2_key.public_key()),
+            balance: 0,
+        },
+    ])
+    .build()
+    .unwrap();
+
+    let tmp_dir = TempPath::new();
+    let db_rw = DbReaderWriter::new(AptosDB::new_for_test(&tmp_dir));
+    let waypoint = generate_waypoint::<AptosVM>(&db_rw, &genesis_txn).unwrap();
+    assert!(maybe_bootstrap::<AptosVM>(&db_rw, &genesis_txn, waypoint).unwrap());
+
+    assert_eq!(get_balance(&account1, &db_rw), 1_000_000);
+    assert_eq!(get_balance(&account2, &db_rw), 0);
+    // Only the balances of the accounts are minted outside of test chains.
+    assert_eq!(get_balance(&testnet_dd_account_address(), &db_rw), 0);
+}
+
 fn execute_and_commit(txns: Vec<Transaction>, db: &DbReaderWriter, signer: &ValidatorSigner) {
     let block_id = HashValue::random();
     let li = db.reader.get_latest_ledger_info().unwrap();
@@ -254,6 +254,67 @@ impl TimelineIndex {
     }
 }
 
+/// BundleIndex keeps track of the transaction bundles: transactions of an account with
+/// consecutive sequence numbers, which must be included in a block together, in order, or not at
+/// all.
+///
+/// It's represented as Map <Address, Map <first sequence_number, last sequence_number>>.
+pub struct BundleIndex {
+    data: HashMap<AccountAddress, BTreeMap<u64, u64>>,
+    size: usize,
+}
+
+impl BundleIndex {
+    pub(crate) fn new() -> Self {
+        Self {
+            data: HashMap::new(),
+            size: 0,
+        }
+    }
+
+    pub(crate) fn insert(&mut self, address: AccountAddress, first: u64, last: u64) {
+        if self
+            .data
+            .entry(address)
+            .or_default()
+            .insert(first, last)
+            .is_none()
+        {
+            self.size += 1;
+        }
+    }
+
+    /// Returns the first and last sequence numbers of the bundle the transaction belongs to.
+    pub(crate) fn get(&self, address: &AccountAddress, sequence_number: u64) -> Option<(u64, u64)> {
+        self.data
+            .get(address)?
+            .range(..=sequence_number)
+            .next_back()
+            .filter(|(_, last)| **last >= sequence_number)
+            .map(|(first, last)| (*first, *last))
+    }
+
+    /// Removes the bundle the transaction belongs to, if any.
+    pub(crate) fn remove(&mut self, txn: &MempoolTransaction) {
+        let address = txn.get_sender();
+        let sequence_number = txn.sequence_info.transaction_sequence_number;
+        if let Some((first, _)) = self.get(&address, sequence_number) {
+            if let Some(bundles) = self.data.get_mut(&address) {
+                if bundles.remove(&first).is_some() {
+                    self.size -= 1;
+                }
+                if bundles.is_empty() {
+                    self.data.remove(&address);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+}
+
 /// ParkingLotIndex keeps track of "not_ready" transactions, e.g., transactions that
 /// can't be included in the next block because their sequence number is too high.
 /// We keep a separate index to be able to efficiently evict them when Mempool is full.
@@ -33,6 +33,9 @@ pub struct Mempool {
     transactions: TransactionStore,
 
     sequence_number_cache: TtlCache<AccountAddress, u64>,
+    // Hashes of the recently committed transactions, by sender and sequence number, so that
+    // resubmissions of them are reported as committed instead of as old sequence numbers.
+    committed_txn_cache: TtlCache<(AccountAddress, u64), HashValue>,
     // For each transaction, an entry with a timestamp is added when the transaction enters mempool.
     // This is used to measure e2e latency of transactions in the system, as well as the time it
     // takes to pick it up by consensus.
@@ -45,6 +48,7 @@ impl Mempool {
         Mempool {
             transactions: TransactionStore::new(&config.mempool),
             sequence_number_cache: TtlCache::new(config.mempool.capacity, Duration::from_secs(100)),
+            committed_txn_cache: TtlCache::new(config.mempool.capacity, Duration::from_secs(100)),
             metrics_cache: TtlCache::new(config.mempool.capacity, Duration::from_secs(100)),
             system_transaction_timeout: Duration::from_secs(
                 config.mempool.system_transaction_timeout_secs,
@@ -109,6 +113,54 @@ impl Mempool {
         }
     }
 
+    /// Records a recently committed transaction.
+    pub(crate) fn add_committed_transaction(
+        &mut self,
+        sender: AccountAddress,
+        sequence_number: u64,
+        hash: HashValue,
+    ) {
+        self.committed_txn_cache
+            .insert((sender, sequence_number), hash);
+    }
+
+    /// Whether the transaction is one of the recently committed ones. A different transaction
+    /// with the same sender and sequence number isn't.
+    pub(crate) fn is_recently_committed(&self, txn: &SignedTransaction) -> bool {
+        self.committed_txn_cache
+            .get(&(txn.sender(), txn.sequence_number()))
+            .map_or(false, |hash| *hash == txn.clone().committed_hash())
+    }
+
+    /// The transactions of the account which can't be included in the next block.
+    pub(crate) fn get_parked_transactions(
+        &self,
+        sender: &AccountAddress,
+    ) -> Vec<SignedTransaction> {
+        self.transactions.get_parked_transactions(sender)
+    }
+
+    /// The lowest sequence number of the transactions of the account in mempool, if any.
+    pub(crate) fn get_lowest_sequence_number(&self, sender: &AccountAddress) -> Option<u64> {
+        self.transactions.get_lowest_sequence_number(sender)
+    }
+
+    /// Evicts a parked transaction which can't become valid anymore, along with the rest of its
+    /// bundle. Returns false if it isn't parked anymore, e.g. it became ready in the meantime.
+    pub(crate) fn evict_parked_transaction(
+        &mut self,
+        sender: &AccountAddress,
+        sequence_number: u64,
+    ) -> bool {
+        let evicted = self
+            .transactions
+            .evict_parked_transaction(sender, sequence_number);
+        if evicted {
+            self.metrics_cache.remove(&(*sender, sequence_number));
+        }
+        evicted
+    }
+
     fn log_latency(&self, account: AccountAddress, sequence_number: u64, metric: &str) {
         if let Some(&creation_time) = self.metrics_cache.get(&(account, sequence_number)) {
             if let Ok(time_delta) = SystemTime::now().duration_since(creation_time) {
@@ -177,6 +229,88 @@ impl Mempool {
         self.transactions.insert(txn_info)
     }
 
+    /// Used to add a bundle of transactions of an account to the Mempool: transactions with
+    /// consecutive sequence numbers, each with its gas amount and ranking score, which are
+    /// included in a block together, in order, or not at all. Either all the transactions are
+    /// added or none is.
+    /// Bundles are broadcast as a whole, see `group_bundles`.
+    pub(crate) fn add_txn_bundle(
+        &mut self,
+        txns: Vec<(SignedTransaction, u64, u64)>,
+        crsn_or_seqno: AccountSequenceInfo,
+        timeline_state: TimelineState,
+    ) -> MempoolStatus {
+        let (first_txn, _, _) = match txns.first() {
+            Some(first) => first,
+            None => {
+                return MempoolStatus::new(MempoolStatusCode::InvalidBundle)
+                    .with_message("bundle is empty".to_string())
+            }
+        };
+        let sender = first_txn.sender();
+        let first_sequence_number = first_txn.sequence_number();
+        let is_sequential = txns.iter().enumerate().all(|(i, (txn, _, _))| {
+            txn.sender() == sender && txn.sequence_number() == first_sequence_number + i as u64
+        });
+        if !is_sequential {
+            return MempoolStatus::new(MempoolStatusCode::InvalidBundle).with_message(
+                "bundle transactions must be of the same account, with consecutive sequence numbers"
+                    .to_string(),
+            );
+        }
+        let db_sequence_number = match crsn_or_seqno {
+            AccountSequenceInfo::Sequential(sequence_number) => sequence_number,
+            AccountSequenceInfo::CRSN { .. } => {
+                return MempoolStatus::new(MempoolStatusCode::InvalidBundle)
+                    .with_message("bundles require sequential sequence numbers".to_string())
+            }
+        };
+        trace!(
+            LogSchema::new(LogEntry::AddTxn).txns(TxnsLog::new_txn(sender, first_sequence_number)),
+            committed_seq_number = db_sequence_number,
+            bundle_size = txns.len()
+        );
+        let sequence_number = self
+            .sequence_number_cache
+            .get(&sender)
+            .map_or(db_sequence_number, |value| max(*value, db_sequence_number));
+        self.sequence_number_cache.insert(sender, sequence_number);
+
+        // don't accept old transactions (e.g. seq is less than account's current seq_number)
+        if first_sequence_number < sequence_number {
+            return MempoolStatus::new(MempoolStatusCode::InvalidSeqNumber).with_message(format!(
+                "transaction sequence number is {}, current sequence number is  {}",
+                first_sequence_number, sequence_number,
+            ));
+        }
+
+        let expiration_time =
+            aptos_infallible::duration_since_epoch() + self.system_transaction_timeout;
+        let txns = txns
+            .into_iter()
+            .map(|(txn, gas_amount, ranking_score)| {
+                MempoolTransaction::new(
+                    txn,
+                    expiration_time,
+                    gas_amount,
+                    ranking_score,
+                    timeline_state,
+                    AccountSequenceInfo::Sequential(sequence_number),
+                )
+            })
+            .collect::<Vec<_>>();
+        let txns_len = txns.len() as u64;
+        let status = self.transactions.insert_bundle(txns);
+        if status.code == MempoolStatusCode::Accepted {
+            let now = SystemTime::now();
+            for i in 0..txns_len {
+                self.metrics_cache
+                    .insert((sender, first_sequence_number + i), now);
+            }
+        }
+        status
+    }
+
     /// Fetches next block of transactions for consensus.
     /// `batch_size` - size of requested block.
     /// `seen_txns` - transactions that were sent to Consensus but were not committed yet,
@@ -214,23 +348,31 @@ impl Mempool {
                 || account_sequence_number == Some(&tx_seq)
                 || matches!(account_seqtype, AccountSequenceInfo::CRSN { .. })
             {
-                let ptr = TxnPointer::from(txn);
-                seen.insert(ptr);
-                result.push(ptr);
+                // the transactions of a bundle are included together, if they all fit
+                let ptrs = self.transactions.bundle_pointers(TxnPointer::from(txn));
+                if (result.len() + ptrs.len()) as u64 > batch_size {
+                    continue;
+                }
+                seen.extend(&ptrs);
+                result.extend(&ptrs);
                 if (result.len() as u64) == batch_size {
                     break;
                 }
 
                 // check if we can now include some transactions
                 // that were skipped before for given account
-                let mut skipped_txn = (txn.address, tx_seq + 1);
+                let mut skipped_txn = (txn.address, tx_seq + ptrs.len() as u64);
                 while skipped.contains(&skipped_txn) {
-                    seen.insert(skipped_txn);
-                    result.push(skipped_txn);
+                    let ptrs = self.transactions.bundle_pointers(skipped_txn);
+                    if (result.len() + ptrs.len()) as u64 > batch_size {
+                        break;
+                    }
+                    seen.extend(&ptrs);
+                    result.extend(&ptrs);
                     if (result.len() as u64) == batch_size {
                         break 'main;
                     }
-                    skipped_txn = (txn.address, skipped_txn.1 + 1);
+                    skipped_txn = (txn.address, skipped_txn.1 + ptrs.len() as u64);
                 }
             } else {
                 skipped.insert(TxnPointer::from(txn));
@@ -267,12 +409,13 @@ impl Mempool {
 
     /// Periodic core mempool garbage collection.
     /// Removes all expired transactions and clears expired entries in metrics
-    /// cache and sequence number cache.
+    /// cache, sequence number cache and committed transaction cache.
     pub(crate) fn gc(&mut self) {
         let now = SystemTime::now();
         self.transactions.gc_by_system_ttl(&self.metrics_cache);
         self.metrics_cache.gc(now);
         self.sequence_number_cache.gc(now);
+        self.committed_txn_cache.gc(now);
     }
 
     /// Garbage collection based on client-specified expiration time.
@@ -296,6 +439,27 @@ impl Mempool {
         self.transactions.timeline_range(start_id, end_id)
     }
 
+    /// Splits the transactions read from the timeline for a broadcast into the transactions
+    /// outside of bundles and the bundles, which must be broadcast as a whole for the peers to
+    /// add them atomically. A bundle is broadcast along with its first transaction, and its other
+    /// transactions are skipped.
+    pub(crate) fn group_bundles(
+        &self,
+        txns: Vec<SignedTransaction>,
+    ) -> (Vec<SignedTransaction>, Vec<Vec<SignedTransaction>>) {
+        let mut single_txns = vec![];
+        let mut bundles = vec![];
+        for txn in txns {
+            let sequence_number = txn.sequence_number();
+            match self.transactions.get_bundle(&txn.sender(), sequence_number) {
+                Some((first, bundle)) if first == sequence_number => bundles.push(bundle),
+                Some(_) => (),
+                None => single_txns.push(txn),
+            }
+        }
+        (single_txns, bundles)
+    }
+
     pub fn gen_snapshot(&self) -> TxnsLog {
         self.transactions.gen_snapshot(&self.metrics_cache)
     }
@@ -304,4 +468,12 @@ impl Mempool {
     pub fn get_parking_lot_size(&self) -> usize {
         self.transactions.get_parking_lot_size()
     }
+
+    pub fn size(&self) -> usize {
+        self.transactions.size()
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize, capacity_per_user: usize) {
+        self.transactions.set_capacity(capacity, capacity_per_user);
+    }
 }
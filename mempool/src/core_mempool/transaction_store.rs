@@ -4,8 +4,8 @@
 use crate::{
     core_mempool::{
         index::{
-            AccountTransactions, ParkingLotIndex, PriorityIndex, PriorityQueueIter, TTLIndex,
-            TimelineIndex,
+            AccountTransactions, BundleIndex, ParkingLotIndex, PriorityIndex, PriorityQueueIter,
+            TTLIndex, TTLOrderingKey, TimelineIndex, TxnPointer,
         },
         transaction::{MempoolTransaction, TimelineState},
         ttl_cache::TtlCache,
@@ -51,6 +51,8 @@ pub struct TransactionStore {
     // Using transaction commited hash because from end user's point view, a transaction should only have
     // one valid hash.
     hash_index: HashMap<HashValue, (AccountAddress, u64)>,
+    // keeps track of the transactions which must be included in a block together
+    bundle_index: BundleIndex,
 
     // configuration
     capacity: usize,
@@ -72,6 +74,7 @@ impl TransactionStore {
             timeline_index: TimelineIndex::new(),
             parking_lot_index: ParkingLotIndex::new(),
             hash_index: HashMap::new(),
+            bundle_index: BundleIndex::new(),
 
             // configuration
             capacity: config.capacity,
@@ -130,6 +133,14 @@ impl TransactionStore {
                 if current_version.txn == txn.txn {
                     return MempoolStatus::new(MempoolStatusCode::Accepted);
                 }
+                if self
+                    .bundle_index
+                    .get(&address, sequence_number.transaction_sequence_number)
+                    .is_some()
+                {
+                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate)
+                        .with_message("Transaction of a bundle already in mempool".to_string());
+                }
                 if current_version.txn.max_gas_amount() == txn.txn.max_gas_amount()
                     && current_version.txn.payload() == txn.txn.payload()
                     && current_version.txn.expiration_timestamp_secs()
@@ -195,6 +206,111 @@ impl TransactionStore {
         MempoolStatus::new(MempoolStatusCode::Accepted)
     }
 
+    /// Insert a bundle of transactions of an account, with consecutive sequence numbers, into
+    /// TransactionStore. Either all the transactions are inserted or none is.
+    pub(crate) fn insert_bundle(&mut self, txns: Vec<MempoolTransaction>) -> MempoolStatus {
+        let address = txns[0].get_sender();
+        let first = txns[0].sequence_info.transaction_sequence_number;
+        let last = first + txns.len() as u64 - 1;
+
+        // the transactions of a bundle can't be updated, only the same bundle can be submitted
+        // again
+        if let Some(account_txns) = self.transactions.get(&address) {
+            let current: Vec<_> = account_txns.range(first..=last).map(|(_, t)| t).collect();
+            if !current.is_empty() {
+                let is_same_bundle = self.bundle_index.get(&address, first) == Some((first, last))
+                    && current.len() == txns.len()
+                    && current.iter().zip(&txns).all(|(c, t)| c.txn == t.txn);
+                if is_same_bundle {
+                    return MempoolStatus::new(MempoolStatusCode::Accepted);
+                }
+                return MempoolStatus::new(MempoolStatusCode::InvalidUpdate)
+                    .with_message("Transactions of the bundle already in mempool".to_string());
+            }
+        }
+
+        if self.system_ttl_index.size() + txns.len() > self.capacity {
+            return MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_message(format!(
+                "mempool size: {}, capacity: {}, bundle size: {}",
+                self.system_ttl_index.size(),
+                self.capacity,
+                txns.len(),
+            ));
+        }
+        let account_txns_len = self.transactions.get(&address).map_or(0, |txns| txns.len());
+        if account_txns_len + txns.len() > self.capacity_per_user {
+            return MempoolStatus::new(MempoolStatusCode::TooManyTransactions).with_message(
+                format!(
+                    "txns length: {} capacity per user: {}, bundle size: {}",
+                    account_txns_len,
+                    self.capacity_per_user,
+                    txns.len(),
+                ),
+            );
+        }
+
+        for txn in txns {
+            let status = self.insert(txn);
+            if status.code != MempoolStatusCode::Accepted {
+                self.remove_range(&address, first, last);
+                return status;
+            }
+        }
+        self.bundle_index.insert(address, first, last);
+        self.track_indices();
+        MempoolStatus::new(MempoolStatusCode::Accepted)
+    }
+
+    /// Returns the transactions to include in a block along with the transaction: the whole
+    /// bundle if the transaction starts one, only the transaction itself otherwise.
+    pub(crate) fn bundle_pointers(&self, ptr: TxnPointer) -> Vec<TxnPointer> {
+        match self.bundle_index.get(&ptr.0, ptr.1) {
+            Some((first, last)) if first == ptr.1 => {
+                (first..=last).map(|seq_num| (ptr.0, seq_num)).collect()
+            }
+            _ => vec![ptr],
+        }
+    }
+
+    /// Returns the first sequence number and the transactions of the bundle the transaction
+    /// belongs to, if any.
+    pub(crate) fn get_bundle(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<(u64, Vec<SignedTransaction>)> {
+        let (first, last) = self.bundle_index.get(address, sequence_number)?;
+        let txns = self
+            .transactions
+            .get(address)?
+            .range(first..=last)
+            .map(|(_, txn)| txn.txn.clone())
+            .collect();
+        Some((first, txns))
+    }
+
+    /// Removes the transactions of the account from sequence number `first` to `last`, and parks
+    /// the transactions following them, which can't be included in a block anymore.
+    fn remove_range(&mut self, address: &AccountAddress, first: u64, last: u64) {
+        let removed = match self.transactions.get_mut(address) {
+            Some(txns) => {
+                let mut removed = txns.split_off(&first);
+                let mut following = removed.split_off(&(last + 1));
+                for txn in following.values() {
+                    self.parking_lot_index.insert(txn);
+                    self.priority_index.remove(txn);
+                    self.timeline_index.remove(txn);
+                }
+                txns.append(&mut following);
+                removed
+            }
+            None => return,
+        };
+        for txn in removed.values() {
+            self.index_remove(txn);
+        }
+    }
+
     fn track_indices(&self) {
         counters::core_mempool_index_size(
             counters::SYSTEM_TTL_INDEX_LABEL,
@@ -220,6 +336,7 @@ impl TransactionStore {
             counters::TRANSACTION_HASH_INDEX_LABEL,
             self.hash_index.len(),
         );
+        counters::core_mempool_index_size(counters::BUNDLE_INDEX_LABEL, self.bundle_index.size());
     }
 
     /// Checks if Mempool is full.
@@ -235,24 +352,72 @@ impl TransactionStore {
         {
             // try to free some space in Mempool from ParkingLot by evicting a non-ready txn
             if let Some((address, sequence_number)) = self.parking_lot_index.get_poppable() {
-                if let Some(txn) = self
-                    .transactions
-                    .get_mut(&address)
-                    .and_then(|txns| txns.remove(&sequence_number))
-                {
-                    debug!(
-                        LogSchema::new(LogEntry::MempoolFullEvictedTxn).txns(TxnsLog::new_txn(
-                            txn.get_sender(),
-                            txn.sequence_info.transaction_sequence_number
-                        ))
-                    );
-                    self.index_remove(&txn);
-                }
+                self.evict(&address, sequence_number, LogEntry::MempoolFullEvictedTxn);
             }
         }
         self.system_ttl_index.size() >= self.capacity
     }
 
+    /// Evicts a transaction, along with the rest of its bundle if it's part of one.
+    fn evict(&mut self, address: &AccountAddress, sequence_number: u64, log_entry: LogEntry) {
+        // a bundle is evicted as a whole
+        if let Some((first, last)) = self.bundle_index.get(address, sequence_number) {
+            debug!(
+                LogSchema::new(log_entry).txns(TxnsLog::new_txn(*address, first)),
+                bundle_last_seq_num = last,
+            );
+            self.remove_range(address, first, last);
+        } else if let Some(txn) = self
+            .transactions
+            .get_mut(address)
+            .and_then(|txns| txns.remove(&sequence_number))
+        {
+            debug!(LogSchema::new(log_entry).txns(TxnsLog::new_txn(
+                txn.get_sender(),
+                txn.sequence_info.transaction_sequence_number
+            )));
+            self.index_remove(&txn);
+        }
+    }
+
+    /// The parked transactions of the account, i.e. those which can't be included in the next
+    /// block.
+    pub(crate) fn get_parked_transactions(
+        &self,
+        address: &AccountAddress,
+    ) -> Vec<SignedTransaction> {
+        self.transactions
+            .get(address)
+            .map_or_else(Vec::new, |txns| {
+                txns.iter()
+                    .filter(|(seq_num, _)| self.parking_lot_index.contains(address, seq_num))
+                    .map(|(_, txn)| txn.txn.clone())
+                    .collect()
+            })
+    }
+
+    /// The lowest sequence number of the transactions of the account, if any.
+    pub(crate) fn get_lowest_sequence_number(&self, address: &AccountAddress) -> Option<u64> {
+        self.transactions
+            .get(address)
+            .and_then(|txns| txns.keys().next().copied())
+    }
+
+    /// Evicts a parked transaction which can't become valid anymore. Returns false if it isn't
+    /// parked anymore.
+    pub(crate) fn evict_parked_transaction(
+        &mut self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> bool {
+        if !self.parking_lot_index.contains(address, &sequence_number) {
+            return false;
+        }
+        self.evict(address, sequence_number, LogEntry::InvalidParkedEvictedTxn);
+        self.track_indices();
+        true
+    }
+
     /// Check if a transaction would be ready for broadcast in mempool upon insertion (without inserting it).
     /// Two ways this can happen:
     /// 1. txn sequence number == curr_sequence_number
@@ -410,6 +575,7 @@ impl TransactionStore {
         self.timeline_index.remove(txn);
         self.parking_lot_index.remove(txn);
         self.hash_index.remove(&txn.get_committed_hash());
+        self.bundle_index.remove(txn);
         self.track_indices();
     }
 
@@ -493,8 +659,21 @@ impl TransactionStore {
             .inc();
 
         let mut gc_txns = index.gc(now);
+        // the other transactions of the bundles of the expired txns expire with them
+        let mut bundle_txns = vec![];
+        for key in &gc_txns {
+            if let Some((first, last)) = self.bundle_index.get(&key.address, key.sequence_number) {
+                bundle_txns.extend((first..=last).map(|sequence_number| TTLOrderingKey {
+                    expiration_time: key.expiration_time,
+                    address: key.address,
+                    sequence_number,
+                }));
+            }
+        }
+        gc_txns.append(&mut bundle_txns);
         // sort the expired txns by order of sequence number per account
         gc_txns.sort_by_key(|key| (key.address, key.sequence_number));
+        gc_txns.dedup_by_key(|key| (key.address, key.sequence_number));
         let mut gc_iter = gc_txns.iter().peekable();
 
         let mut gc_txns_log = TxnsLog::new();
@@ -569,4 +748,16 @@ impl TransactionStore {
     pub(crate) fn get_parking_lot_size(&self) -> usize {
         self.parking_lot_index.size()
     }
+
+    /// Total number of transactions currently held, ready or not.
+    pub(crate) fn size(&self) -> usize {
+        self.system_ttl_index.size()
+    }
+
+    /// Transactions held above a lowered capacity aren't evicted, new ones are rejected until
+    /// enough are removed.
+    pub(crate) fn set_capacity(&mut self, capacity: usize, capacity_per_user: usize) {
+        self.capacity = capacity;
+        self.capacity_per_user = capacity_per_user;
+    }
 }
@@ -18,6 +18,7 @@ pub const SYSTEM_TTL_INDEX_LABEL: &str = "system_ttl";
 pub const TIMELINE_INDEX_LABEL: &str = "timeline";
 pub const PARKING_LOT_INDEX_LABEL: &str = "parking_lot";
 pub const TRANSACTION_HASH_INDEX_LABEL: &str = "transaction_hash";
+pub const BUNDLE_INDEX_LABEL: &str = "bundle";
 
 // Core mempool commit stages labels
 pub const GET_BLOCK_STAGE_LABEL: &str = "get_block";
@@ -32,6 +33,10 @@ pub const GC_CLIENT_EXP_LABEL: &str = "client_expiration";
 pub const GC_ACTIVE_TXN_LABEL: &str = "active";
 pub const GC_PARKED_TXN_LABEL: &str = "parked";
 
+// Invalid parked txn eviction reason labels
+pub const ROTATED_AUTH_KEY_LABEL: &str = "rotated_auth_key";
+pub const INSUFFICIENT_BALANCE_LABEL: &str = "insufficient_balance";
+
 // Mempool service request type labels
 pub const GET_BLOCK_LABEL: &str = "get_block";
 pub const COMMIT_STATE_SYNC_LABEL: &str = "commit_accepted";
@@ -132,6 +137,16 @@ pub static CORE_MEMPOOL_GC_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counter of parked txns evicted on commit because they can't become valid anymore
+pub static CORE_MEMPOOL_INVALID_PARKED_TXN_EVICTIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "core_mempool_invalid_parked_txn_evictions",
+        "Number of parked txns evicted on commit because they can't become valid anymore",
+        &["reason"]
+    )
+    .unwrap()
+});
+
 /// Counter of pending network events to Mempool
 pub static PENDING_MEMPOOL_NETWORK_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
@@ -63,11 +63,16 @@ pub use shared_mempool::{
         ConsensusRequest, ConsensusResponse, MempoolClientRequest, MempoolClientSender,
         MempoolEventsReceiver, SubmissionStatus, TransactionSummary,
     },
+    MempoolConfigUpdater,
 };
 #[cfg(any(test, feature = "fuzzing"))]
 pub use tests::{fuzzing, mocks};
+pub use transaction_filter::TransactionFilter;
+pub use transaction_tracer::{TraceEvent, TraceStage, TransactionTrace, TransactionTracer};
 
 mod core_mempool;
 mod counters;
 mod logging;
 mod shared_mempool;
+mod transaction_filter;
+mod transaction_tracer;
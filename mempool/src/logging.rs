@@ -130,6 +130,7 @@ pub enum LogEntry {
     ReconfigUpdate,
     JsonRpc,
     GetTransaction,
+    GetMempoolSize,
     GetBlock,
     Consensus,
     StateSyncCommit,
@@ -140,6 +141,7 @@ pub enum LogEntry {
     AddTxn,
     RemoveTxn,
     MempoolFullEvictedTxn,
+    InvalidParkedEvictedTxn,
     GCRemoveTxns,
     CleanCommittedTxn,
     CleanRejectedTxn,
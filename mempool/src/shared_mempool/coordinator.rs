@@ -28,6 +28,7 @@ use futures::{
 };
 use mempool_notifications::{MempoolCommitNotification, MempoolNotificationListener};
 use std::{
+    collections::HashSet,
     sync::Arc,
     time::{Duration, Instant, SystemTime},
 };
@@ -76,7 +77,7 @@ pub(crate) async fn coordinator<V>(
                 tasks::process_consensus_request(&smp, msg);
             },
             msg = mempool_listener.select_next_some() => {
-                handle_commit_notification(&mut smp, msg, &mut mempool_listener);
+                handle_commit_notification(&mut smp, &bounded_executor, msg, &mut mempool_listener).await;
             },
             reconfig_notification = mempool_reconfig_events.select_next_some() => {
                 handle_mempool_reconfig_event(&mut smp, &bounded_executor, reconfig_notification.on_chain_configs).await;
@@ -126,6 +127,27 @@ async fn handle_client_request<V>(
                 ))
                 .await;
         }
+        MempoolClientRequest::SubmitTransactionBundle(txns, callback) => {
+            // This timer measures how long it took for the bounded executor to *schedule* the
+            // task.
+            let _timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_LABEL,
+                counters::SPAWN_LABEL,
+            );
+            // This timer measures how long it took for the task to go from scheduled to started.
+            let task_start_timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_LABEL,
+                counters::START_LABEL,
+            );
+            bounded_executor
+                .spawn(tasks::process_client_transaction_bundle_submission(
+                    smp.clone(),
+                    txns,
+                    callback,
+                    task_start_timer,
+                ))
+                .await;
+        }
         MempoolClientRequest::GetTransactionByHash(hash, callback) => {
             // This timer measures how long it took for the bounded executor to *schedule* the
             // task.
@@ -147,13 +169,26 @@ async fn handle_client_request<V>(
                 ))
                 .await;
         }
+        MempoolClientRequest::GetMempoolSize(callback) => {
+            let size = smp.mempool.lock().size();
+            if callback.send(size).is_err() {
+                error!(LogSchema::event_log(
+                    LogEntry::GetMempoolSize,
+                    LogEvent::CallbackFail
+                ));
+                counters::CLIENT_CALLBACK_FAIL.inc();
+            }
+        }
     }
 }
 
 /// Handle removing committed transactions from local mempool immediately.  This should be done
 /// immediately to ensure broadcasts of committed transactions stop as soon as possible.
-fn handle_commit_notification<V>(
+/// A task evicting the parked transactions of the senders which can't become valid anymore is
+/// then spawned.
+async fn handle_commit_notification<V>(
     smp: &mut SharedMempool<V>,
+    bounded_executor: &BoundedExecutor,
     msg: MempoolCommitNotification,
     mempool_listener: &mut MempoolNotificationListener,
 ) where
@@ -169,8 +204,15 @@ fn handle_commit_notification<V>(
         counters::COMMIT_STATE_SYNC_LABEL,
         msg.transactions.len(),
     );
+    {
+        let mut mempool = smp.mempool.lock();
+        for txn in &msg.transactions {
+            mempool.add_committed_transaction(txn.sender, txn.sequence_number, txn.hash);
+        }
+    }
     process_committed_transactions(
         &smp.mempool,
+        &smp.transaction_tracer,
         msg.transactions
             .iter()
             .map(|txn| TransactionSummary {
@@ -182,6 +224,7 @@ fn handle_commit_notification<V>(
         false,
     );
     smp.validator.write().notify_commit();
+    let senders: HashSet<_> = msg.transactions.iter().map(|txn| txn.sender).collect();
     let counter_result = if mempool_listener.ack_commit_notification(msg).is_err() {
         error!(LogSchema::event_log(
             LogEntry::StateSyncCommit,
@@ -193,6 +236,16 @@ fn handle_commit_notification<V>(
     };
     let latency = start_time.elapsed();
     counters::mempool_service_latency(counters::COMMIT_STATE_SYNC_LABEL, counter_result, latency);
+
+    if smp.config.eager_parking_lot_eviction {
+        bounded_executor
+            .spawn(tasks::evict_invalid_parked_transactions(
+                smp.mempool.clone(),
+                smp.db.clone(),
+                senders,
+            ))
+            .await;
+    }
 }
 
 /// Spawn a task to restart the transaction validator with the new reconfig data.
@@ -264,40 +317,16 @@ async fn handle_network_event<V>(
         }
         Event::Message(peer_id, msg) => {
             counters::shared_mempool_event_inc("message");
-            match msg {
+            let (request_id, transactions, bundles) = match msg {
                 MempoolSyncMsg::BroadcastTransactionsRequest {
                     request_id,
                     transactions,
-                } => {
-                    let smp_clone = smp.clone();
-                    let peer = PeerNetworkId::new(network_id, peer_id);
-                    let timeline_state = match smp.network_interface.is_upstream_peer(&peer, None) {
-                        true => TimelineState::NonQualified,
-                        false => TimelineState::NotReady,
-                    };
-                    // This timer measures how long it took for the bounded executor to
-                    // *schedule* the task.
-                    let _timer = counters::task_spawn_latency_timer(
-                        counters::PEER_BROADCAST_EVENT_LABEL,
-                        counters::SPAWN_LABEL,
-                    );
-                    // This timer measures how long it took for the task to go from scheduled
-                    // to started.
-                    let task_start_timer = counters::task_spawn_latency_timer(
-                        counters::PEER_BROADCAST_EVENT_LABEL,
-                        counters::START_LABEL,
-                    );
-                    bounded_executor
-                        .spawn(tasks::process_transaction_broadcast(
-                            smp_clone,
-                            transactions,
-                            request_id,
-                            timeline_state,
-                            peer,
-                            task_start_timer,
-                        ))
-                        .await;
-                }
+                } => (request_id, transactions, vec![]),
+                MempoolSyncMsg::BroadcastTransactionBundlesRequest {
+                    request_id,
+                    transactions,
+                    bundles,
+                } => (request_id, transactions, bundles),
                 MempoolSyncMsg::BroadcastTransactionsResponse {
                     request_id,
                     retry,
@@ -311,8 +340,38 @@ async fn handle_network_event<V>(
                         backoff,
                         ack_timestamp,
                     );
+                    return;
                 }
-            }
+            };
+            let smp_clone = smp.clone();
+            let peer = PeerNetworkId::new(network_id, peer_id);
+            let timeline_state = match smp.network_interface.is_upstream_peer(&peer, None) {
+                true => TimelineState::NonQualified,
+                false => TimelineState::NotReady,
+            };
+            // This timer measures how long it took for the bounded executor to
+            // *schedule* the task.
+            let _timer = counters::task_spawn_latency_timer(
+                counters::PEER_BROADCAST_EVENT_LABEL,
+                counters::SPAWN_LABEL,
+            );
+            // This timer measures how long it took for the task to go from scheduled
+            // to started.
+            let task_start_timer = counters::task_spawn_latency_timer(
+                counters::PEER_BROADCAST_EVENT_LABEL,
+                counters::START_LABEL,
+            );
+            bounded_executor
+                .spawn(tasks::process_transaction_broadcast(
+                    smp_clone,
+                    transactions,
+                    bundles,
+                    request_id,
+                    timeline_state,
+                    peer,
+                    task_start_timer,
+                ))
+                .await;
         }
         Event::RpcRequest(peer_id, _msg, _, _res_tx) => {
             counters::unexpected_msg_count_inc(&network_id);
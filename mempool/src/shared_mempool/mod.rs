@@ -4,8 +4,8 @@
 pub mod network;
 mod runtime;
 pub(crate) mod types;
-pub use runtime::bootstrap;
 #[cfg(any(test, feature = "fuzzing"))]
 pub(crate) use runtime::start_shared_mempool;
+pub use runtime::{bootstrap, MempoolConfigUpdater};
 mod coordinator;
 pub(crate) mod tasks;
@@ -12,6 +12,7 @@ use crate::{
             notify_subscribers, BatchId, PeerSyncState, SharedMempool, SharedMempoolNotification,
         },
     },
+    transaction_tracer::TraceStage,
 };
 use aptos_config::{
     config::{MempoolConfig, PeerRole, RoleType},
@@ -68,6 +69,15 @@ pub enum MempoolSyncMsg {
         /// A backpressure signal from the recipient when it is overwhelmed (e.g., mempool is full).
         backoff: bool,
     },
+    /// Broadcast request issued by the sender when the broadcast holds transaction bundles,
+    /// which the receiver adds to its mempool atomically. It's acked like the other broadcasts.
+    BroadcastTransactionBundlesRequest {
+        request_id: Vec<u8>,
+        transactions: Vec<SignedTransaction>,
+        /// Transactions of one account with consecutive sequence numbers, to be included in a
+        /// block together or not at all
+        bundles: Vec<Vec<SignedTransaction>>,
+    },
 }
 
 /// The interface from Network to Mempool layer.
@@ -224,22 +234,29 @@ impl MempoolNetworkInterface {
             let peer_states = self.sync_states.read_all();
             peer_states
                 .iter()
-                .map(|(peer, state)| (*peer, state.metadata.role))
+                .map(|(peer, state)| (*peer, state.metadata.role, self.ping_latency(peer)))
                 .collect()
         };
 
-        // Order peers by network and by type
+        // Order peers by network, by type and by latency
         // Origin doesn't matter at this point, only inserted ones into peer_states are upstream
         // Validators will always have the full set
         let mut prioritized_peers = self.prioritized_peers.lock();
         let peers: Vec<_> = peers
             .iter()
             .sorted_by(|peer_a, peer_b| compare_prioritized_peers(peer_a, peer_b))
-            .map(|(peer, _)| *peer)
+            .map(|(peer, _, _)| *peer)
             .collect();
         let _ = std::mem::replace(&mut *prioritized_peers, peers);
     }
 
+    /// The average ping latency of the peer, as measured by the peer monitoring service
+    fn ping_latency(&self, peer: &PeerNetworkId) -> Option<Duration> {
+        self.peer_metadata_storage
+            .read(*peer)
+            .and_then(|peer_info| peer_info.monitoring_metadata.average_ping_latency)
+    }
+
     pub fn is_upstream_peer(
         &self,
         peer: &PeerNetworkId,
@@ -455,10 +472,21 @@ impl MempoolNetworkInterface {
         peer: PeerNetworkId,
         batch_id: BatchId,
         transactions: Vec<SignedTransaction>,
+        bundles: Vec<Vec<SignedTransaction>>,
     ) -> Result<(), BroadcastError> {
-        let request = MempoolSyncMsg::BroadcastTransactionsRequest {
-            request_id: bcs::to_bytes(&batch_id).expect("failed BCS serialization of batch ID"),
-            transactions,
+        let request_id = bcs::to_bytes(&batch_id).expect("failed BCS serialization of batch ID");
+        // Batches without bundles are sent as before, so that they're understood by all peers
+        let request = if bundles.is_empty() {
+            MempoolSyncMsg::BroadcastTransactionsRequest {
+                request_id,
+                transactions,
+            }
+        } else {
+            MempoolSyncMsg::BroadcastTransactionBundlesRequest {
+                request_id,
+                transactions,
+                bundles,
+            }
         };
 
         if let Err(e) = self.sender.send_to(peer, request) {
@@ -506,10 +534,21 @@ impl MempoolNetworkInterface {
         let start_time = Instant::now();
         let (batch_id, transactions, metric_label) =
             self.determine_broadcast_batch(peer, scheduled_backoff, smp)?;
+        let (transactions, bundles) = smp.mempool.lock().group_bundles(transactions);
 
-        let num_txns = transactions.len();
+        let txn_pointers: Vec<_> = transactions
+            .iter()
+            .chain(bundles.iter().flatten())
+            .map(|t| (t.sender(), t.sequence_number()))
+            .collect();
+        let num_txns = txn_pointers.len();
         let send_time = SystemTime::now();
-        self.send_batch(peer, batch_id, transactions).await?;
+        self.send_batch(peer, batch_id, transactions, bundles)
+            .await?;
+        for (sender, sequence_number) in txn_pointers {
+            smp.transaction_tracer
+                .record(sender, sequence_number, TraceStage::Broadcast);
+        }
         let num_pending_broadcasts = self.update_broadcast_state(peer, batch_id, send_time)?;
         notify_subscribers(SharedMempoolNotification::Broadcast, &smp.subscribers);
 
@@ -558,8 +597,8 @@ impl NetworkInterface<MempoolSyncMsg, MempoolMultiNetworkSender> for MempoolNetw
 
 /// Provides ordering for peers to send transactions to
 fn compare_prioritized_peers(
-    peer_a: &(PeerNetworkId, PeerRole),
-    peer_b: &(PeerNetworkId, PeerRole),
+    peer_a: &(PeerNetworkId, PeerRole, Option<Duration>),
+    peer_b: &(PeerNetworkId, PeerRole, Option<Duration>),
 ) -> Ordering {
     let peer_network_id_a = peer_a.0;
     let peer_network_id_b = peer_b.0;
@@ -574,12 +613,16 @@ fn compare_prioritized_peers(
             let role_a = peer_a.1;
             let role_b = peer_b.1;
             match role_a.cmp(&role_b) {
-                // Then tiebreak by PeerId for stability
-                Ordering::Equal => {
-                    let peer_id_a = peer_network_id_a.peer_id();
-                    let peer_id_b = peer_network_id_b.peer_id();
-                    peer_id_a.cmp(&peer_id_b)
-                }
+                // Then sort by latency, with unmeasured peers last
+                Ordering::Equal => match compare_latencies(peer_a.2, peer_b.2) {
+                    // Then tiebreak by PeerId for stability
+                    Ordering::Equal => {
+                        let peer_id_a = peer_network_id_a.peer_id();
+                        let peer_id_b = peer_network_id_b.peer_id();
+                        peer_id_a.cmp(&peer_id_b)
+                    }
+                    ordering => ordering,
+                },
                 ordering => ordering,
             }
         }
@@ -587,6 +630,16 @@ fn compare_prioritized_peers(
     }
 }
 
+/// Orders lower latencies first, and unknown latencies last
+fn compare_latencies(latency_a: Option<Duration>, latency_b: Option<Duration>) -> Ordering {
+    match (latency_a, latency_b) {
+        (Some(latency_a), Some(latency_b)) => latency_a.cmp(&latency_b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -600,18 +653,32 @@ mod test {
         let val_1 = (
             PeerNetworkId::new(NetworkId::Vfn, peer_id_1),
             PeerRole::Validator,
+            None,
         );
         let val_2 = (
             PeerNetworkId::new(NetworkId::Vfn, peer_id_2),
             PeerRole::Validator,
+            None,
         );
         let vfn_1 = (
             PeerNetworkId::new(NetworkId::Public, peer_id_1),
             PeerRole::ValidatorFullNode,
+            None,
         );
         let preferred_1 = (
             PeerNetworkId::new(NetworkId::Public, peer_id_1),
             PeerRole::PreferredUpstream,
+            None,
+        );
+        let fast_val_2 = (
+            PeerNetworkId::new(NetworkId::Vfn, peer_id_2),
+            PeerRole::Validator,
+            Some(Duration::from_millis(10)),
+        );
+        let slow_val_1 = (
+            PeerNetworkId::new(NetworkId::Vfn, peer_id_1),
+            PeerRole::Validator,
+            Some(Duration::from_millis(100)),
         );
 
         // NetworkId ordering
@@ -628,6 +695,24 @@ mod test {
             compare_prioritized_peers(&preferred_1, &vfn_1)
         );
 
+        // Latency ordering, with unmeasured peers last
+        assert_eq!(
+            Ordering::Less,
+            compare_prioritized_peers(&fast_val_2, &slow_val_1)
+        );
+        assert_eq!(
+            Ordering::Greater,
+            compare_prioritized_peers(&slow_val_1, &fast_val_2)
+        );
+        assert_eq!(
+            Ordering::Less,
+            compare_prioritized_peers(&slow_val_1, &val_1)
+        );
+        assert_eq!(
+            Ordering::Greater,
+            compare_prioritized_peers(&val_1, &fast_val_2)
+        );
+
         // Tiebreaker on peer_id
         assert_eq!(Ordering::Greater, compare_prioritized_peers(&val_2, &val_1));
         assert_eq!(Ordering::Less, compare_prioritized_peers(&val_1, &val_2));
@@ -8,10 +8,16 @@ use crate::{
         coordinator::{coordinator, gc_coordinator, snapshot_job},
         types::{MempoolEventsReceiver, SharedMempool, SharedMempoolNotification},
     },
+    transaction_filter::TransactionFilter,
+    transaction_tracer::TransactionTracer,
     ConsensusRequest,
 };
-use aptos_config::{config::NodeConfig, network_id::NetworkId};
+use aptos_config::{
+    config::{MempoolConfig, NodeConfig},
+    network_id::NetworkId,
+};
 use aptos_infallible::{Mutex, RwLock};
+use aptos_metrics::runtime::instrumented_runtime;
 
 use event_notifications::ReconfigNotificationListener;
 use futures::channel::mpsc::{self, Receiver, UnboundedSender};
@@ -19,7 +25,7 @@ use mempool_notifications::MempoolNotificationListener;
 use network::application::storage::PeerMetadataStorage;
 use std::{collections::HashMap, sync::Arc};
 use storage_interface::DbReader;
-use tokio::runtime::{Builder, Handle, Runtime};
+use tokio::runtime::{Handle, Runtime};
 use vm_validator::vm_validator::{TransactionValidation, VMValidator};
 
 /// Bootstrap of SharedMempool.
@@ -40,6 +46,8 @@ pub(crate) fn start_shared_mempool<V>(
     mempool_reconfig_events: ReconfigNotificationListener,
     db: Arc<dyn DbReader>,
     validator: Arc<RwLock<V>>,
+    transaction_filter: Arc<TransactionFilter>,
+    transaction_tracer: Arc<TransactionTracer>,
     subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
     peer_metadata_storage: Arc<PeerMetadataStorage>,
 ) where
@@ -58,6 +66,8 @@ pub(crate) fn start_shared_mempool<V>(
         network_senders,
         db,
         validator,
+        transaction_filter,
+        transaction_tracer,
         subscribers,
         config.base.role,
         peer_metadata_storage,
@@ -95,18 +105,20 @@ pub fn bootstrap(
     mempool_listener: MempoolNotificationListener,
     mempool_reconfig_events: ReconfigNotificationListener,
     peer_metadata_storage: Arc<PeerMetadataStorage>,
-) -> Runtime {
-    let runtime = Builder::new_multi_thread()
-        .thread_name("shared-mem")
-        .enable_all()
-        .build()
-        .expect("[shared mempool] failed to create runtime");
+    transaction_filter: Arc<TransactionFilter>,
+    transaction_tracer: Arc<TransactionTracer>,
+) -> (Runtime, MempoolConfigUpdater) {
+    let runtime = instrumented_runtime(
+        "shared-mem",
+        config.runtimes.mempool_worker_threads,
+        config.runtimes.stall_threshold(),
+    );
     let mempool = Arc::new(Mutex::new(CoreMempool::new(config)));
     let vm_validator = Arc::new(RwLock::new(VMValidator::new(Arc::clone(&db))));
     start_shared_mempool(
         runtime.handle(),
         config,
-        mempool,
+        mempool.clone(),
         mempool_network_handles,
         client_events,
         consensus_requests,
@@ -114,8 +126,24 @@ pub fn bootstrap(
         mempool_reconfig_events,
         db,
         vm_validator,
+        transaction_filter,
+        transaction_tracer,
         vec![],
         peer_metadata_storage,
     );
-    runtime
+    (runtime, MempoolConfigUpdater { mempool })
+}
+
+/// Applies the changes of the mempool config which can be made while the node runs
+#[derive(Clone)]
+pub struct MempoolConfigUpdater {
+    mempool: Arc<Mutex<CoreMempool>>,
+}
+
+impl MempoolConfigUpdater {
+    pub fn update(&self, config: &MempoolConfig) {
+        self.mempool
+            .lock()
+            .set_capacity(config.capacity, config.capacity_per_user);
+    }
 }
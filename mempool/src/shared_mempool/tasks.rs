@@ -11,6 +11,7 @@ use crate::{
         notify_subscribers, ScheduledBroadcast, SharedMempool, SharedMempoolNotification,
         SubmissionStatusBundle, TransactionSummary,
     },
+    transaction_tracer::{TraceStage, TransactionTracer},
     ConsensusRequest, ConsensusResponse, SubmissionStatus,
 };
 use anyhow::Result;
@@ -20,20 +21,25 @@ use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::prelude::*;
 use aptos_metrics::HistogramTimer;
 use aptos_types::{
+    account_address::AccountAddress,
+    account_state::AccountState,
     mempool_status::{MempoolStatus, MempoolStatusCode},
     on_chain_config::OnChainConfigPayload,
     transaction::SignedTransaction,
     vm_status::DiscardedVMStatus,
 };
+use fail::fail_point;
 use futures::{channel::oneshot, stream::FuturesUnordered};
 use network::application::interface::NetworkInterface;
 use rayon::prelude::*;
 use std::{
     cmp,
     collections::HashSet,
+    convert::TryFrom,
     sync::Arc,
     time::{Duration, Instant},
 };
+use storage_interface::DbReader;
 use tokio::runtime::Handle;
 use vm_validator::vm_validator::{get_account_sequence_number, TransactionValidation};
 
@@ -105,8 +111,14 @@ pub(crate) async fn process_client_transaction_submission<V>(
 {
     timer.stop_and_record();
     let _timer = counters::process_txn_submit_latency_timer_client();
+    smp.transaction_tracer.start(
+        transaction.sender(),
+        transaction.sequence_number(),
+        TraceStage::Submitted,
+    );
     let statuses = process_incoming_transactions(&smp, vec![transaction], TimelineState::NotReady);
     log_txn_process_results(&statuses, None);
+    trace_txn_process_results(&smp.transaction_tracer, &statuses);
 
     if let Some(status) = statuses.get(0) {
         if callback.send(Ok(status.1.clone())).is_err() {
@@ -119,6 +131,45 @@ pub(crate) async fn process_client_transaction_submission<V>(
     }
 }
 
+/// Processes a bundle of transactions directly submitted by client.
+pub(crate) async fn process_client_transaction_bundle_submission<V>(
+    smp: SharedMempool<V>,
+    transactions: Vec<SignedTransaction>,
+    callback: oneshot::Sender<Result<SubmissionStatus>>,
+    timer: HistogramTimer,
+) where
+    V: TransactionValidation,
+{
+    timer.stop_and_record();
+    let _timer = counters::process_txn_submit_latency_timer_client();
+    let txn_pointers: Vec<_> = transactions
+        .iter()
+        .map(|t| (t.sender(), t.sequence_number()))
+        .collect();
+    for (sender, sequence_number) in &txn_pointers {
+        smp.transaction_tracer
+            .start(*sender, *sequence_number, TraceStage::Submitted);
+    }
+    let status = process_incoming_transaction_bundle(&smp, transactions, TimelineState::NotReady);
+    let stage = if status.0.code == MempoolStatusCode::Accepted {
+        TraceStage::Validated
+    } else {
+        TraceStage::Rejected
+    };
+    for (sender, sequence_number) in txn_pointers {
+        smp.transaction_tracer
+            .record(sender, sequence_number, stage);
+    }
+
+    if callback.send(Ok(status)).is_err() {
+        error!(LogSchema::event_log(
+            LogEntry::JsonRpc,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
 /// Processes get transaction by hash request by client.
 pub(crate) async fn process_client_get_transaction<V>(
     smp: SharedMempool<V>,
@@ -141,10 +192,11 @@ pub(crate) async fn process_client_get_transaction<V>(
     }
 }
 
-/// Processes transactions from other nodes.
+/// Processes transactions and transaction bundles from other nodes.
 pub(crate) async fn process_transaction_broadcast<V>(
     smp: SharedMempool<V>,
     transactions: Vec<SignedTransaction>,
+    bundles: Vec<Vec<SignedTransaction>>,
     request_id: Vec<u8>,
     timeline_state: TimelineState,
     peer: PeerNetworkId,
@@ -154,8 +206,20 @@ pub(crate) async fn process_transaction_broadcast<V>(
 {
     timer.stop_and_record();
     let _timer = counters::process_txn_submit_latency_timer(peer.network_id());
-    let results = process_incoming_transactions(&smp, transactions, timeline_state);
+    for transaction in transactions.iter().chain(bundles.iter().flatten()) {
+        smp.transaction_tracer.start(
+            transaction.sender(),
+            transaction.sequence_number(),
+            TraceStage::ReceivedFromPeer,
+        );
+    }
+    let mut results = process_incoming_transactions(&smp, transactions, timeline_state);
+    for bundle in bundles {
+        let status = process_incoming_transaction_bundle(&smp, bundle.clone(), timeline_state);
+        results.extend(bundle.into_iter().map(|txn| (txn, status.clone())));
+    }
     log_txn_process_results(&results, Some(peer));
+    trace_txn_process_results(&smp.transaction_tracer, &results);
 
     let ack_response = gen_ack_response(request_id, results, &peer);
     let network_sender = smp.network_interface.sender();
@@ -232,6 +296,41 @@ where
 {
     let mut statuses = vec![];
 
+    // Resubmissions of recently committed transactions are answered without going further
+    let transactions: Vec<_> = {
+        let mempool = smp.mempool.lock();
+        transactions
+            .into_iter()
+            .filter_map(|t| {
+                if !mempool.is_recently_committed(&t) {
+                    return Some(t);
+                }
+                statuses.push((
+                    t,
+                    (
+                        MempoolStatus::new(MempoolStatusCode::AlreadyCommitted),
+                        None,
+                    ),
+                ));
+                None
+            })
+            .collect()
+    };
+    // The transactions rejected by the filter of the node aren't validated
+    let transactions: Vec<_> = transactions
+        .into_iter()
+        .filter_map(|t| {
+            if smp.transaction_filter.allows(&t) {
+                return Some(t);
+            }
+            statuses.push((t, (filtered_status(), None)));
+            None
+        })
+        .collect();
+    if transactions.is_empty() {
+        return statuses;
+    }
+
     let start_storage_read = Instant::now();
     // Track latency: fetching seq number
     let seq_numbers = transactions
@@ -323,6 +422,97 @@ where
     statuses
 }
 
+/// Submits a bundle of transactions to the local mempool. The transactions are validated like
+/// the other transactions, but the bundle is rejected if any of them is invalid.
+fn process_incoming_transaction_bundle<V>(
+    smp: &SharedMempool<V>,
+    transactions: Vec<SignedTransaction>,
+    timeline_state: TimelineState,
+) -> SubmissionStatus
+where
+    V: TransactionValidation,
+{
+    let sender = match transactions.first() {
+        Some(txn) => txn.sender(),
+        None => {
+            return (
+                MempoolStatus::new(MempoolStatusCode::InvalidBundle)
+                    .with_message("bundle is empty".to_string()),
+                None,
+            )
+        }
+    };
+    if transactions
+        .iter()
+        .any(|t| !smp.transaction_filter.allows(t))
+    {
+        return (filtered_status(), None);
+    }
+    let crsn_or_seqno = match get_account_sequence_number(smp.db.as_ref(), sender) {
+        Ok(crsn_or_seqno) => crsn_or_seqno,
+        Err(e) => {
+            error!(LogSchema::new(LogEntry::DBError).error(&e));
+            counters::DB_ERROR.inc();
+            return (
+                MempoolStatus::new(MempoolStatusCode::VmError),
+                Some(DiscardedVMStatus::RESOURCE_DOES_NOT_EXIST),
+            );
+        }
+    };
+
+    let valid_signatures = verify_ed25519_signatures(&transactions.iter().collect::<Vec<_>>());
+    if valid_signatures.contains(&false) {
+        return (
+            MempoolStatus::new(MempoolStatusCode::VmError),
+            Some(DiscardedVMStatus::INVALID_SIGNATURE),
+        );
+    }
+
+    let mut bundle = vec![];
+    for transaction in transactions {
+        let validation_result = match smp
+            .validator
+            .read()
+            .validate_transaction(transaction.clone())
+        {
+            Ok(validation_result) => validation_result,
+            Err(_) => return (MempoolStatus::new(MempoolStatusCode::UnknownStatus), None),
+        };
+        if let Some(validation_status) = validation_result.status() {
+            return (
+                MempoolStatus::new(MempoolStatusCode::VmError),
+                Some(validation_status),
+            );
+        }
+        let gas_amount = transaction.max_gas_amount();
+        bundle.push((transaction, gas_amount, validation_result.score()));
+    }
+
+    let status = smp
+        .mempool
+        .lock()
+        .add_txn_bundle(bundle, crsn_or_seqno, timeline_state);
+    notify_subscribers(SharedMempoolNotification::NewTransactions, &smp.subscribers);
+    (status, None)
+}
+
+fn filtered_status() -> MempoolStatus {
+    MempoolStatus::new(MempoolStatusCode::Filtered)
+        .with_message("the transaction calls a function this node doesn't accept".to_string())
+}
+
+/// Records whether the transactions were added to mempool in their traces
+fn trace_txn_process_results(tracer: &TransactionTracer, results: &[SubmissionStatusBundle]) {
+    for (txn, (mempool_status, _)) in results {
+        let stage = if mempool_status.code == MempoolStatusCode::Accepted {
+            TraceStage::Validated
+        } else {
+            TraceStage::Rejected
+        };
+        tracer.record(txn.sender(), txn.sequence_number(), stage);
+    }
+}
+
 fn log_txn_process_results(results: &[SubmissionStatusBundle], sender: Option<PeerNetworkId>) {
     let network = match sender {
         Some(peer) => peer.network_id().to_string(),
@@ -387,6 +577,13 @@ pub(crate) fn process_consensus_request<V: TransactionValidation>(
             }
             counters::mempool_service_transactions(counters::GET_BLOCK_LABEL, txns.len());
             txns.len();
+            for txn in &txns {
+                smp.transaction_tracer.record(
+                    txn.sender(),
+                    txn.sequence_number(),
+                    TraceStage::PulledIntoBlock,
+                );
+            }
             let pulled_block = txns.drain(..).map(SignedTransaction::into).collect();
 
             (
@@ -400,7 +597,13 @@ pub(crate) fn process_consensus_request<V: TransactionValidation>(
                 counters::COMMIT_CONSENSUS_LABEL,
                 transactions.len(),
             );
-            process_committed_transactions(&smp.mempool, transactions, 0, true);
+            process_committed_transactions(
+                &smp.mempool,
+                &smp.transaction_tracer,
+                transactions,
+                0,
+                true,
+            );
             (
                 ConsensusResponse::CommitResponse(),
                 callback,
@@ -425,10 +628,20 @@ pub(crate) fn process_consensus_request<V: TransactionValidation>(
 /// Remove transactions that are committed (or rejected) so that we can stop broadcasting them.
 pub(crate) fn process_committed_transactions(
     mempool: &Mutex<CoreMempool>,
+    transaction_tracer: &TransactionTracer,
     transactions: Vec<TransactionSummary>,
     block_timestamp_usecs: u64,
     is_rejected: bool,
 ) {
+    fail_point!("mempool::process_committed_transactions", |_| {});
+    let stage = if is_rejected {
+        TraceStage::Rejected
+    } else {
+        TraceStage::Committed
+    };
+    for transaction in &transactions {
+        transaction_tracer.record(transaction.sender, transaction.sequence_number, stage);
+    }
     let mut pool = mempool.lock();
 
     for transaction in transactions {
@@ -444,6 +657,96 @@ pub(crate) fn process_committed_transactions(
     }
 }
 
+/// Evicts the parked transactions of the senders which can't become valid anymore given the
+/// latest state of their accounts, e.g. after the senders committed transactions rotating their
+/// authentication keys or spending their balances. They'd otherwise stay in mempool until they
+/// become ready and fail validation.
+pub(crate) async fn evict_invalid_parked_transactions(
+    mempool: Arc<Mutex<CoreMempool>>,
+    db: Arc<dyn DbReader>,
+    senders: HashSet<AccountAddress>,
+) {
+    for sender in senders {
+        let (parked_txns, lowest_sequence_number) = {
+            let pool = mempool.lock();
+            (
+                pool.get_parked_transactions(&sender),
+                pool.get_lowest_sequence_number(&sender),
+            )
+        };
+        if parked_txns.is_empty() {
+            continue;
+        }
+        let account_state = match db
+            .get_latest_account_state(sender)
+            .and_then(|blob| blob.map(|blob| AccountState::try_from(&blob)).transpose())
+        {
+            Ok(Some(account_state)) => account_state,
+            Ok(None) => continue,
+            Err(e) => {
+                error!(LogSchema::new(LogEntry::DBError).account(sender).error(&e));
+                counters::DB_ERROR.inc();
+                continue;
+            }
+        };
+        let invalid_txns: Vec<_> = parked_txns
+            .iter()
+            .filter_map(|txn| {
+                // A transaction of the sender with a lower sequence number may rotate the
+                // authentication key before this one gets executed.
+                let check_authentication_key =
+                    lowest_sequence_number == Some(txn.sequence_number());
+                invalid_parked_txn_reason(txn, &account_state, check_authentication_key)
+                    .map(|reason| (txn.sequence_number(), reason))
+            })
+            .collect();
+        if invalid_txns.is_empty() {
+            continue;
+        }
+        let mut pool = mempool.lock();
+        for (sequence_number, reason) in invalid_txns {
+            if pool.evict_parked_transaction(&sender, sequence_number) {
+                counters::CORE_MEMPOOL_INVALID_PARKED_TXN_EVICTIONS
+                    .with_label_values(&[reason])
+                    .inc();
+            }
+        }
+    }
+}
+
+/// The reason why the parked transaction can't become valid anymore given the latest state of
+/// the account of its sender, if any: it's signed with a key other than the authentication key of
+/// the account, or the balance of the account can't cover its max gas. The authentication key is
+/// only checked if `check_authentication_key` is set.
+pub(crate) fn invalid_parked_txn_reason(
+    txn: &SignedTransaction,
+    account_state: &AccountState,
+    check_authentication_key: bool,
+) -> Option<&'static str> {
+    if check_authentication_key {
+        if let Ok(Some(account)) = account_state.get_account_resource() {
+            let authentication_key = txn.authenticator().sender().authentication_key();
+            if authentication_key.as_ref() != account.authentication_key() {
+                return Some(counters::ROTATED_AUTH_KEY_LABEL);
+            }
+        }
+    }
+    let max_gas_cost = txn.max_gas_amount().saturating_mul(txn.gas_unit_price());
+    let balance = account_state
+        .get_balance_resources()
+        .ok()
+        .and_then(|balances| {
+            balances
+                .iter()
+                .find(|(currency_code, _)| currency_code.as_str() == txn.gas_currency_code())
+                .map(|(_, balance)| balance.coin())
+        });
+    match balance {
+        Some(balance) if balance < max_gas_cost => Some(counters::INSUFFICIENT_BALANCE_LABEL),
+        _ => None,
+    }
+}
+
 /// Processes on-chain reconfiguration notifications.  Restarts validator with the new info.
 pub(crate) async fn process_config_update<V>(
     config_update: OnChainConfigPayload,
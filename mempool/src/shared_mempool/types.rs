@@ -4,7 +4,8 @@
 //! Objects used by/related to shared mempool
 use crate::{
     core_mempool::CoreMempool, network::MempoolNetworkInterface,
-    shared_mempool::network::MempoolNetworkSender,
+    shared_mempool::network::MempoolNetworkSender, transaction_filter::TransactionFilter,
+    transaction_tracer::TransactionTracer,
 };
 use anyhow::Result;
 use aptos_config::{
@@ -47,6 +48,8 @@ where
     pub(crate) network_interface: MempoolNetworkInterface,
     pub db: Arc<dyn DbReader>,
     pub validator: Arc<RwLock<V>>,
+    pub transaction_filter: Arc<TransactionFilter>,
+    pub transaction_tracer: Arc<TransactionTracer>,
     pub subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
 }
 
@@ -57,6 +60,8 @@ impl<V: TransactionValidation + 'static> SharedMempool<V> {
         network_senders: HashMap<NetworkId, MempoolNetworkSender>,
         db: Arc<dyn DbReader>,
         validator: Arc<RwLock<V>>,
+        transaction_filter: Arc<TransactionFilter>,
+        transaction_tracer: Arc<TransactionTracer>,
         subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
         role: RoleType,
         peer_metadata_storage: Arc<PeerMetadataStorage>,
@@ -73,6 +78,8 @@ impl<V: TransactionValidation + 'static> SharedMempool<V> {
             network_interface,
             db,
             validator,
+            transaction_filter,
+            transaction_tracer,
             subscribers,
         }
     }
@@ -215,7 +222,14 @@ pub type SubmissionStatusBundle = (SignedTransaction, SubmissionStatus);
 
 pub enum MempoolClientRequest {
     SubmitTransaction(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>),
+    /// Submits transactions of an account with consecutive sequence numbers, to be included in
+    /// a block together or not at all. The status is the one of the whole bundle.
+    SubmitTransactionBundle(
+        Vec<SignedTransaction>,
+        oneshot::Sender<Result<SubmissionStatus>>,
+    ),
     GetTransactionByHash(HashValue, oneshot::Sender<Option<SignedTransaction>>),
+    GetMempoolSize(oneshot::Sender<usize>),
 }
 
 pub type MempoolClientSender = mpsc::Sender<MempoolClientRequest>;
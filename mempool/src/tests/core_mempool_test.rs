@@ -10,7 +10,10 @@ use crate::{
 };
 use aptos_config::config::NodeConfig;
 use aptos_crypto::HashValue;
-use aptos_types::{account_config::AccountSequenceInfo, transaction::SignedTransaction};
+use aptos_types::{
+    account_config::AccountSequenceInfo, mempool_status::MempoolStatusCode,
+    transaction::SignedTransaction,
+};
 use std::{
     collections::HashSet,
     time::{Duration, SystemTime},
@@ -545,6 +548,38 @@ fn test_parking_lot_evict_only_for_ready_txn_insertion() {
     }
 }
 
+#[test]
+fn test_evict_parked_transaction() {
+    let mut pool = setup_mempool().0;
+    for seq in &[0, 2, 3] {
+        add_txn(&mut pool, TestTransaction::new(1, *seq, 1)).unwrap();
+    }
+    let bundle: Vec<_> = (5..7)
+        .map(|seq| TestTransaction::new(1, seq, 1).make_signed_transaction())
+        .collect();
+    assert_eq!(
+        add_txn_bundle(&mut pool, &bundle),
+        MempoolStatusCode::Accepted
+    );
+    let address = TestTransaction::get_address(1);
+    let parked_seq_nums = |pool: &CoreMempool| {
+        pool.get_parked_transactions(&address)
+            .iter()
+            .map(SignedTransaction::sequence_number)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(parked_seq_nums(&pool), vec![2, 3, 5, 6]);
+
+    // Ready transactions aren't evicted, bundles are evicted as a whole
+    assert!(!pool.evict_parked_transaction(&address, 0));
+    assert!(pool.evict_parked_transaction(&address, 3));
+    assert!(pool.evict_parked_transaction(&address, 5));
+    assert!(!pool.evict_parked_transaction(&address, 3));
+    assert_eq!(parked_seq_nums(&pool), vec![2]);
+    assert_eq!(pool.size(), 2);
+    assert_eq!(pool.get_parking_lot_size(), 1);
+}
+
 #[test]
 fn test_gc_ready_transaction() {
     let mut pool = setup_mempool().0;
@@ -677,3 +712,128 @@ fn test_get_transaction_by_hash_after_the_txn_is_updated() {
     let txn_by_new_hash = pool.get_by_hash(new_txn_hash);
     assert_eq!(txn_by_new_hash, Some(new_txn));
 }
+
+#[test]
+fn test_recently_committed_transaction() {
+    let mut pool = setup_mempool().0;
+    let txn = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    assert!(!pool.is_recently_committed(&txn));
+
+    pool.add_committed_transaction(
+        txn.sender(),
+        txn.sequence_number(),
+        txn.clone().committed_hash(),
+    );
+    pool.remove_transaction(&txn.sender(), txn.sequence_number(), false);
+    assert!(pool.is_recently_committed(&txn));
+
+    // a different transaction with the same sequence number wasn't committed
+    let other_txn = TestTransaction::new(0, 0, 100).make_signed_transaction();
+    assert!(!pool.is_recently_committed(&other_txn));
+}
+
+fn add_txn_bundle(pool: &mut CoreMempool, txns: &[SignedTransaction]) -> MempoolStatusCode {
+    pool.add_txn_bundle(
+        txns.iter()
+            .map(|txn| (txn.clone(), 0, txn.gas_unit_price()))
+            .collect(),
+        AccountSequenceInfo::Sequential(0),
+        TimelineState::NotReady,
+    )
+    .code
+}
+
+#[test]
+fn test_transaction_bundle_in_block() {
+    let (mut pool, mut consensus) = setup_mempool();
+    let bundle: Vec<_> = (0..3)
+        .map(|seq| TestTransaction::new(1, seq, 1).make_signed_transaction())
+        .collect();
+    assert_eq!(
+        add_txn_bundle(&mut pool, &bundle),
+        MempoolStatusCode::Accepted
+    );
+    let transactions = add_txns_to_mempool(&mut pool, vec![TestTransaction::new(0, 0, 5)]);
+
+    // The bundle doesn't fit in the rest of the block
+    assert_eq!(consensus.get_block(&mut pool, 2), transactions);
+    assert!(consensus.get_block(&mut pool, 2).is_empty());
+    assert_eq!(consensus.get_block(&mut pool, 3), bundle);
+
+    // Bundles are broadcast as a whole, along with their first transaction
+    let (timeline, _) = pool.read_timeline(0, 10);
+    assert_eq!(
+        pool.group_bundles(timeline),
+        (transactions.clone(), vec![bundle])
+    );
+    let (timeline, _) = pool.read_timeline(1, 10);
+    assert_eq!(pool.group_bundles(timeline), (transactions, vec![]));
+}
+
+#[test]
+fn test_invalid_transaction_bundle() {
+    let mut pool = setup_mempool().0;
+    let gap = vec![
+        TestTransaction::new(1, 0, 1).make_signed_transaction(),
+        TestTransaction::new(1, 2, 1).make_signed_transaction(),
+    ];
+    assert_eq!(
+        add_txn_bundle(&mut pool, &gap),
+        MempoolStatusCode::InvalidBundle
+    );
+    let senders = vec![
+        TestTransaction::new(1, 0, 1).make_signed_transaction(),
+        TestTransaction::new(2, 1, 1).make_signed_transaction(),
+    ];
+    assert_eq!(
+        add_txn_bundle(&mut pool, &senders),
+        MempoolStatusCode::InvalidBundle
+    );
+    assert_eq!(
+        add_txn_bundle(&mut pool, &[]),
+        MempoolStatusCode::InvalidBundle
+    );
+    assert_eq!(pool.size(), 0);
+
+    let bundle: Vec<_> = (0..2)
+        .map(|seq| TestTransaction::new(1, seq, 1).make_signed_transaction())
+        .collect();
+    assert_eq!(
+        add_txn_bundle(&mut pool, &bundle),
+        MempoolStatusCode::Accepted
+    );
+    // The same bundle can be resubmitted, but its transactions can't be updated
+    assert_eq!(
+        add_txn_bundle(&mut pool, &bundle),
+        MempoolStatusCode::Accepted
+    );
+    assert!(add_txn(&mut pool, TestTransaction::new(1, 1, 5)).is_err());
+    let overlapping: Vec<_> = (1..3)
+        .map(|seq| TestTransaction::new(1, seq, 1).make_signed_transaction())
+        .collect();
+    assert_eq!(
+        add_txn_bundle(&mut pool, &overlapping),
+        MempoolStatusCode::InvalidUpdate
+    );
+    assert_eq!(pool.size(), 2);
+}
+
+#[test]
+fn test_gc_transaction_bundle() {
+    let mut pool = setup_mempool().0;
+    let bundle = vec![
+        TestTransaction::new(1, 0, 1).make_signed_transaction(),
+        TestTransaction::new(1, 1, 1).make_signed_transaction_with_expiration_time(0),
+        TestTransaction::new(1, 2, 1).make_signed_transaction(),
+    ];
+    assert_eq!(
+        add_txn_bundle(&mut pool, &bundle),
+        MempoolStatusCode::Accepted
+    );
+    add_txn(&mut pool, TestTransaction::new(1, 3, 1)).unwrap();
+
+    // The whole bundle expires with its expired transaction
+    pool.gc_by_expiration_time(Duration::from_secs(1));
+    assert_eq!(pool.size(), 1);
+    assert!(pool.get_block(10, HashSet::new()).is_empty());
+}
@@ -4,6 +4,7 @@
 use crate::{
     core_mempool::{CoreMempool, TimelineState},
     shared_mempool::{tasks, types::SharedMempool},
+    TransactionFilter, TransactionTracer,
 };
 use aptos_config::{config::NodeConfig, network_id::NetworkId};
 use aptos_infallible::{Mutex, RwLock};
@@ -42,6 +43,8 @@ pub fn test_mempool_process_incoming_transactions_impl(
         HashMap::new(),
         Arc::new(mock_db),
         vm_validator,
+        Arc::new(TransactionFilter::default()),
+        Arc::new(TransactionTracer::default()),
         vec![],
         config.base.role,
         PeerMetadataStorage::new(&[NetworkId::Validator]),
@@ -5,7 +5,7 @@ use crate::{
     core_mempool::{CoreMempool, TimelineState},
     network::{MempoolNetworkEvents, MempoolNetworkSender},
     shared_mempool::start_shared_mempool,
-    ConsensusRequest, MempoolClientSender,
+    ConsensusRequest, MempoolClientSender, TransactionFilter, TransactionTracer,
 };
 use anyhow::{format_err, Result};
 use aptos_config::{
@@ -133,6 +133,8 @@ impl MockSharedMempool {
             reconfig_event_subscriber,
             db.reader.clone(),
             Arc::new(RwLock::new(validator)),
+            Arc::new(TransactionFilter::default()),
+            Arc::new(TransactionTracer::default()),
             vec![],
             peer_metadata_storage,
         );
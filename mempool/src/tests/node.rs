@@ -8,6 +8,7 @@ use crate::{
         network::MempoolNetworkSender, start_shared_mempool, types::SharedMempoolNotification,
     },
     tests::common::TestTransaction,
+    TransactionFilter, TransactionTracer,
 };
 use aptos_config::{
     config::{Identity, NodeConfig, PeerRole, RoleType},
@@ -595,6 +596,8 @@ fn start_node_mempool(
         reconfig_event_subscriber,
         Arc::new(MockDbReaderWriter),
         Arc::new(RwLock::new(MockVMValidator)),
+        Arc::new(TransactionFilter::default()),
+        Arc::new(TransactionTracer::default()),
         vec![sender],
         peer_metadata_storage,
     );
@@ -2,14 +2,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    counters,
     mocks::MockSharedMempool,
-    shared_mempool::types::TransactionSummary,
+    shared_mempool::{tasks::invalid_parked_txn_reason, types::TransactionSummary},
     tests::common::{batch_add_signed_txn, TestTransaction},
     ConsensusRequest,
 };
-use aptos_types::transaction::Transaction;
+use aptos_types::{
+    account_config::{AccountResource, BalanceResource},
+    account_state::AccountState,
+    transaction::{SignedTransaction, Transaction},
+};
 use futures::{channel::oneshot, executor::block_on, sink::SinkExt};
 use mempool_notifications::MempoolNotificationSender;
+use move_core_types::move_resource::MoveResource;
 use tokio::runtime::Builder;
 
 #[test]
@@ -93,3 +99,55 @@ fn test_mempool_notify_committed_txns() {
     assert_eq!(timeline.len(), 1);
     assert_eq!(timeline.get(0).unwrap(), &kept_txn);
 }
+
+fn account_state(
+    txn: &SignedTransaction,
+    authentication_key: Vec<u8>,
+    balance: u64,
+) -> AccountState {
+    let mut account_state = AccountState::default();
+    account_state.insert(
+        AccountResource::resource_path(),
+        bcs::to_bytes(&AccountResource::new(
+            txn.sequence_number(),
+            authentication_key,
+            txn.sender(),
+        ))
+        .unwrap(),
+    );
+    account_state.insert(
+        BalanceResource::resource_path(),
+        bcs::to_bytes(&BalanceResource::new(balance)).unwrap(),
+    );
+    account_state
+}
+
+#[test]
+fn test_invalid_parked_txn_reason() {
+    // Max gas cost of 100
+    let txn = TestTransaction::new(1, 2, 1).make_signed_transaction();
+    let authentication_key = txn.authenticator().sender().authentication_key().to_vec();
+
+    // Same key and enough balance
+    let valid_state = account_state(&txn, authentication_key.clone(), 100);
+    assert_eq!(invalid_parked_txn_reason(&txn, &valid_state, true), None);
+
+    // Rotated key, only checked when no transaction with a lower sequence number is pending
+    let rotated_state = account_state(&txn, vec![0; 32], 100);
+    assert_eq!(
+        invalid_parked_txn_reason(&txn, &rotated_state, true),
+        Some(counters::ROTATED_AUTH_KEY_LABEL)
+    );
+    assert_eq!(invalid_parked_txn_reason(&txn, &rotated_state, false), None);
+
+    // Balance not covering the max gas
+    let insufficient_state = account_state(&txn, authentication_key, 99);
+    assert_eq!(
+        invalid_parked_txn_reason(&txn, &insufficient_state, true),
+        Some(counters::INSUFFICIENT_BALANCE_LABEL)
+    );
+    assert_eq!(
+        invalid_parked_txn_reason(&txn, &insufficient_state, false),
+        Some(counters::INSUFFICIENT_BALANCE_LABEL)
+    );
+}
@@ -6,7 +6,8 @@ use crate::{
     network::{MempoolNetworkEvents, MempoolNetworkSender, MempoolSyncMsg},
     shared_mempool::start_shared_mempool,
     tests::common::TestTransaction,
-    ConsensusRequest, MempoolClientRequest, MempoolClientSender,
+    ConsensusRequest, MempoolClientRequest, MempoolClientSender, TransactionFilter,
+    TransactionTracer,
 };
 use aptos_config::{
     config::NodeConfig,
@@ -376,6 +377,9 @@ impl MempoolNode {
             MempoolSyncMsg::BroadcastTransactionsResponse { .. } => {
                 panic!("We aren't supposed to be getting as response here");
             }
+            MempoolSyncMsg::BroadcastTransactionBundlesRequest { .. } => {
+                panic!("We aren't supposed to be getting bundles here");
+            }
         };
         let response = MempoolSyncMsg::BroadcastTransactionsResponse {
             request_id,
@@ -499,6 +503,8 @@ fn setup_mempool(
         reconfig_event_subscriber,
         db_ro,
         vm_validator,
+        Arc::new(TransactionFilter::default()),
+        Arc::new(TransactionTracer::default()),
         vec![sender],
         peer_metadata_storage,
     );
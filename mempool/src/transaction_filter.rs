@@ -0,0 +1,235 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Node-local policy on the script functions called by the transactions submitted to mempool,
+//! e.g. to stop accepting the calls of a function during an incident. The transactions rejected
+//! by the filter are neither added to the mempool nor broadcast, but the transactions of the
+//! blocks proposed by other validators are executed as usual, so the filter doesn't change the
+//! validity of blocks.
+
+use anyhow::{bail, Result};
+use aptos_config::config::TransactionFilterConfig;
+use aptos_infallible::RwLock;
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{ScriptFunction, SignedTransaction, TransactionPayload},
+};
+
+/// A script function, or all the functions of a module when `function` is None.
+#[derive(Clone, Debug, PartialEq)]
+struct FunctionPattern {
+    address: AccountAddress,
+    module: String,
+    function: Option<String>,
+}
+
+impl FunctionPattern {
+    fn parse(pattern: &str) -> Result<Self> {
+        let parts: Vec<_> = pattern.split("::").collect();
+        let (address, module, function) = match parts.as_slice() {
+            [address, module] => (address, module, None),
+            [address, module, function] => (address, module, Some(function)),
+            _ => bail!(
+                "Invalid function '{}', expected <address>::<module>[::<function>]",
+                pattern
+            ),
+        };
+        if module.is_empty() || function.map_or(false, |f| f.is_empty()) {
+            bail!("Invalid function '{}', empty name", pattern);
+        }
+        let address = AccountAddress::from_hex_literal(address)
+            .map_err(|_| anyhow::anyhow!("Invalid address in function '{}'", pattern))?;
+
+        Ok(Self {
+            address,
+            module: module.to_string(),
+            function: function.map(|f| f.to_string()),
+        })
+    }
+
+    fn matches(&self, script_fn: &ScriptFunction) -> bool {
+        let module = script_fn.module();
+        module.address() == &self.address
+            && module.name().as_str() == self.module
+            && self
+                .function
+                .as_ref()
+                .map_or(true, |f| script_fn.function().as_str() == f)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Policy {
+    config: TransactionFilterConfig,
+    allow: Vec<FunctionPattern>,
+    deny: Vec<FunctionPattern>,
+}
+
+impl Policy {
+    fn new(config: TransactionFilterConfig) -> Result<Self> {
+        let parse = |patterns: &[String]| -> Result<Vec<_>> {
+            patterns.iter().map(|p| FunctionPattern::parse(p)).collect()
+        };
+        Ok(Self {
+            allow: parse(&config.allow)?,
+            deny: parse(&config.deny)?,
+            config,
+        })
+    }
+}
+
+/// The filter of the transactions entering mempool, which can be changed while the node runs,
+/// e.g. by the admin service.
+#[derive(Debug, Default)]
+pub struct TransactionFilter {
+    policy: RwLock<Policy>,
+}
+
+impl TransactionFilter {
+    pub fn new(config: &TransactionFilterConfig) -> Result<Self> {
+        Ok(Self {
+            policy: RwLock::new(Policy::new(config.clone())?),
+        })
+    }
+
+    pub fn config(&self) -> TransactionFilterConfig {
+        self.policy.read().config.clone()
+    }
+
+    /// Replaces the policy, nothing changes if any of the functions is invalid
+    pub fn update(&self, config: TransactionFilterConfig) -> Result<()> {
+        *self.policy.write() = Policy::new(config)?;
+        Ok(())
+    }
+
+    /// Whether the transaction is accepted: it calls no denied function and, if there is an
+    /// allowlist, it calls one of the allowed functions. Only script functions can be checked,
+    /// so the other payloads (scripts can call any function, and module bundles publish code)
+    /// are rejected as soon as there is an allowlist or a denylist.
+    pub fn allows(&self, txn: &SignedTransaction) -> bool {
+        let policy = self.policy.read();
+        let script_fn = match txn.payload() {
+            TransactionPayload::ScriptFunction(script_fn) => script_fn,
+            _ => return policy.allow.is_empty() && policy.deny.is_empty(),
+        };
+        if policy.deny.iter().any(|p| p.matches(script_fn)) {
+            return false;
+        }
+        policy.allow.is_empty() || policy.allow.iter().any(|p| p.matches(script_fn))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
+    use aptos_types::{
+        account_config::XUS_NAME,
+        chain_id::ChainId,
+        transaction::{ModuleBundle, RawTransaction, Script},
+    };
+    use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+
+    fn transaction(payload: TransactionPayload) -> SignedTransaction {
+        let private_key = Ed25519PrivateKey::generate_for_testing();
+        RawTransaction::new(
+            AccountAddress::random(),
+            0,
+            payload,
+            0,
+            0,
+            XUS_NAME.to_owned(),
+            0,
+            ChainId::test(),
+        )
+        .sign(&private_key, private_key.public_key())
+        .unwrap()
+        .into_inner()
+    }
+
+    fn script_function_txn(address: &str, module: &str, function: &str) -> SignedTransaction {
+        transaction(TransactionPayload::ScriptFunction(ScriptFunction::new(
+            ModuleId::new(
+                AccountAddress::from_hex_literal(address).unwrap(),
+                Identifier::new(module).unwrap(),
+            ),
+            Identifier::new(function).unwrap(),
+            vec![],
+            vec![],
+        )))
+    }
+
+    fn script_txn() -> SignedTransaction {
+        transaction(TransactionPayload::Script(Script::new(
+            vec![],
+            vec![],
+            vec![],
+        )))
+    }
+
+    fn module_bundle_txn() -> SignedTransaction {
+        transaction(TransactionPayload::ModuleBundle(ModuleBundle::new(vec![])))
+    }
+
+    fn config(allow: &[&str], deny: &[&str]) -> TransactionFilterConfig {
+        TransactionFilterConfig {
+            allow: allow.iter().map(|f| f.to_string()).collect(),
+            deny: deny.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_deny() {
+        let filter =
+            TransactionFilter::new(&config(&[], &["0x1::PaymentScripts::p2p", "0x2::M"])).unwrap();
+        assert!(!filter.allows(&script_function_txn("0x1", "PaymentScripts", "p2p")));
+        assert!(filter.allows(&script_function_txn("0x1", "PaymentScripts", "other")));
+        assert!(!filter.allows(&script_function_txn("0x2", "M", "f")));
+        assert!(filter.allows(&script_function_txn("0x3", "M", "f")));
+        assert!(!filter.allows(&script_txn()));
+        assert!(!filter.allows(&module_bundle_txn()));
+    }
+
+    #[test]
+    fn test_allow() {
+        let filter = TransactionFilter::new(&config(
+            &["0x1::PaymentScripts"],
+            &["0x1::PaymentScripts::p2p"],
+        ))
+        .unwrap();
+        assert!(filter.allows(&script_function_txn("0x1", "PaymentScripts", "other")));
+        assert!(!filter.allows(&script_function_txn("0x1", "PaymentScripts", "p2p")));
+        assert!(!filter.allows(&script_function_txn("0x1", "AccountCreationScripts", "f")));
+        assert!(!filter.allows(&script_txn()));
+        assert!(!filter.allows(&module_bundle_txn()));
+    }
+
+    #[test]
+    fn test_no_policy() {
+        let filter = TransactionFilter::default();
+        assert!(filter.allows(&script_function_txn("0x1", "PaymentScripts", "p2p")));
+        assert!(filter.allows(&script_txn()));
+        assert!(filter.allows(&module_bundle_txn()));
+    }
+
+    #[test]
+    fn test_update() {
+        let filter = TransactionFilter::default();
+        let txn = script_function_txn("0x1", "PaymentScripts", "p2p");
+        assert!(filter.allows(&txn));
+
+        filter
+            .update(config(&[], &["0x1::PaymentScripts"]))
+            .unwrap();
+        assert!(!filter.allows(&txn));
+
+        // Invalid updates are rejected as a whole
+        for invalid in &["0x1", "0x1::", "nothex::M", "0x1::M::f::g"] {
+            assert!(filter.update(config(&[], &[*invalid])).is_err());
+        }
+        assert_eq!(filter.config(), config(&[], &["0x1::PaymentScripts"]));
+
+        filter.update(TransactionFilterConfig::default()).unwrap();
+        assert!(filter.allows(&txn));
+    }
+}
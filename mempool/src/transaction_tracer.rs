@@ -0,0 +1,227 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Traces of the lifecycle of recent transactions, for latency debugging. A transaction gets a
+//! correlation ID when it enters the node: when it's submitted by a client (e.g. through the REST
+//! API, which submits to mempool) or received in a broadcast from another node. The components
+//! handling it then record timestamped stage events under that ID, which are also logged so that
+//! the logs of the components can be joined. Only the latest traces are kept, the oldest ones are
+//! dropped once the configured capacity is reached.
+
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// The events recorded for a transaction beyond this are dropped, e.g. when it's broadcast again
+/// and again to many peers.
+pub const MAX_EVENTS_PER_TRACE: usize = 64;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceStage {
+    /// Submitted by a client of the node
+    Submitted,
+    /// Received in a broadcast from another node
+    ReceivedFromPeer,
+    /// Validated and added to mempool
+    Validated,
+    /// Broadcast to another node
+    Broadcast,
+    /// Pulled from mempool into a block by consensus
+    PulledIntoBlock,
+    /// Executed as part of a block by consensus
+    Executed,
+    /// Committed to the ledger
+    Committed,
+    /// Rejected by mempool validation or discarded at execution
+    Rejected,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TraceEvent {
+    pub stage: TraceStage,
+    pub timestamp_usecs: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TransactionTrace {
+    pub correlation_id: u64,
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub events: Vec<TraceEvent>,
+}
+
+#[derive(Debug, Default)]
+struct Traces {
+    next_correlation_id: u64,
+    traces: HashMap<(AccountAddress, u64), TransactionTrace>,
+    /// The traced transactions, from the oldest to the latest
+    order: VecDeque<(AccountAddress, u64)>,
+}
+
+/// The traces of the latest transactions entering the node, shared by the components handling
+/// transactions. The default tracer has no capacity, so it doesn't trace anything.
+#[derive(Debug, Default)]
+pub struct TransactionTracer {
+    capacity: usize,
+    traces: Mutex<Traces>,
+}
+
+impl TransactionTracer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            traces: Mutex::new(Traces::default()),
+        }
+    }
+
+    /// Records the entry of a transaction into the node, assigning it a correlation ID unless it's
+    /// already traced. The oldest trace is dropped if the tracer is full.
+    pub fn start(&self, sender: AccountAddress, sequence_number: u64, stage: TraceStage) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = (sender, sequence_number);
+        let mut traces = self.traces.lock();
+        if !traces.traces.contains_key(&key) {
+            if traces.order.len() >= self.capacity {
+                if let Some(oldest) = traces.order.pop_front() {
+                    traces.traces.remove(&oldest);
+                }
+            }
+            let correlation_id = traces.next_correlation_id;
+            traces.next_correlation_id += 1;
+            traces.traces.insert(
+                key,
+                TransactionTrace {
+                    correlation_id,
+                    sender,
+                    sequence_number,
+                    events: vec![],
+                },
+            );
+            traces.order.push_back(key);
+        }
+        Self::record_event(&mut traces, key, stage);
+    }
+
+    /// Records a stage of a transaction, if it's traced.
+    pub fn record(&self, sender: AccountAddress, sequence_number: u64, stage: TraceStage) {
+        if self.capacity == 0 {
+            return;
+        }
+        Self::record_event(&mut self.traces.lock(), (sender, sequence_number), stage);
+    }
+
+    fn record_event(traces: &mut Traces, key: (AccountAddress, u64), stage: TraceStage) {
+        let trace = match traces.traces.get_mut(&key) {
+            Some(trace) => trace,
+            None => return,
+        };
+        if trace.events.len() >= MAX_EVENTS_PER_TRACE {
+            return;
+        }
+        let timestamp_usecs = aptos_infallible::duration_since_epoch().as_micros() as u64;
+        trace.events.push(TraceEvent {
+            stage,
+            timestamp_usecs,
+        });
+        debug!(
+            correlation_id = trace.correlation_id,
+            sender = trace.sender,
+            sequence_number = trace.sequence_number,
+            stage = ?stage,
+            "Transaction trace event"
+        );
+    }
+
+    /// The trace of a transaction, if it's traced.
+    pub fn get(&self, sender: AccountAddress, sequence_number: u64) -> Option<TransactionTrace> {
+        self.traces
+            .lock()
+            .traces
+            .get(&(sender, sequence_number))
+            .cloned()
+    }
+
+    /// All the traces, from the oldest to the latest.
+    pub fn traces(&self) -> Vec<TransactionTrace> {
+        let traces = self.traces.lock();
+        traces
+            .order
+            .iter()
+            .filter_map(|key| traces.traces.get(key))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stages(trace: &TransactionTrace) -> Vec<TraceStage> {
+        trace.events.iter().map(|event| event.stage).collect()
+    }
+
+    #[test]
+    fn test_trace() {
+        let tracer = TransactionTracer::new(2);
+        let sender = AccountAddress::random();
+
+        // Only the transactions that entered the node are traced
+        tracer.record(sender, 0, TraceStage::Validated);
+        assert_eq!(tracer.get(sender, 0), None);
+
+        tracer.start(sender, 0, TraceStage::Submitted);
+        tracer.record(sender, 0, TraceStage::Validated);
+        tracer.start(sender, 0, TraceStage::ReceivedFromPeer);
+        tracer.record(sender, 0, TraceStage::Committed);
+        let trace = tracer.get(sender, 0).unwrap();
+        assert_eq!(
+            stages(&trace),
+            vec![
+                TraceStage::Submitted,
+                TraceStage::Validated,
+                TraceStage::ReceivedFromPeer,
+                TraceStage::Committed
+            ]
+        );
+        assert!(trace
+            .events
+            .windows(2)
+            .all(|w| w[0].timestamp_usecs <= w[1].timestamp_usecs));
+
+        // Each transaction gets its own correlation ID, the oldest traces are dropped
+        tracer.start(sender, 1, TraceStage::Submitted);
+        tracer.start(sender, 2, TraceStage::Submitted);
+        assert_eq!(tracer.get(sender, 0), None);
+        let traces = tracer.traces();
+        assert_eq!(
+            traces
+                .iter()
+                .map(|trace| (trace.sequence_number, trace.correlation_id))
+                .collect::<Vec<_>>(),
+            vec![(1, 1), (2, 2)]
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        let sender = AccountAddress::random();
+        let tracer = TransactionTracer::default();
+        tracer.start(sender, 0, TraceStage::Submitted);
+        assert!(tracer.traces().is_empty());
+
+        let tracer = TransactionTracer::new(1);
+        for _ in 0..MAX_EVENTS_PER_TRACE + 1 {
+            tracer.start(sender, 0, TraceStage::Broadcast);
+        }
+        assert_eq!(
+            tracer.get(sender, 0).unwrap().events.len(),
+            MAX_EVENTS_PER_TRACE
+        );
+    }
+}
@@ -11,10 +11,11 @@
 //! long as the latter is in its trusted peers set.
 use aptos_config::{
     config::{
-        DiscoveryMethod, NetworkConfig, Peer, PeerRole, PeerSet, RateLimitConfig, RoleType,
-        CONNECTION_BACKOFF_BASE, CONNECTIVITY_CHECK_INTERVAL_MS, MAX_CONCURRENT_NETWORK_REQS,
-        MAX_CONNECTION_DELAY_MS, MAX_FRAME_SIZE, MAX_FULLNODE_OUTBOUND_CONNECTIONS,
-        MAX_INBOUND_CONNECTIONS, NETWORK_CHANNEL_SIZE,
+        DiscoveryMethod, HandshakeRateLimitConfig, NetworkConfig, Peer, PeerRateLimitConfig,
+        PeerRole, PeerSet, RateLimitConfig, RegistrarConfig, RoleType, CONNECTION_BACKOFF_BASE,
+        CONNECTIVITY_CHECK_INTERVAL_MS, MAX_CONCURRENT_NETWORK_REQS, MAX_CONNECTION_DELAY_MS,
+        MAX_FRAME_SIZE, MAX_FULLNODE_OUTBOUND_CONNECTIONS, MAX_INBOUND_CONNECTIONS,
+        NETWORK_CHANNEL_SIZE,
     },
     network_id::NetworkContext,
 };
@@ -22,8 +23,9 @@ use aptos_crypto::x25519::PublicKey;
 use aptos_infallible::RwLock;
 use aptos_logger::prelude::*;
 use aptos_time_service::TimeService;
-use aptos_types::{chain_id::ChainId, network_address::NetworkAddress};
+use aptos_types::{chain_id::ChainId, network_address::NetworkAddress, transaction::Version};
 use event_notifications::{EventSubscriptionService, ReconfigNotificationListener};
+use futures::stream::{BoxStream, StreamExt};
 use network::{
     application::storage::PeerMetadataStorage,
     connectivity_manager::{builder::ConnectivityManagerBuilder, ConnectivityRequest},
@@ -35,6 +37,9 @@ use network::{
     protocols::{
         health_checker::{self, builder::HealthCheckerBuilder},
         network::{AppConfig, NewNetworkEvents, NewNetworkSender},
+        peer_monitoring::{self, builder::PeerMonitorBuilder},
+        reachability::{self, builder::ReachabilityCheckerBuilder},
+        registrar::{self, builder::RegistrarBuilder},
     },
 };
 use network_discovery::DiscoveryChangeListener;
@@ -65,6 +70,9 @@ pub struct NetworkBuilder {
     discovery_listeners: Option<Vec<DiscoveryChangeListener>>,
     connectivity_manager_builder: Option<ConnectivityManagerBuilder>,
     health_checker_builder: Option<HealthCheckerBuilder>,
+    reachability_checker_builder: Option<ReachabilityCheckerBuilder>,
+    peer_monitor_builder: Option<PeerMonitorBuilder>,
+    registrar_builder: Option<RegistrarBuilder>,
     peer_manager_builder: PeerManagerBuilder,
     peer_metadata_storage: Arc<PeerMetadataStorage>,
 }
@@ -83,11 +91,14 @@ impl NetworkBuilder {
         authentication_mode: AuthenticationMode,
         max_frame_size: usize,
         enable_proxy_protocol: bool,
+        enable_quic_0rtt: bool,
         network_channel_size: usize,
         max_concurrent_network_reqs: usize,
         inbound_connection_limit: usize,
         inbound_rate_limit_config: Option<RateLimitConfig>,
         outbound_rate_limit_config: Option<RateLimitConfig>,
+        peer_rate_limit_configs: HashMap<PeerRole, PeerRateLimitConfig>,
+        handshake_rate_limit_config: Option<HandshakeRateLimitConfig>,
     ) -> Self {
         // A network cannot exist without a PeerManager
         // TODO:  construct this in create and pass it to new() as a parameter. The complication is manual construction of NetworkBuilder in various tests.
@@ -103,9 +114,12 @@ impl NetworkBuilder {
             max_concurrent_network_reqs,
             max_frame_size,
             enable_proxy_protocol,
+            enable_quic_0rtt,
             inbound_connection_limit,
             inbound_rate_limit_config,
             outbound_rate_limit_config,
+            peer_rate_limit_configs,
+            handshake_rate_limit_config,
         );
 
         NetworkBuilder {
@@ -116,6 +130,9 @@ impl NetworkBuilder {
             discovery_listeners: None,
             connectivity_manager_builder: None,
             health_checker_builder: None,
+            reachability_checker_builder: None,
+            peer_monitor_builder: None,
+            registrar_builder: None,
             peer_manager_builder,
             peer_metadata_storage,
         }
@@ -143,17 +160,21 @@ impl NetworkBuilder {
             authentication_mode,
             MAX_FRAME_SIZE,
             false, /* Disable proxy protocol */
+            false, /* Disable QUIC 0-RTT */
             NETWORK_CHANNEL_SIZE,
             MAX_CONCURRENT_NETWORK_REQS,
             MAX_INBOUND_CONNECTIONS,
             None,
             None,
+            HashMap::new(),
+            None,
         );
 
         builder.add_connectivity_manager(
             seeds,
             trusted_peers,
             MAX_FULLNODE_OUTBOUND_CONNECTIONS,
+            None, /* Disable outbound connection rotation */
             CONNECTION_BACKOFF_BASE,
             MAX_CONNECTION_DELAY_MS,
             CONNECTIVITY_CHECK_INTERVAL_MS,
@@ -197,11 +218,14 @@ impl NetworkBuilder {
             authentication_mode,
             config.max_frame_size,
             config.enable_proxy_protocol,
+            config.enable_quic_0rtt,
             config.network_channel_size,
             config.max_concurrent_network_reqs,
             config.max_inbound_connections,
             config.inbound_rate_limit_config,
             config.outbound_rate_limit_config,
+            config.peer_rate_limit_configs.clone(),
+            config.handshake_rate_limit_config,
         );
 
         network_builder.add_connection_monitoring(
@@ -210,6 +234,25 @@ impl NetworkBuilder {
             config.ping_failures_tolerated,
         );
 
+        network_builder.add_reachability_checker(config.reachability_check_interval_ms);
+
+        // Keep the synced version reported to peers up to date, if commits are observable
+        let synced_versions =
+            reconfig_subscription_service
+                .as_deref_mut()
+                .map(|subscription_service| {
+                    subscription_service
+                        .subscribe_to_commits()
+                        .expect("Peer monitoring is unable to subscribe to commits!")
+                        .map(|notification| notification.version)
+                        .boxed()
+                });
+        network_builder.add_peer_monitoring(
+            config.peer_monitoring_interval_ms,
+            config.ping_timeout_ms,
+            synced_versions,
+        );
+
         // Always add a connectivity manager to keep track of known peers
         let seeds = merge_seeds(config);
 
@@ -217,6 +260,7 @@ impl NetworkBuilder {
             seeds,
             trusted_peers,
             config.max_outbound_connections,
+            config.outbound_rotation_interval_ms,
             config.connection_backoff_base,
             config.max_connection_delay_ms,
             config.connectivity_check_interval_ms,
@@ -224,6 +268,11 @@ impl NetworkBuilder {
             config.mutual_authentication,
         );
 
+        // Registrations rely on the connectivity manager to dial the registered peers
+        if let Some(registrar_config) = &config.registrar_config {
+            network_builder.add_registrar(registrar_config.clone());
+        }
+
         network_builder.discovery_listeners = Some(Vec::new());
         for discovery_method in config.discovery_methods() {
             let reconfig_listener = if *discovery_method == DiscoveryMethod::Onchain {
@@ -276,6 +325,7 @@ impl NetworkBuilder {
         assert_eq!(self.state, State::BUILT);
         self.state = State::STARTED;
 
+        let listen_address = self.listen_address();
         let executor = self.executor.as_mut().expect("Executor must exist");
         self.peer_manager_builder.start(executor);
         debug!(
@@ -299,6 +349,30 @@ impl NetworkBuilder {
             );
         }
 
+        if let Some(reachability_checker_builder) = self.reachability_checker_builder.as_mut() {
+            reachability_checker_builder.start(executor, listen_address.clone());
+            debug!(
+                NetworkSchema::new(&self.network_context),
+                "{} Started reachability checker", self.network_context
+            );
+        }
+
+        if let Some(peer_monitor_builder) = self.peer_monitor_builder.as_mut() {
+            peer_monitor_builder.start(executor);
+            debug!(
+                NetworkSchema::new(&self.network_context),
+                "{} Started peer monitor", self.network_context
+            );
+        }
+
+        if let Some(registrar_builder) = self.registrar_builder.as_mut() {
+            registrar_builder.start(executor, listen_address);
+            debug!(
+                NetworkSchema::new(&self.network_context),
+                "{} Started registrar", self.network_context
+            );
+        }
+
         if let Some(discovery_listeners) = self.discovery_listeners.take() {
             discovery_listeners
                 .into_iter()
@@ -335,6 +409,7 @@ impl NetworkBuilder {
         seeds: PeerSet,
         trusted_peers: Arc<RwLock<PeerSet>>,
         max_outbound_connections: usize,
+        outbound_rotation_interval_ms: Option<u64>,
         connection_backoff_base: u64,
         max_connection_delay_ms: u64,
         connectivity_check_interval_ms: u64,
@@ -361,6 +436,7 @@ impl NetworkBuilder {
             ConnectionRequestSender::new(self.peer_manager_builder.connection_reqs_tx()),
             pm_conn_mgr_notifs_rx,
             outbound_connection_limit,
+            outbound_rotation_interval_ms,
             mutual_authentication,
         ));
         self
@@ -394,6 +470,13 @@ impl NetworkBuilder {
                 *interval_duration,
                 self.time_service.clone(),
             ),
+            DiscoveryMethod::Dns(dns_names, interval_duration) => DiscoveryChangeListener::dns(
+                self.network_context,
+                conn_mgr_reqs_tx,
+                dns_names.clone(),
+                *interval_duration,
+                self.time_service.clone(),
+            ),
             DiscoveryMethod::None => return,
         };
 
@@ -430,6 +513,71 @@ impl NetworkBuilder {
         self
     }
 
+    /// Add a ReachabilityChecker to the network.
+    fn add_reachability_checker(&mut self, check_interval_ms: Option<u64>) -> &mut Self {
+        let (reachability_network_tx, reachability_network_rx) =
+            self.add_p2p_service(&reachability::network_endpoint_config());
+        self.reachability_checker_builder = Some(ReachabilityCheckerBuilder::new(
+            self.network_context(),
+            self.time_service.clone(),
+            check_interval_ms,
+            reachability_network_tx,
+            reachability_network_rx,
+            self.peer_metadata_storage.clone(),
+        ));
+        debug!(
+            NetworkSchema::new(&self.network_context),
+            "{} Created reachability checker", self.network_context
+        );
+        self
+    }
+
+    /// Add a Registrar to the network.
+    fn add_registrar(&mut self, config: RegistrarConfig) -> &mut Self {
+        let (registrar_network_tx, registrar_network_rx) =
+            self.add_p2p_service(&registrar::network_endpoint_config());
+        self.registrar_builder = Some(RegistrarBuilder::new(
+            self.network_context(),
+            self.time_service.clone(),
+            config,
+            registrar_network_tx,
+            registrar_network_rx,
+            self.peer_metadata_storage.clone(),
+            self.conn_mgr_reqs_tx(),
+        ));
+        debug!(
+            NetworkSchema::new(&self.network_context),
+            "{} Created registrar", self.network_context
+        );
+        self
+    }
+
+    /// Add a PeerMonitor to the network.
+    fn add_peer_monitoring(
+        &mut self,
+        ping_interval_ms: u64,
+        ping_timeout_ms: u64,
+        synced_versions: Option<BoxStream<'static, Version>>,
+    ) -> &mut Self {
+        let (peer_monitoring_network_tx, peer_monitoring_network_rx) =
+            self.add_p2p_service(&peer_monitoring::network_endpoint_config());
+        self.peer_monitor_builder = Some(PeerMonitorBuilder::new(
+            self.network_context(),
+            self.time_service.clone(),
+            ping_interval_ms,
+            ping_timeout_ms,
+            peer_monitoring_network_tx,
+            peer_monitoring_network_rx,
+            self.peer_metadata_storage.clone(),
+            synced_versions,
+        ));
+        debug!(
+            NetworkSchema::new(&self.network_context),
+            "{} Created peer monitor", self.network_context
+        );
+        self
+    }
+
     /// Register a new Peer-to-Peer (both client and service) application with
     /// network and return the specialized client and service interfaces.
     pub fn add_p2p_service<SenderT: NewNetworkSender, EventsT: NewNetworkEvents>(
@@ -0,0 +1,180 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::DiscoveryError;
+use aptos_config::config::{Peer, PeerRole, PeerSet};
+use aptos_time_service::{Interval, TimeService, TimeServiceTrait};
+use aptos_types::{network_address::NetworkAddress, PeerId};
+use futures::{
+    future::{BoxFuture, Future, FutureExt},
+    Stream,
+};
+use std::{
+    pin::Pin,
+    str::FromStr,
+    task::{Context, Poll},
+    time::Duration,
+};
+use trust_dns_resolver::TokioAsyncResolver;
+
+/// Discovers seed peers from DNS TXT records. Every TXT record holds a single
+/// seed address, formatted as `<peer_id>=<network_address>`, e.g.:
+///
+/// `"5b3a...d1e0=/dns4/seed.example.com/tcp/6182/ln-noise-ik/<pubkey>/ln-handshake/0"`
+///
+/// The records are re-resolved on every interval, so operators can rotate seeds
+/// without requiring node config changes.
+pub struct DnsStream {
+    dns_names: Vec<String>,
+    interval: Pin<Box<Interval>>,
+    pending_lookup: Option<BoxFuture<'static, Result<PeerSet, DiscoveryError>>>,
+}
+
+impl DnsStream {
+    pub(crate) fn new(
+        dns_names: Vec<String>,
+        interval_duration: Duration,
+        time_service: TimeService,
+    ) -> Self {
+        DnsStream {
+            dns_names,
+            interval: Box::pin(time_service.interval(interval_duration)),
+            pending_lookup: None,
+        }
+    }
+}
+
+impl Stream for DnsStream {
+    type Item = Result<PeerSet, DiscoveryError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Wait for delay, or add the delay for next call
+        if self.pending_lookup.is_none() {
+            futures::ready!(self.interval.as_mut().poll_next(cx));
+            let dns_names = self.dns_names.clone();
+            self.pending_lookup = Some(lookup_seeds(dns_names).boxed());
+        }
+
+        // Wait for the lookup to complete
+        let lookup_result = futures::ready!(self
+            .pending_lookup
+            .as_mut()
+            .expect("The pending lookup must exist!")
+            .as_mut()
+            .poll(cx));
+        self.pending_lookup = None;
+        Poll::Ready(Some(lookup_result))
+    }
+}
+
+/// Resolves the TXT records of all DNS names and parses them into a `PeerSet`.
+/// A failed lookup fails the entire update, so that the previously discovered
+/// seeds remain in place.
+async fn lookup_seeds(dns_names: Vec<String>) -> Result<PeerSet, DiscoveryError> {
+    let resolver = TokioAsyncResolver::tokio_from_system_conf()
+        .map_err(|error| DiscoveryError::IO(error.into()))?;
+
+    let mut records = vec![];
+    for dns_name in dns_names.iter() {
+        let txt_lookup = resolver
+            .txt_lookup(dns_name.as_str())
+            .await
+            .map_err(|error| DiscoveryError::IO(error.into()))?;
+        for txt in txt_lookup.iter() {
+            // A single TXT record may be split into multiple character strings
+            let record: String = txt
+                .txt_data()
+                .iter()
+                .map(|data| String::from_utf8_lossy(data))
+                .collect();
+            records.push(record);
+        }
+    }
+
+    parse_seed_records(records.iter().map(String::as_str))
+}
+
+/// Parses TXT records of the form `<peer_id>=<network_address>`. Addresses
+/// belonging to the same peer are merged.
+fn parse_seed_records<'a>(
+    records: impl Iterator<Item = &'a str>,
+) -> Result<PeerSet, DiscoveryError> {
+    let mut peers = PeerSet::new();
+    for record in records {
+        let (peer_id, addr) = record.trim().split_once('=').ok_or_else(|| {
+            DiscoveryError::Parsing(format!(
+                "Seed record is not of the form <peer_id>=<network_address>: {}",
+                record
+            ))
+        })?;
+        let peer_id = PeerId::from_str(peer_id.trim())
+            .map_err(|error| DiscoveryError::Parsing(error.to_string()))?;
+        let addr = NetworkAddress::from_str(addr.trim())
+            .map_err(|error| DiscoveryError::Parsing(error.to_string()))?;
+
+        let peer = Peer::from_addrs(PeerRole::Upstream, vec![addr]);
+        if let Some(existing_peer) = peers.get_mut(&peer_id) {
+            existing_peer.addresses.extend(peer.addresses);
+            existing_peer.keys.extend(peer.keys);
+        } else {
+            peers.insert(peer_id, peer);
+        }
+    }
+    Ok(peers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDR: &str = "/dns4/seed.example.com/tcp/6182/ln-noise-ik/080e287879c918794170e258bfaddd75acac5b3e350419044655e4983a487120/ln-handshake/0";
+    const OTHER_ADDR: &str = "/ip4/1.2.3.4/tcp/6182/ln-noise-ik/080e287879c918794170e258bfaddd75acac5b3e350419044655e4983a487120/ln-handshake/0";
+
+    #[test]
+    fn test_parse_seed_records() {
+        let peer_id = PeerId::random();
+        let other_peer_id = PeerId::random();
+        let records = vec![
+            format!("{}={}", peer_id, ADDR),
+            format!("{}={}", peer_id, OTHER_ADDR),
+            format!(" {} = {} ", other_peer_id, ADDR),
+        ];
+
+        let peers = parse_seed_records(records.iter().map(String::as_str)).unwrap();
+        assert_eq!(peers.len(), 2);
+
+        // Verify the addresses of the same peer are merged
+        let peer = peers.get(&peer_id).unwrap();
+        assert_eq!(peer.role, PeerRole::Upstream);
+        assert_eq!(
+            peer.addresses,
+            vec![
+                NetworkAddress::from_str(ADDR).unwrap(),
+                NetworkAddress::from_str(OTHER_ADDR).unwrap()
+            ]
+        );
+        assert_eq!(peer.keys.len(), 1);
+
+        let other_peer = peers.get(&other_peer_id).unwrap();
+        assert_eq!(
+            other_peer.addresses,
+            vec![NetworkAddress::from_str(ADDR).unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_seed_records() {
+        let peer_id = PeerId::random();
+        let invalid_records = vec![
+            ADDR.to_string(),
+            peer_id.to_string(),
+            format!("not_a_peer_id={}", ADDR),
+            format!("{}=/not/an/address", peer_id),
+        ];
+
+        for record in invalid_records {
+            let result = parse_seed_records(std::iter::once(record.as_str()));
+            assert!(matches!(result, Err(DiscoveryError::Parsing(_))));
+        }
+    }
+}
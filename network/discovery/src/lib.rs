@@ -1,7 +1,9 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{counters::DISCOVERY_COUNTS, file::FileStream, validator_set::ValidatorSetStream};
+use crate::{
+    counters::DISCOVERY_COUNTS, dns::DnsStream, file::FileStream, validator_set::ValidatorSetStream,
+};
 use aptos_config::{config::PeerSet, network_id::NetworkContext};
 use aptos_crypto::x25519;
 use aptos_logger::prelude::*;
@@ -22,6 +24,7 @@ use std::{
 use tokio::runtime::Handle;
 
 mod counters;
+mod dns;
 mod file;
 mod validator_set;
 
@@ -42,6 +45,7 @@ pub struct DiscoveryChangeListener {
 enum DiscoveryChangeStream {
     ValidatorSet(ValidatorSetStream),
     File(FileStream),
+    Dns(DnsStream),
 }
 
 impl Stream for DiscoveryChangeStream {
@@ -51,6 +55,7 @@ impl Stream for DiscoveryChangeStream {
         match self.get_mut() {
             Self::ValidatorSet(stream) => Pin::new(stream).poll_next(cx),
             Self::File(stream) => Pin::new(stream).poll_next(cx),
+            Self::Dns(stream) => Pin::new(stream).poll_next(cx),
         }
     }
 }
@@ -95,6 +100,23 @@ impl DiscoveryChangeListener {
         }
     }
 
+    pub fn dns(
+        network_context: NetworkContext,
+        update_channel: channel::Sender<ConnectivityRequest>,
+        dns_names: Vec<String>,
+        interval_duration: Duration,
+        time_service: TimeService,
+    ) -> Self {
+        let source_stream =
+            DiscoveryChangeStream::Dns(DnsStream::new(dns_names, interval_duration, time_service));
+        DiscoveryChangeListener {
+            discovery_source: DiscoverySource::Dns,
+            network_context,
+            update_channel,
+            source_stream,
+        }
+    }
+
     pub fn start(self, executor: &Handle) {
         executor.spawn(Box::pin(self).run());
     }
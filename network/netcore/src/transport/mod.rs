@@ -21,7 +21,9 @@ pub mod boxed;
 #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
 pub mod memory;
 pub mod proxy_protocol;
+pub mod quic;
 pub mod tcp;
+pub mod tls;
 
 /// Origin of how a Connection was established.
 #[derive(Clone, Copy, Hash, PartialEq, Eq, Serialize)]
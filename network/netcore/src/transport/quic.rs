@@ -0,0 +1,466 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! QUIC Transport
+//!
+//! Each connection carries exactly one bidirectional QUIC stream, which gives
+//! the layers above the same reliable, ordered byte-stream they get from TCP.
+//!
+//! QUIC mandates TLS, but AptosNet authenticates peers with a Noise IK handshake
+//! performed on top of the base transport. The TLS certificates used here are
+//! therefore ephemeral and self-signed, and the dialer does not verify them.
+//!
+//! When 0-RTT is enabled, the dialer caches the TLS session tickets issued by
+//! each peer and uses them to send the first bytes of the next connection to
+//! that peer (i.e., the Noise handshake initiation) as early data. Early data
+//! can be replayed, but replayed Noise initiation messages are already handled
+//! by the Noise layer (see `AntiReplayTimestamps`). If a peer rejects the early
+//! data (e.g., because it restarted and lost its session state), the dial fails
+//! and the next dial performs a full handshake.
+use crate::transport::Transport;
+use aptos_types::{
+    network_address::{parse_dns_udp, parse_ip_udp, NetworkAddress, Protocol},
+    PeerId,
+};
+use futures::{
+    future::Future,
+    io::{AsyncRead, AsyncWrite},
+    ready,
+    stream::{Stream, StreamExt},
+};
+use quinn::{
+    ClientConfig, Connecting, Connection, Endpoint, Incoming, NewConnection, RecvStream,
+    SendStream, ServerConfig, TransportConfig, VarInt,
+};
+use std::{
+    fmt, io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, SystemTime},
+};
+use tokio::net::lookup_host;
+use tokio_util::compat::{Compat, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
+
+/// The ALPN protocol identifier negotiated by all AptosNet QUIC connections.
+const ALPN_PROTOCOL: &[u8] = b"aptosnet";
+
+/// The TLS server name used in the ephemeral certificates. Dialers prefix it
+/// with the remote peer id, so that session tickets are cached per peer.
+const SERVER_NAME: &str = "aptosnet";
+
+/// Connections without any activity for this long are closed.
+const MAX_IDLE_TIMEOUT_MS: u32 = 60_000;
+
+/// Interval at which keep-alive packets are sent on otherwise idle connections
+/// (e.g., to keep NAT bindings alive).
+const KEEP_ALIVE_INTERVAL_MS: u64 = 10_000;
+
+/// Transport to build QUIC connections
+#[derive(Clone)]
+pub struct QuicTransport {
+    /// Whether to use 0-RTT session resumption when dialing known peers.
+    enable_0rtt: bool,
+    /// The client configuration shared by all dials. This holds the TLS
+    /// session cache, so it must outlive individual connections.
+    client_config: ClientConfig,
+}
+
+impl QuicTransport {
+    pub fn new(enable_0rtt: bool) -> Self {
+        let mut client_crypto = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(SkipServerVerification))
+            .with_no_client_auth();
+        client_crypto.alpn_protocols = vec![ALPN_PROTOCOL.to_vec()];
+        client_crypto.enable_early_data = enable_0rtt;
+
+        let mut client_config = ClientConfig::new(Arc::new(client_crypto));
+        client_config.transport = transport_config();
+
+        Self {
+            enable_0rtt,
+            client_config,
+        }
+    }
+
+    /// Creates a server configuration with a freshly generated, self-signed
+    /// certificate.
+    fn server_config(&self) -> io::Result<ServerConfig> {
+        let certificate =
+            rcgen::generate_simple_self_signed(vec![SERVER_NAME.into()]).map_err(io_error)?;
+        let cert_chain = vec![rustls::Certificate(
+            certificate.serialize_der().map_err(io_error)?,
+        )];
+        let private_key = rustls::PrivateKey(certificate.serialize_private_key_der());
+
+        let mut server_crypto = rustls::ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(cert_chain, private_key)
+            .map_err(io_error)?;
+        server_crypto.alpn_protocols = vec![ALPN_PROTOCOL.to_vec()];
+        if self.enable_0rtt {
+            // QUIC only supports accepting no early data, or all of it
+            server_crypto.max_early_data_size = u32::MAX;
+        }
+
+        let mut server_config = ServerConfig::with_crypto(Arc::new(server_crypto));
+        server_config.transport = transport_config();
+        Ok(server_config)
+    }
+}
+
+impl fmt::Debug for QuicTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuicTransport")
+            .field("enable_0rtt", &self.enable_0rtt)
+            .finish()
+    }
+}
+
+impl Transport for QuicTransport {
+    type Output = QuicSocket;
+    type Error = ::std::io::Error;
+    type Listener = QuicListenerStream;
+    type Inbound = Pin<Box<dyn Future<Output = io::Result<QuicSocket>> + Send + 'static>>;
+    type Outbound = Pin<Box<dyn Future<Output = io::Result<QuicSocket>> + Send + 'static>>;
+
+    fn listen_on(
+        &self,
+        addr: NetworkAddress,
+    ) -> Result<(Self::Listener, NetworkAddress), Self::Error> {
+        let ((ipaddr, port), addr_suffix) =
+            parse_ip_udp(addr.as_slice()).ok_or_else(|| invalid_addr_error(&addr))?;
+        if !addr_suffix.is_empty() {
+            return Err(invalid_addr_error(&addr));
+        }
+
+        let (endpoint, incoming) =
+            Endpoint::server(self.server_config()?, SocketAddr::new(ipaddr, port))?;
+        let listen_addr = udp_network_address(endpoint.local_addr()?);
+
+        Ok((
+            QuicListenerStream {
+                inner: incoming,
+                _endpoint: endpoint,
+            },
+            listen_addr,
+        ))
+    }
+
+    fn dial(&self, peer_id: PeerId, addr: NetworkAddress) -> Result<Self::Outbound, Self::Error> {
+        let protos = addr.as_slice();
+
+        // ensure addr is well formed to save some work before potentially
+        // spawning a dial task that will fail anyway.
+        parse_ip_udp(protos)
+            .map(|_| ())
+            .or_else(|| parse_dns_udp(protos).map(|_| ()))
+            .ok_or_else(|| invalid_addr_error(&addr))?;
+
+        let client_config = self.client_config.clone();
+        let enable_0rtt = self.enable_0rtt;
+        Ok(Box::pin(async move {
+            let remote_addr = resolve(addr).await?;
+            connect(
+                client_config,
+                remote_addr,
+                peer_server_name(peer_id),
+                enable_0rtt,
+            )
+            .await
+        }))
+    }
+}
+
+/// Returns the transport parameters shared by both dialers and listeners.
+fn transport_config() -> Arc<TransportConfig> {
+    let mut transport_config = TransportConfig::default();
+    transport_config
+        .max_idle_timeout(Some(VarInt::from_u32(MAX_IDLE_TIMEOUT_MS).into()))
+        .keep_alive_interval(Some(Duration::from_millis(KEEP_ALIVE_INTERVAL_MS)))
+        // Each connection carries a single bidirectional stream
+        .max_concurrent_bidi_streams(VarInt::from_u32(1))
+        .max_concurrent_uni_streams(VarInt::from_u32(0));
+    Arc::new(transport_config)
+}
+
+/// Returns the TLS server name used when dialing the given peer. Session
+/// tickets are cached by server name, so this must be unique per peer. A DNS
+/// label is limited to 63 characters, so the hex peer id is split in two.
+pub(crate) fn peer_server_name(peer_id: PeerId) -> String {
+    let peer_id = format!("{:x}", peer_id);
+    let (first_half, second_half) = peer_id.split_at(peer_id.len() / 2);
+    format!("{}.{}.{}", first_half, second_half, SERVER_NAME)
+}
+
+/// Resolves the dial address into a single socket address.
+async fn resolve(addr: NetworkAddress) -> io::Result<SocketAddr> {
+    let protos = addr.as_slice();
+
+    if let Some(((ipaddr, port), _addr_suffix)) = parse_ip_udp(protos) {
+        Ok(SocketAddr::new(ipaddr, port))
+    } else if let Some(((ip_filter, dns_name, port), _addr_suffix)) = parse_dns_udp(protos) {
+        // QUIC dials don't fail fast like TCP, so just use the first address
+        lookup_host((dns_name.as_ref(), port))
+            .await?
+            .find(|socketaddr| ip_filter.matches(socketaddr.ip()))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "could not resolve dns name to any address: name: {}, ip filter: {:?}",
+                        dns_name.as_ref(),
+                        ip_filter,
+                    ),
+                )
+            })
+    } else {
+        Err(invalid_addr_error(&addr))
+    }
+}
+
+/// Connects to the remote address and opens the connection's only stream.
+async fn connect(
+    client_config: ClientConfig,
+    remote_addr: SocketAddr,
+    server_name: String,
+    enable_0rtt: bool,
+) -> io::Result<QuicSocket> {
+    let bind_addr = if remote_addr.is_ipv4() {
+        SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0)
+    } else {
+        SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), 0)
+    };
+    let endpoint = Endpoint::client(bind_addr)?;
+    let connecting = endpoint
+        .connect_with(client_config, remote_addr, &server_name)
+        .map_err(io_error)?;
+
+    // Resuming with 0-RTT only succeeds if we hold a session ticket from an
+    // earlier connection to this peer. Otherwise, fall back to a full handshake.
+    let new_connection = if enable_0rtt {
+        match connecting.into_0rtt() {
+            Ok((new_connection, _zero_rtt_accepted)) => new_connection,
+            Err(connecting) => connecting.await.map_err(io_error)?,
+        }
+    } else {
+        connecting.await.map_err(io_error)?
+    };
+
+    let connection = new_connection.connection;
+    let (send, recv) = connection.open_bi().await.map_err(io_error)?;
+    Ok(QuicSocket::new(connection, send, recv))
+}
+
+/// Waits for the inbound connection handshake and the dialer's stream.
+async fn accept(connecting: Connecting) -> io::Result<QuicSocket> {
+    let NewConnection {
+        connection,
+        mut bi_streams,
+        ..
+    } = connecting.await.map_err(io_error)?;
+
+    let (send, recv) = bi_streams
+        .next()
+        .await
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "connection closed before a stream was opened",
+            )
+        })?
+        .map_err(io_error)?;
+    Ok(QuicSocket::new(connection, send, recv))
+}
+
+/// Converts the socket address into a `/ip4/<addr>/udp/<port>` or
+/// `/ip6/<addr>/udp/<port>` network address.
+fn udp_network_address(sockaddr: SocketAddr) -> NetworkAddress {
+    NetworkAddress::from(Protocol::from(sockaddr.ip())).push(Protocol::Udp(sockaddr.port()))
+}
+
+fn invalid_addr_error(addr: &NetworkAddress) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Invalid NetworkAddress: '{}'", addr),
+    )
+}
+
+fn io_error<E: ::std::error::Error + Send + Sync + 'static>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error)
+}
+
+/// The dialer doesn't verify the listener's TLS certificate: peers are
+/// authenticated by the Noise IK handshake layered on top of the QUIC stream.
+struct SkipServerVerification;
+
+impl rustls::client::ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
+
+#[must_use = "streams do nothing unless polled"]
+pub struct QuicListenerStream {
+    inner: Incoming,
+    // The endpoint is closed once all handles to it are dropped
+    _endpoint: Endpoint,
+}
+
+impl Stream for QuicListenerStream {
+    type Item = io::Result<(
+        Pin<Box<dyn Future<Output = io::Result<QuicSocket>> + Send + 'static>>,
+        NetworkAddress,
+    )>;
+
+    fn poll_next(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<Option<Self::Item>> {
+        match ready!(self.inner.poll_next_unpin(context)) {
+            Some(connecting) => {
+                let dialer_addr = udp_network_address(connecting.remote_address());
+                let inbound: Pin<Box<dyn Future<Output = io::Result<QuicSocket>> + Send>> =
+                    Box::pin(accept(connecting));
+                Poll::Ready(Some(Ok((inbound, dialer_addr))))
+            }
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+/// A single bidirectional stream of a QUIC connection.
+///
+/// The connection handle is held alongside the stream halves so that the
+/// connection stays open for as long as the socket is in use.
+pub struct QuicSocket {
+    connection: Connection,
+    send: Compat<SendStream>,
+    recv: Compat<RecvStream>,
+}
+
+impl QuicSocket {
+    fn new(connection: Connection, send: SendStream, recv: RecvStream) -> Self {
+        Self {
+            connection,
+            send: send.compat_write(),
+            recv: recv.compat(),
+        }
+    }
+}
+
+impl fmt::Debug for QuicSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuicSocket")
+            .field("remote_address", &self.connection.remote_address())
+            .finish()
+    }
+}
+
+impl AsyncRead for QuicSocket {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.recv).poll_read(context, buf)
+    }
+}
+
+impl AsyncWrite for QuicSocket {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.send).poll_write(context, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.send).poll_flush(context)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.send).poll_close(context)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transport::{ConnectionOrigin, Transport, TransportExt};
+    use aptos_types::PeerId;
+    use futures::{
+        future::{join, FutureExt},
+        io::{AsyncReadExt, AsyncWriteExt},
+        stream::StreamExt,
+    };
+    use std::convert::TryFrom;
+
+    #[tokio::test]
+    async fn simple_listen_and_dial() -> Result<(), ::std::io::Error> {
+        let t = QuicTransport::new(true).and_then(|mut out, _addr, origin| async move {
+            match origin {
+                ConnectionOrigin::Inbound => {
+                    let mut buf = [0; 5];
+                    out.read_exact(&mut buf).await?;
+                    assert_eq!(&buf, b"Earth");
+                    out.write_all(b"Air").await?;
+                }
+                ConnectionOrigin::Outbound => {
+                    // The dialer must write first, otherwise the listener
+                    // never learns about the stream.
+                    out.write_all(b"Earth").await?;
+                    let mut buf = [0; 3];
+                    out.read_exact(&mut buf).await?;
+                    assert_eq!(&buf, b"Air");
+                }
+            }
+            Ok(())
+        });
+
+        let (listener, addr) = t.listen_on("/ip4/127.0.0.1/udp/0".parse().unwrap())?;
+        let peer_id = PeerId::random();
+        let dial = t.dial(peer_id, addr)?;
+        let listener = listener.into_future().then(|(maybe_result, _stream)| {
+            let (incoming, _addr) = maybe_result.unwrap().unwrap();
+            incoming.map(Result::unwrap)
+        });
+
+        let (outgoing, _incoming) = join(dial, listener).await;
+        assert!(outgoing.is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_multiaddrs() {
+        let t = QuicTransport::new(false);
+
+        let result = t.listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap());
+        assert!(result.is_err());
+
+        let peer_id = PeerId::random();
+        let result = t.dial(peer_id, "/memory/22".parse().unwrap());
+        assert!(result.is_err());
+
+        let result = t.dial(peer_id, "/ip4/127.0.0.1/tcp/22".parse().unwrap());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_peer_server_name() {
+        let peer_id = PeerId::random();
+        let server_name = peer_server_name(peer_id);
+        assert!(server_name.ends_with(SERVER_NAME));
+        assert!(server_name.split('.').all(|label| label.len() <= 63));
+        assert!(rustls::ServerName::try_from(server_name.as_str()).is_ok());
+    }
+}
@@ -0,0 +1,638 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! TLS Transport
+//!
+//! Wraps TCP connections in mutually authenticated TLS, for networks where
+//! middleboxes block (or fail to parse) the AptosNet Noise handshake. To such
+//! middleboxes, AptosNet connections then look like any other TLS connection.
+//!
+//! Every node presents a self-signed ed25519 certificate whose key is derived
+//! from its x25519 network identity key: the clamped x25519 scalar is used as
+//! the ed25519 signing scalar, so the certificate's public key maps back to the
+//! node's x25519 public key (see `IdentityCertKey`). The dialer checks that the
+//! listener's certificate key is the Noise IK key of the dialed address, and
+//! the listener checks the dialer's certificate key with the `ClientAuthorizer`
+//! it was built with (e.g., against the trusted peers on mutually authenticated
+//! networks). The TLS handshake itself proves possession of the keys. AptosNet
+//! still runs the Noise IK handshake inside the TLS session.
+use crate::transport::{
+    quic::peer_server_name,
+    tcp::{TcpSocket, TcpTransport},
+    Transport,
+};
+use aptos_crypto::{ed25519::ed25519_dalek, x25519, HashValue, ValidCryptoMaterial};
+use aptos_types::{
+    network_address::{parse_dns_tcp, parse_ip_tcp, parse_tls, NetworkAddress, Protocol},
+    PeerId,
+};
+use futures::{
+    future::Future,
+    io::{AsyncRead, AsyncWrite},
+    ready,
+    stream::{Stream, StreamExt},
+};
+use futures_rustls::{TlsAcceptor, TlsConnector, TlsStream};
+use std::{
+    convert::TryFrom,
+    fmt, io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::SystemTime,
+};
+
+/// The ALPN protocol identifier negotiated by all AptosNet TLS connections.
+const ALPN_PROTOCOL: &[u8] = b"aptosnet";
+
+/// Domain separator for the nonce key of the certificate signing key.
+const CERT_NONCE_DOMAIN: &[u8] = b"APTOS::TlsTransport::certificate-nonce";
+
+/// Object identifier of ed25519 subject public keys (RFC 8410).
+const ED25519_OID: &[u64] = &[1, 3, 101, 112];
+
+/// Decides whether a dialer, identified by the x25519 key of its certificate,
+/// may establish a TLS session with the listener.
+pub type ClientAuthorizer = Arc<dyn Fn(&x25519::PublicKey) -> bool + Send + Sync>;
+
+/// Transport to build TLS connections on top of TCP
+#[derive(Clone)]
+pub struct TlsTransport {
+    tcp_transport: TcpTransport,
+    cert_resolver: Arc<IdentityCertResolver>,
+    acceptor: TlsAcceptor,
+}
+
+impl TlsTransport {
+    /// Creates a transport presenting a self-signed certificate for the local
+    /// peer id, signed with a key derived from the local identity key. Dialers
+    /// are only accepted if `authorize_client` accepts their identity key.
+    pub fn new(
+        tcp_transport: TcpTransport,
+        peer_id: PeerId,
+        identity_key: &x25519::PrivateKey,
+        authorize_client: ClientAuthorizer,
+    ) -> io::Result<Self> {
+        let cert_key = IdentityCertKey::new(identity_key);
+        let mut params = rcgen::CertificateParams::new(vec![peer_server_name(peer_id)]);
+        params.alg = &rcgen::PKCS_ED25519;
+        params.key_pair =
+            Some(rcgen::KeyPair::from_remote(Box::new(cert_key.clone())).map_err(io_error)?);
+        let certificate = rcgen::Certificate::from_params(params).map_err(io_error)?;
+        let cert_chain = vec![rustls::Certificate(
+            certificate.serialize_der().map_err(io_error)?,
+        )];
+        let cert_resolver = Arc::new(IdentityCertResolver(Arc::new(
+            rustls::sign::CertifiedKey::new(cert_chain, Arc::new(cert_key)),
+        )));
+
+        let mut server_config = rustls::ServerConfig::builder()
+            .with_safe_defaults()
+            .with_client_cert_verifier(Arc::new(ClientKeyVerifier { authorize_client }))
+            .with_cert_resolver(cert_resolver.clone());
+        server_config.alpn_protocols = vec![ALPN_PROTOCOL.to_vec()];
+
+        Ok(Self {
+            tcp_transport,
+            cert_resolver,
+            acceptor: TlsAcceptor::from(Arc::new(server_config)),
+        })
+    }
+
+    /// Returns a connector which only accepts listeners presenting a
+    /// certificate for `server_key`.
+    fn connector(&self, server_key: x25519::PublicKey) -> TlsConnector {
+        let mut client_config = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(ServerKeyVerifier { server_key }))
+            .with_client_cert_resolver(self.cert_resolver.clone());
+        client_config.alpn_protocols = vec![ALPN_PROTOCOL.to_vec()];
+        TlsConnector::from(Arc::new(client_config))
+    }
+}
+
+impl fmt::Debug for TlsTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsTransport")
+            .field("tcp_transport", &self.tcp_transport)
+            .finish()
+    }
+}
+
+impl Transport for TlsTransport {
+    type Output = TlsSocket;
+    type Error = ::std::io::Error;
+    type Listener = TlsListenerStream;
+    type Inbound = Pin<Box<dyn Future<Output = io::Result<TlsSocket>> + Send + 'static>>;
+    type Outbound = Pin<Box<dyn Future<Output = io::Result<TlsSocket>> + Send + 'static>>;
+
+    fn listen_on(
+        &self,
+        addr: NetworkAddress,
+    ) -> Result<(Self::Listener, NetworkAddress), Self::Error> {
+        let (tcp_addr, addr_suffix) = split_tls_addr(&addr)?;
+        if !addr_suffix.is_empty() {
+            return Err(invalid_addr_error(&addr));
+        }
+
+        let (inner, listen_addr) = self.tcp_transport.listen_on(tcp_addr)?;
+        Ok((
+            TlsListenerStream {
+                inner,
+                acceptor: self.acceptor.clone(),
+            },
+            listen_addr.push(Protocol::Tls),
+        ))
+    }
+
+    /// Dials `/<base_transport>/tls/noise-ik/<pubkey>/handshake/<version>`.
+    /// The listener must present a certificate for the Noise IK `<pubkey>`.
+    fn dial(&self, peer_id: PeerId, addr: NetworkAddress) -> Result<Self::Outbound, Self::Error> {
+        let (tcp_addr, addr_suffix) = split_tls_addr(&addr)?;
+        let server_key = match addr_suffix {
+            [Protocol::NoiseIK(pubkey), Protocol::Handshake(_)] => *pubkey,
+            _ => return Err(invalid_addr_error(&addr)),
+        };
+        let server_name =
+            rustls::ServerName::try_from(peer_server_name(peer_id).as_str()).map_err(io_error)?;
+
+        let outbound = self.tcp_transport.dial(peer_id, tcp_addr)?;
+        let connector = self.connector(server_key);
+        Ok(Box::pin(async move {
+            let socket = outbound.await?;
+            let stream = connector.connect(server_name, socket).await?;
+            Ok(TlsSocket::new(stream.into()))
+        }))
+    }
+}
+
+/// Splits the `/<base_transport>/tls` address into the TCP base transport
+/// address and the unparsed suffix.
+fn split_tls_addr(addr: &NetworkAddress) -> io::Result<(NetworkAddress, &[Protocol])> {
+    let protos = addr.as_slice();
+    parse_ip_tcp(protos)
+        .map(|x| x.1)
+        .or_else(|| parse_dns_tcp(protos).map(|x| x.1))
+        .and_then(parse_tls)
+        .map(|suffix| {
+            let tcp_addr = NetworkAddress::try_from(protos[..2].to_vec())
+                .expect("tcp protos are always non-empty");
+            (tcp_addr, suffix)
+        })
+        .ok_or_else(|| invalid_addr_error(addr))
+}
+
+fn invalid_addr_error(addr: &NetworkAddress) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Invalid NetworkAddress: '{}'", addr),
+    )
+}
+
+fn io_error<E: ::std::error::Error + Send + Sync + 'static>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error)
+}
+
+/// The ed25519 certificate signing key derived from an x25519 identity key.
+///
+/// x25519 keys can't sign, but the clamped x25519 scalar is a valid ed25519
+/// signing scalar whose public key is the Edwards form of the x25519 public
+/// key. The nonce key, which ed25519 normally derives along with the scalar,
+/// is derived from the scalar under a domain separator.
+#[derive(Clone)]
+struct IdentityCertKey {
+    expanded_key: [u8; ed25519_dalek::EXPANDED_SECRET_KEY_LENGTH],
+    public_key: ed25519_dalek::PublicKey,
+}
+
+impl IdentityCertKey {
+    fn new(identity_key: &x25519::PrivateKey) -> Self {
+        let mut scalar = identity_key.to_bytes();
+        scalar[0] &= 248;
+        scalar[31] &= 127;
+        scalar[31] |= 64;
+
+        let mut expanded_key = [0u8; ed25519_dalek::EXPANDED_SECRET_KEY_LENGTH];
+        expanded_key[..32].copy_from_slice(&scalar);
+        let nonce_key = HashValue::sha3_256_of(&[CERT_NONCE_DOMAIN, &scalar[..]].concat());
+        expanded_key[32..].copy_from_slice(&nonce_key.to_vec());
+
+        let public_key = ed25519_dalek::PublicKey::from(&Self::expanded(&expanded_key));
+        Self {
+            expanded_key,
+            public_key,
+        }
+    }
+
+    fn expanded(expanded_key: &[u8]) -> ed25519_dalek::ExpandedSecretKey {
+        ed25519_dalek::ExpandedSecretKey::from_bytes(expanded_key)
+            .expect("expanded keys are always 64 bytes")
+    }
+
+    fn sign(&self, message: &[u8]) -> Vec<u8> {
+        Self::expanded(&self.expanded_key)
+            .sign(message, &self.public_key)
+            .to_bytes()
+            .to_vec()
+    }
+}
+
+impl rcgen::RemoteKeyPair for IdentityCertKey {
+    fn public_key(&self) -> &[u8] {
+        self.public_key.as_bytes()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, rcgen::RcgenError> {
+        Ok(IdentityCertKey::sign(self, message))
+    }
+
+    fn algorithm(&self) -> &'static rcgen::SignatureAlgorithm {
+        &rcgen::PKCS_ED25519
+    }
+}
+
+impl rustls::sign::SigningKey for IdentityCertKey {
+    fn choose_scheme(
+        &self,
+        offered: &[rustls::SignatureScheme],
+    ) -> Option<Box<dyn rustls::sign::Signer>> {
+        if offered.contains(&rustls::SignatureScheme::ED25519) {
+            Some(Box::new(self.clone()))
+        } else {
+            None
+        }
+    }
+
+    fn algorithm(&self) -> rustls::SignatureAlgorithm {
+        rustls::SignatureAlgorithm::ED25519
+    }
+}
+
+impl rustls::sign::Signer for IdentityCertKey {
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, rustls::Error> {
+        Ok(IdentityCertKey::sign(self, message))
+    }
+
+    fn scheme(&self) -> rustls::SignatureScheme {
+        rustls::SignatureScheme::ED25519
+    }
+}
+
+/// Presents the local identity certificate, both as listener and as dialer.
+struct IdentityCertResolver(Arc<rustls::sign::CertifiedKey>);
+
+impl rustls::server::ResolvesServerCert for IdentityCertResolver {
+    fn resolve(
+        &self,
+        _client_hello: rustls::server::ClientHello,
+    ) -> Option<Arc<rustls::sign::CertifiedKey>> {
+        Some(self.0.clone())
+    }
+}
+
+impl rustls::client::ResolvesClientCert for IdentityCertResolver {
+    fn resolve(
+        &self,
+        _acceptable_issuers: &[&[u8]],
+        _sigschemes: &[rustls::SignatureScheme],
+    ) -> Option<Arc<rustls::sign::CertifiedKey>> {
+        Some(self.0.clone())
+    }
+
+    fn has_certs(&self) -> bool {
+        true
+    }
+}
+
+/// Returns the x25519 identity key of the ed25519 key in a peer certificate.
+fn certificate_identity_key(
+    certificate: &rustls::Certificate,
+) -> Result<x25519::PublicKey, rustls::Error> {
+    let invalid = |error: String| rustls::Error::InvalidCertificateData(error);
+    let (algorithm, key) = yasna::parse_der(&certificate.0, |reader| {
+        reader.read_sequence(|reader| {
+            let subject_key = reader.next().read_sequence(|reader| {
+                // Skip the version, serial number, signature, issuer, validity and subject
+                reader.read_optional(|reader| {
+                    reader.read_tagged(yasna::Tag::context(0), |reader| reader.read_der())
+                })?;
+                for _ in 0..5 {
+                    reader.next().read_der()?;
+                }
+                let subject_key = reader.next().read_sequence(|reader| {
+                    let algorithm = reader
+                        .next()
+                        .read_sequence(|reader| reader.next().read_oid())?;
+                    let (key, _unused_bits) = reader.next().read_bitvec_bytes()?;
+                    Ok((algorithm, key))
+                })?;
+                // Skip the unique identifiers and extensions
+                while reader.read_optional(|reader| reader.read_der())?.is_some() {}
+                Ok(subject_key)
+            })?;
+            reader.next().read_der()?;
+            reader.next().read_der()?;
+            Ok(subject_key)
+        })
+    })
+    .map_err(|error| invalid(error.to_string()))?;
+
+    if algorithm.components().as_slice() != ED25519_OID {
+        return Err(invalid(format!(
+            "Unexpected certificate key type: {}",
+            algorithm
+        )));
+    }
+    x25519::PublicKey::from_ed25519_public_bytes(&key).map_err(|error| invalid(error.to_string()))
+}
+
+/// Accepts the listener's certificate if its key is the dialed Noise IK key.
+struct ServerKeyVerifier {
+    server_key: x25519::PublicKey,
+}
+
+impl rustls::client::ServerCertVerifier for ServerKeyVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        let key = certificate_identity_key(end_entity)?;
+        if key != self.server_key {
+            return Err(rustls::Error::InvalidCertificateData(format!(
+                "Certificate key {} doesn't match the dialed key {}",
+                key, self.server_key
+            )));
+        }
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
+
+/// Accepts the dialer's certificate if its key passes the `ClientAuthorizer`.
+struct ClientKeyVerifier {
+    authorize_client: ClientAuthorizer,
+}
+
+impl rustls::server::ClientCertVerifier for ClientKeyVerifier {
+    fn client_auth_root_subjects(&self) -> Option<rustls::DistinguishedNames> {
+        Some(rustls::DistinguishedNames::new())
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _now: SystemTime,
+    ) -> Result<rustls::server::ClientCertVerified, rustls::Error> {
+        let key = certificate_identity_key(end_entity)?;
+        if !(self.authorize_client)(&key) {
+            return Err(rustls::Error::InvalidCertificateData(format!(
+                "Certificate key {} is not trusted",
+                key
+            )));
+        }
+        Ok(rustls::server::ClientCertVerified::assertion())
+    }
+}
+
+#[must_use = "streams do nothing unless polled"]
+pub struct TlsListenerStream {
+    inner: <TcpTransport as Transport>::Listener,
+    acceptor: TlsAcceptor,
+}
+
+impl Stream for TlsListenerStream {
+    type Item = io::Result<(
+        Pin<Box<dyn Future<Output = io::Result<TlsSocket>> + Send + 'static>>,
+        NetworkAddress,
+    )>;
+
+    fn poll_next(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<Option<Self::Item>> {
+        match ready!(self.inner.poll_next_unpin(context)) {
+            Some(Ok((inbound, dialer_addr))) => {
+                let acceptor = self.acceptor.clone();
+                let inbound: Pin<Box<dyn Future<Output = io::Result<TlsSocket>> + Send>> =
+                    Box::pin(async move {
+                        let socket = inbound.await?;
+                        let stream = acceptor.accept(socket).await?;
+                        Ok(TlsSocket::new(stream.into()))
+                    });
+                Poll::Ready(Some(Ok((inbound, dialer_addr))))
+            }
+            Some(Err(e)) => Poll::Ready(Some(Err(e))),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+/// A TLS session on top of a TCP connection
+pub struct TlsSocket {
+    inner: TlsStream<TcpSocket>,
+}
+
+impl TlsSocket {
+    fn new(inner: TlsStream<TcpSocket>) -> Self {
+        Self { inner }
+    }
+}
+
+impl fmt::Debug for TlsSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (socket, _session) = self.inner.get_ref();
+        f.debug_struct("TlsSocket").field("inner", socket).finish()
+    }
+}
+
+impl AsyncRead for TlsSocket {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_read(context, buf)
+    }
+}
+
+impl AsyncWrite for TlsSocket {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(context, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(context)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_close(context)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transport::{ConnectionOrigin, TransportExt};
+    use futures::{
+        future::{join, FutureExt},
+        io::{AsyncReadExt, AsyncWriteExt},
+    };
+
+    fn identity_key(seed: u8) -> x25519::PrivateKey {
+        x25519::PrivateKey::from([seed; 32])
+    }
+
+    fn tls_transport(
+        key: &x25519::PrivateKey,
+        authorize_client: ClientAuthorizer,
+    ) -> io::Result<TlsTransport> {
+        TlsTransport::new(
+            TcpTransport::default(),
+            PeerId::random(),
+            key,
+            authorize_client,
+        )
+    }
+
+    fn any_client() -> ClientAuthorizer {
+        Arc::new(|_: &x25519::PublicKey| true)
+    }
+
+    /// Dials `addr` and accepts the connection on `listener`
+    async fn connect(
+        dialer: &TlsTransport,
+        listener: TlsListenerStream,
+        addr: NetworkAddress,
+    ) -> (io::Result<TlsSocket>, io::Result<TlsSocket>) {
+        let dial = dialer.dial(PeerId::random(), addr).unwrap();
+        let accept = async move {
+            let (next, _listener) = listener.into_future().await;
+            let (incoming, _addr) = next.unwrap().unwrap();
+            incoming.await
+        };
+        join(dial, accept).await
+    }
+
+    #[test]
+    fn certificate_key_is_identity_key() -> Result<(), ::std::io::Error> {
+        let key = identity_key(1);
+        let t = tls_transport(&key, any_client())?;
+
+        let certificate = &t.cert_resolver.0.cert[0];
+        assert_eq!(
+            certificate_identity_key(certificate).unwrap(),
+            key.public_key()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn simple_listen_and_dial() -> Result<(), ::std::io::Error> {
+        let key = identity_key(1);
+        let t = tls_transport(&key, any_client())?.and_then(|mut out, _addr, origin| async move {
+            match origin {
+                ConnectionOrigin::Inbound => {
+                    out.write_all(b"Earth").await?;
+                    out.flush().await?;
+                    let mut buf = [0; 3];
+                    out.read_exact(&mut buf).await?;
+                    assert_eq!(&buf, b"Air");
+                }
+                ConnectionOrigin::Outbound => {
+                    let mut buf = [0; 5];
+                    out.read_exact(&mut buf).await?;
+                    assert_eq!(&buf, b"Earth");
+                    out.write_all(b"Air").await?;
+                    out.flush().await?;
+                }
+            }
+            Ok(())
+        });
+
+        let (listener, addr) = t.listen_on("/ip4/127.0.0.1/tcp/0/tls".parse().unwrap())?;
+        assert!(matches!(
+            addr.as_slice(),
+            [Protocol::Ip4(_), Protocol::Tcp(_), Protocol::Tls]
+        ));
+        let dial = t.dial(
+            PeerId::random(),
+            addr.append_prod_protos(key.public_key(), 0),
+        )?;
+        let listener = listener.into_future().then(|(maybe_result, _stream)| {
+            let (incoming, _addr) = maybe_result.unwrap().unwrap();
+            incoming.map(Result::unwrap)
+        });
+
+        let (outgoing, _incoming) = join(dial, listener).await;
+        assert!(outgoing.is_ok());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dial_rejects_wrong_key() -> Result<(), ::std::io::Error> {
+        let t = tls_transport(&identity_key(1), any_client())?;
+        let (listener, addr) = t.listen_on("/ip4/127.0.0.1/tcp/0/tls".parse().unwrap())?;
+
+        // The listener's certificate isn't for the dialed key
+        let addr = addr.append_prod_protos(identity_key(2).public_key(), 0);
+        let (outgoing, _incoming) = connect(&t, listener, addr).await;
+        assert!(outgoing.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn listen_rejects_untrusted_dialer() -> Result<(), ::std::io::Error> {
+        let listener_key = identity_key(1);
+        let trusted_key = identity_key(2).public_key();
+        let listener_transport = tls_transport(
+            &listener_key,
+            Arc::new(move |key: &x25519::PublicKey| *key == trusted_key),
+        )?;
+        let (listener, addr) =
+            listener_transport.listen_on("/ip4/127.0.0.1/tcp/0/tls".parse().unwrap())?;
+        let addr = addr.append_prod_protos(listener_key.public_key(), 0);
+
+        let untrusted_dialer = tls_transport(&identity_key(3), any_client())?;
+        let (_outgoing, incoming) = connect(&untrusted_dialer, listener, addr).await;
+        assert!(incoming.is_err());
+
+        let (listener, addr) =
+            listener_transport.listen_on("/ip4/127.0.0.1/tcp/0/tls".parse().unwrap())?;
+        let addr = addr.append_prod_protos(listener_key.public_key(), 0);
+
+        let trusted_dialer = tls_transport(&identity_key(2), any_client())?;
+        let (outgoing, incoming) = connect(&trusted_dialer, listener, addr).await;
+        assert!(outgoing.is_ok());
+        assert!(incoming.is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_multiaddrs() {
+        let key = identity_key(1);
+        let t = tls_transport(&key, any_client()).unwrap();
+
+        let result = t.listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap());
+        assert!(result.is_err());
+
+        let result = t.listen_on("/ip4/127.0.0.1/udp/0/tls".parse().unwrap());
+        assert!(result.is_err());
+
+        let peer_id = PeerId::random();
+        let result = t.dial(peer_id, "/memory/22".parse().unwrap());
+        assert!(result.is_err());
+
+        let result = t.dial(peer_id, "/ip4/127.0.0.1/tcp/22".parse().unwrap());
+        assert!(result.is_err());
+
+        // The listener's key is needed to check its certificate
+        let result = t.dial(peer_id, "/ip4/127.0.0.1/tcp/22/tls".parse().unwrap());
+        assert!(result.is_err());
+    }
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    application::types::{PeerError, PeerInfo},
+    application::types::{PeerError, PeerInfo, PeerMonitoringMetadata, ReachabilityStatus},
     transport::ConnectionMetadata,
 };
 use aptos_config::network_id::{NetworkId, PeerNetworkId};
@@ -20,6 +20,8 @@ use std::{
 #[derive(Debug)]
 pub struct PeerMetadataStorage {
     storage: HashMap<NetworkId, LockingHashMap<PeerId, PeerInfo>>,
+    /// Reachability of the local node on each network
+    reachability: HashMap<NetworkId, RwLock<ReachabilityStatus>>,
 }
 
 impl PeerMetadataStorage {
@@ -32,11 +34,15 @@ impl PeerMetadataStorage {
     pub fn new(network_ids: &[NetworkId]) -> Arc<PeerMetadataStorage> {
         let mut peer_metadata_storage = PeerMetadataStorage {
             storage: HashMap::new(),
+            reachability: HashMap::new(),
         };
         network_ids.iter().for_each(|network_id| {
             peer_metadata_storage
                 .storage
                 .insert(*network_id, LockingHashMap::new());
+            peer_metadata_storage
+                .reachability
+                .insert(*network_id, RwLock::new(ReachabilityStatus::Unknown));
         });
         Arc::new(peer_metadata_storage)
     }
@@ -45,6 +51,21 @@ impl PeerMetadataStorage {
         self.storage.keys().copied()
     }
 
+    /// The reachability of the local node on the given network
+    pub fn reachability(&self, network_id: NetworkId) -> ReachabilityStatus {
+        *self.get_reachability(network_id).read()
+    }
+
+    pub fn set_reachability(&self, network_id: NetworkId, status: ReachabilityStatus) {
+        *self.get_reachability(network_id).write() = status;
+    }
+
+    fn get_reachability(&self, network_id: NetworkId) -> &RwLock<ReachabilityStatus> {
+        self.reachability
+            .get(&network_id)
+            .unwrap_or_else(|| panic!("Unexpected network requested: {}", network_id))
+    }
+
     /// Handle common logic of getting a network
     fn get_network(&self, network_id: NetworkId) -> &LockingHashMap<AccountAddress, PeerInfo> {
         self.storage
@@ -113,6 +134,20 @@ impl PeerMetadataStorage {
         self.get_network(network_id).write_lock()
     }
 
+    /// Updates the monitoring metadata of a peer. Does nothing if the peer is unknown.
+    pub fn update_monitoring_metadata<F: FnOnce(&mut PeerMonitoringMetadata)>(
+        &self,
+        peer_network_id: PeerNetworkId,
+        modifier: F,
+    ) {
+        if let Some(peer_info) = self
+            .write_lock(peer_network_id.network_id())
+            .get_mut(&peer_network_id.peer_id())
+        {
+            modifier(&mut peer_info.monitoring_metadata);
+        }
+    }
+
     pub fn insert_connection(
         &self,
         network_id: NetworkId,
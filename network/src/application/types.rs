@@ -1,7 +1,12 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{protocols::wire::handshake::v1::ProtocolId, transport::ConnectionMetadata};
+use crate::{
+    protocols::{peer_monitoring::NodeInfo, wire::handshake::v1::ProtocolId},
+    transport::ConnectionMetadata,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// Errors related to the peer layer in the `NetworkInterface`
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -14,6 +19,7 @@ pub enum PeerError {
 pub struct PeerInfo {
     pub status: PeerState,
     pub active_connection: ConnectionMetadata,
+    pub monitoring_metadata: PeerMonitoringMetadata,
 }
 
 impl PeerInfo {
@@ -21,6 +27,7 @@ impl PeerInfo {
         PeerInfo {
             status: PeerState::Connected,
             active_connection: connection_metadata,
+            monitoring_metadata: PeerMonitoringMetadata::default(),
         }
     }
 
@@ -35,6 +42,15 @@ impl PeerInfo {
     }
 }
 
+/// Metadata about a peer, as collected by the peer monitoring service
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PeerMonitoringMetadata {
+    /// Moving average of the round trip latency of pings to the peer
+    pub average_ping_latency: Option<Duration>,
+    /// The node information last reported by the peer
+    pub node_info: Option<NodeInfo>,
+}
+
 /// The current state of a `Peer` at any one time
 /// TODO: Allow nodes that are unhealthy to stay connected
 #[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq)]
@@ -43,3 +59,19 @@ pub enum PeerState {
     Disconnecting,
     Disconnected,
 }
+
+/// Whether the node is reachable by other peers on its advertised address, as
+/// determined by the reachability checker
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReachabilityStatus {
+    Unknown,
+    Reachable,
+    Unreachable,
+}
+
+impl Default for ReachabilityStatus {
+    fn default() -> Self {
+        ReachabilityStatus::Unknown
+    }
+}
@@ -33,6 +33,7 @@ impl ConnectivityManagerBuilder {
         connection_reqs_tx: ConnectionRequestSender,
         connection_notifs_rx: conn_notifs_channel::Receiver,
         outbound_connection_limit: Option<usize>,
+        outbound_rotation_interval_ms: Option<u64>,
         mutual_authentication: bool,
     ) -> Self {
         let (conn_mgr_reqs_tx, conn_mgr_reqs_rx) = channel::new(
@@ -54,6 +55,7 @@ impl ConnectivityManagerBuilder {
                 ExponentialBackoff::from_millis(backoff_base).factor(1000),
                 Duration::from_millis(max_connection_delay_ms),
                 outbound_connection_limit,
+                outbound_rotation_interval_ms.map(Duration::from_millis),
                 mutual_authentication,
             )),
         }
@@ -9,16 +9,24 @@
 //! Consensus actor informs the ConnectivityManager of eligible nodes.
 //!
 //! Different discovery sources notify the ConnectivityManager of updates to
-//! peers' addresses. Currently, there are 2 discovery sources (ordered by
+//! peers' addresses. Currently, there are 4 discovery sources (ordered by
 //! decreasing dial priority, i.e., first is highest priority):
 //!
 //! 1. Onchain discovery protocol
-//! 2. Seed peers from config
+//! 2. Seed peers from a local file
+//! 3. Seed peers from DNS TXT records
+//! 4. Seed peers from config
 //!
 //! In other words, if a we have some addresses discovered via onchain discovery
 //! and some seed addresses from our local config, we will try the onchain
 //! discovery addresses first and the local seed addresses after.
 //!
+//! If an outbound connection limit is configured, the ConnectivityManager can
+//! also periodically rotate its outbound connections: the least healthy
+//! outbound peer (i.e., the one with the most lost connections) is
+//! disconnected, freeing up a slot for another eligible peer to be dialed.
+//! Peers which never lost a connection are healthy, and never rotated out.
+//!
 //! When dialing a peer with a given list of addresses, we attempt each address
 //! in order with a capped exponential backoff delay until we eventually connect
 //! to the peer. The backoff is capped since, for validators specifically, it is
@@ -29,6 +37,7 @@
 use crate::{
     counters,
     logging::NetworkSchema,
+    peer::DisconnectReason,
     peer_manager::{self, conn_notifs_channel, ConnectionRequestSender, PeerManagerError},
     transport::ConnectionMetadata,
 };
@@ -59,7 +68,7 @@ use std::{
     collections::{hash_map::Entry, HashMap, HashSet},
     fmt, mem,
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tokio_retry::strategy::jitter;
 
@@ -109,6 +118,15 @@ pub struct ConnectivityManager<TBackoff> {
     event_id: u32,
     /// A way to limit the number of connected peers by outgoing dials.
     outbound_connection_limit: Option<usize>,
+    /// The interval at which the least healthy outbound connection is rotated
+    /// out. If `None`, outbound connections are never rotated.
+    outbound_rotation_interval: Option<Duration>,
+    /// The last time an outbound connection was rotated out.
+    last_outbound_rotation: Instant,
+    /// The number of lost connections per discovered peer, disconnects we
+    /// requested aren't counted. Used as a health score to prioritize dials and
+    /// pick outbound connections to rotate out.
+    lost_connections: HashMap<PeerId, u32>,
     /// Random for shuffling which peers will be dialed
     rng: SmallRng,
     /// Whether we are using mutual authentication or not
@@ -122,6 +140,8 @@ pub struct ConnectivityManager<TBackoff> {
 pub enum DiscoverySource {
     OnChainValidatorSet,
     File,
+    Dns,
+    Registrar,
     Config,
 }
 
@@ -139,6 +159,8 @@ impl fmt::Display for DiscoverySource {
             match self {
                 DiscoverySource::OnChainValidatorSet => "OnChainValidatorSet",
                 DiscoverySource::File => "File",
+                DiscoverySource::Dns => "Dns",
+                DiscoverySource::Registrar => "Registrar",
                 DiscoverySource::Config => "Config",
             }
         )
@@ -262,6 +284,7 @@ where
         backoff_strategy: TBackoff,
         max_delay: Duration,
         outbound_connection_limit: Option<usize>,
+        outbound_rotation_interval: Option<Duration>,
         mutual_authentication: bool,
     ) -> Self {
         assert!(
@@ -275,6 +298,7 @@ where
             "{} Initialized connectivity manager", network_context
         );
 
+        let last_outbound_rotation = time_service.now();
         let mut connmgr = Self {
             network_context,
             time_service,
@@ -291,6 +315,9 @@ where
             max_delay,
             event_id: 0,
             outbound_connection_limit,
+            outbound_rotation_interval,
+            last_outbound_rotation,
+            lost_connections: HashMap::new(),
             rng: SmallRng::from_entropy(),
             mutual_authentication,
         };
@@ -354,17 +381,28 @@ where
         );
     }
 
-    /// Disconnect from all peers that are no longer eligible.
+    /// Disconnect from all peers that are no longer eligible, or whose connection was
+    /// authenticated with a key that is no longer trusted.
     ///
     /// For instance, a validator might leave the validator set after a
     /// reconfiguration. If we are currently connected to this validator, calling
-    /// this function will close our connection to it.
+    /// this function will close our connection to it. Likewise, when a validator's
+    /// network key is rotated on-chain, the connection made with its previous key is
+    /// closed and the validator is dialed with the new one. Nodes only load their own
+    /// identity key at startup, so these dials succeed once the rotated validator
+    /// restarted with its new key.
     async fn close_stale_connections(&mut self) {
         let eligible = self.eligible.read().clone();
         let stale_connections: Vec<_> = self
             .connected
             .iter()
-            .filter(|(peer_id, _)| !eligible.contains_key(peer_id))
+            .filter(|(peer_id, metadata)| match eligible.get(peer_id) {
+                Some(peer) => metadata
+                    .addr
+                    .find_noise_proto()
+                    .map_or(false, |pubkey| !peer.keys.contains(&pubkey)),
+                None => true,
+            })
             .filter_map(|(peer_id, metadata)| {
                 // If we're using server only auth, we need to not evict unknown peers
                 // TODO: We should prevent `Unknown` from discovery sources
@@ -436,12 +474,13 @@ where
         }
     }
 
-    fn choose_peers_to_dial(&mut self) -> Vec<(PeerId, DiscoveredPeer)> {
+    /// Returns all discovered peers that are eligible to be dialed, but are
+    /// neither connected nor queued for dialing.
+    fn undialed_eligible_peers(&self) -> Vec<(&PeerId, &DiscoveredPeer)> {
         let network_id = self.network_context.network_id();
         let role = self.network_context.role();
         let roles_to_dial = network_id.upstream_roles(&role);
-        let mut eligible: Vec<_> = self
-            .discovered_peers
+        self.discovered_peers
             .0
             .iter()
             .filter(|(peer_id, peer)| {
@@ -450,12 +489,25 @@ where
                 && !self.dial_queue.contains_key(peer_id) // There is no pending dial to this node.
                 && roles_to_dial.contains(&peer.role) // We can dial this role
             })
+            .collect()
+    }
+
+    fn choose_peers_to_dial(&mut self) -> Vec<(PeerId, DiscoveredPeer)> {
+        let mut eligible: Vec<_> = self
+            .undialed_eligible_peers()
+            .into_iter()
+            .map(|(peer_id, peer)| (*peer_id, peer.clone()))
             .collect();
 
-        // Prioritize by PeerRole
+        // Prioritize by PeerRole, then by the number of lost connections
         // Shuffle so we don't get stuck on certain peers
         eligible.shuffle(&mut self.rng);
-        eligible.sort_by(|(_, peer), (_, other)| peer.role.cmp(&other.role));
+        eligible.sort_by_key(|(peer_id, peer)| {
+            (
+                peer.role,
+                self.lost_connections.get(peer_id).copied().unwrap_or(0),
+            )
+        });
 
         let num_eligible = eligible.len();
 
@@ -464,11 +516,7 @@ where
         // It enforces that a full node cannot have more outgoing connections than `connection_limit`
         // including in flight dials.
         let to_connect = if let Some(conn_limit) = self.outbound_connection_limit {
-            let outbound_connections = self
-                .connected
-                .iter()
-                .filter(|(_, metadata)| metadata.origin == ConnectionOrigin::Outbound)
-                .count();
+            let outbound_connections = self.outbound_connections().count();
             min(
                 conn_limit
                     .saturating_sub(outbound_connections.saturating_add(self.dial_queue.len())),
@@ -478,11 +526,74 @@ where
             num_eligible
         };
 
+        eligible.truncate(to_connect);
         eligible
+    }
+
+    /// Returns the peer ids of all outbound connections.
+    fn outbound_connections(&self) -> impl Iterator<Item = &PeerId> {
+        self.connected
             .iter()
-            .take(to_connect)
-            .map(|(peer_id, peer)| (**peer_id, (*peer).clone()))
-            .collect()
+            .filter(|(_, metadata)| metadata.origin == ConnectionOrigin::Outbound)
+            .map(|(peer_id, _)| peer_id)
+    }
+
+    /// Disconnects from the least healthy outbound peer if the rotation interval
+    /// has elapsed, the outbound connection limit has been reached, the peer lost
+    /// connections and there are other eligible peers waiting to be dialed. The
+    /// freed up slot is filled by the next call to `dial_eligible_peers`.
+    async fn rotate_outbound_connection(&mut self) {
+        let (rotation_interval, conn_limit) = match (
+            self.outbound_rotation_interval,
+            self.outbound_connection_limit,
+        ) {
+            (Some(rotation_interval), Some(conn_limit)) => (rotation_interval, conn_limit),
+            _ => return,
+        };
+
+        let now = self.time_service.now();
+        if now.duration_since(self.last_outbound_rotation) < rotation_interval {
+            return;
+        }
+        self.last_outbound_rotation = now;
+
+        let num_outbound_connections = self.outbound_connections().count();
+        if num_outbound_connections.saturating_add(self.dial_queue.len()) < conn_limit
+            || self.undialed_eligible_peers().is_empty()
+        {
+            return;
+        }
+
+        // Choose the outbound peer with the most lost connections, if any
+        let peer_to_rotate = self
+            .outbound_connections()
+            .filter_map(|peer_id| {
+                let lost_connections = self.lost_connections.get(peer_id).copied()?;
+                Some((lost_connections, *peer_id))
+            })
+            .max();
+        if let Some((_, peer_id)) = peer_to_rotate {
+            info!(
+                NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                "{} Rotating out outbound connection to peer {}",
+                self.network_context,
+                peer_id.short_str()
+            );
+            match self.connection_reqs_tx.disconnect_peer(peer_id).await {
+                Ok(()) => {
+                    counters::disconnects(&self.network_context, counters::ROTATED_LABEL).inc()
+                }
+                Err(e) => info!(
+                    NetworkSchema::new(&self.network_context)
+                        .remote_peer(&peer_id),
+                    error = %e,
+                    "{} Failed to rotate out connection to peer {} : {}",
+                    self.network_context,
+                    peer_id.short_str(),
+                    e
+                ),
+            }
+        }
     }
 
     fn queue_dial_peer<'a>(
@@ -586,6 +697,8 @@ where
         self.cancel_stale_dials().await;
         // Disconnect from connected peers that are no longer eligible.
         self.close_stale_connections().await;
+        // Rotate out the least healthy outbound connection, if required.
+        self.rotate_outbound_connection().await;
         // Dial peers which are eligible but are neither connected nor queued for dialing in the
         // future.
         self.dial_eligible_peers(pending_dials);
@@ -657,7 +770,9 @@ where
 
         // Remove peers that no longer have state
         for peer_id in peers_to_check_remove {
-            self.discovered_peers.try_remove_empty(&peer_id);
+            if self.discovered_peers.try_remove_empty(&peer_id) {
+                self.lost_connections.remove(&peer_id);
+            }
         }
 
         // Make updates to the peers accordingly
@@ -748,10 +863,16 @@ where
                 self.dial_states.remove(&peer_id);
                 self.dial_queue.remove(&peer_id);
             }
-            peer_manager::ConnectionNotification::LostPeer(metadata, _context, _reason) => {
+            peer_manager::ConnectionNotification::LostPeer(metadata, _context, reason) => {
                 let peer_id = metadata.remote_peer_id;
                 if let Some(stored_metadata) = self.connected.get(&peer_id) {
                     // Remove node from connected peers list.
+                    if reason == DisconnectReason::ConnectionLost {
+                        if self.discovered_peers.0.contains_key(&peer_id) {
+                            *self.lost_connections.entry(peer_id).or_insert(0) += 1;
+                        }
+                        counters::disconnects(&self.network_context, counters::LOST_LABEL).inc();
+                    }
 
                     counters::peer_connected(&self.network_context, &peer_id, 0);
 
@@ -83,6 +83,13 @@ struct TestHarness {
 
 impl TestHarness {
     fn new(seeds: PeerSet) -> (Self, ConnectivityManager<FixedInterval>) {
+        Self::new_with_rotation_interval(seeds, None)
+    }
+
+    fn new_with_rotation_interval(
+        seeds: PeerSet,
+        outbound_rotation_interval: Option<Duration>,
+    ) -> (Self, ConnectivityManager<FixedInterval>) {
         let network_context = NetworkContext::mock();
         let time_service = TimeService::mock();
         let (connection_reqs_tx, connection_reqs_rx) =
@@ -103,6 +110,7 @@ impl TestHarness {
             FixedInterval::new(CONNECTION_DELAY),
             MAX_CONNECTION_DELAY,
             Some(MAX_TEST_CONNECTIONS),
+            outbound_rotation_interval,
             true, /* mutual_authentication */
         );
         let mock = Self {
@@ -168,6 +176,16 @@ impl TestHarness {
     }
 
     async fn send_lost_peer_await_delivery(&mut self, peer_id: PeerId, address: NetworkAddress) {
+        self.send_disconnect_await_delivery(peer_id, address, DisconnectReason::ConnectionLost)
+            .await;
+    }
+
+    async fn send_disconnect_await_delivery(
+        &mut self,
+        peer_id: PeerId,
+        address: NetworkAddress,
+        reason: DisconnectReason,
+    ) {
         info!(
             "Sending LostPeer notification for peer: {}",
             peer_id.short_str()
@@ -181,7 +199,7 @@ impl TestHarness {
         let notif = peer_manager::ConnectionNotification::LostPeer(
             metadata,
             NetworkContext::mock(),
-            DisconnectReason::ConnectionLost,
+            reason,
         );
         self.send_notification_await_delivery(peer_id, notif).await;
     }
@@ -217,7 +235,8 @@ impl TestHarness {
             ),
         }
         if success {
-            self.send_lost_peer_await_delivery(peer_id, address).await;
+            self.send_disconnect_await_delivery(peer_id, address, DisconnectReason::Requested)
+                .await;
         }
     }
 
@@ -402,6 +421,57 @@ fn addr_change() {
     block_on(future::join(conn_mgr.start(), test));
 }
 
+#[test]
+fn key_rotation() {
+    let (other_peer_id, other_peer, other_pubkey, other_addr) = test_peer(0);
+    let (mut mock, conn_mgr) = TestHarness::new(HashMap::new());
+
+    let test = async move {
+        // Sending pubkey & address of other peer
+        let update = hashmap! {other_peer_id => other_peer};
+        mock.send_update_discovered_peers(DiscoverySource::OnChainValidatorSet, update)
+            .await;
+
+        // Peer manager receives a request to connect to the other peer.
+        mock.trigger_connectivity_check().await;
+        mock.trigger_pending_dials().await;
+        mock.expect_one_dial_success(other_peer_id, other_addr.clone())
+            .await;
+
+        // The other peer rotates its network key on-chain
+        let new_pubkey =
+            x25519::PrivateKey::generate(&mut StdRng::from_seed([1u8; 32])).public_key();
+        assert_ne!(new_pubkey, other_pubkey);
+        let new_addr = network_address_with_pubkey(DEFAULT_BASE_ADDR, new_pubkey);
+        let new_peer = Peer::new(
+            vec![new_addr.clone()],
+            hashset! { new_pubkey },
+            PeerRole::Validator,
+        );
+        let update = hashmap! {other_peer_id => new_peer};
+        mock.send_update_discovered_peers(DiscoverySource::OnChainValidatorSet, update)
+            .await;
+
+        // The connection authenticated with the previous key is closed
+        mock.trigger_connectivity_check().await;
+        mock.expect_disconnect_success(other_peer_id, other_addr)
+            .await;
+        assert_eq!(0, mock.get_connected_size().await);
+
+        // We should receive dial request to other peer with the new key
+        mock.trigger_connectivity_check().await;
+        mock.trigger_pending_dials().await;
+        mock.expect_one_dial_success(other_peer_id, new_addr.clone())
+            .await;
+
+        // The new connection is kept
+        mock.trigger_connectivity_check().await;
+        assert_eq!(1, mock.get_connected_size().await);
+        assert_eq!(0, mock.get_dial_queue_size().await);
+    };
+    block_on(future::join(conn_mgr.start(), test));
+}
+
 #[test]
 fn lost_connection() {
     let (other_peer_id, other_peer, _, other_addr) = test_peer(0);
@@ -739,6 +809,62 @@ fn public_connection_limit() {
     block_on(future::join(conn_mgr.start(), test));
 }
 
+#[test]
+fn outbound_connection_rotation() {
+    let mut seeds = HashMap::new();
+    let mut addrs = HashMap::new();
+    for i in 0..=MAX_TEST_CONNECTIONS {
+        let (peer_id, peer, _, addr) = test_peer(i);
+        seeds.insert(peer_id, peer);
+        addrs.insert(peer_id, addr);
+    }
+
+    let (mut mock, conn_mgr) =
+        TestHarness::new_with_rotation_interval(seeds, Some(CONNECTIVITY_CHECK_INTERVAL));
+
+    let test = async move {
+        // Should receive MAX_TEST_CONNECTIONS dials
+        mock.trigger_connectivity_check().await;
+        mock.trigger_pending_dials().await;
+        let mut dialed_peers = vec![];
+        for _ in 0..MAX_TEST_CONNECTIONS {
+            let (peer_id, _) = mock.expect_one_dial_inner(Ok(())).await;
+            dialed_peers.push(peer_id);
+        }
+        mock.wait_until_empty_dial_queue().await;
+        let undialed_peer = *addrs
+            .keys()
+            .find(|peer_id| !dialed_peers.contains(peer_id))
+            .unwrap();
+
+        // Healthy peers aren't rotated out
+        mock.trigger_connectivity_check().await;
+        assert_eq!(MAX_TEST_CONNECTIONS, mock.get_connected_size().await);
+
+        // Lose the connection to a peer and reconnect to it
+        let unhealthy_peer = dialed_peers[0];
+        let unhealthy_addr = addrs.get(&unhealthy_peer).unwrap().clone();
+        mock.send_lost_peer_await_delivery(unhealthy_peer, unhealthy_addr.clone())
+            .await;
+        mock.send_new_peer_await_delivery(unhealthy_peer, unhealthy_peer, unhealthy_addr.clone())
+            .await;
+        assert_eq!(MAX_TEST_CONNECTIONS, mock.get_connected_size().await);
+
+        // The unhealthy peer should be rotated out
+        mock.trigger_connectivity_check().await;
+        mock.expect_disconnect_success(unhealthy_peer, unhealthy_addr)
+            .await;
+
+        // The freed up slot should be filled by the undialed peer
+        mock.trigger_connectivity_check().await;
+        mock.trigger_pending_dials().await;
+        mock.expect_one_dial_success(undialed_peer, addrs.get(&undialed_peer).unwrap().clone())
+            .await;
+        assert_eq!(MAX_TEST_CONNECTIONS, mock.get_connected_size().await);
+    };
+    block_on(future::join(conn_mgr.start(), test));
+}
+
 #[test]
 fn basic_update_discovered_peers() {
     let mut rng = StdRng::from_seed(TEST_SEED);
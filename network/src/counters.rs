@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::protocols::wire::handshake::v1::ProtocolId;
-use aptos_config::network_id::NetworkContext;
+use aptos_config::{config::PeerRole, network_id::NetworkContext};
 use aptos_metrics::{
     register_histogram_vec, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
     Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
@@ -11,6 +11,7 @@ use aptos_types::PeerId;
 use netcore::transport::ConnectionOrigin;
 use once_cell::sync::Lazy;
 use short_hex_str::AsShortHexStr;
+use std::time::Duration;
 
 // some type labels
 pub const REQUEST_LABEL: &str = "request";
@@ -85,6 +86,54 @@ pub fn peer_connected(network_context: &NetworkContext, remote_peer_id: &PeerId,
     }
 }
 
+/// Reasons of the disconnects counted by the connectivity manager
+pub const LOST_LABEL: &str = "lost";
+pub const ROTATED_LABEL: &str = "rotated";
+
+pub static DIEM_NETWORK_DISCONNECTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_disconnects",
+        "Number of connections lost, or rotated out by the connectivity manager",
+        &["role_type", "network_id", "peer_id", "reason"]
+    )
+    .unwrap()
+});
+
+pub fn disconnects(network_context: &NetworkContext, reason: &'static str) -> IntCounter {
+    DIEM_NETWORK_DISCONNECTS.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        reason,
+    ])
+}
+
+pub static DIEM_NETWORK_PEER_PING_LATENCY_MS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_network_peer_ping_latency_ms",
+        "Moving average of the round trip latency of the peer monitoring pings to a peer",
+        &["role_type", "network_id", "peer_id", "remote_peer_id"]
+    )
+    .unwrap()
+});
+
+pub fn peer_ping_latency(
+    network_context: &NetworkContext,
+    remote_peer_id: &PeerId,
+    latency: Duration,
+) {
+    if network_context.network_id().is_validator_network() {
+        DIEM_NETWORK_PEER_PING_LATENCY_MS
+            .with_label_values(&[
+                network_context.role().as_str(),
+                network_context.network_id().as_str(),
+                network_context.peer_id().short_str().as_str(),
+                remote_peer_id.short_str().as_str(),
+            ])
+            .set(latency.as_millis() as i64)
+    }
+}
+
 /// Increments the counter based on `NetworkContext`
 pub fn inc_by_with_context(
     counter: &IntCounterVec,
@@ -303,6 +352,172 @@ pub fn direct_send_bytes(
     ])
 }
 
+/// Bytes received from and sent to peers, by the role of the remote peer.
+pub static DIEM_NETWORK_PEER_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_peer_bytes",
+        "Number of bytes transferred with peers by peer role",
+        &["role_type", "network_id", "peer_id", "peer_role", "state"]
+    )
+    .unwrap()
+});
+
+pub fn peer_bytes(
+    network_context: &NetworkContext,
+    peer_role: PeerRole,
+    state_label: &'static str,
+) -> IntCounter {
+    DIEM_NETWORK_PEER_BYTES.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        peer_role.as_str(),
+        state_label,
+    ])
+}
+
+/// Reachability of the local node: 1 if reachable, 0 if unreachable and -1 if unknown.
+pub static DIEM_NETWORK_REACHABILITY: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_network_reachability",
+        "Reachability of the local node on its advertised address",
+        &["role_type", "network_id", "peer_id"]
+    )
+    .unwrap()
+});
+
+pub fn reachability(network_context: &NetworkContext) -> IntGauge {
+    DIEM_NETWORK_REACHABILITY.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+    ])
+}
+
+/// Counters of the dial back probes sent to (outbound) and served for (inbound) peers.
+pub static DIEM_NETWORK_REACHABILITY_PROBES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_reachability_probes",
+        "Number of reachability dial back probes by direction and result",
+        &["role_type", "network_id", "peer_id", "direction", "result"]
+    )
+    .unwrap()
+});
+
+pub fn reachability_probes(
+    network_context: &NetworkContext,
+    direction_label: &'static str,
+    result_label: &'static str,
+) -> IntCounter {
+    DIEM_NETWORK_REACHABILITY_PROBES.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        direction_label,
+        result_label,
+    ])
+}
+
+/// Counters of the inbound Noise handshakes that were rate limited, or that had to
+/// carry a cookie.
+pub static DIEM_NETWORK_NOISE_HANDSHAKE_DEFENSES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_noise_handshake_defenses",
+        "Number of inbound Noise handshakes rate limited or challenged with a cookie",
+        &["role_type", "network_id", "peer_id", "event"]
+    )
+    .unwrap()
+});
+
+pub fn noise_handshake_defenses(
+    network_context: &NetworkContext,
+    event_label: &'static str,
+) -> IntCounter {
+    DIEM_NETWORK_NOISE_HANDSHAKE_DEFENSES.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        event_label,
+    ])
+}
+
+/// Counters of the peer monitoring pings sent to (outbound) and served for (inbound) peers.
+pub static DIEM_NETWORK_PEER_MONITORING_PINGS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_peer_monitoring_pings",
+        "Number of peer monitoring pings by direction and result",
+        &["role_type", "network_id", "peer_id", "direction", "result"]
+    )
+    .unwrap()
+});
+
+pub fn peer_monitoring_pings(
+    network_context: &NetworkContext,
+    direction_label: &'static str,
+    result_label: &'static str,
+) -> IntCounter {
+    DIEM_NETWORK_PEER_MONITORING_PINGS.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        direction_label,
+        result_label,
+    ])
+}
+
+/// Counters of the registrations sent to (outbound) and served for (inbound) peers.
+pub static DIEM_NETWORK_REGISTRAR_REGISTRATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_registrar_registrations",
+        "Number of registrations with registrars by direction and result",
+        &["role_type", "network_id", "peer_id", "direction", "result"]
+    )
+    .unwrap()
+});
+
+pub fn registrar_registrations(
+    network_context: &NetworkContext,
+    direction_label: &'static str,
+    result_label: &'static str,
+) -> IntCounter {
+    DIEM_NETWORK_REGISTRAR_REGISTRATIONS.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        direction_label,
+        result_label,
+    ])
+}
+
+/// Number of peers registered with the local node, as a registrar.
+pub static DIEM_NETWORK_REGISTRAR_REGISTERED_PEERS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_network_registrar_registered_peers",
+        "Number of peers registered with the local node",
+        &["role_type", "network_id", "peer_id"]
+    )
+    .unwrap()
+});
+
+pub fn registrar_registered_peers(network_context: &NetworkContext) -> IntGauge {
+    DIEM_NETWORK_REGISTRAR_REGISTERED_PEERS.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+    ])
+}
+
+/// Counters(enqueued,dequeued,dropped) related to the per-protocol inbound network
+/// notification queues for RPCs and DirectSends.
+pub static PENDING_INBOUND_PROTOCOL_NOTIFICATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_pending_inbound_protocol_notifications",
+        "Number of pending inbound network notifications by protocol and state",
+        &["protocol_id", "state"]
+    )
+    .unwrap()
+});
+
 /// Counters(queued,dequeued,dropped) related to inbound network notifications for RPCs and
 /// DirectSends.
 pub static PENDING_NETWORK_NOTIFICATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
@@ -2,16 +2,23 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    constants,
     protocols::{
         identity::exchange_handshake,
-        wire::handshake::v1::{HandshakeMsg, MessagingProtocolVersion, ProtocolIdSet},
+        wire::{
+            compression,
+            handshake::v1::{HandshakeMsg, MessagingProtocolVersion, ProtocolId, ProtocolIdSet},
+            messaging::v1::{NetworkMessage, NetworkMessageStream, ReadError, RequestId},
+        },
     },
     testutils::fake_socket::ReadOnlyTestSocketVec,
 };
 use aptos_config::network_id::NetworkId;
 use aptos_types::chain_id::ChainId;
-use futures::executor::block_on;
+use futures::{executor::block_on, stream::StreamExt};
 use proptest::{collection::btree_map, prelude::*};
+use serde::de::DeserializeOwned;
+use std::{collections::HashMap, convert::TryInto};
 
 //
 // Handshake Protocol Fuzzer
@@ -94,6 +101,120 @@ prop_compose! {
   }
 }
 
+//
+// NetworkMessage Framing Fuzzer
+// =============================
+//
+
+/// Fuzzing the framing and deserialization of inbound `NetworkMessage`s. Unlike
+/// the `Peer` fuzzer, this skips the actor entirely and only drains the stream.
+pub fn fuzz_network_message_stream(data: &[u8]) {
+    let socket = ReadOnlyTestSocketVec::new(data.to_vec());
+    let mut stream = NetworkMessageStream::new(socket, constants::MAX_FRAME_SIZE, None);
+
+    block_on(async move {
+        while let Some(result) = stream.next().await {
+            // Frames that fail to deserialize are skipped, but IO errors are terminal
+            if let Err(ReadError::IoError(_)) = result {
+                break;
+            }
+        }
+    });
+}
+
+//
+// Protocol Payload Fuzzer
+// =======================
+//
+
+/// Encodes an over-the-wire application payload as input for `fuzz_protocol_payload`:
+/// the protocol id byte, followed by the raw payload.
+pub fn protocol_payload_input(protocol_id: ProtocolId, payload: &[u8]) -> Vec<u8> {
+    let mut input = Vec::with_capacity(payload.len() + 1);
+    input.push(protocol_id as u8);
+    input.extend_from_slice(payload);
+    input
+}
+
+/// Fuzzing the deserialization of application payloads received over-the-wire,
+/// including the decompression step of compressed protocols. The first byte of
+/// the data selects the protocol, and inputs for any protocol other than the
+/// given `protocol_ids` are ignored.
+pub fn fuzz_protocol_payload<T: DeserializeOwned>(protocol_ids: &[ProtocolId], data: &[u8]) {
+    let (protocol_id, payload) = match data.split_first() {
+        Some((protocol_id, payload)) => match bcs::from_bytes::<ProtocolId>(&[*protocol_id]) {
+            Ok(protocol_id) if protocol_ids.contains(&protocol_id) => (protocol_id, payload),
+            _ => return,
+        },
+        None => return,
+    };
+
+    match protocol_id.decompressed() {
+        Some(decompressed_protocol_id) => {
+            if let Ok(payload) = compression::decompress(payload) {
+                let _ = decompressed_protocol_id.from_bytes::<T>(&payload);
+            }
+        }
+        None => {
+            let _ = protocol_id.from_bytes::<T>(payload);
+        }
+    }
+}
+
+//
+// Recorded Traffic Corpus
+// =======================
+//
+
+/// Fuzzer inputs extracted from a recording of inbound network traffic.
+#[derive(Debug, Default)]
+pub struct RecordedTrafficCorpus {
+    /// The individual length-prefixed `NetworkMessage` frames
+    pub frames: Vec<Vec<u8>>,
+    /// The application payloads, encoded with `protocol_payload_input`
+    pub payloads: Vec<(ProtocolId, Vec<u8>)>,
+}
+
+/// Splits a recording of inbound traffic into fuzzer inputs. The recording is the
+/// raw byte stream read from a connection once the handshake has completed, i.e.,
+/// a sequence of length-prefixed `NetworkMessage` frames. RPC responses are
+/// attributed to the protocol of the matching request within the recording, and
+/// the recording is truncated at the first incomplete or oversized frame.
+pub fn corpus_from_recorded_traffic(mut recording: &[u8]) -> RecordedTrafficCorpus {
+    let mut corpus = RecordedTrafficCorpus::default();
+    let mut pending_rpcs: HashMap<RequestId, ProtocolId> = HashMap::new();
+
+    while recording.len() >= 4 {
+        let frame_len = u32::from_be_bytes(recording[..4].try_into().unwrap()) as usize;
+        if frame_len > constants::MAX_FRAME_SIZE || recording.len() < 4 + frame_len {
+            break;
+        }
+        let (frame, remaining) = recording.split_at(4 + frame_len);
+        recording = remaining;
+        corpus.frames.push(frame.to_vec());
+
+        let payload = match bcs::from_bytes::<NetworkMessage>(&frame[4..]) {
+            Ok(NetworkMessage::DirectSendMsg(message)) => {
+                Some((message.protocol_id, message.raw_msg))
+            }
+            Ok(NetworkMessage::RpcRequest(request)) => {
+                pending_rpcs.insert(request.request_id, request.protocol_id);
+                Some((request.protocol_id, request.raw_request))
+            }
+            Ok(NetworkMessage::RpcResponse(response)) => pending_rpcs
+                .remove(&response.request_id)
+                .map(|protocol_id| (protocol_id, response.raw_response)),
+            Ok(NetworkMessage::Error(_)) | Err(_) => None,
+        };
+        if let Some((protocol_id, payload)) = payload {
+            corpus
+                .payloads
+                .push((protocol_id, protocol_payload_input(protocol_id, &payload)));
+        }
+    }
+    corpus
+}
+
 proptest! {
   #[test]
   fn test_handshake_exchange_fuzzer((self_handshake, remote_handshake) in exchange_handshake_input()) {
@@ -104,4 +225,19 @@ proptest! {
   fn test_handshake_negotiation_fuzzer((self_handshake, remote_handshake) in perform_handshake_input()) {
     fuzz_network_handshake_protocol_negotiation(&self_handshake, &remote_handshake);
   }
+
+  #[test]
+  fn test_recorded_traffic_corpus(network_msgs in proptest::collection::vec(any::<NetworkMessage>(), 1..10)) {
+    let mut recording = Vec::new();
+    for network_msg in &network_msgs {
+      let frame = bcs::to_bytes(network_msg).unwrap();
+      recording.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+      recording.extend_from_slice(&frame);
+    }
+
+    let corpus = corpus_from_recorded_traffic(&recording);
+    prop_assert_eq!(corpus.frames.len(), network_msgs.len());
+    prop_assert_eq!(corpus.frames.concat(), recording.clone());
+    fuzz_network_message_stream(&recording);
+  }
 }
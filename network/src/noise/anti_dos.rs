@@ -0,0 +1,276 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The anti_dos module protects the server side of the handshake from connection-exhaustion
+//! attacks, where a flood of handshake messages forces a peer into performing Diffie-Hellman key
+//! exchange operations.
+//!
+//! - Handshakes are rate limited per IP prefix (/24 for IPv4, /48 for IPv6), before anything is
+//!   read from the connection, so that a single network can't monopolize the server.
+//! - When too many handshakes are in progress, the server only processes client messages
+//!   carrying a valid cookie, and replies to the others with a cookie. A cookie is a MAC of the
+//!   client's IP address under a secret rotated every [`COOKIE_SECRET_LIFETIME`], and the server
+//!   closes the connection right after sending it, so it keeps no state per challenged client.
+//!   The client dials again with the cookie, proving that it receives traffic at its address,
+//!   before the server performs any Diffie-Hellman computation.
+
+use aptos_config::config::HandshakeRateLimitConfig;
+use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
+use aptos_rate_limiter::rate_limit::TokenBucketRateLimiter;
+use rand::RngCore;
+use std::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+/// The size of a cookie, appended to every client handshake message. A client without a cookie
+/// sends zeroes.
+pub const COOKIE_SIZE: usize = 16;
+
+/// Cookies are valid for at least this long, and at most twice as long.
+pub const COOKIE_SECRET_LIFETIME: Duration = Duration::from_secs(120);
+
+const COOKIE_SECRET_SIZE: usize = 32;
+
+/// The network an IP address belongs to, handshakes are rate limited per network rather than per
+/// address, as attackers often control many addresses of the same network.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct IpPrefix(IpAddr);
+
+impl IpPrefix {
+    pub fn new(ip: IpAddr) -> Self {
+        match ip {
+            IpAddr::V4(ip) => {
+                let [a, b, c, _] = ip.octets();
+                IpPrefix(IpAddr::V4(Ipv4Addr::new(a, b, c, 0)))
+            }
+            IpAddr::V6(ip) => {
+                let [a, b, c, ..] = ip.segments();
+                IpPrefix(IpAddr::V6(Ipv6Addr::new(a, b, c, 0, 0, 0, 0, 0)))
+            }
+        }
+    }
+}
+
+impl fmt::Display for IpPrefix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            IpAddr::V4(ip) => write!(f, "{}/24", ip),
+            IpAddr::V6(ip) => write!(f, "{}/48", ip),
+        }
+    }
+}
+
+struct CookieSecrets {
+    current: [u8; COOKIE_SECRET_SIZE],
+    previous: [u8; COOKIE_SECRET_SIZE],
+    rotated_at: Instant,
+}
+
+impl CookieSecrets {
+    fn new() -> Self {
+        Self {
+            current: random_secret(),
+            previous: random_secret(),
+            rotated_at: Instant::now(),
+        }
+    }
+
+    /// Returns the current and previous secrets, rotating them if they are too old
+    fn get(&mut self) -> ([u8; COOKIE_SECRET_SIZE], [u8; COOKIE_SECRET_SIZE]) {
+        let elapsed = self.rotated_at.elapsed();
+        if elapsed >= COOKIE_SECRET_LIFETIME * 2 {
+            *self = Self::new();
+        } else if elapsed >= COOKIE_SECRET_LIFETIME {
+            self.previous = self.current;
+            self.current = random_secret();
+            self.rotated_at = Instant::now();
+        }
+        (self.current, self.previous)
+    }
+}
+
+fn random_secret() -> [u8; COOKIE_SECRET_SIZE] {
+    let mut secret = [0u8; COOKIE_SECRET_SIZE];
+    rand::rngs::OsRng.fill_bytes(&mut secret);
+    secret
+}
+
+fn cookie_for(secret: &[u8; COOKIE_SECRET_SIZE], ip: IpAddr) -> [u8; COOKIE_SIZE] {
+    let mut buffer = secret.to_vec();
+    match ip {
+        IpAddr::V4(ip) => buffer.extend_from_slice(&ip.octets()),
+        IpAddr::V6(ip) => buffer.extend_from_slice(&ip.octets()),
+    }
+    let mut cookie = [0u8; COOKIE_SIZE];
+    cookie.copy_from_slice(&HashValue::sha3_256_of(&buffer).as_ref()[..COOKIE_SIZE]);
+    cookie
+}
+
+/// Compares in constant time, so that cookies can't be guessed byte by byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// The state shared by the inbound handshakes of a server to defend against floods.
+pub struct HandshakeAntiDos {
+    rate_limiter: TokenBucketRateLimiter<IpPrefix>,
+    cookie_threshold: usize,
+    pending_handshakes: AtomicUsize,
+    cookie_secrets: Mutex<CookieSecrets>,
+}
+
+impl HandshakeAntiDos {
+    pub fn new(config: &HandshakeRateLimitConfig) -> Self {
+        let (rate_limiter, cookie_threshold) = if config.enabled {
+            (
+                TokenBucketRateLimiter::new(
+                    "handshake",
+                    "inbound".to_string(),
+                    100,
+                    config.ip_prefix_handshake_burst,
+                    config.ip_prefix_handshake_rate,
+                    None,
+                ),
+                config.cookie_threshold,
+            )
+        } else {
+            (TokenBucketRateLimiter::open("handshake"), usize::MAX)
+        };
+
+        Self {
+            rate_limiter,
+            cookie_threshold,
+            pending_handshakes: AtomicUsize::new(0),
+            cookie_secrets: Mutex::new(CookieSecrets::new()),
+        }
+    }
+
+    /// Takes a token from the bucket of the IP prefix, or returns the prefix if it has
+    /// exhausted its handshakes.
+    pub fn check_rate_limit(&self, ip: IpAddr) -> Result<(), IpPrefix> {
+        let prefix = IpPrefix::new(ip);
+        self.rate_limiter
+            .bucket(prefix)
+            .lock()
+            .acquire_all_tokens(1)
+            .map_err(|_| prefix)
+    }
+
+    /// Counts a handshake as in progress until the returned guard is dropped.
+    pub fn start_handshake(&self) -> PendingHandshake {
+        self.pending_handshakes.fetch_add(1, Ordering::Relaxed);
+        PendingHandshake {
+            pending_handshakes: &self.pending_handshakes,
+        }
+    }
+
+    /// Whether there are too many handshakes in progress to process messages without cookies.
+    pub fn is_under_load(&self) -> bool {
+        self.pending_handshakes.load(Ordering::Relaxed) > self.cookie_threshold
+    }
+
+    /// The cookie the client at the IP address has to send back.
+    pub fn cookie(&self, ip: IpAddr) -> [u8; COOKIE_SIZE] {
+        let (current, _) = self.cookie_secrets.lock().get();
+        cookie_for(&current, ip)
+    }
+
+    /// Verifies that the cookie was issued to the IP address, by the current or previous secret.
+    pub fn verify_cookie(&self, ip: IpAddr, cookie: &[u8]) -> bool {
+        let (current, previous) = self.cookie_secrets.lock().get();
+        constant_time_eq(&cookie_for(&current, ip), cookie)
+            | constant_time_eq(&cookie_for(&previous, ip), cookie)
+    }
+}
+
+#[cfg(test)]
+impl HandshakeAntiDos {
+    /// Defenses refilling one handshake per second per IP prefix.
+    pub(crate) fn for_testing(ip_prefix_handshake_burst: usize, cookie_threshold: usize) -> Self {
+        Self::new(&HandshakeRateLimitConfig {
+            ip_prefix_handshake_rate: 1,
+            ip_prefix_handshake_burst,
+            cookie_threshold,
+            enabled: true,
+        })
+    }
+}
+
+/// A guard counting a handshake as in progress.
+pub struct PendingHandshake<'a> {
+    pending_handshakes: &'a AtomicUsize,
+}
+
+impl<'a> Drop for PendingHandshake<'a> {
+    fn drop(&mut self) {
+        self.pending_handshakes.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ip_prefix() {
+        let prefix = IpPrefix::new("1.2.3.4".parse().unwrap());
+        assert_eq!(prefix, IpPrefix::new("1.2.3.200".parse().unwrap()));
+        assert_ne!(prefix, IpPrefix::new("1.2.4.4".parse().unwrap()));
+        assert_eq!(prefix.to_string(), "1.2.3.0/24");
+
+        let prefix = IpPrefix::new("2001:db8:1:2::1".parse().unwrap());
+        assert_eq!(prefix, IpPrefix::new("2001:db8:1:ffff::2".parse().unwrap()));
+        assert_ne!(prefix, IpPrefix::new("2001:db8:2::1".parse().unwrap()));
+        assert_eq!(prefix.to_string(), "2001:db8:1::/48");
+    }
+
+    #[test]
+    fn test_rate_limit_per_prefix() {
+        let anti_dos = HandshakeAntiDos::for_testing(2, 0);
+        let ip = "1.2.3.4".parse().unwrap();
+        let same_prefix_ip = "1.2.3.5".parse().unwrap();
+        let other_prefix_ip = "1.2.4.4".parse().unwrap();
+
+        anti_dos.check_rate_limit(ip).unwrap();
+        anti_dos.check_rate_limit(same_prefix_ip).unwrap();
+        assert_eq!(
+            anti_dos.check_rate_limit(ip),
+            Err(IpPrefix::new(same_prefix_ip))
+        );
+        anti_dos.check_rate_limit(other_prefix_ip).unwrap();
+    }
+
+    #[test]
+    fn test_load() {
+        let anti_dos = HandshakeAntiDos::for_testing(1, 1);
+        let first = anti_dos.start_handshake();
+        assert!(!anti_dos.is_under_load());
+        let second = anti_dos.start_handshake();
+        assert!(anti_dos.is_under_load());
+        drop(first);
+        assert!(!anti_dos.is_under_load());
+        drop(second);
+    }
+
+    #[test]
+    fn test_cookies() {
+        let anti_dos = HandshakeAntiDos::for_testing(1, 0);
+        let ip = "1.2.3.4".parse().unwrap();
+        let other_ip = "1.2.3.5".parse().unwrap();
+
+        let cookie = anti_dos.cookie(ip);
+        assert!(anti_dos.verify_cookie(ip, &cookie));
+        assert!(!anti_dos.verify_cookie(other_ip, &cookie));
+        assert!(!anti_dos.verify_cookie(ip, &[0u8; COOKIE_SIZE]));
+
+        // cookies remain valid for one rotation of the secrets
+        anti_dos.cookie_secrets.lock().rotated_at -= COOKIE_SECRET_LIFETIME;
+        assert!(anti_dos.verify_cookie(ip, &cookie));
+        anti_dos.cookie_secrets.lock().rotated_at -= COOKIE_SECRET_LIFETIME;
+        assert!(!anti_dos.verify_cookie(ip, &cookie));
+    }
+}
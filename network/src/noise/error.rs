@@ -31,6 +31,21 @@ pub enum NoiseHandshakeError {
     #[error("noise client: error finalizing secure connection: {0}")]
     ClientFinalizeFailed(NoiseError),
 
+    #[error("noise client: server is under load and sent us a cookie to dial again with")]
+    ClientCookieReceived,
+
+    #[error("noise client: unexpected server handshake response type: {0}")]
+    UnexpectedServerResponse(u8),
+
+    #[error("noise server: too many handshakes from the client's network: {0}")]
+    HandshakeRateLimited(String),
+
+    #[error("noise server: error sending handshake cookie: {0}")]
+    ServerCookieWriteFailed(io::Error),
+
+    #[error("noise server: under load, sent a cookie to the client and closed the connection")]
+    CookieSent,
+
     #[error("noise server: error reading client handshake init message: {0}")]
     ServerReadFailed(io::Error),
 
@@ -3,26 +3,36 @@
 
 //! The handshake module implements the handshake part of the protocol.
 //! This module also implements additional anti-DoS mitigation,
-//! by including a timestamp in each handshake initialization message.
+//! by including a timestamp in each handshake initialization message,
+//! and by rate limiting and challenging inbound handshakes with cookies
+//! (see the [anti_dos] module).
 //! Refer to the module's documentation for more information.
 //! A successful handshake returns a [`NoiseStream`] which is defined in the
 //! [stream] module.
 //!
 //! [stream]: crate::noise::stream
-
-use crate::noise::{error::NoiseHandshakeError, stream::NoiseStream};
+//! [anti_dos]: crate::noise::anti_dos
+
+use crate::{
+    counters,
+    noise::{
+        anti_dos::{HandshakeAntiDos, COOKIE_SIZE},
+        error::NoiseHandshakeError,
+        stream::NoiseStream,
+    },
+};
 use aptos_config::{
     config::{Peer, PeerRole, PeerSet},
     network_id::NetworkContext,
 };
 use aptos_crypto::{noise, x25519};
-use aptos_infallible::{duration_since_epoch, RwLock};
+use aptos_infallible::{duration_since_epoch, Mutex, RwLock};
 use aptos_logger::trace;
 use aptos_types::PeerId;
 use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
-use netcore::transport::ConnectionOrigin;
+use netcore::transport::{tls::ClientAuthorizer, ConnectionOrigin};
 use short_hex_str::{AsShortHexStr, ShortHexStr};
-use std::{collections::HashMap, convert::TryFrom as _, fmt::Debug, sync::Arc};
+use std::{collections::HashMap, convert::TryFrom as _, fmt::Debug, net::IpAddr, sync::Arc};
 
 /// In a mutually authenticated network, a client message is accompanied with a timestamp.
 /// This is in order to prevent replay attacks, where the attacker does not know the client's static key,
@@ -111,6 +121,23 @@ impl HandshakeAuthMode {
         HandshakeAuthMode::maybe_mutual(Arc::new(RwLock::new(HashMap::default())))
     }
 
+    /// Returns the check applied to the certificate keys of TLS dialers. Like
+    /// the Noise handshake, `Mutual` mode only lets trusted peers dial in.
+    pub fn tls_client_authorizer(&self) -> ClientAuthorizer {
+        match self {
+            HandshakeAuthMode::Mutual { trusted_peers, .. } => {
+                let trusted_peers = trusted_peers.clone();
+                Arc::new(move |key: &x25519::PublicKey| {
+                    trusted_peers
+                        .read()
+                        .values()
+                        .any(|peer| peer.keys.contains(key))
+                })
+            }
+            HandshakeAuthMode::MaybeMutual(_) => Arc::new(|_: &x25519::PublicKey| true),
+        }
+    }
+
     fn anti_replay_timestamps(&self) -> Option<&RwLock<AntiReplayTimestamps>> {
         match &self {
             HandshakeAuthMode::Mutual {
@@ -140,6 +167,11 @@ pub struct NoiseUpgrader {
     noise_config: noise::NoiseConfig,
     /// Handshake authentication can be either mutual or server-only authentication.
     auth_mode: HandshakeAuthMode,
+    /// Rate limits and cookies of inbound handshakes, if enabled.
+    anti_dos: Option<HandshakeAntiDos>,
+    /// The last cookie each server under load replied with, sent along our next handshakes
+    /// to it.
+    cookies: Mutex<HashMap<x25519::PublicKey, [u8; COOKIE_SIZE]>>,
 }
 
 impl NoiseUpgrader {
@@ -153,9 +185,17 @@ impl NoiseUpgrader {
             network_context,
             noise_config: noise::NoiseConfig::new(key),
             auth_mode,
+            anti_dos: None,
+            cookies: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Rate limit inbound handshakes, and require cookies from clients when under load.
+    pub fn with_anti_dos(mut self, anti_dos: HandshakeAntiDos) -> Self {
+        self.anti_dos = Some(anti_dos);
+        self
+    }
+
     /// Perform a protocol upgrade on an underlying connection. In addition perform the noise IK
     /// handshake to establish a noise stream and exchange static public keys. Upon success,
     /// returns the static public key of the remote as well as a NoiseStream.
@@ -199,9 +239,24 @@ impl NoiseUpgrader {
     const CLIENT_MESSAGE_SIZE: usize =
         Self::PROLOGUE_SIZE + noise::handshake_init_msg_len(AntiReplayTimestamps::TIMESTAMP_SIZE);
 
+    /// With cookies (`COOKIE_HANDSHAKE_VERSION`), the client message is followed by a cookie,
+    /// zeroes if the client has none.
+    const COOKIE_CLIENT_MESSAGE_SIZE: usize = Self::CLIENT_MESSAGE_SIZE + COOKIE_SIZE;
+
     /// The server's message contains no payload.
     const SERVER_MESSAGE_SIZE: usize = noise::handshake_resp_msg_len(0);
 
+    /// With cookies, the server's message is preceded by its type, as the server can reply to
+    /// the client message with a cookie instead of a noise message.
+    const HANDSHAKE_RESPONSE: u8 = 0;
+    const COOKIE_REPLY: u8 = 1;
+
+    /// Whether inbound handshakes use cookies, i.e., the anti-DoS defenses are enabled, in which
+    /// case the listen address is advertised with `COOKIE_HANDSHAKE_VERSION`.
+    pub fn requires_cookies(&self) -> bool {
+        self.anti_dos.is_some()
+    }
+
     /// Perform an outbound protocol upgrade on this connection.
     ///
     /// This runs the "client" side of the Noise IK handshake to establish a
@@ -210,24 +265,73 @@ impl NoiseUpgrader {
     /// Noise handshake payload. Currently this counter is always a millisecond-
     /// granularity unix epoch timestamp.
     pub async fn upgrade_outbound<TSocket, F>(
+        &self,
+        socket: TSocket,
+        remote_public_key: x25519::PublicKey,
+        time_provider: F,
+    ) -> Result<NoiseStream<TSocket>, NoiseHandshakeError>
+    where
+        TSocket: AsyncRead + AsyncWrite + Debug + Unpin,
+        F: Fn() -> [u8; AntiReplayTimestamps::TIMESTAMP_SIZE],
+    {
+        self.upgrade_outbound_versioned(socket, remote_public_key, false, time_provider)
+            .await
+    }
+
+    /// Perform an outbound protocol upgrade on a connection to a server advertising
+    /// `COOKIE_HANDSHAKE_VERSION`.
+    ///
+    /// Same as [`NoiseUpgrader::upgrade_outbound`], but the client message carries the last
+    /// cookie the server gave us, if any. The server may reply with a new cookie instead of its
+    /// handshake message and close the connection, in which case the cookie is kept for the next
+    /// handshake and [`NoiseHandshakeError::ClientCookieReceived`] is returned, so that the
+    /// caller can dial again.
+    pub async fn upgrade_outbound_with_cookies<TSocket, F>(
+        &self,
+        socket: TSocket,
+        remote_public_key: x25519::PublicKey,
+        time_provider: F,
+    ) -> Result<NoiseStream<TSocket>, NoiseHandshakeError>
+    where
+        TSocket: AsyncRead + AsyncWrite + Debug + Unpin,
+        F: Fn() -> [u8; AntiReplayTimestamps::TIMESTAMP_SIZE],
+    {
+        self.upgrade_outbound_versioned(socket, remote_public_key, true, time_provider)
+            .await
+    }
+
+    async fn upgrade_outbound_versioned<TSocket, F>(
         &self,
         mut socket: TSocket,
         remote_public_key: x25519::PublicKey,
+        cookies: bool,
         time_provider: F,
     ) -> Result<NoiseStream<TSocket>, NoiseHandshakeError>
     where
         TSocket: AsyncRead + AsyncWrite + Debug + Unpin,
         F: Fn() -> [u8; AntiReplayTimestamps::TIMESTAMP_SIZE],
     {
-        // buffer to hold prologue + first noise handshake message
-        let mut client_message = [0; Self::CLIENT_MESSAGE_SIZE];
+        let client_message_size = if cookies {
+            Self::COOKIE_CLIENT_MESSAGE_SIZE
+        } else {
+            Self::CLIENT_MESSAGE_SIZE
+        };
+
+        // buffer to hold prologue + first noise handshake message (+ cookie)
+        let mut client_message = [0; Self::COOKIE_CLIENT_MESSAGE_SIZE];
 
         // craft prologue = self_peer_id | expected_public_key
         client_message[..PeerId::LENGTH].copy_from_slice(self.network_context.peer_id().as_ref());
         client_message[PeerId::LENGTH..Self::PROLOGUE_SIZE]
             .copy_from_slice(remote_public_key.as_slice());
 
-        let (prologue_msg, mut client_noise_msg) = client_message.split_at_mut(Self::PROLOGUE_SIZE);
+        // the cookie is only known once the server required it on a previous connection
+        if let Some(cookie) = self.cookies.lock().get(&remote_public_key) {
+            client_message[Self::CLIENT_MESSAGE_SIZE..].copy_from_slice(cookie);
+        }
+
+        let (prologue_msg, client_noise_msg) =
+            client_message[..Self::CLIENT_MESSAGE_SIZE].split_at_mut(Self::PROLOGUE_SIZE);
 
         // craft 8-byte payload as current timestamp (in milliseconds)
         let payload = time_provider();
@@ -241,7 +345,7 @@ impl NoiseUpgrader {
                 prologue_msg,
                 remote_public_key,
                 Some(&payload),
-                &mut client_noise_msg,
+                client_noise_msg,
             )
             .map_err(NoiseHandshakeError::BuildClientHandshakeMessageFailed)?;
 
@@ -252,7 +356,7 @@ impl NoiseUpgrader {
             remote_public_key,
         );
         socket
-            .write_all(&client_message)
+            .write_all(&client_message[..client_message_size])
             .await
             .map_err(NoiseHandshakeError::ClientWriteFailed)?;
         socket
@@ -260,12 +364,44 @@ impl NoiseUpgrader {
             .await
             .map_err(NoiseHandshakeError::ClientFlushFailed)?;
 
-        // receive the server's response (<- e, ee, se)
+        // receive the type of the server's response, only sent with cookies
         trace!(
             "{} noise client: handshake read: remote_public_key: {}",
             self.network_context,
             remote_public_key,
         );
+        let mut response_type = [Self::HANDSHAKE_RESPONSE];
+        if cookies {
+            socket
+                .read_exact(&mut response_type)
+                .await
+                .map_err(NoiseHandshakeError::ClientReadFailed)?;
+        }
+
+        match response_type[0] {
+            Self::HANDSHAKE_RESPONSE => (),
+            // the server is under load and closed the connection, keep its cookie for the next
+            // connection
+            Self::COOKIE_REPLY => {
+                trace!(
+                    "{} noise client: handshake cookie: remote_public_key: {}",
+                    self.network_context,
+                    remote_public_key,
+                );
+                let mut cookie = [0u8; COOKIE_SIZE];
+                socket
+                    .read_exact(&mut cookie)
+                    .await
+                    .map_err(NoiseHandshakeError::ClientReadFailed)?;
+                self.cookies.lock().insert(remote_public_key, cookie);
+                return Err(NoiseHandshakeError::ClientCookieReceived);
+            }
+            response_type => {
+                return Err(NoiseHandshakeError::UnexpectedServerResponse(response_type))
+            }
+        }
+
+        // receive the server's response (<- e, ee, se)
         let mut server_response = [0u8; Self::SERVER_MESSAGE_SIZE];
         socket
             .read_exact(&mut server_response)
@@ -296,22 +432,89 @@ impl NoiseUpgrader {
     /// In addition, we will expect the client to include an anti replay attack
     /// counter in the Noise handshake payload in mutual auth scenarios.
     pub async fn upgrade_inbound<TSocket>(
+        &self,
+        socket: TSocket,
+    ) -> Result<(NoiseStream<TSocket>, PeerId, PeerRole), NoiseHandshakeError>
+    where
+        TSocket: AsyncRead + AsyncWrite + Debug + Unpin,
+    {
+        self.upgrade_inbound_from(socket, None).await
+    }
+
+    /// Perform an inbound protocol upgrade on a connection from `remote_ip`.
+    ///
+    /// Same as [`NoiseUpgrader::upgrade_inbound`], but the handshake is also subject to the
+    /// anti-DoS defenses, if enabled and the remote IP address is known: it is rate limited per
+    /// IP prefix, and when too many handshakes are in progress a client without a valid cookie
+    /// is given one and disconnected, see [`NoiseHandshakeError::CookieSent`].
+    pub async fn upgrade_inbound_from<TSocket>(
         &self,
         mut socket: TSocket,
+        remote_ip: Option<IpAddr>,
     ) -> Result<(NoiseStream<TSocket>, PeerId, PeerRole), NoiseHandshakeError>
     where
         TSocket: AsyncRead + AsyncWrite + Debug + Unpin,
     {
+        let cookies = self.requires_cookies();
+        let client_message_size = if cookies {
+            Self::COOKIE_CLIENT_MESSAGE_SIZE
+        } else {
+            Self::CLIENT_MESSAGE_SIZE
+        };
+        let anti_dos = remote_ip.zip(self.anti_dos.as_ref());
+
+        // reject handshakes from networks exceeding their rate limit, before reading anything
+        let _pending_handshake = match anti_dos {
+            Some((remote_ip, anti_dos)) => {
+                anti_dos.check_rate_limit(remote_ip).map_err(|prefix| {
+                    counters::noise_handshake_defenses(&self.network_context, "rate_limited").inc();
+                    NoiseHandshakeError::HandshakeRateLimited(prefix.to_string())
+                })?;
+                Some(anti_dos.start_handshake())
+            }
+            None => None,
+        };
+
         // buffer to contain the client first message
-        let mut client_message = [0; Self::CLIENT_MESSAGE_SIZE];
+        let mut client_message = [0; Self::COOKIE_CLIENT_MESSAGE_SIZE];
+        let client_message = &mut client_message[..client_message_size];
 
-        // receive the prologue + first noise handshake message
+        // receive the prologue + first noise handshake message (+ cookie)
         trace!("{} noise server: handshake read", self.network_context);
         socket
-            .read_exact(&mut client_message)
+            .read_exact(client_message)
             .await
             .map_err(NoiseHandshakeError::ServerReadFailed)?;
 
+        // when under load, don't process the message unless the client proves it owns its
+        // address with a cookie. otherwise, reply with one and close the connection, so that
+        // challenged clients hold neither a connection nor a pending handshake until they dial
+        // again with the cookie.
+        if let Some((remote_ip, anti_dos)) = anti_dos {
+            let cookie_offset = Self::CLIENT_MESSAGE_SIZE;
+            if anti_dos.is_under_load()
+                && !anti_dos.verify_cookie(remote_ip, &client_message[cookie_offset..])
+            {
+                counters::noise_handshake_defenses(&self.network_context, "cookie_sent").inc();
+                let mut cookie_reply = [0u8; 1 + COOKIE_SIZE];
+                cookie_reply[0] = Self::COOKIE_REPLY;
+                cookie_reply[1..].copy_from_slice(&anti_dos.cookie(remote_ip));
+                trace!(
+                    "{} noise server: handshake cookie write",
+                    self.network_context
+                );
+                socket
+                    .write_all(&cookie_reply)
+                    .await
+                    .map_err(NoiseHandshakeError::ServerCookieWriteFailed)?;
+                socket
+                    .flush()
+                    .await
+                    .map_err(NoiseHandshakeError::ServerCookieWriteFailed)?;
+                return Err(NoiseHandshakeError::CookieSent);
+            }
+        }
+
         // extract prologue (remote_peer_id | self_public_key)
         let (remote_peer_id, self_expected_public_key) =
             client_message[..Self::PROLOGUE_SIZE].split_at(PeerId::LENGTH);
@@ -340,7 +543,8 @@ impl NoiseUpgrader {
         }
 
         // parse it
-        let (prologue, client_init_message) = client_message.split_at(Self::PROLOGUE_SIZE);
+        let (prologue, client_init_message) =
+            client_message[..Self::CLIENT_MESSAGE_SIZE].split_at(Self::PROLOGUE_SIZE);
         let (remote_public_key, handshake_state, payload) = self
             .noise_config
             .parse_client_init_message(prologue, client_init_message)
@@ -413,10 +617,11 @@ impl NoiseUpgrader {
 
         // construct the response
         let mut rng = rand::rngs::OsRng;
-        let mut server_response = [0u8; Self::SERVER_MESSAGE_SIZE];
+        let mut server_response = [0u8; 1 + Self::SERVER_MESSAGE_SIZE];
+        server_response[0] = Self::HANDSHAKE_RESPONSE;
         let session = self
             .noise_config
-            .respond_to_client(&mut rng, handshake_state, None, &mut server_response)
+            .respond_to_client(&mut rng, handshake_state, None, &mut server_response[1..])
             .map_err(|err| {
                 NoiseHandshakeError::BuildServerHandshakeMessageFailed(remote_peer_short, err)
             })?;
@@ -427,8 +632,10 @@ impl NoiseUpgrader {
             self.network_context,
             remote_peer_short,
         );
+        // the response type is only sent with cookies
+        let response_offset = if cookies { 0 } else { 1 };
         socket
-            .write_all(&server_response)
+            .write_all(&server_response[response_offset..])
             .await
             .map_err(|err| NoiseHandshakeError::ServerWriteFailed(remote_peer_short, err))?;
 
@@ -699,6 +906,109 @@ mod test {
         server_res.unwrap_err();
     }
 
+    #[test]
+    fn test_handshake_cookie_retry() {
+        let ((client, client_public_key), (server, server_public_key)) =
+            build_peers(true /* is_mutual_auth */);
+
+        // no handshake is allowed in progress, so the client always needs a cookie
+        let server = server.with_anti_dos(HandshakeAntiDos::for_testing(10, 0));
+        let remote_ip = "1.2.3.4".parse().unwrap();
+
+        // the server replies to the first handshake with a cookie and closes the connection
+        let (dialer_socket, listener_socket) = MemorySocket::new_pair();
+        let (client_session, server_session) = block_on(join(
+            client.upgrade_outbound_with_cookies(
+                dialer_socket,
+                server_public_key,
+                AntiReplayTimestamps::now,
+            ),
+            server.upgrade_inbound_from(listener_socket, Some(remote_ip)),
+        ));
+        assert!(matches!(
+            client_session,
+            Err(NoiseHandshakeError::ClientCookieReceived)
+        ));
+        assert!(matches!(
+            server_session,
+            Err(NoiseHandshakeError::CookieSent)
+        ));
+
+        // the client dials again with the cookie
+        let (dialer_socket, listener_socket) = MemorySocket::new_pair();
+        let (client_session, server_session) = block_on(join(
+            client.upgrade_outbound_with_cookies(
+                dialer_socket,
+                server_public_key,
+                AntiReplayTimestamps::now,
+            ),
+            server.upgrade_inbound_from(listener_socket, Some(remote_ip)),
+        ));
+
+        let client_stream = client_session.unwrap();
+        let (server_stream, _, _) = server_session.unwrap();
+        assert_eq!(client_stream.get_remote_static(), server_public_key);
+        assert_eq!(server_stream.get_remote_static(), client_public_key);
+
+        // the cookie isn't valid for another address
+        let (dialer_socket, listener_socket) = MemorySocket::new_pair();
+        let (client_session, server_session) = block_on(join(
+            client.upgrade_outbound_with_cookies(
+                dialer_socket,
+                server_public_key,
+                AntiReplayTimestamps::now,
+            ),
+            server.upgrade_inbound_from(listener_socket, Some("5.6.7.8".parse().unwrap())),
+        ));
+        client_session.unwrap_err();
+        assert!(matches!(
+            server_session,
+            Err(NoiseHandshakeError::CookieSent)
+        ));
+    }
+
+    #[test]
+    fn test_handshake_rate_limit() {
+        let ((client, _), (server, server_public_key)) =
+            build_peers(false /* is_mutual_auth */);
+        let server = server.with_anti_dos(HandshakeAntiDos::for_testing(1, 10));
+
+        // the first handshake from the network is allowed, but not the second one
+        for (remote_ip, is_allowed) in &[("1.2.3.4", true), ("1.2.3.5", false)] {
+            let (dialer_socket, listener_socket) = MemorySocket::new_pair();
+            let (client_session, server_session) = block_on(join(
+                client.upgrade_outbound_with_cookies(
+                    dialer_socket,
+                    server_public_key,
+                    AntiReplayTimestamps::now,
+                ),
+                server.upgrade_inbound_from(listener_socket, Some(remote_ip.parse().unwrap())),
+            ));
+            assert_eq!(client_session.is_ok(), *is_allowed);
+            match server_session {
+                Ok(_) => assert!(is_allowed),
+                Err(NoiseHandshakeError::HandshakeRateLimited(prefix)) => {
+                    assert!(!is_allowed);
+                    assert_eq!(prefix, "1.2.3.0/24");
+                }
+                Err(err) => panic!("Unexpected error: {}", err),
+            }
+        }
+
+        // handshakes from unknown addresses aren't rate limited
+        let (dialer_socket, listener_socket) = MemorySocket::new_pair();
+        let (client_session, server_session) = block_on(join(
+            client.upgrade_outbound_with_cookies(
+                dialer_socket,
+                server_public_key,
+                AntiReplayTimestamps::now,
+            ),
+            server.upgrade_inbound(listener_socket),
+        ));
+        client_session.unwrap();
+        server_session.unwrap();
+    }
+
     #[test]
     fn test_handshake_fragmented_reads() {
         // create an in-memory socket for testing
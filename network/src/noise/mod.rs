@@ -97,6 +97,7 @@
 //! [ik]: https://noiseexplorer.com/patterns/IK
 //! [crypto]: ../aptos_crypto/noise/index.html
 
+pub mod anti_dos;
 pub mod error;
 pub mod handshake;
 pub mod stream;
@@ -104,5 +105,6 @@ pub mod stream;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod fuzzing;
 
+pub use anti_dos::HandshakeAntiDos;
 pub use error::NoiseHandshakeError;
 pub use handshake::{AntiReplayTimestamps, HandshakeAuthMode, NoiseUpgrader};
@@ -108,6 +108,8 @@ pub fn fuzz(data: &[u8]) {
         constants::MAX_FRAME_SIZE,
         None,
         None,
+        None,
+        None,
     );
     executor.spawn(peer.start());
 
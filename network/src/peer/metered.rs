@@ -0,0 +1,87 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_metrics::IntCounter;
+use futures::io::{AsyncRead, AsyncWrite};
+use pin_project::pin_project;
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A socket wrapper counting the bytes read from and written to the inner socket.
+#[pin_project]
+pub struct MeteredSocket<TSocket> {
+    #[pin]
+    inner: TSocket,
+    bytes: IntCounter,
+}
+
+impl<TSocket> MeteredSocket<TSocket> {
+    pub fn new(inner: TSocket, bytes: IntCounter) -> Self {
+        Self { inner, bytes }
+    }
+}
+
+impl<TSocket: AsyncRead> AsyncRead for MeteredSocket<TSocket> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let result = this.inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(read)) = &result {
+            this.bytes.inc_by(*read as u64);
+        }
+        result
+    }
+}
+
+impl<TSocket: AsyncWrite> AsyncWrite for MeteredSocket<TSocket> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let result = this.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = &result {
+            this.bytes.inc_by(*written as u64);
+        }
+        result
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::{
+        executor::block_on,
+        io::{AsyncReadExt, AsyncWriteExt, Cursor},
+    };
+
+    #[test]
+    fn metered_socket_counts_bytes() {
+        let bytes = IntCounter::new("test_bytes", "test").unwrap();
+        let mut socket = MeteredSocket::new(Cursor::new(Vec::new()), bytes.clone());
+
+        block_on(socket.write_all(b"hello world")).unwrap();
+        assert_eq!(bytes.get(), 11);
+
+        let mut socket = MeteredSocket::new(Cursor::new(b"hello".to_vec()), bytes.clone());
+        let mut buf = Vec::new();
+        block_on(socket.read_to_end(&mut buf)).unwrap();
+        assert_eq!(buf, b"hello");
+        assert_eq!(bytes.get(), 16);
+    }
+}
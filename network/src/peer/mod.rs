@@ -21,9 +21,12 @@ use crate::{
     protocols::{
         direct_send::Message,
         rpc::{InboundRpcRequest, InboundRpcs, OutboundRpcRequest, OutboundRpcs},
-        wire::messaging::v1::{
-            DirectSendMsg, ErrorCode, NetworkMessage, NetworkMessageSink, NetworkMessageStream,
-            Priority, ReadError, WriteError,
+        wire::{
+            compression,
+            messaging::v1::{
+                DirectSendMsg, ErrorCode, NetworkMessage, NetworkMessageSink, NetworkMessageStream,
+                Priority, ReadError, WriteError,
+            },
         },
     },
     transport::{self, Connection, ConnectionMetadata},
@@ -31,7 +34,7 @@ use crate::{
 };
 use aptos_config::network_id::NetworkContext;
 use aptos_logger::prelude::*;
-use aptos_rate_limiter::rate_limit::SharedBucket;
+use aptos_rate_limiter::{async_lib::AsyncRateLimiter, rate_limit::SharedBucket};
 use aptos_time_service::{TimeService, TimeServiceTrait};
 use aptos_types::PeerId;
 use bytes::Bytes;
@@ -43,6 +46,7 @@ use futures::{
     stream::StreamExt,
     FutureExt, SinkExt, TryFutureExt,
 };
+use metered::MeteredSocket;
 use serde::Serialize;
 use short_hex_str::AsShortHexStr;
 use std::{fmt, panic, time::Duration};
@@ -51,6 +55,7 @@ use tokio_util::compat::{
     FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt,
 };
 
+mod metered;
 #[cfg(test)]
 mod test;
 
@@ -134,6 +139,10 @@ pub struct Peer<TSocket> {
     inbound_rate_limiter: Option<SharedBucket>,
     /// Optional outbound rate limiter
     outbound_rate_limiter: Option<SharedBucket>,
+    /// Optional inbound rate limiter for this specific peer, based on its role
+    inbound_peer_rate_limiter: Option<SharedBucket>,
+    /// Optional outbound rate limiter for this specific peer, based on its role
+    outbound_peer_rate_limiter: Option<SharedBucket>,
 }
 
 impl<TSocket> Peer<TSocket>
@@ -154,6 +163,8 @@ where
         max_frame_size: usize,
         inbound_rate_limiter: Option<SharedBucket>,
         outbound_rate_limiter: Option<SharedBucket>,
+        inbound_peer_rate_limiter: Option<SharedBucket>,
+        outbound_peer_rate_limiter: Option<SharedBucket>,
     ) -> Self {
         let Connection {
             metadata: connection_metadata,
@@ -186,6 +197,8 @@ where
             max_frame_size,
             inbound_rate_limiter,
             outbound_rate_limiter,
+            inbound_peer_rate_limiter,
+            outbound_peer_rate_limiter,
         }
     }
 
@@ -193,6 +206,19 @@ where
         self.connection_metadata.remote_peer_id
     }
 
+    /// Returns the protocol to use over-the-wire for the given protocol, i.e.,
+    /// its compressed counterpart if compression was negotiated with the peer.
+    fn wire_protocol(&self, protocol_id: ProtocolId) -> ProtocolId {
+        protocol_id
+            .compressed()
+            .filter(|compressed| {
+                self.connection_metadata
+                    .application_protocols
+                    .contains(*compressed)
+            })
+            .unwrap_or(protocol_id)
+    }
+
     pub async fn start(mut self) {
         let remote_peer_id = self.remote_peer_id();
         trace!(
@@ -207,14 +233,32 @@ where
         let (read_socket, write_socket) =
             tokio::io::split(self.connection.take().unwrap().compat());
 
+        // Meter the bytes transferred with the peer, and apply the per peer rate
+        // limits in addition to the per IP rate limits.
+        let peer_role = self.connection_metadata.role;
+        let read_socket = AsyncRateLimiter::new(
+            MeteredSocket::new(
+                read_socket.compat(),
+                counters::peer_bytes(&self.network_context, peer_role, RECEIVED_LABEL),
+            ),
+            self.inbound_peer_rate_limiter.clone(),
+        );
+        let write_socket = AsyncRateLimiter::new(
+            MeteredSocket::new(
+                write_socket.compat_write(),
+                counters::peer_bytes(&self.network_context, peer_role, SENT_LABEL),
+            ),
+            self.outbound_peer_rate_limiter.clone(),
+        );
+
         let mut reader = NetworkMessageStream::new(
-            read_socket.compat(),
+            read_socket,
             self.max_frame_size,
             self.inbound_rate_limiter.clone(),
         )
         .fuse();
         let writer = NetworkMessageSink::new(
-            write_socket.compat_write(),
+            write_socket,
             self.max_frame_size,
             self.outbound_rate_limiter.clone(),
         );
@@ -478,8 +522,8 @@ where
     /// PeerManager.
     fn handle_inbound_direct_send(&mut self, message: DirectSendMsg) {
         let peer_id = self.remote_peer_id();
-        let protocol_id = message.protocol_id;
-        let data = message.raw_msg;
+        let mut protocol_id = message.protocol_id;
+        let mut data = message.raw_msg;
 
         trace!(
             NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
@@ -494,6 +538,25 @@ where
         counters::direct_send_bytes(&self.network_context, RECEIVED_LABEL)
             .inc_by(data.len() as u64);
 
+        // Decompress the message if it was sent using a compressed protocol
+        if let Some(decompressed_protocol_id) = protocol_id.decompressed() {
+            data = match compression::decompress(&data) {
+                Ok(decompressed_data) => decompressed_data,
+                Err(err) => {
+                    warn!(
+                        NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                        error = %err,
+                        "{} Failed to decompress inbound DirectSend message for protocol {}. Error: {}",
+                        self.network_context,
+                        protocol_id,
+                        err
+                    );
+                    return;
+                }
+            };
+            protocol_id = decompressed_protocol_id;
+        }
+
         let notif = PeerNotification::RecvMessage(Message {
             protocol_id,
             mdata: Bytes::from(data),
@@ -527,12 +590,18 @@ where
             // To send an outbound DirectSendMsg, we just bump some counters and
             // push it onto our outbound writer queue.
             PeerRequest::SendDirectSend(message) => {
-                let message_len = message.mdata.len();
                 let protocol_id = message.protocol_id;
+                let wire_protocol_id = self.wire_protocol(protocol_id);
+                let raw_msg = if wire_protocol_id != protocol_id {
+                    compression::compress(message.mdata.as_ref())
+                } else {
+                    Vec::from(message.mdata.as_ref())
+                };
+                let message_len = raw_msg.len();
                 let message = NetworkMessage::DirectSendMsg(DirectSendMsg {
-                    protocol_id,
+                    protocol_id: wire_protocol_id,
                     priority: Priority::default(),
-                    raw_msg: Vec::from(message.mdata.as_ref()),
+                    raw_msg,
                 });
                 let (ack_tx, _ack_rx) = oneshot::channel();
 
@@ -555,8 +624,11 @@ where
                     }
                 }
             }
-            PeerRequest::SendRpc(request) => {
+            PeerRequest::SendRpc(mut request) => {
                 let protocol_id = request.protocol_id;
+                // The request (and response) are compressed by `OutboundRpcs`
+                // if compression was negotiated with the peer.
+                request.protocol_id = self.wire_protocol(protocol_id);
                 if let Err(e) = self
                     .outbound_rpcs
                     .handle_outbound_request(request, write_reqs_tx)
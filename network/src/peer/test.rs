@@ -36,7 +36,7 @@ use futures::{
 };
 use memsocket::MemorySocket;
 use netcore::transport::ConnectionOrigin;
-use std::{collections::HashSet, str::FromStr, time::Duration};
+use std::{collections::HashSet, iter::FromIterator, str::FromStr, time::Duration};
 use tokio::runtime::{Handle, Runtime};
 use tokio_util::compat::{
     FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt,
@@ -90,6 +90,8 @@ fn build_test_peer(
         MAX_FRAME_SIZE,
         None,
         None,
+        None,
+        None,
     );
     let peer_handle = PeerHandle(peer_reqs_tx);
 
@@ -336,6 +338,82 @@ fn peers_send_message_concurrent() {
     rt.block_on(future::join3(peer_a.start(), peer_b.start(), test));
 }
 
+// Messages and rpcs for protocols with negotiated compression should be
+// transparently compressed over-the-wire and decompressed on receipt.
+#[test]
+fn peers_send_compressed_messages() {
+    ::aptos_logger::Logger::init_for_testing();
+    let rt = Runtime::new().unwrap();
+    let (
+        (mut peer_a, mut peer_handle_a, mut connection_notifs_rx_a, mut peer_notifs_rx_a),
+        (mut peer_b, mut peer_handle_b, mut connection_notifs_rx_b, mut peer_notifs_rx_b),
+    ) = build_test_connected_peers(rt.handle().clone(), TimeService::mock());
+
+    // Negotiate compression for both protocols
+    let protocols = ProtocolIdSet::from_iter([
+        ProtocolId::StateSyncDirectSend,
+        ProtocolId::StorageServiceRpc,
+    ])
+    .with_compressed();
+    peer_a.connection_metadata.application_protocols = protocols.clone();
+    peer_b.connection_metadata.application_protocols = protocols;
+
+    let remote_peer_id_a = peer_a.remote_peer_id();
+    let remote_peer_id_b = peer_b.remote_peer_id();
+
+    let test = async move {
+        // Peer A -> large message -> Peer B
+        let message = Message {
+            protocol_id: ProtocolId::StateSyncDirectSend,
+            mdata: Bytes::from(vec![1u8; 64 * 1024]),
+        };
+        peer_handle_a.send_direct_send(message.clone());
+        let notif_b = peer_notifs_rx_b.next().await;
+        assert_eq!(notif_b, Some(PeerNotification::RecvMessage(message)));
+
+        // Peer B -> large rpc request -> Peer A
+        let request_data = Bytes::from(vec![2u8; 64 * 1024]);
+        let response_data = Bytes::from(vec![3u8; 64 * 1024]);
+        let client = peer_handle_b.send_rpc_request(
+            ProtocolId::StorageServiceRpc,
+            request_data.clone(),
+            Duration::from_secs(10),
+        );
+        let expected_response_data = response_data.clone();
+        let server = async move {
+            match peer_notifs_rx_a.next().await.unwrap() {
+                PeerNotification::RecvRpc(request) => {
+                    assert_eq!(request.protocol_id, ProtocolId::StorageServiceRpc);
+                    assert_eq!(request.data, request_data);
+                    request.res_tx.send(Ok(response_data)).unwrap();
+                }
+                notif => panic!("Unexpected PeerNotification: {:?}", notif),
+            }
+        };
+        let (response, _) = future::join(client, server).await;
+        assert_eq!(response.unwrap(), expected_response_data);
+
+        // Shut one peers and the other should shutdown due to ConnectionLost
+        drop(peer_handle_a);
+
+        // Check that we received both shutdown events
+        assert_disconnected_event(
+            remote_peer_id_a,
+            DisconnectReason::Requested,
+            &mut connection_notifs_rx_a,
+        )
+        .await;
+        assert_disconnected_event(
+            remote_peer_id_b,
+            DisconnectReason::ConnectionLost,
+            &mut connection_notifs_rx_b,
+        )
+        .await;
+    };
+
+    rt.block_on(future::join3(peer_a.start(), peer_b.start(), test));
+}
+
 #[test]
 fn peer_recv_rpc() {
     ::aptos_logger::Logger::init_for_testing();
@@ -7,28 +7,36 @@ use crate::{
     counters::NETWORK_RATE_LIMIT_METRICS,
     noise::{stream::NoiseStream, HandshakeAuthMode},
     peer_manager::{
-        conn_notifs_channel, ConnectionRequest, ConnectionRequestSender, PeerManager,
-        PeerManagerNotification, PeerManagerRequest, PeerManagerRequestSender,
+        conn_notifs_channel, ConnectionRequest, ConnectionRequestSender, PeerIdTokenBucketLimiter,
+        PeerManager, PeerManagerNotification, PeerManagerRequest, PeerManagerRequestSender,
     },
     protocols::{network::AppConfig, wire::handshake::v1::ProtocolIdSet},
     transport::{self, AptosNetTransport, Connection, DIEM_TCP_TRANSPORT},
     ProtocolId,
 };
 use aptos_config::{
-    config::{PeerSet, RateLimitConfig, HANDSHAKE_VERSION},
+    config::{
+        HandshakeRateLimitConfig, PeerRateLimitConfig, PeerRole, PeerSet, RateLimitConfig,
+        HANDSHAKE_VERSION,
+    },
     network_id::NetworkContext,
 };
 use aptos_crypto::x25519;
-use aptos_infallible::RwLock;
+use aptos_infallible::{NonZeroUsize, RwLock};
 use aptos_logger::prelude::*;
 use aptos_rate_limiter::rate_limit::TokenBucketRateLimiter;
 use aptos_time_service::TimeService;
 use aptos_types::{chain_id::ChainId, network_address::NetworkAddress, PeerId};
-use channel::{self, aptos_channel, message_queues::QueueStyle};
+use channel::{
+    self, aptos_channel,
+    message_queues::{KeyQueueConfig, QueueStyle},
+};
 #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
 use netcore::transport::memory::MemoryTransport;
 use netcore::transport::{
+    quic::{QuicSocket, QuicTransport},
     tcp::{TcpSocket, TcpTransport},
+    tls::{TlsSocket, TlsTransport},
     Transport,
 };
 use std::{clone::Clone, collections::HashMap, fmt::Debug, net::IpAddr, sync::Arc};
@@ -54,11 +62,15 @@ struct TransportContext {
     authentication_mode: AuthenticationMode,
     trusted_peers: Arc<RwLock<PeerSet>>,
     enable_proxy_protocol: bool,
+    enable_quic_0rtt: bool,
+    handshake_rate_limit_config: Option<HandshakeRateLimitConfig>,
 }
 
 impl TransportContext {
     fn add_protocols(&mut self, protocols: &ProtocolIdSet) {
-        self.supported_protocols = self.supported_protocols.union(protocols);
+        // Also advertise the compressed counterparts of the protocols, so that
+        // compression is negotiated with peers that support it.
+        self.supported_protocols = self.supported_protocols.union(&protocols.with_compressed());
     }
 }
 
@@ -73,6 +85,7 @@ struct PeerManagerContext {
     trusted_peers: Arc<RwLock<PeerSet>>,
     upstream_handlers:
         HashMap<ProtocolId, aptos_channel::Sender<(PeerId, ProtocolId), PeerManagerNotification>>,
+    inbound_queue_configs: HashMap<ProtocolId, KeyQueueConfig>,
     connection_event_handlers: Vec<conn_notifs_channel::Sender>,
 
     max_concurrent_network_reqs: usize,
@@ -81,6 +94,7 @@ struct PeerManagerContext {
     inbound_connection_limit: usize,
     inbound_rate_limit_config: Option<RateLimitConfig>,
     outbound_rate_limit_config: Option<RateLimitConfig>,
+    peer_rate_limit_configs: HashMap<PeerRole, PeerRateLimitConfig>,
 }
 
 impl PeerManagerContext {
@@ -97,6 +111,7 @@ impl PeerManagerContext {
             ProtocolId,
             aptos_channel::Sender<(PeerId, ProtocolId), PeerManagerNotification>,
         >,
+        inbound_queue_configs: HashMap<ProtocolId, KeyQueueConfig>,
         connection_event_handlers: Vec<conn_notifs_channel::Sender>,
 
         max_concurrent_network_reqs: usize,
@@ -105,6 +120,7 @@ impl PeerManagerContext {
         inbound_connection_limit: usize,
         inbound_rate_limit_config: Option<RateLimitConfig>,
         outbound_rate_limit_config: Option<RateLimitConfig>,
+        peer_rate_limit_configs: HashMap<PeerRole, PeerRateLimitConfig>,
     ) -> Self {
         Self {
             pm_reqs_tx,
@@ -115,6 +131,7 @@ impl PeerManagerContext {
             peer_metadata_storage,
             trusted_peers,
             upstream_handlers,
+            inbound_queue_configs,
             connection_event_handlers,
 
             max_concurrent_network_reqs,
@@ -123,6 +140,7 @@ impl PeerManagerContext {
             inbound_connection_limit,
             inbound_rate_limit_config,
             outbound_rate_limit_config,
+            peer_rate_limit_configs,
         }
     }
 
@@ -130,8 +148,26 @@ impl PeerManagerContext {
         &mut self,
         protocol_id: ProtocolId,
         channel: aptos_channel::Sender<(PeerId, ProtocolId), PeerManagerNotification>,
+        inbound_queue: aptos_channel::Config,
     ) -> &mut Self {
         self.upstream_handlers.insert(protocol_id, channel);
+
+        // Each protocol gets its own bounded inbound queue per peer, with the
+        // same queue style and capacity as the upstream handler. This ensures a
+        // flood of messages on one protocol cannot starve the others.
+        let inbound_queue_config = KeyQueueConfig::new(
+            inbound_queue.queue_style,
+            NonZeroUsize!(
+                inbound_queue.max_capacity,
+                "Inbound queue capacity cannot be 0"
+            ),
+        )
+        .labeled_counters(
+            &counters::PENDING_INBOUND_PROTOCOL_NOTIFICATIONS,
+            protocol_id.as_str(),
+        );
+        self.inbound_queue_configs
+            .insert(protocol_id, inbound_queue_config);
         self
     }
 
@@ -146,11 +182,15 @@ impl PeerManagerContext {
 type MemoryPeerManager =
     PeerManager<AptosNetTransport<MemoryTransport>, NoiseStream<memsocket::MemorySocket>>;
 type TcpPeerManager = PeerManager<AptosNetTransport<TcpTransport>, NoiseStream<TcpSocket>>;
+type TlsPeerManager = PeerManager<AptosNetTransport<TlsTransport>, NoiseStream<TlsSocket>>;
+type QuicPeerManager = PeerManager<AptosNetTransport<QuicTransport>, NoiseStream<QuicSocket>>;
 
 enum TransportPeerManager {
     #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
     Memory(MemoryPeerManager),
     Tcp(TcpPeerManager),
+    Tls(TlsPeerManager),
+    Quic(QuicPeerManager),
 }
 
 pub struct PeerManagerBuilder {
@@ -179,9 +219,12 @@ impl PeerManagerBuilder {
         max_concurrent_network_reqs: usize,
         max_frame_size: usize,
         enable_proxy_protocol: bool,
+        enable_quic_0rtt: bool,
         inbound_connection_limit: usize,
         inbound_rate_limit_config: Option<RateLimitConfig>,
         outbound_rate_limit_config: Option<RateLimitConfig>,
+        peer_rate_limit_configs: HashMap<PeerRole, PeerRateLimitConfig>,
+        handshake_rate_limit_config: Option<HandshakeRateLimitConfig>,
     ) -> Self {
         // Setup channel to send requests to peer manager.
         let (pm_reqs_tx, pm_reqs_rx) = aptos_channel::new(
@@ -202,6 +245,8 @@ impl PeerManagerBuilder {
                 authentication_mode,
                 trusted_peers: trusted_peers.clone(),
                 enable_proxy_protocol,
+                enable_quic_0rtt,
+                handshake_rate_limit_config,
             }),
             peer_manager_context: Some(PeerManagerContext::new(
                 pm_reqs_tx,
@@ -211,6 +256,7 @@ impl PeerManagerBuilder {
                 peer_metadata_storage,
                 trusted_peers,
                 HashMap::new(),
+                HashMap::new(),
                 Vec::new(),
                 max_concurrent_network_reqs,
                 channel_size,
@@ -218,6 +264,7 @@ impl PeerManagerBuilder {
                 inbound_connection_limit,
                 inbound_rate_limit_config,
                 outbound_rate_limit_config,
+                peer_rate_limit_configs,
             )),
             peer_manager: None,
             listen_address,
@@ -261,6 +308,8 @@ impl PeerManagerBuilder {
         let protos = transport_context.supported_protocols;
         let chain_id = transport_context.chain_id;
         let enable_proxy_protocol = transport_context.enable_proxy_protocol;
+        let enable_quic_0rtt = transport_context.enable_quic_0rtt;
+        let handshake_rate_limit_config = transport_context.handshake_rate_limit_config;
 
         let (key, auth_mode) = match transport_context.authentication_mode {
             AuthenticationMode::MaybeMutual(key) => (
@@ -286,6 +335,56 @@ impl PeerManagerBuilder {
                         chain_id,
                         protos,
                         enable_proxy_protocol,
+                        handshake_rate_limit_config,
+                    ),
+                    executor,
+                )))
+            }
+            [Ip4(_), Tcp(_), Tls] | [Ip6(_), Tcp(_), Tls] => {
+                let tls_transport = TlsTransport::new(
+                    DIEM_TCP_TRANSPORT.clone(),
+                    self.network_context.peer_id(),
+                    &key,
+                    auth_mode.tls_client_authorizer(),
+                )
+                .unwrap_or_else(|error| {
+                    panic!(
+                        "{} Unable to create the TLS transport: {}",
+                        self.network_context, error
+                    )
+                });
+                Some(TransportPeerManager::Tls(self.build_with_transport(
+                    AptosNetTransport::new(
+                        tls_transport,
+                        self.network_context,
+                        self.time_service.clone(),
+                        key,
+                        auth_mode,
+                        HANDSHAKE_VERSION,
+                        chain_id,
+                        protos,
+                        // Proxy protocol headers would precede the TLS session,
+                        // where the AptosNet transport can't read them
+                        false,
+                        handshake_rate_limit_config,
+                    ),
+                    executor,
+                )))
+            }
+            [Ip4(_), Udp(_)] | [Ip6(_), Udp(_)] => {
+                Some(TransportPeerManager::Quic(self.build_with_transport(
+                    AptosNetTransport::new(
+                        QuicTransport::new(enable_quic_0rtt),
+                        self.network_context,
+                        self.time_service.clone(),
+                        key,
+                        auth_mode,
+                        HANDSHAKE_VERSION,
+                        chain_id,
+                        protos,
+                        // Proxy protocol headers are only prepended by TCP proxies
+                        false,
+                        handshake_rate_limit_config,
                     ),
                     executor,
                 )))
@@ -302,12 +401,15 @@ impl PeerManagerBuilder {
                     chain_id,
                     protos,
                     enable_proxy_protocol,
+                    handshake_rate_limit_config,
                 ),
                 executor,
             ))),
             _ => panic!(
                 "{} Unsupported listen_address: '{}', expected '/memory/<port>', \
-                 '/ip4/<addr>/tcp/<port>', or '/ip6/<addr>/tcp/<port>'.",
+                 '/ip4/<addr>/tcp/<port>', '/ip6/<addr>/tcp/<port>', \
+                 '/ip4/<addr>/tcp/<port>/tls', '/ip6/<addr>/tcp/<port>/tls', \
+                 '/ip4/<addr>/udp/<port>', or '/ip6/<addr>/udp/<port>'.",
                 self.network_context, self.listen_address
             ),
         };
@@ -340,6 +442,11 @@ impl PeerManagerBuilder {
             "outbound",
             pm_context.outbound_rate_limit_config,
         );
+        let (inbound_peer_rate_limiters, outbound_peer_rate_limiters) =
+            peer_token_bucket_rate_limiters(
+                &self.network_context,
+                &pm_context.peer_rate_limit_configs,
+            );
         let peer_mgr = PeerManager::new(
             executor.clone(),
             self.time_service.clone(),
@@ -353,6 +460,7 @@ impl PeerManagerBuilder {
             pm_context.pm_reqs_rx,
             pm_context.connection_reqs_rx,
             pm_context.upstream_handlers,
+            pm_context.inbound_queue_configs,
             pm_context.connection_event_handlers,
             pm_context.max_concurrent_network_reqs,
             pm_context.channel_size,
@@ -360,6 +468,8 @@ impl PeerManagerBuilder {
             pm_context.inbound_connection_limit,
             inbound_rate_limiters,
             outbound_rate_limiters,
+            inbound_peer_rate_limiters,
+            outbound_peer_rate_limiters,
         );
 
         // PeerManager constructor appends a public key to the listen_address.
@@ -390,6 +500,8 @@ impl PeerManagerBuilder {
             #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
             TransportPeerManager::Memory(pm) => self.start_peer_manager(pm, executor),
             TransportPeerManager::Tcp(pm) => self.start_peer_manager(pm, executor),
+            TransportPeerManager::Tls(pm) => self.start_peer_manager(pm, executor),
+            TransportPeerManager::Quic(pm) => self.start_peer_manager(pm, executor),
         }
     }
 
@@ -439,13 +551,11 @@ impl PeerManagerBuilder {
     ) {
         self.transport_context().add_protocols(&config.protocols);
 
-        let (network_notifs_tx, network_notifs_rx) = config
-            .inbound_queue
-            .expect("Requires a service config")
-            .build();
+        let inbound_queue = config.inbound_queue.expect("Requires a service config");
+        let (network_notifs_tx, network_notifs_rx) = inbound_queue.build();
         let pm_context = self.peer_manager_context();
         for protocol in config.protocols.iter() {
-            pm_context.add_upstream_handler(protocol, network_notifs_tx.clone());
+            pm_context.add_upstream_handler(protocol, network_notifs_tx.clone(), inbound_queue);
         }
         let connection_notifs_rx = pm_context.add_connection_event_listener();
 
@@ -473,3 +583,42 @@ fn token_bucket_rate_limiter(
     }
     TokenBucketRateLimiter::open(label)
 }
+
+/// Builds the inbound and outbound per peer token bucket rate limiters for every
+/// peer role with an enabled rate limit config
+fn peer_token_bucket_rate_limiters(
+    network_context: &NetworkContext,
+    configs: &HashMap<PeerRole, PeerRateLimitConfig>,
+) -> (
+    HashMap<PeerRole, PeerIdTokenBucketLimiter>,
+    HashMap<PeerRole, PeerIdTokenBucketLimiter>,
+) {
+    let mut inbound_rate_limiters = HashMap::new();
+    let mut outbound_rate_limiters = HashMap::new();
+    for (role, config) in configs.iter().filter(|(_, config)| config.enabled) {
+        let log_info = format!("{} {}", network_context, role.as_str());
+        inbound_rate_limiters.insert(
+            *role,
+            TokenBucketRateLimiter::new(
+                "peer_inbound",
+                log_info.clone(),
+                config.initial_bucket_fill_percentage,
+                config.ingress_byte_bucket_size,
+                config.ingress_byte_bucket_rate,
+                Some(NETWORK_RATE_LIMIT_METRICS.clone()),
+            ),
+        );
+        outbound_rate_limiters.insert(
+            *role,
+            TokenBucketRateLimiter::new(
+                "peer_outbound",
+                log_info,
+                config.initial_bucket_fill_percentage,
+                config.egress_byte_bucket_size,
+                config.egress_byte_bucket_rate,
+                Some(NETWORK_RATE_LIMIT_METRICS.clone()),
+            ),
+        );
+    }
+    (inbound_rate_limiters, outbound_rate_limiters)
+}
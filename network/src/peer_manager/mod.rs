@@ -26,7 +26,10 @@ use aptos_logger::prelude::*;
 use aptos_rate_limiter::rate_limit::TokenBucketRateLimiter;
 use aptos_time_service::{TimeService, TimeServiceTrait};
 use aptos_types::{network_address::NetworkAddress, PeerId};
-use channel::{self, aptos_channel, message_queues::QueueStyle};
+use channel::{
+    self, aptos_channel,
+    message_queues::{KeyQueueConfig, QueueStyle},
+};
 use futures::{
     channel::oneshot,
     io::{AsyncRead, AsyncWrite, AsyncWriteExt},
@@ -65,6 +68,7 @@ pub use senders::*;
 pub use types::*;
 
 pub type IpAddrTokenBucketLimiter = TokenBucketRateLimiter<IpAddr>;
+pub type PeerIdTokenBucketLimiter = TokenBucketRateLimiter<PeerId>;
 
 /// Responsible for handling and maintaining connections to other Peers
 pub struct PeerManager<TTransport, TSocket>
@@ -99,6 +103,10 @@ where
     /// of messages across (PeerId, ProtocolId).
     upstream_handlers:
         HashMap<ProtocolId, aptos_channel::Sender<(PeerId, ProtocolId), PeerManagerNotification>>,
+    /// The queue configs for the inbound messages of each protocol. Every peer gets a
+    /// separately bounded queue per protocol, so that a flood of messages on one protocol
+    /// cannot starve the delivery of messages on other protocols.
+    inbound_queue_configs: HashMap<ProtocolId, KeyQueueConfig>,
     /// Channels to send NewPeer/LostPeer notifications to.
     connection_event_handlers: Vec<conn_notifs_channel::Sender>,
     /// Channel used to send Dial requests to the ConnectionHandler actor
@@ -126,6 +134,10 @@ where
     inbound_rate_limiters: IpAddrTokenBucketLimiter,
     /// Keyed storage of all outbound rate limiters
     outbound_rate_limiters: IpAddrTokenBucketLimiter,
+    /// Keyed storage of the per peer inbound rate limiters, by role of the peer
+    inbound_peer_rate_limiters: HashMap<PeerRole, PeerIdTokenBucketLimiter>,
+    /// Keyed storage of the per peer outbound rate limiters, by role of the peer
+    outbound_peer_rate_limiters: HashMap<PeerRole, PeerIdTokenBucketLimiter>,
 }
 
 impl<TTransport, TSocket> PeerManager<TTransport, TSocket>
@@ -149,6 +161,7 @@ where
             ProtocolId,
             aptos_channel::Sender<(PeerId, ProtocolId), PeerManagerNotification>,
         >,
+        inbound_queue_configs: HashMap<ProtocolId, KeyQueueConfig>,
         connection_event_handlers: Vec<conn_notifs_channel::Sender>,
         channel_size: usize,
         max_concurrent_network_reqs: usize,
@@ -156,6 +169,8 @@ where
         inbound_connection_limit: usize,
         inbound_rate_limiters: IpAddrTokenBucketLimiter,
         outbound_rate_limiters: IpAddrTokenBucketLimiter,
+        inbound_peer_rate_limiters: HashMap<PeerRole, PeerIdTokenBucketLimiter>,
+        outbound_peer_rate_limiters: HashMap<PeerRole, PeerIdTokenBucketLimiter>,
     ) -> Self {
         let (transport_notifs_tx, transport_notifs_rx) = channel::new(
             channel_size,
@@ -193,6 +208,7 @@ where
             outstanding_disconnect_requests: HashMap::new(),
             phantom_transport: PhantomData,
             upstream_handlers,
+            inbound_queue_configs,
             connection_event_handlers,
             max_concurrent_network_reqs,
             channel_size,
@@ -200,6 +216,8 @@ where
             inbound_connection_limit,
             inbound_rate_limiters,
             outbound_rate_limiters,
+            inbound_peer_rate_limiters,
+            outbound_peer_rate_limiters,
         }
     }
 
@@ -409,6 +427,7 @@ where
                     .addr
                     .find_ip_addr()
                     .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+                let peer_role = lost_conn_metadata.role;
 
                 // Notify upstream if there's still no active connection. This might be redundant,
                 // but does not affect correctness.
@@ -425,6 +444,12 @@ where
                 self.inbound_rate_limiters.try_garbage_collect_key(&ip_addr);
                 self.outbound_rate_limiters
                     .try_garbage_collect_key(&ip_addr);
+                if let Some(limiters) = self.inbound_peer_rate_limiters.get(&peer_role) {
+                    limiters.try_garbage_collect_key(&peer_id);
+                }
+                if let Some(limiters) = self.outbound_peer_rate_limiters.get(&peer_role) {
+                    limiters.try_garbage_collect_key(&peer_id);
+                }
             }
         }
     }
@@ -657,6 +682,14 @@ where
             .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
         let inbound_rate_limiter = self.inbound_rate_limiters.bucket(ip_addr);
         let outbound_rate_limiter = self.outbound_rate_limiters.bucket(ip_addr);
+        let inbound_peer_rate_limiter = self
+            .inbound_peer_rate_limiters
+            .get(&conn_meta.role)
+            .map(|limiters| limiters.bucket(peer_id));
+        let outbound_peer_rate_limiter = self
+            .outbound_peer_rate_limiters
+            .get(&conn_meta.role)
+            .map(|limiters| limiters.bucket(peer_id));
 
         // TODO: Add label for peer.
         let (peer_reqs_tx, peer_reqs_rx) = aptos_channel::new(
@@ -665,10 +698,11 @@ where
             Some(&counters::PENDING_NETWORK_REQUESTS),
         );
         // TODO: Add label for peer.
-        let (peer_notifs_tx, peer_notifs_rx) = aptos_channel::new(
+        let (peer_notifs_tx, peer_notifs_rx) = aptos_channel::new_with_key_queue_configs(
             QueueStyle::FIFO,
             self.channel_size,
             Some(&counters::PENDING_NETWORK_NOTIFICATIONS),
+            self.inbound_queue_configs.clone(),
         );
 
         // Initialize a new Peer actor for this connection.
@@ -686,6 +720,8 @@ where
             self.max_frame_size,
             Some(inbound_rate_limiter),
             Some(outbound_rate_limiter),
+            inbound_peer_rate_limiter,
+            outbound_peer_rate_limiter,
         );
         self.executor.spawn(peer.start());
 
@@ -10,4 +10,7 @@ pub mod rpc;
 
 pub mod health_checker;
 pub mod identity;
+pub mod peer_monitoring;
+pub mod reachability;
+pub mod registrar;
 pub mod wire;
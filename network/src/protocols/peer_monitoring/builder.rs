@@ -0,0 +1,52 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    application::storage::PeerMetadataStorage,
+    protocols::peer_monitoring::{
+        PeerMonitor, PeerMonitoringNetworkEvents, PeerMonitoringNetworkSender,
+    },
+};
+use aptos_config::network_id::NetworkContext;
+use aptos_time_service::TimeService;
+use aptos_types::transaction::Version;
+use futures::stream::BoxStream;
+use std::{sync::Arc, time::Duration};
+use tokio::runtime::Handle;
+
+pub struct PeerMonitorBuilder {
+    service: Option<PeerMonitor>,
+}
+
+impl PeerMonitorBuilder {
+    pub fn new(
+        network_context: NetworkContext,
+        time_service: TimeService,
+        ping_interval_ms: u64,
+        ping_timeout_ms: u64,
+        network_tx: PeerMonitoringNetworkSender,
+        network_rx: PeerMonitoringNetworkEvents,
+        peer_metadata_storage: Arc<PeerMetadataStorage>,
+        synced_versions: Option<BoxStream<'static, Version>>,
+    ) -> Self {
+        let service = PeerMonitor::new(
+            network_context,
+            time_service,
+            network_tx,
+            network_rx,
+            peer_metadata_storage,
+            Duration::from_millis(ping_interval_ms),
+            Duration::from_millis(ping_timeout_ms),
+            synced_versions,
+        );
+        Self {
+            service: Some(service),
+        }
+    }
+
+    pub fn start(&mut self, executor: &Handle) {
+        if let Some(service) = self.service.take() {
+            executor.spawn(service.start());
+        }
+    }
+}
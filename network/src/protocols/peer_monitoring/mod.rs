@@ -0,0 +1,355 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Protocol used to monitor connected peers
+//!
+//! The PeerMonitor periodically pings every connected peer. Pings and their responses
+//! carry the [`NodeInfo`] of the sender (its build version, role and latest synced
+//! version), so both ends of a connection learn about each other. The round trip
+//! latency of the pings and the reported node information are stored in the
+//! [`PeerMetadataStorage`], where applications (e.g., state sync and mempool) use
+//! them for peer selection.
+//!
+//! Unlike the HealthChecker, the PeerMonitor never disconnects from peers: failed
+//! pings are only logged and counted.
+use crate::{
+    application::storage::PeerMetadataStorage,
+    constants::NETWORK_CHANNEL_SIZE,
+    counters::{self, FAILED_LABEL, RECEIVED_LABEL, SENT_LABEL, SUCCEEDED_LABEL},
+    logging::NetworkSchema,
+    peer_manager::{ConnectionRequestSender, PeerManagerRequestSender},
+    protocols::{
+        network::{
+            AppConfig, ApplicationNetworkSender, Event, NetworkEvents, NetworkSender,
+            NewNetworkSender,
+        },
+        rpc::error::RpcError,
+    },
+    ProtocolId,
+};
+use aptos_config::{
+    config::RoleType,
+    network_id::{NetworkContext, PeerNetworkId},
+};
+use aptos_logger::prelude::*;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use aptos_types::{transaction::Version, PeerId};
+use async_trait::async_trait;
+use bytes::Bytes;
+use channel::{aptos_channel, message_queues::QueueStyle};
+use futures::{
+    channel::oneshot,
+    future::{BoxFuture, FutureExt},
+    stream::{BoxStream, FuturesUnordered, StreamExt},
+};
+use serde::{Deserialize, Serialize};
+use short_hex_str::AsShortHexStr;
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+pub mod builder;
+#[cfg(test)]
+mod test;
+
+/// Weight of a new latency sample in the moving average of a peer's ping latency
+pub const LATENCY_SAMPLE_WEIGHT: f64 = 0.25;
+
+/// The interface from Network to PeerMonitor layer.
+pub type PeerMonitoringNetworkEvents = NetworkEvents<PeerMonitoringMsg>;
+
+/// The interface from PeerMonitor to Networking layer.
+#[derive(Clone)]
+pub struct PeerMonitoringNetworkSender {
+    inner: NetworkSender<PeerMonitoringMsg>,
+}
+
+/// Configuration for the network endpoints to support the PeerMonitor.
+pub fn network_endpoint_config() -> AppConfig {
+    AppConfig::p2p(
+        [ProtocolId::PeerMonitoringRpc],
+        aptos_channel::Config::new(NETWORK_CHANNEL_SIZE).queue_style(QueueStyle::LIFO),
+    )
+}
+
+impl NewNetworkSender for PeerMonitoringNetworkSender {
+    fn new(
+        peer_mgr_reqs_tx: PeerManagerRequestSender,
+        connection_reqs_tx: ConnectionRequestSender,
+    ) -> Self {
+        Self {
+            inner: NetworkSender::new(peer_mgr_reqs_tx, connection_reqs_tx),
+        }
+    }
+}
+
+#[async_trait]
+impl ApplicationNetworkSender<PeerMonitoringMsg> for PeerMonitoringNetworkSender {
+    async fn send_rpc(
+        &self,
+        recipient: PeerId,
+        req_msg: PeerMonitoringMsg,
+        timeout: Duration,
+    ) -> Result<PeerMonitoringMsg, RpcError> {
+        let protocol = ProtocolId::PeerMonitoringRpc;
+        self.inner
+            .send_rpc(recipient, protocol, req_msg, timeout)
+            .await
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum PeerMonitoringMsg {
+    /// Pings the peer, carrying the node information of the sender
+    Ping(NodeInfo),
+    /// Response to a ping, carrying the node information of the responder
+    Pong(NodeInfo),
+}
+
+/// Information about a node, exchanged with every ping
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct NodeInfo {
+    /// The git revision the node was built from
+    pub build_version: String,
+    /// The role of the node
+    pub role: RoleType,
+    /// The latest version synced by the node, if known
+    pub synced_version: Option<Version>,
+}
+
+/// The actor pinging connected peers, and answering pings from peers
+pub struct PeerMonitor {
+    network_context: NetworkContext,
+    /// A handle to a time service for easily mocking time-related operations.
+    time_service: TimeService,
+    network_tx: PeerMonitoringNetworkSender,
+    network_rx: PeerMonitoringNetworkEvents,
+    peer_metadata_storage: Arc<PeerMetadataStorage>,
+    /// Time we wait between each round of pings.
+    ping_interval: Duration,
+    /// Ping timeout duration.
+    ping_timeout: Duration,
+    /// The versions synced by the local node, as they're committed
+    synced_versions: Option<BoxStream<'static, Version>>,
+    /// The information about the local node sent to peers
+    node_info: NodeInfo,
+}
+
+impl PeerMonitor {
+    pub fn new(
+        network_context: NetworkContext,
+        time_service: TimeService,
+        network_tx: PeerMonitoringNetworkSender,
+        network_rx: PeerMonitoringNetworkEvents,
+        peer_metadata_storage: Arc<PeerMetadataStorage>,
+        ping_interval: Duration,
+        ping_timeout: Duration,
+        synced_versions: Option<BoxStream<'static, Version>>,
+    ) -> Self {
+        let node_info = NodeInfo {
+            build_version: aptos_metrics::json_metrics::get_git_rev(),
+            role: network_context.role(),
+            synced_version: None,
+        };
+        Self {
+            network_context,
+            time_service,
+            network_tx,
+            network_rx,
+            peer_metadata_storage,
+            ping_interval,
+            ping_timeout,
+            synced_versions,
+            node_info,
+        }
+    }
+
+    pub async fn start(mut self) {
+        info!(
+            NetworkSchema::new(&self.network_context),
+            "{} Peer monitor actor started", self.network_context
+        );
+
+        let mut ticker = self.time_service.interval(self.ping_interval).fuse();
+        let mut synced_versions = self
+            .synced_versions
+            .take()
+            .unwrap_or_else(|| futures::stream::pending().boxed())
+            .fuse();
+        let mut pings = FuturesUnordered::new();
+        // Peers with a ping in flight, which aren't pinged again until it completes
+        let mut pinged_peers = HashSet::new();
+
+        loop {
+            futures::select! {
+                maybe_event = self.network_rx.next() => {
+                    // Shutdown when the network instance shuts down
+                    let event = match maybe_event {
+                        Some(event) => event,
+                        None => break,
+                    };
+
+                    match event {
+                        Event::RpcRequest(peer_id, PeerMonitoringMsg::Ping(node_info), protocol, res_tx) => {
+                            self.handle_ping_request(peer_id, node_info, protocol, res_tx);
+                        }
+                        Event::RpcRequest(peer_id, msg, _, _) => {
+                            warn!(
+                                NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                                "{} Unexpected RPC message from {}: {:?}",
+                                self.network_context,
+                                peer_id,
+                                msg
+                            );
+                        }
+                        Event::Message(peer_id, msg) => {
+                            warn!(
+                                NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                                "{} Unexpected direct send from {}: {:?}",
+                                self.network_context,
+                                peer_id,
+                                msg
+                            );
+                        }
+                        Event::NewPeer(_) | Event::LostPeer(_) => {}
+                    }
+                }
+                _ = ticker.select_next_some() => {
+                    for peer_id in self.connected_peers() {
+                        if pinged_peers.insert(peer_id) {
+                            pings.push(self.ping(peer_id));
+                        }
+                    }
+                }
+                (peer_id, result) = pings.select_next_some() => {
+                    pinged_peers.remove(&peer_id);
+                    self.handle_ping_response(peer_id, result);
+                }
+                version = synced_versions.select_next_some() => {
+                    self.node_info.synced_version = Some(version);
+                }
+            }
+        }
+        warn!(
+            NetworkSchema::new(&self.network_context),
+            "{} Peer monitor actor terminated", self.network_context
+        );
+    }
+
+    /// The connected peers supporting the peer monitoring protocol
+    fn connected_peers(&self) -> Vec<PeerId> {
+        self.peer_metadata_storage
+            .read_filtered(self.network_context.network_id(), |(_, info)| {
+                info.is_connected() && info.supports_protocol(ProtocolId::PeerMonitoringRpc)
+            })
+            .into_keys()
+            .map(|peer_network_id| peer_network_id.peer_id())
+            .collect()
+    }
+
+    /// Pings the peer, resolving to the peer's node information and the round trip latency
+    fn ping(
+        &self,
+        peer_id: PeerId,
+    ) -> BoxFuture<'static, (PeerId, Result<(NodeInfo, Duration), RpcError>)> {
+        let network_tx = self.network_tx.clone();
+        let time_service = self.time_service.clone();
+        let ping_timeout = self.ping_timeout;
+        let ping = PeerMonitoringMsg::Ping(self.node_info.clone());
+        async move {
+            let start = time_service.now();
+            let result = network_tx
+                .send_rpc(peer_id, ping, ping_timeout)
+                .await
+                .and_then(|msg| match msg {
+                    PeerMonitoringMsg::Pong(node_info) => {
+                        Ok((node_info, time_service.now().duration_since(start)))
+                    }
+                    _ => Err(RpcError::InvalidRpcResponse),
+                });
+            (peer_id, result)
+        }
+        .boxed()
+    }
+
+    fn handle_ping_response(
+        &self,
+        peer_id: PeerId,
+        result: Result<(NodeInfo, Duration), RpcError>,
+    ) {
+        match result {
+            Ok((node_info, latency)) => {
+                counters::peer_monitoring_pings(&self.network_context, SENT_LABEL, SUCCEEDED_LABEL)
+                    .inc();
+                let network_context = &self.network_context;
+                self.peer_metadata_storage.update_monitoring_metadata(
+                    PeerNetworkId::new(network_context.network_id(), peer_id),
+                    |metadata| {
+                        let average = average_latency(metadata.average_ping_latency, latency);
+                        counters::peer_ping_latency(network_context, &peer_id, average);
+                        metadata.average_ping_latency = Some(average);
+                        metadata.node_info = Some(node_info);
+                    },
+                );
+            }
+            Err(error) => {
+                counters::peer_monitoring_pings(&self.network_context, SENT_LABEL, FAILED_LABEL)
+                    .inc();
+                debug!(
+                    NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                    error = ?error,
+                    "{} Ping to peer {} failed: {:?}",
+                    self.network_context,
+                    peer_id.short_str(),
+                    error
+                );
+            }
+        }
+    }
+
+    fn handle_ping_request(
+        &self,
+        peer_id: PeerId,
+        node_info: NodeInfo,
+        protocol: ProtocolId,
+        res_tx: oneshot::Sender<Result<Bytes, RpcError>>,
+    ) {
+        self.peer_metadata_storage.update_monitoring_metadata(
+            PeerNetworkId::new(self.network_context.network_id(), peer_id),
+            |metadata| metadata.node_info = Some(node_info),
+        );
+
+        match protocol.to_bytes(&PeerMonitoringMsg::Pong(self.node_info.clone())) {
+            Ok(message) => {
+                counters::peer_monitoring_pings(
+                    &self.network_context,
+                    RECEIVED_LABEL,
+                    SUCCEEDED_LABEL,
+                )
+                .inc();
+                let _ = res_tx.send(Ok(message.into()));
+            }
+            Err(error) => {
+                counters::peer_monitoring_pings(
+                    &self.network_context,
+                    RECEIVED_LABEL,
+                    FAILED_LABEL,
+                )
+                .inc();
+                warn!(
+                    NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                    error = ?error,
+                    "{} Unable to serialize pong response: {}", self.network_context, error
+                );
+            }
+        }
+    }
+}
+
+/// Folds a new latency sample into the moving average of a peer's ping latency
+pub fn average_latency(average: Option<Duration>, sample: Duration) -> Duration {
+    match average {
+        Some(average) => {
+            average.mul_f64(1.0 - LATENCY_SAMPLE_WEIGHT) + sample.mul_f64(LATENCY_SAMPLE_WEIGHT)
+        }
+        None => sample,
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{
+    application::types::PeerMonitoringMetadata,
+    peer_manager::{conn_notifs_channel, PeerManagerNotification, PeerManagerRequest},
+    protocols::{
+        network::{NewNetworkEvents, NewNetworkSender},
+        rpc::InboundRpcRequest,
+        wire::handshake::v1::ProtocolIdSet,
+    },
+    transport::ConnectionMetadata,
+};
+use aptos_time_service::MockTimeService;
+use futures::{executor::block_on, future};
+
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+const PING_TIMEOUT: Duration = Duration::from_millis(500);
+
+struct TestHarness {
+    mock_time: MockTimeService,
+    peer_metadata_storage: Arc<PeerMetadataStorage>,
+    peer_mgr_reqs_rx: aptos_channel::Receiver<(PeerId, ProtocolId), PeerManagerRequest>,
+    peer_mgr_notifs_tx: aptos_channel::Sender<(PeerId, ProtocolId), PeerManagerNotification>,
+    _connection_notifs_tx: conn_notifs_channel::Sender,
+}
+
+impl TestHarness {
+    fn new() -> (Self, PeerMonitor) {
+        ::aptos_logger::Logger::init_for_testing();
+        let mock_time = TimeService::mock();
+        let peer_metadata_storage = PeerMetadataStorage::test();
+
+        let (peer_mgr_reqs_tx, peer_mgr_reqs_rx) = aptos_channel::new(QueueStyle::FIFO, 1, None);
+        let (connection_reqs_tx, _connection_reqs_rx) =
+            aptos_channel::new(QueueStyle::FIFO, 1, None);
+        let (peer_mgr_notifs_tx, peer_mgr_notifs_rx) =
+            aptos_channel::new(QueueStyle::FIFO, 1, None);
+        let (connection_notifs_tx, connection_notifs_rx) = conn_notifs_channel::new();
+
+        let network_tx = PeerMonitoringNetworkSender::new(
+            PeerManagerRequestSender::new(peer_mgr_reqs_tx),
+            ConnectionRequestSender::new(connection_reqs_tx),
+        );
+        let network_rx = PeerMonitoringNetworkEvents::new(peer_mgr_notifs_rx, connection_notifs_rx);
+        let peer_monitor = PeerMonitor::new(
+            NetworkContext::mock(),
+            mock_time.clone(),
+            network_tx,
+            network_rx,
+            peer_metadata_storage.clone(),
+            PING_INTERVAL,
+            PING_TIMEOUT,
+            None,
+        );
+
+        (
+            Self {
+                mock_time: mock_time.into_mock(),
+                peer_metadata_storage,
+                peer_mgr_reqs_rx,
+                peer_mgr_notifs_tx,
+                _connection_notifs_tx: connection_notifs_tx,
+            },
+            peer_monitor,
+        )
+    }
+
+    fn add_peer(&self, peer_id: PeerId) {
+        let mut connection_metadata = ConnectionMetadata::mock(peer_id);
+        connection_metadata.application_protocols = ProtocolIdSet::all_known();
+        self.peer_metadata_storage
+            .insert_connection(NetworkContext::mock().network_id(), connection_metadata);
+    }
+
+    fn monitoring_metadata(&self, peer_id: PeerId) -> PeerMonitoringMetadata {
+        self.peer_metadata_storage
+            .read(PeerNetworkId::new(
+                NetworkContext::mock().network_id(),
+                peer_id,
+            ))
+            .unwrap()
+            .monitoring_metadata
+    }
+
+    /// Waits until the peer monitor has stored node information for the peer
+    async fn wait_for_node_info(&self, peer_id: PeerId) -> PeerMonitoringMetadata {
+        loop {
+            let metadata = self.monitoring_metadata(peer_id);
+            if metadata.node_info.is_some() {
+                return metadata;
+            }
+            tokio::task::yield_now().await;
+        }
+    }
+
+    async fn trigger_ping(&self) {
+        self.mock_time.advance_async(PING_INTERVAL).await;
+    }
+
+    async fn expect_ping_send_pong(&mut self, node_info: NodeInfo) {
+        let req = self.peer_mgr_reqs_rx.next().await.unwrap();
+        let rpc_req = match req {
+            PeerManagerRequest::SendRpc(_peer_id, rpc_req) => rpc_req,
+            _ => panic!("Unexpected PeerManagerRequest: {:?}", req),
+        };
+        assert_eq!(rpc_req.protocol_id, ProtocolId::PeerMonitoringRpc);
+
+        match bcs::from_bytes(&rpc_req.data).unwrap() {
+            PeerMonitoringMsg::Ping(_) => {}
+            msg => panic!("Unexpected PeerMonitoringMsg: {:?}", msg),
+        }
+        let res_data = bcs::to_bytes(&PeerMonitoringMsg::Pong(node_info)).unwrap();
+        rpc_req.res_tx.send(Ok(res_data.into())).unwrap();
+    }
+
+    async fn send_inbound_ping(
+        &mut self,
+        peer_id: PeerId,
+        node_info: NodeInfo,
+    ) -> oneshot::Receiver<Result<Bytes, RpcError>> {
+        let protocol_id = ProtocolId::PeerMonitoringRpc;
+        let data = bcs::to_bytes(&PeerMonitoringMsg::Ping(node_info))
+            .unwrap()
+            .into();
+        let (res_tx, res_rx) = oneshot::channel();
+        let inbound_rpc_req = InboundRpcRequest {
+            protocol_id,
+            data,
+            res_tx,
+        };
+        let (delivered_tx, delivered_rx) = oneshot::channel();
+        self.peer_mgr_notifs_tx
+            .push_with_feedback(
+                (peer_id, protocol_id),
+                PeerManagerNotification::RecvRpc(peer_id, inbound_rpc_req),
+                Some(delivered_tx),
+            )
+            .unwrap();
+        delivered_rx.await.unwrap();
+        res_rx
+    }
+}
+
+fn mock_node_info(synced_version: Version) -> NodeInfo {
+    NodeInfo {
+        build_version: "mock".to_string(),
+        role: RoleType::FullNode,
+        synced_version: Some(synced_version),
+    }
+}
+
+#[test]
+fn test_average_latency() {
+    let sample = Duration::from_millis(100);
+    assert_eq!(average_latency(None, sample), sample);
+    assert_eq!(
+        average_latency(Some(Duration::from_millis(200)), sample),
+        Duration::from_millis(175)
+    );
+}
+
+#[test]
+fn outbound() {
+    let (mut harness, peer_monitor) = TestHarness::new();
+
+    let test = async move {
+        let peer_id = PeerId::new([0x42; PeerId::LENGTH]);
+        harness.add_peer(peer_id);
+
+        // The peer is pinged, and its node information and latency are stored
+        harness.trigger_ping().await;
+        harness.expect_ping_send_pong(mock_node_info(10)).await;
+
+        let metadata = harness.wait_for_node_info(peer_id).await;
+        assert_eq!(metadata.node_info, Some(mock_node_info(10)));
+        assert!(metadata.average_ping_latency.is_some());
+    };
+    block_on(future::join(peer_monitor.start(), test));
+}
+
+#[test]
+fn inbound() {
+    let (mut harness, peer_monitor) = TestHarness::new();
+
+    let test = async move {
+        let peer_id = PeerId::new([0x42; PeerId::LENGTH]);
+        harness.add_peer(peer_id);
+
+        // The peer monitor responds with its own node information
+        let res_rx = harness.send_inbound_ping(peer_id, mock_node_info(10)).await;
+        let res_data = res_rx.await.unwrap().unwrap();
+        match bcs::from_bytes(&res_data).unwrap() {
+            PeerMonitoringMsg::Pong(node_info) => {
+                assert_eq!(node_info.role, NetworkContext::mock().role());
+            }
+            msg => panic!("Unexpected PeerMonitoringMsg: {:?}", msg),
+        }
+
+        // And stores the node information of the pinging peer, without a latency
+        let metadata = harness.monitoring_metadata(peer_id);
+        assert_eq!(metadata.node_info, Some(mock_node_info(10)));
+        assert_eq!(metadata.average_ping_latency, None);
+    };
+    block_on(future::join(peer_monitor.start(), test));
+}
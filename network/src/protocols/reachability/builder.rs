@@ -0,0 +1,58 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    application::storage::PeerMetadataStorage,
+    protocols::reachability::{
+        ReachabilityChecker, ReachabilityNetworkEvents, ReachabilityNetworkSender,
+    },
+};
+use aptos_config::network_id::NetworkContext;
+use aptos_time_service::TimeService;
+use aptos_types::network_address::NetworkAddress;
+use std::{sync::Arc, time::Duration};
+use tokio::runtime::Handle;
+
+pub struct ReachabilityCheckerBuilder {
+    network_context: NetworkContext,
+    time_service: TimeService,
+    check_interval_ms: Option<u64>,
+    network_channels: Option<(ReachabilityNetworkSender, ReachabilityNetworkEvents)>,
+    peer_metadata_storage: Arc<PeerMetadataStorage>,
+}
+
+impl ReachabilityCheckerBuilder {
+    pub fn new(
+        network_context: NetworkContext,
+        time_service: TimeService,
+        check_interval_ms: Option<u64>,
+        network_tx: ReachabilityNetworkSender,
+        network_rx: ReachabilityNetworkEvents,
+        peer_metadata_storage: Arc<PeerMetadataStorage>,
+    ) -> Self {
+        Self {
+            network_context,
+            time_service,
+            check_interval_ms,
+            network_channels: Some((network_tx, network_rx)),
+            peer_metadata_storage,
+        }
+    }
+
+    /// Starts the checker. The listen address is only known once the network is
+    /// built, so it's provided here rather than at construction.
+    pub fn start(&mut self, executor: &Handle, listen_address: NetworkAddress) {
+        if let Some((network_tx, network_rx)) = self.network_channels.take() {
+            let service = ReachabilityChecker::new(
+                self.network_context,
+                self.time_service.clone(),
+                network_tx,
+                network_rx,
+                self.peer_metadata_storage.clone(),
+                self.check_interval_ms.map(Duration::from_millis),
+                listen_address,
+            );
+            executor.spawn(service.start());
+        }
+    }
+}
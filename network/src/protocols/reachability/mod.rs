@@ -0,0 +1,434 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Protocol used to determine whether the local node is publicly reachable
+//!
+//! The ReachabilityChecker periodically asks a few random connected peers to dial
+//! back the node's listen address. A peer serving such a request only ever dials the
+//! IP address it observes for the connection with the requester (combined with the
+//! requested port), so that the protocol can't be used to make peers dial arbitrary
+//! third parties.
+//!
+//! The node is considered reachable if any peer of the latest round was able to dial
+//! it back, and unreachable if all peers that attempted a dial back failed. The status
+//! is stored in the [`PeerMetadataStorage`], where it's served by the node API, used by
+//! the registrar to stop registering the node while it's unreachable, and reported by
+//! telemetry. It's also reported via the `aptos_network_reachability` metric.
+use crate::{
+    application::{storage::PeerMetadataStorage, types::ReachabilityStatus},
+    constants::NETWORK_CHANNEL_SIZE,
+    counters::{self, FAILED_LABEL, RECEIVED_LABEL, SENT_LABEL},
+    logging::NetworkSchema,
+    peer_manager::{ConnectionRequestSender, PeerManagerRequestSender},
+    protocols::{
+        network::{
+            AppConfig, ApplicationNetworkSender, Event, NetworkEvents, NetworkSender,
+            NewNetworkSender,
+        },
+        rpc::error::RpcError,
+    },
+    ProtocolId,
+};
+use aptos_config::network_id::{NetworkContext, PeerNetworkId};
+use aptos_logger::prelude::*;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use aptos_types::{
+    network_address::{parse_tcp, NetworkAddress},
+    PeerId,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use channel::{aptos_channel, message_queues::QueueStyle};
+use futures::{
+    channel::oneshot,
+    future::{self, BoxFuture, FutureExt},
+    stream::{FuturesUnordered, StreamExt},
+};
+#[cfg(any(test, feature = "fuzzing"))]
+use proptest_derive::Arbitrary;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use short_hex_str::AsShortHexStr;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::net::TcpStream;
+
+pub mod builder;
+#[cfg(test)]
+mod test;
+
+/// Number of peers asked to dial back in every round
+pub const PEERS_PER_ROUND: usize = 3;
+/// Timeout of a single dial back, as performed by the serving peer
+pub const DIAL_BACK_TIMEOUT: Duration = Duration::from_secs(5);
+/// Timeout of a dial back request, which includes the dial back itself
+pub const DIAL_BACK_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Maximum number of dial backs served concurrently, further requests are rejected
+pub const MAX_CONCURRENT_DIAL_BACKS: usize = 10;
+
+const REACHABLE_LABEL: &str = "reachable";
+const UNREACHABLE_LABEL: &str = "unreachable";
+const REJECTED_LABEL: &str = "rejected";
+
+/// The interface from Network to ReachabilityChecker layer.
+pub type ReachabilityNetworkEvents = NetworkEvents<ReachabilityMsg>;
+
+/// The interface from ReachabilityChecker to Networking layer.
+#[derive(Clone)]
+pub struct ReachabilityNetworkSender {
+    inner: NetworkSender<ReachabilityMsg>,
+}
+
+/// Configuration for the network endpoints to support the ReachabilityChecker.
+pub fn network_endpoint_config() -> AppConfig {
+    AppConfig::p2p(
+        [ProtocolId::ReachabilityRpc],
+        aptos_channel::Config::new(NETWORK_CHANNEL_SIZE).queue_style(QueueStyle::LIFO),
+    )
+}
+
+impl NewNetworkSender for ReachabilityNetworkSender {
+    fn new(
+        peer_mgr_reqs_tx: PeerManagerRequestSender,
+        connection_reqs_tx: ConnectionRequestSender,
+    ) -> Self {
+        Self {
+            inner: NetworkSender::new(peer_mgr_reqs_tx, connection_reqs_tx),
+        }
+    }
+}
+
+#[async_trait]
+impl ApplicationNetworkSender<ReachabilityMsg> for ReachabilityNetworkSender {
+    async fn send_rpc(
+        &self,
+        recipient: PeerId,
+        req_msg: ReachabilityMsg,
+        timeout: Duration,
+    ) -> Result<ReachabilityMsg, RpcError> {
+        let protocol = ProtocolId::ReachabilityRpc;
+        self.inner
+            .send_rpc(recipient, protocol, req_msg, timeout)
+            .await
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
+pub enum ReachabilityMsg {
+    /// Asks the peer to dial back the given listen address
+    DialBackRequest(NetworkAddress),
+    DialBackResponse(DialBackResult),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
+pub enum DialBackResult {
+    /// The peer was able to dial back the address
+    Reachable,
+    /// The peer was unable to dial back the address
+    Unreachable,
+    /// The peer didn't attempt to dial back, e.g., because it's overloaded or
+    /// because the address isn't a TCP address
+    Rejected,
+}
+
+impl DialBackResult {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DialBackResult::Reachable => REACHABLE_LABEL,
+            DialBackResult::Unreachable => UNREACHABLE_LABEL,
+            DialBackResult::Rejected => REJECTED_LABEL,
+        }
+    }
+}
+
+/// The actor checking the reachability of the local node, and serving dial backs for peers
+pub struct ReachabilityChecker {
+    network_context: NetworkContext,
+    /// A handle to a time service for easily mocking time-related operations.
+    time_service: TimeService,
+    network_tx: ReachabilityNetworkSender,
+    network_rx: ReachabilityNetworkEvents,
+    peer_metadata_storage: Arc<PeerMetadataStorage>,
+    /// Time we wait between each round of reachability checks, if enabled.
+    check_interval: Option<Duration>,
+    /// The address of the local node that peers are asked to dial back
+    listen_address: NetworkAddress,
+}
+
+impl ReachabilityChecker {
+    pub fn new(
+        network_context: NetworkContext,
+        time_service: TimeService,
+        network_tx: ReachabilityNetworkSender,
+        network_rx: ReachabilityNetworkEvents,
+        peer_metadata_storage: Arc<PeerMetadataStorage>,
+        check_interval: Option<Duration>,
+        listen_address: NetworkAddress,
+    ) -> Self {
+        Self {
+            network_context,
+            time_service,
+            network_tx,
+            network_rx,
+            peer_metadata_storage,
+            check_interval,
+            listen_address,
+        }
+    }
+
+    pub async fn start(mut self) {
+        info!(
+            NetworkSchema::new(&self.network_context),
+            "{} Reachability checker actor started", self.network_context
+        );
+        self.update_status(ReachabilityStatus::Unknown);
+
+        let mut ticker = match self.check_interval {
+            Some(check_interval) => self.time_service.interval(check_interval).boxed(),
+            None => futures::stream::pending().boxed(),
+        }
+        .fuse();
+        let mut dial_backs: FuturesUnordered<BoxFuture<'static, ()>> = FuturesUnordered::new();
+        let mut rounds = FuturesUnordered::new();
+
+        loop {
+            futures::select! {
+                maybe_event = self.network_rx.next() => {
+                    // Shutdown when the network instance shuts down
+                    let event = match maybe_event {
+                        Some(event) => event,
+                        None => break,
+                    };
+
+                    match event {
+                        Event::RpcRequest(peer_id, ReachabilityMsg::DialBackRequest(addr), protocol, res_tx) => {
+                            if dial_backs.len() >= MAX_CONCURRENT_DIAL_BACKS {
+                                self.respond(peer_id, protocol, res_tx, DialBackResult::Rejected);
+                            } else {
+                                dial_backs.push(self.serve_dial_back(peer_id, addr, protocol, res_tx));
+                            }
+                        }
+                        Event::RpcRequest(peer_id, msg, _, _) => {
+                            warn!(
+                                NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                                "{} Unexpected RPC message from {}: {:?}",
+                                self.network_context,
+                                peer_id,
+                                msg
+                            );
+                        }
+                        Event::Message(peer_id, msg) => {
+                            warn!(
+                                NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                                "{} Unexpected direct send from {}: {:?}",
+                                self.network_context,
+                                peer_id,
+                                msg
+                            );
+                        }
+                        Event::NewPeer(_) | Event::LostPeer(_) => {}
+                    }
+                }
+                _ = ticker.select_next_some() => {
+                    // Only run a single round at a time
+                    if rounds.is_empty() {
+                        rounds.push(self.check_reachability());
+                    }
+                }
+                results = rounds.select_next_some() => {
+                    if let Some(status) = reachability_status(&results) {
+                        self.update_status(status);
+                    }
+                }
+                _ = dial_backs.select_next_some() => {}
+            }
+        }
+        warn!(
+            NetworkSchema::new(&self.network_context),
+            "{} Reachability checker actor terminated", self.network_context
+        );
+    }
+
+    fn update_status(&self, status: ReachabilityStatus) {
+        let gauge = match status {
+            ReachabilityStatus::Reachable => 1,
+            ReachabilityStatus::Unreachable => 0,
+            ReachabilityStatus::Unknown => -1,
+        };
+        counters::reachability(&self.network_context).set(gauge);
+
+        let network_id = self.network_context.network_id();
+        if self.peer_metadata_storage.reachability(network_id) != status {
+            info!(
+                NetworkSchema::new(&self.network_context),
+                "{} Reachability status changed to {:?}", self.network_context, status
+            );
+            self.peer_metadata_storage
+                .set_reachability(network_id, status);
+        }
+    }
+
+    /// Asks a few random connected peers to dial back the listen address
+    fn check_reachability(&self) -> BoxFuture<'static, Vec<DialBackResult>> {
+        let mut peers: Vec<_> = self
+            .peer_metadata_storage
+            .read_filtered(self.network_context.network_id(), |(_, info)| {
+                info.is_connected() && info.supports_protocol(ProtocolId::ReachabilityRpc)
+            })
+            .into_keys()
+            .map(|peer_network_id| peer_network_id.peer_id())
+            .collect();
+        peers.shuffle(&mut rand::thread_rng());
+        peers.truncate(PEERS_PER_ROUND);
+
+        let network_context = self.network_context;
+        let requests = peers.into_iter().map(|peer_id| {
+            let network_tx = self.network_tx.clone();
+            let listen_address = self.listen_address.clone();
+            async move {
+                let result = network_tx
+                    .send_rpc(
+                        peer_id,
+                        ReachabilityMsg::DialBackRequest(listen_address),
+                        DIAL_BACK_REQUEST_TIMEOUT,
+                    )
+                    .await
+                    .and_then(|msg| match msg {
+                        ReachabilityMsg::DialBackResponse(result) => Ok(result),
+                        _ => Err(RpcError::InvalidRpcResponse),
+                    });
+                match result {
+                    Ok(result) => {
+                        counters::reachability_probes(
+                            &network_context,
+                            SENT_LABEL,
+                            result.as_str(),
+                        )
+                        .inc();
+                        Some(result)
+                    }
+                    Err(error) => {
+                        counters::reachability_probes(&network_context, SENT_LABEL, FAILED_LABEL)
+                            .inc();
+                        debug!(
+                            NetworkSchema::new(&network_context).remote_peer(&peer_id),
+                            error = ?error,
+                            "{} Dial back request to peer {} failed: {:?}",
+                            network_context,
+                            peer_id.short_str(),
+                            error
+                        );
+                        None
+                    }
+                }
+            }
+        });
+
+        future::join_all(requests)
+            .map(|results| results.into_iter().flatten().collect())
+            .boxed()
+    }
+
+    /// Dials back the requesting peer, on the IP address of its connection and the
+    /// requested port
+    fn serve_dial_back(
+        &self,
+        peer_id: PeerId,
+        addr: NetworkAddress,
+        protocol: ProtocolId,
+        res_tx: oneshot::Sender<Result<Bytes, RpcError>>,
+    ) -> BoxFuture<'static, ()> {
+        let observed_addr = self
+            .peer_metadata_storage
+            .read(PeerNetworkId::new(
+                self.network_context.network_id(),
+                peer_id,
+            ))
+            .map(|info| info.active_connection.addr);
+        let target =
+            observed_addr.and_then(|observed_addr| dial_back_target(&observed_addr, &addr));
+
+        let network_context = self.network_context;
+        let time_service = self.time_service.clone();
+        async move {
+            let result = match target {
+                Some(target) => dial_back(&time_service, target).await,
+                None => DialBackResult::Rejected,
+            };
+            respond(&network_context, peer_id, protocol, res_tx, result);
+        }
+        .boxed()
+    }
+
+    fn respond(
+        &self,
+        peer_id: PeerId,
+        protocol: ProtocolId,
+        res_tx: oneshot::Sender<Result<Bytes, RpcError>>,
+        result: DialBackResult,
+    ) {
+        respond(&self.network_context, peer_id, protocol, res_tx, result)
+    }
+}
+
+fn respond(
+    network_context: &NetworkContext,
+    peer_id: PeerId,
+    protocol: ProtocolId,
+    res_tx: oneshot::Sender<Result<Bytes, RpcError>>,
+    result: DialBackResult,
+) {
+    counters::reachability_probes(network_context, RECEIVED_LABEL, result.as_str()).inc();
+    match protocol.to_bytes(&ReachabilityMsg::DialBackResponse(result)) {
+        Ok(message) => {
+            let _ = res_tx.send(Ok(message.into()));
+        }
+        Err(error) => {
+            warn!(
+                NetworkSchema::new(network_context).remote_peer(&peer_id),
+                error = ?error,
+                "{} Unable to serialize dial back response: {}", network_context, error
+            );
+        }
+    }
+}
+
+/// The address to dial back for a request: the IP address observed for the
+/// connection with the requester, combined with the requested TCP port. Returns
+/// `None` if either isn't available.
+pub fn dial_back_target(
+    observed_addr: &NetworkAddress,
+    requested_addr: &NetworkAddress,
+) -> Option<SocketAddr> {
+    let ip_addr = observed_addr.find_ip_addr()?;
+    let ((_, port), _) = parse_tcp(requested_addr.as_slice())?;
+    if port == 0 {
+        return None;
+    }
+    Some(SocketAddr::new(ip_addr, port))
+}
+
+/// Attempts to open a TCP connection to the target. The connection is closed again
+/// immediately, no handshake is performed.
+pub(crate) async fn dial_back(time_service: &TimeService, target: SocketAddr) -> DialBackResult {
+    match time_service
+        .timeout(DIAL_BACK_TIMEOUT, TcpStream::connect(target))
+        .await
+    {
+        Ok(Ok(_)) => DialBackResult::Reachable,
+        _ => DialBackResult::Unreachable,
+    }
+}
+
+/// Aggregates the dial back results of a round into a status. Returns `None` if
+/// no peer attempted to dial back, in which case the status is left unchanged.
+pub fn reachability_status(results: &[DialBackResult]) -> Option<ReachabilityStatus> {
+    if results.contains(&DialBackResult::Reachable) {
+        Some(ReachabilityStatus::Reachable)
+    } else if results.contains(&DialBackResult::Unreachable) {
+        Some(ReachabilityStatus::Unreachable)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use std::{net::IpAddr, str::FromStr};
+use tokio::net::TcpListener;
+
+#[test]
+fn test_dial_back_target() {
+    let observed_addr = NetworkAddress::from_str("/ip4/1.2.3.4/tcp/53172").unwrap();
+
+    // The observed IP address is dialed, on the requested port
+    let requested_addr = NetworkAddress::from_str("/ip4/0.0.0.0/tcp/6180").unwrap();
+    assert_eq!(
+        dial_back_target(&observed_addr, &requested_addr),
+        Some(SocketAddr::new(IpAddr::from_str("1.2.3.4").unwrap(), 6180))
+    );
+
+    // The requested IP address is ignored, so that peers can't be asked to dial third parties
+    let requested_addr = NetworkAddress::from_str("/ip4/5.6.7.8/tcp/6180").unwrap();
+    assert_eq!(
+        dial_back_target(&observed_addr, &requested_addr),
+        Some(SocketAddr::new(IpAddr::from_str("1.2.3.4").unwrap(), 6180))
+    );
+
+    // Non-TCP and ephemeral port addresses can't be dialed back
+    let requested_addr = NetworkAddress::from_str("/ip4/0.0.0.0/udp/6180").unwrap();
+    assert_eq!(dial_back_target(&observed_addr, &requested_addr), None);
+    let requested_addr = NetworkAddress::from_str("/ip4/0.0.0.0/tcp/0").unwrap();
+    assert_eq!(dial_back_target(&observed_addr, &requested_addr), None);
+
+    // Connections without an IP address can't be dialed back
+    let observed_addr = NetworkAddress::from_str("/memory/1234").unwrap();
+    let requested_addr = NetworkAddress::from_str("/ip4/0.0.0.0/tcp/6180").unwrap();
+    assert_eq!(dial_back_target(&observed_addr, &requested_addr), None);
+}
+
+#[test]
+fn test_reachability_status() {
+    use DialBackResult::*;
+
+    assert_eq!(reachability_status(&[]), None);
+    assert_eq!(reachability_status(&[Rejected, Rejected]), None);
+    assert_eq!(
+        reachability_status(&[Unreachable, Rejected]),
+        Some(ReachabilityStatus::Unreachable)
+    );
+    assert_eq!(
+        reachability_status(&[Unreachable, Reachable, Rejected]),
+        Some(ReachabilityStatus::Reachable)
+    );
+}
+
+#[tokio::test]
+async fn test_dial_back() {
+    let time_service = TimeService::real();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let target = listener.local_addr().unwrap();
+    assert_eq!(
+        dial_back(&time_service, target).await,
+        DialBackResult::Reachable
+    );
+
+    // Nothing is listening on the port anymore
+    drop(listener);
+    assert_eq!(
+        dial_back(&time_service, target).await,
+        DialBackResult::Unreachable
+    );
+}
@@ -0,0 +1,61 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    application::storage::PeerMetadataStorage,
+    connectivity_manager::ConnectivityRequest,
+    protocols::registrar::{Registrar, RegistrarNetworkEvents, RegistrarNetworkSender},
+};
+use aptos_config::{config::RegistrarConfig, network_id::NetworkContext};
+use aptos_time_service::TimeService;
+use aptos_types::network_address::NetworkAddress;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+
+pub struct RegistrarBuilder {
+    network_context: NetworkContext,
+    time_service: TimeService,
+    config: RegistrarConfig,
+    network_channels: Option<(RegistrarNetworkSender, RegistrarNetworkEvents)>,
+    peer_metadata_storage: Arc<PeerMetadataStorage>,
+    conn_mgr_reqs_tx: Option<channel::Sender<ConnectivityRequest>>,
+}
+
+impl RegistrarBuilder {
+    pub fn new(
+        network_context: NetworkContext,
+        time_service: TimeService,
+        config: RegistrarConfig,
+        network_tx: RegistrarNetworkSender,
+        network_rx: RegistrarNetworkEvents,
+        peer_metadata_storage: Arc<PeerMetadataStorage>,
+        conn_mgr_reqs_tx: Option<channel::Sender<ConnectivityRequest>>,
+    ) -> Self {
+        Self {
+            network_context,
+            time_service,
+            config,
+            network_channels: Some((network_tx, network_rx)),
+            peer_metadata_storage,
+            conn_mgr_reqs_tx,
+        }
+    }
+
+    /// Starts the registrar. The listen address is only known once the network is
+    /// built, so it's provided here rather than at construction.
+    pub fn start(&mut self, executor: &Handle, listen_address: NetworkAddress) {
+        if let Some((network_tx, network_rx)) = self.network_channels.take() {
+            let service = Registrar::new(
+                self.network_context,
+                self.time_service.clone(),
+                network_tx,
+                network_rx,
+                self.peer_metadata_storage.clone(),
+                self.conn_mgr_reqs_tx.take(),
+                self.config.clone(),
+                listen_address,
+            );
+            executor.spawn(service.start());
+        }
+    }
+}
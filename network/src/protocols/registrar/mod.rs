@@ -0,0 +1,696 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Protocol used by public fullnodes to register with registrar peers
+//!
+//! A node configured with registrars periodically registers its listen address with
+//! the registrars it's connected to (e.g., its seeds), and fetches the peers registered
+//! with them. The fetched peers are handed to the [`ConnectivityManager`] as the
+//! [`DiscoverySource::Registrar`] source, so new nodes only need a few seeds to join
+//! the mesh of public fullnodes.
+//!
+//! A registrar never registers the address requested by a peer as is: it registers the
+//! IP address it observes for the connection with the peer, combined with the requested
+//! port and the network key the peer authenticated with. Peers can thus only register
+//! themselves, and not make the registrar serve arbitrary third parties. The registrar
+//! also dials the address back before registering it, so that only reachable peers are
+//! served. Registrations expire unless they're renewed. While the reachability checker
+//! finds the node unreachable, it doesn't register itself, but still fetches the peers
+//! registered with the registrars.
+//!
+//! The number of registrations is bounded, both overall and per subnet. Once a bound is
+//! reached, new registrations evict the least recently renewed ones (of the subnet, if
+//! its bound is reached), so that a few subnets can neither fill the registrar nor lock
+//! other peers out of it.
+//!
+//! [`ConnectivityManager`]: crate::connectivity_manager::ConnectivityManager
+use crate::{
+    application::{storage::PeerMetadataStorage, types::ReachabilityStatus},
+    connectivity_manager::{ConnectivityRequest, DiscoverySource},
+    constants::NETWORK_CHANNEL_SIZE,
+    counters::{self, FAILED_LABEL, RECEIVED_LABEL, SENT_LABEL},
+    logging::NetworkSchema,
+    peer_manager::{ConnectionRequestSender, PeerManagerRequestSender},
+    protocols::{
+        network::{
+            AppConfig, ApplicationNetworkSender, Event, NetworkEvents, NetworkSender,
+            NewNetworkSender,
+        },
+        reachability::{dial_back, dial_back_target, DialBackResult},
+        rpc::error::RpcError,
+    },
+    ProtocolId,
+};
+use aptos_config::{
+    config::{Peer, PeerRole, PeerSet, RegistrarConfig, HANDSHAKE_VERSION},
+    network_id::{NetworkContext, PeerNetworkId},
+};
+use aptos_logger::prelude::*;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use aptos_types::{network_address::NetworkAddress, PeerId};
+use async_trait::async_trait;
+use bytes::Bytes;
+use channel::{aptos_channel, message_queues::QueueStyle};
+use futures::{
+    channel::oneshot,
+    future::{self, BoxFuture, FutureExt},
+    stream::{FuturesUnordered, StreamExt},
+};
+#[cfg(any(test, feature = "fuzzing"))]
+use proptest_derive::Arbitrary;
+use rand::seq::IteratorRandom;
+use serde::{Deserialize, Serialize};
+use short_hex_str::AsShortHexStr;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+pub mod builder;
+#[cfg(test)]
+mod test;
+
+/// Maximum number of registered peers served in a single response
+pub const MAX_PEERS_PER_RESPONSE: usize = 100;
+/// Timeout of the requests to registrars, which include dialing the requester back
+pub const REGISTRAR_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Maximum number of registrations checked concurrently, further registrations are rejected
+pub const MAX_CONCURRENT_REGISTRATION_CHECKS: usize = 10;
+
+const REGISTERED_LABEL: &str = "registered";
+const REJECTED_LABEL: &str = "rejected";
+const SUPPRESSED_LABEL: &str = "suppressed";
+
+/// The interface from Network to Registrar layer.
+pub type RegistrarNetworkEvents = NetworkEvents<RegistrarMsg>;
+
+/// The interface from Registrar to Networking layer.
+#[derive(Clone)]
+pub struct RegistrarNetworkSender {
+    inner: NetworkSender<RegistrarMsg>,
+}
+
+/// Configuration for the network endpoints to support the Registrar.
+pub fn network_endpoint_config() -> AppConfig {
+    AppConfig::p2p(
+        [ProtocolId::RegistrarRpc],
+        aptos_channel::Config::new(NETWORK_CHANNEL_SIZE).queue_style(QueueStyle::LIFO),
+    )
+}
+
+impl NewNetworkSender for RegistrarNetworkSender {
+    fn new(
+        peer_mgr_reqs_tx: PeerManagerRequestSender,
+        connection_reqs_tx: ConnectionRequestSender,
+    ) -> Self {
+        Self {
+            inner: NetworkSender::new(peer_mgr_reqs_tx, connection_reqs_tx),
+        }
+    }
+}
+
+#[async_trait]
+impl ApplicationNetworkSender<RegistrarMsg> for RegistrarNetworkSender {
+    async fn send_rpc(
+        &self,
+        recipient: PeerId,
+        req_msg: RegistrarMsg,
+        timeout: Duration,
+    ) -> Result<RegistrarMsg, RpcError> {
+        let protocol = ProtocolId::RegistrarRpc;
+        self.inner
+            .send_rpc(recipient, protocol, req_msg, timeout)
+            .await
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
+pub enum RegistrarMsg {
+    /// Registers the sender with its listen address
+    RegisterRequest(NetworkAddress),
+    RegisterResponse(RegistrationResult),
+    /// Asks for the peers registered with the registrar
+    GetPeersRequest,
+    GetPeersResponse(Vec<(PeerId, NetworkAddress)>),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
+pub enum RegistrationResult {
+    /// The sender is registered, until the registration expires
+    Registered,
+    /// The sender isn't registered, e.g., because the peer doesn't serve
+    /// registrations, because the address isn't a TCP address, or because the
+    /// peer couldn't dial it back
+    Rejected,
+}
+
+impl RegistrationResult {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RegistrationResult::Registered => REGISTERED_LABEL,
+            RegistrationResult::Rejected => REJECTED_LABEL,
+        }
+    }
+}
+
+/// The peers registered with the local node, by their address and the expiration
+/// time of their registration
+pub struct Registrations {
+    ttl: Duration,
+    max_registered_peers: usize,
+    max_registered_peers_per_subnet: usize,
+    peers: HashMap<PeerId, (NetworkAddress, Instant)>,
+}
+
+impl Registrations {
+    pub fn new(
+        ttl: Duration,
+        max_registered_peers: usize,
+        max_registered_peers_per_subnet: usize,
+    ) -> Self {
+        Self {
+            ttl,
+            max_registered_peers,
+            max_registered_peers_per_subnet,
+            peers: HashMap::new(),
+        }
+    }
+
+    /// Whether the peer is registered on the address, in which case the address
+    /// doesn't need to be dialed back again to renew the registration
+    pub fn is_registered(&self, peer_id: &PeerId, addr: &NetworkAddress, now: Instant) -> bool {
+        matches!(
+            self.peers.get(peer_id),
+            Some((registered_addr, expiration)) if registered_addr == addr && *expiration > now
+        )
+    }
+
+    /// Registers the peer, or renews its registration. Once the maximum number of
+    /// registered peers is reached, the least recently renewed registration is evicted.
+    /// Once the maximum number of registered peers of the subnet of the address is
+    /// reached, the least recently renewed registration of the subnet is evicted
+    /// instead. Addresses without an IP address are rejected.
+    pub fn register(
+        &mut self,
+        peer_id: PeerId,
+        addr: NetworkAddress,
+        now: Instant,
+    ) -> RegistrationResult {
+        self.remove_expired(now);
+        let addr_subnet = match addr.find_ip_addr() {
+            Some(ip_addr) => subnet(ip_addr),
+            None => return RegistrationResult::Rejected,
+        };
+        // A renewal may move the peer to another address
+        self.peers.remove(&peer_id);
+
+        let in_subnet = |registered_addr: &NetworkAddress| {
+            registered_addr.find_ip_addr().map(subnet) == Some(addr_subnet)
+        };
+        let subnet_len = self
+            .peers
+            .values()
+            .filter(|(registered_addr, _)| in_subnet(registered_addr))
+            .count();
+        let evicted = if subnet_len >= self.max_registered_peers_per_subnet {
+            Some(self.least_recently_renewed(in_subnet))
+        } else if self.peers.len() >= self.max_registered_peers {
+            Some(self.least_recently_renewed(|_| true))
+        } else {
+            None
+        };
+        match evicted {
+            Some(Some(evicted_peer_id)) => {
+                self.peers.remove(&evicted_peer_id);
+            }
+            // The bound is 0
+            Some(None) => return RegistrationResult::Rejected,
+            None => (),
+        }
+
+        self.peers.insert(peer_id, (addr, now + self.ttl));
+        RegistrationResult::Registered
+    }
+
+    /// A random sample of the registered peers, excluding the requester
+    pub fn sample(&mut self, requester: PeerId, now: Instant) -> Vec<(PeerId, NetworkAddress)> {
+        self.remove_expired(now);
+        self.peers
+            .iter()
+            .filter(|(peer_id, _)| **peer_id != requester)
+            .map(|(peer_id, (addr, _))| (*peer_id, addr.clone()))
+            .choose_multiple(&mut rand::thread_rng(), MAX_PEERS_PER_RESPONSE)
+    }
+
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+
+    fn remove_expired(&mut self, now: Instant) {
+        self.peers.retain(|_, (_, expiration)| *expiration > now);
+    }
+
+    /// The peer among the ones with a matching address whose registration expires first
+    fn least_recently_renewed<F: Fn(&NetworkAddress) -> bool>(&self, filter: F) -> Option<PeerId> {
+        self.peers
+            .iter()
+            .filter(|(_, (addr, _))| filter(addr))
+            .min_by_key(|(_, (_, expiration))| *expiration)
+            .map(|(peer_id, _)| *peer_id)
+    }
+}
+
+/// The subnet of an IP address, as a prefix: /24 for IPv4, /48 for IPv6
+fn subnet(ip_addr: IpAddr) -> IpAddr {
+    match ip_addr {
+        IpAddr::V4(ip_addr) => {
+            let [a, b, c, _] = ip_addr.octets();
+            IpAddr::V4(Ipv4Addr::new(a, b, c, 0))
+        }
+        IpAddr::V6(ip_addr) => {
+            let segments = ip_addr.segments();
+            IpAddr::V6(Ipv6Addr::new(
+                segments[0],
+                segments[1],
+                segments[2],
+                0,
+                0,
+                0,
+                0,
+                0,
+            ))
+        }
+    }
+}
+
+/// A registration whose address is being dialed back, answered once the dial back
+/// completes
+struct RegistrationCheck {
+    peer_id: PeerId,
+    addr: NetworkAddress,
+    protocol: ProtocolId,
+    res_tx: oneshot::Sender<Result<Bytes, RpcError>>,
+    result: DialBackResult,
+}
+
+/// The actor registering the local node with registrars, and serving registrations
+/// for peers if enabled
+pub struct Registrar {
+    network_context: NetworkContext,
+    /// A handle to a time service for easily mocking time-related operations.
+    time_service: TimeService,
+    network_tx: RegistrarNetworkSender,
+    network_rx: RegistrarNetworkEvents,
+    peer_metadata_storage: Arc<PeerMetadataStorage>,
+    /// Channel to send the peers fetched from registrars to the ConnectivityManager
+    conn_mgr_reqs_tx: Option<channel::Sender<ConnectivityRequest>>,
+    config: RegistrarConfig,
+    /// The address of the local node that is registered with registrars
+    listen_address: NetworkAddress,
+    registrations: Registrations,
+}
+
+impl Registrar {
+    pub fn new(
+        network_context: NetworkContext,
+        time_service: TimeService,
+        network_tx: RegistrarNetworkSender,
+        network_rx: RegistrarNetworkEvents,
+        peer_metadata_storage: Arc<PeerMetadataStorage>,
+        conn_mgr_reqs_tx: Option<channel::Sender<ConnectivityRequest>>,
+        config: RegistrarConfig,
+        listen_address: NetworkAddress,
+    ) -> Self {
+        let registrations = Registrations::new(
+            Duration::from_millis(config.registration_ttl_ms),
+            config.max_registered_peers,
+            config.max_registered_peers_per_subnet,
+        );
+        Self {
+            network_context,
+            time_service,
+            network_tx,
+            network_rx,
+            peer_metadata_storage,
+            conn_mgr_reqs_tx,
+            config,
+            listen_address,
+            registrations,
+        }
+    }
+
+    pub async fn start(mut self) {
+        info!(
+            NetworkSchema::new(&self.network_context),
+            "{} Registrar actor started", self.network_context
+        );
+
+        let mut ticker = if self.config.registrars.is_empty() {
+            futures::stream::pending().boxed()
+        } else {
+            self.time_service
+                .interval(Duration::from_millis(self.config.registration_interval_ms))
+                .boxed()
+        }
+        .fuse();
+        let mut rounds = FuturesUnordered::new();
+        let mut checks: FuturesUnordered<BoxFuture<'static, RegistrationCheck>> =
+            FuturesUnordered::new();
+
+        loop {
+            futures::select! {
+                maybe_event = self.network_rx.next() => {
+                    // Shutdown when the network instance shuts down
+                    let event = match maybe_event {
+                        Some(event) => event,
+                        None => break,
+                    };
+
+                    match event {
+                        Event::RpcRequest(peer_id, RegistrarMsg::RegisterRequest(addr), protocol, res_tx) => {
+                            let can_check = checks.len() < MAX_CONCURRENT_REGISTRATION_CHECKS;
+                            if let Some(check) = self.serve_registration(peer_id, addr, protocol, res_tx, can_check) {
+                                checks.push(check);
+                            }
+                        }
+                        Event::RpcRequest(peer_id, RegistrarMsg::GetPeersRequest, protocol, res_tx) => {
+                            let peers = if self.config.serve_registrations {
+                                self.registrations.sample(peer_id, self.time_service.now())
+                            } else {
+                                Vec::new()
+                            };
+                            self.respond(peer_id, protocol, res_tx, RegistrarMsg::GetPeersResponse(peers));
+                        }
+                        Event::RpcRequest(peer_id, msg, _, _) => {
+                            warn!(
+                                NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                                "{} Unexpected RPC message from {}: {:?}",
+                                self.network_context,
+                                peer_id,
+                                msg
+                            );
+                        }
+                        Event::Message(peer_id, msg) => {
+                            warn!(
+                                NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                                "{} Unexpected direct send from {}: {:?}",
+                                self.network_context,
+                                peer_id,
+                                msg
+                            );
+                        }
+                        Event::NewPeer(_) | Event::LostPeer(_) => {}
+                    }
+                }
+                _ = ticker.select_next_some() => {
+                    // Only run a single round at a time
+                    if rounds.is_empty() {
+                        rounds.push(self.register());
+                    }
+                }
+                maybe_peers = rounds.select_next_some() => {
+                    if let Some(peers) = maybe_peers {
+                        self.update_discovered_peers(peers);
+                    }
+                }
+                check = checks.select_next_some() => {
+                    let RegistrationCheck { peer_id, addr, protocol, res_tx, result } = check;
+                    let result = self.complete_registration(peer_id, addr, result);
+                    self.respond(peer_id, protocol, res_tx, RegistrarMsg::RegisterResponse(result));
+                }
+            }
+        }
+        warn!(
+            NetworkSchema::new(&self.network_context),
+            "{} Registrar actor terminated", self.network_context
+        );
+    }
+
+    /// Serves a registration request. Renewals of the registration of the same address
+    /// are served right away, while new addresses are first dialed back by the returned
+    /// future. Requests are rejected if there's no room to dial them back.
+    fn serve_registration(
+        &mut self,
+        peer_id: PeerId,
+        addr: NetworkAddress,
+        protocol: ProtocolId,
+        res_tx: oneshot::Sender<Result<Bytes, RpcError>>,
+        can_check: bool,
+    ) -> Option<BoxFuture<'static, RegistrationCheck>> {
+        match self.check_registration(peer_id, addr) {
+            Some((addr, None)) => {
+                let result = self.complete_registration(peer_id, addr, DialBackResult::Reachable);
+                self.respond(
+                    peer_id,
+                    protocol,
+                    res_tx,
+                    RegistrarMsg::RegisterResponse(result),
+                );
+                None
+            }
+            Some((addr, Some(target))) if can_check => {
+                let time_service = self.time_service.clone();
+                Some(
+                    async move {
+                        let result = dial_back(&time_service, target).await;
+                        RegistrationCheck {
+                            peer_id,
+                            addr,
+                            protocol,
+                            res_tx,
+                            result,
+                        }
+                    }
+                    .boxed(),
+                )
+            }
+            _ => {
+                let result = RegistrationResult::Rejected;
+                counters::registrar_registrations(
+                    &self.network_context,
+                    RECEIVED_LABEL,
+                    result.as_str(),
+                )
+                .inc();
+                self.respond(
+                    peer_id,
+                    protocol,
+                    res_tx,
+                    RegistrarMsg::RegisterResponse(result),
+                );
+                None
+            }
+        }
+    }
+
+    /// The address to register the requesting peer on, derived from its connection, if
+    /// registrations are served. It comes with the target to dial back, unless the peer
+    /// is renewing the registration of the same address.
+    fn check_registration(
+        &self,
+        peer_id: PeerId,
+        addr: NetworkAddress,
+    ) -> Option<(NetworkAddress, Option<SocketAddr>)> {
+        if !self.config.serve_registrations {
+            return None;
+        }
+        let observed_addr = self
+            .peer_metadata_storage
+            .read(PeerNetworkId::new(
+                self.network_context.network_id(),
+                peer_id,
+            ))
+            .map(|info| info.active_connection.addr)?;
+        let registration_addr = registration_addr(&observed_addr, &addr)?;
+        if self
+            .registrations
+            .is_registered(&peer_id, &registration_addr, self.time_service.now())
+        {
+            return Some((registration_addr, None));
+        }
+        let target = dial_back_target(&observed_addr, &addr)?;
+        Some((registration_addr, Some(target)))
+    }
+
+    /// Registers the requesting peer on the address of its connection, if it could be
+    /// dialed back
+    fn complete_registration(
+        &mut self,
+        peer_id: PeerId,
+        addr: NetworkAddress,
+        dial_back_result: DialBackResult,
+    ) -> RegistrationResult {
+        let result = match dial_back_result {
+            DialBackResult::Reachable => {
+                self.registrations
+                    .register(peer_id, addr, self.time_service.now())
+            }
+            DialBackResult::Unreachable | DialBackResult::Rejected => RegistrationResult::Rejected,
+        };
+        counters::registrar_registrations(&self.network_context, RECEIVED_LABEL, result.as_str())
+            .inc();
+        counters::registrar_registered_peers(&self.network_context)
+            .set(self.registrations.len() as i64);
+        result
+    }
+
+    /// Registers the listen address with the connected registrars, unless the node is
+    /// known to be unreachable, and fetches the peers registered with them. Returns
+    /// `None` if no registrar responded.
+    fn register(&self) -> BoxFuture<'static, Option<PeerSet>> {
+        // Registrars would fail to dial an unreachable node back anyway
+        let suppressed = self
+            .peer_metadata_storage
+            .reachability(self.network_context.network_id())
+            == ReachabilityStatus::Unreachable;
+
+        let registrars: Vec<_> = self
+            .peer_metadata_storage
+            .read_filtered(self.network_context.network_id(), |(peer_id, info)| {
+                info.is_connected()
+                    && info.supports_protocol(ProtocolId::RegistrarRpc)
+                    && self.config.registrars.contains(*peer_id)
+            })
+            .into_keys()
+            .map(|peer_network_id| peer_network_id.peer_id())
+            .collect();
+
+        let network_context = self.network_context;
+        let requests = registrars.into_iter().map(|peer_id| {
+            let network_tx = self.network_tx.clone();
+            let listen_address = self.listen_address.clone();
+            async move {
+                let result_label = if suppressed {
+                    SUPPRESSED_LABEL
+                } else {
+                    network_tx
+                        .send_rpc(
+                            peer_id,
+                            RegistrarMsg::RegisterRequest(listen_address),
+                            REGISTRAR_REQUEST_TIMEOUT,
+                        )
+                        .await
+                        .and_then(|msg| match msg {
+                            RegistrarMsg::RegisterResponse(result) => Ok(result),
+                            _ => Err(RpcError::InvalidRpcResponse),
+                        })
+                        .map_or(FAILED_LABEL, |result| result.as_str())
+                };
+                counters::registrar_registrations(&network_context, SENT_LABEL, result_label).inc();
+
+                let peers = network_tx
+                    .send_rpc(
+                        peer_id,
+                        RegistrarMsg::GetPeersRequest,
+                        REGISTRAR_REQUEST_TIMEOUT,
+                    )
+                    .await
+                    .and_then(|msg| match msg {
+                        RegistrarMsg::GetPeersResponse(peers) => Ok(peers),
+                        _ => Err(RpcError::InvalidRpcResponse),
+                    });
+                match peers {
+                    Ok(peers) => Some(peers),
+                    Err(error) => {
+                        debug!(
+                            NetworkSchema::new(&network_context).remote_peer(&peer_id),
+                            error = ?error,
+                            "{} Fetching the registered peers from registrar {} failed: {:?}",
+                            network_context,
+                            peer_id.short_str(),
+                            error
+                        );
+                        None
+                    }
+                }
+            }
+        });
+
+        let local_peer_id = self.network_context.peer_id();
+        future::join_all(requests)
+            .map(move |responses| {
+                let responses: Vec<_> = responses.into_iter().flatten().collect();
+                if responses.is_empty() {
+                    None
+                } else {
+                    Some(registered_peer_set(local_peer_id, responses))
+                }
+            })
+            .boxed()
+    }
+
+    fn update_discovered_peers(&mut self, peers: PeerSet) {
+        if let Some(conn_mgr_reqs_tx) = self.conn_mgr_reqs_tx.as_mut() {
+            let request =
+                ConnectivityRequest::UpdateDiscoveredPeers(DiscoverySource::Registrar, peers);
+            if let Err(error) = conn_mgr_reqs_tx.try_send(request) {
+                warn!(
+                    NetworkSchema::new(&self.network_context),
+                    error = ?error,
+                    "{} Failed to send the registered peers to the connectivity manager: {:?}",
+                    self.network_context,
+                    error
+                );
+            }
+        }
+    }
+
+    fn respond(
+        &self,
+        peer_id: PeerId,
+        protocol: ProtocolId,
+        res_tx: oneshot::Sender<Result<Bytes, RpcError>>,
+        msg: RegistrarMsg,
+    ) {
+        match protocol.to_bytes(&msg) {
+            Ok(message) => {
+                let _ = res_tx.send(Ok(message.into()));
+            }
+            Err(error) => {
+                warn!(
+                    NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                    error = ?error,
+                    "{} Unable to serialize registrar response: {}", self.network_context, error
+                );
+            }
+        }
+    }
+}
+
+/// The address registered for a request: the IP address observed for the connection
+/// with the requester, combined with the requested TCP port and the network key the
+/// requester authenticated with. Returns `None` if any of them isn't available.
+pub fn registration_addr(
+    observed_addr: &NetworkAddress,
+    requested_addr: &NetworkAddress,
+) -> Option<NetworkAddress> {
+    let pubkey = observed_addr.find_noise_proto()?;
+    let target = dial_back_target(observed_addr, requested_addr)?;
+    Some(NetworkAddress::from(target).append_prod_protos(pubkey, HANDSHAKE_VERSION))
+}
+
+/// Merges the peers served by registrars into a peer set, excluding the local node
+/// and addresses without a network key (which can't be dialed).
+pub fn registered_peer_set(
+    local_peer_id: PeerId,
+    responses: Vec<Vec<(PeerId, NetworkAddress)>>,
+) -> PeerSet {
+    let mut peers = PeerSet::new();
+    for (peer_id, addr) in responses.into_iter().flatten() {
+        if peer_id == local_peer_id || addr.find_noise_proto().is_none() {
+            continue;
+        }
+        peers
+            .entry(peer_id)
+            .or_insert_with(|| Peer::from_addrs(PeerRole::Known, vec![addr]));
+    }
+    peers
+}
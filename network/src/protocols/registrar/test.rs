@@ -0,0 +1,169 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_crypto::{x25519, Uniform};
+use rand::{rngs::StdRng, SeedableRng};
+use std::str::FromStr;
+
+fn pubkey(seed: u8) -> x25519::PublicKey {
+    let mut rng = StdRng::from_seed([seed; 32]);
+    x25519::PrivateKey::generate(&mut rng).public_key()
+}
+
+fn addr(addr: &str, pubkey: x25519::PublicKey) -> NetworkAddress {
+    NetworkAddress::from_str(addr)
+        .unwrap()
+        .append_prod_protos(pubkey, HANDSHAKE_VERSION)
+}
+
+#[test]
+fn test_registration_addr() {
+    let key = pubkey(0);
+    let observed_addr = addr("/ip4/1.2.3.4/tcp/53172", key);
+
+    // The observed IP address and key are registered, on the requested port
+    let requested_addr = NetworkAddress::from_str("/ip4/0.0.0.0/tcp/6182").unwrap();
+    assert_eq!(
+        registration_addr(&observed_addr, &requested_addr),
+        Some(addr("/ip4/1.2.3.4/tcp/6182", key))
+    );
+
+    // The requested IP address and key are ignored, so that peers can't register third parties
+    let requested_addr = addr("/ip4/5.6.7.8/tcp/6182", pubkey(1));
+    assert_eq!(
+        registration_addr(&observed_addr, &requested_addr),
+        Some(addr("/ip4/1.2.3.4/tcp/6182", key))
+    );
+
+    // Non-TCP addresses can't be registered
+    let requested_addr = NetworkAddress::from_str("/ip4/0.0.0.0/udp/6182").unwrap();
+    assert_eq!(registration_addr(&observed_addr, &requested_addr), None);
+
+    // Connections without a network key can't be registered
+    let observed_addr = NetworkAddress::from_str("/ip4/1.2.3.4/tcp/53172").unwrap();
+    let requested_addr = NetworkAddress::from_str("/ip4/0.0.0.0/tcp/6182").unwrap();
+    assert_eq!(registration_addr(&observed_addr, &requested_addr), None);
+}
+
+#[test]
+fn test_registrations() {
+    let ttl = Duration::from_secs(60);
+    let mut registrations = Registrations::new(ttl, 2, 2);
+    let now = Instant::now();
+    let (peer_a, peer_b, peer_c) = (PeerId::random(), PeerId::random(), PeerId::random());
+    let addr_a = addr("/ip4/1.2.3.4/tcp/6182", pubkey(1));
+    let addr_b = addr("/ip4/5.6.7.8/tcp/6182", pubkey(2));
+    let addr_c = addr("/ip4/9.9.9.9/tcp/6182", pubkey(3));
+
+    assert_eq!(
+        registrations.register(peer_a, addr_a.clone(), now),
+        RegistrationResult::Registered
+    );
+    assert_eq!(
+        registrations.register(peer_b, addr_b.clone(), now + ttl / 4),
+        RegistrationResult::Registered
+    );
+
+    // Registered peers can renew without evicting others
+    assert_eq!(
+        registrations.register(peer_a, addr_a.clone(), now + ttl / 2),
+        RegistrationResult::Registered
+    );
+    assert_eq!(registrations.len(), 2);
+    assert!(registrations.is_registered(&peer_a, &addr_a, now + ttl / 2));
+    assert!(!registrations.is_registered(&peer_a, &addr_c, now + ttl / 2));
+
+    // Once full, new peers evict the least recently renewed registration
+    assert_eq!(
+        registrations.register(peer_c, addr_c.clone(), now + ttl * 3 / 4),
+        RegistrationResult::Registered
+    );
+    assert_eq!(registrations.len(), 2);
+    assert!(!registrations.is_registered(&peer_b, &addr_b, now + ttl * 3 / 4));
+
+    // Requesters aren't served their own registration
+    assert_eq!(
+        registrations.sample(peer_a, now + ttl * 3 / 4),
+        vec![(peer_c, addr_c.clone())]
+    );
+
+    // Registrations expire unless renewed
+    assert_eq!(registrations.sample(peer_b, now + ttl * 3 / 4).len(), 2);
+    assert_eq!(
+        registrations.sample(peer_b, now + ttl * 3 / 2),
+        vec![(peer_c, addr_c)]
+    );
+    assert_eq!(registrations.len(), 1);
+}
+
+#[test]
+fn test_registrations_per_subnet() {
+    let ttl = Duration::from_secs(60);
+    let mut registrations = Registrations::new(ttl, 10, 2);
+    let now = Instant::now();
+    let peers: Vec<_> = (0..4).map(|_| PeerId::random()).collect();
+    let addrs = vec![
+        addr("/ip4/1.2.4.4/tcp/6182", pubkey(0)),
+        addr("/ip4/1.2.3.4/tcp/6182", pubkey(1)),
+        addr("/ip4/1.2.3.5/tcp/6182", pubkey(2)),
+        addr("/ip4/1.2.3.6/tcp/6182", pubkey(3)),
+    ];
+    for (i, (peer_id, addr)) in peers.iter().zip(addrs.iter()).enumerate() {
+        assert_eq!(
+            registrations.register(*peer_id, addr.clone(), now + Duration::from_secs(i as u64)),
+            RegistrationResult::Registered
+        );
+    }
+
+    // The last peer evicted the least recently renewed registration of its full subnet,
+    // rather than the least recently renewed one overall
+    assert_eq!(registrations.len(), 3);
+    let later = now + Duration::from_secs(4);
+    assert!(registrations.is_registered(&peers[0], &addrs[0], later));
+    assert!(!registrations.is_registered(&peers[1], &addrs[1], later));
+    assert!(registrations.is_registered(&peers[3], &addrs[3], later));
+
+    // IPv6 subnets are /48 prefixes
+    assert_eq!(
+        subnet("2001:db8:1:2::1".parse().unwrap()),
+        "2001:db8:1::".parse::<IpAddr>().unwrap()
+    );
+
+    // Addresses without an IP address are rejected
+    let dns_addr = addr("/dns/example.com/tcp/6182", pubkey(4));
+    assert_eq!(
+        registrations.register(PeerId::random(), dns_addr, later),
+        RegistrationResult::Rejected
+    );
+}
+
+#[test]
+fn test_registered_peer_set() {
+    let local_peer_id = PeerId::random();
+    let (peer_a, peer_b) = (PeerId::random(), PeerId::random());
+    let addr_a = addr("/ip4/1.2.3.4/tcp/6182", pubkey(1));
+    let addr_b = addr("/ip4/5.6.7.8/tcp/6182", pubkey(2));
+    let responses = vec![
+        vec![
+            (peer_a, addr_a.clone()),
+            (local_peer_id, addr("/ip4/9.9.9.9/tcp/6182", pubkey(3))),
+        ],
+        vec![
+            (peer_a, addr_b),
+            (
+                peer_b,
+                NetworkAddress::from_str("/ip4/5.6.7.8/tcp/6182").unwrap(),
+            ),
+        ],
+    ];
+
+    // The local node and addresses without a key are skipped, the first address of a
+    // peer wins
+    let peers = registered_peer_set(local_peer_id, responses);
+    assert_eq!(peers.len(), 1);
+    assert_eq!(
+        peers.get(&peer_a),
+        Some(&Peer::from_addrs(PeerRole::Known, vec![addr_a]))
+    );
+}
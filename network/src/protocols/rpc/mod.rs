@@ -53,7 +53,10 @@ use crate::{
     peer_manager::PeerManagerError,
     protocols::{
         network::SerializedRequest,
-        wire::messaging::v1::{NetworkMessage, Priority, RequestId, RpcRequest, RpcResponse},
+        wire::{
+            compression,
+            messaging::v1::{NetworkMessage, Priority, RequestId, RpcRequest, RpcResponse},
+        },
     },
     ProtocolId,
 };
@@ -217,11 +220,19 @@ impl InboundRpcs {
             return Err(RpcError::TooManyPending(self.max_concurrent_inbound_rpcs));
         }
 
-        let protocol_id = request.protocol_id;
+        let wire_protocol_id = request.protocol_id;
         let request_id = request.request_id;
         let priority = request.priority;
         let req_len = request.raw_request.len() as u64;
 
+        // Decompress the request if it was sent using a compressed protocol. If
+        // so, the response is compressed as well.
+        let (protocol_id, request_data) = match wire_protocol_id.decompressed() {
+            Some(protocol_id) => (protocol_id, compression::decompress(&request.raw_request)?),
+            None => (wire_protocol_id, request.raw_request),
+        };
+        let compress_response = wire_protocol_id != protocol_id;
+
         trace!(
             NetworkSchema::new(network_context).remote_peer(&self.remote_peer_id),
             "{} Received inbound rpc request from peer {} with request_id {} and protocol_id {}",
@@ -241,7 +252,7 @@ impl InboundRpcs {
         let (response_tx, response_rx) = oneshot::channel();
         let notif = PeerNotification::RecvRpc(InboundRpcRequest {
             protocol_id,
-            data: Bytes::from(request.raw_request),
+            data: Bytes::from(request_data),
             res_tx: response_tx,
         });
         if let Err(err) = peer_notifs_tx.push(protocol_id, notif) {
@@ -256,11 +267,18 @@ impl InboundRpcs {
             .map(move |result| {
                 // Flatten the errors
                 let maybe_response = match result {
-                    Ok(Ok(Ok(response_bytes))) => Ok(RpcResponse {
-                        request_id,
-                        priority,
-                        raw_response: Vec::from(response_bytes.as_ref()),
-                    }),
+                    Ok(Ok(Ok(response_bytes))) => {
+                        let raw_response = if compress_response {
+                            compression::compress(response_bytes.as_ref())
+                        } else {
+                            Vec::from(response_bytes.as_ref())
+                        };
+                        Ok(RpcResponse {
+                            request_id,
+                            priority,
+                            raw_response,
+                        })
+                    }
                     Ok(Ok(Err(err))) => Err(err),
                     Ok(Err(oneshot::Canceled)) => Err(RpcError::UnexpectedResponseChannelCancel),
                     Err(timeout::Elapsed) => Err(RpcError::TimedOut),
@@ -429,12 +447,21 @@ impl OutboundRpcs {
         let timer =
             counters::outbound_rpc_request_latency(network_context, protocol_id).start_timer();
 
+        // Compress the request (and expect a compressed response) if the
+        // request is sent using a compressed protocol.
+        let is_compressed = protocol_id.decompressed().is_some();
+        let raw_request = if is_compressed {
+            compression::compress(request_data.as_ref())
+        } else {
+            Vec::from(request_data.as_ref())
+        };
+
         // Enqueue rpc request message onto outbound write queue.
         let message = NetworkMessage::RpcRequest(RpcRequest {
             protocol_id,
             request_id,
             priority: Priority::default(),
-            raw_request: Vec::from(request_data.as_ref()),
+            raw_request,
         });
         let (ack_tx, _) = oneshot::channel();
         write_reqs_tx.send((message, ack_tx)).await?;
@@ -453,17 +480,24 @@ impl OutboundRpcs {
         // A future that waits for the rpc response with a timeout. We create the
         // timeout out here to start the timer as soon as we push onto the queue
         // (as opposed to whenever it first gets polled on the queue).
-        let wait_for_response = self
-            .time_service
-            .timeout(timeout, response_rx)
-            .map(|result| {
-                // Flatten errors.
-                match result {
-                    Ok(Ok(response)) => Ok(Bytes::from(response.raw_response)),
-                    Ok(Err(oneshot::Canceled)) => Err(RpcError::UnexpectedResponseChannelCancel),
-                    Err(timeout::Elapsed) => Err(RpcError::TimedOut),
-                }
-            });
+        let wait_for_response =
+            self.time_service
+                .timeout(timeout, response_rx)
+                .map(move |result| {
+                    // Flatten errors.
+                    match result {
+                        Ok(Ok(response)) if is_compressed => {
+                            compression::decompress(&response.raw_response)
+                                .map(Bytes::from)
+                                .map_err(RpcError::from)
+                        }
+                        Ok(Ok(response)) => Ok(Bytes::from(response.raw_response)),
+                        Ok(Err(oneshot::Canceled)) => {
+                            Err(RpcError::UnexpectedResponseChannelCancel)
+                        }
+                        Err(timeout::Elapsed) => Err(RpcError::TimedOut),
+                    }
+                });
 
         // A future that waits for the response and sends it to the application.
         let notify_application = async move {
@@ -0,0 +1,120 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements the payload compression used by compressed protocols.
+//!
+//! Compression is negotiated per [`ProtocolId`] during the handshake: a node that
+//! supports compression for a protocol also advertises its compressed counterpart
+//! (see [`ProtocolId::compressed`]). If both ends support the compressed protocol,
+//! messages for that protocol are sent over-the-wire using the compressed protocol
+//! id and the payload encoding below. Otherwise, messages are sent uncompressed.
+//!
+//! Every payload of a compressed protocol is prefixed with a single flag byte,
+//! which indicates whether the remainder of the payload is raw or zstd compressed.
+//! Payloads smaller than [`COMPRESSION_THRESHOLD_BYTES`] are sent raw, as the
+//! compression overhead isn't worth it for small messages.
+//!
+//! [`ProtocolId`]: crate::ProtocolId
+//! [`ProtocolId::compressed`]: crate::ProtocolId::compressed
+
+use std::io::{self, Read};
+
+/// Payloads smaller than this are sent raw
+pub const COMPRESSION_THRESHOLD_BYTES: usize = 4 * 1024; /* 4 KiB */
+/// Decompressed payloads larger than this are rejected
+pub const MAX_DECOMPRESSED_BYTES: usize = 64 * 1024 * 1024; /* 64 MiB */
+
+/// Favour speed over compression ratio, as compression is on the message path
+const COMPRESSION_LEVEL: i32 = 1;
+
+const RAW_FLAG: u8 = 0;
+const ZSTD_FLAG: u8 = 1;
+
+/// Encodes the given payload, compressing it if it's large enough.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    if data.len() >= COMPRESSION_THRESHOLD_BYTES {
+        if let Ok(compressed) = zstd::bulk::compress(data, COMPRESSION_LEVEL) {
+            // Only send the compressed payload if it's actually smaller
+            if compressed.len() < data.len() {
+                let mut payload = Vec::with_capacity(compressed.len() + 1);
+                payload.push(ZSTD_FLAG);
+                payload.extend_from_slice(&compressed);
+                return payload;
+            }
+        }
+    }
+
+    let mut payload = Vec::with_capacity(data.len() + 1);
+    payload.push(RAW_FLAG);
+    payload.extend_from_slice(data);
+    payload
+}
+
+/// Decodes a payload created by [`compress`].
+pub fn decompress(payload: &[u8]) -> io::Result<Vec<u8>> {
+    let (flag, data) = payload
+        .split_first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing compression flag"))?;
+
+    match *flag {
+        RAW_FLAG => Ok(data.to_vec()),
+        ZSTD_FLAG => {
+            // Limit the decompressed size to protect against decompression bombs
+            let mut decompressed = Vec::new();
+            zstd::stream::read::Decoder::new(data)?
+                .take(MAX_DECOMPRESSED_BYTES as u64 + 1)
+                .read_to_end(&mut decompressed)?;
+            if decompressed.len() > MAX_DECOMPRESSED_BYTES {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Decompressed payload exceeds the maximum size of {} bytes",
+                        MAX_DECOMPRESSED_BYTES
+                    ),
+                ));
+            }
+            Ok(decompressed)
+        }
+        flag => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unknown compression flag: {}", flag),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn small_payloads_are_sent_raw() {
+        let data = vec![7u8; COMPRESSION_THRESHOLD_BYTES - 1];
+        let payload = compress(&data);
+        assert_eq!(payload[0], RAW_FLAG);
+        assert_eq!(decompress(&payload).unwrap(), data);
+    }
+
+    #[test]
+    fn large_payloads_are_compressed() {
+        let data = vec![7u8; 10 * COMPRESSION_THRESHOLD_BYTES];
+        let payload = compress(&data);
+        assert_eq!(payload[0], ZSTD_FLAG);
+        assert!(payload.len() < data.len());
+        assert_eq!(decompress(&payload).unwrap(), data);
+    }
+
+    #[test]
+    fn invalid_payloads_are_rejected() {
+        assert!(decompress(&[]).is_err());
+        assert!(decompress(&[2, 1, 2, 3]).is_err());
+        assert!(decompress(&[ZSTD_FLAG, 1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn oversized_payloads_are_rejected() {
+        let data = vec![0u8; MAX_DECOMPRESSED_BYTES + 1];
+        let payload = compress(&data);
+        assert_eq!(payload[0], ZSTD_FLAG);
+        assert!(decompress(&payload).is_err());
+    }
+}
@@ -51,6 +51,14 @@ pub enum ProtocolId {
     ConsensusRpcJson = 7,
     StorageServiceRpc = 8,
     MempoolRpc = 9,
+    // wire-level compressed counterparts of the protocols above, see `compressed()`
+    ConsensusRpcBcsCompressed = 10,
+    ConsensusRpcJsonCompressed = 11,
+    StateSyncDirectSendCompressed = 12,
+    StorageServiceRpcCompressed = 13,
+    ReachabilityRpc = 14,
+    PeerMonitoringRpc = 15,
+    RegistrarRpc = 16,
 }
 
 /// The encoding types for Protocols
@@ -73,6 +81,13 @@ impl ProtocolId {
             ConsensusRpcJson => "ConsensusRpcJson",
             StorageServiceRpc => "StorageServiceRpc",
             MempoolRpc => "MempoolRpc",
+            ConsensusRpcBcsCompressed => "ConsensusRpcBcsCompressed",
+            ConsensusRpcJsonCompressed => "ConsensusRpcJsonCompressed",
+            StateSyncDirectSendCompressed => "StateSyncDirectSendCompressed",
+            StorageServiceRpcCompressed => "StorageServiceRpcCompressed",
+            ReachabilityRpc => "ReachabilityRpc",
+            PeerMonitoringRpc => "PeerMonitoringRpc",
+            RegistrarRpc => "RegistrarRpc",
         }
     }
 
@@ -88,13 +103,51 @@ impl ProtocolId {
             ProtocolId::ConsensusRpcJson,
             ProtocolId::StorageServiceRpc,
             ProtocolId::MempoolRpc,
+            ProtocolId::ConsensusRpcBcsCompressed,
+            ProtocolId::ConsensusRpcJsonCompressed,
+            ProtocolId::StateSyncDirectSendCompressed,
+            ProtocolId::StorageServiceRpcCompressed,
+            ProtocolId::ReachabilityRpc,
+            ProtocolId::PeerMonitoringRpc,
+            ProtocolId::RegistrarRpc,
         ]
     }
 
+    /// The wire-level compressed counterpart of this protocol, if any.
+    ///
+    /// Compression is only offered for protocols carrying large messages (i.e.,
+    /// block retrieval and state sync chunks). Small, latency-critical messages
+    /// (e.g., consensus votes and proposals) are never compressed.
+    pub fn compressed(self) -> Option<ProtocolId> {
+        use ProtocolId::*;
+        match self {
+            ConsensusRpcBcs => Some(ConsensusRpcBcsCompressed),
+            ConsensusRpcJson => Some(ConsensusRpcJsonCompressed),
+            StateSyncDirectSend => Some(StateSyncDirectSendCompressed),
+            StorageServiceRpc => Some(StorageServiceRpcCompressed),
+            _ => None,
+        }
+    }
+
+    /// The uncompressed protocol of a wire-level compressed protocol, or `None`
+    /// if this protocol isn't compressed.
+    pub fn decompressed(self) -> Option<ProtocolId> {
+        use ProtocolId::*;
+        match self {
+            ConsensusRpcBcsCompressed => Some(ConsensusRpcBcs),
+            ConsensusRpcJsonCompressed => Some(ConsensusRpcJson),
+            StateSyncDirectSendCompressed => Some(StateSyncDirectSend),
+            StorageServiceRpcCompressed => Some(StorageServiceRpc),
+            _ => None,
+        }
+    }
+
     /// How to encode messages for a given `ProtocolId`
     fn encoding(self) -> Encoding {
         match self {
-            ProtocolId::ConsensusDirectSendJson | ProtocolId::ConsensusRpcJson => Encoding::Json,
+            ProtocolId::ConsensusDirectSendJson
+            | ProtocolId::ConsensusRpcJson
+            | ProtocolId::ConsensusRpcJsonCompressed => Encoding::Json,
             _ => Encoding::Bcs,
         }
     }
@@ -190,6 +243,14 @@ impl ProtocolIdSet {
     pub fn insert(&mut self, protocol: ProtocolId) {
         self.0.set(protocol as u8)
     }
+
+    /// Return the set of protocols extended with the compressed counterparts
+    /// of its protocols. Advertising these during the handshake negotiates
+    /// compression with the remote peer.
+    pub fn with_compressed(&self) -> ProtocolIdSet {
+        let compressed: ProtocolIdSet = self.iter().filter_map(ProtocolId::compressed).collect();
+        self.union(&compressed)
+    }
 }
 
 impl FromIterator<ProtocolId> for ProtocolIdSet {
@@ -35,6 +35,42 @@ fn test_as_u8_serde_equiv() {
     }
 }
 
+#[test]
+fn compressed_protocols() {
+    for protocol in ProtocolId::all() {
+        if let Some(compressed) = protocol.compressed() {
+            assert_eq!(compressed.decompressed(), Some(*protocol));
+            assert_eq!(compressed.compressed(), None);
+        }
+    }
+
+    // Compression is only negotiated if both peers support it
+    let protocols = ProtocolIdSet::from_iter([
+        ProtocolId::ConsensusDirectSendBcs,
+        ProtocolId::StorageServiceRpc,
+    ]);
+    let compressed_protocols = protocols.with_compressed();
+    assert_eq!(
+        compressed_protocols,
+        ProtocolIdSet::from_iter([
+            ProtocolId::ConsensusDirectSendBcs,
+            ProtocolId::StorageServiceRpc,
+            ProtocolId::StorageServiceRpcCompressed,
+        ])
+    );
+
+    let compressed_hs = HandshakeMsg::from_supported(compressed_protocols.clone());
+    let (_, common_protos) = compressed_hs
+        .perform_handshake(&HandshakeMsg::from_supported(compressed_protocols.clone()))
+        .unwrap();
+    assert_eq!(common_protos, compressed_protocols);
+
+    let (_, common_protos) = compressed_hs
+        .perform_handshake(&HandshakeMsg::from_supported(protocols.clone()))
+        .unwrap();
+    assert_eq!(common_protos, protocols);
+}
+
 #[test]
 fn represents_same_network() {
     let mut handshake_msg = HandshakeMsg::new_for_testing();
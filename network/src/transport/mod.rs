@@ -3,14 +3,17 @@
 
 use crate::{
     logging::NetworkSchema,
-    noise::{stream::NoiseStream, AntiReplayTimestamps, HandshakeAuthMode, NoiseUpgrader},
+    noise::{
+        stream::NoiseStream, AntiReplayTimestamps, HandshakeAntiDos, HandshakeAuthMode,
+        NoiseUpgrader,
+    },
     protocols::{
         identity::exchange_handshake,
         wire::handshake::v1::{HandshakeMsg, MessagingProtocolVersion, ProtocolIdSet},
     },
 };
 use aptos_config::{
-    config::{PeerRole, HANDSHAKE_VERSION},
+    config::{HandshakeRateLimitConfig, PeerRole, COOKIE_HANDSHAKE_VERSION, HANDSHAKE_VERSION},
     network_id::{NetworkContext, NetworkId},
 };
 use aptos_crypto::x25519;
@@ -19,7 +22,10 @@ use aptos_logger::prelude::*;
 use aptos_time_service::{timeout, TimeService, TimeServiceTrait};
 use aptos_types::{
     chain_id::ChainId,
-    network_address::{parse_dns_tcp, parse_ip_tcp, parse_memory, NetworkAddress},
+    network_address::{
+        parse_dns_tcp, parse_dns_udp, parse_ip_tcp, parse_ip_udp, parse_memory, parse_tls,
+        NetworkAddress,
+    },
     PeerId,
 };
 use futures::{
@@ -258,8 +264,11 @@ async fn upgrade_inbound<T: TSocket>(
     };
 
     // try authenticating via noise handshake
-    let (mut socket, remote_peer_id, peer_role) =
-        ctxt.noise.upgrade_inbound(socket).await.map_err(|err| {
+    let (mut socket, remote_peer_id, peer_role) = ctxt
+        .noise
+        .upgrade_inbound_from(socket, addr.find_ip_addr())
+        .await
+        .map_err(|err| {
             if err.should_security_log() {
                 sample!(
                     SampleRate::Duration(Duration::from_secs(15)),
@@ -321,36 +330,44 @@ async fn upgrade_inbound<T: TSocket>(
 
 /// Upgrade an inbound connection. This means we run a Noise IK handshake for
 /// authentication and then negotiate common supported protocols.
+///
+/// The Noise handshake carries a cookie if the remote advertises the `COOKIE_HANDSHAKE_VERSION`.
 pub async fn upgrade_outbound<T: TSocket>(
     ctxt: Arc<UpgradeContext>,
     fut_socket: impl Future<Output = io::Result<T>>,
     addr: NetworkAddress,
     remote_peer_id: PeerId,
     remote_pubkey: x25519::PublicKey,
+    handshake_version: u8,
 ) -> io::Result<Connection<NoiseStream<T>>> {
     let origin = ConnectionOrigin::Outbound;
     let socket = fut_socket.await?;
 
     // noise handshake
-    let mut socket = ctxt
-        .noise
-        .upgrade_outbound(socket, remote_pubkey, AntiReplayTimestamps::now)
-        .await
-        .map_err(|err| {
-            if err.should_security_log() {
-                sample!(
-                    SampleRate::Duration(Duration::from_secs(15)),
-                    error!(
-                        SecurityEvent::NoiseHandshake,
-                        NetworkSchema::new(&ctxt.noise.network_context)
-                            .network_address(&addr)
-                            .connection_origin(&origin),
-                        error = %err,
-                    )
-                );
-            }
-            io::Error::new(io::ErrorKind::Other, err)
-        })?;
+    let handshake = if handshake_version == COOKIE_HANDSHAKE_VERSION {
+        ctxt.noise
+            .upgrade_outbound_with_cookies(socket, remote_pubkey, AntiReplayTimestamps::now)
+            .await
+    } else {
+        ctxt.noise
+            .upgrade_outbound(socket, remote_pubkey, AntiReplayTimestamps::now)
+            .await
+    };
+    let mut socket = handshake.map_err(|err| {
+        if err.should_security_log() {
+            sample!(
+                SampleRate::Duration(Duration::from_secs(15)),
+                error!(
+                    SecurityEvent::NoiseHandshake,
+                    NetworkSchema::new(&ctxt.noise.network_context)
+                        .network_address(&addr)
+                        .connection_origin(&origin),
+                    error = %err,
+                )
+            );
+        }
+        io::Error::new(io::ErrorKind::Other, err)
+    })?;
 
     // sanity check: Noise IK should always guarantee this is true
     debug_assert_eq!(remote_pubkey, socket.get_remote_static());
@@ -393,7 +410,8 @@ pub async fn upgrade_outbound<T: TSocket>(
 ///
 /// The base transport layer is pluggable, so long as it provides a reliable,
 /// ordered, connection-oriented, byte-stream abstraction (e.g., TCP). We currently
-/// use either `MemoryTransport` or `TcpTransport` as this base layer.
+/// use either `MemoryTransport`, `TcpTransport`, `TlsTransport` or `QuicTransport` as
+/// this base layer.
 ///
 /// Inbound and outbound connections are first established with the `base_transport`
 /// and then negotiate a secure, authenticated transport layer (currently Noise
@@ -427,6 +445,7 @@ where
         chain_id: ChainId,
         application_protocols: ProtocolIdSet,
         enable_proxy_protocol: bool,
+        handshake_rate_limit_config: Option<HandshakeRateLimitConfig>,
     ) -> Self {
         // build supported protocols
         let mut supported_protocols = BTreeMap::new();
@@ -434,8 +453,13 @@ where
 
         let identity_pubkey = identity_key.public_key();
 
+        let mut noise = NoiseUpgrader::new(network_context, identity_key, auth_mode);
+        if let Some(config) = handshake_rate_limit_config {
+            noise = noise.with_anti_dos(HandshakeAntiDos::new(&config));
+        }
+
         let upgrade_context = UpgradeContext::new(
-            NoiseUpgrader::new(network_context, identity_key, auth_mode),
+            noise,
             handshake_version,
             supported_protocols,
             chain_id,
@@ -462,16 +486,24 @@ where
         // and leave for the base_transport to actually parse and dial.
         // TODO(philiphayes): protos[..X] is kinda hacky. `Transport` trait
         // should handle this.
+        // note: the TLS transport checks the listener's certificate against
+        // the Noise IK key, so it's handed the whole address.
         let (base_transport_protos, base_transport_suffix) = parse_ip_tcp(protos)
             .map(|x| (&protos[..2], x.1))
             .or_else(|| parse_dns_tcp(protos).map(|x| (&protos[..2], x.1)))
+            .map(|(tcp_protos, suffix)| match parse_tls(suffix) {
+                Some(suffix) => (protos, suffix),
+                None => (tcp_protos, suffix),
+            })
+            .or_else(|| parse_ip_udp(protos).map(|x| (&protos[..2], x.1)))
+            .or_else(|| parse_dns_udp(protos).map(|x| (&protos[..2], x.1)))
             .or_else(|| parse_memory(protos).map(|x| (&protos[..1], x.1)))
             .ok_or_else(|| {
                 io::Error::new(
                     io::ErrorKind::InvalidInput,
                     format!(
                         "Unexpected dialing network address: '{}', expected: \
-                         memory, ip+tcp, or dns+tcp",
+                         memory, ip+tcp, dns+tcp, ip+tcp+tls, dns+tcp+tls, ip+udp or dns+udp",
                         addr
                     ),
                 )
@@ -517,6 +549,12 @@ where
     /// `/dns/<ipaddr>/tcp/<port>` or
     /// `/dns4/<ipaddr>/tcp/<port>` or
     /// `/dns6/<ipaddr>/tcp/<port>`
+    ///
+    /// If the base transport is `QuicTransport`, then `/<base_transport>` is
+    /// any of the above with `/udp/<port>` in place of `/tcp/<port>`.
+    ///
+    /// If the base transport is `TlsTransport`, then `/<base_transport>` is
+    /// any of the TCP addresses above followed by `/tls`.
     pub fn dial(
         &self,
         peer_id: PeerId,
@@ -529,7 +567,9 @@ where
         let (base_addr, pubkey, handshake_version) = Self::parse_dial_addr(&addr)?;
 
         // Check that the parsed handshake version from the dial addr is supported.
-        if self.ctxt.handshake_version != handshake_version {
+        if self.ctxt.handshake_version != handshake_version
+            && handshake_version != COOKIE_HANDSHAKE_VERSION
+        {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 format!(
@@ -543,7 +583,14 @@ where
         let fut_socket = self.base_transport.dial(peer_id, base_addr)?;
 
         // outbound dial upgrade task
-        let upgrade_fut = upgrade_outbound(self.ctxt.clone(), fut_socket, addr, peer_id, pubkey);
+        let upgrade_fut = upgrade_outbound(
+            self.ctxt.clone(),
+            fut_socket,
+            addr,
+            peer_id,
+            pubkey,
+            handshake_version,
+        );
         let upgrade_fut = timeout_io(self.time_service.clone(), TRANSPORT_TIMEOUT, upgrade_fut);
         Ok(upgrade_fut)
     }
@@ -563,6 +610,16 @@ where
     ///
     /// `/ip4/<ipaddr>/tcp/<port>` or
     /// `/ip6/<ipaddr>/tcp/<port>`
+    ///
+    /// If the base transport is `QuicTransport`, then we expect:
+    ///
+    /// `/ip4/<ipaddr>/udp/<port>` or
+    /// `/ip6/<ipaddr>/udp/<port>`
+    ///
+    /// If the base transport is `TlsTransport`, then we expect:
+    ///
+    /// `/ip4/<ipaddr>/tcp/<port>/tls` or
+    /// `/ip6/<ipaddr>/tcp/<port>/tls`
     pub fn listen_on(
         &self,
         addr: NetworkAddress,
@@ -585,8 +642,12 @@ where
         // (e.g., `/memory/<port>` with no trailers), so we don't need to do any
         // parsing here.
         let (listener, listen_addr) = self.base_transport.listen_on(addr)?;
-        let listen_addr =
-            listen_addr.append_prod_protos(self.identity_pubkey, self.ctxt.handshake_version);
+        let handshake_version = if self.ctxt.noise.requires_cookies() {
+            COOKIE_HANDSHAKE_VERSION
+        } else {
+            self.ctxt.handshake_version
+        };
+        let listen_addr = listen_addr.append_prod_protos(self.identity_pubkey, handshake_version);
 
         // need to move a ctxt into stream task
         let ctxt = self.ctxt.clone();
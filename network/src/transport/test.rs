@@ -6,7 +6,10 @@ use crate::{
     transport::*,
 };
 use aptos_config::{
-    config::{Peer, PeerRole, PeerSet, HANDSHAKE_VERSION},
+    config::{
+        HandshakeRateLimitConfig, Peer, PeerRole, PeerSet, COOKIE_HANDSHAKE_VERSION,
+        HANDSHAKE_VERSION,
+    },
     network_id::NetworkContext,
 };
 use aptos_crypto::{test_utils::TEST_SEED, traits::Uniform, x25519};
@@ -21,7 +24,7 @@ use bytes::{Bytes, BytesMut};
 use futures::{future, io::AsyncWriteExt, stream::StreamExt};
 use netcore::{
     framing::{read_u16frame, write_u16frame},
-    transport::{memory, ConnectionOrigin, Transport},
+    transport::{memory, quic::QuicTransport, tls::TlsTransport, ConnectionOrigin, Transport},
 };
 use rand::{rngs::StdRng, SeedableRng};
 use std::{collections::HashMap, io, iter::FromIterator, sync::Arc};
@@ -55,8 +58,9 @@ enum Auth {
 }
 
 fn setup<TTransport>(
-    base_transport: TTransport,
+    base_transport: impl Fn(PeerId, &x25519::PrivateKey, &HandshakeAuthMode) -> TTransport,
     auth: Auth,
+    listener_handshake_rate_limit_config: Option<HandshakeRateLimitConfig>,
 ) -> (
     Runtime,
     MockTimeService,
@@ -147,7 +151,7 @@ where
         ProtocolIdSet::from_iter([ProtocolId::ConsensusRpcBcs, ProtocolId::DiscoveryDirectSend]);
     let chain_id = ChainId::default();
     let listener_transport = AptosNetTransport::new(
-        base_transport.clone(),
+        base_transport(listener_peer_id, &listener_key, &listener_auth_mode),
         NetworkContext::mock_with_peer_id(listener_peer_id),
         time_service.clone(),
         listener_key,
@@ -156,10 +160,11 @@ where
         chain_id,
         supported_protocols.clone(),
         false, /* Disable proxy protocol */
+        listener_handshake_rate_limit_config,
     );
 
     let dialer_transport = AptosNetTransport::new(
-        base_transport,
+        base_transport(dialer_peer_id, &dialer_key, &dialer_auth_mode),
         NetworkContext::mock_with_peer_id(dialer_peer_id),
         time_service.clone(),
         dialer_key,
@@ -168,6 +173,7 @@ where
         chain_id,
         supported_protocols.clone(),
         false, /* Disable proxy protocol */
+        None,  /* Disable handshake rate limits */
     );
 
     (
@@ -209,8 +215,31 @@ fn expect_ip4_tcp_noise_addr(addr: &NetworkAddress) {
     );
 }
 
+/// Check that the network address matches the format
+/// `"/ip4/<ipaddr>/tcp/<port>/tls/ln-noise-ik/<pubkey>/ln-handshake/<version>"`
+fn expect_ip4_tcp_tls_noise_addr(addr: &NetworkAddress) {
+    assert!(
+        matches!(
+            addr.as_slice(),
+            [Ip4(_), Tcp(_), Tls, NoiseIK(_), Handshake(_)]
+        ),
+        "addr: '{}'",
+        addr
+    );
+}
+
+/// Check that the network address matches the format
+/// `"/ip4/<ipaddr>/udp/<port>/ln-noise-ik/<pubkey>/ln-handshake/<version>"`
+fn expect_ip4_udp_noise_addr(addr: &NetworkAddress) {
+    assert!(
+        matches!(addr.as_slice(), [Ip4(_), Udp(_), NoiseIK(_), Handshake(_)]),
+        "addr: '{}'",
+        addr
+    );
+}
+
 fn test_transport_success<TTransport>(
-    base_transport: TTransport,
+    base_transport: impl Fn(PeerId, &x25519::PrivateKey, &HandshakeAuthMode) -> TTransport,
     auth: Auth,
     listen_addr: &str,
     expect_formatted_addr: fn(&NetworkAddress),
@@ -228,7 +257,7 @@ fn test_transport_success<TTransport>(
         (dialer_peer_id, dialer_transport),
         _trusted_peers,
         supported_protocols,
-    ) = setup(base_transport, auth);
+    ) = setup(base_transport, auth, None);
 
     let _guard = rt.enter();
     let (mut inbounds, listener_addr) = listener_transport
@@ -294,7 +323,7 @@ fn test_transport_success<TTransport>(
 }
 
 fn test_transport_rejects_unauthed_dialer<TTransport>(
-    base_transport: TTransport,
+    base_transport: impl Fn(PeerId, &x25519::PrivateKey, &HandshakeAuthMode) -> TTransport,
     listen_addr: &str,
     expect_formatted_addr: fn(&NetworkAddress),
 ) where
@@ -311,7 +340,7 @@ fn test_transport_rejects_unauthed_dialer<TTransport>(
         (dialer_peer_id, dialer_transport),
         trusted_peers,
         _supported_protocols,
-    ) = setup(base_transport, Auth::Mutual);
+    ) = setup(base_transport, Auth::Mutual, None);
 
     // remove dialer from trusted_peers set
     trusted_peers.write().remove(&dialer_peer_id).unwrap();
@@ -348,7 +377,7 @@ fn test_transport_rejects_unauthed_dialer<TTransport>(
 }
 
 fn test_transport_maybe_mutual<TTransport>(
-    base_transport: TTransport,
+    base_transport: impl Fn(PeerId, &x25519::PrivateKey, &HandshakeAuthMode) -> TTransport,
     listen_addr: &str,
     expect_formatted_addr: fn(&NetworkAddress),
 ) where
@@ -365,7 +394,7 @@ fn test_transport_maybe_mutual<TTransport>(
         (dialer_peer_id, dialer_transport),
         trusted_peers,
         supported_protocols,
-    ) = setup(base_transport, Auth::MaybeMutual);
+    ) = setup(base_transport, Auth::MaybeMutual, None);
 
     let _guard = rt.enter();
     let (mut inbounds, listener_addr) = listener_transport
@@ -496,7 +525,7 @@ fn test_transport_maybe_mutual<TTransport>(
 #[test]
 fn test_memory_transport_mutual_auth() {
     test_transport_success(
-        memory::MemoryTransport,
+        |_, _, _| memory::MemoryTransport,
         Auth::Mutual,
         "/memory/0",
         expect_memory_noise_addr,
@@ -506,7 +535,7 @@ fn test_memory_transport_mutual_auth() {
 #[test]
 fn test_memory_transport_server_only_auth() {
     test_transport_success(
-        memory::MemoryTransport,
+        |_, _, _| memory::MemoryTransport,
         Auth::ServerOnly,
         "/memory/0",
         expect_memory_noise_addr,
@@ -516,7 +545,7 @@ fn test_memory_transport_server_only_auth() {
 #[test]
 fn test_memory_transport_rejects_unauthed_dialer() {
     test_transport_rejects_unauthed_dialer(
-        memory::MemoryTransport,
+        |_, _, _| memory::MemoryTransport,
         "/memory/0",
         expect_memory_noise_addr,
     );
@@ -525,12 +554,51 @@ fn test_memory_transport_rejects_unauthed_dialer() {
 #[test]
 fn test_memory_transport_maybe_mutual() {
     test_transport_maybe_mutual(
-        memory::MemoryTransport,
+        |_, _, _| memory::MemoryTransport,
         "/memory/0",
         expect_memory_noise_addr,
     );
 }
 
+#[test]
+fn test_memory_transport_handshake_rate_limits() {
+    let (rt, _mock_time, (listener_peer_id, listener_transport), (_, dialer_transport), _, _) =
+        setup(
+            |_, _, _| memory::MemoryTransport,
+            Auth::Mutual,
+            Some(HandshakeRateLimitConfig::default()),
+        );
+
+    let _guard = rt.enter();
+    let (mut inbounds, listener_addr) = listener_transport
+        .listen_on("/memory/0".parse().unwrap())
+        .unwrap();
+
+    // the listener requires cookies, which dialers learn from its address
+    assert!(
+        matches!(
+            listener_addr.as_slice(),
+            [Memory(_), NoiseIK(_), Handshake(COOKIE_HANDSHAKE_VERSION)]
+        ),
+        "addr: '{}'",
+        listener_addr
+    );
+
+    let listener_task = async move {
+        let (inbound, _dialer_addr) = inbounds.next().await.unwrap().unwrap();
+        inbound.await.unwrap();
+    };
+    let dialer_task = async move {
+        dialer_transport
+            .dial(listener_peer_id, listener_addr)
+            .unwrap()
+            .await
+            .unwrap();
+    };
+
+    rt.block_on(future::join(listener_task, dialer_task));
+}
+
 /////////////////////////////////////
 // AptosNetTransport<TcpTransport> //
 /////////////////////////////////////
@@ -538,7 +606,7 @@ fn test_memory_transport_maybe_mutual() {
 #[test]
 fn test_tcp_transport_mutual_auth() {
     test_transport_success(
-        DIEM_TCP_TRANSPORT.clone(),
+        |_, _, _| DIEM_TCP_TRANSPORT.clone(),
         Auth::Mutual,
         "/ip4/127.0.0.1/tcp/0",
         expect_ip4_tcp_noise_addr,
@@ -548,7 +616,7 @@ fn test_tcp_transport_mutual_auth() {
 #[test]
 fn test_tcp_transport_server_only_auth() {
     test_transport_success(
-        DIEM_TCP_TRANSPORT.clone(),
+        |_, _, _| DIEM_TCP_TRANSPORT.clone(),
         Auth::ServerOnly,
         "/ip4/127.0.0.1/tcp/0",
         expect_ip4_tcp_noise_addr,
@@ -558,8 +626,97 @@ fn test_tcp_transport_server_only_auth() {
 #[test]
 fn test_tcp_transport_rejects_unauthed_dialer() {
     test_transport_rejects_unauthed_dialer(
-        DIEM_TCP_TRANSPORT.clone(),
+        |_, _, _| DIEM_TCP_TRANSPORT.clone(),
         "/ip4/127.0.0.1/tcp/0",
         expect_ip4_tcp_noise_addr,
     );
 }
+
+/////////////////////////////////////
+// AptosNetTransport<TlsTransport> //
+/////////////////////////////////////
+
+fn tls_transport(
+    peer_id: PeerId,
+    key: &x25519::PrivateKey,
+    auth_mode: &HandshakeAuthMode,
+) -> TlsTransport {
+    TlsTransport::new(
+        DIEM_TCP_TRANSPORT.clone(),
+        peer_id,
+        key,
+        auth_mode.tls_client_authorizer(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_tls_transport_mutual_auth() {
+    test_transport_success(
+        tls_transport,
+        Auth::Mutual,
+        "/ip4/127.0.0.1/tcp/0/tls",
+        expect_ip4_tcp_tls_noise_addr,
+    );
+}
+
+#[test]
+fn test_tls_transport_server_only_auth() {
+    test_transport_success(
+        tls_transport,
+        Auth::ServerOnly,
+        "/ip4/127.0.0.1/tcp/0/tls",
+        expect_ip4_tcp_tls_noise_addr,
+    );
+}
+
+#[test]
+fn test_tls_transport_rejects_unauthed_dialer() {
+    test_transport_rejects_unauthed_dialer(
+        tls_transport,
+        "/ip4/127.0.0.1/tcp/0/tls",
+        expect_ip4_tcp_tls_noise_addr,
+    );
+}
+
+#[test]
+fn test_tls_transport_maybe_mutual() {
+    test_transport_maybe_mutual(
+        tls_transport,
+        "/ip4/127.0.0.1/tcp/0/tls",
+        expect_ip4_tcp_tls_noise_addr,
+    );
+}
+
+//////////////////////////////////////
+// AptosNetTransport<QuicTransport> //
+//////////////////////////////////////
+
+#[test]
+fn test_quic_transport_mutual_auth() {
+    test_transport_success(
+        |_, _, _| QuicTransport::new(true),
+        Auth::Mutual,
+        "/ip4/127.0.0.1/udp/0",
+        expect_ip4_udp_noise_addr,
+    );
+}
+
+#[test]
+fn test_quic_transport_server_only_auth() {
+    test_transport_success(
+        |_, _, _| QuicTransport::new(true),
+        Auth::ServerOnly,
+        "/ip4/127.0.0.1/udp/0",
+        expect_ip4_udp_noise_addr,
+    );
+}
+
+#[test]
+fn test_quic_transport_rejects_unauthed_dialer() {
+    test_transport_rejects_unauthed_dialer(
+        |_, _, _| QuicTransport::new(false),
+        "/ip4/127.0.0.1/udp/0",
+        expect_ip4_udp_noise_addr,
+    );
+}
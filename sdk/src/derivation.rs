@@ -0,0 +1,197 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Derivation of account keys from [BIP-39](https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki)
+//! mnemonic phrases, following [SLIP-0010](https://github.com/satoshilabs/slips/blob/master/slip-0010.md)
+//! for Ed25519 keys, so that accounts can be recovered by any compatible wallet.
+//!
+//! Aptos accounts are derived at the path `m/44'/637'/{account}'/0'/0'`, where 637 is the
+//! [SLIP-0044](https://github.com/satoshilabs/slips/blob/master/slip-0044.md) coin type of Aptos.
+//! SLIP-0010 only defines hardened derivation for Ed25519, so every index of a path is hardened.
+
+use crate::crypto::ed25519::Ed25519PrivateKey;
+use anyhow::{anyhow, ensure, Result};
+use bip39::{Language, Mnemonic, Seed};
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha512;
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+/// The SLIP-0044 coin type of Aptos
+pub const APTOS_COIN_TYPE: u32 = 637;
+
+/// The BIP-44 purpose
+const PURPOSE: u32 = 44;
+/// Indices at or above the offset are hardened
+const HARDENED_OFFSET: u32 = 0x8000_0000;
+/// The HMAC key deriving the master key of the Ed25519 curve from a seed
+const ED25519_SEED_KEY: &[u8] = b"ed25519 seed";
+
+/// A derivation path of hardened indices, such as `m/44'/637'/0'/0'/0'`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// The path of an Aptos account, `m/44'/637'/{account_index}'/0'/0'`.
+    pub fn aptos(account_index: u32) -> Result<Self> {
+        Self::new(vec![PURPOSE, APTOS_COIN_TYPE, account_index, 0, 0])
+    }
+
+    /// Creates a path from indices, which are all hardened and thus must be below 2^31.
+    pub fn new(indices: Vec<u32>) -> Result<Self> {
+        ensure!(
+            indices.iter().all(|index| *index < HARDENED_OFFSET),
+            "Derivation path indices must be below {}",
+            HARDENED_OFFSET
+        );
+        Ok(Self(indices))
+    }
+
+    /// The indices of the path, without their hardened flag.
+    pub fn indices(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = anyhow::Error;
+
+    fn from_str(path: &str) -> Result<Self> {
+        let mut components = path.split('/');
+        ensure!(
+            components.next() == Some("m"),
+            "Derivation path '{}' must start with 'm'",
+            path
+        );
+        let indices = components
+            .map(|component| {
+                let index = component.strip_suffix('\'').ok_or_else(|| {
+                    anyhow!(
+                        "Index '{}' of derivation path '{}' must be hardened",
+                        component,
+                        path
+                    )
+                })?;
+                index.parse::<u32>().map_err(|e| {
+                    anyhow!(
+                        "Invalid index '{}' of derivation path '{}': {}",
+                        component,
+                        path,
+                        e
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::new(indices)
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.0 {
+            write!(f, "/{}'", index)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the seed of a BIP-39 mnemonic phrase in English, after checking the phrase's checksum.
+/// The passphrase may be empty.
+pub fn mnemonic_to_seed(mnemonic_phrase: &str, passphrase: &str) -> Result<Vec<u8>> {
+    let mnemonic = Mnemonic::from_phrase(mnemonic_phrase, Language::English)
+        .map_err(|e| anyhow!("Invalid mnemonic phrase: {}", e))?;
+    Ok(Seed::new(&mnemonic, passphrase).as_bytes().to_vec())
+}
+
+/// Derives the Ed25519 private key at the path from a seed, with SLIP-0010.
+pub fn derive_private_key(seed: &[u8], path: &DerivationPath) -> Ed25519PrivateKey {
+    let (mut key, mut chain_code) = hmac_sha512(ED25519_SEED_KEY, &[seed]);
+    for index in path.indices() {
+        let hardened_index = (index | HARDENED_OFFSET).to_be_bytes();
+        let (child_key, child_chain_code) =
+            hmac_sha512(&chain_code, &[&[0u8], &key, &hardened_index]);
+        key = child_key;
+        chain_code = child_chain_code;
+    }
+    Ed25519PrivateKey::try_from(&key[..]).expect("Any 32 bytes are a valid Ed25519 private key")
+}
+
+/// Returns the two halves of the HMAC-SHA512 of the concatenated data
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha512>::new_varkey(key).expect("HMAC accepts keys of any size");
+    for data in data {
+        mac.update(data);
+    }
+    let output = mac.finalize().into_bytes();
+    let mut left = [0u8; 32];
+    let mut right = [0u8; 32];
+    left.copy_from_slice(&output[..32]);
+    right.copy_from_slice(&output[32..]);
+    (left, right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derivation_path() {
+        let path: DerivationPath = "m/44'/637'/0'/0'/0'".parse().unwrap();
+        assert_eq!(path, DerivationPath::aptos(0).unwrap());
+        assert_eq!(path.to_string(), "m/44'/637'/0'/0'/0'");
+        assert!("m".parse::<DerivationPath>().unwrap().indices().is_empty());
+
+        for invalid_path in &[
+            "",
+            "44'/637'",
+            "m/44'/637",
+            "m/44'/-637'",
+            "m/44'/2147483648'",
+            "m/44'//0'",
+        ] {
+            assert!(invalid_path.parse::<DerivationPath>().is_err());
+        }
+    }
+
+    #[test]
+    fn test_slip10_vectors() {
+        // Test vector 1 of SLIP-0010 for ed25519
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let vectors = [
+            (
+                "m",
+                "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+            ),
+            (
+                "m/0'",
+                "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+            ),
+            (
+                "m/0'/1'/2'",
+                "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9",
+            ),
+            (
+                "m/0'/1'/2'/2'/1000000000'",
+                "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+            ),
+        ];
+        for (path, private_key) in &vectors {
+            let private_key_bytes = derive_private_key(&seed, &path.parse().unwrap()).to_bytes();
+            assert_eq!(hex::encode(private_key_bytes), *private_key);
+        }
+    }
+
+    #[test]
+    fn test_mnemonic_to_seed() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        assert_eq!(
+            hex::encode(mnemonic_to_seed(phrase, "TREZOR").unwrap()),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+
+        // The last word encodes a checksum
+        let invalid_checksum = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+        assert!(mnemonic_to_seed(invalid_checksum, "").is_err());
+        assert!(mnemonic_to_seed("not a mnemonic", "").is_err());
+    }
+}
@@ -8,6 +8,9 @@
 //! This SDK provides all the necessary components for building on top of the Diem Blockchain. Some of the important modules are:
 //!
 //! * `crypto` - Types used for signing and verifying
+//! * `derivation` - Derivation of account keys from mnemonic phrases
+//! * `multisig` - Signing of transactions by the holders of the keys of MultiEd25519 accounts
+//! * `sequence_number` - Allocation of sequence numbers for concurrent submissions from an account
 //! * `transaction_builder` - Includes helpers for constructing transactions
 //! * `types` - Includes types for Diem on-chain data structures
 //!
@@ -21,6 +24,12 @@ pub mod crypto {
     pub use aptos_crypto::*;
 }
 
+pub mod derivation;
+
+pub mod multisig;
+
+pub mod sequence_number;
+
 pub mod transaction_builder;
 
 pub mod types;
@@ -0,0 +1,334 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Accounts authenticated by a K-of-N MultiEd25519 key, whose transactions need the signatures of
+//! at least `threshold` of its Ed25519 keys. The keys are usually held by distinct parties: each
+//! of them produces a [`PartialSignature`] of the transaction, and a [`MultiEd25519Coordinator`]
+//! collects the partial signatures and merges them into the signed transaction.
+
+use crate::{
+    crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+        multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+        traits::{Signature, SigningKey},
+    },
+    transaction_builder::TransactionBuilder,
+    types::{
+        account_address::AccountAddress,
+        transaction::{authenticator::AuthenticationKey, RawTransaction, SignedTransaction},
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// An account authenticated by a K-of-N MultiEd25519 key. It holds no private key, transactions
+/// are signed by the holders of the keys.
+#[derive(Clone, Debug)]
+pub struct MultiEd25519Account {
+    /// Address of the account.
+    address: AccountAddress,
+    /// Public keys and threshold of the account.
+    public_key: MultiEd25519PublicKey,
+    /// Latest known sequence number of the account, it can be different from validator.
+    sequence_number: u64,
+}
+
+impl MultiEd25519Account {
+    /// Creates the account whose address is derived from the public keys and threshold.
+    pub fn new(
+        public_keys: Vec<Ed25519PublicKey>,
+        threshold: u8,
+        sequence_number: u64,
+    ) -> Result<Self> {
+        let public_key = MultiEd25519PublicKey::new(public_keys, threshold)
+            .map_err(|e| anyhow!("Invalid MultiEd25519 public key: {}", e))?;
+        let address = AuthenticationKey::multi_ed25519(&public_key).derived_address();
+        Ok(Self {
+            address,
+            public_key,
+            sequence_number,
+        })
+    }
+
+    /// Builds the transaction to be signed by the holders of the keys, and increments the
+    /// sequence number.
+    pub fn build_transaction(&mut self, builder: TransactionBuilder) -> RawTransaction {
+        let raw_txn = builder
+            .sender(self.address())
+            .sequence_number(self.sequence_number())
+            .build();
+        *self.sequence_number_mut() += 1;
+        raw_txn
+    }
+
+    /// Signs the transaction with all the private keys at once, when they are held by a single
+    /// party.
+    pub fn sign_transaction(
+        &self,
+        raw_txn: RawTransaction,
+        private_keys: &[&Ed25519PrivateKey],
+    ) -> Result<SignedTransaction> {
+        let mut coordinator = MultiEd25519Coordinator::new(raw_txn, self.public_key.clone());
+        for private_key in private_keys {
+            let partial_signature = PartialSignature::sign(
+                coordinator.raw_transaction(),
+                &self.public_key,
+                private_key,
+            )?;
+            coordinator.add(partial_signature)?;
+        }
+        coordinator.combine()
+    }
+
+    pub fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    pub fn public_key(&self) -> &MultiEd25519PublicKey {
+        &self.public_key
+    }
+
+    pub fn authentication_key(&self) -> AuthenticationKey {
+        AuthenticationKey::multi_ed25519(&self.public_key)
+    }
+
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
+
+    pub fn sequence_number_mut(&mut self) -> &mut u64 {
+        &mut self.sequence_number
+    }
+}
+
+/// The signature of a transaction by one of the keys of a MultiEd25519 public key. It can be
+/// serialized to be sent to the coordinator.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PartialSignature {
+    /// Index of the signing key in the MultiEd25519 public key.
+    index: u8,
+    signature: Ed25519Signature,
+}
+
+impl PartialSignature {
+    /// Signs the transaction with one of the keys of the MultiEd25519 public key.
+    pub fn sign(
+        raw_txn: &RawTransaction,
+        public_key: &MultiEd25519PublicKey,
+        private_key: &Ed25519PrivateKey,
+    ) -> Result<Self> {
+        let signer_public_key = Ed25519PublicKey::from(private_key);
+        let index = public_key
+            .public_keys()
+            .iter()
+            .position(|key| *key == signer_public_key)
+            .ok_or_else(|| anyhow!("The private key is not part of the MultiEd25519 key"))?;
+        Ok(Self {
+            index: index as u8,
+            signature: private_key.sign(raw_txn),
+        })
+    }
+
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    pub fn signature(&self) -> &Ed25519Signature {
+        &self.signature
+    }
+}
+
+/// Collects the partial signatures of a transaction by the holders of the keys of a
+/// MultiEd25519 public key, and merges them into the signed transaction once the threshold is
+/// reached.
+#[derive(Clone, Debug)]
+pub struct MultiEd25519Coordinator {
+    raw_txn: RawTransaction,
+    public_key: MultiEd25519PublicKey,
+    partial_signatures: BTreeMap<u8, Ed25519Signature>,
+}
+
+impl MultiEd25519Coordinator {
+    pub fn new(raw_txn: RawTransaction, public_key: MultiEd25519PublicKey) -> Self {
+        Self {
+            raw_txn,
+            public_key,
+            partial_signatures: BTreeMap::new(),
+        }
+    }
+
+    /// The transaction to be signed by the holders of the keys
+    pub fn raw_transaction(&self) -> &RawTransaction {
+        &self.raw_txn
+    }
+
+    /// Adds a partial signature, after verifying it against the public key at its index, so that
+    /// an invalid partial signature is attributed to its signer rather than failing the combined
+    /// signature.
+    pub fn add(&mut self, partial_signature: PartialSignature) -> Result<()> {
+        let index = partial_signature.index;
+        let public_key = self
+            .public_key
+            .public_keys()
+            .get(index as usize)
+            .ok_or_else(|| anyhow!("No public key at index {}", index))?;
+        partial_signature
+            .signature
+            .verify(&self.raw_txn, public_key)
+            .map_err(|e| anyhow!("Invalid partial signature at index {}: {}", index, e))?;
+        self.partial_signatures
+            .insert(index, partial_signature.signature);
+        Ok(())
+    }
+
+    /// The number of distinct keys that signed the transaction so far
+    pub fn num_signatures(&self) -> usize {
+        self.partial_signatures.len()
+    }
+
+    /// Whether enough keys signed the transaction to combine their signatures
+    pub fn is_complete(&self) -> bool {
+        self.num_signatures() >= *self.public_key.threshold() as usize
+    }
+
+    /// Merges the partial signatures into the signed transaction. Only as many signatures as the
+    /// threshold are included, to keep the transaction small.
+    pub fn combine(self) -> Result<SignedTransaction> {
+        let threshold = *self.public_key.threshold() as usize;
+        ensure!(
+            self.is_complete(),
+            "Not enough partial signatures: {} out of {}",
+            self.num_signatures(),
+            threshold
+        );
+        let signatures = self
+            .partial_signatures
+            .into_iter()
+            .take(threshold)
+            .map(|(index, signature)| (signature, index))
+            .collect();
+        let signature = MultiEd25519Signature::new(signatures)
+            .map_err(|e| anyhow!("Failed to combine partial signatures: {}", e))?;
+        Ok(SignedTransaction::new_multisig(
+            self.raw_txn,
+            self.public_key,
+            signature,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto::Uniform, transaction_builder::TransactionFactory, types::chain_id::ChainId,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn keys(num_keys: usize) -> (Vec<Ed25519PrivateKey>, Vec<Ed25519PublicKey>) {
+        let mut rng = StdRng::from_seed([0; 32]);
+        let private_keys: Vec<_> = (0..num_keys)
+            .map(|_| Ed25519PrivateKey::generate(&mut rng))
+            .collect();
+        let public_keys = private_keys.iter().map(Ed25519PublicKey::from).collect();
+        (private_keys, public_keys)
+    }
+
+    fn raw_transaction(account: &mut MultiEd25519Account) -> RawTransaction {
+        account
+            .build_transaction(TransactionFactory::new(ChainId::test()).create_recovery_address())
+    }
+
+    #[test]
+    fn test_coordinator() {
+        let (private_keys, public_keys) = keys(3);
+        let mut account = MultiEd25519Account::new(public_keys, 2, 0).unwrap();
+        let raw_txn = raw_transaction(&mut account);
+        assert_eq!(raw_txn.sender(), account.address());
+        assert_eq!(account.sequence_number(), 1);
+
+        let mut coordinator = MultiEd25519Coordinator::new(raw_txn, account.public_key().clone());
+        // Partial signatures are serialized to be sent to the coordinator
+        let partial_signatures: Vec<PartialSignature> = private_keys[1..]
+            .iter()
+            .map(|private_key| {
+                let partial_signature = PartialSignature::sign(
+                    coordinator.raw_transaction(),
+                    account.public_key(),
+                    private_key,
+                )
+                .unwrap();
+                bcs::from_bytes(&bcs::to_bytes(&partial_signature).unwrap()).unwrap()
+            })
+            .collect();
+
+        coordinator.add(partial_signatures[0].clone()).unwrap();
+        // The same partial signature is only counted once
+        coordinator.add(partial_signatures[0].clone()).unwrap();
+        assert!(!coordinator.is_complete());
+        assert!(coordinator.clone().combine().is_err());
+
+        coordinator.add(partial_signatures[1].clone()).unwrap();
+        assert!(coordinator.is_complete());
+        let signed_txn = coordinator.combine().unwrap();
+        assert_eq!(signed_txn.sender(), account.address());
+        signed_txn.check_signature().unwrap();
+    }
+
+    #[test]
+    fn test_invalid_partial_signatures() {
+        let (private_keys, public_keys) = keys(3);
+        let mut account = MultiEd25519Account::new(public_keys, 2, 0).unwrap();
+        let raw_txn = raw_transaction(&mut account);
+        let other_raw_txn = raw_transaction(&mut account);
+        let mut coordinator = MultiEd25519Coordinator::new(raw_txn, account.public_key().clone());
+
+        // A signature of another transaction
+        let partial_signature =
+            PartialSignature::sign(&other_raw_txn, account.public_key(), &private_keys[0]).unwrap();
+        assert!(coordinator.add(partial_signature.clone()).is_err());
+
+        // A signature attributed to another key
+        let mut misattributed = PartialSignature::sign(
+            coordinator.raw_transaction(),
+            account.public_key(),
+            &private_keys[0],
+        )
+        .unwrap();
+        misattributed.index = 1;
+        assert!(coordinator.add(misattributed.clone()).is_err());
+        misattributed.index = 3;
+        assert!(coordinator.add(misattributed).is_err());
+
+        // A key that isn't part of the account
+        let mut rng = StdRng::from_seed([1; 32]);
+        let other_private_key = Ed25519PrivateKey::generate(&mut rng);
+        assert!(PartialSignature::sign(
+            coordinator.raw_transaction(),
+            account.public_key(),
+            &other_private_key
+        )
+        .is_err());
+        assert_eq!(coordinator.num_signatures(), 0);
+    }
+
+    #[test]
+    fn test_sign_transaction() {
+        let (private_keys, public_keys) = keys(3);
+        let mut account = MultiEd25519Account::new(public_keys, 3, 0).unwrap();
+        let raw_txn = raw_transaction(&mut account);
+        let private_keys: Vec<_> = private_keys.iter().collect();
+
+        assert!(account
+            .sign_transaction(raw_txn.clone(), &private_keys[..2])
+            .is_err());
+        account
+            .sign_transaction(raw_txn, &private_keys)
+            .unwrap()
+            .check_signature()
+            .unwrap();
+
+        assert!(MultiEd25519Account::new(vec![], 1, 0).is_err());
+    }
+}
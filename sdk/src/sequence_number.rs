@@ -0,0 +1,172 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sequence numbers of an account sending many transactions at once, e.g. from several tasks.
+//! An [`AccountSequenceNumberManager`] hands out sequence numbers without waiting for the previous
+//! transactions to commit. A transaction which can't commit anymore, because it was rejected or
+//! it expired, leaves a gap blocking all the transactions after it; the manager detects it when
+//! re-synchronized with the sequence number of the account on chain, and allocates the
+//! sequence numbers from the gap again.
+
+use std::{
+    cmp::max,
+    collections::BTreeMap,
+    mem,
+    sync::{Mutex, MutexGuard},
+};
+
+#[derive(Debug)]
+struct Inner {
+    /// The next sequence number to allocate.
+    next: u64,
+    /// The highest sequence number of the account seen on chain.
+    committed: u64,
+    /// The expiration timestamps of the transactions allocated but not seen committed, by
+    /// sequence number.
+    pending: BTreeMap<u64, u64>,
+}
+
+/// Allocates the sequence numbers of an account for concurrent submissions. It can be shared by
+/// the tasks sending the transactions, e.g. in an `Arc`.
+#[derive(Debug)]
+pub struct AccountSequenceNumberManager {
+    inner: Mutex<Inner>,
+    /// Maximum number of pending transactions, beyond which mempool rejects the transactions of
+    /// the account anyway.
+    max_pending: usize,
+}
+
+impl AccountSequenceNumberManager {
+    /// Creates the manager of an account whose sequence number on chain is
+    /// `on_chain_sequence_number`.
+    pub fn new(on_chain_sequence_number: u64, max_pending: usize) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                next: on_chain_sequence_number,
+                committed: on_chain_sequence_number,
+                pending: BTreeMap::new(),
+            }),
+            max_pending,
+        }
+    }
+
+    fn inner(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().expect("Lock poisoned.")
+    }
+
+    /// Allocates the sequence number of a transaction expiring at `expiration_timestamp_secs`.
+    /// Returns `None` if there are `max_pending` pending transactions already, the caller
+    /// should `sync` before trying again.
+    pub fn allocate(&self, expiration_timestamp_secs: u64) -> Option<u64> {
+        let mut inner = self.inner();
+        if inner.pending.len() >= self.max_pending {
+            return None;
+        }
+        let sequence_number = inner.next;
+        inner.next += 1;
+        inner
+            .pending
+            .insert(sequence_number, expiration_timestamp_secs);
+        Some(sequence_number)
+    }
+
+    /// Records that the submission of the transaction with the sequence number failed. The
+    /// transactions allocated after it can't commit either, their sequence numbers are
+    /// returned, including the failed one, for the caller to sign and submit them again with
+    /// newly allocated sequence numbers.
+    pub fn fail(&self, sequence_number: u64) -> Vec<u64> {
+        let mut inner = self.inner();
+        if !inner.pending.contains_key(&sequence_number) {
+            return Vec::new();
+        }
+        let lost = inner.pending.split_off(&sequence_number);
+        inner.next = sequence_number;
+        lost.into_keys().collect()
+    }
+
+    /// Re-synchronizes with the sequence number of the account on chain, read at the ledger
+    /// timestamp `ledger_timestamp_secs`. The transactions below it are committed. If the
+    /// transaction the account waits for expired, none of the pending transactions can commit:
+    /// their sequence numbers are returned and allocated again, starting from the one on chain.
+    pub fn sync(&self, on_chain_sequence_number: u64, ledger_timestamp_secs: u64) -> Vec<u64> {
+        let mut inner = self.inner();
+        // Reads from a lagging node don't roll the account back.
+        let committed = max(inner.committed, on_chain_sequence_number);
+        inner.committed = committed;
+        inner.pending = inner.pending.split_off(&committed);
+
+        let stuck = match inner.pending.get(&committed) {
+            Some(expiration_timestamp_secs) => *expiration_timestamp_secs <= ledger_timestamp_secs,
+            None => true,
+        };
+        if !stuck {
+            return Vec::new();
+        }
+        inner.next = committed;
+        mem::take(&mut inner.pending).into_keys().collect()
+    }
+
+    /// The number of transactions allocated but not seen committed.
+    pub fn num_pending(&self) -> usize {
+        self.inner().pending.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocate_and_commit() {
+        let manager = AccountSequenceNumberManager::new(5, 3);
+        assert_eq!(manager.allocate(100), Some(5));
+        assert_eq!(manager.allocate(100), Some(6));
+        assert_eq!(manager.allocate(100), Some(7));
+        assert_eq!(manager.allocate(100), None);
+
+        assert!(manager.sync(7, 10).is_empty());
+        assert_eq!(manager.num_pending(), 1);
+        assert_eq!(manager.allocate(100), Some(8));
+
+        // a lagging read changes nothing
+        assert!(manager.sync(6, 10).is_empty());
+        assert_eq!(manager.num_pending(), 2);
+
+        assert!(manager.sync(9, 10).is_empty());
+        assert_eq!(manager.num_pending(), 0);
+        assert_eq!(manager.allocate(100), Some(9));
+    }
+
+    #[test]
+    fn test_failed_submission() {
+        let manager = AccountSequenceNumberManager::new(0, 10);
+        for _ in 0..4 {
+            manager.allocate(100).unwrap();
+        }
+        assert_eq!(manager.fail(2), vec![2, 3]);
+        assert!(manager.fail(3).is_empty());
+        assert_eq!(manager.allocate(100), Some(2));
+        assert_eq!(manager.num_pending(), 3);
+    }
+
+    #[test]
+    fn test_expired_transaction() {
+        let manager = AccountSequenceNumberManager::new(0, 10);
+        manager.allocate(10).unwrap();
+        manager.allocate(100).unwrap();
+        manager.allocate(100).unwrap();
+
+        assert!(manager.sync(0, 9).is_empty());
+        assert_eq!(manager.sync(0, 10), vec![0, 1, 2]);
+        assert_eq!(manager.num_pending(), 0);
+        assert_eq!(manager.allocate(100), Some(0));
+    }
+
+    #[test]
+    fn test_external_transactions() {
+        // transactions sent by someone else move the allocation forward
+        let manager = AccountSequenceNumberManager::new(0, 10);
+        assert!(manager.sync(3, 10).is_empty());
+        assert_eq!(manager.allocate(100), Some(3));
+    }
+}
@@ -6,6 +6,7 @@ use crate::{
         ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
         traits::Uniform,
     },
+    derivation::{self, DerivationPath},
     transaction_builder::TransactionBuilder,
     types::{
         account_address::AccountAddress,
@@ -13,6 +14,9 @@ use crate::{
     },
 };
 
+use anyhow::Result;
+use std::str::FromStr;
+
 pub use aptos_types::*;
 
 #[derive(Debug)]
@@ -44,6 +48,21 @@ impl LocalAccount {
         Self::new(address, key, 0)
     }
 
+    /// Recovers the account of a BIP-39 mnemonic phrase at a derivation path, such as
+    /// `m/44'/637'/0'/0'/0'`, as wallets following the standard do.
+    pub fn from_derive_path(
+        derive_path: &str,
+        mnemonic_phrase: &str,
+        sequence_number: u64,
+    ) -> Result<Self> {
+        let derive_path = DerivationPath::from_str(derive_path)?;
+        let seed = derivation::mnemonic_to_seed(mnemonic_phrase, "")?;
+        let key = AccountKey::from_private_key(derivation::derive_private_key(&seed, &derive_path));
+        let address = key.authentication_key().derived_address();
+
+        Ok(Self::new(address, key, sequence_number))
+    }
+
     pub fn sign_transaction(&self, txn: RawTransaction) -> SignedTransaction {
         txn.sign(self.private_key(), self.public_key().clone())
             .expect("Signing a txn can't fail")
@@ -164,3 +183,25 @@ impl From<Ed25519PrivateKey> for AccountKey {
         Self::from_private_key(private_key)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_derive_path() {
+        let mnemonic_phrase =
+            "shoot island position soft burden budget tooth cruel issue economy destroy above";
+        let account =
+            LocalAccount::from_derive_path("m/44'/637'/0'/0'/0'", mnemonic_phrase, 0).unwrap();
+        assert_eq!(
+            account.private_key().to_bytes().to_vec(),
+            hex::decode("5d996aa76b3212142792d9130796cd2e11e3c445a93118c08414df4f66bc60ec")
+                .unwrap()
+        );
+        assert_eq!(account.sequence_number(), 0);
+
+        assert!(LocalAccount::from_derive_path("m/44'/637'/0'/0/0", mnemonic_phrase, 0).is_err());
+        assert!(LocalAccount::from_derive_path("m/44'/637'/0'/0'/0'", "shoot island", 0).is_err());
+    }
+}
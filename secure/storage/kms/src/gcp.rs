@@ -0,0 +1,373 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A client of Google Cloud KMS, using its REST API. Learn more here:
+//! https://cloud.google.com/kms/docs/reference/rest
+
+use crate::{decode_public_key, request, send_with_retries, uri_encode, Error};
+use aptos_crypto::ed25519::{Ed25519PublicKey, Ed25519Signature};
+use aptos_infallible::Mutex;
+use chrono::DateTime;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use std::{
+    convert::TryFrom,
+    thread,
+    time::{Duration, Instant},
+};
+
+const ENDPOINT: &str = "https://cloudkms.googleapis.com/v1";
+/// The endpoint of the metadata server of Compute Engine and GKE returning the access tokens of
+/// the service account of the instance
+const METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+/// Access tokens are refreshed this long before they expire
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+/// The max time to wait for the generation of a key version
+const GENERATION_TIMEOUT: Duration = Duration::from_secs(30);
+const GENERATION_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A version of a key, holding a key pair
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CryptoKeyVersion {
+    /// The resource name of the version, e.g.
+    /// `projects/*/locations/*/keyRings/*/cryptoKeys/*/cryptoKeyVersions/1`
+    pub name: String,
+    /// e.g. `ENABLED`, `PENDING_GENERATION` or `DESTROY_SCHEDULED`
+    pub state: String,
+    /// The RFC 3339 creation time of the version
+    pub create_time: String,
+}
+
+impl CryptoKeyVersion {
+    /// The number of the version, versions being numbered from 1 in the order of their creation
+    pub fn number(&self) -> Result<u64, Error> {
+        self.name
+            .rsplit('/')
+            .next()
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(|| Error::SerializationError(format!("Invalid version {}", self.name)))
+    }
+
+    /// The creation time of the version, in seconds since the Unix epoch
+    pub fn create_time_secs(&self) -> Result<u64, Error> {
+        Ok(DateTime::parse_from_rfc3339(&self.create_time)?.timestamp() as u64)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.state == "ENABLED"
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListCryptoKeyVersionsResponse {
+    #[serde(default)]
+    crypto_key_versions: Vec<CryptoKeyVersion>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PublicKeyResponse {
+    pem: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AsymmetricSignResponse {
+    signature: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+enum Credentials {
+    /// An access token given by the operator, e.g. from `gcloud auth print-access-token`
+    Token(String),
+    /// The tokens of the service account of the instance, with the expiry of the cached one
+    Metadata(Mutex<Option<(String, Instant)>>),
+}
+
+/// Client provides a client around the key rings of Google Cloud KMS, restricted to Ed25519
+/// signing keys. Requests are authenticated with an OAuth 2.0 access token, either given or
+/// fetched from the metadata server when running on Google Cloud.
+///
+/// Keys are referred to by their id in the key ring. Every key holds versions of its key pair.
+pub struct Client {
+    credentials: Credentials,
+    key_ring: String,
+    protection_level: String,
+}
+
+impl Client {
+    /// Creates a client of the key ring `projects/<project>/locations/<location>/keyRings/<key_ring>`
+    /// whose keys are created with the protection level, e.g. `SOFTWARE` or `HSM`. Without a
+    /// token, tokens are fetched from the metadata server.
+    pub fn new(
+        project: &str,
+        location: &str,
+        key_ring: &str,
+        protection_level: String,
+        token: Option<String>,
+    ) -> Self {
+        let credentials = match token {
+            Some(token) => Credentials::Token(token),
+            None => Credentials::Metadata(Mutex::new(None)),
+        };
+        Self {
+            credentials,
+            key_ring: format!(
+                "projects/{}/locations/{}/keyRings/{}",
+                project, location, key_ring
+            ),
+            protection_level,
+        }
+    }
+
+    /// Checks that the key ring exists and that the credentials grant access to it
+    pub fn get_key_ring(&self) -> Result<(), Error> {
+        self.call::<Value>("GET", &self.key_ring, None)?;
+        Ok(())
+    }
+
+    /// Checks that the key exists
+    pub fn get_crypto_key(&self, key_id: &str) -> Result<(), Error> {
+        self.call::<Value>("GET", &self.crypto_key(key_id), None)?;
+        Ok(())
+    }
+
+    /// Create an Ed25519 signing key, without any version
+    pub fn create_crypto_key(&self, key_id: &str) -> Result<(), Error> {
+        let path = format!(
+            "{}/cryptoKeys?cryptoKeyId={}&skipInitialVersionCreation=true",
+            self.key_ring,
+            uri_encode(key_id)
+        );
+        let body = json!({
+            "purpose": "ASYMMETRIC_SIGN",
+            "versionTemplate": {
+                "algorithm": "EC_SIGN_ED25519",
+                "protectionLevel": self.protection_level,
+            },
+        });
+        self.call::<Value>("POST", &path, Some(&body))?;
+        Ok(())
+    }
+
+    /// Retrieve all the versions of a key, including the destroyed ones
+    pub fn list_crypto_key_versions(&self, key_id: &str) -> Result<Vec<CryptoKeyVersion>, Error> {
+        let mut versions = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut path = format!("{}/cryptoKeyVersions", self.crypto_key(key_id));
+            if let Some(page_token) = page_token.take() {
+                path = format!("{}?pageToken={}", path, uri_encode(&page_token));
+            }
+            let resp: ListCryptoKeyVersionsResponse = self.call("GET", &path, None)?;
+            versions.extend(resp.crypto_key_versions);
+            match resp.next_page_token {
+                Some(next_page_token) if !next_page_token.is_empty() => {
+                    page_token = Some(next_page_token)
+                }
+                _ => return Ok(versions),
+            }
+        }
+    }
+
+    /// Create a new version of a key and wait for the generation of its key pair
+    pub fn create_crypto_key_version(&self, key_id: &str) -> Result<CryptoKeyVersion, Error> {
+        let path = format!("{}/cryptoKeyVersions", self.crypto_key(key_id));
+        let version: CryptoKeyVersion = self.call("POST", &path, Some(&json!({})))?;
+        self.wait_until_generated(version)
+    }
+
+    /// Schedule the destruction of the key pair of a version
+    pub fn destroy_crypto_key_version(&self, version: &str) -> Result<(), Error> {
+        self.call::<Value>("POST", &format!("{}:destroy", version), Some(&json!({})))?;
+        Ok(())
+    }
+
+    /// Retrieve the public key of a version
+    pub fn get_public_key(&self, version: &str) -> Result<Ed25519PublicKey, Error> {
+        let resp: PublicKeyResponse = self.call("GET", &format!("{}/publicKey", version), None)?;
+        decode_pem(&resp.pem)
+    }
+
+    /// Sign a message with a version, using pure Ed25519 over the message itself
+    pub fn asymmetric_sign(
+        &self,
+        version: &str,
+        message: &[u8],
+    ) -> Result<Ed25519Signature, Error> {
+        let body = json!({ "data": base64::encode(message) });
+        let resp: AsymmetricSignResponse =
+            self.call("POST", &format!("{}:asymmetricSign", version), Some(&body))?;
+        Ok(Ed25519Signature::try_from(
+            base64::decode(&resp.signature)?.as_slice(),
+        )?)
+    }
+
+    fn crypto_key(&self, key_id: &str) -> String {
+        format!("{}/cryptoKeys/{}", self.key_ring, key_id)
+    }
+
+    fn wait_until_generated(
+        &self,
+        mut version: CryptoKeyVersion,
+    ) -> Result<CryptoKeyVersion, Error> {
+        let start = Instant::now();
+        while version.state == "PENDING_GENERATION" {
+            if start.elapsed() > GENERATION_TIMEOUT {
+                return Err(Error::InternalError(format!(
+                    "Timed out waiting for the generation of {}",
+                    version.name
+                )));
+            }
+            thread::sleep(GENERATION_POLL_INTERVAL);
+            version = self.call("GET", &version.name, None)?;
+        }
+        if version.is_enabled() {
+            Ok(version)
+        } else {
+            Err(Error::InternalError(format!(
+                "Generation of {} failed: {}",
+                version.name, version.state
+            )))
+        }
+    }
+
+    /// Sends a request on a resource, retrying transient errors, and returns the response
+    fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<T, Error> {
+        let url = format!("{}/{}", ENDPOINT, path);
+        let authorization = format!("Bearer {}", self.access_token()?);
+        let resp = send_with_retries(|| {
+            let mut request = request(method, &url);
+            request.set("Authorization", &authorization);
+            match body {
+                Some(body) => request.send_json(body.clone()),
+                None => request.call(),
+            }
+        });
+        match resp.status() {
+            200 => Ok(serde_json::from_str(&resp.into_string()?)?),
+            404 => Err(Error::NotFound(path.into())),
+            _ => Err(resp.into()),
+        }
+    }
+
+    fn access_token(&self) -> Result<String, Error> {
+        let cache = match &self.credentials {
+            Credentials::Token(token) => return Ok(token.clone()),
+            Credentials::Metadata(cache) => cache,
+        };
+        let mut cache = cache.lock();
+        if let Some((token, expiry)) = &*cache {
+            if Instant::now() < *expiry {
+                return Ok(token.clone());
+            }
+        }
+
+        let resp = send_with_retries(|| {
+            let mut request = request("GET", METADATA_TOKEN_URL);
+            request.set("Metadata-Flavor", "Google").call()
+        });
+        if resp.status() != 200 {
+            return Err(resp.into());
+        }
+        let resp: TokenResponse = serde_json::from_str(&resp.into_string()?)?;
+        let expiry = Instant::now()
+            + Duration::from_secs(resp.expires_in).saturating_sub(TOKEN_EXPIRY_MARGIN);
+        *cache = Some((resp.access_token.clone(), expiry));
+        Ok(resp.access_token)
+    }
+}
+
+/// Decodes an Ed25519 public key from the PEM encoding of its SubjectPublicKeyInfo
+fn decode_pem(pem: &str) -> Result<Ed25519PublicKey, Error> {
+    let der: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    decode_public_key(&base64::decode(der)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::Signature;
+
+    const PROJECT: &str = "PROJECT";
+    const LOCATION: &str = "LOCATION";
+    const KEY_RING: &str = "KEY_RING";
+    const TOKEN: &str = "TOKEN";
+
+    #[test]
+    fn test_decode_pem() {
+        // The public key of the test vector 1 of RFC 8032
+        let pem = "-----BEGIN PUBLIC KEY-----\n\
+                   MCowBQYDK2VwAyEA11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo=\n\
+                   -----END PUBLIC KEY-----\n";
+        assert_eq!(
+            hex::encode(decode_pem(pem).unwrap().to_bytes()),
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+        );
+    }
+
+    #[test]
+    fn test_version() {
+        let version = CryptoKeyVersion {
+            name: "projects/p/locations/l/keyRings/r/cryptoKeys/k/cryptoKeyVersions/12".into(),
+            state: "ENABLED".into(),
+            create_time: "2022-03-01T12:00:00.123456789Z".into(),
+        };
+        assert_eq!(version.number().unwrap(), 12);
+        assert_eq!(version.create_time_secs().unwrap(), 1_646_136_000);
+        assert!(version.is_enabled());
+    }
+
+    // This test depends on the developer providing a key ring and a token that can create, use and
+    // destroy keys in it. Due to the nature of this test, they cannot be provided in this framework
+    // and the test is labeled as ignored.
+    #[ignore]
+    #[test]
+    fn test_keys() {
+        let kms = Client::new(
+            PROJECT,
+            LOCATION,
+            KEY_RING,
+            "SOFTWARE".into(),
+            Some(TOKEN.into()),
+        );
+        kms.get_key_ring().unwrap();
+
+        // Keys can't be deleted, only their versions
+        let key_id = format!("aptos-kms-client-test-{}", chrono::Utc::now().timestamp());
+        kms.get_crypto_key(&key_id).unwrap_err();
+        kms.create_crypto_key(&key_id).unwrap();
+        kms.get_crypto_key(&key_id).unwrap();
+
+        let version = kms.create_crypto_key_version(&key_id).unwrap();
+        assert_eq!(version.number().unwrap(), 1);
+        let versions = kms.list_crypto_key_versions(&key_id).unwrap();
+        assert_eq!(versions.len(), 1);
+
+        let public_key = kms.get_public_key(&version.name).unwrap();
+        let message = b"message";
+        let signature = kms.asymmetric_sign(&version.name, message).unwrap();
+        signature
+            .verify_arbitrary_msg(message, &public_key)
+            .unwrap();
+
+        for version in versions {
+            kms.destroy_crypto_key_version(&version.name).unwrap();
+        }
+    }
+}
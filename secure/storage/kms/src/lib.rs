@@ -0,0 +1,211 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Clients of the key management services of AWS and Google Cloud, restricted to the Ed25519
+//! signing keys that validators use. The private keys are generated by the services and never
+//! leave them: the clients only read the public keys and request signatures.
+
+pub mod aws;
+pub mod gcp;
+
+use aptos_crypto::ed25519::Ed25519PublicKey;
+use proxy::Proxy;
+use std::{convert::TryFrom, thread, time::Duration};
+use thiserror::Error;
+
+/// Request timeout for KMS operations
+const TIMEOUT: u64 = 10_000;
+/// The max number of times a request failing with a transient error is retried
+const MAX_RETRIES: u32 = 3;
+/// The wait before the first retry of a request, doubled for every following retry
+const INITIAL_BACKOFF_MS: u64 = 100;
+/// The DER encoding of the SubjectPublicKeyInfo of an Ed25519 key, up to the key itself.
+const ED25519_SPKI_PREFIX: &[u8] = &[
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("Http error, status code: {0}, status text: {1}, body: {2}")]
+    HttpError(u16, String, String),
+    #[error("Internal error: {0}")]
+    InternalError(String),
+    #[error("404: Not Found: {0}")]
+    NotFound(String),
+    #[error("Serialization error: {0}")]
+    SerializationError(String),
+}
+
+impl From<base64::DecodeError> for Error {
+    fn from(error: base64::DecodeError) -> Self {
+        Self::SerializationError(format!("{}", error))
+    }
+}
+
+impl From<aptos_crypto::traits::CryptoMaterialError> for Error {
+    fn from(error: aptos_crypto::traits::CryptoMaterialError) -> Self {
+        Self::SerializationError(format!("{}", error))
+    }
+}
+
+impl From<chrono::ParseError> for Error {
+    fn from(error: chrono::ParseError) -> Self {
+        Self::SerializationError(format!("{}", error))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Self::SerializationError(format!("{}", error))
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Self::SerializationError(format!("{}", error))
+    }
+}
+
+impl From<ureq::Response> for Error {
+    fn from(resp: ureq::Response) -> Self {
+        if let Some(e) = resp.synthetic_error() {
+            // Local error
+            Error::InternalError(e.to_string())
+        } else {
+            // Clear the buffer
+            let status = resp.status();
+            let status_text = resp.status_text().to_string();
+            match resp.into_string() {
+                Ok(body) => Error::HttpError(status, status_text, body),
+                Err(e) => Error::InternalError(e.to_string()),
+            }
+        }
+    }
+}
+
+/// Sends a request until it gets a response that isn't a transient error (a local error, a
+/// throttling or a server error) or it was retried `MAX_RETRIES` times, backing off
+/// exponentially. `send` builds the request again for every attempt, e.g. to sign it again.
+fn send_with_retries(send: impl Fn() -> ureq::Response) -> ureq::Response {
+    let mut backoff = Duration::from_millis(INITIAL_BACKOFF_MS);
+    let mut resp = send();
+    for _ in 0..MAX_RETRIES {
+        if !is_transient(&resp) {
+            break;
+        }
+        thread::sleep(backoff);
+        backoff *= 2;
+        resp = send();
+    }
+    resp
+}
+
+fn is_transient(resp: &ureq::Response) -> bool {
+    resp.synthetic() || resp.status() == 429 || resp.status() >= 500
+}
+
+/// Creates a request with the timeout and the proxy of the environment, if any
+fn request(method: &str, url: &str) -> ureq::Request {
+    let mut request = ureq::request(method, url);
+    request.timeout_connect(TIMEOUT);
+
+    let proxy = Proxy::new();
+    let host = host(url);
+    let proxy_url = if url.starts_with("https://") {
+        proxy.https(host)
+    } else {
+        proxy.http(host)
+    };
+    if let Some(proxy_url) = proxy_url {
+        request.set_proxy(ureq::Proxy::new(proxy_url).expect("Unable to parse proxy_url"));
+    }
+    request
+}
+
+fn host(url: &str) -> &str {
+    url.splitn(2, "://")
+        .last()
+        .unwrap_or_default()
+        .split('/')
+        .next()
+        .unwrap_or_default()
+}
+
+/// Decodes an Ed25519 public key from the DER encoding of its SubjectPublicKeyInfo, the format in
+/// which both services return public keys.
+fn decode_public_key(der: &[u8]) -> Result<Ed25519PublicKey, Error> {
+    match der.strip_prefix(ED25519_SPKI_PREFIX) {
+        Some(bytes) => Ok(Ed25519PublicKey::try_from(bytes)?),
+        None => Err(Error::SerializationError(
+            "Not the public key of an Ed25519 key".into(),
+        )),
+    }
+}
+
+/// Percent-encodes all the characters but the unreserved ones
+fn uri_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
+    use std::cell::Cell;
+
+    #[test]
+    fn test_retries() {
+        let attempts = Cell::new(0);
+        let resp = send_with_retries(|| {
+            attempts.set(attempts.get() + 1);
+            match attempts.get() {
+                1 => ureq::Response::new(503, "Service Unavailable", ""),
+                2 => ureq::Response::new(429, "Too Many Requests", ""),
+                _ => ureq::Response::new(200, "OK", ""),
+            }
+        });
+        assert_eq!(resp.status(), 200);
+        assert_eq!(attempts.get(), 3);
+
+        // Client errors aren't retried
+        attempts.set(0);
+        let resp = send_with_retries(|| {
+            attempts.set(attempts.get() + 1);
+            ureq::Response::new(400, "Bad Request", "")
+        });
+        assert_eq!(resp.status(), 400);
+        assert_eq!(attempts.get(), 1);
+
+        // Transient errors are retried a bounded number of times
+        attempts.set(0);
+        let resp = send_with_retries(|| {
+            attempts.set(attempts.get() + 1);
+            ureq::Response::new(500, "Internal Server Error", "")
+        });
+        assert_eq!(resp.status(), 500);
+        assert_eq!(attempts.get(), MAX_RETRIES + 1);
+    }
+
+    #[test]
+    fn test_decode_public_key() {
+        let public_key = Ed25519PrivateKey::generate_for_testing().public_key();
+        let mut der = ED25519_SPKI_PREFIX.to_vec();
+        der.extend_from_slice(&public_key.to_bytes());
+        assert_eq!(decode_public_key(&der).unwrap(), public_key);
+
+        // A P-256 key
+        der[8] = 0x71;
+        decode_public_key(&der).unwrap_err();
+    }
+}
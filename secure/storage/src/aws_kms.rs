@@ -0,0 +1,205 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{counters, CryptoStorage, Error, GetResponse, KVStorage, PublicKeyResponse};
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    hash::CryptoHash,
+    signing_message,
+};
+use aptos_kms_client::aws::Client;
+use serde::{de::DeserializeOwned, Serialize};
+
+const BACKEND: &str = "aws_kms";
+
+/// AwsKmsStorage keeps cryptographic keys in AWS KMS, so that validator keys are generated by
+/// KMS and never leave it: signing is done by KMS and private keys can't be exported. KMS holds
+/// no values, so this storage is meant for the keys of a validator, e.g. its owner and operator
+/// keys, and not for the other data of secure storage.
+///
+/// Every key is a KMS key referred to by the alias `alias/<name>`. When a key is rotated, a new
+/// KMS key is created and the alias `alias/<name>_previous` is moved to the current key. The
+/// deletion of the version before it is scheduled, so at most two versions of a key are usable.
+pub struct AwsKmsStorage {
+    client: Client,
+}
+
+impl AwsKmsStorage {
+    pub fn new(
+        region: String,
+        endpoint: Option<String>,
+        access_key_id: String,
+        secret_access_key: String,
+        session_token: Option<String>,
+    ) -> Self {
+        Self {
+            client: Client::new(
+                region,
+                endpoint,
+                access_key_id,
+                secret_access_key,
+                session_token,
+            ),
+        }
+    }
+
+    /// Returns the id of the KMS key the alias points to.
+    fn key_id(&self, alias: &str) -> Result<String, Error> {
+        Ok(self.client.describe_key(alias)?.key_id)
+    }
+
+    /// Returns the id of the KMS key of the version of the key `name` identified by `version`.
+    fn key_id_for_version(&self, name: &str, version: &Ed25519PublicKey) -> Result<String, Error> {
+        for alias in &[get_alias(name), get_alias(&get_previous_version_name(name))] {
+            let key_id = match self.key_id(alias) {
+                Ok(key_id) => key_id,
+                Err(Error::KeyNotSet(_)) => continue,
+                Err(e) => return Err(e),
+            };
+            if &self.client.get_public_key(&key_id)? == version {
+                return Ok(key_id);
+            }
+        }
+        Err(Error::KeyVersionNotFound(name.into(), version.to_string()))
+    }
+
+    fn sign_with<T: CryptoHash + Serialize>(
+        &self,
+        key_id: &str,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        Ok(self.client.sign(key_id, &signing_message(message))?)
+    }
+}
+
+impl KVStorage for AwsKmsStorage {
+    fn available(&self) -> Result<(), Error> {
+        let _timer = counters::start_kms_timer(BACKEND, "available");
+        self.client.list_keys(1)?;
+        Ok(())
+    }
+
+    fn get<T: DeserializeOwned>(&self, _key: &str) -> Result<GetResponse<T>, Error> {
+        Err(Error::InternalError(
+            "AWS KMS storage holds no values".into(),
+        ))
+    }
+
+    fn set<T: Serialize>(&mut self, _key: &str, _value: T) -> Result<(), Error> {
+        Err(Error::InternalError(
+            "AWS KMS storage holds no values".into(),
+        ))
+    }
+
+    /// KMS keys can only be scheduled for deletion, so tests use keys of their own instead.
+    #[cfg(any(test, feature = "testing"))]
+    fn reset_and_clear(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl CryptoStorage for AwsKmsStorage {
+    fn create_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let _timer = counters::start_kms_timer(BACKEND, "create_key");
+        let alias = get_alias(name);
+        match self.key_id(&alias) {
+            Ok(_) => return Err(Error::KeyAlreadyExists(name.into())),
+            Err(Error::KeyNotSet(_)) => (),
+            Err(e) => return Err(e),
+        }
+        let key_id = self.client.create_key(name)?.key_id;
+        self.client.create_alias(&alias, &key_id)?;
+        Ok(self.client.get_public_key(&key_id)?)
+    }
+
+    fn export_private_key(&self, _name: &str) -> Result<Ed25519PrivateKey, Error> {
+        Err(Error::PermissionDenied)
+    }
+
+    /// KMS only imports key material in the format of its own wrapping keys, which isn't
+    /// supported.
+    fn import_private_key(&mut self, _name: &str, _key: Ed25519PrivateKey) -> Result<(), Error> {
+        Err(Error::PermissionDenied)
+    }
+
+    fn export_private_key_for_version(
+        &self,
+        _name: &str,
+        _version: Ed25519PublicKey,
+    ) -> Result<Ed25519PrivateKey, Error> {
+        Err(Error::PermissionDenied)
+    }
+
+    fn get_public_key(&self, name: &str) -> Result<PublicKeyResponse, Error> {
+        let _timer = counters::start_kms_timer(BACKEND, "get_public_key");
+        let metadata = self.client.describe_key(&get_alias(name))?;
+        Ok(PublicKeyResponse {
+            last_update: metadata.creation_date as u64,
+            public_key: self.client.get_public_key(&metadata.key_id)?,
+        })
+    }
+
+    fn get_public_key_previous_version(&self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let _timer = counters::start_kms_timer(BACKEND, "get_public_key_previous_version");
+        match self.key_id(&get_alias(&get_previous_version_name(name))) {
+            Ok(key_id) => Ok(self.client.get_public_key(&key_id)?),
+            Err(Error::KeyNotSet(_)) => Err(Error::KeyVersionNotFound(
+                name.into(),
+                "previous version".into(),
+            )),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn rotate_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let _timer = counters::start_kms_timer(BACKEND, "rotate_key");
+        let alias = get_alias(name);
+        let current_key_id = self.key_id(&alias)?;
+        let key_id = self.client.create_key(name)?.key_id;
+
+        let previous_alias = get_alias(&get_previous_version_name(name));
+        match self.key_id(&previous_alias) {
+            Ok(previous_key_id) => {
+                self.client.update_alias(&previous_alias, &current_key_id)?;
+                self.client.schedule_key_deletion(&previous_key_id)?;
+            }
+            Err(Error::KeyNotSet(_)) => {
+                self.client.create_alias(&previous_alias, &current_key_id)?
+            }
+            Err(e) => return Err(e),
+        }
+
+        self.client.update_alias(&alias, &key_id)?;
+        Ok(self.client.get_public_key(&key_id)?)
+    }
+
+    fn sign<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        let _timer = counters::start_kms_timer(BACKEND, "sign");
+        self.sign_with(&get_alias(name), message)
+    }
+
+    fn sign_using_version<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        version: Ed25519PublicKey,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        let _timer = counters::start_kms_timer(BACKEND, "sign_using_version");
+        let key_id = self.key_id_for_version(name, &version)?;
+        self.sign_with(&key_id, message)
+    }
+}
+
+/// Private helper method to get the alias of the KMS key holding the given key.
+fn get_alias(name: &str) -> String {
+    format!("alias/{}", name)
+}
+
+/// Private helper method to get the name of the previous version of the given key.
+fn get_previous_version_name(name: &str) -> String {
+    format!("{}_previous", name)
+}
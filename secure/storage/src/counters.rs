@@ -0,0 +1,33 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_secure_push_metrics::{register_histogram_vec, HistogramTimer, HistogramVec};
+use once_cell::sync::Lazy;
+
+pub static PKCS11_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_secure_storage_pkcs11_latency",
+        "Time to perform an operation against the PKCS#11 token",
+        &["operation"]
+    )
+    .unwrap()
+});
+
+pub fn start_pkcs11_timer(operation: &str) -> HistogramTimer {
+    PKCS11_LATENCY.with_label_values(&[operation]).start_timer()
+}
+
+pub static KMS_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_secure_storage_kms_latency",
+        "Time to perform an operation against a cloud KMS, including retries",
+        &["backend", "operation"]
+    )
+    .unwrap()
+});
+
+pub fn start_kms_timer(backend: &str, operation: &str) -> HistogramTimer {
+    KMS_LATENCY
+        .with_label_values(&[backend, operation])
+        .start_timer()
+}
@@ -0,0 +1,293 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{from_base64, to_base64, CryptoKVStorage, Error, GetResponse, KVStorage};
+use aptos_temppath::TempPath;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{Aead, NewAead},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+use zeroize::Zeroize;
+
+const FORMAT_VERSION: u32 = 1;
+const KEY_LENGTH: usize = 32;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+// The memory cost of deriving new master keys. Tests use a low cost to run fast, the parameters of
+// existing files are read from them anyway.
+#[cfg(not(test))]
+const KDF_MEMORY_KIB: u32 = 64 * 1024;
+#[cfg(test)]
+const KDF_MEMORY_KIB: u32 = 1024;
+
+/// EncryptedOnDiskStorage is an OnDiskStorage whose file is encrypted with XChaCha20-Poly1305.
+/// The master key encrypting the file is derived from a passphrase with Argon2id, using a salt
+/// and parameters stored in clear in the file, so only the passphrase has to be kept secret.
+/// Like OnDiskStorage, it reads and writes all data to the file, and is intended for single
+/// threads (or must be wrapped by a Arc<RwLock<>>).
+pub struct EncryptedOnDiskStorage {
+    file_path: PathBuf,
+    temp_path: TempPath,
+    time_service: TimeService,
+    master_key: MasterKey,
+}
+
+impl EncryptedOnDiskStorage {
+    /// Opens the storage, creating its file if it doesn't exist. Fails with `PermissionDenied`
+    /// if the passphrase doesn't decrypt an existing file.
+    pub fn new(file_path: PathBuf, passphrase: &str) -> Result<Self, Error> {
+        Self::new_with_time_service(file_path, passphrase, TimeService::real())
+    }
+
+    fn new_with_time_service(
+        file_path: PathBuf,
+        passphrase: &str,
+        time_service: TimeService,
+    ) -> Result<Self, Error> {
+        // The parent will be one when only a filename is supplied. Therefore use the current
+        // working directory provided by PathBuf::new().
+        let file_dir = file_path
+            .parent()
+            .map_or(PathBuf::new(), |p| p.to_path_buf());
+        let temp_path = TempPath::new_with_temp_dir(file_dir);
+
+        match read_encrypted_file(&file_path)? {
+            Some(file) => {
+                let master_key = MasterKey::derive(passphrase, file.kdf.clone())?;
+                let storage = Self {
+                    file_path,
+                    temp_path,
+                    time_service,
+                    master_key,
+                };
+                // Check the passphrase before any write can overwrite the data
+                storage.decrypt(&file)?;
+                Ok(storage)
+            }
+            None => {
+                let storage = Self {
+                    file_path,
+                    temp_path,
+                    time_service,
+                    master_key: MasterKey::generate(passphrase)?,
+                };
+                storage.write(&HashMap::new())?;
+                Ok(storage)
+            }
+        }
+    }
+
+    /// Encrypts the file of an OnDiskStorage into a new EncryptedOnDiskStorage. The plaintext
+    /// file is left as is, for the operator to delete it once the migration is checked.
+    pub fn migrate_from_plaintext(
+        plaintext_path: &Path,
+        file_path: PathBuf,
+        passphrase: &str,
+    ) -> Result<Self, Error> {
+        if read_encrypted_file(&file_path)?.is_some() {
+            return Err(Error::InternalError(format!(
+                "Encrypted storage already exists: {:?}",
+                file_path
+            )));
+        }
+
+        let mut contents = String::new();
+        File::open(plaintext_path)?.read_to_string(&mut contents)?;
+        let data = if contents.is_empty() {
+            HashMap::new()
+        } else {
+            serde_json::from_str(&contents)?
+        };
+
+        let storage = Self::new(file_path, passphrase)?;
+        // The values are copied with their last update time
+        storage.write(&data)?;
+        Ok(storage)
+    }
+
+    /// Re-encrypts the file with a master key derived from the new passphrase, with a new salt.
+    pub fn rotate_master_key(&mut self, new_passphrase: &str) -> Result<(), Error> {
+        let data = self.read()?;
+        self.master_key = MasterKey::generate(new_passphrase)?;
+        self.write(&data)
+    }
+
+    fn read(&self) -> Result<HashMap<String, Value>, Error> {
+        let file = read_encrypted_file(&self.file_path)?
+            .ok_or_else(|| Error::InternalError("Encrypted storage is empty".into()))?;
+        if file.kdf != self.master_key.kdf {
+            return Err(Error::InternalError(
+                "The master key of the storage was rotated, the storage must be reopened".into(),
+            ));
+        }
+        let plaintext = self.decrypt(&file)?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    fn write(&self, data: &HashMap<String, Value>) -> Result<(), Error> {
+        let mut nonce = vec![0; NONCE_LENGTH];
+        OsRng.fill_bytes(&mut nonce);
+        let plaintext = serde_json::to_vec(data)?;
+        let ciphertext = self
+            .master_key
+            .cipher()
+            .encrypt(XNonce::from_slice(&nonce), plaintext.as_ref())
+            .map_err(|e| Error::InternalError(format!("Unable to encrypt storage: {}", e)))?;
+        let file = EncryptedFile {
+            version: FORMAT_VERSION,
+            kdf: self.master_key.kdf.clone(),
+            nonce,
+            ciphertext,
+        };
+
+        let contents = serde_json::to_vec(&file)?;
+        let mut temp_file = File::create(self.temp_path.path())?;
+        temp_file.write_all(&contents)?;
+        fs::rename(&self.temp_path, &self.file_path)?;
+        Ok(())
+    }
+
+    fn decrypt(&self, file: &EncryptedFile) -> Result<Vec<u8>, Error> {
+        self.master_key
+            .cipher()
+            .decrypt(XNonce::from_slice(&file.nonce), file.ciphertext.as_ref())
+            // Authentication fails on a wrong passphrase as on a tampered file
+            .map_err(|_| Error::PermissionDenied)
+    }
+}
+
+impl KVStorage for EncryptedOnDiskStorage {
+    fn available(&self) -> Result<(), Error> {
+        self.read().map(|_| ())
+    }
+
+    fn get<V: DeserializeOwned>(&self, key: &str) -> Result<GetResponse<V>, Error> {
+        let mut data = self.read()?;
+        data.remove(key)
+            .ok_or_else(|| Error::KeyNotSet(key.to_string()))
+            .and_then(|value| serde_json::from_value(value).map_err(|e| e.into()))
+    }
+
+    fn set<V: Serialize>(&mut self, key: &str, value: V) -> Result<(), Error> {
+        let now = self.time_service.now_secs();
+        let mut data = self.read()?;
+        data.insert(
+            key.to_string(),
+            serde_json::to_value(&GetResponse::new(value, now))?,
+        );
+        self.write(&data)
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    fn reset_and_clear(&mut self) -> Result<(), Error> {
+        self.write(&HashMap::new())
+    }
+}
+
+impl CryptoKVStorage for EncryptedOnDiskStorage {}
+
+#[derive(Deserialize, Serialize)]
+struct EncryptedFile {
+    version: u32,
+    kdf: KdfParams,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    nonce: Vec<u8>,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    ciphertext: Vec<u8>,
+}
+
+/// The Argon2id parameters deriving the master key from the passphrase
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct KdfParams {
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    salt: Vec<u8>,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl KdfParams {
+    fn generate() -> Self {
+        let mut salt = vec![0; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        Self {
+            salt,
+            memory_kib: KDF_MEMORY_KIB,
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
+}
+
+struct MasterKey {
+    key: [u8; KEY_LENGTH],
+    kdf: KdfParams,
+}
+
+impl MasterKey {
+    fn generate(passphrase: &str) -> Result<Self, Error> {
+        Self::derive(passphrase, KdfParams::generate())
+    }
+
+    fn derive(passphrase: &str, kdf: KdfParams) -> Result<Self, Error> {
+        let params = Params::new(
+            kdf.memory_kib,
+            kdf.iterations,
+            kdf.parallelism,
+            Some(KEY_LENGTH),
+        )
+        .map_err(|e| Error::InternalError(format!("Invalid key derivation parameters: {}", e)))?;
+        let mut key = [0; KEY_LENGTH];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &kdf.salt, &mut key)
+            .map_err(|e| Error::InternalError(format!("Unable to derive master key: {}", e)))?;
+        Ok(Self { key, kdf })
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(Key::from_slice(&self.key))
+    }
+}
+
+impl Drop for MasterKey {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+/// Returns None if the file doesn't exist or is empty
+fn read_encrypted_file(file_path: &Path) -> Result<Option<EncryptedFile>, Error> {
+    if !file_path.exists() {
+        return Ok(None);
+    }
+    let mut contents = String::new();
+    File::open(file_path)?.read_to_string(&mut contents)?;
+    if contents.is_empty() {
+        return Ok(None);
+    }
+    let file: EncryptedFile = serde_json::from_str(&contents)?;
+    if file.version != FORMAT_VERSION {
+        return Err(Error::SerializationError(format!(
+            "Unsupported encrypted storage version: {}",
+            file.version
+        )));
+    }
+    if file.nonce.len() != NONCE_LENGTH {
+        return Err(Error::SerializationError(format!(
+            "Invalid encrypted storage nonce length: {}",
+            file.nonce.len()
+        )));
+    }
+    Ok(Some(file))
+}
@@ -72,3 +72,41 @@ impl From<aptos_github_client::Error> for Error {
         }
     }
 }
+
+impl From<aptos_s3_client::Error> for Error {
+    fn from(error: aptos_s3_client::Error) -> Self {
+        match error {
+            aptos_s3_client::Error::NotFound(key) => Self::KeyNotSet(key),
+            aptos_s3_client::Error::HttpError(403, _, _) => Self::PermissionDenied,
+            _ => Self::InternalError(format!("{}", error)),
+        }
+    }
+}
+
+impl From<aptos_kms_client::Error> for Error {
+    fn from(error: aptos_kms_client::Error) -> Self {
+        match error {
+            aptos_kms_client::Error::NotFound(key) => Self::KeyNotSet(key),
+            aptos_kms_client::Error::HttpError(403, _, _) => Self::PermissionDenied,
+            // AWS KMS denies access with a 400 and the type of the error
+            aptos_kms_client::Error::HttpError(400, _, ref body)
+                if body.contains("AccessDeniedException") =>
+            {
+                Self::PermissionDenied
+            }
+            _ => Self::InternalError(format!("{}", error)),
+        }
+    }
+}
+
+impl From<cryptoki::error::Error> for Error {
+    fn from(error: cryptoki::error::Error) -> Self {
+        match error {
+            cryptoki::error::Error::Pkcs11(cryptoki::error::RvError::PinIncorrect)
+            | cryptoki::error::Error::Pkcs11(cryptoki::error::RvError::UserNotLoggedIn) => {
+                Self::PermissionDenied
+            }
+            _ => Self::InternalError(format!("{}", error)),
+        }
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{counters, CryptoStorage, Error, GetResponse, KVStorage, PublicKeyResponse};
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    hash::CryptoHash,
+    signing_message,
+};
+use aptos_kms_client::gcp::{Client, CryptoKeyVersion};
+use serde::{de::DeserializeOwned, Serialize};
+
+const BACKEND: &str = "gcp_kms";
+
+/// GcpKmsStorage keeps cryptographic keys in a key ring of Google Cloud KMS, so that validator
+/// keys are generated by KMS and never leave it: signing is done by KMS and private keys can't
+/// be exported. KMS holds no values, so this storage is meant for the keys of a validator, e.g.
+/// its owner and operator keys, and not for the other data of secure storage.
+///
+/// Every key is a KMS key whose id is the name of the key, with the slashes of namespaces
+/// replaced by hyphens, and whose versions are the versions of the key. When a key is rotated, a
+/// new version is created and the versions before the previous one are destroyed, so at most two
+/// versions of a key are usable.
+pub struct GcpKmsStorage {
+    client: Client,
+}
+
+impl GcpKmsStorage {
+    pub fn new(
+        project: &str,
+        location: &str,
+        key_ring: &str,
+        protection_level: String,
+        token: Option<String>,
+    ) -> Self {
+        Self {
+            client: Client::new(project, location, key_ring, protection_level, token),
+        }
+    }
+
+    /// Returns the enabled versions of the key `name`, from the latest to the oldest.
+    fn versions(&self, name: &str) -> Result<Vec<CryptoKeyVersion>, Error> {
+        let mut versions = match self.client.list_crypto_key_versions(&get_key_id(name)) {
+            Ok(versions) => versions,
+            Err(aptos_kms_client::Error::NotFound(_)) => return Err(Error::KeyNotSet(name.into())),
+            Err(e) => return Err(e.into()),
+        };
+        versions.retain(CryptoKeyVersion::is_enabled);
+        let mut numbered = versions
+            .into_iter()
+            .map(|version| Ok((version.number()?, version)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        numbered.sort_by(|(a, _), (b, _)| b.cmp(a));
+        if numbered.is_empty() {
+            return Err(Error::KeyNotSet(name.into()));
+        }
+        Ok(numbered.into_iter().map(|(_, version)| version).collect())
+    }
+
+    fn sign_with<T: CryptoHash + Serialize>(
+        &self,
+        version: &CryptoKeyVersion,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        Ok(self
+            .client
+            .asymmetric_sign(&version.name, &signing_message(message))?)
+    }
+}
+
+impl KVStorage for GcpKmsStorage {
+    fn available(&self) -> Result<(), Error> {
+        let _timer = counters::start_kms_timer(BACKEND, "available");
+        Ok(self.client.get_key_ring()?)
+    }
+
+    fn get<T: DeserializeOwned>(&self, _key: &str) -> Result<GetResponse<T>, Error> {
+        Err(Error::InternalError(
+            "Google Cloud KMS storage holds no values".into(),
+        ))
+    }
+
+    fn set<T: Serialize>(&mut self, _key: &str, _value: T) -> Result<(), Error> {
+        Err(Error::InternalError(
+            "Google Cloud KMS storage holds no values".into(),
+        ))
+    }
+
+    /// KMS keys can't be deleted, so tests use keys of their own instead.
+    #[cfg(any(test, feature = "testing"))]
+    fn reset_and_clear(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl CryptoStorage for GcpKmsStorage {
+    fn create_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let _timer = counters::start_kms_timer(BACKEND, "create_key");
+        let key_id = get_key_id(name);
+        match self.client.get_crypto_key(&key_id) {
+            Ok(()) => return Err(Error::KeyAlreadyExists(name.into())),
+            Err(aptos_kms_client::Error::NotFound(_)) => (),
+            Err(e) => return Err(e.into()),
+        }
+        self.client.create_crypto_key(&key_id)?;
+        let version = self.client.create_crypto_key_version(&key_id)?;
+        Ok(self.client.get_public_key(&version.name)?)
+    }
+
+    fn export_private_key(&self, _name: &str) -> Result<Ed25519PrivateKey, Error> {
+        Err(Error::PermissionDenied)
+    }
+
+    /// KMS only imports key material wrapped by its import jobs, which isn't supported.
+    fn import_private_key(&mut self, _name: &str, _key: Ed25519PrivateKey) -> Result<(), Error> {
+        Err(Error::PermissionDenied)
+    }
+
+    fn export_private_key_for_version(
+        &self,
+        _name: &str,
+        _version: Ed25519PublicKey,
+    ) -> Result<Ed25519PrivateKey, Error> {
+        Err(Error::PermissionDenied)
+    }
+
+    fn get_public_key(&self, name: &str) -> Result<PublicKeyResponse, Error> {
+        let _timer = counters::start_kms_timer(BACKEND, "get_public_key");
+        let version = &self.versions(name)?[0];
+        Ok(PublicKeyResponse {
+            last_update: version.create_time_secs()?,
+            public_key: self.client.get_public_key(&version.name)?,
+        })
+    }
+
+    fn get_public_key_previous_version(&self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let _timer = counters::start_kms_timer(BACKEND, "get_public_key_previous_version");
+        match self.versions(name)?.get(1) {
+            Some(version) => Ok(self.client.get_public_key(&version.name)?),
+            None => Err(Error::KeyVersionNotFound(
+                name.into(),
+                "previous version".into(),
+            )),
+        }
+    }
+
+    fn rotate_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let _timer = counters::start_kms_timer(BACKEND, "rotate_key");
+        let versions = self.versions(name)?;
+        let version = self.client.create_crypto_key_version(&get_key_id(name))?;
+        // The current version becomes the previous one
+        for version in versions.iter().skip(1) {
+            self.client.destroy_crypto_key_version(&version.name)?;
+        }
+        Ok(self.client.get_public_key(&version.name)?)
+    }
+
+    fn sign<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        let _timer = counters::start_kms_timer(BACKEND, "sign");
+        self.sign_with(&self.versions(name)?[0], message)
+    }
+
+    fn sign_using_version<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        version: Ed25519PublicKey,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        let _timer = counters::start_kms_timer(BACKEND, "sign_using_version");
+        for key_version in self.versions(name)?.iter().take(2) {
+            if self.client.get_public_key(&key_version.name)? == version {
+                return self.sign_with(key_version, message);
+            }
+        }
+        Err(Error::KeyVersionNotFound(name.into(), version.to_string()))
+    }
+}
+
+/// Private helper method to get the id of the KMS key holding the given key, as ids may only
+/// contain letters, digits, underscores and hyphens.
+fn get_key_id(name: &str) -> String {
+    name.replace('/', "-")
+}
@@ -3,30 +3,41 @@
 
 #![forbid(unsafe_code)]
 
+mod aws_kms;
+mod counters;
 mod crypto_kv_storage;
 mod crypto_storage;
+mod encrypted_on_disk;
 mod error;
+mod gcp_kms;
 mod github;
 mod in_memory;
 mod kv_storage;
 mod namespaced;
 mod on_disk;
+mod pkcs11;
 mod policy;
+mod s3;
 mod storage;
 mod vault;
 
 pub use crate::{
+    aws_kms::AwsKmsStorage,
     crypto_kv_storage::CryptoKVStorage,
     crypto_storage::{CryptoStorage, PublicKeyResponse},
+    encrypted_on_disk::EncryptedOnDiskStorage,
     error::Error,
+    gcp_kms::GcpKmsStorage,
     github::GitHubStorage,
     in_memory::InMemoryStorage,
     kv_storage::{GetResponse, KVStorage},
     namespaced::Namespaced,
     on_disk::OnDiskStorage,
+    pkcs11::Pkcs11Storage,
     policy::{Capability, Identity, Permission, Policy},
+    s3::S3Storage,
     storage::Storage,
-    vault::VaultStorage,
+    vault::{VaultKubernetesAuth, VaultStorage},
 };
 
 // Some common serializations for interacting with bytes these must be manually added to types via:
@@ -0,0 +1,401 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{counters, CryptoStorage, Error, GetResponse, KVStorage, PublicKeyResponse};
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature, ED25519_PUBLIC_KEY_LENGTH},
+    hash::CryptoHash,
+    signing_message, PrivateKey,
+};
+use aptos_infallible::Mutex;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    mechanism::Mechanism,
+    object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle},
+    session::{Session, UserType},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{convert::TryFrom, path::Path};
+
+/// The DER encoding of the Ed25519 curve OID (1.3.101.112), used as the key pairs' CKA_EC_PARAMS.
+const ED25519_EC_PARAMS: &[u8] = &[0x06, 0x03, 0x2b, 0x65, 0x70];
+/// The DER tag of an OCTET STRING, in which tokens wrap the Ed25519 public key's CKA_EC_POINT.
+const DER_OCTET_STRING_TAG: u8 = 0x04;
+
+/// Pkcs11Storage keeps cryptographic keys inside a PKCS#11 token (e.g., a YubiHSM or a network
+/// HSM), so that validator keys are generated in the token and never leave it: signing is done
+/// by the token and private keys can't be exported. Values are stored as data objects on the
+/// same token.
+///
+/// Every key pair is stored as a public and a private key object, both labelled with the key's
+/// name and sharing a CKA_ID set to their creation time. When a key is rotated, the new key pair
+/// is generated under a temporary label, then the version before the current one is destroyed,
+/// the current key pair is relabelled as the previous version and the new key pair takes the
+/// key's name, so at most two versions of a key are retained.
+pub struct Pkcs11Storage {
+    // The session is only usable for as long as the library is loaded
+    _context: Pkcs11,
+    session: Mutex<Session>,
+    time_service: TimeService,
+}
+
+impl Pkcs11Storage {
+    /// Loads the PKCS#11 library at `library` and logs into the token labelled `token_label`
+    /// as the normal user.
+    pub fn new(library: &Path, token_label: &str, pin: &str) -> Result<Self, Error> {
+        let context = Pkcs11::new(library)?;
+        context.initialize(CInitializeArgs::OsThreads)?;
+
+        let mut slot = None;
+        for candidate in context.get_slots_with_token()? {
+            if context.get_token_info(candidate)?.label().trim_end() == token_label {
+                slot = Some(candidate);
+                break;
+            }
+        }
+        let slot = slot.ok_or_else(|| {
+            Error::InternalError(format!("No PKCS#11 token labelled {}", token_label))
+        })?;
+
+        let session = context.open_session_no_callback(slot, true)?;
+        session.login(UserType::User, Some(pin))?;
+
+        Ok(Self {
+            _context: context,
+            session: Mutex::new(session),
+            time_service: TimeService::real(),
+        })
+    }
+
+    fn find_object(
+        session: &Session,
+        class: ObjectClass,
+        label: &str,
+    ) -> Result<Option<ObjectHandle>, Error> {
+        let template = [
+            Attribute::Class(class),
+            Attribute::Label(label.as_bytes().to_vec()),
+        ];
+        Ok(session.find_objects(&template)?.into_iter().next())
+    }
+
+    /// Returns the public and private key objects of the key pair labelled `label`.
+    fn find_key_pair(
+        session: &Session,
+        label: &str,
+    ) -> Result<(ObjectHandle, ObjectHandle), Error> {
+        let public_key = Self::find_object(session, ObjectClass::PUBLIC_KEY, label)?;
+        let private_key = Self::find_object(session, ObjectClass::PRIVATE_KEY, label)?;
+        match (public_key, private_key) {
+            (Some(public_key), Some(private_key)) => Ok((public_key, private_key)),
+            _ => Err(Error::KeyNotSet(label.into())),
+        }
+    }
+
+    /// Reads the Ed25519 public key held in a public key object, along with its creation time.
+    fn read_public_key(
+        session: &Session,
+        public_key: ObjectHandle,
+    ) -> Result<PublicKeyResponse, Error> {
+        let mut ec_point = None;
+        let mut last_update = 0;
+        for attribute in
+            session.get_attributes(public_key, &[AttributeType::EcPoint, AttributeType::Id])?
+        {
+            match attribute {
+                Attribute::EcPoint(bytes) => ec_point = Some(bytes),
+                // Keys created outside of this storage may use any id, in which case their
+                // creation time is unknown
+                Attribute::Id(id) => {
+                    if let Ok(bytes) = <[u8; 8]>::try_from(id.as_slice()) {
+                        last_update = u64::from_be_bytes(bytes);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let ec_point = ec_point
+            .ok_or_else(|| Error::InternalError("Public key has no CKA_EC_POINT".into()))?;
+        Ok(PublicKeyResponse {
+            last_update,
+            public_key: decode_ec_point(&ec_point)?,
+        })
+    }
+
+    fn key_pair_templates(&self, label: &str) -> (Vec<Attribute>, Vec<Attribute>) {
+        let label = label.as_bytes().to_vec();
+        let id = self.time_service.now_secs().to_be_bytes().to_vec();
+        let public_key = vec![
+            Attribute::Token(true),
+            Attribute::Private(false),
+            Attribute::Verify(true),
+            Attribute::EcParams(ED25519_EC_PARAMS.to_vec()),
+            Attribute::Label(label.clone()),
+            Attribute::Id(id.clone()),
+        ];
+        let private_key = vec![
+            Attribute::Token(true),
+            Attribute::Private(true),
+            Attribute::Sensitive(true),
+            Attribute::Extractable(false),
+            Attribute::Sign(true),
+            Attribute::Label(label),
+            Attribute::Id(id),
+        ];
+        (public_key, private_key)
+    }
+
+    fn generate_key_pair(&self, session: &Session, label: &str) -> Result<(), Error> {
+        let (public_key, private_key) = self.key_pair_templates(label);
+        session.generate_key_pair(&Mechanism::EccEdwardsKeyPairGen, &public_key, &private_key)?;
+        Ok(())
+    }
+
+    /// Destroys the key pair labelled `label`, if any.
+    fn destroy_key_pair(session: &Session, label: &str) -> Result<(), Error> {
+        match Self::find_key_pair(session, label) {
+            Ok((public_key, private_key)) => {
+                session.destroy_object(public_key)?;
+                session.destroy_object(private_key)?;
+                Ok(())
+            }
+            Err(Error::KeyNotSet(_)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn ensure_key_not_set(session: &Session, name: &str) -> Result<(), Error> {
+        match Self::find_key_pair(session, name) {
+            Ok(_) => Err(Error::KeyAlreadyExists(name.into())),
+            Err(Error::KeyNotSet(_)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the private key object of the version of the key `name` identified by `version`.
+    fn private_key_for_version(
+        session: &Session,
+        name: &str,
+        version: &Ed25519PublicKey,
+    ) -> Result<ObjectHandle, Error> {
+        for label in &[name.to_string(), get_previous_version_name(name)] {
+            let (public_key, private_key) = match Self::find_key_pair(session, label) {
+                Ok(key_pair) => key_pair,
+                Err(Error::KeyNotSet(_)) => continue,
+                Err(e) => return Err(e),
+            };
+            if &Self::read_public_key(session, public_key)?.public_key == version {
+                return Ok(private_key);
+            }
+        }
+        Err(Error::KeyVersionNotFound(name.into(), version.to_string()))
+    }
+
+    fn sign_with<T: CryptoHash + Serialize>(
+        session: &Session,
+        private_key: ObjectHandle,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        let signature = session.sign(&Mechanism::Eddsa, private_key, &signing_message(message))?;
+        Ed25519Signature::try_from(signature.as_slice())
+            .map_err(|e| Error::InternalError(e.to_string()))
+    }
+}
+
+impl KVStorage for Pkcs11Storage {
+    fn available(&self) -> Result<(), Error> {
+        let _timer = counters::start_pkcs11_timer("available");
+        self.session.lock().get_session_info()?;
+        Ok(())
+    }
+
+    fn get<T: DeserializeOwned>(&self, key: &str) -> Result<GetResponse<T>, Error> {
+        let _timer = counters::start_pkcs11_timer("get");
+        let session = self.session.lock();
+        let object = Self::find_object(&session, ObjectClass::DATA, key)?
+            .ok_or_else(|| Error::KeyNotSet(key.into()))?;
+        match session
+            .get_attributes(object, &[AttributeType::Value])?
+            .into_iter()
+            .next()
+        {
+            Some(Attribute::Value(value)) => Ok(serde_json::from_slice(&value)?),
+            _ => Err(Error::InternalError(format!("{} has no CKA_VALUE", key))),
+        }
+    }
+
+    fn set<T: Serialize>(&mut self, key: &str, value: T) -> Result<(), Error> {
+        let _timer = counters::start_pkcs11_timer("set");
+        let now = self.time_service.now_secs();
+        let value = serde_json::to_vec(&GetResponse::new(value, now))?;
+        let session = self.session.lock();
+        match Self::find_object(&session, ObjectClass::DATA, key)? {
+            Some(object) => session.update_attributes(object, &[Attribute::Value(value)])?,
+            None => {
+                session.create_object(&[
+                    Attribute::Class(ObjectClass::DATA),
+                    Attribute::Token(true),
+                    Attribute::Private(true),
+                    Attribute::Label(key.as_bytes().to_vec()),
+                    Attribute::Value(value),
+                ])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Destroys every object on the token, so tests must be run against a dedicated token.
+    #[cfg(any(test, feature = "testing"))]
+    fn reset_and_clear(&mut self) -> Result<(), Error> {
+        let session = self.session.lock();
+        for object in session.find_objects(&[])? {
+            session.destroy_object(object)?;
+        }
+        Ok(())
+    }
+}
+
+impl CryptoStorage for Pkcs11Storage {
+    fn create_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let _timer = counters::start_pkcs11_timer("create_key");
+        let session = self.session.lock();
+        Self::ensure_key_not_set(&session, name)?;
+        self.generate_key_pair(&session, name)?;
+        let (public_key, _) = Self::find_key_pair(&session, name)?;
+        Ok(Self::read_public_key(&session, public_key)?.public_key)
+    }
+
+    fn export_private_key(&self, _name: &str) -> Result<Ed25519PrivateKey, Error> {
+        Err(Error::PermissionDenied)
+    }
+
+    fn import_private_key(&mut self, name: &str, key: Ed25519PrivateKey) -> Result<(), Error> {
+        let _timer = counters::start_pkcs11_timer("import_private_key");
+        let session = self.session.lock();
+        Self::ensure_key_not_set(&session, name)?;
+
+        let (mut public_key, mut private_key) = self.key_pair_templates(name);
+        public_key.extend(vec![
+            Attribute::Class(ObjectClass::PUBLIC_KEY),
+            Attribute::KeyType(KeyType::EC_EDWARDS),
+            Attribute::EcPoint(encode_ec_point(&key.public_key())),
+        ]);
+        private_key.extend(vec![
+            Attribute::Class(ObjectClass::PRIVATE_KEY),
+            Attribute::KeyType(KeyType::EC_EDWARDS),
+            Attribute::EcParams(ED25519_EC_PARAMS.to_vec()),
+            Attribute::Value(key.to_bytes().to_vec()),
+        ]);
+        session.create_object(&public_key)?;
+        session.create_object(&private_key)?;
+        Ok(())
+    }
+
+    fn export_private_key_for_version(
+        &self,
+        _name: &str,
+        _version: Ed25519PublicKey,
+    ) -> Result<Ed25519PrivateKey, Error> {
+        Err(Error::PermissionDenied)
+    }
+
+    fn get_public_key(&self, name: &str) -> Result<PublicKeyResponse, Error> {
+        let _timer = counters::start_pkcs11_timer("get_public_key");
+        let session = self.session.lock();
+        let (public_key, _) = Self::find_key_pair(&session, name)?;
+        Self::read_public_key(&session, public_key)
+    }
+
+    fn get_public_key_previous_version(&self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let _timer = counters::start_pkcs11_timer("get_public_key_previous_version");
+        let session = self.session.lock();
+        match Self::find_key_pair(&session, &get_previous_version_name(name)) {
+            Ok((public_key, _)) => Ok(Self::read_public_key(&session, public_key)?.public_key),
+            Err(Error::KeyNotSet(_)) => Err(Error::KeyVersionNotFound(
+                name.into(),
+                "previous version".into(),
+            )),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn rotate_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let _timer = counters::start_pkcs11_timer("rotate_key");
+        let session = self.session.lock();
+        let (current_public_key, current_private_key) = Self::find_key_pair(&session, name)?;
+
+        // Generate the new key pair under a temporary label first, so that the current versions
+        // are untouched if the generation fails. A leftover of an interrupted rotation is dropped.
+        let pending_name = get_pending_version_name(name);
+        Self::destroy_key_pair(&session, &pending_name)?;
+        self.generate_key_pair(&session, &pending_name)?;
+        let (pending_public_key, pending_private_key) =
+            Self::find_key_pair(&session, &pending_name)?;
+
+        let previous_name = get_previous_version_name(name);
+        Self::destroy_key_pair(&session, &previous_name)?;
+
+        let previous_label = [Attribute::Label(previous_name.into_bytes())];
+        session.update_attributes(current_public_key, &previous_label)?;
+        session.update_attributes(current_private_key, &previous_label)?;
+
+        let label = [Attribute::Label(name.as_bytes().to_vec())];
+        session.update_attributes(pending_public_key, &label)?;
+        session.update_attributes(pending_private_key, &label)?;
+
+        Ok(Self::read_public_key(&session, pending_public_key)?.public_key)
+    }
+
+    fn sign<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        let _timer = counters::start_pkcs11_timer("sign");
+        let session = self.session.lock();
+        let (_, private_key) = Self::find_key_pair(&session, name)?;
+        Self::sign_with(&session, private_key, message)
+    }
+
+    fn sign_using_version<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        version: Ed25519PublicKey,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        let _timer = counters::start_pkcs11_timer("sign_using_version");
+        let session = self.session.lock();
+        let private_key = Self::private_key_for_version(&session, name, &version)?;
+        Self::sign_with(&session, private_key, message)
+    }
+}
+
+/// Tokens either return the raw public key as the CKA_EC_POINT of Edwards keys, or, as required
+/// by PKCS#11 v3.0, the public key wrapped in a DER OCTET STRING.
+fn decode_ec_point(ec_point: &[u8]) -> Result<Ed25519PublicKey, Error> {
+    let bytes = match ec_point {
+        [DER_OCTET_STRING_TAG, _, bytes @ ..] if bytes.len() == ED25519_PUBLIC_KEY_LENGTH => bytes,
+        bytes => bytes,
+    };
+    Ed25519PublicKey::try_from(bytes).map_err(|e| Error::SerializationError(e.to_string()))
+}
+
+fn encode_ec_point(public_key: &Ed25519PublicKey) -> Vec<u8> {
+    let mut ec_point = vec![DER_OCTET_STRING_TAG, ED25519_PUBLIC_KEY_LENGTH as u8];
+    ec_point.extend_from_slice(&public_key.to_bytes());
+    ec_point
+}
+
+/// Private helper method to get the name of the previous version of the given key pair, as held
+/// in the token.
+fn get_previous_version_name(name: &str) -> String {
+    format!("{}_previous", name)
+}
+
+/// Private helper method to get the name of the new version of the given key pair while it is
+/// being rotated, as held in the token.
+fn get_pending_version_name(name: &str) -> String {
+    format!("{}_pending", name)
+}
@@ -0,0 +1,56 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{CryptoKVStorage, Error, GetResponse, KVStorage};
+use aptos_s3_client::Client;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// S3Storage leverages an S3 bucket to provide a file system approach to key / value storage,
+/// each key being stored in its own object. Like GitHubStorage, this is not intended for storing
+/// private data but for organizing public data.
+pub struct S3Storage {
+    client: Client,
+    time_service: TimeService,
+}
+
+impl S3Storage {
+    pub fn new(
+        bucket: String,
+        region: String,
+        endpoint: Option<String>,
+        access_key_id: String,
+        secret_access_key: String,
+    ) -> Self {
+        Self {
+            client: Client::new(bucket, region, endpoint, access_key_id, secret_access_key),
+            time_service: TimeService::real(),
+        }
+    }
+}
+
+impl KVStorage for S3Storage {
+    fn available(&self) -> Result<(), Error> {
+        self.client.head_bucket().map_err(Into::into)
+    }
+
+    fn get<T: DeserializeOwned>(&self, key: &str) -> Result<GetResponse<T>, Error> {
+        let data = self.client.get_object(key)?;
+        serde_json::from_slice(&data).map_err(|e| e.into())
+    }
+
+    fn set<T: Serialize>(&mut self, key: &str, value: T) -> Result<(), Error> {
+        let now = self.time_service.now_secs();
+        let data = GetResponse::new(value, now);
+        let data = serde_json::to_vec(&data)?;
+        self.client.put_object(key, &data)?;
+        Ok(())
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    fn reset_and_clear(&mut self) -> Result<(), Error> {
+        self.client.delete_prefix("").map_err(|e| e.into())
+    }
+}
+
+impl CryptoKVStorage for S3Storage {}
@@ -1,8 +1,9 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
-    CryptoStorage, Error, GetResponse, GitHubStorage, InMemoryStorage, KVStorage, Namespaced,
-    OnDiskStorage, PublicKeyResponse, VaultStorage,
+    AwsKmsStorage, CryptoStorage, EncryptedOnDiskStorage, Error, GcpKmsStorage, GetResponse,
+    GitHubStorage, InMemoryStorage, KVStorage, Namespaced, OnDiskStorage, Pkcs11Storage,
+    PublicKeyResponse, S3Storage, VaultStorage,
 };
 use aptos_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature};
 use enum_dispatch::enum_dispatch;
@@ -16,11 +17,16 @@ use serde::{de::DeserializeOwned, Serialize};
 /// T: Storage. This boilerplate can be 100% generated by a proc macro.
 #[enum_dispatch(KVStorage, CryptoStorage)]
 pub enum Storage {
+    AwsKmsStorage(AwsKmsStorage),
+    EncryptedOnDiskStorage(EncryptedOnDiskStorage),
+    GcpKmsStorage(GcpKmsStorage),
     GitHubStorage(GitHubStorage),
     VaultStorage(VaultStorage),
     InMemoryStorage(InMemoryStorage),
     NamespacedStorage(Namespaced<Box<Storage>>),
     OnDiskStorage(OnDiskStorage),
+    Pkcs11Storage(Pkcs11Storage),
+    S3Storage(S3Storage),
 }
 
 impl KVStorage for Box<Storage> {
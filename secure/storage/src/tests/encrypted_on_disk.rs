@@ -0,0 +1,82 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{tests::suite, EncryptedOnDiskStorage, Error, KVStorage, OnDiskStorage, Storage};
+use aptos_temppath::TempPath;
+use std::fs;
+
+const PASSPHRASE: &str = "correct horse battery staple";
+
+#[test]
+fn encrypted_on_disk() {
+    let path_buf = TempPath::new().path().to_path_buf();
+    let mut storage = Storage::from(EncryptedOnDiskStorage::new(path_buf, PASSPHRASE).unwrap());
+    suite::execute_all_storage_tests(&mut storage);
+}
+
+#[test]
+fn encrypted_on_disk_is_encrypted() {
+    let path_buf = TempPath::new().path().to_path_buf();
+    let mut storage = EncryptedOnDiskStorage::new(path_buf.clone(), PASSPHRASE).unwrap();
+    storage.set("secret", "plaintext value").unwrap();
+
+    let contents = fs::read_to_string(&path_buf).unwrap();
+    assert!(!contents.contains("plaintext value"));
+    assert!(!contents.contains("secret"));
+
+    // The storage can be reopened with its passphrase only
+    let storage = EncryptedOnDiskStorage::new(path_buf.clone(), PASSPHRASE).unwrap();
+    assert_eq!(
+        storage.get::<String>("secret").unwrap().value,
+        "plaintext value"
+    );
+    assert_eq!(
+        EncryptedOnDiskStorage::new(path_buf, "wrong passphrase").err(),
+        Some(Error::PermissionDenied)
+    );
+}
+
+#[test]
+fn encrypted_on_disk_rotate_master_key() {
+    let path_buf = TempPath::new().path().to_path_buf();
+    let mut storage = EncryptedOnDiskStorage::new(path_buf.clone(), PASSPHRASE).unwrap();
+    storage.set("key", 1u64).unwrap();
+    let last_update = storage.get::<u64>("key").unwrap().last_update;
+
+    storage.rotate_master_key("new passphrase").unwrap();
+    let response = storage.get::<u64>("key").unwrap();
+    assert_eq!(response.value, 1);
+    assert_eq!(response.last_update, last_update);
+
+    assert_eq!(
+        EncryptedOnDiskStorage::new(path_buf.clone(), PASSPHRASE).err(),
+        Some(Error::PermissionDenied)
+    );
+    let storage = EncryptedOnDiskStorage::new(path_buf, "new passphrase").unwrap();
+    assert_eq!(storage.get::<u64>("key").unwrap().value, 1);
+}
+
+#[test]
+fn encrypted_on_disk_migrate_from_plaintext() {
+    let plaintext_path = TempPath::new().path().to_path_buf();
+    let mut plaintext = OnDiskStorage::new(plaintext_path.clone());
+    plaintext.set("key", "value").unwrap();
+    let last_update = plaintext.get::<String>("key").unwrap().last_update;
+
+    let path_buf = TempPath::new().path().to_path_buf();
+    let storage = EncryptedOnDiskStorage::migrate_from_plaintext(
+        &plaintext_path,
+        path_buf.clone(),
+        PASSPHRASE,
+    )
+    .unwrap();
+    let response = storage.get::<String>("key").unwrap();
+    assert_eq!(response.value, "value");
+    assert_eq!(response.last_update, last_update);
+
+    // An existing encrypted storage isn't overwritten
+    assert!(
+        EncryptedOnDiskStorage::migrate_from_plaintext(&plaintext_path, path_buf, PASSPHRASE)
+            .is_err()
+    );
+}
@@ -0,0 +1,124 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{AwsKmsStorage, CryptoStorage, Error, GcpKmsStorage, KVStorage, Storage};
+use aptos_crypto::{ed25519::Ed25519PrivateKey, test_utils::TestAptosCrypto, Signature, Uniform};
+
+// The shared suite can't be run as it relies on values and on exporting private keys. KMS keys
+// can't be deleted right away, so every run creates keys of its own, whose deletion is left to
+// the developer. These tests are run via: `cargo xtest -- --ignored`
+// Also the constants below must be defined with proper values -- never commit these values to
+// the repository.
+const AWS_REGION: &str = "AWS_REGION";
+const AWS_ACCESS_KEY_ID: &str = "AWS_ACCESS_KEY_ID";
+const AWS_SECRET_ACCESS_KEY: &str = "AWS_SECRET_ACCESS_KEY";
+
+const GCP_PROJECT: &str = "GCP_PROJECT";
+const GCP_LOCATION: &str = "GCP_LOCATION";
+const GCP_KEY_RING: &str = "GCP_KEY_RING";
+const GCP_TOKEN: &str = "GCP_TOKEN";
+
+const KMS_TESTS: &[fn(&mut Storage, &str)] = &[
+    test_no_values,
+    test_keys_cannot_be_exported,
+    test_create_sign_rotate_sign,
+];
+
+#[ignore]
+#[test]
+fn aws_kms_storage() {
+    let mut storage = Storage::from(AwsKmsStorage::new(
+        AWS_REGION.into(),
+        None,
+        AWS_ACCESS_KEY_ID.into(),
+        AWS_SECRET_ACCESS_KEY.into(),
+        None,
+    ));
+    execute_kms_tests(&mut storage);
+}
+
+#[ignore]
+#[test]
+fn gcp_kms_storage() {
+    let mut storage = Storage::from(GcpKmsStorage::new(
+        GCP_PROJECT,
+        GCP_LOCATION,
+        GCP_KEY_RING,
+        "SOFTWARE".into(),
+        Some(GCP_TOKEN.into()),
+    ));
+    execute_kms_tests(&mut storage);
+}
+
+fn execute_kms_tests(storage: &mut Storage) {
+    storage.available().unwrap();
+    let run = chrono::Utc::now().timestamp();
+    for (i, test) in KMS_TESTS.iter().enumerate() {
+        test(storage, &format!("Test_Key_Name_{}_{}", run, i));
+    }
+}
+
+/// KMS holds keys only.
+fn test_no_values(storage: &mut Storage, _name: &str) {
+    assert!(storage.get::<u64>("U64_Key").is_err());
+    assert!(storage.set("U64_Key", 10).is_err());
+}
+
+/// Keys generated by KMS are used to sign, but never leave it.
+fn test_keys_cannot_be_exported(storage: &mut Storage, name: &str) {
+    let public_key = storage.create_key(name).unwrap();
+    assert_eq!(storage.get_public_key(name).unwrap().public_key, public_key);
+    assert_eq!(
+        storage.create_key(name).unwrap_err(),
+        Error::KeyAlreadyExists(name.to_string())
+    );
+
+    let message = TestAptosCrypto("Hello, World".to_string());
+    let signature = storage.sign(name, &message).unwrap();
+    signature.verify(&message, &public_key).unwrap();
+
+    assert_eq!(
+        storage.export_private_key(name).unwrap_err(),
+        Error::PermissionDenied
+    );
+    assert_eq!(
+        storage
+            .export_private_key_for_version(name, public_key)
+            .unwrap_err(),
+        Error::PermissionDenied
+    );
+    assert_eq!(
+        storage
+            .import_private_key(name, Ed25519PrivateKey::generate_for_testing())
+            .unwrap_err(),
+        Error::PermissionDenied
+    );
+}
+
+/// The previous version can still be used to sign after a rotation.
+fn test_create_sign_rotate_sign(storage: &mut Storage, name: &str) {
+    let public_key = storage.create_key(name).unwrap();
+    let message = TestAptosCrypto("Hello, World".to_string());
+    assert!(storage.get_public_key_previous_version(name).is_err());
+
+    for _ in 0..3 {
+        let previous_public_key = storage.get_public_key(name).unwrap().public_key;
+        let new_public_key = storage.rotate_key(name).unwrap();
+        assert_eq!(
+            storage.get_public_key_previous_version(name).unwrap(),
+            previous_public_key
+        );
+
+        let signature = storage
+            .sign_using_version(name, previous_public_key.clone(), &message)
+            .unwrap();
+        signature.verify(&message, &previous_public_key).unwrap();
+        let signature = storage.sign(name, &message).unwrap();
+        signature.verify(&message, &new_public_key).unwrap();
+    }
+
+    // Only the current and previous versions are retained
+    assert!(storage
+        .sign_using_version(name, public_key, &message)
+        .is_err());
+}
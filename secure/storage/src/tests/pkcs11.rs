@@ -0,0 +1,124 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{CryptoStorage, Error, KVStorage, Pkcs11Storage, Storage};
+use aptos_crypto::{
+    ed25519::Ed25519PrivateKey, test_utils::TestAptosCrypto, PrivateKey, Signature, SigningKey,
+    Uniform,
+};
+use std::path::Path;
+
+// The shared suite can't be run as it relies on exporting private keys. These tests must be run
+// in series against a dedicated token (e.g., initialized with SoftHSM) via:
+// `cargo xtest -- --ignored --test-threads=1`
+// Also the constants below must be defined with proper values -- never commit these values to
+// the repository.
+const LIBRARY: &str = "LIBRARY";
+const TOKEN_LABEL: &str = "TOKEN_LABEL";
+const PIN: &str = "PIN";
+
+const CRYPTO_NAME: &str = "Test_Key_Name";
+const U64_KEY: &str = "U64_Key";
+
+const PKCS11_TESTS: &[fn(&mut Storage)] = &[
+    test_get_set,
+    test_keys_cannot_be_exported,
+    test_import_sign_rotate_sign,
+];
+
+#[ignore]
+#[test]
+fn pkcs11_storage() {
+    let mut storage =
+        Storage::from(Pkcs11Storage::new(Path::new(LIBRARY), TOKEN_LABEL, PIN).unwrap());
+    storage.available().unwrap();
+    storage.reset_and_clear().unwrap();
+    for test in PKCS11_TESTS.iter() {
+        test(&mut storage);
+        storage.reset_and_clear().unwrap();
+    }
+}
+
+/// Values are stored in and overwritten on the token.
+fn test_get_set(storage: &mut Storage) {
+    assert_eq!(
+        storage.get::<u64>(U64_KEY).unwrap_err(),
+        Error::KeyNotSet(U64_KEY.to_string())
+    );
+    storage.set(U64_KEY, 10).unwrap();
+    assert_eq!(storage.get::<u64>(U64_KEY).unwrap().value, 10);
+    storage.set(U64_KEY, 647).unwrap();
+    assert_eq!(storage.get::<u64>(U64_KEY).unwrap().value, 647);
+}
+
+/// Keys generated on the token are used to sign, but never leave it.
+fn test_keys_cannot_be_exported(storage: &mut Storage) {
+    let public_key = storage.create_key(CRYPTO_NAME).unwrap();
+    assert_eq!(
+        storage.get_public_key(CRYPTO_NAME).unwrap().public_key,
+        public_key
+    );
+    assert_eq!(
+        storage.create_key(CRYPTO_NAME).unwrap_err(),
+        Error::KeyAlreadyExists(CRYPTO_NAME.to_string())
+    );
+
+    let message = TestAptosCrypto("Hello, World".to_string());
+    let signature = storage.sign(CRYPTO_NAME, &message).unwrap();
+    signature.verify(&message, &public_key).unwrap();
+
+    assert_eq!(
+        storage.export_private_key(CRYPTO_NAME).unwrap_err(),
+        Error::PermissionDenied
+    );
+    assert_eq!(
+        storage
+            .export_private_key_for_version(CRYPTO_NAME, public_key)
+            .unwrap_err(),
+        Error::PermissionDenied
+    );
+}
+
+/// Imported keys sign like software keys, and the previous version can still be used to sign
+/// after a rotation.
+fn test_import_sign_rotate_sign(storage: &mut Storage) {
+    let private_key = Ed25519PrivateKey::generate_for_testing();
+    let public_key = private_key.public_key();
+    storage
+        .import_private_key(CRYPTO_NAME, private_key.clone())
+        .unwrap();
+    assert_eq!(
+        storage.get_public_key(CRYPTO_NAME).unwrap().public_key,
+        public_key
+    );
+
+    let message = TestAptosCrypto("Hello, World".to_string());
+    let signature = storage.sign(CRYPTO_NAME, &message).unwrap();
+    assert_eq!(signature, private_key.sign(&message));
+    assert!(storage
+        .get_public_key_previous_version(CRYPTO_NAME)
+        .is_err());
+
+    for _ in 0..3 {
+        let previous_public_key = storage.get_public_key(CRYPTO_NAME).unwrap().public_key;
+        let new_public_key = storage.rotate_key(CRYPTO_NAME).unwrap();
+        assert_eq!(
+            storage
+                .get_public_key_previous_version(CRYPTO_NAME)
+                .unwrap(),
+            previous_public_key
+        );
+
+        let signature = storage
+            .sign_using_version(CRYPTO_NAME, previous_public_key.clone(), &message)
+            .unwrap();
+        signature.verify(&message, &previous_public_key).unwrap();
+        let signature = storage.sign(CRYPTO_NAME, &message).unwrap();
+        signature.verify(&message, &new_public_key).unwrap();
+    }
+
+    // Only the current and previous versions are retained
+    assert!(storage
+        .sign_using_version(CRYPTO_NAME, public_key, &message)
+        .is_err());
+}
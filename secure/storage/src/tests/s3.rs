@@ -0,0 +1,24 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{tests::suite, S3Storage, Storage};
+
+const BUCKET: &str = "BUCKET";
+const REGION: &str = "REGION";
+const ACCESS_KEY_ID: &str = "ACCESS_KEY_ID";
+const SECRET_ACCESS_KEY: &str = "SECRET_ACCESS_KEY";
+
+// The constants above must be defined with proper values -- never commit these values to the
+// repository. The bucket is cleared by the tests.
+#[ignore]
+#[test]
+fn s3_storage() {
+    let mut storage = Storage::from(S3Storage::new(
+        BUCKET.into(),
+        REGION.into(),
+        None,
+        ACCESS_KEY_ID.into(),
+        SECRET_ACCESS_KEY.into(),
+    ));
+    suite::execute_all_storage_tests(&mut storage);
+}
@@ -10,7 +10,9 @@ use crate::{
     Capability, CryptoStorage, Error, Identity, KVStorage, Namespaced, Permission, Policy, Storage,
 };
 use aptos_crypto::{test_utils::TestAptosCrypto, Signature};
+use aptos_global_constants::SAFETY_DATA;
 use aptos_vault_client::dev::{self, ROOT_TOKEN};
+use std::time::Duration;
 
 /// VaultStorage namespace constants
 const VAULT_NAMESPACE_1: &str = "namespace_1";
@@ -41,6 +43,8 @@ const WRITER: &str = "writer";
 const VAULT_TESTS: &[fn()] = &[
     test_suite_multiple_namespaces,
     test_suite_no_namespaces,
+    test_suite_cached,
+    test_vault_cache,
     test_vault_cas,
     test_vault_crypto_policies,
     test_vault_key_trimming,
@@ -83,6 +87,12 @@ fn test_suite_multiple_namespaces() {
     suite::execute_all_storage_tests(&mut storage_3);
 }
 
+/// Runs the test suite on a VaultStorage instance that caches its reads
+fn test_suite_cached() {
+    let mut storage = Storage::from(create_vault().with_cache_ttl(Some(Duration::from_secs(3600))));
+    suite::execute_all_storage_tests(&mut storage);
+}
+
 /// Creates and initializes a VaultStorage instance for testing. If a namespace is specified, the
 /// instance will perform all storage operations under that namespace.
 fn create_vault_with_namespace(namespace: &str) -> Namespaced<Box<Storage>> {
@@ -412,6 +422,55 @@ fn test_vault_cas() {
     assert_eq!(with_cas.get::<u64>("test").unwrap().value, 6);
 }
 
+fn test_vault_cache() {
+    let mut cached = create_vault().with_cache_ttl(Some(Duration::from_secs(3600)));
+    let mut other = create_vault_storage(ROOT_TOKEN.into(), None, false);
+
+    // Writes update the cache
+    cached.set("test", 1).unwrap();
+    assert_eq!(cached.get::<u64>("test").unwrap().value, 1);
+
+    // Writes of others aren't read until the cache expires
+    other.set("test", 2).unwrap();
+    assert_eq!(cached.get::<u64>("test").unwrap().value, 1);
+
+    // Unless a write fails because of them
+    cached.set("test", 3).unwrap_err();
+    assert_eq!(cached.get::<u64>("test").unwrap().value, 2);
+    cached.set("test", 3).unwrap();
+    assert_eq!(other.get::<u64>("test").unwrap().value, 3);
+
+    // Public keys are cached as well, until the key is changed through the storage
+    let public_key = cached.create_key(CRYPTO_KEY).unwrap();
+    let rotated_key = other.rotate_key(CRYPTO_KEY).unwrap();
+    assert_eq!(
+        cached.get_public_key(CRYPTO_KEY).unwrap().public_key,
+        public_key
+    );
+    let new_key = cached.rotate_key(CRYPTO_KEY).unwrap();
+    assert_eq!(
+        cached.get_public_key(CRYPTO_KEY).unwrap().public_key,
+        new_key
+    );
+    assert_eq!(
+        cached.get_public_key_previous_version(CRYPTO_KEY).unwrap(),
+        rotated_key
+    );
+
+    // The safety data is never cached, namespaced or not
+    cached.set(SAFETY_DATA, 1).unwrap();
+    other.set(SAFETY_DATA, 2).unwrap();
+    assert_eq!(cached.get::<u64>(SAFETY_DATA).unwrap().value, 2);
+    let mut namespaced_cached = Namespaced::new(
+        VAULT_NAMESPACE_1,
+        create_vault().with_cache_ttl(Some(Duration::from_secs(3600))),
+    );
+    let mut namespaced_other = Namespaced::new(VAULT_NAMESPACE_1, other);
+    namespaced_cached.set(SAFETY_DATA, 1).unwrap();
+    namespaced_other.set(SAFETY_DATA, 2).unwrap();
+    assert_eq!(namespaced_cached.get::<u64>(SAFETY_DATA).unwrap().value, 2);
+}
+
 fn test_vault_key_trimming() {
     let mut storage = create_vault();
 
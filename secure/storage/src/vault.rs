@@ -9,20 +9,33 @@ use aptos_crypto::{
     ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
     hash::CryptoHash,
 };
+use aptos_global_constants::SAFETY_DATA;
 use aptos_infallible::RwLock;
+use aptos_logger::prelude::*;
 use aptos_time_service::{TimeService, TimeServiceTrait};
-use aptos_vault_client::Client;
-use chrono::DateTime;
+use aptos_vault_client::{Client, ReadResponse};
+use chrono::{DateTime, Utc};
 use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
 use std::{
     collections::HashMap,
-    sync::atomic::{AtomicU64, Ordering},
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Once, Weak,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
-#[cfg(any(test, feature = "testing"))]
-use aptos_vault_client::ReadResponse;
-
 const TRANSIT_NAMESPACE_SEPARATOR: &str = "__";
+/// How often the token is checked in the background, to be renewed before it expires even when
+/// the storage isn't used.
+const TOKEN_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+/// The secrets which are never cached: reading a stale safety data, e.g. after a failover to
+/// another safety rules instance, could lead to voting twice in a round.
+const UNCACHED_SECRETS: &[&str] = &[SAFETY_DATA];
 
 /// VaultStorage utilizes Vault for maintaining encrypted, authenticated data. This
 /// version currently matches the behavior of OnDiskStorage and InMemoryStorage. In the future,
@@ -32,12 +45,12 @@ const TRANSIT_NAMESPACE_SEPARATOR: &str = "__";
 /// calls pointers to data keys, Vault has actually a secret that contains multiple key value
 /// pairs.
 pub struct VaultStorage {
-    client: Client,
-    time_service: TimeService,
-    renew_ttl_secs: Option<u32>,
-    next_renewal: AtomicU64,
+    token: Arc<TokenManager>,
+    token_refresh: Once,
     use_cas: bool,
     secret_versions: RwLock<HashMap<String, u32>>,
+    secret_cache: ResponseCache<ReadResponse<Value>>,
+    key_cache: ResponseCache<Vec<ReadResponse<Ed25519PublicKey>>>,
 }
 
 impl VaultStorage {
@@ -50,38 +63,68 @@ impl VaultStorage {
         connection_timeout_ms: Option<u64>,
         response_timeout_ms: Option<u64>,
     ) -> Self {
+        let time_service = TimeService::real();
         Self {
-            client: Client::new(
-                host,
-                token,
-                certificate,
-                connection_timeout_ms,
-                response_timeout_ms,
-            ),
-            time_service: TimeService::real(),
-            renew_ttl_secs,
-            next_renewal: AtomicU64::new(0),
+            token: Arc::new(TokenManager {
+                client: Client::new(
+                    host,
+                    token,
+                    certificate,
+                    connection_timeout_ms,
+                    response_timeout_ms,
+                ),
+                time_service: time_service.clone(),
+                renew_ttl_secs,
+                kubernetes_auth: None,
+                logged_in: AtomicBool::new(false),
+                next_renewal: AtomicU64::new(0),
+            }),
+            token_refresh: Once::new(),
             use_cas,
             secret_versions: RwLock::new(HashMap::new()),
+            secret_cache: ResponseCache::new(None, time_service.clone()),
+            key_cache: ResponseCache::new(None, time_service),
         }
     }
 
+    /// Makes all the requests in the given Vault Enterprise namespace. This is unrelated to
+    /// `Namespaced`, which prefixes the keys.
+    pub fn with_vault_namespace(mut self, namespace: Option<String>) -> Self {
+        self.token_manager_mut().client.set_namespace(namespace);
+        self
+    }
+
+    /// Obtains the token by logging in with the Kubernetes auth method instead of using the one
+    /// given to `new`. A new token is logged in with whenever the current one can't be renewed.
+    pub fn with_kubernetes_auth(mut self, kubernetes_auth: Option<VaultKubernetesAuth>) -> Self {
+        self.token_manager_mut().kubernetes_auth = kubernetes_auth;
+        self
+    }
+
+    /// Keeps the secrets and the public keys read for the given duration instead of reading them
+    /// from Vault on every access. The secrets written and the keys changed by this storage are
+    /// kept up to date, changes made by others can be missed for up to the duration. The safety
+    /// data is always read from Vault.
+    pub fn with_cache_ttl(mut self, cache_ttl: Option<Duration>) -> Self {
+        let time_service = self.token.time_service.clone();
+        self.secret_cache = ResponseCache::new(cache_ttl, time_service.clone());
+        self.key_cache = ResponseCache::new(cache_ttl, time_service);
+        self
+    }
+
+    fn token_manager_mut(&mut self) -> &mut TokenManager {
+        Arc::get_mut(&mut self.token)
+            .expect("The token manager can't be changed once the storage is used")
+    }
+
     // Made into an accessor so we can get auto-renewal
     fn client(&self) -> &Client {
-        if self.renew_ttl_secs.is_some() {
-            let now = self.time_service.now_secs();
-            let next_renewal = self.next_renewal.load(Ordering::Relaxed);
-            if now >= next_renewal {
-                let result = self.client.renew_token_self(self.renew_ttl_secs);
-                if let Ok(ttl) = result {
-                    let next_renewal = now + (ttl as u64) / 2;
-                    self.next_renewal.store(next_renewal, Ordering::Relaxed);
-                } else if let Err(e) = result {
-                    aptos_logger::error!("Unable to renew lease: {}", e.to_string());
-                }
-            }
+        if self.token.needs_refresh() {
+            self.token_refresh
+                .call_once(|| TokenManager::start_refresh(Arc::downgrade(&self.token)));
+            self.token.refresh();
         }
-        &self.client
+        &self.token.client
     }
 
     #[cfg(any(test, feature = "testing"))]
@@ -114,7 +157,7 @@ impl VaultStorage {
 
     #[cfg(any(test, feature = "testing"))]
     pub fn revoke_token_self(&self) -> Result<(), Error> {
-        Ok(self.client.revoke_token_self()?)
+        Ok(self.token.client.revoke_token_self()?)
     }
 
     #[cfg(any(test, feature = "testing"))]
@@ -125,8 +168,18 @@ impl VaultStorage {
         Ok(self.client().read_ed25519_key(name)?)
     }
 
-    fn key_version(&self, name: &str, version: &Ed25519PublicKey) -> Result<u32, Error> {
+    /// Reads the versions of the key, from the cache if they're in it
+    fn read_ed25519_key(&self, name: &str) -> Result<Vec<ReadResponse<Ed25519PublicKey>>, Error> {
+        if let Some(pubkeys) = self.key_cache.get(name) {
+            return Ok(pubkeys);
+        }
         let pubkeys = self.client().read_ed25519_key(name)?;
+        self.key_cache.insert(name, pubkeys.clone());
+        Ok(pubkeys)
+    }
+
+    fn key_version(&self, name: &str, version: &Ed25519PublicKey) -> Result<u32, Error> {
+        let pubkeys = self.read_ed25519_key(name)?;
         let pubkey = pubkeys.iter().find(|pubkey| version == &pubkey.value);
         Ok(pubkey
             .ok_or_else(|| Error::KeyVersionNotFound(name.into(), version.to_string()))?
@@ -142,6 +195,11 @@ impl VaultStorage {
             .map(|(_, key)| key)
             .unwrap_or(name)
     }
+
+    /// Whether the secret with the given unnamespaced name can be kept in the cache.
+    fn cacheable(key: &str) -> bool {
+        !UNCACHED_SECRETS.contains(&key)
+    }
 }
 
 impl KVStorage for VaultStorage {
@@ -156,7 +214,16 @@ impl KVStorage for VaultStorage {
     fn get<T: DeserializeOwned>(&self, key: &str) -> Result<GetResponse<T>, Error> {
         let secret = key;
         let key = self.unnamespaced(key);
-        let resp = self.client().read_secret(secret, key)?;
+        let resp = match self.secret_cache.get(secret) {
+            Some(resp) => resp,
+            None => {
+                let resp = self.client().read_secret(secret, key)?;
+                if Self::cacheable(key) {
+                    self.secret_cache.insert(secret, resp.clone());
+                }
+                resp
+            }
+        };
         let last_update = DateTime::parse_from_rfc3339(&resp.creation_time)?.timestamp() as u64;
         let value: T = serde_json::from_value(resp.value)?;
         self.secret_versions
@@ -173,18 +240,34 @@ impl KVStorage for VaultStorage {
         } else {
             None
         };
-        let new_version =
-            self.client()
-                .write_secret(secret, key, &serde_json::to_value(&value)?, version)?;
+        let value = serde_json::to_value(&value)?;
+        let new_version = match self.client().write_secret(secret, key, &value, version) {
+            Ok(new_version) => new_version,
+            Err(e) => {
+                // The secret may have been changed by others
+                self.secret_cache.remove(secret);
+                return Err(e.into());
+            }
+        };
         self.secret_versions
             .write()
             .insert(key.to_string(), new_version);
+        // Vault sets the creation time of the version, the local time is close enough for the
+        // cache
+        if Self::cacheable(key) {
+            self.secret_cache.insert(
+                secret,
+                ReadResponse::new(Utc::now().to_rfc3339(), value, new_version),
+            );
+        }
         Ok(())
     }
 
     #[cfg(any(test, feature = "testing"))]
     fn reset_and_clear(&mut self) -> Result<(), Error> {
         self.secret_versions.write().clear();
+        self.secret_cache.clear();
+        self.key_cache.clear();
         self.reset_kv("")?;
         self.reset_crypto()?;
         Ok(())
@@ -200,6 +283,7 @@ impl CryptoStorage for VaultStorage {
             Err(e) => return Err(e),
         }
 
+        self.key_cache.remove(&ns_name);
         self.client().create_ed25519_key(&ns_name, true)?;
         self.get_public_key(name).map(|v| v.public_key)
     }
@@ -227,6 +311,7 @@ impl CryptoStorage for VaultStorage {
             Err(e) => return Err(e),
         }
 
+        self.key_cache.remove(&ns_name);
         self.client()
             .import_ed25519_key(&ns_name, &key)
             .map_err(|e| e.into())
@@ -234,7 +319,7 @@ impl CryptoStorage for VaultStorage {
 
     fn get_public_key(&self, name: &str) -> Result<PublicKeyResponse, Error> {
         let name = self.crypto_name(name);
-        let resp = self.client().read_ed25519_key(&name)?;
+        let resp = self.read_ed25519_key(&name)?;
         let mut last_key = resp.first().ok_or(Error::KeyNotSet(name))?;
         for key in &resp {
             last_key = if last_key.version > key.version {
@@ -252,7 +337,7 @@ impl CryptoStorage for VaultStorage {
 
     fn get_public_key_previous_version(&self, name: &str) -> Result<Ed25519PublicKey, Error> {
         let name = self.crypto_name(name);
-        let pubkeys = self.client().read_ed25519_key(&name)?;
+        let pubkeys = self.read_ed25519_key(&name)?;
         let highest_version = pubkeys.iter().map(|pubkey| pubkey.version).max();
         match highest_version {
             Some(version) => {
@@ -268,6 +353,7 @@ impl CryptoStorage for VaultStorage {
 
     fn rotate_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
         let ns_name = self.crypto_name(name);
+        self.key_cache.remove(&ns_name);
         self.client().rotate_key(&ns_name)?;
         Ok(self.client().trim_key_versions(&ns_name)?)
     }
@@ -307,6 +393,146 @@ impl CryptoStorage for VaultStorage {
     }
 }
 
+/// The Kubernetes auth method, logged in with the token of the service account of the pod
+pub struct VaultKubernetesAuth {
+    /// The path the auth method is mounted at, usually `kubernetes`
+    pub mount_path: String,
+    /// The Vault role bound to the service account
+    pub role: String,
+    /// The path of the service account token. It's read on every login, as Kubernetes rotates
+    /// projected tokens.
+    pub service_account_token_path: PathBuf,
+}
+
+impl VaultKubernetesAuth {
+    fn login(&self, client: &Client) -> Result<u32, Error> {
+        let jwt = fs::read_to_string(&self.service_account_token_path)?;
+        Ok(client.login_kubernetes(&self.mount_path, &self.role, jwt.trim())?)
+    }
+}
+
+/// Keeps the token of the client valid, renewing its lease at half of its duration, and logging
+/// in again with the Kubernetes auth method when it's configured and renewing fails.
+struct TokenManager {
+    client: Client,
+    time_service: TimeService,
+    renew_ttl_secs: Option<u32>,
+    kubernetes_auth: Option<VaultKubernetesAuth>,
+    logged_in: AtomicBool,
+    next_renewal: AtomicU64,
+}
+
+impl TokenManager {
+    fn needs_refresh(&self) -> bool {
+        (self.renew_ttl_secs.is_some() || self.kubernetes_auth.is_some())
+            && self.time_service.now_secs() >= self.next_renewal.load(Ordering::Relaxed)
+    }
+
+    fn refresh(&self) {
+        let now = self.time_service.now_secs();
+        let result = match &self.kubernetes_auth {
+            Some(kubernetes_auth) => {
+                let renewed = if self.logged_in.load(Ordering::Relaxed) {
+                    self.renew()
+                } else {
+                    None
+                };
+                match renewed {
+                    Some(Ok(ttl)) => Ok(ttl),
+                    _ => kubernetes_auth.login(&self.client).map(|ttl| {
+                        self.logged_in.store(true, Ordering::Relaxed);
+                        ttl
+                    }),
+                }
+            }
+            None => match self.renew() {
+                Some(result) => result,
+                None => return,
+            },
+        };
+        match result {
+            Ok(ttl) => {
+                let next_renewal = now + (ttl as u64) / 2;
+                self.next_renewal.store(next_renewal, Ordering::Relaxed);
+            }
+            Err(e) => error!("Unable to renew lease: {}", e.to_string()),
+        }
+    }
+
+    /// Returns None when the lease isn't renewed
+    fn renew(&self) -> Option<Result<u32, Error>> {
+        self.renew_ttl_secs.map(|renew_ttl_secs| {
+            self.client
+                .renew_token_self(Some(renew_ttl_secs))
+                .map_err(|e| e.into())
+        })
+    }
+
+    /// Refreshes the token in the background until the storage is dropped
+    fn start_refresh(token: Weak<TokenManager>) {
+        let result = thread::Builder::new()
+            .name("vault-token".into())
+            .spawn(move || loop {
+                thread::sleep(TOKEN_REFRESH_INTERVAL);
+                match token.upgrade() {
+                    Some(token) if token.needs_refresh() => token.refresh(),
+                    Some(_) => (),
+                    None => break,
+                }
+            });
+        if let Err(e) = result {
+            error!(
+                "Unable to start renewing the lease in the background: {}",
+                e
+            );
+        }
+    }
+}
+
+/// Responses read from Vault, by secret or key name, kept until their TTL expires. Nothing is
+/// kept without a TTL.
+struct ResponseCache<T> {
+    ttl: Option<Duration>,
+    time_service: TimeService,
+    entries: RwLock<HashMap<String, (Instant, T)>>,
+}
+
+impl<T: Clone> ResponseCache<T> {
+    fn new(ttl: Option<Duration>, time_service: TimeService) -> Self {
+        Self {
+            ttl,
+            time_service,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<T> {
+        let now = self.time_service.now();
+        match self.entries.read().get(name) {
+            Some((expiration, value)) if now < *expiration => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    fn insert(&self, name: &str, value: T) {
+        if let Some(ttl) = self.ttl {
+            let expiration = self.time_service.now() + ttl;
+            self.entries
+                .write()
+                .insert(name.to_string(), (expiration, value));
+        }
+    }
+
+    fn remove(&self, name: &str) {
+        self.entries.write().remove(name);
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    fn clear(&self) {
+        self.entries.write().clear();
+    }
+}
+
 #[cfg(test)]
 pub mod policy {
     use super::*;
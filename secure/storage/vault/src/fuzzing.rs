@@ -3,9 +3,10 @@
 
 use crate::{
     CreateTokenAuth, CreateTokenResponse, ExportKey, ExportKeyResponse, ListKeys, ListKeysResponse,
-    ListPoliciesResponse, ReadKey, ReadKeyResponse, ReadKeys, ReadSecretData, ReadSecretListData,
-    ReadSecretListResponse, ReadSecretMetadata, ReadSecretResponse, RenewTokenAuth,
-    RenewTokenResponse, SealStatusResponse, Signature, SignatureResponse,
+    ListPoliciesResponse, LoginAuth, LoginResponse, ReadKey, ReadKeyResponse, ReadKeys,
+    ReadSecretData, ReadSecretListData, ReadSecretListResponse, ReadSecretMetadata,
+    ReadSecretResponse, RenewTokenAuth, RenewTokenResponse, SealStatusResponse, Signature,
+    SignatureResponse,
 };
 use aptos_types::proptest_types::arb_json_value;
 use proptest::prelude::*;
@@ -142,6 +143,29 @@ prop_compose! {
     }
 }
 
+// This generates an arbitrary login response returned by vault.
+prop_compose! {
+    pub fn arb_login_response(
+    )(
+        status in any::<u16>(),
+        status_text in any::<String>(),
+        client_token in any::<String>(),
+        lease_duration in any::<u32>(),
+    ) -> Response {
+        let auth = LoginAuth {
+            client_token,
+            lease_duration,
+        };
+        let login_response = LoginResponse {
+            auth,
+        };
+
+        let login_response =
+            serde_json::to_string::<LoginResponse>(&login_response).unwrap();
+        Response::new(status, &status_text, &login_response)
+    }
+}
+
 // This generates an arbitrary transit create response returned by vault, as well as an arbitrary
 // string name.
 prop_compose! {
@@ -297,17 +321,18 @@ prop_compose! {
 mod tests {
     use crate::{
         fuzzing::{
-            arb_generic_response, arb_policy_list_response, arb_secret_list_response,
-            arb_secret_read_response, arb_token_create_response, arb_token_renew_response,
-            arb_transit_create_response, arb_transit_export_response, arb_transit_list_response,
-            arb_transit_read_response, arb_transit_sign_response, arb_unsealed_response,
+            arb_generic_response, arb_login_response, arb_policy_list_response,
+            arb_secret_list_response, arb_secret_read_response, arb_token_create_response,
+            arb_token_renew_response, arb_transit_create_response, arb_transit_export_response,
+            arb_transit_list_response, arb_transit_read_response, arb_transit_sign_response,
+            arb_unsealed_response,
         },
-        process_generic_response, process_policy_list_response, process_policy_read_response,
-        process_secret_list_response, process_secret_read_response, process_token_create_response,
-        process_token_renew_response, process_transit_create_response,
-        process_transit_export_response, process_transit_list_response,
-        process_transit_read_response, process_transit_restore_response,
-        process_transit_sign_response, process_unsealed_response,
+        process_generic_response, process_login_response, process_policy_list_response,
+        process_policy_read_response, process_secret_list_response, process_secret_read_response,
+        process_token_create_response, process_token_renew_response,
+        process_transit_create_response, process_transit_export_response,
+        process_transit_list_response, process_transit_read_response,
+        process_transit_restore_response, process_transit_sign_response, process_unsealed_response,
     };
     use proptest::prelude::*;
 
@@ -344,6 +369,11 @@ mod tests {
             let _ = process_token_create_response(response);
         }
 
+        #[test]
+        fn process_login_response_proptest(response in arb_login_response()) {
+            let _ = process_login_response(response);
+        }
+
         #[test]
         fn process_token_renew_response_proptest(response in arb_token_renew_response()) {
             let _ = process_token_renew_response(response);
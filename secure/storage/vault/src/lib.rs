@@ -9,6 +9,7 @@ use aptos_crypto::{
     ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature, ED25519_PRIVATE_KEY_LENGTH},
     PrivateKey,
 };
+use aptos_infallible::RwLock;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::{
@@ -113,7 +114,9 @@ impl From<serde_json::Error> for Error {
 pub struct Client {
     agent: ureq::Agent,
     host: String,
-    token: String,
+    token: RwLock<String>,
+    /// The Vault Enterprise namespace the requests are made in, if any.
+    namespace: Option<String>,
     tls_connector: Arc<native_tls::TlsConnector>,
 
     /// Timeout for new socket connections to vault.
@@ -148,13 +151,37 @@ impl Client {
         Self {
             agent: ureq::Agent::new().set("connection", "keep-alive").build(),
             host,
-            token,
+            token: RwLock::new(token),
+            namespace: None,
             tls_connector,
             connection_timeout_ms,
             response_timeout_ms,
         }
     }
 
+    /// Makes all the requests in the given Vault Enterprise namespace.
+    pub fn set_namespace(&mut self, namespace: Option<String>) {
+        self.namespace = namespace;
+    }
+
+    /// Logs in with the Kubernetes auth method mounted at `mount_path`, using the JWT of a
+    /// service account bound to `role`. The returned token is used for the following requests,
+    /// its lease duration is returned.
+    pub fn login_kubernetes(&self, mount_path: &str, role: &str, jwt: &str) -> Result<u32, Error> {
+        let request = self
+            .agent
+            .post(&format!("{}/v1/auth/{}/login", self.host, mount_path));
+        let mut request = self.upgrade_request_without_token(request);
+        if let Some(namespace) = &self.namespace {
+            request.set("X-Vault-Namespace", namespace);
+        }
+        let resp = request.send_json(json!({ "role": role, "jwt": jwt }));
+
+        let (token, lease_duration) = process_login_response(resp)?;
+        *self.token.write() = token;
+        Ok(lease_duration)
+    }
+
     pub fn delete_policy(&self, policy_name: &str) -> Result<(), Error> {
         let request = self
             .agent
@@ -480,7 +507,10 @@ impl Client {
 
     fn upgrade_request(&self, request: ureq::Request) -> ureq::Request {
         let mut request = self.upgrade_request_without_token(request);
-        request.set("X-Vault-Token", &self.token);
+        request.set("X-Vault-Token", &self.token.read());
+        if let Some(namespace) = &self.namespace {
+            request.set("X-Vault-Namespace", namespace);
+        }
         request
     }
 
@@ -583,6 +613,17 @@ pub fn process_token_create_response(resp: Response) -> Result<String, Error> {
     }
 }
 
+/// Processes the response returned by a login vault request, returning the token and its lease
+/// duration.
+pub fn process_login_response(resp: Response) -> Result<(String, u32), Error> {
+    if resp.ok() {
+        let resp: LoginResponse = serde_json::from_str(&resp.into_string()?)?;
+        Ok((resp.auth.client_token, resp.auth.lease_duration))
+    } else {
+        Err(resp.into())
+    }
+}
+
 /// Processes the response returned by a token renew vault request.
 pub fn process_token_renew_response(resp: Response) -> Result<u32, Error> {
     if resp.ok() {
@@ -856,7 +897,7 @@ pub struct BackupInfo {
 }
 
 /// Provides a simple wrapper for all read APIs.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ReadResponse<T> {
     pub creation_time: String,
     pub value: T,
@@ -912,6 +953,36 @@ struct CreateTokenAuth {
     client_token: String,
 }
 
+/// Below is a sample output of a LoginResponse, returned by auth methods like Kubernetes. Only the
+/// fields leveraged by this framework are decoded.
+/// {
+///   "auth": {
+///     "client_token": "62b858f9-529c-6b26-e0b8-0457b6aacdb4",
+///     "accessor": "afa306d0-be3d-c8d2-b0d7-2676e1c0d9b4",
+///     "policies": ["default"],
+///     "metadata": {
+///       "role": "test",
+///       "service_account_name": "vault-auth",
+///       "service_account_namespace": "default",
+///       "service_account_secret_name": "vault-auth-token-pd21c",
+///       "service_account_uid": "aa9aa8ff-98d0-11e7-9bb7-0800276d99bf"
+///     },
+///     "lease_duration": 2764800,
+///     "renewable": true
+///   }
+/// }
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct LoginResponse {
+    auth: LoginAuth,
+}
+
+/// See LoginResponse
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct LoginAuth {
+    client_token: String,
+    lease_duration: u32,
+}
+
 /// Below is a sample output of ExportKeyResponse
 /// {
 ///   "data": {
@@ -53,6 +53,7 @@ pub enum LogEntry {
 pub enum LogEvent {
     AggregateSummary,
     NoPeersToPoll,
+    PeerBlocklisted,
     PeerIgnored,
     PeerNoLongerIgnored,
     PeerPollingError,
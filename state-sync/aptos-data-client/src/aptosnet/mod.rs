@@ -31,12 +31,13 @@ use network::{
     protocols::{rpc::error::RpcError, wire::handshake::v1::ProtocolId},
 };
 use rand::seq::SliceRandom;
-use std::{convert::TryFrom, fmt, sync::Arc, time::Duration};
+use std::{collections::HashSet, convert::TryFrom, fmt, sync::Arc, time::Duration};
 use storage_service_client::StorageServiceClient;
 use storage_service_types::{
-    AccountStatesChunkWithProofRequest, Epoch, EpochEndingLedgerInfoRequest, StorageServerSummary,
-    StorageServiceRequest, StorageServiceResponse, TransactionOutputsWithProofRequest,
-    TransactionsWithProofRequest,
+    AccountStatesChunkWithProofRequest, Epoch, EpochEndingLedgerInfoRequest,
+    NewTransactionOutputsWithProofRequest, NewTransactionsWithProofRequest, StorageServerSummaryV2,
+    StorageServiceError, StorageServiceRequest, StorageServiceResponse,
+    TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
 };
 
 mod logging;
@@ -48,6 +49,7 @@ mod tests;
 // Useful constants for the Diem Data Client
 const GLOBAL_DATA_LOG_FREQ_SECS: u64 = 5;
 const POLLER_ERROR_LOG_FREQ_SECS: u64 = 1;
+const REFERENCE_PING_LATENCY_SECS: f64 = 0.1;
 
 /// A [`AptosDataClient`] that fulfills requests from remote peers' Storage Service
 /// over AptosNet.
@@ -58,8 +60,12 @@ const POLLER_ERROR_LOG_FREQ_SECS: u64 = 1;
 /// 2. Does basic type conversions and error handling on the responses.
 /// 3. Routes requests to peers that advertise availability for that data.
 /// 4. Maintains peer scores based on each peer's observed quality of service
-///    and upper client reports of invalid or malicious data.
-/// 5. Selects high quality peers to send each request to.
+///    and upper client reports of invalid or malicious data. Peers that
+///    repeatedly send malicious data are temporarily blocklisted, with the
+///    offences decaying over time.
+/// 5. Selects high quality peers to send each request to. Peers are chosen
+///    randomly, weighted by their score and the number of requests already
+///    in-flight to them, so that concurrent requests are spread across peers.
 /// 6. Exposes a condensed data summary of our peers' data advertisements.
 ///
 /// The client currently assumes 1-request => 1-response. Streaming responses
@@ -91,7 +97,11 @@ impl AptosNetDataClient {
         let client = Self {
             data_client_config,
             network_client,
-            peer_states: Arc::new(RwLock::new(PeerStates::new(storage_service_config))),
+            peer_states: Arc::new(RwLock::new(PeerStates::new(
+                storage_service_config,
+                data_client_config,
+                time_service.clone(),
+            ))),
             global_summary_cache: Arc::new(RwLock::new(GlobalDataSummary::empty())),
             response_id_generator: Arc::new(U64IdGenerator::new()),
         };
@@ -108,7 +118,7 @@ impl AptosNetDataClient {
     }
 
     /// Update a peer's data summary.
-    fn update_summary(&self, peer: PeerNetworkId, summary: StorageServerSummary) {
+    fn update_summary(&self, peer: PeerNetworkId, summary: StorageServerSummaryV2) {
         self.peer_states.write().update_summary(peer, summary)
     }
 
@@ -131,7 +141,10 @@ impl AptosNetDataClient {
                             peer_metadata.is_connected()
                                 && peer_metadata.supports_protocol(ProtocolId::StorageServiceRpc)
                         })
-                        .into_keys()
+                        .into_iter()
+                        .map(|(peer, peer_metadata)| {
+                            (peer, peer_metadata.monitoring_metadata.average_ping_latency)
+                        })
                 })
                 .collect::<Vec<_>>()
         };
@@ -145,17 +158,25 @@ impl AptosNetDataClient {
         let internal_peer_states = self.peer_states.read();
         let all_serviceable = all_connected
             .into_iter()
-            .filter(|peer| internal_peer_states.can_service_request(peer, request))
+            .filter(|(peer, _)| internal_peer_states.can_service_request(peer, request))
             .collect::<Vec<_>>();
 
+        if all_serviceable.is_empty() {
+            return Err(Error::DataIsUnavailable(
+                "No connected peers are advertising that they can serve this data range".to_owned(),
+            ));
+        }
+
         all_serviceable
-            .choose(&mut rand::thread_rng())
-            .copied()
-            .ok_or_else(|| {
-                Error::DataIsUnavailable(
-                    "No connected peers are advertising that they can serve this data range"
-                        .to_owned(),
-                )
+            .choose_weighted(&mut rand::thread_rng(), |(peer, latency)| {
+                internal_peer_states.get_selection_weight(peer) * latency_weight(*latency)
+            })
+            .map(|(peer, _)| *peer)
+            .map_err(|error| {
+                Error::UnexpectedErrorEncountered(format!(
+                    "Failed to choose a weighted peer: {}",
+                    error
+                ))
             })
     }
 
@@ -224,14 +245,22 @@ impl AptosNetDataClient {
 
         increment_counter(&metrics::SENT_REQUESTS, request.get_label().into());
 
+        // Data subscriptions are held by the server until new data exists, so
+        // they require a longer timeout.
+        let timeout_ms = if request.is_data_subscription_request() {
+            self.data_client_config.subscription_response_timeout_ms
+        } else {
+            self.data_client_config.response_timeout_ms
+        };
+
+        // Track the request as in-flight until a response is received (or the
+        // request is dropped).
+        let in_flight_request = InFlightRequest::new(self.peer_states.clone(), peer);
         let result = self
             .network_client
-            .send_request(
-                peer,
-                request.clone(),
-                Duration::from_millis(self.data_client_config.response_timeout_ms),
-            )
+            .send_request(peer, request.clone(), Duration::from_millis(timeout_ms))
             .await;
+        drop(in_flight_request);
 
         match result {
             Ok(response) => {
@@ -268,6 +297,15 @@ impl AptosNetDataClient {
                 Ok(Response::new(context, response))
             }
             Err(err) => {
+                // An expired data subscription only means that no new data was
+                // committed in the meantime, so the peer isn't penalized for it.
+                let is_expired_subscription = matches!(
+                    err,
+                    storage_service_client::Error::StorageServiceError(
+                        StorageServiceError::SubscriptionExpired(_)
+                    )
+                );
+
                 // Convert network error and storage service error types into
                 // data client errors. Also categorize the error type for scoring
                 // purposes.
@@ -277,9 +315,12 @@ impl AptosNetDataClient {
                         RpcError::TimedOut => Error::TimeoutWaitingForResponse(err.to_string()),
                         _ => Error::UnexpectedErrorEncountered(err.to_string()),
                     },
-                    storage_service_client::Error::StorageServiceError(err) => {
-                        Error::UnexpectedErrorEncountered(err.to_string())
-                    }
+                    storage_service_client::Error::StorageServiceError(err) => match err {
+                        StorageServiceError::SubscriptionExpired(_) => {
+                            Error::TimeoutWaitingForResponse(err.to_string())
+                        }
+                        _ => Error::UnexpectedErrorEncountered(err.to_string()),
+                    },
                 };
 
                 error!(
@@ -293,7 +334,9 @@ impl AptosNetDataClient {
 
                 increment_counter(&metrics::ERROR_RESPONSES, request.get_label().into());
 
-                self.notify_bad_response(id, peer, &request, ErrorType::NotUseful);
+                if !is_expired_subscription {
+                    self.notify_bad_response(id, peer, &request, ErrorType::NotUseful);
+                }
                 Err(client_err)
             }
         }
@@ -312,6 +355,18 @@ impl AptosNetDataClient {
     }
 }
 
+/// Scales the selection weight of a peer by its ping latency (as measured by the
+/// peer monitoring service), halving the weight at the reference latency. Peers
+/// with an unknown latency aren't penalized.
+pub(crate) fn latency_weight(latency: Option<Duration>) -> f64 {
+    match latency {
+        Some(latency) => {
+            REFERENCE_PING_LATENCY_SECS / (REFERENCE_PING_LATENCY_SECS + latency.as_secs_f64())
+        }
+        None => 1.0,
+    }
+}
+
 #[async_trait]
 impl AptosDataClient for AptosNetDataClient {
     fn get_global_data_summary(&self) -> GlobalDataSummary {
@@ -348,6 +403,35 @@ impl AptosDataClient for AptosNetDataClient {
         Ok(response.map(|epoch_change| epoch_change.ledger_info_with_sigs))
     }
 
+    async fn get_new_transaction_outputs_with_proof(
+        &self,
+        known_version: Version,
+        known_epoch: Epoch,
+    ) -> Result<Response<(TransactionOutputListWithProof, LedgerInfoWithSignatures)>> {
+        let request = StorageServiceRequest::GetNewTransactionOutputsWithProof(
+            NewTransactionOutputsWithProofRequest {
+                known_version,
+                known_epoch,
+            },
+        );
+        self.send_request_and_decode(request).await
+    }
+
+    async fn get_new_transactions_with_proof(
+        &self,
+        known_version: Version,
+        known_epoch: Epoch,
+        include_events: bool,
+    ) -> Result<Response<(TransactionListWithProof, LedgerInfoWithSignatures)>> {
+        let request =
+            StorageServiceRequest::GetNewTransactionsWithProof(NewTransactionsWithProofRequest {
+                known_version,
+                known_epoch,
+                include_events,
+            });
+        self.send_request_and_decode(request).await
+    }
+
     async fn get_number_of_account_states(&self, version: Version) -> Result<Response<u64>> {
         let request = StorageServiceRequest::GetNumberOfAccountsAtVersion(version);
         self.send_request_and_decode(request).await
@@ -387,6 +471,25 @@ impl AptosDataClient for AptosNetDataClient {
     }
 }
 
+/// Marks a request to a peer as in-flight for as long as it is held.
+struct InFlightRequest {
+    peer_states: Arc<RwLock<PeerStates>>,
+    peer: PeerNetworkId,
+}
+
+impl InFlightRequest {
+    fn new(peer_states: Arc<RwLock<PeerStates>>, peer: PeerNetworkId) -> Self {
+        peer_states.write().start_in_flight_request(peer);
+        Self { peer_states, peer }
+    }
+}
+
+impl Drop for InFlightRequest {
+    fn drop(&mut self) {
+        self.peer_states.write().finish_in_flight_request(self.peer);
+    }
+}
+
 /// The AptosNet-specific request context needed to update a peer's scoring.
 struct AptosNetResponseCallback {
     data_client: AptosNetDataClient,
@@ -449,6 +552,11 @@ impl DataSummaryPoller {
         let ticker = self.time_service.interval(self.poll_interval);
         futures::pin_mut!(ticker);
 
+        // Peers that failed to serve the versioned summary (e.g., because they
+        // run an older storage service) are polled for the original summary,
+        // which retains no account states.
+        let mut summary_v1_peers = HashSet::new();
+
         // TODO(philiphayes): rather than polling one at a time, maybe do
         // round-robin with a few concurrent polls.
         loop {
@@ -476,18 +584,15 @@ impl DataSummaryPoller {
                 }
             };
 
-            let timer = start_timer(
-                &metrics::REQUEST_LATENCIES,
+            let request = if summary_v1_peers.contains(&peer) {
                 StorageServiceRequest::GetStorageServerSummary
-                    .get_label()
-                    .into(),
-            );
-            let result: Result<StorageServerSummary> = self
+            } else {
+                StorageServiceRequest::GetStorageServerSummaryV2
+            };
+            let timer = start_timer(&metrics::REQUEST_LATENCIES, request.get_label().into());
+            let result: Result<StorageServerSummaryV2> = self
                 .data_client
-                .send_request_to_peer_and_decode(
-                    peer,
-                    StorageServiceRequest::GetStorageServerSummary,
-                )
+                .send_request_to_peer_and_decode(peer, request.clone())
                 .await
                 .map(Response::into_payload);
             drop(timer);
@@ -502,6 +607,9 @@ impl DataSummaryPoller {
                             .error(&error)
                             .peer(&peer))
                     );
+                    if request == StorageServiceRequest::GetStorageServerSummaryV2 {
+                        summary_v1_peers.insert(peer);
+                    }
                     continue;
                 }
             };
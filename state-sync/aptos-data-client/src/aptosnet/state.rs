@@ -5,10 +5,17 @@ use crate::{
     aptosnet::logging::{LogEntry, LogEvent, LogSchema},
     AdvertisedData, GlobalDataSummary, OptimalChunkSizes, ResponseError,
 };
-use aptos_config::{config::StorageServiceConfig, network_id::PeerNetworkId};
-use aptos_logger::debug;
-use std::collections::HashMap;
-use storage_service_types::{StorageServerSummary, StorageServiceRequest};
+use aptos_config::{
+    config::{AptosDataClientConfig, StorageServiceConfig},
+    network_id::PeerNetworkId,
+};
+use aptos_logger::{debug, warn};
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use storage_service_types::{CompleteDataRange, StorageServerSummaryV2, StorageServiceRequest};
 
 /// Scores for peer rankings based on preferences and behavior.
 const MAX_SCORE: f64 = 100.0;
@@ -22,6 +29,9 @@ const NOT_USEFUL_MULTIPLIER: f64 = 0.95;
 const MALICIOUS_MULTIPLIER: f64 = 0.8;
 /// Ignore a peer when their score dips below this threshold.
 const IGNORE_PEER_THRESHOLD: f64 = 25.0;
+/// The maximum number of times the blocklist duration is doubled for peers
+/// that are repeatedly blocklisted.
+const MAX_BLOCKLIST_DURATION_DOUBLINGS: u32 = 6;
 
 pub(crate) enum ErrorType {
     /// A response or error that's not actively malicious but also doesn't help
@@ -47,9 +57,21 @@ impl From<ResponseError> for ErrorType {
 struct PeerState {
     /// The latest observed advertised data for this peer, or `None` if we
     /// haven't polled them yet.
-    storage_summary: Option<StorageServerSummary>,
+    storage_summary: Option<StorageServerSummaryV2>,
     /// For now, a simplified port of the original state-sync v1 scoring system.
     score: f64,
+    /// The number of requests that have been sent to this peer and are still
+    /// waiting for a response.
+    in_flight_requests: u64,
+    /// The number of malicious responses observed from this peer that have
+    /// not yet decayed.
+    offences: u64,
+    /// The time at which the last offence was recorded (or decayed).
+    last_offence_time: Option<Instant>,
+    /// The number of times this peer has been blocklisted.
+    num_times_blocklisted: u32,
+    /// If the peer is blocklisted, the time at which the blocklisting expires.
+    blocklisted_until: Option<Instant>,
 }
 
 impl Default for PeerState {
@@ -57,12 +79,23 @@ impl Default for PeerState {
         Self {
             storage_summary: None,
             score: STARTING_SCORE,
+            in_flight_requests: 0,
+            offences: 0,
+            last_offence_time: None,
+            num_times_blocklisted: 0,
+            blocklisted_until: None,
         }
     }
 }
 
 impl PeerState {
-    fn storage_summary_if_not_ignored(&self) -> Option<&StorageServerSummary> {
+    fn is_blocklisted(&self, now: Instant) -> bool {
+        self.blocklisted_until
+            .map(|blocklisted_until| now < blocklisted_until)
+            .unwrap_or(false)
+    }
+
+    fn storage_summary_if_not_ignored(&self) -> Option<&StorageServerSummaryV2> {
         if self.score <= IGNORE_PEER_THRESHOLD {
             None
         } else {
@@ -70,6 +103,14 @@ impl PeerState {
         }
     }
 
+    /// The weight with which this peer should be selected for the next
+    /// request. Higher scoring peers are preferred, and the weight is divided
+    /// amongst in-flight requests so that concurrent requests are spread
+    /// across all serviceable peers.
+    fn selection_weight(&self) -> f64 {
+        self.score / (self.in_flight_requests + 1) as f64
+    }
+
     fn update_score_success(&mut self) {
         self.score = f64::min(self.score + SUCCESSFUL_RESPONSE_DELTA, MAX_SCORE);
     }
@@ -81,25 +122,88 @@ impl PeerState {
         };
         self.score = f64::max(self.score * multiplier, MIN_SCORE);
     }
+
+    /// Forgives a single offence for every full decay interval that has
+    /// elapsed since the last offence.
+    fn decay_offences(&mut self, now: Instant, decay_interval: Duration) {
+        if let Some(last_offence_time) = self.last_offence_time {
+            let decay_interval_ms = decay_interval.as_millis();
+            if decay_interval_ms == 0 {
+                self.offences = 0;
+                return;
+            }
+            let elapsed_ms = now.saturating_duration_since(last_offence_time).as_millis();
+            let num_decays = elapsed_ms / decay_interval_ms;
+            if num_decays > 0 {
+                self.offences = self.offences.saturating_sub(num_decays as u64);
+
+                // Carry over any partially elapsed decay interval
+                let remainder_ms = (elapsed_ms % decay_interval_ms) as u64;
+                self.last_offence_time = Some(now - Duration::from_millis(remainder_ms));
+            }
+        }
+    }
+
+    /// Records a new offence and blocklists the peer if it has too many
+    /// outstanding offences. Returns the blocklist expiry time if the peer was
+    /// newly blocklisted.
+    fn record_offence(&mut self, now: Instant, config: &AptosDataClientConfig) -> Option<Instant> {
+        self.decay_offences(
+            now,
+            Duration::from_millis(config.peer_offence_decay_interval_ms),
+        );
+        self.offences += 1;
+        self.last_offence_time = Some(now);
+        if self.offences < config.max_peer_offences {
+            return None;
+        }
+
+        // Repeat offenders are blocklisted for exponentially longer durations
+        let doublings = u32::min(self.num_times_blocklisted, MAX_BLOCKLIST_DURATION_DOUBLINGS);
+        let blocklist_duration =
+            Duration::from_millis(config.peer_blocklist_duration_ms) * 2u32.pow(doublings);
+        let blocklisted_until = now + blocklist_duration;
+        self.blocklisted_until = Some(blocklisted_until);
+        self.num_times_blocklisted += 1;
+        self.offences = 0;
+        Some(blocklisted_until)
+    }
 }
 
-/// Contains all of the unbanned peers' most recent [`StorageServerSummary`] data
+/// Contains all of the unbanned peers' most recent [`StorageServerSummaryV2`] data
 /// advertisements and data-client internal metadata for scoring.
 // TODO(philiphayes): this map needs to be garbage collected
 #[derive(Debug)]
 pub(crate) struct PeerStates {
     config: StorageServiceConfig,
+    data_client_config: AptosDataClientConfig,
+    time_service: TimeService,
     inner: HashMap<PeerNetworkId, PeerState>,
 }
 
 impl PeerStates {
-    pub fn new(config: StorageServiceConfig) -> Self {
+    pub fn new(
+        config: StorageServiceConfig,
+        data_client_config: AptosDataClientConfig,
+        time_service: TimeService,
+    ) -> Self {
         Self {
             config,
+            data_client_config,
+            time_service,
             inner: HashMap::new(),
         }
     }
 
+    /// Returns true iff the given peer is currently blocklisted
+    pub fn is_blocklisted(&self, peer: &PeerNetworkId) -> bool {
+        let now = self.time_service.now();
+        self.inner
+            .get(peer)
+            .map(|peer_state| peer_state.is_blocklisted(now))
+            .unwrap_or(false)
+    }
+
     /// Returns true if a connected storage service peer can actually fulfill a
     /// request, given our current view of their advertised data summary.
     pub fn can_service_request(
@@ -107,6 +211,12 @@ impl PeerStates {
         peer: &PeerNetworkId,
         request: &StorageServiceRequest,
     ) -> bool {
+        // Blocklisted peers are not sent any requests until the blocklisting
+        // expires.
+        if self.is_blocklisted(peer) {
+            return false;
+        }
+
         // Storage services can always respond to data advertisement requests.
         // We need this outer check, since we need to be able to send data summary
         // requests to new peers (who don't have a peer state yet).
@@ -121,6 +231,26 @@ impl PeerStates {
             .unwrap_or(false)
     }
 
+    /// Returns the weight with which the given peer should be selected to
+    /// service the next request.
+    pub fn get_selection_weight(&self, peer: &PeerNetworkId) -> f64 {
+        self.inner
+            .get(peer)
+            .map(PeerState::selection_weight)
+            .unwrap_or(STARTING_SCORE)
+    }
+
+    /// Tracks a new request sent to the given peer
+    pub fn start_in_flight_request(&mut self, peer: PeerNetworkId) {
+        self.inner.entry(peer).or_default().in_flight_requests += 1;
+    }
+
+    /// Tracks the completion (or cancellation) of a request sent to the given peer
+    pub fn finish_in_flight_request(&mut self, peer: PeerNetworkId) {
+        let peer_state = self.inner.entry(peer).or_default();
+        peer_state.in_flight_requests = peer_state.in_flight_requests.saturating_sub(1);
+    }
+
     pub fn update_score_success(&mut self, peer: PeerNetworkId) {
         let old_score = self.inner.entry(peer).or_default().score;
         self.inner.entry(peer).or_default().update_score_success();
@@ -136,12 +266,29 @@ impl PeerStates {
     }
 
     pub fn update_score_error(&mut self, peer: PeerNetworkId, error: ErrorType) {
-        let old_score = self.inner.entry(peer).or_default().score;
-        self.inner
-            .entry(peer)
-            .or_default()
-            .update_score_error(error);
-        let new_score = self.inner.entry(peer).or_default().score;
+        let now = self.time_service.now();
+        let peer_state = self.inner.entry(peer).or_default();
+        let old_score = peer_state.score;
+
+        // Repeated malicious responses get the peer temporarily blocklisted
+        if let ErrorType::Malicious = error {
+            if let Some(blocklisted_until) =
+                peer_state.record_offence(now, &self.data_client_config)
+            {
+                warn!(
+                    (LogSchema::new(LogEntry::PeerStates)
+                        .event(LogEvent::PeerBlocklisted)
+                        .message(&format!(
+                            "Peer will be blocklisted for {:?}",
+                            blocklisted_until.saturating_duration_since(now)
+                        ))
+                        .peer(&peer))
+                );
+            }
+        }
+
+        peer_state.update_score_error(error);
+        let new_score = peer_state.score;
         if old_score > IGNORE_PEER_THRESHOLD && new_score <= IGNORE_PEER_THRESHOLD {
             debug!(
                 (LogSchema::new(LogEntry::PeerStates)
@@ -152,8 +299,25 @@ impl PeerStates {
         }
     }
 
-    pub fn update_summary(&mut self, peer: PeerNetworkId, summary: StorageServerSummary) {
-        self.inner.entry(peer).or_default().storage_summary = Some(summary);
+    /// Updates the peer's data summary. Peers that advertise a synced ledger
+    /// info older than one they previously advertised are penalized for the
+    /// stale advertisement.
+    pub fn update_summary(&mut self, peer: PeerNetworkId, summary: StorageServerSummaryV2) {
+        let peer_state = self.inner.entry(peer).or_default();
+        let previous_version = peer_state
+            .storage_summary
+            .as_ref()
+            .and_then(synced_ledger_info_version);
+        let is_stale = match (previous_version, synced_ledger_info_version(&summary)) {
+            (Some(previous_version), Some(new_version)) => new_version < previous_version,
+            (Some(_), None) => true,
+            _ => false,
+        };
+        peer_state.storage_summary = Some(summary);
+
+        if is_stale {
+            self.update_score_error(peer, ErrorType::NotUseful);
+        }
     }
 
     pub fn aggregate_summary(&self) -> GlobalDataSummary {
@@ -165,17 +329,28 @@ impl PeerStates {
         let mut max_account_states_chunk_sizes = vec![];
 
         // only include likely-not-malicious peers in the data summary aggregation.
+        let now = self.time_service.now();
         let summaries = self
             .inner
             .values()
+            .filter(|peer_state| !peer_state.is_blocklisted(now))
             .filter_map(PeerState::storage_summary_if_not_ignored);
 
         // collect each peer's protocol and data advertisements
-        for summary in summaries {
+        for StorageServerSummaryV2 {
+            summary,
+            retained_account_states,
+        } in summaries
+        {
             // collect aggregate data advertisements
             if let Some(account_states) = summary.data_summary.account_states {
                 aggregate_data.account_states.push(account_states);
             }
+            for version in retained_account_states {
+                if let Ok(account_states) = CompleteDataRange::new(*version, *version) {
+                    aggregate_data.account_states.push(account_states);
+                }
+            }
             if let Some(epoch_ending_ledger_infos) = summary.data_summary.epoch_ending_ledger_infos
             {
                 aggregate_data
@@ -224,6 +399,15 @@ impl PeerStates {
     }
 }
 
+fn synced_ledger_info_version(summary: &StorageServerSummaryV2) -> Option<u64> {
+    summary
+        .summary
+        .data_summary
+        .synced_ledger_info
+        .as_ref()
+        .map(|ledger_info| ledger_info.ledger_info().version())
+}
+
 fn median<T: Ord + Copy>(values: &mut [T]) -> Option<T> {
     values.sort_unstable();
     let idx = values.len() / 2;
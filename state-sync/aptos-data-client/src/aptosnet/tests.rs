@@ -1,7 +1,10 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{AptosDataClient, AptosNetDataClient, DataSummaryPoller, Error};
+use super::{
+    latency_weight, state::ErrorType, AptosDataClient, AptosNetDataClient, DataSummaryPoller,
+    Error, InFlightRequest,
+};
 use aptos_config::{
     config::{AptosDataClientConfig, StorageServiceConfig},
     network_id::{NetworkId, PeerNetworkId},
@@ -28,8 +31,8 @@ use std::{collections::BTreeMap, sync::Arc, time::Duration};
 use storage_service_client::{StorageServiceClient, StorageServiceNetworkSender};
 use storage_service_server::network::{NetworkRequest, ResponseSender};
 use storage_service_types::{
-    CompleteDataRange, DataSummary, ProtocolMetadata, StorageServerSummary, StorageServiceError,
-    StorageServiceMessage, StorageServiceRequest, StorageServiceResponse,
+    CompleteDataRange, DataSummary, ProtocolMetadata, StorageServerSummary, StorageServerSummaryV2,
+    StorageServiceError, StorageServiceMessage, StorageServiceRequest, StorageServiceResponse,
     TransactionsWithProofRequest,
 };
 
@@ -43,21 +46,24 @@ fn mock_ledger_info(version: Version) -> LedgerInfoWithSignatures {
     )
 }
 
-fn mock_storage_summary(version: Version) -> StorageServerSummary {
-    StorageServerSummary {
-        protocol_metadata: ProtocolMetadata {
-            max_epoch_chunk_size: 1000,
-            max_transaction_chunk_size: 1000,
-            max_transaction_output_chunk_size: 1000,
-            max_account_states_chunk_size: 1000,
-        },
-        data_summary: DataSummary {
-            synced_ledger_info: Some(mock_ledger_info(version)),
-            epoch_ending_ledger_infos: None,
-            transactions: Some(CompleteDataRange::new(0, version).unwrap()),
-            transaction_outputs: None,
-            account_states: None,
+fn mock_storage_summary(version: Version) -> StorageServerSummaryV2 {
+    StorageServerSummaryV2 {
+        summary: StorageServerSummary {
+            protocol_metadata: ProtocolMetadata {
+                max_epoch_chunk_size: 1000,
+                max_transaction_chunk_size: 1000,
+                max_transaction_output_chunk_size: 1000,
+                max_account_states_chunk_size: 1000,
+            },
+            data_summary: DataSummary {
+                synced_ledger_info: Some(mock_ledger_info(version)),
+                epoch_ending_ledger_infos: None,
+                transactions: Some(CompleteDataRange::new(0, version).unwrap()),
+                transaction_outputs: None,
+                account_states: None,
+            },
         },
+        retained_account_states: vec![],
     }
 }
 
@@ -111,6 +117,14 @@ impl MockNetwork {
         PeerNetworkId::new(network_id, peer_id)
     }
 
+    /// Set the ping latency of a peer, as measured by the peer monitoring service
+    fn set_ping_latency(&mut self, peer: PeerNetworkId, latency: Duration) {
+        self.peer_infos
+            .update_monitoring_metadata(peer, |metadata| {
+                metadata.average_ping_latency = Some(latency)
+            });
+    }
+
     /// Get the next request sent from the client.
     async fn next_request(&mut self) -> Option<NetworkRequest> {
         match self.peer_mgr_reqs_rx.next().await {
@@ -167,10 +181,10 @@ async fn test_request_works_only_when_data_available() {
     let (peer, protocol, request, response_sender) = mock_network.next_request().await.unwrap();
     assert_eq!(peer, expected_peer.peer_id());
     assert_eq!(protocol, ProtocolId::StorageServiceRpc);
-    assert_matches!(request, StorageServiceRequest::GetStorageServerSummary);
+    assert_matches!(request, StorageServiceRequest::GetStorageServerSummaryV2);
 
     let summary = mock_storage_summary(200);
-    response_sender.send(Ok(StorageServiceResponse::StorageServerSummary(summary)));
+    response_sender.send(Ok(StorageServiceResponse::StorageServerSummaryV2(summary)));
 
     // let the poller finish processing the response
     tokio::task::yield_now().await;
@@ -205,6 +219,41 @@ async fn test_request_works_only_when_data_available() {
     assert_eq!(response.payload, TransactionListWithProof::new_empty());
 }
 
+#[tokio::test]
+async fn test_poller_falls_back_to_summary_v1() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, mock_time, client, poller) = MockNetwork::new();
+
+    tokio::spawn(poller.start());
+    mock_network.add_connected_peer();
+
+    // the peer fails to serve the versioned summary
+    tokio::task::yield_now().await;
+    mock_time.advance_async(Duration::from_millis(1_000)).await;
+    let (_, _, request, response_sender) = mock_network.next_request().await.unwrap();
+    assert_matches!(request, StorageServiceRequest::GetStorageServerSummaryV2);
+    response_sender.send(Err(StorageServiceError::InternalError(
+        "unknown request".into(),
+    )));
+
+    // so it's polled for the original summary afterwards
+    tokio::task::yield_now().await;
+    mock_time.advance_async(Duration::from_millis(1_000)).await;
+    let (_, _, request, response_sender) = mock_network.next_request().await.unwrap();
+    assert_matches!(request, StorageServiceRequest::GetStorageServerSummary);
+    response_sender.send(Ok(StorageServiceResponse::StorageServerSummary(
+        mock_storage_summary(200).summary,
+    )));
+
+    // let the poller finish processing the response
+    tokio::task::yield_now().await;
+    let global_summary = client.get_global_data_summary();
+    assert!(global_summary
+        .advertised_data
+        .transactions
+        .contains(&CompleteDataRange::new(0, 200).unwrap()));
+}
+
 // 1. 2 peers
 // 2. one advertises bad range, one advertises honest range
 // 3. sending a bunch of requests to the bad range (which will always go to the
@@ -370,8 +419,8 @@ async fn bad_peer_is_eventually_added_back() {
                         TransactionListWithProof::new_empty(),
                     )))
                 }
-                StorageServiceRequest::GetStorageServerSummary => response_sender.send(Ok(
-                    StorageServiceResponse::StorageServerSummary(mock_storage_summary(200)),
+                StorageServiceRequest::GetStorageServerSummaryV2 => response_sender.send(Ok(
+                    StorageServiceResponse::StorageServerSummaryV2(mock_storage_summary(200)),
                 )),
                 _ => panic!("unexpected: {:?}", request),
             }
@@ -423,3 +472,219 @@ async fn bad_peer_is_eventually_added_back() {
         .transactions
         .contains(&CompleteDataRange::new(0, 200).unwrap()));
 }
+
+#[tokio::test]
+async fn high_scoring_peers_are_preferred() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, _mock_time, client, _poller) = MockNetwork::new();
+
+    // Both peers advertise the same data.
+    let good_peer = mock_network.add_connected_peer();
+    let poor_peer = mock_network.add_connected_peer();
+    client.update_summary(good_peer, mock_storage_summary(200));
+    client.update_summary(poor_peer, mock_storage_summary(200));
+
+    // Raise the good peer's score to the max and lower the poor peer's score
+    // to just above the ignore threshold.
+    {
+        let mut peer_states = client.peer_states.write();
+        for _ in 0..50 {
+            peer_states.update_score_success(good_peer);
+        }
+        for _ in 0..12 {
+            peer_states.update_score_error(poor_peer, ErrorType::NotUseful);
+        }
+    }
+
+    // The good peer should be chosen for the vast majority of requests.
+    let request = StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+        proof_version: 200,
+        start_version: 0,
+        end_version: 200,
+        include_events: false,
+    });
+    let num_good_peer_choices = (0..1000)
+        .filter(|_| client.choose_peer(&request).unwrap() == good_peer)
+        .count();
+    assert!(num_good_peer_choices > 650, "{}", num_good_peer_choices);
+}
+
+#[tokio::test]
+async fn low_latency_peers_are_preferred() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, _mock_time, client, _poller) = MockNetwork::new();
+
+    // Both peers advertise the same data and have the same score.
+    let near_peer = mock_network.add_connected_peer();
+    let far_peer = mock_network.add_connected_peer();
+    client.update_summary(near_peer, mock_storage_summary(200));
+    client.update_summary(far_peer, mock_storage_summary(200));
+    mock_network.set_ping_latency(near_peer, Duration::from_millis(10));
+    mock_network.set_ping_latency(far_peer, Duration::from_millis(500));
+
+    // The near peer should be chosen for the vast majority of requests.
+    let request = StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+        proof_version: 200,
+        start_version: 0,
+        end_version: 200,
+        include_events: false,
+    });
+    let num_near_peer_choices = (0..1000)
+        .filter(|_| client.choose_peer(&request).unwrap() == near_peer)
+        .count();
+    assert!(num_near_peer_choices > 750, "{}", num_near_peer_choices);
+}
+
+#[test]
+fn test_latency_weight() {
+    assert!((latency_weight(None) - 1.0).abs() < f64::EPSILON);
+    assert!((latency_weight(Some(Duration::from_millis(100))) - 0.5).abs() < f64::EPSILON);
+    assert!(latency_weight(Some(Duration::from_millis(10))) > 0.9);
+}
+
+#[tokio::test]
+async fn requests_are_spread_across_peers() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, _mock_time, client, _poller) = MockNetwork::new();
+
+    // Both peers advertise the same data and have the same score.
+    let busy_peer = mock_network.add_connected_peer();
+    let idle_peer = mock_network.add_connected_peer();
+    client.update_summary(busy_peer, mock_storage_summary(200));
+    client.update_summary(idle_peer, mock_storage_summary(200));
+
+    // Mark several requests to the busy peer as in-flight.
+    let in_flight_requests: Vec<_> = (0..4)
+        .map(|_| InFlightRequest::new(client.peer_states.clone(), busy_peer))
+        .collect();
+
+    // The idle peer should be chosen for the vast majority of requests.
+    let request = StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+        proof_version: 200,
+        start_version: 0,
+        end_version: 200,
+        include_events: false,
+    });
+    let num_idle_peer_choices = (0..1000)
+        .filter(|_| client.choose_peer(&request).unwrap() == idle_peer)
+        .count();
+    assert!(num_idle_peer_choices > 700, "{}", num_idle_peer_choices);
+
+    // Once the requests complete, both peers are chosen equally often.
+    drop(in_flight_requests);
+    let num_idle_peer_choices = (0..1000)
+        .filter(|_| client.choose_peer(&request).unwrap() == idle_peer)
+        .count();
+    assert!(
+        (350..650).contains(&num_idle_peer_choices),
+        "{}",
+        num_idle_peer_choices
+    );
+}
+
+#[tokio::test]
+async fn repeat_offenders_are_blocklisted() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, mock_time, client, _poller) = MockNetwork::new();
+
+    // Add a high scoring peer that advertises data
+    let peer = mock_network.add_connected_peer();
+    client.update_summary(peer, mock_storage_summary(200));
+    {
+        let mut peer_states = client.peer_states.write();
+        for _ in 0..50 {
+            peer_states.update_score_success(peer);
+        }
+    }
+
+    // Verify the peer can service requests until it hits the offence limit
+    let data_client_config = AptosDataClientConfig::default();
+    let request = StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+        proof_version: 200,
+        start_version: 0,
+        end_version: 200,
+        include_events: false,
+    });
+    for _ in 0..data_client_config.max_peer_offences - 1 {
+        client
+            .peer_states
+            .write()
+            .update_score_error(peer, ErrorType::Malicious);
+        assert_eq!(client.choose_peer(&request).unwrap(), peer);
+    }
+    client
+        .peer_states
+        .write()
+        .update_score_error(peer, ErrorType::Malicious);
+
+    // The peer is now blocklisted (even for summary requests) and its
+    // advertisement is no longer included in the global summary.
+    assert!(client.peer_states.read().is_blocklisted(&peer));
+    assert_matches!(
+        client.choose_peer(&request),
+        Err(Error::DataIsUnavailable(_))
+    );
+    assert_matches!(
+        client.choose_peer(&StorageServiceRequest::GetStorageServerSummary),
+        Err(Error::DataIsUnavailable(_))
+    );
+    client.update_global_summary_cache();
+    assert!(client
+        .get_global_data_summary()
+        .advertised_data
+        .transactions
+        .is_empty());
+
+    // Once the blocklist duration elapses, the peer is usable again
+    mock_time
+        .advance_async(Duration::from_millis(
+            data_client_config.peer_blocklist_duration_ms,
+        ))
+        .await;
+    assert!(!client.peer_states.read().is_blocklisted(&peer));
+    assert_eq!(client.choose_peer(&request).unwrap(), peer);
+
+    // Offences decay over time, so sporadic bad responses don't blocklist
+    for _ in 0..data_client_config.max_peer_offences {
+        client
+            .peer_states
+            .write()
+            .update_score_error(peer, ErrorType::Malicious);
+        mock_time
+            .advance_async(Duration::from_millis(
+                data_client_config.peer_offence_decay_interval_ms,
+            ))
+            .await;
+    }
+    assert!(!client.peer_states.read().is_blocklisted(&peer));
+}
+
+#[tokio::test]
+async fn stale_advertisements_are_penalized() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, _mock_time, client, _poller) = MockNetwork::new();
+
+    // Both peers advertise the same data
+    let honest_peer = mock_network.add_connected_peer();
+    let stale_peer = mock_network.add_connected_peer();
+    client.update_summary(honest_peer, mock_storage_summary(200));
+    client.update_summary(stale_peer, mock_storage_summary(200));
+
+    // The stale peer keeps regressing its advertised version
+    client.update_summary(honest_peer, mock_storage_summary(300));
+    for version in (50..200).rev().step_by(10) {
+        client.update_summary(stale_peer, mock_storage_summary(version));
+    }
+
+    // The stale peer should eventually be ignored, even though it still
+    // advertises the requested data.
+    let request = StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+        proof_version: 50,
+        start_version: 0,
+        end_version: 50,
+        include_events: false,
+    });
+    for _ in 0..100 {
+        assert_eq!(client.choose_peer(&request).unwrap(), honest_peer);
+    }
+}
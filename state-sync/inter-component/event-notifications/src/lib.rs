@@ -11,7 +11,7 @@ use aptos_types::{
     event::EventKey,
     move_resource::MoveStorage,
     on_chain_config,
-    on_chain_config::{config_address, ConfigID, OnChainConfigPayload},
+    on_chain_config::{config_address, ConfigID, OnChainConfig, OnChainConfigPayload},
     transaction::Version,
 };
 use channel::{aptos_channel, message_queues::QueueStyle};
@@ -20,6 +20,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, HashSet},
     convert::TryFrom,
+    fmt::Debug,
     iter::FromIterator,
     ops::Deref,
     pin::Pin,
@@ -35,6 +36,8 @@ mod tests;
 // Maximum channel sizes for each notification subscriber. If messages are not
 // consumed, they will be dropped (oldest messages first). The remaining messages
 // will be retrieved using FIFO ordering.
+const COMMIT_NOTIFICATION_CHANNEL_SIZE: usize = 1;
+const CONFIG_NOTIFICATION_CHANNEL_SIZE: usize = 1;
 const EVENT_NOTIFICATION_CHANNEL_SIZE: usize = 100;
 const RECONFIG_NOTIFICATION_CHANNEL_SIZE: usize = 1;
 
@@ -60,6 +63,9 @@ impl From<SendError> for Error {
 /// allowing state sync to notify the subscription service of new events.
 pub trait EventNotificationSender: Send {
     /// Notify the subscription service of the events at the specified version.
+    /// This is expected to be called every time state sync commits new data to
+    /// storage (even if no events were committed), as commit subscribers are
+    /// notified of the new synced version.
     fn notify_events(&mut self, version: Version, events: Vec<ContractEvent>) -> Result<(), Error>;
 
     /// Forces the subscription service to notify subscribers of the current
@@ -70,8 +76,32 @@ pub trait EventNotificationSender: Send {
 }
 
 /// The subscription service offered by state sync, responsible for notifying
-/// subscribers of on-chain events.
+/// subscribers (e.g., mempool, consensus and the api) of state sync progress.
+///
+/// Four types of subscriptions are offered:
+/// 1. Commit subscriptions: notified of the latest synced version every time
+///    state sync commits new data to storage.
+/// 2. Event subscriptions: notified of all committed events that match the
+///    subscribed event keys.
+/// 3. Reconfig subscriptions: notified of the new on-chain configurations
+///    every time a new epoch begins (and on startup).
+/// 4. Config subscriptions: notified of the old and new values of a single
+///    on-chain configuration whenever it changes at a new epoch (and on startup).
+///
+/// The delivery contract is the same for all subscriptions: notifications are
+/// delivered in the order they were committed, but notifications are never
+/// allowed to block state sync. Commit subscribers are notified of a version
+/// after the other subscribers are sent its notifications. Instead, each subscriber has a bounded buffer
+/// and if the buffer fills up, the oldest notifications are dropped. Commit
+/// and reconfig subscribers only buffer the latest notification (as newer
+/// notifications supersede older ones), as do config subscribers (whose
+/// notifications always diff against the last value sent, even if the
+/// subscriber missed it), while event subscribers buffer up to
+/// `EVENT_NOTIFICATION_CHANNEL_SIZE` notifications.
 pub struct EventSubscriptionService {
+    // Commit subscription registry
+    commit_subscriptions: HashMap<SubscriptionId, CommitSubscription>,
+
     // Event subscription registry
     event_key_subscriptions: HashMap<EventKey, HashSet<SubscriptionId>>,
     subscription_id_to_event_subscription: HashMap<SubscriptionId, EventSubscription>,
@@ -79,6 +109,9 @@ pub struct EventSubscriptionService {
     // Reconfig subscription registry
     reconfig_subscriptions: HashMap<SubscriptionId, ReconfigSubscription>,
 
+    // Config subscription registry
+    config_subscriptions: HashMap<SubscriptionId, Box<dyn ConfigSubscription>>,
+
     // Database to fetch on-chain configuration data
     storage: Arc<RwLock<DbReaderWriter>>,
 
@@ -92,9 +125,11 @@ pub struct EventSubscriptionService {
 impl EventSubscriptionService {
     pub fn new(config_registry: &[ConfigID], storage: Arc<RwLock<DbReaderWriter>>) -> Self {
         Self {
+            commit_subscriptions: HashMap::new(),
             event_key_subscriptions: HashMap::new(),
             subscription_id_to_event_subscription: HashMap::new(),
             reconfig_subscriptions: HashMap::new(),
+            config_subscriptions: HashMap::new(),
             config_registry: config_registry.to_vec(),
             storage,
             subscription_id_generator: U64IdGenerator::new(),
@@ -186,10 +221,97 @@ impl EventSubscriptionService {
         })
     }
 
+    /// Returns a ConfigNotificationListener that can be monitored for changes
+    /// to the on-chain config `T`. Subscribers will be sent a notification
+    /// containing the old and new values of the config whenever a new epoch
+    /// changes it (including when it is created or removed), as well as the
+    /// first time the configs are read (e.g., on startup). If `T` is not part
+    /// of the config registry, it is added so that it is fetched on every
+    /// reconfiguration. Note: only the latest notification is buffered, but
+    /// each notification diffs against the last value sent to the subscriber.
+    pub fn subscribe_to_config<T>(&mut self) -> Result<ConfigNotificationListener<T>, Error>
+    where
+        T: OnChainConfig + Clone + Debug + PartialEq + 'static,
+    {
+        let (notification_sender, notification_receiver) =
+            aptos_channel::new(QueueStyle::KLAST, CONFIG_NOTIFICATION_CHANNEL_SIZE, None);
+
+        // Make sure the config is fetched at every reconfiguration
+        if !self.config_registry.contains(&T::CONFIG_ID) {
+            self.config_registry.push(T::CONFIG_ID);
+        }
+
+        // Create a new config subscription
+        let subscription_id = self.get_new_subscription_id();
+        let config_subscription = TypedConfigSubscription {
+            subscription_id,
+            notification_sender,
+            last_config: None,
+            notified: false,
+        };
+
+        // Store the new subscription
+        if let Some(old_subscription) = self
+            .config_subscriptions
+            .insert(subscription_id, Box::new(config_subscription))
+        {
+            panic!(
+                "Duplicate config subscription found! This should not occur! ID: {}, subscription: {:?}",
+                subscription_id, old_subscription
+            );
+        }
+
+        Ok(ConfigNotificationListener {
+            notification_receiver,
+        })
+    }
+
+    /// Returns a CommitNotificationListener that can be monitored for
+    /// commits. Subscribers will be sent a notification containing the latest
+    /// synced version every time state sync commits new data to storage.
+    /// Note: only the latest notification is buffered, so if the subscriber
+    /// is slow to process notifications, intermediate commits will be skipped.
+    pub fn subscribe_to_commits(&mut self) -> Result<CommitNotificationListener, Error> {
+        let (notification_sender, notification_receiver) =
+            aptos_channel::new(QueueStyle::KLAST, COMMIT_NOTIFICATION_CHANNEL_SIZE, None);
+
+        // Create a new commit subscription
+        let subscription_id = self.get_new_subscription_id();
+        let commit_subscription = CommitSubscription {
+            subscription_id,
+            notification_sender,
+        };
+
+        // Store the new subscription
+        if let Some(old_subscription) = self
+            .commit_subscriptions
+            .insert(subscription_id, commit_subscription)
+        {
+            panic!(
+                "Duplicate commit subscription found! This should not occur! ID: {}, subscription: {:?}",
+                subscription_id, old_subscription
+            );
+        }
+
+        Ok(CommitNotificationListener {
+            notification_receiver,
+        })
+    }
+
     fn get_new_subscription_id(&mut self) -> u64 {
         self.subscription_id_generator.next()
     }
 
+    /// This notifies all the commit subscribers of the new synced version.
+    /// Subscriptions whose listener has been dropped are removed.
+    fn notify_commit_subscribers(&mut self, version: Version) {
+        self.commit_subscriptions.retain(|_, commit_subscription| {
+            commit_subscription
+                .notify_subscriber_of_commit(version)
+                .is_ok()
+        });
+    }
+
     /// This notifies all the event subscribers of the new events found at the
     /// specified version. If a reconfiguration event (i.e., new epoch) is found,
     /// this method will return true.
@@ -244,13 +366,20 @@ impl EventSubscriptionService {
     }
 
     /// This notifies all the reconfiguration subscribers of the on-chain
-    /// configurations at the specified version.
+    /// configurations at the specified version, and all the config subscribers
+    /// whose config has changed. Config subscriptions whose listener has been
+    /// dropped are removed.
     fn notify_reconfiguration_subscribers(&mut self, version: Version) -> Result<(), Error> {
-        if self.reconfig_subscriptions.is_empty() {
+        if self.reconfig_subscriptions.is_empty() && self.config_subscriptions.is_empty() {
             return Ok(()); // No reconfiguration subscribers!
         }
 
         let new_configs = self.read_on_chain_configs(version)?;
+        self.config_subscriptions.retain(|_, config_subscription| {
+            config_subscription
+                .notify_subscriber_of_config(version, &new_configs)
+                .is_ok()
+        });
         for (_, reconfig_subscription) in self.reconfig_subscriptions.iter_mut() {
             reconfig_subscription.notify_subscriber_of_configs(version, new_configs.clone())?;
         }
@@ -327,20 +456,21 @@ impl EventSubscriptionService {
 
 impl EventNotificationSender for EventSubscriptionService {
     fn notify_events(&mut self, version: Version, events: Vec<ContractEvent>) -> Result<(), Error> {
-        if events.is_empty() {
-            return Ok(()); // No events!
+        if !events.is_empty() {
+            // Notify event subscribers and check if a reconfiguration event was processed
+            let reconfig_event_processed = self.notify_event_subscribers(version, events)?;
+
+            // If a reconfiguration event was found, also notify the reconfig subscribers
+            // of the new configuration values.
+            if reconfig_event_processed {
+                self.notify_reconfiguration_subscribers(version)?;
+            }
         }
 
-        // Notify event subscribers and check if a reconfiguration event was processed
-        let reconfig_event_processed = self.notify_event_subscribers(version, events)?;
-
-        // If a reconfiguration event was found, also notify the reconfig subscribers
-        // of the new configuration values.
-        if reconfig_event_processed {
-            self.notify_reconfiguration_subscribers(version)
-        } else {
-            Ok(())
-        }
+        // Notify the commit subscribers of the new synced version last, so that
+        // the events and configs of the version are sent before it.
+        self.notify_commit_subscribers(version);
+        Ok(())
     }
 
     fn notify_initial_configs(&mut self, version: Version) -> Result<(), Error> {
@@ -351,6 +481,24 @@ impl EventNotificationSender for EventSubscriptionService {
 /// A unique ID used to identify each subscription.
 type SubscriptionId = u64;
 
+/// A single commit subscription, holding the channel to send the corresponding
+/// notifications.
+#[derive(Debug)]
+struct CommitSubscription {
+    pub subscription_id: SubscriptionId,
+    pub notification_sender: channel::aptos_channel::Sender<(), CommitNotification>,
+}
+
+impl CommitSubscription {
+    fn notify_subscriber_of_commit(&mut self, version: Version) -> Result<(), Error> {
+        let commit_notification = CommitNotification { version };
+
+        self.notification_sender
+            .push((), commit_notification)
+            .map_err(|error| Error::UnexpectedErrorEncountered(format!("{:?}", error)))
+    }
+}
+
 /// A single event subscription, holding the subscription identifier, channel to
 /// send the corresponding notifications and a buffer to hold pending events.
 #[derive(Debug)]
@@ -402,6 +550,63 @@ impl ReconfigSubscription {
     }
 }
 
+/// A type-erased config subscription, allowing subscriptions to configs of
+/// different types to be stored in the same registry.
+trait ConfigSubscription: Debug + Send + Sync {
+    /// Notifies the subscriber if its config has changed in the given payload.
+    fn notify_subscriber_of_config(
+        &mut self,
+        version: Version,
+        on_chain_configs: &OnChainConfigPayload,
+    ) -> Result<(), Error>;
+}
+
+/// A single config subscription, holding the channel to send the
+/// corresponding notifications and the last config value sent.
+#[derive(Debug)]
+struct TypedConfigSubscription<T> {
+    pub subscription_id: SubscriptionId,
+    pub notification_sender: channel::aptos_channel::Sender<(), ConfigNotification<T>>,
+    pub last_config: Option<T>,
+    pub notified: bool,
+}
+
+impl<T> ConfigSubscription for TypedConfigSubscription<T>
+where
+    T: OnChainConfig + Clone + Debug + PartialEq + 'static,
+{
+    fn notify_subscriber_of_config(
+        &mut self,
+        version: Version,
+        on_chain_configs: &OnChainConfigPayload,
+    ) -> Result<(), Error> {
+        // A config that is missing from the payload, or that fails to
+        // deserialize, is treated as not existing on-chain.
+        let new_config = on_chain_configs.get::<T>().ok();
+        if self.notified && new_config == self.last_config {
+            return Ok(()); // The config hasn't changed!
+        }
+
+        let config_notification = ConfigNotification {
+            version,
+            epoch: on_chain_configs.epoch(),
+            old_config: std::mem::replace(&mut self.last_config, new_config.clone()),
+            new_config,
+        };
+        self.notified = true;
+
+        self.notification_sender
+            .push((), config_notification)
+            .map_err(|error| Error::UnexpectedErrorEncountered(format!("{:?}", error)))
+    }
+}
+
+/// A notification for new data committed to storage.
+#[derive(Debug)]
+pub struct CommitNotification {
+    pub version: Version,
+}
+
 /// A notification for events.
 #[derive(Debug)]
 pub struct EventNotification {
@@ -416,12 +621,29 @@ pub struct ReconfigNotification {
     pub on_chain_configs: OnChainConfigPayload,
 }
 
+/// A notification for a change to the on-chain config `T`, holding the value
+/// previously sent to the subscriber (if any) and the value at the new epoch
+/// (if any).
+#[derive(Debug)]
+pub struct ConfigNotification<T> {
+    pub version: Version,
+    pub epoch: u64,
+    pub old_config: Option<T>,
+    pub new_config: Option<T>,
+}
+
+/// A subscription listener for commits.
+pub type CommitNotificationListener = NotificationListener<CommitNotification>;
+
 /// A subscription listener for on-chain events.
 pub type EventNotificationListener = NotificationListener<EventNotification>;
 
 /// A subscription listener for reconfigurations.
 pub type ReconfigNotificationListener = NotificationListener<ReconfigNotification>;
 
+/// A subscription listener for changes to the on-chain config `T`.
+pub type ConfigNotificationListener<T> = NotificationListener<ConfigNotification<T>>;
+
 /// The component responsible for listening to subscription notifications.
 #[derive(Debug)]
 pub struct NotificationListener<T> {
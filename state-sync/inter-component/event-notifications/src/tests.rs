@@ -4,8 +4,8 @@
 #![forbid(unsafe_code)]
 
 use crate::{
-    Error, EventNotificationListener, EventNotificationSender, EventSubscriptionService,
-    ReconfigNotificationListener,
+    CommitNotificationListener, ConfigNotificationListener, Error, EventNotificationListener,
+    EventNotificationSender, EventSubscriptionService, ReconfigNotificationListener,
 };
 use aptos_infallible::RwLock;
 use aptos_types::{
@@ -98,6 +98,41 @@ fn test_reconfig_notification_no_queuing() {
     assert_eq!(notification_count, 1);
 }
 
+#[test]
+fn test_commit_subscribers() {
+    // Create subscription service and mock database
+    let mut event_service = create_event_subscription_service();
+
+    // Create commit subscribers
+    let mut listener_1 = event_service.subscribe_to_commits().unwrap();
+    let mut listener_2 = event_service.subscribe_to_commits().unwrap();
+
+    // Notify the subscription service of a commit without any events and
+    // verify the notifications are received.
+    notify_events(&mut event_service, 10, vec![]);
+    verify_commit_notifications_received(vec![&mut listener_1, &mut listener_2], 10);
+
+    // Notify the subscription service of several commits (with events) and
+    // verify only the latest commit notification is received (i.e., older
+    // commit notifications were dropped).
+    let event = create_test_event(create_random_event_key());
+    for version in 11..20 {
+        notify_events(&mut event_service, version, vec![event.clone()]);
+    }
+    verify_commit_notifications_received(vec![&mut listener_1, &mut listener_2], 19);
+    verify_no_commit_notifications(vec![&mut listener_1, &mut listener_2]);
+
+    // Verify that forced reconfigurations don't notify commit subscribers
+    notify_initial_configs(&mut event_service, 19);
+    verify_no_commit_notifications(vec![&mut listener_1, &mut listener_2]);
+
+    // Verify that dropped subscribers are removed without affecting the others
+    drop(listener_1);
+    notify_events(&mut event_service, 20, vec![]);
+    verify_commit_notifications_received(vec![&mut listener_2], 20);
+    assert_eq!(event_service.commit_subscriptions.len(), 1);
+}
+
 #[test]
 fn test_dynamic_subscribers() {
     // Create subscription service and mock database
@@ -413,6 +448,97 @@ fn test_missing_configs() {
     }
 }
 
+#[test]
+fn test_config_subscribers() {
+    // Create subscription service and mock database
+    let mut event_service = create_event_subscription_service();
+
+    // Create config subscribers for a config that exists on-chain
+    let mut listener_1 = event_service
+        .subscribe_to_config::<on_chain_config::Version>()
+        .unwrap();
+    let mut listener_2 = event_service
+        .subscribe_to_config::<on_chain_config::Version>()
+        .unwrap();
+
+    // Verify the first notification contains the on-chain value and no old value
+    notify_initial_configs(&mut event_service, 0);
+    let mut expected_config = None;
+    for listener in [&mut listener_1, &mut listener_2] {
+        if let Some(config_notification) = listener.select_next_some().now_or_never() {
+            assert_eq!(config_notification.version, 0);
+            assert_eq!(config_notification.epoch, 1);
+            assert_eq!(config_notification.old_config, None);
+            assert!(config_notification.new_config.is_some());
+            expected_config = config_notification.new_config;
+        } else {
+            panic!("Expected a config notification but got None!");
+        }
+    }
+
+    // Verify that reconfigurations leaving the config unchanged don't notify the subscribers
+    let reconfig_event = create_test_event(on_chain_config::new_epoch_event_key());
+    for _ in 0..10 {
+        notify_initial_configs(&mut event_service, 0);
+        notify_events(&mut event_service, 0, vec![reconfig_event.clone()]);
+        verify_no_config_notifications(vec![&mut listener_1, &mut listener_2]);
+    }
+
+    // Verify a new subscriber is notified of the current value, without re-notifying others
+    let mut listener_3 = event_service
+        .subscribe_to_config::<on_chain_config::Version>()
+        .unwrap();
+    notify_events(&mut event_service, 0, vec![reconfig_event]);
+    if let Some(config_notification) = listener_3.select_next_some().now_or_never() {
+        assert_eq!(config_notification.old_config, None);
+        assert_eq!(config_notification.new_config, expected_config);
+    } else {
+        panic!("Expected a config notification but got None!");
+    }
+    verify_no_config_notifications(vec![&mut listener_1, &mut listener_2]);
+
+    // Verify that dropped subscribers are removed without affecting the others
+    let listener_4 = event_service
+        .subscribe_to_config::<on_chain_config::Version>()
+        .unwrap();
+    drop(listener_4);
+    notify_initial_configs(&mut event_service, 0);
+    assert_eq!(event_service.config_subscriptions.len(), 3);
+    verify_no_config_notifications(vec![&mut listener_1, &mut listener_2, &mut listener_3]);
+}
+
+#[test]
+fn test_config_subscribers_missing_config() {
+    // Create subscription service and mock database
+    let mut event_service = create_event_subscription_service();
+
+    // Subscribe to a config that does not exist on-chain and verify it was registered
+    let mut config_listener = event_service
+        .subscribe_to_config::<TestOnChainConfig>()
+        .unwrap();
+    assert!(event_service
+        .config_registry
+        .contains(&TestOnChainConfig::CONFIG_ID));
+
+    // Verify the first notification reports the config as missing
+    notify_initial_configs(&mut event_service, 0);
+    if let Some(config_notification) = config_listener.select_next_some().now_or_never() {
+        assert_eq!(config_notification.old_config, None);
+        assert_eq!(config_notification.new_config, None);
+    } else {
+        panic!("Expected a config notification but got None!");
+    }
+
+    // Verify subsequent reconfigurations don't notify the subscriber
+    notify_initial_configs(&mut event_service, 0);
+    verify_no_config_notifications(vec![&mut config_listener]);
+
+    // Verify reconfig subscribers still receive all the configs found on-chain
+    let mut reconfig_listener = event_service.subscribe_to_reconfigurations().unwrap();
+    notify_initial_configs(&mut event_service, 0);
+    verify_reconfig_notifications_received(vec![&mut reconfig_listener], 0, 1);
+}
+
 /// Defines a new on-chain config for test purposes.
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub struct TestOnChainConfig {
@@ -462,6 +588,13 @@ fn count_reconfig_notifications(listener: &mut ReconfigNotificationListener) ->
     notification_count
 }
 
+// Ensures that no commit notifications have been received by the listeners
+fn verify_no_commit_notifications(listeners: Vec<&mut CommitNotificationListener>) {
+    for listener in listeners {
+        assert!(listener.select_next_some().now_or_never().is_none());
+    }
+}
+
 // Ensures that no event notifications have been received by the listeners
 fn verify_no_event_notifications(listeners: Vec<&mut EventNotificationListener>) {
     for listener in listeners {
@@ -469,6 +602,13 @@ fn verify_no_event_notifications(listeners: Vec<&mut EventNotificationListener>)
     }
 }
 
+// Ensures that no config notifications have been received by the listeners
+fn verify_no_config_notifications<T>(listeners: Vec<&mut ConfigNotificationListener<T>>) {
+    for listener in listeners {
+        assert!(listener.select_next_some().now_or_never().is_none());
+    }
+}
+
 // Ensures that no reconfig notifications have been received by the listeners
 fn verify_no_reconfig_notifications(listeners: Vec<&mut ReconfigNotificationListener>) {
     for listener in listeners {
@@ -476,6 +616,20 @@ fn verify_no_reconfig_notifications(listeners: Vec<&mut ReconfigNotificationList
     }
 }
 
+// Ensures that the specified listeners have received the expected notifications.
+fn verify_commit_notifications_received(
+    listeners: Vec<&mut CommitNotificationListener>,
+    expected_version: Version,
+) {
+    for listener in listeners {
+        if let Some(commit_notification) = listener.select_next_some().now_or_never() {
+            assert_eq!(commit_notification.version, expected_version);
+        } else {
+            panic!("Expected a commit notification but got None!");
+        }
+    }
+}
+
 // Ensures that the specified listeners have received the expected notifications.
 fn verify_event_notification_received(
     listeners: Vec<&mut EventNotificationListener>,
@@ -3,6 +3,7 @@
 
 #![forbid(unsafe_code)]
 
+use aptos_crypto::HashValue;
 use aptos_types::{account_address::AccountAddress, transaction::Transaction};
 use async_trait::async_trait;
 use futures::{
@@ -88,6 +89,7 @@ impl MempoolNotificationSender for MempoolNotifier {
                 Transaction::UserTransaction(signed_txn) => Some(CommittedTransaction {
                     sender: signed_txn.sender(),
                     sequence_number: signed_txn.sequence_number(),
+                    hash: signed_txn.clone().committed_hash(),
                 }),
                 _ => None,
             })
@@ -197,6 +199,7 @@ impl fmt::Display for MempoolCommitNotification {
 pub struct CommittedTransaction {
     pub sender: AccountAddress,
     pub sequence_number: u64,
+    pub hash: HashValue,
 }
 
 impl fmt::Display for CommittedTransaction {
@@ -323,6 +326,7 @@ mod tests {
                         vec![CommittedTransaction {
                             sender: signed_transaction.sender(),
                             sequence_number: signed_transaction.sequence_number(),
+                            hash: signed_transaction.clone().committed_hash(),
                         }]
                     );
                     assert_eq!(
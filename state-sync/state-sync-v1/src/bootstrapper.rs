@@ -7,6 +7,7 @@ use crate::{
     network::{StateSyncEvents, StateSyncSender},
 };
 use aptos_config::{config::NodeConfig, network_id::NetworkId};
+use aptos_metrics::runtime::instrumented_runtime;
 use aptos_types::waypoint::Waypoint;
 use consensus_notifications::ConsensusNotificationListener;
 use event_notifications::EventSubscriptionService;
@@ -15,7 +16,7 @@ use futures::channel::mpsc;
 use mempool_notifications::MempoolNotificationSender;
 use std::{collections::HashMap, sync::Arc};
 use storage_interface::DbReader;
-use tokio::runtime::{Builder, Runtime};
+use tokio::runtime::Runtime;
 
 /// Creates and bootstraps new state syncs and creates clients for
 /// communicating with those state syncs.
@@ -36,11 +37,11 @@ impl StateSyncBootstrapper {
         event_subscription_service: EventSubscriptionService,
         read_only_mode: bool,
     ) -> Self {
-        let runtime = Builder::new_multi_thread()
-            .thread_name("state-sync-v1")
-            .enable_all()
-            .build()
-            .expect("[State Sync] Failed to create runtime!");
+        let runtime = instrumented_runtime(
+            "state-sync-v1",
+            node_config.runtimes.state_sync_worker_threads,
+            node_config.runtimes.stall_threshold(),
+        );
 
         let executor_proxy =
             ExecutorProxy::new(storage, chunk_executor, event_subscription_service);
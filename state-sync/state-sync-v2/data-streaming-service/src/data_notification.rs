@@ -38,6 +38,8 @@ pub enum DataPayload {
 pub enum DataClientRequest {
     AccountsWithProof(AccountsWithProofRequest),
     EpochEndingLedgerInfos(EpochEndingLedgerInfosRequest),
+    NewTransactionOutputsWithProof(NewTransactionOutputsWithProofRequest),
+    NewTransactionsWithProof(NewTransactionsWithProofRequest),
     NumberOfAccounts(NumberOfAccountsRequest),
     TransactionsWithProof(TransactionsWithProofRequest),
     TransactionOutputsWithProof(TransactionOutputsWithProofRequest),
@@ -49,11 +51,21 @@ impl DataClientRequest {
         match self {
             Self::AccountsWithProof(_) => "accounts_with_proof",
             Self::EpochEndingLedgerInfos(_) => "epoch_ending_ledger_infos",
+            Self::NewTransactionOutputsWithProof(_) => "new_transaction_outputs_with_proof",
+            Self::NewTransactionsWithProof(_) => "new_transactions_with_proof",
             Self::NumberOfAccounts(_) => "number_of_accounts",
             Self::TransactionsWithProof(_) => "transactions_with_proof",
             Self::TransactionOutputsWithProof(_) => "transaction_outputs_with_proof",
         }
     }
+
+    /// Returns true iff the request is a subscription to new data
+    pub fn is_subscription_request(&self) -> bool {
+        matches!(
+            self,
+            Self::NewTransactionOutputsWithProof(_) | Self::NewTransactionsWithProof(_)
+        )
+    }
 }
 
 /// A request for fetching account states.
@@ -71,6 +83,21 @@ pub struct EpochEndingLedgerInfosRequest {
     pub end_epoch: Epoch,
 }
 
+/// A client subscription for new transaction outputs beyond a known version.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NewTransactionOutputsWithProofRequest {
+    pub known_version: Version,
+    pub known_epoch: Epoch,
+}
+
+/// A client subscription for new transactions beyond a known version.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NewTransactionsWithProofRequest {
+    pub known_version: Version,
+    pub known_epoch: Epoch,
+    pub include_events: bool,
+}
+
 /// A client request for fetching the number of accounts at a version.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct NumberOfAccountsRequest {
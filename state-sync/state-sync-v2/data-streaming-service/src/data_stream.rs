@@ -5,7 +5,8 @@ use crate::{
     data_notification,
     data_notification::{
         AccountsWithProofRequest, DataClientRequest, DataNotification, DataPayload,
-        EpochEndingLedgerInfosRequest, NotificationId, NumberOfAccountsRequest,
+        EpochEndingLedgerInfosRequest, NewTransactionOutputsWithProofRequest,
+        NewTransactionsWithProofRequest, NotificationId, NumberOfAccountsRequest,
         TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
     },
     error::Error,
@@ -86,6 +87,12 @@ pub struct DataStream<T> {
     // If this count becomes too large, the stream is evidently blocked (i.e.,
     // unable to make progress) and will automatically terminate.
     request_failure_count: u64,
+
+    // The number of data client requests that may currently be in-flight. This
+    // grows by one with every notification sent along the stream (up to the
+    // configured maximum) and is halved on every request failure, so that
+    // streams back off when the network is unable to keep up.
+    concurrent_requests_limit: u64,
 }
 
 impl<T: AptosDataClient + Send + Clone + 'static> DataStream<T> {
@@ -106,7 +113,7 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStream<T> {
         let data_stream_listener = DataStreamListener::new(notification_receiver);
 
         // Create a new stream engine
-        let stream_engine = StreamEngine::new(stream_request, advertised_data)?;
+        let stream_engine = StreamEngine::new(config, stream_request, advertised_data)?;
 
         // Create a new data stream
         let data_stream = Self {
@@ -121,6 +128,7 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStream<T> {
             notification_id_generator,
             stream_end_notification_id: None,
             request_failure_count: 0,
+            concurrent_requests_limit: config.max_concurrent_requests,
         };
 
         Ok((data_stream, data_stream_listener))
@@ -162,6 +170,14 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStream<T> {
         notification_id: &NotificationId,
         notification_feedback: &NotificationFeedback,
     ) -> Result<(), Error> {
+        // The client no longer requires the data, so there's nothing to report
+        if matches!(
+            notification_feedback,
+            NotificationFeedback::DataNoLongerRequired
+        ) {
+            return Ok(());
+        }
+
         if self.stream_end_notification_id == Some(*notification_id) {
             return if matches!(notification_feedback, NotificationFeedback::EndOfStream) {
                 Ok(())
@@ -195,13 +211,11 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStream<T> {
     ) -> Result<(), Error> {
         // Determine how many requests (at most) can be sent to the network
         let num_sent_requests = self.get_sent_data_requests().len() as u64;
+        // Note: the queue may hold more requests than the current limit if the
+        // limit was recently lowered. In that case, no new requests are sent.
         let max_num_requests_to_send = self
-            .config
-            .max_concurrent_requests
-            .checked_sub(num_sent_requests)
-            .ok_or_else(|| {
-                Error::IntegerOverflow("Max number of requests to send has overflown!".into())
-            })?;
+            .concurrent_requests_limit
+            .saturating_sub(num_sent_requests);
 
         if max_num_requests_to_send > 0 {
             let client_requests = self
@@ -373,6 +387,20 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStream<T> {
             .error(&data_client_error.clone().into())
             .message("Encountered a data client error!"));
 
+        // Data subscriptions are expected to time out (or be dropped by the
+        // server) when no new data is committed, so these aren't failures.
+        if data_client_request.is_subscription_request()
+            && matches!(
+                data_client_error,
+                aptos_data_client::Error::TimeoutWaitingForResponse(_)
+            )
+        {
+            let pending_client_response = self.send_client_request(data_client_request.clone());
+            self.get_sent_data_requests()
+                .push_front(pending_client_response);
+            return Ok(());
+        }
+
         // TODO(joshlind): can we identify the best way to react to the error?
         self.resend_data_client_request(data_client_request)
     }
@@ -386,6 +414,9 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStream<T> {
         // Increment the number of client failures for this request
         self.request_failure_count += 1;
 
+        // Back off by reducing the number of concurrent requests
+        self.decrease_concurrent_requests_limit();
+
         // Resend the client request
         let pending_client_response = self.send_client_request(data_client_request.clone());
 
@@ -451,11 +482,39 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStream<T> {
 
             // Reset the failure count. We've sent a notification and can move on.
             self.request_failure_count = 0;
+
+            // Allow one more concurrent request
+            self.increase_concurrent_requests_limit();
         }
 
         Ok(())
     }
 
+    /// Additively increases the concurrent requests limit (up to the max)
+    fn increase_concurrent_requests_limit(&mut self) {
+        self.concurrent_requests_limit = self
+            .concurrent_requests_limit
+            .saturating_add(1)
+            .min(self.config.max_concurrent_requests);
+    }
+
+    /// Halves the concurrent requests limit (down to a single request)
+    fn decrease_concurrent_requests_limit(&mut self) {
+        let concurrent_requests_limit = (self.concurrent_requests_limit / 2).max(1);
+        if concurrent_requests_limit != self.concurrent_requests_limit {
+            debug!(
+                (LogSchema::new(LogEntry::SendDataRequests)
+                    .stream_id(self.data_stream_id)
+                    .event(LogEvent::Pending)
+                    .message(&format!(
+                        "Reduced the number of concurrent data requests to {:?}",
+                        concurrent_requests_limit
+                    )))
+            );
+        }
+        self.concurrent_requests_limit = concurrent_requests_limit;
+    }
+
     fn insert_notification_response_mapping(
         &mut self,
         notification_id: NotificationId,
@@ -538,6 +597,12 @@ impl<T: AptosDataClient + Send + Clone + 'static> DataStream<T> {
             .expect("Sent data requests should be initialized!")
     }
 
+    #[cfg(test)]
+    /// This is exposed and used only for test purposes.
+    pub fn get_concurrent_requests_limit(&self) -> u64 {
+        self.concurrent_requests_limit
+    }
+
     #[cfg(test)]
     /// This is exposed and used only for test purposes.
     pub fn get_sent_requests_and_notifications(
@@ -611,6 +676,18 @@ fn sanity_check_client_response(
                 ResponsePayload::EpochEndingLedgerInfos(_)
             )
         }
+        DataClientRequest::NewTransactionOutputsWithProof(_) => {
+            matches!(
+                data_client_response.payload,
+                ResponsePayload::NewTransactionOutputsWithProof(_)
+            )
+        }
+        DataClientRequest::NewTransactionsWithProof(_) => {
+            matches!(
+                data_client_response.payload,
+                ResponsePayload::NewTransactionsWithProof(_)
+            )
+        }
         DataClientRequest::NumberOfAccounts(_) => {
             matches!(
                 data_client_response.payload,
@@ -675,6 +752,12 @@ fn spawn_request_task<T: AptosDataClient + Send + Clone + 'static>(
             DataClientRequest::EpochEndingLedgerInfos(request) => {
                 get_epoch_ending_ledger_infos(aptos_data_client, request).await
             }
+            DataClientRequest::NewTransactionOutputsWithProof(request) => {
+                get_new_transaction_outputs_with_proof(aptos_data_client, request).await
+            }
+            DataClientRequest::NewTransactionsWithProof(request) => {
+                get_new_transactions_with_proof(aptos_data_client, request).await
+            }
             DataClientRequest::NumberOfAccounts(request) => {
                 get_number_of_account_states(aptos_data_client, request).await
             }
@@ -729,6 +812,31 @@ async fn get_epoch_ending_ledger_infos<T: AptosDataClient + Send + Clone + 'stat
         .map(|response| response.map(ResponsePayload::from))
 }
 
+async fn get_new_transaction_outputs_with_proof<T: AptosDataClient + Send + Clone + 'static>(
+    aptos_data_client: T,
+    request: NewTransactionOutputsWithProofRequest,
+) -> Result<Response<ResponsePayload>, aptos_data_client::Error> {
+    let client_response = aptos_data_client
+        .get_new_transaction_outputs_with_proof(request.known_version, request.known_epoch);
+    client_response
+        .await
+        .map(|response| response.map(ResponsePayload::from))
+}
+
+async fn get_new_transactions_with_proof<T: AptosDataClient + Send + Clone + 'static>(
+    aptos_data_client: T,
+    request: NewTransactionsWithProofRequest,
+) -> Result<Response<ResponsePayload>, aptos_data_client::Error> {
+    let client_response = aptos_data_client.get_new_transactions_with_proof(
+        request.known_version,
+        request.known_epoch,
+        request.include_events,
+    );
+    client_response
+        .await
+        .map(|response| response.map(ResponsePayload::from))
+}
+
 async fn get_number_of_account_states<T: AptosDataClient + Send + Clone + 'static>(
     aptos_data_client: T,
     request: NumberOfAccountsRequest,
@@ -5,11 +5,13 @@ use crate::{
     data_notification::{
         AccountsWithProofRequest, DataClientRequest,
         DataClientRequest::{
-            AccountsWithProof, EpochEndingLedgerInfos, NumberOfAccounts,
-            TransactionOutputsWithProof, TransactionsWithProof,
+            AccountsWithProof, EpochEndingLedgerInfos, NewTransactionOutputsWithProof,
+            NewTransactionsWithProof, NumberOfAccounts, TransactionOutputsWithProof,
+            TransactionsWithProof,
         },
-        DataNotification, DataPayload, EpochEndingLedgerInfosRequest, NumberOfAccountsRequest,
-        TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
+        DataNotification, DataPayload, EpochEndingLedgerInfosRequest,
+        NewTransactionOutputsWithProofRequest, NewTransactionsWithProofRequest,
+        NumberOfAccountsRequest, TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
     },
     error::Error,
     logging::{LogEntry, LogEvent, LogSchema},
@@ -17,6 +19,7 @@ use crate::{
         Epoch, GetAllAccountsRequest, GetAllEpochEndingLedgerInfosRequest, StreamRequest,
     },
 };
+use aptos_config::config::DataStreamingServiceConfig;
 use aptos_data_client::{AdvertisedData, GlobalDataSummary, ResponsePayload};
 use aptos_id_generator::{IdGenerator, U64IdGenerator};
 use aptos_logger::prelude::*;
@@ -95,15 +98,16 @@ pub enum StreamEngine {
 
 impl StreamEngine {
     pub fn new(
+        config: DataStreamingServiceConfig,
         stream_request: &StreamRequest,
         advertised_data: &AdvertisedData,
     ) -> Result<Self, Error> {
         match stream_request {
             StreamRequest::ContinuouslyStreamTransactionOutputs(_) => {
-                Ok(ContinuousTransactionStreamEngine::new(stream_request)?.into())
+                Ok(ContinuousTransactionStreamEngine::new(config, stream_request)?.into())
             }
             StreamRequest::ContinuouslyStreamTransactions(_) => {
-                Ok(ContinuousTransactionStreamEngine::new(stream_request)?.into())
+                Ok(ContinuousTransactionStreamEngine::new(config, stream_request)?.into())
             }
             StreamRequest::GetAllAccounts(request) => {
                 Ok(AccountsStreamEngine::new(request)?.into())
@@ -321,6 +325,14 @@ pub struct ContinuousTransactionStreamEngine {
     // True iff a request has been created to fetch an epoch ending ledger info
     pub end_of_epoch_requested: bool,
 
+    // True iff the stream should subscribe to new data once it has caught up
+    // to the highest advertised version (and there is no final target).
+    pub enable_subscription_streaming: bool,
+
+    // True iff a data subscription is currently pending. While pending, the
+    // end of the requested data range is unknown, so no other requests are made.
+    pub subscription_requested: bool,
+
     // The next version and epoch that we're waiting to send to the
     // client along the stream. All versions before this have been sent.
     pub next_stream_version_and_epoch: (Version, Epoch),
@@ -335,13 +347,18 @@ pub struct ContinuousTransactionStreamEngine {
 }
 
 impl ContinuousTransactionStreamEngine {
-    fn new(stream_request: &StreamRequest) -> Result<Self, Error> {
+    fn new(
+        config: DataStreamingServiceConfig,
+        stream_request: &StreamRequest,
+    ) -> Result<Self, Error> {
         match stream_request {
             StreamRequest::ContinuouslyStreamTransactions(request) => {
                 Ok(ContinuousTransactionStreamEngine {
                     request: stream_request.clone(),
                     current_target_ledger_info: None,
                     end_of_epoch_requested: false,
+                    enable_subscription_streaming: config.enable_subscription_streaming,
+                    subscription_requested: false,
                     next_stream_version_and_epoch: (request.start_version, request.start_epoch),
                     next_request_version_and_epoch: (request.start_version, request.start_epoch),
                     stream_is_complete: false,
@@ -352,6 +369,8 @@ impl ContinuousTransactionStreamEngine {
                     request: stream_request.clone(),
                     current_target_ledger_info: None,
                     end_of_epoch_requested: false,
+                    enable_subscription_streaming: config.enable_subscription_streaming,
+                    subscription_requested: false,
                     next_stream_version_and_epoch: (request.start_version, request.start_epoch),
                     next_request_version_and_epoch: (request.start_version, request.start_epoch),
                     stream_is_complete: false,
@@ -397,6 +416,87 @@ impl ContinuousTransactionStreamEngine {
         }
     }
 
+    /// Returns true iff the stream has a final target ledger info
+    fn has_final_target(&self) -> bool {
+        match &self.request {
+            StreamRequest::ContinuouslyStreamTransactions(request) => request.target.is_some(),
+            StreamRequest::ContinuouslyStreamTransactionOutputs(request) => {
+                request.target.is_some()
+            }
+            request => invalid_stream_request!(request),
+        }
+    }
+
+    /// Creates a data subscription request for all new data beyond the
+    /// highest version already requested.
+    fn create_subscription_request(&mut self) -> Result<DataClientRequest, Error> {
+        let (next_request_version, next_request_epoch) = self.next_request_version_and_epoch;
+        let known_version = next_request_version
+            .checked_sub(1)
+            .ok_or_else(|| Error::IntegerOverflow("The known version has overflown!".into()))?;
+        let subscription_request = match &self.request {
+            StreamRequest::ContinuouslyStreamTransactions(request) => {
+                NewTransactionsWithProof(NewTransactionsWithProofRequest {
+                    known_version,
+                    known_epoch: next_request_epoch,
+                    include_events: request.include_events,
+                })
+            }
+            StreamRequest::ContinuouslyStreamTransactionOutputs(_) => {
+                NewTransactionOutputsWithProof(NewTransactionOutputsWithProofRequest {
+                    known_version,
+                    known_epoch: next_request_epoch,
+                })
+            }
+            request => invalid_stream_request!(request),
+        };
+        self.subscription_requested = true;
+
+        Ok(subscription_request)
+    }
+
+    /// Transforms the response to a data subscription into a data notification.
+    /// The ledger info in the response becomes the current target, as all
+    /// data in the response is proven against it.
+    fn handle_subscription_response(
+        &mut self,
+        known_version: Version,
+        num_versions: usize,
+        target_ledger_info: LedgerInfoWithSignatures,
+        client_response_payload: ResponsePayload,
+        notification_id_generator: Arc<U64IdGenerator>,
+    ) -> Result<Option<DataNotification>, Error> {
+        self.subscription_requested = false;
+        if num_versions == 0 {
+            return Ok(None); // There's no new data. Resubscribe.
+        }
+
+        // Calculate the version range of the new data
+        let start_version = known_version
+            .checked_add(1)
+            .ok_or_else(|| Error::IntegerOverflow("The start version has overflown!".into()))?;
+        let end_version = known_version
+            .checked_add(num_versions as u64)
+            .ok_or_else(|| Error::IntegerOverflow("The end version has overflown!".into()))?;
+        if end_version > target_ledger_info.ledger_info().version() {
+            return Err(Error::AptosDataClientResponseIsInvalid(format!(
+                "The subscription data is beyond the ledger info! End version: {:?}, ledger info: {:?}",
+                end_version, target_ledger_info
+            )));
+        }
+
+        // Update the stream and request progress, and create the notification
+        self.current_target_ledger_info = Some(target_ledger_info);
+        self.update_stream_version_and_epoch(start_version, end_version)?;
+        self.update_request_version_and_epoch(end_version)?;
+        let data_notification = self.create_data_notification(
+            end_version,
+            client_response_payload,
+            notification_id_generator,
+        )?;
+        Ok(Some(data_notification))
+    }
+
     fn get_target_ledger_info(&self) -> &LedgerInfoWithSignatures {
         self.current_target_ledger_info
             .as_ref()
@@ -535,13 +635,25 @@ impl DataStreamEngine for ContinuousTransactionStreamEngine {
         if self.current_target_ledger_info.is_none() && self.end_of_epoch_requested {
             return Ok(vec![]); // We are waiting for the epoch ending ledger info
         }
+        if self.subscription_requested {
+            return Ok(vec![]); // We are waiting for the data subscription response
+        }
 
         // If we don't have a syncing target, select one.
         let (next_request_version, next_request_epoch) = self.next_request_version_and_epoch;
         if self.current_target_ledger_info.is_none() {
-            // Select a new ledger info from the advertised data
+            // Select a new ledger info from the advertised data. If we've
+            // already caught up, subscribe to new data (if enabled).
             let target_ledger_info =
-                self.select_target_ledger_info(&global_data_summary.advertised_data)?;
+                match self.select_target_ledger_info(&global_data_summary.advertised_data) {
+                    Ok(target_ledger_info) => target_ledger_info,
+                    Err(Error::NoDataToFetch(_))
+                        if self.enable_subscription_streaming && !self.has_final_target() =>
+                    {
+                        return Ok(vec![self.create_subscription_request()?]);
+                    }
+                    Err(error) => return Err(error),
+                };
             if target_ledger_info.ledger_info().epoch() > next_request_epoch {
                 // There was an epoch change. Request an epoch ending ledger info.
                 info!(
@@ -606,6 +718,11 @@ impl DataStreamEngine for ContinuousTransactionStreamEngine {
     }
 
     fn is_remaining_data_available(&self, advertised_data: &AdvertisedData) -> bool {
+        // Data subscriptions can always be made (the data doesn't exist yet)
+        if self.subscription_requested {
+            return true;
+        }
+
         let advertised_ranges = match &self.request {
             StreamRequest::ContinuouslyStreamTransactions(_) => &advertised_data.transactions,
             StreamRequest::ContinuouslyStreamTransactionOutputs(_) => {
@@ -705,6 +822,42 @@ impl DataStreamEngine for ContinuousTransactionStreamEngine {
                 }
                 request => invalid_stream_request!(request),
             },
+            NewTransactionOutputsWithProof(request) => match &client_response_payload {
+                ResponsePayload::NewTransactionOutputsWithProof((
+                    transaction_outputs_with_proof,
+                    target_ledger_info,
+                )) => {
+                    let num_versions = transaction_outputs_with_proof
+                        .transactions_and_outputs
+                        .len();
+                    let target_ledger_info = target_ledger_info.clone();
+                    self.handle_subscription_response(
+                        request.known_version,
+                        num_versions,
+                        target_ledger_info,
+                        client_response_payload,
+                        notification_id_generator,
+                    )
+                }
+                _ => invalid_response_type!(client_response_payload),
+            },
+            NewTransactionsWithProof(request) => match &client_response_payload {
+                ResponsePayload::NewTransactionsWithProof((
+                    transactions_with_proof,
+                    target_ledger_info,
+                )) => {
+                    let num_versions = transactions_with_proof.transactions.len();
+                    let target_ledger_info = target_ledger_info.clone();
+                    self.handle_subscription_response(
+                        request.known_version,
+                        num_versions,
+                        target_ledger_info,
+                        client_response_payload,
+                        notification_id_generator,
+                    )
+                }
+                _ => invalid_response_type!(client_response_payload),
+            },
             request => invalid_client_request!(request, self),
         }
     }
@@ -1237,6 +1390,16 @@ fn create_data_notification(
                 _ => invalid_response_type!(client_response_type),
             }
         }
+        ResponsePayload::NewTransactionOutputsWithProof((
+            transactions_output_chunk,
+            target_ledger_info,
+        )) => DataPayload::ContinuousTransactionOutputsWithProof(
+            target_ledger_info,
+            transactions_output_chunk,
+        ),
+        ResponsePayload::NewTransactionsWithProof((transactions_chunk, target_ledger_info)) => {
+            DataPayload::ContinuousTransactionsWithProof(target_ledger_info, transactions_chunk)
+        }
         _ => invalid_response_type!(client_response_type),
     };
 
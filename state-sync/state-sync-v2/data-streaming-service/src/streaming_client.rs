@@ -209,6 +209,7 @@ pub struct TerminateStreamRequest {
 /// The feedback for a given notification.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum NotificationFeedback {
+    DataNoLongerRequired,
     EmptyPayloadData,
     EndOfStream,
     InvalidPayloadData,
@@ -220,6 +221,7 @@ impl NotificationFeedback {
     /// Returns a summary label for the notification feedback
     pub fn get_label(&self) -> &'static str {
         match self {
+            Self::DataNoLongerRequired => "data_no_longer_required",
             Self::EmptyPayloadData => "empty_payload_data",
             Self::EndOfStream => "end_of_stream",
             Self::InvalidPayloadData => "invalid_payload_data",
@@ -91,6 +91,12 @@ async fn test_stream_blocked() {
                         &NotificationFeedback::PayloadTypeIsIncorrect
                     ));
 
+                    // Feedback that the data is no longer required is always accepted
+                    assert_ok!(data_stream.handle_notification_feedback(
+                        &data_notification.notification_id,
+                        &NotificationFeedback::DataNoLongerRequired,
+                    ));
+
                     // Provide valid feedback for the notification
                     assert_ok!(data_stream.handle_notification_feedback(
                         &data_notification.notification_id,
@@ -105,6 +111,77 @@ async fn test_stream_blocked() {
     }
 }
 
+#[tokio::test]
+async fn test_stream_dynamic_concurrency() {
+    // Create an epoch ending data stream
+    let streaming_service_config = DataStreamingServiceConfig::default();
+    let max_concurrent_requests = streaming_service_config.max_concurrent_requests;
+    let (mut data_stream, mut stream_listener) =
+        create_epoch_ending_stream(streaming_service_config, MIN_ADVERTISED_EPOCH_END);
+
+    // Initialize the data stream and verify the max number of requests were sent
+    let global_data_summary = create_global_data_summary(1);
+    data_stream
+        .initialize_data_requests(global_data_summary.clone())
+        .unwrap();
+    assert_eq!(
+        data_stream.get_concurrent_requests_limit(),
+        max_concurrent_requests
+    );
+    verify_num_sent_requests(&mut data_stream, max_concurrent_requests);
+
+    // Fail the request at the head of the queue (multiple times) and verify
+    // the number of concurrent requests is halved each time (down to one).
+    // The failed request is resent, but no new requests are sent because the
+    // in-flight requests already exceed the limit.
+    let mut expected_limit = max_concurrent_requests;
+    while expected_limit > 1 {
+        set_timeout_response_in_queue(&mut data_stream, 0);
+        data_stream
+            .process_data_responses(global_data_summary.clone())
+            .unwrap();
+
+        expected_limit /= 2;
+        assert_eq!(
+            data_stream.get_concurrent_requests_limit(),
+            expected_limit.max(1)
+        );
+        verify_num_sent_requests(&mut data_stream, max_concurrent_requests);
+    }
+
+    // Respond successfully to the resent request and verify the number of
+    // concurrent requests grows again (but no new requests are sent yet).
+    set_epoch_ending_response_in_queue(&mut data_stream, 0);
+    data_stream
+        .process_data_responses(global_data_summary.clone())
+        .unwrap();
+    assert_eq!(data_stream.get_concurrent_requests_limit(), 2);
+    verify_num_sent_requests(&mut data_stream, max_concurrent_requests - 1);
+
+    // Respond successfully to all remaining requests and verify the limit
+    // grows back to the maximum and new requests are sent.
+    for index in 0..max_concurrent_requests - 1 {
+        set_epoch_ending_response_in_queue(&mut data_stream, index as usize);
+    }
+    data_stream
+        .process_data_responses(global_data_summary.clone())
+        .unwrap();
+    assert_eq!(
+        data_stream.get_concurrent_requests_limit(),
+        max_concurrent_requests
+    );
+    verify_num_sent_requests(&mut data_stream, max_concurrent_requests);
+
+    // Verify a notification was sent for every successful response
+    for _ in 0..max_concurrent_requests {
+        verify_epoch_ending_notification(
+            &mut stream_listener,
+            create_ledger_info(0, MIN_ADVERTISED_EPOCH_END, true),
+        )
+        .await;
+    }
+}
+
 #[tokio::test]
 async fn test_stream_garbage_collection() {
     // Create a transaction stream
@@ -425,6 +502,17 @@ fn set_epoch_ending_response_in_queue(
     pending_response.lock().client_response = client_response;
 }
 
+/// Sets the client response at the index in the pending queue to contain a
+/// timeout error.
+fn set_timeout_response_in_queue(data_stream: &mut DataStream<MockAptosDataClient>, index: usize) {
+    let (sent_requests, _) = data_stream.get_sent_requests_and_notifications();
+    let pending_response = sent_requests.as_mut().unwrap().get_mut(index).unwrap();
+    let client_response = Some(Err(aptos_data_client::Error::TimeoutWaitingForResponse(
+        "Timed out!".into(),
+    )));
+    pending_response.lock().client_response = client_response;
+}
+
 /// Sets the client response at the head of the pending queue to contain an
 /// transaction response.
 fn set_transaction_response_at_queue_head(data_stream: &mut DataStream<MockAptosDataClient>) {
@@ -454,6 +542,18 @@ fn insert_response_into_pending_queue(
     sent_requests.as_mut().unwrap().push_front(pending_response);
 }
 
+/// Verifies that the given number of client requests are in the sent request queue
+fn verify_num_sent_requests(
+    data_stream: &mut DataStream<MockAptosDataClient>,
+    expected_num_requests: u64,
+) {
+    let (sent_requests, _) = data_stream.get_sent_requests_and_notifications();
+    assert_eq!(
+        sent_requests.as_ref().unwrap().len() as u64,
+        expected_num_requests
+    );
+}
+
 /// Verifies that a client request was resubmitted (i.e., pushed to the head of the
 /// sent request queue)
 fn verify_client_request_resubmitted(
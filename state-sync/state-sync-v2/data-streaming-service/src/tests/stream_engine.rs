@@ -2,12 +2,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    data_notification::{DataClientRequest, EpochEndingLedgerInfosRequest},
+    data_notification::{
+        DataClientRequest, EpochEndingLedgerInfosRequest, NewTransactionOutputsWithProofRequest,
+    },
     error::Error,
-    stream_engine::{DataStreamEngine, EpochEndingStreamEngine, StreamEngine},
-    streaming_client::{GetAllEpochEndingLedgerInfosRequest, StreamRequest},
-    tests::utils::initialize_logger,
+    stream_engine::{
+        ContinuousTransactionStreamEngine, DataStreamEngine, EpochEndingStreamEngine, StreamEngine,
+    },
+    streaming_client::{
+        ContinuouslyStreamTransactionOutputsRequest, GetAllEpochEndingLedgerInfosRequest,
+        StreamRequest,
+    },
+    tests::utils::{create_ledger_info, initialize_logger},
 };
+use aptos_config::config::DataStreamingServiceConfig;
 use aptos_data_client::{GlobalDataSummary, OptimalChunkSizes, ResponsePayload};
 use aptos_id_generator::U64IdGenerator;
 use claim::{assert_matches, assert_ok};
@@ -140,7 +148,11 @@ fn test_epoch_ending_stream_engine() {
 
     // Try to create a stream engine where there is no advertised data
     // and verify an error is returned.
-    let result = StreamEngine::new(&stream_request, &GlobalDataSummary::empty().advertised_data);
+    let result = StreamEngine::new(
+        DataStreamingServiceConfig::default(),
+        &stream_request,
+        &GlobalDataSummary::empty().advertised_data,
+    );
     assert_matches!(result, Err(Error::DataIsUnavailable(_)));
 
     // Create a data summary with various advertised epoch ranges (highest is one)
@@ -154,7 +166,11 @@ fn test_epoch_ending_stream_engine() {
     ];
 
     // Try to create a stream engine where the highest epoch is one
-    let result = StreamEngine::new(&stream_request, &global_data_summary.advertised_data);
+    let result = StreamEngine::new(
+        DataStreamingServiceConfig::default(),
+        &stream_request,
+        &global_data_summary.advertised_data,
+    );
     assert_ok!(result);
 
     // Create a global data summary with non-zero advertised epoch ranges
@@ -169,7 +185,13 @@ fn test_epoch_ending_stream_engine() {
     ];
 
     // Create a new data stream engine and verify the highest epoch is chosen
-    match StreamEngine::new(&stream_request, &global_data_summary.advertised_data).unwrap() {
+    match StreamEngine::new(
+        DataStreamingServiceConfig::default(),
+        &stream_request,
+        &global_data_summary.advertised_data,
+    )
+    .unwrap()
+    {
         StreamEngine::EpochEndingStreamEngine(stream_engine) => {
             assert_eq!(stream_engine.end_epoch, 1000);
         }
@@ -238,6 +260,79 @@ fn test_update_epoch_ending_stream_panic() {
         .unwrap();
 }
 
+#[test]
+fn test_continuous_stream_subscriptions() {
+    // Create a continuous stream engine that has already caught up
+    let highest_version = 1000;
+    let mut stream_engine = create_continuous_stream_engine(highest_version + 1, true);
+    let mut global_data_summary = GlobalDataSummary::empty();
+    global_data_summary.advertised_data.synced_ledger_infos =
+        vec![create_ledger_info(highest_version, 0, false)];
+
+    // Verify a single subscription request is created
+    let client_requests = stream_engine
+        .create_data_client_requests(5, &global_data_summary)
+        .unwrap();
+    let expected_requests = vec![DataClientRequest::NewTransactionOutputsWithProof(
+        NewTransactionOutputsWithProofRequest {
+            known_version: highest_version,
+            known_epoch: 0,
+        },
+    )];
+    assert_eq!(client_requests, expected_requests);
+    assert!(stream_engine.is_remaining_data_available(&global_data_summary.advertised_data));
+
+    // Verify no more requests are created while the subscription is pending
+    let client_requests = stream_engine
+        .create_data_client_requests(5, &global_data_summary)
+        .unwrap();
+    assert!(client_requests.is_empty());
+
+    // Create a continuous stream engine with subscriptions disabled
+    let mut stream_engine = create_continuous_stream_engine(highest_version + 1, false);
+
+    // Verify no subscription requests are created
+    let result = stream_engine.create_data_client_requests(5, &global_data_summary);
+    assert_matches!(result, Err(Error::NoDataToFetch(_)));
+}
+
+fn create_continuous_stream_engine(
+    start_version: u64,
+    enable_subscription_streaming: bool,
+) -> ContinuousTransactionStreamEngine {
+    initialize_logger();
+
+    // Create a continuous transaction output stream request
+    let stream_request = StreamRequest::ContinuouslyStreamTransactionOutputs(
+        ContinuouslyStreamTransactionOutputsRequest {
+            start_version,
+            start_epoch: 0,
+            target: None,
+        },
+    );
+
+    // Create a new continuous stream engine
+    let config = DataStreamingServiceConfig {
+        enable_subscription_streaming,
+        ..Default::default()
+    };
+    match StreamEngine::new(
+        config,
+        &stream_request,
+        &GlobalDataSummary::empty().advertised_data,
+    )
+    .unwrap()
+    {
+        StreamEngine::ContinuousTransactionStreamEngine(stream_engine) => stream_engine,
+        unexpected_engine => {
+            panic!(
+                "Expected continuous stream engine but got {:?}",
+                unexpected_engine
+            );
+        }
+    }
+}
+
 fn create_epoch_ending_stream_engine(start_epoch: u64, end_epoch: u64) -> EpochEndingStreamEngine {
     initialize_logger();
 
@@ -254,7 +349,13 @@ fn create_epoch_ending_stream_engine(start_epoch: u64, end_epoch: u64) -> EpochE
         .epoch_ending_ledger_infos = vec![CompleteDataRange::new(start_epoch, end_epoch).unwrap()];
 
     // Create a new epoch ending stream engine
-    match StreamEngine::new(&stream_request, &global_data_summary.advertised_data).unwrap() {
+    match StreamEngine::new(
+        DataStreamingServiceConfig::default(),
+        &stream_request,
+        &global_data_summary.advertised_data,
+    )
+    .unwrap()
+    {
         StreamEngine::EpochEndingStreamEngine(stream_engine) => stream_engine,
         unexpected_engine => {
             panic!(
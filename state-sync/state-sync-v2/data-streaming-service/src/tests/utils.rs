@@ -69,6 +69,24 @@ impl MockAptosDataClient {
         }
     }
 
+    fn get_highest_synced_ledger_info(&self) -> LedgerInfoWithSignatures {
+        self.synced_ledger_infos.last().unwrap().clone()
+    }
+
+    /// Returns the end version of any new data beyond the known version
+    /// (i.e., the highest synced version), or None if there is no new data.
+    fn get_new_data_end_version(&self, known_version: Version) -> Option<Version> {
+        let highest_version = self
+            .get_highest_synced_ledger_info()
+            .ledger_info()
+            .version();
+        if known_version < highest_version {
+            Some(highest_version)
+        } else {
+            None
+        }
+    }
+
     fn emulate_network_latencies(&self) {
         // Sleep for 100 - 500 ms to emulate variance
         thread::sleep(Duration::from_millis(create_range_random_u64(100, 500)));
@@ -201,6 +219,58 @@ impl AptosDataClient for MockAptosDataClient {
         // Return the transaction list with proofs
         Ok(create_data_client_response(transaction_list_with_proof))
     }
+
+    async fn get_new_transaction_outputs_with_proof(
+        &self,
+        known_version: Version,
+        _known_epoch: Epoch,
+    ) -> Result<
+        Response<(TransactionOutputListWithProof, LedgerInfoWithSignatures)>,
+        aptos_data_client::Error,
+    > {
+        self.emulate_network_latencies();
+
+        // Create the new transactions and outputs up to the highest ledger info
+        let highest_ledger_info = self.get_highest_synced_ledger_info();
+        let mut output_list_with_proof = TransactionOutputListWithProof::new_empty();
+        if let Some(end_version) = self.get_new_data_end_version(known_version) {
+            let mut transactions_and_outputs = vec![];
+            for _ in known_version + 1..=end_version {
+                transactions_and_outputs.push((create_transaction(), create_transaction_output()));
+            }
+            output_list_with_proof.first_transaction_output_version = Some(known_version + 1);
+            output_list_with_proof.transactions_and_outputs = transactions_and_outputs;
+        }
+        Ok(create_data_client_response((
+            output_list_with_proof,
+            highest_ledger_info,
+        )))
+    }
+
+    async fn get_new_transactions_with_proof(
+        &self,
+        known_version: Version,
+        _known_epoch: Epoch,
+        include_events: bool,
+    ) -> Result<
+        Response<(TransactionListWithProof, LedgerInfoWithSignatures)>,
+        aptos_data_client::Error,
+    > {
+        self.emulate_network_latencies();
+
+        // Create the new transactions up to the highest ledger info
+        let highest_ledger_info = self.get_highest_synced_ledger_info();
+        let transaction_list_with_proof = match self.get_new_data_end_version(known_version) {
+            Some(end_version) => {
+                create_transaction_list_with_proof(known_version + 1, end_version, include_events)
+            }
+            None => TransactionListWithProof::new_empty(),
+        };
+        Ok(create_data_client_response((
+            transaction_list_with_proof,
+            highest_ledger_info,
+        )))
+    }
 }
 
 #[derive(Debug)]
@@ -9,6 +9,7 @@ use aptos_config::config::BootstrappingMode;
 use aptos_data_client::GlobalDataSummary;
 use aptos_logger::*;
 use aptos_types::{
+    account_state_blob::AccountStatesChunkWithProof,
     epoch_change::Verifier,
     epoch_state::EpochState,
     ledger_info::LedgerInfoWithSignatures,
@@ -21,7 +22,7 @@ use data_streaming_service::{
     streaming_client::{DataStreamingClient, NotificationFeedback, StreamingServiceClient},
 };
 use futures::channel::oneshot;
-use std::{collections::BTreeMap, sync::Arc};
+use std::{cmp, collections::BTreeMap, sync::Arc};
 use storage_interface::DbReader;
 
 /// A simple container for verified epoch states and epoch ending ledger infos
@@ -180,6 +181,15 @@ impl VerifiedEpochStates {
         self.new_epoch_ending_ledger_infos.get(&version).cloned()
     }
 
+    /// Returns all epoch ending ledger infos fetched from the network (in
+    /// increasing version order).
+    pub fn all_epoch_ending_ledger_infos(&self) -> Vec<LedgerInfoWithSignatures> {
+        self.new_epoch_ending_ledger_infos
+            .values()
+            .cloned()
+            .collect()
+    }
+
     /// Returns the highest known ledger info we've fetched (if any)
     pub fn get_highest_known_ledger_info(&self) -> Option<LedgerInfoWithSignatures> {
         if !self.new_epoch_ending_ledger_infos.is_empty() {
@@ -210,8 +220,44 @@ impl VerifiedEpochStates {
     }
 }
 
+/// A simple container to manage the state of account state syncing (i.e.,
+/// when the node bootstraps from the latest account state snapshot).
+#[derive(Default)]
+struct AccountStateSyncer {
+    // Whether all account states have been sent to the storage synchronizer
+    all_account_states_sent: bool,
+
+    // Whether the account synchronizer has been initialized
+    initialized_account_synchronizer: bool,
+
+    // The epoch ending ledger info at the snapshot version
+    ledger_info_to_sync: Option<LedgerInfoWithSignatures>,
+
+    // The index of the next account state to fetch
+    next_account_index_to_process: u64,
+
+    // The verified transaction output at the snapshot version
+    transaction_output_to_sync: Option<TransactionOutputListWithProof>,
+}
+
+impl AccountStateSyncer {
+    /// Returns true iff the transaction output at the snapshot version is
+    /// still being fetched.
+    fn fetching_transaction_output(&self) -> bool {
+        self.ledger_info_to_sync.is_some() && self.transaction_output_to_sync.is_none()
+    }
+
+    /// Resets the account state syncer so that the snapshot is synced from scratch
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
 /// A simple component that manages the bootstrapping of the node
 pub struct Bootstrapper<StorageSyncer> {
+    // The state of account state syncing (if the node bootstraps from a snapshot)
+    account_state_syncer: AccountStateSyncer,
+
     // The currently active data stream (provided by the data streaming service)
     active_data_stream: Option<DataStreamListener>,
 
@@ -253,6 +299,7 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> Bootstrapper<StorageSy
         let verified_epoch_states = VerifiedEpochStates::new(latest_epoch_state);
 
         Self {
+            account_state_syncer: AccountStateSyncer::default(),
             active_data_stream: None,
             bootstrap_notifier_channel: None,
             bootstrapped: false,
@@ -363,16 +410,41 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> Bootstrapper<StorageSy
             );
         }
 
-        // Fetch all data until the epoch ending ledger info for the current epoch
+        // If we're bootstrapping from the latest account state snapshot, fetch
+        // the snapshot at the highest known epoch ending version. This also
+        // holds if storage already contains a prefix of the ledger (e.g., the
+        // node was restarted): the accumulator of the prefix is confirmed when
+        // the snapshot is finalized, and the versions between the prefix and
+        // the snapshot are skipped (as they are for genesis). Snapshots beyond
+        // the target version (if any) are never fetched, as we can't sync
+        // backwards.
+        let snapshot_beyond_target = self
+            .driver_configuration
+            .target_version
+            .map(|target_version| highest_known_ledger_version > target_version)
+            .unwrap_or(false);
+        if self.driver_configuration.config.bootstrapping_mode
+            == BootstrappingMode::DownloadLatestAccountStates
+            && !snapshot_beyond_target
+        {
+            return self.fetch_account_states(highest_known_ledger_info).await;
+        }
+
+        // Fetch all data until the epoch ending ledger info for the current
+        // epoch (or the target version, if it comes first).
         let next_version = highest_synced_version.checked_add(1).ok_or_else(|| {
             Error::IntegerOverflow("The next output version has overflown!".into())
         })?;
-        let end_version = self
+        let mut end_version = self
             .verified_epoch_states
             .next_epoch_ending_version(highest_synced_version)
             .expect("No higher epoch ending version known!");
+        if let Some(target_version) = self.driver_configuration.target_version {
+            end_version = cmp::min(end_version, target_version);
+        }
         let data_stream = match self.driver_configuration.config.bootstrapping_mode {
-            BootstrappingMode::ApplyTransactionOutputsFromGenesis => {
+            BootstrappingMode::ApplyTransactionOutputsFromGenesis
+            | BootstrappingMode::DownloadLatestAccountStates => {
                 self.streaming_service_client
                     .get_all_transaction_outputs(
                         next_version,
@@ -391,9 +463,6 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> Bootstrapper<StorageSy
                     )
                     .await?
             }
-            bootstrapping_mode => {
-                unimplemented!("Bootstrapping mode not supported: {:?}", bootstrapping_mode)
-            }
         };
         self.speculative_stream_state = Some(SpeculativeStreamState::new(
             utils::fetch_latest_epoch_state(self.storage.clone())?,
@@ -405,6 +474,59 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> Bootstrapper<StorageSy
         Ok(())
     }
 
+    /// Initializes an active data stream to fetch the account state snapshot
+    /// at the version of the given (epoch ending) ledger info. The
+    /// transaction output at the snapshot version is fetched first, followed
+    /// by all account states.
+    async fn fetch_account_states(
+        &mut self,
+        highest_known_ledger_info: LedgerInfoWithSignatures,
+    ) -> Result<(), Error> {
+        // Wait for the storage synchronizer to finalize the snapshot
+        if self.account_state_syncer.all_account_states_sent {
+            return Ok(());
+        }
+
+        // Fetch the snapshot version and verify it hasn't changed
+        let version = highest_known_ledger_info.ledger_info().version();
+        if let Some(ledger_info_to_sync) = &self.account_state_syncer.ledger_info_to_sync {
+            let version_to_sync = ledger_info_to_sync.ledger_info().version();
+            if version_to_sync != version {
+                return Err(Error::UnexpectedError(format!(
+                    "The account state snapshot version has changed! Syncing: {:?}, highest known: {:?}",
+                    version_to_sync, version
+                )));
+            }
+        } else {
+            self.account_state_syncer.ledger_info_to_sync = Some(highest_known_ledger_info.clone());
+        }
+
+        // Fetch either the transaction output or the account states
+        let data_stream = if self.account_state_syncer.fetching_transaction_output() {
+            self.streaming_service_client
+                .get_all_transaction_outputs(version, version, version)
+                .await?
+        } else {
+            self.streaming_service_client
+                .get_all_accounts(
+                    version,
+                    Some(self.account_state_syncer.next_account_index_to_process),
+                )
+                .await?
+        };
+        let synced_version = version
+            .checked_sub(1)
+            .ok_or_else(|| Error::IntegerOverflow("The synced version has overflown!".into()))?;
+        self.speculative_stream_state = Some(SpeculativeStreamState::new(
+            utils::fetch_latest_epoch_state(self.storage.clone())?,
+            Some(highest_known_ledger_info),
+            synced_version,
+        ));
+        self.active_data_stream = Some(data_stream);
+
+        Ok(())
+    }
+
     /// Processes any notifications already pending on the active stream
     async fn process_active_stream_notifications(&mut self) -> Result<(), Error> {
         loop {
@@ -429,6 +551,22 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> Bootstrapper<StorageSy
                     )
                     .await?;
                 }
+                DataPayload::AccountStatesWithProof(account_states_with_proof) => {
+                    self.process_account_states_payload(
+                        data_notification.notification_id,
+                        account_states_with_proof,
+                    )
+                    .await?;
+                }
+                DataPayload::TransactionOutputsWithProof(transaction_outputs_with_proof)
+                    if self.account_state_syncer.fetching_transaction_output() =>
+                {
+                    self.process_snapshot_output_payload(
+                        data_notification.notification_id,
+                        transaction_outputs_with_proof,
+                    )
+                    .await?;
+                }
                 DataPayload::TransactionOutputsWithProof(transaction_outputs_with_proof) => {
                     let payload_start_version =
                         transaction_outputs_with_proof.first_transaction_output_version;
@@ -589,6 +727,123 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> Bootstrapper<StorageSy
         Ok(())
     }
 
+    /// Process the transaction output at the account state snapshot version
+    async fn process_snapshot_output_payload(
+        &mut self,
+        notification_id: NotificationId,
+        transaction_outputs_with_proof: TransactionOutputListWithProof,
+    ) -> Result<(), Error> {
+        // Verify the payload starting version
+        let payload_start_version = self
+            .verify_payload_start_version(
+                notification_id,
+                transaction_outputs_with_proof.first_transaction_output_version,
+            )
+            .await?;
+
+        // Verify the payload contains only the output at the snapshot version
+        let num_transaction_outputs = transaction_outputs_with_proof
+            .transactions_and_outputs
+            .len();
+        if num_transaction_outputs != 1 {
+            self.terminate_active_stream(notification_id, NotificationFeedback::InvalidPayloadData)
+                .await?;
+            return Err(Error::InvalidPayload(format!(
+                "Expected a single transaction output at the snapshot version! Found: {:?}",
+                num_transaction_outputs
+            )));
+        }
+
+        // Verify the output against the ledger info at the snapshot version
+        let ledger_info_to_sync = self
+            .account_state_syncer
+            .ledger_info_to_sync
+            .clone()
+            .expect("The ledger info to sync should exist!");
+        if let Err(error) = transaction_outputs_with_proof.verify(
+            ledger_info_to_sync.ledger_info(),
+            Some(payload_start_version),
+        ) {
+            self.terminate_active_stream(notification_id, NotificationFeedback::PayloadProofFailed)
+                .await?;
+            return Err(Error::VerificationError(format!(
+                "The transaction output at the snapshot version failed verification: {:?}",
+                error
+            )));
+        }
+
+        // Save the verified output until the account states have been fetched
+        self.account_state_syncer.transaction_output_to_sync = Some(transaction_outputs_with_proof);
+        self.get_speculative_stream_state()
+            .update_synced_version(payload_start_version);
+
+        Ok(())
+    }
+
+    /// Process a single account states payload
+    async fn process_account_states_payload(
+        &mut self,
+        notification_id: NotificationId,
+        account_states_with_proof: AccountStatesChunkWithProof,
+    ) -> Result<(), Error> {
+        // Verify we're expecting account states
+        let (ledger_info_to_sync, transaction_output_to_sync) = match (
+            &self.account_state_syncer.ledger_info_to_sync,
+            &self.account_state_syncer.transaction_output_to_sync,
+        ) {
+            (Some(ledger_info_to_sync), Some(transaction_output_to_sync)) => (
+                ledger_info_to_sync.clone(),
+                transaction_output_to_sync.clone(),
+            ),
+            _ => {
+                self.terminate_active_stream(
+                    notification_id,
+                    NotificationFeedback::PayloadTypeIsIncorrect,
+                )
+                .await?;
+                return Err(Error::InvalidPayload(
+                    "Received account states but no snapshot is being synced!".into(),
+                ));
+            }
+        };
+
+        // Verify the payload start index
+        let expected_index = self.account_state_syncer.next_account_index_to_process;
+        if account_states_with_proof.first_index != expected_index {
+            self.terminate_active_stream(notification_id, NotificationFeedback::InvalidPayloadData)
+                .await?;
+            return Err(Error::VerificationError(format!(
+                "The account states start index does not match the expected index! Start: {:?}, expected: {:?}",
+                account_states_with_proof.first_index, expected_index
+            )));
+        }
+
+        // Initialize the account synchronizer (if it hasn't already been initialized)
+        if !self.account_state_syncer.initialized_account_synchronizer {
+            self.storage_synchronizer.initialize_account_synchronizer(
+                self.verified_epoch_states.all_epoch_ending_ledger_infos(),
+                ledger_info_to_sync,
+                transaction_output_to_sync,
+            )?;
+            self.account_state_syncer.initialized_account_synchronizer = true;
+        }
+
+        // Save the account states and update the next index to process
+        let last_index = account_states_with_proof.last_index;
+        let last_chunk = account_states_with_proof.is_last_chunk();
+        self.storage_synchronizer
+            .save_account_states(notification_id, account_states_with_proof)?;
+        self.account_state_syncer.next_account_index_to_process =
+            last_index.checked_add(1).ok_or_else(|| {
+                Error::IntegerOverflow("The next account index to process has overflown!".into())
+            })?;
+        if last_chunk {
+            self.account_state_syncer.all_account_states_sent = true;
+        }
+
+        Ok(())
+    }
+
     /// Process a single transaction or transaction output data payload
     async fn process_transaction_or_output_payload(
         &mut self,
@@ -618,7 +873,8 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> Bootstrapper<StorageSy
         // Execute/apply and commit the transactions/outputs
         let num_transactions_or_outputs = match self.driver_configuration.config.bootstrapping_mode
         {
-            BootstrappingMode::ApplyTransactionOutputsFromGenesis => {
+            BootstrappingMode::ApplyTransactionOutputsFromGenesis
+            | BootstrappingMode::DownloadLatestAccountStates => {
                 if let Some(transaction_outputs_with_proof) = transaction_outputs_with_proof {
                     let num_transaction_outputs = transaction_outputs_with_proof
                         .transactions_and_outputs
@@ -662,9 +918,6 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> Bootstrapper<StorageSy
                     ));
                 }
             }
-            bootstrapping_mode => {
-                unimplemented!("Bootstrapping mode not supported: {:?}", bootstrapping_mode)
-            }
         };
         let synced_version = payload_start_version
             .checked_add(num_transactions_or_outputs as u64)
@@ -721,7 +974,8 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> Bootstrapper<StorageSy
     ) -> Result<Option<LedgerInfoWithSignatures>, Error> {
         // Calculate the payload end version
         let num_versions = match self.driver_configuration.config.bootstrapping_mode {
-            BootstrappingMode::ApplyTransactionOutputsFromGenesis => {
+            BootstrappingMode::ApplyTransactionOutputsFromGenesis
+            | BootstrappingMode::DownloadLatestAccountStates => {
                 if let Some(transaction_outputs_with_proof) = transaction_outputs_with_proof {
                     transaction_outputs_with_proof
                         .transactions_and_outputs
@@ -751,9 +1005,6 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> Bootstrapper<StorageSy
                     ));
                 }
             }
-            bootstrapping_mode => {
-                unimplemented!("Bootstrapping mode not supported: {:?}", bootstrapping_mode)
-            }
         };
         let payload_end_version = payload_start_version
             .checked_add(num_versions as u64)
@@ -803,6 +1054,19 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> Bootstrapper<StorageSy
         .await
     }
 
+    /// Handles an error notification sent by the storage synchronizer. If
+    /// the node is syncing an account state snapshot, the snapshot will be
+    /// synced again from scratch.
+    pub async fn handle_storage_synchronizer_error(
+        &mut self,
+        notification_id: NotificationId,
+        notification_feedback: NotificationFeedback,
+    ) -> Result<(), Error> {
+        self.account_state_syncer.reset();
+        self.terminate_active_stream(notification_id, notification_feedback)
+            .await
+    }
+
     /// Terminates the currently active stream with the provided feedback
     pub async fn terminate_active_stream(
         &mut self,
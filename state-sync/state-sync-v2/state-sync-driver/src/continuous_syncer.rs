@@ -7,6 +7,7 @@ use crate::{
 };
 use aptos_config::config::ContinuousSyncingMode;
 use aptos_infallible::Mutex;
+use aptos_logger::*;
 use aptos_types::{
     ledger_info::LedgerInfoWithSignatures,
     transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
@@ -38,6 +39,11 @@ pub struct ContinuousSyncer<StorageSyncer> {
 
     // The storage synchronizer used to update local storage
     storage_synchronizer: StorageSyncer,
+
+    // A verified ledger info beyond the target version (if any). Once found,
+    // all remaining data up to the target version is fetched using this
+    // ledger info as the proof.
+    target_proof_ledger_info: Option<LedgerInfoWithSignatures>,
 }
 
 impl<StorageSyncer: StorageSynchronizerInterface + Clone> ContinuousSyncer<StorageSyncer> {
@@ -54,6 +60,7 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> ContinuousSyncer<Stora
             streaming_service_client,
             storage,
             storage_synchronizer,
+            target_proof_ledger_info: None,
         }
     }
 
@@ -88,6 +95,18 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> ContinuousSyncer<Stora
             .checked_add(1)
             .ok_or_else(|| Error::IntegerOverflow("The next version has overflown!".into()))?;
 
+        // If we've already found a proof beyond the target version, only
+        // fetch the data remaining up to the target version.
+        if let Some(target_proof_ledger_info) = self.target_proof_ledger_info.clone() {
+            return self
+                .initialize_target_version_stream(
+                    next_version,
+                    highest_synced_version,
+                    target_proof_ledger_info,
+                )
+                .await;
+        }
+
         // Initialize a new active data stream
         let sync_request_target = consensus_sync_request
             .lock()
@@ -124,6 +143,40 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> ContinuousSyncer<Stora
         Ok(())
     }
 
+    /// Initializes a (bounded) data stream that fetches all data up to the
+    /// target version, proven by the given ledger info.
+    async fn initialize_target_version_stream(
+        &mut self,
+        next_version: Version,
+        highest_synced_version: Version,
+        target_proof_ledger_info: LedgerInfoWithSignatures,
+    ) -> Result<(), Error> {
+        let target_version = self.driver_configuration.target_version.ok_or_else(|| {
+            Error::UnexpectedError("A target proof exists without a target version!".into())
+        })?;
+        let proof_version = target_proof_ledger_info.ledger_info().version();
+        let active_data_stream = match self.driver_configuration.config.continuous_syncing_mode {
+            ContinuousSyncingMode::ApplyTransactionOutputs => {
+                self.streaming_service_client
+                    .get_all_transaction_outputs(next_version, target_version, proof_version)
+                    .await?
+            }
+            ContinuousSyncingMode::ExecuteTransactions => {
+                self.streaming_service_client
+                    .get_all_transactions(next_version, target_version, proof_version, false)
+                    .await?
+            }
+        };
+        self.speculative_stream_state = Some(SpeculativeStreamState::new(
+            utils::fetch_latest_epoch_state(self.storage.clone())?,
+            Some(target_proof_ledger_info),
+            highest_synced_version,
+        ));
+        self.active_data_stream = Some(active_data_stream);
+
+        Ok(())
+    }
+
     /// Processes any notifications already pending on the active stream
     async fn process_active_stream_notifications(
         &mut self,
@@ -165,15 +218,56 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> ContinuousSyncer<Stora
                     )
                     .await?;
                 }
+                DataPayload::TransactionOutputsWithProof(transaction_outputs_with_proof) => {
+                    let payload_start_version =
+                        transaction_outputs_with_proof.first_transaction_output_version;
+                    let proof_ledger_info = self.get_target_proof_ledger_info()?;
+                    self.process_transaction_or_output_payload(
+                        consensus_sync_request.clone(),
+                        data_notification.notification_id,
+                        proof_ledger_info,
+                        None,
+                        Some(transaction_outputs_with_proof),
+                        payload_start_version,
+                    )
+                    .await?;
+                }
+                DataPayload::TransactionsWithProof(transactions_with_proof) => {
+                    let payload_start_version = transactions_with_proof.first_transaction_version;
+                    let proof_ledger_info = self.get_target_proof_ledger_info()?;
+                    self.process_transaction_or_output_payload(
+                        consensus_sync_request.clone(),
+                        data_notification.notification_id,
+                        proof_ledger_info,
+                        Some(transactions_with_proof),
+                        None,
+                        payload_start_version,
+                    )
+                    .await?;
+                }
                 _ => {
                     return self
                         .handle_end_of_stream_or_invalid_payload(data_notification)
                         .await;
                 }
             }
+
+            // The stream may have been terminated (e.g., the target version
+            // is within reach).
+            if self.active_data_stream.is_none() {
+                return Ok(());
+            }
         }
     }
 
+    /// Returns the verified ledger info beyond the target version. Bounded
+    /// streams are only used once this ledger info has been found.
+    fn get_target_proof_ledger_info(&self) -> Result<LedgerInfoWithSignatures, Error> {
+        self.target_proof_ledger_info.clone().ok_or_else(|| {
+            Error::InvalidPayload("Received a bounded payload without a target proof!".into())
+        })
+    }
+
     /// Returns the highest synced version and epoch in storage
     fn get_highest_synced_version_and_epoch(&self) -> Result<(Version, Version), Error> {
         let highest_synced_version = utils::fetch_latest_synced_version(self.storage.clone())?;
@@ -205,6 +299,43 @@ impl<StorageSyncer: StorageSynchronizerInterface + Clone> ContinuousSyncer<Stora
         )
         .await?;
 
+        // If the payload would take us beyond the target version, stop
+        // streaming and fetch the remaining data using this proof instead.
+        if let Some(target_version) = self.driver_configuration.target_version {
+            let num_versions = match (
+                &transaction_list_with_proof,
+                &transaction_outputs_with_proof,
+            ) {
+                (Some(transaction_list_with_proof), _) => {
+                    transaction_list_with_proof.transactions.len()
+                }
+                (_, Some(transaction_outputs_with_proof)) => transaction_outputs_with_proof
+                    .transactions_and_outputs
+                    .len(),
+                (None, None) => 0,
+            };
+            let payload_end_version = payload_start_version
+                .checked_add(num_versions as u64)
+                .and_then(|version| version.checked_sub(1))
+                .ok_or_else(|| {
+                    Error::IntegerOverflow("The payload end version has overflown!".into())
+                })?;
+            if payload_end_version > target_version {
+                info!(
+                    "Found a proof beyond the target version! Target version: {:?}, proof version: {:?}",
+                    target_version,
+                    ledger_info_with_signatures.ledger_info().version()
+                );
+                self.target_proof_ledger_info = Some(ledger_info_with_signatures);
+                return self
+                    .terminate_active_stream(
+                        notification_id,
+                        NotificationFeedback::DataNoLongerRequired,
+                    )
+                    .await;
+            }
+        }
+
         // Execute/apply and commit the transactions/outputs
         let num_transactions_or_outputs =
             match self.driver_configuration.config.continuous_syncing_mode {
@@ -17,7 +17,7 @@ use aptos_config::config::{RoleType, StateSyncDriverConfig};
 use aptos_data_client::AptosDataClient;
 use aptos_infallible::Mutex;
 use aptos_logger::*;
-use aptos_types::waypoint::Waypoint;
+use aptos_types::{transaction::Version, waypoint::Waypoint};
 use consensus_notifications::{
     ConsensusCommitNotification, ConsensusNotification, ConsensusSyncNotification,
 };
@@ -37,21 +37,30 @@ const MAX_CONNECTION_DEADLINE_SECS: u64 = 10;
 /// The configuration of the state sync driver
 #[derive(Clone)]
 pub struct DriverConfiguration {
-    // The config file of the driver
+    // The config file of the driver (with the syncing modes of the node role applied)
     pub config: StateSyncDriverConfig,
 
     // The role of the node
     pub role: RoleType,
 
+    // The version at which to stop syncing (if any)
+    pub target_version: Option<Version>,
+
     // The trusted waypoint for the node
     pub waypoint: Waypoint,
 }
 
 impl DriverConfiguration {
-    pub fn new(config: StateSyncDriverConfig, role: RoleType, waypoint: Waypoint) -> Self {
+    pub fn new(
+        config: StateSyncDriverConfig,
+        role: RoleType,
+        target_version: Option<Version>,
+        waypoint: Waypoint,
+    ) -> Self {
         Self {
-            config,
+            config: config.for_role(role),
             role,
+            target_version,
             waypoint,
         }
     }
@@ -393,7 +402,7 @@ impl<
             }
         } else if let Err(error) = self
             .bootstrapper
-            .terminate_active_stream(notification_id, notification_feedback)
+            .handle_storage_synchronizer_error(notification_id, notification_feedback)
             .await
         {
             panic!(
@@ -460,8 +469,47 @@ impl<
         }
     }
 
+    /// Returns true iff a target version is configured and the node has
+    /// already synced to it.
+    fn reached_target_version(&self) -> Result<bool, Error> {
+        if let Some(target_version) = self.driver_configuration.target_version {
+            let latest_synced_version = utils::fetch_latest_synced_version(self.storage.clone())?;
+            if latest_synced_version > target_version {
+                return Err(Error::UnexpectedError(format!(
+                    "Synced beyond the target version! Synced version: {:?}, target version: {:?}",
+                    latest_synced_version, target_version
+                )));
+            }
+            Ok(latest_synced_version == target_version)
+        } else {
+            Ok(false)
+        }
+    }
+
     /// Checks that state sync is making progress
     async fn drive_progress(&mut self) {
+        // If we've reached the target version, stop applying any new data.
+        // Storage remains available to serve reads.
+        match self.reached_target_version() {
+            Ok(true) => {
+                trace!("The target version has been reached. There's nothing to do.");
+                if !self.bootstrapper.is_bootstrapped() {
+                    if let Err(error) = self.bootstrapper.bootstrapping_complete() {
+                        error!(
+                            "Failed to mark bootstrapping as complete! Error: {:?}",
+                            error
+                        );
+                    }
+                }
+                return;
+            }
+            Ok(false) => {}
+            Err(error) => {
+                error!("Error found when checking the target version: {:?}", error);
+                return;
+            }
+        }
+
         // Fetch the global data summary and verify we have active peers
         let global_data_summary = self.aptos_data_client.get_global_data_summary();
         if global_data_summary.is_empty() {
@@ -12,6 +12,7 @@ use crate::{
 };
 use aptos_config::config::NodeConfig;
 use aptos_data_client::aptosnet::AptosNetDataClient;
+use aptos_metrics::runtime::instrumented_runtime;
 use aptos_types::waypoint::Waypoint;
 use consensus_notifications::ConsensusNotificationListener;
 use data_streaming_service::streaming_client::StreamingServiceClient;
@@ -20,8 +21,8 @@ use executor_types::ChunkExecutorTrait;
 use futures::channel::mpsc;
 use mempool_notifications::MempoolNotificationSender;
 use std::sync::Arc;
-use storage_interface::DbReader;
-use tokio::runtime::{Builder, Runtime};
+use storage_interface::DbReaderWriter;
+use tokio::runtime::Runtime;
 
 /// Creates a new state sync driver and client
 pub struct DriverFactory {
@@ -38,7 +39,7 @@ impl DriverFactory {
         create_runtime: bool,
         node_config: &NodeConfig,
         waypoint: Waypoint,
-        storage: Arc<dyn DbReader>,
+        storage: DbReaderWriter,
         chunk_executor: Arc<ChunkExecutor>,
         mempool_notification_sender: MempoolNotifier,
         consensus_listener: ConsensusNotificationListener,
@@ -60,13 +61,11 @@ impl DriverFactory {
 
         // Create a new runtime (if required)
         let driver_runtime = if create_runtime {
-            Some(
-                Builder::new_multi_thread()
-                    .thread_name("state-sync-driver")
-                    .enable_all()
-                    .build()
-                    .expect("Failed to create state sync v2 driver runtime!"),
-            )
+            Some(instrumented_runtime(
+                "state-sync-driver",
+                node_config.runtimes.state_sync_worker_threads,
+                node_config.runtimes.stall_threshold(),
+            ))
         } else {
             None
         };
@@ -76,6 +75,7 @@ impl DriverFactory {
             chunk_executor,
             commit_notification_sender,
             error_notification_sender,
+            storage.clone(),
             driver_runtime.as_ref(),
         );
 
@@ -83,6 +83,7 @@ impl DriverFactory {
         let driver_configuration = DriverConfiguration::new(
             node_config.state_sync.state_sync_driver,
             node_config.base.role,
+            node_config.state_sync.target_version,
             waypoint,
         );
 
@@ -98,7 +99,7 @@ impl DriverFactory {
             storage_synchronizer,
             aptos_data_client,
             streaming_service_client,
-            storage,
+            storage.reader,
         );
 
         // Spawn the driver
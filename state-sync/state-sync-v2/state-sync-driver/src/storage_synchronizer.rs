@@ -7,13 +7,13 @@ use crate::{
 };
 use aptos_logger::prelude::*;
 use aptos_types::{
-    account_state_blob::AccountStatesChunkWithProof,
+    account_state_blob::{AccountStateBlob, AccountStatesChunkWithProof},
     ledger_info::LedgerInfoWithSignatures,
-    transaction::{TransactionListWithProof, TransactionOutputListWithProof},
+    transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
 };
 use data_streaming_service::data_notification::NotificationId;
 use executor_types::ChunkExecutorTrait;
-use futures::{channel::mpsc, SinkExt, StreamExt};
+use futures::{channel::mpsc, executor::block_on, SinkExt, StreamExt};
 use std::{
     future::Future,
     sync::{
@@ -21,7 +21,8 @@ use std::{
         Arc,
     },
 };
-use tokio::runtime::Runtime;
+use storage_interface::{DbReaderWriter, StateSnapshotReceiver};
+use tokio::runtime::{Handle, Runtime};
 
 // TODO(joshlind): add structured logging support!
 
@@ -53,34 +54,88 @@ pub trait StorageSynchronizerInterface {
         end_of_epoch_ledger_info: Option<LedgerInfoWithSignatures>,
     ) -> Result<(), Error>;
 
+    /// Initializes an account synchronizer that will save the account
+    /// states of the snapshot at the `target_ledger_info` version. Once all
+    /// account states have been saved, the snapshot is finalized by writing
+    /// the `target_output_with_proof` and all `epoch_change_proofs` to
+    /// storage.
+    ///
+    /// Note: this assumes that the ledger infos and the output at the
+    /// target version have already been verified.
+    fn initialize_account_synchronizer(
+        &mut self,
+        epoch_change_proofs: Vec<LedgerInfoWithSignatures>,
+        target_ledger_info: LedgerInfoWithSignatures,
+        target_output_with_proof: TransactionOutputListWithProof,
+    ) -> Result<(), Error>;
+
     /// Returns true iff there is transaction data that is still waiting
     /// to be executed/applied or committed.
     fn pending_transaction_data(&self) -> bool;
 
-    /// Saves the given account states to storage
+    /// Saves the given account states to storage. Assumes the account
+    /// synchronizer has already been initialized.
     fn save_account_states(
         &mut self,
+        notification_id: NotificationId,
         account_states_with_proof: AccountStatesChunkWithProof,
     ) -> Result<(), Error>;
 }
 
 /// The implementation of the `StorageSynchronizerInterface` used by state sync
-#[derive(Clone)]
-pub struct StorageSynchronizer {
+pub struct StorageSynchronizer<ChunkExecutor> {
+    // A channel through which to notify the account synchronizer of new account state chunks
+    account_state_notifier: Option<mpsc::Sender<(NotificationId, AccountStatesChunkWithProof)>>,
+
+    // The executor for transaction and transaction output chunks
+    chunk_executor: Arc<ChunkExecutor>,
+
+    // A channel through which to notify the driver of committed data
+    commit_notification_sender: mpsc::UnboundedSender<CommitNotification>,
+
+    // A channel through which to notify the driver of storage errors
+    error_notification_sender: mpsc::UnboundedSender<ErrorNotification>,
+
     // A channel through which to notify the executor of new transaction data chunks
     executor_notifier: mpsc::Sender<TransactionDataChunk>,
 
     // The number of transaction data chunks pending execute/apply, or commit
     pending_transaction_chunks: Arc<AtomicU64>,
+
+    // The runtime on which to spawn the storage synchronizer tasks
+    runtime: Option<Handle>,
+
+    // The interface to read from and write to storage
+    storage: DbReaderWriter,
+}
+
+// Note: this is implemented manually because deriving `Clone` would require
+// the chunk executor to also implement `Clone`.
+impl<ChunkExecutor> Clone for StorageSynchronizer<ChunkExecutor> {
+    fn clone(&self) -> Self {
+        Self {
+            account_state_notifier: self.account_state_notifier.clone(),
+            chunk_executor: self.chunk_executor.clone(),
+            commit_notification_sender: self.commit_notification_sender.clone(),
+            error_notification_sender: self.error_notification_sender.clone(),
+            executor_notifier: self.executor_notifier.clone(),
+            pending_transaction_chunks: self.pending_transaction_chunks.clone(),
+            runtime: self.runtime.clone(),
+            storage: self.storage.clone(),
+        }
+    }
 }
 
-impl StorageSynchronizer {
-    pub fn new<ChunkExecutor: ChunkExecutorTrait + 'static>(
+impl<ChunkExecutor: ChunkExecutorTrait + 'static> StorageSynchronizer<ChunkExecutor> {
+    pub fn new(
         chunk_executor: Arc<ChunkExecutor>,
         commit_notification_sender: mpsc::UnboundedSender<CommitNotification>,
         error_notification_sender: mpsc::UnboundedSender<ErrorNotification>,
+        storage: DbReaderWriter,
         runtime: Option<&Runtime>,
     ) -> Self {
+        let runtime = runtime.map(|runtime| runtime.handle().clone());
+
         // Create a channel to notify the executor when transaction data chunks are ready
         let (executor_notifier, executor_listener) = mpsc::channel(MAX_PENDING_CHUNKS);
 
@@ -97,22 +152,28 @@ impl StorageSynchronizer {
             executor_listener,
             committer_notifier,
             pending_transaction_chunks.clone(),
-            runtime,
+            runtime.clone(),
         );
 
         // Spawn the committer that commits executed (but pending) chunks
         spawn_committer(
-            chunk_executor,
+            chunk_executor.clone(),
             committer_listener,
-            commit_notification_sender,
-            error_notification_sender,
+            commit_notification_sender.clone(),
+            error_notification_sender.clone(),
             pending_transaction_chunks.clone(),
-            runtime,
+            runtime.clone(),
         );
 
         Self {
+            account_state_notifier: None,
+            chunk_executor,
+            commit_notification_sender,
+            error_notification_sender,
             executor_notifier,
             pending_transaction_chunks,
+            runtime,
+            storage,
         }
     }
 
@@ -133,7 +194,9 @@ impl StorageSynchronizer {
     }
 }
 
-impl StorageSynchronizerInterface for StorageSynchronizer {
+impl<ChunkExecutor: ChunkExecutorTrait + 'static> StorageSynchronizerInterface
+    for StorageSynchronizer<ChunkExecutor>
+{
     fn apply_transaction_outputs(
         &mut self,
         notification_id: NotificationId,
@@ -166,15 +229,52 @@ impl StorageSynchronizerInterface for StorageSynchronizer {
         self.notify_executor(transaction_data_chunk)
     }
 
+    fn initialize_account_synchronizer(
+        &mut self,
+        epoch_change_proofs: Vec<LedgerInfoWithSignatures>,
+        target_ledger_info: LedgerInfoWithSignatures,
+        target_output_with_proof: TransactionOutputListWithProof,
+    ) -> Result<(), Error> {
+        // Create a channel to notify the account synchronizer of new account state chunks
+        let (account_state_notifier, account_state_listener) = mpsc::channel(MAX_PENDING_CHUNKS);
+        self.account_state_notifier = Some(account_state_notifier);
+
+        // Spawn the account synchronizer that saves account state chunks
+        spawn_account_synchronizer(
+            self.chunk_executor.clone(),
+            account_state_listener,
+            self.storage.clone(),
+            epoch_change_proofs,
+            target_ledger_info,
+            target_output_with_proof,
+            self.commit_notification_sender.clone(),
+            self.error_notification_sender.clone(),
+            self.runtime.clone(),
+        );
+
+        Ok(())
+    }
+
     fn pending_transaction_data(&self) -> bool {
         self.pending_transaction_chunks.load(Ordering::Relaxed) > 0
     }
 
     fn save_account_states(
         &mut self,
-        _account_states_with_proof: AccountStatesChunkWithProof,
+        notification_id: NotificationId,
+        account_states_with_proof: AccountStatesChunkWithProof,
     ) -> Result<(), Error> {
-        unimplemented!("Saving account states to storage is not currently supported!")
+        let account_state_notifier = self.account_state_notifier.as_mut().ok_or_else(|| {
+            Error::UnexpectedError("The account synchronizer has not been initialized!".into())
+        })?;
+        account_state_notifier
+            .try_send((notification_id, account_states_with_proof))
+            .map_err(|error| {
+                Error::UnexpectedError(format!(
+                    "Failed to send account states to the account synchronizer: {:?}",
+                    error
+                ))
+            })
     }
 }
 
@@ -202,7 +302,7 @@ fn spawn_executor<ChunkExecutor: ChunkExecutorTrait + 'static>(
     mut executor_listener: mpsc::Receiver<TransactionDataChunk>,
     mut committer_notifier: mpsc::Sender<NotificationId>,
     pending_transaction_chunks: Arc<AtomicU64>,
-    runtime: Option<&Runtime>,
+    runtime: Option<Handle>,
 ) {
     // Create an executor
     let executor = async move {
@@ -262,7 +362,7 @@ fn spawn_committer<ChunkExecutor: ChunkExecutorTrait + 'static>(
     mut commit_notification_sender: mpsc::UnboundedSender<CommitNotification>,
     error_notification_sender: mpsc::UnboundedSender<ErrorNotification>,
     pending_transaction_chunks: Arc<AtomicU64>,
-    runtime: Option<&Runtime>,
+    runtime: Option<Handle>,
 ) {
     // Create an executor
     let committer = async move {
@@ -293,9 +393,148 @@ fn spawn_committer<ChunkExecutor: ChunkExecutorTrait + 'static>(
     spawn(runtime, committer);
 }
 
+/// Spawns a dedicated account synchronizer that saves account state chunks
+/// and finalizes the state snapshot once the last chunk has been saved.
+///
+/// Note: the state snapshot receiver is not `Send`, so the account
+/// synchronizer runs on a blocking thread that owns the receiver.
+fn spawn_account_synchronizer<ChunkExecutor: ChunkExecutorTrait + 'static>(
+    chunk_executor: Arc<ChunkExecutor>,
+    mut account_state_listener: mpsc::Receiver<(NotificationId, AccountStatesChunkWithProof)>,
+    storage: DbReaderWriter,
+    epoch_change_proofs: Vec<LedgerInfoWithSignatures>,
+    target_ledger_info: LedgerInfoWithSignatures,
+    target_output_with_proof: TransactionOutputListWithProof,
+    mut commit_notification_sender: mpsc::UnboundedSender<CommitNotification>,
+    error_notification_sender: mpsc::UnboundedSender<ErrorNotification>,
+    runtime: Option<Handle>,
+) {
+    // Create an account synchronizer
+    let account_synchronizer = move || {
+        let version = target_ledger_info.ledger_info().version();
+        let mut state_snapshot_receiver = None;
+        while let Some((notification_id, account_states_with_proof)) =
+            block_on(account_state_listener.next())
+        {
+            // Create the state snapshot receiver (if it doesn't already exist)
+            if state_snapshot_receiver.is_none() {
+                match create_state_snapshot_receiver(&storage, &target_output_with_proof, version) {
+                    Ok(receiver) => state_snapshot_receiver = Some(receiver),
+                    Err(error) => {
+                        block_on(send_storage_synchronizer_error(
+                            error_notification_sender,
+                            notification_id,
+                            error,
+                        ));
+                        return;
+                    }
+                }
+            }
+            let receiver = state_snapshot_receiver
+                .as_mut()
+                .expect("The state snapshot receiver should exist!");
+
+            // Save the account state chunk
+            let last_chunk = account_states_with_proof.is_last_chunk();
+            if let Err(error) = receiver.add_chunk(
+                account_states_with_proof.account_blobs,
+                account_states_with_proof.proof,
+            ) {
+                let error = format!("Failed to save the account state chunk! Error: {:?}", error);
+                block_on(send_storage_synchronizer_error(
+                    error_notification_sender,
+                    notification_id,
+                    error,
+                ));
+                return;
+            }
+            if !last_chunk {
+                continue;
+            }
+
+            // All account states have been saved. Finalize the state snapshot.
+            let (transactions, events) = target_output_with_proof
+                .transactions_and_outputs
+                .iter()
+                .map(|(transaction, output)| (transaction.clone(), output.events().to_vec()))
+                .unzip::<_, _, Vec<_>, Vec<_>>();
+            let result = state_snapshot_receiver
+                .take()
+                .expect("The state snapshot receiver should exist!")
+                .finish_box()
+                .and_then(|()| {
+                    storage.writer.finalize_state_snapshot(
+                        version,
+                        target_output_with_proof,
+                        &epoch_change_proofs,
+                    )
+                })
+                .and_then(|()| chunk_executor.reset());
+            if let Err(error) = result {
+                let error = format!("Failed to finalize the state snapshot! Error: {:?}", error);
+                block_on(send_storage_synchronizer_error(
+                    error_notification_sender,
+                    notification_id,
+                    error,
+                ));
+                return;
+            }
+            info!("Finalized the state snapshot at version: {:?}", version);
+
+            // Notify the driver of the newly committed snapshot
+            let commit_notification = CommitNotification::new(events.concat(), transactions);
+            if let Err(error) = block_on(commit_notification_sender.send(commit_notification)) {
+                let error = format!("Failed to send commit notification! Error: {:?}", error);
+                block_on(send_storage_synchronizer_error(
+                    error_notification_sender,
+                    notification_id,
+                    error,
+                ));
+            }
+            return;
+        }
+    };
+
+    // Spawn the account synchronizer
+    if let Some(runtime) = runtime {
+        runtime.spawn_blocking(account_synchronizer);
+    } else {
+        tokio::task::spawn_blocking(account_synchronizer);
+    }
+}
+
+/// Creates a state snapshot receiver for the account states at the given
+/// version. The expected state root is taken from the verified output.
+fn create_state_snapshot_receiver(
+    storage: &DbReaderWriter,
+    target_output_with_proof: &TransactionOutputListWithProof,
+    version: Version,
+) -> Result<Box<dyn StateSnapshotReceiver<AccountStateBlob>>, String> {
+    let expected_root_hash = target_output_with_proof
+        .proof
+        .transaction_infos
+        .first()
+        .ok_or_else(|| {
+            format!(
+                "The target output is missing a transaction info! Version: {:?}",
+                version
+            )
+        })?
+        .state_root_hash();
+    storage
+        .writer
+        .get_state_snapshot_receiver(version, expected_root_hash)
+        .map_err(|error| {
+            format!(
+                "Failed to initialize the state snapshot receiver! Error: {:?}",
+                error
+            )
+        })
+}
+
 /// Spawns a future on a specified runtime. If no runtime is specified, uses
 /// the current runtime.
-fn spawn(runtime: Option<&Runtime>, future: impl Future<Output = ()> + Send + 'static) {
+fn spawn(runtime: Option<Handle>, future: impl Future<Output = ()> + Send + 'static) {
     if let Some(runtime) = runtime {
         runtime.spawn(future);
     } else {
@@ -0,0 +1,246 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    bootstrapper::Bootstrapper, driver::DriverConfiguration, error::Error,
+    storage_synchronizer::StorageSynchronizerInterface,
+};
+use aptos_config::config::{BootstrappingMode, RoleType, StateSyncDriverConfig};
+use aptos_crypto::HashValue;
+use aptos_data_client::GlobalDataSummary;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    account_state_blob::AccountStatesChunkWithProof,
+    block_info::BlockInfo,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    transaction::{
+        Transaction, TransactionListWithProof, TransactionOutputListWithProof, WriteSetPayload,
+    },
+    validator_signer::ValidatorSigner,
+    validator_verifier::ValidatorVerifier,
+    waypoint::Waypoint,
+};
+use aptos_vm::AptosVM;
+use aptosdb::AptosDB;
+use channel::{aptos_channel, message_queues::QueueStyle};
+use data_streaming_service::{
+    data_notification::{DataNotification, DataPayload, NotificationId},
+    data_stream::DataStreamListener,
+    streaming_client::{
+        new_streaming_service_client_listener_pair, GetAllEpochEndingLedgerInfosRequest,
+        GetAllTransactionOutputsRequest, StreamRequest,
+    },
+};
+use executor::block_executor::BlockExecutor;
+use executor_test_helpers::{bootstrap_genesis, gen_block_metadata, gen_ledger_info_with_sigs};
+use executor_types::BlockExecutorTrait;
+use futures::{channel::mpsc, StreamExt};
+use std::sync::Arc;
+use storage_interface::{DbReader, DbReaderWriter};
+use storage_service_types::CompleteDataRange;
+
+/// A storage synchronizer that never holds pending data. Bootstrapping
+/// stops before any data is handed to it.
+#[derive(Clone)]
+struct MockStorageSynchronizer;
+
+impl StorageSynchronizerInterface for MockStorageSynchronizer {
+    fn apply_transaction_outputs(
+        &mut self,
+        _: NotificationId,
+        _: TransactionOutputListWithProof,
+        _: LedgerInfoWithSignatures,
+        _: Option<LedgerInfoWithSignatures>,
+    ) -> Result<(), Error> {
+        unimplemented!()
+    }
+
+    fn execute_transactions(
+        &mut self,
+        _: NotificationId,
+        _: TransactionListWithProof,
+        _: LedgerInfoWithSignatures,
+        _: Option<LedgerInfoWithSignatures>,
+    ) -> Result<(), Error> {
+        unimplemented!()
+    }
+
+    fn initialize_account_synchronizer(
+        &mut self,
+        _: Vec<LedgerInfoWithSignatures>,
+        _: LedgerInfoWithSignatures,
+        _: TransactionOutputListWithProof,
+    ) -> Result<(), Error> {
+        unimplemented!()
+    }
+
+    fn pending_transaction_data(&self) -> bool {
+        false
+    }
+
+    fn save_account_states(
+        &mut self,
+        _: NotificationId,
+        _: AccountStatesChunkWithProof,
+    ) -> Result<(), Error> {
+        unimplemented!()
+    }
+}
+
+#[tokio::test]
+async fn test_fast_sync_from_non_empty_storage() {
+    // Create a database that has synced beyond genesis (i.e., to version 1)
+    let (storage, signer) = create_storage_with_synced_block();
+    assert_eq!(storage.get_latest_version().unwrap(), 1);
+
+    // Create a bootstrapper that downloads the latest account states
+    let mut driver_config = StateSyncDriverConfig::default();
+    driver_config.bootstrapping_mode = BootstrappingMode::DownloadLatestAccountStates;
+    let driver_configuration =
+        DriverConfiguration::new(driver_config, RoleType::FullNode, None, Waypoint::default());
+    let (streaming_service_client, mut streaming_service_listener) =
+        new_streaming_service_client_listener_pair();
+    let mut bootstrapper = Bootstrapper::new(
+        driver_configuration,
+        streaming_service_client,
+        storage,
+        MockStorageSynchronizer,
+    );
+
+    // Serve every stream request with a new stream that is fed by the test
+    let (stream_sender, mut stream_receiver) = mpsc::unbounded();
+    tokio::spawn(async move {
+        while let Some(request_message) = streaming_service_listener.next().await {
+            if let StreamRequest::TerminateStream(_) = request_message.stream_request {
+                continue;
+            }
+            let (notification_sender, notification_receiver) =
+                aptos_channel::new(QueueStyle::FIFO, 10, None);
+            let _ = request_message
+                .response_sender
+                .send(Ok(DataStreamListener::new(notification_receiver)));
+            stream_sender
+                .unbounded_send((request_message.stream_request, notification_sender))
+                .unwrap();
+        }
+    });
+
+    // Advertise the end of epoch 1 and verify the epoch ending ledger infos are fetched
+    let mut global_data_summary = GlobalDataSummary::empty();
+    global_data_summary
+        .advertised_data
+        .epoch_ending_ledger_infos = vec![CompleteDataRange::from_genesis(1)];
+    bootstrapper
+        .drive_progress(&global_data_summary)
+        .await
+        .unwrap();
+    let (stream_request, notification_sender) = stream_receiver.next().await.unwrap();
+    assert_eq!(
+        stream_request,
+        StreamRequest::GetAllEpochEndingLedgerInfos(GetAllEpochEndingLedgerInfosRequest {
+            start_epoch: 1,
+        })
+    );
+
+    // Send the ledger info ending epoch 1 (far beyond the synced version) and end the stream
+    let snapshot_version = 100;
+    let epoch_ending_ledger_info = create_epoch_ending_ledger_info(1, snapshot_version, &signer);
+    for (notification_id, data_payload) in vec![
+        DataPayload::EpochEndingLedgerInfos(vec![epoch_ending_ledger_info]),
+        DataPayload::EndOfStream,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let data_notification = DataNotification {
+            notification_id: notification_id as NotificationId,
+            data_payload,
+        };
+        notification_sender.push((), data_notification).unwrap();
+    }
+    bootstrapper
+        .drive_progress(&global_data_summary)
+        .await
+        .unwrap();
+
+    // Verify there are no higher epoch ending ledger infos to fetch
+    bootstrapper
+        .drive_progress(&global_data_summary)
+        .await
+        .unwrap();
+
+    // Verify the output at the snapshot version is fetched, instead of all
+    // outputs from the synced version.
+    bootstrapper
+        .drive_progress(&global_data_summary)
+        .await
+        .unwrap();
+    let (stream_request, _) = stream_receiver.next().await.unwrap();
+    assert_eq!(
+        stream_request,
+        StreamRequest::GetAllTransactionOutputs(GetAllTransactionOutputsRequest {
+            start_version: snapshot_version,
+            end_version: snapshot_version,
+            proof_version: snapshot_version,
+        })
+    );
+    assert!(!bootstrapper.is_bootstrapped());
+}
+
+/// Creates a test database with the genesis transaction and a single block
+/// committed, along with the signer of the genesis validator.
+fn create_storage_with_synced_block() -> (Arc<dyn DbReader>, ValidatorSigner) {
+    let (genesis, validators) = vm_genesis::test_genesis_change_set_and_validators(Some(1));
+    let signer = ValidatorSigner::new(validators[0].data.address, validators[0].key.clone());
+
+    let db_path = TempPath::new();
+    db_path.create_as_dir().unwrap();
+    let (db, db_rw) = DbReaderWriter::wrap(AptosDB::new_for_test(db_path.path()));
+    let genesis_txn = Transaction::GenesisTransaction(WriteSetPayload::Direct(genesis));
+    bootstrap_genesis::<AptosVM>(&db_rw, &genesis_txn).unwrap();
+
+    // Execute and commit a block with only the block metadata transaction
+    let executor = BlockExecutor::<AptosVM>::new(db_rw);
+    let block_id = HashValue::random();
+    let block_metadata = Transaction::BlockMetadata(gen_block_metadata(1, signer.author()));
+    let output = executor
+        .execute_block(
+            (block_id, vec![block_metadata]),
+            executor.committed_block_id(),
+        )
+        .unwrap();
+    let ledger_info_with_sigs = gen_ledger_info_with_sigs(1, &output, block_id, vec![&signer]);
+    executor
+        .commit_blocks(vec![block_id], ledger_info_with_sigs)
+        .unwrap();
+
+    (db, signer)
+}
+
+/// Creates a ledger info ending the given epoch at the specified version,
+/// signed by the given signer.
+fn create_epoch_ending_ledger_info(
+    epoch: u64,
+    version: u64,
+    signer: &ValidatorSigner,
+) -> LedgerInfoWithSignatures {
+    let next_epoch_state = EpochState {
+        epoch: epoch + 1,
+        verifier: ValidatorVerifier::new_single(signer.author(), signer.public_key()),
+    };
+    let block_info = BlockInfo::new(
+        epoch,
+        0,
+        HashValue::zero(),
+        HashValue::random(),
+        version,
+        0,
+        Some(next_epoch_state),
+    );
+    let ledger_info = LedgerInfo::new(block_info, HashValue::zero());
+    let signatures = vec![(signer.author(), signer.sign(&ledger_info))]
+        .into_iter()
+        .collect();
+    LedgerInfoWithSignatures::new(ledger_info, signatures)
+}
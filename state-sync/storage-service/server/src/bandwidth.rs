@@ -0,0 +1,85 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_types::PeerId;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+// The duration of a single bandwidth quota window
+const QUOTA_WINDOW_DURATION: Duration = Duration::from_secs(1);
+
+/// The number of response bytes sent to a single peer in the current window
+struct PeerBandwidth {
+    bytes_sent: u64,
+    window_start: Instant,
+}
+
+/// Enforces per-peer bandwidth quotas by tracking the number of response
+/// bytes sent to each peer in fixed (one second) windows. Once a peer has
+/// used up its quota, its requests are rejected until the next window.
+/// Peers are forgotten once their window expires.
+pub struct PeerBandwidthQuotas {
+    max_bytes_per_window: u64,
+    peer_bandwidths: HashMap<PeerId, PeerBandwidth>,
+}
+
+impl PeerBandwidthQuotas {
+    pub fn new(max_bytes_per_second: u64) -> Self {
+        Self {
+            max_bytes_per_window: max_bytes_per_second,
+            peer_bandwidths: HashMap::new(),
+        }
+    }
+
+    /// Returns true iff the peer still has bandwidth quota left in the
+    /// current window.
+    pub fn has_remaining_quota(&mut self, peer: PeerId, now: Instant) -> bool {
+        self.get_peer_bandwidth(peer, now).bytes_sent < self.max_bytes_per_window
+    }
+
+    /// Records the number of bytes sent to the peer in the current window
+    pub fn record_bytes_sent(&mut self, peer: PeerId, num_bytes: u64, now: Instant) {
+        let peer_bandwidth = self.get_peer_bandwidth(peer, now);
+        peer_bandwidth.bytes_sent = peer_bandwidth.bytes_sent.saturating_add(num_bytes);
+    }
+
+    /// Records the number of bytes sent to the peer in the current window iff
+    /// the peer still has quota left. Checking and recording at once ensures
+    /// concurrent responses can't all be sent on the same remaining quota.
+    pub fn try_record_bytes_sent(&mut self, peer: PeerId, num_bytes: u64, now: Instant) -> bool {
+        let max_bytes_per_window = self.max_bytes_per_window;
+        let peer_bandwidth = self.get_peer_bandwidth(peer, now);
+        if peer_bandwidth.bytes_sent >= max_bytes_per_window {
+            return false;
+        }
+        peer_bandwidth.bytes_sent = peer_bandwidth.bytes_sent.saturating_add(num_bytes);
+        true
+    }
+
+    /// Removes the peers whose window has expired, as they have their whole
+    /// quota left, like peers we never sent anything to.
+    pub fn remove_idle_peers(&mut self, now: Instant) {
+        self.peer_bandwidths.retain(|_, peer_bandwidth| {
+            now.saturating_duration_since(peer_bandwidth.window_start) < QUOTA_WINDOW_DURATION
+        });
+    }
+
+    /// Returns the bandwidth of the peer for the current window. If the
+    /// previous window has expired, a new window is started.
+    fn get_peer_bandwidth(&mut self, peer: PeerId, now: Instant) -> &mut PeerBandwidth {
+        let peer_bandwidth = self
+            .peer_bandwidths
+            .entry(peer)
+            .or_insert_with(|| PeerBandwidth {
+                bytes_sent: 0,
+                window_start: now,
+            });
+        if now.saturating_duration_since(peer_bandwidth.window_start) >= QUOTA_WINDOW_DURATION {
+            peer_bandwidth.bytes_sent = 0;
+            peer_bandwidth.window_start = now;
+        }
+        peer_bandwidth
+    }
+}
@@ -4,35 +4,44 @@
 #![forbid(unsafe_code)]
 
 use crate::{
+    bandwidth::PeerBandwidthQuotas,
     logging::{LogEntry, LogSchema},
     metrics::{increment_counter, start_timer},
-    network::StorageServiceNetworkEvents,
+    network::{NetworkRequest, ResponseSender, StorageServiceNetworkEvents},
+    subscription::DataSubscriptionRequest,
 };
 use ::network::ProtocolId;
 use aptos_config::config::StorageServiceConfig;
+use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
+use aptos_time_service::{TimeService, TimeServiceTrait};
 use aptos_types::{
     account_state_blob::AccountStatesChunkWithProof,
     epoch_change::EpochChangeProof,
+    ledger_info::LedgerInfoWithSignatures,
     transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
+    PeerId,
 };
 use bounded_executor::BoundedExecutor;
 use futures::stream::StreamExt;
 use serde::{Deserialize, Serialize};
-use std::{sync::Arc, time::Duration};
+use std::{cmp::min, collections::HashMap, sync::Arc, time::Duration};
 use storage_interface::DbReader;
 use storage_service_types::{
     AccountStatesChunkWithProofRequest, CompleteDataRange, DataSummary,
-    EpochEndingLedgerInfoRequest, ProtocolMetadata, Result, ServerProtocolVersion,
-    StorageServerSummary, StorageServiceError, StorageServiceRequest, StorageServiceResponse,
-    TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
+    EpochEndingLedgerInfoRequest, NewTransactionOutputsWithProofRequest,
+    NewTransactionsWithProofRequest, ProtocolMetadata, Result, ServerProtocolVersion,
+    StorageServerSummary, StorageServerSummaryV2, StorageServiceError, StorageServiceRequest,
+    StorageServiceResponse, TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
 };
 use thiserror::Error;
 use tokio::runtime::Handle;
 
+mod bandwidth;
 mod logging;
 mod metrics;
 pub mod network;
+mod subscription;
 
 #[cfg(test)]
 mod tests;
@@ -68,9 +77,16 @@ pub struct StorageServiceServer<T> {
     config: StorageServiceConfig,
     bounded_executor: BoundedExecutor,
     storage: T,
+    time_service: TimeService,
     // TODO(philiphayes): would like a "multi-network" stream here, so we only
     // need one service for all networks.
     network_requests: StorageServiceNetworkEvents,
+
+    // The pending data subscriptions (at most one per peer)
+    data_subscriptions: Arc<Mutex<HashMap<PeerId, DataSubscriptionRequest>>>,
+
+    // The bandwidth quotas enforced for each peer
+    peer_bandwidth_quotas: Arc<Mutex<PeerBandwidthQuotas>>,
 }
 
 impl<T: StorageReaderInterface> StorageServiceServer<T> {
@@ -78,43 +94,229 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
         config: StorageServiceConfig,
         executor: Handle,
         storage: T,
+        time_service: TimeService,
         network_requests: StorageServiceNetworkEvents,
     ) -> Self {
         let bounded_executor =
             BoundedExecutor::new(config.max_concurrent_requests as usize, executor);
+        let peer_bandwidth_quotas = Arc::new(Mutex::new(PeerBandwidthQuotas::new(
+            config.max_bytes_per_peer_per_second,
+        )));
         Self {
             config,
             bounded_executor,
             storage,
+            time_service,
             network_requests,
+            data_subscriptions: Arc::new(Mutex::new(HashMap::new())),
+            peer_bandwidth_quotas,
         }
     }
 
     pub async fn start(mut self) {
-        while let Some(request) = self.network_requests.next().await {
-            // Log the request
-            let (peer, protocol, request, response_sender) = request;
-            debug!(LogSchema::new(LogEntry::ReceivedStorageRequest)
-                .request(&request)
-                .message(&format!(
-                    "Received storage request. Peer: {:?}, protocol: {:?}.",
-                    peer, protocol,
-                )));
-
-            // All handler methods are currently CPU-bound and synchronous
-            // I/O-bound, so we want to spawn on the blocking thread pool to
-            // avoid starving other async tasks on the same runtime.
-            let storage = self.storage.clone();
-            let config = self.config;
-            self.bounded_executor
-                .spawn_blocking(move || {
-                    let response = Handler::new(config, storage).call(protocol, request);
-                    log_storage_response(&response);
-                    response_sender.send(response);
+        let subscription_ticker = self.time_service.interval(Duration::from_millis(
+            self.config.subscription_refresh_interval_ms,
+        ));
+        futures::pin_mut!(subscription_ticker);
+
+        loop {
+            ::tokio::select! {
+                request = self.network_requests.next() => {
+                    match request {
+                        Some(request) => self.handle_network_request(request).await,
+                        None => return, // The network request stream has terminated
+                    }
+                }
+                _ = subscription_ticker.next() => {
+                    self.refresh_data_subscriptions().await;
+                    self.peer_bandwidth_quotas
+                        .lock()
+                        .remove_idle_peers(self.time_service.now());
+                }
+            }
+        }
+    }
+
+    /// Handles a single network request. Data subscriptions are held until
+    /// new data becomes available, while all other requests are served
+    /// immediately.
+    async fn handle_network_request(&mut self, network_request: NetworkRequest) {
+        // Log the request
+        let (peer, protocol, request, response_sender) = network_request;
+        debug!(LogSchema::new(LogEntry::ReceivedStorageRequest)
+            .request(&request)
+            .message(&format!(
+                "Received storage request. Peer: {:?}, protocol: {:?}.",
+                peer, protocol,
+            )));
+
+        // Reject the request if the peer has used up its bandwidth quota (the
+        // quota is checked again when the response is sent, see
+        // `spawn_request_handler`). Note: summary requests are always served,
+        // as peers poll these frequently to discover what data we can serve.
+        let now = self.time_service.now();
+        if !request.is_get_storage_server_summary()
+            && !self
+                .peer_bandwidth_quotas
+                .lock()
+                .has_remaining_quota(peer, now)
+        {
+            increment_counter(
+                &metrics::STORAGE_ERRORS_ENCOUNTERED,
+                protocol,
+                "too_many_requests".into(),
+            );
+            let response = Err(StorageServiceError::TooManyRequests(format!(
+                "The bandwidth quota has been exceeded for peer: {:?}",
+                peer
+            )));
+            log_storage_response(&response);
+            response_sender.send(response);
+            return;
+        }
+
+        // Hold data subscriptions until new data is available (this replaces
+        // any existing subscription for the peer).
+        if request.is_data_subscription_request() {
+            let expiry_time = now + Duration::from_millis(self.config.max_subscription_period_ms);
+            let data_subscription =
+                DataSubscriptionRequest::new(expiry_time, protocol, request, response_sender);
+            self.data_subscriptions
+                .lock()
+                .insert(peer, data_subscription);
+            return;
+        }
+
+        self.spawn_request_handler(peer, protocol, request, response_sender)
+            .await;
+    }
+
+    /// Expires all expired data subscriptions and serves the subscriptions
+    /// for which new data is now available.
+    async fn refresh_data_subscriptions(&mut self) {
+        // Remove the expired subscriptions and notify the peers
+        let now = self.time_service.now();
+        let expired_subscriptions: Vec<_> = {
+            let mut data_subscriptions = self.data_subscriptions.lock();
+            let expired_peers: Vec<_> = data_subscriptions
+                .iter()
+                .filter(|(_, data_subscription)| data_subscription.is_expired(now))
+                .map(|(peer, _)| *peer)
+                .collect();
+            expired_peers
+                .into_iter()
+                .filter_map(|peer| data_subscriptions.remove(&peer))
+                .collect()
+        };
+        for data_subscription in expired_subscriptions {
+            let (protocol, request, response_sender) = data_subscription.into_parts();
+            increment_counter(
+                &metrics::STORAGE_ERRORS_ENCOUNTERED,
+                protocol,
+                "subscription_expired".into(),
+            );
+            let response = Err(StorageServiceError::SubscriptionExpired(format!(
+                "No new data was available for request: {:?}",
+                request
+            )));
+            log_storage_response(&response);
+            response_sender.send(response);
+        }
+        if self.data_subscriptions.lock().is_empty() {
+            return;
+        }
+
+        // Fetch the highest synced version in storage
+        let highest_synced_version = match self.storage.get_data_summary() {
+            Ok(data_summary) => match data_summary.synced_ledger_info {
+                Some(synced_ledger_info) => synced_ledger_info.ledger_info().version(),
+                None => return,
+            },
+            Err(error) => {
+                error!(LogSchema::new(LogEntry::StorageServiceError)
+                    .error(&error)
+                    .message("Failed to refresh the data subscriptions!"));
+                return;
+            }
+        };
+
+        // Remove the subscriptions that can now be served
+        let ready_subscriptions: Vec<_> = {
+            let mut data_subscriptions = self.data_subscriptions.lock();
+            let ready_peers: Vec<_> = data_subscriptions
+                .iter()
+                .filter(|(_, data_subscription)| {
+                    data_subscription.known_version() < highest_synced_version
+                })
+                .map(|(peer, _)| *peer)
+                .collect();
+            ready_peers
+                .into_iter()
+                .filter_map(|peer| {
+                    data_subscriptions
+                        .remove(&peer)
+                        .map(|data_subscription| (peer, data_subscription))
                 })
+                .collect()
+        };
+
+        // Serve the ready subscriptions
+        for (peer, data_subscription) in ready_subscriptions {
+            let (protocol, request, response_sender) = data_subscription.into_parts();
+            self.spawn_request_handler(peer, protocol, request, response_sender)
                 .await;
         }
     }
+
+    /// Spawns a handler for the given request and records the response size
+    /// against the bandwidth quota of the peer. The response is replaced by an
+    /// error if the quota was used up while it was being served.
+    async fn spawn_request_handler(
+        &self,
+        peer: PeerId,
+        protocol: ProtocolId,
+        request: StorageServiceRequest,
+        response_sender: ResponseSender,
+    ) {
+        // All handler methods are currently CPU-bound and synchronous
+        // I/O-bound, so we want to spawn on the blocking thread pool to
+        // avoid starving other async tasks on the same runtime.
+        let storage = self.storage.clone();
+        let config = self.config;
+        let peer_bandwidth_quotas = self.peer_bandwidth_quotas.clone();
+        let time_service = self.time_service.clone();
+        self.bounded_executor
+            .spawn_blocking(move || {
+                let is_summary_request = request.is_get_storage_server_summary();
+                let mut response = Handler::new(config, storage).call(protocol, request);
+                if let Ok(num_bytes) = bcs::serialized_size(&response) {
+                    let num_bytes = num_bytes as u64;
+                    let now = time_service.now();
+                    let mut peer_bandwidth_quotas = peer_bandwidth_quotas.lock();
+                    let within_quota = if is_summary_request {
+                        peer_bandwidth_quotas.record_bytes_sent(peer, num_bytes, now);
+                        true
+                    } else {
+                        peer_bandwidth_quotas.try_record_bytes_sent(peer, num_bytes, now)
+                    };
+                    drop(peer_bandwidth_quotas);
+                    if !within_quota {
+                        increment_counter(
+                            &metrics::STORAGE_ERRORS_ENCOUNTERED,
+                            protocol,
+                            "too_many_requests".into(),
+                        );
+                        response = Err(StorageServiceError::TooManyRequests(format!(
+                            "The bandwidth quota has been exceeded for peer: {:?}",
+                            peer
+                        )));
+                    }
+                }
+                log_storage_response(&response);
+                response_sender.send(response);
+            })
+            .await;
+    }
 }
 
 /// The `Handler` is the "pure" inbound request handler. It contains all the
@@ -158,11 +360,20 @@ impl<T: StorageReaderInterface> Handler<T> {
             StorageServiceRequest::GetEpochEndingLedgerInfos(request) => {
                 self.get_epoch_ending_ledger_infos(request)
             }
+            StorageServiceRequest::GetNewTransactionOutputsWithProof(request) => {
+                self.get_new_transaction_outputs_with_proof(request)
+            }
+            StorageServiceRequest::GetNewTransactionsWithProof(request) => {
+                self.get_new_transactions_with_proof(request)
+            }
             StorageServiceRequest::GetNumberOfAccountsAtVersion(version) => {
                 self.get_number_of_accounts_at_version(*version)
             }
             StorageServiceRequest::GetServerProtocolVersion => self.get_server_protocol_version(),
             StorageServiceRequest::GetStorageServerSummary => self.get_storage_server_summary(),
+            StorageServiceRequest::GetStorageServerSummaryV2 => {
+                self.get_storage_server_summary_v2()
+            }
             StorageServiceRequest::GetTransactionOutputsWithProof(request) => {
                 self.get_transaction_outputs_with_proof(request)
             }
@@ -223,6 +434,105 @@ impl<T: StorageReaderInterface> Handler<T> {
         ))
     }
 
+    fn get_new_transaction_outputs_with_proof(
+        &self,
+        request: &NewTransactionOutputsWithProofRequest,
+    ) -> Result<StorageServiceResponse, Error> {
+        let (target_ledger_info, start_version, end_version) = self.get_data_subscription_range(
+            request.known_version,
+            request.known_epoch,
+            self.config.max_transaction_output_chunk_size,
+        )?;
+        let transaction_output_list_with_proof = self.storage.get_transaction_outputs_with_proof(
+            target_ledger_info.ledger_info().version(),
+            start_version,
+            end_version,
+        )?;
+
+        Ok(StorageServiceResponse::NewTransactionOutputsWithProof((
+            transaction_output_list_with_proof,
+            target_ledger_info,
+        )))
+    }
+
+    fn get_new_transactions_with_proof(
+        &self,
+        request: &NewTransactionsWithProofRequest,
+    ) -> Result<StorageServiceResponse, Error> {
+        let (target_ledger_info, start_version, end_version) = self.get_data_subscription_range(
+            request.known_version,
+            request.known_epoch,
+            self.config.max_transaction_chunk_size,
+        )?;
+        let transactions_with_proof = self.storage.get_transactions_with_proof(
+            target_ledger_info.ledger_info().version(),
+            start_version,
+            end_version,
+            request.include_events,
+        )?;
+
+        Ok(StorageServiceResponse::NewTransactionsWithProof((
+            transactions_with_proof,
+            target_ledger_info,
+        )))
+    }
+
+    /// Returns the target ledger info and the version range (inclusive) of
+    /// the new data to send to a peer with the given known version and epoch.
+    /// If the peer is in an older epoch, the target is the epoch ending
+    /// ledger info of that epoch, so that the peer can verify the data.
+    fn get_data_subscription_range(
+        &self,
+        known_version: Version,
+        known_epoch: u64,
+        max_chunk_size: u64,
+    ) -> Result<(LedgerInfoWithSignatures, Version, Version), Error> {
+        // Fetch the highest synced ledger info
+        let synced_ledger_info = self
+            .storage
+            .get_data_summary()?
+            .synced_ledger_info
+            .ok_or_else(|| Error::StorageErrorEncountered("No synced ledger info found!".into()))?;
+        if synced_ledger_info.ledger_info().version() <= known_version {
+            return Err(Error::InvalidRequest(format!(
+                "No new data beyond the known version: {:?}",
+                known_version
+            )));
+        }
+
+        // Identify the target ledger info
+        let target_ledger_info = if known_epoch < synced_ledger_info.ledger_info().epoch() {
+            self.storage
+                .get_epoch_ending_ledger_infos(known_epoch, known_epoch)?
+                .ledger_info_with_sigs
+                .first()
+                .cloned()
+                .ok_or_else(|| {
+                    Error::StorageErrorEncountered(format!(
+                        "No epoch ending ledger info found for epoch: {:?}",
+                        known_epoch
+                    ))
+                })?
+        } else {
+            synced_ledger_info
+        };
+
+        // Calculate the range of new data (bounded by the max chunk size)
+        let target_version = target_ledger_info.ledger_info().version();
+        let start_version = known_version
+            .checked_add(1)
+            .ok_or_else(|| Error::InvalidRequest("The start version has overflown!".into()))?;
+        let end_version = min(target_version, known_version.saturating_add(max_chunk_size));
+        if end_version < start_version {
+            return Err(Error::InvalidRequest(format!(
+                "The known version is beyond the target! Known version: {:?}, target version: {:?}",
+                known_version, target_version
+            )));
+        }
+
+        Ok((target_ledger_info, start_version, end_version))
+    }
+
     fn get_number_of_accounts_at_version(
         &self,
         version: Version,
@@ -243,8 +553,8 @@ impl<T: StorageReaderInterface> Handler<T> {
         ))
     }
 
-    fn get_storage_server_summary(&self) -> Result<StorageServiceResponse, Error> {
-        let storage_server_summary = StorageServerSummary {
+    fn fetch_storage_server_summary(&self) -> Result<StorageServerSummary, Error> {
+        Ok(StorageServerSummary {
             protocol_metadata: ProtocolMetadata {
                 max_epoch_chunk_size: self.config.max_epoch_chunk_size,
                 max_transaction_chunk_size: self.config.max_transaction_chunk_size,
@@ -252,13 +562,31 @@ impl<T: StorageReaderInterface> Handler<T> {
                 max_account_states_chunk_size: self.config.max_account_states_chunk_sizes,
             },
             data_summary: self.storage.get_data_summary()?,
-        };
+        })
+    }
+
+    fn get_storage_server_summary(&self) -> Result<StorageServiceResponse, Error> {
+        let storage_server_summary = self.fetch_storage_server_summary()?;
 
         Ok(StorageServiceResponse::StorageServerSummary(
             storage_server_summary,
         ))
     }
 
+    fn get_storage_server_summary_v2(&self) -> Result<StorageServiceResponse, Error> {
+        let summary = self.fetch_storage_server_summary()?;
+        let retained_account_states = self
+            .storage
+            .get_retained_account_states(&summary.data_summary.account_states)?;
+
+        Ok(StorageServiceResponse::StorageServerSummaryV2(
+            StorageServerSummaryV2 {
+                summary,
+                retained_account_states,
+            },
+        ))
+    }
+
     fn get_transaction_outputs_with_proof(
         &self,
         request: &TransactionOutputsWithProofRequest,
@@ -297,6 +625,14 @@ pub trait StorageReaderInterface: Clone + Send + 'static {
     /// Returns a data summary of the underlying storage state.
     fn get_data_summary(&self) -> Result<DataSummary, Error>;
 
+    /// Returns the versions below the given account states range whose account
+    /// states are still held, e.g., the epoch ending snapshots exempted from
+    /// pruning (lowest to highest).
+    fn get_retained_account_states(
+        &self,
+        account_states_range: &Option<CompleteDataRange<Version>>,
+    ) -> Result<Vec<Version>, Error>;
+
     /// Returns a list of transactions with a proof relative to the
     /// `proof_version`. The transaction list is expected to start at
     /// `start_version` and end at `end_version` (inclusive).
@@ -476,6 +812,24 @@ impl StorageReaderInterface for StorageReader {
         Ok(data_summary)
     }
 
+    fn get_retained_account_states(
+        &self,
+        account_states_range: &Option<CompleteDataRange<Version>>,
+    ) -> Result<Vec<Version>, Error> {
+        let account_states_range = match account_states_range {
+            Some(account_states_range) => account_states_range,
+            None => return Ok(vec![]),
+        };
+        let retained_versions = self
+            .storage
+            .get_retained_state_versions()
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?;
+        Ok(retained_versions
+            .into_iter()
+            .filter(|version| *version < account_states_range.lowest())
+            .collect())
+    }
+
     fn get_transactions_with_proof(
         &self,
         proof_version: u64,
@@ -575,6 +929,7 @@ fn log_storage_response(storage_response: &Result<StorageServiceResponse, Storag
             if matches!(
                 storage_response,
                 StorageServiceResponse::StorageServerSummary(_)
+                    | StorageServiceResponse::StorageServerSummaryV2(_)
             ) {
                 // We expect peers to be polling our storage server summary frequently,
                 // so only log this response periodically.
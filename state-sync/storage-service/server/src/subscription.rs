@@ -0,0 +1,57 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::network::ResponseSender;
+use ::network::ProtocolId;
+use aptos_types::transaction::Version;
+use std::time::Instant;
+use storage_service_types::StorageServiceRequest;
+
+/// A data subscription request that is held by the server until new data
+/// (i.e., beyond the version known by the peer) becomes available, or until
+/// the subscription expires. Expired subscriptions are dropped without a
+/// response, and the peer is expected to resubscribe.
+pub struct DataSubscriptionRequest {
+    expiry_time: Instant,
+    protocol: ProtocolId,
+    request: StorageServiceRequest,
+    response_sender: ResponseSender,
+}
+
+impl DataSubscriptionRequest {
+    pub fn new(
+        expiry_time: Instant,
+        protocol: ProtocolId,
+        request: StorageServiceRequest,
+        response_sender: ResponseSender,
+    ) -> Self {
+        Self {
+            expiry_time,
+            protocol,
+            request,
+            response_sender,
+        }
+    }
+
+    /// Returns the highest version known by the peer
+    pub fn known_version(&self) -> Version {
+        match &self.request {
+            StorageServiceRequest::GetNewTransactionOutputsWithProof(request) => {
+                request.known_version
+            }
+            StorageServiceRequest::GetNewTransactionsWithProof(request) => request.known_version,
+            request => unreachable!("Unexpected data subscription request: {:?}", request),
+        }
+    }
+
+    /// Returns true iff the subscription has expired
+    pub fn is_expired(&self, now: Instant) -> bool {
+        now >= self.expiry_time
+    }
+
+    /// Consumes the subscription and returns the protocol, request and
+    /// response sender required to serve it.
+    pub fn into_parts(self) -> (ProtocolId, StorageServiceRequest, ResponseSender) {
+        (self.protocol, self.request, self.response_sender)
+    }
+}
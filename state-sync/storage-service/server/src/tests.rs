@@ -8,6 +8,7 @@ use anyhow::Result;
 use aptos_config::config::StorageServiceConfig;
 use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, SigningKey, Uniform};
 use aptos_logger::Level;
+use aptos_time_service::{MockTimeService, TimeService};
 use aptos_types::{
     account_address::AccountAddress,
     account_state_blob::AccountStatesChunkWithProof,
@@ -26,23 +27,27 @@ use aptos_types::{
     write_set::WriteSet,
     PeerId,
 };
+use bytes::Bytes;
 use channel::aptos_channel;
-use claim::{assert_none, assert_some};
-use futures::channel::oneshot;
+use claim::{assert_matches, assert_none, assert_some};
+use futures::channel::{oneshot, oneshot::Canceled};
 use move_core_types::language_storage::TypeTag;
 use network::{
     peer_manager::PeerManagerNotification,
     protocols::{
-        network::NewNetworkEvents, rpc::InboundRpcRequest, wire::handshake::v1::ProtocolId,
+        network::NewNetworkEvents,
+        rpc::{error::RpcError, InboundRpcRequest},
+        wire::handshake::v1::ProtocolId,
     },
 };
-use std::{collections::BTreeMap, sync::Arc};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 use storage_interface::DbReader;
 use storage_service_types::{
     AccountStatesChunkWithProofRequest, CompleteDataRange, DataSummary,
-    EpochEndingLedgerInfoRequest, ProtocolMetadata, ServerProtocolVersion, StorageServerSummary,
-    StorageServiceError, StorageServiceMessage, StorageServiceRequest, StorageServiceResponse,
-    TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
+    EpochEndingLedgerInfoRequest, NewTransactionOutputsWithProofRequest,
+    NewTransactionsWithProofRequest, ProtocolMetadata, ServerProtocolVersion, StorageServerSummary,
+    StorageServerSummaryV2, StorageServiceError, StorageServiceMessage, StorageServiceRequest,
+    StorageServiceResponse, TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
 };
 
 // TODO(joshlind): Expand these test cases to better test storage interaction
@@ -55,6 +60,7 @@ const LAST_EPOCH: u64 = 10;
 const LAST_TXN_VERSION: u64 = 100;
 const NUM_ACCOUNTS_AT_VERSION: u64 = 1000;
 const PROTOCOL_VERSION: u64 = 1;
+const RETAINED_STATE_VERSION: u64 = 30;
 const STATE_PRUNE_WINDOW: u64 = 50;
 
 #[tokio::test]
@@ -167,6 +173,36 @@ async fn test_get_storage_server_summary() {
     );
 }
 
+#[tokio::test]
+async fn test_get_storage_server_summary_v2() {
+    let (mut mock_client, service) = MockClient::new();
+    tokio::spawn(service.start());
+
+    // Process requests to fetch both versions of the storage summary
+    let summary = match mock_client
+        .send_request(StorageServiceRequest::GetStorageServerSummary)
+        .await
+        .unwrap()
+    {
+        StorageServiceResponse::StorageServerSummary(summary) => summary,
+        response => panic!("Unexpected response: {:?}", response),
+    };
+    let response = mock_client
+        .send_request(StorageServiceRequest::GetStorageServerSummaryV2)
+        .await
+        .unwrap();
+
+    // Verify the response extends the summary with the retained account states
+    // below the account states range only
+    assert_eq!(
+        response,
+        StorageServiceResponse::StorageServerSummaryV2(StorageServerSummaryV2 {
+            summary,
+            retained_account_states: vec![RETAINED_STATE_VERSION],
+        })
+    );
+}
+
 #[tokio::test]
 async fn test_get_transactions_with_proof_events() {
     let (mut mock_client, service) = MockClient::new();
@@ -307,14 +343,207 @@ async fn test_get_epoch_ending_ledger_infos() {
     };
 }
 
+#[tokio::test]
+async fn test_get_new_transaction_outputs_with_proof() {
+    let (mut mock_client, service) = MockClient::new();
+    tokio::spawn(service.start());
+
+    // Subscribe to new transaction outputs beyond the known version
+    let known_version = 90;
+    let request = StorageServiceRequest::GetNewTransactionOutputsWithProof(
+        NewTransactionOutputsWithProofRequest {
+            known_version,
+            known_epoch: LAST_EPOCH,
+        },
+    );
+    let response_receiver = mock_client.send_request_async(request);
+
+    // Advance time so that the subscription is refreshed and served
+    let response = mock_client
+        .wait_for_subscription_response(response_receiver)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Verify the response is correct
+    match response {
+        StorageServiceResponse::NewTransactionOutputsWithProof((
+            outputs_with_proof,
+            ledger_info,
+        )) => {
+            assert_eq!(
+                outputs_with_proof.transactions_and_outputs.len() as u64,
+                LAST_TXN_VERSION - known_version
+            );
+            assert_eq!(
+                outputs_with_proof.first_transaction_output_version,
+                Some(known_version + 1)
+            );
+            assert_eq!(
+                ledger_info,
+                create_test_ledger_info_with_sigs(LAST_EPOCH, LAST_TXN_VERSION)
+            );
+        }
+        _ => panic!("Expected new outputs with proof but got: {:?}", response),
+    };
+}
+
+#[tokio::test]
+async fn test_get_new_transactions_with_proof() {
+    let (mut mock_client, service) = MockClient::new();
+    tokio::spawn(service.start());
+
+    // Subscribe to new transactions beyond the known version
+    let known_version = 95;
+    let request =
+        StorageServiceRequest::GetNewTransactionsWithProof(NewTransactionsWithProofRequest {
+            known_version,
+            known_epoch: LAST_EPOCH,
+            include_events: false,
+        });
+    let response_receiver = mock_client.send_request_async(request);
+
+    // Advance time so that the subscription is refreshed and served
+    let response = mock_client
+        .wait_for_subscription_response(response_receiver)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Verify the response is correct
+    match response {
+        StorageServiceResponse::NewTransactionsWithProof((
+            transactions_with_proof,
+            ledger_info,
+        )) => {
+            assert_eq!(
+                transactions_with_proof.transactions.len() as u64,
+                LAST_TXN_VERSION - known_version
+            );
+            assert_eq!(
+                transactions_with_proof.first_transaction_version,
+                Some(known_version + 1)
+            );
+            assert_none!(transactions_with_proof.events);
+            assert_eq!(
+                ledger_info,
+                create_test_ledger_info_with_sigs(LAST_EPOCH, LAST_TXN_VERSION)
+            );
+        }
+        _ => panic!(
+            "Expected new transactions with proof but got: {:?}",
+            response
+        ),
+    };
+}
+
+#[tokio::test]
+async fn test_data_subscription_expiry() {
+    let (mut mock_client, service) = MockClient::new();
+    tokio::spawn(service.start());
+
+    // Subscribe to new transaction outputs at the highest synced version
+    let request = StorageServiceRequest::GetNewTransactionOutputsWithProof(
+        NewTransactionOutputsWithProofRequest {
+            known_version: LAST_TXN_VERSION,
+            known_epoch: LAST_EPOCH,
+        },
+    );
+    let response_receiver = mock_client.send_request_async(request);
+
+    // Verify the subscription expires with an explicit error (no new data)
+    let response = mock_client
+        .wait_for_subscription_response(response_receiver)
+        .await
+        .unwrap();
+    assert_matches!(response, Err(StorageServiceError::SubscriptionExpired(_)));
+}
+
+#[tokio::test]
+async fn test_peer_bandwidth_quota() {
+    let storage_config = StorageServiceConfig {
+        max_bytes_per_peer_per_second: 1,
+        ..Default::default()
+    };
+    let (mut mock_client, service) = MockClient::new_with_config(storage_config);
+    tokio::spawn(service.start());
+
+    // Verify the first request is served (and uses up the quota)
+    let request = StorageServiceRequest::GetNumberOfAccountsAtVersion(0);
+    let response = mock_client.send_request(request.clone()).await.unwrap();
+    assert_eq!(
+        response,
+        StorageServiceResponse::NumberOfAccountsAtVersion(NUM_ACCOUNTS_AT_VERSION)
+    );
+
+    // Verify the next request is rejected
+    let response = mock_client.send_request(request.clone()).await;
+    assert_matches!(response, Err(StorageServiceError::TooManyRequests(_)));
+
+    // Verify that summary requests are still served
+    let response = mock_client
+        .send_request(StorageServiceRequest::GetStorageServerSummary)
+        .await
+        .unwrap();
+    assert_matches!(response, StorageServiceResponse::StorageServerSummary(_));
+
+    // Advance time to the next quota window and verify the request is served
+    mock_client.mock_time.advance_secs_async(1).await;
+    let response = mock_client.send_request(request).await.unwrap();
+    assert_eq!(
+        response,
+        StorageServiceResponse::NumberOfAccountsAtVersion(NUM_ACCOUNTS_AT_VERSION)
+    );
+}
+
+#[tokio::test]
+async fn test_peer_bandwidth_quota_concurrent_requests() {
+    let storage_config = StorageServiceConfig {
+        max_bytes_per_peer_per_second: 1,
+        ..Default::default()
+    };
+    let (mut mock_client, service) = MockClient::new_with_config(storage_config);
+    tokio::spawn(service.start());
+
+    // Send requests without waiting for the responses, so they're all
+    // admitted before the first response is sent
+    let request = StorageServiceRequest::GetNumberOfAccountsAtVersion(0);
+    let response_receivers: Vec<_> = (0..5)
+        .map(|_| mock_client.send_request_async(request.clone()))
+        .collect();
+
+    // Verify a single response is sent on the quota
+    let mut num_served = 0;
+    for response_receiver in response_receivers {
+        match MockClient::deserialize_response(response_receiver.await.unwrap()) {
+            Ok(response) => {
+                assert_eq!(
+                    response,
+                    StorageServiceResponse::NumberOfAccountsAtVersion(NUM_ACCOUNTS_AT_VERSION)
+                );
+                num_served += 1;
+            }
+            Err(error) => assert_matches!(error, StorageServiceError::TooManyRequests(_)),
+        }
+    }
+    assert_eq!(num_served, 1);
+}
+
 /// A wrapper around the inbound network interface/channel for easily sending
 /// mock client requests to a [`StorageServiceServer`].
 struct MockClient {
+    mock_time: MockTimeService,
     peer_mgr_notifs_tx: aptos_channel::Sender<(PeerId, ProtocolId), PeerManagerNotification>,
 }
 
 impl MockClient {
     fn new() -> (Self, StorageServiceServer<StorageReader>) {
+        Self::new_with_config(StorageServiceConfig::default())
+    }
+
+    fn new_with_config(
+        storage_config: StorageServiceConfig,
+    ) -> (Self, StorageServiceServer<StorageReader>) {
         initialize_logger();
         let storage = StorageReader::new(Arc::new(MockDbReader));
 
@@ -327,20 +556,37 @@ impl MockClient {
             StorageServiceNetworkEvents::new(peer_mgr_notifs_rx, connection_notifs_rx);
 
         let executor = tokio::runtime::Handle::current();
+        let time_service = TimeService::mock();
         let storage_server = StorageServiceServer::new(
-            StorageServiceConfig::default(),
+            storage_config,
             executor,
             storage,
+            time_service.clone(),
             network_requests,
         );
 
-        (Self { peer_mgr_notifs_tx }, storage_server)
+        let mock_client = Self {
+            mock_time: time_service.into_mock(),
+            peer_mgr_notifs_tx,
+        };
+        (mock_client, storage_server)
     }
 
+    /// Sends the request and waits for the response
     async fn send_request(
         &mut self,
         request: StorageServiceRequest,
     ) -> Result<StorageServiceResponse, StorageServiceError> {
+        let response_receiver = self.send_request_async(request);
+        let response = response_receiver.await.unwrap();
+        Self::deserialize_response(response)
+    }
+
+    /// Sends the request and returns the receiver for the response
+    fn send_request_async(
+        &mut self,
+        request: StorageServiceRequest,
+    ) -> oneshot::Receiver<Result<Bytes, RpcError>> {
         // craft the inbound Rpc notification
         let peer_id = PeerId::ZERO;
         let protocol_id = ProtocolId::StorageServiceRpc;
@@ -360,10 +606,33 @@ impl MockClient {
             .push((peer_id, protocol_id), notif)
             .unwrap();
 
-        // wait for the response and deserialize
-        let response = res_rx.await.unwrap().unwrap();
-        let response = protocol_id
-            .from_bytes::<StorageServiceMessage>(&response)
+        res_rx
+    }
+
+    /// Advances time (so that the server refreshes its data subscriptions)
+    /// until a response is sent for the subscription, or the subscription
+    /// is dropped.
+    async fn wait_for_subscription_response(
+        &mut self,
+        mut response_receiver: oneshot::Receiver<Result<Bytes, RpcError>>,
+    ) -> Result<Result<StorageServiceResponse, StorageServiceError>, Canceled> {
+        let refresh_interval =
+            Duration::from_millis(StorageServiceConfig::default().subscription_refresh_interval_ms);
+        loop {
+            if let Some(response) = response_receiver.try_recv()? {
+                return Ok(Self::deserialize_response(response));
+            }
+            self.mock_time.advance_async(refresh_interval).await;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    /// Deserializes the raw response sent by the server
+    fn deserialize_response(
+        response: Result<Bytes, RpcError>,
+    ) -> Result<StorageServiceResponse, StorageServiceError> {
+        let response = ProtocolId::StorageServiceRpc
+            .from_bytes::<StorageServiceMessage>(&response.unwrap())
             .unwrap();
         match response {
             StorageServiceMessage::Response(response) => response,
@@ -548,6 +817,11 @@ impl DbReader for MockDbReader {
     fn get_state_prune_window(&self) -> Option<usize> {
         Some(STATE_PRUNE_WINDOW as usize)
     }
+
+    fn get_retained_state_versions(&self) -> Result<Vec<Version>> {
+        // The versions within the account states range aren't advertised
+        Ok(vec![RETAINED_STATE_VERSION, LAST_TXN_VERSION])
+    }
 }
 
 /// Initializes the Diem logger for tests
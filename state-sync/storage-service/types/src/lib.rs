@@ -34,6 +34,10 @@ pub type Result<T, E = StorageServiceError> = ::std::result::Result<T, E>;
 pub enum StorageServiceError {
     #[error("Internal service error: {0}")]
     InternalError(String),
+    #[error("Too many requests (the peer bandwidth quota was exceeded): {0}")]
+    TooManyRequests(String),
+    #[error("The data subscription expired before new data was available: {0}")]
+    SubscriptionExpired(String),
 }
 
 /// A single storage service message sent or received over AptosNet.
@@ -58,6 +62,9 @@ pub enum StorageServiceRequest {
     GetStorageServerSummary,               // Fetches a summary of the storage server state
     GetTransactionOutputsWithProof(TransactionOutputsWithProofRequest), // Fetches a list of transaction outputs with a proof
     GetTransactionsWithProof(TransactionsWithProofRequest), // Fetches a list of transactions with a proof
+    GetNewTransactionOutputsWithProof(NewTransactionOutputsWithProofRequest), // Subscribes to new transaction outputs beyond a known version
+    GetNewTransactionsWithProof(NewTransactionsWithProofRequest), // Subscribes to new transactions beyond a known version
+    GetStorageServerSummaryV2, // Fetches a summary of the storage server state, including the retained account states
 }
 
 impl StorageServiceRequest {
@@ -71,11 +78,26 @@ impl StorageServiceRequest {
             Self::GetStorageServerSummary => "get_storage_server_summary",
             Self::GetTransactionOutputsWithProof(_) => "get_transaction_outputs_with_proof",
             Self::GetTransactionsWithProof(_) => "get_transactions_with_proof",
+            Self::GetNewTransactionOutputsWithProof(_) => "get_new_transaction_outputs_with_proof",
+            Self::GetNewTransactionsWithProof(_) => "get_new_transactions_with_proof",
+            Self::GetStorageServerSummaryV2 => "get_storage_server_summary_v2",
         }
     }
 
     pub fn is_get_storage_server_summary(&self) -> bool {
-        matches!(self, &Self::GetStorageServerSummary)
+        matches!(
+            self,
+            &Self::GetStorageServerSummary | &Self::GetStorageServerSummaryV2
+        )
+    }
+
+    /// Returns true iff the request is a subscription to new data, i.e., the
+    /// server will only respond once data beyond the known version exists.
+    pub fn is_data_subscription_request(&self) -> bool {
+        matches!(
+            self,
+            &Self::GetNewTransactionOutputsWithProof(_) | &Self::GetNewTransactionsWithProof(_)
+        )
     }
 }
 
@@ -91,6 +113,9 @@ pub enum StorageServiceResponse {
     StorageServerSummary(StorageServerSummary),
     TransactionOutputsWithProof(TransactionOutputListWithProof),
     TransactionsWithProof(TransactionListWithProof),
+    NewTransactionOutputsWithProof((TransactionOutputListWithProof, LedgerInfoWithSignatures)),
+    NewTransactionsWithProof((TransactionListWithProof, LedgerInfoWithSignatures)),
+    StorageServerSummaryV2(StorageServerSummaryV2),
 }
 
 // TODO(philiphayes): is there a proc-macro for this?
@@ -105,6 +130,9 @@ impl StorageServiceResponse {
             Self::StorageServerSummary(_) => "storage_server_summary",
             Self::TransactionOutputsWithProof(_) => "transaction_outputs_with_proof",
             Self::TransactionsWithProof(_) => "transactions_with_proof",
+            Self::NewTransactionOutputsWithProof(_) => "new_transaction_outputs_with_proof",
+            Self::NewTransactionsWithProof(_) => "new_transactions_with_proof",
+            Self::StorageServerSummaryV2(_) => "storage_server_summary_v2",
         }
     }
 }
@@ -116,6 +144,9 @@ impl Display for StorageServiceResponse {
             StorageServiceResponse::StorageServerSummary(storage_summary) => {
                 format!("{:?}", storage_summary)
             }
+            StorageServiceResponse::StorageServerSummaryV2(storage_summary) => {
+                format!("{:?}", storage_summary)
+            }
             _ => "...".into(),
         };
         write!(
@@ -160,6 +191,34 @@ impl TryFrom<StorageServiceResponse> for EpochChangeProof {
     }
 }
 
+impl TryFrom<StorageServiceResponse>
+    for (TransactionOutputListWithProof, LedgerInfoWithSignatures)
+{
+    type Error = UnexpectedResponseError;
+    fn try_from(response: StorageServiceResponse) -> Result<Self, Self::Error> {
+        match response {
+            StorageServiceResponse::NewTransactionOutputsWithProof(inner) => Ok(inner),
+            _ => Err(UnexpectedResponseError(format!(
+                "expected new_transaction_outputs_with_proof, found {}",
+                response.get_label()
+            ))),
+        }
+    }
+}
+
+impl TryFrom<StorageServiceResponse> for (TransactionListWithProof, LedgerInfoWithSignatures) {
+    type Error = UnexpectedResponseError;
+    fn try_from(response: StorageServiceResponse) -> Result<Self, Self::Error> {
+        match response {
+            StorageServiceResponse::NewTransactionsWithProof(inner) => Ok(inner),
+            _ => Err(UnexpectedResponseError(format!(
+                "expected new_transactions_with_proof, found {}",
+                response.get_label()
+            ))),
+        }
+    }
+}
+
 impl TryFrom<StorageServiceResponse> for u64 {
     type Error = UnexpectedResponseError;
     fn try_from(response: StorageServiceResponse) -> Result<Self, Self::Error> {
@@ -199,6 +258,22 @@ impl TryFrom<StorageServiceResponse> for StorageServerSummary {
     }
 }
 
+/// Servers which don't support `GetStorageServerSummaryV2` can still be polled
+/// for a `StorageServerSummary`, which retains no account states.
+impl TryFrom<StorageServiceResponse> for StorageServerSummaryV2 {
+    type Error = UnexpectedResponseError;
+    fn try_from(response: StorageServiceResponse) -> Result<Self, Self::Error> {
+        match response {
+            StorageServiceResponse::StorageServerSummaryV2(inner) => Ok(inner),
+            StorageServiceResponse::StorageServerSummary(inner) => Ok(inner.into()),
+            _ => Err(UnexpectedResponseError(format!(
+                "expected storage_server_summary_v2, found {}",
+                response.get_label()
+            ))),
+        }
+    }
+}
+
 impl TryFrom<StorageServiceResponse> for TransactionOutputListWithProof {
     type Error = UnexpectedResponseError;
     fn try_from(response: StorageServiceResponse) -> Result<Self, Self::Error> {
@@ -234,6 +309,25 @@ pub struct AccountStatesChunkWithProofRequest {
     pub end_account_index: u64,   // The account index to stop fetching account states (inclusive)
 }
 
+/// A storage service subscription for new transaction outputs (with a proof)
+/// beyond the version already known by the client. The server responds once
+/// new outputs exist (or drops the subscription once it expires).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct NewTransactionOutputsWithProofRequest {
+    pub known_version: u64, // The highest version known by the client
+    pub known_epoch: u64,   // The highest epoch known by the client
+}
+
+/// A storage service subscription for new transactions (with a proof) beyond
+/// the version already known by the client. The server responds once new
+/// transactions exist (or drops the subscription once it expires).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct NewTransactionsWithProofRequest {
+    pub known_version: u64,   // The highest version known by the client
+    pub known_epoch: u64,     // The highest epoch known by the client
+    pub include_events: bool, // Whether or not to include events in the response
+}
+
 /// A storage service request for fetching a transaction output list with a
 /// corresponding proof.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -282,6 +376,56 @@ impl StorageServerSummary {
     }
 }
 
+/// A storage server summary, extended with the account states still held below
+/// the account states range. This is a separate message (rather than a new field
+/// of the summary) so that the summaries sent to older clients stay compatible.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StorageServerSummaryV2 {
+    pub summary: StorageServerSummary,
+    /// The versions below the account states range whose account states are
+    /// still held in storage, e.g., the epoch ending snapshots exempted from
+    /// pruning (in ascending order).
+    pub retained_account_states: Vec<Version>,
+}
+
+impl StorageServerSummaryV2 {
+    pub fn can_service(&self, request: &StorageServiceRequest) -> bool {
+        use StorageServiceRequest::*;
+        if self.summary.can_service(request) {
+            return true;
+        }
+
+        // The retained account states can also be served
+        let is_retained =
+            |version: &Version| self.retained_account_states.binary_search(version).is_ok();
+        match request {
+            GetAccountStatesChunkWithProof(chunk_request) => {
+                let can_create_proof = self
+                    .summary
+                    .data_summary
+                    .synced_ledger_info
+                    .as_ref()
+                    .map(|li| li.ledger_info().version() >= chunk_request.version)
+                    .unwrap_or(false);
+                self.summary.protocol_metadata.can_service(request)
+                    && can_create_proof
+                    && is_retained(&chunk_request.version)
+            }
+            GetNumberOfAccountsAtVersion(version) => is_retained(version),
+            _ => false,
+        }
+    }
+}
+
+impl From<StorageServerSummary> for StorageServerSummaryV2 {
+    fn from(summary: StorageServerSummary) -> Self {
+        Self {
+            summary,
+            retained_account_states: vec![],
+        }
+    }
+}
+
 /// A summary of the protocol metadata for the storage service instance, such as
 /// the maximum chunk sizes supported for different requests.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -298,7 +442,10 @@ impl ProtocolMetadata {
         match request {
             GetServerProtocolVersion
             | GetStorageServerSummary
+            | GetStorageServerSummaryV2
             | GetNumberOfAccountsAtVersion(_) => true,
+            // The server bounds the size of data subscription responses itself
+            GetNewTransactionOutputsWithProof(_) | GetNewTransactionsWithProof(_) => true,
             GetAccountStatesChunkWithProof(request) => {
                 CompleteDataRange::new(request.start_account_index, request.end_account_index)
                     .map_or(false, |range| {
@@ -380,6 +527,7 @@ impl DataSummary {
             // storage services can always serve these metadata requests
             GetServerProtocolVersion => true,
             GetStorageServerSummary => true,
+            GetStorageServerSummaryV2 => true,
             GetAccountStatesChunkWithProof(request) => {
                 let proof_version = request.version;
 
@@ -406,6 +554,18 @@ impl DataSummary {
                     .map(|range| range.superset_of(&desired_range))
                     .unwrap_or(false)
             }
+            GetNewTransactionOutputsWithProof(request) => {
+                // The server must hold all outputs after the known version
+                self.transaction_outputs
+                    .map(|range| range.lowest() <= request.known_version.saturating_add(1))
+                    .unwrap_or(false)
+            }
+            GetNewTransactionsWithProof(request) => {
+                // The server must hold all transactions after the known version
+                self.transactions
+                    .map(|range| range.lowest() <= request.known_version.saturating_add(1))
+                    .unwrap_or(false)
+            }
             GetNumberOfAccountsAtVersion(version) => self
                 .account_states
                 .map(|range| range.contains(*version))
@@ -644,6 +804,23 @@ mod tests {
         })
     }
 
+    fn get_new_txns_request(known_version: Version) -> StorageServiceRequest {
+        StorageServiceRequest::GetNewTransactionsWithProof(NewTransactionsWithProofRequest {
+            known_version,
+            known_epoch: 0,
+            include_events: false,
+        })
+    }
+
+    fn get_new_txn_outputs_request(known_version: Version) -> StorageServiceRequest {
+        StorageServiceRequest::GetNewTransactionOutputsWithProof(
+            NewTransactionOutputsWithProofRequest {
+                known_version,
+                known_epoch: 0,
+            },
+        )
+    }
+
     fn get_account_states_request(version: Version) -> StorageServiceRequest {
         get_account_state_chunks_request(version, 0, 1000)
     }
@@ -784,6 +961,44 @@ mod tests {
         // can provide proof, but out of range ==> cannot service
         assert!(!summary.can_service(&get_account_states_request(50)));
         assert!(!summary.can_service(&get_account_states_request(99)));
+
+        // retained below the range => can service
+        let summary = StorageServerSummaryV2 {
+            summary: StorageServerSummary {
+                protocol_metadata: ProtocolMetadata::default(),
+                data_summary: summary,
+            },
+            retained_account_states: vec![50, 75, 260],
+        };
+        assert!(summary.can_service(&get_account_states_request(50)));
+        assert!(summary.can_service(&get_account_states_request(75)));
+        assert!(summary.can_service(&get_account_states_request(200)));
+        assert!(!summary.can_service(&get_account_states_request(60)));
+        assert!(!summary.can_service(&get_account_states_request(99)));
+
+        // retained, but cannot provide proof => cannot service
+        assert!(!summary.can_service(&get_account_states_request(260)));
+    }
+
+    #[test]
+    fn test_data_summary_can_service_data_subscriptions() {
+        let summary = DataSummary {
+            synced_ledger_info: Some(mock_ledger_info(250)),
+            transactions: Some(range(100, 250)),
+            transaction_outputs: Some(range(150, 250)),
+            ..Default::default()
+        };
+
+        // holds all data after the known version => can service
+        assert!(summary.can_service(&get_new_txns_request(99)));
+        assert!(summary.can_service(&get_new_txns_request(250)));
+        assert!(summary.can_service(&get_new_txn_outputs_request(149)));
+        assert!(summary.can_service(&get_new_txn_outputs_request(300)));
+
+        // missing data after the known version => cannot service
+        assert!(!summary.can_service(&get_new_txns_request(50)));
+        assert!(!summary.can_service(&get_new_txn_outputs_request(99)));
+        assert!(!summary.can_service(&get_new_txn_outputs_request(148)));
     }
 
     #[test]
@@ -0,0 +1,95 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compares reading account states one by one with reading them in batches, which read the
+//! nodes at each depth of the state Merkle tree with a single RocksDB MultiGet.
+
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_temppath::TempPath;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_state_blob::AccountStateBlob,
+    transaction::{Transaction, TransactionInfo, TransactionToCommit},
+    vm_status::KeptVMStatus,
+    write_set::WriteSet,
+};
+use aptosdb::AptosDB;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rand::{prelude::StdRng, seq::SliceRandom, SeedableRng};
+use storage_interface::{DbReader, DbWriter};
+
+const NUM_ACCOUNTS: usize = 100_000;
+const NUM_ACCOUNTS_PER_TXN: usize = 10_000;
+const BATCH_SIZES: &[usize] = &[10, 100, 1000];
+
+fn create_db(path: &TempPath, addresses: &[AccountAddress]) -> AptosDB {
+    let db = AptosDB::new_for_test(path);
+    let txns_to_commit = addresses
+        .chunks(NUM_ACCOUNTS_PER_TXN)
+        .map(|addresses| {
+            let transaction = Transaction::StateCheckpoint;
+            let transaction_info = TransactionInfo::new(
+                transaction.hash(),
+                HashValue::zero(),
+                HashValue::zero(),
+                0,
+                KeptVMStatus::Executed,
+            );
+            let account_states = addresses
+                .iter()
+                .map(|address| (*address, AccountStateBlob::from(address.to_vec())))
+                .collect();
+            TransactionToCommit::new(
+                transaction,
+                transaction_info,
+                account_states,
+                None,
+                WriteSet::default(),
+                vec![],
+            )
+        })
+        .collect::<Vec<_>>();
+    db.save_transactions(&txns_to_commit, 0, None).unwrap();
+    db
+}
+
+fn account_state_reads(c: &mut Criterion) {
+    let mut rng = StdRng::from_seed([0u8; 32]);
+    let addresses = (0..NUM_ACCOUNTS)
+        .map(|_| AccountAddress::random())
+        .collect::<Vec<_>>();
+    let path = TempPath::new();
+    let db = create_db(&path, &addresses);
+    let version = (NUM_ACCOUNTS / NUM_ACCOUNTS_PER_TXN) as u64 - 1;
+
+    let mut group = c.benchmark_group("account_state_reads");
+    for batch_size in BATCH_SIZES {
+        let batch = addresses
+            .choose_multiple(&mut rng, *batch_size)
+            .cloned()
+            .collect::<Vec<_>>();
+        group.throughput(Throughput::Elements(*batch_size as u64));
+
+        group.bench_function(BenchmarkId::new("get", batch_size), |b| {
+            b.iter(|| {
+                batch
+                    .iter()
+                    .map(|address| {
+                        db.get_account_state_with_proof_by_version(*address, version)
+                            .unwrap()
+                    })
+                    .collect::<Vec<_>>()
+            })
+        });
+        group.bench_function(BenchmarkId::new("multi_get", batch_size), |b| {
+            b.iter(|| {
+                db.get_account_states_with_proof_by_version(&batch, version)
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, account_state_reads);
+criterion_main!(benches);
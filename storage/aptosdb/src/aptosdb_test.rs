@@ -563,6 +563,99 @@ fn verify_committed_transactions(
     verify_account_txns(db, group_txns_by_account(txns_to_commit), ledger_info);
 }
 
+fn test_finalize_state_snapshot_impl(
+    input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
+    // Commit all blocks to the source db
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let mut cur_ver = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+        db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+            .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+    }
+    let ledger_info_with_sigs = input.last().unwrap().1.clone();
+    let version = ledger_info_with_sigs.ledger_info().version();
+
+    // Restore the state snapshot at the latest version into an empty db
+    let snapshot_tmp_dir = TempPath::new();
+    let snapshot_db = AptosDB::new_for_test(&snapshot_tmp_dir);
+    let output_with_proof = db.get_transaction_outputs(version, 1, version).unwrap();
+    let expected_root_hash = output_with_proof.proof.transaction_infos[0].state_root_hash();
+    let mut receiver = snapshot_db
+        .get_state_snapshot_receiver(version, expected_root_hash)
+        .unwrap();
+    let num_accounts = db.get_account_count(version).unwrap();
+    let chunk = db
+        .get_account_chunk_with_proof(version, 0, num_accounts)
+        .unwrap();
+    assert!(chunk.is_last_chunk());
+    receiver
+        .add_chunk(chunk.account_blobs, chunk.proof)
+        .unwrap();
+    receiver.finish_box().unwrap();
+
+    // Finalize the snapshot and verify the db is bootstrapped at the snapshot version
+    snapshot_db
+        .finalize_state_snapshot(version, output_with_proof, &[ledger_info_with_sigs.clone()])
+        .unwrap();
+    assert_eq!(
+        snapshot_db.get_latest_ledger_info().unwrap(),
+        ledger_info_with_sigs
+    );
+    assert_eq!(snapshot_db.get_latest_version().unwrap(), version);
+    assert_eq!(
+        snapshot_db.get_latest_tree_state().unwrap(),
+        db.get_latest_tree_state().unwrap()
+    );
+}
+
+fn test_catch_up_with_primary_impl(
+    input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
+    let tmp_dir = TempPath::new();
+    let tmp_dir_sec = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+
+    let (first_txns, first_ledger_info) = input.first().unwrap();
+    db.save_transactions(first_txns, 0, Some(first_ledger_info))
+        .unwrap();
+    let db_sec =
+        AptosDB::open_as_secondary(tmp_dir.path(), tmp_dir_sec.path(), RocksdbConfig::default())
+            .unwrap();
+    assert_eq!(
+        db_sec.ledger_store.get_latest_ledger_info().unwrap(),
+        *first_ledger_info
+    );
+
+    let mut cur_ver = first_txns.len() as u64;
+    for (txns_to_commit, ledger_info_with_sigs) in input.iter().skip(1) {
+        db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+            .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+    }
+
+    // The secondary only sees the writes of the primary once caught up.
+    let (last_txns, last_ledger_info) = input.last().unwrap();
+    assert_eq!(
+        db_sec.ledger_store.get_latest_ledger_info().unwrap(),
+        *first_ledger_info
+    );
+    db_sec.try_catch_up_with_primary().unwrap();
+    assert_eq!(
+        db_sec.ledger_store.get_latest_ledger_info().unwrap(),
+        *last_ledger_info
+    );
+    verify_committed_transactions(
+        &db_sec,
+        last_txns,
+        cur_ver - last_txns.len() as u64,
+        last_ledger_info,
+        true, /* is_latest */
+    );
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
 
@@ -575,6 +668,16 @@ proptest! {
     fn test_sync_transactions(input in arb_blocks_to_commit()) {
         test_sync_transactions_impl(input);
     }
+
+    #[test]
+    fn test_finalize_state_snapshot(input in arb_blocks_to_commit()) {
+        test_finalize_state_snapshot_impl(input);
+    }
+
+    #[test]
+    fn test_catch_up_with_primary(input in arb_blocks_to_commit()) {
+        test_catch_up_with_primary_impl(input);
+    }
 }
 
 #[test]
@@ -26,6 +26,7 @@ impl Aptossum {
             NO_OP_STORAGE_PRUNER_CONFIG, /* no prune_window */
             RocksdbConfig::default(),
             true, /* account_count_migration, ignored anyway */
+            true, /* state_value_index, ignored anyway */
         )?;
         Ok(Aptossum { db })
     }
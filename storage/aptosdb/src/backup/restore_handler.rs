@@ -91,34 +91,7 @@ impl RestoreHandler {
         num_leaves: LeafCount,
         frozen_subtrees: &[HashValue],
     ) -> Result<()> {
-        let mut cs = ChangeSet::new();
-        let positions: Vec<_> = FrozenSubTreeIterator::new(num_leaves).collect();
-
-        ensure!(
-            positions.len() == frozen_subtrees.len(),
-            "Number of frozen subtree roots not expected. Expected: {}, actual: {}",
-            positions.len(),
-            frozen_subtrees.len(),
-        );
-
-        positions
-            .iter()
-            .zip(frozen_subtrees.iter().rev())
-            .map(|(p, h)| {
-                if let Some(_h) = self.db.get::<TransactionAccumulatorSchema>(p)? {
-                    ensure!(
-                        h == &_h,
-                        "Frozen subtree root does not match that already in DB. Provided: {}, in db: {}.",
-                        h,
-                        _h,
-                    );
-                } else {
-                    cs.batch.put::<TransactionAccumulatorSchema>(p, h)?;
-                }
-                Ok(())
-            })
-            .collect::<Result<Vec<_>>>()?;
-        self.db.write_schemas(cs.batch)
+        confirm_or_save_frozen_subtrees(&self.db, num_leaves, frozen_subtrees)
     }
 
     pub fn save_transactions(
@@ -167,3 +140,41 @@ impl RestoreHandler {
             .map_or(0, |(ver, _txn_info)| ver + 1))
     }
 }
+
+/// Confirms the frozen subtree roots of a transaction accumulator with `num_leaves` leaves
+/// against those already in the DB, saving any that are missing. `frozen_subtrees` are
+/// ordered as the left siblings of an accumulator range proof starting at leaf `num_leaves`.
+pub(crate) fn confirm_or_save_frozen_subtrees(
+    db: &DB,
+    num_leaves: LeafCount,
+    frozen_subtrees: &[HashValue],
+) -> Result<()> {
+    let mut cs = ChangeSet::new();
+    let positions: Vec<_> = FrozenSubTreeIterator::new(num_leaves).collect();
+
+    ensure!(
+        positions.len() == frozen_subtrees.len(),
+        "Number of frozen subtree roots not expected. Expected: {}, actual: {}",
+        positions.len(),
+        frozen_subtrees.len(),
+    );
+
+    positions
+        .iter()
+        .zip(frozen_subtrees.iter().rev())
+        .map(|(p, h)| {
+            if let Some(_h) = db.get::<TransactionAccumulatorSchema>(p)? {
+                ensure!(
+                    h == &_h,
+                    "Frozen subtree root does not match that already in DB. Provided: {}, in db: {}.",
+                    h,
+                    _h,
+                );
+            } else {
+                cs.batch.put::<TransactionAccumulatorSchema>(p, h)?;
+            }
+            Ok(())
+        })
+        .collect::<Result<Vec<_>>>()?;
+    db.write_schemas(cs.batch)
+}
@@ -12,7 +12,8 @@ use crate::{
     ledger_counters::{LedgerCounter, LedgerCounterBumps},
     schema::{
         event::EventSchema, event_accumulator::EventAccumulatorSchema,
-        event_by_key::EventByKeySchema, event_by_version::EventByVersionSchema,
+        event_by_creation_number::EventByCreationNumberSchema, event_by_key::EventByKeySchema,
+        event_by_version::EventByVersionSchema,
     },
 };
 use accumulator::{HashReader, MerkleAccumulator};
@@ -30,7 +31,7 @@ use aptos_types::{
     proof::{position::Position, EventAccumulatorProof, EventProof},
     transaction::Version,
 };
-use schemadb::{schema::ValueCodec, ReadOptions, SchemaIterator, DB};
+use schemadb::{schema::ValueCodec, ReadOptions, SchemaBatch, SchemaIterator, DB};
 use std::{
     convert::{TryFrom, TryInto},
     iter::Peekable,
@@ -207,6 +208,96 @@ impl EventStore {
         Ok(result)
     }
 
+    /// Like [`lookup_events_by_key`](Self::lookup_events_by_key), with the event stream
+    /// identified by the address of its creator and its creation number. Streams the index has no
+    /// entry for are looked up by their `EventKey`: the DBs written before the index existed have
+    /// it only once migrated by
+    /// [`backfill_event_by_creation_number_index`](Self::backfill_event_by_creation_number_index).
+    pub fn lookup_events_by_creation_number(
+        &self,
+        address: AccountAddress,
+        creation_number: u64,
+        start_seq_num: u64,
+        limit: u64,
+        ledger_version: u64,
+    ) -> Result<
+        Vec<(
+            u64,     // sequence number
+            Version, // transaction version it belongs to
+            u64,     // index among events for the same transaction
+        )>,
+    > {
+        if self
+            .db
+            .get::<EventByCreationNumberSchema>(&(address, creation_number, start_seq_num))?
+            .is_none()
+        {
+            return self.lookup_events_by_key(
+                &EventKey::new_from_address(&address, creation_number),
+                start_seq_num,
+                limit,
+                ledger_version,
+            );
+        }
+
+        let mut iter = self
+            .db
+            .iter::<EventByCreationNumberSchema>(ReadOptions::default())?;
+        iter.seek(&(address, creation_number, start_seq_num))?;
+
+        let mut result = Vec::new();
+        let mut cur_seq = start_seq_num;
+        for res in iter.take(limit as usize) {
+            let ((addr, num, seq), (ver, idx)) = res?;
+            if addr != address || num != creation_number || ver > ledger_version {
+                break;
+            }
+            ensure!(
+                seq == cur_seq,
+                "DB corrupt: Sequence number not continuous, expected: {}, actual: {}.",
+                cur_seq,
+                seq
+            );
+            result.push((seq, ver, idx));
+            cur_seq += 1;
+        }
+
+        Ok(result)
+    }
+
+    /// Writes the `EventByCreationNumberSchema` entries of all the events indexed by their
+    /// `EventKey`, `batch_size` entries per write. Needed once by the DBs written before the index
+    /// existed, rewriting the existing entries is harmless. Returns the number of entries written.
+    pub fn backfill_event_by_creation_number_index(&self, batch_size: usize) -> Result<usize> {
+        ensure!(batch_size > 0, "Batch size must be positive.");
+        let mut iter = self.db.iter::<EventByKeySchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+
+        let mut num_written = 0;
+        let mut batch = SchemaBatch::new();
+        let mut batch_len = 0;
+        for res in iter {
+            let ((event_key, seq_num), value) = res?;
+            batch.put::<EventByCreationNumberSchema>(
+                &(
+                    event_key.get_creator_address(),
+                    event_key.get_creation_number(),
+                    seq_num,
+                ),
+                &value,
+            )?;
+            batch_len += 1;
+            if batch_len == batch_size {
+                self.db.write_schemas(batch)?;
+                num_written += batch_len;
+                batch = SchemaBatch::new();
+                batch_len = 0;
+            }
+        }
+        self.db.write_schemas(batch)?;
+        Ok(num_written + batch_len)
+    }
+
     fn lookup_event_by_key(
         &self,
         event_key: &EventKey,
@@ -247,6 +338,14 @@ impl EventStore {
                     &(*event.key(), event.sequence_number()),
                     &(version, idx as u64),
                 )?;
+                cs.batch.put::<EventByCreationNumberSchema>(
+                    &(
+                        event.key().get_creator_address(),
+                        event.key().get_creation_number(),
+                        event.sequence_number(),
+                    ),
+                    &(version, idx as u64),
+                )?;
                 cs.batch.put::<EventByVersionSchema>(
                     &(*event.key(), version, event.sequence_number()),
                     &(idx as u64),
@@ -367,3 +367,60 @@ proptest! {
         test_get_last_version_before_timestamp_impl(new_block_events)
     }
 }
+
+#[test]
+fn test_lookup_events_by_creation_number() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.event_store;
+
+    let address = AccountAddress::random();
+    let key = EventKey::new_from_address(&address, 3);
+    let other_key = EventKey::new_from_address(&address, 4);
+    let events = vec![
+        ContractEvent::new(key, 0, TypeTag::Bool, vec![]),
+        ContractEvent::new(other_key, 0, TypeTag::Bool, vec![]),
+        ContractEvent::new(key, 1, TypeTag::Bool, vec![]),
+    ];
+    save(store, 0, &events[..2]);
+    save(store, 1, &events[2..]);
+
+    let expected = store.lookup_events_by_key(&key, 0, 10, 1).unwrap();
+    assert_eq!(expected, vec![(0, 0, 0), (1, 1, 0)]);
+    assert_eq!(
+        store
+            .lookup_events_by_creation_number(address, 3, 0, 10, 1)
+            .unwrap(),
+        expected
+    );
+    assert_eq!(
+        store
+            .lookup_events_by_creation_number(address, 3, 0, 10, 0)
+            .unwrap(),
+        vec![(0, 0, 0)]
+    );
+
+    // A DB written before the index existed falls back to the event key, until backfilled.
+    let mut batch = SchemaBatch::new();
+    for key in [(address, 3, 0), (address, 3, 1), (address, 4, 0)] {
+        batch.delete::<EventByCreationNumberSchema>(&key).unwrap();
+    }
+    store.db.write_schemas(batch).unwrap();
+    assert_eq!(
+        store
+            .lookup_events_by_creation_number(address, 3, 0, 10, 1)
+            .unwrap(),
+        expected
+    );
+
+    assert_eq!(store.backfill_event_by_creation_number_index(2).unwrap(), 3);
+    assert_eq!(
+        store
+            .db
+            .iter::<EventByCreationNumberSchema>(ReadOptions::default())
+            .unwrap()
+            .map(|res| res.unwrap().0)
+            .collect::<Vec<_>>(),
+        vec![(address, 3, 0), (address, 3, 1), (address, 4, 0)]
+    );
+}
@@ -37,7 +37,10 @@ mod aptosdb_test;
 pub use aptosdb_test::test_save_blocks_impl;
 
 use crate::{
-    backup::{backup_handler::BackupHandler, restore_handler::RestoreHandler},
+    backup::{
+        backup_handler::BackupHandler,
+        restore_handler::{self, RestoreHandler},
+    },
     change_set::{ChangeSet, SealedChangeSet},
     errors::AptosDbError,
     event_store::EventStore,
@@ -72,12 +75,14 @@ use aptos_types::{
         TransactionInfoListWithProof,
     },
     state_proof::StateProof,
+    state_storage_usage::StateStorageUsage,
     transaction::{
         AccountTransactionsWithProof, TransactionInfo, TransactionListWithProof, TransactionOutput,
         TransactionOutputListWithProof, TransactionToCommit, TransactionWithProof, Version,
         PRE_GENESIS_VERSION,
     },
 };
+use fail::fail_point;
 use itertools::zip_eq;
 use move_core_types::{
     language_storage::{ModuleId, StructTag},
@@ -95,7 +100,8 @@ use std::{
     time::{Duration, Instant},
 };
 use storage_interface::{
-    DbReader, DbWriter, MoveDbReader, Order, StartupInfo, StateSnapshotReceiver, TreeState,
+    DbReader, DbWriter, MoveDbReader, Order, StartupInfo, StateItem, StateSnapshotReceiver,
+    TreeState, STATE_ITEM_PROOF_INTERVAL,
 };
 
 const MAX_LIMIT: u64 = 5000;
@@ -234,12 +240,15 @@ impl AptosDB {
             /* LedgerInfo CF = */ DEFAULT_CF_NAME,
             EPOCH_BY_VERSION_CF_NAME,
             EVENT_ACCUMULATOR_CF_NAME,
+            EVENT_BY_CREATION_NUMBER_CF_NAME,
             EVENT_BY_KEY_CF_NAME,
             EVENT_BY_VERSION_CF_NAME,
             EVENT_CF_NAME,
             JELLYFISH_MERKLE_NODE_CF_NAME,
             LEDGER_COUNTERS_CF_NAME,
             STALE_NODE_INDEX_CF_NAME,
+            STATE_STORAGE_USAGE_CF_NAME,
+            STATE_VALUE_CF_NAME,
             TRANSACTION_CF_NAME,
             TRANSACTION_ACCUMULATOR_CF_NAME,
             TRANSACTION_BY_ACCOUNT_CF_NAME,
@@ -249,10 +258,19 @@ impl AptosDB {
         ]
     }
 
+    /// The column families looked up by a key prefix, with the length of the prefix.
+    fn prefix_bloom_filters() -> HashMap<ColumnFamilyName, usize> {
+        [(STATE_VALUE_CF_NAME, state_value::KEY_PREFIX_LEN)]
+            .iter()
+            .cloned()
+            .collect()
+    }
+
     fn new_with_db(
         db: DB,
         storage_pruner_config: StoragePrunerConfig,
         account_count_migration: bool,
+        state_value_index: bool,
     ) -> Self {
         let db = Arc::new(db);
         let transaction_store = Arc::new(TransactionStore::new(Arc::clone(&db)));
@@ -261,17 +279,22 @@ impl AptosDB {
             db: Arc::clone(&db),
             event_store: Arc::new(EventStore::new(Arc::clone(&db))),
             ledger_store: Arc::new(LedgerStore::new(Arc::clone(&db))),
-            state_store: Arc::new(StateStore::new(Arc::clone(&db), account_count_migration)),
+            state_store: Arc::new(StateStore::new(
+                Arc::clone(&db),
+                account_count_migration,
+                state_value_index,
+            )),
             transaction_store: Arc::clone(&transaction_store),
             system_store: SystemStore::new(Arc::clone(&db)),
             rocksdb_property_reporter: RocksdbPropertyReporter::new(Arc::clone(&db)),
-            pruner: match storage_pruner_config {
-                NO_OP_STORAGE_PRUNER_CONFIG => None,
-                _ => Some(Pruner::new(
+            pruner: if storage_pruner_config == NO_OP_STORAGE_PRUNER_CONFIG {
+                None
+            } else {
+                Some(Pruner::new(
                     Arc::clone(&db),
                     storage_pruner_config,
                     Arc::clone(&transaction_store),
-                )),
+                ))
             },
         }
     }
@@ -282,6 +305,7 @@ impl AptosDB {
         storage_pruner_config: StoragePrunerConfig,
         rocksdb_config: RocksdbConfig,
         account_count_migration: bool, // ignored when opening readonly
+        state_value_index: bool,       // ignored when opening readonly
     ) -> Result<Self> {
         ensure!(
             storage_pruner_config.eq(&NO_OP_STORAGE_PRUNER_CONFIG) || !readonly,
@@ -293,7 +317,7 @@ impl AptosDB {
 
         let mut rocksdb_opts = gen_rocksdb_options(&rocksdb_config);
 
-        let (db, account_count_migration) = if readonly {
+        let (db, account_count_migration, state_value_index) = if readonly {
             (
                 DB::open_readonly(
                     path.clone(),
@@ -302,22 +326,33 @@ impl AptosDB {
                     &rocksdb_opts,
                 )?,
                 true,
+                true,
             )
         } else {
             rocksdb_opts.create_if_missing(true);
             rocksdb_opts.create_missing_column_families(true);
             (
-                DB::open(
+                DB::open_with_prefix_bloom_filters(
                     path.clone(),
                     "aptosdb",
                     Self::column_families(),
+                    &Self::prefix_bloom_filters(),
                     &rocksdb_opts,
                 )?,
                 account_count_migration,
+                state_value_index,
             )
         };
 
-        let ret = Self::new_with_db(db, storage_pruner_config, account_count_migration);
+        let ret = Self::new_with_db(
+            db,
+            storage_pruner_config,
+            account_count_migration,
+            state_value_index,
+        );
+        if !state_value_index {
+            ret.state_store.delete_state_value_index()?;
+        }
         info!(
             path = path,
             time_ms = %instant.elapsed().as_millis(),
@@ -326,6 +361,9 @@ impl AptosDB {
         Ok(ret)
     }
 
+    /// Opens the DB of a node as a RocksDB secondary instance, which can read it while the node
+    /// runs without risking its corruption. `secondary_path` is where the secondary keeps its own
+    /// logs. See `try_catch_up_with_primary`.
     pub fn open_as_secondary<P: AsRef<Path> + Clone>(
         db_root_path: P,
         secondary_path: P,
@@ -347,9 +385,18 @@ impl AptosDB {
             )?,
             NO_OP_STORAGE_PRUNER_CONFIG,
             true, // account_count_migration
+            true, // state_value_index
         ))
     }
 
+    /// Catches up a DB opened with `open_as_secondary` with the writes made by the primary
+    /// since, so that long running readers (e.g. analytics or backup tooling) can follow a live
+    /// node.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.db.try_catch_up_with_primary()?;
+        self.ledger_store.reload_latest_ledger_info()
+    }
+
     /// This opens db in non-readonly mode, without the pruner.
     #[cfg(any(test, feature = "fuzzing"))]
     pub fn new_for_test<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
@@ -359,6 +406,7 @@ impl AptosDB {
             NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
             RocksdbConfig::default(),
             true, /* account_count_migration */
+            true, /* state_value_index */
         )
         .expect("Unable to open AptosDB")
     }
@@ -480,6 +528,25 @@ impl AptosDB {
         })
     }
 
+    /// Flushes the DB to disk. Called on shutdown, so that the next start recovers quickly.
+    pub fn flush(&self) -> Result<()> {
+        self.db.flush_all()
+    }
+
+    /// Backfills the index of the events by the address of the creator and the creation number
+    /// of their stream, for the DBs written before it existed. See
+    /// [`DbReader::get_events_by_creation_number`].
+    pub fn backfill_event_by_creation_number_index(&self, batch_size: usize) -> Result<usize> {
+        let num_written = self
+            .event_store
+            .backfill_event_by_creation_number_index(batch_size)?;
+        info!(
+            num_written = num_written,
+            "Backfilled the event by creation number index."
+        );
+        Ok(num_written)
+    }
+
     // ================================== Private APIs ==================================
     fn get_events_with_proof_by_event_key(
         &self,
@@ -488,6 +555,61 @@ impl AptosDB {
         order: Order,
         limit: u64,
         ledger_version: Version,
+    ) -> Result<Vec<EventWithProof>> {
+        self.get_events_with_proof_impl(
+            event_key,
+            start_seq_num,
+            order,
+            limit,
+            ledger_version,
+            |first_seq, real_limit| {
+                self.event_store.lookup_events_by_key(
+                    event_key,
+                    first_seq,
+                    real_limit,
+                    ledger_version,
+                )
+            },
+        )
+    }
+
+    fn get_events_with_proof_by_creation_number(
+        &self,
+        address: AccountAddress,
+        creation_number: u64,
+        start_seq_num: u64,
+        order: Order,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithProof>> {
+        self.get_events_with_proof_impl(
+            &EventKey::new_from_address(&address, creation_number),
+            start_seq_num,
+            order,
+            limit,
+            ledger_version,
+            |first_seq, real_limit| {
+                self.event_store.lookup_events_by_creation_number(
+                    address,
+                    creation_number,
+                    first_seq,
+                    real_limit,
+                    ledger_version,
+                )
+            },
+        )
+    }
+
+    /// `lookup_indices` looks up the (sequence number, version, index) of the events of the
+    /// stream, from a sequence number on, in ascending order.
+    fn get_events_with_proof_impl(
+        &self,
+        event_key: &EventKey,
+        start_seq_num: u64,
+        order: Order,
+        limit: u64,
+        ledger_version: Version,
+        lookup_indices: impl FnOnce(u64, u64) -> Result<Vec<(u64, Version, u64)>>,
     ) -> Result<Vec<EventWithProof>> {
         error_if_too_many_requested(limit, MAX_LIMIT)?;
         let get_latest = order == Order::Descending && start_seq_num == u64::max_value();
@@ -506,12 +628,7 @@ impl AptosDB {
         let (first_seq, real_limit) = get_first_seq_num_and_limit(order, cursor, limit)?;
 
         // Query the index.
-        let mut event_indices = self.event_store.lookup_events_by_key(
-            event_key,
-            first_seq,
-            real_limit,
-            ledger_version,
-        )?;
+        let mut event_indices = lookup_indices(first_seq, real_limit)?;
 
         // When descending, it's possible that user is asking for something beyond the latest
         // sequence number, in which case we will consider it a bad request and return an empty
@@ -686,10 +803,8 @@ impl DbReader for AptosDB {
         gauged_api("get_latest_account_state", || {
             let ledger_info_with_sigs = self.ledger_store.get_latest_ledger_info()?;
             let version = ledger_info_with_sigs.ledger_info().version();
-            let (blob, _proof) = self
-                .state_store
-                .get_account_state_with_proof_by_version(address, version)?;
-            Ok(blob)
+            self.state_store
+                .get_account_state_by_version(address, version)
         })
     }
 
@@ -901,6 +1016,30 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_events_by_creation_number(
+        &self,
+        address: AccountAddress,
+        creation_number: u64,
+        start: u64,
+        order: Order,
+        limit: u64,
+    ) -> Result<Vec<(u64, ContractEvent)>> {
+        gauged_api("get_events_by_creation_number", || {
+            let events = self.get_events_with_proof_by_creation_number(
+                address,
+                creation_number,
+                start,
+                order,
+                limit,
+                self.get_latest_version()?,
+            )?;
+            Ok(events
+                .into_iter()
+                .map(|e| (e.transaction_version, e.event))
+                .collect())
+        })
+    }
+
     fn get_events_with_proofs(
         &self,
         event_key: &EventKey,
@@ -1041,6 +1180,33 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_account_state_by_version(
+        &self,
+        address: AccountAddress,
+        version: Version,
+    ) -> Result<Option<AccountStateBlob>> {
+        gauged_api("get_account_state_by_version", || {
+            self.state_store
+                .get_account_state_by_version(address, version)
+        })
+    }
+
+    fn get_account_states_with_proof_by_version(
+        &self,
+        addresses: &[AccountAddress],
+        version: Version,
+    ) -> Result<
+        Vec<(
+            Option<AccountStateBlob>,
+            SparseMerkleProof<AccountStateBlob>,
+        )>,
+    > {
+        gauged_api("get_account_states_with_proof_by_version", || {
+            self.state_store
+                .get_account_states_with_proof_by_version(addresses, version)
+        })
+    }
+
     fn get_latest_tree_state(&self) -> Result<TreeState> {
         gauged_api("get_latest_tree_state", || {
             let tree_state = match self.ledger_store.get_latest_transaction_info_option()? {
@@ -1204,6 +1370,17 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_state_storage_usage(&self, version: Version) -> Result<StateStorageUsage> {
+        gauged_api("get_state_storage_usage", || {
+            self.state_store
+                .get_state_storage_usage(version)?
+                .ok_or_else(|| {
+                    AptosDbError::NotFound(format!("State storage usage at version {}", version))
+                        .into()
+                })
+        })
+    }
+
     fn get_account_chunk_with_proof(
         &self,
         version: Version,
@@ -1216,22 +1393,44 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_state_item_iterator(
+        &self,
+        version: Version,
+        start_key: HashValue,
+    ) -> Result<Box<dyn Iterator<Item = Result<StateItem>> + Send + Sync>> {
+        gauged_api("get_state_item_iterator", || {
+            Ok(Box::new(self.state_store.get_state_item_iterator(
+                version,
+                start_key,
+                STATE_ITEM_PROOF_INTERVAL,
+            )?) as Box<dyn Iterator<Item = _> + Send + Sync>)
+        })
+    }
+
     fn get_state_prune_window(&self) -> Option<usize> {
         self.pruner
             .as_ref()
             .map(|x| x.get_state_store_pruner_window() as usize)
     }
+
+    fn get_retained_state_versions(&self) -> Result<Vec<Version>> {
+        gauged_api("get_retained_state_versions", || {
+            Ok(self
+                .pruner
+                .as_ref()
+                .map(|pruner| pruner.get_retained_state_versions())
+                .unwrap_or_default())
+        })
+    }
 }
 
 impl ModuleResolver for AptosDB {
     type Error = anyhow::Error;
 
     fn get_module(&self, module_id: &ModuleId) -> Result<Option<Vec<u8>>> {
-        let (account_state_with_proof, _) = self.get_account_state_with_proof_by_version(
-            *module_id.address(),
-            self.get_latest_version()?,
-        )?;
-        if let Some(account_state_blob) = account_state_with_proof {
+        if let Some(account_state_blob) =
+            self.get_account_state_by_version(*module_id.address(), self.get_latest_version()?)?
+        {
             let account_state = AccountState::try_from(&account_state_blob)?;
             Ok(account_state.get(&module_id.access_vector()).cloned())
         } else {
@@ -1244,9 +1443,9 @@ impl ResourceResolver for AptosDB {
     type Error = anyhow::Error;
 
     fn get_resource(&self, address: &AccountAddress, tag: &StructTag) -> Result<Option<Vec<u8>>> {
-        let (account_state_with_proof, _) =
-            self.get_account_state_with_proof_by_version(*address, self.get_latest_version()?)?;
-        if let Some(account_state_blob) = account_state_with_proof {
+        if let Some(account_state_blob) =
+            self.get_account_state_by_version(*address, self.get_latest_version()?)?
+        {
             let account_state = AccountState::try_from(&account_state_blob)?;
             Ok(account_state.get(&tag.access_vector()).cloned())
         } else {
@@ -1314,6 +1513,9 @@ impl DbWriter for AptosDB {
                 let _timer = DIEM_STORAGE_OTHER_TIMERS_SECONDS
                     .with_label_values(&["save_transactions_commit"])
                     .start_timer();
+                fail_point!("aptosdb::save_transactions", |_| {
+                    Err(format_err!("Injected error in save_transactions"))
+                });
                 self.commit(sealed_cs)?;
             }
 
@@ -1359,6 +1561,76 @@ impl DbWriter for AptosDB {
                 .get_snapshot_receiver(version, expected_root_hash)
         })
     }
+
+    fn finalize_state_snapshot(
+        &self,
+        version: Version,
+        output_with_proof: TransactionOutputListWithProof,
+        ledger_infos: &[LedgerInfoWithSignatures],
+    ) -> Result<()> {
+        gauged_api("finalize_state_snapshot", || {
+            // Ensure the output with proof only contains the transaction at the snapshot version
+            ensure!(
+                output_with_proof.first_transaction_output_version == Some(version),
+                "The output with proof does not start at the snapshot version {}, got: {:?}",
+                version,
+                output_with_proof.first_transaction_output_version,
+            );
+            let num_outputs = output_with_proof.transactions_and_outputs.len();
+            let num_infos = output_with_proof.proof.transaction_infos.len();
+            ensure!(
+                num_outputs == 1 && num_infos == 1,
+                "Expected a single transaction output and info, got: {} outputs and {} infos",
+                num_outputs,
+                num_infos,
+            );
+            let (transaction, output) = &output_with_proof.transactions_and_outputs[0];
+            let transaction_info = &output_with_proof.proof.transaction_infos[0];
+
+            // Ensure the restored state snapshot matches the transaction info
+            let state_root_hash = self.state_store.get_root_hash(version)?;
+            ensure!(
+                state_root_hash == transaction_info.state_root_hash(),
+                "The restored state root hash {} does not match the expected root hash {}",
+                state_root_hash,
+                transaction_info.state_root_hash(),
+            );
+            ensure!(!ledger_infos.is_empty(), "No LedgerInfos to save.");
+
+            // Save the frozen subtrees of the transaction accumulator before the
+            // snapshot version, so the transaction info can be appended to them.
+            restore_handler::confirm_or_save_frozen_subtrees(
+                &self.db,
+                version,
+                output_with_proof
+                    .proof
+                    .ledger_info_to_transaction_infos_proof
+                    .left_siblings(),
+            )?;
+
+            // Save the transaction, write set, events, transaction info and ledger infos
+            let mut cs = ChangeSet::new();
+            self.transaction_store
+                .put_transaction(version, transaction, &mut cs)?;
+            self.transaction_store
+                .put_write_set(version, output.write_set(), &mut cs)?;
+            self.event_store
+                .put_events(version, output.events(), &mut cs)?;
+            self.ledger_store.put_transaction_infos(
+                version,
+                std::slice::from_ref(transaction_info),
+                &mut cs,
+            )?;
+            for ledger_info in ledger_infos {
+                self.ledger_store.put_ledger_info(ledger_info, &mut cs)?;
+            }
+            self.db.write_schemas(cs.batch)?;
+
+            self.ledger_store
+                .set_latest_ledger_info(ledger_infos.last().unwrap().clone());
+            Ok(())
+        })
+    }
 }
 
 // Convert requested range and order to a range in ascending order.
@@ -59,6 +59,16 @@ pub static DIEM_STORAGE_LATEST_ACCOUNT_COUNT: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Bytes of the state values written to the index of state values by version, on top of the ones
+/// written to the state Merkle tree.
+pub static DIEM_STORAGE_STATE_VALUE_INDEX_BYTES: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_storage_state_value_index_bytes",
+        "Bytes of state values written to the index of state values by version"
+    )
+    .unwrap()
+});
+
 pub static DIEM_STORAGE_PRUNE_WINDOW: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!("aptos_storage_prune_window", "Aptos storage prune window").unwrap()
 });
@@ -18,6 +18,7 @@ use crate::TransactionStore;
 use aptos_types::transaction::Version;
 use schemadb::DB;
 use std::{
+    collections::BTreeSet,
     sync::{
         mpsc::{channel, Sender},
         Arc,
@@ -49,6 +50,9 @@ pub(crate) struct Pruner {
     /// as this is accessed both by the Pruner thread and the worker thread.
     #[allow(dead_code)]
     least_readable_version: Arc<Mutex<Vec<Version>>>,
+    /// The versions below the least readable state version whose state is still held, as
+    /// recorded by the state store pruner.
+    retained_state_versions: Arc<Mutex<BTreeSet<Version>>>,
 }
 
 #[cfg(test)]
@@ -68,6 +72,7 @@ impl Pruner {
 
         let least_readable_version = Arc::new(Mutex::new(vec![0, 0]));
         let worker_progress_clone = Arc::clone(&least_readable_version);
+        let retained_state_versions = Arc::new(Mutex::new(BTreeSet::new()));
 
         DIEM_STORAGE_PRUNE_WINDOW
             .set(storage_pruner_config.state_store_prune_window.unwrap() as i64);
@@ -76,6 +81,9 @@ impl Pruner {
             transaction_store,
             command_receiver,
             least_readable_version,
+            storage_pruner_config.keep_epoch_ending_versions,
+            &storage_pruner_config.pinned_versions,
+            Arc::clone(&retained_state_versions),
         );
         let worker_thread = std::thread::Builder::new()
             .name("aptosdb_pruner".into())
@@ -92,6 +100,7 @@ impl Pruner {
             worker_thread: Some(worker_thread),
             command_sender: Mutex::new(command_sender),
             least_readable_version: worker_progress_clone,
+            retained_state_versions,
         }
     }
 
@@ -99,6 +108,16 @@ impl Pruner {
         self.state_store_prune_window
     }
 
+    /// Returns the versions below the least readable state version whose state is still held,
+    /// in ascending order.
+    pub fn get_retained_state_versions(&self) -> Vec<Version> {
+        self.retained_state_versions
+            .lock()
+            .iter()
+            .copied()
+            .collect()
+    }
+
     /// Sends pruning command to the worker thread when necessary.
     pub fn wake(&self, latest_version: Version) {
         let least_readable_state_store_version =
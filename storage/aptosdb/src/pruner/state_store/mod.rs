@@ -2,16 +2,22 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    jellyfish_merkle_node::JellyfishMerkleNodeSchema, metrics::DIEM_PRUNER_LEAST_READABLE_VERSION,
-    pruner::db_pruner::DBPruner, stale_node_index::StaleNodeIndexSchema,
+    epoch_by_version::EpochByVersionSchema, jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+    metrics::DIEM_PRUNER_LEAST_READABLE_VERSION, pruner::db_pruner::DBPruner,
+    stale_node_index::StaleNodeIndexSchema, state_value::StateValueSchema,
     DIEM_STORAGE_OTHER_TIMERS_SECONDS,
 };
+use aptos_crypto::HashValue;
 use aptos_infallible::Mutex;
-use aptos_jellyfish_merkle::StaleNodeIndex;
+use aptos_jellyfish_merkle::{
+    node_type::{Node, NodeKey},
+    StaleNodeIndex,
+};
 use aptos_logger::{error, info, warn};
 use aptos_types::transaction::{AtomicVersion, Version};
 use schemadb::{ReadOptions, SchemaBatch, SchemaIterator, DB};
 use std::{
+    collections::{BTreeSet, HashMap},
     iter::Peekable,
     sync::{atomic::Ordering, Arc},
     thread::sleep,
@@ -21,6 +27,10 @@ use std::{
 #[cfg(test)]
 mod test;
 
+/// The maximum number of retained versions reported below the least readable version, the
+/// latest ones being kept.
+pub const MAX_RETAINED_STATE_VERSIONS: usize = 100;
+
 pub struct StateStorePruner {
     db: Arc<DB>,
     index_min_nonpurged_version: AtomicVersion,
@@ -28,6 +38,18 @@ pub struct StateStorePruner {
     /// Keeps track of the target version that the pruner needs to achieve.
     target_version: AtomicVersion,
     least_readable_version: AtomicVersion,
+    /// Whether the state at the epoch ending versions is exempted from pruning.
+    keep_epoch_ending_versions: bool,
+    /// The epoch ending versions before this one have been added to the exempted versions.
+    epoch_ending_versions_read_until: AtomicVersion,
+    /// The versions whose state is exempted from pruning: the pinned versions, and the epoch
+    /// ending versions read so far if they are exempted.
+    exempted_versions: Mutex<BTreeSet<Version>>,
+    /// The exempted versions before this one have been checked for retained state.
+    retained_versions_checked_until: AtomicVersion,
+    /// The exempted versions below the least readable version whose state is actually held,
+    /// bounded by `MAX_RETAINED_STATE_VERSIONS`. Shared with the `Pruner`.
+    retained_versions: Arc<Mutex<BTreeSet<Version>>>,
 }
 
 impl DBPruner for StateStorePruner {
@@ -44,6 +66,19 @@ impl DBPruner for StateStorePruner {
                         "[state pruner worker] initialized."
                     );
                     self.record_progress(least_readable_version);
+                    let result = if self.keep_epoch_ending_versions {
+                        self.read_epoch_ending_versions()
+                    } else {
+                        Ok(())
+                    };
+                    if let Err(e) =
+                        result.and_then(|()| self.record_retained_versions(least_readable_version))
+                    {
+                        warn!(
+                            error = ?e,
+                            "Failed recording the retained state versions, ignored.",
+                        );
+                    }
                     return;
                 }
                 Err(e) => {
@@ -60,14 +95,24 @@ impl DBPruner for StateStorePruner {
     fn prune(&self, max_versions: usize) -> anyhow::Result<Version> {
         let least_readable_version = self.least_readable_version.load(Ordering::Relaxed);
         let target_version = self.target_version();
+        if self.keep_epoch_ending_versions {
+            self.read_epoch_ending_versions()?;
+        }
         return match prune_state_store(
             self.db.clone(),
             least_readable_version,
             target_version,
             max_versions,
+            &self.exempted_versions.lock(),
         ) {
             Ok(new_least_readable_version) => {
                 self.record_progress(new_least_readable_version);
+                if let Err(e) = self.record_retained_versions(new_least_readable_version) {
+                    warn!(
+                        error = ?e,
+                        "Failed recording the retained state versions, ignored.",
+                    );
+                }
                 // Try to purge the log.
                 if let Err(e) = self.maybe_purge_index() {
                     warn!(
@@ -131,6 +176,9 @@ impl StateStorePruner {
         db: Arc<DB>,
         index_min_nonpurged_version: Version,
         index_purged_at: Instant,
+        keep_epoch_ending_versions: bool,
+        pinned_versions: &[Version],
+        retained_versions: Arc<Mutex<BTreeSet<Version>>>,
     ) -> Self {
         StateStorePruner {
             db,
@@ -138,7 +186,72 @@ impl StateStorePruner {
             index_purged_at: Mutex::new(index_purged_at),
             target_version: AtomicVersion::new(0),
             least_readable_version: AtomicVersion::new(0),
+            keep_epoch_ending_versions,
+            epoch_ending_versions_read_until: AtomicVersion::new(0),
+            exempted_versions: Mutex::new(pinned_versions.iter().copied().collect()),
+            retained_versions_checked_until: AtomicVersion::new(0),
+            retained_versions,
+        }
+    }
+
+    /// Records the exempted versions which the pruner has passed and whose state is still held,
+    /// i.e., their root node exists. This leaves out the versions whose state was never written,
+    /// e.g., the epochs ending before the DB was restored from a snapshot.
+    fn record_retained_versions(&self, least_readable_version: Version) -> anyhow::Result<()> {
+        let checked_until = self.retained_versions_checked_until.load(Ordering::Relaxed);
+        if least_readable_version <= checked_until {
+            return Ok(());
+        }
+
+        let passed_versions = self
+            .exempted_versions
+            .lock()
+            .range(checked_until..least_readable_version)
+            .copied()
+            .collect::<Vec<_>>();
+        let mut newly_retained = vec![];
+        for version in passed_versions {
+            if self
+                .db
+                .get::<JellyfishMerkleNodeSchema>(&NodeKey::new_empty_path(version))?
+                .is_some()
+            {
+                newly_retained.push(version);
+            }
+        }
+
+        let mut retained_versions = self.retained_versions.lock();
+        retained_versions.extend(newly_retained);
+        if retained_versions.len() > MAX_RETAINED_STATE_VERSIONS {
+            let first_kept = *retained_versions
+                .iter()
+                .nth_back(MAX_RETAINED_STATE_VERSIONS - 1)
+                .expect("Should exist.");
+            *retained_versions = retained_versions.split_off(&first_kept);
+        }
+        self.retained_versions_checked_until
+            .store(least_readable_version, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Adds the epoch ending versions committed since the last read to the exempted versions.
+    fn read_epoch_ending_versions(&self) -> anyhow::Result<()> {
+        let mut iter = self
+            .db
+            .iter::<EpochByVersionSchema>(ReadOptions::default())?;
+        iter.seek(
+            &self
+                .epoch_ending_versions_read_until
+                .load(Ordering::Relaxed),
+        )?;
+        let mut exempted_versions = self.exempted_versions.lock();
+        for res in iter {
+            let (version, _epoch) = res?;
+            exempted_versions.insert(version);
+            self.epoch_ending_versions_read_until
+                .store(version + 1, Ordering::Relaxed);
         }
+        Ok(())
     }
 
     /// Purge the stale node index so that after restart not too much already pruned stuff is dealt
@@ -182,11 +295,15 @@ impl StateStorePruner {
     }
 }
 
+/// Deletes the nodes which became stale between `least_readable_version` and `target_version`,
+/// except those still live at any of the `exempted_versions`, whose state stays readable. The
+/// state values superseded by then are deleted along with the leaves of their keys.
 pub fn prune_state_store(
     db: Arc<DB>,
     least_readable_version: Version,
     target_version: Version,
     max_versions: usize,
+    exempted_versions: &BTreeSet<Version>,
 ) -> anyhow::Result<Version> {
     let indices =
         StaleNodeIndicesByVersionIterator::new(&db, least_readable_version, target_version)?
@@ -204,14 +321,84 @@ pub fn prune_state_store(
             .start_timer();
         let new_least_readable_version = indices.last().expect("Should exist.").stale_since_version;
         let mut batch = SchemaBatch::new();
-        indices
+        let node_keys = indices
             .into_iter()
-            .try_for_each(|index| batch.delete::<JellyfishMerkleNodeSchema>(&index.node_key))?;
+            // A node is live from the version it's created at until it becomes stale.
+            .filter(|index| {
+                exempted_versions
+                    .range(index.node_key.version()..index.stale_since_version)
+                    .next()
+                    .is_none()
+            })
+            .map(|index| (index.node_key, index.stale_since_version))
+            .collect::<Vec<_>>();
+
+        // A leaf also becomes stale when it only moves in the tree, so its key is used to find
+        // the values superseded, rather than its version.
+        let nodes = db.multi_get::<JellyfishMerkleNodeSchema>(
+            &node_keys
+                .iter()
+                .map(|(node_key, _)| node_key.clone())
+                .collect::<Vec<_>>(),
+        )?;
+        let mut stale_keys = HashMap::new();
+        for ((_, stale_since_version), node) in node_keys.iter().zip(nodes) {
+            if let Some(Node::Leaf(leaf)) = node {
+                let version = stale_keys.entry(leaf.account_key()).or_insert(0);
+                *version = (*version).max(*stale_since_version);
+            }
+        }
+        for (key_hash, stale_since_version) in stale_keys {
+            prune_state_values(
+                &db,
+                &mut batch,
+                key_hash,
+                stale_since_version,
+                exempted_versions,
+            )?;
+        }
+
+        node_keys
+            .iter()
+            .try_for_each(|(node_key, _)| batch.delete::<JellyfishMerkleNodeSchema>(node_key))?;
         db.write_schemas(batch)?;
         Ok(new_least_readable_version)
     }
 }
 
+/// Deletes the values of `key_hash` superseded at or before `stale_since_version`, except those
+/// still live at any of the `exempted_versions`.
+fn prune_state_values(
+    db: &DB,
+    batch: &mut SchemaBatch,
+    key_hash: HashValue,
+    stale_since_version: Version,
+    exempted_versions: &BTreeSet<Version>,
+) -> anyhow::Result<()> {
+    let mut iter = db.iter::<StateValueSchema>(ReadOptions::default())?;
+    iter.seek(&(key_hash, stale_since_version))?;
+    // The values of the key are ordered from the latest version: the first one is live at
+    // `stale_since_version`, and each following one until the version of the previous one.
+    let mut superseded_at = None;
+    for res in iter {
+        let ((found_hash, version), _) = res?;
+        if found_hash != key_hash {
+            break;
+        }
+        if let Some(superseded_at) = superseded_at {
+            if exempted_versions
+                .range(version..superseded_at)
+                .next()
+                .is_none()
+            {
+                batch.delete::<StateValueSchema>(&(key_hash, version))?;
+            }
+        }
+        superseded_at = Some(version);
+    }
+    Ok(())
+}
+
 struct StaleNodeIndicesByVersionIterator<'a> {
     inner: Peekable<SchemaIterator<'a, StaleNodeIndexSchema>>,
     target_least_readable_version: Version,
@@ -1,11 +1,17 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{change_set::ChangeSet, pruner::*, state_store::StateStore, AptosDB};
+use crate::{
+    change_set::ChangeSet, epoch_by_version::EpochByVersionSchema, pruner::*,
+    state_store::StateStore, state_value::StateValueSchema, AptosDB,
+};
 use aptos_crypto::HashValue;
 use aptos_temppath::TempPath;
-use aptos_types::{account_address::AccountAddress, account_state_blob::AccountStateBlob};
-use std::collections::HashMap;
+use aptos_types::{
+    account_address::{AccountAddress, HashAccountAddress},
+    account_state_blob::AccountStateBlob,
+};
+use std::collections::{BTreeSet, HashMap};
 
 fn put_account_state_set(
     db: &DB,
@@ -37,6 +43,28 @@ fn verify_state_in_store(
         .get_account_state_with_proof_by_version(address, version)
         .unwrap();
     assert_eq!(value.as_ref(), expected_value);
+    let value = state_store
+        .get_account_state_by_version(address, version)
+        .unwrap();
+    assert_eq!(value.as_ref(), expected_value);
+}
+
+fn verify_state_pruned(
+    db: &DB,
+    state_store: &StateStore,
+    address: AccountAddress,
+    version: Version,
+) {
+    assert!(state_store
+        .get_account_state_with_proof_by_version(address, version)
+        .is_err());
+    assert!(state_store
+        .get_account_state_by_version(address, version)
+        .is_err());
+    assert!(db
+        .get::<StateValueSchema>(&(address.hash(), version))
+        .unwrap()
+        .is_none());
 }
 
 #[test]
@@ -49,14 +77,15 @@ fn test_state_store_pruner() {
     let tmp_dir = TempPath::new();
     let aptos_db = AptosDB::new_for_test(&tmp_dir);
     let db = aptos_db.db;
-    let state_store = &StateStore::new(Arc::clone(&db), true /* account_count_migration */);
+    let state_store = &StateStore::new(
+        Arc::clone(&db),
+        true, /* account_count_migration */
+        true, /* state_value_index */
+    );
     let transaction_store = &aptos_db.transaction_store;
     let pruner = Pruner::new(
         Arc::clone(&db),
-        StoragePrunerConfig {
-            state_store_prune_window: Some(0),
-            default_prune_window: Some(0),
-        },
+        StoragePrunerConfig::new(Some(0), Some(0)),
         Arc::clone(transaction_store),
     );
 
@@ -100,9 +129,7 @@ fn test_state_store_pruner() {
             )
             .unwrap();
         // root0 is gone.
-        assert!(state_store
-            .get_account_state_with_proof_by_version(address, 0)
-            .is_err());
+        verify_state_pruned(&db, state_store, address, 0);
         // root1 is still there.
         verify_state_in_store(state_store, address, Some(&value1), 1);
         verify_state_in_store(state_store, address, Some(&value2), 2);
@@ -116,14 +143,70 @@ fn test_state_store_pruner() {
             )
             .unwrap();
         // root1 is gone.
-        assert!(state_store
-            .get_account_state_with_proof_by_version(address, 1)
-            .is_err());
+        verify_state_pruned(&db, state_store, address, 1);
         // root2 is still there.
         verify_state_in_store(state_store, address, Some(&value2), 2);
     }
 }
 
+#[test]
+fn test_state_store_pruner_exempted_versions() {
+    let address = AccountAddress::new([1u8; AccountAddress::LENGTH]);
+    let values = (0..5u8)
+        .map(|i| AccountStateBlob::from(vec![i]))
+        .collect::<Vec<_>>();
+
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let db = aptos_db.db;
+    let state_store = &StateStore::new(
+        Arc::clone(&db),
+        true, /* account_count_migration */
+        true, /* state_value_index */
+    );
+    let pruner = Pruner::new(
+        Arc::clone(&db),
+        StoragePrunerConfig {
+            keep_epoch_ending_versions: true,
+            pinned_versions: vec![1],
+            ..StoragePrunerConfig::new(Some(0), Some(0))
+        },
+        Arc::clone(&aptos_db.transaction_store),
+    );
+
+    for (version, value) in values.iter().enumerate() {
+        put_account_state_set(
+            &db,
+            state_store,
+            vec![(address, value.clone())],
+            version as Version,
+        );
+    }
+    // Version 3 ends epoch 0.
+    db.put::<EpochByVersionSchema>(&3, &0).unwrap();
+
+    pruner
+        .wake_and_wait(
+            4, /* latest_version */
+            PrunerIndex::StateStorePrunerIndex as usize,
+        )
+        .unwrap();
+    for version in [0, 2] {
+        verify_state_pruned(&db, state_store, address, version);
+    }
+    // The pinned version and the epoch ending version are still there.
+    for version in [1, 3, 4] {
+        verify_state_in_store(
+            state_store,
+            address,
+            Some(&values[version as usize]),
+            version,
+        );
+    }
+    // And only the versions below the least readable one are reported as retained.
+    assert_eq!(pruner.get_retained_state_versions(), vec![1, 3]);
+}
+
 #[test]
 fn test_worker_quit_eagerly() {
     let address = AccountAddress::new([1u8; AccountAddress::LENGTH]);
@@ -134,7 +217,11 @@ fn test_worker_quit_eagerly() {
     let tmp_dir = TempPath::new();
     let aptos_db = AptosDB::new_for_test(&tmp_dir);
     let db = aptos_db.db;
-    let state_store = &StateStore::new(Arc::clone(&db), true /* account_count_migration */);
+    let state_store = &StateStore::new(
+        Arc::clone(&db),
+        true, /* account_count_migration */
+        true, /* state_value_index */
+    );
 
     let _root0 = put_account_state_set(
         &db,
@@ -161,7 +248,10 @@ fn test_worker_quit_eagerly() {
             Arc::clone(&db),
             Arc::clone(&aptos_db.transaction_store),
             command_receiver,
-            Arc::new(Mutex::new(vec![0, 0])), /* progress */
+            Arc::new(Mutex::new(vec![0, 0])),      /* progress */
+            false,                                 /* keep_epoch_ending_versions */
+            &[],                                   /* pinned_versions */
+            Arc::new(Mutex::new(BTreeSet::new())), /* retained_state_versions */
         );
         command_sender
             .send(Command::Prune {
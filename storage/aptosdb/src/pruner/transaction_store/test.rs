@@ -44,10 +44,7 @@ fn verify_txn_store_pruner(
 
     let pruner = Pruner::new(
         Arc::clone(&aptos_db.db),
-        StoragePrunerConfig {
-            state_store_prune_window: Some(0),
-            default_prune_window: Some(0),
-        },
+        StoragePrunerConfig::new(Some(0), Some(0)),
         Arc::clone(transaction_store),
     );
 
@@ -9,6 +9,7 @@ use aptos_infallible::Mutex;
 use crate::{pruner::state_store::StateStorePruner, TransactionStore};
 use itertools::zip_eq;
 use std::{
+    collections::BTreeSet,
     sync::{mpsc::Receiver, Arc},
     time::Instant,
 };
@@ -36,6 +37,9 @@ impl Worker {
         transaction_store: Arc<TransactionStore>,
         command_receiver: Receiver<Command>,
         least_readable_versions: Arc<Mutex<Vec<Version>>>,
+        keep_epoch_ending_versions: bool,
+        pinned_versions: &[Version],
+        retained_state_versions: Arc<Mutex<BTreeSet<Version>>>,
     ) -> Self {
         Self {
             db_pruners: vec![
@@ -43,6 +47,9 @@ impl Worker {
                     Arc::clone(&db),
                     0,
                     Instant::now(),
+                    keep_epoch_ending_versions,
+                    pinned_versions,
+                    retained_state_versions,
                 ))),
                 Mutex::new(Arc::new(TransactionStorePruner::new(
                     Arc::clone(&db),
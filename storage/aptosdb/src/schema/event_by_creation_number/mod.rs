@@ -0,0 +1,87 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an event index via which a ContractEvent (
+//! represented by a <txn_version, event_idx> tuple so that it can be fetched from `EventSchema`)
+//! can be found by the <address, creation_number, seq_num> tuple identifying it, independent of
+//! the encoding of the `EventKey`. The keys are prefixed by the address, so the event streams of
+//! an account are adjacent.
+//!
+//! ```text
+//! |<---------------key--------------->|<----value---->|
+//! | address | creation_num | seq_num | txn_ver | idx |
+//! ```
+
+use crate::schema::{ensure_slice_len_eq, EVENT_BY_CREATION_NUMBER_CF_NAME};
+use anyhow::Result;
+use aptos_types::{account_address::AccountAddress, transaction::Version};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::{convert::TryFrom, mem::size_of};
+
+define_schema!(
+    EventByCreationNumberSchema,
+    Key,
+    Value,
+    EVENT_BY_CREATION_NUMBER_CF_NAME
+);
+
+type CreationNumber = u64;
+type SeqNum = u64;
+type Key = (AccountAddress, CreationNumber, SeqNum);
+
+type Index = u64;
+type Value = (Version, Index);
+
+impl KeyCodec<EventByCreationNumberSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref address, creation_number, seq_num) = *self;
+
+        let mut encoded = address.to_vec();
+        encoded.write_u64::<BigEndian>(creation_number)?;
+        encoded.write_u64::<BigEndian>(seq_num)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+
+        const CREATION_NUMBER_OFFSET: usize = AccountAddress::LENGTH;
+        const SEQ_NUM_OFFSET: usize = CREATION_NUMBER_OFFSET + size_of::<CreationNumber>();
+        let address = AccountAddress::try_from(&data[..CREATION_NUMBER_OFFSET])?;
+        let creation_number =
+            (&data[CREATION_NUMBER_OFFSET..SEQ_NUM_OFFSET]).read_u64::<BigEndian>()?;
+        let seq_num = (&data[SEQ_NUM_OFFSET..]).read_u64::<BigEndian>()?;
+
+        Ok((address, creation_number, seq_num))
+    }
+}
+
+impl ValueCodec<EventByCreationNumberSchema> for Value {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        let (version, index) = *self;
+
+        let mut encoded = Vec::with_capacity(size_of::<Version>() + size_of::<Index>());
+        encoded.write_u64::<BigEndian>(version)?;
+        encoded.write_u64::<BigEndian>(index)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+
+        const VERSION_SIZE: usize = size_of::<Version>();
+        let version = (&data[..VERSION_SIZE]).read_u64::<BigEndian>()?;
+        let index = (&data[VERSION_SIZE..]).read_u64::<BigEndian>()?;
+
+        Ok((version, index))
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,24 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use proptest::prelude::*;
+use schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        address in any::<AccountAddress>(),
+        creation_number in any::<u64>(),
+        seq_num in any::<u64>(),
+        version in any::<Version>(),
+        index in any::<u64>(),
+    ) {
+        assert_encode_decode::<EventByCreationNumberSchema>(
+            &(address, creation_number, seq_num),
+            &(version, index),
+        );
+    }
+}
+
+test_no_panic_decoding!(EventByCreationNumberSchema);
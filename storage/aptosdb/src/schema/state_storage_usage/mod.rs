@@ -0,0 +1,55 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the usage of the state at each version: the
+//! number of items in the state and their total size.
+//!
+//! ```text
+//! |<--key-->|<---value--->|
+//! | version | state usage |
+//! ```
+//!
+//! `Version` is serialized in big endian so that records in RocksDB will be in order of it's
+//! numeric value.
+
+use super::STATE_STORAGE_USAGE_CF_NAME;
+use crate::schema::ensure_slice_len_eq;
+use anyhow::Result;
+use aptos_types::{state_storage_usage::StateStorageUsage, transaction::Version};
+use byteorder::{BigEndian, ReadBytesExt};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(
+    StateStorageUsageSchema,
+    Version,
+    StateStorageUsage,
+    STATE_STORAGE_USAGE_CF_NAME
+);
+
+impl KeyCodec<StateStorageUsageSchema> for Version {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Version>())?;
+        Ok(data.read_u64::<BigEndian>()?)
+    }
+}
+
+impl ValueCodec<StateStorageUsageSchema> for StateStorageUsage {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        bcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test;
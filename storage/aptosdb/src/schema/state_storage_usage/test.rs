@@ -0,0 +1,18 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use proptest::prelude::*;
+use schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        version in any::<Version>(),
+        usage in any::<StateStorageUsage>(),
+    ) {
+        assert_encode_decode::<StateStorageUsageSchema>(&version, &usage);
+    }
+}
+
+test_no_panic_decoding!(StateStorageUsageSchema);
@@ -0,0 +1,66 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the values of the state, indexed by the hash
+//! of their key and the version they were written at, so that the latest value of a key at a
+//! version can be read with a single seek instead of a walk down the state Merkle tree.
+//!
+//! ```text
+//! |<-------------key------------>|<---value--->|
+//! | key hash | !version          | state value |
+//! ```
+//!
+//! The version is bitwise negated and serialized in big endian, so that the records of a key are
+//! ordered from the latest version to the oldest one. The column family has a prefix bloom filter
+//! on the key hash, which lets the lookups of keys without any record skip most SST files.
+
+use crate::schema::{ensure_slice_len_eq, STATE_VALUE_CF_NAME};
+use anyhow::Result;
+use aptos_crypto::HashValue;
+use aptos_types::{account_state_blob::AccountStateBlob, transaction::Version};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(StateValueSchema, Key, AccountStateBlob, STATE_VALUE_CF_NAME);
+
+type Key = (HashValue, Version);
+
+/// The length of the prefix the records of a key share.
+pub(crate) const KEY_PREFIX_LEN: usize = HashValue::LENGTH;
+
+impl KeyCodec<StateValueSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref key_hash, version) = *self;
+
+        let mut encoded = key_hash.to_vec();
+        encoded.write_u64::<BigEndian>(!version)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, KEY_PREFIX_LEN + size_of::<Version>())?;
+
+        let key_hash = HashValue::from_slice(&data[..KEY_PREFIX_LEN])?;
+        let version = !(&data[KEY_PREFIX_LEN..]).read_u64::<BigEndian>()?;
+
+        Ok((key_hash, version))
+    }
+}
+
+impl ValueCodec<StateValueSchema> for AccountStateBlob {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        bcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,31 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use proptest::prelude::*;
+use schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        key_hash in any::<HashValue>(),
+        version in any::<Version>(),
+        blob in any::<AccountStateBlob>(),
+    ) {
+        assert_encode_decode::<StateValueSchema>(&(key_hash, version), &blob);
+    }
+
+    #[test]
+    fn test_later_versions_first(
+        key_hash in any::<HashValue>(),
+        version in 0..Version::MAX,
+    ) {
+        let later = <Key as KeyCodec<StateValueSchema>>::encode_key(&(key_hash, version + 1))
+            .unwrap();
+        let earlier = <Key as KeyCodec<StateValueSchema>>::encode_key(&(key_hash, version))
+            .unwrap();
+        prop_assert!(later < earlier);
+    }
+}
+
+test_no_panic_decoding!(StateValueSchema);
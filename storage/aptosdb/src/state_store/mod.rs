@@ -9,8 +9,10 @@ mod state_store_test;
 use crate::{
     change_set::ChangeSet,
     ledger_counters::LedgerCounter,
+    metrics::DIEM_STORAGE_STATE_VALUE_INDEX_BYTES,
     schema::{
         jellyfish_merkle_node::JellyfishMerkleNodeSchema, stale_node_index::StaleNodeIndexSchema,
+        state_storage_usage::StateStorageUsageSchema, state_value::StateValueSchema,
     },
     AptosDbError,
 };
@@ -25,12 +27,13 @@ use aptos_types::{
     account_state_blob::{AccountStateBlob, AccountStatesChunkWithProof},
     nibble::{nibble_path::NibblePath, ROOT_NIBBLE_HEIGHT},
     proof::{SparseMerkleProof, SparseMerkleRangeProof},
+    state_storage_usage::StateStorageUsage,
     transaction::Version,
 };
 use itertools::process_results;
-use schemadb::{SchemaBatch, DB};
+use schemadb::{ReadOptions, SchemaBatch, DB};
 use std::{collections::HashMap, sync::Arc};
-use storage_interface::StateSnapshotReceiver;
+use storage_interface::{StateItem, StateSnapshotReceiver};
 
 type LeafNode = aptos_jellyfish_merkle::node_type::LeafNode<AccountStateBlob>;
 type Node = aptos_jellyfish_merkle::node_type::Node<AccountStateBlob>;
@@ -40,16 +43,31 @@ type NodeBatch = aptos_jellyfish_merkle::NodeBatch<AccountStateBlob>;
 pub(crate) struct StateStore {
     db: Arc<DB>,
     pub account_count_migration: bool,
+    /// Whether the state values are also written to `StateValueSchema`, indexed by version.
+    pub state_value_index: bool,
 }
 
 impl StateStore {
-    pub fn new(db: Arc<DB>, account_count_migration: bool) -> Self {
+    pub fn new(db: Arc<DB>, account_count_migration: bool, state_value_index: bool) -> Self {
         Self {
             db,
             account_count_migration,
+            state_value_index,
         }
     }
 
+    /// Deletes all the state values indexed by version. The index is deleted whenever the node
+    /// runs without it, so that it only ever holds the values written since it was last enabled,
+    /// and the reads of values written before fall back to the state Merkle tree.
+    pub fn delete_state_value_index(&self) -> Result<()> {
+        let mut batch = SchemaBatch::new();
+        batch.delete_range_inclusive::<StateValueSchema>(
+            &(HashValue::zero(), Version::max_value()),
+            &(HashValue::new([0xff; HashValue::LENGTH]), 0),
+        )?;
+        self.db.write_schemas(batch)
+    }
+
     /// Get the account state blob given account address and root hash of state Merkle tree
     pub fn get_account_state_with_proof_by_version(
         &self,
@@ -63,6 +81,57 @@ impl StateStore {
             .get_with_proof(address.hash(), version)
     }
 
+    /// Get the account state blob given account address and version, without proof. If enabled,
+    /// the blob is read from the state values indexed by version, falling back to the state Merkle
+    /// tree for accounts last written before the index was populated, e.g. restored from a
+    /// snapshot.
+    pub fn get_account_state_by_version(
+        &self,
+        address: AccountAddress,
+        version: Version,
+    ) -> Result<Option<AccountStateBlob>> {
+        // Like reads with a proof, reads of a pruned version fail: every version has a root in
+        // the state Merkle tree, which is only deleted once the version is pruned.
+        self.get_node(&NodeKey::new_empty_path(version))?;
+
+        let key_hash = address.hash();
+        if !self.state_value_index {
+            return JellyfishMerkleTree::new_migration(self, self.account_count_migration)
+                .get(key_hash, version);
+        }
+        let mut iter = self.db.iter::<StateValueSchema>(ReadOptions::default())?;
+        iter.seek(&(key_hash, version))?;
+        // The column family has a prefix bloom filter, so the records past the ones of the key
+        // sought may be missing: only the first one is looked at, and only if it's of that key.
+        if let Some(((found_hash, _), blob)) = iter.next().transpose()? {
+            if found_hash == key_hash {
+                return Ok(Some(blob));
+            }
+        }
+        JellyfishMerkleTree::new_migration(self, self.account_count_migration)
+            .get(key_hash, version)
+    }
+
+    /// Get the account state blobs of a batch of addresses, reading the nodes at each depth of
+    /// the state Merkle tree with a single MultiGet.
+    pub fn get_account_states_with_proof_by_version(
+        &self,
+        addresses: &[AccountAddress],
+        version: Version,
+    ) -> Result<
+        Vec<(
+            Option<AccountStateBlob>,
+            SparseMerkleProof<AccountStateBlob>,
+        )>,
+    > {
+        let keys = addresses
+            .iter()
+            .map(|address| address.hash())
+            .collect::<Vec<_>>();
+        JellyfishMerkleTree::new_migration(self, self.account_count_migration)
+            .batch_get_with_proof(&keys, version)
+    }
+
     /// Gets the proof that proves a range of accounts.
     pub fn get_account_state_range_proof(
         &self,
@@ -82,6 +151,20 @@ impl StateStore {
         first_version: Version,
         cs: &mut ChangeSet,
     ) -> Result<Vec<HashValue>> {
+        self.put_state_storage_usages(&account_state_sets, first_version, cs)?;
+        if self.state_value_index {
+            // The blobs are written a second time, besides the leaves of the state Merkle tree.
+            for (i, account_states) in account_state_sets.iter().enumerate() {
+                for (address, blob) in account_states {
+                    cs.batch.put::<StateValueSchema>(
+                        &(address.hash(), first_version + i as Version),
+                        blob,
+                    )?;
+                    DIEM_STORAGE_STATE_VALUE_INDEX_BYTES.inc_by(blob.as_ref().len() as u64);
+                }
+            }
+        }
+
         let blob_sets = account_state_sets
             .into_iter()
             .map(|account_states| {
@@ -121,6 +204,53 @@ impl StateStore {
         Ok(new_root_hash_vec)
     }
 
+    /// Put the usage of the state after each of `account_state_sets` to `batch`, computed from
+    /// the usage at the version before `first_version`. Nothing is put if that usage is unknown,
+    /// e.g. for a DB restored from a state snapshot.
+    fn put_state_storage_usages(
+        &self,
+        account_state_sets: &[HashMap<AccountAddress, AccountStateBlob>],
+        first_version: Version,
+        cs: &mut ChangeSet,
+    ) -> Result<()> {
+        let mut usage = if first_version == 0 {
+            StateStorageUsage::zero()
+        } else {
+            match self.get_state_storage_usage(first_version - 1)? {
+                Some(usage) => usage,
+                None => return Ok(()),
+            }
+        };
+
+        let tree = JellyfishMerkleTree::new_migration(self, self.account_count_migration);
+        // sizes of the blobs updated by the previous sets
+        let mut blob_sizes = HashMap::new();
+        for (i, account_states) in account_state_sets.iter().enumerate() {
+            for (address, blob) in account_states {
+                let old_size = match blob_sizes.get(address) {
+                    Some(size) => Some(*size),
+                    None if first_version > 0 => tree
+                        .get(address.hash(), first_version - 1)?
+                        .map(|blob| blob.as_ref().len()),
+                    None => None,
+                };
+                if let Some(old_size) = old_size {
+                    usage.remove_item(old_size);
+                }
+                usage.add_item(blob.as_ref().len());
+                blob_sizes.insert(*address, blob.as_ref().len());
+            }
+            cs.batch
+                .put::<StateStorageUsageSchema>(&(first_version + i as Version), &usage)?;
+        }
+        Ok(())
+    }
+
+    /// Get the usage of the state at the version, if it's tracked.
+    pub fn get_state_storage_usage(&self, version: Version) -> Result<Option<StateStorageUsage>> {
+        self.db.get::<StateStorageUsageSchema>(&version)
+    }
+
     pub fn get_root_hash(&self, version: Version) -> Result<HashValue> {
         JellyfishMerkleTree::new_migration(self, self.account_count_migration)
             .get_root_hash(version)
@@ -192,6 +322,36 @@ impl StateStore {
         })
     }
 
+    /// Iterates over the accounts at the version from `start_key` on, attaching a range proof to
+    /// every `proof_interval`th account and to the last one.
+    pub fn get_state_item_iterator(
+        self: &Arc<Self>,
+        version: Version,
+        start_key: HashValue,
+        proof_interval: usize,
+    ) -> Result<impl Iterator<Item = Result<StateItem>> + Send + Sync> {
+        let store = Arc::clone(self);
+        let mut accounts =
+            JellyfishMerkleIterator::new(Arc::clone(self), version, start_key)?.peekable();
+        let mut num_items = 0;
+        Ok(std::iter::from_fn(move || {
+            accounts.next().map(|account| {
+                let (key, value) = account?;
+                num_items += 1;
+                let range_proof = if num_items % proof_interval == 0 || accounts.peek().is_none() {
+                    Some(store.get_account_state_range_proof(key, version)?)
+                } else {
+                    None
+                };
+                Ok(StateItem {
+                    key,
+                    value,
+                    range_proof,
+                })
+            })
+        }))
+    }
+
     pub fn get_snapshot_receiver(
         self: &Arc<Self>,
         version: Version,
@@ -211,6 +371,10 @@ impl TreeReader<AccountStateBlob> for StateStore {
         self.db.get::<JellyfishMerkleNodeSchema>(node_key)
     }
 
+    fn get_node_options(&self, node_keys: &[NodeKey]) -> Result<Vec<Option<Node>>> {
+        self.db.multi_get::<JellyfishMerkleNodeSchema>(node_keys)
+    }
+
     fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>> {
         // Since everything has the same version during restore, we seek to the first node and get
         // its version.
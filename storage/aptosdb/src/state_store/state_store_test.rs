@@ -14,7 +14,7 @@ use proptest::{
     collection::{hash_map, vec},
     prelude::*,
 };
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use storage_interface::StateSnapshotReceiver;
 
 fn put_account_state_set(
@@ -65,6 +65,7 @@ fn prune_stale_indices(
         least_readable_version,
         target_least_readable_version,
         limit,
+        &BTreeSet::new(), /* exempted_versions */
     )
     .unwrap();
 }
@@ -81,6 +82,19 @@ fn verify_state_in_store(
         .unwrap();
     assert_eq!(value.as_ref(), expected_value);
     proof.verify(root, address.hash(), value.as_ref()).unwrap();
+    assert_eq!(
+        store
+            .get_account_state_by_version(address, version)
+            .unwrap()
+            .as_ref(),
+        expected_value
+    );
+    assert_eq!(
+        store
+            .get_account_states_with_proof_by_version(&[address], version)
+            .unwrap(),
+        vec![(value, proof)]
+    );
 }
 
 #[test]
@@ -137,6 +151,22 @@ fn test_state_store_reader_writer() {
     verify_state_in_store(store, address1, Some(&value1_update), 1, root);
     verify_state_in_store(store, address2, Some(&value2), 1, root);
     verify_state_in_store(store, address3, Some(&value3), 1, root);
+
+    // Batched reads match the single reads, at any version
+    let addresses = [address3, address1, address2, address1];
+    for version in 0..=1 {
+        assert_eq!(
+            store
+                .get_account_states_with_proof_by_version(&addresses, version)
+                .unwrap(),
+            addresses
+                .iter()
+                .map(|address| store
+                    .get_account_state_with_proof_by_version(*address, version)
+                    .unwrap())
+                .collect::<Vec<_>>()
+        );
+    }
 }
 
 #[test]
@@ -233,6 +263,187 @@ fn test_retired_records() {
     }
 }
 
+#[test]
+fn test_get_account_state_by_version() {
+    let address1 = AccountAddress::new([1u8; AccountAddress::LENGTH]);
+    let address2 = AccountAddress::new([2u8; AccountAddress::LENGTH]);
+    let address3 = AccountAddress::new([3u8; AccountAddress::LENGTH]);
+    let value1 = AccountStateBlob::from(vec![0x01]);
+    let value1_update = AccountStateBlob::from(vec![0x11]);
+    let value2 = AccountStateBlob::from(vec![0x02]);
+
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+    update_store(
+        store,
+        vec![
+            (address1, value1.clone()),
+            (address2, value2.clone()),
+            (address1, value1_update.clone()),
+        ]
+        .into_iter(),
+        0, /* first_version */
+    );
+
+    // Each version reads the latest value written at or before it
+    let expected = [
+        (Some(&value1), None),
+        (Some(&value1), Some(&value2)),
+        (Some(&value1_update), Some(&value2)),
+    ];
+    for (version, (expected1, expected2)) in expected.iter().enumerate() {
+        let version = version as Version;
+        let get = |address| {
+            store
+                .get_account_state_by_version(address, version)
+                .unwrap()
+        };
+        assert_eq!(get(address1).as_ref(), *expected1);
+        assert_eq!(get(address2).as_ref(), *expected2);
+        assert_eq!(get(address3), None);
+    }
+
+    // A state restored from a snapshot isn't indexed by version, and is read from the tree
+    let version = 2;
+    let tmp_dir2 = TempPath::new();
+    let db2 = AptosDB::new_for_test(&tmp_dir2);
+    let store2 = &db2.state_store;
+    let mut restore = JellyfishMerkleRestore::new(
+        Arc::clone(store2),
+        version,
+        store.get_root_hash(version).unwrap(),
+        true, /* leaf_count_migration */
+    )
+    .unwrap();
+    let mut chunk = vec![
+        (address1.hash(), value1_update.clone()),
+        (address2.hash(), value2.clone()),
+    ];
+    chunk.sort_unstable_by_key(|(key, _value)| *key);
+    let proof = store
+        .get_account_state_range_proof(chunk.last().unwrap().0, version)
+        .unwrap();
+    restore.add_chunk(chunk, proof).unwrap();
+    restore.finish().unwrap();
+    assert_eq!(
+        store2
+            .get_account_state_by_version(address1, version)
+            .unwrap(),
+        Some(value1_update)
+    );
+    assert_eq!(
+        store2
+            .get_account_state_by_version(address3, version)
+            .unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_state_value_index_toggled() {
+    let address = AccountAddress::new([1u8; AccountAddress::LENGTH]);
+    let value = AccountStateBlob::from(vec![0x01]);
+    let value_update = AccountStateBlob::from(vec![0x11]);
+
+    let tmp_dir = TempPath::new();
+    let open = |state_value_index| {
+        AptosDB::open(
+            &tmp_dir,
+            false, /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            RocksdbConfig::default(),
+            true, /* account_count_migration */
+            state_value_index,
+        )
+        .unwrap()
+    };
+    let index_is_empty = |store: &StateStore| {
+        let mut iter = store
+            .db
+            .iter::<StateValueSchema>(ReadOptions::default())
+            .unwrap();
+        iter.seek_to_first();
+        iter.next().is_none()
+    };
+
+    let db = open(true);
+    update_store(&db.state_store, vec![(address, value)].into_iter(), 0);
+    assert!(!index_is_empty(&db.state_store));
+    drop(db);
+
+    // Without the index, nothing is indexed and the index is deleted.
+    let db = open(false);
+    assert!(index_is_empty(&db.state_store));
+    update_store(
+        &db.state_store,
+        vec![(address, value_update.clone())].into_iter(),
+        1,
+    );
+    assert!(index_is_empty(&db.state_store));
+    drop(db);
+
+    // The value written while the index was disabled isn't shadowed by an older indexed one.
+    let db = open(true);
+    assert_eq!(
+        db.state_store
+            .get_account_state_by_version(address, 1)
+            .unwrap(),
+        Some(value_update)
+    );
+}
+
+#[test]
+fn test_state_storage_usage() {
+    let address1 = AccountAddress::new([1u8; AccountAddress::LENGTH]);
+    let address2 = AccountAddress::new([2u8; AccountAddress::LENGTH]);
+    let value1 = AccountStateBlob::from(vec![0x01; 3]);
+    let value1_update = AccountStateBlob::from(vec![0x11; 10]);
+    let value2 = AccountStateBlob::from(vec![0x02; 5]);
+    let value2_update = AccountStateBlob::from(vec![0x12; 1]);
+
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+
+    put_account_state_set(
+        store,
+        vec![(address1, value1), (address2, value2)],
+        0, /* version */
+        3, /* expected_nodes_created */
+        0, /* expected_nodes_retired */
+        0, /* expected_blobs_retired */
+    );
+    assert_eq!(
+        store.get_state_storage_usage(0).unwrap(),
+        Some(StateStorageUsage::new(2, 8))
+    );
+
+    // Multiple versions in one batch, updating the same account.
+    let mut cs = ChangeSet::new();
+    store
+        .put_account_state_sets(
+            vec![
+                vec![(address1, value1_update)].into_iter().collect(),
+                vec![(address2, value2_update)].into_iter().collect(),
+            ],
+            None,
+            1, /* first_version */
+            &mut cs,
+        )
+        .unwrap();
+    store.db.write_schemas(cs.batch).unwrap();
+    assert_eq!(
+        store.get_state_storage_usage(1).unwrap(),
+        Some(StateStorageUsage::new(2, 15))
+    );
+    assert_eq!(
+        store.get_state_storage_usage(2).unwrap(),
+        Some(StateStorageUsage::new(2, 11))
+    );
+    assert_eq!(store.get_state_storage_usage(3).unwrap(), None);
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
 
@@ -265,6 +476,63 @@ proptest! {
         }
     }
 
+    #[test]
+    fn test_get_state_item_iterator(
+        (input, proof_interval, start_idx) in hash_map(any::<AccountAddress>(), any::<AccountStateBlob>(), 2..500)
+            .prop_flat_map(|input| {
+                let len = input.len();
+                (Just(input), 1..len, 0..len)
+            })
+    ) {
+        let tmp_dir1 = TempPath::new();
+        let db1 = AptosDB::new_for_test(&tmp_dir1);
+        let store1 = &db1.state_store;
+        init_store(store1, input.clone().into_iter());
+        let version = (input.len() - 1) as Version;
+        let expected_root_hash = store1.get_root_hash(version).unwrap();
+
+        let mut ordered_input: Vec<_> = input
+            .into_iter()
+            .map(|(addr, value)| (addr.hash(), value))
+            .collect();
+        ordered_input.sort_unstable_by_key(|(key, _value)| *key);
+
+        // The iterator starts at the start key
+        let start_key = ordered_input[start_idx].0;
+        let items = store1
+            .get_state_item_iterator(version, start_key, proof_interval)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        prop_assert_eq!(
+            items.iter().map(|item| (item.key, item.value.clone())).collect::<Vec<_>>(),
+            ordered_input[start_idx..].to_vec()
+        );
+
+        // The state can be restored from the chunks delimited by the proofs
+        let items = store1
+            .get_state_item_iterator(version, HashValue::zero(), proof_interval)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let tmp_dir2 = TempPath::new();
+        let db2 = AptosDB::new_for_test(&tmp_dir2);
+        let mut restore = db2.state_store.get_snapshot_receiver(version, expected_root_hash).unwrap();
+        let mut chunk = vec![];
+        for (idx, item) in items.into_iter().enumerate() {
+            chunk.push((item.key, item.value));
+            prop_assert_eq!(
+                item.range_proof.is_some(),
+                (idx + 1) % proof_interval == 0 || idx + 1 == ordered_input.len()
+            );
+            if let Some(proof) = item.range_proof {
+                restore.add_chunk(std::mem::take(&mut chunk), proof).unwrap();
+            }
+        }
+        restore.finish_box().unwrap();
+        prop_assert_eq!(db2.state_store.get_root_hash(version).unwrap(), expected_root_hash);
+    }
+
     #[test]
     fn test_raw_restore(
         (input, batch1_size) in hash_map(any::<AccountAddress>(), any::<AccountStateBlob>(), 2..1000)
@@ -408,6 +676,7 @@ proptest! {
                 NO_OP_STORAGE_PRUNER_CONFIG,  /* pruner config */
                 RocksdbConfig::default(),
                 true, /* account_count_migration */
+                true, /* state_value_index */
             ).unwrap();
             let store2 = &db2.state_store;
             // confirm that leaf counts were not written
@@ -498,6 +767,7 @@ proptest! {
                 NO_OP_STORAGE_PRUNER_CONFIG,
                 RocksdbConfig::default(),
                 false, /* account_count_migration */
+                true, /* state_value_index */
             ).unwrap();
             let store = &db.state_store;
             init_store(store, before.into_iter());
@@ -3,7 +3,8 @@
 
 use crate::{
     backup_types::{
-        epoch_ending::restore::EpochHistory, state_snapshot::manifest::StateSnapshotBackup,
+        epoch_ending::restore::EpochHistory,
+        state_snapshot::manifest::{StateSnapshotBackup, StateSnapshotChunk},
     },
     metrics::{
         restore::{
@@ -16,17 +17,20 @@ use crate::{
     },
     storage::{BackupStorage, FileHandle},
     utils::{
-        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, GlobalRestoreOptions,
-        RestoreRunMode,
+        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, stream::TryStreamX,
+        GlobalRestoreOptions, RestoreRunMode,
     },
 };
 use anyhow::{anyhow, ensure, Result};
 use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
 use aptos_types::{
-    account_state_blob::AccountStateBlob, ledger_info::LedgerInfoWithSignatures,
-    proof::TransactionInfoWithProof, transaction::Version,
+    account_state_blob::AccountStateBlob,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{SparseMerkleRangeProof, TransactionInfoWithProof},
+    transaction::Version,
 };
+use futures::{future, stream, StreamExt, TryFutureExt, TryStreamExt};
 use std::sync::Arc;
 use storage_interface::StateSnapshotReceiver;
 use structopt::StructOpt;
@@ -50,6 +54,7 @@ pub struct StateSnapshotRestoreController {
     target_version: Version,
     epoch_history: Option<Arc<EpochHistory>>,
     account_count_migration: bool,
+    concurrent_downloads: usize,
 }
 
 impl StateSnapshotRestoreController {
@@ -67,6 +72,7 @@ impl StateSnapshotRestoreController {
             target_version: global_opt.target_version,
             epoch_history,
             account_count_migration: global_opt.account_count_migration,
+            concurrent_downloads: global_opt.concurrent_downloads,
         }
     }
 
@@ -134,23 +140,47 @@ impl StateSnapshotRestoreController {
         // FIXME update counters
         ver_gauge.set(self.version as i64);
         tgt_leaf_idx.set(manifest.chunks.last().map_or(0, |c| c.last_idx as i64));
-        for chunk in manifest.chunks {
-            let blobs = self.read_account_state_chunk(chunk.blobs).await?;
-            let proof = self.storage.load_bcs_file(&chunk.proof).await?;
 
+        // Chunks are downloaded and deserialized concurrently, at most `concurrent_downloads` of
+        // them buffered at a time, while the receiver adds the ones before them in order.
+        let con = self.concurrent_downloads;
+        let storage = self.storage.clone();
+        let mut loaded_chunk_stream = stream::iter(manifest.chunks)
+            .map(move |chunk| {
+                let storage = storage.clone();
+                tokio::task::spawn(async move { Self::load_chunk(&storage, chunk).await })
+                    .err_into::<anyhow::Error>()
+                    .and_then(future::ready)
+            })
+            .map(Result::<_>::Ok)
+            .try_buffered_x(con, con);
+        while let Some((last_idx, blobs, proof)) = loaded_chunk_stream.try_next().await? {
             receiver.add_chunk(blobs, proof)?;
-            leaf_idx.set(chunk.last_idx as i64);
+            leaf_idx.set(last_idx as i64);
         }
 
         receiver.finish()?;
         Ok(())
     }
 
+    async fn load_chunk(
+        storage: &Arc<dyn BackupStorage>,
+        chunk: StateSnapshotChunk,
+    ) -> Result<(
+        usize,
+        Vec<(HashValue, AccountStateBlob)>,
+        SparseMerkleRangeProof,
+    )> {
+        let blobs = Self::read_account_state_chunk(storage, &chunk.blobs).await?;
+        let proof = storage.load_bcs_file(&chunk.proof).await?;
+        Ok((chunk.last_idx, blobs, proof))
+    }
+
     async fn read_account_state_chunk(
-        &self,
-        file_handle: FileHandle,
+        storage: &Arc<dyn BackupStorage>,
+        file_handle: &FileHandle,
     ) -> Result<Vec<(HashValue, AccountStateBlob)>> {
-        let mut file = self.storage.open_for_read(&file_handle).await?;
+        let mut file = storage.open_for_read(file_handle).await?;
 
         let mut chunk = vec![];
 
@@ -14,7 +14,10 @@ use backup_cli::{
         state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
-    coordinators::backup::{BackupCoordinator, BackupCoordinatorOpt},
+    coordinators::{
+        backup::{BackupCoordinator, BackupCoordinatorOpt},
+        verify_remote::VerifyRemoteCoordinator,
+    },
     metadata::{cache, cache::MetadataCacheOpt},
     storage::StorageOpt,
     utils::{
@@ -38,6 +41,11 @@ enum OneShotCommand {
     Query(OneShotQueryType),
     #[structopt(about = "Do a one shot backup.")]
     Backup(OneShotBackupOpt),
+    #[structopt(
+        about = "Verify the backups in the storage without restoring them: checks the manifests \
+        and the checksums of the files recorded by the storage."
+    )]
+    VerifyRemote(OneShotVerifyRemoteOpt),
 }
 
 #[derive(StructOpt)]
@@ -69,6 +77,16 @@ struct OneShotQueryBackupStorageStateOpt {
     storage: StorageOpt,
 }
 
+#[derive(StructOpt)]
+struct OneShotVerifyRemoteOpt {
+    #[structopt(flatten)]
+    metadata_cache: MetadataCacheOpt,
+    #[structopt(flatten)]
+    concurrent_downloads: ConcurrentDownloadsOpt,
+    #[structopt(subcommand)]
+    storage: StorageOpt,
+}
+
 #[derive(StructOpt)]
 struct OneShotBackupOpt {
     #[structopt(flatten)]
@@ -195,6 +213,16 @@ async fn main_impl() -> Result<()> {
                     }
                 }
             }
+            OneShotCommand::VerifyRemote(opt) => {
+                let summary = VerifyRemoteCoordinator::new(
+                    opt.storage.init_storage().await?,
+                    opt.metadata_cache,
+                    opt.concurrent_downloads.get(),
+                )
+                .run()
+                .await?;
+                println!("{}", summary)
+            }
         },
         Command::Coordinator(coordinator_cmd) => match coordinator_cmd {
             CoordinatorCommand::Run(opt) => {
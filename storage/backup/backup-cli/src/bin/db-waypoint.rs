@@ -0,0 +1,148 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_config::config::NO_OP_STORAGE_PRUNER_CONFIG;
+use aptos_infallible::duration_since_epoch;
+use aptos_logger::{prelude::*, Level, Logger};
+use aptos_types::{chain_id::ChainId, waypoint::Waypoint};
+use aptosdb::AptosDB;
+use backup_cli::{
+    metadata::{cache, cache::MetadataCacheOpt},
+    storage::StorageOpt,
+    utils::{
+        backup_service_client::{BackupServiceClient, BackupServiceClientOpt},
+        ConcurrentDownloadsOpt, RocksdbOpt,
+    },
+    waypoint::{
+        verify_waypoint, waypoint_from_backup, waypoint_from_db, SignedWaypointDocument,
+        WaypointDocument,
+    },
+};
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(about = "Waypoint generation, verification and publishing tool.")]
+enum Command {
+    #[structopt(about = "Derives the waypoint of an epoch ending ledger info from a trusted DB.")]
+    FromDb(FromDbOpt),
+    #[structopt(
+        about = "Derives the waypoint of an epoch ending ledger info from a trusted backup."
+    )]
+    FromBackup(FromBackupOpt),
+    #[structopt(
+        about = "Verifies a waypoint against the epoch change proof served by the backup \
+        service of a node, starting from a trusted waypoint."
+    )]
+    Verify(VerifyOpt),
+    #[structopt(about = "Signs waypoints into a JSON document to be published.")]
+    Publish(PublishOpt),
+}
+
+#[derive(StructOpt)]
+struct FromDbOpt {
+    #[structopt(long = "db-dir", parse(from_os_str))]
+    db_dir: PathBuf,
+    #[structopt(
+        long,
+        help = "Epoch ended by the ledger info. Defaults to the latest one."
+    )]
+    epoch: Option<u64>,
+    #[structopt(flatten)]
+    rocksdb_opt: RocksdbOpt,
+}
+
+#[derive(StructOpt)]
+struct FromBackupOpt {
+    #[structopt(
+        long,
+        help = "Epoch ended by the ledger info. Defaults to the latest one."
+    )]
+    epoch: Option<u64>,
+    #[structopt(flatten)]
+    metadata_cache: MetadataCacheOpt,
+    #[structopt(flatten)]
+    concurrent_downloads: ConcurrentDownloadsOpt,
+    #[structopt(subcommand)]
+    storage: StorageOpt,
+}
+
+#[derive(StructOpt)]
+struct VerifyOpt {
+    #[structopt(long, help = "Waypoint to verify from, e.g. the genesis waypoint.")]
+    trusted_waypoint: Waypoint,
+    #[structopt(long, help = "Waypoint to verify.")]
+    waypoint: Waypoint,
+    #[structopt(flatten)]
+    client: BackupServiceClientOpt,
+}
+
+#[derive(StructOpt)]
+struct PublishOpt {
+    #[structopt(long = "waypoint", required = true, help = "Waypoints to publish.")]
+    waypoints: Vec<Waypoint>,
+    #[structopt(long)]
+    chain_id: ChainId,
+    #[structopt(
+        long,
+        parse(from_os_str),
+        help = "BCS serialized Ed25519 private key of the publisher."
+    )]
+    signing_key_file: PathBuf,
+    #[structopt(long, parse(from_os_str), help = "Path of the signed JSON document.")]
+    output: PathBuf,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    main_impl().await.map_err(|e| {
+        error!("main_impl() failed: {}", e);
+        e
+    })
+}
+
+async fn main_impl() -> Result<()> {
+    Logger::new().level(Level::Info).read_env().init();
+
+    match Command::from_args() {
+        Command::FromDb(opt) => {
+            let db = AptosDB::open(
+                &opt.db_dir,
+                true,                        /* read_only */
+                NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+                opt.rocksdb_opt.into(),
+                false, /* account_count_migration */
+                true, /* state_value_index */
+            )?;
+            println!("{}", waypoint_from_db(&db, opt.epoch)?);
+        }
+        Command::FromBackup(opt) => {
+            let storage = opt.storage.init_storage().await?;
+            let view = cache::sync_and_load(
+                &opt.metadata_cache,
+                storage.clone(),
+                opt.concurrent_downloads.get(),
+            )
+            .await?;
+            println!("{}", waypoint_from_backup(storage, &view, opt.epoch).await?);
+        }
+        Command::Verify(opt) => {
+            let client = BackupServiceClient::new_with_opt(opt.client);
+            verify_waypoint(&client, opt.trusted_waypoint, opt.waypoint).await?;
+            println!("Waypoint {} verified.", opt.waypoint);
+        }
+        Command::Publish(opt) => {
+            let private_key = generate_key::load_key(&opt.signing_key_file);
+            let document = WaypointDocument {
+                chain_id: opt.chain_id,
+                waypoints: opt.waypoints,
+                timestamp_secs: duration_since_epoch().as_secs(),
+            };
+            let signed = SignedWaypointDocument::sign(document, &private_key);
+            std::fs::write(&opt.output, serde_json::to_vec_pretty(&signed)?)?;
+            info!("Signed waypoint document written to {:?}.", opt.output);
+        }
+    }
+    Ok(())
+}
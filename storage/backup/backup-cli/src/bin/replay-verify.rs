@@ -60,6 +60,7 @@ async fn main_impl() -> Result<()> {
         NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
         opt.rocksdb_opt.into(),
         true, /* account_count_migration */
+        true, /* state_value_index */
     )?)
     .get_restore_handler();
     ReplayVerifyCoordinator::new(
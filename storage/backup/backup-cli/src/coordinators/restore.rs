@@ -3,7 +3,7 @@
 
 use crate::{
     backup_types::{
-        epoch_ending::restore::EpochHistoryRestoreController,
+        epoch_ending::restore::{EpochHistory, EpochHistoryRestoreController},
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
         transaction::restore::TransactionRestoreBatchController,
     },
@@ -18,6 +18,7 @@ use crate::{
 use anyhow::{bail, Result};
 use aptos_logger::prelude::*;
 use aptos_types::transaction::Version;
+use futures::try_join;
 use std::sync::Arc;
 use structopt::StructOpt;
 
@@ -166,26 +167,44 @@ impl RestoreCoordinator {
             ))
         };
 
-        if let Some(backup) = state_snapshot {
-            StateSnapshotRestoreController::new(
-                StateSnapshotRestoreOpt {
-                    manifest_handle: backup.manifest,
-                    version: backup.version,
-                },
-                self.global_opt.clone(),
-                Arc::clone(&self.storage),
+        // Transactions up to the state snapshot version are saved without being replayed, so
+        // the backups ending by then are restored concurrently with the state snapshot, and only
+        // the rest wait for it.
+        let (txns_before_snapshot, txns_after_snapshot): (Vec<_>, Vec<_>) = match &state_snapshot {
+            Some(backup) => transactions
+                .into_iter()
+                .partition(|t| t.last_version <= backup.version),
+            None => (Vec::new(), transactions),
+        };
+
+        let state_snapshot_restore = async {
+            if let Some(backup) = state_snapshot {
+                StateSnapshotRestoreController::new(
+                    StateSnapshotRestoreOpt {
+                        manifest_handle: backup.manifest,
+                        version: backup.version,
+                    },
+                    self.global_opt.clone(),
+                    Arc::clone(&self.storage),
+                    epoch_history.clone(),
+                )
+                .run()
+                .await?;
+            }
+            Result::<()>::Ok(())
+        };
+        let txn_save = self
+            .transaction_restore(
+                txns_before_snapshot,
+                replay_transactions_from_version,
                 epoch_history.clone(),
             )
-            .run()
-            .await?;
-        }
+            .run();
+        try_join!(state_snapshot_restore, txn_save)?;
 
-        let txn_manifests = transactions.into_iter().map(|b| b.manifest).collect();
-        TransactionRestoreBatchController::new(
-            self.global_opt,
-            self.storage,
-            txn_manifests,
-            Some(replay_transactions_from_version),
+        self.transaction_restore(
+            txns_after_snapshot,
+            replay_transactions_from_version,
             epoch_history,
         )
         .run()
@@ -196,6 +215,21 @@ impl RestoreCoordinator {
 }
 
 impl RestoreCoordinator {
+    fn transaction_restore(
+        &self,
+        transactions: Vec<TransactionBackupMeta>,
+        replay_from_version: Version,
+        epoch_history: Option<Arc<EpochHistory>>,
+    ) -> TransactionRestoreBatchController {
+        TransactionRestoreBatchController::new(
+            self.global_opt.clone(),
+            Arc::clone(&self.storage),
+            transactions.into_iter().map(|b| b.manifest).collect(),
+            Some(replay_from_version),
+            epoch_history,
+        )
+    }
+
     fn target_version(&self) -> Version {
         self.global_opt.target_version
     }
@@ -0,0 +1,233 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::manifest::EpochEndingBackup, state_snapshot::manifest::StateSnapshotBackup,
+        transaction::manifest::TransactionBackup,
+    },
+    metadata,
+    metadata::cache::MetadataCacheOpt,
+    storage::{BackupStorage, FileHandle},
+    utils::storage_ext::BackupStorageExt,
+};
+use anyhow::{bail, ensure, Result};
+use aptos_crypto::HashValue;
+use aptos_logger::prelude::*;
+use futures::stream::{self, StreamExt};
+use std::{fmt, sync::Arc};
+
+/// Verifies the backups in a storage without restoring them: every manifest is checked to be
+/// consistent with its metadata and with itself, and every file is read back and checked against
+/// the checksum the storage recorded when writing it, if any.
+///
+/// Unlike the `VerifyCoordinator`, this doesn't verify the signatures and proofs in the backups,
+/// but it catches files which are missing, truncated or corrupted by the storage.
+pub struct VerifyRemoteCoordinator {
+    storage: Arc<dyn BackupStorage>,
+    metadata_cache_opt: MetadataCacheOpt,
+    concurrent_downloads: usize,
+}
+
+#[derive(Default)]
+pub struct VerifyRemoteSummary {
+    pub manifests: usize,
+    /// Files whose content matches the checksum recorded by the storage.
+    pub files_verified: usize,
+    /// Files which were read, but for which the storage recorded no checksum.
+    pub files_without_checksum: usize,
+}
+
+impl fmt::Display for VerifyRemoteSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "manifests: {}, files_verified: {}, files_without_checksum: {}",
+            self.manifests, self.files_verified, self.files_without_checksum,
+        )
+    }
+}
+
+impl VerifyRemoteCoordinator {
+    pub fn new(
+        storage: Arc<dyn BackupStorage>,
+        metadata_cache_opt: MetadataCacheOpt,
+        concurrent_downloads: usize,
+    ) -> Self {
+        Self {
+            storage,
+            metadata_cache_opt,
+            concurrent_downloads,
+        }
+    }
+
+    pub async fn run(self) -> Result<VerifyRemoteSummary> {
+        info!("Verify remote coordinator started.");
+        let metadata_view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+
+        let mut summary = VerifyRemoteSummary::default();
+        let mut errors = Vec::new();
+        // Manifests are files too, so they are verified with the files they refer to
+        let mut files = Vec::new();
+
+        for backup in metadata_view.epoch_ending_backups() {
+            summary.manifests += 1;
+            files.push(backup.manifest.clone());
+            let res = async {
+                let manifest: EpochEndingBackup =
+                    self.storage.load_json_file(&backup.manifest).await?;
+                manifest.verify()?;
+                ensure!(
+                    manifest.first_epoch == backup.first_epoch
+                        && manifest.last_epoch == backup.last_epoch,
+                    "Epochs in manifest [{}, {}] don't match metadata [{}, {}].",
+                    manifest.first_epoch,
+                    manifest.last_epoch,
+                    backup.first_epoch,
+                    backup.last_epoch,
+                );
+                Ok(manifest
+                    .chunks
+                    .into_iter()
+                    .map(|chunk| chunk.ledger_infos)
+                    .collect::<Vec<_>>())
+            }
+            .await;
+            match res {
+                Ok(chunk_files) => files.extend(chunk_files),
+                Err(e) => errors.push(format!("{}: {}", backup.manifest, e)),
+            }
+        }
+
+        for backup in metadata_view.state_snapshot_backups() {
+            summary.manifests += 1;
+            files.push(backup.manifest.clone());
+            let res = async {
+                let manifest: StateSnapshotBackup =
+                    self.storage.load_json_file(&backup.manifest).await?;
+                verify_state_snapshot_manifest(&manifest)?;
+                ensure!(
+                    manifest.version == backup.version,
+                    "Version in manifest {} doesn't match metadata {}.",
+                    manifest.version,
+                    backup.version,
+                );
+                let mut chunk_files = vec![manifest.proof];
+                for chunk in manifest.chunks {
+                    chunk_files.push(chunk.blobs);
+                    chunk_files.push(chunk.proof);
+                }
+                Ok(chunk_files)
+            }
+            .await;
+            match res {
+                Ok(chunk_files) => files.extend(chunk_files),
+                Err(e) => errors.push(format!("{}: {}", backup.manifest, e)),
+            }
+        }
+
+        for backup in metadata_view.transaction_backups() {
+            summary.manifests += 1;
+            files.push(backup.manifest.clone());
+            let res = async {
+                let manifest: TransactionBackup =
+                    self.storage.load_json_file(&backup.manifest).await?;
+                manifest.verify()?;
+                ensure!(
+                    manifest.first_version == backup.first_version
+                        && manifest.last_version == backup.last_version,
+                    "Versions in manifest [{}, {}] don't match metadata [{}, {}].",
+                    manifest.first_version,
+                    manifest.last_version,
+                    backup.first_version,
+                    backup.last_version,
+                );
+                let mut chunk_files = Vec::new();
+                for chunk in manifest.chunks {
+                    chunk_files.push(chunk.transactions);
+                    chunk_files.push(chunk.proof);
+                }
+                Ok(chunk_files)
+            }
+            .await;
+            match res {
+                Ok(chunk_files) => files.extend(chunk_files),
+                Err(e) => errors.push(format!("{}: {}", backup.manifest, e)),
+            }
+        }
+
+        let storage = &self.storage;
+        let results: Vec<_> = stream::iter(files)
+            .map(|file_handle| async move {
+                let res = verify_file(storage, &file_handle).await;
+                (file_handle, res)
+            })
+            .buffer_unordered(self.concurrent_downloads)
+            .collect()
+            .await;
+        for (file_handle, res) in results {
+            match res {
+                Ok(true) => summary.files_verified += 1,
+                Ok(false) => summary.files_without_checksum += 1,
+                Err(e) => errors.push(format!("{}: {}", file_handle, e)),
+            }
+        }
+
+        for error in &errors {
+            error!(error = %error, "Backup verification failed.");
+        }
+        if !errors.is_empty() {
+            bail!(
+                "{} problems found in the backups, first one: {}",
+                errors.len(),
+                errors[0]
+            );
+        }
+        info!(summary = %summary, "Verify remote coordinator exiting with success.");
+        Ok(summary)
+    }
+}
+
+fn verify_state_snapshot_manifest(manifest: &StateSnapshotBackup) -> Result<()> {
+    ensure!(!manifest.chunks.is_empty(), "No chunks.");
+    let mut next_idx = 0;
+    for chunk in &manifest.chunks {
+        ensure!(
+            chunk.first_idx == next_idx,
+            "Chunk ranges not continuous. Expected first index: {}, actual: {}.",
+            next_idx,
+            chunk.first_idx,
+        );
+        ensure!(
+            chunk.last_idx >= chunk.first_idx && chunk.last_key >= chunk.first_key,
+            "Chunk range invalid. [{}, {}]",
+            chunk.first_idx,
+            chunk.last_idx,
+        );
+        next_idx = chunk.last_idx + 1;
+    }
+    Ok(())
+}
+
+/// Reads a file back, returning whether the storage recorded a checksum for it, which matches.
+async fn verify_file(storage: &Arc<dyn BackupStorage>, file_handle: &FileHandle) -> Result<bool> {
+    let content = storage.read_all(file_handle).await?;
+    match storage.file_checksum(file_handle).await? {
+        Some(checksum) => {
+            let actual = HashValue::sha3_256_of(&content);
+            ensure!(
+                actual == checksum,
+                "Checksum mismatch, recorded: {}, actual: {}.",
+                checksum,
+                actual,
+            );
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
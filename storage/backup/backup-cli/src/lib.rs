@@ -9,3 +9,4 @@ pub mod metadata;
 pub mod metrics;
 pub mod storage;
 pub mod utils;
+pub mod waypoint;
@@ -3,6 +3,7 @@
 
 pub mod command_adapter;
 pub mod local_fs;
+pub mod object_store;
 
 #[cfg(test)]
 mod test_util;
@@ -12,8 +13,13 @@ mod tests;
 use crate::storage::{
     command_adapter::{CommandAdapter, CommandAdapterOpt},
     local_fs::{LocalFs, LocalFsOpt},
+    object_store::{
+        azure::AzureOpt,
+        s3::{GcsOpt, S3Opt},
+    },
 };
 use anyhow::{ensure, Result};
+use aptos_crypto::HashValue;
 use async_trait::async_trait;
 use once_cell::sync::Lazy;
 #[cfg(test)]
@@ -167,6 +173,11 @@ pub trait BackupStorage: Send + Sync {
     ///   2. But the cache does expect the content stays the same for a file handle, so when
     /// reorganising metadata files, give them new unique names.
     async fn list_metadata_files(&self) -> Result<Vec<FileHandle>>;
+    /// The SHA3-256 of the content of a file, as recorded by the storage when the file was
+    /// written. `None` if the storage doesn't record checksums, or didn't for this file.
+    async fn file_checksum(&self, _file_handle: &FileHandleRef) -> Result<Option<HashValue>> {
+        Ok(None)
+    }
 }
 
 #[derive(StructOpt)]
@@ -175,6 +186,12 @@ pub enum StorageOpt {
     LocalFs(LocalFsOpt),
     #[structopt(about = "Select the CommandAdapter backup store.")]
     CommandAdapter(CommandAdapterOpt),
+    #[structopt(about = "Select the S3 backup store.")]
+    S3(S3Opt),
+    #[structopt(about = "Select the Google Cloud Storage backup store.")]
+    Gcs(GcsOpt),
+    #[structopt(about = "Select the Azure Blob Storage backup store.")]
+    Azure(AzureOpt),
 }
 
 impl StorageOpt {
@@ -182,6 +199,9 @@ impl StorageOpt {
         Ok(match self {
             StorageOpt::LocalFs(opt) => Arc::new(LocalFs::new_with_opt(opt)),
             StorageOpt::CommandAdapter(opt) => Arc::new(CommandAdapter::new_with_opt(opt).await?),
+            StorageOpt::S3(opt) => Arc::new(opt.into_storage()),
+            StorageOpt::Gcs(opt) => Arc::new(opt.into_storage()),
+            StorageOpt::Azure(opt) => Arc::new(opt.into_storage()),
         })
     }
 }
@@ -0,0 +1,248 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::object_store::{ObjectClient, ObjectStore, ObjectStoreOpt, PartTag};
+use anyhow::{bail, Result};
+use aptos_s3_client::xml_elements;
+use async_trait::async_trait;
+use bytes::Bytes;
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use sha3::{Digest, Sha3_256};
+use std::{collections::BTreeMap, sync::Arc};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct AzureOpt {
+    #[structopt(long, help = "Storage account holding the container.")]
+    pub account: String,
+    #[structopt(long, help = "Container holding the backups.")]
+    pub container: String,
+    #[structopt(long, default_value = "", help = "Prefix of the names of the backups.")]
+    pub prefix: String,
+    #[structopt(
+        long,
+        env = "AZURE_STORAGE_SAS_TOKEN",
+        hide_env_values = true,
+        help = "Shared access signature granting access to the container, as a query string."
+    )]
+    pub sas_token: String,
+    #[structopt(flatten)]
+    pub object_store: ObjectStoreOpt,
+}
+
+impl AzureOpt {
+    pub fn into_storage(self) -> ObjectStore {
+        let client = AzureClient::new(&self.account, &self.container, &self.sas_token);
+        ObjectStore::new_with_opt(Arc::new(client), &self.prefix, self.object_store)
+    }
+}
+
+/// A client of the blobs of a container of Azure Blob Storage, authenticated by a shared access
+/// signature. Multipart uploads are uploads of blocks, which stay uncommitted until the block list
+/// of the blob is put.
+pub struct AzureClient {
+    /// URL of the container, without trailing slash.
+    container_url: String,
+    sas_token: String,
+    client: reqwest::Client,
+}
+
+impl AzureClient {
+    const API_VERSION: &'static str = "2020-10-02";
+    /// Length of the hex-encoded part number at the start of block ids, which must all have the
+    /// same length in a blob.
+    const PART_NUMBER_LEN: usize = 6;
+    /// Length of the prefix of the hex-encoded SHA3-256 of the content in block ids.
+    const DIGEST_LEN: usize = 32;
+
+    pub fn new(account: &str, container: &str, sas_token: &str) -> Self {
+        Self {
+            container_url: format!("https://{}.blob.core.windows.net/{}", account, container),
+            sas_token: sas_token.trim_start_matches('?').to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn request(&self, method: Method, key: &str) -> RequestBuilder {
+        let url = if key.is_empty() {
+            format!("{}?{}", self.container_url, self.sas_token)
+        } else {
+            format!("{}/{}?{}", self.container_url, key, self.sas_token)
+        };
+        self.client
+            .request(method, &url)
+            .header("x-ms-version", Self::API_VERSION)
+    }
+
+    async fn check(response: Response) -> Result<Response> {
+        if !response.status().is_success() {
+            let status = response.status();
+            bail!(
+                "Azure request failed: {}, {}",
+                status,
+                response.text().await?
+            );
+        }
+        Ok(response)
+    }
+
+    /// Parses the part number of a block id.
+    fn part_number(block_id: &str) -> Option<u32> {
+        let decoded = base64::decode(block_id).ok()?;
+        let part_number = decoded.get(..Self::PART_NUMBER_LEN)?;
+        u32::from_str_radix(std::str::from_utf8(part_number).ok()?, 16).ok()
+    }
+}
+
+#[async_trait]
+impl ObjectClient for AzureClient {
+    async fn put_object(&self, key: &str, content: Bytes) -> Result<()> {
+        let response = self
+            .request(Method::PUT, key)
+            .header("x-ms-blob-type", "BlockBlob")
+            .body(content)
+            .send()
+            .await?;
+        Self::check(response).await?;
+        Ok(())
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Option<Bytes>> {
+        let response = self.request(Method::GET, key).send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(Self::check(response).await?.bytes().await?))
+    }
+
+    async fn get_object_range(&self, key: &str, offset: u64, length: u64) -> Result<Bytes> {
+        if length == 0 {
+            return Ok(Bytes::new());
+        }
+        let response = self
+            .request(Method::GET, key)
+            .header(
+                "x-ms-range",
+                format!("bytes={}-{}", offset, offset + length - 1),
+            )
+            .send()
+            .await?;
+        match response.status() {
+            // The range starts at or after the end of the blob
+            StatusCode::RANGE_NOT_SATISFIABLE => Ok(Bytes::new()),
+            _ => Ok(Self::check(response).await?.bytes().await?),
+        }
+    }
+
+    async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        let mut marker = String::new();
+        loop {
+            let mut request = self.request(Method::GET, "").query(&[
+                ("restype", "container"),
+                ("comp", "list"),
+                ("prefix", prefix),
+            ]);
+            if !marker.is_empty() {
+                request = request.query(&[("marker", &marker)]);
+            }
+            let body = Self::check(request.send().await?).await?.text().await?;
+            names.extend(xml_elements(&body, "Name"));
+            marker = xml_elements(&body, "NextMarker").pop().unwrap_or_default();
+            if marker.is_empty() {
+                return Ok(names);
+            }
+        }
+    }
+
+    async fn resume_upload(&self, key: &str) -> Result<(String, BTreeMap<u32, PartTag>)> {
+        // Uncommitted blocks are kept by the blob until they are committed or expire, so there is
+        // no upload to start
+        let response = self
+            .request(Method::GET, key)
+            .query(&[("comp", "blocklist"), ("blocklisttype", "uncommitted")])
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok((String::new(), BTreeMap::new()));
+        }
+        let body = Self::check(response).await?.text().await?;
+        let parts = xml_elements(&body, "Name")
+            .into_iter()
+            .filter_map(|block_id| Some((Self::part_number(&block_id)?, block_id)))
+            .collect();
+        Ok((String::new(), parts))
+    }
+
+    async fn upload_part(
+        &self,
+        key: &str,
+        _upload_id: &str,
+        part_number: u32,
+        content: Bytes,
+    ) -> Result<PartTag> {
+        let block_id = self.part_tag(part_number, &content);
+        let response = self
+            .request(Method::PUT, key)
+            .query(&[("comp", "block"), ("blockid", &block_id)])
+            .body(content)
+            .send()
+            .await?;
+        Self::check(response).await?;
+        Ok(block_id)
+    }
+
+    async fn complete_upload(
+        &self,
+        key: &str,
+        _upload_id: &str,
+        parts: Vec<(u32, PartTag)>,
+    ) -> Result<()> {
+        let blocks: String = parts
+            .iter()
+            .map(|(_, block_id)| format!("<Latest>{}</Latest>", block_id))
+            .collect();
+        let body = format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?><BlockList>{}</BlockList>",
+            blocks
+        );
+        let response = self
+            .request(Method::PUT, key)
+            .query(&[("comp", "blocklist")])
+            .body(body)
+            .send()
+            .await?;
+        Self::check(response).await?;
+        Ok(())
+    }
+
+    fn part_tag(&self, part_number: u32, content: &[u8]) -> PartTag {
+        // The block id identifies the content, for a resumed upload to skip the blocks which were
+        // uploaded with the same content
+        let digest = hex::encode(Sha3_256::digest(content));
+        base64::encode(format!(
+            "{:0width$x}{}",
+            part_number,
+            &digest[..Self::DIGEST_LEN],
+            width = Self::PART_NUMBER_LEN
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_ids() {
+        let client = AzureClient::new("account", "container", "?sv=1&sig=2");
+        let first = client.part_tag(1, b"content");
+        let last = client.part_tag(0x123, b"other content");
+        // All the block ids of a blob must have the same length
+        assert_eq!(first.len(), last.len());
+        assert_ne!(first, client.part_tag(1, b"changed content"));
+        assert_eq!(AzureClient::part_number(&first), Some(1));
+        assert_eq!(AzureClient::part_number(&last), Some(0x123));
+        assert_eq!(AzureClient::part_number("not base64"), None);
+    }
+}
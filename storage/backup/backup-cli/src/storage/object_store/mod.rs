@@ -0,0 +1,420 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod azure;
+pub mod s3;
+
+#[cfg(test)]
+mod tests;
+
+use super::{BackupHandle, BackupHandleRef, FileHandle, FileHandleRef};
+
+use crate::{
+    storage::{BackupStorage, ShellSafeName, TextLine},
+    utils::error_notes::ErrorNotes,
+};
+use anyhow::{bail, Result};
+use aptos_crypto::HashValue;
+use aptos_logger::prelude::*;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{
+    task::{Context, Poll},
+    Future,
+};
+use sha3::{Digest, Sha3_256};
+use std::{collections::BTreeMap, pin::Pin, sync::Arc, time::Duration};
+use structopt::StructOpt;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, DuplexStream},
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::io::StreamReader;
+
+/// The tag a store gives to a part of a multipart upload
+pub type PartTag = String;
+
+/// The operations on objects of a bucket that `ObjectStore` is built upon.
+#[async_trait]
+pub trait ObjectClient: Send + Sync {
+    /// Creates or overwrites an object.
+    async fn put_object(&self, key: &str, content: Bytes) -> Result<()>;
+    /// Reads a whole object, `None` if it doesn't exist.
+    async fn get_object(&self, key: &str) -> Result<Option<Bytes>>;
+    /// Reads at most `length` bytes of an existing object from `offset`, fewer at its end.
+    async fn get_object_range(&self, key: &str, offset: u64, length: u64) -> Result<Bytes>;
+    /// Lists the keys of the objects starting with the prefix.
+    async fn list_objects(&self, prefix: &str) -> Result<Vec<String>>;
+    /// Returns the id of an unfinished multipart upload of the object and the parts uploaded so
+    /// far, keyed by part number, or starts a new upload when there is none.
+    async fn resume_upload(&self, key: &str) -> Result<(String, BTreeMap<u32, PartTag>)>;
+    /// Uploads a part of a multipart upload, numbered from 1.
+    async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: u32,
+        content: Bytes,
+    ) -> Result<PartTag>;
+    /// Assembles the object from the parts of the upload, in order.
+    async fn complete_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: Vec<(u32, PartTag)>,
+    ) -> Result<()>;
+    /// The tag the store gives to a part with this content, so that parts uploaded before an
+    /// interruption are not uploaded again when the upload is resumed.
+    fn part_tag(&self, part_number: u32, content: &[u8]) -> PartTag;
+}
+
+#[derive(StructOpt)]
+pub struct ObjectStoreOpt {
+    #[structopt(
+        long = "part-size-mb",
+        default_value = "64",
+        help = "Size of the parts of multipart uploads and ranged downloads, in MiB. \
+        Files smaller than a part are uploaded in a single request."
+    )]
+    pub part_size_mb: usize,
+    #[structopt(
+        long,
+        default_value = "5",
+        help = "Number of times a request to the object store is retried before failing."
+    )]
+    pub max_retries: usize,
+}
+
+/// A storage backend that stores everything in a bucket of an object store, under a prefix.
+///
+/// Files are uploaded in parts; an upload interrupted by a failure is resumed by the next attempt
+/// to write the same file, which only uploads the parts which changed. Files are downloaded in
+/// ranges, each retried on its own. The SHA3-256 of each file is stored next to it, under
+/// `checksums/`, for files truncated or corrupted by the store to be detected. Being in the same
+/// bucket, the checksums don't protect against a tampered bucket: that's caught when restoring or
+/// verifying the backups, by checking their signatures and proofs against a trusted waypoint.
+pub struct ObjectStore {
+    client: Arc<dyn ObjectClient>,
+    /// Prefix of all the keys, without trailing slash.
+    prefix: String,
+    part_size: usize,
+    max_retries: usize,
+}
+
+impl ObjectStore {
+    const METADATA_DIR: &'static str = "metadata";
+    const CHECKSUM_DIR: &'static str = "checksums";
+
+    pub fn new(
+        client: Arc<dyn ObjectClient>,
+        prefix: &str,
+        part_size: usize,
+        max_retries: usize,
+    ) -> Self {
+        Self {
+            client,
+            prefix: prefix.trim_matches('/').to_string(),
+            part_size,
+            max_retries,
+        }
+    }
+
+    pub fn new_with_opt(client: Arc<dyn ObjectClient>, prefix: &str, opt: ObjectStoreOpt) -> Self {
+        Self::new(client, prefix, opt.part_size_mb << 20, opt.max_retries)
+    }
+
+    fn key(&self, file_handle: &FileHandleRef) -> String {
+        if self.prefix.is_empty() {
+            file_handle.to_string()
+        } else {
+            format!("{}/{}", self.prefix, file_handle)
+        }
+    }
+
+    fn checksum_key(&self, file_handle: &FileHandleRef) -> String {
+        self.key(&format!("{}/{}", Self::CHECKSUM_DIR, file_handle))
+    }
+
+    fn uploader(&self, file_handle: &FileHandleRef) -> Uploader {
+        Uploader {
+            client: self.client.clone(),
+            key: self.key(file_handle),
+            checksum_key: self.checksum_key(file_handle),
+            part_size: self.part_size,
+            max_retries: self.max_retries,
+        }
+    }
+}
+
+#[async_trait]
+impl BackupStorage for ObjectStore {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        // There are no directories in an object store
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = format!("{}/{}", backup_handle, name.as_ref());
+        let (writer, reader) = tokio::io::duplex(1 << 20);
+        let (finish_tx, finish_rx) = oneshot::channel();
+        let uploader = self.uploader(&file_handle);
+        let upload = tokio::spawn(async move { uploader.upload(reader, finish_rx).await });
+        Ok((
+            file_handle,
+            Box::new(UploadSink {
+                writer,
+                finish_tx: Some(finish_tx),
+                upload: Some(upload),
+            }),
+        ))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let key = self.key(file_handle);
+        let (client, part_size, max_retries) =
+            (self.client.clone(), self.part_size as u64, self.max_retries);
+        // The first range is read here, for a missing file to fail the opening
+        let first = retry(max_retries, &key, || {
+            client.get_object_range(&key, 0, part_size)
+        })
+        .await
+        .err_notes(&key)?;
+
+        let (tx, rx) = mpsc::channel(2);
+        tokio::spawn(async move {
+            let mut offset = first.len() as u64;
+            let mut range = Ok(first);
+            loop {
+                let last = match &range {
+                    Ok(bytes) => (bytes.len() as u64) < part_size,
+                    Err(_) => true,
+                };
+                let range_to_send = range
+                    .map_err(|e: anyhow::Error| std::io::Error::new(std::io::ErrorKind::Other, e));
+                if tx.send(range_to_send).await.is_err() || last {
+                    break;
+                }
+                range = retry(max_retries, &key, || {
+                    client.get_object_range(&key, offset, part_size)
+                })
+                .await;
+                if let Ok(bytes) = &range {
+                    offset += bytes.len() as u64;
+                }
+            }
+        });
+        Ok(Box::new(StreamReader::new(ReceiverStream::new(rx))))
+    }
+
+    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+        let file_handle = format!("{}/{}", Self::METADATA_DIR, name.as_ref());
+        self.uploader(&file_handle)
+            .put(Bytes::copy_from_slice(content.as_ref().as_bytes()))
+            .await
+            .err_notes(&file_handle)
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let dir = self.key(&format!("{}/", Self::METADATA_DIR));
+        let keys = retry(self.max_retries, &dir, || self.client.list_objects(&dir)).await?;
+        let key_prefix_len = self.key("").len();
+        Ok(keys
+            .into_iter()
+            .map(|key| key[key_prefix_len..].to_string())
+            .collect())
+    }
+
+    async fn file_checksum(&self, file_handle: &FileHandleRef) -> Result<Option<HashValue>> {
+        let key = self.checksum_key(file_handle);
+        match retry(self.max_retries, &key, || self.client.get_object(&key)).await? {
+            Some(checksum) => Ok(Some(HashValue::from_hex(std::str::from_utf8(&checksum)?)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Uploads a file and its checksum.
+struct Uploader {
+    client: Arc<dyn ObjectClient>,
+    key: String,
+    checksum_key: String,
+    part_size: usize,
+    max_retries: usize,
+}
+
+impl Uploader {
+    /// Uploads what is written to the sink, in parts if it is larger than a part, once the sink is
+    /// shut down. When the sink is dropped without being shut down, the multipart upload is left
+    /// unfinished to be resumed.
+    async fn upload(
+        self,
+        mut reader: DuplexStream,
+        mut finish_rx: oneshot::Receiver<()>,
+    ) -> Result<()> {
+        let mut hasher = Sha3_256::new();
+        let mut part = read_part(&mut reader, self.part_size).await?;
+        hasher.update(&part);
+        if part.len() < self.part_size {
+            ensure_finished(&mut finish_rx, &self.key)?;
+            retry(self.max_retries, &self.key, || {
+                self.client.put_object(&self.key, part.clone())
+            })
+            .await?;
+        } else {
+            let (upload_id, uploaded) = retry(self.max_retries, &self.key, || {
+                self.client.resume_upload(&self.key)
+            })
+            .await?;
+            let mut parts = Vec::new();
+            let mut part_number = 1;
+            loop {
+                let tag = self.client.part_tag(part_number, &part);
+                let tag = if uploaded.get(&part_number) == Some(&tag) {
+                    debug!(key = %self.key, part_number = part_number, "Part already uploaded.");
+                    tag
+                } else {
+                    retry(self.max_retries, &self.key, || {
+                        self.client
+                            .upload_part(&self.key, &upload_id, part_number, part.clone())
+                    })
+                    .await?
+                };
+                parts.push((part_number, tag));
+
+                if part.len() < self.part_size {
+                    break;
+                }
+                part = read_part(&mut reader, self.part_size).await?;
+                if part.is_empty() {
+                    break;
+                }
+                hasher.update(&part);
+                part_number += 1;
+            }
+            ensure_finished(&mut finish_rx, &self.key)?;
+            retry(self.max_retries, &self.key, || {
+                self.client
+                    .complete_upload(&self.key, &upload_id, parts.clone())
+            })
+            .await?;
+        }
+
+        let checksum = HashValue::new(hasher.finalize().into()).to_hex();
+        retry(self.max_retries, &self.checksum_key, || {
+            self.client
+                .put_object(&self.checksum_key, Bytes::from(checksum.clone()))
+        })
+        .await
+    }
+
+    /// Uploads a small file at once.
+    async fn put(self, content: Bytes) -> Result<()> {
+        let checksum = HashValue::new(Sha3_256::digest(&content).into()).to_hex();
+        retry(self.max_retries, &self.key, || {
+            self.client.put_object(&self.key, content.clone())
+        })
+        .await?;
+        retry(self.max_retries, &self.checksum_key, || {
+            self.client
+                .put_object(&self.checksum_key, Bytes::from(checksum.clone()))
+        })
+        .await
+    }
+}
+
+/// Reads up to a part, which is only shorter than `part_size` at the end of the reader.
+async fn read_part(reader: &mut DuplexStream, part_size: usize) -> Result<Bytes> {
+    let mut part = Vec::with_capacity(part_size);
+    reader.take(part_size as u64).read_to_end(&mut part).await?;
+    Ok(part.into())
+}
+
+fn ensure_finished(finish_rx: &mut oneshot::Receiver<()>, key: &str) -> Result<()> {
+    if finish_rx.try_recv().is_err() {
+        bail!(
+            "Writing {} was interrupted, leaving its upload to be resumed.",
+            key
+        );
+    }
+    Ok(())
+}
+
+/// Runs a request until it succeeds or fails `max_retries + 1` times, backing off linearly.
+async fn retry<T, F, Fut>(max_retries: usize, key: &str, mut request: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match request().await {
+            Ok(res) => return Ok(res),
+            Err(e) if attempt < max_retries => {
+                attempt += 1;
+                warn!(
+                    key = %key,
+                    attempt = attempt,
+                    error = %e,
+                    "Object store request failed, retrying."
+                );
+                tokio::time::sleep(Duration::from_secs(attempt as u64)).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// The writer of a file, which is uploaded by a task reading the other end of `writer`. Shutting
+/// down the sink waits for the upload to complete.
+struct UploadSink {
+    writer: DuplexStream,
+    finish_tx: Option<oneshot::Sender<()>>,
+    upload: Option<JoinHandle<Result<()>>>,
+}
+
+impl AsyncWrite for UploadSink {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, tokio::io::Error>> {
+        Pin::new(&mut self.writer).poll_write(cx, buf)
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), tokio::io::Error>> {
+        Pin::new(&mut self.writer).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), tokio::io::Error>> {
+        if let Some(finish_tx) = self.finish_tx.take() {
+            // The uploader checks this once it reads the end of the file
+            let _ = finish_tx.send(());
+        }
+        futures::ready!(Pin::new(&mut self.writer).poll_shutdown(cx))?;
+        let res = match self.upload.as_mut() {
+            Some(upload) => futures::ready!(Pin::new(upload).poll(cx)),
+            None => return Poll::Ready(Ok(())),
+        };
+        self.upload = None;
+        Poll::Ready(match res {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(tokio::io::Error::new(tokio::io::ErrorKind::Other, e)),
+            Err(e) => Err(tokio::io::Error::new(tokio::io::ErrorKind::Other, e)),
+        })
+    }
+}
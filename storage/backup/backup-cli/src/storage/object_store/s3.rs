@@ -0,0 +1,202 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::object_store::{ObjectClient, ObjectStore, ObjectStoreOpt, PartTag};
+use anyhow::Result;
+use aptos_s3_client::{Client, Error};
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::{collections::BTreeMap, sync::Arc};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct S3Opt {
+    #[structopt(long, help = "Bucket holding the backups.")]
+    pub bucket: String,
+    #[structopt(long, default_value = "", help = "Prefix of the keys of the backups.")]
+    pub prefix: String,
+    #[structopt(long, env = "AWS_REGION", help = "Region of the bucket.")]
+    pub region: String,
+    #[structopt(
+        long,
+        help = "[Defaults to the AWS endpoint of the region] Endpoint of a service compatible \
+        with S3."
+    )]
+    pub endpoint: Option<String>,
+    #[structopt(long, env = "AWS_ACCESS_KEY_ID", hide_env_values = true)]
+    pub access_key_id: String,
+    #[structopt(long, env = "AWS_SECRET_ACCESS_KEY", hide_env_values = true)]
+    pub secret_access_key: String,
+    #[structopt(flatten)]
+    pub object_store: ObjectStoreOpt,
+}
+
+impl S3Opt {
+    pub fn into_storage(self) -> ObjectStore {
+        let client = Client::new(
+            self.bucket,
+            self.region,
+            self.endpoint,
+            self.access_key_id,
+            self.secret_access_key,
+        );
+        ObjectStore::new_with_opt(
+            Arc::new(S3Client::new(client)),
+            &self.prefix,
+            self.object_store,
+        )
+    }
+}
+
+/// Google Cloud Storage, through its XML API which is compatible with S3, authenticated by HMAC
+/// keys.
+#[derive(StructOpt)]
+pub struct GcsOpt {
+    #[structopt(long, help = "Bucket holding the backups.")]
+    pub bucket: String,
+    #[structopt(long, default_value = "", help = "Prefix of the keys of the backups.")]
+    pub prefix: String,
+    #[structopt(long, env = "GCS_HMAC_ACCESS_ID", hide_env_values = true)]
+    pub hmac_access_id: String,
+    #[structopt(long, env = "GCS_HMAC_SECRET", hide_env_values = true)]
+    pub hmac_secret: String,
+    #[structopt(flatten)]
+    pub object_store: ObjectStoreOpt,
+}
+
+impl GcsOpt {
+    const ENDPOINT: &'static str = "https://storage.googleapis.com";
+    const REGION: &'static str = "auto";
+
+    pub fn into_storage(self) -> ObjectStore {
+        let client = Client::new(
+            self.bucket,
+            Self::REGION.to_string(),
+            Some(Self::ENDPOINT.to_string()),
+            self.hmac_access_id,
+            self.hmac_secret,
+        );
+        ObjectStore::new_with_opt(
+            Arc::new(S3Client::new(client)),
+            &self.prefix,
+            self.object_store,
+        )
+    }
+}
+
+/// Runs the requests of the blocking S3 client on the blocking threads of the runtime.
+pub struct S3Client {
+    client: Arc<Client>,
+}
+
+impl S3Client {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client: Arc::new(client),
+        }
+    }
+
+    async fn call<T, F>(&self, request: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Client) -> Result<T, Error> + Send + 'static,
+    {
+        let client = self.client.clone();
+        Ok(tokio::task::spawn_blocking(move || request(&client)).await??)
+    }
+}
+
+/// ETags are quoted in responses, but not always in listings.
+fn unquote(etag: &str) -> String {
+    etag.trim_matches('"').to_string()
+}
+
+#[async_trait]
+impl ObjectClient for S3Client {
+    async fn put_object(&self, key: &str, content: Bytes) -> Result<()> {
+        let key = key.to_string();
+        self.call(move |client| client.put_object(&key, &content))
+            .await
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Option<Bytes>> {
+        let key = key.to_string();
+        self.call(move |client| match client.get_object(&key) {
+            Ok(content) => Ok(Some(content.into())),
+            Err(Error::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        })
+        .await
+    }
+
+    async fn get_object_range(&self, key: &str, offset: u64, length: u64) -> Result<Bytes> {
+        let key = key.to_string();
+        self.call(move |client| {
+            client
+                .get_object_range(&key, offset, length)
+                .map(Bytes::from)
+        })
+        .await
+    }
+
+    async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let prefix = prefix.to_string();
+        self.call(move |client| client.list_objects(&prefix)).await
+    }
+
+    async fn resume_upload(&self, key: &str) -> Result<(String, BTreeMap<u32, PartTag>)> {
+        let key = key.to_string();
+        self.call(move |client| {
+            // The latest upload is the one of the last attempt
+            match client.list_multipart_uploads(&key)?.pop() {
+                Some(upload_id) => {
+                    let parts = client
+                        .list_parts(&key, &upload_id)?
+                        .into_iter()
+                        .map(|(number, etag)| (number, unquote(&etag)))
+                        .collect();
+                    Ok((upload_id, parts))
+                }
+                None => Ok((client.create_multipart_upload(&key)?, BTreeMap::new())),
+            }
+        })
+        .await
+    }
+
+    async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: u32,
+        content: Bytes,
+    ) -> Result<PartTag> {
+        let (key, upload_id) = (key.to_string(), upload_id.to_string());
+        self.call(move |client| {
+            client
+                .upload_part(&key, &upload_id, part_number, &content)
+                .map(|etag| unquote(&etag))
+        })
+        .await
+    }
+
+    async fn complete_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: Vec<(u32, PartTag)>,
+    ) -> Result<()> {
+        let (key, upload_id) = (key.to_string(), upload_id.to_string());
+        let parts: Vec<_> = parts
+            .into_iter()
+            .map(|(number, etag)| (number, format!("\"{}\"", etag)))
+            .collect();
+        self.call(move |client| client.complete_multipart_upload(&key, &upload_id, &parts))
+            .await
+    }
+
+    fn part_tag(&self, _part_number: u32, content: &[u8]) -> PartTag {
+        // The ETag of a part is its MD5, unless the bucket encrypts it with a KMS key, in which
+        // case parts are uploaded again
+        format!("{:x}", md5::compute(content))
+    }
+}
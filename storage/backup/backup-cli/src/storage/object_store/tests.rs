@@ -0,0 +1,212 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::test_util::{
+    arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
+    test_write_and_read_impl,
+};
+use aptos_infallible::Mutex;
+use proptest::prelude::*;
+use std::convert::TryInto;
+use tokio::{io::AsyncWriteExt, runtime::Runtime};
+
+/// An object store in memory, which counts the parts uploaded.
+#[derive(Default)]
+struct MemoryClient {
+    objects: Mutex<BTreeMap<String, Bytes>>,
+    uploads: Mutex<BTreeMap<String, BTreeMap<u32, Bytes>>>,
+    parts_uploaded: Mutex<usize>,
+}
+
+#[async_trait]
+impl ObjectClient for MemoryClient {
+    async fn put_object(&self, key: &str, content: Bytes) -> Result<()> {
+        self.objects.lock().insert(key.to_string(), content);
+        Ok(())
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Option<Bytes>> {
+        Ok(self.objects.lock().get(key).cloned())
+    }
+
+    async fn get_object_range(&self, key: &str, offset: u64, length: u64) -> Result<Bytes> {
+        let object = self
+            .objects
+            .lock()
+            .get(key)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("{} not found", key))?;
+        let start = object.len().min(offset as usize);
+        let end = object.len().min((offset + length) as usize);
+        Ok(object.slice(start..end))
+    }
+
+    async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        Ok(self
+            .objects
+            .lock()
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+
+    async fn resume_upload(&self, key: &str) -> Result<(String, BTreeMap<u32, PartTag>)> {
+        let mut uploads = self.uploads.lock();
+        let parts = uploads.entry(key.to_string()).or_default();
+        let tags = parts
+            .iter()
+            .map(|(number, content)| (*number, self.part_tag(*number, content)))
+            .collect();
+        Ok((key.to_string(), tags))
+    }
+
+    async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: u32,
+        content: Bytes,
+    ) -> Result<PartTag> {
+        *self.parts_uploaded.lock() += 1;
+        let tag = self.part_tag(part_number, &content);
+        self.uploads
+            .lock()
+            .get_mut(upload_id)
+            .ok_or_else(|| anyhow::anyhow!("No upload of {}", key))?
+            .insert(part_number, content);
+        Ok(tag)
+    }
+
+    async fn complete_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: Vec<(u32, PartTag)>,
+    ) -> Result<()> {
+        let uploaded = self
+            .uploads
+            .lock()
+            .remove(upload_id)
+            .ok_or_else(|| anyhow::anyhow!("No upload of {}", key))?;
+        let mut object = Vec::new();
+        for (number, tag) in parts {
+            let part = &uploaded[&number];
+            assert_eq!(tag, self.part_tag(number, part));
+            object.extend_from_slice(part);
+        }
+        self.objects.lock().insert(key.to_string(), object.into());
+        Ok(())
+    }
+
+    fn part_tag(&self, part_number: u32, content: &[u8]) -> PartTag {
+        format!("{}-{}", part_number, HashValue::sha3_256_of(content))
+    }
+}
+
+fn new_store(client: Arc<MemoryClient>) -> ObjectStore {
+    // Tiny parts, for the files of the tests to be uploaded in several parts
+    ObjectStore::new(client, "/backups/", 100, 0)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups()
+    ) {
+        let store = new_store(Arc::new(MemoryClient::default()));
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_write_and_read_impl(Box::new(store), backups));
+    }
+
+    #[test]
+    fn test_save_list_metadata_files(
+        input in arb_metadata_files(),
+    ) {
+        let store = new_store(Arc::new(MemoryClient::default()));
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_save_and_list_metadata_files_impl(Box::new(store), input));
+    }
+}
+
+#[tokio::test]
+async fn test_checksums() {
+    let client = Arc::new(MemoryClient::default());
+    let store = new_store(client.clone());
+    let content = vec![7u8; 250];
+
+    let backup = store
+        .create_backup(&"backup".try_into().unwrap())
+        .await
+        .unwrap();
+    let (handle, mut file) = store
+        .create_for_write(&backup, &"file".try_into().unwrap())
+        .await
+        .unwrap();
+    file.write_all(&content).await.unwrap();
+    file.shutdown().await.unwrap();
+
+    assert_eq!(handle, "backup/file");
+    assert!(client.objects.lock().contains_key("backups/backup/file"));
+    assert_eq!(
+        store.file_checksum(&handle).await.unwrap(),
+        Some(HashValue::sha3_256_of(&content))
+    );
+    assert_eq!(store.file_checksum("backup/missing").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_resume_interrupted_upload() {
+    let client = Arc::new(MemoryClient::default());
+    let store = new_store(client.clone());
+    let backup = store
+        .create_backup(&"backup".try_into().unwrap())
+        .await
+        .unwrap();
+    let content: Vec<u8> = (0..=255).collect();
+
+    // The writer is dropped without being shut down, leaving the upload unfinished
+    let (handle, mut file) = store
+        .create_for_write(&backup, &"file".try_into().unwrap())
+        .await
+        .unwrap();
+    file.write_all(&content).await.unwrap();
+    drop(file);
+    while client
+        .uploads
+        .lock()
+        .get("backups/backup/file")
+        .map(BTreeMap::len)
+        != Some(3)
+    {
+        tokio::task::yield_now().await;
+    }
+    assert!(store.open_for_read(&handle).await.is_err());
+    assert_eq!(*client.parts_uploaded.lock(), 3);
+
+    // Writing the same content again only uploads the parts which changed
+    let mut changed = content.clone();
+    changed[250] = 0;
+    let (_, mut file) = store
+        .create_for_write(&backup, &"file".try_into().unwrap())
+        .await
+        .unwrap();
+    file.write_all(&changed).await.unwrap();
+    file.shutdown().await.unwrap();
+    assert_eq!(*client.parts_uploaded.lock(), 4);
+
+    let mut read = Vec::new();
+    store
+        .open_for_read(&handle)
+        .await
+        .unwrap()
+        .read_to_end(&mut read)
+        .await
+        .unwrap();
+    assert_eq!(read, changed);
+}
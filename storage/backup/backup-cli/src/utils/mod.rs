@@ -173,6 +173,7 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
                 NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
                 opt.rocksdb_opt.into(),
                 opt.account_count_migration,
+                true, /* state_value_index */
             )?)
             .get_restore_handler();
             RestoreRunMode::Restore { restore_handler }
@@ -0,0 +1,204 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation and verification of waypoints: deriving them from the epoch ending ledger infos
+//! of a trusted DB or backup, verifying them against the epoch change proofs served by a node,
+//! and publishing them in signed JSON documents which clients can fetch from untrusted places.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    backup_types::epoch_ending::manifest::EpochEndingBackup,
+    metadata::view::MetadataView,
+    storage::BackupStorage,
+    utils::{
+        backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
+        storage_ext::BackupStorageExt,
+    },
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    PrivateKey, Signature, SigningKey,
+};
+use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
+use aptos_types::{
+    chain_id::ChainId, epoch_change::EpochChangeProof, ledger_info::LedgerInfoWithSignatures,
+    waypoint::Waypoint,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use storage_interface::DbReader;
+
+/// Derives the waypoint of the ledger info ending the epoch from a trusted DB, or of the latest
+/// epoch ending ledger info if no epoch is given.
+pub fn waypoint_from_db(db: &dyn DbReader, epoch: Option<u64>) -> Result<Waypoint> {
+    let latest_li = db.get_latest_ledger_info()?;
+    let latest_li = latest_li.ledger_info();
+    let epoch = match epoch {
+        Some(epoch) => epoch,
+        None if latest_li.ends_epoch() => return Waypoint::new_epoch_boundary(latest_li),
+        None => latest_li
+            .epoch()
+            .checked_sub(1)
+            .ok_or_else(|| anyhow!("No epoch has ended yet."))?,
+    };
+    ensure!(
+        epoch < latest_li.next_block_epoch(),
+        "Epoch {} hasn't ended yet, latest epoch: {}.",
+        epoch,
+        latest_li.epoch(),
+    );
+
+    let proof = db.get_epoch_ending_ledger_infos(epoch, epoch + 1)?;
+    let li = proof
+        .ledger_info_with_sigs
+        .first()
+        .ok_or_else(|| anyhow!("Epoch ending ledger info of epoch {} not found.", epoch))?;
+    Waypoint::new_epoch_boundary(li.ledger_info())
+}
+
+/// Derives the waypoint of the ledger info ending the epoch from a trusted backup, or of the
+/// latest epoch ending ledger info in the backup if no epoch is given. The waypoint is derived
+/// from the ledger info itself and checked against the one in the manifest.
+pub async fn waypoint_from_backup(
+    storage: Arc<dyn BackupStorage>,
+    metadata_view: &MetadataView,
+    epoch: Option<u64>,
+) -> Result<Waypoint> {
+    let backups = metadata_view.epoch_ending_backups();
+    let epoch = match epoch {
+        Some(epoch) => epoch,
+        None => backups
+            .iter()
+            .map(|backup| backup.last_epoch)
+            .max()
+            .ok_or_else(|| anyhow!("No epoch ending backup found."))?,
+    };
+    let backup = backups
+        .iter()
+        .find(|backup| backup.first_epoch <= epoch && epoch <= backup.last_epoch)
+        .ok_or_else(|| anyhow!("No epoch ending backup covers epoch {}.", epoch))?;
+
+    let manifest: EpochEndingBackup = storage.load_json_file(&backup.manifest).await?;
+    manifest.verify()?;
+    let chunk = manifest
+        .chunks
+        .iter()
+        .find(|chunk| chunk.first_epoch <= epoch && epoch <= chunk.last_epoch)
+        .ok_or_else(|| anyhow!("No chunk covers epoch {} in manifest.", epoch))?;
+
+    let mut file = storage.open_for_read(&chunk.ledger_infos).await?;
+    let mut chunk_epoch = chunk.first_epoch;
+    while let Some(record_bytes) = file.read_record_bytes().await? {
+        if chunk_epoch == epoch {
+            let li: LedgerInfoWithSignatures = bcs::from_bytes(&record_bytes)?;
+            ensure!(
+                li.ledger_info().epoch() == epoch,
+                "LedgerInfo epoch not expected. Expected: {}, actual: {}.",
+                epoch,
+                li.ledger_info().epoch(),
+            );
+            let waypoint = Waypoint::new_epoch_boundary(li.ledger_info())?;
+            let wp_manifest = manifest.waypoints[(epoch - manifest.first_epoch) as usize];
+            ensure!(
+                waypoint == wp_manifest,
+                "Waypoints don't match. In manifest: {}, In chunk: {}",
+                wp_manifest,
+                waypoint,
+            );
+            return Ok(waypoint);
+        }
+        chunk_epoch += 1;
+    }
+    bail!("Epoch {} not found in chunk {}.", epoch, chunk.ledger_infos)
+}
+
+/// Verifies a waypoint against the epoch change proof served by the backup service of a node,
+/// starting from a trusted waypoint, e.g. the genesis waypoint. Every epoch change between the
+/// two waypoints must be signed by the validators of the previous epoch.
+pub async fn verify_waypoint(
+    client: &BackupServiceClient,
+    trusted_waypoint: Waypoint,
+    waypoint: Waypoint,
+) -> Result<()> {
+    ensure!(
+        trusted_waypoint.version() <= waypoint.version(),
+        "The waypoint (version {}) is behind the trusted waypoint (version {}).",
+        waypoint.version(),
+        trusted_waypoint.version(),
+    );
+    let db_state = client
+        .get_db_state()
+        .await?
+        .ok_or_else(|| anyhow!("The node DB is empty."))?;
+
+    let mut file = client
+        .get_epoch_ending_ledger_infos(0, db_state.epoch)
+        .await?;
+    let mut ledger_infos = Vec::new();
+    while let Some(record_bytes) = file.read_record_bytes().await? {
+        let li: LedgerInfoWithSignatures = bcs::from_bytes(&record_bytes)?;
+        let version = li.ledger_info().version();
+        if version > waypoint.version() {
+            break;
+        }
+        if version >= trusted_waypoint.version() {
+            ledger_infos.push(li);
+        }
+    }
+    ensure!(
+        ledger_infos.first().map(|li| li.ledger_info().version())
+            == Some(trusted_waypoint.version()),
+        "No epoch ending ledger info at the trusted waypoint version {}.",
+        trusted_waypoint.version(),
+    );
+
+    let proof = EpochChangeProof::new(ledger_infos, false /* more */);
+    let li = proof.verify(&trusted_waypoint)?;
+    let verified_waypoint = Waypoint::new_epoch_boundary(li.ledger_info())?;
+    ensure!(
+        verified_waypoint == waypoint,
+        "Waypoints don't match. Verified: {}, given: {}",
+        verified_waypoint,
+        waypoint,
+    );
+    Ok(())
+}
+
+/// The waypoints published by an operator
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, CryptoHasher, BCSCryptoHash)]
+pub struct WaypointDocument {
+    pub chain_id: ChainId,
+    pub waypoints: Vec<Waypoint>,
+    pub timestamp_secs: u64,
+}
+
+/// A `WaypointDocument` with the signature of its publisher. Clients check the signature against
+/// the public key of a publisher they trust, not against the key in the document.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SignedWaypointDocument {
+    pub document: WaypointDocument,
+    pub public_key: Ed25519PublicKey,
+    pub signature: Ed25519Signature,
+}
+
+impl SignedWaypointDocument {
+    pub fn sign(document: WaypointDocument, private_key: &Ed25519PrivateKey) -> Self {
+        Self {
+            signature: private_key.sign(&document),
+            public_key: private_key.public_key(),
+            document,
+        }
+    }
+
+    pub fn verify(&self, publisher_key: &Ed25519PublicKey) -> Result<&WaypointDocument> {
+        ensure!(
+            &self.public_key == publisher_key,
+            "The document isn't signed by the publisher."
+        );
+        self.signature.verify(&self.document, publisher_key)?;
+        Ok(&self.document)
+    }
+}
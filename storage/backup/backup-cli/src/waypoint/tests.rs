@@ -0,0 +1,139 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+    metadata::cache::{sync_and_load, MetadataCacheOpt},
+    storage::{local_fs::LocalFs, BackupStorage},
+    utils::{
+        backup_service_client::BackupServiceClient,
+        test_utils::{start_local_backup_service, tmp_db_with_random_content},
+        GlobalBackupOpt,
+    },
+    waypoint::{
+        verify_waypoint, waypoint_from_backup, waypoint_from_db, SignedWaypointDocument,
+        WaypointDocument,
+    },
+};
+use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, Uniform};
+use aptos_temppath::TempPath;
+use aptos_types::{chain_id::ChainId, waypoint::Waypoint};
+use std::{convert::TryFrom, sync::Arc};
+use structopt::StructOpt;
+use tokio::time::Duration;
+
+fn waypoint(version: u64, value: HashValue) -> Waypoint {
+    format!("{}:{}", version, value.to_hex()).parse().unwrap()
+}
+
+#[test]
+fn test_derive_and_verify_waypoints() {
+    let (_db_dir, db, blocks) = tmp_db_with_random_content();
+    let epoch_ending_waypoints = blocks
+        .iter()
+        .map(|(_, li)| li.ledger_info())
+        .filter(|li| li.ends_epoch())
+        .map(|li| Waypoint::new_epoch_boundary(li).unwrap())
+        .collect::<Vec<_>>();
+    let latest_epoch = blocks.last().unwrap().1.ledger_info().next_block_epoch();
+
+    assert_eq!(
+        waypoint_from_db(db.as_ref(), None).ok(),
+        epoch_ending_waypoints.last().cloned()
+    );
+    assert_eq!(
+        waypoint_from_db(db.as_ref(), Some(0)).ok(),
+        epoch_ending_waypoints.first().cloned()
+    );
+    assert!(waypoint_from_db(db.as_ref(), Some(latest_epoch)).is_err());
+
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+    let (rt, port) = start_local_backup_service(db);
+    let client = BackupServiceClient::new(format!("http://localhost:{}", port));
+    if let (Some(genesis_waypoint), Some(latest_waypoint)) = (
+        epoch_ending_waypoints.first().cloned(),
+        epoch_ending_waypoints.last().cloned(),
+    ) {
+        rt.block_on(
+            EpochEndingBackupController::new(
+                EpochEndingBackupOpt {
+                    start_epoch: 0,
+                    end_epoch: latest_epoch,
+                },
+                GlobalBackupOpt {
+                    max_chunk_size: 1024,
+                },
+                Arc::new(BackupServiceClient::new(format!(
+                    "http://localhost:{}",
+                    port
+                ))),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+        let metadata_view = rt
+            .block_on(sync_and_load(
+                &MetadataCacheOpt::from_iter(vec!["exe"]),
+                Arc::clone(&store),
+                1,
+            ))
+            .unwrap();
+        for (epoch, waypoint) in epoch_ending_waypoints.iter().enumerate() {
+            assert_eq!(
+                rt.block_on(waypoint_from_backup(
+                    Arc::clone(&store),
+                    &metadata_view,
+                    Some(epoch as u64)
+                ))
+                .unwrap(),
+                *waypoint
+            );
+        }
+        assert_eq!(
+            rt.block_on(waypoint_from_backup(
+                Arc::clone(&store),
+                &metadata_view,
+                None
+            ))
+            .unwrap(),
+            latest_waypoint
+        );
+
+        rt.block_on(verify_waypoint(&client, genesis_waypoint, latest_waypoint))
+            .unwrap();
+        let wrong_waypoint = waypoint(latest_waypoint.version(), HashValue::zero());
+        assert!(rt
+            .block_on(verify_waypoint(&client, genesis_waypoint, wrong_waypoint))
+            .is_err());
+        if genesis_waypoint != latest_waypoint {
+            assert!(rt
+                .block_on(verify_waypoint(&client, latest_waypoint, genesis_waypoint))
+                .is_err());
+        }
+    }
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
+
+#[test]
+fn test_signed_waypoint_document() {
+    let private_key = Ed25519PrivateKey::generate_for_testing();
+    let document = WaypointDocument {
+        chain_id: ChainId::test(),
+        waypoints: vec![waypoint(0, HashValue::random())],
+        timestamp_secs: 1,
+    };
+    let signed = SignedWaypointDocument::sign(document.clone(), &private_key);
+    let signed: SignedWaypointDocument =
+        serde_json::from_str(&serde_json::to_string(&signed).unwrap()).unwrap();
+    assert_eq!(signed.verify(&private_key.public_key()).unwrap(), &document);
+
+    let other_key = Ed25519PrivateKey::try_from(&[1u8; 32][..]).unwrap();
+    assert!(signed.verify(&other_key.public_key()).is_err());
+    let mut tampered = signed;
+    tampered.document.timestamp_secs = 2;
+    assert!(tampered.verify(&private_key.public_key()).is_err());
+}
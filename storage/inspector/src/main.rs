@@ -39,6 +39,14 @@ enum Command {
     },
     #[structopt(name = "list-accounts")]
     ListAccounts,
+    /// Backfills the index of the events by the creation number of their stream, for DBs
+    /// written by versions of the node which didn't maintain it. Opens the DB for writing, the
+    /// node must be stopped.
+    #[structopt(name = "migrate-event-index")]
+    MigrateEventIndex {
+        #[structopt(long, default_value = "10000")]
+        batch_size: usize,
+    },
 }
 
 /// Print out latest information stored in the DB.
@@ -167,12 +175,14 @@ fn main() {
     let log_dir = tempfile::tempdir().expect("Unable to get temp dir");
     info!("Opening DB at: {:?}, log at {:?}", p, log_dir.path());
 
+    let readonly = !matches!(opt.cmd, Some(Command::MigrateEventIndex { .. }));
     let db = AptosDB::open(
         p,
-        true,                        /* readonly */
+        readonly,
         NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
         RocksdbConfig::default(),
         true, /* account_count_migration, ignored anyway */
+        true, /* state_value_index, ignored anyway */
     )
     .expect("Unable to open AptosDB");
     info!("DB opened successfully.");
@@ -191,6 +201,12 @@ fn main() {
             Command::ListAccounts => {
                 list_accounts(&db);
             }
+            Command::MigrateEventIndex { batch_size } => {
+                let num_written = db
+                    .backfill_event_by_creation_number_index(batch_size)
+                    .expect("Unable to backfill the event index");
+                println!("Indexed {} events.", num_written);
+            }
         }
     } else {
         print_head(&db).expect("Unable to read information from DB");
@@ -598,6 +598,48 @@ fn many_keys_get_proof_and_verify_tree_root(seed: &[u8], num_keys: usize) {
     }
 }
 
+#[test]
+fn test_batch_get_with_proof() {
+    let mut rng: StdRng = StdRng::from_seed([0u8; 32]);
+    let db = MockTreeStore::default();
+    let tree = JellyfishMerkleTree::new(&db);
+    assert!(tree
+        .batch_get_with_proof(&[HashValue::random_with_rng(&mut rng)], 0)
+        .unwrap_err()
+        .downcast::<MissingRootError>()
+        .is_ok());
+
+    let kvs = (0..100)
+        .map(|_| {
+            (
+                HashValue::random_with_rng(&mut rng),
+                ValueBlob::from(HashValue::random_with_rng(&mut rng).to_vec()),
+            )
+        })
+        .collect::<Vec<_>>();
+    let (roots, batch) = tree
+        .batch_put_value_sets(vec![kvs.clone()], None, 0 /* version */)
+        .unwrap();
+    db.write_tree_update_batch(batch).unwrap();
+
+    // Existing keys, a duplicate and non-existing keys, in any order
+    let mut keys = kvs.iter().map(|(k, _)| *k).collect::<Vec<_>>();
+    keys.push(kvs[0].0);
+    keys.extend((0..10).map(|_| HashValue::random_with_rng(&mut rng)));
+    keys.reverse();
+
+    let results = tree.batch_get_with_proof(&keys, 0).unwrap();
+    assert_eq!(results.len(), keys.len());
+    for (key, (value, proof)) in keys.iter().zip(results) {
+        assert_eq!(
+            (value.clone(), proof.clone()),
+            tree.get_with_proof(*key, 0).unwrap()
+        );
+        assert!(proof.verify(roots[0], *key, value.as_ref()).is_ok());
+    }
+    assert!(tree.batch_get_with_proof(&[], 0).unwrap().is_empty());
+}
+
 #[test]
 fn test_1000_keys() {
     let seed: &[_] = &[1, 2, 3, 4];
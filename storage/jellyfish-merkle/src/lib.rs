@@ -122,6 +122,15 @@ pub trait TreeReader<V> {
     /// Gets node given a node key. Returns `None` if the node does not exist.
     fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node<V>>>;
 
+    /// Gets nodes given their node keys, in the order of the keys. Returns `None` for the nodes
+    /// that don't exist. Storages supporting batched reads should override it.
+    fn get_node_options(&self, node_keys: &[NodeKey]) -> Result<Vec<Option<Node<V>>>> {
+        node_keys
+            .iter()
+            .map(|node_key| self.get_node_option(node_key))
+            .collect()
+    }
+
     /// Gets the rightmost leaf. Note that this assumes we are in the process of restoring the tree
     /// and all nodes are at the same version.
     fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode<V>)>>;
@@ -946,6 +955,100 @@ where
         bail!("Jellyfish Merkle tree has cyclic graph inside.");
     }
 
+    /// Returns the values and proofs of a batch of keys at `version`, in the order of the keys.
+    /// The lookups walk down the tree together, so that the nodes at each depth are read with a
+    /// single batched read and the nodes shared by the lookups, like the root, are read once.
+    pub fn batch_get_with_proof(
+        &self,
+        keys: &[HashValue],
+        version: Version,
+    ) -> Result<Vec<(Option<V>, SparseMerkleProof<V>)>> {
+        let mut results: Vec<Option<(Option<V>, SparseMerkleProof<V>)>> =
+            keys.iter().map(|_| None).collect();
+        // The lookups still walking down: index of the key, next node key and siblings so far
+        let mut pending: Vec<(usize, NodeKey, Vec<HashValue>)> = (0..keys.len())
+            .map(|index| (index, NodeKey::new_empty_path(version), vec![]))
+            .collect();
+
+        // We limit the number of loops here deliberately to avoid potential cyclic graph bugs
+        // in the tree structure.
+        for nibble_depth in 0..=ROOT_NIBBLE_HEIGHT {
+            if pending.is_empty() {
+                break;
+            }
+            let node_keys = pending
+                .iter()
+                .map(|(_, node_key, _)| node_key.clone())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+            let nodes = node_keys
+                .iter()
+                .cloned()
+                .zip(self.reader.get_node_options(&node_keys)?)
+                .collect::<HashMap<_, _>>();
+
+            let mut next_pending = vec![];
+            for (index, node_key, mut siblings) in pending {
+                let key = keys[index];
+                let node = match &nodes[&node_key] {
+                    Some(node) => node,
+                    None if nibble_depth == 0 => return Err(MissingRootError { version }.into()),
+                    None => bail!("Missing node at {:?}.", node_key),
+                };
+                match node {
+                    Node::Internal(internal_node) => {
+                        let (child_node_key, mut siblings_in_internal) = internal_node
+                            .get_child_with_siblings(&node_key, key.get_nibble(nibble_depth));
+                        siblings.append(&mut siblings_in_internal);
+                        match child_node_key {
+                            Some(child_node_key) => {
+                                next_pending.push((index, child_node_key, siblings))
+                            }
+                            None => {
+                                siblings.reverse();
+                                results[index] =
+                                    Some((None, SparseMerkleProof::new(None, siblings)));
+                            }
+                        }
+                    }
+                    Node::Leaf(leaf_node) => {
+                        let value = if leaf_node.account_key() == key {
+                            Some(leaf_node.value().clone())
+                        } else {
+                            None
+                        };
+                        siblings.reverse();
+                        results[index] = Some((
+                            value,
+                            SparseMerkleProof::new(Some(leaf_node.clone().into()), siblings),
+                        ));
+                    }
+                    Node::Null => {
+                        ensure!(
+                            nibble_depth == 0,
+                            "Non-root null node exists with node key {:?}",
+                            node_key
+                        );
+                        results[index] = Some((None, SparseMerkleProof::new(None, vec![])));
+                    }
+                }
+            }
+            pending = next_pending;
+        }
+        ensure!(
+            pending.is_empty(),
+            "Jellyfish Merkle tree has cyclic graph inside."
+        );
+
+        Ok(results
+            .into_iter()
+            .map(|result| {
+                result.expect("Every lookup must have reached a leaf or an empty subtree.")
+            })
+            .collect())
+    }
+
     /// Gets the proof that shows a list of keys up to `rightmost_key_to_prove` exist at `version`.
     pub fn get_range_proof(
         &self,
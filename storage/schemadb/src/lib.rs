@@ -22,7 +22,8 @@ use crate::{
         DIEM_SCHEMADB_BATCH_COMMIT_BYTES, DIEM_SCHEMADB_BATCH_COMMIT_LATENCY_SECONDS,
         DIEM_SCHEMADB_BATCH_PUT_LATENCY_SECONDS, DIEM_SCHEMADB_DELETES, DIEM_SCHEMADB_GET_BYTES,
         DIEM_SCHEMADB_GET_LATENCY_SECONDS, DIEM_SCHEMADB_INCLUSIVE_RANGE_DELETES,
-        DIEM_SCHEMADB_ITER_BYTES, DIEM_SCHEMADB_ITER_LATENCY_SECONDS, DIEM_SCHEMADB_PUT_BYTES,
+        DIEM_SCHEMADB_ITER_BYTES, DIEM_SCHEMADB_ITER_LATENCY_SECONDS,
+        DIEM_SCHEMADB_MULTI_GET_LATENCY_SECONDS, DIEM_SCHEMADB_PUT_BYTES,
         DIEM_SCHEMADB_RANGE_DELETES,
     },
     schema::{KeyCodec, Schema, SeekKeyCodec, ValueCodec},
@@ -45,6 +46,9 @@ pub type Options = rocksdb::Options;
 /// Type alias to improve readability.
 pub type ColumnFamilyName = &'static str;
 
+/// Bits per key of the prefix bloom filters, for a false positive rate of about 1%.
+const PREFIX_BLOOM_FILTER_BITS_PER_KEY: i32 = 10;
+
 /// Name for the `default` column family that's always open by RocksDB. We use it to store
 /// [`LedgerInfo`](../types/ledger_info/struct.LedgerInfo.html).
 pub const DEFAULT_CF_NAME: ColumnFamilyName = "default";
@@ -238,6 +242,21 @@ impl DB {
         name: &'static str,
         column_families: Vec<ColumnFamilyName>,
         db_opts: &rocksdb::Options,
+    ) -> Result<Self> {
+        Self::open_with_prefix_bloom_filters(path, name, column_families, &HashMap::new(), db_opts)
+    }
+
+    /// Like `open`, with a prefix bloom filter for each column family of `prefix_lens`, whose keys
+    /// all start with a prefix of the given length. Seeking a key whose prefix isn't in such a
+    /// column family then rarely reads from its SST files. Iterating past the prefix of the key
+    /// sought is undefined in these column families, so readers must check the prefix of the keys
+    /// they get.
+    pub fn open_with_prefix_bloom_filters(
+        path: impl AsRef<Path>,
+        name: &'static str,
+        column_families: Vec<ColumnFamilyName>,
+        prefix_lens: &HashMap<ColumnFamilyName, usize>,
+        db_opts: &rocksdb::Options,
     ) -> Result<Self> {
         {
             let cfs_set: HashSet<_> = column_families.iter().collect();
@@ -249,9 +268,13 @@ impl DB {
                 cfs_set.len() == column_families.len(),
                 "Duplicate column family name found.",
             );
+            ensure!(
+                prefix_lens.keys().all(|cf_name| cfs_set.contains(cf_name)),
+                "Prefix bloom filter set for an unknown column family.",
+            );
         }
 
-        let db = DB::open_cf(db_opts, path, name, column_families)?;
+        let db = DB::open_cf(db_opts, path, name, column_families, prefix_lens)?;
         Ok(db)
     }
 
@@ -281,11 +304,19 @@ impl DB {
         DB::open_cf_as_secondary(db_opts, primary_path, secondary_path, name, column_families)
     }
 
+    /// Replays the writes of the primary made since the secondary was opened or last caught up.
+    /// Only meaningful for a DB opened with `open_as_secondary`.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.inner.try_catch_up_with_primary()?;
+        Ok(())
+    }
+
     fn open_cf(
         db_opts: &rocksdb::Options,
         path: impl AsRef<Path>,
         name: &'static str,
         column_families: Vec<ColumnFamilyName>,
+        prefix_lens: &HashMap<ColumnFamilyName, usize>,
     ) -> Result<DB> {
         let inner = rocksdb::DB::open_cf_descriptors(
             db_opts,
@@ -293,6 +324,14 @@ impl DB {
             column_families.iter().map(|cf_name| {
                 let mut cf_opts = rocksdb::Options::default();
                 cf_opts.set_compression_type(rocksdb::DBCompressionType::Lz4);
+                if let Some(prefix_len) = prefix_lens.get(cf_name) {
+                    cf_opts.set_prefix_extractor(rocksdb::SliceTransform::create_fixed_prefix(
+                        *prefix_len,
+                    ));
+                    let mut table_opts = rocksdb::BlockBasedOptions::default();
+                    table_opts.set_bloom_filter(PREFIX_BLOOM_FILTER_BITS_PER_KEY, false);
+                    cf_opts.set_block_based_table_factory(&table_opts);
+                }
                 rocksdb::ColumnFamilyDescriptor::new((*cf_name).to_string(), cf_opts)
             }),
         )?;
@@ -365,6 +404,34 @@ impl DB {
             .transpose()
     }
 
+    /// Reads a batch of records by key with a single RocksDB MultiGet, which amortizes the cost of
+    /// point lookups. The records are returned in the order of the keys.
+    pub fn multi_get<S: Schema>(&self, schema_keys: &[S::Key]) -> Result<Vec<Option<S::Value>>> {
+        let _timer = DIEM_SCHEMADB_MULTI_GET_LATENCY_SECONDS
+            .with_label_values(&[S::COLUMN_FAMILY_NAME])
+            .start_timer();
+
+        let cf_handle = self.get_cf_handle(S::COLUMN_FAMILY_NAME)?;
+        let keys = schema_keys
+            .iter()
+            .map(|key| Ok((cf_handle, <S::Key as KeyCodec<S>>::encode_key(key)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        self.inner
+            .multi_get_cf(keys)
+            .into_iter()
+            .map(|result| {
+                let result = result?;
+                DIEM_SCHEMADB_GET_BYTES
+                    .with_label_values(&[S::COLUMN_FAMILY_NAME])
+                    .observe(result.as_ref().map_or(0.0, |v| v.len() as f64));
+                result
+                    .map(|raw_value| <S::Value as ValueCodec<S>>::decode_value(&raw_value))
+                    .transpose()
+            })
+            .collect()
+    }
+
     /// Writes single record.
     pub fn put<S: Schema>(&self, key: &S::Key, value: &S::Value) -> Result<()> {
         // Not necessary to use a batch, but we'd like a central place to bump counters.
@@ -482,8 +549,7 @@ impl DB {
         })
     }
 
-    /// Flushes all memtable data. This is only used for testing `get_approximate_sizes_cf` in unit
-    /// tests.
+    /// Flushes all memtable data, so that the write-ahead log needn't be replayed on reopening.
     pub fn flush_all(&self) -> Result<()> {
         for cf_name in &self.column_families {
             let cf_handle = self.get_cf_handle(cf_name)?;
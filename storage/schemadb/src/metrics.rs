@@ -42,6 +42,18 @@ pub static DIEM_SCHEMADB_GET_LATENCY_SECONDS: Lazy<HistogramVec> = Lazy::new(||
     .unwrap()
 });
 
+pub static DIEM_SCHEMADB_MULTI_GET_LATENCY_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        // metric name
+        "aptos_schemadb_multi_get_latency_seconds",
+        // metric description
+        "Aptos schemadb multi_get latency in seconds",
+        // metric labels (dimensions)
+        &["cf_name"]
+    )
+    .unwrap()
+});
+
 pub static DIEM_SCHEMADB_GET_BYTES: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
         // metric name
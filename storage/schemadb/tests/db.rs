@@ -172,6 +172,27 @@ fn test_schema_put_get() {
     );
 }
 
+#[test]
+fn test_schema_multi_get() {
+    let db = TestDB::new();
+
+    db.put::<TestSchema1>(&TestField(0), &TestField(0)).unwrap();
+    db.put::<TestSchema1>(&TestField(2), &TestField(2)).unwrap();
+    db.put::<TestSchema2>(&TestField(1), &TestField(3)).unwrap();
+
+    assert_eq!(
+        db.multi_get::<TestSchema1>(&[TestField(2), TestField(1), TestField(0)])
+            .unwrap(),
+        vec![Some(TestField(2)), None, Some(TestField(0))],
+    );
+    assert_eq!(
+        db.multi_get::<TestSchema2>(&[TestField(0), TestField(1)])
+            .unwrap(),
+        vec![None, Some(TestField(3))],
+    );
+    assert!(db.multi_get::<TestSchema2>(&[]).unwrap().is_empty());
+}
+
 fn test_schemabatch_delete_range_util(begin: u32, end: u32, is_inclusive: bool) {
     let db = TestDB::new();
     let mut db_batch = SchemaBatch::new();
@@ -405,6 +426,23 @@ fn test_open_as_secondary() {
     );
 }
 
+#[test]
+fn test_catch_up_with_primary() {
+    let tmpdir = aptos_temppath::TempPath::new();
+    let tmpdir_sec = aptos_temppath::TempPath::new();
+
+    let db = open_db(&tmpdir);
+    let db_sec = open_db_as_secondary(&tmpdir, &tmpdir_sec);
+    db.put::<TestSchema1>(&TestField(0), &TestField(0)).unwrap();
+    assert_eq!(db_sec.get::<TestSchema1>(&TestField(0)).unwrap(), None);
+
+    db_sec.try_catch_up_with_primary().unwrap();
+    assert_eq!(
+        db_sec.get::<TestSchema1>(&TestField(0)).unwrap(),
+        Some(TestField(0)),
+    );
+}
+
 #[test]
 fn test_report_size() {
     let db = TestDB::new();
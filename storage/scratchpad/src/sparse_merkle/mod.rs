@@ -46,8 +46,12 @@
 //! dropped as long as trees depending on it still hold strong references to it via the chain of
 //! "base trees".
 //!     2. Even if a tree is not dropped, when nodes it created are persisted to DB, all of them
-//! and those created by its previous versions can be dropped, which we express by calling "prune()"
-//! on it which replaces the strong references to its root and its base tree with weak references.
+//! and those created by its previous versions can be dropped. Each version carries a generation,
+//! one above that of its base tree, and a tree keeps links only to its children, so once the
+//! oldest trees are dropped (e.g. the executor prunes the committed blocks) their nodes are freed
+//! while being shared by all the branches spawned on top of them. A frozen tree holds its oldest
+//! living ancestor and ignores nodes of generations older than it, which might be alive only
+//! because of other references, so it never relies on nodes that may be freed concurrently.
 //!     3. We can hold strong references to recently accessed nodes that have already been persisted
 //! in an LRU flavor cache for less DB reads.
 //!
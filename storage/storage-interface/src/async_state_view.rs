@@ -0,0 +1,92 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::DbReader;
+use anyhow::Result;
+use aptos_types::{
+    access_path::AccessPath, account_address::AccountAddress, account_state::AccountState,
+    transaction::Version,
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    convert::TryFrom,
+    slice,
+    sync::Arc,
+};
+
+/// `AsyncStateView` is the asynchronous variant of
+/// [`StateView`](aptos_state_view::StateView), for the readers which shouldn't block their
+/// runtime on storage, like the API. Reading many access paths at once with
+/// `multi_get_state_values` lets the storage amortize the lookups, e.g. into RocksDB MultiGets,
+/// which the executor does for the accounts a block is known to touch before running the VM.
+#[async_trait::async_trait]
+pub trait AsyncStateView: Send + Sync {
+    /// Gets the state for a single access path.
+    async fn get_state_value(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .multi_get_state_values(slice::from_ref(access_path))
+            .await?
+            .pop()
+            .flatten())
+    }
+
+    /// Gets the states for a batch of access paths, in the order of the access paths.
+    async fn multi_get_state_values(
+        &self,
+        access_paths: &[AccessPath],
+    ) -> Result<Vec<Option<Vec<u8>>>>;
+}
+
+/// `DbStateView` is an [`AsyncStateView`] of the persisted state at a version. The accounts of
+/// a batch are read at once, on the blocking threads of the runtime. The proofs aren't verified,
+/// the view trusts its reader.
+pub struct DbStateView {
+    reader: Arc<dyn DbReader>,
+    version: Version,
+}
+
+impl DbStateView {
+    pub fn new(reader: Arc<dyn DbReader>, version: Version) -> Self {
+        Self { reader, version }
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncStateView for DbStateView {
+    async fn multi_get_state_values(
+        &self,
+        access_paths: &[AccessPath],
+    ) -> Result<Vec<Option<Vec<u8>>>> {
+        let addresses = access_paths
+            .iter()
+            .map(|access_path| access_path.address)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let reader = Arc::clone(&self.reader);
+        let version = self.version;
+        let (addresses, blobs) = tokio::task::spawn_blocking(move || {
+            let blobs = reader.get_account_states_with_proof_by_version(&addresses, version);
+            (addresses, blobs)
+        })
+        .await?;
+
+        let account_states = addresses
+            .into_iter()
+            .zip(blobs?)
+            .map(|(address, (blob, _proof))| {
+                let account_state = blob.as_ref().map(AccountState::try_from).transpose()?;
+                Ok((address, account_state))
+            })
+            .collect::<Result<HashMap<AccountAddress, Option<AccountState>>>>()?;
+        Ok(access_paths
+            .iter()
+            .map(|access_path| {
+                account_states
+                    .get(&access_path.address)
+                    .and_then(Option::as_ref)
+                    .and_then(|account_state| account_state.get(&access_path.path).cloned())
+            })
+            .collect())
+    }
+}
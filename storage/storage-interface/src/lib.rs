@@ -4,7 +4,7 @@
 use anyhow::{format_err, Result};
 use aptos_crypto::{hash::SPARSE_MERKLE_PLACEHOLDER_HASH, HashValue};
 use aptos_types::{
-    access_path::AccessPath,
+    access_path::{AccessPath, StructTagPrefix},
     account_address::AccountAddress,
     account_config::aptos_root_address,
     account_state::AccountState,
@@ -21,16 +21,21 @@ use aptos_types::{
         SparseMerkleRangeProof, TransactionAccumulatorSummary,
     },
     state_proof::StateProof,
+    state_storage_usage::StateStorageUsage,
     transaction::{
         AccountTransactionsWithProof, TransactionInfo, TransactionListWithProof,
         TransactionOutputListWithProof, TransactionToCommit, TransactionWithProof, Version,
     },
 };
-use move_core_types::resolver::{ModuleResolver, ResourceResolver};
+use move_core_types::{
+    language_storage::StructTag,
+    resolver::{ModuleResolver, ResourceResolver},
+};
 use serde::{Deserialize, Serialize};
 use std::{convert::TryFrom, sync::Arc};
 use thiserror::Error;
 
+pub mod async_state_view;
 #[cfg(any(feature = "testing", feature = "fuzzing"))]
 pub mod mock;
 pub mod state_view;
@@ -269,6 +274,20 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns events of the event stream identified by the address of its creator and its
+    /// creation number, the number of streams the address created before it. Equivalent to
+    /// [`get_events`](DbReader::get_events) with the `EventKey` derived from them.
+    fn get_events_by_creation_number(
+        &self,
+        address: AccountAddress,
+        creation_number: u64,
+        start: u64,
+        order: Order,
+        limit: u64,
+    ) -> Result<Vec<(u64, ContractEvent)>> {
+        unimplemented!()
+    }
+
     /// Returns events by given event key
     fn get_events_with_proofs(
         &self,
@@ -420,6 +439,63 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Gets an account state by account address at a version, without proof. Readers which can
+    /// skip the state Merkle tree for such reads override the default, which drops the proof.
+    fn get_account_state_by_version(
+        &self,
+        address: AccountAddress,
+        version: Version,
+    ) -> Result<Option<AccountStateBlob>> {
+        Ok(self
+            .get_account_state_with_proof_by_version(address, version)?
+            .0)
+    }
+
+    /// Gets the account states of a batch of addresses at a version, in the order of the addresses,
+    /// with the proofs of `get_account_state_with_proof_by_version`. Readers supporting batched
+    /// reads amortize the lookups of the addresses, the default makes one lookup per address.
+    fn get_account_states_with_proof_by_version(
+        &self,
+        addresses: &[AccountAddress],
+        version: Version,
+    ) -> Result<
+        Vec<(
+            Option<AccountStateBlob>,
+            SparseMerkleProof<AccountStateBlob>,
+        )>,
+    > {
+        addresses
+            .iter()
+            .map(|address| self.get_account_state_with_proof_by_version(*address, version))
+            .collect()
+    }
+
+    /// Gets up to `limit` resources of an account at a version whose struct tags match the
+    /// prefix, e.g. all the resources declared in a module, in the order of their paths and
+    /// starting after the resource of the `cursor` if any, or `None` if the account doesn't exist.
+    /// The account state is read as a whole, but only the paths of its resources are decoded, and
+    /// only the values of the matching ones are copied out of it.
+    fn get_account_resources_by_prefix(
+        &self,
+        address: AccountAddress,
+        struct_tag_prefix: &StructTagPrefix,
+        version: Version,
+        cursor: Option<&StructTag>,
+        limit: usize,
+    ) -> Result<Option<Vec<(StructTag, Vec<u8>)>>> {
+        let blob = match self.get_account_state_by_version(address, version)? {
+            Some(blob) => blob,
+            None => return Ok(None),
+        };
+        Ok(Some(
+            blob.get_resources_by_prefix(struct_tag_prefix, cursor)?
+                .into_iter()
+                .take(limit)
+                .map(|(struct_tag, value)| (struct_tag, value.to_vec()))
+                .collect(),
+        ))
+    }
+
     /// Gets the latest TreeState no matter if db has been bootstrapped.
     /// Used by the Db-bootstrapper.
     fn get_latest_tree_state(&self) -> Result<TreeState> {
@@ -487,6 +563,14 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns the number of items in the state at the given version and their total size.
+    ///
+    /// The usage is tracked from the genesis on, it's unknown for the versions of a DB restored
+    /// from a state snapshot.
+    fn get_state_storage_usage(&self, version: Version) -> Result<StateStorageUsage> {
+        unimplemented!()
+    }
+
     /// Get a chunk of account data, addressed by the index of the account.
     fn get_account_chunk_with_proof(
         &self,
@@ -497,10 +581,29 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns an iterator over the accounts of the state at the given version, in the order of
+    /// their keys, from `start_key` on. Range proofs are attached periodically, so that the
+    /// accounts can be verified against the state root hash in chunks as they're streamed, e.g.
+    /// to serve a state snapshot or to take a census of all the accounts.
+    fn get_state_item_iterator(
+        &self,
+        version: Version,
+        start_key: HashValue,
+    ) -> Result<Box<dyn Iterator<Item = Result<StateItem>> + Send + Sync>> {
+        unimplemented!()
+    }
+
     /// Get the state prune window config value.
     fn get_state_prune_window(&self) -> Option<usize> {
         unimplemented!()
     }
+
+    /// Get the versions below the state prune window whose state is still held, i.e. the pinned
+    /// and epoch ending versions exempted from pruning (at most a bounded number of the latest
+    /// ones), in ascending order.
+    fn get_retained_state_versions(&self) -> Result<Vec<Version>> {
+        unimplemented!()
+    }
 }
 
 impl MoveStorage for &dyn DbReader {
@@ -594,6 +697,21 @@ pub trait DbWriter: Send + Sync {
     ) -> Result<Box<dyn StateSnapshotReceiver<AccountStateBlob>>> {
         unimplemented!()
     }
+
+    /// Finalizes a state snapshot that has already been restored to the database through
+    /// a state snapshot receiver. This is required to bootstrap the database at the snapshot
+    /// version, i.e., without the transactions before it.
+    ///
+    /// `output_with_proof` must contain the single transaction (and output) at `version`,
+    /// proven against the last of the given (epoch ending) `ledger_infos`.
+    fn finalize_state_snapshot(
+        &self,
+        version: Version,
+        output_with_proof: TransactionOutputListWithProof,
+        ledger_infos: &[LedgerInfoWithSignatures],
+    ) -> Result<()> {
+        unimplemented!()
+    }
 }
 
 pub trait MoveDbReader:
@@ -636,6 +754,21 @@ pub enum StorageRequest {
     SaveTransactionsRequest(Box<SaveTransactionsRequest>),
 }
 
+/// The number of items yielded by `DbReader::get_state_item_iterator` between two range proofs.
+pub const STATE_ITEM_PROOF_INTERVAL: usize = 1000;
+
+/// An account of the state at a version, as yielded by `DbReader::get_state_item_iterator`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StateItem {
+    /// The hash of the account address, which orders the accounts in the state.
+    pub key: HashValue,
+    pub value: AccountStateBlob,
+    /// The proof of the range of the state ending with this item, attached to every
+    /// `STATE_ITEM_PROOF_INTERVAL`th item and to the last one. It proves the items yielded since
+    /// the previous proof, like the proof of a chunk of a state snapshot.
+    pub range_proof: Option<SparseMerkleRangeProof>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 pub struct GetAccountStateWithProofByVersionRequest {
     /// The access path to query with.
@@ -1,7 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::DbReader;
+use crate::{async_state_view::AsyncStateView, DbReader};
 use anyhow::{format_err, Result};
 use aptos_crypto::{hash::SPARSE_MERKLE_PLACEHOLDER_HASH, HashValue};
 use aptos_state_view::{StateView, StateViewId};
@@ -16,7 +16,7 @@ use aptos_types::{
 use parking_lot::RwLock;
 use scratchpad::{AccountStatus, FrozenSparseMerkleTree, SparseMerkleTree};
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, BTreeSet, HashMap},
     convert::TryInto,
     sync::Arc,
 };
@@ -111,6 +111,86 @@ impl VerifiedStateView {
         }
     }
 
+    fn verify_proof(
+        &self,
+        address: AccountAddress,
+        blob: Option<&AccountStateBlob>,
+        proof: &SparseMerkleProof<AccountStateBlob>,
+    ) -> Result<()> {
+        proof
+            .verify(self.latest_persistent_state_root, address.hash(), blob)
+            .map_err(|err| {
+                format_err!(
+                    "Proof is invalid for address {:?} with state root hash {:?}: {}",
+                    address,
+                    self.latest_persistent_state_root,
+                    err
+                )
+            })
+    }
+
+    /// Loads the accounts which aren't cached yet into the cache, like [`StateView::get`] does
+    /// for a single account, except that the ones in persistent storage are read in a batch.
+    fn cache_accounts(&self, addresses: &[AccountAddress]) -> Result<()> {
+        let addresses = {
+            let account_to_state_cache = self.account_to_state_cache.read();
+            addresses
+                .iter()
+                .filter(|address| !account_to_state_cache.contains_key(address))
+                .cloned()
+                .collect::<BTreeSet<_>>()
+        };
+
+        let mut account_blobs = Vec::with_capacity(addresses.len());
+        let mut persisted_addresses = vec![];
+        for address in addresses {
+            match self.speculative_state.get(address.hash()) {
+                AccountStatus::ExistsInScratchPad(blob) => {
+                    account_blobs.push((address, Some(blob)))
+                }
+                AccountStatus::DoesNotExist => account_blobs.push((address, None)),
+                AccountStatus::ExistsInDB | AccountStatus::Unknown => {
+                    persisted_addresses.push(address)
+                }
+            }
+        }
+        let blobs_and_proofs = match self.latest_persistent_version {
+            Some(version) => self
+                .reader
+                .get_account_states_with_proof_by_version(&persisted_addresses, version)?,
+            None => persisted_addresses
+                .iter()
+                .map(|_| (None, SparseMerkleProof::new(None, vec![])))
+                .collect(),
+        };
+        for (address, (blob, proof)) in persisted_addresses.into_iter().zip(blobs_and_proofs) {
+            self.verify_proof(address, blob.as_ref(), &proof)?;
+            self.account_to_proof_cache
+                .write()
+                .insert(address.hash(), proof);
+            account_blobs.push((address, blob));
+        }
+
+        let account_states = account_blobs
+            .into_iter()
+            .map(|(address, blob)| {
+                let account_state = blob
+                    .as_ref()
+                    .map(TryInto::try_into)
+                    .transpose()?
+                    .unwrap_or_default();
+                Ok((address, account_state))
+            })
+            .collect::<Result<Vec<(AccountAddress, AccountState)>>>()?;
+        let mut account_to_state_cache = self.account_to_state_cache.write();
+        for (address, account_state) in account_states {
+            account_to_state_cache
+                .entry(address)
+                .or_insert(account_state);
+        }
+        Ok(())
+    }
+
     pub fn into_state_cache(self) -> StateCache {
         StateCache {
             frozen_base: self.speculative_state,
@@ -154,20 +234,7 @@ impl StateView for VerifiedStateView {
                         .get_account_state_with_proof_by_version(address, version)?,
                     None => (None, SparseMerkleProof::new(None, vec![])),
                 };
-                proof
-                    .verify(
-                        self.latest_persistent_state_root,
-                        address.hash(),
-                        blob.as_ref(),
-                    )
-                    .map_err(|err| {
-                        format_err!(
-                            "Proof is invalid for address {:?} with state root hash {:?}: {}",
-                            address,
-                            self.latest_persistent_state_root,
-                            err
-                        )
-                    })?;
+                self.verify_proof(address, blob.as_ref(), &proof)?;
 
                 // multiple threads may enter this code, and another thread might add
                 // an address before this one. Thus the insertion might return a None here.
@@ -196,3 +263,23 @@ impl StateView for VerifiedStateView {
         self.latest_persistent_version.is_none()
     }
 }
+
+/// The accounts of the access paths are read in a batch, but on the calling thread: the view is
+/// meant for the execution threads, which don't run an async runtime.
+#[async_trait::async_trait]
+impl AsyncStateView for VerifiedStateView {
+    async fn multi_get_state_values(
+        &self,
+        access_paths: &[AccessPath],
+    ) -> Result<Vec<Option<Vec<u8>>>> {
+        let addresses = access_paths
+            .iter()
+            .map(|access_path| access_path.address)
+            .collect::<Vec<_>>();
+        self.cache_accounts(&addresses)?;
+        access_paths
+            .iter()
+            .map(|access_path| self.get(access_path))
+            .collect()
+    }
+}
@@ -27,8 +27,6 @@ pub fn make_corpus(
     // TODO: Allow custom proptest configs?
     let mut gen = ValueGenerator::new();
 
-    let mut sha1 = Sha1::new();
-
     let mut idx = 0;
     while idx < num_items {
         let result = match target.generate(idx, &mut gen) {
@@ -38,23 +36,64 @@ pub fn make_corpus(
                 break;
             }
         };
-        // Use the SHA-1 of the result as the file name.
-        sha1.update(&result);
+        write_corpus_item(corpus_dir, &result, debug)?;
+        idx += 1;
+    }
+    Ok(idx)
+}
+
+/// Splits a recording of inbound network traffic into corpus items for the network
+/// framing and payload fuzz targets. `corpus_dir` returns the corpus directory of a
+/// target, which should be present once returned. Returns the number of items written.
+pub fn make_corpus_from_traffic(
+    recording: &Path,
+    corpus_dir: impl Fn(FuzzTarget) -> PathBuf,
+    debug: bool,
+) -> Result<usize> {
+    let recording = fs::read(recording)
+        .with_context(|| format!("Failed to read recording: {:?}", recording))?;
+    let corpus = network::fuzzing::corpus_from_recorded_traffic(&recording);
+
+    let framing_target = FuzzTarget::by_name("NetworkMessageFraming")
+        .ok_or_else(|| format_err!("Missing the network framing fuzz target"))?;
+    let framing_dir = corpus_dir(framing_target);
+    for frame in &corpus.frames {
+        write_corpus_item(&framing_dir, frame, debug)?;
+    }
 
-        let hash = sha1.finalize_reset();
-        let name = hex::encode(hash.as_slice());
-        let path = corpus_dir.join(name);
-        let mut f = fs::File::create(&path)
-            .with_context(|| format!("Failed to create file: {:?}", path))?;
-        if debug {
-            println!("Writing {} bytes to file: {:?}", result.len(), path);
+    let mut item_count = corpus.frames.len();
+    for (protocol_id, payload) in &corpus.payloads {
+        match FuzzTarget::by_protocol(*protocol_id) {
+            Some(target) => {
+                write_corpus_item(&corpus_dir(target), payload, debug)?;
+                item_count += 1;
+            }
+            None => {
+                if debug {
+                    println!(
+                        "Skipping payload for protocol without a fuzz target: {}",
+                        protocol_id
+                    );
+                }
+            }
         }
+    }
+    Ok(item_count)
+}
 
-        f.write_all(&result)
-            .with_context(|| format!("Failed to write to file: {:?}", path))?;
-        idx += 1;
+/// Writes a corpus item into the corpus directory, using the SHA-1 of the item as the file name.
+fn write_corpus_item(corpus_dir: &Path, item: &[u8], debug: bool) -> Result<()> {
+    let hash = Sha1::digest(item);
+    let name = hex::encode(hash.as_slice());
+    let path = corpus_dir.join(name);
+    let mut f =
+        fs::File::create(&path).with_context(|| format!("Failed to create file: {:?}", path))?;
+    if debug {
+        println!("Writing {} bytes to file: {:?}", item.len(), path);
     }
-    Ok(idx)
+
+    f.write_all(item)
+        .with_context(|| format!("Failed to write to file: {:?}", path))
 }
 
 /// Fuzz a target by running `cargo fuzz run`.
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{FuzzTarget, FuzzTargetImpl};
+use ::network::ProtocolId;
 use anyhow::{format_err, Result};
 use once_cell::sync::Lazy;
 use std::{collections::BTreeMap, env};
@@ -47,6 +48,15 @@ static ALL_TARGETS: Lazy<BTreeMap<&'static str, Box<dyn FuzzTargetImpl>>> = Lazy
         Box::new(network::NetworkHandshakeExchange::default()),
         Box::new(network::NetworkHandshakeNegotiation::default()),
         Box::new(network::PeerNetworkMessagesReceive::default()),
+        Box::new(network::NetworkMessageFraming::default()),
+        Box::new(network::ConsensusPayload::default()),
+        Box::new(network::MempoolPayload::default()),
+        Box::new(network::StateSyncPayload::default()),
+        Box::new(network::StorageServicePayload::default()),
+        Box::new(network::HealthCheckerPayload::default()),
+        Box::new(network::ReachabilityPayload::default()),
+        Box::new(network::PeerMonitoringPayload::default()),
+        Box::new(network::RegistrarPayload::default()),
         // Safety Rules Server (LSR)
         Box::new(safety_rules::SafetyRulesConstructAndSignVote::default()),
         Box::new(safety_rules::SafetyRulesInitialize::default()),
@@ -60,6 +70,7 @@ static ALL_TARGETS: Lazy<BTreeMap<&'static str, Box<dyn FuzzTargetImpl>>> = Lazy
         Box::new(secure_storage_vault::VaultSecretListResponse::default()),
         Box::new(secure_storage_vault::VaultSecretReadResponse::default()),
         Box::new(secure_storage_vault::VaultTokenCreateResponse::default()),
+        Box::new(secure_storage_vault::VaultLoginResponse::default()),
         Box::new(secure_storage_vault::VaultTokenRenewResponse::default()),
         Box::new(secure_storage_vault::VaultTransitCreateResponse::default()),
         Box::new(secure_storage_vault::VaultTransitExportResponse::default()),
@@ -113,6 +124,11 @@ impl FuzzTarget {
         ALL_TARGETS.get(name).map(|target| FuzzTarget(&**target))
     }
 
+    /// Get the fuzz target for the payloads received over the given network protocol.
+    pub fn by_protocol(protocol_id: ProtocolId) -> Option<Self> {
+        network::protocol_payload_target(protocol_id).and_then(Self::by_name)
+    }
+
     /// A list of all fuzz targets.
     pub fn all_targets() -> impl Iterator<Item = Self> {
         ALL_TARGETS.values().map(|target| FuzzTarget(&**target))
@@ -124,3 +124,295 @@ impl FuzzTargetImpl for PeerNetworkMessagesReceive {
         peer::fuzzing::fuzz(data);
     }
 }
+
+//
+// NetworkMessage framing
+//
+
+use network::fuzzing::fuzz_network_message_stream;
+
+#[derive(Clone, Debug, Default)]
+pub struct NetworkMessageFraming;
+impl FuzzTargetImpl for NetworkMessageFraming {
+    fn description(&self) -> &'static str {
+        "network length-prefixed framing and deserialization of inbound NetworkMessages"
+    }
+
+    fn generate(&self, _idx: usize, gen: &mut ValueGenerator) -> Option<Vec<u8>> {
+        Some(peer::fuzzing::generate_corpus(gen))
+    }
+
+    fn fuzz(&self, data: &[u8]) {
+        fuzz_network_message_stream(data);
+    }
+}
+
+//
+// Protocol payloads
+//
+
+use aptos_config::config::RoleType;
+use aptos_mempool::network::MempoolSyncMsg;
+use aptos_types::transaction::SignedTransaction;
+use consensus::network_interface::ConsensusMsg;
+use consensus_types::{
+    proposal_msg::ProposalMsg,
+    proptest_types::{arb_proposal, arb_sync_info, arb_timeout_kind, arb_vote},
+    vote_msg::VoteMsg,
+};
+use network::{
+    fuzzing::{fuzz_protocol_payload, protocol_payload_input},
+    protocols::{
+        health_checker::HealthCheckerMsg,
+        peer_monitoring::{NodeInfo, PeerMonitoringMsg},
+        reachability::ReachabilityMsg,
+        registrar::RegistrarMsg,
+        wire::compression,
+    },
+    ProtocolId,
+};
+use proptest::{
+    arbitrary::any,
+    collection::vec,
+    option, prop_oneof,
+    sample::select,
+    strategy::{Just, Strategy},
+};
+use serde::Serialize;
+use state_sync_v1::{fuzzing::arb_state_sync_msg, network::StateSyncMessage};
+use storage_service_types::{
+    EpochEndingLedgerInfoRequest, StorageServiceMessage, StorageServiceRequest,
+    TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
+};
+
+/// Encodes a valid message as the payload of one of the given protocols, i.e., with
+/// the encoding of the protocol (and compressed for compressed protocols), so the
+/// fuzzer starts from inputs the deserializer accepts.
+fn generate_protocol_payload<T: Serialize>(
+    protocol_ids: &[ProtocolId],
+    message: &T,
+    gen: &mut ValueGenerator,
+) -> Vec<u8> {
+    let protocol_id = gen.generate(select(protocol_ids.to_vec()));
+    let payload = match protocol_id.decompressed() {
+        Some(decompressed_protocol_id) => {
+            compression::compress(&decompressed_protocol_id.to_bytes(message).unwrap())
+        }
+        None => protocol_id.to_bytes(message).unwrap(),
+    };
+    protocol_payload_input(protocol_id, &payload)
+}
+
+/// Declares a fuzz target for the deserializer of the payloads received over the
+/// given protocols. The corpus is generated from the messages of `$strategy`.
+macro_rules! protocol_payload_target {
+    ($target:ident, $message:ty, $strategy:expr, $protocol_ids:expr, $description:expr) => {
+        #[derive(Clone, Debug, Default)]
+        pub struct $target;
+        impl $target {
+            /// The protocols the payload is received over
+            pub const PROTOCOL_IDS: &'static [ProtocolId] = $protocol_ids;
+        }
+        impl FuzzTargetImpl for $target {
+            fn description(&self) -> &'static str {
+                $description
+            }
+
+            fn generate(&self, _idx: usize, gen: &mut ValueGenerator) -> Option<Vec<u8>> {
+                let message: $message = gen.generate($strategy);
+                Some(generate_protocol_payload(Self::PROTOCOL_IDS, &message, gen))
+            }
+
+            fn fuzz(&self, data: &[u8]) {
+                fuzz_protocol_payload::<$message>(Self::PROTOCOL_IDS, data);
+            }
+        }
+    };
+}
+
+protocol_payload_target!(
+    ConsensusPayload,
+    ConsensusMsg,
+    prop_oneof![
+        (arb_proposal(), arb_sync_info(arb_timeout_kind())).prop_map(|(proposal, sync_info)| {
+            ConsensusMsg::ProposalMsg(Box::new(ProposalMsg::new(proposal, sync_info)))
+        }),
+        (
+            arb_vote(arb_timeout_kind()),
+            arb_sync_info(arb_timeout_kind())
+        )
+            .prop_map(
+                |(vote, sync_info)| ConsensusMsg::VoteMsg(Box::new(VoteMsg::new(vote, sync_info)))
+            ),
+        arb_sync_info(arb_timeout_kind())
+            .prop_map(|sync_info| ConsensusMsg::SyncInfo(Box::new(sync_info))),
+    ],
+    &[
+        ProtocolId::ConsensusRpcBcs,
+        ProtocolId::ConsensusRpcJson,
+        ProtocolId::ConsensusRpcBcsCompressed,
+        ProtocolId::ConsensusRpcJsonCompressed,
+        ProtocolId::ConsensusDirectSendBcs,
+        ProtocolId::ConsensusDirectSendJson,
+    ],
+    "network payload deserializer for consensus RPCs and direct sends"
+);
+
+protocol_payload_target!(
+    MempoolPayload,
+    MempoolSyncMsg,
+    prop_oneof![
+        (
+            vec(any::<u8>(), 0..32),
+            vec(any::<SignedTransaction>(), 0..10)
+        )
+            .prop_map(|(request_id, transactions)| {
+                MempoolSyncMsg::BroadcastTransactionsRequest {
+                    request_id,
+                    transactions,
+                }
+            }),
+        (vec(any::<u8>(), 0..32), any::<bool>(), any::<bool>()).prop_map(
+            |(request_id, retry, backoff)| MempoolSyncMsg::BroadcastTransactionsResponse {
+                request_id,
+                retry,
+                backoff,
+            }
+        ),
+    ],
+    &[ProtocolId::MempoolDirectSend, ProtocolId::MempoolRpc],
+    "network payload deserializer for mempool RPCs and direct sends"
+);
+
+protocol_payload_target!(
+    StateSyncPayload,
+    StateSyncMessage,
+    arb_state_sync_msg(),
+    &[
+        ProtocolId::StateSyncDirectSend,
+        ProtocolId::StateSyncDirectSendCompressed,
+    ],
+    "network payload deserializer for state sync direct sends"
+);
+
+protocol_payload_target!(
+    StorageServicePayload,
+    StorageServiceMessage,
+    prop_oneof![
+        Just(StorageServiceRequest::GetServerProtocolVersion),
+        Just(StorageServiceRequest::GetStorageServerSummary),
+        any::<u64>().prop_map(StorageServiceRequest::GetNumberOfAccountsAtVersion),
+        (any::<u64>(), any::<u64>()).prop_map(|(start_epoch, expected_end_epoch)| {
+            StorageServiceRequest::GetEpochEndingLedgerInfos(EpochEndingLedgerInfoRequest {
+                start_epoch,
+                expected_end_epoch,
+            })
+        }),
+        (any::<u64>(), any::<u64>(), any::<u64>(), any::<bool>()).prop_map(
+            |(proof_version, start_version, end_version, include_events)| {
+                StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+                    proof_version,
+                    start_version,
+                    end_version,
+                    include_events,
+                })
+            }
+        ),
+        (any::<u64>(), any::<u64>(), any::<u64>()).prop_map(
+            |(proof_version, start_version, end_version)| {
+                StorageServiceRequest::GetTransactionOutputsWithProof(
+                    TransactionOutputsWithProofRequest {
+                        proof_version,
+                        start_version,
+                        end_version,
+                    },
+                )
+            }
+        ),
+    ]
+    .prop_map(StorageServiceMessage::Request),
+    &[
+        ProtocolId::StorageServiceRpc,
+        ProtocolId::StorageServiceRpcCompressed,
+    ],
+    "network payload deserializer for storage service RPCs"
+);
+
+protocol_payload_target!(
+    HealthCheckerPayload,
+    HealthCheckerMsg,
+    any::<HealthCheckerMsg>(),
+    &[ProtocolId::HealthCheckerRpc],
+    "network payload deserializer for health checker RPCs"
+);
+
+protocol_payload_target!(
+    ReachabilityPayload,
+    ReachabilityMsg,
+    any::<ReachabilityMsg>(),
+    &[ProtocolId::ReachabilityRpc],
+    "network payload deserializer for reachability RPCs"
+);
+
+protocol_payload_target!(
+    PeerMonitoringPayload,
+    PeerMonitoringMsg,
+    (
+        any::<bool>(),
+        vec(any::<u8>(), 0..20),
+        select(vec![RoleType::Validator, RoleType::FullNode]),
+        option::of(any::<u64>()),
+    )
+        .prop_map(|(ping, build_version, role, synced_version)| {
+            let node_info = NodeInfo {
+                build_version: hex::encode(build_version),
+                role,
+                synced_version,
+            };
+            if ping {
+                PeerMonitoringMsg::Ping(node_info)
+            } else {
+                PeerMonitoringMsg::Pong(node_info)
+            }
+        }),
+    &[ProtocolId::PeerMonitoringRpc],
+    "network payload deserializer for peer monitoring RPCs"
+);
+
+protocol_payload_target!(
+    RegistrarPayload,
+    RegistrarMsg,
+    any::<RegistrarMsg>(),
+    &[ProtocolId::RegistrarRpc],
+    "network payload deserializer for registrar RPCs"
+);
+
+/// Returns the payload fuzz target for messages received over the given protocol.
+pub fn protocol_payload_target(protocol_id: ProtocolId) -> Option<&'static str> {
+    let targets: [(&'static str, &'static [ProtocolId]); 8] = [
+        (ConsensusPayload.name(), ConsensusPayload::PROTOCOL_IDS),
+        (MempoolPayload.name(), MempoolPayload::PROTOCOL_IDS),
+        (StateSyncPayload.name(), StateSyncPayload::PROTOCOL_IDS),
+        (
+            StorageServicePayload.name(),
+            StorageServicePayload::PROTOCOL_IDS,
+        ),
+        (
+            HealthCheckerPayload.name(),
+            HealthCheckerPayload::PROTOCOL_IDS,
+        ),
+        (
+            ReachabilityPayload.name(),
+            ReachabilityPayload::PROTOCOL_IDS,
+        ),
+        (
+            PeerMonitoringPayload.name(),
+            PeerMonitoringPayload::PROTOCOL_IDS,
+        ),
+        (RegistrarPayload.name(), RegistrarPayload::PROTOCOL_IDS),
+    ];
+    targets
+        .iter()
+        .find(|(_, protocol_ids)| protocol_ids.contains(&protocol_id))
+        .map(|(name, _)| *name)
+}
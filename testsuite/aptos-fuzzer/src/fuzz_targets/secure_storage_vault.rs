@@ -5,16 +5,17 @@ use crate::{corpus_from_strategy, fuzz_data_to_value, FuzzTargetImpl};
 use aptos_proptest_helpers::ValueGenerator;
 use aptos_vault_client::{
     fuzzing::{
-        arb_generic_response, arb_policy_list_response, arb_secret_list_response,
-        arb_secret_read_response, arb_token_create_response, arb_token_renew_response,
-        arb_transit_create_response, arb_transit_export_response, arb_transit_list_response,
-        arb_transit_read_response, arb_transit_sign_response, arb_unsealed_response,
+        arb_generic_response, arb_login_response, arb_policy_list_response,
+        arb_secret_list_response, arb_secret_read_response, arb_token_create_response,
+        arb_token_renew_response, arb_transit_create_response, arb_transit_export_response,
+        arb_transit_list_response, arb_transit_read_response, arb_transit_sign_response,
+        arb_unsealed_response,
     },
-    process_generic_response, process_policy_list_response, process_policy_read_response,
-    process_secret_list_response, process_secret_read_response, process_token_create_response,
-    process_token_renew_response, process_transit_create_response, process_transit_export_response,
-    process_transit_list_response, process_transit_read_response, process_transit_restore_response,
-    process_transit_sign_response, process_unsealed_response,
+    process_generic_response, process_login_response, process_policy_list_response,
+    process_policy_read_response, process_secret_list_response, process_secret_read_response,
+    process_token_create_response, process_token_renew_response, process_transit_create_response,
+    process_transit_export_response, process_transit_list_response, process_transit_read_response,
+    process_transit_restore_response, process_transit_sign_response, process_unsealed_response,
 };
 
 #[derive(Clone, Debug, Default)]
@@ -137,6 +138,26 @@ impl FuzzTargetImpl for VaultTokenCreateResponse {
     }
 }
 
+#[derive(Clone, Debug, Default)]
+pub struct VaultLoginResponse;
+
+/// This implementation will fuzz process_login_response(): the method used by the vault client
+/// to process a login request from the vault backend.
+impl FuzzTargetImpl for VaultLoginResponse {
+    fn description(&self) -> &'static str {
+        "Secure storage vault: process_login_response()"
+    }
+
+    fn generate(&self, _idx: usize, _gen: &mut ValueGenerator) -> Option<Vec<u8>> {
+        Some(corpus_from_strategy(arb_login_response()))
+    }
+
+    fn fuzz(&self, data: &[u8]) {
+        let response = fuzz_data_to_value(data, arb_login_response());
+        let _ = process_login_response(response);
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct VaultTokenRenewResponse;
 
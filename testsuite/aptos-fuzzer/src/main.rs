@@ -60,6 +60,18 @@ enum Command {
         #[structopt(name = "ARGS", parse(from_os_str), allow_hyphen_values = true)]
         args: Vec<OsString>,
     },
+    /// Generate corpus for the network fuzz targets from a recording of inbound traffic
+    #[structopt(name = "corpus-from-traffic")]
+    CorpusFromTraffic {
+        /// The recorded traffic: the raw length-prefixed NetworkMessage frames read
+        /// from a connection after the handshake
+        #[structopt(name = "RECORDING", parse(from_os_str))]
+        recording: PathBuf,
+        /// Custom parent directory of the target corpus directories (required if not
+        /// running under `cargo run`)
+        #[structopt(long = "corpus-dir", parse(from_os_str))]
+        corpus_dir: Option<PathBuf>,
+    },
     /// List fuzz targets
     #[structopt(name = "list")]
     List {
@@ -143,6 +155,23 @@ fn main() {
             let artifact_dir = artifact_dir.unwrap_or_else(|| default_artifact_dir(target));
             commands::fuzz_target(target, corpus_dir, artifact_dir, args).unwrap();
         }
+        Command::CorpusFromTraffic {
+            recording,
+            corpus_dir,
+        } => {
+            let target_corpus_dir = |target: FuzzTarget| match &corpus_dir {
+                Some(dir) => {
+                    let dir = dir.join(target.name());
+                    fs::create_dir_all(&dir).expect("Failed to create directory");
+                    dir
+                }
+                None => default_corpus_dir(target).0,
+            };
+            let item_count =
+                commands::make_corpus_from_traffic(&recording, target_corpus_dir, opt.debug)
+                    .expect("Failed to create corpus");
+            println!("Wrote {} items to corpus", item_count);
+        }
         Command::List { no_desc } => {
             commands::list_targets(no_desc);
         }
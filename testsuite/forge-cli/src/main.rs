@@ -7,9 +7,17 @@ use forge::{ForgeConfig, Options, Result, *};
 use std::{env, num::NonZeroUsize, process, time::Duration};
 use structopt::StructOpt;
 use testcases::{
-    compatibility_test::SimpleValidatorUpgrade, fixed_tps_test::FixedTpsTest,
-    gas_price_test::NonZeroGasPrice, generate_traffic, partial_nodes_down_test::PartialNodesDown,
-    performance_test::PerformanceBenchmark, reconfiguration_test::ReconfigurationTest,
+    compatibility_test::SimpleValidatorUpgrade,
+    fixed_tps_test::FixedTpsTest,
+    gas_price_test::NonZeroGasPrice,
+    generate_traffic,
+    network_chaos_test::{
+        NetworkBandwidthTest, NetworkDelayTest, NetworkLossTest, NetworkPartitionTest,
+    },
+    partial_nodes_down_test::PartialNodesDown,
+    performance_test::PerformanceBenchmark,
+    reconfiguration_test::ReconfigurationTest,
+    state_sync_catch_up_test::StateSyncCatchUp,
     state_sync_performance::StateSyncPerformance,
 };
 use tokio::runtime::Runtime;
@@ -331,6 +339,8 @@ fn get_test_suite(suite_name: &str) -> ForgeConfig<'static> {
         "land_blocking_compat" => land_blocking_test_compat_suite(),
         "land_blocking" => land_blocking_test_suite(),
         "pre_release" => pre_release_suite(),
+        "liveness" => liveness_suite(),
+        "state_sync_catch_up" => state_sync_catch_up_suite(),
         single_test => single_test_suite(single_test),
     }
 }
@@ -358,6 +368,10 @@ fn single_test_suite(test_name: &str) -> ForgeConfig<'static> {
         "state_sync" => config.with_network_tests(&[&StateSyncPerformance]),
         "compat" => config.with_network_tests(&[&SimpleValidatorUpgrade]),
         "config" => config.with_network_tests(&[&ReconfigurationTest]),
+        "partition" => config.with_network_tests(&[&NetworkPartitionTest]),
+        "delay" => config.with_network_tests(&[&NetworkDelayTest]),
+        "bandwidth" => config.with_network_tests(&[&NetworkBandwidthTest]),
+        "loss" => config.with_network_tests(&[&NetworkLossTest]),
         _ => config.with_network_tests(&[&PerformanceBenchmark]),
     }
 }
@@ -392,6 +406,43 @@ fn pre_release_suite() -> ForgeConfig<'static> {
         ])
 }
 
+fn liveness_suite() -> ForgeConfig<'static> {
+    // network chaos requires Chaos Mesh, so these only run on k8s swarms
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(30).unwrap())
+        .with_network_tests(&[
+            &NetworkPartitionTest,
+            &NetworkDelayTest,
+            &NetworkBandwidthTest,
+            &NetworkLossTest,
+        ])
+}
+
+static STATE_SYNC_LAGGING_FULLNODE: StateSyncCatchUp = StateSyncCatchUp {
+    name: "state-sync-lagging-fullnode",
+    lag_duration: Duration::from_secs(120),
+    clear_storage: false,
+    min_throughput_tps: 500,
+    max_time_to_synced: Duration::from_secs(300),
+};
+
+static STATE_SYNC_FULLNODE_FROM_GENESIS: StateSyncCatchUp = StateSyncCatchUp {
+    name: "state-sync-fullnode-from-genesis",
+    lag_duration: Duration::from_secs(120),
+    clear_storage: true,
+    min_throughput_tps: 500,
+    max_time_to_synced: Duration::from_secs(600),
+};
+
+fn state_sync_catch_up_suite() -> ForgeConfig<'static> {
+    ForgeConfig::default()
+        .with_initial_validator_count(NonZeroUsize::new(30).unwrap())
+        .with_network_tests(&[
+            &STATE_SYNC_LAGGING_FULLNODE,
+            &STATE_SYNC_FULLNODE_FROM_GENESIS,
+        ])
+}
+
 //TODO Make public test later
 #[derive(Debug)]
 struct GetMetadata;
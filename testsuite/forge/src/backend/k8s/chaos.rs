@@ -0,0 +1,120 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{K8sNode, Result, SwarmChaos};
+use anyhow::{bail, format_err};
+use aptos_logger::*;
+use aptos_sdk::types::PeerId;
+use serde_json::{json, Value};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fs::File,
+    hash::{Hash, Hasher},
+    io::Write,
+    process::Command,
+};
+use tempfile::TempDir;
+
+const KUBECTL_BIN: &str = "kubectl";
+const CHAOS_MESH_API_VERSION: &str = "chaos-mesh.org/v1alpha1";
+const NAMESPACE: &str = "default";
+
+/// Injects the chaos with a Chaos Mesh `NetworkChaos`, which must be installed in the cluster.
+pub(crate) fn inject_chaos(nodes: &HashMap<PeerId, &K8sNode>, chaos: &SwarmChaos) -> Result<()> {
+    info!("Injecting chaos: {}", chaos);
+    kubectl_network_chaos("apply", &network_chaos(nodes, chaos)?)
+}
+
+pub(crate) fn remove_chaos(nodes: &HashMap<PeerId, &K8sNode>, chaos: &SwarmChaos) -> Result<()> {
+    info!("Removing chaos: {}", chaos);
+    kubectl_network_chaos("delete", &network_chaos(nodes, chaos)?)
+}
+
+fn kubectl_network_chaos(action: &str, network_chaos: &Value) -> Result<()> {
+    let tmp_dir = TempDir::new().expect("Could not create temp dir");
+    let path = tmp_dir.path().join("network_chaos.json");
+    let mut file = File::create(&path)?;
+    file.write_all(network_chaos.to_string().as_bytes())?;
+
+    let file_arg = path.to_str().unwrap();
+    let args = [action, "-f", file_arg];
+    debug!("Running kubectl {:?}", args);
+    let output = Command::new(KUBECTL_BIN).args(&args).output()?;
+    if !output.status.success() {
+        bail!(
+            "Failed to {} network chaos: {}",
+            action,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(())
+}
+
+/// Builds the `NetworkChaos` resource of the chaos, named after its content for it to be removed
+/// by building it again.
+fn network_chaos(nodes: &HashMap<PeerId, &K8sNode>, chaos: &SwarmChaos) -> Result<Value> {
+    let (group_a, group_b) = chaos.groups();
+    if group_a.is_empty() || group_b.is_empty() {
+        bail!("Chaos requires two non-empty groups of nodes: {}", chaos);
+    }
+
+    let mut spec = match chaos {
+        SwarmChaos::Partition(_) => json!({ "action": "partition" }),
+        SwarmChaos::Delay(c) => json!({
+            "action": "delay",
+            "delay": {
+                "latency": format!("{}ms", c.latency.as_millis()),
+                "jitter": format!("{}ms", c.jitter.as_millis()),
+                "correlation": c.correlation_percentage.to_string(),
+            },
+        }),
+        SwarmChaos::Bandwidth(c) => json!({
+            "action": "bandwidth",
+            "bandwidth": {
+                // tc rates in bps are in bytes per second
+                "rate": format!("{}bps", c.rate_bytes_per_sec),
+                "limit": c.limit_bytes,
+                "buffer": c.buffer_bytes,
+            },
+        }),
+        SwarmChaos::Loss(c) => json!({
+            "action": "loss",
+            "loss": {
+                "loss": c.loss_percentage.to_string(),
+                "correlation": c.correlation_percentage.to_string(),
+            },
+        }),
+    };
+    spec["mode"] = json!("all");
+    spec["selector"] = pod_selector(nodes, group_a)?;
+    spec["direction"] = json!("both");
+    spec["target"] = json!({
+        "mode": "all",
+        "selector": pod_selector(nodes, group_b)?,
+    });
+
+    let mut hasher = DefaultHasher::new();
+    chaos.hash(&mut hasher);
+    Ok(json!({
+        "apiVersion": CHAOS_MESH_API_VERSION,
+        "kind": "NetworkChaos",
+        "metadata": {
+            "name": format!("forge-chaos-{:016x}", hasher.finish()),
+            "namespace": NAMESPACE,
+        },
+        "spec": spec,
+    }))
+}
+
+fn pod_selector(nodes: &HashMap<PeerId, &K8sNode>, group: &[PeerId]) -> Result<Value> {
+    let pods = group
+        .iter()
+        .map(|peer_id| {
+            nodes
+                .get(peer_id)
+                .map(|node| format!("{}-0", node.sts_name))
+                .ok_or_else(|| format_err!("Invalid id: {}", peer_id))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(json!({ "pods": { NAMESPACE: pods } }))
+}
@@ -7,6 +7,7 @@ use rand::rngs::StdRng;
 use std::{env, fs::File, io::Read, num::NonZeroUsize, path::PathBuf};
 use tokio::runtime::Runtime;
 
+mod chaos;
 mod cluster_helper;
 mod node;
 mod swarm;
@@ -2,8 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    backend::k8s::node::K8sNode, create_k8s_client, query_sequence_numbers, remove_helm_release,
-    set_validator_image_tag, ChainInfo, FullNode, Node, Result, Swarm, Validator, Version,
+    backend::k8s::{chaos, node::K8sNode},
+    create_k8s_client, query_sequence_numbers, remove_helm_release, set_validator_image_tag,
+    ChainInfo, FullNode, Node, Result, Swarm, SwarmChaos, Validator, Version,
 };
 use anyhow::{anyhow, bail, format_err};
 use aptos_config::config::NodeConfig;
@@ -132,6 +133,14 @@ impl K8sSwarm {
     fn get_kube_client(&self) -> K8sClient {
         self.kube_client.clone()
     }
+
+    fn nodes(&self) -> HashMap<PeerId, &K8sNode> {
+        self.validators
+            .iter()
+            .chain(self.fullnodes.iter())
+            .map(|(peer_id, node)| (*peer_id, node))
+            .collect()
+    }
 }
 
 #[async_trait::async_trait]
@@ -248,6 +257,18 @@ impl Swarm for K8sSwarm {
             )
         }
     }
+
+    fn supports_chaos(&self) -> bool {
+        true
+    }
+
+    fn inject_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        chaos::inject_chaos(&self.nodes(), &chaos)
+    }
+
+    fn remove_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        chaos::remove_chaos(&self.nodes(), &chaos)
+    }
 }
 
 pub(crate) fn k8s_retry_strategy() -> impl Iterator<Item = Duration> {
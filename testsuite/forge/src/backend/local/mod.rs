@@ -124,6 +124,11 @@ impl LocalFactory {
         Self::with_revision_and_workspace(&merge_base)
     }
 
+    /// A builder of swarms running the versions of the factory, starting with the latest one.
+    pub fn swarm_builder(&self) -> LocalSwarmBuilder {
+        LocalSwarm::builder(self.versions.clone())
+    }
+
     pub async fn new_swarm<R>(
         &self,
         rng: R,
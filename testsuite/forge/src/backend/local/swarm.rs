@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    ChainInfo, FullNode, HealthCheckError, LocalNode, LocalVersion, Node, NodeExt, Swarm, SwarmExt,
-    Validator, Version,
+    ChainInfo, FullNode, HealthCheckError, LocalNode, LocalVersion, Node, NodeExt, Swarm,
+    SwarmChaos, SwarmExt, Validator, Version,
 };
 use anyhow::{anyhow, bail, Result};
 use aptos_config::config::NodeConfig;
@@ -73,11 +73,32 @@ impl AsRef<Path> for SwarmDirectory {
     }
 }
 
+/// Builds a [`LocalSwarm`], a network of `aptos-node` processes running on the local host, e.g.
+/// for the integration tests of crates depending on Forge:
+///
+/// ```no_run
+/// # async fn run() -> forge::Result<()> {
+/// use forge::{LocalFactory, Swarm};
+/// use std::num::NonZeroUsize;
+///
+/// let factory = LocalFactory::from_workspace()?;
+/// let mut swarm = factory
+///     .swarm_builder()
+///     .number_of_validators(NonZeroUsize::new(4).unwrap())
+///     .number_of_fullnodes(1)
+///     .build(rand::rngs::OsRng)?;
+/// swarm.launch().await?;
+/// let client = swarm.chain_info().rest_client();
+/// # Ok(())
+/// # }
+/// ```
 pub struct LocalSwarmBuilder {
     versions: Arc<HashMap<Version, LocalVersion>>,
     initial_version: Option<Version>,
     template: NodeConfig,
     number_of_validators: NonZeroUsize,
+    fullnode_template: NodeConfig,
+    number_of_fullnodes: usize,
     dir: Option<PathBuf>,
     genesis_modules: Option<Vec<Vec<u8>>>,
 }
@@ -89,6 +110,8 @@ impl LocalSwarmBuilder {
             initial_version: None,
             template: NodeConfig::default_for_validator(),
             number_of_validators: NonZeroUsize::new(1).unwrap(),
+            fullnode_template: NodeConfig::default_for_validator_full_node(),
+            number_of_fullnodes: 0,
             dir: None,
             genesis_modules: None,
         }
@@ -109,11 +132,27 @@ impl LocalSwarmBuilder {
         self
     }
 
+    /// The config the validator fullnodes are generated from.
+    pub fn fullnode_template(mut self, fullnode_template: NodeConfig) -> Self {
+        self.fullnode_template = fullnode_template;
+        self
+    }
+
+    /// The number of validators getting a validator fullnode, at most the number of validators.
+    /// The fullnodes are started along with the validators by [`LocalSwarm::launch`].
+    pub fn number_of_fullnodes(mut self, number_of_fullnodes: usize) -> Self {
+        self.number_of_fullnodes = number_of_fullnodes;
+        self
+    }
+
+    /// Where the configs, data and logs of the nodes are kept. Defaults to a temporary directory
+    /// removed with the swarm.
     pub fn dir<T: AsRef<Path>>(mut self, dir: T) -> Self {
         self.dir = Some(dir.as_ref().into());
         self
     }
 
+    /// The modules published at genesis. Defaults to the current release of the framework.
     pub fn genesis_modules(mut self, genesis_modules: Vec<Vec<u8>>) -> Self {
         self.genesis_modules = Some(genesis_modules);
         self
@@ -123,6 +162,14 @@ impl LocalSwarmBuilder {
     where
         R: ::rand::RngCore + ::rand::CryptoRng,
     {
+        if self.number_of_fullnodes > self.number_of_validators.get() {
+            bail!(
+                "Can't have {} validator fullnodes for {} validators",
+                self.number_of_fullnodes,
+                self.number_of_validators
+            );
+        }
+
         let dir = if let Some(dir) = self.dir {
             if dir.exists() {
                 fs::remove_dir_all(&dir)?;
@@ -162,7 +209,7 @@ impl LocalSwarmBuilder {
         });
         let version = versions.get(&initial_version).unwrap();
 
-        let validators = validators
+        let mut validators = validators
             .into_iter()
             .map(|v| {
                 let node = LocalNode::new(version.to_owned(), v.name, v.directory)?;
@@ -170,6 +217,31 @@ impl LocalSwarmBuilder {
             })
             .collect::<Result<HashMap<_, _>>>()?;
 
+        let mut node_name_counter = validators.len() as u64;
+        let mut fullnodes = HashMap::new();
+        for validator in validators.values_mut().take(self.number_of_fullnodes) {
+            let mut validator_config = validator.config().clone();
+            let fullnode_config = FullnodeConfig::validator_fullnode(
+                node_name_counter.to_string(),
+                dir.as_ref(),
+                self.fullnode_template.clone(),
+                &mut validator_config,
+                &genesis_waypoint,
+                &genesis,
+            )?;
+            node_name_counter += 1;
+
+            validator_config.save(validator.config_path())?;
+            *validator.config_mut() = validator_config;
+
+            let fullnode = LocalNode::new(
+                version.to_owned(),
+                fullnode_config.name,
+                fullnode_config.directory,
+            )?;
+            fullnodes.insert(fullnode.peer_id(), fullnode);
+        }
+
         let root_account = LocalAccount::new(
             aptos_sdk::types::account_config::aptos_root_address(),
             AccountKey::from_private_key(root_keys.root_key),
@@ -193,12 +265,12 @@ impl LocalSwarmBuilder {
         );
 
         Ok(LocalSwarm {
-            node_name_counter: validators.len() as u64,
+            node_name_counter,
             genesis,
             genesis_waypoint,
             versions,
             validators,
-            fullnodes: HashMap::new(),
+            fullnodes,
             dir,
             root_account,
             treasury_compliance_account,
@@ -228,6 +300,8 @@ impl LocalSwarm {
         LocalSwarmBuilder::new(versions)
     }
 
+    /// Starts the nodes built by the [`LocalSwarmBuilder`] and waits for the network to make
+    /// progress.
     pub async fn launch(&mut self) -> Result<()> {
         // Start all the validators
         for validator in self.validators.values_mut() {
@@ -237,7 +311,20 @@ impl LocalSwarm {
         // Wait for all of them to startup
         let deadline = Instant::now() + Duration::from_secs(60);
         self.wait_for_startup().await?;
-        self.wait_for_connectivity(deadline).await?;
+
+        // The validator fullnodes have no public peers to be connected to, only the validators
+        // are checked for connectivity
+        let fullnodes = mem::take(&mut self.fullnodes);
+        let connectivity = self.wait_for_connectivity(deadline).await;
+        self.fullnodes = fullnodes;
+        connectivity?;
+
+        for fullnode in self.fullnodes.values_mut() {
+            fullnode.start()?;
+        }
+        for fullnode in self.fullnodes.values_mut() {
+            fullnode.wait_until_healthy(deadline).await?;
+        }
         self.liveness_check(deadline).await?;
 
         println!("Swarm launched successfully.");
@@ -499,4 +586,18 @@ impl Swarm for LocalSwarm {
         self.dir.persist();
         self.dir.display().to_string()
     }
+
+    fn supports_chaos(&self) -> bool {
+        // All the local nodes share the loopback interface, and their outbound connections come
+        // from ephemeral ports, so the traffic between two groups of nodes can't be told apart
+        false
+    }
+
+    fn inject_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        bail!("Chaos isn't supported by the local swarm: {}", chaos)
+    }
+
+    fn remove_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        bail!("Chaos isn't supported by the local swarm: {}", chaos)
+    }
 }
@@ -0,0 +1,93 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_sdk::types::PeerId;
+use std::{fmt, time::Duration};
+
+/// Chaos injected in the network between two groups of nodes of a Swarm. Traffic within a group
+/// isn't affected, traffic between the groups is in both directions.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SwarmChaos {
+    Partition(SwarmNetworkPartition),
+    Delay(SwarmNetworkDelay),
+    Bandwidth(SwarmNetworkBandwidth),
+    Loss(SwarmNetworkLoss),
+}
+
+impl SwarmChaos {
+    /// Returns the two groups of nodes between which the chaos is injected
+    pub fn groups(&self) -> (&[PeerId], &[PeerId]) {
+        let (group_a, group_b) = match self {
+            SwarmChaos::Partition(c) => (&c.group_a, &c.group_b),
+            SwarmChaos::Delay(c) => (&c.group_a, &c.group_b),
+            SwarmChaos::Bandwidth(c) => (&c.group_a, &c.group_b),
+            SwarmChaos::Loss(c) => (&c.group_a, &c.group_b),
+        };
+        (group_a, group_b)
+    }
+}
+
+impl fmt::Display for SwarmChaos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (group_a, group_b) = self.groups();
+        match self {
+            SwarmChaos::Partition(_) => write!(f, "Partition")?,
+            SwarmChaos::Delay(c) => write!(
+                f,
+                "Delay of {:?} (jitter {:?}, correlation {}%)",
+                c.latency, c.jitter, c.correlation_percentage
+            )?,
+            SwarmChaos::Bandwidth(c) => write!(
+                f,
+                "Bandwidth of {} bytes/s (limit {} bytes, buffer {} bytes)",
+                c.rate_bytes_per_sec, c.limit_bytes, c.buffer_bytes
+            )?,
+            SwarmChaos::Loss(c) => write!(
+                f,
+                "Loss of {}% (correlation {}%)",
+                c.loss_percentage, c.correlation_percentage
+            )?,
+        }
+        write!(f, " between {} and {} nodes", group_a.len(), group_b.len())
+    }
+}
+
+/// Drops all the traffic between the groups
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SwarmNetworkPartition {
+    pub group_a: Vec<PeerId>,
+    pub group_b: Vec<PeerId>,
+}
+
+/// Delays every packet between the groups
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SwarmNetworkDelay {
+    pub group_a: Vec<PeerId>,
+    pub group_b: Vec<PeerId>,
+    pub latency: Duration,
+    pub jitter: Duration,
+    /// How much the delay of a packet depends on the delay of the previous one
+    pub correlation_percentage: u64,
+}
+
+/// Caps the bandwidth between the groups, with a token bucket
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SwarmNetworkBandwidth {
+    pub group_a: Vec<PeerId>,
+    pub group_b: Vec<PeerId>,
+    pub rate_bytes_per_sec: u64,
+    /// Bytes which can be queued waiting for tokens, beyond which packets are dropped
+    pub limit_bytes: u64,
+    /// Size of the bucket, i.e. the bytes which can be sent at once above the rate
+    pub buffer_bytes: u64,
+}
+
+/// Drops a share of the packets between the groups
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SwarmNetworkLoss {
+    pub group_a: Vec<PeerId>,
+    pub group_b: Vec<PeerId>,
+    pub loss_percentage: u64,
+    /// How much the loss of a packet depends on the loss of the previous one
+    pub correlation_percentage: u64,
+}
@@ -5,6 +5,8 @@ mod admin;
 pub use admin::*;
 mod aptos;
 pub use aptos::*;
+mod chaos;
+pub use chaos::*;
 mod network;
 pub use network::*;
 mod test;
@@ -1,7 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{ChainInfo, FullNode, NodeExt, Result, Validator, Version};
+use crate::{ChainInfo, FullNode, NodeExt, Result, SwarmChaos, Validator, Version};
 use anyhow::{anyhow, bail};
 use aptos_config::config::NodeConfig;
 use aptos_rest_client::Client as RestClient;
@@ -63,6 +63,15 @@ pub trait Swarm: Sync {
     fn chain_info(&mut self) -> ChainInfo<'_>;
 
     fn logs_location(&mut self) -> String;
+
+    /// Whether chaos can be injected in the network of the Swarm
+    fn supports_chaos(&self) -> bool;
+
+    /// Injects chaos in the network of the Swarm, which lasts until it's removed
+    fn inject_chaos(&mut self, chaos: SwarmChaos) -> Result<()>;
+
+    /// Removes chaos previously injected with `inject_chaos`
+    fn remove_chaos(&mut self, chaos: SwarmChaos) -> Result<()>;
 }
 
 impl<T: ?Sized> SwarmExt for T where T: Swarm {}
@@ -8,6 +8,7 @@ use std::{fmt, time::Duration};
 #[derive(Default, Debug, Serialize)]
 pub struct TestReport {
     metrics: Vec<ReportedMetric>,
+    criteria: Vec<ReportedCriterion>,
     text: String,
 }
 
@@ -18,6 +19,16 @@ pub struct ReportedMetric {
     pub value: f64,
 }
 
+/// The outcome of checking a measured value of a test against a threshold
+#[derive(Debug, Serialize)]
+pub struct ReportedCriterion {
+    pub test_name: String,
+    pub criterion: String,
+    pub value: f64,
+    pub threshold: f64,
+    pub passed: bool,
+}
+
 impl TestReport {
     pub fn new() -> Self {
         Default::default()
@@ -31,6 +42,35 @@ impl TestReport {
         });
     }
 
+    /// Reports whether `value` met the `threshold` of the criterion, returning whether it passed
+    pub fn report_criterion<E: ToString, C: ToString>(
+        &mut self,
+        test: E,
+        criterion: C,
+        value: f64,
+        threshold: f64,
+        passed: bool,
+    ) -> bool {
+        let test_name = test.to_string();
+        let criterion = criterion.to_string();
+        self.report_text(format!(
+            "{} : {} {} ({}, threshold {})",
+            test_name,
+            if passed { "passed" } else { "(!) failed" },
+            criterion,
+            value,
+            threshold
+        ));
+        self.criteria.push(ReportedCriterion {
+            test_name,
+            criterion,
+            value,
+            threshold,
+            passed,
+        });
+        passed
+    }
+
     pub fn report_text(&mut self, text: String) {
         if !self.text.is_empty() {
             self.text.push('\n');
@@ -47,11 +87,15 @@ impl TestReport {
         } else {
             stats.latency / stats.committed
         };
+        let p50_latency = stats.latency_buckets.percentile(50, 100);
+        let p90_latency = stats.latency_buckets.percentile(90, 100);
         let p99_latency = stats.latency_buckets.percentile(99, 100);
         self.report_metric(test_name.clone(), "submitted_txn", submitted_txn as f64);
         self.report_metric(test_name.clone(), "expired_txn", expired_txn as f64);
         self.report_metric(test_name.clone(), "avg_tps", avg_tps as f64);
         self.report_metric(test_name.clone(), "avg_latency", avg_latency_client as f64);
+        self.report_metric(test_name.clone(), "p50_latency", p50_latency as f64);
+        self.report_metric(test_name.clone(), "p90_latency", p90_latency as f64);
         self.report_metric(test_name.clone(), "p99_latency", p99_latency as f64);
         let expired_text = if expired_txn == 0 {
             "no expired txns".to_string()
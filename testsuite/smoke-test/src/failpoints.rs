@@ -0,0 +1,129 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    smoke_test_environment::new_local_swarm,
+    test_utils::{assert_balance, create_and_fund_account, transfer_coins},
+};
+use aptos_types::PeerId;
+use forge::{HealthCheckError, LocalSwarm, NodeExt, Swarm, SwarmExt};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+const ADMIN_TOKEN: &str = "failpoints";
+
+/// The failpoints at the critical transitions of a validator, in the order of the pipeline
+const CRASH_POINTS: &[&str] = &[
+    "consensus::broadcast_proposal",
+    "consensus::save_vote",
+    "consensus::send_vote",
+    "consensus::commit",
+    "executor::commit_blocks",
+    "aptosdb::save_transactions",
+    "mempool::process_committed_transactions",
+];
+
+/// Enables the admin service of the validator, through which its failpoints are configured
+async fn enable_admin_service(swarm: &mut LocalSwarm, peer_id: PeerId) {
+    let validator = swarm.validator_mut(peer_id).unwrap();
+    let mut config = validator.config().clone();
+    config.admin_service.enabled = true;
+    config.admin_service.authentication_token = Some(ADMIN_TOKEN.into());
+    config.save(validator.config_path()).unwrap();
+    validator.restart().await.unwrap();
+}
+
+async fn set_failpoint(port: u16, name: &str, actions: Option<&str>) {
+    let mut updates = BTreeMap::new();
+    updates.insert(name, actions);
+    let response = reqwest::Client::new()
+        .post(format!("http://127.0.0.1:{}/failpoints", port))
+        .bearer_auth(ADMIN_TOKEN)
+        .json(&updates)
+        .send()
+        .await
+        .unwrap();
+    assert!(
+        response.status().is_success(),
+        "Failed to set failpoint {}: {}",
+        name,
+        response.text().await.unwrap_or_default()
+    );
+}
+
+/// Whether the process of the validator has exited, e.g. after panicking at a failpoint
+async fn has_crashed(swarm: &mut LocalSwarm, peer_id: PeerId) -> bool {
+    matches!(
+        swarm.validator_mut(peer_id).unwrap().health_check().await,
+        Err(HealthCheckError::NotRunning)
+    )
+}
+
+#[tokio::test]
+async fn test_crash_recovery_at_failpoints() {
+    // - Start a swarm of 4 nodes, with the admin service of one of them enabled.
+    // - For every crash point: make the node panic at the point and keep submitting
+    //   transactions to the others until it crashes.
+    // - Restart the node, whose failpoints are cleared with the process, and verify it recovers
+    //   and catches up with the others.
+    let mut swarm = new_local_swarm(4).await;
+    let validator_peer_ids = swarm.validators().map(|v| v.peer_id()).collect::<Vec<_>>();
+    let node_to_crash = validator_peer_ids[0];
+    enable_admin_service(&mut swarm, node_to_crash).await;
+    swarm.launch().await.unwrap();
+    let admin_port = swarm
+        .validator(node_to_crash)
+        .unwrap()
+        .config()
+        .admin_service
+        .port;
+
+    let client_0 = swarm.validator(node_to_crash).unwrap().rest_client();
+    let client_1 = swarm
+        .validator(validator_peer_ids[1])
+        .unwrap()
+        .rest_client();
+    let transaction_factory = swarm.chain_info().transaction_factory();
+    let mut account_0 = create_and_fund_account(&mut swarm, 1000).await;
+    let account_1 = create_and_fund_account(&mut swarm, 10).await;
+    let mut transferred = 0;
+
+    for failpoint in CRASH_POINTS {
+        set_failpoint(admin_port, failpoint, Some("panic")).await;
+
+        // Transactions keep the pipeline of the node busy until it reaches the failpoint
+        let deadline = Instant::now() + Duration::from_secs(60);
+        while !has_crashed(&mut swarm, node_to_crash).await {
+            assert!(
+                Instant::now() < deadline,
+                "Timed out waiting for the validator to crash at {}",
+                failpoint
+            );
+            transfer_coins(
+                &client_1,
+                &transaction_factory,
+                &mut account_0,
+                &account_1,
+                1,
+            )
+            .await;
+            transferred += 1;
+        }
+
+        let validator = swarm.validator_mut(node_to_crash).unwrap();
+        validator.start().unwrap();
+        validator
+            .wait_until_healthy(Instant::now() + Duration::from_secs(10))
+            .await
+            .unwrap_or_else(|e| panic!("Failed to recover from {}: {}", failpoint, e));
+        swarm
+            .wait_for_all_nodes_to_catchup(Instant::now() + Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        assert_balance(&client_0, &account_0, 1000 - transferred).await;
+        assert_balance(&client_0, &account_1, 10 + transferred).await;
+    }
+}
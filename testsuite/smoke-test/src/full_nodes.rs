@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    smoke_test_environment::new_local_swarm,
+    smoke_test_environment::{new_local_swarm, new_local_swarm_builder},
     test_utils::{
         assert_balance, create_and_fund_account, transfer_coins, transfer_coins_non_blocking,
     },
@@ -13,9 +13,11 @@ use aptos_config::{
 };
 use aptos_types::network_address::{NetworkAddress, Protocol};
 use forge::{NodeExt, Swarm, SwarmExt};
+use rand::rngs::OsRng;
 use std::{
     collections::HashSet,
     net::Ipv4Addr,
+    num::NonZeroUsize,
     time::{Duration, Instant},
 };
 
@@ -121,6 +123,33 @@ async fn test_full_node_basic_flow() {
     assert_balance(&pfn_client, &account_1, 13).await;
 }
 
+#[tokio::test]
+async fn test_swarm_built_with_fullnodes() {
+    let mut swarm = new_local_swarm_builder()
+        .number_of_validators(NonZeroUsize::new(2).unwrap())
+        .number_of_fullnodes(1)
+        .build(OsRng)
+        .unwrap();
+    swarm.launch().await.unwrap();
+    assert_eq!(swarm.full_nodes().count(), 1);
+
+    let transaction_factory = swarm.chain_info().transaction_factory();
+    let vfn_client = swarm.full_nodes().next().unwrap().rest_client();
+    let mut account_0 = create_and_fund_account(&mut swarm, 10).await;
+    let account_1 = create_and_fund_account(&mut swarm, 10).await;
+
+    transfer_coins(
+        &vfn_client,
+        &transaction_factory,
+        &mut account_0,
+        &account_1,
+        1,
+    )
+    .await;
+    assert_balance(&vfn_client, &account_0, 9).await;
+    assert_balance(&vfn_client, &account_1, 11).await;
+}
+
 #[tokio::test]
 async fn test_vfn_failover() {
     let mut swarm = new_local_swarm(4).await;
@@ -15,6 +15,8 @@ mod client;
 #[cfg(test)]
 mod consensus;
 #[cfg(test)]
+mod failpoints;
+#[cfg(test)]
 mod full_nodes;
 #[cfg(test)]
 mod genesis;
@@ -116,6 +116,19 @@ async fn test_auto_validate_options() {
 async fn test_consensus_key_rotation() {
     let (_swarm, op_tool, backend, mut storage) = launch_swarm_with_op_tool_and_backend(1).await;
 
+    // Verify that a dry run reports the rotation without performing it
+    let plan = op_tool
+        .rotate_consensus_key_dry_run(&backend)
+        .await
+        .unwrap();
+    assert!(plan.generates_new_key);
+    assert_eq!(plan.storage_key, plan.on_chain_key);
+    assert_eq!(Some(plan.storage_key.clone()), plan.validator_set_key);
+    assert_eq!(
+        plan.storage_key,
+        storage.get_public_key(CONSENSUS_KEY).unwrap().public_key
+    );
+
     // Rotate the consensus key
     let (txn_ctx, new_consensus_key) = op_tool.rotate_consensus_key(&backend, false).await.unwrap();
     assert!(txn_ctx.execution_result.unwrap().success);
@@ -142,8 +155,9 @@ async fn test_consensus_key_rotation() {
     // Here, we expected the op_tool to see that the consensus key in storage doesn't match the one
     // on-chain, and thus it should simply forward a transaction to the blockchain.
     let rotated_consensus_key = storage.rotate_key(CONSENSUS_KEY).unwrap();
+    // The rotation always waits for the transaction, even without auto validation.
     let (txn_ctx, new_consensus_key) = op_tool.rotate_consensus_key(&backend, true).await.unwrap();
-    assert!(txn_ctx.execution_result.is_none());
+    assert!(txn_ctx.execution_result.unwrap().success);
     assert_eq!(rotated_consensus_key, new_consensus_key);
 }
 
@@ -444,6 +458,7 @@ async fn test_single_node_operations() {
     let (mut swarm, op_tool, backend, mut storage) = launch_swarm_with_op_tool_and_backend(1).await;
 
     test_account_resource(&swarm, &op_tool, &backend, &mut storage).await;
+    test_check_storage(&swarm, &op_tool, &backend, &mut storage).await;
     test_create_operator_bcs_file(&mut swarm, &op_tool, &backend, &mut storage).await;
     test_create_operator_hex_file(&mut swarm, &op_tool, &backend, &mut storage).await;
     test_create_validator_bcs_file(&mut swarm, &op_tool, &backend, &mut storage).await;
@@ -458,6 +473,28 @@ async fn test_single_node_operations() {
     test_verify_validator_state(&swarm, &op_tool, &backend, &mut storage).await;
 }
 
+async fn test_check_storage(
+    _swarm: &LocalSwarm,
+    op_tool: &OperationalTool,
+    backend: &SecureBackend,
+    storage: &mut Storage,
+) {
+    // Check the storage by signing with the consensus key
+    let health = op_tool
+        .check_storage(CONSENSUS_KEY, 5, backend)
+        .await
+        .unwrap();
+    let storage_consensus_key = storage.get_public_key(CONSENSUS_KEY).unwrap().public_key;
+    assert_eq!(storage_consensus_key, health.public_key);
+    assert!(health.mean_sign_latency_us <= health.max_sign_latency_us);
+
+    // Keys that don't exist can't be checked
+    op_tool
+        .check_storage("missing_key", 5, backend)
+        .await
+        .unwrap_err();
+}
+
 async fn test_extract_private_key(
     swarm: &LocalSwarm,
     op_tool: &OperationalTool,
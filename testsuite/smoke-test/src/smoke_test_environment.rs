@@ -1,14 +1,14 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use forge::{LocalFactory, LocalSwarm};
+use forge::{LocalFactory, LocalSwarm, LocalSwarmBuilder};
 use once_cell::sync::Lazy;
 use rand::rngs::OsRng;
 use std::num::NonZeroUsize;
 
-pub async fn new_local_swarm(num_validators: usize) -> LocalSwarm {
-    static FACTORY: Lazy<LocalFactory> = Lazy::new(|| LocalFactory::from_workspace().unwrap());
+static FACTORY: Lazy<LocalFactory> = Lazy::new(|| LocalFactory::from_workspace().unwrap());
 
+pub async fn new_local_swarm(num_validators: usize) -> LocalSwarm {
     ::aptos_logger::Logger::new().init();
 
     FACTORY
@@ -16,3 +16,11 @@ pub async fn new_local_swarm(num_validators: usize) -> LocalSwarm {
         .await
         .unwrap()
 }
+
+/// A builder of swarms running the aptos-node of the workspace, for the tests needing more than
+/// validators, e.g. validator fullnodes
+pub fn new_local_swarm_builder() -> LocalSwarmBuilder {
+    ::aptos_logger::Logger::new().init();
+
+    FACTORY.swarm_builder()
+}
@@ -5,7 +5,7 @@ use crate::{
     smoke_test_environment::new_local_swarm,
     test_utils::{create_and_fund_account, transfer_and_reconfig, transfer_coins},
 };
-use aptos_config::config::{BootstrappingMode, ContinuousSyncingMode, NodeConfig};
+use aptos_config::config::{BootstrappingMode, ContinuousSyncingMode, NodeConfig, SyncingModes};
 use aptos_rest_client::Client as RestClient;
 use aptos_sdk::{transaction_builder::TransactionFactory, types::LocalAccount};
 use forge::{LocalSwarm, NodeExt, Swarm, SwarmExt};
@@ -35,6 +35,26 @@ async fn test_full_node_bootstrap_outputs() {
     test_full_node_sync(vfn_config, swarm, true).await;
 }
 
+#[tokio::test]
+async fn test_full_node_bootstrap_outputs_for_role() {
+    // Create a validator swarm of 1 validator node
+    let swarm = new_local_swarm(1).await;
+
+    // Create a fullnode config that only syncs transaction outputs on fullnodes
+    let mut vfn_config = NodeConfig::default_for_validator_full_node();
+    let driver_config = &mut vfn_config.state_sync.state_sync_driver;
+    driver_config.enable_state_sync_v2 = true;
+    driver_config.bootstrapping_mode = BootstrappingMode::ExecuteTransactionsFromGenesis;
+    driver_config.continuous_syncing_mode = ContinuousSyncingMode::ExecuteTransactions;
+    driver_config.fullnode_syncing_modes = Some(SyncingModes {
+        bootstrapping_mode: BootstrappingMode::ApplyTransactionOutputsFromGenesis,
+        continuous_syncing_mode: ContinuousSyncingMode::ApplyTransactionOutputs,
+    });
+
+    // Test the ability of the fullnode to sync
+    test_full_node_sync(vfn_config, swarm, true).await;
+}
+
 #[tokio::test]
 async fn test_full_node_bootstrap_transactions() {
     // Create a validator swarm of 1 validator node
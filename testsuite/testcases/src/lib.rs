@@ -4,9 +4,11 @@
 pub mod compatibility_test;
 pub mod fixed_tps_test;
 pub mod gas_price_test;
+pub mod network_chaos_test;
 pub mod partial_nodes_down_test;
 pub mod performance_test;
 pub mod reconfiguration_test;
+pub mod state_sync_catch_up_test;
 pub mod state_sync_performance;
 
 use aptos_sdk::{transaction_builder::TransactionFactory, types::PeerId};
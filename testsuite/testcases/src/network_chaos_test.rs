@@ -0,0 +1,169 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::bail;
+use aptos_sdk::types::PeerId;
+use forge::{
+    NetworkContext, NetworkTest, Result, SwarmChaos, SwarmExt, SwarmNetworkBandwidth,
+    SwarmNetworkDelay, SwarmNetworkLoss, SwarmNetworkPartition, Test,
+};
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+
+/// Partitions the largest minority of the validators from the others, which must keep committing
+/// transactions, then checks the minority catches up once the partition heals.
+pub struct NetworkPartitionTest;
+
+impl Test for NetworkPartitionTest {
+    fn name(&self) -> &'static str {
+        "network-partition"
+    }
+}
+
+impl NetworkTest for NetworkPartitionTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let (minority, majority) = match split_validators(ctx, self.name())? {
+            Some(groups) => groups,
+            None => return Ok(()),
+        };
+        let chaos = SwarmChaos::Partition(SwarmNetworkPartition {
+            group_a: minority,
+            group_b: majority.clone(),
+        });
+        run_with_chaos(ctx, self.name(), chaos, &majority)
+    }
+}
+
+/// Adds latency between the largest minority of the validators and the others.
+pub struct NetworkDelayTest;
+
+impl Test for NetworkDelayTest {
+    fn name(&self) -> &'static str {
+        "network-delay"
+    }
+}
+
+impl NetworkTest for NetworkDelayTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let (minority, majority) = match split_validators(ctx, self.name())? {
+            Some(groups) => groups,
+            None => return Ok(()),
+        };
+        let all_validators = [minority.clone(), majority.clone()].concat();
+        let chaos = SwarmChaos::Delay(SwarmNetworkDelay {
+            group_a: minority,
+            group_b: majority,
+            latency: Duration::from_millis(200),
+            jitter: Duration::from_millis(20),
+            correlation_percentage: 50,
+        });
+        run_with_chaos(ctx, self.name(), chaos, &all_validators)
+    }
+}
+
+/// Caps the bandwidth between the largest minority of the validators and the others.
+pub struct NetworkBandwidthTest;
+
+impl Test for NetworkBandwidthTest {
+    fn name(&self) -> &'static str {
+        "network-bandwidth"
+    }
+}
+
+impl NetworkTest for NetworkBandwidthTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let (minority, majority) = match split_validators(ctx, self.name())? {
+            Some(groups) => groups,
+            None => return Ok(()),
+        };
+        let all_validators = [minority.clone(), majority.clone()].concat();
+        let chaos = SwarmChaos::Bandwidth(SwarmNetworkBandwidth {
+            group_a: minority,
+            group_b: majority,
+            rate_bytes_per_sec: 1024 * 1024,
+            limit_bytes: 20 * 1024 * 1024,
+            buffer_bytes: 10_000,
+        });
+        run_with_chaos(ctx, self.name(), chaos, &all_validators)
+    }
+}
+
+/// Drops a share of the packets between the largest minority of the validators and the others.
+pub struct NetworkLossTest;
+
+impl Test for NetworkLossTest {
+    fn name(&self) -> &'static str {
+        "network-loss"
+    }
+}
+
+impl NetworkTest for NetworkLossTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let (minority, majority) = match split_validators(ctx, self.name())? {
+            Some(groups) => groups,
+            None => return Ok(()),
+        };
+        let all_validators = [minority.clone(), majority.clone()].concat();
+        let chaos = SwarmChaos::Loss(SwarmNetworkLoss {
+            group_a: minority,
+            group_b: majority,
+            loss_percentage: 20,
+            correlation_percentage: 25,
+        });
+        run_with_chaos(ctx, self.name(), chaos, &all_validators)
+    }
+}
+
+/// Splits the validators into the largest minority which BFT tolerates failing, and the others.
+/// Returns None, skipping the test, if the swarm doesn't support chaos.
+fn split_validators(
+    ctx: &mut NetworkContext<'_>,
+    name: &str,
+) -> Result<Option<(Vec<PeerId>, Vec<PeerId>)>> {
+    if !ctx.swarm().supports_chaos() {
+        println!("Skipping {}: the swarm doesn't support chaos", name);
+        return Ok(None);
+    }
+    let mut minority = ctx
+        .swarm()
+        .validators()
+        .map(|v| v.peer_id())
+        .collect::<Vec<_>>();
+    // BFT only tolerates a failing minority with at least 4 validators
+    if minority.len() < 4 {
+        bail!(
+            "{} requires at least 4 validators, the swarm has {}",
+            name,
+            minority.len()
+        );
+    }
+    let majority = minority.split_off((minority.len() - 1) / 3);
+    Ok(Some((minority, majority)))
+}
+
+/// Generates traffic to the validators while the chaos is injected, then removes the chaos and
+/// checks all the nodes are live and catch up.
+fn run_with_chaos(
+    ctx: &mut NetworkContext<'_>,
+    name: &str,
+    chaos: SwarmChaos,
+    traffic_validators: &[PeerId],
+) -> Result<()> {
+    let duration = Duration::from_secs(120);
+    println!("Injecting chaos: {}", chaos);
+    ctx.swarm().inject_chaos(chaos.clone())?;
+
+    let txn_stat = generate_traffic(ctx, traffic_validators, duration, 0, None);
+    // Remove the chaos even if the traffic failed, not to leave the network broken
+    ctx.swarm().remove_chaos(chaos)?;
+    ctx.report
+        .report_txn_stats(name.to_string(), txn_stat?, duration);
+
+    let runtime = Runtime::new()?;
+    let deadline = Instant::now() + Duration::from_secs(120);
+    runtime.block_on(ctx.swarm().liveness_check(deadline))?;
+    runtime.block_on(ctx.swarm().wait_for_all_nodes_to_catchup(deadline))?;
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::{bail, format_err};
+use aptos_rest_client::Client as RestClient;
+use aptos_sdk::transaction_builder::TransactionFactory;
+use forge::{NetworkContext, NetworkTest, NodeExt, Result, Test, TxnEmitter};
+use rand::{
+    rngs::{OsRng, StdRng},
+    seq::IteratorRandom,
+    Rng, SeedableRng,
+};
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+
+/// Stops a fullnode while the network is loaded, then restarts it while the network is still
+/// loaded and measures how fast it catches up with the version the validators were at when it
+/// restarted. The test fails if the catch up is slower than the success criteria.
+pub struct StateSyncCatchUp {
+    pub name: &'static str,
+    /// How long the fullnode is stopped, while transactions are emitted to the validators
+    pub lag_duration: Duration,
+    /// Whether the fullnode syncs from genesis, rather than from the version it stopped at
+    pub clear_storage: bool,
+    /// The minimum number of transactions synced per second by the fullnode
+    pub min_throughput_tps: u64,
+    /// The maximum time taken by the fullnode to be synced, from its restart
+    pub max_time_to_synced: Duration,
+}
+
+impl Test for StateSyncCatchUp {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl NetworkTest for StateSyncCatchUp {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let runtime = Runtime::new()?;
+        let mut rng = StdRng::from_seed(OsRng.gen());
+        let all_validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        let fullnode_id = ctx
+            .swarm()
+            .full_nodes()
+            .map(|v| v.peer_id())
+            .choose(&mut rng)
+            .ok_or_else(|| format_err!("The swarm has no fullnode to lag"))?;
+        let validator_client = ctx.swarm().validators().next().unwrap().rest_client();
+        let fullnode_client = ctx.swarm().full_node(fullnode_id).unwrap().rest_client();
+
+        // 1. stop the fullnode and let it lag behind the loaded network
+        let start_version = if self.clear_storage {
+            0
+        } else {
+            runtime.block_on(latest_version(&fullnode_client))?
+        };
+        let fullnode = ctx.swarm().full_node_mut(fullnode_id).unwrap();
+        fullnode.stop()?;
+        if self.clear_storage {
+            fullnode.clear_storage()?;
+        }
+        generate_traffic(ctx, &all_validators, self.lag_duration, 0, None)?;
+
+        // 2. restart the fullnode, keeping the network loaded while it catches up
+        let target_version = runtime.block_on(latest_version(&validator_client))?;
+        println!(
+            "The fullnode is going to restart and sync from version {} to {}",
+            start_version, target_version
+        );
+        let fullnode = ctx.swarm().full_node_mut(fullnode_id).unwrap();
+        runtime.block_on(fullnode.start())?;
+        let restart_instant = Instant::now();
+
+        let validator_clients = ctx
+            .swarm()
+            .validators()
+            .map(|n| n.rest_client())
+            .collect::<Vec<_>>();
+        let emit_job_request = ctx.global_job.clone().rest_clients(validator_clients);
+        let chain_info = ctx.swarm().chain_info();
+        let mut emitter = TxnEmitter::new(
+            chain_info.treasury_compliance_account,
+            chain_info.designated_dealer_account,
+            validator_client,
+            TransactionFactory::new(chain_info.chain_id),
+            SeedableRng::from_rng(&mut rng)?,
+        );
+        let time_to_synced = runtime.block_on(async {
+            let job = emitter.start_job(emit_job_request).await?;
+            let synced = wait_for_version(
+                &fullnode_client,
+                target_version,
+                restart_instant,
+                // Keep waiting past the threshold, to report how late the fullnode was
+                self.max_time_to_synced * 2,
+            )
+            .await;
+            emitter.stop_job(job).await;
+            synced
+        })?;
+
+        // 3. check the catch up against the success criteria
+        let synced_versions = target_version.saturating_sub(start_version);
+        let throughput = synced_versions as f64 / time_to_synced.as_secs_f64().max(1.0);
+        println!(
+            "The fullnode synced {} versions in {:?}: {:.0} txn/sec",
+            synced_versions, time_to_synced, throughput
+        );
+        ctx.report
+            .report_metric(self.name(), "synced_versions", synced_versions as f64);
+        ctx.report.report_metric(
+            self.name(),
+            "time_to_synced_secs",
+            time_to_synced.as_secs_f64(),
+        );
+        ctx.report
+            .report_metric(self.name(), "catch_up_throughput", throughput);
+        let throughput_passed = ctx.report.report_criterion(
+            self.name(),
+            "min_catch_up_throughput",
+            throughput,
+            self.min_throughput_tps as f64,
+            throughput >= self.min_throughput_tps as f64,
+        );
+        let time_passed = ctx.report.report_criterion(
+            self.name(),
+            "max_time_to_synced_secs",
+            time_to_synced.as_secs_f64(),
+            self.max_time_to_synced.as_secs_f64(),
+            time_to_synced <= self.max_time_to_synced,
+        );
+        if !throughput_passed || !time_passed {
+            bail!("The fullnode didn't meet the state sync success criteria");
+        }
+
+        Ok(())
+    }
+}
+
+async fn latest_version(client: &RestClient) -> Result<u64> {
+    Ok(client.get_ledger_information().await?.into_inner().version)
+}
+
+/// Waits for the node to be synced to the version, returning the time elapsed since `start`
+async fn wait_for_version(
+    client: &RestClient,
+    version: u64,
+    start: Instant,
+    timeout: Duration,
+) -> Result<Duration> {
+    // The node doesn't serve requests until it's started, so errors are retried
+    while latest_version(client).await.unwrap_or(0) < version {
+        if start.elapsed() > timeout {
+            bail!(
+                "The fullnode didn't sync to version {} within {:?}",
+                version,
+                timeout
+            );
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    Ok(start.elapsed())
+}
@@ -36,12 +36,16 @@
 //! `path` will be set to "/a" and use the `get_prefix()` method from statedb
 
 use crate::account_address::AccountAddress;
+use anyhow::{bail, Error, Result};
 use aptos_crypto::hash::HashValue;
-use move_core_types::language_storage::{ModuleId, ResourceKey, StructTag, CODE_TAG, RESOURCE_TAG};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, ResourceKey, StructTag, CODE_TAG, RESOURCE_TAG},
+};
 #[cfg(any(test, feature = "fuzzing"))]
 use proptest_derive::Arbitrary;
 use serde::{Deserialize, Serialize};
-use std::{convert::TryFrom, fmt};
+use std::{convert::TryFrom, fmt, str::FromStr};
 
 #[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Ord, PartialOrd)]
 #[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
@@ -137,6 +141,98 @@ impl fmt::Display for AccessPath {
     }
 }
 
+/// A prefix of the struct tags of resources: the resources declared at an address, in a module,
+/// or all the instantiations of a struct. Since the struct tag is the first thing encoded in the
+/// path of a resource, the paths of the resources matching a prefix share a common prefix too, and
+/// are contiguous in an account state.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StructTagPrefix {
+    Address(AccountAddress),
+    Module(ModuleId),
+    Struct { module: ModuleId, name: Identifier },
+}
+
+impl StructTagPrefix {
+    /// The common prefix of the paths of the resources matching `self`, in their BCS encoding
+    pub fn path_prefix(&self) -> Vec<u8> {
+        let (address, module, name) = match self {
+            Self::Address(address) => (address, None, None),
+            Self::Module(module) => (module.address(), Some(module.name().as_str()), None),
+            Self::Struct { module, name } => (
+                module.address(),
+                Some(module.name().as_str()),
+                Some(name.as_str()),
+            ),
+        };
+        let mut prefix = vec![RESOURCE_TAG];
+        prefix.extend(bcs::to_bytes(address).expect("Unexpected serialization error"));
+        for identifier in module.into_iter().chain(name) {
+            prefix.extend(bcs::to_bytes(identifier).expect("Unexpected serialization error"));
+        }
+        prefix
+    }
+
+    pub fn matches(&self, struct_tag: &StructTag) -> bool {
+        match self {
+            Self::Address(address) => &struct_tag.address == address,
+            Self::Module(module) => {
+                &struct_tag.address == module.address()
+                    && struct_tag.module.as_ref() == module.name()
+            }
+            Self::Struct { module, name } => {
+                &struct_tag.address == module.address()
+                    && struct_tag.module.as_ref() == module.name()
+                    && &struct_tag.name == name
+            }
+        }
+    }
+}
+
+/// Parses `0x1::*`, `0x1::Module::*` or `0x1::Module::Struct`
+impl FromStr for StructTagPrefix {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts = s.split("::").collect::<Vec<_>>();
+        let address = match parts.first() {
+            Some(address) => AccountAddress::from_hex_literal(address)?,
+            None => bail!("Invalid struct tag prefix: {}", s),
+        };
+        Ok(match parts[1..] {
+            ["*"] => Self::Address(address),
+            [module, "*"] => Self::Module(ModuleId::new(address, Identifier::new(module)?)),
+            [module, name] => Self::Struct {
+                module: ModuleId::new(address, Identifier::new(module)?),
+                name: Identifier::new(name)?,
+            },
+            _ => bail!("Invalid struct tag prefix: {}", s),
+        })
+    }
+}
+
+impl fmt::Display for StructTagPrefix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Address(address) => write!(f, "{}::*", address.to_hex_literal()),
+            Self::Module(module) => {
+                write!(
+                    f,
+                    "{}::{}::*",
+                    module.address().to_hex_literal(),
+                    module.name()
+                )
+            }
+            Self::Struct { module, name } => write!(
+                f,
+                "{}::{}::{}",
+                module.address().to_hex_literal(),
+                module.name(),
+                name
+            ),
+        }
+    }
+}
+
 impl From<&ModuleId> for AccessPath {
     fn from(id: &ModuleId) -> AccessPath {
         AccessPath {
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    access_path::Path,
+    access_path::{AccessPath, Path, StructTagPrefix},
     account_address::AccountAddress,
     account_config::{
         currency_code_from_type_tag, AccountResource, AccountRole, BalanceResource, CRSNResource,
@@ -23,7 +23,26 @@ use move_core_types::{
     identifier::Identifier, language_storage::StructTag, move_resource::MoveResource,
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::{collections::btree_map::BTreeMap, convert::TryFrom, fmt};
+use std::{collections::btree_map::BTreeMap, convert::TryFrom, fmt, ops::Bound};
+
+/// The first path of the resources matching a prefix, starting after the resource of the cursor
+/// if any.
+pub(crate) fn resource_paths_start(
+    path_prefix: &[u8],
+    cursor: Option<&StructTag>,
+) -> Bound<Vec<u8>> {
+    match cursor {
+        Some(struct_tag) => {
+            let path = AccessPath::resource_access_vec(struct_tag.clone());
+            if path.as_slice() >= path_prefix {
+                Bound::Excluded(path)
+            } else {
+                Bound::Included(path_prefix.to_vec())
+            }
+        }
+        None => Bound::Included(path_prefix.to_vec()),
+    }
+}
 
 #[derive(Clone, Default, Deserialize, PartialEq, Serialize)]
 pub struct AccountState(BTreeMap<Vec<u8>, Vec<u8>>);
@@ -276,6 +295,24 @@ impl AccountState {
         })
     }
 
+    /// Returns the resources whose struct tags match the prefix, in the order of their paths,
+    /// starting after the resource of the cursor if any. Only the matching resources are visited.
+    pub fn get_resources_by_prefix<'a>(
+        &'a self,
+        prefix: &StructTagPrefix,
+        cursor: Option<&StructTag>,
+    ) -> impl Iterator<Item = (StructTag, &'a [u8])> + 'a {
+        let path_prefix = prefix.path_prefix();
+        let start = resource_paths_start(&path_prefix, cursor);
+        self.0
+            .range((start, Bound::Unbounded))
+            .take_while(move |(k, _)| k.starts_with(&path_prefix))
+            .filter_map(|(k, v)| match Path::try_from(k) {
+                Ok(Path::Resource(struct_tag)) => Some((struct_tag, v.as_ref())),
+                Ok(Path::Code(_)) | Err(_) => None,
+            })
+    }
+
     /// Given a particular `MoveResource`, return an iterator with all instances
     /// of that resource (there may be multiple with different generic type parameters).
     pub fn get_resources_with_type<T: MoveResource>(
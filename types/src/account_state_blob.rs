@@ -2,25 +2,27 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    access_path::{Path, StructTagPrefix},
     account_address::{AccountAddress, HashAccountAddress},
     account_config::{AccountResource, BalanceResource, DiemAccountResource},
-    account_state::AccountState,
+    account_state::{resource_paths_start, AccountState},
     ledger_info::LedgerInfo,
     proof::{AccountStateProof, SparseMerkleRangeProof},
     transaction::Version,
 };
-use anyhow::{anyhow, ensure, Error, Result};
+use anyhow::{anyhow, bail, ensure, Error, Result};
 use aptos_crypto::{
-    hash::{CryptoHash, CryptoHasher},
+    hash::{CryptoHash, CryptoHasher, SPARSE_MERKLE_PLACEHOLDER_HASH},
     HashValue,
 };
 use aptos_crypto_derive::CryptoHasher;
+use move_core_types::language_storage::StructTag;
 #[cfg(any(test, feature = "fuzzing"))]
 use proptest::{arbitrary::Arbitrary, prelude::*};
 #[cfg(any(test, feature = "fuzzing"))]
 use proptest_derive::Arbitrary;
 use serde::{Deserialize, Deserializer, Serialize};
-use std::{convert::TryFrom, fmt};
+use std::{convert::TryFrom, fmt, ops::Bound};
 
 #[derive(Clone, Eq, PartialEq, Serialize, CryptoHasher)]
 pub struct AccountStateBlob {
@@ -52,6 +54,74 @@ impl AccountStateBlob {
         let hash = hasher.finish();
         Self { blob, hash }
     }
+
+    /// Returns the resources whose struct tags match the prefix, in the order of their paths,
+    /// starting after the resource of the cursor if any, like
+    /// [`AccountState::get_resources_by_prefix`] but without deserializing the account state: only
+    /// the paths of its BCS encoded map are decoded, the values are borrowed from the blob.
+    pub fn get_resources_by_prefix(
+        &self,
+        prefix: &StructTagPrefix,
+        cursor: Option<&StructTag>,
+    ) -> Result<Vec<(StructTag, &[u8])>> {
+        let path_prefix = prefix.path_prefix();
+        let start = resource_paths_start(&path_prefix, cursor);
+        let mut bytes = self.blob.as_slice();
+        let mut resources = vec![];
+        for _ in 0..read_bcs_length(&mut bytes)? {
+            let path = read_bcs_bytes(&mut bytes)?;
+            let value = read_bcs_bytes(&mut bytes)?;
+            let after_start = match &start {
+                Bound::Included(start) => path >= start.as_slice(),
+                Bound::Excluded(start) => path > start.as_slice(),
+                Bound::Unbounded => true,
+            };
+            if after_start && path.starts_with(&path_prefix) {
+                if let Ok(Path::Resource(struct_tag)) = Path::try_from(path) {
+                    resources.push((path, struct_tag, value));
+                }
+            }
+        }
+        ensure!(bytes.is_empty(), "Trailing bytes in account state blob");
+        // BCS orders the entries of a map by their encoded keys, which start with their length
+        resources.sort_by(|(path1, _, _), (path2, _, _)| path1.cmp(path2));
+        Ok(resources
+            .into_iter()
+            .map(|(_, struct_tag, value)| (struct_tag, value))
+            .collect())
+    }
+}
+
+/// Reads a ULEB128 encoded length, as BCS encodes those of sequences and maps.
+fn read_bcs_length(bytes: &mut &[u8]) -> Result<usize> {
+    let mut length = 0u64;
+    for shift in (0..32).step_by(7) {
+        let (byte, rest) = bytes
+            .split_first()
+            .ok_or_else(|| anyhow!("Unexpected end of account state blob"))?;
+        *bytes = rest;
+        length |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            ensure!(
+                length <= u64::from(u32::MAX),
+                "Invalid length in account state blob"
+            );
+            return Ok(length as usize);
+        }
+    }
+    bail!("Invalid length in account state blob")
+}
+
+/// Reads BCS encoded bytes, borrowing them.
+fn read_bcs_bytes<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8]> {
+    let length = read_bcs_length(bytes)?;
+    ensure!(
+        bytes.len() >= length,
+        "Unexpected end of account state blob"
+    );
+    let (value, rest) = bytes.split_at(length);
+    *bytes = rest;
+    Ok(value)
 }
 
 impl fmt::Debug for AccountStateBlob {
@@ -243,6 +313,17 @@ pub struct AccountStatesChunkWithProof {
     pub proof: SparseMerkleRangeProof, // The proof to ensure the chunk is in the account states
 }
 
+impl AccountStatesChunkWithProof {
+    /// Returns true iff this chunk holds the last account states at the version,
+    /// i.e., there are no non-empty subtrees to the right of the chunk.
+    pub fn is_last_chunk(&self) -> bool {
+        self.proof
+            .right_siblings()
+            .iter()
+            .all(|sibling| *sibling == *SPARSE_MERKLE_PLACEHOLDER_HASH)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{AccountStateWithProof, *};
@@ -27,6 +27,7 @@ pub mod proof;
 pub mod proptest_types;
 pub mod serde_helper;
 pub mod state_proof;
+pub mod state_storage_usage;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod test_helpers;
 pub mod timestamp;
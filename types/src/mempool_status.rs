@@ -63,6 +63,12 @@ pub enum MempoolStatusCode {
     // transaction didn't pass vm_validation
     VmError = 5,
     UnknownStatus = 6,
+    // The same transaction was recently committed
+    AlreadyCommitted = 7,
+    // Transactions of a bundle aren't of the same account with consecutive sequence numbers
+    InvalidBundle = 8,
+    // The transaction calls a function the transaction filter of the node doesn't accept
+    Filtered = 9,
 }
 
 impl TryFrom<u64> for MempoolStatusCode {
@@ -77,6 +83,9 @@ impl TryFrom<u64> for MempoolStatusCode {
             4 => Ok(MempoolStatusCode::InvalidUpdate),
             5 => Ok(MempoolStatusCode::VmError),
             6 => Ok(MempoolStatusCode::UnknownStatus),
+            7 => Ok(MempoolStatusCode::AlreadyCommitted),
+            8 => Ok(MempoolStatusCode::InvalidBundle),
+            9 => Ok(MempoolStatusCode::Filtered),
             _ => Err("invalid StatusCode"),
         }
     }
@@ -122,6 +122,12 @@ pub enum Protocol {
     // probably need to move network wire into its own crate to avoid circular
     // dependency b/w network and types.
     Handshake(u8),
+    // QUIC over UDP. Appended last to keep the BCS encoding of existing
+    // protocols stable.
+    Udp(u16),
+    // TLS over TCP, for networks where middleboxes block the Noise handshake.
+    // The Noise handshake is still performed, inside the TLS session.
+    Tls,
 }
 
 /// A minimally parsed DNS name. We don't really do any checking other than
@@ -251,9 +257,10 @@ impl NetworkAddress {
     /// `"/dns4/<domain>/tcp/<port>"` or
     /// `"/dns6/<domain>/tcp/<port>"` or
     /// `"/dns/<domain>/tcp/<port>"` or
+    /// `"/ip4/<addr>/udp/<port>"` (and the ip6, dns, dns4 and dns6 variants) or
     /// cfg!(test) `"/memory/<port>"`
     ///
-    /// followed by transport upgrade handshake protocols:
+    /// optionally followed by `"/tls"` for the TCP transports, and then by transport upgrade handshake protocols:
     ///
     /// `"/ln-noise-ik/<pubkey>/ln-handshake/<version>"`
     ///
@@ -290,6 +297,14 @@ impl NetworkAddress {
         })
     }
 
+    /// Parses out the first `/ln-handshake/<version>` from a `NetworkAddress`.
+    pub fn find_handshake_version(&self) -> Option<u8> {
+        self.0.iter().find_map(|proto| match proto {
+            Protocol::Handshake(version) => Some(*version),
+            _ => None,
+        })
+    }
+
     /// A function to rotate public keys for `NoiseIK` protocols
     pub fn rotate_noise_public_key(
         &mut self,
@@ -470,6 +485,15 @@ pub fn arb_aptosnet_addr() -> impl Strategy<Value = NetworkAddress> {
             .prop_map(|(name, port)| vec![Protocol::Dns4(name), Protocol::Tcp(port)]),
         any::<(DnsName, u16)>()
             .prop_map(|(name, port)| vec![Protocol::Dns6(name), Protocol::Tcp(port)]),
+        any::<(Ipv4Addr, u16)>()
+            .prop_map(|(addr, port)| vec![Protocol::Ip4(addr), Protocol::Udp(port)]),
+        any::<(DnsName, u16)>()
+            .prop_map(|(name, port)| vec![Protocol::Dns(name), Protocol::Udp(port)]),
+        any::<(Ipv4Addr, u16)>().prop_map(|(addr, port)| vec![
+            Protocol::Ip4(addr),
+            Protocol::Tcp(port),
+            Protocol::Tls
+        ]),
     ];
     let arb_aptosnet_protos = any::<(x25519::PublicKey, u8)>()
         .prop_map(|(pubkey, hs)| vec![Protocol::NoiseIK(pubkey), Protocol::Handshake(hs)]);
@@ -505,6 +529,8 @@ impl fmt::Display for Protocol {
                     .expect("ValidCryptoMaterialStringExt::to_encoded_string is infallible")
             ),
             Handshake(version) => write!(f, "/ln-handshake/{}", version),
+            Udp(port) => write!(f, "/udp/{}", port),
+            Tls => write!(f, "/tls"),
         }
     }
 }
@@ -535,6 +561,8 @@ impl Protocol {
                 args.next().ok_or(ParseError::UnexpectedEnd)?,
             )?),
             "ln-handshake" => Protocol::Handshake(parse_one(args)?),
+            "udp" => Protocol::Udp(parse_one(args)?),
+            "tls" => Protocol::Tls,
             unknown => return Err(ParseError::UnknownProtocolType(unknown.to_string())),
         };
         Ok(protocol)
@@ -709,6 +737,51 @@ pub fn parse_dns_tcp(protos: &[Protocol]) -> Option<((IpFilter, &DnsName, u16),
     }
 }
 
+/// parse the `&[Protocol]` into the `"/ip4/<addr>/udp/<port>"` or
+/// `"/ip6/<addr>/udp/<port>"` prefix and unparsed `&[Protocol]` suffix.
+pub fn parse_ip_udp(protos: &[Protocol]) -> Option<((IpAddr, u16), &[Protocol])> {
+    use Protocol::*;
+
+    if protos.len() < 2 {
+        return None;
+    }
+
+    let (prefix, suffix) = protos.split_at(2);
+    match prefix {
+        [Ip4(ip), Udp(port)] => Some(((IpAddr::V4(*ip), *port), suffix)),
+        [Ip6(ip), Udp(port)] => Some(((IpAddr::V6(*ip), *port), suffix)),
+        _ => None,
+    }
+}
+
+/// parse the `&[Protocol]` into the `"/dns/<domain>/udp/<port>"`,
+/// `"/dns4/<domain>/udp/<port>"`, or `"/dns6/<domain>/udp/<port>"` prefix and
+/// unparsed `&[Protocol]` suffix.
+pub fn parse_dns_udp(protos: &[Protocol]) -> Option<((IpFilter, &DnsName, u16), &[Protocol])> {
+    use Protocol::*;
+
+    if protos.len() < 2 {
+        return None;
+    }
+
+    let (prefix, suffix) = protos.split_at(2);
+    match prefix {
+        [Dns(name), Udp(port)] => Some(((IpFilter::Any, name, *port), suffix)),
+        [Dns4(name), Udp(port)] => Some(((IpFilter::OnlyIp4, name, *port), suffix)),
+        [Dns6(name), Udp(port)] => Some(((IpFilter::OnlyIp6, name, *port), suffix)),
+        _ => None,
+    }
+}
+
+/// parse the `&[Protocol]` into the `"/tls"` prefix and unparsed `&[Protocol]`
+/// suffix.
+pub fn parse_tls(protos: &[Protocol]) -> Option<&[Protocol]> {
+    match protos.split_first() {
+        Some((Protocol::Tls, suffix)) => Some(suffix),
+        _ => None,
+    }
+}
+
 pub fn parse_tcp(protos: &[Protocol]) -> Option<((String, u16), &[Protocol])> {
     use Protocol::*;
 
@@ -753,11 +826,17 @@ fn parse_aptosnet_protos(protos: &[Protocol]) -> Option<&[Protocol]> {
     // ---
     // parse_ip_tcp
     // <or> parse_dns_tcp
+    // <or> parse_ip_udp
+    // <or> parse_dns_udp
     // <or> cfg!(test) parse_memory
 
     let transport_suffix = parse_ip_tcp(protos)
         .map(|x| x.1)
         .or_else(|| parse_dns_tcp(protos).map(|x| x.1))
+        // the tcp transports can be wrapped in tls
+        .map(|suffix| parse_tls(suffix).unwrap_or(suffix))
+        .or_else(|| parse_ip_udp(protos).map(|x| x.1))
+        .or_else(|| parse_dns_udp(protos).map(|x| x.1))
         .or_else(|| {
             if cfg!(test) {
                 parse_memory(protos).map(|x| x.1)
@@ -935,6 +1014,57 @@ mod test {
         assert_eq!(None, parse_ip_tcp(addr.as_slice()));
     }
 
+    #[test]
+    fn test_parse_ip_udp() {
+        let addr = NetworkAddress::from_str("/ip4/1.2.3.4/udp/123").unwrap();
+        let expected_suffix: &[Protocol] = &[];
+        assert_eq!(
+            parse_ip_udp(addr.as_slice()).unwrap(),
+            ((IpAddr::from_str("1.2.3.4").unwrap(), 123), expected_suffix)
+        );
+
+        let addr = NetworkAddress::from_str("/ip6/::1/udp/123/memory/999").unwrap();
+        let expected_suffix: &[Protocol] = &[Protocol::Memory(999)];
+        assert_eq!(
+            parse_ip_udp(addr.as_slice()).unwrap(),
+            ((IpAddr::from_str("::1").unwrap(), 123), expected_suffix)
+        );
+
+        let addr = NetworkAddress::from_str("/ip4/1.2.3.4/tcp/123").unwrap();
+        assert_eq!(None, parse_ip_udp(addr.as_slice()));
+
+        let dns_name = DnsName::from_str("example.com").unwrap();
+        let addr = NetworkAddress::from_str("/dns4/example.com/udp/123").unwrap();
+        let expected_suffix: &[Protocol] = &[];
+        assert_eq!(
+            parse_dns_udp(addr.as_slice()).unwrap(),
+            ((IpFilter::OnlyIp4, &dns_name, 123), expected_suffix)
+        );
+    }
+
+    #[test]
+    fn test_parse_tls() {
+        let pubkey_str = "080e287879c918794170e258bfaddd75acac5b3e350419044655e4983a487120";
+        let addr = NetworkAddress::from_str(&format!(
+            "/ip4/1.2.3.4/tcp/6180/tls/ln-noise-ik/{}/ln-handshake/0",
+            pubkey_str
+        ))
+        .unwrap();
+        assert!(addr.is_aptosnet_addr());
+
+        let (_, suffix) = parse_ip_tcp(addr.as_slice()).unwrap();
+        let suffix = parse_tls(suffix).unwrap();
+        assert!(parse_noise_ik(suffix).is_some());
+
+        // tls is only supported on top of tcp
+        let addr = NetworkAddress::from_str(&format!(
+            "/ip4/1.2.3.4/udp/6180/tls/ln-noise-ik/{}/ln-handshake/0",
+            pubkey_str
+        ))
+        .unwrap();
+        assert!(!addr.is_aptosnet_addr());
+    }
+
     #[test]
     fn test_parse_dns_tcp() {
         let dns_name = DnsName::from_str("example.com").unwrap();
@@ -0,0 +1,18 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::on_chain_config::OnChainConfig;
+use serde::{Deserialize, Serialize};
+
+/// Defines the max gas the transactions of a block may use, in the units of the gas used by the
+/// transactions. The block ends with the transaction reaching the limit: the transactions after
+/// it are retried in the next blocks. If the config isn't published on chain, the gas of the
+/// blocks isn't limited.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BlockGasLimit {
+    pub max_gas_per_block: u64,
+}
+
+impl OnChainConfig for BlockGasLimit {
+    const IDENTIFIER: &'static str = "BlockGasLimit";
+}
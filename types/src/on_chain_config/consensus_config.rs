@@ -10,6 +10,7 @@ use serde::{Deserialize, Serialize};
 pub enum OnChainConsensusConfig {
     V1(ConsensusConfigV1),
     V2(ConsensusConfigV2),
+    V3(ConsensusConfigV3),
 }
 
 /// The public interface that exposes all values with safe fallback.
@@ -19,6 +20,7 @@ impl OnChainConsensusConfig {
         match &self {
             OnChainConsensusConfig::V1(config) => config.two_chain,
             OnChainConsensusConfig::V2(config) => config.two_chain,
+            OnChainConsensusConfig::V3(config) => config.two_chain,
         }
     }
 
@@ -26,6 +28,7 @@ impl OnChainConsensusConfig {
     pub fn leader_reputation_exclude_round(&self) -> u64 {
         match &self {
             OnChainConsensusConfig::V2(config) => config.exclude_round,
+            OnChainConsensusConfig::V3(config) => config.exclude_round,
             // default value before onchain config
             _ => 4,
         }
@@ -35,6 +38,7 @@ impl OnChainConsensusConfig {
     pub fn decoupled_execution(&self) -> bool {
         match &self {
             OnChainConsensusConfig::V2(config) => config.decoupled_execution,
+            OnChainConsensusConfig::V3(config) => config.decoupled_execution,
             _ => false,
         }
     }
@@ -48,9 +52,15 @@ impl OnChainConsensusConfig {
         }
         match &self {
             OnChainConsensusConfig::V2(config) => config.back_pressure_limit,
+            OnChainConsensusConfig::V3(config) => config.back_pressure_limit,
             _ => 10,
         }
     }
+
+    /// Mark the blocks proposed after a reconfiguration as its suffix or not.
+    pub fn mark_reconfiguration_suffix(&self) -> bool {
+        matches!(self, OnChainConsensusConfig::V3(_))
+    }
 }
 
 /// This is used when on-chain config is not initialized.
@@ -79,6 +89,15 @@ pub struct ConsensusConfigV2 {
     pub exclude_round: u64,
 }
 
+/// Same as V2, and the blocks proposed after a reconfiguration are marked as its suffix.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ConsensusConfigV3 {
+    pub two_chain: bool,
+    pub decoupled_execution: bool,
+    pub back_pressure_limit: u64,
+    pub exclude_round: u64,
+}
+
 impl OnChainConfig for OnChainConsensusConfig {
     const IDENTIFIER: &'static str = "ConsensusConfig";
 
@@ -0,0 +1,79 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::on_chain_config::OnChainConfig;
+use serde::{Deserialize, Serialize};
+
+/// The protocol features which can be enabled on chain. New behaviors are gated on a flag so that
+/// validators running a release supporting them switch over together, at the reconfiguration
+/// enabling the flag. The values match the constants of the `Features` Move module, and are all
+/// below `FIRST_RUNTIME_FEATURE`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u64)]
+pub enum FeatureFlag {
+    /// Modules may carry a metadata section, which is checked and stripped when publishing them.
+    ModuleMetadata = 0,
+}
+
+impl From<FeatureFlag> for u64 {
+    fn from(flag: FeatureFlag) -> Self {
+        flag as u64
+    }
+}
+
+/// The features below are reserved for the protocol flags of `FeatureFlag`, and the ones from there
+/// on are runtime features, which modules can require in their metadata.
+pub const FIRST_RUNTIME_FEATURE: u64 = 64;
+
+/// Defines the features enabled on chain, as a bitset where feature `n` is bit `n % 8` of byte
+/// `n / 8`. It holds both the protocol flags and the runtime features, in their separate ranges.
+/// If the config isn't published on chain, no feature is enabled.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Features {
+    pub features: Vec<u8>,
+}
+
+impl Features {
+    pub fn new(enabled: &[u64]) -> Self {
+        let mut features = Self::default();
+        for feature in enabled {
+            features.enable(*feature);
+        }
+        features
+    }
+
+    pub fn is_enabled(&self, feature: impl Into<u64>) -> bool {
+        let (byte_index, bit_mask) = Self::position(feature.into());
+        self.features
+            .get(byte_index)
+            .map_or(false, |byte| byte & bit_mask != 0)
+    }
+
+    pub fn enable(&mut self, feature: impl Into<u64>) {
+        let (byte_index, bit_mask) = Self::position(feature.into());
+        if self.features.len() <= byte_index {
+            self.features.resize(byte_index + 1, 0);
+        }
+        self.features[byte_index] |= bit_mask;
+    }
+
+    pub fn disable(&mut self, feature: impl Into<u64>) {
+        let (byte_index, bit_mask) = Self::position(feature.into());
+        if let Some(byte) = self.features.get_mut(byte_index) {
+            *byte &= !bit_mask;
+        }
+    }
+
+    /// Whether modules can require the feature, i.e., it isn't a protocol flag
+    pub fn is_runtime_feature(feature: u64) -> bool {
+        feature >= FIRST_RUNTIME_FEATURE
+    }
+
+    fn position(feature: u64) -> (usize, u8) {
+        ((feature / 8) as usize, 1 << (feature % 8))
+    }
+}
+
+impl OnChainConfig for Features {
+    const IDENTIFIER: &'static str = "Features";
+}
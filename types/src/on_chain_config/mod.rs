@@ -17,21 +17,29 @@ use move_core_types::{
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{collections::HashMap, fmt, sync::Arc};
 
+mod block_gas_limit;
 mod consensus_config;
 mod diem_version;
+mod features;
 mod parallel_execution_config;
 mod registered_currencies;
+mod storage_gas_schedule;
 mod validator_set;
 mod vm_config;
 mod vm_publishing_option;
 
 pub use self::{
-    consensus_config::{ConsensusConfigV1, ConsensusConfigV2, OnChainConsensusConfig},
+    block_gas_limit::BlockGasLimit,
+    consensus_config::{
+        ConsensusConfigV1, ConsensusConfigV2, ConsensusConfigV3, OnChainConsensusConfig,
+    },
     diem_version::{
         Version, DIEM_MAX_KNOWN_VERSION, DIEM_VERSION_2, DIEM_VERSION_3, DIEM_VERSION_4,
     },
+    features::{FeatureFlag, Features, FIRST_RUNTIME_FEATURE},
     parallel_execution_config::{ParallelExecutionConfig, ReadWriteSetAnalysis},
     registered_currencies::RegisteredCurrencies,
+    storage_gas_schedule::StorageGasSchedule,
     validator_set::ValidatorSet,
     vm_config::VMConfig,
     vm_publishing_option::VMPublishingOption,
@@ -0,0 +1,22 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::on_chain_config::OnChainConfig;
+use serde::{Deserialize, Serialize};
+
+/// Defines the fees charged for the state used by the transactions, in internal gas units. A
+/// slot is a resource or a module in the state. If the config isn't published on chain, by
+/// `CoreFramework::StorageGasSchedule`, the VM doesn't charge any storage fee.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct StorageGasSchedule {
+    /// Charged for every slot created.
+    pub per_slot_create: u64,
+    /// Charged for every byte written, to a new or an existing slot.
+    pub per_byte_write: u64,
+    /// Refunded for every slot deleted.
+    pub per_slot_refund: u64,
+}
+
+impl OnChainConfig for StorageGasSchedule {
+    const IDENTIFIER: &'static str = "StorageGasSchedule";
+}
@@ -0,0 +1,43 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#[cfg(any(test, feature = "fuzzing"))]
+use proptest_derive::Arbitrary;
+use serde::{Deserialize, Serialize};
+
+/// The number of items in the state at a version, i.e. of account state blobs, and their total
+/// size in bytes.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
+pub struct StateStorageUsage {
+    items: u64,
+    bytes: u64,
+}
+
+impl StateStorageUsage {
+    pub fn new(items: u64, bytes: u64) -> Self {
+        Self { items, bytes }
+    }
+
+    pub fn zero() -> Self {
+        Self::new(0, 0)
+    }
+
+    pub fn items(&self) -> u64 {
+        self.items
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    pub fn add_item(&mut self, bytes: usize) {
+        self.items += 1;
+        self.bytes += bytes as u64;
+    }
+
+    pub fn remove_item(&mut self, bytes: usize) {
+        self.items -= 1;
+        self.bytes -= bytes as u64;
+    }
+}
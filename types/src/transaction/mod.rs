@@ -22,6 +22,7 @@ use aptos_crypto::{
     ed25519::*,
     hash::{CryptoHash, EventAccumulatorHasher},
     multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+    secp256k1_ecdsa::{Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey, Secp256k1EcdsaSignature},
     traits::{signing_message, SigningKey},
     HashValue,
 };
@@ -45,7 +46,7 @@ mod script;
 mod transaction_argument;
 
 pub use change_set::ChangeSet;
-pub use module::{Module, ModuleBundle};
+pub use module::{Module, ModuleBundle, ModuleMetadata};
 pub use script::{
     ArgumentABI, Script, ScriptABI, ScriptFunction, ScriptFunctionABI, TransactionScriptABI,
     TypeArgumentABI,
@@ -298,6 +299,19 @@ impl RawTransaction {
         )))
     }
 
+    /// Signs the given `RawTransaction` with a secp256k1 ECDSA private key. Note that this consumes
+    /// the `RawTransaction` and turns it into a `SignatureCheckedTransaction`.
+    pub fn sign_secp256k1_ecdsa(
+        self,
+        private_key: &Secp256k1EcdsaPrivateKey,
+        public_key: Secp256k1EcdsaPublicKey,
+    ) -> Result<SignatureCheckedTransaction> {
+        let signature = private_key.sign(&self);
+        Ok(SignatureCheckedTransaction(
+            SignedTransaction::new_secp256k1_ecdsa(self, public_key, signature),
+        ))
+    }
+
     /// Signs the given multi-agent `RawTransaction`, which is a transaction with secondary
     /// signers in addition to a sender. The private keys of the sender and the
     /// secondary signers are used to sign the transaction.
@@ -567,6 +581,18 @@ impl SignedTransaction {
         }
     }
 
+    pub fn new_secp256k1_ecdsa(
+        raw_txn: RawTransaction,
+        public_key: Secp256k1EcdsaPublicKey,
+        signature: Secp256k1EcdsaSignature,
+    ) -> SignedTransaction {
+        let authenticator = TransactionAuthenticator::secp256k1_ecdsa(public_key, signature);
+        SignedTransaction {
+            raw_txn,
+            authenticator,
+        }
+    }
+
     pub fn new_multi_agent(
         raw_txn: RawTransaction,
         sender: AccountAuthenticator,
@@ -1,8 +1,23 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::{ensure, Result};
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::{convert::TryInto, fmt};
+
+/// Marks the end of a module carrying a metadata section. The section follows the bytecode of the
+/// module, as the BCS bytes of its `ModuleMetadata` and their length as a little-endian u32.
+const METADATA_SECTION_MAGIC: &[u8; 4] = b"MDAT";
+
+/// The metadata a package attaches to each of its modules.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ModuleMetadata {
+    /// The version of the compiler the module was built with.
+    pub compiler_version: u64,
+    /// The runtime features the module needs, which must be enabled in the on-chain `Features`
+    /// config for it to be published.
+    pub required_features: Vec<u64>,
+}
 
 #[derive(Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Module {
@@ -15,6 +30,33 @@ impl Module {
         Module { code }
     }
 
+    /// Appends a metadata section to the bytecode of the module.
+    pub fn new_with_metadata(mut code: Vec<u8>, metadata: &ModuleMetadata) -> Module {
+        let metadata_bytes = bcs::to_bytes(metadata).expect("Unable to serialize metadata");
+        let metadata_len = metadata_bytes.len() as u32;
+        code.extend(metadata_bytes);
+        code.extend_from_slice(&metadata_len.to_le_bytes());
+        code.extend_from_slice(METADATA_SECTION_MAGIC);
+        Module { code }
+    }
+
+    /// Splits the module into its bytecode and the metadata section following it, if any.
+    pub fn split_metadata(&self) -> Result<(&[u8], Option<ModuleMetadata>)> {
+        let code = match self.code.strip_suffix(METADATA_SECTION_MAGIC) {
+            Some(code) => code,
+            None => return Ok((&self.code, None)),
+        };
+        ensure!(code.len() >= 4, "Truncated module metadata section");
+        let (code, metadata_len) = code.split_at(code.len() - 4);
+        let metadata_len = u32::from_le_bytes(metadata_len.try_into()?) as usize;
+        ensure!(
+            code.len() >= metadata_len,
+            "Module metadata section longer than the module"
+        );
+        let (code, metadata_bytes) = code.split_at(code.len() - metadata_len);
+        Ok((code, Some(bcs::from_bytes(metadata_bytes)?)))
+    }
+
     pub fn code(&self) -> &[u8] {
         &self.code
     }
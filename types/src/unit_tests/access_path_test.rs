@@ -1,7 +1,17 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{access_path::AccessPath, account_address::AccountAddress};
+use crate::{
+    access_path::{AccessPath, StructTagPrefix},
+    account_address::AccountAddress,
+    account_state::AccountState,
+    account_state_blob::AccountStateBlob,
+};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+};
+use std::convert::TryFrom;
 
 #[test]
 fn access_path_ord() {
@@ -25,3 +35,166 @@ fn access_path_ord() {
     assert_eq!(ap2, ap3);
     assert!(ap3 < ap4);
 }
+
+fn struct_tag(address: u8, module: &str, name: &str, type_params: Vec<TypeTag>) -> StructTag {
+    StructTag {
+        address: AccountAddress::new([address; AccountAddress::LENGTH]),
+        module: Identifier::new(module).unwrap(),
+        name: Identifier::new(name).unwrap(),
+        type_params,
+    }
+}
+
+#[test]
+fn struct_tag_prefix_parse_and_display() {
+    for prefix in &["0x1::*", "0x1::Token::*", "0x1::Token::Token"] {
+        assert_eq!(
+            prefix.parse::<StructTagPrefix>().unwrap().to_string(),
+            *prefix
+        );
+    }
+    for invalid in &[
+        "0x1",
+        "0x1::Token",
+        "0x1::*::Token",
+        "0x1::Token::Token<u8>",
+        "1::*",
+    ] {
+        assert!(invalid.parse::<StructTagPrefix>().is_err());
+    }
+}
+
+#[test]
+fn struct_tag_prefix_of_paths() {
+    let address = AccountAddress::new([1u8; AccountAddress::LENGTH]);
+    let module = ModuleId::new(address, Identifier::new("Token").unwrap());
+    let prefixes = vec![
+        StructTagPrefix::Address(address),
+        StructTagPrefix::Module(module.clone()),
+        StructTagPrefix::Struct {
+            module,
+            name: Identifier::new("Token").unwrap(),
+        },
+    ];
+    let struct_tags = vec![
+        (struct_tag(1, "Token", "Token", vec![]), 3),
+        (struct_tag(1, "Token", "Token", vec![TypeTag::U8]), 3),
+        (struct_tag(1, "Token", "TokenStore", vec![]), 2),
+        (struct_tag(1, "Tokens", "Token", vec![]), 1),
+        (struct_tag(2, "Token", "Token", vec![]), 0),
+    ];
+    for (struct_tag, num_matching_prefixes) in struct_tags {
+        let path = AccessPath::resource_access_vec(struct_tag.clone());
+        for (i, prefix) in prefixes.iter().enumerate() {
+            let matching = i < num_matching_prefixes;
+            assert_eq!(prefix.matches(&struct_tag), matching);
+            assert_eq!(path.starts_with(&prefix.path_prefix()), matching);
+        }
+    }
+}
+
+#[test]
+fn account_state_resources_by_prefix() {
+    let struct_tags = vec![
+        struct_tag(1, "Token", "Token", vec![]),
+        struct_tag(1, "Token", "Token", vec![TypeTag::U8]),
+        struct_tag(1, "Token", "Token", vec![TypeTag::U64]),
+        struct_tag(1, "Token", "TokenStore", vec![]),
+        struct_tag(1, "Tokens", "Token", vec![]),
+        struct_tag(2, "Token", "Token", vec![]),
+    ];
+    let mut account_state = AccountState::default();
+    for (i, struct_tag) in struct_tags.iter().enumerate() {
+        account_state.insert(
+            AccessPath::resource_access_vec(struct_tag.clone()),
+            vec![i as u8],
+        );
+    }
+    let resources = |prefix: &StructTagPrefix, cursor: Option<&StructTag>| {
+        account_state
+            .get_resources_by_prefix(prefix, cursor)
+            .map(|(struct_tag, value)| {
+                assert!(prefix.matches(&struct_tag));
+                value[0]
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let module = ModuleId::new(struct_tags[0].address, struct_tags[0].module.clone());
+    let mut module_resources = resources(&StructTagPrefix::Module(module.clone()), None);
+    module_resources.sort_unstable();
+    assert_eq!(module_resources, vec![0, 1, 2, 3]);
+
+    // Paging with the last resource of the previous page as the cursor
+    let prefix = StructTagPrefix::Struct {
+        module,
+        name: struct_tags[0].name.clone(),
+    };
+    let first_page = resources(&prefix, None);
+    assert_eq!(first_page.len(), 3);
+    let cursor = &struct_tags[first_page[0] as usize];
+    assert_eq!(resources(&prefix, Some(cursor)), first_page[1..].to_vec());
+
+    // A cursor before the prefix starts at the prefix, a cursor after it ends the iteration
+    assert_eq!(
+        resources(&prefix, Some(&struct_tag(0, "Token", "Token", vec![]))),
+        first_page
+    );
+    assert!(resources(&prefix, Some(&struct_tags[5])).is_empty());
+}
+
+#[test]
+fn account_state_blob_resources_by_prefix() {
+    // A path longer than 127 bytes has a longer encoded length, which BCS orders after the others
+    let long_name = "T".repeat(200);
+    let struct_tags = vec![
+        struct_tag(1, "Token", "Token", vec![]),
+        struct_tag(
+            1,
+            "Token",
+            "Token",
+            vec![TypeTag::Struct(struct_tag(1, "Token", &long_name, vec![]))],
+        ),
+        struct_tag(1, "Token", "Token", vec![TypeTag::U8]),
+        struct_tag(1, "Token", &long_name, vec![]),
+        struct_tag(1, "Tokens", "Token", vec![]),
+        struct_tag(2, "Token", "Token", vec![]),
+    ];
+    let mut account_state = AccountState::default();
+    for (i, struct_tag) in struct_tags.iter().enumerate() {
+        account_state.insert(
+            AccessPath::resource_access_vec(struct_tag.clone()),
+            vec![i as u8],
+        );
+    }
+    let blob = AccountStateBlob::try_from(&account_state).unwrap();
+
+    let module = ModuleId::new(struct_tags[0].address, struct_tags[0].module.clone());
+    let prefixes = vec![
+        StructTagPrefix::Address(struct_tags[0].address),
+        StructTagPrefix::Module(module.clone()),
+        StructTagPrefix::Struct {
+            module,
+            name: struct_tags[0].name.clone(),
+        },
+    ];
+    let cursors = std::iter::once(None).chain(struct_tags.iter().map(Some));
+    for cursor in cursors {
+        for prefix in &prefixes {
+            let expected = account_state
+                .get_resources_by_prefix(prefix, cursor)
+                .collect::<Vec<_>>();
+            assert_eq!(
+                blob.get_resources_by_prefix(prefix, cursor).unwrap(),
+                expected
+            );
+        }
+    }
+
+    // A truncated blob is rejected
+    let bytes = blob.as_ref();
+    let truncated = AccountStateBlob::from(bytes[..bytes.len() - 1].to_vec());
+    assert!(truncated
+        .get_resources_by_prefix(&prefixes[0], None)
+        .is_err());
+}
@@ -7,6 +7,7 @@ mod canonical_serialization_examples;
 mod code_debug_fmt_test;
 mod contract_event_test;
 mod currency_code_test;
+mod module_metadata_test;
 mod transaction_test;
 mod trusted_state_test;
 mod validator_set_test;
@@ -0,0 +1,83 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    on_chain_config::{FeatureFlag, Features, FIRST_RUNTIME_FEATURE},
+    transaction::{Module, ModuleMetadata},
+};
+use proptest::{collection::vec, prelude::*};
+
+proptest! {
+    #[test]
+    fn module_metadata_roundtrip(
+        code in vec(any::<u8>(), 0..64),
+        compiler_version in any::<u64>(),
+        required_features in vec(any::<u64>(), 0..4),
+    ) {
+        let metadata = ModuleMetadata {
+            compiler_version,
+            required_features,
+        };
+        let module = Module::new_with_metadata(code.clone(), &metadata);
+        let (split_code, split_metadata) = module.split_metadata().unwrap();
+        prop_assert_eq!(split_code, &code[..]);
+        prop_assert_eq!(split_metadata, Some(metadata));
+    }
+
+    #[test]
+    fn features_enable_disable(enabled in vec(0u64..64, 0..8), feature in 0u64..64) {
+        let mut features = Features::new(&enabled);
+        prop_assert_eq!(features.is_enabled(feature), enabled.contains(&feature));
+        features.enable(feature);
+        prop_assert!(features.is_enabled(feature));
+        features.disable(feature);
+        prop_assert!(!features.is_enabled(feature));
+    }
+}
+
+#[test]
+fn feature_flags() {
+    let features = Features::new(&[0]);
+    assert!(features.is_enabled(FeatureFlag::ModuleMetadata));
+    assert!(!Features::default().is_enabled(FeatureFlag::ModuleMetadata));
+    assert!(!Features::is_runtime_feature(
+        FeatureFlag::ModuleMetadata.into()
+    ));
+    assert!(Features::is_runtime_feature(FIRST_RUNTIME_FEATURE));
+}
+
+#[test]
+fn module_without_metadata() {
+    let module = Module::new(vec![0xa1, 0x1c, 0xeb, 0x0b, 1, 2, 3]);
+    let (code, metadata) = module.split_metadata().unwrap();
+    assert_eq!(code, module.code());
+    assert_eq!(metadata, None);
+}
+
+#[test]
+fn malformed_module_metadata() {
+    let metadata = ModuleMetadata {
+        compiler_version: 1,
+        required_features: vec![3],
+    };
+    let code = Module::new_with_metadata(vec![0xa1, 0x1c, 0xeb, 0x0b], &metadata).into_inner();
+    let metadata_len = code.len() - 4 - 8;
+
+    // A section claiming to be longer than the module
+    let mut too_long = code.clone();
+    let len_offset = too_long.len() - 8;
+    too_long[len_offset..len_offset + 4].copy_from_slice(&(code.len() as u32).to_le_bytes());
+    assert!(Module::new(too_long).split_metadata().is_err());
+
+    // A section too short to hold its length
+    assert!(Module::new(code[code.len() - 6..].to_vec())
+        .split_metadata()
+        .is_err());
+
+    // Metadata which doesn't deserialize
+    let mut truncated = code[..4].to_vec();
+    truncated.extend_from_slice(&code[5..4 + metadata_len]);
+    truncated.extend_from_slice(&((metadata_len - 1) as u32).to_le_bytes());
+    truncated.extend_from_slice(&code[code.len() - 4..]);
+    assert!(Module::new(truncated).split_metadata().is_err());
+}
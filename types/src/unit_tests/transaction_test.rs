@@ -6,13 +6,14 @@ use crate::{
     account_config::XUS_NAME,
     chain_id::ChainId,
     transaction::{
-        AccountTransactionsWithProof, RawTransaction, Script, SignedTransaction, Transaction,
-        TransactionInfo, TransactionListWithProof, TransactionPayload, TransactionWithProof,
+        authenticator::AuthenticationKey, AccountTransactionsWithProof, RawTransaction, Script,
+        SignedTransaction, Transaction, TransactionInfo, TransactionListWithProof,
+        TransactionPayload, TransactionWithProof,
     },
 };
 use aptos_crypto::{
     ed25519::{self, Ed25519PrivateKey, Ed25519Signature},
-    PrivateKey, Uniform,
+    secp256k1_ecdsa, PrivateKey, Uniform,
 };
 use bcs::test_helpers::assert_canonical_encode_decode;
 use proptest::prelude::*;
@@ -46,6 +47,30 @@ proptest! {
         assert!(signed_txn.check_signature().is_ok());
     }
 
+    #[test]
+    fn test_sign_raw_transaction_secp256k1_ecdsa(
+        raw_txn in any::<RawTransaction>(),
+        other_raw_txn in any::<RawTransaction>(),
+        keypair in secp256k1_ecdsa::keypair_strategy(),
+    ) {
+        prop_assume!(raw_txn != other_raw_txn);
+        let txn = raw_txn
+            .sign_secp256k1_ecdsa(&keypair.private_key, keypair.public_key.clone())
+            .unwrap();
+        let signed_txn = txn.into_inner();
+        let authenticator = signed_txn.authenticator();
+        assert_eq!(
+            authenticator.sender().authentication_key(),
+            AuthenticationKey::secp256k1_ecdsa(&keypair.public_key)
+        );
+        assert!(signed_txn.clone().check_signature().is_ok());
+        assert_canonical_encode_decode(signed_txn);
+
+        // The signature doesn't authenticate another transaction
+        let forged_txn = SignedTransaction::new_with_authenticator(other_raw_txn, authenticator);
+        assert!(forged_txn.check_signature().is_err());
+    }
+
     #[test]
     fn transaction_payload_bcs_roundtrip(txn_payload in any::<TransactionPayload>()) {
         assert_canonical_encode_decode(txn_payload);
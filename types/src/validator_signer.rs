@@ -3,6 +3,9 @@
 
 use crate::account_address::AccountAddress;
 use aptos_crypto::{
+    bls12381::{
+        BLS12381PrivateKey, BLS12381ProofOfPossession, BLS12381PublicKey, BLS12381Signature,
+    },
     ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
     hash::CryptoHash,
     test_utils::TEST_SEED,
@@ -14,12 +17,14 @@ use std::convert::TryFrom;
 
 /// ValidatorSigner associates an author with public and private keys with helpers for signing and
 /// validating. This struct can be used for all signing operations including block and network
-/// signing, respectively.
+/// signing, respectively. A BLS12-381 private key can optionally be attached, for signatures that
+/// are aggregated.
 #[derive(Debug)]
 #[cfg_attr(any(test, feature = "fuzzing"), derive(Clone))]
 pub struct ValidatorSigner {
     author: AccountAddress,
     private_key: Ed25519PrivateKey,
+    bls_private_key: Option<BLS12381PrivateKey>,
 }
 
 impl ValidatorSigner {
@@ -27,9 +32,16 @@ impl ValidatorSigner {
         ValidatorSigner {
             author,
             private_key,
+            bls_private_key: None,
         }
     }
 
+    /// Attaches a BLS12-381 private key to this signer.
+    pub fn with_bls_private_key(mut self, bls_private_key: BLS12381PrivateKey) -> Self {
+        self.bls_private_key = Some(bls_private_key);
+        self
+    }
+
     /// Constructs a signature for `message` using `private_key`.
     pub fn sign<T: Serialize + CryptoHash>(&self, message: &T) -> Ed25519Signature {
         self.private_key.sign(message)
@@ -45,6 +57,26 @@ impl ValidatorSigner {
         self.private_key.public_key()
     }
 
+    /// Constructs a BLS12-381 signature for `message`, if this signer has a BLS12-381 private key.
+    pub fn bls_sign<T: Serialize + CryptoHash>(&self, message: &T) -> Option<BLS12381Signature> {
+        self.bls_private_key
+            .as_ref()
+            .map(|private_key| private_key.sign(message))
+    }
+
+    /// Returns the BLS12-381 public key associated with this signer, if any.
+    pub fn bls_public_key(&self) -> Option<BLS12381PublicKey> {
+        self.bls_private_key.as_ref().map(PrivateKey::public_key)
+    }
+
+    /// Returns a proof of possession of the BLS12-381 private key associated with this signer, if
+    /// any.
+    pub fn bls_proof_of_possession(&self) -> Option<BLS12381ProofOfPossession> {
+        self.bls_private_key
+            .as_ref()
+            .map(BLS12381ProofOfPossession::create)
+    }
+
     /// Returns the private key associated with this signer. Only available for testing purposes.
     #[cfg(any(test, feature = "fuzzing"))]
     pub fn private_key(&self) -> &Ed25519PrivateKey {
@@ -78,7 +110,7 @@ impl ValidatorSigner {
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod proptests {
     use super::*;
-    use aptos_crypto::Genesis;
+    use aptos_crypto::{test_utils::TestAptosCrypto, Genesis, Signature};
     use proptest::{prelude::*, sample, strategy::LazyJust};
 
     #[allow(clippy::redundant_closure)]
@@ -133,7 +165,23 @@ pub mod proptests {
             let public_key = signing_key.public_key();
             let signer = ValidatorSigner::new(AccountAddress::random(), signing_key);
             prop_assert_eq!(public_key, signer.public_key());
+            prop_assert!(signer.bls_public_key().is_none());
         }
 
+        #[test]
+        fn test_bls_signer(signing_key in arb_signing_key()){
+            let bls_private_key = BLS12381PrivateKey::generate_for_testing();
+            let bls_public_key = bls_private_key.public_key();
+            let signer = ValidatorSigner::new(AccountAddress::random(), signing_key)
+                .with_bls_private_key(bls_private_key);
+            prop_assert_eq!(Some(bls_public_key.clone()), signer.bls_public_key());
+
+            let pop = signer.bls_proof_of_possession().unwrap();
+            prop_assert!(pop.verify(&bls_public_key).is_ok());
+
+            let message = TestAptosCrypto("Hello, World".to_string());
+            let signature = signer.bls_sign(&message).unwrap();
+            prop_assert!(signature.verify(&message, &bls_public_key).is_ok());
+        }
     }
 }
@@ -204,6 +204,31 @@ impl ValidatorVerifier {
         Ok(())
     }
 
+    /// Batch verifies the signatures of known authors on possibly distinct messages. A failure
+    /// doesn't identify the invalid signature, callers are expected to fall back to
+    /// [`Self::verify`] to find it.
+    pub fn batch_verify_signatures<T: CryptoHash + Serialize>(
+        &self,
+        signed_messages: &[(AccountAddress, &T, &Ed25519Signature)],
+    ) -> std::result::Result<(), VerifyError> {
+        let public_keys = signed_messages
+            .iter()
+            .map(|(author, _, _)| self.get_public_key(author))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(VerifyError::UnknownAuthor)?;
+        let public_keys: Vec<_> = public_keys.iter().collect();
+        let messages: Vec<_> = signed_messages
+            .iter()
+            .map(|(_, message, _)| *message)
+            .collect();
+        let signatures: Vec<_> = signed_messages
+            .iter()
+            .map(|(_, _, signature)| *signature)
+            .collect();
+        Ed25519Signature::batch_verify_messages(&messages, &public_keys, &signatures)
+            .map_err(|_| VerifyError::InvalidSignature)
+    }
+
     /// Ensure there are not more than the maximum expected signatures (all possible signatures).
     fn check_num_of_signatures(
         &self,
@@ -435,6 +460,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_batch_verify_signatures() {
+        let (validator_signers, validator_verifier) = random_validator_verifier(3, None, false);
+        let messages: Vec<_> = (0..validator_signers.len())
+            .map(|i| TestAptosCrypto(format!("Hello, World {}", i)))
+            .collect();
+        let signatures: Vec<_> = validator_signers
+            .iter()
+            .zip(&messages)
+            .map(|(signer, message)| signer.sign(message))
+            .collect();
+        let mut signed_messages: Vec<_> = validator_signers
+            .iter()
+            .zip(&messages)
+            .zip(&signatures)
+            .map(|((signer, message), signature)| (signer.author(), message, signature))
+            .collect();
+        assert_eq!(
+            validator_verifier.batch_verify_signatures(&signed_messages),
+            Ok(())
+        );
+
+        // A signature on another message
+        signed_messages[0].2 = &signatures[1];
+        assert_eq!(
+            validator_verifier.batch_verify_signatures(&signed_messages),
+            Err(VerifyError::InvalidSignature)
+        );
+
+        // A signature by an unknown author
+        let unknown_validator_signer = ValidatorSigner::random([100; 32]);
+        let unknown_signature = unknown_validator_signer.sign(&messages[0]);
+        signed_messages[0] = (
+            unknown_validator_signer.author(),
+            &messages[0],
+            &unknown_signature,
+        );
+        assert_eq!(
+            validator_verifier.batch_verify_signatures(&signed_messages),
+            Err(VerifyError::UnknownAuthor)
+        );
+    }
+
     #[test]
     fn test_equal_vote_quorum_validators() {
         const NUM_SIGNERS: u8 = 7;